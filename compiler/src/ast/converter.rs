@@ -1,10 +1,14 @@
 //! Python AST to internal AST conversion
 
 use pyo3::prelude::*;
-use pyo3::types::{PyAnyMethods, PyList, PyListMethods, PyTypeMethods};
+use pyo3::types::{PyAnyMethods, PyList, PyListMethods, PyModule, PyTypeMethods};
+use std::sync::Arc;
 
+use crate::ast::dataclass::{dataclass_methods, DataclassField};
 use crate::ast::types::*;
 use crate::error::{CompilerError, Result};
+use crate::span::Span;
+use crate::vfs::{DiskFileSystem, FileSystem};
 
 // ============================================================================
 // Import Types
@@ -46,6 +50,81 @@ pub struct ImportInfo {
     pub module_path: std::path::PathBuf,
     /// What is being imported
     pub kind: ImportKind,
+    /// Where the import statement is
+    pub span: Span,
+}
+
+/// A module the compiler provides itself instead of loading it from a source
+/// file; uses of its members lower directly to runtime calls
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
+pub enum IntrinsicModule {
+    /// `sys`: argv, exit and the standard streams
+    Sys,
+    /// `os`: environment variables and the working directory
+    Os,
+    /// `math`: float functions on LLVM intrinsics, and the constants pi and e
+    Math,
+    /// `time`: wall-clock and monotonic time, and sleeping
+    Time,
+    /// `random`: a seedable pseudo-random generator
+    Random,
+    /// `dataclasses`: the `dataclass` class decorator
+    Dataclasses,
+    /// `abc`: the `ABC` base class and the `abstractmethod` decorator
+    Abc,
+}
+
+impl IntrinsicModule {
+    /// The intrinsic module imported as `name`, if any
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "sys" => Some(IntrinsicModule::Sys),
+            "os" => Some(IntrinsicModule::Os),
+            "math" => Some(IntrinsicModule::Math),
+            "time" => Some(IntrinsicModule::Time),
+            "random" => Some(IntrinsicModule::Random),
+            "dataclasses" => Some(IntrinsicModule::Dataclasses),
+            "abc" => Some(IntrinsicModule::Abc),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            IntrinsicModule::Sys => "sys",
+            IntrinsicModule::Os => "os",
+            IntrinsicModule::Math => "math",
+            IntrinsicModule::Time => "time",
+            IntrinsicModule::Random => "random",
+            IntrinsicModule::Dataclasses => "dataclasses",
+            IntrinsicModule::Abc => "abc",
+        }
+    }
+
+    /// The names the module defines
+    pub fn members(self) -> &'static [&'static str] {
+        match self {
+            IntrinsicModule::Sys => &["argv", "exit", "stdin", "stdout", "stderr"],
+            IntrinsicModule::Os => &["getenv", "environ", "getcwd"],
+            IntrinsicModule::Math => &[
+                "sqrt", "sin", "cos", "log", "exp", "floor", "ceil", "pow", "pi", "e",
+            ],
+            IntrinsicModule::Time => &["time", "monotonic", "sleep"],
+            IntrinsicModule::Random => &["random", "randint", "seed", "choice"],
+            IntrinsicModule::Dataclasses => &["dataclass"],
+            IntrinsicModule::Abc => &["ABC", "abstractmethod"],
+        }
+    }
+}
+
+/// An import of an intrinsic module (`import sys` or `from sys import argv`)
+#[derive(Debug, Clone)]
+pub struct IntrinsicImport {
+    pub module: IntrinsicModule,
+    /// What is being imported (never `Star`)
+    pub kind: ImportKind,
+    /// Where the import statement is
+    pub span: Span,
 }
 
 /// What kind of import this is
@@ -67,13 +146,38 @@ pub enum ImportKind {
 pub struct AstConverter {
     /// Search paths for module resolution (in order of priority)
     search_paths: Vec<std::path::PathBuf>,
+    /// Where imported modules are looked up
+    files: Arc<dyn FileSystem>,
 }
 
 impl AstConverter {
     /// Create a new converter with the entry file's directory as the primary search path
     pub fn new(entry_dir: &std::path::Path) -> Self {
-        let search_paths = vec![entry_dir.to_path_buf()];
-        AstConverter { search_paths }
+        Self::with_search_paths(vec![entry_dir.to_path_buf()])
+    }
+
+    /// Create a converter that resolves absolute imports against several roots, in order
+    pub fn with_search_paths(search_paths: Vec<std::path::PathBuf>) -> Self {
+        AstConverter {
+            search_paths,
+            files: Arc::new(DiskFileSystem),
+        }
+    }
+
+    /// Look imported modules up in `files` instead of on disk
+    pub fn with_file_system(mut self, files: Arc<dyn FileSystem>) -> Self {
+        self.files = files;
+        self
+    }
+
+    /// Where imported modules are looked up
+    pub fn file_system(&self) -> &dyn FileSystem {
+        self.files.as_ref()
+    }
+
+    /// The roots absolute imports are resolved against, in order
+    pub fn search_paths(&self) -> &[std::path::PathBuf] {
+        &self.search_paths
     }
 
     // Module(stmt* body, type_ignore* type_ignores)
@@ -89,6 +193,8 @@ impl AstConverter {
 
             let mut stmts = Vec::new();
             let mut imports = Vec::new();
+            let mut intrinsic_imports = Vec::new();
+            let mut all = None;
 
             // Process all statements, converting imports and other code
             for py_stmt in py_stmts.iter() {
@@ -96,11 +202,28 @@ impl AstConverter {
                 match class_name.to_string().as_str() {
                     "Import" => {
                         // Convert import statements
-                        self.convert_import(&py_stmt, &path, &mut imports)?;
+                        self.convert_import(&py_stmt, &path, &mut imports, &mut intrinsic_imports)
+                            .map_err(|e| e.at(self.get_span(&py_stmt)))?;
                     }
                     "ImportFrom" => {
                         // Convert import-from statements
-                        self.convert_import_from(&py_stmt, &path, &mut imports)?;
+                        self.convert_import_from(
+                            &py_stmt,
+                            &path,
+                            &mut imports,
+                            &mut intrinsic_imports,
+                        )
+                        .map_err(|e| e.at(self.get_span(&py_stmt)))?;
+                    }
+                    // `T = TypeVar("T")` only names a type parameter
+                    "Assign" if self.is_type_var_definition(&py_stmt) => {}
+                    // `__all__ = [...]` only names what `from module import *`
+                    // imports
+                    "Assign" | "AnnAssign" if self.is_all_definition(&py_stmt) => {
+                        all = Some(
+                            self.convert_all(&py_stmt)
+                                .map_err(|e| e.at(self.get_span(&py_stmt)))?,
+                        );
                     }
                     _ => {
                         // Convert regular statements
@@ -113,6 +236,8 @@ impl AstConverter {
                 id,
                 path,
                 imports,
+                intrinsic_imports,
+                all,
                 body: stmts,
             };
 
@@ -120,18 +245,86 @@ impl AstConverter {
         })
     }
 
+    /// Whether an assignment defines a type variable (`T = TypeVar("T")`)
+    fn is_type_var_definition(&self, py_stmt: &Bound<'_, PyAny>) -> bool {
+        let value = py_stmt.getattr("value").unwrap();
+        value.get_type().name().unwrap().to_string() == "Call"
+            && self
+                .annotation_name(&value.getattr("func").unwrap())
+                .as_deref()
+                == Some("TypeVar")
+    }
+
+    /// Whether an assignment defines `__all__`
+    fn is_all_definition(&self, py_stmt: &Bound<'_, PyAny>) -> bool {
+        let target = match py_stmt.getattr("targets") {
+            Ok(targets) => {
+                let targets = targets.cast_into::<PyList>().unwrap();
+                if targets.len() != 1 {
+                    return false;
+                }
+                targets.get_item(0).unwrap()
+            }
+            Err(_) => py_stmt.getattr("target").unwrap(),
+        };
+        target.get_type().name().unwrap().to_string() == "Name"
+            && self.get_name_id(&target) == "__all__"
+    }
+
+    /// The names `__all__` lists, which must be string literals
+    fn convert_all(&self, py_stmt: &Bound<'_, PyAny>) -> Result<Vec<String>> {
+        let not_literal = || {
+            CompilerError::UnsupportedFeature(
+                "__all__ must be a list or tuple of string literals".to_string(),
+            )
+        };
+        let value = py_stmt.getattr("value").unwrap();
+        if !matches!(
+            value.get_type().name().unwrap().to_string().as_str(),
+            "List" | "Tuple"
+        ) {
+            return Err(not_literal());
+        }
+        self.get_list_attr(&value, "elts")
+            .iter()
+            .map(|elt| {
+                let is_constant = elt.get_type().name().unwrap().to_string() == "Constant";
+                is_constant
+                    .then(|| elt.getattr("value").unwrap().extract::<String>().ok())
+                    .flatten()
+                    .ok_or_else(not_literal)
+            })
+            .collect()
+    }
+
     // Import(alias* names)
     fn convert_import(
         &self,
         py_stmt: &Bound<'_, PyAny>,
         current_path: &std::path::Path,
         imports: &mut Vec<ImportInfo>,
+        intrinsic_imports: &mut Vec<IntrinsicImport>,
     ) -> Result<()> {
         let py_names = self.get_list_attr(py_stmt, "names");
 
         for py_alias in py_names.iter() {
             let alias = self.convert_import_alias(&py_alias);
 
+            // typing and collections.abc are only used in annotations
+            // (typing.List[int]), which the compiler resolves on its own
+            if alias.name == "typing" || alias.name == "collections.abc" {
+                continue;
+            }
+
+            if let Some(module) = self.intrinsic_module(&alias.name, 0) {
+                intrinsic_imports.push(IntrinsicImport {
+                    module,
+                    kind: ImportKind::Module { alias: alias.alias },
+                    span: self.get_span(py_stmt),
+                });
+                continue;
+            }
+
             // Resolve the module path (level 0 = absolute import)
             let module_path = self.resolve(&alias.name, current_path, 0)?;
             let module_id = ModuleName::new(self.path_to_module_id(&module_path));
@@ -141,6 +334,7 @@ impl AstConverter {
                 module_id,
                 module_path,
                 kind: ImportKind::Module { alias: alias.alias },
+                span: self.get_span(py_stmt),
             });
         }
 
@@ -153,6 +347,7 @@ impl AstConverter {
         py_stmt: &Bound<'_, PyAny>,
         current_path: &std::path::Path,
         imports: &mut Vec<ImportInfo>,
+        intrinsic_imports: &mut Vec<IntrinsicImport>,
     ) -> Result<()> {
         let module = py_stmt
             .getattr("module")
@@ -173,6 +368,35 @@ impl AstConverter {
 
         let module_name = module.as_deref().unwrap_or("");
 
+        // typing and collections.abc only provide names used in annotations
+        // (e.g., Iterator), which the compiler resolves on its own
+        if level == 0 && (module_name == "typing" || module_name == "collections.abc") {
+            return Ok(());
+        }
+
+        if let Some(module) = self.intrinsic_module(module_name, level) {
+            for alias in &names {
+                if alias.name == "*" {
+                    return Err(CompilerError::UnsupportedFeature(format!(
+                        "'from {} import *' is not supported",
+                        module.name()
+                    )));
+                }
+                if !module.members().contains(&alias.name.as_str()) {
+                    return Err(CompilerError::ImportNameNotFound {
+                        module: module.name().to_string(),
+                        name: alias.name.clone(),
+                    });
+                }
+            }
+            intrinsic_imports.push(IntrinsicImport {
+                module,
+                kind: ImportKind::Names(names),
+                span: self.get_span(py_stmt),
+            });
+            return Ok(());
+        }
+
         // Special case: from . import x, y (module_name is empty, level > 0)
         // Each imported name is treated as a separate module from the current directory
         if module_name.is_empty() && level > 0 {
@@ -200,6 +424,7 @@ impl AstConverter {
                     module_id: relative_module_id,
                     module_path: relative_module_path,
                     kind: ImportKind::Module { alias: local_name },
+                    span: self.get_span(py_stmt),
                 });
             }
             return Ok(());
@@ -218,6 +443,7 @@ impl AstConverter {
             } else {
                 ImportKind::Names(names)
             },
+            span: self.get_span(py_stmt),
         });
 
         Ok(())
@@ -235,31 +461,44 @@ impl AstConverter {
         ImportAlias { name, alias }
     }
 
-    // stmt = FunctionDef | ClassDef | Return | If | While | Assign | AnnAssign | AugAssign | Expr
+    // stmt = FunctionDef | ClassDef | Return | If | While | For | With | Assign | AnnAssign | AugAssign
+    //      | Expr | Try | Raise
     /// Convert a Python statement node
     /// Returns None for import statements (which are handled separately)
     fn convert_stmt(&self, py_stmt: &Bound<'_, PyAny>) -> Result<Stmt> {
         Python::attach(|_py| {
             let class_name = py_stmt.get_type().name().unwrap();
+            let span = self.get_span(py_stmt);
 
-            match class_name.to_string().as_str() {
+            let stmt = match class_name.to_string().as_str() {
                 "FunctionDef" => self.convert_function_def(py_stmt),
                 "ClassDef" => self.convert_class_def(py_stmt),
                 "Return" => self.convert_return(py_stmt),
                 "If" => self.convert_if(py_stmt),
                 "While" => self.convert_while(py_stmt),
                 "For" => self.convert_for(py_stmt),
+                "With" => self.convert_with(py_stmt),
                 "Assign" => self.convert_assign(py_stmt),
                 "AnnAssign" => self.convert_ann_assign(py_stmt),
                 "AugAssign" => self.convert_aug_assign(py_stmt),
                 "Expr" => self.convert_expr_stmt(py_stmt),
                 "Try" => self.convert_try(py_stmt),
                 "Raise" => self.convert_raise(py_stmt),
+                "Global" => Ok(Stmt::Global {
+                    names: self.get_string_list_attr(py_stmt, "names"),
+                    span,
+                }),
+                "Nonlocal" => Ok(Stmt::Nonlocal {
+                    names: self.get_string_list_attr(py_stmt, "names"),
+                    span,
+                }),
+                "Delete" => self.convert_delete(py_stmt),
                 _ => Err(CompilerError::UnsupportedFeature(format!(
                     "Unsupported statement type: {}",
                     class_name
                 ))),
-            }
+            };
+            stmt.map_err(|e| e.at(span))
         })
     }
 
@@ -271,34 +510,121 @@ impl AstConverter {
     fn convert_function_def(&self, node: &Bound<'_, PyAny>) -> Result<Stmt> {
         Python::attach(|_py| {
             let name = self.get_string_attr(node, "name");
-            let py_args = node.getattr("args").unwrap();
-            let py_args_list = self.get_list_attr(&py_args, "args");
-
-            let mut args = Vec::new();
-            for py_arg in py_args_list.iter() {
-                let arg_name = self.get_string_attr(&py_arg, "arg");
-                let annotation = self.get_optional_type_annotation(&py_arg, "annotation");
-                args.push(Arg {
-                    name: arg_name,
-                    annotation,
-                });
-            }
-
-            // Get return type annotation
-            let return_type = self.get_optional_type_annotation(node, "returns");
+            let args = self.convert_arguments(&node.getattr("args").unwrap())?;
 
             // Get function body
             let body = self.convert_stmt_list(node, "body")?;
 
+            // Get return type annotation; a generator function is annotated with
+            // the iterator it produces (e.g., Iterator[int])
+            let return_type = if contains_yield(&body) {
+                Some(self.get_generator_annotation(node, &name)?)
+            } else {
+                self.get_optional_type_annotation(node, "returns")?
+            };
+
+            let (exported, extern_name) = self.get_function_decorators(node, &name)?;
+            if extern_name.is_some() && !self.is_stub_body(node) {
+                return Err(CompilerError::UnsupportedFeature(format!(
+                    "Extern function '{}' must have an empty body (...)",
+                    name
+                )));
+            }
+
             Ok(Stmt::FunctionDef {
                 name,
                 args,
                 return_type,
                 body,
+                exported,
+                extern_name,
+                span: self.get_span(node),
             })
         })
     }
 
+    // Whether a function is decorated with `@export`, and the C function an
+    // `@extern("name")` decorator names: the only function decorators
+    // supported
+    fn get_function_decorators(
+        &self,
+        node: &Bound<'_, PyAny>,
+        function_name: &str,
+    ) -> Result<(bool, Option<String>)> {
+        Python::attach(|_py| {
+            let decorators = self.get_list_attr(node, "decorator_list");
+            let mut exported = false;
+            let mut extern_name = None;
+            for decorator in decorators.iter() {
+                match decorator.get_type().name().unwrap().to_string().as_str() {
+                    "Name" if self.get_string_attr(&decorator, "id") == "export" => {
+                        exported = true;
+                    }
+                    "Call" if self.dotted_name(&decorator.getattr("func").unwrap()).as_deref()
+                        == Some("extern") =>
+                    {
+                        let args: Vec<_> = self.get_list_attr(&decorator, "args").iter().collect();
+                        let name = match args.as_slice() {
+                            [arg] => arg
+                                .getattr("value")
+                                .ok()
+                                .and_then(|value| value.extract::<String>().ok()),
+                            _ => None,
+                        };
+                        if name.is_none() || !self.get_list_attr(&decorator, "keywords").is_empty()
+                        {
+                            return Err(CompilerError::UnsupportedFeature(format!(
+                                "@extern on function '{}' takes the C function's name as a string",
+                                function_name
+                            )));
+                        }
+                        extern_name = name;
+                    }
+                    _ => {
+                        return Err(CompilerError::UnsupportedFeature(format!(
+                            "Unsupported decorator on function '{}' (only @export and @extern are supported)",
+                            function_name
+                        )))
+                    }
+                }
+            }
+            if exported && extern_name.is_some() {
+                return Err(CompilerError::UnsupportedFeature(format!(
+                    "Function '{}' cannot be both @export and @extern",
+                    function_name
+                )));
+            }
+            Ok((exported, extern_name))
+        })
+    }
+
+    // Positional parameters of a function or method; `defaults` holds the
+    // default values of the last len(defaults) of them
+    fn convert_arguments(&self, py_args: &Bound<'_, PyAny>) -> Result<Vec<Arg>> {
+        Python::attach(|_py| {
+            let py_args_list = self.get_list_attr(py_args, "args");
+            let py_defaults = self.get_list_attr(py_args, "defaults");
+            let first_default = py_args_list.len() - py_defaults.len();
+
+            let mut args = Vec::new();
+            for (i, py_arg) in py_args_list.iter().enumerate() {
+                let arg_name = self.get_string_attr(&py_arg, "arg");
+                let annotation = self.get_optional_type_annotation(&py_arg, "annotation")?;
+                let default = if i >= first_default {
+                    Some(self.convert_expr(&py_defaults.get_item(i - first_default).unwrap())?)
+                } else {
+                    None
+                };
+                args.push(Arg {
+                    name: arg_name,
+                    annotation,
+                    default,
+                });
+            }
+            Ok(args)
+        })
+    }
+
     // ClassDef(identifier name, expr* bases, keyword* keywords, stmt* body,
     //          expr* decorator_list, type_param* type_params)
     fn convert_class_def(&self, node: &Bound<'_, PyAny>) -> Result<Stmt> {
@@ -306,41 +632,126 @@ impl AstConverter {
             let name = self.get_string_attr(node, "name");
             let py_bases_list = self.get_list_attr(node, "bases");
 
+            // `Generic[T, ...]` declares type parameters, and `Protocol` a
+            // protocol, rather than naming a base; `ABC` only marks a class
+            // that may have abstract methods, which any class may
+            let mut type_params = Vec::new();
+            let mut is_protocol = false;
+            let mut bases = Vec::new();
+            for py_base in py_bases_list.iter() {
+                if py_base.get_type().name().unwrap().to_string() != "Subscript" {
+                    if self.annotation_name(&py_base).as_deref() == Some("Protocol") {
+                        is_protocol = true;
+                    } else if self.is_abc_base(&py_base) {
+                        continue;
+                    } else {
+                        bases.push(py_base);
+                    }
+                    continue;
+                }
+                let base_name = self.annotation_name(&py_base.getattr("value").unwrap());
+                if base_name.as_deref() != Some("Generic") {
+                    return Err(CompilerError::UnsupportedFeature(format!(
+                        "Class '{}' cannot inherit from a generic class",
+                        name
+                    )));
+                }
+                for param in self.subscript_items(&py_base.getattr("slice").unwrap()) {
+                    if param.get_type().name().unwrap().to_string() != "Name" {
+                        return Err(CompilerError::UnsupportedFeature(format!(
+                            "Generic[...] of class '{}' must list type variables",
+                            name
+                        )));
+                    }
+                    type_params.push(self.get_name_id(&param));
+                }
+            }
+
+            // PEP 695 type parameters: class Stack[T]:
+            if let Ok(py_params) = node.getattr("type_params") {
+                for param in py_params.cast_into::<PyList>().unwrap().iter() {
+                    if param.get_type().name().unwrap().to_string() != "TypeVar" {
+                        return Err(CompilerError::UnsupportedFeature(format!(
+                            "Class '{}' may only have plain type parameters",
+                            name
+                        )));
+                    }
+                    type_params.push(self.get_string_attr(&param, "name"));
+                }
+            }
+
             // Only single inheritance is supported
-            let base = if py_bases_list.len() > 1 {
+            let base = if bases.len() > 1 {
                 return Err(CompilerError::UnsupportedFeature(
                     "Multiple inheritance is not supported".to_string(),
                 ));
-            } else if py_bases_list.len() == 1 {
-                Some(self.get_name_id(&py_bases_list.get_item(0).unwrap()))
+            } else if bases.len() == 1 {
+                Some(self.get_name_id(&bases[0]))
             } else {
                 None
             };
+            if base.is_some() && !type_params.is_empty() {
+                return Err(CompilerError::UnsupportedFeature(format!(
+                    "Generic class '{}' cannot have a base class",
+                    name
+                )));
+            }
+
+            if is_protocol && (base.is_some() || !type_params.is_empty()) {
+                return Err(CompilerError::UnsupportedFeature(format!(
+                    "Protocol '{}' cannot have a base class or type parameters",
+                    name
+                )));
+            }
+
+            let is_dataclass = self.is_dataclass(node, &name)?;
+            if is_dataclass && (base.is_some() || is_protocol) {
+                return Err(CompilerError::UnsupportedFeature(format!(
+                    "Dataclass '{}' cannot have a base class",
+                    name
+                )));
+            }
 
             let py_body_list = self.get_list_attr(node, "body");
 
             let mut class_body = Vec::new();
+            let mut dataclass_fields = Vec::new();
             for py_item in py_body_list.iter() {
                 let item_class = py_item.get_type().name().unwrap();
 
                 match item_class.to_string().as_str() {
                     // AnnAssign(expr target, expr annotation, expr? value, int simple)
+                    "AnnAssign" if is_protocol => {
+                        return Err(CompilerError::UnsupportedFeature(format!(
+                            "Protocol '{}' may only declare methods",
+                            name
+                        )));
+                    }
                     "AnnAssign" => {
-                        // Field definition (e.g., x: int)
-                        // Check that there's no value (pure annotation)
+                        // Field definition (e.g., x: int); only a dataclass
+                        // field may have a value, which is its __init__ default
                         let py_value = py_item.getattr("value").unwrap();
-                        if !py_value.is_none() {
+                        let default = if py_value.is_none() {
+                            None
+                        } else if is_dataclass {
+                            Some(self.convert_expr(&py_value)?)
+                        } else {
                             return Err(CompilerError::UnsupportedFeature(
                                 "Field initialization in class body not supported (use __init__)"
                                     .to_string(),
                             ));
-                        }
+                        };
 
                         let target = py_item.getattr("target").unwrap();
                         let field_name = self.get_name_id(&target);
                         let annotation = py_item.getattr("annotation").unwrap();
                         let field_type = self.get_type_annotation(&annotation)?;
 
+                        dataclass_fields.push(DataclassField {
+                            name: field_name.clone(),
+                            annotation: field_type.clone(),
+                            default,
+                        });
                         class_body.push(ClassBodyItem::FieldDef {
                             name: field_name,
                             annotation: field_type,
@@ -348,29 +759,97 @@ impl AstConverter {
                     }
                     // FunctionDef(identifier name, arguments args, stmt* body, ...)
                     "FunctionDef" => {
-                        let method_name = self.get_string_attr(&py_item, "name");
-                        let py_args = py_item.getattr("args").unwrap();
-                        let py_args_list = self.get_list_attr(&py_args, "args");
-
-                        let mut args = Vec::new();
-                        for py_arg in py_args_list.iter() {
-                            let arg_name = self.get_string_attr(&py_arg, "arg");
-                            let annotation =
-                                self.get_optional_type_annotation(&py_arg, "annotation");
-                            args.push(Arg {
-                                name: arg_name,
-                                annotation,
-                            });
+                        let mut method_name = self.get_string_attr(&py_item, "name");
+                        let mut kind = self.get_method_kind(&py_item, &name, &method_name)?;
+                        if kind == MethodKind::Instance && self.is_stub_body(&py_item) {
+                            kind = MethodKind::Abstract;
+                        }
+                        let args = self.convert_arguments(&py_item.getattr("args").unwrap())?;
+                        if kind != MethodKind::Static && args.is_empty() {
+                            return Err(CompilerError::TypeErrorSimple(format!(
+                                "Method '{}.{}' needs a '{}' parameter",
+                                name,
+                                method_name,
+                                if kind == MethodKind::Class {
+                                    "cls"
+                                } else {
+                                    "self"
+                                }
+                            )));
                         }
 
-                        let return_type = self.get_optional_type_annotation(&py_item, "returns");
-                        let method_body = self.convert_stmt_list(&py_item, "body")?;
+                        let return_type = self.get_optional_type_annotation(&py_item, "returns")?;
+                        match kind {
+                            MethodKind::Property if args.len() != 1 || return_type.is_none() => {
+                                return Err(CompilerError::TypeErrorSimple(format!(
+                                    "Property '{}.{}' must take only 'self' and declare its return type",
+                                    name, method_name
+                                )));
+                            }
+                            MethodKind::Setter => {
+                                let has_getter = class_body.iter().any(|item| {
+                                    matches!(item, ClassBodyItem::MethodDef {
+                                        name,
+                                        kind: MethodKind::Property,
+                                        ..
+                                    } if *name == method_name)
+                                });
+                                if !has_getter {
+                                    return Err(CompilerError::TypeErrorSimple(format!(
+                                        "Setter '{}.{}' must follow a @property of the same name",
+                                        name, method_name
+                                    )));
+                                }
+                                if args.len() != 2 || args[1].annotation.is_none() {
+                                    return Err(CompilerError::TypeErrorSimple(format!(
+                                        "Setter '{}.{}' must take 'self' and an annotated new value",
+                                        name, method_name
+                                    )));
+                                }
+                                method_name = property_setter_name(&method_name);
+                            }
+                            _ => {}
+                        }
+                        // A protocol method only declares its signature; its
+                        // body (usually `...`) is never run
+                        if is_protocol {
+                            if !matches!(kind, MethodKind::Instance | MethodKind::Abstract) {
+                                return Err(CompilerError::UnsupportedFeature(format!(
+                                    "Protocol method '{}.{}' must be an instance method",
+                                    name, method_name
+                                )));
+                            }
+                            class_body.push(ClassBodyItem::MethodDef {
+                                name: method_name,
+                                args,
+                                return_type,
+                                body: Vec::new(),
+                                kind: MethodKind::Instance,
+                                span: self.get_span(&py_item),
+                            });
+                            continue;
+                        }
+                        // An abstract method's body is never run; lowering
+                        // gives it one that raises
+                        let method_body = if kind == MethodKind::Abstract {
+                            Vec::new()
+                        } else {
+                            self.convert_stmt_list(&py_item, "body")?
+                        };
+                        if contains_yield(&method_body) {
+                            return Err(CompilerError::UnsupportedFeature(format!(
+                                "Generator methods are not supported: '{}.{}' (use a generator function)",
+                                name, method_name
+                            )));
+                        }
 
                         class_body.push(ClassBodyItem::MethodDef {
                             name: method_name,
                             args,
                             return_type,
                             body: method_body,
+                            kind,
+                            span: self.get_span(&py_item),
                         });
                     }
                     _ => {
@@ -382,14 +861,144 @@ impl AstConverter {
                 }
             }
 
+            if is_dataclass {
+                let methods = dataclass_methods(&name, &dataclass_fields, &class_body)?;
+                class_body.extend(methods);
+            }
+
             Ok(Stmt::ClassDef {
                 name,
                 base,
+                type_params,
+                is_protocol,
                 body: class_body,
+                span: self.get_span(node),
             })
         })
     }
 
+    // Whether a class is decorated with `@dataclass` (or `@dataclasses.dataclass`),
+    // the only class decorator supported
+    fn is_dataclass(&self, node: &Bound<'_, PyAny>, class_name: &str) -> Result<bool> {
+        Python::attach(|_py| {
+            let decorators = self.get_list_attr(node, "decorator_list");
+            let mut is_dataclass = false;
+            for decorator in decorators.iter() {
+                let decorator_name = match decorator.get_type().name().unwrap().to_string().as_str()
+                {
+                    "Name" => self.get_string_attr(&decorator, "id"),
+                    "Attribute" => self.get_string_attr(&decorator, "attr"),
+                    "Call" => {
+                        return Err(CompilerError::UnsupportedFeature(format!(
+                            "Class decorator arguments are not supported (on class '{}')",
+                            class_name
+                        )))
+                    }
+                    _ => String::new(),
+                };
+                if decorator_name != "dataclass" {
+                    return Err(CompilerError::UnsupportedFeature(format!(
+                        "Unsupported decorator on class '{}' (only @dataclass is supported)",
+                        class_name
+                    )));
+                }
+                is_dataclass = true;
+            }
+            Ok(is_dataclass)
+        })
+    }
+
+    // How a method is bound: `@staticmethod`, `@classmethod`, `@property` and
+    // `@<method>.setter` are the only method decorators supported
+    fn get_method_kind(
+        &self,
+        node: &Bound<'_, PyAny>,
+        class_name: &str,
+        method_name: &str,
+    ) -> Result<MethodKind> {
+        Python::attach(|_py| {
+            let decorators = self.get_list_attr(node, "decorator_list");
+            if decorators.len() > 1 {
+                return Err(CompilerError::UnsupportedFeature(format!(
+                    "Method '{}.{}' has more than one decorator",
+                    class_name, method_name
+                )));
+            }
+            let Some(decorator) = decorators.iter().next() else {
+                return Ok(MethodKind::Instance);
+            };
+            let decorator_name = match decorator.get_type().name().unwrap().to_string().as_str() {
+                "Name" => self.get_string_attr(&decorator, "id"),
+                // @x.setter
+                "Attribute" => {
+                    let attr = self.get_string_attr(&decorator, "attr");
+                    let target = decorator.getattr("value").unwrap();
+                    if target.get_type().name().unwrap().to_string() == "Name" {
+                        format!("{}.{}", self.get_string_attr(&target, "id"), attr)
+                    } else {
+                        attr
+                    }
+                }
+                _ => String::new(),
+            };
+            match decorator_name.as_str() {
+                "staticmethod" => Ok(MethodKind::Static),
+                "classmethod" => Ok(MethodKind::Class),
+                "property" => Ok(MethodKind::Property),
+                "abstractmethod" | "abc.abstractmethod" => Ok(MethodKind::Abstract),
+                name if name == property_setter_name(method_name) => Ok(MethodKind::Setter),
+                _ => Err(CompilerError::UnsupportedFeature(format!(
+                    "Unsupported decorator on method '{}.{}' (only @staticmethod, @classmethod, @property, @abstractmethod and @{}.setter are supported)",
+                    class_name, method_name, method_name
+                ))),
+            }
+        })
+    }
+
+    // Whether a class base is `ABC` (or `abc.ABC`)
+    fn is_abc_base(&self, node: &Bound<'_, PyAny>) -> bool {
+        matches!(self.dotted_name(node).as_deref(), Some("ABC" | "abc.ABC"))
+    }
+
+    // Whether a function body only marks it unimplemented: `...` or
+    // `raise NotImplementedError`, optionally after a docstring
+    fn is_stub_body(&self, node: &Bound<'_, PyAny>) -> bool {
+        Python::attach(|_py| {
+            let body: Vec<_> = self.get_list_attr(node, "body").iter().collect();
+            let is_constant = |stmt: &Bound<'_, PyAny>, type_name: &str| {
+                stmt.get_type().name().unwrap().to_string() == "Expr"
+                    && stmt.getattr("value").is_ok_and(|value| {
+                        value.get_type().name().unwrap().to_string() == "Constant"
+                            && value
+                                .getattr("value")
+                                .unwrap()
+                                .get_type()
+                                .name()
+                                .unwrap()
+                                .to_string()
+                                == type_name
+                    })
+            };
+            let stmt = match body.as_slice() {
+                [stmt] => stmt,
+                [docstring, stmt] if is_constant(docstring, "str") => stmt,
+                _ => return false,
+            };
+            if is_constant(stmt, "ellipsis") {
+                return true;
+            }
+            if stmt.get_type().name().unwrap().to_string() != "Raise" {
+                return false;
+            }
+            let mut exc = stmt.getattr("exc").unwrap();
+            if exc.get_type().name().unwrap().to_string() == "Call" {
+                exc = exc.getattr("func").unwrap();
+            }
+            exc.get_type().name().unwrap().to_string() == "Name"
+                && self.get_string_attr(&exc, "id") == "NotImplementedError"
+        })
+    }
+
     // Return(expr? value)
     fn convert_return(&self, node: &Bound<'_, PyAny>) -> Result<Stmt> {
         Python::attach(|_py| {
@@ -399,7 +1008,10 @@ impl AstConverter {
             } else {
                 Some(self.convert_expr(&py_value)?)
             };
-            Ok(Stmt::Return { value })
+            Ok(Stmt::Return {
+                value,
+                span: self.get_span(node),
+            })
         })
     }
 
@@ -409,7 +1021,12 @@ impl AstConverter {
             let test = self.convert_expr(&node.getattr("test").unwrap())?;
             let body = self.convert_stmt_list(node, "body")?;
             let orelse = self.convert_stmt_list(node, "orelse")?;
-            Ok(Stmt::If { test, body, orelse })
+            Ok(Stmt::If {
+                test,
+                body,
+                orelse,
+                span: self.get_span(node),
+            })
         })
     }
 
@@ -418,7 +1035,13 @@ impl AstConverter {
         Python::attach(|_py| {
             let test = self.convert_expr(&node.getattr("test").unwrap())?;
             let body = self.convert_stmt_list(node, "body")?;
-            Ok(Stmt::While { test, body })
+            let orelse = self.convert_stmt_list(node, "orelse")?;
+            Ok(Stmt::While {
+                test,
+                body,
+                orelse,
+                span: self.get_span(node),
+            })
         })
     }
 
@@ -436,7 +1059,48 @@ impl AstConverter {
             let target = self.get_string_attr(&py_target, "id");
             let iter = self.convert_expr(&node.getattr("iter").unwrap())?;
             let body = self.convert_stmt_list(node, "body")?;
-            Ok(Stmt::For { target, iter, body })
+            let orelse = self.convert_stmt_list(node, "orelse")?;
+            Ok(Stmt::For {
+                target,
+                iter,
+                body,
+                orelse,
+                span: self.get_span(node),
+            })
+        })
+    }
+
+    // With(withitem* items, stmt* body, string? type_comment)
+    // withitem = (expr context_expr, expr? optional_vars)
+    fn convert_with(&self, node: &Bound<'_, PyAny>) -> Result<Stmt> {
+        Python::attach(|_py| {
+            let mut items = Vec::new();
+            for py_item in self.get_list_attr(node, "items").iter() {
+                let context = self.convert_expr(&py_item.getattr("context_expr").unwrap())?;
+                let py_target = py_item.getattr("optional_vars").unwrap();
+                let target = if py_target.is_none() {
+                    None
+                } else {
+                    Some(self.convert_expr(&py_target)?)
+                };
+                items.push((context, target));
+            }
+
+            // `with a, b: body` is `with a: with b: body`
+            let mut body = self.convert_stmt_list(node, "body")?;
+            while let Some((context, target)) = items.pop() {
+                let with_stmt = Stmt::With {
+                    context,
+                    target,
+                    body,
+                    span: self.get_span(node),
+                };
+                if items.is_empty() {
+                    return Ok(with_stmt);
+                }
+                body = vec![with_stmt];
+            }
+            unreachable!("with statement without items")
         })
     }
 
@@ -453,6 +1117,7 @@ impl AstConverter {
                 handlers,
                 orelse,
                 finalbody,
+                span: self.get_span(node),
             })
         })
     }
@@ -502,8 +1167,18 @@ impl AstConverter {
                 Some(self.convert_expr(&py_exc)?)
             };
 
-            // Note: 'cause' (raise X from Y) is not supported
-            Ok(Stmt::Raise { exc })
+            let py_cause = node.getattr("cause").unwrap();
+            let cause = if py_cause.is_none() {
+                None
+            } else {
+                Some(self.convert_expr(&py_cause)?)
+            };
+
+            Ok(Stmt::Raise {
+                exc,
+                cause,
+                span: self.get_span(node),
+            })
         })
     }
 
@@ -526,6 +1201,7 @@ impl AstConverter {
                 target: target_expr,
                 value,
                 type_annotation: None,
+                span: self.get_span(node),
             })
         })
     }
@@ -549,6 +1225,21 @@ impl AstConverter {
                 target: target_expr,
                 value,
                 type_annotation: Some(annotation),
+                span: self.get_span(node),
+            })
+        })
+    }
+
+    // Delete(expr* targets)
+    fn convert_delete(&self, node: &Bound<'_, PyAny>) -> Result<Stmt> {
+        Python::attach(|_py| {
+            let mut targets = Vec::new();
+            for target in self.get_list_attr(node, "targets").iter() {
+                targets.push(self.convert_expr(&target)?);
+            }
+            Ok(Stmt::Delete {
+                targets,
+                span: self.get_span(node),
             })
         })
     }
@@ -565,19 +1256,39 @@ impl AstConverter {
                 target: target_name,
                 op,
                 value,
+                span: self.get_span(node),
             })
         })
     }
 
     // Expr(expr value)
+    // Yield(expr? value) is only supported as a statement
     fn convert_expr_stmt(&self, node: &Bound<'_, PyAny>) -> Result<Stmt> {
         Python::attach(|_py| {
-            let value = self.convert_expr(&node.getattr("value").unwrap())?;
-            Ok(Stmt::Expr { value })
+            let py_value = node.getattr("value").unwrap();
+            if py_value.get_type().name().unwrap().to_string() == "Yield" {
+                let py_yielded = py_value.getattr("value").unwrap();
+                if py_yielded.is_none() {
+                    return Err(CompilerError::UnsupportedFeature(
+                        "yield without a value is not supported".to_string(),
+                    ));
+                }
+                let value = self.convert_expr(&py_yielded)?;
+                return Ok(Stmt::Yield {
+                    value,
+                    span: self.get_span(node),
+                });
+            }
+            let value = self.convert_expr(&py_value)?;
+            Ok(Stmt::Expr {
+                value,
+                span: self.get_span(node),
+            })
         })
     }
 
-    // expr = Constant | Name | BinOp | Compare | BoolOp | UnaryOp | Call | List | Subscript | Attribute
+    // expr = Constant | Name | BinOp | Compare | BoolOp | UnaryOp | Call | List | Dict | Set | Tuple | Subscript | Slice | Attribute
+    //      | ListComp | SetComp | DictComp | JoinedStr
     fn convert_expr(&self, py_expr: &Bound<'_, PyAny>) -> Result<Expr> {
         Python::attach(|_py| {
             let class_name = py_expr.get_type().name().unwrap();
@@ -591,8 +1302,21 @@ impl AstConverter {
                 "UnaryOp" => self.convert_unaryop(py_expr),
                 "Call" => self.convert_call(py_expr),
                 "List" => self.convert_list(py_expr),
+                "Dict" => self.convert_dict(py_expr),
+                "Set" => self.convert_set(py_expr),
+                "Tuple" => self.convert_tuple(py_expr),
                 "Subscript" => self.convert_subscript(py_expr),
+                "Slice" => self.convert_slice(py_expr),
                 "Attribute" => self.convert_attribute(py_expr),
+                "Starred" => self.convert_starred(py_expr),
+                "ListComp" => self.convert_list_comp(py_expr),
+                "SetComp" => self.convert_set_comp(py_expr),
+                "DictComp" => self.convert_dict_comp(py_expr),
+                "JoinedStr" => self.convert_joined_str(py_expr),
+                "Yield" | "YieldFrom" => Err(CompilerError::UnsupportedFeature(format!(
+                    "{} is only supported as a statement with a value (e.g., `yield x`)",
+                    class_name
+                ))),
                 _ => Err(CompilerError::UnsupportedFeature(format!(
                     "Unsupported expression type: {}",
                     class_name
@@ -733,9 +1457,25 @@ impl AstConverter {
                 args.push(self.convert_expr(&py_arg)?);
             }
 
+            let mut keywords = Vec::new();
+            for py_keyword in self.get_list_attr(node, "keywords").iter() {
+                // A None name marks a `**mapping` argument
+                let arg = py_keyword.getattr("arg").unwrap();
+                if arg.is_none() {
+                    return Err(CompilerError::UnsupportedFeature(
+                        "Keyword argument unpacking (**) is not supported".to_string(),
+                    ));
+                }
+                keywords.push(Keyword {
+                    arg: arg.extract::<String>().unwrap(),
+                    value: self.convert_expr(&py_keyword.getattr("value").unwrap())?,
+                });
+            }
+
             Ok(Expr::Call {
                 func: Box::new(func),
                 args,
+                keywords,
             })
         })
     }
@@ -754,6 +1494,60 @@ impl AstConverter {
         })
     }
 
+    // Dict(expr?* keys, expr* values)
+    fn convert_dict(&self, node: &Bound<'_, PyAny>) -> Result<Expr> {
+        Python::attach(|_py| {
+            let keys_pylist = self.get_list_attr(node, "keys");
+            let values_pylist = self.get_list_attr(node, "values");
+
+            let mut keys = Vec::new();
+            for py_key in keys_pylist.iter() {
+                // A None key marks a `**mapping` unpacking entry
+                if py_key.is_none() {
+                    return Err(CompilerError::UnsupportedFeature(
+                        "Dict unpacking (**) is not supported".to_string(),
+                    ));
+                }
+                keys.push(self.convert_expr(&py_key)?);
+            }
+
+            let mut values = Vec::new();
+            for py_value in values_pylist.iter() {
+                values.push(self.convert_expr(&py_value)?);
+            }
+
+            Ok(Expr::Dict { keys, values })
+        })
+    }
+
+    // Set(expr* elts)
+    fn convert_set(&self, node: &Bound<'_, PyAny>) -> Result<Expr> {
+        Python::attach(|_py| {
+            let elts_pylist = self.get_list_attr(node, "elts");
+
+            let mut elts = Vec::new();
+            for py_elt in elts_pylist.iter() {
+                elts.push(self.convert_expr(&py_elt)?);
+            }
+
+            Ok(Expr::Set { elts })
+        })
+    }
+
+    // Tuple(expr* elts, expr_context ctx)
+    fn convert_tuple(&self, node: &Bound<'_, PyAny>) -> Result<Expr> {
+        Python::attach(|_py| {
+            let elts_pylist = self.get_list_attr(node, "elts");
+
+            let mut elts = Vec::new();
+            for py_elt in elts_pylist.iter() {
+                elts.push(self.convert_expr(&py_elt)?);
+            }
+
+            Ok(Expr::Tuple { elts })
+        })
+    }
+
     // Subscript(expr value, expr slice, expr_context ctx)
     fn convert_subscript(&self, node: &Bound<'_, PyAny>) -> Result<Expr> {
         Python::attach(|_py| {
@@ -767,6 +1561,143 @@ impl AstConverter {
         })
     }
 
+    // Slice(expr? lower, expr? upper, expr? step)
+    fn convert_slice(&self, node: &Bound<'_, PyAny>) -> Result<Expr> {
+        Python::attach(|_py| {
+            let convert_bound = |attr: &str| -> Result<Option<Box<Expr>>> {
+                let py_bound = node.getattr(attr).unwrap();
+                if py_bound.is_none() {
+                    Ok(None)
+                } else {
+                    Ok(Some(Box::new(self.convert_expr(&py_bound)?)))
+                }
+            };
+            Ok(Expr::Slice {
+                lower: convert_bound("lower")?,
+                upper: convert_bound("upper")?,
+                step: convert_bound("step")?,
+            })
+        })
+    }
+
+    // ListComp(expr elt, comprehension* generators)
+    fn convert_list_comp(&self, node: &Bound<'_, PyAny>) -> Result<Expr> {
+        Python::attach(|_py| {
+            let elt = self.convert_expr(&node.getattr("elt").unwrap())?;
+            let generators = self.convert_comprehensions(node)?;
+            Ok(Expr::ListComp {
+                elt: Box::new(elt),
+                generators,
+            })
+        })
+    }
+
+    // SetComp(expr elt, comprehension* generators)
+    fn convert_set_comp(&self, node: &Bound<'_, PyAny>) -> Result<Expr> {
+        Python::attach(|_py| {
+            let elt = self.convert_expr(&node.getattr("elt").unwrap())?;
+            let generators = self.convert_comprehensions(node)?;
+            Ok(Expr::SetComp {
+                elt: Box::new(elt),
+                generators,
+            })
+        })
+    }
+
+    // DictComp(expr key, expr value, comprehension* generators)
+    fn convert_dict_comp(&self, node: &Bound<'_, PyAny>) -> Result<Expr> {
+        Python::attach(|_py| {
+            let key = self.convert_expr(&node.getattr("key").unwrap())?;
+            let value = self.convert_expr(&node.getattr("value").unwrap())?;
+            let generators = self.convert_comprehensions(node)?;
+            Ok(Expr::DictComp {
+                key: Box::new(key),
+                value: Box::new(value),
+                generators,
+            })
+        })
+    }
+
+    // JoinedStr(expr* values)
+    // FormattedValue(expr value, int conversion, expr? format_spec)
+    fn convert_joined_str(&self, node: &Bound<'_, PyAny>) -> Result<Expr> {
+        Python::attach(|_py| {
+            let mut parts = Vec::new();
+            for py_part in self.get_list_attr(node, "values").iter() {
+                let part_kind = py_part.get_type().name().unwrap().to_string();
+                match part_kind.as_str() {
+                    "Constant" => {
+                        let text = py_part
+                            .getattr("value")
+                            .unwrap()
+                            .extract::<String>()
+                            .unwrap();
+                        parts.push(FStringPart::Literal(text));
+                    }
+                    "FormattedValue" => {
+                        if !py_part.getattr("format_spec").unwrap().is_none() {
+                            return Err(CompilerError::UnsupportedFeature(
+                                "Format specifications in f-strings are not supported".to_string(),
+                            ));
+                        }
+                        // -1 = no conversion, 115 = !s, 114 = !r, 97 = !a
+                        let conversion = py_part
+                            .getattr("conversion")
+                            .unwrap()
+                            .extract::<i64>()
+                            .unwrap();
+                        let repr = match conversion {
+                            -1 | 115 => false,
+                            114 => true,
+                            _ => {
+                                return Err(CompilerError::UnsupportedFeature(
+                                    "The !a conversion in f-strings is not supported".to_string(),
+                                ))
+                            }
+                        };
+                        let value = self.convert_expr(&py_part.getattr("value").unwrap())?;
+                        parts.push(FStringPart::Value { value, repr });
+                    }
+                    _ => {
+                        return Err(CompilerError::UnsupportedFeature(format!(
+                            "Unsupported f-string part: {}",
+                            part_kind
+                        )))
+                    }
+                }
+            }
+            Ok(Expr::FString { parts })
+        })
+    }
+
+    // comprehension = (expr target, expr iter, expr* ifs, int is_async)
+    fn convert_comprehensions(&self, node: &Bound<'_, PyAny>) -> Result<Vec<Comprehension>> {
+        Python::attach(|_py| {
+            let mut generators = Vec::new();
+            for py_gen in self.get_list_attr(node, "generators").iter() {
+                let is_async = py_gen
+                    .getattr("is_async")
+                    .unwrap()
+                    .extract::<i64>()
+                    .unwrap();
+                if is_async != 0 {
+                    return Err(CompilerError::UnsupportedFeature(
+                        "Async comprehensions are not supported".to_string(),
+                    ));
+                }
+
+                let target = self.convert_expr(&py_gen.getattr("target").unwrap())?;
+                let iter = self.convert_expr(&py_gen.getattr("iter").unwrap())?;
+                let mut ifs = Vec::new();
+                for py_if in self.get_list_attr(&py_gen, "ifs").iter() {
+                    ifs.push(self.convert_expr(&py_if)?);
+                }
+                generators.push(Comprehension { target, iter, ifs });
+            }
+            Ok(generators)
+        })
+    }
+
     // Attribute(expr value, identifier attr, expr_context ctx)
     fn convert_attribute(&self, node: &Bound<'_, PyAny>) -> Result<Expr> {
         Python::attach(|_py| {
@@ -780,6 +1711,16 @@ impl AstConverter {
         })
     }
 
+    // Starred(expr value, expr_context ctx)
+    fn convert_starred(&self, node: &Bound<'_, PyAny>) -> Result<Expr> {
+        Python::attach(|_py| {
+            let value = self.convert_expr(&node.getattr("value").unwrap())?;
+            Ok(Expr::Starred {
+                value: Box::new(value),
+            })
+        })
+    }
+
     // operator = Add | Sub | Mult | MatMult | Div | Mod | Pow | LShift
     //          | RShift | BitOr | BitXor | BitAnd | FloorDiv
     fn convert_bin_operator(&self, py_op: &Bound<'_, PyAny>) -> Result<BinOperator> {
@@ -819,6 +1760,10 @@ impl AstConverter {
                 "LtE" => Ok(CompareOp::LtE),
                 "Gt" => Ok(CompareOp::Gt),
                 "GtE" => Ok(CompareOp::GtE),
+                "In" => Ok(CompareOp::In),
+                "NotIn" => Ok(CompareOp::NotIn),
+                "Is" => Ok(CompareOp::Is),
+                "IsNot" => Ok(CompareOp::IsNot),
                 _ => Err(CompilerError::UnsupportedFeature(format!(
                     "Unsupported comparison operator: {}",
                     class_name
@@ -833,8 +1778,13 @@ impl AstConverter {
             let class_name = py_annot.get_type().name().unwrap();
 
             match class_name.to_string().as_str() {
-                "Name" => {
-                    let id = self.get_string_attr(py_annot, "id");
+                "Name" | "Attribute" => {
+                    let Some(id) = self.annotation_name(py_annot) else {
+                        return Err(CompilerError::UnsupportedFeature(
+                            "Dotted annotations must name a member of typing or collections.abc"
+                                .to_string(),
+                        ));
+                    };
                     match id.as_str() {
                         "int" => Ok(TypeAnnotation::Int),
                         "float" => Ok(TypeAnnotation::Float),
@@ -842,28 +1792,122 @@ impl AstConverter {
                         "bool" => Ok(TypeAnnotation::Bool),
                         "bytes" => Ok(TypeAnnotation::Bytes),
                         "bytearray" => Ok(TypeAnnotation::ByteArray),
+                        "TextIO" => Ok(TypeAnnotation::TextIO),
                         class_name => Ok(TypeAnnotation::ClassName(class_name.to_string())),
                     }
                 }
                 "Subscript" => {
+                    let base = py_annot.getattr("value").unwrap();
                     let slice = py_annot.getattr("slice").unwrap();
-                    let inner_type = self.get_type_annotation(&slice)?;
-                    Ok(TypeAnnotation::List(Box::new(inner_type)))
+                    let base_name = self.annotation_name(&base).unwrap_or_default();
+                    match base_name.as_str() {
+                        "dict" => {
+                            // dict[K, V]: the slice is a Tuple of exactly two annotations
+                            let elts = slice.getattr("elts").ok();
+                            match elts.map(|e| e.cast_into::<PyList>()) {
+                                Some(Ok(elts)) if elts.len() == 2 => {
+                                    let key_type =
+                                        self.get_type_annotation(&elts.get_item(0).unwrap())?;
+                                    let value_type =
+                                        self.get_type_annotation(&elts.get_item(1).unwrap())?;
+                                    Ok(TypeAnnotation::Dict(
+                                        Box::new(key_type),
+                                        Box::new(value_type),
+                                    ))
+                                }
+                                _ => Err(CompilerError::UnsupportedFeature(
+                                    "dict annotation requires key and value types: dict[K, V]"
+                                        .to_string(),
+                                )),
+                            }
+                        }
+                        "set" => Ok(TypeAnnotation::Set(Box::new(
+                            self.get_type_annotation(&slice)?,
+                        ))),
+                        "frozenset" => Ok(TypeAnnotation::FrozenSet(Box::new(
+                            self.get_type_annotation(&slice)?,
+                        ))),
+                        "tuple" => {
+                            // tuple[A, B, ...] lists one annotation per element;
+                            // tuple[A] is a 1-tuple
+                            let elts: Vec<_> = match slice.getattr("elts") {
+                                Ok(elts) => elts.cast_into::<PyList>().unwrap().iter().collect(),
+                                Err(_) => vec![slice],
+                            };
+                            let mut elem_types = Vec::new();
+                            for elt in elts.iter() {
+                                let is_ellipsis = elt.get_type().name().unwrap().to_string()
+                                    == "Constant"
+                                    && elt
+                                        .getattr("value")
+                                        .unwrap()
+                                        .get_type()
+                                        .name()
+                                        .unwrap()
+                                        .to_string()
+                                        == "ellipsis";
+                                if is_ellipsis {
+                                    return Err(CompilerError::UnsupportedFeature(
+                                        "Variable-length tuple annotations (tuple[T, ...]) are not supported"
+                                            .to_string(),
+                                    ));
+                                }
+                                elem_types.push(self.get_type_annotation(elt)?);
+                            }
+                            Ok(TypeAnnotation::Tuple(elem_types))
+                        }
+                        "Optional" => Ok(TypeAnnotation::optional(
+                            self.get_type_annotation(&slice)?,
+                        )),
+                        "Union" => {
+                            let members: Vec<_> = match slice.getattr("elts") {
+                                Ok(elts) => elts.cast_into::<PyList>().unwrap().iter().collect(),
+                                Err(_) => vec![slice],
+                            };
+                            self.get_union_annotation(&members)
+                        }
+                        "Iterator" | "Iterable" | "Generator" => {
+                            Err(CompilerError::UnsupportedFeature(format!(
+                                "{} annotations are only supported as the return type of a generator function",
+                                base_name
+                            )))
+                        }
+                        _ => {
+                            let inner_type = self.get_type_annotation(&slice)?;
+                            Ok(TypeAnnotation::List(Box::new(inner_type)))
+                        }
+                    }
                 }
-                // Handle string annotations (forward references) like "ClassName"
+                // int | None
+                "BinOp" => {
+                    let op = py_annot.getattr("op").unwrap();
+                    if op.get_type().name().unwrap().to_string() != "BitOr" {
+                        return Err(CompilerError::UnsupportedFeature(
+                            "Only | may combine types in an annotation".to_string(),
+                        ));
+                    }
+                    let members = [
+                        py_annot.getattr("left").unwrap(),
+                        py_annot.getattr("right").unwrap(),
+                    ];
+                    self.get_union_annotation(&members)
+                }
+                // String annotations (forward references) like "ClassName" or
+                // "Node[T]" are parsed as the annotation they contain
                 "Constant" => {
                     let value = py_annot.getattr("value").unwrap();
                     if let Ok(s) = value.extract::<String>() {
-                        // Treat string annotation the same as a bare name
-                        match s.as_str() {
-                            "int" => Ok(TypeAnnotation::Int),
-                            "float" => Ok(TypeAnnotation::Float),
-                            "str" => Ok(TypeAnnotation::Str),
-                            "bool" => Ok(TypeAnnotation::Bool),
-                            "bytes" => Ok(TypeAnnotation::Bytes),
-                            "bytearray" => Ok(TypeAnnotation::ByteArray),
-                            class_name => Ok(TypeAnnotation::ClassName(class_name.to_string())),
-                        }
+                        let parsed = PyModule::import(py_annot.py(), "ast")
+                            .and_then(|ast| {
+                                ast.call_method1("parse", (s.trim(), "<annotation>", "eval"))
+                            })
+                            .map_err(|_| {
+                                CompilerError::UnsupportedFeature(format!(
+                                    "Invalid string annotation: '{}'",
+                                    s
+                                ))
+                            })?;
+                        self.get_type_annotation(&parsed.getattr("body").unwrap())
                     } else {
                         Err(CompilerError::UnsupportedFeature(
                             "String annotation must be a string literal".to_string(),
@@ -878,28 +1922,151 @@ impl AstConverter {
         })
     }
 
+    /// The name an annotation refers to. `typing.X` and `collections.abc.X`
+    /// are read as `X`, and the typing aliases of builtin types (`List`,
+    /// `Dict`, `Set`, `FrozenSet`, `Tuple`) as the builtin's name. None for
+    /// other dotted names.
+    fn annotation_name(&self, node: &Bound<'_, PyAny>) -> Option<String> {
+        let name = match node.get_type().name().unwrap().to_string().as_str() {
+            "Name" => self.get_name_id(node),
+            "Attribute" => {
+                let module = self.dotted_name(&node.getattr("value").unwrap())?;
+                if module != "typing" && module != "collections.abc" {
+                    return None;
+                }
+                self.get_string_attr(node, "attr")
+            }
+            _ => return None,
+        };
+        let builtin = match name.as_str() {
+            "List" => "list",
+            "Dict" => "dict",
+            "Set" => "set",
+            "FrozenSet" => "frozenset",
+            "Tuple" => "tuple",
+            _ => return Some(name),
+        };
+        Some(builtin.to_string())
+    }
+
+    /// The items of a subscript's slice: each element of `X[A, B]`, or the
+    /// single one of `X[A]`
+    fn subscript_items<'py>(&self, slice: &Bound<'py, PyAny>) -> Vec<Bound<'py, PyAny>> {
+        match slice.getattr("elts") {
+            Ok(elts) => elts.cast_into::<PyList>().unwrap().iter().collect(),
+            Err(_) => vec![slice.clone()],
+        }
+    }
+
+    /// The dotted name of a name or a chain of its attributes (`collections.abc`)
+    fn dotted_name(&self, node: &Bound<'_, PyAny>) -> Option<String> {
+        match node.get_type().name().unwrap().to_string().as_str() {
+            "Name" => Some(self.get_name_id(node)),
+            "Attribute" => Some(format!(
+                "{}.{}",
+                self.dotted_name(&node.getattr("value").unwrap())?,
+                self.get_string_attr(node, "attr")
+            )),
+            _ => None,
+        }
+    }
+
+    /// Get the annotation of a union (`Union[A, B]` or `A | B`); the only
+    /// unions supported are a type with None, which are Optional
+    fn get_union_annotation(&self, members: &[Bound<'_, PyAny>]) -> Result<TypeAnnotation> {
+        let is_none = |member: &Bound<'_, PyAny>| {
+            member.get_type().name().unwrap().to_string() == "Constant"
+                && member.getattr("value").unwrap().is_none()
+        };
+        let types: Vec<_> = members.iter().filter(|member| !is_none(member)).collect();
+        match types.as_slice() {
+            [inner] if types.len() < members.len() => {
+                Ok(TypeAnnotation::optional(self.get_type_annotation(inner)?))
+            }
+            _ => Err(CompilerError::UnsupportedFeature(
+                "Union types are only supported as a type or None (Optional[T])".to_string(),
+            )),
+        }
+    }
+
     /// Get optional type annotation (returns None if attribute is None or missing)
     fn get_optional_type_annotation(
         &self,
         node: &Bound<'_, PyAny>,
         attr: &str,
-    ) -> Option<TypeAnnotation> {
+    ) -> Result<Option<TypeAnnotation>> {
         Python::attach(|_| match node.getattr(attr) {
             Ok(py_annot) => {
                 if py_annot.is_none() {
-                    None
+                    Ok(None)
                 } else {
                     // Check if this is a Constant with value None (for -> None return type)
                     if let Ok(value) = py_annot.getattr("value") {
                         if value.is_none() {
-                            return None;
+                            return Ok(None);
                         }
                     }
 
-                    Some(self.get_type_annotation(&py_annot).unwrap())
+                    Ok(Some(self.get_type_annotation(&py_annot)?))
                 }
             }
-            Err(_) => None,
+            Err(_) => Ok(None),
+        })
+    }
+
+    /// Get the return annotation of a generator function: Iterator[T], Iterable[T]
+    /// or Generator[T, None, None] (values can't be sent into a generator)
+    fn get_generator_annotation(
+        &self,
+        node: &Bound<'_, PyAny>,
+        func_name: &str,
+    ) -> Result<TypeAnnotation> {
+        Python::attach(|_| {
+            let expected = || {
+                CompilerError::UnsupportedFeature(format!(
+                    "Generator function '{}' must be annotated to return Iterator[T]",
+                    func_name
+                ))
+            };
+            let py_annot = node.getattr("returns").unwrap();
+            if py_annot.get_type().name().unwrap().to_string() != "Subscript" {
+                return Err(expected());
+            }
+            let base = py_annot.getattr("value").unwrap();
+            let slice = py_annot.getattr("slice").unwrap();
+            let Some(base_name) = self.annotation_name(&base) else {
+                return Err(expected());
+            };
+            match base_name.as_str() {
+                "Iterator" | "Iterable" => Ok(TypeAnnotation::Iterator(Box::new(
+                    self.get_type_annotation(&slice)?,
+                ))),
+                "Generator" => {
+                    // Generator[Yield, Send, Return]: only the yield type may be set
+                    let elts = slice.getattr("elts").ok();
+                    match elts.map(|e| e.cast_into::<PyList>()) {
+                        Some(Ok(elts)) if elts.len() == 3 => {
+                            for i in 1..3 {
+                                let elt = elts.get_item(i).unwrap();
+                                let is_none = elt.get_type().name().unwrap().to_string()
+                                    == "Constant"
+                                    && elt.getattr("value").unwrap().is_none();
+                                if !is_none {
+                                    return Err(CompilerError::UnsupportedFeature(format!(
+                                        "Generator function '{}' must use None for the send and return types: Generator[T, None, None]",
+                                        func_name
+                                    )));
+                                }
+                            }
+                            Ok(TypeAnnotation::Iterator(Box::new(
+                                self.get_type_annotation(&elts.get_item(0).unwrap())?,
+                            )))
+                        }
+                        _ => Err(expected()),
+                    }
+                }
+                _ => Err(expected()),
+            }
         })
     }
 
@@ -908,11 +2075,30 @@ impl AstConverter {
         node.getattr(attr).unwrap().extract::<String>().unwrap()
     }
 
+    // Helper: Get where a statement starts; Python's columns are 0-based
+    fn get_span(&self, node: &Bound<'_, PyAny>) -> Span {
+        let line = node.getattr("lineno").unwrap().extract::<u32>().unwrap();
+        let col_offset = node
+            .getattr("col_offset")
+            .unwrap()
+            .extract::<u32>()
+            .unwrap();
+        Span::new(line, col_offset + 1)
+    }
+
     // Helper: Get list attribute (only call for required fields)
     fn get_list_attr<'py>(&self, node: &Bound<'py, PyAny>, attr: &str) -> Bound<'py, PyList> {
         node.getattr(attr).unwrap().cast_into::<PyList>().unwrap()
     }
 
+    // Helper: Get list of string attribute from node
+    fn get_string_list_attr(&self, node: &Bound<'_, PyAny>, attr: &str) -> Vec<String> {
+        self.get_list_attr(node, attr)
+            .iter()
+            .map(|item| item.extract::<String>().unwrap())
+            .collect()
+    }
+
     // Helper: Get name id from Name node
     fn get_name_id(&self, node: &Bound<'_, PyAny>) -> String {
         self.get_string_attr(node, "id")
@@ -956,6 +2142,20 @@ impl AstConverter {
         }
     }
 
+    /// The intrinsic module an import refers to. Like the standard library, an
+    /// intrinsic module is only used when no source file on the search paths
+    /// has its name.
+    fn intrinsic_module(&self, module_name: &str, level: usize) -> Option<IntrinsicModule> {
+        if level > 0 {
+            return None;
+        }
+        let module = IntrinsicModule::from_name(module_name)?;
+        match self.resolve_absolute(module_name) {
+            Ok(_) => None,
+            Err(_) => Some(module),
+        }
+    }
+
     /// Resolve an absolute import (level = 0)
     fn resolve_absolute(&self, module_name: &str) -> Result<std::path::PathBuf> {
         let parts: Vec<&str> = module_name.split('.').collect();
@@ -1028,7 +2228,7 @@ impl AstConverter {
         // Navigate to the correct subdirectory for all but the last part
         for &part in &parts[..parts.len() - 1] {
             current = current.join(part);
-            if !current.is_dir() {
+            if !self.files.is_dir(&current) {
                 return None;
             }
             // Any directory is treated as a package (directories are packages, modules are .py files)
@@ -1038,10 +2238,16 @@ impl AstConverter {
 
         // Try as a module file: dir/module.py
         let module_file = current.join(format!("{}.py", last_part));
-        if module_file.exists() {
+        if self.files.is_file(&module_file) {
             return Some(module_file);
         }
 
+        // Or as the interface of a static library: dir/module.pyi
+        let interface_file = current.join(format!("{}.pyi", last_part));
+        if self.files.is_file(&interface_file) {
+            return Some(interface_file);
+        }
+
         // Packages are just directories - cannot import a directory directly
         None
     }
@@ -1049,12 +2255,15 @@ impl AstConverter {
     /// Convert a file path back to a module ID
     pub fn path_to_module_id(&self, path: &std::path::Path) -> String {
         // Canonicalize input path if possible
-        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        let path = self
+            .files
+            .canonicalize(path)
+            .unwrap_or_else(|_| path.to_path_buf());
 
         // Find which search path this file is under
         for search_path in &self.search_paths {
             // Canonicalize search path for comparison
-            if let Ok(canonical_search) = search_path.canonicalize() {
+            if let Ok(canonical_search) = self.files.canonicalize(search_path) {
                 if let Ok(relative) = path.strip_prefix(&canonical_search) {
                     return self.relative_path_to_module_id(relative);
                 }
@@ -1074,8 +2283,11 @@ impl AstConverter {
         for component in relative.components() {
             if let std::path::Component::Normal(s) = component {
                 let s = s.to_string_lossy();
-                // Remove .py extension from module files
-                let name = s.strip_suffix(".py").unwrap_or(&s);
+                // Remove .py (or .pyi) extension from module files
+                let name = s
+                    .strip_suffix(".py")
+                    .or_else(|| s.strip_suffix(".pyi"))
+                    .unwrap_or(&s);
                 parts.push(name.to_string());
             }
         }
@@ -1133,4 +2345,186 @@ def add(a: int, b: int) -> int:
             assert_eq!(module.body.len(), 1);
         });
     }
+
+    #[test]
+    fn test_convert_statement_spans() {
+        let source = r#"
+def f(x: int) -> int:
+    if x > 0:
+        return x
+    return 0
+"#;
+        let py_ast = parse_python(source).unwrap();
+
+        let temp_dir = std::env::temp_dir();
+        let converter = AstConverter::new(&temp_dir);
+        Python::attach(|py| {
+            let module = converter
+                .convert_module(
+                    py_ast.bind(py),
+                    std::path::PathBuf::from("test.py"),
+                    ModuleName::new("test"),
+                )
+                .unwrap();
+            let Stmt::FunctionDef { body, span, .. } = &module.body[0] else {
+                panic!("expected a function definition");
+            };
+            assert_eq!(*span, Span::new(2, 1));
+            let spans: Vec<Span> = body.iter().map(Stmt::span).collect();
+            assert_eq!(spans, vec![Span::new(3, 5), Span::new(5, 5)]);
+            let Stmt::If {
+                body: then_body, ..
+            } = &body[0]
+            else {
+                panic!("expected an if statement");
+            };
+            assert_eq!(then_body[0].span(), Span::new(4, 9));
+        });
+    }
+
+    #[test]
+    fn test_convert_typing_aliases() {
+        let source = r#"
+import typing
+from typing import Dict, List, Tuple
+
+def f(a: List[int], b: typing.Dict[str, int], c: typing.Optional[str]) -> Tuple[int, str]:
+    return (len(a), c)
+"#;
+        let py_ast = parse_python(source).unwrap();
+
+        let temp_dir = std::env::temp_dir();
+        let converter = AstConverter::new(&temp_dir);
+        Python::attach(|py| {
+            let module = converter
+                .convert_module(
+                    py_ast.bind(py),
+                    std::path::PathBuf::from("test.py"),
+                    ModuleName::new("test"),
+                )
+                .unwrap();
+            let Stmt::FunctionDef {
+                args, return_type, ..
+            } = &module.body[0]
+            else {
+                panic!("expected a function definition");
+            };
+            let annotations: Vec<_> = args.iter().map(|arg| arg.annotation.clone()).collect();
+            assert_eq!(
+                annotations,
+                vec![
+                    Some(TypeAnnotation::List(Box::new(TypeAnnotation::Int))),
+                    Some(TypeAnnotation::Dict(
+                        Box::new(TypeAnnotation::Str),
+                        Box::new(TypeAnnotation::Int)
+                    )),
+                    Some(TypeAnnotation::optional(TypeAnnotation::Str)),
+                ]
+            );
+            assert_eq!(
+                return_type,
+                &Some(TypeAnnotation::Tuple(vec![
+                    TypeAnnotation::Int,
+                    TypeAnnotation::Str
+                ]))
+            );
+        });
+    }
+
+    #[test]
+    fn test_convert_generic_class() {
+        let source = r#"
+from typing import Generic, TypeVar
+
+T = TypeVar("T")
+
+class Box(Generic[T]):
+    item: T
+
+    def pair(self, other: "Box[T]") -> tuple[T, T]:
+        return (self.item, other.item)
+"#;
+        let py_ast = parse_python(source).unwrap();
+
+        let temp_dir = std::env::temp_dir();
+        let converter = AstConverter::new(&temp_dir);
+        Python::attach(|py| {
+            let module = converter
+                .convert_module(
+                    py_ast.bind(py),
+                    std::path::PathBuf::from("test.py"),
+                    ModuleName::new("test"),
+                )
+                .unwrap();
+            // The TypeVar assignment is dropped
+            assert_eq!(module.body.len(), 1);
+            let Stmt::ClassDef {
+                base,
+                type_params,
+                body,
+                ..
+            } = &module.body[0]
+            else {
+                panic!("expected a class definition");
+            };
+            assert_eq!(base, &None);
+            assert_eq!(type_params, &vec!["T".to_string()]);
+            let ClassBodyItem::MethodDef { args, .. } = &body[1] else {
+                panic!("expected a method definition");
+            };
+            assert_eq!(
+                args[1].annotation,
+                Some(TypeAnnotation::Generic {
+                    name: "Box".to_string(),
+                    args: vec![TypeAnnotation::ClassName("T".to_string())],
+                })
+            );
+        });
+    }
+
+    #[test]
+    fn test_convert_protocol_class() {
+        let source = r#"
+from typing import Protocol
+
+class Shape(Protocol):
+    def area(self) -> int: ...
+"#;
+        let py_ast = parse_python(source).unwrap();
+
+        let temp_dir = std::env::temp_dir();
+        let converter = AstConverter::new(&temp_dir);
+        Python::attach(|py| {
+            let module = converter
+                .convert_module(
+                    py_ast.bind(py),
+                    std::path::PathBuf::from("test.py"),
+                    ModuleName::new("test"),
+                )
+                .unwrap();
+            let Stmt::ClassDef {
+                base,
+                is_protocol,
+                body,
+                ..
+            } = &module.body[0]
+            else {
+                panic!("expected a class definition");
+            };
+            assert_eq!(base, &None);
+            assert!(is_protocol);
+            let ClassBodyItem::MethodDef {
+                name,
+                return_type,
+                body,
+                ..
+            } = &body[0]
+            else {
+                panic!("expected a method definition");
+            };
+            assert_eq!(name, "area");
+            assert_eq!(return_type, &Some(TypeAnnotation::Int));
+            assert!(body.is_empty());
+        });
+    }
 }
@@ -0,0 +1,186 @@
+//! `@dataclass` support
+//!
+//! The methods a dataclass gets are generated here as ordinary AST methods,
+//! so definition collection and body lowering treat them like hand-written
+//! ones. For
+//!
+//! ```text
+//! @dataclass
+//! class Point:
+//!     x: int
+//!     y: int = 0
+//! ```
+//!
+//! the class gains (unless it defines them itself):
+//!
+//! ```text
+//! def __init__(self, x: int, y: int = 0) -> None:
+//!     self.x = x
+//!     self.y = y
+//! def __eq__(self, other: Point) -> bool:
+//!     return self.x == other.x and self.y == other.y
+//! def __repr__(self) -> str:
+//!     return f"Point(x={self.x!r}, y={self.y!r})"
+//! ```
+
+use crate::error::{CompilerError, Result};
+use crate::span::Span;
+
+use super::types::{
+    Arg, BoolOp, ClassBodyItem, CompareOp, Constant, Expr, FStringPart, MethodKind, Stmt,
+    TypeAnnotation,
+};
+
+/// A dataclass field: a class-level annotation with an optional default
+#[derive(Debug, Clone)]
+pub struct DataclassField {
+    pub name: String,
+    pub annotation: TypeAnnotation,
+    pub default: Option<Expr>,
+}
+
+/// The methods `@dataclass` adds to `class_name`, skipping any the class
+/// body already defines
+pub fn dataclass_methods(
+    class_name: &str,
+    fields: &[DataclassField],
+    body: &[ClassBodyItem],
+) -> Result<Vec<ClassBodyItem>> {
+    // As in Python, fields without a default cannot follow one with a default
+    let mut first_default: Option<&str> = None;
+    for field in fields {
+        match (&field.default, first_default) {
+            (Some(_), None) => first_default = Some(&field.name),
+            (None, Some(default_name)) => {
+                return Err(CompilerError::TypeErrorSimple(format!(
+                    "non-default argument '{}' follows default argument '{}' in dataclass '{}'",
+                    field.name, default_name, class_name
+                )))
+            }
+            _ => {}
+        }
+    }
+
+    let defines = |method: &str| {
+        body.iter()
+            .any(|item| matches!(item, ClassBodyItem::MethodDef { name, .. } if name == method))
+    };
+
+    let names: Vec<&str> = fields.iter().map(|field| field.name.as_str()).collect();
+    let mut methods = Vec::new();
+    if !defines("__init__") {
+        methods.push(init_method(fields));
+    }
+    if !defines("__eq__") {
+        methods.push(eq_method(class_name, &names));
+    }
+    if !defines("__repr__") {
+        methods.push(repr_method(class_name, &names));
+    }
+    Ok(methods)
+}
+
+fn self_param() -> Arg {
+    Arg {
+        name: "self".to_string(),
+        annotation: None,
+        default: None,
+    }
+}
+
+/// `obj.field`
+fn field_of(obj: &str, field: &str) -> Expr {
+    Expr::Attribute {
+        value: Box::new(Expr::Name(obj.to_string())),
+        attr: field.to_string(),
+    }
+}
+
+fn init_method(fields: &[DataclassField]) -> ClassBodyItem {
+    let mut args = vec![self_param()];
+    args.extend(fields.iter().map(|field| Arg {
+        name: field.name.clone(),
+        annotation: Some(field.annotation.clone()),
+        default: field.default.clone(),
+    }));
+    let body = fields
+        .iter()
+        .map(|field| Stmt::Assign {
+            target: field_of("self", &field.name),
+            value: Expr::Name(field.name.clone()),
+            type_annotation: None,
+            span: Span::default(),
+        })
+        .collect();
+    ClassBodyItem::MethodDef {
+        name: "__init__".to_string(),
+        args,
+        return_type: None,
+        body,
+        kind: MethodKind::Instance,
+        span: Span::default(),
+    }
+}
+
+/// `__eq__` comparing `fields` of `self` and `other` one by one
+pub(super) fn eq_method(class_name: &str, fields: &[&str]) -> ClassBodyItem {
+    let mut comparisons: Vec<Expr> = fields
+        .iter()
+        .map(|field| Expr::Compare {
+            left: Box::new(field_of("self", field)),
+            ops: vec![CompareOp::Eq],
+            comparators: vec![field_of("other", field)],
+        })
+        .collect();
+    let result = match comparisons.len() {
+        0 => Expr::Constant(Constant::Bool(true)),
+        1 => comparisons.pop().unwrap(),
+        _ => Expr::BoolOp {
+            op: BoolOp::And,
+            values: comparisons,
+        },
+    };
+    ClassBodyItem::MethodDef {
+        name: "__eq__".to_string(),
+        args: vec![
+            self_param(),
+            Arg {
+                name: "other".to_string(),
+                annotation: Some(TypeAnnotation::ClassName(class_name.to_string())),
+                default: None,
+            },
+        ],
+        return_type: Some(TypeAnnotation::Bool),
+        body: vec![Stmt::Return {
+            value: Some(result),
+            span: Span::default(),
+        }],
+        kind: MethodKind::Instance,
+        span: Span::default(),
+    }
+}
+
+/// `__repr__` giving `ClassName(field=value, ...)` for `fields`
+pub(super) fn repr_method(class_name: &str, fields: &[&str]) -> ClassBodyItem {
+    let mut parts = vec![FStringPart::Literal(format!("{}(", class_name))];
+    for (i, field) in fields.iter().enumerate() {
+        let separator = if i == 0 { "" } else { ", " };
+        parts.push(FStringPart::Literal(format!("{}{}=", separator, field)));
+        parts.push(FStringPart::Value {
+            value: field_of("self", field),
+            repr: true,
+        });
+    }
+    parts.push(FStringPart::Literal(")".to_string()));
+    ClassBodyItem::MethodDef {
+        name: "__repr__".to_string(),
+        args: vec![self_param()],
+        return_type: Some(TypeAnnotation::Str),
+        body: vec![Stmt::Return {
+            value: Some(Expr::FString { parts }),
+            span: Span::default(),
+        }],
+        kind: MethodKind::Instance,
+        span: Span::default(),
+    }
+}
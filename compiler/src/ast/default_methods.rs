@@ -0,0 +1,124 @@
+//! Default `__repr__` and `__eq__` for plain classes
+//!
+//! Enabled by `CompilerOptions::synthesize_methods`. Without it, printing an
+//! instance of a class that defines neither `__str__` nor `__repr__` gives
+//! `<Name object>`. With it, every class that does not define (or inherit)
+//! the methods gets the same ones `@dataclass` would generate:
+//!
+//! ```text
+//! class Point:
+//!     x: int
+//!     y: int
+//!
+//! def __eq__(self, other: Point) -> bool:
+//!     return self.x == other.x and self.y == other.y
+//! def __repr__(self) -> str:
+//!     return f"Point(x={self.x!r}, y={self.y!r})"
+//! ```
+//!
+//! Fields are the class-level annotations, inherited ones first; those only
+//! `__init__` assigns are not known here. Subclasses are only handled when
+//! their whole base chain is defined in the same module, since the fields
+//! of other bases are not known here either; generic classes and protocols
+//! are left alone.
+
+use std::collections::HashMap;
+
+use super::dataclass::{eq_method, repr_method};
+use super::types::{ClassBodyItem, Module, Stmt};
+
+/// A class definition as seen by the synthesis: its base and body
+struct ClassInfo<'a> {
+    base: Option<&'a str>,
+    body: &'a [ClassBodyItem],
+}
+
+/// Add the default `__eq__` and `__repr__` to the classes of `module`
+pub fn synthesize_default_methods(module: &mut Module) {
+    let mut additions: HashMap<String, Vec<ClassBodyItem>> = HashMap::new();
+    {
+        let classes: HashMap<&str, ClassInfo> = module
+            .body
+            .iter()
+            .filter_map(|stmt| match stmt {
+                Stmt::ClassDef {
+                    name,
+                    base,
+                    type_params,
+                    is_protocol: false,
+                    body,
+                    ..
+                } if type_params.is_empty() => Some((
+                    name.as_str(),
+                    ClassInfo {
+                        base: base.as_deref(),
+                        body,
+                    },
+                )),
+                _ => None,
+            })
+            .collect();
+
+        for name in classes.keys() {
+            let Some(chain) = class_chain(&classes, name) else {
+                continue;
+            };
+            let defines = |method: &str| {
+                chain.iter().any(|class| {
+                    class.body.iter().any(|item| {
+                        matches!(item, ClassBodyItem::MethodDef { name, .. } if name == method)
+                    })
+                })
+            };
+            // Fields in layout order: the root class's first
+            let fields: Vec<&str> = chain
+                .iter()
+                .rev()
+                .flat_map(|class| class.body.iter())
+                .filter_map(|item| match item {
+                    ClassBodyItem::FieldDef { name, .. } => Some(name.as_str()),
+                    ClassBodyItem::MethodDef { .. } => None,
+                })
+                .collect();
+
+            let mut methods = Vec::new();
+            if !defines("__eq__") {
+                methods.push(eq_method(name, &fields));
+            }
+            if !defines("__repr__") {
+                methods.push(repr_method(name, &fields));
+            }
+            if !methods.is_empty() {
+                additions.insert(name.to_string(), methods);
+            }
+        }
+    }
+
+    for stmt in &mut module.body {
+        if let Stmt::ClassDef { name, body, .. } = stmt {
+            if let Some(methods) = additions.remove(name.as_str()) {
+                body.extend(methods);
+            }
+        }
+    }
+}
+
+/// `name` followed by its bases, or `None` if a base is not a plain class
+/// of this module
+fn class_chain<'a>(
+    classes: &'a HashMap<&str, ClassInfo<'a>>,
+    name: &str,
+) -> Option<Vec<&'a ClassInfo<'a>>> {
+    let mut chain = Vec::new();
+    let mut current = Some(name);
+    while let Some(class_name) = current {
+        let class = classes.get(class_name)?;
+        // A cyclic hierarchy is reported later, by definition collection
+        if chain.len() > classes.len() {
+            return None;
+        }
+        chain.push(class);
+        current = class.base;
+    }
+    Some(chain)
+}
@@ -1,4 +1,7 @@
 pub mod converter;
+pub mod dataclass;
+pub mod default_methods;
+pub mod super_init;
 pub mod types;
 
 pub use converter::*;
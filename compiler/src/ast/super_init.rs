@@ -0,0 +1,141 @@
+//! Implicit `super().__init__()` calls
+//!
+//! Enabled by `CompilerOptions::auto_super_init`. A subclass whose
+//! `__init__` never calls `super().__init__(...)` leaves the fields its
+//! base's `__init__` assigns unset, which lowering reports as an error
+//! unless the subclass assigns them itself. With the option, such an
+//! `__init__` first calls the base's, when that needs no arguments (every
+//! parameter after `self` has a default), as a C++ or Java constructor runs
+//! the base's default constructor first:
+//!
+//! ```text
+//! class Square(Shape):
+//!     def __init__(self, side: int) -> None:
+//!         super().__init__()    # added
+//!         self.side = side
+//! ```
+//!
+//! Only bases defined in the same module are looked at, since the
+//! signatures of others are not known here; generic classes and protocols
+//! are left alone.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::span::Span;
+
+use super::types::{calls_super_init, Arg, ClassBodyItem, Expr, MethodKind, Module, Stmt};
+
+/// Make the `__init__` of the classes of `module` that do not call their
+/// base's call it first, where the base's takes no arguments
+pub fn insert_super_init_calls(module: &mut Module) {
+    let mut missing: HashSet<String> = HashSet::new();
+    {
+        let classes: HashMap<&str, (Option<&str>, &[ClassBodyItem])> = module
+            .body
+            .iter()
+            .filter_map(|stmt| match stmt {
+                Stmt::ClassDef {
+                    name,
+                    base,
+                    type_params,
+                    is_protocol: false,
+                    body,
+                    ..
+                } if type_params.is_empty() => {
+                    Some((name.as_str(), (base.as_deref(), body.as_slice())))
+                }
+                _ => None,
+            })
+            .collect();
+
+        for (name, (base, body)) in &classes {
+            let Some((_, init_body)) = init_method(body) else {
+                continue;
+            };
+            if calls_super_init(init_body) {
+                continue;
+            }
+            let Some(base_args) = inherited_init(&classes, *base) else {
+                continue;
+            };
+            if base_args.iter().skip(1).all(|arg| arg.default.is_some()) {
+                missing.insert(name.to_string());
+            }
+        }
+    }
+
+    for stmt in &mut module.body {
+        let Stmt::ClassDef { name, body, .. } = stmt else {
+            continue;
+        };
+        if !missing.contains(name.as_str()) {
+            continue;
+        }
+        for item in body {
+            if let ClassBodyItem::MethodDef {
+                name,
+                body,
+                kind: MethodKind::Instance,
+                ..
+            } = item
+            {
+                if name == "__init__" {
+                    body.insert(0, super_init_call());
+                }
+            }
+        }
+    }
+}
+
+/// The parameters and body of the `__init__` a class body defines
+fn init_method(body: &[ClassBodyItem]) -> Option<(&[Arg], &[Stmt])> {
+    body.iter().find_map(|item| match item {
+        ClassBodyItem::MethodDef {
+            name,
+            args,
+            body,
+            kind: MethodKind::Instance,
+            ..
+        } if name == "__init__" => Some((args.as_slice(), body.as_slice())),
+        _ => None,
+    })
+}
+
+/// The parameters of the `__init__` the class `base` defines or inherits,
+/// or `None` if none of its bases in this module defines one
+fn inherited_init<'a>(
+    classes: &HashMap<&str, (Option<&'a str>, &'a [ClassBodyItem])>,
+    base: Option<&str>,
+) -> Option<&'a [Arg]> {
+    let mut current = base;
+    // A cyclic hierarchy is reported later, by definition collection
+    for _ in 0..classes.len() {
+        let &(base, body) = classes.get(current?)?;
+        if let Some((args, _)) = init_method(body) {
+            return Some(args);
+        }
+        current = base;
+    }
+    None
+}
+
+/// `super().__init__()`, which the compiler generates, so its position is
+/// unknown
+fn super_init_call() -> Stmt {
+    let parent = Expr::Call {
+        func: Box::new(Expr::Name("super".to_string())),
+        args: Vec::new(),
+        keywords: Vec::new(),
+    };
+    Stmt::Expr {
+        value: Expr::Call {
+            func: Box::new(Expr::Attribute {
+                value: Box::new(parent),
+                attr: "__init__".to_string(),
+            }),
+            args: Vec::new(),
+            keywords: Vec::new(),
+        },
+        span: Span::default(),
+    }
+}
@@ -1,3 +1,7 @@
+use std::collections::BTreeSet;
+
+use crate::span::Span;
+
 /// Type annotations in Python source
 #[derive(Debug, Clone, PartialEq)]
 pub enum TypeAnnotation {
@@ -13,10 +17,40 @@ pub enum TypeAnnotation {
     Bytes,
     /// bytearray type (mutable byte sequence)
     ByteArray,
+    /// typing.TextIO, the text file returned by open()
+    TextIO,
     /// list[int] type
     List(Box<TypeAnnotation>),
+    /// dict[str, int] type
+    Dict(Box<TypeAnnotation>, Box<TypeAnnotation>),
+    /// set[int] type
+    Set(Box<TypeAnnotation>),
+    /// frozenset[int] type
+    FrozenSet(Box<TypeAnnotation>),
+    /// tuple[int, str] type (fixed arity)
+    Tuple(Vec<TypeAnnotation>),
+    /// Iterator[int] (or Iterable[int], Generator[int, None, None]) type,
+    /// only valid as the return type of a generator function
+    Iterator(Box<TypeAnnotation>),
     /// Class name type (e.g., Point, Rectangle)
     ClassName(String),
+    /// Optional[int] (or int | None, Union[int, None]) type
+    Optional(Box<TypeAnnotation>),
+    /// A generic class given type arguments (e.g., Stack[int], Pair[str, int])
+    Generic {
+        name: String,
+        args: Vec<TypeAnnotation>,
+    },
+}
+
+impl TypeAnnotation {
+    /// `inner | None`; an Optional type stays as it is
+    pub fn optional(inner: TypeAnnotation) -> TypeAnnotation {
+        match inner {
+            TypeAnnotation::Optional(_) => inner,
+            _ => TypeAnnotation::Optional(Box::new(inner)),
+        }
+    }
 }
 
 /// A complete Python module
@@ -25,14 +59,20 @@ pub struct Module {
     pub id: crate::ast::ModuleName,
     pub path: std::path::PathBuf,
     pub imports: Vec<crate::ast::ImportInfo>,
+    /// Imports of modules the compiler provides itself (e.g., `sys`)
+    pub intrinsic_imports: Vec<crate::ast::IntrinsicImport>,
+    /// The names `__all__` lists, which `from module import *` imports
+    /// instead of every public name
+    pub all: Option<Vec<String>>,
     pub body: Vec<Stmt>,
 }
 
-/// Function argument with optional type annotation
+/// Function argument with optional type annotation and default value
 #[derive(Debug, Clone)]
 pub struct Arg {
     pub name: String,
     pub annotation: Option<TypeAnnotation>,
+    pub default: Option<Expr>,
 }
 
 /// An except handler clause in a try statement
@@ -54,15 +94,45 @@ pub enum ClassBodyItem {
         name: String,
         annotation: TypeAnnotation,
     },
-    /// Method definition (function with implicit self parameter)
+    /// Method definition (function with implicit self parameter, unless
+    /// `kind` says otherwise)
     MethodDef {
         name: String,
         args: Vec<Arg>,
         return_type: Option<TypeAnnotation>,
         body: Vec<Stmt>,
+        kind: MethodKind,
+        /// Where the `def` is; unknown for generated methods
+        span: Span,
     },
 }
 
+/// How a method is bound, from its decorator
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MethodKind {
+    /// A plain method; the first parameter is the instance
+    Instance,
+    /// `@staticmethod`: no implicit first parameter
+    Static,
+    /// `@classmethod`: the first parameter is the class
+    Class,
+    /// `@property`: a getter run when the attribute is read
+    Property,
+    /// `@name.setter`: run when the property is assigned; registered under
+    /// [`property_setter_name`]
+    Setter,
+    /// `@abstractmethod`, or an instance method whose body is only `...` or
+    /// `raise NotImplementedError`: subclasses must override it, and a class
+    /// that leaves one unimplemented cannot be instantiated
+    Abstract,
+}
+
+/// The name a property's setter is registered under, which cannot clash with
+/// a method name
+pub fn property_setter_name(property: &str) -> String {
+    format!("{}.setter", property)
+}
+
 /// Binary operators
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum BinOperator {
@@ -89,6 +159,10 @@ pub enum CompareOp {
     LtE,   // <=
     Gt,    // >
     GtE,   // >=
+    In,    // in
+    NotIn, // not in
+    Is,    // is
+    IsNot, // is not
 }
 
 /// Boolean operators
@@ -105,7 +179,8 @@ pub enum UnaryOp {
     USub, // - (unary minus)
 }
 
-/// Statements
+/// Statements; each records the `span` where it starts in the source,
+/// unknown for statements the compiler generates
 #[derive(Debug, Clone)]
 pub enum Stmt {
     /// Function definition
@@ -114,13 +189,27 @@ pub enum Stmt {
         args: Vec<Arg>,
         return_type: Option<TypeAnnotation>,
         body: Vec<Stmt>,
+        /// Whether the function is decorated with `@export`, which gives it
+        /// a C symbol of its own name
+        exported: bool,
+        /// The C function an `@extern("name")` declaration stands for; its
+        /// body is only `...`
+        extern_name: Option<String>,
+        span: Span,
     },
 
     /// Class definition
     ClassDef {
         name: String,
         base: Option<String>,
+        /// Type parameters of a generic class (`class Stack(Generic[T])` or
+        /// `class Stack[T]:`); empty for other classes
+        type_params: Vec<String>,
+        /// Whether the class is a `typing.Protocol`, whose methods only
+        /// declare the signatures a class must provide to be passed for it
+        is_protocol: bool,
         body: Vec<ClassBodyItem>,
+        span: Span,
     },
 
     /// If statement with optional else
@@ -128,20 +217,28 @@ pub enum Stmt {
         test: Expr,
         body: Vec<Stmt>,
         orelse: Vec<Stmt>,
+        span: Span,
     },
 
-    /// While loop
-    While { test: Expr, body: Vec<Stmt> },
+    /// While loop; `orelse` runs when the loop exits without `break`
+    While {
+        test: Expr,
+        body: Vec<Stmt>,
+        orelse: Vec<Stmt>,
+        span: Span,
+    },
 
-    /// For loop
+    /// For loop; `orelse` runs when the loop exits without `break`
     For {
         target: String,
         iter: Expr,
         body: Vec<Stmt>,
+        orelse: Vec<Stmt>,
+        span: Span,
     },
 
     /// Return statement
-    Return { value: Option<Expr> },
+    Return { value: Option<Expr>, span: Span },
 
     /// Assignment with optional type annotation
     /// Target can be Name, Attribute (a.b.c), or Subscript (a[0][1])
@@ -149,6 +246,7 @@ pub enum Stmt {
         target: Expr,
         value: Expr,
         type_annotation: Option<TypeAnnotation>,
+        span: Span,
     },
 
     /// Augmented assignment (+=, -=, etc.)
@@ -156,10 +254,11 @@ pub enum Stmt {
         target: String,
         op: BinOperator,
         value: Expr,
+        span: Span,
     },
 
     /// Expression statement (e.g., function call)
-    Expr { value: Expr },
+    Expr { value: Expr, span: Span },
 
     /// Try/except/finally statement
     Try {
@@ -167,12 +266,534 @@ pub enum Stmt {
         handlers: Vec<ExceptHandler>,
         orelse: Vec<Stmt>,    // else clause (runs if no exception)
         finalbody: Vec<Stmt>, // finally clause (always runs)
+        span: Span,
     },
 
     /// Raise statement
     Raise {
-        exc: Option<Expr>, // None for bare 'raise' (re-raise)
+        exc: Option<Expr>,   // None for bare 'raise' (re-raise)
+        cause: Option<Expr>, // `raise exc from cause`
+        span: Span,
     },
+
+    /// With statement with a single context manager
+    /// (`with a, b:` is converted to nested With statements)
+    With {
+        context: Expr,
+        target: Option<Expr>, // `as` target, if any
+        body: Vec<Stmt>,
+        span: Span,
+    },
+
+    /// Yield statement (makes the enclosing function a generator)
+    Yield { value: Expr, span: Span },
+
+    /// `global a, b` declaration
+    Global { names: Vec<String>, span: Span },
+
+    /// `nonlocal a, b` declaration
+    Nonlocal { names: Vec<String>, span: Span },
+
+    /// `del a, d[k]` statement
+    Delete { targets: Vec<Expr>, span: Span },
+}
+
+impl Stmt {
+    /// Where the statement starts in the source
+    pub fn span(&self) -> Span {
+        match self {
+            Stmt::FunctionDef { span, .. }
+            | Stmt::ClassDef { span, .. }
+            | Stmt::If { span, .. }
+            | Stmt::While { span, .. }
+            | Stmt::For { span, .. }
+            | Stmt::Return { span, .. }
+            | Stmt::Assign { span, .. }
+            | Stmt::AugAssign { span, .. }
+            | Stmt::Expr { span, .. }
+            | Stmt::Try { span, .. }
+            | Stmt::Raise { span, .. }
+            | Stmt::With { span, .. }
+            | Stmt::Yield { span, .. }
+            | Stmt::Global { span, .. }
+            | Stmt::Nonlocal { span, .. }
+            | Stmt::Delete { span, .. } => *span,
+        }
+    }
+}
+
+/// Check whether a function body yields, i.e. defines a generator function
+/// (nested function definitions are not searched)
+pub fn contains_yield(body: &[Stmt]) -> bool {
+    body.iter().any(|stmt| match stmt {
+        Stmt::Yield { .. } => true,
+        Stmt::If { body, orelse, .. }
+        | Stmt::While { body, orelse, .. }
+        | Stmt::For { body, orelse, .. } => contains_yield(body) || contains_yield(orelse),
+        Stmt::With { body, .. } => contains_yield(body),
+        Stmt::Try {
+            body,
+            handlers,
+            orelse,
+            finalbody,
+            ..
+        } => {
+            contains_yield(body)
+                || handlers.iter().any(|h| contains_yield(&h.body))
+                || contains_yield(orelse)
+                || contains_yield(finalbody)
+        }
+        _ => false,
+    })
+}
+
+/// Collect every variable name a function body reads or binds, including
+/// inside nested function definitions
+pub fn referenced_names(body: &[Stmt]) -> BTreeSet<String> {
+    let mut names = BTreeSet::new();
+    for stmt in body {
+        collect_stmt_names(stmt, &mut names);
+    }
+    names
+}
+
+/// Collect every variable name an expression reads
+pub fn expr_names(expr: &Expr) -> BTreeSet<String> {
+    let mut names = BTreeSet::new();
+    collect_expr_names(expr, &mut names);
+    names
+}
+
+/// Visit every statement of a function body, including those nested in
+/// compound statements but not the bodies of nested definitions
+pub fn walk_block<'s>(body: &'s [Stmt], visit: &mut dyn FnMut(&'s Stmt)) {
+    for stmt in body {
+        visit(stmt);
+        match stmt {
+            Stmt::If { body, orelse, .. }
+            | Stmt::While { body, orelse, .. }
+            | Stmt::For { body, orelse, .. } => {
+                walk_block(body, visit);
+                walk_block(orelse, visit);
+            }
+            Stmt::With { body, .. } => walk_block(body, visit),
+            Stmt::Try {
+                body,
+                handlers,
+                orelse,
+                finalbody,
+                ..
+            } => {
+                walk_block(body, visit);
+                for handler in handlers {
+                    walk_block(&handler.body, visit);
+                }
+                walk_block(orelse, visit);
+                walk_block(finalbody, visit);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Collect the names a function body binds (assignment, `for`, `with` and
+/// `del` targets, exception names and nested definitions); nested function
+/// bodies are not searched
+pub fn assigned_names(body: &[Stmt]) -> BTreeSet<String> {
+    let mut names = BTreeSet::new();
+    walk_block(body, &mut |stmt| match stmt {
+        Stmt::FunctionDef { name, .. }
+        | Stmt::ClassDef { name, .. }
+        | Stmt::AugAssign { target: name, .. }
+        | Stmt::For { target: name, .. } => {
+            names.insert(name.clone());
+        }
+        Stmt::Assign { target, .. } => collect_target_names(target, &mut names),
+        Stmt::Delete { targets, .. } => {
+            for target in targets {
+                collect_target_names(target, &mut names);
+            }
+        }
+        Stmt::With {
+            target: Some(target),
+            ..
+        } => collect_target_names(target, &mut names),
+        Stmt::Try { handlers, .. } => {
+            names.extend(handlers.iter().filter_map(|handler| handler.name.clone()));
+        }
+        _ => {}
+    });
+    names
+}
+
+/// Collect the variables and attributes (as dotted paths, see [`target_path`])
+/// a block of statements assigns or deletes, including in nested blocks
+pub fn assigned_paths(body: &[Stmt]) -> BTreeSet<String> {
+    let mut paths = assigned_names(body);
+    walk_block(body, &mut |stmt| match stmt {
+        Stmt::Assign { target, .. } => collect_target_paths(target, &mut paths),
+        Stmt::Delete { targets, .. } => {
+            for target in targets {
+                collect_target_paths(target, &mut paths);
+            }
+        }
+        _ => {}
+    });
+    paths
+}
+
+fn collect_target_paths(target: &Expr, paths: &mut BTreeSet<String>) {
+    match target {
+        Expr::Tuple { elts } => {
+            for elt in elts {
+                collect_target_paths(elt, paths);
+            }
+        }
+        Expr::Starred { value } => collect_target_paths(value, paths),
+        _ => paths.extend(target_path(target)),
+    }
+}
+
+/// The dotted path of a variable or a chain of attributes of one
+/// (`node`, `self.next.value`); None for other expressions
+pub fn target_path(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Name(name) => Some(name.clone()),
+        Expr::Attribute { value, attr } => {
+            target_path(value).map(|base| format!("{}.{}", base, attr))
+        }
+        _ => None,
+    }
+}
+
+/// Check whether a block of statements always ends by returning or raising
+pub fn always_exits(body: &[Stmt]) -> bool {
+    match body.last() {
+        Some(Stmt::Return { .. } | Stmt::Raise { .. }) => true,
+        Some(Stmt::If { body, orelse, .. }) => always_exits(body) && always_exits(orelse),
+        _ => false,
+    }
+}
+
+/// Collect every type annotation written in a block of statements: those of
+/// variables, and the signatures, fields and bodies of definitions in it
+pub fn annotations(body: &[Stmt]) -> Vec<&TypeAnnotation> {
+    fn signature<'s>(
+        args: &'s [Arg],
+        return_type: &'s Option<TypeAnnotation>,
+        body: &'s [Stmt],
+        found: &mut Vec<&'s TypeAnnotation>,
+    ) {
+        found.extend(args.iter().filter_map(|arg| arg.annotation.as_ref()));
+        found.extend(return_type.as_ref());
+        found.extend(annotations(body));
+    }
+
+    let mut found = Vec::new();
+    walk_block(body, &mut |stmt| match stmt {
+        Stmt::Assign {
+            type_annotation: Some(annotation),
+            ..
+        } => found.push(annotation),
+        Stmt::FunctionDef {
+            args,
+            return_type,
+            body,
+            ..
+        } => signature(args, return_type, body, &mut found),
+        Stmt::ClassDef { body, .. } => {
+            for item in body {
+                match item {
+                    ClassBodyItem::FieldDef { annotation, .. } => found.push(annotation),
+                    ClassBodyItem::MethodDef {
+                        args,
+                        return_type,
+                        body,
+                        ..
+                    } => signature(args, return_type, body, &mut found),
+                }
+            }
+        }
+        _ => {}
+    });
+    found
+}
+
+/// Collect the names a function body declares `global` (nested function
+/// bodies are not searched)
+pub fn global_names(body: &[Stmt]) -> BTreeSet<String> {
+    let mut names = BTreeSet::new();
+    walk_block(body, &mut |stmt| {
+        if let Stmt::Global {
+            names: declared, ..
+        } = stmt
+        {
+            names.extend(declared.iter().cloned());
+        }
+    });
+    names
+}
+
+/// Collect the names a function body declares `nonlocal` (nested function
+/// bodies are not searched)
+pub fn nonlocal_names(body: &[Stmt]) -> BTreeSet<String> {
+    let mut names = BTreeSet::new();
+    walk_block(body, &mut |stmt| {
+        if let Stmt::Nonlocal {
+            names: declared, ..
+        } = stmt
+        {
+            names.extend(declared.iter().cloned());
+        }
+    });
+    names
+}
+
+/// Check whether a method body calls `super().__init__(...)`, including in
+/// nested blocks
+pub fn calls_super_init(body: &[Stmt]) -> bool {
+    let mut found = false;
+    walk_block(body, &mut |stmt| {
+        if let Stmt::Expr {
+            value: Expr::Call { func, .. },
+            ..
+        } = stmt
+        {
+            found |= is_super_init(func);
+        }
+    });
+    found
+}
+
+/// `super().__init__`, the callee of a call of the parent's `__init__`
+fn is_super_init(func: &Expr) -> bool {
+    let Expr::Attribute { value, attr } = func else {
+        return false;
+    };
+    let Expr::Call { func, args, .. } = value.as_ref() else {
+        return false;
+    };
+    attr == "__init__"
+        && args.is_empty()
+        && matches!(func.as_ref(), Expr::Name(name) if name == "super")
+}
+
+/// Collect the bodies of the functions defined directly in a function body
+pub fn nested_function_bodies(body: &[Stmt]) -> Vec<&[Stmt]> {
+    let mut bodies = Vec::new();
+    walk_block(body, &mut |stmt| {
+        if let Stmt::FunctionDef { body, .. } = stmt {
+            bodies.push(body.as_slice());
+        }
+    });
+    bodies
+}
+
+/// Names bound by an assignment target (attribute and subscript targets bind none)
+fn collect_target_names(target: &Expr, names: &mut BTreeSet<String>) {
+    match target {
+        Expr::Name(name) => {
+            names.insert(name.clone());
+        }
+        Expr::Tuple { elts } => {
+            for elt in elts {
+                collect_target_names(elt, names);
+            }
+        }
+        Expr::Starred { value } => collect_target_names(value, names),
+        _ => {}
+    }
+}
+
+fn collect_stmt_names(stmt: &Stmt, names: &mut BTreeSet<String>) {
+    match stmt {
+        Stmt::FunctionDef { args, body, .. } => {
+            for default in args.iter().filter_map(|arg| arg.default.as_ref()) {
+                collect_expr_names(default, names);
+            }
+            for stmt in body {
+                collect_stmt_names(stmt, names);
+            }
+        }
+        Stmt::ClassDef { .. } => {}
+        Stmt::If {
+            test, body, orelse, ..
+        }
+        | Stmt::While {
+            test, body, orelse, ..
+        } => {
+            collect_expr_names(test, names);
+            for stmt in body.iter().chain(orelse) {
+                collect_stmt_names(stmt, names);
+            }
+        }
+        Stmt::For {
+            target,
+            iter,
+            body,
+            orelse,
+            ..
+        } => {
+            names.insert(target.clone());
+            collect_expr_names(iter, names);
+            for stmt in body.iter().chain(orelse) {
+                collect_stmt_names(stmt, names);
+            }
+        }
+        Stmt::Return { value, .. } => {
+            if let Some(value) = value {
+                collect_expr_names(value, names);
+            }
+        }
+        Stmt::Raise { exc, cause, .. } => {
+            for value in exc.iter().chain(cause) {
+                collect_expr_names(value, names);
+            }
+        }
+        Stmt::Assign { target, value, .. } => {
+            collect_expr_names(target, names);
+            collect_expr_names(value, names);
+        }
+        Stmt::AugAssign { target, value, .. } => {
+            names.insert(target.clone());
+            collect_expr_names(value, names);
+        }
+        Stmt::Expr { value, .. } | Stmt::Yield { value, .. } => collect_expr_names(value, names),
+        Stmt::Global {
+            names: declared, ..
+        }
+        | Stmt::Nonlocal {
+            names: declared, ..
+        } => {
+            names.extend(declared.iter().cloned());
+        }
+        Stmt::Delete { targets, .. } => {
+            for target in targets {
+                collect_expr_names(target, names);
+            }
+        }
+        Stmt::Try {
+            body,
+            handlers,
+            orelse,
+            finalbody,
+            ..
+        } => {
+            for stmt in body.iter().chain(orelse).chain(finalbody) {
+                collect_stmt_names(stmt, names);
+            }
+            for handler in handlers {
+                if let Some(name) = &handler.name {
+                    names.insert(name.clone());
+                }
+                for stmt in &handler.body {
+                    collect_stmt_names(stmt, names);
+                }
+            }
+        }
+        Stmt::With {
+            context,
+            target,
+            body,
+            ..
+        } => {
+            collect_expr_names(context, names);
+            if let Some(target) = target {
+                collect_expr_names(target, names);
+            }
+            for stmt in body {
+                collect_stmt_names(stmt, names);
+            }
+        }
+    }
+}
+
+fn collect_expr_names(expr: &Expr, names: &mut BTreeSet<String>) {
+    match expr {
+        Expr::Constant(_) => {}
+        Expr::Name(name) => {
+            names.insert(name.clone());
+        }
+        Expr::BinOp { left, right, .. } => {
+            collect_expr_names(left, names);
+            collect_expr_names(right, names);
+        }
+        Expr::Compare {
+            left, comparators, ..
+        } => {
+            collect_expr_names(left, names);
+            for comparator in comparators {
+                collect_expr_names(comparator, names);
+            }
+        }
+        Expr::UnaryOp { operand, .. } => collect_expr_names(operand, names),
+        Expr::Call {
+            func,
+            args,
+            keywords,
+        } => {
+            collect_expr_names(func, names);
+            for arg in args {
+                collect_expr_names(arg, names);
+            }
+            for keyword in keywords {
+                collect_expr_names(&keyword.value, names);
+            }
+        }
+        Expr::BoolOp { values: elts, .. }
+        | Expr::List { elts }
+        | Expr::Set { elts }
+        | Expr::Tuple { elts } => {
+            for elt in elts {
+                collect_expr_names(elt, names);
+            }
+        }
+        Expr::Dict { keys, values } => {
+            for elt in keys.iter().chain(values) {
+                collect_expr_names(elt, names);
+            }
+        }
+        Expr::Subscript { value, index } => {
+            collect_expr_names(value, names);
+            collect_expr_names(index, names);
+        }
+        Expr::Slice { lower, upper, step } => {
+            for bound in [lower, upper, step].into_iter().flatten() {
+                collect_expr_names(bound, names);
+            }
+        }
+        Expr::Attribute { value, .. } | Expr::Starred { value } => collect_expr_names(value, names),
+        Expr::ListComp { elt, generators } | Expr::SetComp { elt, generators } => {
+            collect_expr_names(elt, names);
+            collect_comprehension_names(generators, names);
+        }
+        Expr::DictComp {
+            key,
+            value,
+            generators,
+        } => {
+            collect_expr_names(key, names);
+            collect_expr_names(value, names);
+            collect_comprehension_names(generators, names);
+        }
+        Expr::FString { parts } => {
+            for part in parts {
+                if let FStringPart::Value { value, .. } = part {
+                    collect_expr_names(value, names);
+                }
+            }
+        }
+    }
+}
+
+fn collect_comprehension_names(generators: &[Comprehension], names: &mut BTreeSet<String>) {
+    for generator in generators {
+        collect_expr_names(&generator.target, names);
+        collect_expr_names(&generator.iter, names);
+        for cond in &generator.ifs {
+            collect_expr_names(cond, names);
+        }
+    }
 }
 
 /// Constant values
@@ -216,14 +837,86 @@ pub enum Expr {
     UnaryOp { op: UnaryOp, operand: Box<Expr> },
 
     /// Function call
-    Call { func: Box<Expr>, args: Vec<Expr> },
+    Call {
+        func: Box<Expr>,
+        args: Vec<Expr>,
+        keywords: Vec<Keyword>,
+    },
 
     /// List literal
     List { elts: Vec<Expr> },
 
-    /// Subscript (e.g., list[0])
+    /// Dict literal (keys and values are parallel)
+    Dict { keys: Vec<Expr>, values: Vec<Expr> },
+
+    /// Set literal (never empty; `{}` is a dict)
+    Set { elts: Vec<Expr> },
+
+    /// Tuple literal (e.g., (1, "a")); also the target of `a, b = ...`
+    Tuple { elts: Vec<Expr> },
+
+    /// Subscript (e.g., list[0], s[1:5])
     Subscript { value: Box<Expr>, index: Box<Expr> },
 
+    /// Slice (e.g., the `1:5:2` in s[1:5:2]); only valid as a subscript index
+    Slice {
+        lower: Option<Box<Expr>>,
+        upper: Option<Box<Expr>>,
+        step: Option<Box<Expr>>,
+    },
+
     /// Attribute access (e.g., obj.field)
     Attribute { value: Box<Expr>, attr: String },
+
+    /// Starred expression (e.g., the `*rest` in `a, *rest = xs` or `*args` in `f(*args)`)
+    Starred { value: Box<Expr> },
+
+    /// List comprehension (e.g., [x * 2 for x in xs if x > 0])
+    ListComp {
+        elt: Box<Expr>,
+        generators: Vec<Comprehension>,
+    },
+
+    /// Set comprehension (e.g., {x % 3 for x in xs})
+    SetComp {
+        elt: Box<Expr>,
+        generators: Vec<Comprehension>,
+    },
+
+    /// Dict comprehension (e.g., {k: len(k) for k in names})
+    DictComp {
+        key: Box<Expr>,
+        value: Box<Expr>,
+        generators: Vec<Comprehension>,
+    },
+
+    /// F-string (e.g., f"x={x}")
+    FString { parts: Vec<FStringPart> },
+}
+
+/// One piece of an f-string
+#[derive(Debug, Clone)]
+pub enum FStringPart {
+    /// Literal text between replacement fields
+    Literal(String),
+    /// Replacement field (e.g., {x} or {x!r})
+    Value { value: Expr, repr: bool },
+}
+
+/// One `for target in iter if cond` clause of a comprehension
+#[derive(Debug, Clone)]
+pub struct Comprehension {
+    /// Loop target: a variable name or a tuple of names to unpack into
+    pub target: Expr,
+    /// Iterable expression
+    pub iter: Expr,
+    /// Filter conditions, all of which must hold
+    pub ifs: Vec<Expr>,
+}
+
+/// A `name=value` keyword argument of a call
+#[derive(Debug, Clone)]
+pub struct Keyword {
+    pub arg: String,
+    pub value: Expr,
 }
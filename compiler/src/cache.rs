@@ -0,0 +1,188 @@
+//! Incremental builds
+//!
+//! A rebuild reuses what did not change at two granularities:
+//!
+//! - [`ModuleCache`] keeps the AST of each parsed module with a fingerprint
+//!   of its source, so a module is only parsed again once its source
+//!   changes. A `Compiler` keeps one across the binaries of a project, and
+//!   the language server across the compiles of an editing session.
+//! - [`BuildCache`] is a directory (`pycc --incremental`) keeping the object
+//!   file of each program, keyed on the sources of its modules and the
+//!   options it was built with. When neither changed since the last build,
+//!   the object is relinked instead of generating code for it again.
+//!
+//! Lowering and code generation work on the whole program. The TIR of a
+//! module depends on the types of the modules it imports and on the generic
+//! instances the modules importing it use, and the program is generated into
+//! one LLVM module, which the optimizer inlines across. The program's object
+//! is therefore the unit of generated code the build cache reuses.
+
+use std::collections::HashMap;
+use std::fs;
+use std::hash::Hasher;
+use std::path::{Path, PathBuf};
+
+use crate::ast::Module;
+use crate::error::Result;
+
+/// A 64-bit FNV-1a hash, stable across runs and Rust versions, unlike
+/// `DefaultHasher`, so it can name files that outlive the compiler process
+pub struct Fingerprint(u64);
+
+impl Default for Fingerprint {
+    fn default() -> Self {
+        Fingerprint(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for Fingerprint {
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= u64::from(byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// The fingerprint of a file's contents
+pub fn fingerprint(bytes: &[u8]) -> u64 {
+    let mut hasher = Fingerprint::default();
+    hasher.write(bytes);
+    hasher.finish()
+}
+
+/// Parsed modules, by the search paths they were resolved against and their
+/// path
+#[derive(Default)]
+pub struct ModuleCache {
+    modules: HashMap<(Vec<PathBuf>, PathBuf), (u64, Module)>,
+    hits: usize,
+}
+
+impl ModuleCache {
+    /// The module parsed from `path` when its source had fingerprint
+    /// `source`, if it was
+    pub fn get(&mut self, search_paths: &[PathBuf], path: &Path, source: u64) -> Option<&Module> {
+        let module = self
+            .modules
+            .get(&(search_paths.to_vec(), path.to_path_buf()))
+            .filter(|(parsed, _)| *parsed == source)
+            .map(|(_, module)| module);
+        self.hits += usize::from(module.is_some());
+        module
+    }
+
+    /// Keep the module parsed from `path`, replacing the one parsed from an
+    /// older source
+    pub fn insert(&mut self, search_paths: &[PathBuf], path: &Path, source: u64, module: Module) {
+        self.modules.insert(
+            (search_paths.to_vec(), path.to_path_buf()),
+            (source, module),
+        );
+    }
+
+    /// How many modules were taken from the cache instead of being parsed
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    /// The fingerprint of the source the module at `path` was last parsed
+    /// from
+    pub fn source_fingerprint(&self, search_paths: &[PathBuf], path: &Path) -> Option<u64> {
+        self.modules
+            .get(&(search_paths.to_vec(), path.to_path_buf()))
+            .map(|(source, _)| *source)
+    }
+}
+
+/// A directory of object files, one per program, named after the program's
+/// entry module and the key it was built with
+pub struct BuildCache {
+    dir: PathBuf,
+}
+
+impl BuildCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        BuildCache { dir: dir.into() }
+    }
+
+    fn object_path(&self, program: &str, key: u64) -> PathBuf {
+        self.dir.join(format!("{program}-{key:016x}.o"))
+    }
+
+    /// The object file of `program` built with `key`, if it is cached
+    pub fn get(&self, program: &str, key: u64) -> Option<PathBuf> {
+        let path = self.object_path(program, key);
+        path.is_file().then_some(path)
+    }
+
+    /// Cache the object file `write` writes for `program` built with `key`,
+    /// returning its path. The objects of the program's earlier builds are
+    /// removed; the object only takes its name once it is complete, so an
+    /// interrupted build leaves nothing to be found by the next one.
+    pub fn store(
+        &self,
+        program: &str,
+        key: u64,
+        write: impl FnOnce(&Path) -> Result<()>,
+    ) -> Result<PathBuf> {
+        fs::create_dir_all(&self.dir)?;
+        let path = self.object_path(program, key);
+        let partial = path.with_extension("o.partial");
+        write(&partial)?;
+        fs::rename(&partial, &path)?;
+
+        let prefix = format!("{program}-");
+        for entry in fs::read_dir(&self.dir)?.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            // Names of other programs may start with this one's (`app` and
+            // `app-cli`), so the rest must be a key alone
+            let stale = name
+                .strip_prefix(&prefix)
+                .and_then(|rest| rest.strip_suffix(".o"))
+                .is_some_and(|key| key.len() == 16 && key.chars().all(|c| c.is_ascii_hexdigit()));
+            if stale && entry.path() != path {
+                let _ = fs::remove_file(entry.path());
+            }
+        }
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_fingerprint_is_fnv1a() {
+        assert_eq!(fingerprint(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fingerprint(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_ne!(fingerprint(b"x = 1\n"), fingerprint(b"x = 2\n"));
+    }
+
+    fn write(contents: &'static str) -> impl FnOnce(&Path) -> Result<()> {
+        move |path| Ok(fs::write(path, contents)?)
+    }
+
+    #[test]
+    fn test_build_cache_keeps_latest_object() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = BuildCache::new(temp_dir.path().join("cache"));
+
+        assert!(cache.get("app", 1).is_none());
+        let first = cache.store("app", 1, write("first")).unwrap();
+        cache.store("app-cli", 1, write("other")).unwrap();
+        assert_eq!(cache.get("app", 1), Some(first.clone()));
+
+        let second = cache.store("app", 2, write("second")).unwrap();
+        assert_eq!(fs::read_to_string(&second).unwrap(), "second");
+        assert!(cache.get("app", 1).is_none());
+        assert!(!first.exists());
+        assert!(cache.get("app-cli", 1).is_some());
+    }
+}
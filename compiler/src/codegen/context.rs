@@ -2,14 +2,16 @@
 
 use inkwell::builder::Builder;
 use inkwell::context::Context;
-use inkwell::module::Module;
+use inkwell::module::{FlagBehavior, Module};
 use inkwell::targets::{InitializationConfig, Target, TargetTriple};
 use inkwell::types::StructType;
 use inkwell::values::{FunctionValue, PointerValue};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use crate::driver::Target as CompilerTarget;
 
+use super::tir::debug_info::DebugInfo;
+
 /// Code generation context
 pub struct CodegenContext<'ctx> {
     pub(crate) context: &'ctx Context,
@@ -30,6 +32,65 @@ pub struct CodegenContext<'ctx> {
 
     /// Class name -> LLVM struct type
     pub(crate) class_types: HashMap<String, StructType<'ctx>>,
+
+    /// Whether int `+`, `-`, `*`, `**`, `//` and negation raise OverflowError
+    /// instead of wrapping around
+    pub(crate) checked_int: bool,
+
+    /// Whether division by zero raises ZeroDivisionError and out-of-range
+    /// subscripts raise IndexError (off for release builds that skip them)
+    pub(crate) runtime_checks: bool,
+
+    /// Whether class instances, strings and lists are reference counted and
+    /// freed when the last reference goes away (see codegen/tir/refcount.rs)
+    pub(crate) refcount: bool,
+
+    /// Whether main reports counted objects still alive at exit
+    pub(crate) leak_check: bool,
+
+    /// Whether class instances are traced by the runtime's collector, with a
+    /// shadow stack of roots (see codegen/tir/gc.rs)
+    pub(crate) tracing_gc: bool,
+
+    /// Whether the runtime allocates from a bump arena freed only at exit
+    pub(crate) arena_alloc: bool,
+
+    /// Whether small functions are marked `alwaysinline`
+    pub(crate) inline_small_functions: bool,
+
+    /// Whether try blocks catch exceptions with setjmp instead of polling
+    /// after every statement (see Try in codegen/tir/statements.rs)
+    pub(crate) setjmp_exceptions: bool,
+
+    /// Whether functions keep a record on the runtime's traceback stack
+    /// (see codegen/tir/traceback.rs)
+    pub(crate) traceback: bool,
+
+    /// How many functions may run nested before a call raises
+    /// RecursionError, if any limit (see codegen/tir/recursion.rs)
+    pub(crate) recursion_limit: Option<u32>,
+
+    /// LLVM target features the LLVM passes tune the code for
+    pub(crate) target_features: &'static str,
+
+    /// Whether the module is built into a shared library, started by a
+    /// constructor instead of main (see codegen/tir/exports.rs)
+    pub(crate) shared_library: bool,
+
+    /// Whether the module is built into a static library, whose modules are
+    /// initialized by the program it is linked into (see codegen/tir/exports.rs)
+    pub(crate) static_library: bool,
+
+    /// DWARF debug info, when it is generated (see codegen/tir/debug_info.rs)
+    pub(crate) debug_info: Option<DebugInfo<'ctx>>,
+
+    /// Symbols of the module globals another module defines, which are only
+    /// declared (a pyrepl input's module uses the globals of earlier inputs)
+    pub(crate) external_globals: HashSet<String>,
+
+    /// Name of the function the program starts from: main, unless the module
+    /// is loaded into a JIT next to others that have one
+    pub(crate) main_name: String,
 }
 
 impl<'ctx> CodegenContext<'ctx> {
@@ -45,11 +106,21 @@ impl<'ctx> CodegenContext<'ctx> {
             CompilerTarget::RiscV64 => {
                 Target::initialize_riscv(&InitializationConfig::default());
             }
+            CompilerTarget::Aarch64 => {
+                Target::initialize_aarch64(&InitializationConfig::default());
+            }
         }
 
         // Set the target triple for the specified target architecture
         let target_triple = TargetTriple::create(target.triple());
         module.set_triple(&target_triple);
+        if let Some(abi) = target.llvm_abi() {
+            module.add_metadata_flag(
+                "target-abi",
+                FlagBehavior::Error,
+                context.metadata_string(abi),
+            );
+        }
 
         CodegenContext {
             context,
@@ -60,9 +131,32 @@ impl<'ctx> CodegenContext<'ctx> {
             global_variables: HashMap::new(),
             functions: HashMap::new(),
             class_types: HashMap::new(),
+            checked_int: false,
+            runtime_checks: true,
+            refcount: false,
+            leak_check: false,
+            tracing_gc: false,
+            arena_alloc: false,
+            inline_small_functions: false,
+            setjmp_exceptions: false,
+            traceback: false,
+            recursion_limit: None,
+            target_features: target.llvm_features(),
+            shared_library: false,
+            static_library: false,
+            debug_info: None,
+            external_globals: HashSet::new(),
+            main_name: "main".to_string(),
         }
     }
 
+    /// Override the target triple chosen in `new` (for user-supplied targets)
+    pub fn set_target_triple(&mut self, triple: &str) {
+        Target::initialize_all(&InitializationConfig::default());
+        self.module.set_triple(&TargetTriple::create(triple));
+        self.target_features = "";
+    }
+
     pub fn get_module(&self) -> &Module<'ctx> {
         &self.module
     }
@@ -0,0 +1,80 @@
+//! Embedded data files (`pycc --embed`)
+//!
+//! Files are baked into a read-only table that matches the runtime's
+//! `EmbeddedFile` layout in embed.h: `{ const char* path, i64 path_len, const u8* data, i64 len }`.
+
+use inkwell::module::Linkage;
+use inkwell::values::PointerValue;
+use inkwell::AddressSpace;
+
+use super::context::CodegenContext;
+
+/// Section holding embedded file contents and the lookup table
+const EMBED_SECTION: &str = ".rodata.pyc_embed";
+
+/// A file to bake into the executable
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmbeddedFile {
+    /// Path the program will look the file up by (no leading "./")
+    pub path: String,
+    /// File contents
+    pub data: Vec<u8>,
+}
+
+impl<'ctx> CodegenContext<'ctx> {
+    /// Emit `__pyc_embedded_files` and `__pyc_embedded_count`
+    ///
+    /// Always emitted (possibly empty) because the runtime references both symbols.
+    pub(crate) fn emit_embedded_files(&mut self, files: &[EmbeddedFile]) {
+        let i64_type = self.context.i64_type();
+        let ptr_type = self.context.ptr_type(AddressSpace::default());
+        let entry_type = self.context.struct_type(
+            &[
+                ptr_type.into(), // path
+                i64_type.into(), // path_len
+                ptr_type.into(), // data
+                i64_type.into(), // len
+            ],
+            false,
+        );
+
+        let entries: Vec<_> = files
+            .iter()
+            .map(|file| {
+                let path = self.add_embedded_bytes(file.path.as_bytes(), "embed_path");
+                let data = self.add_embedded_bytes(&file.data, "embed_data");
+                entry_type.const_named_struct(&[
+                    path.into(),
+                    i64_type.const_int(file.path.len() as u64, false).into(),
+                    data.into(),
+                    i64_type.const_int(file.data.len() as u64, false).into(),
+                ])
+            })
+            .collect();
+
+        let table_type = entry_type.array_type(entries.len() as u32);
+        let table = self
+            .module
+            .add_global(table_type, None, "__pyc_embedded_files");
+        table.set_initializer(&entry_type.const_array(&entries));
+        table.set_constant(true);
+        table.set_section(Some(EMBED_SECTION));
+
+        let count = self
+            .module
+            .add_global(i64_type, None, "__pyc_embedded_count");
+        count.set_initializer(&i64_type.const_int(entries.len() as u64, false));
+        count.set_constant(true);
+    }
+
+    /// Add a private constant byte array and return a pointer to it
+    fn add_embedded_bytes(&self, bytes: &[u8], name: &str) -> PointerValue<'ctx> {
+        let value = self.context.const_string(bytes, false);
+        let global = self.module.add_global(value.get_type(), None, name);
+        global.set_initializer(&value);
+        global.set_constant(true);
+        global.set_linkage(Linkage::Private);
+        global.set_section(Some(EMBED_SECTION));
+        global.as_pointer_value()
+    }
+}
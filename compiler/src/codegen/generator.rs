@@ -5,13 +5,18 @@
 //! Unlike AST-based codegen, all operations are infallible since types
 //! and symbols are pre-resolved in the TIR.
 
+use std::collections::HashSet;
+
 use inkwell::context::Context;
 use inkwell::module::Module as LLVMModule;
 
-use crate::driver::Target;
+use crate::driver::{OptLevel, Target};
+use crate::error::Result;
+use crate::tir::escape::find_stack_locals;
 use crate::tir::TirProgram;
 
 use super::context::CodegenContext;
+use super::embed::EmbeddedFile;
 
 /// Code generator
 ///
@@ -19,26 +24,241 @@ use super::context::CodegenContext;
 pub struct Codegen<'ctx> {
     context: &'ctx Context,
     target: Target,
+    embedded_files: Vec<EmbeddedFile>,
+    target_triple: Option<String>,
+    checked_int: bool,
+    runtime_checks: bool,
+    refcount: bool,
+    leak_check: bool,
+    tracing_gc: bool,
+    arena_alloc: bool,
+    inline_small_functions: bool,
+    setjmp_exceptions: bool,
+    traceback: bool,
+    recursion_limit: Option<u32>,
+    opt_level: OptLevel,
+    shared_library: bool,
+    static_library: bool,
+    debug_info: bool,
+    external_globals: HashSet<String>,
+    main_name: String,
 }
 
 impl<'ctx> Codegen<'ctx> {
     pub fn new(context: &'ctx Context, target: Target) -> Self {
-        Codegen { context, target }
+        Codegen {
+            context,
+            target,
+            embedded_files: Vec::new(),
+            target_triple: None,
+            checked_int: false,
+            runtime_checks: true,
+            refcount: false,
+            leak_check: false,
+            tracing_gc: false,
+            arena_alloc: false,
+            inline_small_functions: false,
+            setjmp_exceptions: false,
+            traceback: false,
+            recursion_limit: None,
+            opt_level: OptLevel::O0,
+            shared_library: false,
+            static_library: false,
+            debug_info: false,
+            external_globals: HashSet::new(),
+            main_name: "main".to_string(),
+        }
+    }
+
+    /// Emit code for an arbitrary LLVM triple instead of the built-in target's
+    pub fn with_target_triple(mut self, triple: &str) -> Self {
+        self.target_triple = Some(triple.to_string());
+        self
+    }
+
+    /// Bake data files into the generated module
+    pub fn with_embedded_files(mut self, files: Vec<EmbeddedFile>) -> Self {
+        self.embedded_files = files;
+        self
+    }
+
+    /// Raise OverflowError when int arithmetic leaves the 64-bit range
+    pub fn with_checked_int(mut self, checked_int: bool) -> Self {
+        self.checked_int = checked_int;
+        self
+    }
+
+    /// Raise ZeroDivisionError and IndexError (on by default); without the
+    /// checks those operations are undefined behaviour
+    pub fn with_runtime_checks(mut self, runtime_checks: bool) -> Self {
+        self.runtime_checks = runtime_checks;
+        self
+    }
+
+    /// Count references to class instances, strings and lists and free them
+    /// when the last one goes away
+    pub fn with_refcount(mut self, refcount: bool) -> Self {
+        self.refcount = refcount;
+        self
+    }
+
+    /// Report instances still alive when the program ends (needs refcount)
+    pub fn with_leak_check(mut self, leak_check: bool) -> Self {
+        self.leak_check = leak_check;
+        self
+    }
+
+    /// Collect unreachable class instances, cycles included, with the
+    /// runtime's mark-sweep collector
+    pub fn with_tracing_gc(mut self, tracing_gc: bool) -> Self {
+        self.tracing_gc = tracing_gc;
+        self
+    }
+
+    /// Allocate runtime memory from a bump arena that is only released at
+    /// exit, instead of malloc
+    pub fn with_arena_alloc(mut self, arena_alloc: bool) -> Self {
+        self.arena_alloc = arena_alloc;
+        self
+    }
+
+    /// Mark small functions (accessors, one-line methods) `alwaysinline`,
+    /// so LLVM inlines every call to them
+    pub fn with_inline_small_functions(mut self, inline_small_functions: bool) -> Self {
+        self.inline_small_functions = inline_small_functions;
+        self
+    }
+
+    /// Compile try blocks with setjmp, so that a raise longjmps to its
+    /// handler instead of every statement of the block polling for it
+    pub fn with_setjmp_exceptions(mut self, setjmp_exceptions: bool) -> Self {
+        self.setjmp_exceptions = setjmp_exceptions;
+        self
+    }
+
+    /// Push a record of each function being run, updated with the line of
+    /// the statement it runs, for the traceback of an uncaught exception
+    pub fn with_traceback(mut self, traceback: bool) -> Self {
+        self.traceback = traceback;
+        self
+    }
+
+    /// Raise RecursionError in a call that would run more than `limit`
+    /// functions nested, instead of overflowing the stack
+    pub fn with_recursion_limit(mut self, limit: Option<u32>) -> Self {
+        self.recursion_limit = limit;
+        self
+    }
+
+    /// Run LLVM's pass pipeline for a level over the generated module
+    /// (none at `OptLevel::O0`, the default)
+    pub fn with_opt_level(mut self, opt_level: OptLevel) -> Self {
+        self.opt_level = opt_level;
+        self
+    }
+
+    /// Build the module into a shared library: a constructor initializes
+    /// the modules when it is loaded, and there is no main
+    pub fn with_shared_library(mut self, shared_library: bool) -> Self {
+        self.shared_library = shared_library;
+        self
+    }
+
+    /// Build the module into a static library: the program linking it calls
+    /// an initializer for its modules, and only the exported functions and
+    /// the initializer keep their symbols
+    pub fn with_static_library(mut self, static_library: bool) -> Self {
+        self.static_library = static_library;
+        self
+    }
+
+    /// Describe the functions and their variables in DWARF, so that
+    /// debuggers and profilers show the Python names and lines
+    pub fn with_debug_info(mut self, debug_info: bool) -> Self {
+        self.debug_info = debug_info;
+        self
+    }
+
+    /// Declare the module globals of these symbols instead of defining them,
+    /// for a module loaded into the JIT next to the one defining them
+    pub fn with_external_globals(mut self, symbols: HashSet<String>) -> Self {
+        self.external_globals = symbols;
+        self
+    }
+
+    /// Name the function the program starts from `name` instead of main
+    pub fn with_main_name(mut self, name: &str) -> Self {
+        self.main_name = name.to_string();
+        self
     }
 
     /// Generate code from a TIR program
     ///
-    /// Since TIR has all types and symbols resolved, this operation is infallible.
-    pub fn codegen_tir(self, program: &TirProgram) -> LLVMModule<'ctx> {
+    /// Since TIR has all types and symbols resolved, generating code cannot
+    /// fail; only exporting a function whose name the runtime uses (or
+    /// declaring an extern function the runtime declares differently) can,
+    /// and the LLVM passes, on a triple LLVM has no backend for.
+    pub fn codegen_tir(self, program: &TirProgram) -> Result<LLVMModule<'ctx>> {
         let mut codegen = CodegenContext::new(self.context, "main", self.target);
+        codegen.checked_int = self.checked_int;
+        codegen.runtime_checks = self.runtime_checks;
+        codegen.refcount = self.refcount;
+        codegen.leak_check = self.leak_check;
+        codegen.tracing_gc = self.tracing_gc;
+        codegen.arena_alloc = self.arena_alloc;
+        codegen.inline_small_functions = self.inline_small_functions;
+        codegen.setjmp_exceptions = self.setjmp_exceptions;
+        codegen.traceback = self.traceback;
+        codegen.recursion_limit = self.recursion_limit;
+        codegen.shared_library = self.shared_library;
+        codegen.static_library = self.static_library;
+        codegen.external_globals = self.external_globals;
+        codegen.main_name = self.main_name;
+        if let Some(triple) = &self.target_triple {
+            codegen.set_target_triple(triple);
+        }
+        if self.debug_info {
+            codegen.begin_debug_info(program, self.opt_level != OptLevel::O0);
+        }
 
         // Declare runtime functions
         codegen.declare_runtime_functions();
 
+        // Declare the C functions of @extern declarations
+        codegen.declare_tir_externs(program)?;
+
         // Generate all code from TIR
         codegen.codegen_tir_program(program);
 
-        codegen.get_module().clone()
+        // C symbols for the @export functions
+        codegen.generate_tir_exports(program)?;
+
+        if self.static_library {
+            // The program the library is linked into defines the symbols
+            // the runtime references, and its own ones must not clash
+            codegen.internalize_static_library(program);
+        } else {
+            // Embedded data files (read-only table consulted by the runtime)
+            codegen.emit_embedded_files(&self.embedded_files);
+
+            // Whether the runtime's index checks are on
+            codegen.emit_runtime_checks_flag();
+
+            // Which allocator the runtime uses
+            codegen.emit_arena_alloc_flag();
+
+            // Whether the runtime counts references to strings and lists
+            codegen.emit_refcount_flag();
+
+            // Whether a raise returns or jumps to its handler
+            codegen.emit_setjmp_exceptions_flag();
+        }
+
+        codegen.finish_debug_info();
+
+        codegen.run_llvm_passes(self.opt_level)?;
+
+        Ok(codegen.get_module().clone())
     }
 }
 
@@ -46,30 +266,38 @@ impl<'ctx> CodegenContext<'ctx> {
     /// Generate code from a TIR program using a 6-pass algorithm
     ///
     /// Pass 1: Declare all class struct types
-    /// Pass 2: Declare all function signatures
+    /// Pass 2: Declare all function signatures, then the class vtables
     /// Pass 3: Declare all global variables
     /// Pass 4: Generate all function bodies
     /// Pass 5: Generate module initialization functions
-    /// Pass 6: Generate main entry point
+    /// Pass 6: Generate main entry point (or a library's initializer)
     pub fn codegen_tir_program(&mut self, program: &TirProgram) {
         // Pass 1: Declare all class struct types
         for class in &program.classes {
             self.declare_tir_class(class, program);
         }
 
+        // Release functions for reference-counted classes (pycc --refcount)
+        self.generate_rc_release_functions(program);
+
         // Pass 2: Declare all functions
         for func in &program.functions {
             self.declare_tir_function(func, program);
         }
+        for class in &program.classes {
+            self.declare_tir_vtable(class, program);
+        }
 
         // Pass 3: Declare all global variables (before function bodies)
         for module in &program.modules {
             self.declare_tir_module_globals(module, program);
         }
 
-        // Pass 4: Generate all function bodies
+        // Pass 4: Generate all function bodies, keeping the instances and
+        // lists that escape analysis finds do not escape on the stack
+        let stack_locals = find_stack_locals(program);
         for func in &program.functions {
-            self.codegen_tir_function(func, program);
+            self.codegen_tir_function(func, program, stack_locals.function(func.id));
         }
 
         // Pass 5: Generate module init functions
@@ -78,6 +306,12 @@ impl<'ctx> CodegenContext<'ctx> {
         }
 
         // Pass 6: Generate main
-        self.generate_tir_main(program);
+        if self.static_library {
+            self.generate_tir_static_library_init(program);
+        } else if self.shared_library {
+            self.generate_tir_library_init(program);
+        } else {
+            self.generate_tir_main(program);
+        }
     }
 }
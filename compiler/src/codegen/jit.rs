@@ -0,0 +1,184 @@
+//! In-process execution (`pyrun --jit`, `pyrepl`)
+//!
+//! Instead of writing an object file and linking it into an executable, the
+//! runtime's bitcode (the same file the linker takes, see runtime/build.rs)
+//! is linked into the generated module, which LLVM's MCJIT compiles to
+//! memory and runs in this process. Everything but `main` is made internal
+//! first and what the program does not reach is removed, so only the C
+//! library functions the program uses are left to resolve. They resolve to
+//! those of this process, and the target's ICU shared libraries are loaded,
+//! when it has them, for the runtime's Unicode functions.
+//!
+//! A [`JitSession`] loads one module after the other into the same engine,
+//! keeping the symbols each exports (pyrepl's module globals) visible to
+//! those loaded after it, which declare them instead of defining them.
+
+use std::collections::HashSet;
+use std::ffi::{c_int, c_void};
+use std::path::{Path, PathBuf};
+
+use inkwell::execution_engine::ExecutionEngine;
+use inkwell::module::{Linkage, Module};
+use inkwell::passes::PassBuilderOptions;
+use inkwell::support::load_library_permanently;
+use inkwell::targets::{InitializationConfig, Target};
+use inkwell::values::FunctionValue;
+
+use crate::driver::OptLevel;
+use crate::error::{CompilerError, Result};
+
+use super::passes::{codegen_level, target_machine};
+
+/// ICU's shared libraries, in the order they depend on each other
+const ICU_LIBRARIES: [&str; 3] = ["libicudata.so", "libicuuc.so", "libicui18n.so"];
+
+extern "C" {
+    fn fflush(stream: *mut c_void) -> c_int;
+}
+
+/// Link the runtime at `runtime` into the program's module and run its
+/// `main` with `args` (`args[0]` being the program's name), returning the
+/// status `main` returns
+///
+/// A program that calls `sys.exit` (or raises an uncaught exception) exits
+/// this process instead of returning.
+pub fn run_jit(
+    module: &Module<'_>,
+    runtime: &Path,
+    icu_lib: &Path,
+    level: OptLevel,
+    args: &[String],
+) -> Result<i32> {
+    initialize_native()?;
+    link_runtime(module, runtime, level, |name| name == "main")?;
+    load_icu_libraries(icu_lib);
+
+    let engine = module
+        .create_jit_execution_engine(codegen_level(level))
+        .map_err(|e| CompilerError::LLVMError(format!("Cannot start the JIT: {e}")))?;
+    let main = entry_function(module, "main")?;
+    Ok(run_as_main(&engine, main, args))
+}
+
+/// Modules loaded into one JIT one after the other, each able to use the
+/// symbols the ones before it export
+pub struct JitSession<'ctx> {
+    /// Created with the first module loaded
+    engine: Option<ExecutionEngine<'ctx>>,
+    runtime: PathBuf,
+    level: OptLevel,
+}
+
+impl<'ctx> JitSession<'ctx> {
+    /// A session linking the runtime at `runtime` into each module it loads
+    pub fn new(runtime: &Path, icu_lib: &Path, level: OptLevel) -> Result<Self> {
+        initialize_native()?;
+        load_icu_libraries(icu_lib);
+        Ok(JitSession {
+            engine: None,
+            runtime: runtime.to_path_buf(),
+            level,
+        })
+    }
+
+    /// Link the runtime into `module`, load it, and run its function `entry`
+    /// as main with `args`, returning the status it returns
+    ///
+    /// The symbols in `exported` stay visible to the modules loaded later;
+    /// all the others the module defines become internal to it.
+    pub fn run(
+        &mut self,
+        module: Module<'ctx>,
+        entry: &str,
+        exported: &HashSet<String>,
+        args: &[String],
+    ) -> Result<i32> {
+        link_runtime(&module, &self.runtime, self.level, |name| {
+            name == entry || exported.contains(name)
+        })?;
+        let function = entry_function(&module, entry)?;
+        match &self.engine {
+            Some(engine) => engine.add_module(&module).map_err(|()| {
+                CompilerError::LLVMError("Cannot load the module into the JIT".to_string())
+            })?,
+            None => {
+                let engine = module
+                    .create_jit_execution_engine(codegen_level(self.level))
+                    .map_err(|e| CompilerError::LLVMError(format!("Cannot start the JIT: {e}")))?;
+                self.engine = Some(engine);
+            }
+        }
+        let engine = self.engine.as_ref().unwrap();
+        Ok(run_as_main(engine, function, args))
+    }
+}
+
+fn initialize_native() -> Result<()> {
+    Target::initialize_native(&InitializationConfig::default())
+        .map_err(|e| CompilerError::LLVMError(format!("Cannot initialize the JIT: {e}")))
+}
+
+/// Link the runtime into `module`, make every symbol it defines internal
+/// but those `keep` names, and remove what they do not reach
+fn link_runtime(
+    module: &Module<'_>,
+    runtime: &Path,
+    level: OptLevel,
+    keep: impl Fn(&str) -> bool,
+) -> Result<()> {
+    let runtime_module = Module::parse_bitcode_from_path(runtime, module.get_context())
+        .map_err(|e| CompilerError::LLVMError(format!("Cannot read {}: {e}", runtime.display())))?;
+    module
+        .link_in_module(runtime_module)
+        .map_err(|e| CompilerError::LLVMError(format!("Cannot link the runtime: {e}")))?;
+
+    for function in module.get_functions() {
+        let name = function.get_name().to_string_lossy();
+        if function.count_basic_blocks() > 0 && !keep(&name) {
+            function.set_linkage(Linkage::Internal);
+        }
+    }
+    for global in module.get_globals() {
+        let name = global.get_name().to_string_lossy();
+        if global.get_initializer().is_some() && !name.starts_with("llvm.") && !keep(&name) {
+            global.set_linkage(Linkage::Internal);
+        }
+    }
+    let machine = target_machine(module, "", level)?;
+    module
+        .run_passes("globaldce", &machine, PassBuilderOptions::create())
+        .map_err(|e| CompilerError::LLVMError(format!("globaldce pass failed: {e}")))
+}
+
+fn load_icu_libraries(icu_lib: &Path) {
+    for library in ICU_LIBRARIES {
+        let path = icu_lib.join(library);
+        if path.is_file() {
+            // Returns whether loading failed; the symbols are then missing
+            // when the engine resolves them, which it reports
+            let _ = load_library_permanently(&path);
+        }
+    }
+}
+
+fn entry_function<'ctx>(module: &Module<'ctx>, name: &str) -> Result<FunctionValue<'ctx>> {
+    module
+        .get_function(name)
+        .ok_or_else(|| CompilerError::LLVMError(format!("The program has no {name}")))
+}
+
+/// Run `function`, an `int main(int, char**)`, with `args`, then flush what
+/// it printed: the process goes on, so exiting does not flush it
+fn run_as_main<'ctx>(
+    engine: &ExecutionEngine<'ctx>,
+    function: FunctionValue<'ctx>,
+    args: &[String],
+) -> i32 {
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    // The function and the runtime it calls into are linked into a module
+    // the engine has loaded
+    let status = unsafe { engine.run_function_as_main(function, &args) };
+    // A null stream flushes every stream
+    unsafe { fflush(std::ptr::null_mut()) };
+    status
+}
@@ -1,8 +1,16 @@
 mod context;
+mod embed;
+mod jit;
+mod object;
+mod passes;
 mod runtime;
 mod tir;
 
 pub mod generator;
 
 pub use context::CodegenContext;
+pub use embed::EmbeddedFile;
 pub use generator::Codegen;
+pub use jit::{run_jit, JitSession};
+pub use object::{undefined_symbols, write_assembly_file, write_object_file};
+pub(crate) use tir::declarations::global_symbol;
@@ -0,0 +1,73 @@
+//! Native object files
+//!
+//! The generated module is compiled to a relocatable object for its target
+//! by LLVM's target machine, which the driver then links with the runtime
+//! (or leaves for the user's own linker with `pycc --emit-obj`). The same
+//! target machine writes the assembly of `pycc --emit=asm`. A program
+//! compiled to bitcode instead (`Compiler::compile_to_bitcode`) comes with
+//! the symbols it leaves for the linker, so that its caller can link it.
+
+use std::path::Path;
+
+use inkwell::module::Module;
+use inkwell::targets::FileType;
+use inkwell::values::BasicValue;
+
+use crate::driver::OptLevel;
+use crate::error::{CompilerError, Result};
+
+use super::passes::target_machine;
+
+/// Compile a module to an object file at `path`, for the target features
+/// `features` (`+m,+a,...`, or empty for the target's defaults)
+pub fn write_object_file(
+    module: &Module<'_>,
+    features: &str,
+    level: OptLevel,
+    path: &Path,
+) -> Result<()> {
+    write_machine_code(module, features, level, FileType::Object, path)
+}
+
+/// Compile a module to a text assembly file at `path`
+pub fn write_assembly_file(
+    module: &Module<'_>,
+    features: &str,
+    level: OptLevel,
+    path: &Path,
+) -> Result<()> {
+    write_machine_code(module, features, level, FileType::Assembly, path)
+}
+
+fn write_machine_code(
+    module: &Module<'_>,
+    features: &str,
+    level: OptLevel,
+    file_type: FileType,
+    path: &Path,
+) -> Result<()> {
+    let machine = target_machine(module, features, level)?;
+    machine
+        .write_to_file(module, file_type, path)
+        .map_err(|e| CompilerError::LLVMError(format!("Cannot write {}: {e}", path.display())))
+}
+
+/// The symbols a module uses without defining them, sorted; intrinsics are
+/// LLVM's own and not among them
+pub fn undefined_symbols(module: &Module<'_>) -> Vec<String> {
+    let functions = module
+        .get_functions()
+        .filter(|function| function.count_basic_blocks() == 0)
+        .map(|function| function.as_global_value());
+    let globals = module
+        .get_globals()
+        .filter(|global| global.get_initializer().is_none());
+    let mut symbols: Vec<String> = functions
+        .chain(globals)
+        .filter(|global| global.as_pointer_value().get_first_use().is_some())
+        .map(|global| global.get_name().to_string_lossy().into_owned())
+        .filter(|name| !name.starts_with("llvm."))
+        .collect();
+    symbols.sort();
+    symbols
+}
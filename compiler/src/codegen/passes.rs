@@ -0,0 +1,79 @@
+//! LLVM optimization pipeline
+//!
+//! From `--opt-level=1` up, the generated module goes through LLVM's
+//! default pass pipeline for the level (`default<O2>` and so on, run by the
+//! new pass manager) before it is handed to the linker. Level 0 runs no
+//! passes. The passes are tuned for the module's target, whose data layout
+//! the module takes on first. codegen/object.rs compiles the module with
+//! the same target machine.
+
+use inkwell::module::Module;
+use inkwell::passes::PassBuilderOptions;
+use inkwell::targets::{CodeModel, RelocMode, Target, TargetMachine};
+use inkwell::OptimizationLevel;
+
+use crate::driver::OptLevel;
+use crate::error::{CompilerError, Result};
+
+use super::context::CodegenContext;
+
+/// The pass pipeline of a level, if it runs one
+fn pipeline(level: OptLevel) -> Option<&'static str> {
+    match level {
+        OptLevel::O0 => None,
+        OptLevel::O1 => Some("default<O1>"),
+        OptLevel::O2 => Some("default<O2>"),
+        OptLevel::O3 => Some("default<O3>"),
+        OptLevel::Os => Some("default<Os>"),
+    }
+}
+
+/// How hard the target machine works at a level
+pub(crate) fn codegen_level(level: OptLevel) -> OptimizationLevel {
+    match level {
+        OptLevel::O0 => OptimizationLevel::None,
+        OptLevel::O1 => OptimizationLevel::Less,
+        OptLevel::O2 | OptLevel::Os => OptimizationLevel::Default,
+        OptLevel::O3 => OptimizationLevel::Aggressive,
+    }
+}
+
+impl<'ctx> CodegenContext<'ctx> {
+    /// Run LLVM's pass pipeline for `level` over the module
+    pub(crate) fn run_llvm_passes(&self, level: OptLevel) -> Result<()> {
+        let Some(pipeline) = pipeline(level) else {
+            return Ok(());
+        };
+        let machine = target_machine(&self.module, self.target_features, level)?;
+        self.module
+            .run_passes(pipeline, &machine, PassBuilderOptions::create())
+            .map_err(|e| CompilerError::LLVMError(format!("{pipeline} passes failed: {e}")))
+    }
+}
+
+/// A target machine for the module's triple, whose data layout the module
+/// takes on
+pub(crate) fn target_machine(
+    module: &Module<'_>,
+    features: &str,
+    level: OptLevel,
+) -> Result<TargetMachine> {
+    let triple = module.get_triple();
+    let name = triple.as_str().to_string_lossy().into_owned();
+    let target = Target::from_triple(&triple)
+        .map_err(|e| CompilerError::LLVMError(format!("No LLVM backend for {name}: {e}")))?;
+    let machine = target
+        .create_target_machine(
+            &triple,
+            "generic",
+            features,
+            codegen_level(level),
+            RelocMode::PIC,
+            CodeModel::Default,
+        )
+        .ok_or_else(|| {
+            CompilerError::LLVMError(format!("Cannot create a target machine for {name}"))
+        })?;
+    module.set_data_layout(&machine.get_target_data().get_data_layout());
+    Ok(machine)
+}
@@ -1,5 +1,6 @@
 //! Runtime function declarations for code generation
 
+use inkwell::types::{BasicType, StructType};
 use inkwell::AddressSpace;
 
 use super::context::CodegenContext;
@@ -16,21 +17,17 @@ impl<'ctx> CodegenContext<'ctx> {
         }
         let i64_type = self.context.i64_type();
         let void_type = self.context.void_type();
+        let i8_type = self.context.i8_type();
         let i8_ptr_type = self.context.ptr_type(AddressSpace::default());
+        let f64_type = self.context.f64_type();
 
-        // List type: { i64*, i64, i64 }
-        let _list_type = self.context.struct_type(
-            &[
-                i8_ptr_type.into(), // data pointer (cast from i64*)
-                i64_type.into(),    // len
-                i64_type.into(),    // cap
-            ],
-            false,
-        );
         let list_ptr_type = self.context.ptr_type(AddressSpace::default());
 
-        // list_new() -> List*
-        declare_fn!(list_ptr_type, "__pyc___builtin___list___init__");
+        // list_new(i64 kind) -> List*
+        declare_fn!(list_ptr_type, "__pyc___builtin___list___init__", i64_type);
+
+        // list_init_in(List*, i64 kind) -> void: (re)initialize a list in a stack slot
+        declare_fn!(void_type, "__pyc_list_init_in", list_ptr_type, i64_type);
 
         // list_append(List*, i64) -> void
         declare_fn!(
@@ -40,6 +37,66 @@ impl<'ctx> CodegenContext<'ctx> {
             i64_type
         );
 
+        // list_pop(List*, i64 index) -> i64
+        declare_fn!(
+            i64_type,
+            "__pyc___builtin___list_pop",
+            list_ptr_type,
+            i64_type
+        );
+
+        // list_insert(List*, i64 index, i64) -> void
+        declare_fn!(
+            void_type,
+            "__pyc___builtin___list_insert",
+            list_ptr_type,
+            i64_type,
+            i64_type
+        );
+
+        // list_remove(List*, i64, i64 kind) -> void
+        declare_fn!(
+            void_type,
+            "__pyc___builtin___list_remove",
+            list_ptr_type,
+            i64_type,
+            i64_type
+        );
+
+        // list_index(List*, i64, i64 kind, i64 start, i64 stop) -> i64
+        declare_fn!(
+            i64_type,
+            "__pyc___builtin___list_index",
+            list_ptr_type,
+            i64_type,
+            i64_type,
+            i64_type,
+            i64_type
+        );
+
+        // list_count(List*, i64, i64 kind) -> i64
+        declare_fn!(
+            i64_type,
+            "__pyc___builtin___list_count",
+            list_ptr_type,
+            i64_type,
+            i64_type
+        );
+
+        // list_extend(List*, List* other) -> void
+        declare_fn!(
+            void_type,
+            "__pyc___builtin___list_extend",
+            list_ptr_type,
+            list_ptr_type
+        );
+
+        // list_reverse(List*) -> void
+        declare_fn!(void_type, "__pyc___builtin___list_reverse", list_ptr_type);
+
+        // list_clear(List*) -> void
+        declare_fn!(void_type, "__pyc___builtin___list_clear", list_ptr_type);
+
         // list_getitem(List*, i64) -> i64
         declare_fn!(
             i64_type,
@@ -57,14 +114,174 @@ impl<'ctx> CodegenContext<'ctx> {
             i64_type
         );
 
+        // list_delitem(List*, i64) -> void
+        declare_fn!(
+            void_type,
+            "__pyc___builtin___list___delitem__",
+            list_ptr_type,
+            i64_type
+        );
+
         // list_len(List*) -> i64
         declare_fn!(i64_type, "__pyc___builtin___list___len__", list_ptr_type);
 
-        let i8_type = self.context.i8_type();
+        // list_slice(List*, i64 start, i64 stop, i64 step) -> List*
+        declare_fn!(
+            list_ptr_type,
+            "__pyc___builtin___list___slice__",
+            list_ptr_type,
+            i64_type,
+            i64_type,
+            i64_type
+        );
+
+        // list_unpack(List*, i64 before, i64 after) -> List*
+        declare_fn!(
+            list_ptr_type,
+            "__pyc___builtin___list___unpack__",
+            list_ptr_type,
+            i64_type,
+            i64_type
+        );
+
+        // list_concat(List*, List*) -> List* (list + list)
+        declare_fn!(
+            list_ptr_type,
+            "__pyc___builtin___list___add__",
+            list_ptr_type,
+            list_ptr_type
+        );
+
+        // list_iadd(List*, List*) -> List* (list += list extends in place)
+        declare_fn!(
+            list_ptr_type,
+            "__pyc___builtin___list___iadd__",
+            list_ptr_type,
+            list_ptr_type
+        );
+
+        // list_eq(List*, List*, i64 kind) -> i8
+        declare_fn!(
+            i8_type,
+            "__pyc___builtin___list___eq__",
+            list_ptr_type,
+            list_ptr_type,
+            i64_type
+        );
+
+        // list_repeat(List*, i64 n) -> List* (list * n and n * list)
+        declare_fn!(
+            list_ptr_type,
+            "__pyc___builtin___list___mul__",
+            list_ptr_type,
+            i64_type
+        );
+        declare_fn!(
+            list_ptr_type,
+            "__pyc___builtin___list___rmul__",
+            list_ptr_type,
+            i64_type
+        );
+
+        // list_argsort(List* keys, i64 kind, i8 reverse) -> List* (stable order of keys)
+        declare_fn!(
+            list_ptr_type,
+            "__pyc___builtin___list___argsort__",
+            list_ptr_type,
+            i64_type,
+            i8_type
+        );
+
+        // list_permute(List*, List* order) -> void
+        declare_fn!(
+            void_type,
+            "__pyc___builtin___list___permute__",
+            list_ptr_type,
+            list_ptr_type
+        );
+
+        // list[float] and list[bool] take and return their items typed
+        // (f64 and i8) instead of as i64 slots; see TirType::list_item_suffix
+        for (suffix, item_type) in [
+            ("float", f64_type.as_basic_type_enum()),
+            ("bool", i8_type.as_basic_type_enum()),
+        ] {
+            let name = |method: &str| format!("__pyc___builtin___list_{suffix}_{method}");
+            // append(List*, T) -> void
+            declare_fn!(void_type, &name("append"), list_ptr_type, item_type);
+            // pop(List*, i64 index) -> T
+            declare_fn!(item_type, &name("pop"), list_ptr_type, i64_type);
+            // insert(List*, i64 index, T) -> void
+            declare_fn!(
+                void_type,
+                &name("insert"),
+                list_ptr_type,
+                i64_type,
+                item_type
+            );
+            // remove(List*, T, i64 kind) -> void
+            declare_fn!(
+                void_type,
+                &name("remove"),
+                list_ptr_type,
+                item_type,
+                i64_type
+            );
+            // index(List*, T, i64 kind, i64 start, i64 stop) -> i64
+            declare_fn!(
+                i64_type,
+                &name("index"),
+                list_ptr_type,
+                item_type,
+                i64_type,
+                i64_type,
+                i64_type
+            );
+            // count(List*, T, i64 kind) -> i64
+            declare_fn!(i64_type, &name("count"), list_ptr_type, item_type, i64_type);
+            // __getitem__(List*, i64) -> T
+            declare_fn!(item_type, &name("__getitem__"), list_ptr_type, i64_type);
+            // __setitem__(List*, i64, T) -> void
+            declare_fn!(
+                void_type,
+                &name("__setitem__"),
+                list_ptr_type,
+                i64_type,
+                item_type
+            );
+        }
 
         // class_new(i64) -> void*
         declare_fn!(i8_ptr_type, "class_new", i64_type);
 
+        // Reference counting (pycc --refcount); see refcount.h
+        // __pyc_rc_new(i64 size, void (*release)(void*)) -> void*
+        declare_fn!(i8_ptr_type, "__pyc_rc_new", i64_type, i8_ptr_type);
+        // __pyc_rc_incref(void*) -> void
+        declare_fn!(void_type, "__pyc_rc_incref", i8_ptr_type);
+        // __pyc_rc_decref(void*) -> void
+        declare_fn!(void_type, "__pyc_rc_decref", i8_ptr_type);
+        // __pyc_rc_unown(void*) -> void
+        declare_fn!(void_type, "__pyc_rc_unown", i8_ptr_type);
+        // __pyc_rc_release_unowned(void*) -> void
+        declare_fn!(void_type, "__pyc_rc_release_unowned", i8_ptr_type);
+        // __pyc_rc_leak_check() -> void
+        declare_fn!(void_type, "__pyc_rc_leak_check");
+
+        // Tracing collector (pycc --gc=tracing); see gc.h
+        // __pyc_gc_init() -> void
+        declare_fn!(void_type, "__pyc_gc_init");
+        // __pyc_gc_add_root(void** slot) -> void
+        declare_fn!(void_type, "__pyc_gc_add_root", i8_ptr_type);
+        // __pyc_gc_push_frame(void*** roots, i64 count) -> void
+        declare_fn!(void_type, "__pyc_gc_push_frame", i8_ptr_type, i64_type);
+        // __pyc_gc_pop_frame(void* result) -> void
+        declare_fn!(void_type, "__pyc_gc_pop_frame", i8_ptr_type);
+        // __pyc_gc_end_statement() -> void
+        declare_fn!(void_type, "__pyc_gc_end_statement");
+        // __pyc_gc_collect_at_exit() -> void
+        declare_fn!(void_type, "__pyc_gc_collect_at_exit");
+
         // ByteArray type: { u8*, i64, i64 } (same layout as List but with u8 elements)
         let bytearray_ptr_type = self.context.ptr_type(AddressSpace::default());
 
@@ -103,6 +320,16 @@ impl<'ctx> CodegenContext<'ctx> {
             bytearray_ptr_type
         );
 
+        // bytearray_slice(ByteArray*, i64 start, i64 stop, i64 step) -> ByteArray*
+        declare_fn!(
+            bytearray_ptr_type,
+            "__pyc___builtin___bytearray___slice__",
+            bytearray_ptr_type,
+            i64_type,
+            i64_type,
+            i64_type
+        );
+
         // Bytes* type (same layout as other pointer types)
         let bytes_ptr_type = self.context.ptr_type(AddressSpace::default());
 
@@ -117,6 +344,16 @@ impl<'ctx> CodegenContext<'ctx> {
             i64_type
         );
 
+        // bytes_slice(Bytes*, i64 start, i64 stop, i64 step) -> Bytes*
+        declare_fn!(
+            bytes_ptr_type,
+            "__pyc___builtin___bytes___slice__",
+            bytes_ptr_type,
+            i64_type,
+            i64_type,
+            i64_type
+        );
+
         // String* type (same layout as other pointer types)
         let string_ptr_type = self.context.ptr_type(AddressSpace::default());
 
@@ -145,6 +382,16 @@ impl<'ctx> CodegenContext<'ctx> {
             i64_type
         );
 
+        // str_slice(String*, i64 start, i64 stop, i64 step) -> String* (codepoint indices)
+        declare_fn!(
+            string_ptr_type,
+            "__pyc___builtin___str___slice__",
+            string_ptr_type,
+            i64_type,
+            i64_type,
+            i64_type
+        );
+
         // str_add(String*, String*) -> String* (string concatenation)
         declare_fn!(
             string_ptr_type,
@@ -153,6 +400,20 @@ impl<'ctx> CodegenContext<'ctx> {
             string_ptr_type
         );
 
+        // str_repeat(String*, i64 n) -> String* (s * n and n * s)
+        declare_fn!(
+            string_ptr_type,
+            "__pyc___builtin___str___mul__",
+            string_ptr_type,
+            i64_type
+        );
+        declare_fn!(
+            string_ptr_type,
+            "__pyc___builtin___str___rmul__",
+            string_ptr_type,
+            i64_type
+        );
+
         // String comparison operators
         declare_fn!(
             i8_type,
@@ -226,6 +487,13 @@ impl<'ctx> CodegenContext<'ctx> {
             string_ptr_type
         );
 
+        declare_fn!(
+            i8_type,
+            "__pyc___builtin___str___contains__",
+            string_ptr_type,
+            string_ptr_type
+        );
+
         declare_fn!(
             i8_type,
             "__pyc___builtin___str_startswith",
@@ -263,9 +531,55 @@ impl<'ctx> CodegenContext<'ctx> {
         declare_fn!(void_type, "__pyc___builtin___bool___print__", i8_type);
 
         // float.__print__(f64) -> void (prints float without newline)
-        let f64_type = self.context.f64_type();
         declare_fn!(void_type, "__pyc___builtin___float___print__", f64_type);
 
+        // int.__str__(i64) -> String* (f-string replacement fields)
+        declare_fn!(string_ptr_type, "__pyc___builtin___int___str__", i64_type);
+
+        // bool.__str__(i8) -> String*
+        declare_fn!(string_ptr_type, "__pyc___builtin___bool___str__", i8_type);
+
+        // float.__str__(f64) -> String*
+        declare_fn!(string_ptr_type, "__pyc___builtin___float___str__", f64_type);
+
+        // int(str, base) -> i64 (raises ValueError on a bad literal)
+        declare_fn!(
+            i64_type,
+            "__pyc___builtin___int_from_str",
+            string_ptr_type,
+            i64_type
+        );
+
+        // int(float) -> i64
+        declare_fn!(i64_type, "__pyc___builtin___int_from_float", f64_type);
+
+        // int(bool) -> i64
+        declare_fn!(i64_type, "__pyc___builtin___int_from_bool", i8_type);
+
+        // float(str) -> f64 (raises ValueError on a bad literal)
+        declare_fn!(
+            f64_type,
+            "__pyc___builtin___float_from_str",
+            string_ptr_type
+        );
+
+        // float(int) -> f64
+        declare_fn!(f64_type, "__pyc___builtin___float_from_int", i64_type);
+
+        // round(float, ndigits) -> f64
+        declare_fn!(
+            f64_type,
+            "__pyc___builtin___float_round",
+            f64_type,
+            i64_type
+        );
+
+        // round(int, ndigits) -> i64
+        declare_fn!(i64_type, "__pyc___builtin___int_round", i64_type, i64_type);
+
+        // float.is_integer(f64) -> i8
+        declare_fn!(i8_type, "__pyc___builtin___float_is_integer", f64_type);
+
         // bytes.__str__(Bytes*) -> String*
         declare_fn!(
             string_ptr_type,
@@ -280,53 +594,199 @@ impl<'ctx> CodegenContext<'ctx> {
             bytes_ptr_type
         );
 
-        // bytearray.__str__(ByteArray*) -> String*
+        // str.encode(String*) -> Bytes* (UTF-8)
         declare_fn!(
-            string_ptr_type,
-            "__pyc___builtin___bytearray___str__",
-            bytearray_ptr_type
+            bytes_ptr_type,
+            "__pyc___builtin___str_encode",
+            string_ptr_type
         );
 
-        // bytearray.__repr__(ByteArray*) -> String*
+        // bytes.decode(Bytes*) -> String* (raises UnicodeDecodeError)
         declare_fn!(
             string_ptr_type,
-            "__pyc___builtin___bytearray___repr__",
-            bytearray_ptr_type
+            "__pyc___builtin___bytes_decode",
+            bytes_ptr_type
         );
 
-        // list.__str__(List*) -> String*
+        // bytes.__add__(Bytes*, Bytes*) -> Bytes*
         declare_fn!(
-            string_ptr_type,
-            "__pyc___builtin___list___str__",
-            list_ptr_type
+            bytes_ptr_type,
+            "__pyc___builtin___bytes___add__",
+            bytes_ptr_type,
+            bytes_ptr_type
         );
 
-        // list.__repr__(List*) -> String*
+        // bytes.find / bytes.count(Bytes*, Bytes* sub) -> i64
         declare_fn!(
-            string_ptr_type,
-            "__pyc___builtin___list___repr__",
-            list_ptr_type
+            i64_type,
+            "__pyc___builtin___bytes_find",
+            bytes_ptr_type,
+            bytes_ptr_type
+        );
+        declare_fn!(
+            i64_type,
+            "__pyc___builtin___bytes_count",
+            bytes_ptr_type,
+            bytes_ptr_type
         );
 
-        // Low-level I/O functions (no newlines)
-        // write_str_impl(const char*) -> void
-        declare_fn!(void_type, "write_str_impl", i8_ptr_type);
+        // bytes.__contains__ / startswith / endswith(Bytes*, Bytes*) -> i8
+        declare_fn!(
+            i8_type,
+            "__pyc___builtin___bytes___contains__",
+            bytes_ptr_type,
+            bytes_ptr_type
+        );
+        declare_fn!(
+            i8_type,
+            "__pyc___builtin___bytes_startswith",
+            bytes_ptr_type,
+            bytes_ptr_type
+        );
+        declare_fn!(
+            i8_type,
+            "__pyc___builtin___bytes_endswith",
+            bytes_ptr_type,
+            bytes_ptr_type
+        );
 
-        // write_string_impl(String*) -> void
-        declare_fn!(void_type, "write_string_impl", string_ptr_type);
+        // bytes.strip(Bytes*) -> Bytes*
+        declare_fn!(
+            bytes_ptr_type,
+            "__pyc___builtin___bytes_strip",
+            bytes_ptr_type
+        );
 
-        // write_char_impl(char) -> void
-        declare_fn!(void_type, "write_char_impl", i8_type);
+        // bytes.replace(Bytes*, Bytes* old, Bytes* new) -> Bytes*
+        declare_fn!(
+            bytes_ptr_type,
+            "__pyc___builtin___bytes_replace",
+            bytes_ptr_type,
+            bytes_ptr_type,
+            bytes_ptr_type
+        );
 
-        // write_newline_impl() -> void
-        declare_fn!(void_type, "write_newline_impl");
+        // bytes.hex(Bytes*) -> String*
+        declare_fn!(
+            string_ptr_type,
+            "__pyc___builtin___bytes_hex",
+            bytes_ptr_type
+        );
 
-        // write_space_impl() -> void
-        declare_fn!(void_type, "write_space_impl");
+        // bytes.split(Bytes*, Bytes* sep) -> List* (raises ValueError for an empty sep)
+        declare_fn!(
+            list_ptr_type,
+            "__pyc___builtin___bytes_split",
+            bytes_ptr_type,
+            bytes_ptr_type
+        );
 
-        // int64_to_str_impl(i64, char*) -> char*
+        // bytes.split() with no separator (Bytes*) -> List*
+        declare_fn!(
+            list_ptr_type,
+            "__pyc___builtin___bytes___split_whitespace__",
+            bytes_ptr_type
+        );
+
+        // bytes.join(Bytes* sep, List* parts) -> Bytes*
+        declare_fn!(
+            bytes_ptr_type,
+            "__pyc___builtin___bytes_join",
+            bytes_ptr_type,
+            list_ptr_type
+        );
+
+        // bytearray.__str__(ByteArray*) -> String*
+        declare_fn!(
+            string_ptr_type,
+            "__pyc___builtin___bytearray___str__",
+            bytearray_ptr_type
+        );
+
+        // bytearray.__repr__(ByteArray*) -> String*
+        declare_fn!(
+            string_ptr_type,
+            "__pyc___builtin___bytearray___repr__",
+            bytearray_ptr_type
+        );
+
+        // bytearray.__setslice__(ByteArray*, i64 start, i64 stop, i64 step, Bytes*) -> void
+        declare_fn!(
+            void_type,
+            "__pyc___builtin___bytearray___setslice__",
+            bytearray_ptr_type,
+            i64_type,
+            i64_type,
+            i64_type,
+            bytes_ptr_type
+        );
+
+        // bytearray.extend(ByteArray*, Bytes*) -> void
+        declare_fn!(
+            void_type,
+            "__pyc___builtin___bytearray_extend",
+            bytearray_ptr_type,
+            bytes_ptr_type
+        );
+
+        // bytearray.__iadd__(ByteArray*, Bytes*) -> ByteArray*
+        declare_fn!(
+            bytearray_ptr_type,
+            "__pyc___builtin___bytearray___iadd__",
+            bytearray_ptr_type,
+            bytes_ptr_type
+        );
+
+        // bytearray(Bytes*) -> ByteArray*
+        declare_fn!(
+            bytearray_ptr_type,
+            "__pyc___builtin___bytearray_from_bytes",
+            bytes_ptr_type
+        );
+
+        // bytes(ByteArray*) -> Bytes*
+        declare_fn!(
+            bytes_ptr_type,
+            "__pyc___builtin___bytes_from_bytearray",
+            bytearray_ptr_type
+        );
+
+        // list.__str__(List*) -> String*
+        declare_fn!(
+            string_ptr_type,
+            "__pyc___builtin___list___str__",
+            list_ptr_type
+        );
+
+        // list.__repr__(List*) -> String*
+        declare_fn!(
+            string_ptr_type,
+            "__pyc___builtin___list___repr__",
+            list_ptr_type
+        );
+
+        // Low-level I/O functions (no newlines)
+        // write_str_impl(const char*) -> void
+        declare_fn!(void_type, "write_str_impl", i8_ptr_type);
+
+        // write_string_impl(String*) -> void
+        declare_fn!(void_type, "write_string_impl", string_ptr_type);
+
+        // write_char_impl(char) -> void
+        declare_fn!(void_type, "write_char_impl", i8_type);
+
+        // write_newline_impl() -> void
+        declare_fn!(void_type, "write_newline_impl");
+
+        // write_space_impl() -> void
+        declare_fn!(void_type, "write_space_impl");
+
+        // int64_to_str_impl(i64, char*) -> char*
         declare_fn!(i8_ptr_type, "int64_to_str_impl", i64_type, i8_ptr_type);
 
+        // int64_to_str_radix_impl(i64 value, i64 base) -> String* (hex/oct/bin)
+        declare_fn!(i8_ptr_type, "int64_to_str_radix_impl", i64_type, i64_type);
+
         // ================================================================
         // Exception handling runtime functions
         // ================================================================
@@ -348,6 +808,19 @@ impl<'ctx> CodegenContext<'ctx> {
         // __pyc_pop_exception_frame() -> void
         declare_fn!(void_type, "__pyc_pop_exception_frame");
 
+        // __pyc_traceback_push(TracebackFrame*) -> void (pycc --traceback)
+        declare_fn!(void_type, "__pyc_traceback_push", i8_ptr_type);
+
+        // __pyc_traceback_pop() -> void
+        declare_fn!(void_type, "__pyc_traceback_pop");
+
+        // __pyc_recursion_enter(i64 limit) -> i32 (pycc --recursion-limit)
+        // Nonzero if the function must return at once
+        declare_fn!(i32_type, "__pyc_recursion_enter", i64_type);
+
+        // __pyc_recursion_leave() -> void
+        declare_fn!(void_type, "__pyc_recursion_leave");
+
         // __pyc_get_exception() -> Exception*
         declare_fn!(exception_ptr_type, "__pyc_get_exception");
 
@@ -366,6 +839,30 @@ impl<'ctx> CodegenContext<'ctx> {
         // __pyc_reraise() -> void (noreturn)
         declare_fn!(void_type, "__pyc_reraise");
 
+        // __pyc_get_handled_exception() -> Exception*
+        declare_fn!(exception_ptr_type, "__pyc_get_handled_exception");
+
+        // __pyc_set_handled_exception(Exception*) -> void
+        declare_fn!(void_type, "__pyc_set_handled_exception", exception_ptr_type);
+
+        // __pyc_report_exception(Exception*) -> void (pyrepl)
+        declare_fn!(void_type, "__pyc_report_exception", exception_ptr_type);
+
+        // __pyc_raise_overflow() -> void (OverflowError, checked-int mode)
+        declare_fn!(void_type, "__pyc_raise_overflow");
+
+        // __pyc_raise_negative_shift() -> void (ValueError, checked-int mode)
+        declare_fn!(void_type, "__pyc_raise_negative_shift");
+
+        // __pyc_raise_zero_division(const char* message) -> void (ZeroDivisionError)
+        declare_fn!(void_type, "__pyc_raise_zero_division", i8_ptr_type);
+
+        // __pyc_raise_math_domain_error() -> void (ValueError from a math function)
+        declare_fn!(void_type, "__pyc_raise_math_domain_error");
+
+        // __pyc_raise_math_range_error() -> void (OverflowError from math.exp/pow)
+        declare_fn!(void_type, "__pyc_raise_math_range_error");
+
         // Exception.__init__(String* message) -> Exception*
         declare_fn!(
             exception_ptr_type,
@@ -396,6 +893,26 @@ impl<'ctx> CodegenContext<'ctx> {
             exception_ptr_type
         );
 
+        // Exception.__cause__ and Exception.__context__ (Exception*) -> Exception*
+        declare_fn!(
+            exception_ptr_type,
+            "__pyc___builtin___Exception___cause__",
+            exception_ptr_type
+        );
+        declare_fn!(
+            exception_ptr_type,
+            "__pyc___builtin___Exception___context__",
+            exception_ptr_type
+        );
+
+        // __pyc_exception_with_cause(Exception*, Exception* cause) -> Exception*
+        declare_fn!(
+            exception_ptr_type,
+            "__pyc_exception_with_cause",
+            exception_ptr_type,
+            exception_ptr_type
+        );
+
         // __pyc_exception_matches(Exception*, const char* type_name) -> i32
         declare_fn!(
             i32_type,
@@ -465,6 +982,123 @@ impl<'ctx> CodegenContext<'ctx> {
             range_ptr_type
         );
 
+        // ================================================================
+        // File runtime functions
+        // ================================================================
+
+        // File* type (pointer to File struct)
+        let file_ptr_type = self.context.ptr_type(AddressSpace::default());
+
+        // open(String* path, String* mode) -> File*
+        declare_fn!(
+            file_ptr_type,
+            "__pyc___builtin___open",
+            string_ptr_type,
+            string_ptr_type
+        );
+
+        // file.read(File*) -> String*
+        declare_fn!(
+            string_ptr_type,
+            "__pyc___builtin___file_read",
+            file_ptr_type
+        );
+
+        // file.readline(File*) -> String*
+        declare_fn!(
+            string_ptr_type,
+            "__pyc___builtin___file_readline",
+            file_ptr_type
+        );
+
+        // file.write(File*, String*) -> i64
+        declare_fn!(
+            i64_type,
+            "__pyc___builtin___file_write",
+            file_ptr_type,
+            string_ptr_type
+        );
+
+        // file.close(File*) -> void
+        declare_fn!(void_type, "__pyc___builtin___file_close", file_ptr_type);
+
+        // file.__enter__(File*) -> File*
+        declare_fn!(
+            file_ptr_type,
+            "__pyc___builtin___file___enter__",
+            file_ptr_type
+        );
+
+        // file.__exit__(File*) -> void
+        declare_fn!(void_type, "__pyc___builtin___file___exit__", file_ptr_type);
+
+        // ================================================================
+        // sys module runtime functions
+        // ================================================================
+
+        // __pyc_sys_init(i32 argc, char** argv) -> void, called from main
+        declare_fn!(void_type, "__pyc_sys_init", i32_type, i8_ptr_type);
+
+        // sys.argv -> List* (of String*)
+        declare_fn!(list_ptr_type, "__pyc_sys_argv");
+
+        // sys.exit(i64) -> void
+        declare_fn!(void_type, "__pyc_sys_exit", i64_type);
+
+        // sys.exit(String*) -> void
+        declare_fn!(void_type, "__pyc_sys_exit_message", string_ptr_type);
+
+        // sys.stdin / sys.stdout / sys.stderr -> File*
+        declare_fn!(file_ptr_type, "__pyc_sys_stdin");
+        declare_fn!(file_ptr_type, "__pyc_sys_stdout");
+        declare_fn!(file_ptr_type, "__pyc_sys_stderr");
+
+        // ================================================================
+        // os module runtime functions
+        // ================================================================
+
+        // os.getenv(String* key, String* default) -> String*
+        declare_fn!(
+            string_ptr_type,
+            "__pyc_os_getenv",
+            string_ptr_type,
+            string_ptr_type
+        );
+
+        // os.environ -> Dict* (str -> str)
+        declare_fn!(i8_ptr_type, "__pyc_os_environ");
+
+        // os.getcwd() -> String*
+        declare_fn!(string_ptr_type, "__pyc_os_getcwd");
+
+        // ================================================================
+        // time module runtime functions
+        // ================================================================
+
+        // time.time() / time.monotonic() -> f64 seconds
+        declare_fn!(f64_type, "__pyc_time_time");
+        declare_fn!(f64_type, "__pyc_time_monotonic");
+
+        // time.sleep(f64 seconds) -> void
+        declare_fn!(void_type, "__pyc_time_sleep", f64_type);
+
+        // ================================================================
+        // random module runtime functions
+        // ================================================================
+
+        // random.seed(i64) / random.seed() -> void
+        declare_fn!(void_type, "__pyc_random_seed", i64_type);
+        declare_fn!(void_type, "__pyc_random_reseed");
+
+        // random.random() -> f64 in [0, 1)
+        declare_fn!(f64_type, "__pyc_random_random");
+
+        // random.randint(i64 a, i64 b) -> i64 in [a, b]
+        declare_fn!(i64_type, "__pyc_random_randint", i64_type, i64_type);
+
+        // random.choice() index: (i64 len) -> i64 in [0, len)
+        declare_fn!(i64_type, "__pyc_random_index", i64_type);
+
         // ================================================================
         // List iterator runtime functions
         // ================================================================
@@ -493,6 +1127,20 @@ impl<'ctx> CodegenContext<'ctx> {
             list_iterator_ptr_type
         );
 
+        // list_iterator_float.__next__(ListIterator*) -> f64
+        declare_fn!(
+            f64_type,
+            "__pyc___builtin___list_iterator_float___next__",
+            list_iterator_ptr_type
+        );
+
+        // list_iterator_bool.__next__(ListIterator*) -> i8
+        declare_fn!(
+            i8_type,
+            "__pyc___builtin___list_iterator_bool___next__",
+            list_iterator_ptr_type
+        );
+
         // list_iterator.__dealloc__(ListIterator*) -> void
         declare_fn!(
             void_type,
@@ -500,6 +1148,354 @@ impl<'ctx> CodegenContext<'ctx> {
             list_iterator_ptr_type
         );
 
+        // ================================================================
+        // Tuple runtime functions
+        // ================================================================
+
+        // Tuple* type (pointer to Tuple struct); elements are i64 slots
+        let tuple_ptr_type = self.context.ptr_type(AddressSpace::default());
+
+        // tuple.__init__(i64 len, const i8* kinds) -> Tuple*
+        declare_fn!(
+            tuple_ptr_type,
+            "__pyc___builtin___tuple___init__",
+            i64_type,
+            tuple_ptr_type
+        );
+
+        // __pyc_tuple_set(Tuple*, i64 index, i64 value) -> void
+        declare_fn!(
+            void_type,
+            "__pyc_tuple_set",
+            tuple_ptr_type,
+            i64_type,
+            i64_type
+        );
+
+        // tuple.__getitem__(Tuple*, i64) -> i64
+        declare_fn!(
+            i64_type,
+            "__pyc___builtin___tuple___getitem__",
+            tuple_ptr_type,
+            i64_type
+        );
+
+        // tuple.__len__(Tuple*) -> i64
+        declare_fn!(i64_type, "__pyc___builtin___tuple___len__", tuple_ptr_type);
+
+        // tuple.__str__(Tuple*) -> String*
+        declare_fn!(
+            string_ptr_type,
+            "__pyc___builtin___tuple___str__",
+            tuple_ptr_type
+        );
+
+        // tuple.__repr__(Tuple*) -> String*
+        declare_fn!(
+            string_ptr_type,
+            "__pyc___builtin___tuple___repr__",
+            tuple_ptr_type
+        );
+
+        // ================================================================
+        // Dict runtime functions
+        // ================================================================
+
+        // Dict* type (pointer to Dict struct); keys and values are i64 slots
+        let dict_ptr_type = self.context.ptr_type(AddressSpace::default());
+
+        // dict.__init__(i64 key_kind, i64 value_kind) -> Dict*
+        declare_fn!(
+            dict_ptr_type,
+            "__pyc___builtin___dict___init__",
+            i64_type,
+            i64_type
+        );
+
+        // dict.__getitem__(Dict*, i64) -> i64
+        declare_fn!(
+            i64_type,
+            "__pyc___builtin___dict___getitem__",
+            dict_ptr_type,
+            i64_type
+        );
+
+        // dict.__setitem__(Dict*, i64, i64) -> void
+        declare_fn!(
+            void_type,
+            "__pyc___builtin___dict___setitem__",
+            dict_ptr_type,
+            i64_type,
+            i64_type
+        );
+
+        // dict.__delitem__(Dict*, i64) -> void
+        declare_fn!(
+            void_type,
+            "__pyc___builtin___dict___delitem__",
+            dict_ptr_type,
+            i64_type
+        );
+
+        // dict.__contains__(Dict*, i64) -> i8
+        declare_fn!(
+            i8_type,
+            "__pyc___builtin___dict___contains__",
+            dict_ptr_type,
+            i64_type
+        );
+
+        // dict.__len__(Dict*) -> i64
+        declare_fn!(i64_type, "__pyc___builtin___dict___len__", dict_ptr_type);
+
+        // dict.keys(Dict*) -> List*
+        declare_fn!(list_ptr_type, "__pyc___builtin___dict_keys", dict_ptr_type);
+
+        // dict.values(Dict*) -> List*
+        declare_fn!(
+            list_ptr_type,
+            "__pyc___builtin___dict_values",
+            dict_ptr_type
+        );
+
+        // dict.items(Dict*) -> List* (of 2-tuples)
+        declare_fn!(list_ptr_type, "__pyc___builtin___dict_items", dict_ptr_type);
+
+        // dict.__iter__(Dict*) -> ListIterator*
+        declare_fn!(
+            list_iterator_ptr_type,
+            "__pyc___builtin___dict___iter__",
+            dict_ptr_type
+        );
+
+        // dict.__str__(Dict*) -> String*
+        declare_fn!(
+            string_ptr_type,
+            "__pyc___builtin___dict___str__",
+            dict_ptr_type
+        );
+
+        // dict.__repr__(Dict*) -> String*
+        declare_fn!(
+            string_ptr_type,
+            "__pyc___builtin___dict___repr__",
+            dict_ptr_type
+        );
+
+        // ================================================================
+        // Set and frozenset runtime functions
+        // ================================================================
+
+        // Sets are Dict* tables keyed by the elements (values unused)
+        let set_ptr_type = dict_ptr_type;
+
+        // set.__init__(i64 elem_kind) -> Dict*
+        declare_fn!(set_ptr_type, "__pyc___builtin___set___init__", i64_type);
+
+        // set.add(Dict*, i64) -> void
+        declare_fn!(
+            void_type,
+            "__pyc___builtin___set_add",
+            set_ptr_type,
+            i64_type
+        );
+
+        // set.remove(Dict*, i64) -> void
+        declare_fn!(
+            void_type,
+            "__pyc___builtin___set_remove",
+            set_ptr_type,
+            i64_type
+        );
+
+        // set.discard(Dict*, i64) -> void
+        declare_fn!(
+            void_type,
+            "__pyc___builtin___set_discard",
+            set_ptr_type,
+            i64_type
+        );
+
+        // set.__contains__(Dict*, i64) -> i8
+        declare_fn!(
+            i8_type,
+            "__pyc___builtin___set___contains__",
+            set_ptr_type,
+            i64_type
+        );
+
+        // set.__len__(Dict*) -> i64
+        declare_fn!(i64_type, "__pyc___builtin___set___len__", set_ptr_type);
+
+        // set.__or__(Dict*, Dict*) -> Dict*
+        declare_fn!(
+            set_ptr_type,
+            "__pyc___builtin___set___or__",
+            set_ptr_type,
+            set_ptr_type
+        );
+
+        // set.__and__(Dict*, Dict*) -> Dict*
+        declare_fn!(
+            set_ptr_type,
+            "__pyc___builtin___set___and__",
+            set_ptr_type,
+            set_ptr_type
+        );
+
+        // set.__sub__(Dict*, Dict*) -> Dict*
+        declare_fn!(
+            set_ptr_type,
+            "__pyc___builtin___set___sub__",
+            set_ptr_type,
+            set_ptr_type
+        );
+
+        // set.__xor__(Dict*, Dict*) -> Dict*
+        declare_fn!(
+            set_ptr_type,
+            "__pyc___builtin___set___xor__",
+            set_ptr_type,
+            set_ptr_type
+        );
+
+        // set.union(Dict*, Dict*) -> Dict*
+        declare_fn!(
+            set_ptr_type,
+            "__pyc___builtin___set_union",
+            set_ptr_type,
+            set_ptr_type
+        );
+
+        // set.intersection(Dict*, Dict*) -> Dict*
+        declare_fn!(
+            set_ptr_type,
+            "__pyc___builtin___set_intersection",
+            set_ptr_type,
+            set_ptr_type
+        );
+
+        // set.update(Dict*, Dict*) -> void
+        declare_fn!(
+            void_type,
+            "__pyc___builtin___set_update",
+            set_ptr_type,
+            set_ptr_type
+        );
+
+        // set.update_from_list(Dict*, List*) -> void
+        declare_fn!(
+            void_type,
+            "__pyc___builtin___set_update_from_list",
+            set_ptr_type,
+            list_ptr_type
+        );
+
+        // set.__iter__(Dict*) -> ListIterator*
+        declare_fn!(
+            list_iterator_ptr_type,
+            "__pyc___builtin___set___iter__",
+            set_ptr_type
+        );
+
+        // set.__str__(Dict*) -> String*
+        declare_fn!(
+            string_ptr_type,
+            "__pyc___builtin___set___str__",
+            set_ptr_type
+        );
+
+        // set.__repr__(Dict*) -> String*
+        declare_fn!(
+            string_ptr_type,
+            "__pyc___builtin___set___repr__",
+            set_ptr_type
+        );
+
+        // frozenset.__contains__(Dict*, i64) -> i8
+        declare_fn!(
+            i8_type,
+            "__pyc___builtin___frozenset___contains__",
+            set_ptr_type,
+            i64_type
+        );
+
+        // frozenset.__len__(Dict*) -> i64
+        declare_fn!(
+            i64_type,
+            "__pyc___builtin___frozenset___len__",
+            set_ptr_type
+        );
+
+        // frozenset.__or__(Dict*, Dict*) -> Dict*
+        declare_fn!(
+            set_ptr_type,
+            "__pyc___builtin___frozenset___or__",
+            set_ptr_type,
+            set_ptr_type
+        );
+
+        // frozenset.__and__(Dict*, Dict*) -> Dict*
+        declare_fn!(
+            set_ptr_type,
+            "__pyc___builtin___frozenset___and__",
+            set_ptr_type,
+            set_ptr_type
+        );
+
+        // frozenset.__sub__(Dict*, Dict*) -> Dict*
+        declare_fn!(
+            set_ptr_type,
+            "__pyc___builtin___frozenset___sub__",
+            set_ptr_type,
+            set_ptr_type
+        );
+
+        // frozenset.__xor__(Dict*, Dict*) -> Dict*
+        declare_fn!(
+            set_ptr_type,
+            "__pyc___builtin___frozenset___xor__",
+            set_ptr_type,
+            set_ptr_type
+        );
+
+        // frozenset.union(Dict*, Dict*) -> Dict*
+        declare_fn!(
+            set_ptr_type,
+            "__pyc___builtin___frozenset_union",
+            set_ptr_type,
+            set_ptr_type
+        );
+
+        // frozenset.intersection(Dict*, Dict*) -> Dict*
+        declare_fn!(
+            set_ptr_type,
+            "__pyc___builtin___frozenset_intersection",
+            set_ptr_type,
+            set_ptr_type
+        );
+
+        // frozenset.__iter__(Dict*) -> ListIterator*
+        declare_fn!(
+            list_iterator_ptr_type,
+            "__pyc___builtin___frozenset___iter__",
+            set_ptr_type
+        );
+
+        // frozenset.__str__(Dict*) -> String*
+        declare_fn!(
+            string_ptr_type,
+            "__pyc___builtin___frozenset___str__",
+            set_ptr_type
+        );
+
+        // frozenset.__repr__(Dict*) -> String*
+        declare_fn!(
+            string_ptr_type,
+            "__pyc___builtin___frozenset___repr__",
+            set_ptr_type
+        );
+
         // ================================================================
         // StopIteration exception runtime functions
         // ================================================================
@@ -527,4 +1523,60 @@ impl<'ctx> CodegenContext<'ctx> {
             exception_ptr_type
         );
     }
+
+    /// Emit `__pyc_runtime_checks`, which the runtime's subscript functions
+    /// consult before raising IndexError
+    pub(crate) fn emit_runtime_checks_flag(&mut self) {
+        let i8_type = self.context.i8_type();
+        let flag = self
+            .module
+            .add_global(i8_type, None, "__pyc_runtime_checks");
+        flag.set_initializer(&i8_type.const_int(self.runtime_checks as u64, false));
+        flag.set_constant(true);
+    }
+
+    /// Emit `__pyc_arena_alloc`, which tells the runtime's rt_malloc family
+    /// (runtime/src/alloc.c) to allocate from its arena
+    pub(crate) fn emit_arena_alloc_flag(&mut self) {
+        let i8_type = self.context.i8_type();
+        let flag = self.module.add_global(i8_type, None, "__pyc_arena_alloc");
+        flag.set_initializer(&i8_type.const_int(self.arena_alloc as u64, false));
+        flag.set_constant(true);
+    }
+
+    /// Emit `__pyc_refcount`, which tells the runtime (runtime/src/refcount.c)
+    /// to count the references to its strings and lists
+    pub(crate) fn emit_refcount_flag(&mut self) {
+        let i8_type = self.context.i8_type();
+        let flag = self.module.add_global(i8_type, None, "__pyc_refcount");
+        flag.set_initializer(&i8_type.const_int(self.refcount as u64, false));
+        flag.set_constant(true);
+    }
+
+    /// Emit `__pyc_setjmp_exceptions`, which tells `__pyc_raise`
+    /// (runtime/src/exception.c) to longjmp to the innermost try block
+    pub(crate) fn emit_setjmp_exceptions_flag(&mut self) {
+        let i8_type = self.context.i8_type();
+        let flag = self
+            .module
+            .add_global(i8_type, None, "__pyc_setjmp_exceptions");
+        flag.set_initializer(&i8_type.const_int(self.setjmp_exceptions as u64, false));
+        flag.set_constant(true);
+    }
+
+    /// The runtime's List struct (runtime.h), for lists in stack slots
+    pub(crate) fn list_struct_type(&self) -> StructType<'ctx> {
+        let i64_type = self.context.i64_type();
+        let i8_type = self.context.i8_type();
+        self.context.struct_type(
+            &[
+                self.context.ptr_type(AddressSpace::default()).into(), // data (i64 slots, or bytes for bools)
+                i64_type.into(),                                       // len
+                i64_type.into(),                                       // cap
+                i8_type.into(),                                        // item kind
+                i8_type.into(),                                        // item size
+            ],
+            false,
+        )
+    }
 }
@@ -0,0 +1,319 @@
+//! DWARF debug info (`pycc -g`)
+//!
+//! Each function lowered from a `def` gets a subprogram named after its
+//! qualified name (`app.Point.norm`), at the line of the `def` in its
+//! module's file, and each module initializer one named `<module>` at the
+//! top of the file. Their parameters and locals are described with their
+//! Python types, so that gdb and perf show Python names and lines.
+//!
+//! The instructions of each statement are attributed to the line and
+//! column it starts at; those the compiler adds around them (reference
+//! counting, generated functions) to the statement before them, or to the
+//! `def`.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use inkwell::debug_info::{
+    AsDIScope, DICompileUnit, DIFile, DIFlags, DIFlagsConstants, DISubprogram, DIType,
+    DWARFEmissionKind, DWARFSourceLanguage, DebugInfoBuilder,
+};
+use inkwell::module::FlagBehavior;
+use inkwell::values::{FunctionValue, PointerValue};
+
+use crate::codegen::context::CodegenContext;
+use crate::span::Span;
+use crate::tir::{ModuleId, TirProgram, TirType};
+
+/// Version of the debug info metadata LLVM reads
+const DEBUG_INFO_VERSION: u64 = 3;
+
+/// DWARF base type encodings (DW_ATE_*)
+const DW_ATE_ADDRESS: u32 = 0x01;
+const DW_ATE_BOOLEAN: u32 = 0x02;
+const DW_ATE_FLOAT: u32 = 0x04;
+const DW_ATE_SIGNED: u32 = 0x05;
+
+/// The debug info of the module being generated
+pub(crate) struct DebugInfo<'ctx> {
+    builder: DebugInfoBuilder<'ctx>,
+    compile_unit: DICompileUnit<'ctx>,
+    /// The file of each module, indexed by ModuleId
+    files: Vec<DIFile<'ctx>>,
+    /// Types described so far, by the name Python gives them
+    types: HashMap<String, DIType<'ctx>>,
+    /// The subprogram of the function being generated, and its file and line
+    current: Option<(DISubprogram<'ctx>, DIFile<'ctx>, u32)>,
+    /// Whether the LLVM passes optimize the module
+    optimized: bool,
+}
+
+/// The file name and directory of a source file, as debuggers look it up
+fn file_location(path: &Path) -> (String, String) {
+    let path = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let directory = path
+        .parent()
+        .map(|dir| dir.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    (name, directory)
+}
+
+impl<'ctx> CodegenContext<'ctx> {
+    /// Start describing the program, whose compile unit is the entry
+    /// module's file
+    pub(crate) fn begin_debug_info(&mut self, program: &TirProgram, optimized: bool) {
+        let (name, directory) = file_location(&program.module(program.entry).path);
+        let (builder, compile_unit) = self.module.create_debug_info_builder(
+            true,
+            DWARFSourceLanguage::Python,
+            &name,
+            &directory,
+            "pycc",
+            optimized,
+            "",
+            0,
+            "",
+            DWARFEmissionKind::Full,
+            0,
+            false,
+            false,
+            "",
+            "",
+        );
+        let files = program
+            .modules
+            .iter()
+            .map(|module| {
+                let (name, directory) = file_location(&module.path);
+                builder.create_file(&name, &directory)
+            })
+            .collect();
+        self.module.add_basic_value_flag(
+            "Debug Info Version",
+            FlagBehavior::Warning,
+            self.context.i32_type().const_int(DEBUG_INFO_VERSION, false),
+        );
+        self.debug_info = Some(DebugInfo {
+            builder,
+            compile_unit,
+            files,
+            types: HashMap::new(),
+            current: None,
+            optimized,
+        });
+    }
+
+    /// Resolve the descriptions once the whole module is generated
+    pub(crate) fn finish_debug_info(&self) {
+        if let Some(debug_info) = &self.debug_info {
+            debug_info.builder.finalize();
+        }
+    }
+
+    /// Describe `function` as `name`, defined at `line` of `module`, taking
+    /// `params` (`self` first, for a method of `class`), and attribute the
+    /// instructions built from here on to that line
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn begin_debug_function(
+        &mut self,
+        function: FunctionValue<'ctx>,
+        name: &str,
+        module: ModuleId,
+        line: u32,
+        class: Option<&TirType>,
+        params: &[(String, TirType)],
+        return_type: &TirType,
+        program: &TirProgram,
+    ) {
+        let Some(debug_info) = &mut self.debug_info else {
+            return;
+        };
+        let file = debug_info.files[module.index()];
+        let param_types: Vec<DIType<'ctx>> = class
+            .into_iter()
+            .chain(params.iter().map(|(_, ty)| ty))
+            .map(|ty| debug_info.describe_type(ty, program))
+            .collect();
+        let return_type =
+            (*return_type != TirType::Void).then(|| debug_info.describe_type(return_type, program));
+        let subroutine_type = debug_info.builder.create_subroutine_type(
+            file,
+            return_type,
+            &param_types,
+            DIFlags::PUBLIC,
+        );
+        let linkage_name = function.get_name().to_string_lossy().into_owned();
+        let subprogram = debug_info.builder.create_function(
+            debug_info.compile_unit.as_debug_info_scope(),
+            name,
+            Some(&linkage_name),
+            file,
+            line,
+            subroutine_type,
+            false,
+            true,
+            line,
+            DIFlags::PUBLIC,
+            debug_info.optimized,
+        );
+        function.set_subprogram(subprogram);
+        debug_info.current = Some((subprogram, file, line));
+
+        let location = debug_info.builder.create_debug_location(
+            self.context,
+            line,
+            0,
+            subprogram.as_debug_info_scope(),
+            None,
+        );
+        self.builder.set_current_debug_location(location);
+    }
+
+    /// Describe the local `name` of the function being described, stored at
+    /// `ptr`
+    pub(crate) fn debug_local(
+        &mut self,
+        ptr: PointerValue<'ctx>,
+        name: &str,
+        ty: &TirType,
+        program: &TirProgram,
+    ) {
+        let Some(debug_info) = &mut self.debug_info else {
+            return;
+        };
+        let Some((subprogram, file, line)) = debug_info.current else {
+            return;
+        };
+        let di_type = debug_info.describe_type(ty, program);
+        let variable = debug_info.builder.create_auto_variable(
+            subprogram.as_debug_info_scope(),
+            name,
+            file,
+            line,
+            di_type,
+            true,
+            DIFlags::ZERO,
+            0,
+        );
+        let location = debug_info.builder.create_debug_location(
+            self.context,
+            line,
+            0,
+            subprogram.as_debug_info_scope(),
+            None,
+        );
+        let block = self.builder.get_insert_block().unwrap();
+        debug_info
+            .builder
+            .insert_declare_at_end(ptr, Some(variable), None, location, block);
+    }
+
+    /// Describe the parameters of the function being described, named
+    /// `params` (`self` first, for a method of `class`). Their values are
+    /// tracked from the start of the function, so this runs once its body
+    /// is generated.
+    pub(crate) fn debug_params(
+        &mut self,
+        function: FunctionValue<'ctx>,
+        class: Option<&TirType>,
+        params: &[(String, TirType)],
+        program: &TirProgram,
+    ) {
+        let Some(debug_info) = &mut self.debug_info else {
+            return;
+        };
+        let Some((subprogram, file, line)) = debug_info.current else {
+            return;
+        };
+        let Some(first) = function
+            .get_first_basic_block()
+            .and_then(|entry| entry.get_first_instruction())
+        else {
+            return;
+        };
+        let location = debug_info.builder.create_debug_location(
+            self.context,
+            line,
+            0,
+            subprogram.as_debug_info_scope(),
+            None,
+        );
+        let self_param = class.map(|ty| ("self", ty));
+        let named = self_param
+            .into_iter()
+            .chain(params.iter().map(|(name, ty)| (name.as_str(), ty)));
+        for (i, (name, ty)) in named.enumerate() {
+            let di_type = debug_info.describe_type(ty, program);
+            let variable = debug_info.builder.create_parameter_variable(
+                subprogram.as_debug_info_scope(),
+                name,
+                i as u32 + 1,
+                file,
+                line,
+                di_type,
+                true,
+                DIFlags::ZERO,
+            );
+            let value = function.get_nth_param(i as u32).unwrap();
+            debug_info
+                .builder
+                .insert_dbg_value_before(value, variable, None, location, first);
+        }
+    }
+
+    /// Attribute the instructions built from here on to `span` of the
+    /// function being described
+    pub(crate) fn debug_location(&self, span: Span) {
+        let Some(debug_info) = &self.debug_info else {
+            return;
+        };
+        let Some((subprogram, _, _)) = debug_info.current else {
+            return;
+        };
+        let location = debug_info.builder.create_debug_location(
+            self.context,
+            span.line,
+            span.column,
+            subprogram.as_debug_info_scope(),
+            None,
+        );
+        self.builder.set_current_debug_location(location);
+    }
+
+    /// Stop attributing instructions to the function just described
+    pub(crate) fn end_debug_function(&mut self) {
+        if let Some(debug_info) = &mut self.debug_info {
+            debug_info.current = None;
+            self.builder.unset_current_debug_location();
+        }
+    }
+}
+
+impl<'ctx> DebugInfo<'ctx> {
+    /// The description of a value of type `ty`: ints, floats and bools as
+    /// themselves, and everything else as the pointer it is
+    fn describe_type(&mut self, ty: &TirType, program: &TirProgram) -> DIType<'ctx> {
+        let name = program.type_name(ty);
+        if let Some(&di_type) = self.types.get(&name) {
+            return di_type;
+        }
+        // Pointers are 64-bit on all supported targets
+        let (size_in_bits, encoding) = match ty {
+            TirType::Int => (64, DW_ATE_SIGNED),
+            TirType::Float => (64, DW_ATE_FLOAT),
+            TirType::Bool => (8, DW_ATE_BOOLEAN),
+            _ => (64, DW_ATE_ADDRESS),
+        };
+        let di_type = self
+            .builder
+            .create_basic_type(&name, size_in_bits, encoding, DIFlags::ZERO)
+            .unwrap()
+            .as_type();
+        self.types.insert(name, di_type);
+        di_type
+    }
+}
@@ -1,9 +1,22 @@
+use inkwell::attributes::{Attribute, AttributeLoc};
+use inkwell::module::Linkage;
 use inkwell::types::{BasicType, BasicTypeEnum};
-use inkwell::values::{AnyValue, BasicValueEnum};
+use inkwell::values::{AnyValue, BasicValueEnum, PointerValue};
 
 use crate::codegen::context::CodegenContext;
 use crate::tir::decls::{TirClass, TirFunction};
-use crate::tir::{TirModule, TirProgram, TirType};
+use crate::tir::opt::is_small_function;
+use crate::tir::{ClassId, TirModule, TirProgram, TirType};
+
+/// Name of the vtable global of a class
+pub(crate) fn vtable_name(class_name: &str) -> String {
+    format!("__pyc_vtable_{}", class_name.replace('.', "_"))
+}
+
+/// Symbol of the global variable `global` of module `module`
+pub(crate) fn global_symbol(module: &str, global: &str) -> String {
+    format!("{}_{}", module.replace('.', "_"), global)
+}
 
 /// Helper to extract BasicValueEnum from a call site
 pub(crate) fn call_result_to_basic_value<'ctx>(
@@ -23,9 +36,11 @@ impl<'ctx> CodegenContext<'ctx> {
     pub(crate) fn declare_tir_module_globals(&mut self, module: &TirModule, program: &TirProgram) {
         for global in &module.globals {
             let llvm_ty = self.tir_type_to_llvm(&global.ty, program);
-            let global_name = format!("{}_{}", module.name.replace('.', "_"), global.name);
+            let global_name = global_symbol(&module.name, &global.name);
             let global_var = self.module.add_global(llvm_ty, None, &global_name);
-            global_var.set_initializer(&llvm_ty.const_zero());
+            if !self.external_globals.contains(&global_name) {
+                global_var.set_initializer(&llvm_ty.const_zero());
+            }
             // Use module-qualified name as key to avoid collisions
             let key = format!("{}::{}", module.name, global.name);
             self.global_variables
@@ -34,11 +49,17 @@ impl<'ctx> CodegenContext<'ctx> {
     }
 
     pub(crate) fn declare_tir_class(&mut self, class: &TirClass, program: &TirProgram) {
-        // Create the struct type with all fields (inherited first, then own)
-        let field_types: Vec<BasicTypeEnum<'ctx>> = class
-            .all_fields()
-            .map(|(_, ty)| self.tir_type_to_llvm(ty, program))
-            .collect();
+        // Create the struct type with all fields (inherited first, then own),
+        // after the vtable pointer of a class hierarchy with overrides
+        let mut field_types: Vec<BasicTypeEnum<'ctx>> = Vec::new();
+        if program.has_vtable_pointer(class.id) {
+            field_types.push(self.context.ptr_type(Default::default()).into());
+        }
+        field_types.extend(
+            class
+                .all_fields()
+                .map(|(_, ty)| self.tir_type_to_llvm(ty, program)),
+        );
 
         let struct_type = self.context.opaque_struct_type(&class.qualified_name);
         struct_type.set_body(&field_types, false);
@@ -48,6 +69,35 @@ impl<'ctx> CodegenContext<'ctx> {
             .insert(class.qualified_name.clone(), struct_type);
     }
 
+    /// The index of a field in the struct type of `class`
+    pub(crate) fn field_index(&self, class: ClassId, field: usize, program: &TirProgram) -> u32 {
+        (field + usize::from(program.has_vtable_pointer(class))) as u32
+    }
+
+    /// Emit the vtable of a class that has one: the address of its
+    /// implementation of each overridden method, in slot order
+    pub(crate) fn declare_tir_vtable(&mut self, class: &TirClass, program: &TirProgram) {
+        if class.vtable.is_empty() {
+            return;
+        }
+        let ptr_type = self.context.ptr_type(Default::default());
+        let methods: Vec<PointerValue<'ctx>> = class
+            .vtable
+            .iter()
+            .map(|(_, func)| {
+                let name = &program.function(*func).qualified_name;
+                self.functions[name].as_global_value().as_pointer_value()
+            })
+            .collect();
+        let vtable_type = ptr_type.array_type(methods.len() as u32);
+        let vtable = self
+            .module
+            .add_global(vtable_type, None, &vtable_name(&class.qualified_name));
+        vtable.set_initializer(&ptr_type.const_array(&methods));
+        vtable.set_constant(true);
+        vtable.set_linkage(Linkage::Private);
+    }
+
     pub(crate) fn declare_tir_function(&mut self, func: &TirFunction, program: &TirProgram) {
         // Skip runtime functions - they're already declared by the runtime
         if func.runtime_name.is_some() {
@@ -86,6 +136,13 @@ impl<'ctx> CodegenContext<'ctx> {
         // Create the LLVM function
         let llvm_name = format!("__pyc_{}", func.qualified_name.replace('.', "_"));
         let fn_value = self.module.add_function(&llvm_name, fn_type, None);
+        if self.inline_small_functions && is_small_function(func) {
+            let always_inline = Attribute::get_named_enum_kind_id("alwaysinline");
+            fn_value.add_attribute(
+                AttributeLoc::Function,
+                self.context.create_enum_attribute(always_inline, 0),
+            );
+        }
 
         // Store it
         self.functions.insert(func.qualified_name.clone(), fn_value);
@@ -104,8 +161,13 @@ impl<'ctx> CodegenContext<'ctx> {
             TirType::Class(_class_id) => {
                 // All classes (including Bytes, Str) are represented as pointers
                 self.context.ptr_type(Default::default()).into()
+            }
+            // Tuples are heap objects too, an Optional is a pointer that is
+            // null for None, and None itself is always null
+            TirType::Tuple(_) | TirType::Optional(_) | TirType::NoneType => {
+                self.context.ptr_type(Default::default()).into()
             } // NOTE: TirType no longer has TypeVar variant - type inference is enforced
-                                                               // at compile-time by the two-layer TIR architecture (TirTypeUnresolved -> TirType)
+              // at compile-time by the two-layer TIR architecture (TirTypeUnresolved -> TirType)
         }
     }
 }
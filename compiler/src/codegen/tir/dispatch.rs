@@ -0,0 +1,62 @@
+//! Dynamic dispatch of overridden methods
+//!
+//! Every class with a vtable (`TirClass::vtable`) gets a constant array of
+//! its implementations of the overridden methods, emitted with the other
+//! declarations. The instances of the classes of its hierarchy start with a
+//! pointer to their class's array, stored when they are constructed. A
+//! virtual call loads the method from the receiver's vtable and calls it
+//! with the signature of the method of the receiver's declared class, which
+//! every override shares.
+
+use inkwell::values::PointerValue;
+use inkwell::AddressSpace;
+
+use crate::tir::decls::TirClass;
+
+use super::declarations::vtable_name;
+use super::function_gen::FunctionGenContext;
+
+impl<'ctx, 'a> FunctionGenContext<'ctx, 'a> {
+    /// Point a new instance at its class's vtable, if the class has one
+    pub(crate) fn store_vtable_pointer(&mut self, instance: PointerValue<'ctx>, class: &TirClass) {
+        let Some(vtable) = self
+            .ctx
+            .module
+            .get_global(&vtable_name(&class.qualified_name))
+        else {
+            return;
+        };
+        // The pointer is the instance's first field
+        self.ctx
+            .builder
+            .build_store(instance, vtable.as_pointer_value())
+            .unwrap();
+    }
+
+    /// The method in vtable `slot` of the receiver's class
+    pub(crate) fn load_virtual_method(
+        &mut self,
+        receiver: PointerValue<'ctx>,
+        slot: usize,
+    ) -> PointerValue<'ctx> {
+        let ptr_type = self.ctx.context.ptr_type(AddressSpace::default());
+        let vtable = self
+            .ctx
+            .builder
+            .build_load(ptr_type, receiver, "vtable")
+            .unwrap()
+            .into_pointer_value();
+        let index = self.ctx.context.i64_type().const_int(slot as u64, false);
+        let method_slot = unsafe {
+            self.ctx
+                .builder
+                .build_gep(ptr_type, vtable, &[index], "method_slot")
+                .unwrap()
+        };
+        self.ctx
+            .builder
+            .build_load(ptr_type, method_slot, "method")
+            .unwrap()
+            .into_pointer_value()
+    }
+}
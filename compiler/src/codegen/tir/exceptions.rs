@@ -0,0 +1,433 @@
+//! Exception frames of try blocks
+//!
+//! Every try block pushes a frame on the runtime's exception frame stack
+//! (runtime/src/exception.c) and pops it when it reaches its finally block;
+//! a return out of a try block pops the frames it leaves. The frames are
+//! allocated in the function's entry block, so a try block in a loop reuses
+//! its frame.
+//!
+//! By default a raise only records the exception, and a try block polls
+//! for it after each statement of its body, handlers, else and finally
+//! blocks. The finally block takes the exception still pending (one no
+//! handler caught, or one raised in a handler or the else block), runs,
+//! then raises it again. The runtime's handled exception, which a bare
+//! raise re-raises and a new exception takes as its `__context__`, is set
+//! by the handlers and the finally block, and put back as the try block
+//! ends or a return leaves it.
+//!
+//! A return in a try block whose finally block has a body goes through it:
+//! the value is kept in a local of its own, another local records that the
+//! function is returning, and the finally block goes on returning once it
+//! ends, through the finally blocks of the try blocks around it. An
+//! exception reaching a handler or a finally block ends the return, as in
+//! Python. (The language has no `break` or `continue` to leave a try block
+//! otherwise.)
+//!
+//! With `pycc --exceptions=setjmp`, a try block also saves its registers in
+//! its frame's jump buffer, laid out as `__builtin_setjmp` lays it out, and
+//! a raise longjmps back there instead of returning. The handlers and the
+//! else block arm the frame again, so that a raise in them lands in the
+//! finally block. The locals of a function with a try block are then kept
+//! in memory: a local promoted to a register would read as it was when the
+//! frame was armed once a raise jumps back.
+
+use inkwell::basic_block::BasicBlock;
+use inkwell::intrinsics::Intrinsic;
+use inkwell::values::{BasicValueEnum, InstructionOpcode, IntValue, PointerValue};
+use inkwell::{AddressSpace, IntPredicate};
+
+use crate::tir::{LocalId, TirType};
+
+use super::declarations::call_result_to_basic_value;
+use super::function_gen::FunctionGenContext;
+
+/// A try block being generated, up to its finally block
+pub(crate) struct TryScope<'ctx> {
+    /// Where an exception goes: to the handlers, then, from the handlers
+    /// and the else block, to the finally block
+    pub(crate) raised: BasicBlock<'ctx>,
+    /// The finally block, which a return goes through if it has a body
+    finally: Option<BasicBlock<'ctx>>,
+    /// Exception frames pushed, the try block's included
+    frames: usize,
+    /// The exception being handled as the try block started
+    handled: PointerValue<'ctx>,
+    /// Whether a return went through the finally block
+    pub(crate) returns: bool,
+}
+
+/// The locals of a function in which a return goes through finally blocks:
+/// whether it is returning, and the value it returns
+pub(crate) struct FinallyReturn {
+    returning: LocalId,
+    value: Option<(LocalId, TirType)>,
+}
+
+impl FinallyReturn {
+    /// Add the locals to the function's
+    pub(crate) fn add_locals(locals: &mut Vec<(String, TirType)>, return_type: &TirType) -> Self {
+        let returning = LocalId(locals.len() as u32);
+        locals.push(("__returning".to_string(), TirType::Bool));
+        let value = (*return_type != TirType::Void).then(|| {
+            let local = LocalId(locals.len() as u32);
+            locals.push(("__return".to_string(), return_type.clone()));
+            (local, return_type.clone())
+        });
+        FinallyReturn { returning, value }
+    }
+}
+
+impl<'ctx, 'a> FunctionGenContext<'ctx, 'a> {
+    /// Allocate a try block's frame and push it
+    pub(crate) fn push_exception_frame(&mut self) -> PointerValue<'ctx> {
+        // Room for the runtime's ExceptionFrame (exception.h)
+        let frame_type = self.ctx.context.i64_type().array_type(9);
+        let frame = self.at_function_entry(|this| {
+            this.ctx
+                .builder
+                .build_alloca(frame_type, "exc_frame")
+                .unwrap()
+        });
+        let push_fn = self
+            .ctx
+            .module
+            .get_function("__pyc_push_exception_frame")
+            .unwrap();
+        self.ctx
+            .builder
+            .build_call(push_fn, &[frame.into()], "")
+            .unwrap();
+        self.exception_frames += 1;
+        frame
+    }
+
+    /// Pop the innermost try block's frame, as its finally block starts
+    pub(crate) fn pop_exception_frame(&mut self) {
+        self.build_pop_exception_frame();
+        self.exception_frames -= 1;
+    }
+
+    /// Pop the frames of the try blocks a return leaves, and put back the
+    /// exception being handled as the outermost started
+    pub(crate) fn pop_exception_frames_at_return(&mut self) {
+        for _ in 0..self.exception_frames {
+            self.build_pop_exception_frame();
+        }
+        if let Some(&handled) = self.handled_exceptions.first() {
+            self.set_handled_exception(handled);
+        }
+    }
+
+    /// Start a try block's body, once its frame is pushed
+    pub(crate) fn begin_try_scope(
+        &mut self,
+        handlers: BasicBlock<'ctx>,
+        finally: Option<BasicBlock<'ctx>>,
+    ) {
+        let handled = *self.handled_exceptions.last().unwrap();
+        self.try_scopes.push(TryScope {
+            raised: handlers,
+            finally,
+            frames: self.exception_frames,
+            handled,
+            returns: false,
+        });
+    }
+
+    /// Whether a return leaves a try block whose finally block has a body
+    pub(crate) fn return_has_finally(&self) -> bool {
+        self.try_scopes.iter().any(|scope| scope.finally.is_some())
+    }
+
+    /// Go to the finally block of the innermost such try block, leaving the
+    /// ones inside it
+    pub(crate) fn return_to_finally(&mut self) {
+        let scope = self
+            .try_scopes
+            .iter_mut()
+            .rev()
+            .find(|scope| scope.finally.is_some())
+            .unwrap();
+        scope.returns = true;
+        let (finally, frames, handled) = (scope.finally.unwrap(), scope.frames, scope.handled);
+        self.set_returning(true);
+        for _ in frames..self.exception_frames {
+            self.build_pop_exception_frame();
+        }
+        // The handlers left no longer handle their exceptions
+        self.set_handled_exception(handled);
+        self.ctx
+            .builder
+            .build_unconditional_branch(finally)
+            .unwrap();
+    }
+
+    /// Record whether the function is returning through finally blocks
+    pub(crate) fn set_returning(&mut self, returning: bool) {
+        let Some(finally_return) = &self.finally_return else {
+            return;
+        };
+        let (ptr, ty) = self.locals[finally_return.returning.index()];
+        let value = ty.into_int_type().const_int(returning as u64, false);
+        self.ctx.builder.build_store(ptr, value).unwrap();
+    }
+
+    /// Whether the function is returning through finally blocks
+    pub(crate) fn returning(&mut self) -> IntValue<'ctx> {
+        let finally_return = self.finally_return.as_ref().unwrap();
+        let (ptr, ty) = self.locals[finally_return.returning.index()];
+        let returning = self
+            .ctx
+            .builder
+            .build_load(ty, ptr, "returning")
+            .unwrap()
+            .into_int_value();
+        self.ctx
+            .builder
+            .build_int_compare(
+                IntPredicate::NE,
+                returning,
+                ty.into_int_type().const_zero(),
+                "is_returning",
+            )
+            .unwrap()
+    }
+
+    /// Keep the value a return going through finally blocks returns
+    pub(crate) fn keep_returned_value(&mut self, value: BasicValueEnum<'ctx>) {
+        let (local, _) = self
+            .finally_return
+            .as_ref()
+            .and_then(|finally_return| finally_return.value.as_ref())
+            .unwrap();
+        let (ptr, _) = self.locals[local.index()];
+        if self.rc_locals.contains(&ptr) {
+            self.rc_store(ptr, value);
+        } else {
+            self.ctx.builder.build_store(ptr, value).unwrap();
+        }
+    }
+
+    /// The value kept by keep_returned_value, and its type
+    pub(crate) fn returned_value(&mut self) -> Option<(BasicValueEnum<'ctx>, TirType)> {
+        let (local, ty) = self.finally_return.as_ref()?.value.clone()?;
+        let (ptr, llvm_type) = self.locals[local.index()];
+        let value = self
+            .ctx
+            .builder
+            .build_load(llvm_type, ptr, "returned")
+            .unwrap();
+        Some((value, ty))
+    }
+
+    /// Remember the exception being handled as a try block starts
+    pub(crate) fn save_handled_exception(&mut self) {
+        let handled = self.call_exception_fn("__pyc_get_handled_exception", &[], "handled");
+        self.handled_exceptions.push(handled);
+    }
+
+    /// Put back the exception being handled as the innermost try block
+    /// started
+    pub(crate) fn restore_handled_exception(&mut self) {
+        let handled = *self.handled_exceptions.last().unwrap();
+        self.set_handled_exception(handled);
+    }
+
+    /// Make `exc` the exception being handled
+    pub(crate) fn set_handled_exception(&mut self, exc: PointerValue<'ctx>) {
+        self.call_exception_fn("__pyc_set_handled_exception", &[exc.into()], "");
+    }
+
+    /// The pending exception, or null
+    pub(crate) fn pending_exception(&mut self) -> PointerValue<'ctx> {
+        self.call_exception_fn("__pyc_get_exception", &[], "pending")
+    }
+
+    fn call_exception_fn(
+        &mut self,
+        name: &str,
+        args: &[BasicValueEnum<'ctx>],
+        result: &str,
+    ) -> PointerValue<'ctx> {
+        let function = self.ctx.module.get_function(name).unwrap();
+        let args: Vec<_> = args.iter().map(|&arg| arg.into()).collect();
+        let call = self
+            .ctx
+            .builder
+            .build_call(function, &args, result)
+            .unwrap();
+        let null = self
+            .ctx
+            .context
+            .ptr_type(AddressSpace::default())
+            .const_null();
+        call_result_to_basic_value(call, null.into()).into_pointer_value()
+    }
+
+    /// Branch to `raised` if an exception is pending, and go on in a new
+    /// block otherwise
+    pub(crate) fn poll_exception(&mut self, raised: BasicBlock<'ctx>, name: &str) {
+        let func = self.ctx.current_function.unwrap();
+        let i32_type = self.ctx.context.i32_type();
+        let has_exc_fn = self.ctx.module.get_function("__pyc_has_exception").unwrap();
+        let has_exc_call = self
+            .ctx
+            .builder
+            .build_call(has_exc_fn, &[], "has_exc")
+            .unwrap();
+        let has_exc =
+            call_result_to_basic_value(has_exc_call, i32_type.const_zero().into()).into_int_value();
+        let has_exc_bool = self
+            .ctx
+            .builder
+            .build_int_compare(IntPredicate::NE, has_exc, i32_type.const_zero(), "poll")
+            .unwrap();
+        let cont_bb = self.ctx.context.append_basic_block(func, name);
+        self.ctx
+            .builder
+            .build_conditional_branch(has_exc_bool, raised, cont_bb)
+            .unwrap();
+        self.ctx.builder.position_at_end(cont_bb);
+    }
+
+    fn build_pop_exception_frame(&mut self) {
+        let pop_fn = self
+            .ctx
+            .module
+            .get_function("__pyc_pop_exception_frame")
+            .unwrap();
+        self.ctx.builder.build_call(pop_fn, &[], "").unwrap();
+    }
+
+    /// Save the registers in a try block's frame, then continue in `next`,
+    /// or in `raised` once a raise jumps back (`--exceptions=setjmp`)
+    pub(crate) fn arm_exception_frame(
+        &mut self,
+        frame: PointerValue<'ctx>,
+        raised: BasicBlock<'ctx>,
+        next: BasicBlock<'ctx>,
+    ) {
+        self.keep_locals_in_memory();
+        let jumped = self.build_setjmp(frame);
+        self.ctx
+            .builder
+            .build_conditional_branch(jumped, raised, next)
+            .unwrap();
+    }
+
+    /// `__builtin_setjmp(frame)`: the frame pointer and stack pointer go in
+    /// words 0 and 2 of the buffer, and the intrinsic stores the address to
+    /// resume at in word 1. True when a raise jumped back.
+    fn build_setjmp(&mut self, frame: PointerValue<'ctx>) -> IntValue<'ctx> {
+        let ptr_type = self.ctx.context.ptr_type(AddressSpace::default());
+        let i32_type = self.ctx.context.i32_type();
+        let i64_type = self.ctx.context.i64_type();
+
+        let frame_address = Intrinsic::find("llvm.frameaddress")
+            .unwrap()
+            .get_declaration(&self.ctx.module, &[ptr_type.into()])
+            .unwrap();
+        let frame_pointer_call = self
+            .ctx
+            .builder
+            .build_call(frame_address, &[i32_type.const_zero().into()], "fp")
+            .unwrap();
+        let frame_pointer =
+            call_result_to_basic_value(frame_pointer_call, ptr_type.const_null().into());
+        self.ctx.builder.build_store(frame, frame_pointer).unwrap();
+
+        let stack_save = Intrinsic::find("llvm.stacksave")
+            .unwrap()
+            .get_declaration(&self.ctx.module, &[ptr_type.into()])
+            .unwrap();
+        let stack_pointer_call = self.ctx.builder.build_call(stack_save, &[], "sp").unwrap();
+        let stack_pointer =
+            call_result_to_basic_value(stack_pointer_call, ptr_type.const_null().into());
+        let stack_slot = unsafe {
+            self.ctx
+                .builder
+                .build_gep(ptr_type, frame, &[i64_type.const_int(2, false)], "sp_slot")
+                .unwrap()
+        };
+        self.ctx
+            .builder
+            .build_store(stack_slot, stack_pointer)
+            .unwrap();
+
+        let setjmp = Intrinsic::find("llvm.eh.sjlj.setjmp")
+            .unwrap()
+            .get_declaration(&self.ctx.module, &[])
+            .unwrap();
+        let setjmp_call = self
+            .ctx
+            .builder
+            .build_call(setjmp, &[frame.into()], "setjmp")
+            .unwrap();
+        let result =
+            call_result_to_basic_value(setjmp_call, i32_type.const_zero().into()).into_int_value();
+        self.ctx
+            .builder
+            .build_int_compare(IntPredicate::NE, result, i32_type.const_zero(), "raised")
+            .unwrap()
+    }
+
+    /// Hand the address of every local to an empty inline asm statement, so
+    /// that LLVM cannot promote them to registers (once per function)
+    fn keep_locals_in_memory(&mut self) {
+        if self.locals_in_memory {
+            return;
+        }
+        self.locals_in_memory = true;
+
+        let ptr_type = self.ctx.context.ptr_type(AddressSpace::default());
+        let fn_type = self
+            .ctx
+            .context
+            .void_type()
+            .fn_type(&[ptr_type.into()], false);
+        let asm = self.ctx.context.create_inline_asm(
+            fn_type,
+            String::new(),
+            "r".to_string(),
+            true,
+            false,
+            None,
+            false,
+        );
+        let slots: Vec<_> = self.locals.iter().map(|(ptr, _)| *ptr).collect();
+        self.at_function_entry(|this| {
+            for slot in slots {
+                this.ctx
+                    .builder
+                    .build_indirect_call(fn_type, asm, &[slot.into()], "")
+                    .unwrap();
+            }
+        });
+    }
+
+    /// Build at the top of the entry block, after its allocas, then go back
+    /// to the end of the current block
+    pub(crate) fn at_function_entry<R>(&mut self, build: impl FnOnce(&mut Self) -> R) -> R {
+        let current = self.ctx.builder.get_insert_block().unwrap();
+        let entry = self
+            .ctx
+            .current_function
+            .unwrap()
+            .get_first_basic_block()
+            .unwrap();
+        let mut first = entry.get_first_instruction();
+        while let Some(instruction) = first {
+            if instruction.get_opcode() != InstructionOpcode::Alloca {
+                break;
+            }
+            first = instruction.get_next_instruction();
+        }
+        match first {
+            Some(instruction) => self.ctx.builder.position_before(&instruction),
+            None => self.ctx.builder.position_at_end(entry),
+        }
+        let result = build(self);
+        self.ctx.builder.position_at_end(current);
+        result
+    }
+}
@@ -0,0 +1,146 @@
+//! Shared libraries (`pycc --shared`)
+//!
+//! A function decorated with `@export` gets a C symbol of its own name,
+//! next to its `__pyc_<module>_<name>` one, so that C code can call it:
+//!
+//! ```python
+//! @export
+//! def add(a: int, b: int) -> int:
+//!     return a + b
+//! ```
+//!
+//! ```c
+//! int64_t add(int64_t a, int64_t b);
+//! ```
+//!
+//! A shared library has no `main` to start the program from. Instead, a
+//! constructor in `llvm.global_ctors` runs the module initializers when the
+//! library is loaded, before any exported function can be called.
+//!
+//! A static library (`pycc --emit=staticlib`, see staticlib.rs) is linked
+//! into a program that already has a runtime and a main, so neither a
+//! constructor nor the runtime's flags are generated. An initializer named
+//! after the package's entry module initializes its modules instead, which
+//! the program calls from the module importing the library, and every other
+//! symbol the library defines becomes internal, so that its modules and the
+//! program's can have the same names.
+
+use inkwell::module::Linkage;
+use inkwell::AddressSpace;
+
+use crate::codegen::context::CodegenContext;
+use crate::error::{CompilerError, Result};
+use crate::staticlib::{exported_symbols, init_symbol};
+use crate::tir::{TirProgram, TirType};
+
+use super::declarations::call_result_to_basic_value;
+
+/// Priority of the library constructor: the default one, which C's
+/// `__attribute__((constructor))` also uses
+const LIBRARY_INIT_PRIORITY: u64 = 65535;
+
+impl<'ctx> CodegenContext<'ctx> {
+    /// Define a function named after each exported function that calls it
+    pub(crate) fn generate_tir_exports(&mut self, program: &TirProgram) -> Result<()> {
+        for &func_id in &program.exports {
+            let func = program.function(func_id);
+            if self.module.get_function(&func.name).is_some() {
+                return Err(CompilerError::CodegenError(format!(
+                    "Cannot export '{}': the runtime already defines a symbol of that name",
+                    func.name
+                )));
+            }
+
+            // Parameters and return values are ints, floats and bools, whose
+            // LLVM types are already those of C's int64_t, double and bool
+            let target = self.functions[&func.qualified_name];
+            let function = self
+                .module
+                .add_function(&func.name, target.get_type(), None);
+            let entry = self.context.append_basic_block(function, "entry");
+            self.builder.position_at_end(entry);
+
+            let args: Vec<_> = function.get_param_iter().map(|arg| arg.into()).collect();
+            let call = self.builder.build_call(target, &args, "result").unwrap();
+            if func.return_type == TirType::Void {
+                self.builder.build_return(None).unwrap();
+            } else {
+                let zero = self.context.i64_type().const_zero().into();
+                let result = call_result_to_basic_value(call, zero);
+                self.builder.build_return(Some(&result)).unwrap();
+            }
+        }
+        Ok(())
+    }
+
+    /// Generate the constructor that starts the program when the library is
+    /// loaded, in place of main
+    pub(crate) fn generate_tir_library_init(&mut self, program: &TirProgram) {
+        let i32_type = self.context.i32_type();
+        let ptr_type = self.context.ptr_type(AddressSpace::default());
+        let fn_type = self.context.void_type().fn_type(&[], false);
+        let function =
+            self.module
+                .add_function("__pyc_library_init", fn_type, Some(Linkage::Internal));
+
+        let entry = self.context.append_basic_block(function, "entry");
+        self.builder.position_at_end(entry);
+
+        // There is no command line: sys.argv is empty
+        self.build_program_start(
+            i32_type.const_zero().into(),
+            ptr_type.const_null().into(),
+            program,
+        );
+        self.builder.build_return(None).unwrap();
+
+        // llvm.global_ctors = [{ i32 priority, ptr function, ptr data }]
+        let ctor_type = self
+            .context
+            .struct_type(&[i32_type.into(), ptr_type.into(), ptr_type.into()], false);
+        let ctor = ctor_type.const_named_struct(&[
+            i32_type.const_int(LIBRARY_INIT_PRIORITY, false).into(),
+            function.as_global_value().as_pointer_value().into(),
+            ptr_type.const_null().into(),
+        ]);
+        let ctors = self
+            .module
+            .add_global(ctor_type.array_type(1), None, "llvm.global_ctors");
+        ctors.set_linkage(Linkage::Appending);
+        ctors.set_initializer(&ctor_type.const_array(&[ctor]));
+    }
+
+    /// Generate the function a program calls to initialize the modules of
+    /// the static library, in place of main
+    pub(crate) fn generate_tir_static_library_init(&mut self, program: &TirProgram) {
+        let fn_type = self.context.void_type().fn_type(&[], false);
+        let name = init_symbol(&program.module(program.entry).name);
+        let function = self.module.add_function(&name, fn_type, None);
+
+        let entry = self.context.append_basic_block(function, "entry");
+        self.builder.position_at_end(entry);
+        self.gc_add_global_roots(program);
+        self.build_module_inits(program);
+        self.builder.build_return(None).unwrap();
+    }
+
+    /// Make every function and global the static library defines internal,
+    /// but its exported functions and initializer
+    pub(crate) fn internalize_static_library(&mut self, program: &TirProgram) {
+        let kept = exported_symbols(program);
+        let is_kept = |name: &str| name.starts_with("llvm.") || kept.iter().any(|k| k == name);
+
+        for function in self.module.get_functions() {
+            let name = function.get_name().to_string_lossy().to_string();
+            if function.count_basic_blocks() > 0 && !is_kept(&name) {
+                function.set_linkage(Linkage::Internal);
+            }
+        }
+        for global in self.module.get_globals() {
+            let name = global.get_name().to_string_lossy().to_string();
+            if global.get_initializer().is_some() && !is_kept(&name) {
+                global.set_linkage(Linkage::Internal);
+            }
+        }
+    }
+}
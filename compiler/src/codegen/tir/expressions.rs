@@ -1,11 +1,13 @@
-use inkwell::values::BasicValueEnum;
+use inkwell::values::{BasicMetadataValueEnum, BasicValueEnum};
 
 use crate::ast::UnaryOp;
 use crate::tir::expr::{TirConstant, TirExpr, TirExprKind};
+use crate::tir::llvm_intrinsic::declare_llvm_intrinsic;
 use crate::tir::{TirProgram, TirType};
 
 use super::declarations::call_result_to_basic_value;
 use super::function_gen::FunctionGenContext;
+use super::refcount::rc_release_name;
 
 impl<'ctx, 'a> FunctionGenContext<'ctx, 'a> {
     pub(crate) fn codegen_expr(
@@ -45,11 +47,30 @@ impl<'ctx, 'a> FunctionGenContext<'ctx, 'a> {
                         .ptr_type(inkwell::AddressSpace::default())
                         .const_null()
                         .into();
-                    return call_result_to_basic_value(result, default);
+                    let result = call_result_to_basic_value(result, default);
+                    let temporaries: Vec<_> = [(left, lhs), (right, rhs)]
+                        .into_iter()
+                        .filter(|(operand, _)| self.is_rc_temporary(operand, program))
+                        .map(|(_, value)| value)
+                        .collect();
+                    let result_counted = self.ctx.is_refcounted(&expr.ty, program);
+                    self.rc_release_arguments(&temporaries, result, result_counted);
+                    return result;
                 }
 
                 let lhs = self.codegen_expr(left, program);
-                let rhs = self.codegen_expr(right, program);
+                let mut rhs = self.codegen_expr(right, program);
+
+                // int / int is checked before the conversion to float, since
+                // CPython words the error differently from float division
+                if matches!(op, crate::ast::BinOperator::Div)
+                    && matches!(left.ty, TirType::Int)
+                    && matches!(right.ty, TirType::Int)
+                {
+                    rhs = self
+                        .check_int_divisor(rhs.into_int_value(), "division by zero")
+                        .into();
+                }
 
                 // Determine if we should use float or int operations
                 let is_float_op = matches!(expr.ty, TirType::Float)
@@ -70,6 +91,18 @@ impl<'ctx, 'a> FunctionGenContext<'ctx, 'a> {
             TirExprKind::Compare { left, op, right } => {
                 use crate::ast::CompareOp;
 
+                // Identity: pointers must be the same object, unboxed values
+                // the same value (floats fall through to a float compare)
+                if matches!(op, CompareOp::Is | CompareOp::IsNot)
+                    && !matches!(left.ty, TirType::Float)
+                {
+                    let lhs = self.codegen_expr(left, program);
+                    let rhs = self.codegen_expr(right, program);
+                    let lhs = self.value_to_i64(lhs);
+                    let rhs = self.value_to_i64(rhs);
+                    return self.codegen_compare(lhs, *op, rhs).into();
+                }
+
                 // Special case: String comparison
                 if matches!(left.ty, TirType::Class(_)) {
                     let lhs = self.codegen_expr(left, program);
@@ -83,6 +116,10 @@ impl<'ctx, 'a> FunctionGenContext<'ctx, 'a> {
                         CompareOp::LtE => "__pyc___builtin___str___le__",
                         CompareOp::Gt => "__pyc___builtin___str___gt__",
                         CompareOp::GtE => "__pyc___builtin___str___ge__",
+                        CompareOp::In | CompareOp::NotIn => {
+                            unreachable!("membership tests are lowered to method calls")
+                        }
+                        CompareOp::Is | CompareOp::IsNot => unreachable!("handled above"),
                     };
 
                     let func = self
@@ -142,6 +179,11 @@ impl<'ctx, 'a> FunctionGenContext<'ctx, 'a> {
                                 .build_float_neg(float_val, "fneg")
                                 .unwrap()
                                 .into()
+                        } else if self.ctx.checked_int && matches!(operand.ty, TirType::Int) {
+                            // -INT64_MIN does not fit
+                            let zero = self.ctx.context.i64_type().const_zero();
+                            self.codegen_checked_int_op("llvm.ssub", zero, val.into_int_value())
+                                .into()
                         } else {
                             let int_val = val.into_int_value();
                             self.ctx
@@ -154,7 +196,7 @@ impl<'ctx, 'a> FunctionGenContext<'ctx, 'a> {
                 }
             }
 
-            TirExprKind::Call { func, args } => {
+            TirExprKind::Call { func, args } | TirExprKind::VirtualCall { func, args, .. } => {
                 let func_def = program.function(*func);
 
                 // Get the LLVM function - either by runtime_name or qualified_name
@@ -172,16 +214,33 @@ impl<'ctx, 'a> FunctionGenContext<'ctx, 'a> {
                 let param_types: Vec<_> = fn_type.get_param_types();
 
                 // Evaluate args with automatic type conversion based on LLVM param types
-                let mut call_args = Vec::new();
+                let is_extern = func_def.runtime_name.is_some() && program.externs.contains(func);
+                let mut call_args: Vec<BasicMetadataValueEnum<'ctx>> = Vec::new();
+                // New objects passed to a runtime function, freed after the call
+                let mut temporaries = Vec::new();
                 for (i, arg) in args.iter().enumerate() {
-                    let arg_val = self.codegen_expr(arg, program);
+                    let mut arg_val = self.codegen_expr(arg, program);
+                    if func_def.runtime_name.is_some() && self.is_rc_temporary(arg, program) {
+                        temporaries.push(arg_val);
+                    }
+                    if is_extern {
+                        arg_val = self.c_argument(arg_val, &arg.ty, program);
+                    }
 
                     // Convert value to match LLVM parameter type if needed
                     let converted = if i < param_types.len() {
                         let expected_type = param_types[i];
-                        if expected_type.is_int_type() && arg_val.is_pointer_value() {
-                            // LLVM expects i64 but we have a pointer - convert
+                        let expects_i64 = expected_type.is_int_type()
+                            && expected_type.into_int_type().get_bit_width() == 64;
+                        let is_i64 = arg_val.is_int_value()
+                            && arg_val.into_int_value().get_type().get_bit_width() == 64;
+                        if expects_i64 && !is_i64 {
+                            // LLVM expects i64 (generic container slot) but we have a
+                            // pointer, bool or float - convert
                             self.value_to_i64(arg_val).into()
+                        } else if expected_type.is_float_type() && arg_val.is_int_value() {
+                            // An int passed as a float item (list[float].append(1))
+                            self.convert_to_float(arg_val).into()
                         } else {
                             arg_val
                         }
@@ -191,179 +250,67 @@ impl<'ctx, 'a> FunctionGenContext<'ctx, 'a> {
                     call_args.push(converted.into());
                 }
 
-                let call = self
-                    .ctx
-                    .builder
-                    .build_call(fn_value, &call_args, "call")
-                    .unwrap();
+                let call = match &expr.kind {
+                    // The override of the receiver's class, called like the
+                    // method of its declared class
+                    TirExprKind::VirtualCall { slot, .. } => {
+                        let BasicMetadataValueEnum::PointerValue(receiver) = call_args[0] else {
+                            unreachable!("the receiver of a virtual call is an instance");
+                        };
+                        let method = self.load_virtual_method(receiver, *slot);
+                        self.ctx
+                            .builder
+                            .build_indirect_call(fn_type, method, &call_args, "call")
+                            .unwrap()
+                    }
+                    _ => self
+                        .ctx
+                        .builder
+                        .build_call(fn_value, &call_args, "call")
+                        .unwrap(),
+                };
                 let default = self.ctx.context.i64_type().const_int(0, false).into();
-                let result = call_result_to_basic_value(call, default);
+                let mut result = call_result_to_basic_value(call, default);
 
-                // Convert result if TIR expects a Class but LLVM returned i64
-                if let TirType::Class(_) = &expr.ty {
-                    if result.is_int_value() {
-                        return self.value_to_pointer(result).into();
-                    }
+                // Convert result if LLVM returned an i64 container slot but TIR expects
+                // a Class, tuple, bool or float
+                if result.is_int_value() && result.into_int_value().get_type().get_bit_width() == 64
+                {
+                    result = self.i64_slot_to_value(result.into_int_value(), &expr.ty);
                 }
 
+                let result_counted = self.ctx.is_refcounted(&expr.ty, program);
+                self.rc_release_arguments(&temporaries, result, result_counted);
                 result
             }
 
             TirExprKind::Construct { class, args } => {
                 let class_def = program.class(*class);
 
-                // Handle bytearray specially
+                // Handle bytearray specially: bytearray() or a copy of a bytes value
                 if class_def.qualified_name == "__builtin__.bytearray" {
-                    if let Some(bytearray_new) = self
+                    let (name, call_args): (_, Vec<BasicMetadataValueEnum>) = match args.first() {
+                        Some(bytes) => (
+                            "__pyc___builtin___bytearray_from_bytes",
+                            vec![self.codegen_expr(bytes, program).into()],
+                        ),
+                        None => ("__pyc___builtin___bytearray___init__", vec![]),
+                    };
+                    let null = self
                         .ctx
-                        .module
-                        .get_function("__pyc___builtin___bytearray___init__")
-                    {
-                        let call = self
-                            .ctx
-                            .builder
-                            .build_call(bytearray_new, &[], "bytearray")
-                            .unwrap();
-                        let default = self
-                            .ctx
-                            .context
-                            .ptr_type(Default::default())
-                            .const_null()
-                            .into();
-                        let ba_ptr = call_result_to_basic_value(call, default);
-
-                        // If there's a bytes argument, copy bytes into bytearray
-                        if !args.is_empty() {
-                            let bytes_ptr = self.codegen_expr(&args[0], program);
-
-                            // Get length using bytes_len(Bytes*)
-                            let bytes_len_fn = self
-                                .ctx
-                                .module
-                                .get_function("__pyc___builtin___bytes___len__")
-                                .expect("__pyc___builtin___bytes___len__ not declared");
-                            let len_call = self
-                                .ctx
-                                .builder
-                                .build_call(bytes_len_fn, &[bytes_ptr.into()], "len")
-                                .unwrap();
-                            let i64_type = self.ctx.context.i64_type();
-                            let len = call_result_to_basic_value(
-                                len_call,
-                                i64_type.const_int(0, false).into(),
-                            )
-                            .into_int_value();
-
-                            // Loop and append each byte using bytes_getitem
-                            if let Some(bytearray_append) = self
-                                .ctx
-                                .module
-                                .get_function("__pyc___builtin___bytearray_append")
-                            {
-                                if let Some(bytes_getitem) = self
-                                    .ctx
-                                    .module
-                                    .get_function("__pyc___builtin___bytes___getitem__")
-                                {
-                                    let zero = i64_type.const_int(0, false);
-                                    let one = i64_type.const_int(1, false);
-
-                                    // Create loop blocks
-                                    let current_fn = self.ctx.current_function.unwrap();
-                                    let loop_cond = self
-                                        .ctx
-                                        .context
-                                        .append_basic_block(current_fn, "ba_loop_cond");
-                                    let loop_body = self
-                                        .ctx
-                                        .context
-                                        .append_basic_block(current_fn, "ba_loop_body");
-                                    let loop_end = self
-                                        .ctx
-                                        .context
-                                        .append_basic_block(current_fn, "ba_loop_end");
-
-                                    // Allocate index
-                                    let idx_ptr =
-                                        self.ctx.builder.build_alloca(i64_type, "idx").unwrap();
-                                    self.ctx.builder.build_store(idx_ptr, zero).unwrap();
-                                    self.ctx
-                                        .builder
-                                        .build_unconditional_branch(loop_cond)
-                                        .unwrap();
-
-                                    // Loop condition
-                                    self.ctx.builder.position_at_end(loop_cond);
-                                    let idx = self
-                                        .ctx
-                                        .builder
-                                        .build_load(i64_type, idx_ptr, "idx_val")
-                                        .unwrap()
-                                        .into_int_value();
-                                    let cond = self
-                                        .ctx
-                                        .builder
-                                        .build_int_compare(
-                                            inkwell::IntPredicate::SLT,
-                                            idx,
-                                            len,
-                                            "cond",
-                                        )
-                                        .unwrap();
-                                    self.ctx
-                                        .builder
-                                        .build_conditional_branch(cond, loop_body, loop_end)
-                                        .unwrap();
-
-                                    // Loop body
-                                    self.ctx.builder.position_at_end(loop_body);
-                                    let call = self
-                                        .ctx
-                                        .builder
-                                        .build_call(
-                                            bytes_getitem,
-                                            &[bytes_ptr.into(), idx.into()],
-                                            "byte",
-                                        )
-                                        .unwrap();
-                                    let byte_val = call_result_to_basic_value(
-                                        call,
-                                        i64_type.const_int(0, false).into(),
-                                    );
-                                    self.ctx
-                                        .builder
-                                        .build_call(
-                                            bytearray_append,
-                                            &[ba_ptr.into(), byte_val.into()],
-                                            "",
-                                        )
-                                        .unwrap();
-                                    let next_idx = self
-                                        .ctx
-                                        .builder
-                                        .build_int_add(idx, one, "next_idx")
-                                        .unwrap();
-                                    self.ctx.builder.build_store(idx_ptr, next_idx).unwrap();
-                                    self.ctx
-                                        .builder
-                                        .build_unconditional_branch(loop_cond)
-                                        .unwrap();
-
-                                    // After loop
-                                    self.ctx.builder.position_at_end(loop_end);
-                                }
-                            }
-                        }
-
-                        return ba_ptr;
-                    } else {
-                        return self
-                            .ctx
-                            .context
-                            .ptr_type(Default::default())
-                            .const_null()
-                            .into();
-                    }
+                        .context
+                        .ptr_type(Default::default())
+                        .const_null()
+                        .into();
+                    let Some(func) = self.ctx.module.get_function(name) else {
+                        return null;
+                    };
+                    let call = self
+                        .ctx
+                        .builder
+                        .build_call(func, &call_args, "bytearray")
+                        .unwrap();
+                    return call_result_to_basic_value(call, null);
                 }
 
                 // Note: range() is now handled by TirExprKind::Range
@@ -379,6 +326,34 @@ impl<'ctx, 'a> FunctionGenContext<'ctx, 'a> {
                         .into();
                 }
 
+                // Handle set/frozenset builtin classes: a new set filled from the
+                // optional list/set argument
+                if class_def.qualified_name == "__builtin__.set"
+                    || class_def.qualified_name == "__builtin__.frozenset"
+                {
+                    let set_ptr = self.codegen_set_new(&class_def.type_params[0], program);
+                    if let Some(arg) = args.first() {
+                        let from_list = matches!(&arg.ty, TirType::Class(arg_class)
+                            if program.class(*arg_class).qualified_name == "__builtin__.list");
+                        let update_name = if from_list {
+                            "__pyc___builtin___set_update_from_list"
+                        } else {
+                            "__pyc___builtin___set_update"
+                        };
+                        let update_fn = self
+                            .ctx
+                            .module
+                            .get_function(update_name)
+                            .unwrap_or_else(|| panic!("{} function not declared", update_name));
+                        let iterable = self.codegen_expr(arg, program);
+                        self.ctx
+                            .builder
+                            .build_call(update_fn, &[set_ptr.into(), iterable.into()], "")
+                            .unwrap();
+                    }
+                    return set_ptr;
+                }
+
                 // Handle Exception builtin class
                 if class_def.qualified_name == "__builtin__.Exception" {
                     if let Some(exc_init) = self
@@ -484,12 +459,32 @@ impl<'ctx, 'a> FunctionGenContext<'ctx, 'a> {
                 let class_type = self.ctx.class_types[&class_def.qualified_name];
                 let size = class_type.size_of().unwrap();
 
-                // Call class_new runtime function
-                if let Some(class_new) = self.ctx.module.get_function("class_new") {
+                // Counted or traced instances come from the runtime's table,
+                // with the class's release function to run when they are freed
+                let counted = self.ctx.is_refcounted_class(*class, program);
+                let (new_fn, new_args): (_, Vec<BasicMetadataValueEnum>) =
+                    if self.ctx.is_managed_class(*class, program) {
+                        let release = match self
+                            .ctx
+                            .module
+                            .get_function(&rc_release_name(&class_def.qualified_name))
+                        {
+                            Some(release) => release.as_global_value().as_pointer_value(),
+                            None => self.ctx.context.ptr_type(Default::default()).const_null(),
+                        };
+                        (
+                            self.ctx.module.get_function("__pyc_rc_new"),
+                            vec![size.into(), release.into()],
+                        )
+                    } else {
+                        (self.ctx.module.get_function("class_new"), vec![size.into()])
+                    };
+
+                if let Some(class_new) = new_fn {
                     let call = self
                         .ctx
                         .builder
-                        .build_call(class_new, &[size.into()], "instance")
+                        .build_call(class_new, &new_args, "instance")
                         .unwrap();
                     let default = self
                         .ctx
@@ -498,6 +493,13 @@ impl<'ctx, 'a> FunctionGenContext<'ctx, 'a> {
                         .const_null()
                         .into();
                     let ptr = call_result_to_basic_value(call, default);
+                    self.store_vtable_pointer(ptr.into_pointer_value(), class_def);
+
+                    // Hold the instance while __init__ runs, so it survives
+                    // __init__ dropping its reference to self
+                    if counted {
+                        self.rc_call("__pyc_rc_incref", ptr);
+                    }
 
                     // Call __init__ if it exists
                     if let Some(init_func_id) = class_def.get_method("__init__") {
@@ -518,6 +520,9 @@ impl<'ctx, 'a> FunctionGenContext<'ctx, 'a> {
                                 .unwrap();
                         }
                     }
+                    if counted {
+                        self.rc_call("__pyc_rc_unown", ptr);
+                    }
 
                     ptr
                 } else {
@@ -596,30 +601,58 @@ impl<'ctx, 'a> FunctionGenContext<'ctx, 'a> {
                 // Convert to pointer if needed (e.g., from list_get which returns i64)
                 let obj_ptr = self.value_to_pointer(obj_val);
 
+                let index = self.ctx.field_index(*class, field.index(), program);
                 let field_ptr = self
                     .ctx
                     .builder
-                    .build_struct_gep(class_type, obj_ptr, field.index() as u32, "field_ptr")
+                    .build_struct_gep(class_type, obj_ptr, index, "field_ptr")
                     .unwrap();
 
                 let field_ty = self.ctx.tir_type_to_llvm(&expr.ty, program);
-                self.ctx
+                let value = self
+                    .ctx
                     .builder
                     .build_load(field_ty, field_ptr, "field")
-                    .unwrap()
+                    .unwrap();
+
+                // An instance just returned or constructed has no references, so
+                // free it once read, keeping a counted field value alive
+                let temporary = matches!(
+                    object.kind,
+                    TirExprKind::Call { .. }
+                        | TirExprKind::VirtualCall { .. }
+                        | TirExprKind::Construct { .. }
+                );
+                if temporary && self.ctx.is_refcounted(&object.ty, program) {
+                    let counted_field = self.ctx.is_refcounted(&expr.ty, program);
+                    if counted_field {
+                        self.rc_call("__pyc_rc_incref", value);
+                    }
+                    self.rc_call("__pyc_rc_release_unowned", obj_val);
+                    if counted_field {
+                        self.rc_call("__pyc_rc_unown", value);
+                    }
+                }
+                value
             }
 
-            TirExprKind::List {
-                elements,
-                elem_ty: _,
-            } => {
-                // Create a new list
+            TirExprKind::List { elements, elem_ty } => {
+                // Create a new list; the kind tells the runtime how to print the items
                 if let Some(list_new) = self
                     .ctx
                     .module
                     .get_function("__pyc___builtin___list___init__")
                 {
-                    let call = self.ctx.builder.build_call(list_new, &[], "list").unwrap();
+                    let kind = self
+                        .ctx
+                        .context
+                        .i64_type()
+                        .const_int(dict_kind(elem_ty, program), false);
+                    let call = self
+                        .ctx
+                        .builder
+                        .build_call(list_new, &[kind.into()], "list")
+                        .unwrap();
                     let default = self
                         .ctx
                         .context
@@ -627,24 +660,8 @@ impl<'ctx, 'a> FunctionGenContext<'ctx, 'a> {
                         .const_null()
                         .into();
                     let list_ptr = call_result_to_basic_value(call, default);
-
-                    // Append each element
-                    if let Some(list_append) = self
-                        .ctx
-                        .module
-                        .get_function("__pyc___builtin___list_append")
-                    {
-                        for elem in elements {
-                            let val = self.codegen_expr(elem, program);
-                            // Convert value to i64 for list storage
-                            let val_i64 = self.value_to_i64(val);
-                            self.ctx
-                                .builder
-                                .build_call(list_append, &[list_ptr.into(), val_i64.into()], "")
-                                .unwrap();
-                        }
-                    }
-
+                    let items = self.codegen_list_items(elements, elem_ty, program);
+                    self.append_list_items(list_ptr, items, elem_ty);
                     list_ptr
                 } else {
                     self.ctx
@@ -655,6 +672,292 @@ impl<'ctx, 'a> FunctionGenContext<'ctx, 'a> {
                 }
             }
 
+            TirExprKind::Dict {
+                entries,
+                key_ty,
+                value_ty,
+            } => {
+                // Create a new dict; the kinds tell the runtime how to hash and print entries
+                let i64_type = self.ctx.context.i64_type();
+                let dict_new = self
+                    .ctx
+                    .module
+                    .get_function("__pyc___builtin___dict___init__")
+                    .unwrap_or_else(|| panic!("dict.__init__ function not declared"));
+                let key_kind = i64_type.const_int(dict_kind(key_ty, program), false);
+                let value_kind = i64_type.const_int(dict_kind(value_ty, program), false);
+                let call = self
+                    .ctx
+                    .builder
+                    .build_call(dict_new, &[key_kind.into(), value_kind.into()], "dict")
+                    .unwrap();
+                let default = self
+                    .ctx
+                    .context
+                    .ptr_type(Default::default())
+                    .const_null()
+                    .into();
+                let dict_ptr = call_result_to_basic_value(call, default);
+
+                // Insert each entry in source order (later duplicates overwrite earlier ones)
+                let dict_setitem = self
+                    .ctx
+                    .module
+                    .get_function("__pyc___builtin___dict___setitem__")
+                    .unwrap_or_else(|| panic!("dict.__setitem__ function not declared"));
+                for (key, value) in entries {
+                    let key_val = self.codegen_expr(key, program);
+                    let key_i64 = self.value_to_i64(key_val);
+                    let value_val = self.codegen_expr(value, program);
+                    let value_i64 = self.value_to_i64(value_val);
+                    self.ctx
+                        .builder
+                        .build_call(
+                            dict_setitem,
+                            &[dict_ptr.into(), key_i64.into(), value_i64.into()],
+                            "",
+                        )
+                        .unwrap();
+                }
+
+                dict_ptr
+            }
+
+            TirExprKind::Set { elements, elem_ty } => {
+                let set_ptr = self.codegen_set_new(elem_ty, program);
+                let set_add = self
+                    .ctx
+                    .module
+                    .get_function("__pyc___builtin___set_add")
+                    .unwrap_or_else(|| panic!("set.add function not declared"));
+                for elem in elements {
+                    let val = self.codegen_expr(elem, program);
+                    // Convert value to i64 for set storage
+                    let val_i64 = self.value_to_i64(val);
+                    self.ctx
+                        .builder
+                        .build_call(set_add, &[set_ptr.into(), val_i64.into()], "")
+                        .unwrap();
+                }
+                set_ptr
+            }
+
+            TirExprKind::Tuple { elements } => {
+                let TirType::Tuple(elem_types) = &expr.ty else {
+                    panic!("Tuple literal with non-tuple type {:?}", expr.ty);
+                };
+                let tuple_ptr = self.codegen_tuple_new(elem_types, program);
+                let tuple_set = self
+                    .ctx
+                    .module
+                    .get_function("__pyc_tuple_set")
+                    .unwrap_or_else(|| panic!("__pyc_tuple_set function not declared"));
+                let i64_type = self.ctx.context.i64_type();
+                for (i, elem) in elements.iter().enumerate() {
+                    let val = self.codegen_expr(elem, program);
+                    // Tuple elements are stored in i64 slots
+                    let val_i64 = self.value_to_i64(val);
+                    self.ctx
+                        .builder
+                        .build_call(
+                            tuple_set,
+                            &[
+                                tuple_ptr.into(),
+                                i64_type.const_int(i as u64, false).into(),
+                                val_i64.into(),
+                            ],
+                            "",
+                        )
+                        .unwrap();
+                }
+                tuple_ptr
+            }
+
+            TirExprKind::TupleGet { tuple, index } => {
+                let tuple_ptr = self.codegen_expr(tuple, program);
+                let tuple_getitem = self
+                    .ctx
+                    .module
+                    .get_function("__pyc___builtin___tuple___getitem__")
+                    .unwrap_or_else(|| panic!("tuple.__getitem__ function not declared"));
+                let index_val = self.ctx.context.i64_type().const_int(*index as u64, false);
+                let call = self
+                    .ctx
+                    .builder
+                    .build_call(
+                        tuple_getitem,
+                        &[tuple_ptr.into(), index_val.into()],
+                        "tuple_item",
+                    )
+                    .unwrap();
+                let default = self.ctx.context.i64_type().const_int(0, false).into();
+                let slot = call_result_to_basic_value(call, default).into_int_value();
+                self.i64_slot_to_value(slot, &expr.ty)
+            }
+
+            TirExprKind::MathIntrinsic { func, args } => {
+                let float_args: Vec<_> = args
+                    .iter()
+                    .map(|arg| {
+                        let value = self.codegen_expr(arg, program);
+                        self.convert_to_float(value)
+                    })
+                    .collect();
+                self.codegen_math_intrinsic(*func, &float_args).into()
+            }
+
+            TirExprKind::LlvmIntrinsic { name, args } => {
+                // Lowering checked that the intrinsic exists
+                let intrinsic = declare_llvm_intrinsic(&self.ctx.module, name).unwrap();
+                let i1_type = self.ctx.context.bool_type();
+                let i8_type = self.ctx.context.i8_type();
+
+                // Bools are i8, which an i1 parameter takes truncated
+                let call_args: Vec<BasicMetadataValueEnum> = args
+                    .iter()
+                    .map(|arg| {
+                        let value = self.codegen_expr(arg, program);
+                        if arg.ty == TirType::Bool {
+                            self.ctx
+                                .builder
+                                .build_int_truncate(value.into_int_value(), i1_type, "to_i1")
+                                .unwrap()
+                                .into()
+                        } else {
+                            value.into()
+                        }
+                    })
+                    .collect();
+                let call = self
+                    .ctx
+                    .builder
+                    .build_call(intrinsic, &call_args, "intrinsic")
+                    .unwrap();
+
+                let default = self.ctx.context.i64_type().const_zero().into();
+                let result = call_result_to_basic_value(call, default);
+                if expr.ty == TirType::Bool {
+                    self.ctx
+                        .builder
+                        .build_int_z_extend(result.into_int_value(), i8_type, "to_i8")
+                        .unwrap()
+                        .into()
+                } else {
+                    result
+                }
+            }
+
+            TirExprKind::Slice {
+                func,
+                object,
+                start,
+                stop,
+                step,
+            } => {
+                let runtime_name = program
+                    .function(*func)
+                    .runtime_name
+                    .as_ref()
+                    .unwrap_or_else(|| panic!("__slice__ must be a runtime function"));
+                let slice_fn = self
+                    .ctx
+                    .module
+                    .get_function(runtime_name)
+                    .unwrap_or_else(|| panic!("Runtime function {} not found", runtime_name));
+
+                let object_val = self.codegen_expr(object, program);
+                let mut call_args = vec![object_val.into()];
+                for (bound, missing) in [(start, i64::MIN), (stop, i64::MIN), (step, 1)] {
+                    let bound_val = match bound {
+                        Some(bound_expr) => self.codegen_expr(bound_expr, program),
+                        // SLICE_NONE lets the runtime pick the default bound;
+                        // a missing step is 1
+                        None => self
+                            .ctx
+                            .context
+                            .i64_type()
+                            .const_int(missing as u64, false)
+                            .into(),
+                    };
+                    call_args.push(bound_val.into());
+                }
+
+                let call = self
+                    .ctx
+                    .builder
+                    .build_call(slice_fn, &call_args, "slice")
+                    .unwrap();
+                let default = self
+                    .ctx
+                    .context
+                    .ptr_type(Default::default())
+                    .const_null()
+                    .into();
+                let result = call_result_to_basic_value(call, default);
+                if self.is_rc_temporary(object, program) {
+                    let result_counted = self.ctx.is_refcounted(&expr.ty, program);
+                    self.rc_release_arguments(&[object_val], result, result_counted);
+                }
+                result
+            }
+
+            TirExprKind::Block { stmts, result } => {
+                // The enclosing statement's temporaries are still live
+                self.expr_block_depth += 1;
+                for stmt in stmts {
+                    self.codegen_stmt(stmt, program);
+                }
+                self.expr_block_depth -= 1;
+                self.codegen_expr(result, program)
+            }
+
+            TirExprKind::WrapOptional { value } => {
+                let val = self.codegen_expr(value, program);
+                if !is_boxed_in_optional(&value.ty) {
+                    return val;
+                }
+                // Box the value in an i64 slot, so that None can be the null pointer
+                let class_new = self
+                    .ctx
+                    .module
+                    .get_function("class_new")
+                    .expect("class_new function not declared");
+                let slot_size = self.ctx.context.i64_type().size_of();
+                let call = self
+                    .ctx
+                    .builder
+                    .build_call(class_new, &[slot_size.into()], "optional_box")
+                    .unwrap();
+                let default = self
+                    .ctx
+                    .context
+                    .ptr_type(Default::default())
+                    .const_null()
+                    .into();
+                let box_ptr = call_result_to_basic_value(call, default).into_pointer_value();
+                let slot = self.value_to_i64(val);
+                self.ctx.builder.build_store(box_ptr, slot).unwrap();
+                box_ptr.into()
+            }
+
+            TirExprKind::UnwrapOptional { value } => {
+                let val = self.codegen_expr(value, program);
+                if !is_boxed_in_optional(&expr.ty) {
+                    return val;
+                }
+                let slot = self
+                    .ctx
+                    .builder
+                    .build_load(
+                        self.ctx.context.i64_type(),
+                        val.into_pointer_value(),
+                        "optional_slot",
+                    )
+                    .unwrap()
+                    .into_int_value();
+                self.i64_slot_to_value(slot, &expr.ty)
+            }
+
             TirExprKind::Bytes { data } => {
                 // Create a static Bytes struct: { i64 len, [N x i8] data }
                 // This matches the C Bytes struct layout with flexible array member
@@ -778,6 +1081,123 @@ impl<'ctx, 'a> FunctionGenContext<'ctx, 'a> {
         }
     }
 
+    /// Create an empty set whose elements are hashed according to `elem_ty`
+    fn codegen_set_new(&mut self, elem_ty: &TirType, program: &TirProgram) -> BasicValueEnum<'ctx> {
+        let set_new = self
+            .ctx
+            .module
+            .get_function("__pyc___builtin___set___init__")
+            .unwrap_or_else(|| panic!("set.__init__ function not declared"));
+        let elem_kind = self
+            .ctx
+            .context
+            .i64_type()
+            .const_int(dict_kind(elem_ty, program), false);
+        let call = self
+            .ctx
+            .builder
+            .build_call(set_new, &[elem_kind.into()], "set")
+            .unwrap();
+        let default = self
+            .ctx
+            .context
+            .ptr_type(Default::default())
+            .const_null()
+            .into();
+        call_result_to_basic_value(call, default)
+    }
+
+    /// Evaluate the elements of a list literal, as the list's items:
+    /// floats and bools stay as they are, anything else becomes an i64 slot
+    pub(crate) fn codegen_list_items(
+        &mut self,
+        elements: &[TirExpr],
+        elem_ty: &TirType,
+        program: &TirProgram,
+    ) -> Vec<BasicValueEnum<'ctx>> {
+        let mut items = Vec::with_capacity(elements.len());
+        for elem in elements {
+            let val = self.codegen_expr(elem, program);
+            items.push(match elem_ty {
+                TirType::Float => self.convert_to_float(val).into(),
+                TirType::Bool => val,
+                _ => self.value_to_i64(val).into(),
+            });
+        }
+        items
+    }
+
+    /// Append items from `codegen_list_items` to a list, with the typed
+    /// append for floats and bools
+    pub(crate) fn append_list_items(
+        &mut self,
+        list_ptr: BasicValueEnum<'ctx>,
+        items: Vec<BasicValueEnum<'ctx>>,
+        elem_ty: &TirType,
+    ) {
+        let append_name = format!(
+            "__pyc___builtin___list{}_append",
+            elem_ty.list_item_suffix()
+        );
+        let Some(list_append) = self.ctx.module.get_function(&append_name) else {
+            return;
+        };
+        for item in items {
+            self.ctx
+                .builder
+                .build_call(list_append, &[list_ptr.into(), item.into()], "")
+                .unwrap();
+        }
+    }
+
+    /// Allocate a tuple of the given element types; the runtime keeps a pointer to a
+    /// constant table of their `DICT_KIND_*` values so it can print and hash the tuple
+    fn codegen_tuple_new(
+        &mut self,
+        elem_types: &[TirType],
+        program: &TirProgram,
+    ) -> BasicValueEnum<'ctx> {
+        let i8_type = self.ctx.context.i8_type();
+        let i64_type = self.ctx.context.i64_type();
+        let kinds: Vec<_> = elem_types
+            .iter()
+            .map(|ty| i8_type.const_int(dict_kind(ty, program), false))
+            .collect();
+        let kinds_array = i8_type.const_array(&kinds);
+        let kinds_global = self
+            .ctx
+            .module
+            .add_global(kinds_array.get_type(), None, "tuple_kinds");
+        kinds_global.set_initializer(&kinds_array);
+        kinds_global.set_constant(true);
+        kinds_global.set_linkage(inkwell::module::Linkage::Private);
+
+        let tuple_new = self
+            .ctx
+            .module
+            .get_function("__pyc___builtin___tuple___init__")
+            .unwrap_or_else(|| panic!("tuple.__init__ function not declared"));
+        let call = self
+            .ctx
+            .builder
+            .build_call(
+                tuple_new,
+                &[
+                    i64_type.const_int(elem_types.len() as u64, false).into(),
+                    kinds_global.as_pointer_value().into(),
+                ],
+                "tuple",
+            )
+            .unwrap();
+        let default = self
+            .ctx
+            .context
+            .ptr_type(Default::default())
+            .const_null()
+            .into();
+        call_result_to_basic_value(call, default)
+    }
+
     /// Create a string constant and return a pointer to it
     /// Creates a String struct matching the C layout: { i64 len, i32 cp_count, i16 flags, char[] data }
     fn create_string_constant(&mut self, s: &str) -> inkwell::values::BasicValueEnum<'ctx> {
@@ -848,3 +1268,30 @@ impl<'ctx, 'a> FunctionGenContext<'ctx, 'a> {
         global.as_pointer_value().into()
     }
 }
+
+/// Runtime `DICT_KIND_*` constant (runtime.h) for a dict key/value, set element,
+/// tuple element or list item type
+pub(super) fn dict_kind(ty: &TirType, program: &TirProgram) -> u64 {
+    match ty {
+        TirType::Int => 0,
+        TirType::Float => 1,
+        TirType::Bool => 2,
+        TirType::Class(class_id) => match program.class(*class_id).qualified_name.as_str() {
+            "__builtin__.str" => 3,
+            "__builtin__.bytes" => 4,
+            "__builtin__.list" => 7,
+            "__builtin__.dict" => 8,
+            "__builtin__.set" => 9,
+            "__builtin__.frozenset" => 10,
+            _ => 5,
+        },
+        TirType::Tuple(_) => 6,
+        _ => 5,
+    }
+}
+
+/// Whether the value of an Optional of type `ty` is boxed: int, float and bool
+/// values are, while other values are pointers that cannot be null
+fn is_boxed_in_optional(ty: &TirType) -> bool {
+    matches!(ty, TirType::Int | TirType::Float | TirType::Bool)
+}
@@ -0,0 +1,108 @@
+//! C functions declared with `@extern`
+//!
+//! ```python
+//! @extern("puts")
+//! def puts(s: str) -> int: ...
+//! ```
+//!
+//! declares the C function `puts`, which calls of `puts` then call
+//! directly. An int is passed as an `int64_t`, a float as a `double` and a
+//! bool as a `bool`; a str is passed as a `const char*` to its
+//! NUL-terminated UTF-8 data, and bytes as a `const uint8_t*` to its data.
+
+use inkwell::types::BasicMetadataTypeEnum;
+use inkwell::values::BasicValueEnum;
+
+use crate::codegen::context::CodegenContext;
+use crate::error::{CompilerError, Result};
+use crate::tir::{TirProgram, TirType};
+
+use super::function_gen::FunctionGenContext;
+
+impl<'ctx> CodegenContext<'ctx> {
+    /// Declare the C function of each extern function, unless the runtime
+    /// (or another extern function) already declares it with the same
+    /// signature
+    pub(crate) fn declare_tir_externs(&mut self, program: &TirProgram) -> Result<()> {
+        for &func_id in &program.externs {
+            let func = program.function(func_id);
+            let c_name = func.runtime_name.as_deref().unwrap();
+
+            // Strs and bytes are pointers, whether to their headers or
+            // their data
+            let param_types: Vec<BasicMetadataTypeEnum> = func
+                .params
+                .iter()
+                .map(|(_, ty)| self.tir_type_to_llvm(ty, program).into())
+                .collect();
+            let fn_type = if func.return_type == TirType::Void {
+                self.context.void_type().fn_type(&param_types, false)
+            } else {
+                self.tir_type_to_llvm(&func.return_type, program)
+                    .fn_type(&param_types, false)
+            };
+
+            match self.module.get_function(c_name) {
+                None => {
+                    self.module.add_function(c_name, fn_type, None);
+                }
+                Some(declared) if declared.get_type() == fn_type => {}
+                Some(_) => {
+                    return Err(CompilerError::CodegenError(format!(
+                        "@extern(\"{}\") on '{}' does not match an earlier declaration of {}",
+                        c_name, func.name, c_name
+                    )))
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'ctx, 'a> FunctionGenContext<'ctx, 'a> {
+    /// The value a C function receives for an argument of type `ty`: the
+    /// data of a str or bytes, and anything else as it is
+    pub(crate) fn c_argument(
+        &self,
+        value: BasicValueEnum<'ctx>,
+        ty: &TirType,
+        program: &TirProgram,
+    ) -> BasicValueEnum<'ctx> {
+        let TirType::Class(class_id) = ty else {
+            return value;
+        };
+        let i64_type = self.ctx.context.i64_type();
+        let i32_type = self.ctx.context.i32_type();
+        let i16_type = self.ctx.context.i16_type();
+        let data_type = self.ctx.context.i8_type().array_type(0);
+
+        // The headers of String { i64 len, i32 cp_count, i16 flags, data }
+        // and Bytes { i64 len, data } (runtime/src/str.h and bytes.h)
+        let (header, data_field) = match program.class(*class_id).qualified_name.as_str() {
+            "__builtin__.str" => (
+                self.ctx.context.struct_type(
+                    &[
+                        i64_type.into(),
+                        i32_type.into(),
+                        i16_type.into(),
+                        data_type.into(),
+                    ],
+                    false,
+                ),
+                3,
+            ),
+            "__builtin__.bytes" => (
+                self.ctx
+                    .context
+                    .struct_type(&[i64_type.into(), data_type.into()], false),
+                1,
+            ),
+            _ => return value,
+        };
+        self.ctx
+            .builder
+            .build_struct_gep(header, value.into_pointer_value(), data_field, "data")
+            .unwrap()
+            .into()
+    }
+}
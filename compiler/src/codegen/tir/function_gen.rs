@@ -1,9 +1,13 @@
 use inkwell::types::BasicTypeEnum;
 use inkwell::values::{BasicValueEnum, PointerValue};
+use inkwell::AddressSpace;
 
 use crate::codegen::context::CodegenContext;
 use crate::tir::decls::TirFunction;
-use crate::tir::{TirModule, TirProgram, TirType};
+use crate::tir::stmt::TirStmt;
+use crate::tir::{LocalId, TirModule, TirProgram, TirType};
+
+use super::exceptions::{FinallyReturn, TryScope};
 
 pub(crate) struct FunctionGenContext<'ctx, 'a> {
     /// The codegen context
@@ -14,10 +18,54 @@ pub(crate) struct FunctionGenContext<'ctx, 'a> {
 
     /// Parameters as values (not pointers)
     pub(crate) params: Vec<BasicValueEnum<'ctx>>,
+
+    /// Local slots holding counted references (pycc --refcount)
+    pub(crate) rc_locals: Vec<PointerValue<'ctx>>,
+
+    /// Parameters (and `self`) holding counted references until the return
+    pub(crate) rc_params: Vec<BasicValueEnum<'ctx>>,
+
+    /// Nesting of block expressions being generated; their statements do
+    /// not end the enclosing statement's temporaries (pycc --gc=tracing)
+    pub(crate) expr_block_depth: usize,
+
+    /// Slots of the locals whose instances and lists live on the stack,
+    /// indexed by LocalId (see stack_alloc.rs)
+    pub(crate) stack_slots: Vec<Option<PointerValue<'ctx>>>,
+
+    /// Exception frames pushed by the try blocks being generated, which a
+    /// return pops (see exceptions.rs)
+    pub(crate) exception_frames: usize,
+
+    /// The exception being handled as each try block being generated
+    /// started, which it restores as it ends (see exceptions.rs)
+    pub(crate) handled_exceptions: Vec<PointerValue<'ctx>>,
+
+    /// The try blocks being generated, up to their finally blocks (see
+    /// exceptions.rs)
+    pub(crate) try_scopes: Vec<TryScope<'ctx>>,
+
+    /// The locals of a function returning through finally blocks
+    pub(crate) finally_return: Option<FinallyReturn>,
+
+    /// Whether the locals were kept out of registers for setjmp
+    pub(crate) locals_in_memory: bool,
+
+    /// The function's record on the traceback stack (see traceback.rs)
+    pub(crate) traceback_record: Option<PointerValue<'ctx>>,
+
+    /// Whether the function counts itself on the recursion depth (see
+    /// recursion.rs)
+    pub(crate) recursion_guard: bool,
 }
 
 impl<'ctx> CodegenContext<'ctx> {
-    pub(crate) fn codegen_tir_function(&mut self, func: &TirFunction, program: &TirProgram) {
+    pub(crate) fn codegen_tir_function(
+        &mut self,
+        func: &TirFunction,
+        program: &TirProgram,
+        stack_locals: &[LocalId],
+    ) {
         // Skip runtime functions - they have no body to codegen
         if func.runtime_name.is_some() {
             return;
@@ -37,13 +85,39 @@ impl<'ctx> CodegenContext<'ctx> {
             self.current_class = Some(class.qualified_name.clone());
         }
 
+        // Functions lowered from a `def` are described in the debug info
+        let self_type = func.class.map(TirType::Class);
+        if let Some((module, line)) = func.location {
+            self.begin_debug_function(
+                fn_value,
+                &func.qualified_name,
+                module,
+                line,
+                self_type.as_ref(),
+                &func.params,
+                &func.return_type,
+                program,
+            );
+        }
+
+        // A return leaving a try block through its finally block keeps its
+        // value, and that it is returning, in locals of its own
+        let mut local_types = func.locals.clone();
+        let finally_return = func
+            .body
+            .iter()
+            .any(TirStmt::returns_through_finally)
+            .then(|| FinallyReturn::add_locals(&mut local_types, &func.return_type));
+
         // Allocate local variables
         let mut locals: Vec<(PointerValue<'ctx>, BasicTypeEnum<'ctx>)> = Vec::new();
-        for (name, ty) in &func.locals {
+        for (name, ty) in &local_types {
             let llvm_ty = self.tir_type_to_llvm(ty, program);
             let ptr = self.builder.build_alloca(llvm_ty, name).unwrap();
+            self.debug_local(ptr, name, ty, program);
             locals.push((ptr, llvm_ty));
         }
+        let stack_slots = self.stack_slots(stack_locals, &func.locals, program);
 
         // Collect parameters
         let mut params: Vec<BasicValueEnum<'ctx>> = Vec::new();
@@ -54,11 +128,35 @@ impl<'ctx> CodegenContext<'ctx> {
         }
 
         // Generate body
+        let self_counted = func
+            .class
+            .is_some_and(|class_id| self.is_refcounted_class(class_id, program));
+        let self_managed = func
+            .class
+            .is_some_and(|class_id| self.is_managed_class(class_id, program));
         let mut fn_ctx = FunctionGenContext {
             ctx: self,
             locals,
             params,
+            rc_locals: Vec::new(),
+            rc_params: Vec::new(),
+            expr_block_depth: 0,
+            stack_slots,
+            exception_frames: 0,
+            handled_exceptions: Vec::new(),
+            try_scopes: Vec::new(),
+            finally_return,
+            locals_in_memory: false,
+            traceback_record: None,
+            recursion_guard: false,
         };
+        fn_ctx.rc_begin(&local_types, &func.params, self_counted, program);
+        fn_ctx.gc_begin(&local_types, &func.params, self_managed, program);
+        fn_ctx.set_returning(false);
+        if let Some((module, _)) = func.location {
+            fn_ctx.recursion_begin();
+            fn_ctx.traceback_begin(&func.name, module, program);
+        }
 
         for stmt in &func.body {
             fn_ctx.codegen_stmt(stmt, program);
@@ -67,9 +165,13 @@ impl<'ctx> CodegenContext<'ctx> {
         // Only void functions need implicit return terminators.
         // Non-void functions must have explicit returns on all paths (validated during TIR lowering).
         if func.return_type == TirType::Void {
+            fn_ctx.end_scope_at_missing_returns();
             self.add_missing_terminators();
         }
 
+        self.debug_params(fn_value, self_type.as_ref(), &func.params, program);
+        self.end_debug_function();
+
         self.current_function = None;
         self.current_class = None;
     }
@@ -84,6 +186,16 @@ impl<'ctx> CodegenContext<'ctx> {
 
         let entry = self.context.append_basic_block(function, "entry");
         self.builder.position_at_end(entry);
+        self.begin_debug_function(
+            function,
+            "<module>",
+            module.id,
+            1,
+            None,
+            &[],
+            &TirType::Void,
+            program,
+        );
 
         // Globals are already declared in declare_tir_module_globals
 
@@ -92,6 +204,7 @@ impl<'ctx> CodegenContext<'ctx> {
         for (name, ty) in &module.init_locals {
             let llvm_ty = self.tir_type_to_llvm(ty, program);
             let ptr = self.builder.build_alloca(llvm_ty, name).unwrap();
+            self.debug_local(ptr, name, ty, program);
             locals.push((ptr, llvm_ty));
         }
 
@@ -99,7 +212,21 @@ impl<'ctx> CodegenContext<'ctx> {
             ctx: self,
             locals,
             params: Vec::new(),
+            rc_locals: Vec::new(),
+            rc_params: Vec::new(),
+            expr_block_depth: 0,
+            stack_slots: Vec::new(),
+            exception_frames: 0,
+            handled_exceptions: Vec::new(),
+            try_scopes: Vec::new(),
+            finally_return: None,
+            locals_in_memory: false,
+            traceback_record: None,
+            recursion_guard: false,
         };
+        fn_ctx.rc_begin(&module.init_locals, &[], false, program);
+        fn_ctx.gc_begin(&module.init_locals, &[], false, program);
+        fn_ctx.traceback_begin("<module>", module.id, program);
 
         for stmt in &module.init_body {
             fn_ctx.codegen_stmt(stmt, program);
@@ -107,22 +234,86 @@ impl<'ctx> CodegenContext<'ctx> {
 
         // Only add return if the current block doesn't already have a terminator
         // (e.g., if the last statement was a raise/unreachable)
-        if let Some(current_block) = self.builder.get_insert_block() {
+        if let Some(current_block) = fn_ctx.ctx.builder.get_insert_block() {
             if current_block.get_terminator().is_none() {
-                self.builder.build_return(None).unwrap();
+                fn_ctx.rc_end_scope();
+                fn_ctx.gc_end_scope(None);
+                fn_ctx.traceback_end();
+                fn_ctx.ctx.builder.build_return(None).unwrap();
             }
         }
+        self.end_debug_function();
         self.current_function = None;
     }
 
     pub(crate) fn generate_tir_main(&mut self, program: &TirProgram) {
         let i32_type = self.context.i32_type();
-        let fn_type = i32_type.fn_type(&[], false);
-        let function = self.module.add_function("main", fn_type, None);
+        let ptr_type = self.context.ptr_type(AddressSpace::default());
+        let fn_type = i32_type.fn_type(&[i32_type.into(), ptr_type.into()], false);
+        let function = self.module.add_function(&self.main_name, fn_type, None);
 
         let entry = self.context.append_basic_block(function, "entry");
         self.builder.position_at_end(entry);
 
+        let argc = function.get_nth_param(0).unwrap();
+        let argv = function.get_nth_param(1).unwrap();
+        self.build_program_start(argc, argv, program);
+
+        // Drop the globals' references; any object still alive has leaked
+        if self.leak_check {
+            let ptr_type = self.context.ptr_type(AddressSpace::default());
+            for module in &program.modules {
+                for global in &module.globals {
+                    if !self.is_refcounted(&global.ty, program) {
+                        continue;
+                    }
+                    let key = format!("{}::{}", module.name, global.name);
+                    let ptr = self.global_variables[&key];
+                    let value = self
+                        .builder
+                        .build_load(ptr_type, ptr, &global.name)
+                        .unwrap()
+                        .into_pointer_value();
+                    self.build_rc_call("__pyc_rc_decref", value);
+                }
+            }
+            // The collector frees what counting cannot: cycles
+            let collect = self
+                .module
+                .get_function("__pyc_gc_collect_at_exit")
+                .unwrap();
+            self.builder.build_call(collect, &[], "").unwrap();
+            let leak_check = self.module.get_function("__pyc_rc_leak_check").unwrap();
+            self.builder.build_call(leak_check, &[], "").unwrap();
+        }
+
+        // Return 0
+        let zero = i32_type.const_int(0, false);
+        self.builder.build_return(Some(&zero)).unwrap();
+    }
+
+    /// Run the program: hand argc/argv to the runtime, then initialize
+    /// every module (main, or a shared library's constructor)
+    pub(crate) fn build_program_start(
+        &mut self,
+        argc: BasicValueEnum<'ctx>,
+        argv: BasicValueEnum<'ctx>,
+        program: &TirProgram,
+    ) {
+        // Hand argc/argv to the runtime for sys.argv
+        let sys_init = self.module.get_function("__pyc_sys_init").unwrap();
+        self.builder
+            .build_call(sys_init, &[argc.into(), argv.into()], "")
+            .unwrap();
+
+        // Start the tracing collector before anything is allocated
+        self.gc_init(program);
+
+        self.build_module_inits(program);
+    }
+
+    /// Call every module's init function
+    pub(crate) fn build_module_inits(&mut self, program: &TirProgram) {
         // Call all module init functions in order (they are already sorted by dependency)
         // This ensures globals are initialized before any function tries to use them
         for module in &program.modules {
@@ -131,10 +322,6 @@ impl<'ctx> CodegenContext<'ctx> {
                 self.builder.build_call(module_init, &[], "").unwrap();
             }
         }
-
-        // Return 0
-        let zero = i32_type.const_int(0, false);
-        self.builder.build_return(Some(&zero)).unwrap();
     }
 
     /// Add implicit return terminators to basic blocks that don't have one.
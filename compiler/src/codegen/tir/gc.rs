@@ -0,0 +1,168 @@
+//! Shadow stack for the tracing collector (`pycc --gc=tracing`)
+//!
+//! The runtime's mark-sweep collector (runtime/src/gc.c) cannot see LLVM
+//! registers, so codegen tells it where instances are held:
+//!
+//! - every function pushes a frame with the addresses of its instance locals,
+//!   and of stack slots holding its instance parameters and `self`, and pops
+//!   it on every return, handing its result to the caller
+//! - instance globals are registered once in main (or a static library's
+//!   initializer)
+//! - instances allocated during a statement stay in the runtime's nursery
+//!   until the next statement of the same function starts, which covers
+//!   temporaries such as arguments being evaluated
+
+use inkwell::values::BasicValueEnum;
+use inkwell::AddressSpace;
+
+use crate::codegen::context::CodegenContext;
+use crate::tir::{TirProgram, TirType};
+
+use super::function_gen::FunctionGenContext;
+
+impl<'ctx> CodegenContext<'ctx> {
+    /// Turn the collector on and register the instance globals (in main,
+    /// before the module inits run)
+    pub(crate) fn gc_init(&mut self, program: &TirProgram) {
+        if !self.tracing_gc {
+            return;
+        }
+        let init = self.module.get_function("__pyc_gc_init").unwrap();
+        self.builder.build_call(init, &[], "").unwrap();
+        self.gc_add_global_roots(program);
+    }
+
+    /// Register the instance globals with the collector (in a static
+    /// library's initializer, whose program has already started it)
+    pub(crate) fn gc_add_global_roots(&mut self, program: &TirProgram) {
+        if !self.tracing_gc {
+            return;
+        }
+        let add_root = self.module.get_function("__pyc_gc_add_root").unwrap();
+        for module in &program.modules {
+            for global in &module.globals {
+                if !self.is_managed(&global.ty, program) {
+                    continue;
+                }
+                let key = format!("{}::{}", module.name, global.name);
+                let slot = self.global_variables[&key];
+                self.builder
+                    .build_call(add_root, &[slot.into()], "")
+                    .unwrap();
+            }
+        }
+    }
+}
+
+impl<'ctx, 'a> FunctionGenContext<'ctx, 'a> {
+    /// Push the function's frame: its instance locals start out as None, and
+    /// parameters are copied to stack slots the collector can read
+    pub(crate) fn gc_begin(
+        &mut self,
+        local_types: &[(String, TirType)],
+        param_types: &[(String, TirType)],
+        self_managed: bool,
+        program: &TirProgram,
+    ) {
+        if !self.ctx.tracing_gc {
+            return;
+        }
+        let ptr_type = self.ctx.context.ptr_type(AddressSpace::default());
+
+        let mut roots = Vec::new();
+        for (i, (_, ty)) in local_types.iter().enumerate() {
+            if self.ctx.is_managed(ty, program) {
+                let (ptr, _) = self.locals[i];
+                self.ctx
+                    .builder
+                    .build_store(ptr, ptr_type.const_null())
+                    .unwrap();
+                roots.push(ptr);
+            }
+        }
+        let mut param_values = Vec::new();
+        if self_managed {
+            let self_value = self
+                .ctx
+                .current_function
+                .unwrap()
+                .get_first_param()
+                .unwrap();
+            param_values.push(self_value);
+        }
+        for (i, (_, ty)) in param_types.iter().enumerate() {
+            if self.ctx.is_managed(ty, program) {
+                param_values.push(self.params[i]);
+            }
+        }
+        for value in param_values {
+            let slot = self.ctx.builder.build_alloca(ptr_type, "gc_param").unwrap();
+            self.ctx.builder.build_store(slot, value).unwrap();
+            roots.push(slot);
+        }
+
+        let i64_type = self.ctx.context.i64_type();
+        let frame_type = ptr_type.array_type(roots.len() as u32);
+        let frame = self
+            .ctx
+            .builder
+            .build_alloca(frame_type, "gc_frame")
+            .unwrap();
+        for (i, root) in roots.iter().enumerate() {
+            let index = i64_type.const_int(i as u64, false);
+            let entry = unsafe {
+                self.ctx
+                    .builder
+                    .build_gep(
+                        frame_type,
+                        frame,
+                        &[i64_type.const_zero(), index],
+                        "gc_root",
+                    )
+                    .unwrap()
+            };
+            self.ctx.builder.build_store(entry, *root).unwrap();
+        }
+        let push = self.ctx.module.get_function("__pyc_gc_push_frame").unwrap();
+        let count = i64_type.const_int(roots.len() as u64, false);
+        self.ctx
+            .builder
+            .build_call(push, &[frame.into(), count.into()], "")
+            .unwrap();
+    }
+
+    /// Pop the function's frame before a return; a returned instance stays
+    /// in the caller's nursery
+    pub(crate) fn gc_end_scope(&mut self, result: Option<BasicValueEnum<'ctx>>) {
+        if !self.ctx.tracing_gc {
+            return;
+        }
+        let result = match result {
+            Some(value) => self.value_to_pointer(value),
+            None => self
+                .ctx
+                .context
+                .ptr_type(AddressSpace::default())
+                .const_null(),
+        };
+        let pop = self.ctx.module.get_function("__pyc_gc_pop_frame").unwrap();
+        self.ctx
+            .builder
+            .build_call(pop, &[result.into()], "")
+            .unwrap();
+    }
+
+    /// A statement starts: the previous statements' temporaries are dead.
+    /// Statements inside an expression (a block) leave them alone.
+    pub(crate) fn gc_statement_boundary(&mut self) {
+        if !self.ctx.tracing_gc || self.expr_block_depth > 0 {
+            return;
+        }
+        let end_statement = self
+            .ctx
+            .module
+            .get_function("__pyc_gc_end_statement")
+            .unwrap();
+        self.ctx.builder.build_call(end_statement, &[], "").unwrap();
+    }
+}
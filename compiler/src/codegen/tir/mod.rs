@@ -1,8 +1,18 @@
 // TIR-based code generation - submodules
 
+pub(crate) mod debug_info;
 pub(crate) mod declarations;
+pub(crate) mod dispatch;
+pub(crate) mod exceptions;
+pub(crate) mod exports;
 pub(crate) mod expressions;
+pub(crate) mod externs;
 pub(crate) mod function_gen;
+pub(crate) mod gc;
 pub(crate) mod operators;
+pub(crate) mod recursion;
+pub(crate) mod refcount;
+pub(crate) mod stack_alloc;
 pub(crate) mod statements;
+pub(crate) mod traceback;
 pub(crate) mod value_utils;
@@ -1,5 +1,5 @@
 use crate::ast::BoolOp;
-use crate::tir::expr::TirExpr;
+use crate::tir::expr::{MathFunc, TirExpr};
 use crate::tir::TirProgram;
 use inkwell::values::AnyValue;
 
@@ -14,24 +14,51 @@ impl<'ctx, 'a> FunctionGenContext<'ctx, 'a> {
     ) -> inkwell::values::IntValue<'ctx> {
         use crate::ast::BinOperator::*;
         match op {
+            Add if self.ctx.checked_int => self.codegen_checked_int_op("llvm.sadd", lhs, rhs),
+            Sub if self.ctx.checked_int => self.codegen_checked_int_op("llvm.ssub", lhs, rhs),
+            Mult if self.ctx.checked_int => self.codegen_checked_int_op("llvm.smul", lhs, rhs),
             Add => self.ctx.builder.build_int_add(lhs, rhs, "add").unwrap(),
             Sub => self.ctx.builder.build_int_sub(lhs, rhs, "sub").unwrap(),
             Mult => self.ctx.builder.build_int_mul(lhs, rhs, "mul").unwrap(),
-            Div => self
-                .ctx
-                .builder
-                .build_int_signed_div(lhs, rhs, "div")
-                .unwrap(),
-            FloorDiv => self
-                .ctx
-                .builder
-                .build_int_signed_div(lhs, rhs, "floordiv")
-                .unwrap(),
-            Mod => self
-                .ctx
-                .builder
-                .build_int_signed_rem(lhs, rhs, "mod")
-                .unwrap(),
+            Div => {
+                let rhs = self.check_int_divisor(rhs, "division by zero");
+                self.ctx
+                    .builder
+                    .build_int_signed_div(lhs, rhs, "div")
+                    .unwrap()
+            }
+            FloorDiv => {
+                if self.ctx.checked_int {
+                    // INT64_MIN // -1 is the only quotient out of range
+                    let builder = &self.ctx.builder;
+                    let i64_type = self.ctx.context.i64_type();
+                    let is_min = builder
+                        .build_int_compare(
+                            inkwell::IntPredicate::EQ,
+                            lhs,
+                            i64_type.const_int(i64::MIN as u64, true),
+                            "is_min",
+                        )
+                        .unwrap();
+                    let by_minus_one = builder
+                        .build_int_compare(
+                            inkwell::IntPredicate::EQ,
+                            rhs,
+                            i64_type.const_all_ones(),
+                            "by_minus_one",
+                        )
+                        .unwrap();
+                    let overflow = builder.build_and(is_min, by_minus_one, "overflow").unwrap();
+                    self.raise_if(overflow, "__pyc_raise_overflow", &[]);
+                }
+                let rhs = self.check_int_divisor(rhs, "integer division or modulo by zero");
+                self.codegen_floor_divmod(lhs, rhs).0
+            }
+            Mod => {
+                let rhs = self.check_int_divisor(rhs, "integer modulo by zero");
+                self.codegen_floor_divmod(lhs, rhs).1
+            }
+            LShift if self.ctx.checked_int => self.codegen_checked_lshift(lhs, rhs),
             LShift => self
                 .ctx
                 .builder
@@ -57,6 +84,27 @@ impl<'ctx, 'a> FunctionGenContext<'ctx, 'a> {
     ) -> inkwell::values::FloatValue<'ctx> {
         use crate::ast::BinOperator::*;
         let f64_type = self.ctx.context.f64_type();
+        if self.ctx.runtime_checks {
+            let message = match op {
+                Div => Some("float division by zero"),
+                FloorDiv => Some("float floor division by zero"),
+                Mod => Some("float modulo"),
+                _ => None,
+            };
+            if let Some(message) = message {
+                let is_zero = self
+                    .ctx
+                    .builder
+                    .build_float_compare(
+                        inkwell::FloatPredicate::OEQ,
+                        rhs,
+                        f64_type.const_zero(),
+                        "is_zero",
+                    )
+                    .unwrap();
+                self.raise_zero_division_if(is_zero, message);
+            }
+        }
         match op {
             Add => self.ctx.builder.build_float_add(lhs, rhs, "fadd").unwrap(),
             Sub => self.ctx.builder.build_float_sub(lhs, rhs, "fsub").unwrap(),
@@ -78,7 +126,31 @@ impl<'ctx, 'a> FunctionGenContext<'ctx, 'a> {
                 // LLVM guarantees floor returns the same type as input (f64)
                 call.as_any_value_enum().into_float_value()
             }
-            Mod => self.ctx.builder.build_float_rem(lhs, rhs, "fmod").unwrap(),
+            Mod => {
+                // The remainder takes the sign of the divisor, as in Python:
+                // r = fmod(a, b), plus b when r is nonzero and the signs differ
+                let rem = self.ctx.builder.build_float_rem(lhs, rhs, "fmod").unwrap();
+                let zero = f64_type.const_zero();
+                let builder = &self.ctx.builder;
+                let rem_nonzero = builder
+                    .build_float_compare(inkwell::FloatPredicate::ONE, rem, zero, "rem_nonzero")
+                    .unwrap();
+                let rem_neg = builder
+                    .build_float_compare(inkwell::FloatPredicate::OLT, rem, zero, "rem_neg")
+                    .unwrap();
+                let rhs_neg = builder
+                    .build_float_compare(inkwell::FloatPredicate::OLT, rhs, zero, "rhs_neg")
+                    .unwrap();
+                let signs_differ = builder.build_xor(rem_neg, rhs_neg, "signs_differ").unwrap();
+                let adjust = builder
+                    .build_and(rem_nonzero, signs_differ, "adjust")
+                    .unwrap();
+                let adjusted = builder.build_float_add(rem, rhs, "fmod_adj").unwrap();
+                builder
+                    .build_select(adjust, adjusted, rem, "fmod_floor")
+                    .unwrap()
+                    .into_float_value()
+            }
             Pow => self.codegen_float_pow(lhs, rhs),
             // Bitwise operations are not valid for floats - TIR type checking prevents this
             // Return 0.0 for exhaustiveness (this code path should never execute)
@@ -86,6 +158,254 @@ impl<'ctx, 'a> FunctionGenContext<'ctx, 'a> {
         }
     }
 
+    /// Generate the quotient and remainder of Python's floor division: LLVM's
+    /// sdiv/srem truncate toward zero, so where the remainder is nonzero and
+    /// its sign differs from the divisor's, the quotient is one lower and the
+    /// divisor is added to the remainder (-7 // 2 == -4, -7 % 2 == 1)
+    pub(crate) fn codegen_floor_divmod(
+        &self,
+        lhs: inkwell::values::IntValue<'ctx>,
+        rhs: inkwell::values::IntValue<'ctx>,
+    ) -> (
+        inkwell::values::IntValue<'ctx>,
+        inkwell::values::IntValue<'ctx>,
+    ) {
+        let builder = &self.ctx.builder;
+        let i64_type = self.ctx.context.i64_type();
+        let zero = i64_type.const_zero();
+
+        // x // -1 is -x, wrapping for INT64_MIN, where sdiv would trap;
+        // dividing by 1 instead keeps the remainder 0
+        let by_minus_one = builder
+            .build_int_compare(
+                inkwell::IntPredicate::EQ,
+                rhs,
+                i64_type.const_all_ones(),
+                "by_minus_one",
+            )
+            .unwrap();
+        let divisor = builder
+            .build_select(by_minus_one, i64_type.const_int(1, false), rhs, "divisor")
+            .unwrap()
+            .into_int_value();
+        let quot = builder.build_int_signed_div(lhs, divisor, "div").unwrap();
+        let neg_lhs = builder.build_int_neg(lhs, "neg_lhs").unwrap();
+        let quot = builder
+            .build_select(by_minus_one, neg_lhs, quot, "quot")
+            .unwrap()
+            .into_int_value();
+        let rem = builder.build_int_signed_rem(lhs, divisor, "rem").unwrap();
+        let rem_nonzero = builder
+            .build_int_compare(inkwell::IntPredicate::NE, rem, zero, "rem_nonzero")
+            .unwrap();
+        let sign_bits = builder.build_xor(rem, rhs, "sign_bits").unwrap();
+        let signs_differ = builder
+            .build_int_compare(inkwell::IntPredicate::SLT, sign_bits, zero, "signs_differ")
+            .unwrap();
+        let adjust = builder
+            .build_and(rem_nonzero, signs_differ, "adjust")
+            .unwrap();
+
+        let floor_quot = builder
+            .build_int_sub(quot, i64_type.const_int(1, false), "quot_adj")
+            .unwrap();
+        let floor_rem = builder.build_int_add(rem, rhs, "rem_adj").unwrap();
+        (
+            builder
+                .build_select(adjust, floor_quot, quot, "floordiv")
+                .unwrap()
+                .into_int_value(),
+            builder
+                .build_select(adjust, floor_rem, rem, "mod")
+                .unwrap()
+                .into_int_value(),
+        )
+    }
+
+    /// Call an `llvm.s{add,sub,mul}.with.overflow` intrinsic, giving the
+    /// wrapped result and whether it overflowed
+    fn codegen_int_with_overflow(
+        &self,
+        intrinsic: &str,
+        lhs: inkwell::values::IntValue<'ctx>,
+        rhs: inkwell::values::IntValue<'ctx>,
+    ) -> (
+        inkwell::values::IntValue<'ctx>,
+        inkwell::values::IntValue<'ctx>,
+    ) {
+        let i64_type = self.ctx.context.i64_type();
+        let intrinsic =
+            inkwell::intrinsics::Intrinsic::find(&format!("{}.with.overflow", intrinsic)).unwrap();
+        let intrinsic_fn = intrinsic
+            .get_declaration(&self.ctx.module, &[i64_type.into()])
+            .unwrap();
+        let call = self
+            .ctx
+            .builder
+            .build_call(intrinsic_fn, &[lhs.into(), rhs.into()], "checked")
+            .unwrap();
+        // The intrinsics return { i64 result, i1 overflow }
+        let pair = call.as_any_value_enum().into_struct_value();
+        let value = self
+            .ctx
+            .builder
+            .build_extract_value(pair, 0, "value")
+            .unwrap()
+            .into_int_value();
+        let overflow = self
+            .ctx
+            .builder
+            .build_extract_value(pair, 1, "overflow")
+            .unwrap()
+            .into_int_value();
+        (value, overflow)
+    }
+
+    /// An int operation in checked-int mode: raise OverflowError if the
+    /// result does not fit in 64 bits
+    pub(crate) fn codegen_checked_int_op(
+        &self,
+        intrinsic: &str,
+        lhs: inkwell::values::IntValue<'ctx>,
+        rhs: inkwell::values::IntValue<'ctx>,
+    ) -> inkwell::values::IntValue<'ctx> {
+        let (value, overflow) = self.codegen_int_with_overflow(intrinsic, lhs, rhs);
+        self.raise_if(overflow, "__pyc_raise_overflow", &[]);
+        value
+    }
+
+    /// `lhs << rhs` in checked-int mode: a negative count raises ValueError,
+    /// and losing any bit raises OverflowError, as does a count of 64 or
+    /// more unless `lhs` is 0. The shift still runs after a raise, so an out
+    /// of range count shifts by 0 instead
+    fn codegen_checked_lshift(
+        &self,
+        lhs: inkwell::values::IntValue<'ctx>,
+        rhs: inkwell::values::IntValue<'ctx>,
+    ) -> inkwell::values::IntValue<'ctx> {
+        use inkwell::IntPredicate;
+        let builder = &self.ctx.builder;
+        let i64_type = self.ctx.context.i64_type();
+        let zero = i64_type.const_zero();
+        let is_negative = builder
+            .build_int_compare(IntPredicate::SLT, rhs, zero, "negative_count")
+            .unwrap();
+        self.raise_if(is_negative, "__pyc_raise_negative_shift", &[]);
+
+        let in_range = builder
+            .build_int_compare(
+                IntPredicate::ULT,
+                rhs,
+                i64_type.const_int(64, false),
+                "count_in_range",
+            )
+            .unwrap();
+        let count = builder
+            .build_select(in_range, rhs, zero, "count")
+            .unwrap()
+            .into_int_value();
+        let shifted = builder.build_left_shift(lhs, count, "lshift").unwrap();
+        let back = builder
+            .build_right_shift(shifted, count, true, "unshifted")
+            .unwrap();
+        let lost_bits = builder
+            .build_int_compare(IntPredicate::NE, back, lhs, "lost_bits")
+            .unwrap();
+        let nonzero = builder
+            .build_int_compare(IntPredicate::NE, lhs, zero, "nonzero")
+            .unwrap();
+        let too_far = builder
+            .build_int_compare(
+                IntPredicate::SGE,
+                rhs,
+                i64_type.const_int(64, false),
+                "too_far",
+            )
+            .unwrap();
+        let too_far = builder.build_and(too_far, nonzero, "too_far").unwrap();
+        let overflow = builder.build_or(lost_bits, too_far, "overflow").unwrap();
+        self.raise_if(overflow, "__pyc_raise_overflow", &[]);
+        shifted
+    }
+
+    /// Call the runtime function `raise_fn` when the i1 `condition` is set.
+    /// As with `raise`, execution continues past it until the enclosing try
+    /// block polls, and the program exits when there is none
+    pub(crate) fn raise_if(
+        &self,
+        condition: inkwell::values::IntValue<'ctx>,
+        raise_fn: &str,
+        args: &[inkwell::values::BasicMetadataValueEnum<'ctx>],
+    ) {
+        let current_fn = self
+            .ctx
+            .builder
+            .get_insert_block()
+            .unwrap()
+            .get_parent()
+            .unwrap();
+        let raise_bb = self.ctx.context.append_basic_block(current_fn, "raise");
+        let cont_bb = self.ctx.context.append_basic_block(current_fn, "no_raise");
+        self.ctx
+            .builder
+            .build_conditional_branch(condition, raise_bb, cont_bb)
+            .unwrap();
+
+        self.ctx.builder.position_at_end(raise_bb);
+        let raise_fn = self.ctx.module.get_function(raise_fn).unwrap();
+        self.ctx.builder.build_call(raise_fn, args, "").unwrap();
+        self.ctx
+            .builder
+            .build_unconditional_branch(cont_bb)
+            .unwrap();
+
+        self.ctx.builder.position_at_end(cont_bb);
+    }
+
+    /// Raise ZeroDivisionError when the int `rhs` is zero, unless runtime
+    /// checks are off. Gives the divisor to use: 1 in place of 0, since the
+    /// division still runs after the raise and would trap
+    pub(crate) fn check_int_divisor(
+        &self,
+        rhs: inkwell::values::IntValue<'ctx>,
+        message: &str,
+    ) -> inkwell::values::IntValue<'ctx> {
+        if !self.ctx.runtime_checks {
+            return rhs;
+        }
+        let i64_type = self.ctx.context.i64_type();
+        let is_zero = self
+            .ctx
+            .builder
+            .build_int_compare(
+                inkwell::IntPredicate::EQ,
+                rhs,
+                i64_type.const_zero(),
+                "is_zero",
+            )
+            .unwrap();
+        self.raise_zero_division_if(is_zero, message);
+        self.ctx
+            .builder
+            .build_select(is_zero, i64_type.const_int(1, false), rhs, "divisor")
+            .unwrap()
+            .into_int_value()
+    }
+
+    /// Raise ZeroDivisionError with `message` when the i1 `is_zero` is set
+    fn raise_zero_division_if(&self, is_zero: inkwell::values::IntValue<'ctx>, message: &str) {
+        let message = self
+            .ctx
+            .builder
+            .build_global_string_ptr(message, "zero_division_msg")
+            .unwrap();
+        self.raise_if(
+            is_zero,
+            "__pyc_raise_zero_division",
+            &[message.as_pointer_value().into()],
+        );
+    }
+
     /// Generate code for float exponentiation using llvm.pow intrinsic
     pub(crate) fn codegen_float_pow(
         &self,
@@ -106,6 +426,106 @@ impl<'ctx, 'a> FunctionGenContext<'ctx, 'a> {
         call.as_any_value_enum().into_float_value()
     }
 
+    /// Generate code for a `math` function by calling its f64 LLVM intrinsic.
+    /// LLVM folds constant arguments and lowers the rest to instructions or to
+    /// the math routines in libc, so no separate libm is linked.
+    ///
+    /// Where CPython raises, the intrinsic gives NaN or an infinity instead,
+    /// so the result is checked: NaN from arguments that are not NaN is a
+    /// ValueError (`sqrt(-1)`, `sin(inf)`), and so are `log(0)` and
+    /// `pow(0, y)` with a negative y; an infinity from finite arguments is
+    /// an OverflowError (`exp(1000)`)
+    pub(crate) fn codegen_math_intrinsic(
+        &self,
+        func: MathFunc,
+        args: &[inkwell::values::FloatValue<'ctx>],
+    ) -> inkwell::values::FloatValue<'ctx> {
+        use inkwell::FloatPredicate;
+        let f64_type = self.ctx.context.f64_type();
+        let intrinsic = inkwell::intrinsics::Intrinsic::find(func.llvm_name()).unwrap();
+        let intrinsic_fn = intrinsic
+            .get_declaration(&self.ctx.module, &[f64_type.into()])
+            .unwrap();
+        let call_args: Vec<_> = args.iter().map(|arg| (*arg).into()).collect();
+        let call = self
+            .ctx
+            .builder
+            .build_call(intrinsic_fn, &call_args, "math")
+            .unwrap();
+        // The f64 overloads of these intrinsics all return f64
+        let result = call.as_any_value_enum().into_float_value();
+
+        let builder = &self.ctx.builder;
+        let zero = f64_type.const_zero();
+        let compare = |predicate, lhs, rhs, name| {
+            builder
+                .build_float_compare(predicate, lhs, rhs, name)
+                .unwrap()
+        };
+        let all = |flags: Vec<inkwell::values::IntValue<'ctx>>, name| {
+            flags
+                .into_iter()
+                .reduce(|a, b| builder.build_and(a, b, name).unwrap())
+                .unwrap()
+        };
+        let inf = f64_type.const_float(f64::INFINITY);
+        let neg_inf = f64_type.const_float(f64::NEG_INFINITY);
+        // ONE and ORD are false for NaN
+        let finite_args = all(
+            args.iter()
+                .map(|arg| {
+                    let below = compare(FloatPredicate::ONE, *arg, inf, "not_inf");
+                    let above = compare(FloatPredicate::ONE, *arg, neg_inf, "not_neg_inf");
+                    builder.build_and(below, above, "finite").unwrap()
+                })
+                .collect(),
+            "finite_args",
+        );
+        let number_args = all(
+            args.iter()
+                .map(|arg| compare(FloatPredicate::ORD, *arg, *arg, "not_nan"))
+                .collect(),
+            "number_args",
+        );
+
+        let nan_result = compare(FloatPredicate::UNO, result, result, "nan_result");
+        let mut domain_error = builder
+            .build_and(nan_result, number_args, "domain_error")
+            .unwrap();
+        let pole = match func {
+            MathFunc::Log => Some(compare(FloatPredicate::OEQ, args[0], zero, "log_zero")),
+            MathFunc::Pow => {
+                let zero_base = compare(FloatPredicate::OEQ, args[0], zero, "zero_base");
+                let negative_exp = compare(FloatPredicate::OLT, args[1], zero, "negative_exp");
+                let pole = builder.build_and(zero_base, negative_exp, "pole").unwrap();
+                Some(builder.build_and(pole, finite_args, "pole").unwrap())
+            }
+            _ => None,
+        };
+        if let Some(pole) = pole {
+            domain_error = builder
+                .build_or(domain_error, pole, "domain_error")
+                .unwrap();
+        }
+
+        let inf_result = compare(FloatPredicate::OEQ, result, inf, "inf_result");
+        let neg_inf_result = compare(FloatPredicate::OEQ, result, neg_inf, "neg_inf_result");
+        let inf_result = builder
+            .build_or(inf_result, neg_inf_result, "inf_result")
+            .unwrap();
+        let range_error = builder
+            .build_and(inf_result, finite_args, "range_error")
+            .unwrap();
+        let no_domain_error = builder.build_not(domain_error, "no_domain_error").unwrap();
+        let range_error = builder
+            .build_and(range_error, no_domain_error, "range_error")
+            .unwrap();
+
+        self.raise_if(domain_error, "__pyc_raise_math_domain_error", &[]);
+        self.raise_if(range_error, "__pyc_raise_math_range_error", &[]);
+        result
+    }
+
     /// Generate code for integer exponentiation using exponentiation by squaring
     pub(crate) fn codegen_pow(
         &self,
@@ -177,11 +597,8 @@ impl<'ctx, 'a> FunctionGenContext<'ctx, 'a> {
             .unwrap();
 
         // result = is_odd ? result * base : result
-        let result_times_base = self
-            .ctx
-            .builder
-            .build_int_mul(current_result, current_base, "result_times_base")
-            .unwrap();
+        let (result_times_base, result_overflow) =
+            self.codegen_int_with_overflow("llvm.smul", current_result, current_base);
         let new_result = self
             .ctx
             .builder
@@ -190,11 +607,8 @@ impl<'ctx, 'a> FunctionGenContext<'ctx, 'a> {
             .into_int_value();
 
         // base = base * base
-        let new_base = self
-            .ctx
-            .builder
-            .build_int_mul(current_base, current_base, "new_base")
-            .unwrap();
+        let (new_base, base_overflow) =
+            self.codegen_int_with_overflow("llvm.smul", current_base, current_base);
 
         // exp = exp >> 1
         let new_exp = self
@@ -203,15 +617,35 @@ impl<'ctx, 'a> FunctionGenContext<'ctx, 'a> {
             .build_right_shift(current_exp, one, false, "new_exp")
             .unwrap();
 
+        if self.ctx.checked_int {
+            // Only the multiplications whose result is used count: the
+            // result's when the bit is set, the base's while bits remain
+            let builder = &self.ctx.builder;
+            let result_overflow = builder
+                .build_and(result_overflow, is_odd, "result_overflow")
+                .unwrap();
+            let more_bits = builder
+                .build_int_compare(inkwell::IntPredicate::NE, new_exp, zero, "more_bits")
+                .unwrap();
+            let base_overflow = builder
+                .build_and(base_overflow, more_bits, "base_overflow")
+                .unwrap();
+            let overflow = builder
+                .build_or(result_overflow, base_overflow, "pow_overflow")
+                .unwrap();
+            self.raise_if(overflow, "__pyc_raise_overflow", &[]);
+        }
+        let loop_latch_bb = self.ctx.builder.get_insert_block().unwrap();
+
         self.ctx
             .builder
             .build_unconditional_branch(loop_bb)
             .unwrap();
 
         // Add phi incoming values from loop body
-        result_phi.add_incoming(&[(&new_result, loop_body_bb)]);
-        base_phi.add_incoming(&[(&new_base, loop_body_bb)]);
-        exp_phi.add_incoming(&[(&new_exp, loop_body_bb)]);
+        result_phi.add_incoming(&[(&new_result, loop_latch_bb)]);
+        base_phi.add_incoming(&[(&new_base, loop_latch_bb)]);
+        exp_phi.add_incoming(&[(&new_exp, loop_latch_bb)]);
 
         // Loop end: return result
         self.ctx.builder.position_at_end(loop_end_bb);
@@ -227,12 +661,13 @@ impl<'ctx, 'a> FunctionGenContext<'ctx, 'a> {
         use crate::ast::CompareOp::*;
         use inkwell::IntPredicate::*;
         let predicate = match op {
-            Eq => EQ,
-            NotEq => NE,
+            Eq | Is => EQ,
+            NotEq | IsNot => NE,
             Lt => SLT,
             LtE => SLE,
             Gt => SGT,
             GtE => SGE,
+            In | NotIn => unreachable!("membership tests are lowered to method calls"),
         };
         // Get i1 result then extend to i8 for consistency with bool representation
         let cmp = self
@@ -255,12 +690,14 @@ impl<'ctx, 'a> FunctionGenContext<'ctx, 'a> {
         use crate::ast::CompareOp::*;
         use inkwell::FloatPredicate::*;
         let predicate = match op {
-            Eq => OEQ,    // Ordered and equal
-            NotEq => ONE, // Ordered and not equal
-            Lt => OLT,    // Ordered and less than
-            LtE => OLE,   // Ordered and less than or equal
-            Gt => OGT,    // Ordered and greater than
-            GtE => OGE,   // Ordered and greater than or equal
+            Eq | Is => OEQ, // Ordered and equal
+            NotEq => ONE,   // Ordered and not equal
+            IsNot => UNE,   // Unordered or not equal, so that it negates `is`
+            Lt => OLT,      // Ordered and less than
+            LtE => OLE,     // Ordered and less than or equal
+            Gt => OGT,      // Ordered and greater than
+            GtE => OGE,     // Ordered and greater than or equal
+            In | NotIn => unreachable!("membership tests are lowered to method calls"),
         };
         // Get i1 result then extend to i8 for consistency with bool representation
         let cmp = self
@@ -0,0 +1,89 @@
+//! Recursion depth guard (`pycc --recursion-limit=N`)
+//!
+//! Runaway recursion would overflow the stack and crash the program. With a
+//! limit, every function lowered from a `def` counts itself on the
+//! runtime's recursion depth as it starts and uncounts itself on every
+//! return. A function that would go beyond the limit raises RecursionError,
+//! as Python does, and returns at once instead of running its body
+//! (runtime/src/exception.c).
+//!
+//! With polling, the functions between that raise and the try block
+//! catching it return one by one, and a call they make meanwhile returns at
+//! once too. A try block's exception frame remembers the depth, which a
+//! raise jumping to the block restores (`--exceptions=setjmp`).
+
+use inkwell::IntPredicate;
+
+use super::declarations::call_result_to_basic_value;
+use super::function_gen::FunctionGenContext;
+
+impl<'ctx, 'a> FunctionGenContext<'ctx, 'a> {
+    /// Count the function on the recursion depth, returning at once when
+    /// the runtime refuses it
+    pub(crate) fn recursion_begin(&mut self) {
+        let Some(limit) = self.ctx.recursion_limit else {
+            return;
+        };
+        let i32_type = self.ctx.context.i32_type();
+        let enter = self
+            .ctx
+            .module
+            .get_function("__pyc_recursion_enter")
+            .unwrap();
+        let limit = self.ctx.context.i64_type().const_int(limit as u64, false);
+        let call = self
+            .ctx
+            .builder
+            .build_call(enter, &[limit.into()], "recursion_enter")
+            .unwrap();
+        let refused =
+            call_result_to_basic_value(call, i32_type.const_zero().into()).into_int_value();
+        let refused = self
+            .ctx
+            .builder
+            .build_int_compare(
+                IntPredicate::NE,
+                refused,
+                i32_type.const_zero(),
+                "recursion_refused",
+            )
+            .unwrap();
+
+        let func = self.ctx.current_function.unwrap();
+        let refused_bb = self
+            .ctx
+            .context
+            .append_basic_block(func, "recursion.refused");
+        let body_bb = self.ctx.context.append_basic_block(func, "recursion.body");
+        self.ctx
+            .builder
+            .build_conditional_branch(refused, refused_bb, body_bb)
+            .unwrap();
+
+        // The value is never used: an exception is pending
+        self.ctx.builder.position_at_end(refused_bb);
+        self.rc_end_scope();
+        self.gc_end_scope(None);
+        match func.get_type().get_return_type() {
+            Some(ty) => self.ctx.builder.build_return(Some(&ty.const_zero())),
+            None => self.ctx.builder.build_return(None),
+        }
+        .unwrap();
+
+        self.ctx.builder.position_at_end(body_bb);
+        self.recursion_guard = true;
+    }
+
+    /// Uncount the function before a return
+    pub(crate) fn recursion_end(&mut self) {
+        if !self.recursion_guard {
+            return;
+        }
+        let leave = self
+            .ctx
+            .module
+            .get_function("__pyc_recursion_leave")
+            .unwrap();
+        self.ctx.builder.build_call(leave, &[], "").unwrap();
+    }
+}
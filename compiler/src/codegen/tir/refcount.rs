@@ -0,0 +1,287 @@
+//! Reference counting (`pycc --refcount`)
+//!
+//! Instances of user classes are allocated with `__pyc_rc_new`, and strings
+//! and lists by the runtime with `__pyc_rc_alloc`; all are counted in the
+//! runtime's side table (runtime/src/refcount.c). Codegen counts the
+//! references held by
+//!
+//! - locals, parameters and `self`: taken when assigned or on function entry,
+//!   dropped on every return
+//! - fields: taken when assigned, dropped by the class's release function when
+//!   the instance is freed
+//! - globals: taken when assigned, dropped in main before `--leak-check`
+//!
+//! A new object starts with no references, and so does a returned one: the
+//! callee takes a reference for the value it returns and hands it over with
+//! `__pyc_rc_unown`. The first store takes a reference, and an expression
+//! statement discarding it frees it.
+//!
+//! Containers count the references their items hold: the runtime takes one
+//! when an item is stored and drops it when the item is overwritten or
+//! removed, or the list is freed. Other runtime functions keep nothing they
+//! are passed, so a new object passed to one is freed after the call; what
+//! the runtime holds for the whole run (exception messages, file paths) it
+//! untracks instead. Dicts, sets and tuples
+//! are not counted themselves and are never freed, nor are reference cycles,
+//! unless the tracing collector (gc.rs) is on as well.
+
+use inkwell::values::{BasicValueEnum, PointerValue};
+use inkwell::AddressSpace;
+
+use crate::codegen::context::CodegenContext;
+use crate::tir::expr::{TirExpr, TirExprKind, VarRef};
+use crate::tir::ids::ClassId;
+use crate::tir::stmt::TirLValue;
+use crate::tir::{TirProgram, TirType};
+
+use super::function_gen::FunctionGenContext;
+
+/// Name of the function that drops the references an instance's fields hold
+pub(crate) fn rc_release_name(class_name: &str) -> String {
+    format!("__pyc_release_{}", class_name.replace('.', "_"))
+}
+
+impl<'ctx> CodegenContext<'ctx> {
+    /// Whether instances of the class are allocated in the runtime's table
+    /// (with `--refcount` or `--gc=tracing`): user classes, but not builtins
+    /// or exceptions, which the runtime allocates itself
+    pub(crate) fn is_managed_class(&self, class: ClassId, program: &TirProgram) -> bool {
+        if !self.refcount && !self.tracing_gc {
+            return false;
+        }
+        let mut current = Some(class);
+        while let Some(class_id) = current {
+            let class_def = program.class(class_id);
+            if class_def.qualified_name.starts_with("__builtin__.") {
+                return false;
+            }
+            current = class_def.parent;
+        }
+        true
+    }
+
+    /// Whether values of the type are managed instances
+    pub(crate) fn is_managed(&self, ty: &TirType, program: &TirProgram) -> bool {
+        match ty {
+            TirType::Class(class) => self.is_managed_class(*class, program),
+            TirType::Optional(inner) => self.is_managed(inner, program),
+            _ => false,
+        }
+    }
+
+    /// Whether instances of the class are reference counted
+    pub(crate) fn is_refcounted_class(&self, class: ClassId, program: &TirProgram) -> bool {
+        self.refcount && self.is_managed_class(class, program)
+    }
+
+    /// Whether values of the type are counted references: managed
+    /// instances, strings and lists
+    pub(crate) fn is_refcounted(&self, ty: &TirType, program: &TirProgram) -> bool {
+        if !self.refcount {
+            return false;
+        }
+        match ty {
+            TirType::Class(class) => {
+                self.is_managed_class(*class, program)
+                    || matches!(
+                        program.class(*class).qualified_name.as_str(),
+                        "__builtin__.str" | "__builtin__.list"
+                    )
+            }
+            TirType::Optional(inner) => self.is_refcounted(inner, program),
+            _ => false,
+        }
+    }
+
+    /// Call one of the runtime's count operations on an instance pointer
+    pub(crate) fn build_rc_call(&self, name: &str, obj: PointerValue<'ctx>) {
+        let func = self
+            .module
+            .get_function(name)
+            .unwrap_or_else(|| panic!("{} function not declared", name));
+        self.builder.build_call(func, &[obj.into()], "").unwrap();
+    }
+
+    /// Emit `__pyc_release_<class>` for every counted class; it decrefs the
+    /// counted fields before the runtime frees the instance
+    pub(crate) fn generate_rc_release_functions(&mut self, program: &TirProgram) {
+        let ptr_type = self.context.ptr_type(AddressSpace::default());
+        let fn_type = self.context.void_type().fn_type(&[ptr_type.into()], false);
+        for class in &program.classes {
+            if !self.is_refcounted_class(class.id, program) {
+                continue;
+            }
+            let function =
+                self.module
+                    .add_function(&rc_release_name(&class.qualified_name), fn_type, None);
+            let entry = self.context.append_basic_block(function, "entry");
+            self.builder.position_at_end(entry);
+
+            let obj = function.get_first_param().unwrap().into_pointer_value();
+            let class_type = self.class_types[&class.qualified_name];
+            for (index, (name, ty)) in class.all_fields().enumerate() {
+                if !self.is_refcounted(ty, program) {
+                    continue;
+                }
+                let index = self.field_index(class.id, index, program);
+                let field_ptr = self
+                    .builder
+                    .build_struct_gep(class_type, obj, index, name)
+                    .unwrap();
+                let field = self
+                    .builder
+                    .build_load(ptr_type, field_ptr, name)
+                    .unwrap()
+                    .into_pointer_value();
+                self.build_rc_call("__pyc_rc_decref", field);
+            }
+            self.builder.build_return(None).unwrap();
+        }
+    }
+}
+
+impl<'ctx, 'a> FunctionGenContext<'ctx, 'a> {
+    /// Call a count operation on a value of a counted type
+    pub(crate) fn rc_call(&mut self, name: &str, value: BasicValueEnum<'ctx>) {
+        let obj = self.value_to_pointer(value);
+        self.ctx.build_rc_call(name, obj);
+    }
+
+    /// Start counting in a function body: counted locals start out as None,
+    /// and parameters (including a counted `self`) hold a reference until
+    /// the function returns
+    pub(crate) fn rc_begin(
+        &mut self,
+        local_types: &[(String, TirType)],
+        param_types: &[(String, TirType)],
+        self_counted: bool,
+        program: &TirProgram,
+    ) {
+        let null = self
+            .ctx
+            .context
+            .ptr_type(AddressSpace::default())
+            .const_null();
+        for (i, (_, ty)) in local_types.iter().enumerate() {
+            if self.ctx.is_refcounted(ty, program) {
+                let (ptr, _) = self.locals[i];
+                self.ctx.builder.build_store(ptr, null).unwrap();
+                self.rc_locals.push(ptr);
+            }
+        }
+        if self_counted {
+            let self_value = self
+                .ctx
+                .current_function
+                .unwrap()
+                .get_first_param()
+                .unwrap();
+            self.rc_params.push(self_value);
+        }
+        for (i, (_, ty)) in param_types.iter().enumerate() {
+            if self.ctx.is_refcounted(ty, program) {
+                self.rc_params.push(self.params[i]);
+            }
+        }
+        for param in self.rc_params.clone() {
+            self.rc_call("__pyc_rc_incref", param);
+        }
+    }
+
+    /// Drop the references held by the function's locals and parameters,
+    /// before each return
+    pub(crate) fn rc_end_scope(&mut self) {
+        let ptr_type = self.ctx.context.ptr_type(AddressSpace::default());
+        for ptr in self.rc_locals.clone() {
+            let value = self.ctx.builder.build_load(ptr_type, ptr, "owned").unwrap();
+            self.rc_call("__pyc_rc_decref", value);
+        }
+        for param in self.rc_params.clone() {
+            self.rc_call("__pyc_rc_decref", param);
+        }
+    }
+
+    /// Give void functions' fall-through paths a return that ends the scope
+    pub(crate) fn end_scope_at_missing_returns(&mut self) {
+        if self.rc_locals.is_empty()
+            && self.rc_params.is_empty()
+            && !self.ctx.tracing_gc
+            && self.traceback_record.is_none()
+            && !self.recursion_guard
+        {
+            return;
+        }
+        let func = self.ctx.current_function.unwrap();
+        let mut block = func.get_first_basic_block();
+        while let Some(bb) = block {
+            if bb.get_terminator().is_none() {
+                self.ctx.builder.position_at_end(bb);
+                self.rc_end_scope();
+                self.gc_end_scope(None);
+                self.traceback_end();
+                self.recursion_end();
+                self.ctx.builder.build_return(None).unwrap();
+            }
+            block = bb.get_next_basic_block();
+        }
+    }
+
+    /// Store a counted reference: take the new one before dropping the old,
+    /// so that assigning a variable to itself keeps the instance alive
+    pub(crate) fn rc_store(&mut self, ptr: PointerValue<'ctx>, value: BasicValueEnum<'ctx>) {
+        let ptr_type = self.ctx.context.ptr_type(AddressSpace::default());
+        self.rc_call("__pyc_rc_incref", value);
+        let old = self.ctx.builder.build_load(ptr_type, ptr, "old").unwrap();
+        self.ctx.builder.build_store(ptr, value).unwrap();
+        self.rc_call("__pyc_rc_decref", old);
+    }
+
+    /// Whether the lvalue holds a counted reference
+    pub(crate) fn is_refcounted_lvalue(&self, lvalue: &TirLValue, program: &TirProgram) -> bool {
+        match lvalue {
+            TirLValue::Var(VarRef::Local(local_id)) => {
+                self.rc_locals.contains(&self.locals[local_id.index()].0)
+            }
+            TirLValue::Var(VarRef::Global(mod_id, global_id)) => {
+                let global = &program.module(*mod_id).globals[global_id.index()];
+                self.ctx.is_refcounted(&global.ty, program)
+            }
+            TirLValue::Var(_) => false,
+            TirLValue::Field { class, field, .. } => program
+                .class(*class)
+                .all_fields()
+                .nth(field.index())
+                .is_some_and(|(_, ty)| self.ctx.is_refcounted(ty, program)),
+        }
+    }
+
+    /// Whether the value of an argument to a runtime function may be a new
+    /// object, which nothing refers to once the call returns: any counted
+    /// value but a variable's
+    pub(crate) fn is_rc_temporary(&self, arg: &TirExpr, program: &TirProgram) -> bool {
+        !matches!(arg.kind, TirExprKind::Var(_)) && self.ctx.is_refcounted(&arg.ty, program)
+    }
+
+    /// Free the new objects passed to a runtime function once it returns.
+    /// The result is held meanwhile: it may be one of the arguments, or an
+    /// item of one.
+    pub(crate) fn rc_release_arguments(
+        &mut self,
+        temporaries: &[BasicValueEnum<'ctx>],
+        result: BasicValueEnum<'ctx>,
+        result_counted: bool,
+    ) {
+        if temporaries.is_empty() {
+            return;
+        }
+        if result_counted {
+            self.rc_call("__pyc_rc_incref", result);
+        }
+        for &value in temporaries {
+            self.rc_call("__pyc_rc_release_unowned", value);
+        }
+        if result_counted {
+            self.rc_call("__pyc_rc_unown", result);
+        }
+    }
+}
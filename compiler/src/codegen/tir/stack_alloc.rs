@@ -0,0 +1,145 @@
+//! Stack allocation of instances and lists that do not escape
+//!
+//! Escape analysis (tir/escape.rs) finds the locals whose values never
+//! outlive their function or the local's next assignment. Each gets a slot
+//! in the function's entry block, which every construction into the local
+//! reuses instead of calling class_new or list_new:
+//!
+//! - an instance is zeroed and initialized in place, once its `__init__`
+//!   arguments are evaluated (they may read the local's previous instance)
+//! - a list header is reset in place by the runtime, which keeps the
+//!   previous list's item buffer, so a list rebuilt in a loop stops
+//!   allocating once the buffer is big enough
+//!
+//! Reference counted lists and instances, and traced instances, stay on the
+//! heap.
+
+use inkwell::values::{BasicMetadataValueEnum, BasicValueEnum, PointerValue};
+use inkwell::AddressSpace;
+
+use crate::codegen::context::CodegenContext;
+use crate::tir::expr::{TirExpr, TirExprKind};
+use crate::tir::{ClassId, LocalId, TirProgram, TirType};
+
+use super::expressions::dict_kind;
+use super::function_gen::FunctionGenContext;
+
+impl<'ctx> CodegenContext<'ctx> {
+    /// Allocate the slots of a function's stack-allocated locals (in its
+    /// entry block), indexed by LocalId
+    pub(crate) fn stack_slots(
+        &mut self,
+        stack_locals: &[LocalId],
+        local_types: &[(String, TirType)],
+        program: &TirProgram,
+    ) -> Vec<Option<PointerValue<'ctx>>> {
+        let mut slots = vec![None; local_types.len()];
+        for local in stack_locals {
+            let (name, ty) = &local_types[local.index()];
+            let TirType::Class(class_id) = ty else {
+                continue;
+            };
+            if self.is_refcounted(ty, program) {
+                continue;
+            }
+            let class_def = program.class(*class_id);
+            let slot = if class_def.qualified_name == "__builtin__.list" {
+                let slot = self
+                    .builder
+                    .build_alloca(self.list_struct_type(), name)
+                    .unwrap();
+                // No item buffer to reuse yet
+                let null = self.context.ptr_type(AddressSpace::default()).const_null();
+                self.builder.build_store(slot, null).unwrap();
+                slot
+            } else if self.is_managed_class(*class_id, program) {
+                continue;
+            } else {
+                let class_type = self.class_types[&class_def.qualified_name];
+                self.builder.build_alloca(class_type, name).unwrap()
+            };
+            slots[local.index()] = Some(slot);
+        }
+        slots
+    }
+}
+
+impl<'ctx, 'a> FunctionGenContext<'ctx, 'a> {
+    /// The value assigned to a local, constructed in the local's stack slot
+    /// if it has one
+    pub(crate) fn codegen_local_value(
+        &mut self,
+        local: LocalId,
+        value: &TirExpr,
+        program: &TirProgram,
+    ) -> BasicValueEnum<'ctx> {
+        let Some(slot) = self.stack_slots.get(local.index()).copied().flatten() else {
+            return self.codegen_expr(value, program);
+        };
+        match &value.kind {
+            TirExprKind::Construct { class, args } => {
+                self.codegen_stack_instance(slot, *class, args, program)
+            }
+            TirExprKind::List { elements, elem_ty } => {
+                self.codegen_stack_list(slot, elements, elem_ty, program)
+            }
+            _ => self.codegen_expr(value, program),
+        }
+    }
+
+    fn codegen_stack_instance(
+        &mut self,
+        slot: PointerValue<'ctx>,
+        class: ClassId,
+        args: &[TirExpr],
+        program: &TirProgram,
+    ) -> BasicValueEnum<'ctx> {
+        let mut init_args: Vec<BasicMetadataValueEnum<'ctx>> = vec![slot.into()];
+        for arg in args {
+            init_args.push(self.codegen_expr(arg, program).into());
+        }
+
+        let class_def = program.class(class);
+        let class_type = self.ctx.class_types[&class_def.qualified_name];
+        self.ctx
+            .builder
+            .build_store(slot, class_type.const_zero())
+            .unwrap();
+        self.store_vtable_pointer(slot, class_def);
+
+        if let Some(init_func_id) = class_def.get_method("__init__") {
+            let init_func = program.function(init_func_id);
+            if let Some(&init_fn) = self.ctx.functions.get(&init_func.qualified_name) {
+                self.ctx
+                    .builder
+                    .build_call(init_fn, &init_args, "")
+                    .unwrap();
+            }
+        }
+        slot.into()
+    }
+
+    fn codegen_stack_list(
+        &mut self,
+        slot: PointerValue<'ctx>,
+        elements: &[TirExpr],
+        elem_ty: &TirType,
+        program: &TirProgram,
+    ) -> BasicValueEnum<'ctx> {
+        let items = self.codegen_list_items(elements, elem_ty, program);
+
+        let init_in = self.ctx.module.get_function("__pyc_list_init_in").unwrap();
+        let kind = self
+            .ctx
+            .context
+            .i64_type()
+            .const_int(dict_kind(elem_ty, program), false);
+        self.ctx
+            .builder
+            .build_call(init_in, &[slot.into(), kind.into()], "")
+            .unwrap();
+
+        self.append_list_items(slot.into(), items, elem_ty);
+        slot.into()
+    }
+}
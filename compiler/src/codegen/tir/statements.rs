@@ -1,22 +1,37 @@
-use inkwell::AddressSpace;
+use inkwell::basic_block::BasicBlock;
+use inkwell::values::BasicValueEnum;
 
-use crate::tir::stmt::TirStmt;
-use crate::tir::TirProgram;
+use crate::tir::stmt::{TirLValue, TirStmt};
+use crate::tir::{TirProgram, TirType, VarRef};
 
 use super::declarations::call_result_to_basic_value;
 use super::function_gen::FunctionGenContext;
 
 impl<'ctx, 'a> FunctionGenContext<'ctx, 'a> {
     pub(crate) fn codegen_stmt(&mut self, stmt: &TirStmt, program: &TirProgram) {
+        if let Some(span) = stmt.span() {
+            self.ctx.debug_location(span);
+            self.traceback_line(span.line);
+        }
+        self.gc_statement_boundary();
         match stmt {
-            TirStmt::Let { local, ty: _, init } => {
-                let value = self.codegen_expr(init, program);
+            TirStmt::Let { local, ty, init } => {
+                let value = self.codegen_local_value(*local, init, program);
                 let (ptr, _) = self.locals[local.index()];
-                self.ctx.builder.build_store(ptr, value).unwrap();
+                if self.ctx.is_refcounted(ty, program) {
+                    self.rc_store(ptr, value);
+                } else {
+                    self.ctx.builder.build_store(ptr, value).unwrap();
+                }
             }
 
             TirStmt::Assign { target, value } => {
-                let val = self.codegen_expr(value, program);
+                let val = match target {
+                    TirLValue::Var(VarRef::Local(local)) => {
+                        self.codegen_local_value(*local, value, program)
+                    }
+                    _ => self.codegen_expr(value, program),
+                };
                 self.store_to_lvalue(target, val, program);
             }
 
@@ -33,17 +48,19 @@ impl<'ctx, 'a> FunctionGenContext<'ctx, 'a> {
             }
 
             TirStmt::Expr(expr) => {
-                self.codegen_expr(expr, program);
+                let value = self.codegen_expr(expr, program);
+                // A discarded new instance has no references, so it is freed here
+                if self.ctx.is_refcounted(&expr.ty, program) {
+                    self.rc_call("__pyc_rc_release_unowned", value);
+                }
             }
 
             TirStmt::Return(Some(expr)) => {
                 let value = self.codegen_expr(expr, program);
-                self.ctx.builder.build_return(Some(&value)).unwrap();
+                self.codegen_return(Some((value, &expr.ty)), program);
             }
 
-            TirStmt::Return(None) => {
-                self.ctx.builder.build_return(None).unwrap();
-            }
+            TirStmt::Return(None) => self.codegen_return(None, program),
 
             TirStmt::If {
                 cond,
@@ -161,12 +178,13 @@ impl<'ctx, 'a> FunctionGenContext<'ctx, 'a> {
                 orelse,
                 finalbody,
             } => {
-                // Polling-based exception handling:
+                // Polling-based exception handling (the default):
                 // After each statement in try body, check __pyc_has_exception() and branch to handlers if set.
-                // This avoids setjmp/longjmp which don't work in freestanding mode.
+                // With --exceptions=setjmp the try body polls nothing: a raise longjmps to the
+                // handlers instead (see exceptions.rs).
+                let setjmp = self.ctx.setjmp_exceptions;
 
                 let func = self.ctx.current_function.unwrap();
-                let i32_type = self.ctx.context.i32_type();
 
                 // Create basic blocks
                 let try_bb = self.ctx.context.append_basic_block(func, "try.body");
@@ -175,117 +193,50 @@ impl<'ctx, 'a> FunctionGenContext<'ctx, 'a> {
                 let finally_bb = self.ctx.context.append_basic_block(func, "try.finally");
                 let end_bb = self.ctx.context.append_basic_block(func, "try.end");
 
-                // Allocate exception frame on stack (for marking that we have a handler)
-                let frame_type = self.ctx.context.i8_type().array_type(64);
-                let frame_ptr = self
-                    .ctx
-                    .builder
-                    .build_alloca(frame_type, "exc_frame")
-                    .unwrap();
+                // Remember the exception being handled, which the handlers
+                // and the finally block replace
+                self.save_handled_exception();
 
-                // Push exception frame
-                let push_fn = self
-                    .ctx
-                    .module
-                    .get_function("__pyc_push_exception_frame")
-                    .unwrap();
-                self.ctx
-                    .builder
-                    .build_call(push_fn, &[frame_ptr.into()], "")
-                    .unwrap();
+                // Push the exception frame (marking that we have a handler)
+                let frame_ptr = self.push_exception_frame();
+                self.begin_try_scope(handlers_bb, (!finalbody.is_empty()).then_some(finally_bb));
 
                 // Branch to try body
-                self.ctx.builder.build_unconditional_branch(try_bb).unwrap();
-                self.ctx.builder.position_at_end(try_bb);
-
-                // Generate try body with polling after each statement
-                let has_exc_fn = self.ctx.module.get_function("__pyc_has_exception").unwrap();
-
-                for (i, s) in body.iter().enumerate() {
-                    // Execute statement
-                    self.codegen_stmt(s, program);
-
-                    // Check if current block was terminated (e.g., by return)
-                    let should_poll =
-                        if let Some(current_block) = self.ctx.builder.get_insert_block() {
-                            current_block.get_terminator().is_none()
-                        } else {
-                            false
-                        };
-
-                    if !should_poll {
-                        break; // Block already terminated, no more statements to generate
-                    }
-
-                    // Poll for exception
-                    let has_exc_call = self
-                        .ctx
-                        .builder
-                        .build_call(has_exc_fn, &[], "has_exc")
-                        .unwrap();
-                    let has_exc = call_result_to_basic_value(
-                        has_exc_call,
-                        i32_type.const_int(0, false).into(),
-                    )
-                    .into_int_value();
-                    let has_exc_bool = self
-                        .ctx
-                        .builder
-                        .build_int_compare(
-                            inkwell::IntPredicate::NE,
-                            has_exc,
-                            i32_type.const_int(0, false),
-                            "poll",
-                        )
-                        .unwrap();
-
-                    // Create continuation block
-                    let cont_bb = self
-                        .ctx
-                        .context
-                        .append_basic_block(func, &format!("try.cont_{}", i));
-                    self.ctx
-                        .builder
-                        .build_conditional_branch(has_exc_bool, handlers_bb, cont_bb)
-                        .unwrap();
-
-                    // Continue in new block
-                    self.ctx.builder.position_at_end(cont_bb);
+                if setjmp {
+                    self.arm_exception_frame(frame_ptr, handlers_bb, try_bb);
+                } else {
+                    self.ctx.builder.build_unconditional_branch(try_bb).unwrap();
                 }
+                self.ctx.builder.position_at_end(try_bb);
 
-                // If we reach here without exception, go to else block
-                if let Some(current_block) = self.ctx.builder.get_insert_block() {
-                    if current_block.get_terminator().is_none() {
-                        self.ctx
-                            .builder
-                            .build_unconditional_branch(else_bb)
-                            .unwrap();
-                    }
-                }
+                // Generate try body; if we reach its end without exception,
+                // go to else block
+                self.codegen_try_stmts(body, handlers_bb, else_bb, "try.cont", program);
+                self.try_scopes.last_mut().unwrap().raised = finally_bb;
 
                 // Handlers block (exception caught)
                 self.ctx.builder.position_at_end(handlers_bb);
 
-                // Create unhandled block first
-                let unhandled_bb = self.ctx.context.append_basic_block(func, "unhandled");
+                // A raise in a handler goes to finally (which will re-raise)
+                let dispatch_bb = if setjmp {
+                    let dispatch_bb = self.ctx.context.append_basic_block(func, "try.dispatch");
+                    self.arm_exception_frame(frame_ptr, finally_bb, dispatch_bb);
+                    self.ctx.builder.position_at_end(dispatch_bb);
+                    dispatch_bb
+                } else {
+                    handlers_bb
+                };
 
-                // If no handlers, go directly to unhandled (which will re-raise after finally)
+                // If no handler matches, the exception stays pending and
+                // finally re-raises it
                 if handlers.is_empty() {
                     self.ctx
                         .builder
-                        .build_unconditional_branch(unhandled_bb)
+                        .build_unconditional_branch(finally_bb)
                         .unwrap();
                 } else {
                     // Get current exception
-                    let get_exc_fn = self.ctx.module.get_function("__pyc_get_exception").unwrap();
-                    let exc_call = self.ctx.builder.build_call(get_exc_fn, &[], "exc").unwrap();
-                    let default_ptr = self
-                        .ctx
-                        .context
-                        .ptr_type(AddressSpace::default())
-                        .const_null()
-                        .into();
-                    let exc_val = call_result_to_basic_value(exc_call, default_ptr);
+                    let exc_val = self.pending_exception();
 
                     // Check each handler
                     let mut handler_bbs = Vec::new();
@@ -298,7 +249,7 @@ impl<'ctx, 'a> FunctionGenContext<'ctx, 'a> {
                     }
 
                     // For each handler, check if it matches and branch appropriately
-                    let mut current_check_bb = handlers_bb;
+                    let mut current_check_bb = dispatch_bb;
                     for (i, handler) in handlers.iter().enumerate() {
                         self.ctx.builder.position_at_end(current_check_bb);
 
@@ -309,7 +260,7 @@ impl<'ctx, 'a> FunctionGenContext<'ctx, 'a> {
                                     .context
                                     .append_basic_block(func, &format!("check_{}", i + 1))
                             } else {
-                                unhandled_bb
+                                finally_bb
                             };
 
                             // Get the exception class name
@@ -395,147 +346,134 @@ impl<'ctx, 'a> FunctionGenContext<'ctx, 'a> {
                             .get_function("__pyc_clear_exception")
                             .unwrap();
                         self.ctx.builder.build_call(clear_fn, &[], "").unwrap();
-
-                        // Execute handler body with polling for new exceptions
-                        for (j, s) in handler.body.iter().enumerate() {
-                            self.codegen_stmt(s, program);
-
-                            // Check if current block was terminated (e.g., by return)
-                            let should_poll =
-                                if let Some(current_block) = self.ctx.builder.get_insert_block() {
-                                    current_block.get_terminator().is_none()
-                                } else {
-                                    false
-                                };
-
-                            if !should_poll {
-                                break;
-                            }
-
-                            // Poll for new exception raised in handler
-                            let has_exc_call = self
-                                .ctx
-                                .builder
-                                .build_call(has_exc_fn, &[], "has_exc")
-                                .unwrap();
-                            let has_exc = call_result_to_basic_value(
-                                has_exc_call,
-                                i32_type.const_int(0, false).into(),
-                            )
-                            .into_int_value();
-                            let has_exc_bool = self
-                                .ctx
-                                .builder
-                                .build_int_compare(
-                                    inkwell::IntPredicate::NE,
-                                    has_exc,
-                                    i32_type.const_int(0, false),
-                                    "poll_handler",
-                                )
-                                .unwrap();
-
-                            // Create continuation block for this handler statement
-                            let cont_bb = self
-                                .ctx
-                                .context
-                                .append_basic_block(func, &format!("handler_{}.cont_{}", i, j));
-                            // If exception, go to finally (which will re-raise)
-                            self.ctx
-                                .builder
-                                .build_conditional_branch(has_exc_bool, finally_bb, cont_bb)
-                                .unwrap();
-
-                            self.ctx.builder.position_at_end(cont_bb);
-                        }
-
-                        // Go to finally normally (no exception in handler)
-                        if let Some(current_block) = self.ctx.builder.get_insert_block() {
-                            if current_block.get_terminator().is_none() {
-                                self.ctx
-                                    .builder
-                                    .build_unconditional_branch(finally_bb)
-                                    .unwrap();
-                            }
-                        }
+                        // ... which a bare raise re-raises; a return it
+                        // interrupted is over
+                        self.set_handled_exception(exc_val);
+                        self.set_returning(false);
+
+                        // Execute handler body; a new exception in it goes
+                        // to finally (which will re-raise)
+                        let name = format!("handler_{}.cont", i);
+                        self.codegen_try_stmts(
+                            &handler.body,
+                            finally_bb,
+                            finally_bb,
+                            &name,
+                            program,
+                        );
                     }
                 }
 
-                // Unhandled exception - re-raise after finally
-                self.ctx.builder.position_at_end(unhandled_bb);
-                self.ctx
-                    .builder
-                    .build_unconditional_branch(finally_bb)
-                    .unwrap();
-
-                // Else block (no exception occurred)
+                // Else block (no exception occurred); the handlers do not
+                // catch what it raises
                 self.ctx.builder.position_at_end(else_bb);
-                for s in orelse {
-                    self.codegen_stmt(s, program);
-                }
-                if let Some(current_block) = self.ctx.builder.get_insert_block() {
-                    if current_block.get_terminator().is_none() {
-                        self.ctx
-                            .builder
-                            .build_unconditional_branch(finally_bb)
-                            .unwrap();
-                    }
+                if setjmp && !orelse.is_empty() {
+                    let else_body_bb = self.ctx.context.append_basic_block(func, "try.else_body");
+                    self.arm_exception_frame(frame_ptr, finally_bb, else_body_bb);
+                    self.ctx.builder.position_at_end(else_body_bb);
                 }
+                self.codegen_try_stmts(orelse, finally_bb, finally_bb, "try.else_cont", program);
 
-                // Finally block (always runs)
+                // Finally block (always runs); a return in it leaves the
+                // try blocks around this one
+                let scope = self.try_scopes.pop().unwrap();
                 self.ctx.builder.position_at_end(finally_bb);
 
                 // Pop exception frame
-                let pop_fn = self
+                self.pop_exception_frame();
+
+                // Set the pending exception aside while the finally body
+                // runs, as the exception being handled
+                let pending = self.pending_exception();
+                let clear_fn = self
                     .ctx
                     .module
-                    .get_function("__pyc_pop_exception_frame")
+                    .get_function("__pyc_clear_exception")
                     .unwrap();
-                self.ctx.builder.build_call(pop_fn, &[], "").unwrap();
-
-                // Execute finally body
-                for s in finalbody {
-                    self.codegen_stmt(s, program);
-                }
-
-                // Check if there's a pending exception to re-raise
-                let has_exc_call = self
+                self.ctx.builder.build_call(clear_fn, &[], "").unwrap();
+                let pending_bb = self
+                    .ctx
+                    .context
+                    .append_basic_block(func, "try.finally_pending");
+                let finally_body_bb = self
+                    .ctx
+                    .context
+                    .append_basic_block(func, "try.finally_body");
+                let is_pending = self
                     .ctx
                     .builder
-                    .build_call(has_exc_fn, &[], "has_exc")
+                    .build_is_not_null(pending, "is_pending")
                     .unwrap();
-                let default_i32_zero = i32_type.const_int(0, false).into();
-                let has_exc =
-                    call_result_to_basic_value(has_exc_call, default_i32_zero).into_int_value();
-                let has_exc_bool = self
-                    .ctx
+                self.ctx
                     .builder
-                    .build_int_compare(
-                        inkwell::IntPredicate::NE,
-                        has_exc,
-                        i32_type.const_int(0, false),
-                        "has_exc_bool",
-                    )
+                    .build_conditional_branch(is_pending, pending_bb, finally_body_bb)
                     .unwrap();
+                self.ctx.builder.position_at_end(pending_bb);
+                self.set_handled_exception(pending);
+                self.set_returning(false);
+                self.ctx
+                    .builder
+                    .build_unconditional_branch(finally_body_bb)
+                    .unwrap();
+                self.ctx.builder.position_at_end(finally_body_bb);
 
+                // Execute finally body; an exception raised in it replaces
+                // the pending one, and is left for an outer try block
+                let finally_raised_bb = self
+                    .ctx
+                    .context
+                    .append_basic_block(func, "try.finally_raised");
                 let reraise_bb = self.ctx.context.append_basic_block(func, "reraise");
-
-                if let Some(current_block) = self.ctx.builder.get_insert_block() {
-                    if current_block.get_terminator().is_none() {
-                        self.ctx
-                            .builder
-                            .build_conditional_branch(has_exc_bool, reraise_bb, end_bb)
-                            .unwrap();
-                    }
-                }
+                self.codegen_try_stmts(
+                    finalbody,
+                    finally_raised_bb,
+                    reraise_bb,
+                    "try.finally_cont",
+                    program,
+                );
+                self.ctx.builder.position_at_end(finally_raised_bb);
+                self.restore_handled_exception();
+                self.set_returning(false);
+                self.ctx.builder.build_unconditional_branch(end_bb).unwrap();
 
                 // Re-raise block
-                // After reraise, if there's an outer exception frame, __pyc_reraise returns
+                // After the raise, if there's an outer exception frame, __pyc_raise returns
                 // and we continue to end_bb. The outer try block will poll and catch the exception.
                 self.ctx.builder.position_at_end(reraise_bb);
-                let reraise_fn = self.ctx.module.get_function("__pyc_reraise").unwrap();
-                self.ctx.builder.build_call(reraise_fn, &[], "").unwrap();
-                // Branch to end_bb - if reraise returned, there's an outer handler that will catch it
-                self.ctx.builder.build_unconditional_branch(end_bb).unwrap();
+                self.restore_handled_exception();
+                let raise_bb = self.ctx.context.append_basic_block(func, "reraise.raise");
+                let done_bb = if scope.returns {
+                    self.ctx.context.append_basic_block(func, "try.done")
+                } else {
+                    end_bb
+                };
+                let is_pending = self
+                    .ctx
+                    .builder
+                    .build_is_not_null(pending, "is_pending")
+                    .unwrap();
+                self.ctx
+                    .builder
+                    .build_conditional_branch(is_pending, raise_bb, done_bb)
+                    .unwrap();
+                self.ctx.builder.position_at_end(raise_bb);
+                let raise_fn = self.ctx.module.get_function("__pyc_raise").unwrap();
+                self.ctx
+                    .builder
+                    .build_call(raise_fn, &[pending.into()], "")
+                    .unwrap();
+                // Branch to end_bb - if the raise returned, there's an outer handler that will catch it
+                self.ctx
+                    .builder
+                    .build_unconditional_branch(done_bb)
+                    .unwrap();
+
+                // A return that went through the finally block goes on
+                if scope.returns {
+                    self.ctx.builder.position_at_end(done_bb);
+                    self.continue_return(end_bb, program);
+                }
+                self.handled_exceptions.pop();
 
                 // End block (continue after try)
                 self.ctx.builder.position_at_end(end_bb);
@@ -558,6 +496,119 @@ impl<'ctx, 'a> FunctionGenContext<'ctx, 'a> {
                 // The try block's polling will detect it and branch to handlers.
                 // If not in a try block, __pyc_raise exits the program.
             }
+
+            TirStmt::Yield(_) => {
+                unreachable!("yield is rewritten into the generator's __next__ during lowering")
+            }
+        }
+    }
+
+    /// Generate the statements of a try block's body, handler, else or
+    /// finally block, then branch to `next`. With polling, each statement
+    /// that does not end the block is followed by a poll branching to
+    /// `raised`.
+    fn codegen_try_stmts(
+        &mut self,
+        stmts: &[TirStmt],
+        raised: BasicBlock<'ctx>,
+        next: BasicBlock<'ctx>,
+        name: &str,
+        program: &TirProgram,
+    ) {
+        for (i, s) in stmts.iter().enumerate() {
+            self.codegen_stmt(s, program);
+
+            // Check if current block was terminated (e.g., by return)
+            if self.block_terminated() {
+                return;
+            }
+            if !self.ctx.setjmp_exceptions {
+                self.poll_exception(raised, &format!("{}_{}", name, i));
+            }
+        }
+        self.ctx.builder.build_unconditional_branch(next).unwrap();
+    }
+
+    /// Return `value`, through the finally blocks of the try blocks the
+    /// return leaves
+    fn codegen_return(
+        &mut self,
+        value: Option<(BasicValueEnum<'ctx>, &TirType)>,
+        program: &TirProgram,
+    ) {
+        if let (Some(_), Some(scope)) = (value, self.try_scopes.last()) {
+            // An exception raised computing the value goes to the handlers
+            if !self.ctx.setjmp_exceptions {
+                let raised = scope.raised;
+                self.poll_exception(raised, "return.value");
+            }
+        }
+        if !self.return_has_finally() {
+            self.build_return(value, program);
+            return;
+        }
+        if let Some((value, _)) = value {
+            self.keep_returned_value(value);
+        }
+        self.return_to_finally();
+    }
+
+    /// After a finally block, go on with a return that went through it, or
+    /// on to `end` if there is none
+    fn continue_return(&mut self, end: BasicBlock<'ctx>, program: &TirProgram) {
+        let func = self.ctx.current_function.unwrap();
+        let return_bb = self.ctx.context.append_basic_block(func, "try.return");
+        let returning = self.returning();
+        self.ctx
+            .builder
+            .build_conditional_branch(returning, return_bb, end)
+            .unwrap();
+        self.ctx.builder.position_at_end(return_bb);
+        if self.return_has_finally() {
+            self.return_to_finally();
+        } else {
+            let value = self.returned_value();
+            self.build_return(value.as_ref().map(|(value, ty)| (*value, ty)), program);
+        }
+    }
+
+    /// Return `value` from the function, ending its scope
+    fn build_return(
+        &mut self,
+        value: Option<(BasicValueEnum<'ctx>, &TirType)>,
+        program: &TirProgram,
+    ) {
+        let Some((value, ty)) = value else {
+            self.rc_end_scope();
+            self.gc_end_scope(None);
+            self.traceback_end();
+            self.recursion_end();
+            self.pop_exception_frames_at_return();
+            self.ctx.builder.build_return(None).unwrap();
+            return;
+        };
+        // Hold the returned instance while the locals let go of it,
+        // then hand it to the caller without a reference
+        let counted = self.ctx.is_refcounted(ty, program);
+        if counted {
+            self.rc_call("__pyc_rc_incref", value);
+        }
+        self.rc_end_scope();
+        if counted {
+            self.rc_call("__pyc_rc_unown", value);
+        }
+        let managed = self.ctx.is_managed(ty, program);
+        self.gc_end_scope(managed.then_some(value));
+        self.traceback_end();
+        self.recursion_end();
+        self.pop_exception_frames_at_return();
+        self.ctx.builder.build_return(Some(&value)).unwrap();
+    }
+
+    fn block_terminated(&self) -> bool {
+        match self.ctx.builder.get_insert_block() {
+            Some(block) => block.get_terminator().is_some(),
+            None => true,
         }
     }
 }
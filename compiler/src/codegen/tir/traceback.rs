@@ -0,0 +1,112 @@
+//! Traceback records (`pycc --traceback`)
+//!
+//! The runtime prints the functions an uncaught exception was raised
+//! through, outermost first, as Python does (runtime/src/exception.c), from
+//! a stack of records codegen maintains:
+//!
+//! - every function lowered from a `def`, and every module init, pushes a
+//!   record with its name and file on entry, and pops it on every return
+//! - each statement with a known position stores its line in the record,
+//!   so that a caller's record holds the line of the call it is in
+//!
+//! A try block's exception frame remembers the top record, which a raise
+//! jumping to the block restores (`--exceptions=setjmp`); with polling, the
+//! functions between the raise and the handler return and pop their own.
+
+use inkwell::types::StructType;
+use inkwell::values::BasicValueEnum;
+use inkwell::AddressSpace;
+
+use crate::tir::{ModuleId, TirProgram};
+
+use super::function_gen::FunctionGenContext;
+
+impl<'ctx, 'a> FunctionGenContext<'ctx, 'a> {
+    /// Push the record of the function `name` of `module`
+    pub(crate) fn traceback_begin(&mut self, name: &str, module: ModuleId, program: &TirProgram) {
+        if !self.ctx.traceback {
+            return;
+        }
+        let record_type = self.traceback_record_type();
+        let record = self.at_function_entry(|this| {
+            this.ctx
+                .builder
+                .build_alloca(record_type, "traceback")
+                .unwrap()
+        });
+        let path = program.module(module).path.display().to_string();
+        let fields: [BasicValueEnum<'ctx>; 3] = [
+            self.ctx
+                .builder
+                .build_global_string_ptr(name, "traceback_function")
+                .unwrap()
+                .as_pointer_value()
+                .into(),
+            self.ctx
+                .builder
+                .build_global_string_ptr(&path, "traceback_file")
+                .unwrap()
+                .as_pointer_value()
+                .into(),
+            self.ctx.context.i64_type().const_zero().into(),
+        ];
+        for (i, value) in fields.into_iter().enumerate() {
+            let field = self
+                .ctx
+                .builder
+                .build_struct_gep(record_type, record, i as u32 + 1, "traceback_field")
+                .unwrap();
+            self.ctx.builder.build_store(field, value).unwrap();
+        }
+        let push = self
+            .ctx
+            .module
+            .get_function("__pyc_traceback_push")
+            .unwrap();
+        self.ctx
+            .builder
+            .build_call(push, &[record.into()], "")
+            .unwrap();
+        self.traceback_record = Some(record);
+    }
+
+    /// Store the line of the statement starting in the function's record
+    pub(crate) fn traceback_line(&mut self, line: u32) {
+        let Some(record) = self.traceback_record else {
+            return;
+        };
+        let record_type = self.traceback_record_type();
+        let field = self
+            .ctx
+            .builder
+            .build_struct_gep(record_type, record, 3, "traceback_line")
+            .unwrap();
+        let line = self.ctx.context.i64_type().const_int(line as u64, false);
+        self.ctx.builder.build_store(field, line).unwrap();
+    }
+
+    /// Pop the function's record before a return
+    pub(crate) fn traceback_end(&mut self) {
+        if self.traceback_record.is_none() {
+            return;
+        }
+        let pop = self.ctx.module.get_function("__pyc_traceback_pop").unwrap();
+        self.ctx.builder.build_call(pop, &[], "").unwrap();
+    }
+
+    /// The runtime's TracebackFrame (exception.h): the record below, the
+    /// function's name and file, and the line being run
+    fn traceback_record_type(&self) -> StructType<'ctx> {
+        let ptr_type = self.ctx.context.ptr_type(AddressSpace::default());
+        let i64_type = self.ctx.context.i64_type();
+        self.ctx.context.struct_type(
+            &[
+                ptr_type.into(),
+                ptr_type.into(),
+                ptr_type.into(),
+                i64_type.into(),
+            ],
+            false,
+        )
+    }
+}
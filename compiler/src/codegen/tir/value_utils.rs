@@ -2,7 +2,7 @@ use inkwell::values::{BasicValueEnum, PointerValue};
 
 use crate::tir::expr::VarRef;
 use crate::tir::stmt::TirLValue;
-use crate::tir::TirProgram;
+use crate::tir::{TirProgram, TirType};
 
 use super::function_gen::FunctionGenContext;
 
@@ -90,9 +90,10 @@ impl<'ctx, 'a> FunctionGenContext<'ctx, 'a> {
                 let class_def = program.class(*class);
                 let class_type = self.ctx.class_types[&class_def.qualified_name];
                 let obj_ptr = self.value_to_pointer(obj_val);
+                let index = self.ctx.field_index(*class, field.index(), program);
                 self.ctx
                     .builder
-                    .build_struct_gep(class_type, obj_ptr, field.index() as u32, "field_ptr")
+                    .build_struct_gep(class_type, obj_ptr, index, "field_ptr")
                     .unwrap()
             }
         }
@@ -105,7 +106,11 @@ impl<'ctx, 'a> FunctionGenContext<'ctx, 'a> {
         program: &TirProgram,
     ) {
         let ptr = self.codegen_lvalue(lvalue, program);
-        self.ctx.builder.build_store(ptr, value).unwrap();
+        if self.is_refcounted_lvalue(lvalue, program) {
+            self.rc_store(ptr, value);
+        } else {
+            self.ctx.builder.build_store(ptr, value).unwrap();
+        }
     }
 
     /// Convert a value to a pointer, using int_to_ptr if necessary
@@ -194,6 +199,33 @@ impl<'ctx, 'a> FunctionGenContext<'ctx, 'a> {
         }
     }
 
+    /// Convert an i64 container slot back to the LLVM representation of `ty`
+    /// (the inverse of value_to_i64): pointers for classes, tuples and optionals, i8 for bool,
+    /// f64 for float; int slots are returned unchanged
+    pub(crate) fn i64_slot_to_value(
+        &mut self,
+        slot: inkwell::values::IntValue<'ctx>,
+        ty: &TirType,
+    ) -> BasicValueEnum<'ctx> {
+        match ty {
+            TirType::Class(_) | TirType::Tuple(_) | TirType::Optional(_) | TirType::NoneType => {
+                self.value_to_pointer(slot.into()).into()
+            }
+            TirType::Bool => self
+                .ctx
+                .builder
+                .build_int_truncate(slot, self.ctx.context.i8_type(), "i64_to_bool")
+                .unwrap()
+                .into(),
+            TirType::Float => self
+                .ctx
+                .builder
+                .build_bit_cast(slot, self.ctx.context.f64_type(), "i64_to_float")
+                .unwrap(),
+            TirType::Int | TirType::Void => slot.into(),
+        }
+    }
+
     /// Convert a value to bool (i1 for branching), comparing to zero if necessary
     /// TIR types only produce IntValue, FloatValue, or PointerValue - other cases are handled
     /// for exhaustiveness but should never occur with valid TIR.
@@ -0,0 +1,575 @@
+//! Rendering compile errors for people
+//!
+//! An error located in a file is shown with a frame of the source around it,
+//! as rustc shows its own:
+//!
+//! ```text
+//! error: Cannot assign Str to variable 'x' of type Int
+//!  --> app.py:4:5
+//!   |
+//! 2 |     x = 1
+//!   |     ----- 'x' gets its type here
+//! ...
+//! 4 |     x = "hello"
+//!   |     ^^^^^^^^^^^
+//!   |
+//!   = help: ...
+//! ```
+//!
+//! The primary caret underlines the statement the error is in, secondary
+//! labels (`CompilerError::with_label`) other lines of the same file that
+//! explain it, and help notes (`CompilerError::with_help`) follow the frame.
+//! The source is read back from the file only here, on the way out.
+//!
+//! Warnings (see `lint`) are rendered the same way. With
+//! `--error-format=short`, each diagnostic is instead one line,
+//! `file:line:column: error: message`, for tools to read. With
+//! `--error-format=json`, each is one line of JSON, for editors and CI:
+//!
+//! ```text
+//! {"severity":"error","code":null,"message":"Cannot assign Str to variable 'x' of type Int",
+//!  "file":"app.py","span":{"line":4,"column":5},
+//!  "children":[{"severity":"note","message":"'x' gets its type here","span":{"line":2,"column":5}}],
+//!  "rendered":"error: Cannot assign ..."}
+//! ```
+//!
+//! Labels are `note` children with their span, help notes `help` children
+//! without one, and `rendered` is the human form.
+
+use std::fmt::Write;
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::error::CompilerError;
+use crate::lint::Warning;
+use crate::span::Span;
+
+/// A note on another position of the file an error is in
+#[derive(Debug, Clone)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+}
+
+/// Whether a diagnostic stops the build
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Severity {
+    #[default]
+    Error,
+    Warning,
+}
+
+impl Severity {
+    pub fn name(self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        }
+    }
+
+    fn style(self) -> &'static str {
+        match self {
+            Severity::Error => RED,
+            Severity::Warning => YELLOW,
+        }
+    }
+}
+
+/// How diagnostics are printed (`--error-format`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ErrorFormat {
+    /// With a frame of the source, labels and help
+    #[default]
+    Human,
+    /// One `file:line:column: severity: message` line each
+    Short,
+    /// One JSON object per line each
+    Json,
+}
+
+impl FromStr for ErrorFormat {
+    type Err = CompilerError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "human" => Ok(ErrorFormat::Human),
+            "short" => Ok(ErrorFormat::Short),
+            "json" => Ok(ErrorFormat::Json),
+            _ => Err(CompilerError::CodegenError(format!(
+                "Unknown error format '{s}'. Supported: human, short, json"
+            ))),
+        }
+    }
+}
+
+/// One error or warning, ready to render: its message, where it is, and
+/// what explains it
+#[derive(Debug, Clone, Default)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// The lint a warning (or a warning made an error) is about
+    pub code: Option<String>,
+    pub message: String,
+    pub path: Option<PathBuf>,
+    pub span: Option<Span>,
+    pub labels: Vec<Label>,
+    pub help: Vec<String>,
+}
+
+/// ANSI styles of the parts of a diagnostic
+const RED: &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const BLUE: &str = "\x1b[1;34m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+fn paint(text: &str, style: &str, color: bool) -> String {
+    if color && !text.is_empty() {
+        format!("{}{}{}", style, text, RESET)
+    } else {
+        text.to_string()
+    }
+}
+
+/// A line of the frame: its number, then what to underline on it
+struct FrameLine {
+    line: u32,
+    marks: Vec<(Span, char, String)>,
+}
+
+impl Diagnostic {
+    /// The diagnostics of `error`, one per error it collects
+    pub fn from_error(error: &CompilerError) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        collect(error, Diagnostic::default(), &mut diagnostics);
+        diagnostics
+    }
+
+    /// The diagnostic of a warning
+    pub fn from_warning(warning: &Warning) -> Diagnostic {
+        Diagnostic {
+            severity: Severity::Warning,
+            code: Some(warning.lint.name().to_string()),
+            message: warning.message.clone(),
+            path: Some(warning.path.clone()),
+            span: Some(warning.span),
+            ..Diagnostic::default()
+        }
+    }
+
+    /// `error` or `warning`, with the lint if any: `warning[unused-import]`
+    fn title(&self) -> String {
+        match &self.code {
+            Some(code) => format!("{}[{}]", self.severity.name(), code),
+            None => self.severity.name().to_string(),
+        }
+    }
+
+    /// Render the diagnostic as one line, `file:line:column: error: message`
+    pub fn render_short(&self) -> String {
+        match (&self.path, self.span) {
+            (Some(path), Some(span)) => format!(
+                "{}:{}: {}: {}\n",
+                path.display(),
+                span,
+                self.title(),
+                self.message
+            ),
+            (None, Some(span)) => format!("line {}: {}: {}\n", span, self.title(), self.message),
+            _ => format!("{}: {}\n", self.title(), self.message),
+        }
+    }
+
+    /// Render the diagnostic as one line of JSON: its severity, code,
+    /// message, file and span, its labels and help as children, and its
+    /// human form
+    pub fn render_json(&self) -> String {
+        let children: Vec<String> = self
+            .labels
+            .iter()
+            .map(|label| json_child("note", &label.message, Some(label.span)))
+            .chain(self.help.iter().map(|help| json_child("help", help, None)))
+            .collect();
+        let path = self.path.as_ref().map(|path| path.display().to_string());
+        format!(
+            "{{\"severity\":{},\"code\":{},\"message\":{},\"file\":{},\"span\":{},\"children\":[{}],\"rendered\":{}}}\n",
+            json_string(self.severity.name()),
+            json_option(self.code.as_deref()),
+            json_string(&self.message),
+            json_option(path.as_deref()),
+            json_span(self.span),
+            children.join(","),
+            json_string(&self.render(false))
+        )
+    }
+
+    /// Render the diagnostic, with ANSI colors if `color`
+    pub fn render(&self, color: bool) -> String {
+        let mut out = format!(
+            "{}{}\n",
+            paint(&self.title(), self.severity.style(), color),
+            paint(&format!(": {}", self.message), BOLD, color)
+        );
+        let Some(span) = self.span else {
+            for help in &self.help {
+                let _ = writeln!(out, "  = help: {}", help);
+            }
+            return out;
+        };
+
+        let source = self
+            .path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok());
+        let lines: Vec<&str> = source
+            .as_deref()
+            .map(|s| s.lines().collect())
+            .unwrap_or_default();
+        let frame = self.frame_lines(span, lines.len());
+        let width = frame
+            .iter()
+            .map(|f| f.line)
+            .max()
+            .unwrap_or(span.line)
+            .to_string()
+            .len();
+        let gutter = paint(&format!("{} |", " ".repeat(width)), BLUE, color);
+
+        let location = match &self.path {
+            Some(path) => format!("{}:{}", path.display(), span),
+            None => format!("line {}", span),
+        };
+        let _ = writeln!(
+            out,
+            "{}{} {}",
+            " ".repeat(width),
+            paint("-->", BLUE, color),
+            location
+        );
+        if !frame.is_empty() {
+            let _ = writeln!(out, "{}", gutter);
+        }
+        let mut previous: Option<u32> = None;
+        for frame_line in &frame {
+            if previous.is_some_and(|p| frame_line.line > p + 1) {
+                let _ = writeln!(out, "{}", paint("...", BLUE, color));
+            }
+            previous = Some(frame_line.line);
+            let text = lines[frame_line.line as usize - 1];
+            let number = paint(
+                &format!("{:>width$} |", frame_line.line, width = width),
+                BLUE,
+                color,
+            );
+            let _ = writeln!(out, "{} {}", number, text);
+            for (mark_span, mark, message) in &frame_line.marks {
+                let (indent, length) = underline(text, mark_span.column);
+                let style = if *mark == '^' {
+                    self.severity.style()
+                } else {
+                    BLUE
+                };
+                let mut marks = mark.to_string().repeat(length);
+                if !message.is_empty() {
+                    marks.push(' ');
+                    marks.push_str(message);
+                }
+                let _ = writeln!(out, "{} {}{}", gutter, indent, paint(&marks, style, color));
+            }
+        }
+        if !self.help.is_empty() {
+            if !frame.is_empty() {
+                let _ = writeln!(out, "{}", gutter);
+            }
+            for help in &self.help {
+                let _ = writeln!(
+                    out,
+                    "{} {} help: {}",
+                    " ".repeat(width),
+                    paint("=", BLUE, color),
+                    help
+                );
+            }
+        }
+        out
+    }
+
+    /// The lines of a file of `line_count` lines to show, in order: the
+    /// error's, and those of the labels
+    fn frame_lines(&self, span: Span, line_count: usize) -> Vec<FrameLine> {
+        let marks = std::iter::once((span, '^', String::new())).chain(
+            self.labels
+                .iter()
+                .map(|label| (label.span, '-', label.message.clone())),
+        );
+        let mut frame: Vec<FrameLine> = Vec::new();
+        for (mark_span, mark, message) in marks {
+            if !mark_span.is_known() || mark_span.line as usize > line_count {
+                continue;
+            }
+            match frame.iter_mut().find(|f| f.line == mark_span.line) {
+                Some(frame_line) => frame_line.marks.push((mark_span, mark, message)),
+                None => frame.push(FrameLine {
+                    line: mark_span.line,
+                    marks: vec![(mark_span, mark, message)],
+                }),
+            }
+        }
+        frame.sort_by_key(|f| f.line);
+        frame
+    }
+}
+
+/// Add the diagnostics of `error` to `out`, each starting from what the
+/// errors around it say (`context`)
+fn collect(error: &CompilerError, context: Diagnostic, out: &mut Vec<Diagnostic>) {
+    match error {
+        CompilerError::Multiple(errors) => {
+            for error in errors {
+                collect(error, context.clone(), out);
+            }
+        }
+        CompilerError::Located { path, span, error } => {
+            let context = Diagnostic {
+                path: path.clone().or(context.path),
+                span: Some(*span),
+                ..context
+            };
+            collect(error, context, out);
+        }
+        CompilerError::Annotated {
+            error,
+            labels,
+            help,
+        } => {
+            let mut context = context;
+            context.labels.extend(labels.iter().cloned());
+            context.help.extend(help.iter().cloned());
+            collect(error, context, out);
+        }
+        CompilerError::Lint { lint, message } => out.push(Diagnostic {
+            code: Some(lint.name().to_string()),
+            message: message.clone(),
+            ..context
+        }),
+        error => out.push(Diagnostic {
+            message: error.to_string(),
+            ..context
+        }),
+    }
+}
+
+/// The indent before the mark under `column` of `text`, and how many
+/// characters to mark: the rest of the line, without trailing whitespace
+/// or comment
+fn underline(text: &str, column: u32) -> (String, usize) {
+    // Columns count UTF-8 bytes, as Python's col_offset does
+    let start = (column as usize).saturating_sub(1).min(text.len());
+    let prefix = text.get(..start).unwrap_or("");
+    // Tabs are kept, so that the marks line up however they are shown
+    let indent = prefix
+        .chars()
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect();
+    let rest = text.get(start..).unwrap_or("");
+    let rest = strip_comment(rest).trim_end();
+    (indent, rest.chars().count().max(1))
+}
+
+/// `text` up to a `#` comment outside string literals
+pub(crate) fn strip_comment(text: &str) -> &str {
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    for (i, c) in text.char_indices() {
+        match quote {
+            Some(_) if escaped => escaped = false,
+            Some(_) if c == '\\' => escaped = true,
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == '#' => return &text[..i],
+            None => {}
+        }
+    }
+    text
+}
+
+/// A child of a JSON diagnostic: a label or help note
+fn json_child(severity: &str, message: &str, span: Option<Span>) -> String {
+    format!(
+        "{{\"severity\":{},\"message\":{},\"span\":{}}}",
+        json_string(severity),
+        json_string(message),
+        json_span(span)
+    )
+}
+
+/// `{"line":L,"column":C}`, or `null` for a diagnostic of no position
+fn json_span(span: Option<Span>) -> String {
+    match span.filter(|span| span.is_known()) {
+        Some(span) => format!("{{\"line\":{},\"column\":{}}}", span.line, span.column),
+        None => "null".to_string(),
+    }
+}
+
+fn json_option(text: Option<&str>) -> String {
+    text.map(json_string).unwrap_or_else(|| "null".to_string())
+}
+
+/// `text` as a JSON string literal
+pub(crate) fn json_string(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + 2);
+    out.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Render `error` in `format`, with ANSI colors if `color`: each error it
+/// collects with its frame, then how many there are
+pub fn render(error: &CompilerError, format: ErrorFormat, color: bool) -> String {
+    let diagnostics = Diagnostic::from_error(error);
+    match format {
+        ErrorFormat::Short => return diagnostics.iter().map(Diagnostic::render_short).collect(),
+        ErrorFormat::Json => return diagnostics.iter().map(Diagnostic::render_json).collect(),
+        ErrorFormat::Human => {}
+    }
+    let mut out = diagnostics
+        .iter()
+        .map(|diagnostic| diagnostic.render(color))
+        .collect::<Vec<_>>()
+        .join("\n");
+    if diagnostics.len() > 1 {
+        let _ = writeln!(
+            out,
+            "\n{}",
+            paint(
+                &format!("error: aborting due to {} errors", diagnostics.len()),
+                RED,
+                color
+            )
+        );
+    }
+    out
+}
+
+/// Render `warnings` in `format`, with ANSI colors if `color`
+pub fn render_warnings(warnings: &[Warning], format: ErrorFormat, color: bool) -> String {
+    let diagnostics = warnings.iter().map(Diagnostic::from_warning);
+    match format {
+        ErrorFormat::Short => diagnostics.map(|d| d.render_short()).collect(),
+        ErrorFormat::Json => diagnostics.map(|d| d.render_json()).collect(),
+        ErrorFormat::Human => diagnostics.map(|d| d.render(color) + "\n").collect(),
+    }
+}
+
+/// The name of `candidates` closest to `name`, if one is close enough to be
+/// a misspelling of it
+pub fn suggest<'a>(name: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    // As many edits as rustc allows: a third of the name's length
+    let limit = (name.chars().count() / 3).max(1);
+    candidates
+        .into_iter()
+        .filter(|candidate| *candidate != name)
+        // Names the compiler makes up start with an underscore
+        .filter(|candidate| !candidate.starts_with('_') || name.starts_with('_'))
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= limit)
+        .min()
+        .map(|(_, candidate)| candidate)
+}
+
+/// The edit distance between `a` and `b`: how many characters to insert,
+/// delete, replace or swap with the next one to make one the other
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_suggest_close_names() {
+        let names = ["count", "counter", "total", "_tmp_3"];
+        assert_eq!(suggest("coutn", names), Some("count"));
+        assert_eq!(suggest("totl", names), Some("total"));
+        assert_eq!(suggest("xyz", names), None);
+        assert_eq!(suggest("tmp_3", names), None);
+    }
+
+    #[test]
+    fn test_underline_stops_before_comment() {
+        assert_eq!(underline("    x = 1  # one", 5), ("    ".to_string(), 5));
+        assert_eq!(underline("\tx = '#'", 2), ("\t".to_string(), 7));
+    }
+
+    #[test]
+    fn test_render_without_file() {
+        let error = CompilerError::UndefinedVariable("coutn".to_string())
+            .with_help("a local variable with a similar name exists: 'count'")
+            .at(Span::new(3, 5));
+        let rendered = render(&error, ErrorFormat::Human, false);
+        assert_eq!(
+            rendered,
+            "error: Undefined variable: 'coutn'\n \
+             --> line 3:5\n  \
+             = help: a local variable with a similar name exists: 'count'\n"
+        );
+        assert_eq!(
+            render(&error, ErrorFormat::Short, false),
+            "line 3:5: error: Undefined variable: 'coutn'\n"
+        );
+    }
+
+    #[test]
+    fn test_render_json() {
+        let error = CompilerError::UndefinedVariable("coutn".to_string())
+            .with_label(Span::new(2, 5), "\"count\" is bound here")
+            .with_help("a local variable with a similar name exists: 'count'")
+            .at(Span::new(3, 5));
+        let json = render(&error, ErrorFormat::Json, false);
+        assert!(json.starts_with(
+            "{\"severity\":\"error\",\"code\":null,\"message\":\"Undefined variable: 'coutn'\",\
+             \"file\":null,\"span\":{\"line\":3,\"column\":5},\"children\":[\
+             {\"severity\":\"note\",\"message\":\"\\\"count\\\" is bound here\",\"span\":{\"line\":2,\"column\":5}},\
+             {\"severity\":\"help\",\"message\":\"a local variable with a similar name exists: 'count'\",\"span\":null}],\
+             \"rendered\":\"error: Undefined variable: 'coutn'\\n"
+        ));
+        assert!(json.ends_with("}\n"));
+        assert_eq!(json.lines().count(), 1);
+    }
+}
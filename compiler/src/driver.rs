@@ -1,18 +1,38 @@
 use inkwell::context::Context;
+use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
 use std::env;
+use std::ffi::OsString;
+use std::fmt;
 use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io::{BufRead, IsTerminal, Write};
 use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::str::FromStr;
+use std::sync::Arc;
 
-use pyo3::Python;
+use pyo3::types::{PyAnyMethods, PyModule};
+use pyo3::{PyResult, Python};
 
+use crate::ast::default_methods::synthesize_default_methods;
+use crate::ast::super_init::insert_super_init_calls;
 use crate::ast::{AstConverter, Module, ModuleName};
+use crate::cache::{fingerprint, BuildCache, Fingerprint, ModuleCache};
 use crate::codegen::generator::Codegen;
-use crate::error::{CompilerError, Result};
+use crate::codegen::{
+    run_jit, undefined_symbols, write_assembly_file, write_object_file, EmbeddedFile, JitSession,
+};
+use crate::diagnostics::{self, ErrorFormat};
+use crate::error::{CompilerError, ErrorCollector, Result};
+use crate::lint::Warning;
+use crate::project::Project;
 use crate::python_ast::parse_python;
-use crate::tir::lower_to_tir;
+use crate::repl;
+use crate::staticlib;
+use crate::tir::opt::optimize;
+use crate::tir::{lower_to_tir, TirProgram};
+use crate::vfs::{DiskFileSystem, FileSystem, MemoryFileSystem, OverlayFileSystem};
 
 /// Target-specific configuration
 struct TargetConfig {
@@ -23,6 +43,18 @@ struct TargetConfig {
     musl_lib_path: &'static str,
     icu_lib_path: &'static str,
     libcxx_lib_path: &'static str,
+    /// GCC's libraries for the target (libstdc++ and libgcc)
+    gcc_lib_path: &'static str,
+    /// Whether `long double` is a 128-bit float, whose arithmetic in musl's
+    /// printf needs libgcc's soft-float routines
+    soft_float_long_double: bool,
+    /// Whether LLVM can lower the setjmp/longjmp intrinsics behind
+    /// `--exceptions=setjmp` for this architecture
+    setjmp_exceptions: bool,
+    /// LLVM target features and ABI the program is compiled for, matching
+    /// the flags the runtime is built with
+    llvm_features: &'static str,
+    llvm_abi: Option<&'static str>,
 }
 
 const X86_64_CONFIG: TargetConfig = TargetConfig {
@@ -33,6 +65,11 @@ const X86_64_CONFIG: TargetConfig = TargetConfig {
     musl_lib_path: runtime::MUSL_X86_64_LIB,
     icu_lib_path: runtime::ICU_X86_64_LIB,
     libcxx_lib_path: runtime::LIBCXX_X86_64_LIB,
+    gcc_lib_path: "/usr/lib/gcc/x86_64-linux-gnu/13",
+    soft_float_long_double: false,
+    setjmp_exceptions: true,
+    llvm_features: "",
+    llvm_abi: None,
 };
 
 const RISCV64_CONFIG: TargetConfig = TargetConfig {
@@ -43,6 +80,27 @@ const RISCV64_CONFIG: TargetConfig = TargetConfig {
     musl_lib_path: runtime::MUSL_RISCV64_LIB,
     icu_lib_path: runtime::ICU_RISCV64_LIB,
     libcxx_lib_path: runtime::LIBCXX_RISCV64_LIB,
+    gcc_lib_path: "/usr/lib/gcc-cross/riscv64-linux-gnu/13",
+    soft_float_long_double: true,
+    setjmp_exceptions: false,
+    // riscv64gc with hard floats (clang's -mabi=lp64d)
+    llvm_features: "+m,+a,+f,+d,+c",
+    llvm_abi: Some("lp64d"),
+};
+
+const AARCH64_CONFIG: TargetConfig = TargetConfig {
+    triple: "aarch64-unknown-linux-musl",
+    clang_target: Some("--target=aarch64-linux-musl"),
+    runtime_filename: "runtime-aarch64.o",
+    qemu_command: Some("qemu-aarch64"),
+    musl_lib_path: runtime::MUSL_AARCH64_LIB,
+    icu_lib_path: runtime::ICU_AARCH64_LIB,
+    libcxx_lib_path: runtime::LIBCXX_AARCH64_LIB,
+    gcc_lib_path: "/usr/lib/gcc-cross/aarch64-linux-gnu/13",
+    soft_float_long_double: true,
+    setjmp_exceptions: true,
+    llvm_features: "",
+    llvm_abi: None,
 };
 
 /// Target architecture for compilation
@@ -51,6 +109,7 @@ pub enum Target {
     #[default]
     X86_64,
     RiscV64,
+    Aarch64,
 }
 
 impl Target {
@@ -58,6 +117,7 @@ impl Target {
         match self {
             Target::X86_64 => &X86_64_CONFIG,
             Target::RiscV64 => &RISCV64_CONFIG,
+            Target::Aarch64 => &AARCH64_CONFIG,
         }
     }
 
@@ -77,6 +137,35 @@ impl Target {
         self.config().qemu_command
     }
 
+    /// Whether try blocks can be compiled with `--exceptions=setjmp`
+    pub fn supports_setjmp_exceptions(&self) -> bool {
+        self.config().setjmp_exceptions
+    }
+
+    /// LLVM target features to generate code for (`+m,+a,...`)
+    pub fn llvm_features(&self) -> &'static str {
+        self.config().llvm_features
+    }
+
+    /// The calling convention ABI, where LLVM's default is not the one the
+    /// runtime is built with
+    pub fn llvm_abi(&self) -> Option<&'static str> {
+        self.config().llvm_abi
+    }
+
+    /// The architecture the compiler runs on, if it is one of the targets
+    pub fn host() -> Option<Target> {
+        if cfg!(target_arch = "x86_64") {
+            Some(Target::X86_64)
+        } else if cfg!(target_arch = "riscv64") {
+            Some(Target::RiscV64)
+        } else if cfg!(target_arch = "aarch64") {
+            Some(Target::Aarch64)
+        } else {
+            None
+        }
+    }
+
     /// Get the musl library directory (set at compile time by runtime crate)
     pub fn musl_lib_dir(&self) -> PathBuf {
         PathBuf::from(self.config().musl_lib_path)
@@ -92,6 +181,16 @@ impl Target {
         PathBuf::from(self.config().libcxx_lib_path)
     }
 
+    /// GCC's library directory for the target (libstdc++, libgcc)
+    pub fn gcc_lib_dir(&self) -> PathBuf {
+        PathBuf::from(self.config().gcc_lib_path)
+    }
+
+    /// Whether linking needs libgcc's 128-bit float routines even without ICU
+    fn soft_float_long_double(&self) -> bool {
+        self.config().soft_float_long_double
+    }
+
     fn find_workspace_root() -> Option<PathBuf> {
         // Try CARGO_MANIFEST_DIR first (available during tests)
         if let Ok(manifest_dir) = env::var("CARGO_MANIFEST_DIR") {
@@ -127,29 +226,377 @@ impl FromStr for Target {
         match s.to_lowercase().as_str() {
             "x86_64" | "x86-64" | "amd64" => Ok(Target::X86_64),
             "riscv64" | "riscv" | "riscv64gc" => Ok(Target::RiscV64),
+            "aarch64" | "arm64" => Ok(Target::Aarch64),
+            _ => Err(CompilerError::CodegenError(format!(
+                "Unknown target '{s}'. Supported: x86_64, riscv64, aarch64"
+            ))),
+        }
+    }
+}
+
+/// How class instances are reclaimed, besides `--refcount`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GcMode {
+    /// Instances live until the program exits (or their count drops to zero)
+    #[default]
+    None,
+    /// A mark-sweep collector frees unreachable instances, cycles included
+    Tracing,
+}
+
+impl FromStr for GcMode {
+    type Err = CompilerError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "none" => Ok(GcMode::None),
+            "tracing" => Ok(GcMode::Tracing),
+            _ => Err(CompilerError::CodegenError(format!(
+                "Unknown garbage collector '{s}'. Supported: none, tracing"
+            ))),
+        }
+    }
+}
+
+/// Where the runtime gets its memory from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AllocMode {
+    /// malloc and free
+    #[default]
+    Malloc,
+    /// A bump arena that grows as needed and is released at exit; freeing
+    /// does nothing
+    Arena,
+}
+
+impl FromStr for AllocMode {
+    type Err = CompilerError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "malloc" => Ok(AllocMode::Malloc),
+            "arena" => Ok(AllocMode::Arena),
+            _ => Err(CompilerError::CodegenError(format!(
+                "Unknown allocator '{s}'. Supported: malloc, arena"
+            ))),
+        }
+    }
+}
+
+/// How a raised exception reaches the `except` clause that handles it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExceptionModel {
+    /// A raise records the exception and returns; try blocks check for it
+    /// after every statement. Works on every target.
+    #[default]
+    Polling,
+    /// Try blocks save their registers with setjmp and a raise longjmps back
+    /// to the innermost one, so statements that do not raise cost nothing.
+    /// x86_64 and aarch64 only.
+    SetJmp,
+}
+
+impl FromStr for ExceptionModel {
+    type Err = CompilerError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "polling" => Ok(ExceptionModel::Polling),
+            "setjmp" => Ok(ExceptionModel::SetJmp),
             _ => Err(CompilerError::CodegenError(format!(
-                "Unknown target '{s}'. Supported: x86_64, riscv64"
+                "Unknown exception model '{s}'. Supported: polling, setjmp"
             ))),
         }
     }
 }
 
+/// How much the compiler optimizes the program
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum OptLevel {
+    /// The program is compiled as written
+    #[default]
+    O0,
+    /// Constant expressions are folded, branches that are never taken and
+    /// locals that are never read are removed, and small functions are
+    /// inlined; the generated module then goes through LLVM's `-O1` passes
+    O1,
+    /// As `O1`, with LLVM's `-O2` passes
+    O2,
+    /// As `O1`, with LLVM's `-O3` passes
+    O3,
+    /// As `O1`, with LLVM's `-Os` passes, which favour smaller code
+    Os,
+}
+
+impl FromStr for OptLevel {
+    type Err = CompilerError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "0" | "o0" => Ok(OptLevel::O0),
+            "1" | "o1" => Ok(OptLevel::O1),
+            "2" | "o2" => Ok(OptLevel::O2),
+            "3" | "o3" => Ok(OptLevel::O3),
+            "s" | "os" => Ok(OptLevel::Os),
+            _ => Err(CompilerError::CodegenError(format!(
+                "Unknown optimization level '{s}'. Supported: 0, 1, 2, 3, s"
+            ))),
+        }
+    }
+}
+
+/// The program that links executables for the built-in targets
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Linker {
+    /// clang when it is installed, lld otherwise
+    #[default]
+    Auto,
+    /// The clang driver, which runs lld for cross targets
+    Clang,
+    /// `ld.lld`, or the `rust-lld` of the Rust toolchain, run directly
+    Lld,
+}
+
+impl FromStr for Linker {
+    type Err = CompilerError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "auto" => Ok(Linker::Auto),
+            "clang" => Ok(Linker::Clang),
+            "lld" => Ok(Linker::Lld),
+            _ => Err(CompilerError::CodegenError(format!(
+                "Unknown linker '{s}'. Supported: auto, clang, lld"
+            ))),
+        }
+    }
+}
+
+/// What becomes of the warnings lowering finds (see `lint`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WarningLevel {
+    /// They are printed, and the build goes on
+    #[default]
+    Warn,
+    /// They are not printed
+    Allow,
+    /// They are errors, and the build fails
+    Error,
+}
+
+impl FromStr for WarningLevel {
+    type Err = CompilerError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "warn" => Ok(WarningLevel::Warn),
+            "allow" => Ok(WarningLevel::Allow),
+            "error" => Ok(WarningLevel::Error),
+            _ => Err(CompilerError::CodegenError(format!(
+                "Unknown warning level '{s}'. Supported: warn, allow, error"
+            ))),
+        }
+    }
+}
+
+/// An artifact of compilation written next to the output (`--emit=llvm-ir`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmitKind {
+    /// LLVM IR as text (`.ll`)
+    LlvmIr,
+    /// LLVM bitcode (`.bc`)
+    LlvmBc,
+    /// Target assembly (`.s`)
+    Asm,
+    /// Native object file (`.o`)
+    Obj,
+    /// A static library (`.a`) of the program's `@export` functions, with
+    /// its interface (`.pyi`), written to the output path instead of an
+    /// executable (see staticlib.rs)
+    StaticLib,
+}
+
+impl EmitKind {
+    /// Extension of the file the artifact is written to
+    pub fn extension(self) -> &'static str {
+        match self {
+            EmitKind::LlvmIr => "ll",
+            EmitKind::LlvmBc => "bc",
+            EmitKind::Asm => "s",
+            EmitKind::Obj => "o",
+            EmitKind::StaticLib => "a",
+        }
+    }
+}
+
+impl FromStr for EmitKind {
+    type Err = CompilerError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "llvm-ir" => Ok(EmitKind::LlvmIr),
+            "llvm-bc" => Ok(EmitKind::LlvmBc),
+            "asm" => Ok(EmitKind::Asm),
+            "obj" => Ok(EmitKind::Obj),
+            "staticlib" => Ok(EmitKind::StaticLib),
+            _ => Err(CompilerError::CodegenError(format!(
+                "Unknown emit kind '{s}'. Supported: llvm-ir, llvm-bc, asm, obj, staticlib"
+            ))),
+        }
+    }
+}
+
+/// Default linker command for custom targets
+///
+/// Placeholders: `{triple}`, `{sysroot}`, `{crt_begin}`, `{crt_end}`, `{inputs}` (compiled
+/// object followed by the runtime object) and `{output}`. A token that is a placeholder
+/// becomes one argument per value, none if it is empty (e.g., `{crt_end}` without crt
+/// objects). Inside a larger token, each value gives an argument with the rest of the
+/// token around it (`-Wl,{crt_begin}`), and an empty placeholder is an error (e.g.,
+/// `--sysroot={sysroot}` without a sysroot).
+pub const DEFAULT_LINKER_TEMPLATE: &str = "clang --target={triple} --sysroot={sysroot} \
+     -fuse-ld=lld -static -nostdlib {crt_begin} {inputs} -L{sysroot}/lib -lc {crt_end} \
+     -o {output} -flto -O2";
+
+/// User-supplied target for triples beyond the built-in configurations
+#[derive(Debug, Clone, Default)]
+pub struct CustomTarget {
+    /// LLVM target triple (e.g., "aarch64-none-elf")
+    pub triple: String,
+    /// Sysroot containing the target's libc
+    pub sysroot: Option<PathBuf>,
+    /// Startup objects linked before the program (e.g., crt1.o, crti.o)
+    pub crt_begin: Vec<PathBuf>,
+    /// Objects linked after everything else (e.g., crtn.o)
+    pub crt_end: Vec<PathBuf>,
+    /// Runtime object built for this triple
+    pub runtime: Option<PathBuf>,
+    /// Linker command template (defaults to [`DEFAULT_LINKER_TEMPLATE`])
+    pub linker_template: Option<String>,
+}
+
+impl CustomTarget {
+    /// Expand the linker template into a command
+    pub fn linker_command(&self, inputs: &[PathBuf], output: &Path) -> Result<Command> {
+        let template = self
+            .linker_template
+            .as_deref()
+            .unwrap_or(DEFAULT_LINKER_TEMPLATE);
+
+        let paths = |list: &[PathBuf]| -> Vec<String> {
+            list.iter().map(|p| p.display().to_string()).collect()
+        };
+        let sysroot: Vec<String> = self
+            .sysroot
+            .iter()
+            .map(|p| p.display().to_string())
+            .collect();
+        let placeholders: [(&str, Vec<String>); 6] = [
+            ("{triple}", vec![self.triple.clone()]),
+            ("{sysroot}", sysroot),
+            ("{crt_begin}", paths(&self.crt_begin)),
+            ("{crt_end}", paths(&self.crt_end)),
+            ("{inputs}", paths(inputs)),
+            ("{output}", vec![output.display().to_string()]),
+        ];
+
+        let mut args = Vec::new();
+        'tokens: for token in template.split_whitespace() {
+            let mut expanded = vec![token.to_string()];
+            for (name, values) in &placeholders {
+                if !token.contains(name) {
+                    continue;
+                }
+                if values.is_empty() {
+                    if token == *name {
+                        continue 'tokens;
+                    }
+                    return Err(CompilerError::CodegenError(format!(
+                        "Linker template argument '{}' uses {}, which has no value for \
+                         target '{}'",
+                        token, name, self.triple
+                    )));
+                }
+                // One argument per value (e.g., -L{sysroot}/lib, or each of {inputs})
+                expanded = expanded
+                    .iter()
+                    .flat_map(|t| values.iter().map(move |value| t.replace(name, value)))
+                    .collect();
+            }
+            args.extend(expanded);
+        }
+
+        let (program, rest) = args.split_first().ok_or_else(|| {
+            CompilerError::CodegenError("Linker command template is empty".to_string())
+        })?;
+        let mut cmd = Command::new(program);
+        cmd.args(rest);
+        Ok(cmd)
+    }
+}
+
 /// Build all modules starting from an entry file (handles cyclic imports)
 pub fn build_modules(
     entry_path: &Path,
     entry_dir: &Path,
+) -> Result<(HashMap<ModuleName, Module>, ModuleName)> {
+    build_modules_with_search_paths(entry_path, vec![entry_dir.to_path_buf()])
+}
+
+/// Build all modules reachable from an entry file, resolving imports against `search_paths`
+pub fn build_modules_with_search_paths(
+    entry_path: &Path,
+    search_paths: Vec<PathBuf>,
+) -> Result<(HashMap<ModuleName, Module>, ModuleName)> {
+    build_modules_cached(entry_path, search_paths, &mut ModuleCache::default())
+}
+
+/// Build all modules reachable from an entry file, parsing only those whose
+/// source changed since `cache` last saw them
+pub fn build_modules_cached(
+    entry_path: &Path,
+    search_paths: Vec<PathBuf>,
+    cache: &mut ModuleCache,
+) -> Result<(HashMap<ModuleName, Module>, ModuleName)> {
+    let converter = AstConverter::with_search_paths(search_paths);
+    parse_modules(entry_path, &converter, cache)
+}
+
+/// Parse the entry module and the modules it imports, as `converter`
+/// resolves them
+fn parse_modules(
+    entry_path: &Path,
+    converter: &AstConverter,
+    cache: &mut ModuleCache,
 ) -> Result<(HashMap<ModuleName, Module>, ModuleName)> {
     let mut modules = HashMap::new();
     let mut visited = HashSet::new();
-    let converter = AstConverter::new(entry_dir);
-
-    let entry_name = parse_module_recursive(entry_path, &converter, &mut modules, &mut visited)?;
+    let entry_name =
+        parse_module_recursive(entry_path, converter, cache, &mut modules, &mut visited)?;
     Ok((modules, entry_name))
 }
 
+fn parse_module(path: &Path, converter: &AstConverter, cache: &mut ModuleCache) -> Result<Module> {
+    let source = converter.file_system().read_to_string(path)?;
+    let source_hash = fingerprint(source.as_bytes());
+    if let Some(module) = cache.get(converter.search_paths(), path, source_hash) {
+        return Ok(module.clone());
+    }
+
+    let module_name = ModuleName::new(converter.path_to_module_id(path));
+    let py_ast = parse_python(&source).map_err(|e| e.in_file(path))?;
+    let module = Python::attach(|py| {
+        converter.convert_module(py_ast.bind(py), path.to_path_buf(), module_name)
+    })
+    .map_err(|e| e.in_file(path))?;
+    cache.insert(converter.search_paths(), path, source_hash, module.clone());
+    Ok(module)
+}
+
 fn parse_module_recursive(
     path: &Path,
     converter: &AstConverter,
+    cache: &mut ModuleCache,
     modules: &mut HashMap<ModuleName, Module>,
     visited: &mut HashSet<PathBuf>,
 ) -> Result<ModuleName> {
@@ -159,12 +606,7 @@ fn parse_module_recursive(
     }
     visited.insert(path.to_path_buf());
 
-    let source = fs::read_to_string(path).unwrap();
-    let py_ast = parse_python(&source)?;
-
-    let parsed = Python::attach(|py| {
-        converter.convert_module(py_ast.bind(py), path.to_path_buf(), module_name.clone())
-    })?;
+    let parsed = parse_module(path, converter, cache)?;
 
     let dep_paths: Vec<_> = parsed
         .imports
@@ -173,36 +615,443 @@ fn parse_module_recursive(
         .collect();
     modules.insert(module_name.clone(), parsed);
 
+    let mut errors = ErrorCollector::new();
     for dep_path in dep_paths {
-        let _ = parse_module_recursive(&dep_path, converter, modules, visited);
+        errors.try_collect(parse_module_recursive(
+            &dep_path, converter, cache, modules, visited,
+        ));
     }
 
-    Ok(module_name)
+    errors.into_result_with(module_name)
+}
+
+/// `path` with its `..` and symbolic links resolved, so two spellings of a
+/// module's path compare equal
+fn same_file(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// The static libraries the interfaces among `modules` name, to link the
+/// program with
+fn linked_libraries(
+    modules: &HashMap<ModuleName, Module>,
+    files: &dyn FileSystem,
+) -> Result<Vec<PathBuf>> {
+    let mut libraries = Vec::new();
+    for module in modules.values() {
+        if let Some(archive) = staticlib::linked_archive(files, &module.path)? {
+            if !libraries.contains(&archive) {
+                libraries.push(archive);
+            }
+        }
+    }
+    libraries.sort();
+    Ok(libraries)
+}
+
+/// Read the files to embed; directories are embedded recursively and glob
+/// patterns (e.g., `data/*.txt`) are expanded with Python's `glob` module
+///
+/// Each file is keyed by the path it was given as (without a leading "./"),
+/// which is the path the program uses to look it up at runtime. Two files
+/// under the same key are an error.
+pub fn collect_embedded_files(paths: &[PathBuf]) -> Result<Vec<EmbeddedFile>> {
+    fn visit(path: &Path, files: &mut Vec<EmbeddedFile>) -> Result<()> {
+        if path.is_dir() {
+            let mut entries: Vec<_> = fs::read_dir(path)?
+                .map(|e| e.map(|e| e.path()))
+                .collect::<std::io::Result<_>>()?;
+            entries.sort();
+            for entry in entries {
+                visit(&entry, files)?;
+            }
+            return Ok(());
+        }
+
+        let data = fs::read(path).map_err(|e| {
+            CompilerError::IOError(std::io::Error::new(
+                e.kind(),
+                format!("Cannot embed {}: {e}", path.display()),
+            ))
+        })?;
+        let key = path.to_string_lossy();
+        let key = key.trim_start_matches("./").to_string();
+        files.push(EmbeddedFile { path: key, data });
+        Ok(())
+    }
+
+    let mut files = Vec::new();
+    for path in paths {
+        let pattern = path.to_string_lossy();
+        if pattern.contains(['*', '?', '[']) {
+            let mut matches = Python::attach(|py| -> PyResult<Vec<String>> {
+                let glob = PyModule::import(py, "glob")?;
+                let matches = glob.call_method1("glob", (pattern.as_ref(),))?;
+                matches.extract::<Vec<String>>()
+            })?;
+            matches.sort();
+            for m in matches {
+                visit(Path::new(&m), &mut files)?;
+            }
+        } else {
+            visit(path, &mut files)?;
+        }
+    }
+
+    // open() could only ever see one of two files under the same path
+    let mut keys = HashSet::new();
+    for file in &files {
+        if !keys.insert(file.path.as_str()) {
+            return Err(CompilerError::CodegenError(format!(
+                "'{}' is embedded more than once; each --embed path or pattern must match \
+                 different files",
+                file.path
+            )));
+        }
+    }
+    Ok(files)
+}
+
+/// A linker installed on this machine
+#[derive(Debug)]
+enum LinkerProgram {
+    Clang,
+    /// lld at `path`, told which flavor of linker to act as when it is
+    /// `rust-lld`
+    Lld {
+        path: PathBuf,
+        flavor: Vec<&'static str>,
+    },
+}
+
+/// Environment variable listing directories to resolve imports against,
+/// as `PYTHONPATH` does for Python
+pub const SEARCH_PATH_VAR: &str = "PYCPATH";
+
+/// The directories `PYCPATH` lists, separated as `PATH`'s are; empty
+/// entries are skipped
+pub fn env_search_paths() -> Vec<PathBuf> {
+    env::var_os(SEARCH_PATH_VAR)
+        .map(|paths| {
+            env::split_paths(&paths)
+                .filter(|path| !path.as_os_str().is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// `name` in a directory of `PATH`
+fn find_in_path(name: &str) -> Option<PathBuf> {
+    env::split_paths(&env::var_os("PATH")?)
+        .map(|dir| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+/// `ld.lld` from `PATH`, or else the `rust-lld` next to the libraries of
+/// the Rust toolchain (`rustc --print target-libdir`)
+fn find_lld() -> Option<LinkerProgram> {
+    if let Some(path) = find_in_path("ld.lld") {
+        return Some(LinkerProgram::Lld {
+            path,
+            flavor: Vec::new(),
+        });
+    }
+    let output = Command::new("rustc")
+        .args(["--print", "target-libdir"])
+        .output()
+        .ok()?;
+    let libdir = PathBuf::from(String::from_utf8(output.stdout).ok()?.trim());
+    let path = libdir.parent()?.join("bin").join("rust-lld");
+    path.is_file().then(|| LinkerProgram::Lld {
+        path,
+        flavor: vec!["-flavor", "gnu"],
+    })
 }
 
 /// Compiler configuration options
 #[derive(Default)]
 pub struct CompilerOptions {
     pub emit_ast: bool,
+    /// Print the resolved TIR program, after its optimizations, in the
+    /// textual form of `tir::printer` (`--emit-tir`)
+    pub emit_tir: bool,
     pub emit_llvm: bool,
+    /// Write the program to the output path as a native object file instead
+    /// of linking an executable (`--emit-obj`)
+    pub emit_obj: bool,
+    /// Link a shared library exporting the `@export` functions instead of
+    /// an executable (`--shared`)
+    pub shared: bool,
+    /// Artifacts written next to the output, which name them after it with
+    /// their own extensions (`--emit=llvm-ir,asm` writes `app.ll` and `app.s`)
+    pub emit: Vec<EmitKind>,
+    /// Generate DWARF debug info naming the Python functions, their lines
+    /// and their variables (`-g`)
+    pub debug_info: bool,
     pub target: Target,
+    /// Data files (or directories) baked into the executable
+    pub embed: Vec<PathBuf>,
+    /// Overrides `target` with a user-supplied triple, sysroot and linker command
+    pub custom_target: Option<CustomTarget>,
+    /// Give classes without `__repr__`/`__eq__` field-wise defaults, as
+    /// `@dataclass` does
+    pub synthesize_methods: bool,
+    /// Start a subclass `__init__` that never calls `super().__init__()`
+    /// with that call, when the base's `__init__` takes no arguments
+    pub auto_super_init: bool,
+    /// Raise OverflowError when int arithmetic leaves the 64-bit range
+    /// instead of wrapping around
+    pub checked_int: bool,
+    /// Skip the ZeroDivisionError and IndexError checks (release builds);
+    /// dividing by zero or indexing out of range is then undefined behaviour
+    pub no_runtime_checks: bool,
+    /// Free class instances, strings and lists when their last reference
+    /// goes away
+    pub refcount: bool,
+    /// Reference counting plus a check at exit that every counted object
+    /// was freed; the program fails if any are still alive
+    pub leak_check: bool,
+    /// Garbage collector for class instances (`--gc=tracing`)
+    pub gc: GcMode,
+    /// Runtime allocator (`--alloc=arena`)
+    pub alloc: AllocMode,
+    /// TIR optimizations run before codegen and LLVM passes run after it
+    /// (`--opt-level=2`)
+    pub opt_level: OptLevel,
+    /// How try blocks catch exceptions (`--exceptions=setjmp`)
+    pub exceptions: ExceptionModel,
+    /// Keep a record of the functions being run and their current lines,
+    /// which an uncaught exception prints as a traceback (`--traceback`)
+    pub traceback: bool,
+    /// Raise RecursionError when a call would run more than this many
+    /// functions nested, instead of overflowing the stack
+    /// (`--recursion-limit=1000`)
+    pub recursion_limit: Option<u32>,
+    /// What links executables for the built-in targets (`--linker=lld`)
+    pub linker: Linker,
+    /// Whether warnings are printed, ignored or fail the build (`-W error`)
+    pub warnings: WarningLevel,
+    /// How warnings are printed (`--error-format=short`)
+    pub error_format: ErrorFormat,
+    /// Directories imports are resolved against after the entry's own (or
+    /// a project's source roots), in order (`-I`, `PYCPATH`)
+    pub search_paths: Vec<PathBuf>,
+    /// Directory keeping the object file of each program built, which is
+    /// relinked instead of generated again while the program's sources and
+    /// options are unchanged (`--incremental`)
+    pub incremental: Option<PathBuf>,
+}
+
+/// A program compiled to LLVM bitcode, which the caller links itself
+/// (`Compiler::compile_to_bitcode`)
+#[derive(Debug, Clone)]
+pub struct CompiledModule {
+    /// The program's LLVM module, as bitcode
+    pub bitcode: Vec<u8>,
+    /// The target triple the module was generated for
+    pub triple: String,
+    /// The symbols the module uses without defining them, sorted: the
+    /// runtime's (`Compiler::find_runtime_library`), the C library's and
+    /// those of `@extern` functions
+    pub undefined_symbols: Vec<String>,
+    /// The static libraries of the interfaces the program imports
+    pub libraries: Vec<PathBuf>,
 }
 
 /// Main compiler - orchestrates parsing, type checking, codegen, and linking
 pub struct Compiler {
     options: CompilerOptions,
+    /// Modules parsed by earlier compiles, reused while their source is
+    /// unchanged (the binaries of a project share most of theirs)
+    modules: RefCell<ModuleCache>,
+    /// Where modules are read from: the disk, unless `with_file_system`
+    /// says otherwise
+    files: Arc<dyn FileSystem>,
 }
 
 impl Compiler {
     pub fn new(options: CompilerOptions) -> Self {
-        Self { options }
+        Self {
+            options,
+            modules: RefCell::default(),
+            files: Arc::new(DiskFileSystem),
+        }
+    }
+
+    /// Read the modules to compile, and resolve their imports, in `files`
+    /// instead of on disk
+    pub fn with_file_system(mut self, files: impl FileSystem + 'static) -> Self {
+        self.files = Arc::new(files);
+        self
     }
 
-    /// Compile a Python source file to an executable
+    /// Compile a Python source file to an executable (or an object file,
+    /// with `emit_obj`)
     pub fn compile(&self, input_path: &Path, output_path: &Path) -> Result<()> {
-        self.with_llvm_module(input_path, |module| {
-            self.link_executable(module, output_path)
-        })
+        let canonical = self.validate_input(input_path)?;
+        let entry_dir = canonical.parent().unwrap().to_path_buf();
+        self.build(&canonical, &self.converter(vec![entry_dir]), output_path)
+    }
+
+    /// Compile a module given as a string to an executable, as if it were
+    /// the file `<name>.py` of the current directory: it imports the modules
+    /// next to it, in the file system the compiler reads, and it is compiled
+    /// even if no such file exists (or in place of the one that does)
+    pub fn compile_source(&self, name: &str, source: &str, output_path: &Path) -> Result<()> {
+        let (path, converter) = self.source_converter(name, source)?;
+        self.build(&path, &converter, output_path)
+    }
+
+    /// Compile a Python source file to LLVM bitcode, without linking it,
+    /// for programs that link it with the runtime themselves
+    pub fn compile_to_bitcode(&self, input_path: &Path) -> Result<CompiledModule> {
+        let canonical = self.validate_input(input_path)?;
+        let entry_dir = canonical.parent().unwrap().to_path_buf();
+        self.build_bitcode(&canonical, &self.converter(vec![entry_dir]))
+    }
+
+    /// Compile a module given as a string to LLVM bitcode, as
+    /// `compile_source` and `compile_to_bitcode` do
+    pub fn compile_source_to_bitcode(&self, name: &str, source: &str) -> Result<CompiledModule> {
+        let (path, converter) = self.source_converter(name, source)?;
+        self.build_bitcode(&path, &converter)
+    }
+
+    /// The path a module given as a string is compiled as, and the
+    /// converter that finds it there
+    pub(crate) fn source_converter(
+        &self,
+        name: &str,
+        source: &str,
+    ) -> Result<(PathBuf, AstConverter)> {
+        let is_module_name = !name.is_empty()
+            && name.split('.').all(|part| {
+                part.chars().next().is_some_and(|c| !c.is_ascii_digit())
+                    && part.chars().all(|c| c.is_alphanumeric() || c == '_')
+            });
+        if !is_module_name {
+            return Err(CompilerError::IOError(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Not a module name: '{name}'"),
+            )));
+        }
+
+        let root = match self.files.canonicalize(Path::new(".")) {
+            Ok(root) => root,
+            Err(_) => env::current_dir()?,
+        };
+        let path = root.join(format!("{}.py", name.replace('.', "/")));
+        let files = OverlayFileSystem::new(
+            MemoryFileSystem::new().with_file(&path, source),
+            self.files.clone(),
+        );
+        let converter = AstConverter::with_search_paths(self.search_paths(vec![root]))
+            .with_file_system(Arc::new(files));
+        Ok((path, converter))
+    }
+
+    /// Compile every binary declared by a project into `output_dir`
+    ///
+    /// All modules under the project's source roots are parsed, and those no
+    /// binary imports type-checked, first, so their errors are still reported.
+    pub fn compile_project(&self, project: &Project, output_dir: &Path) -> Result<Vec<PathBuf>> {
+        let converter = self.converter(project.import_paths());
+        let paths = self.parse_project(project, &converter)?;
+        let mut reached = HashSet::new();
+        for binary in &project.binaries {
+            let (modules, _) = self.parse_program(&project.entry_path(binary)?, &converter)?;
+            reached.extend(modules.values().map(|m| same_file(&m.path)));
+        }
+        self.check_unimported(paths, &converter, reached)?;
+
+        fs::create_dir_all(output_dir)?;
+        let mut outputs = Vec::new();
+        for binary in &project.binaries {
+            let entry = project.entry_path(binary)?;
+            let mut output = output_dir.join(&binary.name);
+            if self.options.emit_obj {
+                output.set_extension("o");
+            }
+            if self.static_library() {
+                output = output_dir.join(format!("lib{}.a", binary.name));
+            }
+            self.build(&entry, &converter, &output)?;
+            outputs.push(output);
+        }
+        Ok(outputs)
+    }
+
+    /// Type-check a Python file and the modules it imports: parse and lower
+    /// them, without generating code or linking (`pycc --check`)
+    pub fn check(&self, input_path: &Path) -> Result<()> {
+        let canonical = self.validate_input(input_path)?;
+        let entry_dir = canonical.parent().unwrap().to_path_buf();
+        self.lower_program(&canonical, &self.converter(vec![entry_dir]))
+            .map(|_| ())
+    }
+
+    /// Type-check every binary declared by a project, and every other module
+    /// under its source roots along with the modules it imports
+    pub fn check_project(&self, project: &Project) -> Result<()> {
+        let converter = self.converter(project.import_paths());
+        let paths = self.parse_project(project, &converter)?;
+        let mut errors = ErrorCollector::new();
+        let mut reached = HashSet::new();
+        for binary in &project.binaries {
+            let lowered = project.entry_path(binary).and_then(|entry| {
+                let (modules, entry_name) = self.parse_program(&entry, &converter)?;
+                reached.extend(modules.values().map(|m| same_file(&m.path)));
+                self.lower_modules(modules, entry_name)
+            });
+            errors.try_collect(lowered);
+        }
+        errors.try_collect(self.check_unimported(paths, &converter, reached));
+        errors.into_result()
+    }
+
+    /// Parse all modules under the project's source roots, returning their
+    /// paths
+    fn parse_project(&self, project: &Project, converter: &AstConverter) -> Result<Vec<PathBuf>> {
+        let paths = project.discover_modules()?;
+        let mut cache = self.modules.borrow_mut();
+        let mut errors = ErrorCollector::new();
+        for path in &paths {
+            errors.try_collect(parse_module(path, converter, &mut cache));
+        }
+        errors.into_result_with(paths)
+    }
+
+    /// Type-check the modules among `paths` outside `reached`, each lowered
+    /// as a program of its own
+    ///
+    /// Those importing the most go first, so a module another one imports is
+    /// checked as part of it instead of being checked (and reported) twice.
+    fn check_unimported(
+        &self,
+        paths: Vec<PathBuf>,
+        converter: &AstConverter,
+        mut reached: HashSet<PathBuf>,
+    ) -> Result<()> {
+        let mut errors = ErrorCollector::new();
+        let mut unimported = Vec::new();
+        for path in paths {
+            if reached.contains(&same_file(&path)) {
+                continue;
+            }
+            match self.parse_program(&path, converter) {
+                Ok(program) => unimported.push(program),
+                Err(e) => errors.push(e),
+            }
+        }
+        unimported.sort_by_key(|(modules, _)| std::cmp::Reverse(modules.len()));
+        for (modules, entry_name) in unimported {
+            if reached.contains(&same_file(&modules[&entry_name].path)) {
+                continue;
+            }
+            reached.extend(modules.values().map(|m| same_file(&m.path)));
+            errors.try_collect(self.lower_modules(modules, entry_name));
+        }
+        errors.into_result()
     }
 
     /// Compile and run a Python file
@@ -212,24 +1061,214 @@ impl Compiler {
         self.execute(&temp_exe, args)
     }
 
-    fn with_llvm_module<F>(&self, input_path: &Path, f: F) -> Result<()>
-    where
-        F: for<'ctx> FnOnce(&inkwell::module::Module<'ctx>) -> Result<()>,
-    {
+    /// Compile and run a Python file in this process, with LLVM's JIT
+    /// instead of an executable (`pyrun --jit`)
+    pub fn run_jit(&self, input_path: &Path, args: &[String]) -> Result<()> {
+        self.check_jit()?;
         let canonical = self.validate_input(input_path)?;
-        let entry_dir = canonical.parent().unwrap();
+        let entry_dir = canonical.parent().unwrap().to_path_buf();
+        let converter = self.converter(vec![entry_dir]);
+
+        let (modules, entry_name) = self.parse_program(&canonical, &converter)?;
+        if !linked_libraries(&modules, converter.file_system())?.is_empty() {
+            return Err(CompilerError::CodegenError(
+                "--jit cannot run a program importing a static library".to_string(),
+            ));
+        }
+        let tir_program = self.lower_modules(modules, entry_name)?;
+        let runtime = self.find_runtime_library()?;
+        let icu_lib = self.options.target.icu_lib_dir();
+        let mut argv = vec![input_path.display().to_string()];
+        argv.extend_from_slice(args);
 
-        let (modules, entry_name) = build_modules(&canonical, entry_dir)?;
-        if self.options.emit_ast {
-            for module in modules.values() {
-                println!("=== Module {} AST ===\n{:#?}", module.id, module);
+        let status = self.with_llvm_module(tir_program, |module| {
+            run_jit(module, &runtime, &icu_lib, self.options.opt_level, &argv)
+        })?;
+        if status != 0 {
+            return Err(CompilerError::CodegenError(format!(
+                "Program exited with status: {status}"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Run an interactive session (`pyrepl`): read inputs from `input`,
+    /// compile and run each in this process, and write the prompts to
+    /// `prompts`, until the end of the input
+    pub fn run_repl(&self, input: impl BufRead, prompts: impl Write) -> Result<()> {
+        self.check_repl()?;
+        repl::run(self, self.options.error_format, input, prompts)
+    }
+
+    /// A JIT for the modules of a session's inputs
+    pub(crate) fn jit_session<'ctx>(&self) -> Result<JitSession<'ctx>> {
+        JitSession::new(
+            &self.find_runtime_library()?,
+            &self.options.target.icu_lib_dir(),
+            self.options.opt_level,
+        )
+    }
+
+    /// Compile the program of `entry` to `output_path`
+    ///
+    /// With `incremental`, the object file of a program whose sources and
+    /// options have not changed since it was last built is taken from the
+    /// build cache and only linked again.
+    fn build(&self, entry: &Path, converter: &AstConverter, output_path: &Path) -> Result<()> {
+        if self.options.exceptions == ExceptionModel::SetJmp {
+            self.check_setjmp_exceptions()?;
+        }
+        if self.options.shared {
+            self.check_shared_library()?;
+        }
+        if self.static_library() {
+            self.check_static_library()?;
+        }
+
+        let (modules, entry_name) = self.parse_program(entry, converter)?;
+        let libraries = linked_libraries(&modules, converter.file_system())?;
+        let cached = match self.build_cache() {
+            Some(cache) => Some((cache, self.build_key(&modules, converter.search_paths())?)),
+            None => None,
+        };
+        let tir_program = self.lower_modules(modules, entry_name.clone())?;
+
+        if self.static_library() {
+            if !libraries.is_empty() {
+                return Err(CompilerError::CodegenError(
+                    "A static library cannot import another static library's interface".to_string(),
+                ));
             }
+            return self.write_static_library(tir_program, output_path);
         }
+        let Some((cache, key)) = cached else {
+            return self.with_llvm_module(tir_program, |module| {
+                self.write_output(module, &libraries, output_path)
+            });
+        };
+        let object = match cache.get(&entry_name.0, key) {
+            Some(object) => object,
+            None => self.with_llvm_module(tir_program, |module| {
+                cache.store(&entry_name.0, key, |path| self.write_object(module, path))
+            })?,
+        };
+        if self.options.emit_obj {
+            fs::copy(&object, output_path)?;
+            Ok(())
+        } else {
+            self.link_executable(&object, &libraries, output_path)
+        }
+    }
 
-        let tir_program = lower_to_tir(modules, entry_name)?;
+    /// Compile the program of `entry` to LLVM bitcode
+    fn build_bitcode(&self, entry: &Path, converter: &AstConverter) -> Result<CompiledModule> {
+        if self.options.exceptions == ExceptionModel::SetJmp {
+            self.check_setjmp_exceptions()?;
+        }
+
+        let (modules, entry_name) = self.parse_program(entry, converter)?;
+        let libraries = linked_libraries(&modules, converter.file_system())?;
+        let tir_program = self.lower_modules(modules, entry_name)?;
+        self.with_llvm_module(tir_program, |module| {
+            Ok(CompiledModule {
+                bitcode: module.write_bitcode_to_memory().as_slice().to_vec(),
+                triple: module.get_triple().as_str().to_string_lossy().into_owned(),
+                undefined_symbols: undefined_symbols(module),
+                libraries,
+            })
+        })
+    }
+
+    /// Whether the program is built into a static library
+    /// (`--emit=staticlib`)
+    fn static_library(&self) -> bool {
+        self.options.emit.contains(&EmitKind::StaticLib)
+    }
+
+    /// The build cache of `incremental`, unless the build prints or writes
+    /// what only generating code gives (`--emit-llvm`, `--emit`), or prints
+    /// the optimized TIR
+    fn build_cache(&self) -> Option<BuildCache> {
+        let needs_codegen =
+            self.options.emit_tir || self.options.emit_llvm || !self.options.emit.is_empty();
+        match &self.options.incremental {
+            Some(dir) if !needs_codegen => Some(BuildCache::new(dir)),
+            _ => None,
+        }
+    }
+
+    /// The key of a program's object file in the build cache: the sources
+    /// of its modules, the options that change the code generated for them,
+    /// and the compiler that generates it
+    fn build_key(
+        &self,
+        modules: &HashMap<ModuleName, Module>,
+        search_paths: &[PathBuf],
+    ) -> Result<u64> {
+        let mut key = Fingerprint::default();
+        env!("CARGO_PKG_VERSION").hash(&mut key);
+        // A rebuilt compiler may generate other code for the same sources
+        if let Ok(modified) = env::current_exe().and_then(|exe| fs::metadata(exe)?.modified()) {
+            modified.hash(&mut key);
+        }
+
+        let options = &self.options;
+        let triple = options.custom_target.as_ref().map(|custom| &custom.triple);
+        let codegen_options: [&dyn fmt::Debug; 16] = [
+            &options.target,
+            &triple,
+            &options.synthesize_methods,
+            &options.auto_super_init,
+            &options.checked_int,
+            &options.no_runtime_checks,
+            &options.refcount,
+            &options.leak_check,
+            &options.gc,
+            &options.alloc,
+            &options.opt_level,
+            &options.exceptions,
+            &options.traceback,
+            &options.recursion_limit,
+            &options.shared,
+            &options.debug_info,
+        ];
+        for option in codegen_options {
+            format!("{:?}", option).hash(&mut key);
+        }
+        for file in collect_embedded_files(&options.embed)? {
+            file.path.hash(&mut key);
+            file.data.hash(&mut key);
+        }
+
+        let cache = self.modules.borrow();
+        let mut names: Vec<&ModuleName> = modules.keys().collect();
+        names.sort_by(|a, b| a.0.cmp(&b.0));
+        for name in names {
+            let path = &modules[name].path;
+            name.0.hash(&mut key);
+            path.hash(&mut key);
+            cache.source_fingerprint(search_paths, path).hash(&mut key);
+        }
+        Ok(key.finish())
+    }
+
+    /// Generate the LLVM module of a lowered program and hand it to `f`
+    fn with_llvm_module<F, T>(&self, mut tir_program: TirProgram, f: F) -> Result<T>
+    where
+        F: for<'ctx> FnOnce(&inkwell::module::Module<'ctx>) -> Result<T>,
+    {
+        if self.options.opt_level >= OptLevel::O1 {
+            optimize(&mut tir_program, self.options.checked_int);
+        }
+        if self.options.emit_tir {
+            println!("=== TIR ===\n{}", tir_program);
+        }
+        let embedded_files = collect_embedded_files(&self.options.embed)?;
         let context = Context::create();
-        let codegen = Codegen::new(&context, self.options.target);
-        let llvm_module = codegen.codegen_tir(&tir_program);
+        let llvm_module = self
+            .codegen(&context)
+            .with_embedded_files(embedded_files)
+            .codegen_tir(&tir_program)?;
 
         if self.options.emit_llvm {
             println!(
@@ -241,15 +1280,218 @@ impl Compiler {
         f(&llvm_module)
     }
 
+    /// The code generator for the options
+    pub(crate) fn codegen<'ctx>(&self, context: &'ctx Context) -> Codegen<'ctx> {
+        let mut codegen = Codegen::new(context, self.options.target)
+            .with_checked_int(self.options.checked_int)
+            .with_runtime_checks(!self.options.no_runtime_checks)
+            .with_refcount(self.options.refcount || self.options.leak_check)
+            .with_leak_check(self.options.leak_check)
+            .with_tracing_gc(self.options.gc == GcMode::Tracing)
+            .with_arena_alloc(self.options.alloc == AllocMode::Arena)
+            .with_inline_small_functions(self.options.opt_level >= OptLevel::O1)
+            .with_setjmp_exceptions(self.options.exceptions == ExceptionModel::SetJmp)
+            .with_traceback(self.options.traceback)
+            .with_recursion_limit(self.options.recursion_limit)
+            .with_opt_level(self.options.opt_level)
+            .with_shared_library(self.options.shared)
+            .with_static_library(self.static_library())
+            .with_debug_info(self.options.debug_info);
+        if let Some(custom) = &self.options.custom_target {
+            codegen = codegen.with_target_triple(&custom.triple);
+        }
+        codegen
+    }
+
+    /// Parse the entry module and its imports and lower them to TIR,
+    /// resolving and checking every type, then report the warnings found
+    fn lower_program(&self, entry: &Path, converter: &AstConverter) -> Result<TirProgram> {
+        let (modules, entry_name) = self.parse_program(entry, converter)?;
+        self.lower_modules(modules, entry_name)
+    }
+
+    /// The converter resolving imports against `roots`, then the options'
+    /// search paths, in the compiler's file system
+    fn converter(&self, roots: Vec<PathBuf>) -> AstConverter {
+        AstConverter::with_search_paths(self.search_paths(roots))
+            .with_file_system(self.files.clone())
+    }
+
+    /// `roots` followed by the directories of `-I` and `PYCPATH`
+    fn search_paths(&self, mut roots: Vec<PathBuf>) -> Vec<PathBuf> {
+        roots.extend(self.options.search_paths.iter().cloned());
+        roots
+    }
+
+    /// Parse the entry module and the modules it imports, reusing those
+    /// parsed before whose source is unchanged
+    pub(crate) fn parse_program(
+        &self,
+        entry: &Path,
+        converter: &AstConverter,
+    ) -> Result<(HashMap<ModuleName, Module>, ModuleName)> {
+        let (mut modules, entry_name) =
+            parse_modules(entry, converter, &mut self.modules.borrow_mut())?;
+        if self.options.synthesize_methods {
+            for module in modules.values_mut() {
+                synthesize_default_methods(module);
+            }
+        }
+        if self.options.auto_super_init {
+            for module in modules.values_mut() {
+                insert_super_init_calls(module);
+            }
+        }
+        if self.options.emit_ast {
+            for module in modules.values() {
+                println!("=== Module {} AST ===\n{:#?}", module.id, module);
+            }
+        }
+        Ok((modules, entry_name))
+    }
+
+    /// Lower the parsed modules to TIR, resolving and checking every type,
+    /// then report the warnings found
+    fn lower_modules(
+        &self,
+        modules: HashMap<ModuleName, Module>,
+        entry_name: ModuleName,
+    ) -> Result<TirProgram> {
+        let mut tir_program = lower_to_tir(modules, entry_name)?;
+        self.report_warnings(std::mem::take(&mut tir_program.warnings))?;
+        Ok(tir_program)
+    }
+
+    /// Print the warnings lowering found, or under `-W error`, fail with them
+    pub(crate) fn report_warnings(&self, warnings: Vec<Warning>) -> Result<()> {
+        match self.options.warnings {
+            WarningLevel::Allow => Ok(()),
+            WarningLevel::Warn => {
+                let color = std::io::stderr().is_terminal();
+                eprint!(
+                    "{}",
+                    diagnostics::render_warnings(&warnings, self.options.error_format, color)
+                );
+                Ok(())
+            }
+            WarningLevel::Error => {
+                let mut errors = ErrorCollector::new();
+                for warning in warnings {
+                    errors.push(warning.into_error());
+                }
+                errors.into_result()
+            }
+        }
+    }
+
+    /// Fail on targets whose LLVM backend cannot lower setjmp/longjmp
+    fn check_setjmp_exceptions(&self) -> Result<()> {
+        let (triple, supported) = match &self.options.custom_target {
+            Some(custom) => (
+                custom.triple.as_str(),
+                custom.triple.starts_with("x86_64") || custom.triple.starts_with("aarch64"),
+            ),
+            None => (
+                self.options.target.triple(),
+                self.options.target.supports_setjmp_exceptions(),
+            ),
+        };
+        if supported {
+            Ok(())
+        } else {
+            Err(CompilerError::CodegenError(format!(
+                "--exceptions=setjmp is not supported on {triple}; use --exceptions=polling"
+            )))
+        }
+    }
+
+    /// Fail unless the program is built for this machine, as an executable
+    fn check_jit(&self) -> Result<()> {
+        if self.options.exceptions == ExceptionModel::SetJmp {
+            self.check_setjmp_exceptions()?;
+        }
+        if Target::host() != Some(self.options.target) || self.options.custom_target.is_some() {
+            return Err(CompilerError::CodegenError(
+                "--jit runs the program in this process, so it only runs programs built for \
+                 this machine"
+                    .to_string(),
+            ));
+        }
+        if self.options.shared || self.static_library() {
+            return Err(CompilerError::CodegenError(
+                "--jit runs a program, not a library".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Fail on the options a session cannot run with: those `--jit` cannot,
+    /// and those whose runtime state would not be shared by the inputs
+    fn check_repl(&self) -> Result<()> {
+        self.check_jit()?;
+        let conflicting = if self.options.gc == GcMode::Tracing {
+            Some("--gc=tracing")
+        } else if self.options.alloc == AllocMode::Arena {
+            Some("--alloc=arena")
+        } else if self.options.leak_check {
+            Some("--leak-check")
+        } else {
+            None
+        };
+        match conflicting {
+            Some(option) => Err(CompilerError::CodegenError(format!(
+                "pyrepl cannot run with {option}"
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Fail on the options a static library cannot be built with
+    fn check_static_library(&self) -> Result<()> {
+        let conflicting = if self.options.shared {
+            Some("--shared")
+        } else if self.options.emit_obj {
+            Some("--emit-obj")
+        } else if self.options.leak_check {
+            Some("--leak-check")
+        } else if !self.options.embed.is_empty() {
+            Some("--embed")
+        } else {
+            None
+        };
+        match conflicting {
+            Some(option) => Err(CompilerError::CodegenError(format!(
+                "--emit=staticlib cannot be combined with {option}"
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Fail on the options a shared library cannot be built with
+    fn check_shared_library(&self) -> Result<()> {
+        if self.options.leak_check {
+            return Err(CompilerError::CodegenError(
+                "--leak-check needs an executable, which checks for leaks when it exits"
+                    .to_string(),
+            ));
+        }
+        if self.options.custom_target.is_some() {
+            return Err(CompilerError::CodegenError(
+                "--shared is only supported on the built-in targets".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
     fn validate_input(&self, input_path: &Path) -> Result<PathBuf> {
-        let canonical = input_path.canonicalize().map_err(|e| {
+        let canonical = self.files.canonicalize(input_path).map_err(|e| {
             CompilerError::IOError(std::io::Error::new(
                 std::io::ErrorKind::NotFound,
                 format!("Input file not found {}: {e}", input_path.display()),
             ))
         })?;
 
-        if !canonical.is_file() {
+        if !self.files.is_file(&canonical) {
             return Err(CompilerError::IOError(std::io::Error::new(
                 std::io::ErrorKind::InvalidInput,
                 format!(
@@ -278,93 +1520,190 @@ impl Compiler {
         Ok(canonical)
     }
 
-    fn link_executable<'ctx>(
+    /// Link the module (and the static libraries it imports) into an
+    /// executable (or a shared library, with `shared`) at `path`, or write
+    /// it there as an object file with `emit_obj`
+    fn write_output(
+        &self,
+        llvm_module: &inkwell::module::Module<'_>,
+        libraries: &[PathBuf],
+        path: &Path,
+    ) -> Result<()> {
+        self.write_artifacts(llvm_module, path)?;
+        if self.options.emit_obj {
+            self.write_object(llvm_module, path)
+        } else {
+            let obj_path = Self::object_path(path);
+            self.write_object(llvm_module, &obj_path)?;
+            let linked = self.link_executable(&obj_path, libraries, path);
+            let _ = fs::remove_file(&obj_path);
+            linked
+        }
+    }
+
+    /// Build the program into the static library `archive`, and write its
+    /// interface next to it
+    fn write_static_library(&self, tir_program: TirProgram, archive: &Path) -> Result<()> {
+        let interface = staticlib::interface(&tir_program, archive);
+        let interface_path = staticlib::interface_path(archive, &tir_program);
+        let symbols = staticlib::exported_symbols(&tir_program);
+        self.with_llvm_module(tir_program, |module| {
+            self.write_artifacts(module, archive)?;
+            let obj_path = Self::object_path(archive);
+            self.write_object(module, &obj_path)?;
+            let written = staticlib::write_archive(&obj_path, &symbols, archive);
+            let _ = fs::remove_file(&obj_path);
+            written
+        })?;
+        fs::write(interface_path, interface)?;
+        Ok(())
+    }
+
+    /// Write the artifacts of `emit` next to `output_path`
+    fn write_artifacts(
         &self,
-        llvm_module: &inkwell::module::Module<'ctx>,
+        llvm_module: &inkwell::module::Module<'_>,
         output_path: &Path,
     ) -> Result<()> {
-        let runtime_path = self.find_runtime_library()?;
-        let musl_lib = self.options.target.musl_lib_dir();
-        let icu_lib = self.options.target.icu_lib_dir();
-        let bc_path = output_path.with_extension("bc");
+        for &kind in &self.options.emit {
+            let path = output_path.with_extension(kind.extension());
+            let cannot_write = |e: String| {
+                CompilerError::LLVMError(format!("Cannot write {}: {e}", path.display()))
+            };
+            match kind {
+                EmitKind::LlvmIr => llvm_module
+                    .print_to_file(&path)
+                    .map_err(|e| cannot_write(e.to_string()))?,
+                EmitKind::LlvmBc => {
+                    if !llvm_module.write_bitcode_to_path(&path) {
+                        return Err(cannot_write("LLVM could not write the bitcode".to_string()));
+                    }
+                }
+                EmitKind::Asm => write_assembly_file(
+                    llvm_module,
+                    self.target_features(),
+                    self.options.opt_level,
+                    &path,
+                )?,
+                EmitKind::Obj => self.write_object(llvm_module, &path)?,
+                // The output itself (see write_static_library)
+                EmitKind::StaticLib => {}
+            }
+        }
+        Ok(())
+    }
 
-        llvm_module.write_bitcode_to_path(&bc_path);
+    /// LLVM features of the target (`+m,+a,...`), left to LLVM's defaults
+    /// for custom targets
+    fn target_features(&self) -> &'static str {
+        match &self.options.custom_target {
+            Some(_) => "",
+            None => self.options.target.llvm_features(),
+        }
+    }
 
-        // Static linking with musl and ICU
-        let mut cmd = Command::new("clang");
+    /// Compile the module to a native object file for the target
+    fn write_object(&self, llvm_module: &inkwell::module::Module<'_>, path: &Path) -> Result<()> {
+        write_object_file(
+            llvm_module,
+            self.target_features(),
+            self.options.opt_level,
+            path,
+        )
+    }
 
-        // Target-specific flags must come first
-        if let Some(target_flag) = self.options.target.clang_target() {
-            cmd.arg(target_flag);
-            cmd.arg("-fuse-ld=lld");
-            if matches!(self.options.target, Target::RiscV64) {
-                cmd.arg("-mabi=lp64d");
-            }
+    /// Where the program's object file goes while it is linked into
+    /// `output_path`
+    fn object_path(output_path: &Path) -> PathBuf {
+        output_path.with_extension("pyc.o")
+    }
+
+    /// Link the program's object file at `obj_path` with the static
+    /// libraries it imports and the runtime into `output_path`
+    fn link_executable(
+        &self,
+        obj_path: &Path,
+        libraries: &[PathBuf],
+        output_path: &Path,
+    ) -> Result<()> {
+        if let Some(custom) = &self.options.custom_target {
+            return self.link_custom_target(obj_path, libraries, output_path, custom);
         }
 
-        // Static linking with no default libraries
-        cmd.arg("-static").arg("-nostdlib");
+        let runtime_path = self.find_runtime_library()?;
+        let linker = self.find_linker()?;
+        let musl_lib = self.options.target.musl_lib_dir();
+        let icu_lib = self.options.target.icu_lib_dir();
+
+        // Static linking with musl and ICU, in the linker's own terms. A
+        // shared library is loaded into a process that already has a C
+        // library and startup code, so it links neither musl nor the CRT
+        // objects
+        let shared = self.options.shared;
+        let mut args: Vec<OsString> = vec![if shared { "-shared" } else { "-static" }.into()];
 
         // musl CRT start objects
-        cmd.arg(format!("{}/crt1.o", musl_lib.display()))
-            .arg(format!("{}/crti.o", musl_lib.display()));
+        if !shared {
+            args.push(musl_lib.join("crt1.o").into());
+            args.push(musl_lib.join("crti.o").into());
+        }
 
-        // Our compiled code and runtime
-        cmd.arg(&bc_path).arg(&runtime_path);
+        // Our compiled code, the static libraries it imports and runtime
+        args.push(obj_path.into());
+        args.extend(libraries.iter().map(OsString::from));
+        args.push(runtime_path.into());
 
         // Library search paths
-        cmd.arg(format!("-L{}", musl_lib.display()));
+        args.push(format!("-L{}", musl_lib.display()).into());
 
         // Check if ICU is available (not a placeholder path)
         let icu_available = !icu_lib.to_string_lossy().contains("placeholder");
 
         if icu_available {
-            cmd.arg(format!("-L{}", icu_lib.display()));
+            args.push(format!("-L{}", icu_lib.display()).into());
 
             // Add GCC library path for libstdc++ (target-specific)
-            match self.options.target {
-                Target::X86_64 => {
-                    cmd.arg("-L/usr/lib/gcc/x86_64-linux-gnu/13");
-                }
-                Target::RiscV64 => {
-                    cmd.arg("-L/usr/lib/gcc-cross/riscv64-linux-gnu/13");
-                }
-            }
+            args.push(format!("-L{}", self.options.target.gcc_lib_dir().display()).into());
 
             // Static ICU libraries (order matters, use --start-group/--end-group for circular deps)
-            cmd.arg("-Wl,--start-group")
-                .arg("-l:libicui18n.a")
-                .arg("-l:libicuuc.a")
-                .arg("-l:libicudata.a")
-                .arg("-Wl,--end-group");
+            for arg in [
+                "--start-group",
+                "-l:libicui18n.a",
+                "-l:libicuuc.a",
+                "-l:libicudata.a",
+                "--end-group",
+            ] {
+                args.push(arg.into());
+            }
 
             // Static libstdc++ for ICU's C++ code
-            cmd.arg("-l:libstdc++.a")
-                .arg("-l:libgcc.a")
-                .arg("-l:libgcc_eh.a"); // Exception handling
+            args.push("-l:libstdc++.a".into());
+            args.push("-l:libgcc.a".into());
+            args.push("-l:libgcc_eh.a".into()); // Exception handling
         }
 
-        // For RISC-V, we always need libgcc for soft-float operations (128-bit float)
-        // even without ICU, because musl's printf uses these
-        if matches!(self.options.target, Target::RiscV64) && !icu_available {
-            cmd.arg("-L/usr/lib/gcc-cross/riscv64-linux-gnu/13")
-                .arg("-l:libgcc.a");
+        // For RISC-V and aarch64, we always need libgcc for soft-float operations (128-bit
+        // float) even without ICU, because musl's printf uses these
+        if self.options.target.soft_float_long_double() && !icu_available {
+            args.push(format!("-L{}", self.options.target.gcc_lib_dir().display()).into());
+            args.push("-l:libgcc.a".into());
         }
 
-        // musl libc (must come after C++ libs since they may reference libc functions)
-        cmd.arg("-lc");
+        if !shared {
+            // musl libc (must come after C++ libs since they may reference libc functions)
+            args.push("-lc".into());
 
-        // musl CRT end object
-        cmd.arg(format!("{}/crtn.o", musl_lib.display()));
+            // musl CRT end object
+            args.push(musl_lib.join("crtn.o").into());
+        }
 
         // Output file
-        cmd.arg("-o").arg(output_path);
+        args.push("-o".into());
+        args.push(output_path.into());
 
-        // Optimization flags
-        cmd.args(["-flto", "-O2"]);
+        let mut cmd = self.linker_command(&linker, &args);
 
         let output = cmd.output().map_err(CompilerError::IOError)?;
-        let _ = fs::remove_file(&bc_path);
 
         if !output.status.success() {
             return Err(CompilerError::CodegenError(format!(
@@ -375,7 +1714,99 @@ impl Compiler {
         Ok(())
     }
 
-    fn find_runtime_library(&self) -> Result<PathBuf> {
+    /// The linker `options.linker` asks for, found on this machine
+    fn find_linker(&self) -> Result<LinkerProgram> {
+        let not_found = |wanted: &str| {
+            CompilerError::CodegenError(format!(
+                "No {wanted} found to link with; install clang or lld (ld.lld), \
+                 or a Rust toolchain that ships rust-lld"
+            ))
+        };
+        match self.options.linker {
+            Linker::Clang => Ok(LinkerProgram::Clang),
+            Linker::Lld => find_lld().ok_or_else(|| not_found("lld")),
+            Linker::Auto => {
+                if find_in_path("clang").is_some() {
+                    Ok(LinkerProgram::Clang)
+                } else {
+                    find_lld().ok_or_else(|| not_found("linker"))
+                }
+            }
+        }
+    }
+
+    /// The command that runs `linker` with linker arguments `args`
+    fn linker_command(&self, linker: &LinkerProgram, args: &[OsString]) -> Command {
+        match linker {
+            LinkerProgram::Clang => {
+                let mut cmd = Command::new("clang");
+
+                // Target-specific flags must come first
+                if let Some(target_flag) = self.options.target.clang_target() {
+                    cmd.arg(target_flag);
+                    cmd.arg("-fuse-ld=lld");
+                    if matches!(self.options.target, Target::RiscV64) {
+                        cmd.arg("-mabi=lp64d");
+                    }
+                }
+
+                // No default libraries; options clang does not know go to the linker
+                cmd.arg("-nostdlib");
+                for arg in args {
+                    if arg.to_string_lossy().starts_with("--") {
+                        let mut wrapped = OsString::from("-Wl,");
+                        wrapped.push(arg);
+                        cmd.arg(wrapped);
+                    } else {
+                        cmd.arg(arg);
+                    }
+                }
+
+                // The runtime is bitcode, optimized as it is linked
+                cmd.args(["-flto", "-O2"]);
+                cmd
+            }
+            LinkerProgram::Lld { path, flavor } => {
+                let mut cmd = Command::new(path);
+                cmd.args(flavor).args(args);
+
+                // The runtime is bitcode, optimized as it is linked
+                cmd.arg("--lto-O2");
+                cmd
+            }
+        }
+    }
+
+    fn link_custom_target(
+        &self,
+        obj_path: &Path,
+        libraries: &[PathBuf],
+        output_path: &Path,
+        custom: &CustomTarget,
+    ) -> Result<()> {
+        let runtime_path = custom.runtime.clone().ok_or_else(|| {
+            CompilerError::CodegenError(format!(
+                "Custom target '{}' needs a runtime object built for it (--runtime)",
+                custom.triple
+            ))
+        })?;
+        let mut inputs = vec![obj_path.to_path_buf()];
+        inputs.extend_from_slice(libraries);
+        inputs.push(runtime_path);
+        let mut cmd = custom.linker_command(&inputs, output_path)?;
+        let output = cmd.output().map_err(CompilerError::IOError)?;
+
+        if !output.status.success() {
+            return Err(CompilerError::CodegenError(format!(
+                "Linking failed:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            )));
+        }
+        Ok(())
+    }
+
+    /// The runtime library of the target, which programs are linked with
+    pub fn find_runtime_library(&self) -> Result<PathBuf> {
         let workspace = Target::find_workspace_root().ok_or_else(|| {
             CompilerError::IOError(std::io::Error::new(
                 std::io::ErrorKind::NotFound,
@@ -499,6 +1930,267 @@ mod tests {
         assert!(format!("{:?}", result.unwrap_err()).contains("must be a file, not a directory"));
     }
 
+    #[test]
+    fn test_build_modules_cached_reparses_changed_modules() {
+        let temp_dir = TempDir::new().unwrap();
+        let main = create_temp_file(
+            &temp_dir,
+            "main.py",
+            "import helper\nimport other\nhelper.f()\nother.g()\n",
+        );
+        create_temp_file(&temp_dir, "helper.py", "def f() -> None:\n    print(1)\n");
+        create_temp_file(&temp_dir, "other.py", "def g() -> None:\n    print(2)\n");
+        let search_paths = vec![temp_dir.path().to_path_buf()];
+        let mut cache = ModuleCache::default();
+
+        let (modules, _) = build_modules_cached(&main, search_paths.clone(), &mut cache).unwrap();
+        assert_eq!(modules.len(), 3);
+        assert_eq!(cache.hits(), 0);
+        let helper = temp_dir.path().join("helper.py");
+        let parsed = cache.source_fingerprint(&search_paths, &helper).unwrap();
+
+        // Only the touched module is parsed again
+        create_temp_file(&temp_dir, "helper.py", "def f() -> None:\n    print(3)\n");
+        let (modules, _) = build_modules_cached(&main, search_paths.clone(), &mut cache).unwrap();
+        assert_eq!(modules.len(), 3);
+        assert_eq!(cache.hits(), 2);
+        assert_ne!(
+            cache.source_fingerprint(&search_paths, &helper),
+            Some(parsed)
+        );
+
+        create_temp_file(&temp_dir, "main.py", "print(2)\n");
+        let (modules, entry) = build_modules_cached(&main, search_paths, &mut cache).unwrap();
+        assert_eq!(modules.len(), 1);
+        assert!(modules[&entry].imports.is_empty());
+    }
+
+    #[test]
+    fn test_check_in_memory_file_system() {
+        let files = MemoryFileSystem::new()
+            .with_file(
+                "/virtual/app/main.py",
+                "import util\nprint(util.double(2))\n",
+            )
+            .with_file(
+                "/virtual/app/util.py",
+                "def double(x: int) -> int:\n    return x * 2\n",
+            )
+            .with_file(
+                "/virtual/app/broken.py",
+                "import util\nutil.double(\"2\")\n",
+            );
+        let compiler = Compiler::new(CompilerOptions::default()).with_file_system(files);
+
+        compiler.check(Path::new("/virtual/app/main.py")).unwrap();
+        assert!(compiler.check(Path::new("/virtual/app/broken.py")).is_err());
+        let missing = compiler.check(Path::new("/virtual/app/missing.py"));
+        assert!(format!("{:?}", missing.unwrap_err()).contains("Input file not found"));
+    }
+
+    #[test]
+    fn test_compile_source() {
+        let temp_dir = TempDir::new().unwrap();
+        let options = CompilerOptions {
+            emit_obj: true,
+            ..Default::default()
+        };
+        let compiler = Compiler::new(options);
+        let output = temp_dir.path().join("main.o");
+
+        compiler
+            .compile_source("main", "print(1 + 2)\n", &output)
+            .unwrap();
+        assert!(output.is_file());
+
+        let result = compiler.compile_source("1main", "print(1)\n", &output);
+        assert!(format!("{:?}", result.unwrap_err()).contains("Not a module name: '1main'"));
+    }
+
+    #[test]
+    fn test_compile_to_bitcode() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = create_temp_file(
+            &temp_dir,
+            "main.py",
+            "@extern(\"puts\")\ndef puts(s: str) -> int: ...\n\nputs(\"hi\")\n",
+        );
+        let compiler = Compiler::new(CompilerOptions::default());
+
+        let compiled = compiler.compile_to_bitcode(&path).unwrap();
+        assert!(compiled.bitcode.starts_with(b"BC\xC0\xDE"));
+        assert_eq!(compiled.triple, Target::X86_64.triple());
+        assert!(compiled.undefined_symbols.contains(&"puts".to_string()));
+        assert!(compiled
+            .undefined_symbols
+            .contains(&"__pyc_sys_init".to_string()));
+        assert!(!compiled.undefined_symbols.contains(&"main".to_string()));
+        assert!(compiled.libraries.is_empty());
+
+        let from_source = compiler
+            .compile_source_to_bitcode("main", "print(1)\n")
+            .unwrap();
+        assert!(from_source.bitcode.starts_with(b"BC\xC0\xDE"));
+    }
+
+    #[test]
+    fn test_collect_embedded_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_dir = temp_dir.path().join("data");
+        fs::create_dir(&data_dir).unwrap();
+        fs::write(data_dir.join("b.txt"), "second").unwrap();
+        fs::write(data_dir.join("a.txt"), "first").unwrap();
+
+        let files = collect_embedded_files(&[data_dir.clone()]).unwrap();
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].path, data_dir.join("a.txt").to_string_lossy());
+        assert_eq!(files[0].data, b"first");
+        assert_eq!(files[1].data, b"second");
+
+        let missing = collect_embedded_files(&[temp_dir.path().join("missing.txt")]);
+        assert!(format!("{}", missing.unwrap_err()).contains("Cannot embed"));
+
+        let twice = collect_embedded_files(&[data_dir.clone(), data_dir.join("b.txt")]);
+        assert!(format!("{}", twice.unwrap_err()).contains("b.txt' is embedded more than once"));
+    }
+
+    #[test]
+    fn test_custom_target_linker_command() {
+        let custom = CustomTarget {
+            triple: "aarch64-none-elf".to_string(),
+            crt_begin: vec![PathBuf::from("crt0.o")],
+            linker_template: Some("ld.lld {crt_begin} {inputs} {crt_end} -o {output}".to_string()),
+            ..Default::default()
+        };
+        let cmd = custom
+            .linker_command(
+                &[PathBuf::from("main.bc"), PathBuf::from("rt.o")],
+                Path::new("app"),
+            )
+            .unwrap();
+
+        assert_eq!(cmd.get_program(), "ld.lld");
+        let args: Vec<_> = cmd.get_args().map(|a| a.to_str().unwrap()).collect();
+        assert_eq!(args, ["crt0.o", "main.bc", "rt.o", "-o", "app"]);
+    }
+
+    #[test]
+    fn test_custom_target_empty_placeholder_in_argument() {
+        let custom = CustomTarget {
+            triple: "aarch64-none-elf".to_string(),
+            linker_template: Some("ld.lld --sysroot={sysroot} {inputs} -o {output}".to_string()),
+            ..Default::default()
+        };
+        let err = custom
+            .linker_command(&[PathBuf::from("main.bc")], Path::new("app"))
+            .unwrap_err();
+        assert!(format!("{}", err).contains("'--sysroot={sysroot}' uses {sysroot}"));
+    }
+
+    #[test]
+    fn test_custom_target_list_placeholder_in_argument() {
+        let custom = CustomTarget {
+            triple: "aarch64-none-elf".to_string(),
+            crt_begin: vec![PathBuf::from("crt0.o"), PathBuf::from("crti.o")],
+            linker_template: Some("clang -Wl,{crt_begin} {inputs} -o {output}".to_string()),
+            ..Default::default()
+        };
+        let cmd = custom
+            .linker_command(&[PathBuf::from("main.bc")], Path::new("app"))
+            .unwrap();
+
+        let args: Vec<_> = cmd.get_args().map(|a| a.to_str().unwrap()).collect();
+        assert_eq!(args, ["-Wl,crt0.o", "-Wl,crti.o", "main.bc", "-o", "app"]);
+    }
+
+    #[test]
+    fn test_custom_target_default_template() {
+        let custom = CustomTarget {
+            triple: "riscv32-unknown-elf".to_string(),
+            sysroot: Some(PathBuf::from("/opt/sysroot")),
+            ..Default::default()
+        };
+        let cmd = custom
+            .linker_command(&[PathBuf::from("main.bc")], Path::new("app"))
+            .unwrap();
+
+        let args: Vec<_> = cmd.get_args().map(|a| a.to_str().unwrap()).collect();
+        assert!(args.contains(&"--target=riscv32-unknown-elf"));
+        assert!(args.contains(&"--sysroot=/opt/sysroot"));
+        assert!(args.contains(&"-L/opt/sysroot/lib"));
+    }
+
+    #[test]
+    fn test_linker_commands() {
+        let compiler = Compiler::new(CompilerOptions::default());
+        let args: Vec<OsString> = ["main.o", "--start-group", "-l:libicuuc.a", "--end-group"]
+            .into_iter()
+            .map(OsString::from)
+            .collect();
+
+        let cmd = compiler.linker_command(&LinkerProgram::Clang, &args);
+        assert_eq!(cmd.get_program(), "clang");
+        let clang_args: Vec<_> = cmd.get_args().map(|a| a.to_str().unwrap()).collect();
+        assert_eq!(
+            clang_args,
+            [
+                "-nostdlib",
+                "main.o",
+                "-Wl,--start-group",
+                "-l:libicuuc.a",
+                "-Wl,--end-group",
+                "-flto",
+                "-O2"
+            ]
+        );
+
+        let rust_lld = LinkerProgram::Lld {
+            path: PathBuf::from("/rust/bin/rust-lld"),
+            flavor: vec!["-flavor", "gnu"],
+        };
+        let cmd = compiler.linker_command(&rust_lld, &args);
+        assert_eq!(cmd.get_program(), "/rust/bin/rust-lld");
+        let lld_args: Vec<_> = cmd.get_args().map(|a| a.to_str().unwrap()).collect();
+        assert_eq!(
+            lld_args,
+            [
+                "-flavor",
+                "gnu",
+                "main.o",
+                "--start-group",
+                "-l:libicuuc.a",
+                "--end-group",
+                "--lto-O2"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_setjmp_exceptions_targets() {
+        let setjmp = |target, custom_target| {
+            Compiler::new(CompilerOptions {
+                target,
+                custom_target,
+                exceptions: ExceptionModel::SetJmp,
+                ..Default::default()
+            })
+            .check_setjmp_exceptions()
+        };
+        assert!(setjmp(Target::X86_64, None).is_ok());
+        assert!(setjmp(Target::Aarch64, None).is_ok());
+        let err = setjmp(Target::RiscV64, None).unwrap_err();
+        assert!(err.to_string().contains("riscv64-unknown-linux-musl"));
+
+        let custom = |triple: &str| {
+            Some(CustomTarget {
+                triple: triple.to_string(),
+                ..Default::default()
+            })
+        };
+        assert!(setjmp(Target::RiscV64, custom("aarch64-none-elf")).is_ok());
+        assert!(setjmp(Target::X86_64, custom("riscv32-unknown-elf")).is_err());
+    }
+
     #[test]
     fn test_symlink_to_py_file() {
         use std::os::unix::fs::symlink;
@@ -1,5 +1,11 @@
+use std::path::{Path, PathBuf};
+
 use thiserror::Error;
 
+use crate::diagnostics::Label;
+use crate::lint::Lint;
+use crate::span::Span;
+
 #[derive(Error, Debug)]
 pub enum CompilerError {
     #[error("Parse error: {0}")]
@@ -83,10 +89,145 @@ pub enum CompilerError {
 
     #[error("Circular import detected: {0}")]
     CircularImport(String),
+
+    #[error("Project configuration error: {0}")]
+    ProjectConfigError(String),
+
+    #[error("Target spec error: {0}")]
+    TargetSpecError(String),
+
+    #[error("Language server error: {0}")]
+    LspError(String),
+
+    /// A warning turned into an error (`-W error`)
+    #[error("{message}")]
+    Lint { lint: Lint, message: String },
+
+    /// An error in the source: the statement it is in, and the file once
+    /// the module is known
+    #[error("{}", format_located(.path.as_deref(), .span, .error))]
+    Located {
+        path: Option<PathBuf>,
+        span: Span,
+        error: Box<CompilerError>,
+    },
+
+    /// An error with notes that explain it, shown around its source frame
+    /// (see `diagnostics`)
+    #[error("{error}")]
+    Annotated {
+        error: Box<CompilerError>,
+        labels: Vec<Label>,
+        help: Vec<String>,
+    },
 }
 
 pub type Result<T> = std::result::Result<T, CompilerError>;
 
+impl CompilerError {
+    /// Locate the error at `span`. An error already located (at the inner
+    /// statement it is in) keeps its position, and an unknown span changes
+    /// nothing.
+    pub fn at(self, span: Span) -> Self {
+        match self {
+            CompilerError::Multiple(errors) => {
+                CompilerError::Multiple(errors.into_iter().map(|e| e.at(span)).collect())
+            }
+            CompilerError::Located { .. } => self,
+            error if span.is_known() => CompilerError::Located {
+                path: None,
+                span,
+                error: Box::new(error),
+            },
+            error => error,
+        }
+    }
+
+    /// Name the file of the located errors that do not name one yet
+    pub fn in_file(self, path: &Path) -> Self {
+        match self {
+            CompilerError::Multiple(errors) => {
+                CompilerError::Multiple(errors.into_iter().map(|e| e.in_file(path)).collect())
+            }
+            CompilerError::Located {
+                path: None,
+                span,
+                error,
+            } => CompilerError::Located {
+                path: Some(path.to_path_buf()),
+                span,
+                error,
+            },
+            error => error,
+        }
+    }
+
+    /// Note that `span` of the same file explains the error, with `message`
+    pub fn with_label(self, span: Span, message: impl Into<String>) -> Self {
+        let label = Label {
+            span,
+            message: message.into(),
+        };
+        self.annotate(|labels, _| labels.push(label))
+    }
+
+    /// Add a help note: what the user may have meant, or how to fix it
+    pub fn with_help(self, help: impl Into<String>) -> Self {
+        let help = help.into();
+        self.annotate(|_, notes| notes.push(help))
+    }
+
+    /// Add to the notes of the error, inside its location
+    fn annotate(self, add: impl FnOnce(&mut Vec<Label>, &mut Vec<String>)) -> Self {
+        match self {
+            CompilerError::Located { path, span, error } => CompilerError::Located {
+                path,
+                span,
+                error: Box::new(error.annotate(add)),
+            },
+            CompilerError::Annotated {
+                error,
+                mut labels,
+                mut help,
+            } => {
+                add(&mut labels, &mut help);
+                CompilerError::Annotated {
+                    error,
+                    labels,
+                    help,
+                }
+            }
+            error => {
+                let mut labels = Vec::new();
+                let mut help = Vec::new();
+                add(&mut labels, &mut help);
+                CompilerError::Annotated {
+                    error: Box::new(error),
+                    labels,
+                    help,
+                }
+            }
+        }
+    }
+
+    /// Where in the source the error is, if known
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            CompilerError::Located { span, .. } => Some(*span),
+            _ => None,
+        }
+    }
+}
+
+/// Format an error located at `span`: `file:line:column: message`. The
+/// source frame around it is left to `diagnostics::render`.
+fn format_located(path: Option<&Path>, span: &Span, error: &CompilerError) -> String {
+    match path {
+        Some(path) => format!("{}:{}: {}", path.display(), span, error),
+        None => format!("line {}: {}", span, error),
+    }
+}
+
 /// Format multiple errors for display.
 fn format_multiple_errors(errors: &[CompilerError]) -> String {
     if errors.is_empty() {
@@ -115,9 +256,13 @@ impl ErrorCollector {
         Self { errors: Vec::new() }
     }
 
-    /// Add an error to the collection.
+    /// Add an error to the collection; the errors another collection
+    /// gathered are added one by one.
     pub fn push(&mut self, error: CompilerError) {
-        self.errors.push(error);
+        match error {
+            CompilerError::Multiple(errors) => self.errors.extend(errors),
+            error => self.errors.push(error),
+        }
     }
 
     /// Check if any errors have been collected.
@@ -169,6 +314,15 @@ impl ErrorCollector {
         }
     }
 
+    /// Locate the errors collected since there were `start` of them at
+    /// `span` of the file at `path`
+    pub fn locate_since(&mut self, start: usize, span: Span, path: &Path) {
+        for error in &mut self.errors[start..] {
+            let taken = std::mem::replace(error, CompilerError::Multiple(Vec::new()));
+            *error = taken.at(span).in_file(path);
+        }
+    }
+
     /// Get a reference to the collected errors.
     pub fn errors(&self) -> &[CompilerError] {
         &self.errors
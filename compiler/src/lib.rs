@@ -1,11 +1,28 @@
 pub mod ast;
+pub mod cache;
 pub mod codegen;
+pub mod diagnostics;
 pub mod driver;
 pub mod error;
+pub mod lint;
+pub mod lsp;
+pub mod project;
 pub mod python_ast;
+pub mod repl;
+pub mod span;
+pub mod staticlib;
+pub mod target_spec;
 pub mod tir;
+pub mod vfs;
 
 // Re-export for convenience
 pub use ast::ModuleName;
-pub use driver::{Compiler, CompilerOptions, Target};
+pub use diagnostics::ErrorFormat;
+pub use driver::{
+    env_search_paths, AllocMode, CompiledModule, Compiler, CompilerOptions, CustomTarget, EmitKind,
+    ExceptionModel, GcMode, Linker, OptLevel, Target, WarningLevel, SEARCH_PATH_VAR,
+};
 pub use error::{CompilerError, Result};
+pub use project::{BinaryTarget, Project};
+pub use span::Span;
+pub use target_spec::TargetSpec;
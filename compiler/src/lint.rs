@@ -0,0 +1,109 @@
+//! Warnings
+//!
+//! Lowering notes code that compiles but is likely a mistake: variables
+//! assigned and never read, imports never used, statements after a
+//! `return` or `raise`, and names that shadow a builtin. They are printed
+//! before the program is built, or with `-W error`, fail the build as
+//! errors (see `driver::WarningLevel`).
+
+use std::fmt;
+use std::path::PathBuf;
+
+use crate::error::CompilerError;
+use crate::span::Span;
+
+/// What a warning is about; its name is shown with it (`warning[unused-variable]`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Lint {
+    /// A local variable that is assigned but never read
+    UnusedVariable,
+    /// An imported module or name the module never uses
+    UnusedImport,
+    /// A statement after one that always returns or raises
+    UnreachableCode,
+    /// A definition, variable or parameter named like a builtin (`list`, `len`)
+    ShadowedBuiltin,
+}
+
+impl Lint {
+    pub fn name(self) -> &'static str {
+        match self {
+            Lint::UnusedVariable => "unused-variable",
+            Lint::UnusedImport => "unused-import",
+            Lint::UnreachableCode => "unreachable-code",
+            Lint::ShadowedBuiltin => "shadowed-builtin",
+        }
+    }
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A warning at a statement of a source file
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Warning {
+    pub path: PathBuf,
+    pub span: Span,
+    pub lint: Lint,
+    pub message: String,
+}
+
+impl Warning {
+    /// The warning as an error, under `-W error`
+    pub fn into_error(self) -> CompilerError {
+        CompilerError::Lint {
+            lint: self.lint,
+            message: self.message,
+        }
+        .at(self.span)
+        .in_file(&self.path)
+    }
+}
+
+/// The names Python's builtins module defines that programs call or use as
+/// types; binding one of them hides the builtin
+pub const BUILTIN_NAMES: &[&str] = &[
+    "abs",
+    "all",
+    "any",
+    "bool",
+    "bytearray",
+    "bytes",
+    "chr",
+    "dict",
+    "enumerate",
+    "filter",
+    "float",
+    "frozenset",
+    "hash",
+    "id",
+    "input",
+    "int",
+    "isinstance",
+    "iter",
+    "len",
+    "list",
+    "map",
+    "max",
+    "min",
+    "next",
+    "object",
+    "open",
+    "ord",
+    "print",
+    "range",
+    "repr",
+    "reversed",
+    "round",
+    "set",
+    "sorted",
+    "str",
+    "sum",
+    "super",
+    "tuple",
+    "type",
+    "zip",
+];
@@ -0,0 +1,1151 @@
+//! Language server (`pyls`)
+//!
+//! Speaks the Language Server Protocol over stdin and stdout, so that
+//! editors show what the compiler finds:
+//!
+//! - diagnostics: when a file is opened or saved, it is compiled as the
+//!   entry of a program, up to lowering, and the errors and warnings found
+//!   are published for each file they are in; imports resolve against the
+//!   file's directory and then those of `PYCPATH`
+//! - hover: the type TIR gives the variable, parameter, function or class
+//!   under the cursor
+//! - go to definition: where the name under the cursor is bound, in its
+//!   function, its module, or the module it is imported from
+//!
+//! Messages are JSON-RPC, each after a `Content-Length` header. They are
+//! parsed with Python's json module, as target specs are. One that cannot be
+//! read is answered with a JSON-RPC error, and the server goes on.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fs;
+use std::io::{BufRead, Read, Write};
+use std::path::{Path, PathBuf};
+
+use pyo3::prelude::*;
+use pyo3::types::{PyBool, PyDict, PyFloat, PyInt, PyList, PyModule, PyString};
+
+use crate::ast::{walk_block, Arg, ClassBodyItem, Expr, ImportKind, Module, Stmt};
+use crate::cache::ModuleCache;
+use crate::diagnostics::{json_string, strip_comment, Diagnostic, Severity};
+use crate::driver::{build_modules_cached, env_search_paths};
+use crate::error::{CompilerError, Result};
+use crate::span::Span;
+use crate::tir::{lower_to_tir, TirFunction, TirModule, TirProgram};
+
+/// JSON-RPC error code of a message whose body is not JSON, or that has no
+/// `Content-Length` to read it by
+const PARSE_ERROR: i64 = -32700;
+
+/// JSON-RPC error code of a JSON message the server cannot take, such as one
+/// with an int beyond 64 bits
+const INVALID_REQUEST: i64 = -32600;
+
+/// JSON-RPC error code of a request for a method the server does not have
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// A JSON value of a message
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+static NULL: Json = Json::Null;
+
+impl Json {
+    /// Parse the JSON text of a message
+    pub fn parse(text: &str) -> Result<Json> {
+        Python::attach(|py| {
+            let value = PyModule::import(py, "json")?.call_method1("loads", (text,))?;
+            Json::from_py(&value)
+        })
+        .map_err(CompilerError::from)
+    }
+
+    /// The id of a message `parse` rejected, when its text is a JSON object
+    /// with a usable id
+    fn recover_id(text: &str) -> Json {
+        Python::attach(|py| -> PyResult<Json> {
+            let value = PyModule::import(py, "json")?.call_method1("loads", (text,))?;
+            let id = value.cast::<PyDict>()?.get_item("id")?;
+            id.map_or(Ok(Json::Null), |id| Json::from_py(&id))
+        })
+        .unwrap_or(Json::Null)
+    }
+
+    fn from_py(value: &Bound<'_, PyAny>) -> PyResult<Json> {
+        // bool is a subclass of int, so it is checked first
+        if value.is_none() {
+            Ok(Json::Null)
+        } else if value.is_instance_of::<PyBool>() {
+            Ok(Json::Bool(value.extract()?))
+        } else if value.is_instance_of::<PyInt>() {
+            Ok(Json::Int(value.extract()?))
+        } else if value.is_instance_of::<PyFloat>() {
+            Ok(Json::Float(value.extract()?))
+        } else if value.is_instance_of::<PyString>() {
+            Ok(Json::String(value.extract()?))
+        } else if let Ok(list) = value.cast::<PyList>() {
+            list.iter()
+                .map(|item| Json::from_py(&item))
+                .collect::<PyResult<_>>()
+                .map(Json::Array)
+        } else if let Ok(dict) = value.cast::<PyDict>() {
+            dict.iter()
+                .map(|(key, item)| Ok((key.extract()?, Json::from_py(&item)?)))
+                .collect::<PyResult<_>>()
+                .map(Json::Object)
+        } else {
+            Err(pyo3::exceptions::PyValueError::new_err(
+                "Unexpected JSON value",
+            ))
+        }
+    }
+
+    /// An object of `fields`, in order
+    pub fn object<'a>(fields: impl IntoIterator<Item = (&'a str, Json)>) -> Json {
+        Json::Object(
+            fields
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value))
+                .collect(),
+        )
+    }
+
+    /// The member `key` of an object; null if there is none
+    pub fn get(&self, key: &str) -> &Json {
+        match self {
+            Json::Object(fields) => fields
+                .iter()
+                .find(|(name, _)| name == key)
+                .map_or(&NULL, |(_, value)| value),
+            _ => &NULL,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Json::Int(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        *self == Json::Null
+    }
+}
+
+impl From<&str> for Json {
+    fn from(text: &str) -> Self {
+        Json::String(text.to_string())
+    }
+}
+
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Json::Null => f.write_str("null"),
+            Json::Bool(value) => write!(f, "{}", value),
+            Json::Int(value) => write!(f, "{}", value),
+            // JSON has no NaN or infinities
+            Json::Float(value) if !value.is_finite() => f.write_str("null"),
+            Json::Float(value) => write!(f, "{}", value),
+            Json::String(text) => f.write_str(&json_string(text)),
+            Json::Array(items) => {
+                f.write_str("[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                f.write_str("]")
+            }
+            Json::Object(fields) => {
+                f.write_str("{")?;
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write!(f, "{}:{}", json_string(key), value)?;
+                }
+                f.write_str("}")
+            }
+        }
+    }
+}
+
+/// Read the next message: headers up to an empty line, then as many bytes
+/// as `Content-Length` says. None at the end of the input; a message that
+/// cannot be read gives the error response to answer it with instead.
+pub fn read_message(input: &mut impl BufRead) -> Result<Option<std::result::Result<Json, Json>>> {
+    let mut length = None;
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+    let Some(length) = length else {
+        return Ok(Some(Err(error_response(
+            &Json::Null,
+            PARSE_ERROR,
+            "Message without a valid Content-Length".to_string(),
+        ))));
+    };
+    let mut body = vec![0; length];
+    input.read_exact(&mut body)?;
+    let text = String::from_utf8_lossy(&body);
+    Ok(Some(Json::parse(&text).map_err(|error| {
+        let id = Json::recover_id(&text);
+        let code = if id.is_null() {
+            PARSE_ERROR
+        } else {
+            INVALID_REQUEST
+        };
+        error_response(&id, code, format!("Cannot read message: {}", error))
+    })))
+}
+
+/// Write a message with its `Content-Length` header
+pub fn write_message(output: &mut impl Write, message: &Json) -> Result<()> {
+    let body = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    output.flush()?;
+    Ok(())
+}
+
+/// Serve the messages of `input` until the client exits
+pub fn run(mut input: impl BufRead, mut output: impl Write) -> Result<()> {
+    let mut server = Server::default();
+    while let Some(message) = read_message(&mut input)? {
+        // A bad message is answered, and the server reads on
+        let message = match message {
+            Ok(message) => message,
+            Err(reply) => {
+                write_message(&mut output, &reply)?;
+                continue;
+            }
+        };
+        if message.get("method").as_str() == Some("exit") {
+            break;
+        }
+        for reply in server.handle(&message) {
+            write_message(&mut output, &reply)?;
+        }
+    }
+    Ok(())
+}
+
+/// The state of the server: what the last compile of each file found
+#[derive(Default)]
+pub struct Server {
+    analyses: HashMap<PathBuf, Analysis>,
+    /// The files diagnostics were last published for, by the file whose
+    /// compile found them
+    published: HashMap<PathBuf, Vec<PathBuf>>,
+    /// The modules parsed so far; a save only reparses the saved file
+    modules: ModuleCache,
+}
+
+impl Server {
+    /// Handle a request or notification, returning the response and the
+    /// notifications to send
+    pub fn handle(&mut self, message: &Json) -> Vec<Json> {
+        let id = message.get("id");
+        let params = message.get("params");
+        let path = params
+            .get("textDocument")
+            .get("uri")
+            .as_str()
+            .and_then(uri_to_path);
+        match message.get("method").as_str().unwrap_or("") {
+            "initialize" => vec![response(id, capabilities())],
+            "shutdown" => vec![response(id, Json::Null)],
+            "textDocument/didOpen" | "textDocument/didSave" => match path {
+                Some(path) => self.analyze(&path),
+                None => Vec::new(),
+            },
+            "textDocument/didClose" => match path {
+                Some(path) => self.close(&path),
+                None => Vec::new(),
+            },
+            "textDocument/hover" => {
+                let hover = path.and_then(|path| self.hover(&path, params.get("position")));
+                vec![response(id, hover.unwrap_or(Json::Null))]
+            }
+            "textDocument/definition" => {
+                let location = path.and_then(|path| self.definition(&path, params.get("position")));
+                vec![response(id, location.unwrap_or(Json::Null))]
+            }
+            method if !id.is_null() => vec![error_response(
+                id,
+                METHOD_NOT_FOUND,
+                format!("Unknown method '{}'", method),
+            )],
+            // Notifications the server has nothing to do for (`initialized`,
+            // `textDocument/didChange`)
+            _ => Vec::new(),
+        }
+    }
+
+    /// Compile `path`, and publish what was found in each file, clearing
+    /// what the last compile of it published
+    fn analyze(&mut self, path: &Path) -> Vec<Json> {
+        let (analysis, diagnostics) = Analysis::run(path, &mut self.modules);
+        let mut by_file: BTreeMap<PathBuf, Vec<Json>> = BTreeMap::new();
+        by_file.insert(path.to_path_buf(), Vec::new());
+        for file in self.published.remove(path).unwrap_or_default() {
+            by_file.entry(file).or_default();
+        }
+        for diagnostic in &diagnostics {
+            let file = diagnostic
+                .path
+                .clone()
+                .unwrap_or_else(|| path.to_path_buf());
+            by_file
+                .entry(file)
+                .or_default()
+                .push(lsp_diagnostic(diagnostic, path));
+        }
+        self.published
+            .insert(path.to_path_buf(), by_file.keys().cloned().collect());
+        self.analyses.insert(path.to_path_buf(), analysis);
+        by_file
+            .into_iter()
+            .map(|(file, diagnostics)| publish_diagnostics(&file, diagnostics))
+            .collect()
+    }
+
+    /// Forget `path`, clearing what its compile published
+    fn close(&mut self, path: &Path) -> Vec<Json> {
+        self.analyses.remove(path);
+        self.published
+            .remove(path)
+            .unwrap_or_default()
+            .iter()
+            .map(|file| publish_diagnostics(file, Vec::new()))
+            .collect()
+    }
+
+    /// The analysis of `path`, compiling it if it has not been
+    fn analysis(&mut self, path: &Path) -> &Analysis {
+        self.analyses
+            .entry(path.to_path_buf())
+            .or_insert_with(|| Analysis::run(path, &mut self.modules).0)
+    }
+
+    /// The type of the name at `position` of `path`, as Markdown
+    fn hover(&mut self, path: &Path, position: &Json) -> Option<Json> {
+        let (line, text) = source_line(path, position)?;
+        let (names, start, end) = names_at(&text, position.get("character").as_i64()?)?;
+        let analysis = self.analysis(path);
+        let signature = analysis.describe(path, line, &names)?;
+        Some(Json::object([
+            (
+                "contents",
+                Json::object([
+                    ("kind", "markdown".into()),
+                    (
+                        "value",
+                        Json::String(format!("```python\n{}\n```", signature)),
+                    ),
+                ]),
+            ),
+            (
+                "range",
+                Json::object([
+                    ("start", lsp_position(line - 1, &text, start)),
+                    ("end", lsp_position(line - 1, &text, end)),
+                ]),
+            ),
+        ]))
+    }
+
+    /// Where the name at `position` of `path` is bound
+    fn definition(&mut self, path: &Path, position: &Json) -> Option<Json> {
+        let (line, text) = source_line(path, position)?;
+        let (names, _, _) = names_at(&text, position.get("character").as_i64()?)?;
+        let analysis = self.analysis(path);
+        let (file, span) = analysis.definition(path, line, &names)?;
+        Some(lsp_location(&file, span))
+    }
+}
+
+/// What compiling a file found: its modules, and the program if it lowered
+#[derive(Default)]
+struct Analysis {
+    modules: Vec<Module>,
+    program: Option<TirProgram>,
+}
+
+/// A function or method, as its definition is written
+struct FunctionScope<'m> {
+    args: &'m [Arg],
+    body: &'m [Stmt],
+    span: Span,
+}
+
+impl Analysis {
+    /// Compile `path` up to lowering, returning what it found and its errors
+    /// and warnings
+    fn run(path: &Path, cache: &mut ModuleCache) -> (Analysis, Vec<Diagnostic>) {
+        let dir = path.parent().unwrap_or(Path::new("."));
+        let mut search_paths = vec![dir.to_path_buf()];
+        search_paths.extend(env_search_paths());
+        let (modules, entry) = match build_modules_cached(path, search_paths, cache) {
+            Ok(built) => built,
+            Err(e) => return (Analysis::default(), Diagnostic::from_error(&e)),
+        };
+        let module_list = modules.values().cloned().collect();
+        match lower_to_tir(modules, entry) {
+            Ok(program) => {
+                let diagnostics = program
+                    .warnings
+                    .iter()
+                    .map(Diagnostic::from_warning)
+                    .collect();
+                let analysis = Analysis {
+                    modules: module_list,
+                    program: Some(program),
+                };
+                (analysis, diagnostics)
+            }
+            Err(e) => {
+                let analysis = Analysis {
+                    modules: module_list,
+                    program: None,
+                };
+                (analysis, Diagnostic::from_error(&e))
+            }
+        }
+    }
+
+    fn module(&self, path: &Path) -> Option<&Module> {
+        self.modules
+            .iter()
+            .find(|module| same_file(&module.path, path))
+    }
+
+    fn module_named(&self, name: &str) -> Option<&Module> {
+        self.modules.iter().find(|module| module.id.0 == name)
+    }
+
+    /// The module an import in `module` binds to `name` (`import app.utils
+    /// as utils`), if any
+    fn imported_module(&self, module: &Module, name: &str) -> Option<&Module> {
+        module.imports.iter().find_map(|import| match &import.kind {
+            ImportKind::Module { alias } => {
+                let bound = alias.as_deref().unwrap_or(&import.source_name);
+                (bound == name).then(|| self.module_named(&import.module_id.0))?
+            }
+            _ => None,
+        })
+    }
+
+    /// The module a `from` import in `module` takes `name` from, with the
+    /// name it has there
+    fn imported_name<'a>(
+        &'a self,
+        module: &'a Module,
+        name: &str,
+    ) -> Option<(&'a Module, &'a str)> {
+        module.imports.iter().find_map(|import| match &import.kind {
+            ImportKind::Names(names) => names
+                .iter()
+                .find(|alias| alias.alias.as_deref().unwrap_or(&alias.name) == name)
+                .and_then(|alias| {
+                    let from = self.module_named(&import.module_id.0)?;
+                    Some((from, alias.name.as_str()))
+                }),
+            _ => None,
+        })
+    }
+
+    /// How TIR types the name `names` (a variable, or the member of a module,
+    /// `utils.helper`) at `line` of `path`
+    fn describe(&self, path: &Path, line: u32, names: &[String]) -> Option<String> {
+        let program = self.program.as_ref()?;
+        let module = self.module(path)?;
+        let current = tir_module(program, path)?;
+        let name = names.last()?;
+        if names.len() == 2 {
+            let from = self.imported_module(module, &names[0])?;
+            return describe_member(program, tir_module_named(program, &from.id.0)?, name);
+        }
+
+        if let Some(scope) = enclosing_function(&module.body.iter().collect::<Vec<_>>(), line) {
+            let function = program
+                .functions
+                .iter()
+                .find(|function| function.location == Some((current.id, scope.span.line)));
+            if let Some(function) = function {
+                if name == "self" {
+                    if let Some(class) = function.class {
+                        return Some(format!("self: {}", program.class_name(class)));
+                    }
+                }
+                let variable = function
+                    .params
+                    .iter()
+                    .chain(&function.locals)
+                    .find(|(local, _)| local == name);
+                if let Some((_, ty)) = variable {
+                    return Some(format!("{}: {}", name, program.type_name(ty)));
+                }
+            }
+        }
+
+        if let Some(description) = describe_member(program, current, name) {
+            return Some(description);
+        }
+        if let Some((_, ty)) = current.init_locals.iter().find(|(local, _)| local == name) {
+            return Some(format!("{}: {}", name, program.type_name(ty)));
+        }
+        if let Some(from) = self.imported_module(module, name) {
+            return Some(format!("module {}", from.id.0));
+        }
+        let (from, original) = self.imported_name(module, name)?;
+        describe_member(program, tir_module_named(program, &from.id.0)?, original)
+    }
+
+    /// Where the name `names` at `line` of `path` is bound: in the
+    /// function around the line, the module, or the module it is imported
+    /// from
+    fn definition(&self, path: &Path, line: u32, names: &[String]) -> Option<(PathBuf, Span)> {
+        let module = self.module(path)?;
+        let name = names.last()?;
+        if names.len() == 2 {
+            let from = self.imported_module(module, &names[0])?;
+            return Some((from.path.clone(), binding(&from.body, name)?));
+        }
+
+        if let Some(scope) = enclosing_function(&module.body.iter().collect::<Vec<_>>(), line) {
+            if scope.args.iter().any(|arg| &arg.name == name) {
+                return Some((module.path.clone(), scope.span));
+            }
+            if let Some(span) = binding(scope.body, name) {
+                return Some((module.path.clone(), span));
+            }
+        }
+
+        if let Some(span) = binding(&module.body, name) {
+            return Some((module.path.clone(), span));
+        }
+        if let Some(from) = self.imported_module(module, name) {
+            return Some((from.path.clone(), Span::new(1, 1)));
+        }
+        let (from, original) = self.imported_name(module, name)?;
+        Some((from.path.clone(), binding(&from.body, original)?))
+    }
+}
+
+/// The global, function or class `name` of a module, as TIR types it
+fn describe_member(program: &TirProgram, module: &TirModule, name: &str) -> Option<String> {
+    if let Some(global) = module.globals.iter().find(|global| global.name == name) {
+        return Some(format!("{}: {}", name, program.type_name(&global.ty)));
+    }
+    let function = module
+        .functions
+        .iter()
+        .map(|&id| program.function(id))
+        .find(|function| function.name == name);
+    if let Some(function) = function {
+        return Some(signature(program, function));
+    }
+    let qualified_name = format!("{}.{}", module.name, name);
+    module
+        .classes
+        .iter()
+        .map(|&id| program.class(id))
+        .find(|class| class.qualified_name == qualified_name)
+        .map(|class| match class.parent {
+            Some(parent) => format!("class {}({})", name, program.class_name(parent)),
+            None => format!("class {}", name),
+        })
+}
+
+/// `def name(a: int) -> str`
+fn signature(program: &TirProgram, function: &TirFunction) -> String {
+    let params: Vec<String> = function
+        .params
+        .iter()
+        .map(|(name, ty)| format!("{}: {}", name, program.type_name(ty)))
+        .collect();
+    format!(
+        "def {}({}) -> {}",
+        function.name,
+        params.join(", "),
+        program.type_name(&function.return_type)
+    )
+}
+
+fn tir_module<'p>(program: &'p TirProgram, path: &Path) -> Option<&'p TirModule> {
+    program
+        .modules
+        .iter()
+        .find(|module| same_file(&module.path, path))
+}
+
+fn tir_module_named<'p>(program: &'p TirProgram, name: &str) -> Option<&'p TirModule> {
+    program.modules.iter().find(|module| module.name == name)
+}
+
+/// The innermost function or method whose definition `line` is in, among
+/// the statements of a block: the one of the last statement to start at or
+/// before the line, if it is one or contains one
+fn enclosing_function<'m>(block: &[&'m Stmt], line: u32) -> Option<FunctionScope<'m>> {
+    let stmt: &'m Stmt = *block.iter().rev().find(|stmt| stmt.span().line <= line)?;
+    match stmt {
+        Stmt::FunctionDef {
+            args, body, span, ..
+        } => enclosing_function(&body.iter().collect::<Vec<_>>(), line).or(Some(FunctionScope {
+            args,
+            body,
+            span: *span,
+        })),
+        Stmt::ClassDef { body, .. } => {
+            let (args, body, span) = body
+                .iter()
+                .filter_map(|item| match item {
+                    ClassBodyItem::MethodDef {
+                        args, body, span, ..
+                    } => Some((args, body, *span)),
+                    ClassBodyItem::FieldDef { .. } => None,
+                })
+                .rfind(|(_, _, span)| span.is_known() && span.line <= line)?;
+            enclosing_function(&body.iter().collect::<Vec<_>>(), line).or(Some(FunctionScope {
+                args,
+                body,
+                span,
+            }))
+        }
+        Stmt::If { body, orelse, .. }
+        | Stmt::While { body, orelse, .. }
+        | Stmt::For { body, orelse, .. } => {
+            enclosing_function(&body.iter().chain(orelse).collect::<Vec<_>>(), line)
+        }
+        Stmt::With { body, .. } => enclosing_function(&body.iter().collect::<Vec<_>>(), line),
+        Stmt::Try {
+            body,
+            handlers,
+            orelse,
+            finalbody,
+            ..
+        } => {
+            let stmts = body
+                .iter()
+                .chain(handlers.iter().flat_map(|handler| &handler.body))
+                .chain(orelse)
+                .chain(finalbody)
+                .collect::<Vec<_>>();
+            enclosing_function(&stmts, line)
+        }
+        _ => None,
+    }
+}
+
+/// Where a block first binds `name`: the definition, assignment, loop or
+/// `with` that does, outside nested definitions
+fn binding(body: &[Stmt], name: &str) -> Option<Span> {
+    let mut found = None;
+    walk_block(body, &mut |stmt| {
+        if found.is_some() {
+            return;
+        }
+        let binds = match stmt {
+            Stmt::FunctionDef { name: bound, .. }
+            | Stmt::ClassDef { name: bound, .. }
+            | Stmt::For { target: bound, .. } => bound == name,
+            Stmt::Assign { target, .. }
+            | Stmt::With {
+                target: Some(target),
+                ..
+            } => target_binds(target, name),
+            _ => false,
+        };
+        if binds {
+            found = Some(stmt.span());
+        }
+    });
+    found
+}
+
+fn target_binds(target: &Expr, name: &str) -> bool {
+    match target {
+        Expr::Name(bound) => bound == name,
+        Expr::Tuple { elts } => elts.iter().any(|elt| target_binds(elt, name)),
+        Expr::Starred { value } => target_binds(value, name),
+        _ => false,
+    }
+}
+
+/// The name under UTF-16 offset `character` of `text`, with the name before
+/// it if it is an attribute of one (`utils.helper`), and the byte range of
+/// the name
+fn names_at(text: &str, character: i64) -> Option<(Vec<String>, usize, usize)> {
+    let offset = byte_offset(text, character);
+    let is_name = |c: char| c.is_alphanumeric() || c == '_';
+    let start = text[..offset]
+        .char_indices()
+        .rev()
+        .take_while(|&(_, c)| is_name(c))
+        .last()
+        .map_or(offset, |(i, _)| i);
+    let end = text[offset..]
+        .char_indices()
+        .find(|&(_, c)| !is_name(c))
+        .map_or(text.len(), |(i, _)| offset + i);
+    if start == end || text[start..].starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+    let mut names = vec![text[start..end].to_string()];
+    if let Some(before) = text[..start].strip_suffix('.') {
+        let owner: String = before
+            .chars()
+            .rev()
+            .take_while(|&c| is_name(c))
+            .collect::<Vec<_>>()
+            .into_iter()
+            .rev()
+            .collect();
+        let is_chained = before[..before.len() - owner.len()].ends_with('.');
+        if !owner.is_empty() && !is_chained {
+            names.insert(0, owner);
+        }
+    }
+    Some((names, start, end))
+}
+
+/// The byte offset of UTF-16 offset `character` of `text`
+fn byte_offset(text: &str, character: i64) -> usize {
+    let mut units = 0;
+    for (i, c) in text.char_indices() {
+        if units >= character {
+            return i;
+        }
+        units += c.len_utf16() as i64;
+    }
+    text.len()
+}
+
+/// The 1-based line at `position` of `path`, and its text
+fn source_line(path: &Path, position: &Json) -> Option<(u32, String)> {
+    let line = position.get("line").as_i64()?;
+    let source = fs::read_to_string(path).ok()?;
+    let text = source.lines().nth(usize::try_from(line).ok()?)?;
+    Some((line as u32 + 1, text.to_string()))
+}
+
+/// The LSP position of byte `offset` of `text`, line `line` (0-based)
+fn lsp_position(line: u32, text: &str, offset: usize) -> Json {
+    let offset = offset.min(text.len());
+    let character = text.get(..offset).unwrap_or(text).encode_utf16().count();
+    Json::object([
+        ("line", Json::Int(line.into())),
+        ("character", Json::Int(character as i64)),
+    ])
+}
+
+/// The range a span marks in `lines`: from its column to the end of its
+/// line, without trailing whitespace or comment
+fn lsp_range(lines: &[&str], span: Option<Span>) -> Json {
+    let Some(span) = span.filter(|span| span.is_known()) else {
+        let start = Json::object([("line", Json::Int(0)), ("character", Json::Int(0))]);
+        return Json::object([("start", start.clone()), ("end", start)]);
+    };
+    let line = span.line - 1;
+    let text = lines.get(line as usize).copied().unwrap_or("");
+    let start = (span.column as usize).saturating_sub(1).min(text.len());
+    let rest = text.get(start..).unwrap_or("");
+    let end = start + strip_comment(rest).trim_end().len();
+    Json::object([
+        ("start", lsp_position(line, text, start)),
+        ("end", lsp_position(line, text, end)),
+    ])
+}
+
+fn lsp_location(file: &Path, span: Span) -> Json {
+    let lines = read_lines(file);
+    let lines: Vec<&str> = lines.lines().collect();
+    Json::object([
+        ("uri", Json::String(path_to_uri(file))),
+        ("range", lsp_range(&lines, Some(span))),
+    ])
+}
+
+/// An LSP diagnostic: the message with its help notes, at the span of
+/// `diagnostic`, with its labels as related information
+fn lsp_diagnostic(diagnostic: &Diagnostic, default_path: &Path) -> Json {
+    let path = diagnostic.path.as_deref().unwrap_or(default_path);
+    let source = read_lines(path);
+    let lines: Vec<&str> = source.lines().collect();
+    let mut message = diagnostic.message.clone();
+    for help in &diagnostic.help {
+        message.push_str("\nhelp: ");
+        message.push_str(help);
+    }
+    let related = diagnostic
+        .labels
+        .iter()
+        .map(|label| {
+            Json::object([
+                (
+                    "location",
+                    Json::object([
+                        ("uri", Json::String(path_to_uri(path))),
+                        ("range", lsp_range(&lines, Some(label.span))),
+                    ]),
+                ),
+                ("message", Json::String(label.message.clone())),
+            ])
+        })
+        .collect();
+    let severity = match diagnostic.severity {
+        Severity::Error => 1,
+        Severity::Warning => 2,
+    };
+    let mut fields = vec![
+        ("range", lsp_range(&lines, diagnostic.span)),
+        ("severity", Json::Int(severity)),
+        ("source", "pycc".into()),
+        ("message", Json::String(message)),
+        ("relatedInformation", Json::Array(related)),
+    ];
+    if let Some(code) = &diagnostic.code {
+        fields.push(("code", Json::String(code.clone())));
+    }
+    Json::object(fields)
+}
+
+fn publish_diagnostics(file: &Path, diagnostics: Vec<Json>) -> Json {
+    Json::object([
+        ("jsonrpc", "2.0".into()),
+        ("method", "textDocument/publishDiagnostics".into()),
+        (
+            "params",
+            Json::object([
+                ("uri", Json::String(path_to_uri(file))),
+                ("diagnostics", Json::Array(diagnostics)),
+            ]),
+        ),
+    ])
+}
+
+fn response(id: &Json, result: Json) -> Json {
+    Json::object([
+        ("jsonrpc", "2.0".into()),
+        ("id", id.clone()),
+        ("result", result),
+    ])
+}
+
+fn error_response(id: &Json, code: i64, message: String) -> Json {
+    Json::object([
+        ("jsonrpc", "2.0".into()),
+        ("id", id.clone()),
+        (
+            "error",
+            Json::object([
+                ("code", Json::Int(code)),
+                ("message", Json::String(message)),
+            ]),
+        ),
+    ])
+}
+
+/// What the server does: full documents are compiled when opened or saved
+fn capabilities() -> Json {
+    Json::object([
+        (
+            "capabilities",
+            Json::object([
+                (
+                    "textDocumentSync",
+                    Json::object([
+                        ("openClose", Json::Bool(true)),
+                        ("change", Json::Int(0)),
+                        ("save", Json::object([("includeText", Json::Bool(false))])),
+                    ]),
+                ),
+                ("hoverProvider", Json::Bool(true)),
+                ("definitionProvider", Json::Bool(true)),
+            ]),
+        ),
+        (
+            "serverInfo",
+            Json::object([
+                ("name", "pyls".into()),
+                ("version", env!("CARGO_PKG_VERSION").into()),
+            ]),
+        ),
+    ])
+}
+
+fn read_lines(path: &Path) -> String {
+    fs::read_to_string(path).unwrap_or_default()
+}
+
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// The path of a `file://` URI, with its %-escapes decoded
+fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let encoded = uri.strip_prefix("file://")?;
+    let bytes = encoded.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escape = (bytes[i] == b'%')
+            .then(|| encoded.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escape {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    Some(PathBuf::from(String::from_utf8(decoded).ok()?))
+}
+
+/// The `file://` URI of a path, escaping what URIs cannot hold
+fn path_to_uri(path: &Path) -> String {
+    let path = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    let mut uri = String::from("file://");
+    for byte in path.to_string_lossy().bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' | b'~' => {
+                uri.push(byte as char)
+            }
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    uri
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn request(id: i64, method: &str, params: Json) -> Json {
+        Json::object([
+            ("jsonrpc", "2.0".into()),
+            ("id", Json::Int(id)),
+            ("method", method.into()),
+            ("params", params),
+        ])
+    }
+
+    fn text_document(path: &Path, line: i64, character: i64) -> Json {
+        Json::object([
+            (
+                "textDocument",
+                Json::object([("uri", Json::String(path_to_uri(path)))]),
+            ),
+            (
+                "position",
+                Json::object([
+                    ("line", Json::Int(line)),
+                    ("character", Json::Int(character)),
+                ]),
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let text =
+            r#"{"id":1,"params":{"uri":"file:///a%20b.py","ok":true,"list":[null,1.5,"\"q\"\n"]}}"#;
+        let json = Json::parse(text).unwrap();
+        assert_eq!(json.get("id").as_i64(), Some(1));
+        assert_eq!(json.to_string(), text);
+        assert_eq!(
+            uri_to_path(json.get("params").get("uri").as_str().unwrap()),
+            Some(PathBuf::from("/a b.py"))
+        );
+        assert_eq!(path_to_uri(Path::new("/a b.py")), "file:///a%20b.py");
+    }
+
+    #[test]
+    fn test_bad_messages_are_answered() {
+        let frame = |body: &str| format!("Content-Length: {}\r\n\r\n{}", body.len(), body);
+        let input = [
+            frame("{not json"),
+            "Content-Length: many\r\n\r\n".to_string(),
+            frame(r#"{"jsonrpc":"2.0","id":7,"method":"shutdown","params":{"n":99999999999999999999}}"#),
+            frame(r#"{"jsonrpc":"2.0","id":8,"method":"shutdown"}"#),
+        ]
+        .concat();
+        let mut output = Vec::new();
+        run(input.as_bytes(), &mut output).unwrap();
+
+        let mut replies = output.as_slice();
+        let mut next = || read_message(&mut replies).unwrap().unwrap().unwrap();
+        for (id, code) in [
+            (Json::Null, PARSE_ERROR),
+            (Json::Null, PARSE_ERROR),
+            (Json::Int(7), INVALID_REQUEST),
+        ] {
+            let reply = next();
+            assert_eq!(reply.get("id"), &id);
+            assert_eq!(reply.get("error").get("code").as_i64(), Some(code));
+        }
+        let reply = next();
+        assert_eq!(reply.get("id").as_i64(), Some(8));
+        assert!(reply.get("result").is_null());
+        assert!(read_message(&mut replies).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_json_non_finite_float() {
+        let json = Json::Array(vec![
+            Json::Float(f64::NAN),
+            Json::Float(f64::INFINITY),
+            Json::Float(f64::NEG_INFINITY),
+            Json::Float(-0.25),
+        ]);
+        assert_eq!(json.to_string(), "[null,null,null,-0.25]");
+    }
+
+    #[test]
+    fn test_names_at() {
+        let names = |text: &str, character| names_at(text, character).map(|(names, _, _)| names);
+        assert_eq!(
+            names("    x = utils.helper(1)", 16),
+            Some(vec!["utils".to_string(), "helper".to_string()])
+        );
+        assert_eq!(
+            names("    x = utils.helper(1)", 9),
+            Some(vec!["utils".to_string()])
+        );
+        assert_eq!(names("    x = a.b.c", 12), Some(vec!["c".to_string()]));
+        assert_eq!(names("    x = 12", 9), None);
+    }
+
+    #[test]
+    fn test_diagnostics_hover_and_definition() {
+        let temp_dir = TempDir::new().unwrap();
+        let helper = temp_dir.path().join("helper.py");
+        fs::write(&helper, "def double(n: int) -> int:\n    return n * 2\n").unwrap();
+        let main = temp_dir.path().join("main.py");
+        fs::write(
+            &main,
+            "from helper import double\n\ndef main() -> None:\n    total = double(21)\n    print(total)\n    name: str = 5\n\nmain()\n",
+        )
+        .unwrap();
+
+        let mut server = Server::default();
+        let opened = Json::object([(
+            "textDocument",
+            Json::object([("uri", Json::String(path_to_uri(&main)))]),
+        )]);
+        let published = server.handle(&Json::object([
+            ("jsonrpc", "2.0".into()),
+            ("method", "textDocument/didOpen".into()),
+            ("params", opened),
+        ]));
+        assert_eq!(published.len(), 1);
+        let diagnostics = published[0].get("params").get("diagnostics");
+        let Json::Array(diagnostics) = diagnostics else {
+            panic!("no diagnostics: {}", published[0]);
+        };
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].get("range").get("start").to_string(),
+            r#"{"line":5,"character":4}"#
+        );
+        assert_eq!(diagnostics[0].get("severity").as_i64(), Some(1));
+
+        fs::write(
+            &main,
+            "from helper import double\n\ndef main() -> None:\n    total = double(21)\n    print(total)\n\nmain()\n",
+        )
+        .unwrap();
+        let saved = Json::object([(
+            "textDocument",
+            Json::object([("uri", Json::String(path_to_uri(&main)))]),
+        )]);
+        let published = server.handle(&Json::object([
+            ("jsonrpc", "2.0".into()),
+            ("method", "textDocument/didSave".into()),
+            ("params", saved),
+        ]));
+        assert_eq!(
+            published[0].get("params").get("diagnostics"),
+            &Json::Array(Vec::new())
+        );
+
+        let hover = server.handle(&request(
+            1,
+            "textDocument/hover",
+            text_document(&main, 4, 11),
+        ));
+        assert_eq!(
+            hover[0].get("result").get("contents").get("value").as_str(),
+            Some("```python\ntotal: int\n```")
+        );
+        let hover = server.handle(&request(
+            2,
+            "textDocument/hover",
+            text_document(&main, 3, 14),
+        ));
+        assert_eq!(
+            hover[0].get("result").get("contents").get("value").as_str(),
+            Some("```python\ndef double(n: int) -> int\n```")
+        );
+
+        let definition = server.handle(&request(
+            3,
+            "textDocument/definition",
+            text_document(&main, 3, 14),
+        ));
+        let location = definition[0].get("result");
+        assert_eq!(
+            location.get("uri").as_str(),
+            Some(path_to_uri(&helper).as_str())
+        );
+        assert_eq!(
+            location.get("range").get("start").to_string(),
+            r#"{"line":0,"character":0}"#
+        );
+
+        let unknown = server.handle(&request(4, "textDocument/rename", Json::Null));
+        assert_eq!(
+            unknown[0].get("error").get("code").as_i64(),
+            Some(METHOD_NOT_FOUND)
+        );
+    }
+}
@@ -0,0 +1,258 @@
+//! Project configuration for `pycc --project`
+//!
+//! A project is a directory containing a `pyproject.toml` with a `[tool.pycc]` table:
+//!
+//! ```toml
+//! [tool.pycc]
+//! source-roots = ["src"]      # optional, defaults to the project directory
+//! search-paths = ["../libs"]  # optional, more directories to import from
+//!
+//! [tool.pycc.binaries]
+//! app = "app.main"            # binary name -> entry module
+//! ```
+//!
+//! Every module under the source roots is type-checked, while those under the
+//! search paths are only checked when a binary imports them.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use pyo3::prelude::*;
+use pyo3::types::PyModule;
+
+use crate::error::{CompilerError, Result};
+
+/// Name of the project configuration file
+pub const PYPROJECT_FILE: &str = "pyproject.toml";
+
+/// A binary declared in `[tool.pycc.binaries]`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BinaryTarget {
+    /// Output executable name
+    pub name: String,
+    /// Dotted name of the entry module (e.g., "app.main")
+    pub module: String,
+}
+
+/// A loaded project: its source roots and declared binaries
+#[derive(Debug, Clone)]
+pub struct Project {
+    pub root: PathBuf,
+    pub source_roots: Vec<PathBuf>,
+    /// Directories imports are also resolved against, after the source roots
+    pub search_paths: Vec<PathBuf>,
+    pub binaries: Vec<BinaryTarget>,
+}
+
+impl Project {
+    /// Load the project rooted at `dir` from its `pyproject.toml`
+    pub fn load(dir: &Path) -> Result<Self> {
+        let root = dir.canonicalize().map_err(|e| {
+            CompilerError::IOError(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("Project directory not found {}: {e}", dir.display()),
+            ))
+        })?;
+
+        let config_path = root.join(PYPROJECT_FILE);
+        let source = fs::read_to_string(&config_path).map_err(|e| {
+            CompilerError::ProjectConfigError(format!("Cannot read {}: {e}", config_path.display()))
+        })?;
+
+        Self::from_toml(root, &source)
+    }
+
+    /// Build a project from the contents of a `pyproject.toml`
+    pub fn from_toml(root: PathBuf, source: &str) -> Result<Self> {
+        let table = Python::attach(|py| parse_pycc_table(py, source))
+            .map_err(|e| CompilerError::ProjectConfigError(format!("{PYPROJECT_FILE}: {e}")))?;
+
+        let roots = table.roots.unwrap_or_else(|| vec![".".to_string()]);
+        let source_roots = directories(&root, roots, "Source root")?;
+        let search_paths = directories(&root, table.search_paths, "Search path")?;
+
+        let binaries: Vec<_> = table
+            .binaries
+            .into_iter()
+            .map(|(name, module)| BinaryTarget { name, module })
+            .collect();
+        if binaries.is_empty() {
+            return Err(CompilerError::ProjectConfigError(format!(
+                "No binaries declared in [tool.pycc.binaries] of {PYPROJECT_FILE}"
+            )));
+        }
+
+        Ok(Project {
+            root,
+            source_roots,
+            search_paths,
+            binaries,
+        })
+    }
+
+    /// Find every `.py` file under the source roots (sorted, hidden directories skipped)
+    pub fn discover_modules(&self) -> Result<Vec<PathBuf>> {
+        let mut found = Vec::new();
+        for source_root in &self.source_roots {
+            collect_py_files(source_root, &mut found)?;
+        }
+        found.sort();
+        found.dedup();
+        Ok(found)
+    }
+
+    /// The directories imports are resolved against: the source roots, then
+    /// the search paths
+    pub fn import_paths(&self) -> Vec<PathBuf> {
+        self.source_roots
+            .iter()
+            .chain(&self.search_paths)
+            .cloned()
+            .collect()
+    }
+
+    /// Resolve a binary's entry module to a file under the source roots
+    pub fn entry_path(&self, binary: &BinaryTarget) -> Result<PathBuf> {
+        let relative: PathBuf = binary.module.split('.').collect();
+        self.source_roots
+            .iter()
+            .map(|r| r.join(&relative).with_extension("py"))
+            .find(|p| p.is_file())
+            .ok_or_else(|| CompilerError::ModuleNotFound(binary.module.clone()))
+    }
+}
+
+/// The `[tool.pycc]` table, as written
+#[derive(Default)]
+struct PyccTable {
+    roots: Option<Vec<String>>,
+    search_paths: Vec<String>,
+    binaries: BTreeMap<String, String>,
+}
+
+/// Extract `source-roots`, `search-paths` and `binaries` from `[tool.pycc]`
+/// using Python's tomllib
+fn parse_pycc_table(py: Python<'_>, source: &str) -> PyResult<PyccTable> {
+    let data = PyModule::import(py, "tomllib")?.call_method1("loads", (source,))?;
+    let Some(pycc) = data
+        .get_item("tool")
+        .and_then(|tool| tool.get_item("pycc"))
+        .ok()
+    else {
+        return Ok(PyccTable::default());
+    };
+
+    let roots = match pycc.get_item("source-roots") {
+        Ok(v) => Some(v.extract::<Vec<String>>()?),
+        Err(_) => None,
+    };
+    let search_paths = match pycc.get_item("search-paths") {
+        Ok(v) => v.extract::<Vec<String>>()?,
+        Err(_) => Vec::new(),
+    };
+    let binaries = match pycc.get_item("binaries") {
+        Ok(v) => v.extract::<BTreeMap<String, String>>()?,
+        Err(_) => BTreeMap::new(),
+    };
+    Ok(PyccTable {
+        roots,
+        search_paths,
+        binaries,
+    })
+}
+
+/// The directories `dirs` names relative to the project root; `what` names
+/// them in the error for one that is not a directory
+fn directories(root: &Path, dirs: Vec<String>, what: &str) -> Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for dir in dirs {
+        let path = root.join(&dir);
+        if !path.is_dir() {
+            return Err(CompilerError::ProjectConfigError(format!(
+                "{what} '{dir}' is not a directory"
+            )));
+        }
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+fn collect_py_files(dir: &Path, out: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        if name.starts_with('.') || name == "__pycache__" {
+            continue;
+        }
+        if path.is_dir() {
+            collect_py_files(&path, out)?;
+        } else if path.extension().is_some_and(|ext| ext == "py") {
+            out.push(path);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_project_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().to_path_buf();
+        fs::create_dir_all(root.join("src/app")).unwrap();
+        fs::write(root.join("src/app/main.py"), "print(1)").unwrap();
+        fs::write(root.join("src/app/util.py"), "x: int = 1").unwrap();
+
+        let project = Project::from_toml(
+            root.clone(),
+            "[tool.pycc]\nsource-roots = [\"src\"]\n\n[tool.pycc.binaries]\napp = \"app.main\"\n",
+        )
+        .unwrap();
+
+        assert_eq!(project.binaries.len(), 1);
+        assert_eq!(project.binaries[0].name, "app");
+        assert_eq!(project.discover_modules().unwrap().len(), 2);
+        assert_eq!(
+            project.entry_path(&project.binaries[0]).unwrap(),
+            root.join("src/app/main.py")
+        );
+    }
+
+    #[test]
+    fn test_project_search_paths() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().join("app");
+        fs::create_dir_all(&root).unwrap();
+        fs::create_dir_all(temp_dir.path().join("libs")).unwrap();
+        fs::write(temp_dir.path().join("libs/util.py"), "x: int = 1").unwrap();
+
+        let project = Project::from_toml(
+            root.clone(),
+            "[tool.pycc]\nsearch-paths = [\"../libs\"]\n\n[tool.pycc.binaries]\napp = \"main\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            project.import_paths(),
+            vec![root.join("."), root.join("../libs")]
+        );
+        // Modules under a search path are not the project's own
+        assert!(project.discover_modules().unwrap().is_empty());
+
+        let result = Project::from_toml(
+            root,
+            "[tool.pycc]\nsearch-paths = [\"missing\"]\n\n[tool.pycc.binaries]\napp = \"main\"\n",
+        );
+        assert!(format!("{:?}", result.unwrap_err()).contains("Search path 'missing'"));
+    }
+
+    #[test]
+    fn test_project_without_binaries() {
+        let temp_dir = TempDir::new().unwrap();
+        let result = Project::from_toml(temp_dir.path().to_path_buf(), "[project]\nname = \"x\"\n");
+        assert!(format!("{:?}", result.unwrap_err()).contains("No binaries declared"));
+    }
+}
@@ -1,4 +1,5 @@
 use crate::error::{CompilerError, Result};
+use crate::span::Span;
 use pyo3::prelude::*;
 use pyo3::types::PyModule;
 
@@ -11,8 +12,22 @@ pub fn parse_python(source: &str) -> Result<Py<PyAny>> {
         // Call ast.parse(source)
         let parsed = ast_module.call_method1("parse", (source,)).map_err(|e| {
             CompilerError::ParseError(format!("Failed to parse Python source: {}", e))
+                .at(syntax_error_span(py, &e))
         })?;
 
         Ok(parsed.into())
     })
 }
+
+/// Where a SyntaxError points (its `offset` is already 1-based)
+fn syntax_error_span(py: Python<'_>, error: &PyErr) -> Span {
+    let value = error.value(py);
+    let attr = |name: &str| {
+        value
+            .getattr(name)
+            .ok()
+            .and_then(|v| v.extract::<u32>().ok())
+            .unwrap_or(0)
+    };
+    Span::new(attr("lineno"), attr("offset").max(1))
+}
@@ -0,0 +1,279 @@
+//! Interactive sessions (`pyrepl`)
+//!
+//! Each input is compiled as the continuation of the inputs before it: the
+//! session's source is every input accepted so far followed by the new one,
+//! and all of it is parsed and lowered again, so that the new input is
+//! type-checked against the functions, classes and globals the earlier ones
+//! defined. Of the lowered program, the module init keeps only the new
+//! input's statements, since those of the earlier inputs already ran, and
+//! the program is generated into a fresh LLVM module that is loaded into the
+//! session's JIT (see codegen/jit.rs) next to those of the earlier inputs.
+//! The module globals they define are only declared in it, so the new input
+//! reads and writes the same ones: the global scope persists between inputs.
+//! An imported module is initialized once, by the input importing it.
+//!
+//! An input that is an expression prints its value with `repr()`, as
+//! Python's REPL does (with `__repr__` for instances). An input that does
+//! not compile is reported and left out of the session, and an exception an
+//! input raises is reported without ending the session.
+
+use std::collections::HashSet;
+use std::io::{BufRead, IsTerminal, Write};
+use std::mem;
+use std::path::{Path, PathBuf};
+
+use inkwell::context::Context;
+
+use crate::codegen::{global_symbol, JitSession};
+use crate::diagnostics::{self, strip_comment, ErrorFormat};
+use crate::driver::Compiler;
+use crate::error::Result;
+use crate::lint::{Lint, Warning};
+use crate::tir::stmt::TirExceptHandler;
+use crate::tir::{
+    lower_to_tir, FuncId, LocalId, TirExpr, TirExprKind, TirFunction, TirProgram, TirStmt, TirType,
+    VarRef,
+};
+
+/// The module the inputs are compiled into
+const SESSION_MODULE: &str = "__main__";
+
+/// Read inputs from `input` until it ends, writing the prompts to `prompts`,
+/// and compile and run each; errors are reported in `format`
+pub fn run(
+    compiler: &Compiler,
+    format: ErrorFormat,
+    mut input: impl BufRead,
+    mut prompts: impl Write,
+) -> Result<()> {
+    let context = Context::create();
+    let mut session = Session::new(compiler, &context)?;
+    let color = std::io::stderr().is_terminal();
+    while let Some(text) = read_input(&mut input, &mut prompts)? {
+        if text.trim().is_empty() {
+            continue;
+        }
+        if let Err(error) = session.eval(&text) {
+            eprint!("{}", diagnostics::render(&error, format, color));
+        }
+    }
+    writeln!(prompts)?;
+    Ok(())
+}
+
+/// Read the next input: a line, or a block (a line ending with `:`, or a
+/// decorator, and the lines after it up to an empty one); None at the end
+/// of the input
+fn read_input(input: &mut impl BufRead, prompts: &mut impl Write) -> Result<Option<String>> {
+    write!(prompts, ">>> ")?;
+    prompts.flush()?;
+    let mut text = String::new();
+    if input.read_line(&mut text)? == 0 {
+        return Ok(None);
+    }
+    let first = strip_comment(&text).trim_end();
+    if first.ends_with(':') || first.starts_with('@') {
+        loop {
+            write!(prompts, "... ")?;
+            prompts.flush()?;
+            let mut line = String::new();
+            if input.read_line(&mut line)? == 0 || line.trim().is_empty() {
+                break;
+            }
+            text.push_str(&line);
+        }
+    }
+    Ok(Some(text))
+}
+
+/// The inputs run so far and the JIT their modules are loaded into
+struct Session<'ctx, 'c> {
+    compiler: &'c Compiler,
+    context: &'ctx Context,
+    jit: JitSession<'ctx>,
+    /// The inputs accepted so far, one after the other
+    source: String,
+    /// Number of statements of the session module's init the earlier
+    /// inputs ran
+    init_len: usize,
+    /// Modules whose init has run
+    initialized: HashSet<String>,
+    /// Symbols of the module globals the loaded modules define
+    globals: HashSet<String>,
+    /// Number of modules loaded, which names the entry function of the next
+    loaded: usize,
+}
+
+impl<'ctx, 'c> Session<'ctx, 'c> {
+    fn new(compiler: &'c Compiler, context: &'ctx Context) -> Result<Self> {
+        let jit = compiler.jit_session()?;
+        Ok(Session {
+            compiler,
+            context,
+            jit,
+            source: String::new(),
+            init_len: 0,
+            initialized: HashSet::new(),
+            globals: HashSet::new(),
+            loaded: 0,
+        })
+    }
+
+    /// Compile and run an input, and add it to the session if it compiles
+    fn eval(&mut self, input: &str) -> Result<()> {
+        let input = input.trim_end();
+
+        // An expression prints its value; anything else fails to compile
+        // as an argument of repr() (a call of a function returning nothing
+        // too), and is compiled as it is
+        let printed = format!("print(repr(\n{input}\n))");
+        let expression = if input.contains('\n') {
+            None
+        } else {
+            self.lower(&printed).ok().map(|lowered| (printed, lowered))
+        };
+        let (code, (entry, program)) = match expression {
+            Some(expression) => expression,
+            None => (input.to_string(), self.lower(input)?),
+        };
+
+        self.load(entry, program)?;
+        self.source.push_str(&code);
+        self.source.push('\n');
+        Ok(())
+    }
+
+    /// Parse and lower the session's source followed by `code`, returning
+    /// the path of the session module with the program
+    fn lower(&self, code: &str) -> Result<(PathBuf, TirProgram)> {
+        let source = format!("{}{}\n", self.source, code);
+        let (entry, converter) = self.compiler.source_converter(SESSION_MODULE, &source)?;
+        let (modules, entry_name) = self.compiler.parse_program(&entry, &converter)?;
+        Ok((entry, lower_to_tir(modules, entry_name)?))
+    }
+
+    /// Generate the code the input adds to the session into a module, load
+    /// it into the JIT and run it
+    fn load(&mut self, entry: PathBuf, mut program: TirProgram) -> Result<()> {
+        let warnings = mem::take(&mut program.warnings)
+            .into_iter()
+            .filter(|warning| self.is_new(warning, &entry, &program))
+            .collect();
+        self.compiler.report_warnings(warnings)?;
+
+        let init_len = program.module(program.entry).init_body.len();
+        self.keep_new_code(&mut program);
+        let defined: HashSet<String> = program
+            .modules
+            .iter()
+            .flat_map(|module| {
+                module
+                    .globals
+                    .iter()
+                    .map(|global| global_symbol(&module.name, &global.name))
+            })
+            .collect();
+
+        let main_name = format!("__pyc_repl_{}", self.loaded);
+        let module = self
+            .compiler
+            .codegen(self.context)
+            .with_external_globals(self.globals.clone())
+            .with_main_name(&main_name)
+            .codegen_tir(&program)?;
+        self.jit
+            .run(module, &main_name, &defined, &[String::new()])?;
+
+        self.loaded += 1;
+        self.init_len = init_len;
+        self.initialized
+            .extend(program.modules.iter().map(|module| module.name.clone()));
+        self.globals.extend(defined);
+        Ok(())
+    }
+
+    /// Whether a warning is about the new input, or a module it imports
+    /// first, rather than code reported on before
+    fn is_new(&self, warning: &Warning, entry: &Path, program: &TirProgram) -> bool {
+        if warning.path == entry {
+            // The input's first statement is unreachable when the input
+            // before it raised, which it did when it ran
+            let lines = self.source.lines().count() as u32;
+            let after_raise =
+                warning.lint == Lint::UnreachableCode && warning.span.line == lines + 1;
+            return warning.span.line > lines && !after_raise;
+        }
+        !program
+            .modules
+            .iter()
+            .any(|module| module.path == warning.path && self.initialized.contains(&module.name))
+    }
+
+    /// Leave out of the module inits the statements that ran already, and
+    /// report the exception the new input raises instead of exiting
+    fn keep_new_code(&self, program: &mut TirProgram) {
+        let entry = program.entry;
+        for module in &mut program.modules {
+            if module.id == entry {
+                module.init_body.drain(..self.init_len);
+            } else if self.initialized.contains(&module.name) {
+                module.init_body.clear();
+            }
+        }
+        catch_exceptions(program);
+    }
+}
+
+/// Run the session module's init in a try block whose handler reports the
+/// exception, as the runtime does one no handler catches, but goes on
+fn catch_exceptions(program: &mut TirProgram) {
+    if program.module(program.entry).init_body.is_empty() {
+        return;
+    }
+    let Some(exception) = program
+        .classes
+        .iter()
+        .find(|class| class.qualified_name == "__builtin__.Exception")
+        .map(|class| TirType::Class(class.id))
+    else {
+        return;
+    };
+    let report = FuncId(program.functions.len() as u32);
+    program.functions.push(TirFunction {
+        id: report,
+        name: "__pyc_report_exception".to_string(),
+        qualified_name: "__pyc_report_exception".to_string(),
+        params: vec![("exc".to_string(), exception.clone())],
+        return_type: TirType::Void,
+        locals: Vec::new(),
+        body: Vec::new(),
+        class: None,
+        runtime_name: Some("__pyc_report_exception".to_string()),
+        location: None,
+    });
+
+    let entry = program.entry;
+    let module = &mut program.modules[entry.index()];
+    let local = LocalId(module.init_locals.len() as u32);
+    module
+        .init_locals
+        .push(("__exc".to_string(), exception.clone()));
+    let call = TirExprKind::Call {
+        func: report,
+        args: vec![TirExpr::new(
+            TirExprKind::Var(VarRef::Local(local)),
+            exception,
+        )],
+    };
+    let body = mem::take(&mut module.init_body);
+    module.init_body = vec![TirStmt::Try {
+        body,
+        handlers: vec![TirExceptHandler {
+            exc_class: None,
+            local: Some(local),
+            body: vec![TirStmt::Expr(TirExpr::new(call, TirType::Void))],
+        }],
+        orelse: Vec::new(),
+        finalbody: Vec::new(),
+    }];
+}
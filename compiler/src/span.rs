@@ -0,0 +1,32 @@
+//! Source positions
+//!
+//! The converter records where each statement starts, from the `lineno` and
+//! `col_offset` of Python's AST nodes; lowering carries the position of the
+//! statement an expression belongs to into the TIR, and errors report it.
+
+use std::fmt;
+
+/// A position in a source file: a 1-based line and column. The default
+/// span, line 0, is unknown (code the compiler generates).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Span {
+    pub line: u32,
+    pub column: u32,
+}
+
+impl Span {
+    pub fn new(line: u32, column: u32) -> Self {
+        Span { line, column }
+    }
+
+    /// Whether the span is a position in the source
+    pub fn is_known(self) -> bool {
+        self.line > 0
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
@@ -0,0 +1,219 @@
+//! Static libraries (`pycc --emit=staticlib`)
+//!
+//! A package is compiled once into an archive (`libmathlib.a`) of an object
+//! file whose symbols are all internal but its `@export` functions and an
+//! initializer, and an interface (`mathlib.pyi`) next to it, which declares
+//! those functions with `@extern`:
+//!
+//! ```python
+//! # pycc-staticlib: libmathlib.a
+//!
+//! @extern("add")
+//! def add(a: int, b: int) -> int: ...
+//!
+//! @extern("__pyc_lib_init_mathlib")
+//! def _init_library() -> None: ...
+//!
+//! _init_library()
+//! ```
+//!
+//! A program importing `mathlib` finds the interface as it finds a module,
+//! parses it instead of the package's source, and calls the archive's
+//! functions as C functions. The `pycc-staticlib` line names the archive the
+//! program is linked with, and calling the initializer when the interface
+//! module is initialized runs the package's module-level code before the
+//! code of the modules that import it.
+
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::Result;
+use crate::tir::TirProgram;
+use crate::vfs::FileSystem;
+
+/// The comment of an interface naming its archive, relative to the interface
+const ARCHIVE_DIRECTIVE: &str = "# pycc-staticlib: ";
+
+/// The C symbol of the function that initializes the modules of the package
+/// whose entry module is `package`
+pub fn init_symbol(package: &str) -> String {
+    format!("__pyc_lib_init_{}", package.replace('.', "_"))
+}
+
+/// The symbols the archive of `program` defines: its exported functions and
+/// its initializer
+pub fn exported_symbols(program: &TirProgram) -> Vec<String> {
+    program
+        .exports
+        .iter()
+        .map(|&func_id| program.function(func_id).name.clone())
+        .chain([init_symbol(&program.module(program.entry).name)])
+        .collect()
+}
+
+/// Where the interface of the archive at `archive` is written: next to it,
+/// named after the package's entry module (`app.mathlib` is imported as
+/// `mathlib`)
+pub fn interface_path(archive: &Path, program: &TirProgram) -> PathBuf {
+    let package = &program.module(program.entry).name;
+    let name = package.rsplit('.').next().unwrap_or(package);
+    archive.with_file_name(format!("{}.pyi", name))
+}
+
+/// The interface of `program`, built into the archive at `archive`
+pub fn interface(program: &TirProgram, archive: &Path) -> String {
+    let archive_name = archive
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let package = &program.module(program.entry).name;
+    let mut text = format!(
+        "# Interface of the static library {}, written by pycc --emit=staticlib\n{}{}\n",
+        archive_name, ARCHIVE_DIRECTIVE, archive_name
+    );
+    for &func_id in &program.exports {
+        let func = program.function(func_id);
+        let params: Vec<String> = func
+            .params
+            .iter()
+            .map(|(name, ty)| format!("{}: {}", name, program.type_name(ty)))
+            .collect();
+        let _ = write!(
+            text,
+            "\n@extern(\"{}\")\ndef {}({}) -> {}: ...\n",
+            func.name,
+            func.name,
+            params.join(", "),
+            program.type_name(&func.return_type)
+        );
+    }
+    let _ = write!(
+        text,
+        "\n@extern(\"{}\")\ndef _init_library() -> None: ...\n\n_init_library()\n",
+        init_symbol(package)
+    );
+    text
+}
+
+/// The archive the interface at `path` in `files` names, if it is one
+pub fn linked_archive(files: &dyn FileSystem, path: &Path) -> Result<Option<PathBuf>> {
+    if path.extension().is_none_or(|extension| extension != "pyi") {
+        return Ok(None);
+    }
+    let source = files.read_to_string(path)?;
+    let archive = source
+        .lines()
+        .find_map(|line| line.strip_prefix(ARCHIVE_DIRECTIVE))
+        .map(|name| path.with_file_name(name.trim()));
+    Ok(archive)
+}
+
+/// Write an ar archive of the object file at `object`, with the symbol
+/// table linkers look `symbols` up in, as `ar rcs` does
+pub fn write_archive(object: &Path, symbols: &[String], archive: &Path) -> Result<()> {
+    let data = fs::read(object)?;
+
+    // The symbol table member: the number of symbols, the offset of the
+    // member defining each (all the object's) as 32-bit big-endian numbers,
+    // then their names, each ending with a NUL
+    let mut names = Vec::new();
+    for symbol in symbols {
+        names.extend_from_slice(symbol.as_bytes());
+        names.push(0);
+    }
+    let table_len = 4 + 4 * symbols.len() + names.len();
+    let object_offset =
+        (ARCHIVE_MAGIC.len() + MEMBER_HEADER_LEN + table_len + table_len % 2) as u32;
+    let mut table = Vec::with_capacity(table_len);
+    table.extend_from_slice(&(symbols.len() as u32).to_be_bytes());
+    for _ in symbols {
+        table.extend_from_slice(&object_offset.to_be_bytes());
+    }
+    table.extend_from_slice(&names);
+
+    let mut bytes = ARCHIVE_MAGIC.to_vec();
+    push_member(&mut bytes, "/", &table);
+    push_member(&mut bytes, "lib.o/", &data);
+    fs::write(archive, bytes)?;
+    Ok(())
+}
+
+const ARCHIVE_MAGIC: &[u8] = b"!<arch>\n";
+
+const MEMBER_HEADER_LEN: usize = 60;
+
+/// Append a member: its header (name, date, owner, group, mode and size,
+/// padded with spaces, then a backquote and a newline), then its data,
+/// padded to an even length
+fn push_member(bytes: &mut Vec<u8>, name: &str, data: &[u8]) {
+    let header = format!(
+        "{:<16}{:<12}{:<6}{:<6}{:<8}{:<10}`\n",
+        name,
+        0,
+        0,
+        0,
+        644,
+        data.len()
+    );
+    debug_assert_eq!(header.len(), MEMBER_HEADER_LEN);
+    bytes.extend_from_slice(header.as_bytes());
+    bytes.extend_from_slice(data);
+    if data.len() % 2 == 1 {
+        bytes.push(b'\n');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vfs::DiskFileSystem;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_write_archive() {
+        let temp_dir = TempDir::new().unwrap();
+        let object = temp_dir.path().join("lib.o");
+        fs::write(&object, b"OBJ").unwrap();
+        let archive = temp_dir.path().join("libm.a");
+        let symbols = ["add".to_string(), "__pyc_lib_init_m".to_string()];
+        write_archive(&object, &symbols, &archive).unwrap();
+
+        let bytes = fs::read(&archive).unwrap();
+        assert!(bytes.starts_with(b"!<arch>\n/               0 "));
+        // 4 + 2 * 4 + "add\0__pyc_lib_init_m\0" bytes of table, then the object
+        let table = &bytes[68..68 + 33];
+        assert_eq!(&table[..4], &2u32.to_be_bytes());
+        let object_offset = 8 + 60 + 34;
+        assert_eq!(&table[4..8], &(object_offset as u32).to_be_bytes());
+        assert_eq!(&table[12..], b"add\0__pyc_lib_init_m\0");
+        assert_eq!(
+            &bytes[object_offset..object_offset + 16],
+            b"lib.o/          "
+        );
+        assert_eq!(
+            &bytes[object_offset + 48..object_offset + 60],
+            b"3         `\n"
+        );
+        assert_eq!(&bytes[object_offset + 60..], b"OBJ\n");
+    }
+
+    #[test]
+    fn test_linked_archive() {
+        let temp_dir = TempDir::new().unwrap();
+        let interface = temp_dir.path().join("mathlib.pyi");
+        fs::write(
+            &interface,
+            "# Interface\n# pycc-staticlib: libmathlib.a\n\n_init_library()\n",
+        )
+        .unwrap();
+        assert_eq!(
+            linked_archive(&DiskFileSystem, &interface).unwrap(),
+            Some(temp_dir.path().join("libmathlib.a"))
+        );
+
+        let source = temp_dir.path().join("mathlib.py");
+        fs::write(&source, "# pycc-staticlib: libmathlib.a\n").unwrap();
+        assert_eq!(linked_archive(&DiskFileSystem, &source).unwrap(), None);
+    }
+}
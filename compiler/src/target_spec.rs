@@ -0,0 +1,210 @@
+//! Target specification files for `pycc --target-spec`
+//!
+//! A target spec describes a custom target in a TOML file (or a JSON file
+//! with the same keys, when its name ends in `.json`), so that a target can
+//! be added without patching the built-in configurations:
+//!
+//! ```toml
+//! triple = "armv7-unknown-linux-musleabihf"
+//! sysroot = "/opt/musl-armv7"                   # optional
+//! runtime = "runtime-armv7.o"
+//! crt-begin = ["/opt/musl-armv7/lib/crt1.o"]    # optional
+//! crt-end = ["/opt/musl-armv7/lib/crtn.o"]      # optional
+//! linker = "ld.lld {crt_begin} {inputs} -L{sysroot}/lib -lc {crt_end} -o {output}"
+//! ```
+//!
+//! `linker` is a linker command template (see [`DEFAULT_LINKER_TEMPLATE`],
+//! used when it is left out). Relative paths are relative to the spec file.
+//!
+//! [`DEFAULT_LINKER_TEMPLATE`]: crate::driver::DEFAULT_LINKER_TEMPLATE
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use pyo3::prelude::*;
+use pyo3::types::PyModule;
+
+use crate::driver::CustomTarget;
+use crate::error::{CompilerError, Result};
+
+/// The keys a target spec may have
+const KEYS: [&str; 6] = [
+    "triple",
+    "sysroot",
+    "runtime",
+    "crt-begin",
+    "crt-end",
+    "linker",
+];
+
+/// A custom target loaded from a spec file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TargetSpec {
+    /// LLVM target triple
+    pub triple: String,
+    /// Sysroot containing the target's libc
+    pub sysroot: Option<PathBuf>,
+    /// Runtime object built for the triple
+    pub runtime: Option<PathBuf>,
+    /// Startup objects linked before the program
+    pub crt_begin: Vec<PathBuf>,
+    /// Objects linked after everything else
+    pub crt_end: Vec<PathBuf>,
+    /// Linker command template
+    pub linker: Option<String>,
+}
+
+/// The values of a spec file's keys, as written
+struct RawSpec {
+    triple: Option<String>,
+    sysroot: Option<String>,
+    runtime: Option<String>,
+    crt_begin: Vec<String>,
+    crt_end: Vec<String>,
+    linker: Option<String>,
+}
+
+impl TargetSpec {
+    /// Load a target spec from a TOML or JSON file
+    pub fn load(path: &Path) -> Result<Self> {
+        let source = fs::read_to_string(path).map_err(|e| {
+            CompilerError::TargetSpecError(format!("Cannot read {}: {e}", path.display()))
+        })?;
+        let dir = path.parent().unwrap_or(Path::new("."));
+        let is_json = path.extension().is_some_and(|ext| ext == "json");
+        Self::from_source(dir, &source, is_json)
+            .map_err(|e| CompilerError::TargetSpecError(format!("{}: {e}", path.display())))
+    }
+
+    /// Build a target spec from the contents of a spec file in `dir`
+    pub fn parse(dir: &Path, source: &str, is_json: bool) -> Result<Self> {
+        Self::from_source(dir, source, is_json).map_err(CompilerError::TargetSpecError)
+    }
+
+    fn from_source(dir: &Path, source: &str, is_json: bool) -> std::result::Result<Self, String> {
+        let raw =
+            Python::attach(|py| parse_spec(py, source, is_json)).map_err(|e| e.to_string())?;
+
+        let triple = raw.triple.ok_or_else(|| "Missing 'triple'".to_string())?;
+        let path = |p: String| dir.join(p);
+        Ok(TargetSpec {
+            triple,
+            sysroot: raw.sysroot.map(path),
+            runtime: raw.runtime.map(path),
+            crt_begin: raw.crt_begin.into_iter().map(path).collect(),
+            crt_end: raw.crt_end.into_iter().map(path).collect(),
+            linker: raw.linker,
+        })
+    }
+}
+
+impl From<TargetSpec> for CustomTarget {
+    fn from(spec: TargetSpec) -> Self {
+        CustomTarget {
+            triple: spec.triple,
+            sysroot: spec.sysroot,
+            crt_begin: spec.crt_begin,
+            crt_end: spec.crt_end,
+            runtime: spec.runtime,
+            linker_template: spec.linker,
+        }
+    }
+}
+
+/// Read the keys of a spec using Python's tomllib (or json)
+fn parse_spec(py: Python<'_>, source: &str, is_json: bool) -> PyResult<RawSpec> {
+    let parser = if is_json { "json" } else { "tomllib" };
+    let data = PyModule::import(py, parser)?.call_method1("loads", (source,))?;
+
+    let keys: Vec<String> = data.call_method0("keys")?.extract()?;
+    if let Some(unknown) = keys.iter().find(|key| !KEYS.contains(&key.as_str())) {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "Unknown key '{unknown}'. Supported: {}",
+            KEYS.join(", ")
+        )));
+    }
+
+    let string = |key: &str| -> PyResult<Option<String>> {
+        match data.get_item(key) {
+            Ok(v) => Ok(Some(v.extract()?)),
+            Err(_) => Ok(None),
+        }
+    };
+    let list = |key: &str| -> PyResult<Vec<String>> {
+        match data.get_item(key) {
+            Ok(v) => v.extract(),
+            Err(_) => Ok(Vec::new()),
+        }
+    };
+    Ok(RawSpec {
+        triple: string("triple")?,
+        sysroot: string("sysroot")?,
+        runtime: string("runtime")?,
+        crt_begin: list("crt-begin")?,
+        crt_end: list("crt-end")?,
+        linker: string("linker")?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_toml_spec() {
+        let spec = TargetSpec::parse(
+            Path::new("/specs"),
+            "triple = \"armv7-unknown-linux-musleabihf\"\n\
+             sysroot = \"/opt/musl-armv7\"\n\
+             runtime = \"runtime-armv7.o\"\n\
+             crt-begin = [\"/opt/musl-armv7/lib/crt1.o\"]\n\
+             linker = \"ld.lld {inputs} -o {output}\"\n",
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(spec.triple, "armv7-unknown-linux-musleabihf");
+        assert_eq!(spec.sysroot, Some(PathBuf::from("/opt/musl-armv7")));
+        // Relative to the spec file
+        assert_eq!(spec.runtime, Some(PathBuf::from("/specs/runtime-armv7.o")));
+        assert_eq!(
+            spec.crt_begin,
+            vec![PathBuf::from("/opt/musl-armv7/lib/crt1.o")]
+        );
+        assert!(spec.crt_end.is_empty());
+
+        let custom = CustomTarget::from(spec);
+        let cmd = custom
+            .linker_command(&[PathBuf::from("main.o")], Path::new("app"))
+            .unwrap();
+        let args: Vec<_> = cmd.get_args().map(|a| a.to_str().unwrap()).collect();
+        assert_eq!(args, ["main.o", "-o", "app"]);
+    }
+
+    #[test]
+    fn test_json_spec() {
+        let spec = TargetSpec::parse(
+            Path::new("."),
+            r#"{"triple": "aarch64-none-elf", "crt-end": ["crtn.o"]}"#,
+            true,
+        )
+        .unwrap();
+        assert_eq!(spec.triple, "aarch64-none-elf");
+        assert_eq!(spec.crt_end, vec![PathBuf::from("./crtn.o")]);
+        assert_eq!(spec.linker, None);
+    }
+
+    #[test]
+    fn test_invalid_specs() {
+        let error = |source: &str| {
+            TargetSpec::parse(Path::new("."), source, false)
+                .unwrap_err()
+                .to_string()
+        };
+        assert!(error("sysroot = \"/opt\"\n").contains("Missing 'triple'"));
+        assert!(error("triple = \"x\"\nlinker-template = \"ld\"\n")
+            .contains("Unknown key 'linker-template'"));
+        // A triple that is not a string
+        assert!(TargetSpec::parse(Path::new("."), "triple = [1]\n", false).is_err());
+    }
+}
@@ -2,7 +2,7 @@
 //!
 //! Functions and classes are stored in flat vectors and referenced by ID.
 
-use super::ids::{ClassId, FuncId};
+use super::ids::{ClassId, FuncId, ModuleId};
 use super::stmt::TirStmt;
 use super::types::TirType;
 
@@ -37,6 +37,10 @@ pub struct TirFunction {
     /// If Some, this function is an external runtime function (no body to codegen)
     /// The value is the runtime function name to call (e.g., "list_len", "bytearray_append")
     pub runtime_name: Option<String>,
+
+    /// Module and line of the `def` it was lowered from, for debug info;
+    /// None for functions the compiler generates
+    pub location: Option<(ModuleId, u32)>,
 }
 
 /// A typed class definition
@@ -63,6 +67,12 @@ pub struct TirClass {
     /// The FuncId points to the function in TirProgram.functions
     pub methods: Vec<(String, FuncId)>,
 
+    /// Virtual-dispatch table: the methods some subclass overrides, with
+    /// this class's implementation of each. A subclass keeps its parent's
+    /// slots in order and appends its own, so a slot means the same method
+    /// throughout a hierarchy. Empty when nothing is overridden.
+    pub vtable: Vec<(String, FuncId)>,
+
     /// Generic type parameters (e.g., element type for list[int])
     pub type_params: Vec<TirType>,
 }
@@ -76,6 +86,11 @@ impl TirClass {
             .map(|(_, id)| *id)
     }
 
+    /// The vtable slot of a method, if it is overridden
+    pub fn vtable_slot(&self, name: &str) -> Option<usize> {
+        self.vtable.iter().position(|(n, _)| n == name)
+    }
+
     /// Get all fields in struct layout order (inherited first, then own)
     pub fn all_fields(&self) -> impl Iterator<Item = &(String, TirType)> {
         self.inherited_fields.iter().chain(self.fields.iter())
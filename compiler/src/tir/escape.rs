@@ -0,0 +1,680 @@
+//! Escape analysis
+//!
+//! Finds the locals whose class instances and lists can live in their
+//! function's stack frame instead of the heap. A local qualifies when every
+//! value constructed into it (`p = Point(..)`, `xs = [..]`) is dead by the
+//! time the function returns or the local is assigned again, because the
+//! local is only used:
+//!
+//! - to read or write a field
+//! - as an argument of a user function or method whose parameter does not
+//!   escape in turn (nor `self` in the class's `__init__`); a call of an
+//!   overridden method may run any of its overrides
+//! - as an argument of a list method that does not hold on to the list:
+//!   anything but `__iter__` and `__iadd__` on the list itself, and the
+//!   other list of `extend`, `+`, `+=` and `==`, whose items are copied
+//! - by a `for` loop over the list, whose iterator is only used by the loop,
+//!   which does not reassign the local
+//!
+//! Any other use (returning it, storing it, copying it to another variable,
+//! passing it to a constructor or another runtime function) keeps the local
+//! on the heap. Codegen gives each of these locals one slot, reused by every
+//! construction into it (see codegen/tir/stack_alloc.rs).
+//!
+//! Whether parameters escape is worked out for all user functions together:
+//! starting from "none escape", a parameter is marked when its function
+//! lets it escape, until nothing changes.
+
+use super::decls::TirFunction;
+use super::expr::{TirExpr, TirExprKind, VarRef};
+use super::ids::{ClassId, FuncId, LocalId};
+use super::program::TirProgram;
+use super::stmt::{TirLValue, TirStmt};
+use super::types::TirType;
+
+/// The stack-allocated locals of each function
+#[derive(Debug)]
+pub struct StackLocals {
+    /// Indexed by FuncId
+    functions: Vec<Vec<LocalId>>,
+}
+
+impl StackLocals {
+    /// The locals of a function whose instances or lists do not escape it
+    pub fn function(&self, id: FuncId) -> &[LocalId] {
+        &self.functions[id.index()]
+    }
+}
+
+/// Run escape analysis on every user function
+pub fn find_stack_locals(program: &TirProgram) -> StackLocals {
+    let escaping = escaping_params(program);
+    let functions = program
+        .functions
+        .iter()
+        .map(|func| {
+            if func.runtime_name.is_some() {
+                return Vec::new();
+            }
+            stack_locals_of(program, &escaping, func)
+        })
+        .collect();
+    StackLocals { functions }
+}
+
+/// For each function, whether each of its call arguments (the receiver
+/// first, for methods) may outlive the call
+fn escaping_params(program: &TirProgram) -> Vec<Vec<bool>> {
+    let mut escaping: Vec<Vec<bool>> = program
+        .functions
+        .iter()
+        .map(|func| vec![false; func.params.len() + usize::from(func.class.is_some())])
+        .collect();
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for func in &program.functions {
+            if func.runtime_name.is_some() {
+                continue;
+            }
+            for position in 0..escaping[func.id.index()].len() {
+                if escaping[func.id.index()][position] {
+                    continue;
+                }
+                let var = match (func.class, position) {
+                    (Some(_), 0) => VarRef::SelfRef,
+                    (Some(_), _) => VarRef::Param(position as u32 - 1),
+                    (None, _) => VarRef::Param(position as u32),
+                };
+                if Uses::escapes(program, &escaping, &func.body, var) {
+                    escaping[func.id.index()][position] = true;
+                    changed = true;
+                }
+            }
+        }
+    }
+    escaping
+}
+
+/// How the constructions into a local fit a stack slot
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Sites {
+    /// Nothing is constructed into the local
+    None,
+    /// Every construction builds a value of the local's own type
+    Fit,
+    /// Some construction cannot go in the local's slot
+    Unfit,
+}
+
+fn stack_locals_of(
+    program: &TirProgram,
+    escaping: &[Vec<bool>],
+    func: &TirFunction,
+) -> Vec<LocalId> {
+    let mut sites = vec![Sites::None; func.locals.len()];
+    for_each_node(&func.body, &mut |node| {
+        let (local, value) = match node {
+            Node::Stmt(TirStmt::Let { local, init, .. }) => (*local, init),
+            Node::Stmt(TirStmt::Assign {
+                target: TirLValue::Var(VarRef::Local(local)),
+                value,
+            }) => (*local, value),
+            _ => return,
+        };
+        let fits = match &value.kind {
+            TirExprKind::Construct { class, .. } => {
+                func.locals[local.index()].1 == TirType::Class(*class)
+                    && stack_class(program, escaping, *class)
+            }
+            TirExprKind::List { .. } => func.locals[local.index()].1 == value.ty,
+            _ => return,
+        };
+        let site = &mut sites[local.index()];
+        *site = if fits && *site != Sites::Unfit {
+            Sites::Fit
+        } else {
+            Sites::Unfit
+        };
+    });
+
+    (0..func.locals.len())
+        .filter(|&index| sites[index] == Sites::Fit)
+        .map(|index| LocalId(index as u32))
+        .filter(|&local| !Uses::escapes(program, escaping, &func.body, VarRef::Local(local)))
+        .collect()
+}
+
+/// Whether instances of a class can be built in a stack slot: it is a user
+/// class (builtins and exceptions come from the runtime) whose `__init__`
+/// does not let `self` escape
+fn stack_class(program: &TirProgram, escaping: &[Vec<bool>], class: ClassId) -> bool {
+    let mut current = Some(class);
+    while let Some(id) = current {
+        let class_def = program.class(id);
+        if class_def.qualified_name.starts_with("__builtin__.") {
+            return false;
+        }
+        current = class_def.parent;
+    }
+    match program.class(class).get_method("__init__") {
+        Some(init) => !escaping[init.index()][0],
+        None => true,
+    }
+}
+
+/// Whether a list runtime method leaves the list passed as argument
+/// `position` where it was
+fn list_method_keeps_list(runtime_name: &str, position: usize) -> bool {
+    let Some(method) = runtime_name.strip_prefix("__pyc___builtin___list_") else {
+        return false;
+    };
+    if method.starts_with("iterator") {
+        return false;
+    }
+    let method = method
+        .strip_prefix("float_")
+        .or_else(|| method.strip_prefix("bool_"))
+        .unwrap_or(method);
+    match position {
+        0 => !matches!(method, "__iter__" | "__iadd__"),
+        1 => matches!(method, "extend" | "__add__" | "__iadd__" | "__eq__"),
+        _ => false,
+    }
+}
+
+/// Looks for a use of a variable that lets its value escape
+struct Uses<'a> {
+    program: &'a TirProgram,
+    escaping: &'a [Vec<bool>],
+    /// The whole function body
+    body: &'a [TirStmt],
+    var: VarRef,
+    escaped: bool,
+}
+
+impl<'a> Uses<'a> {
+    fn escapes(
+        program: &'a TirProgram,
+        escaping: &'a [Vec<bool>],
+        body: &'a [TirStmt],
+        var: VarRef,
+    ) -> bool {
+        let mut uses = Uses {
+            program,
+            escaping,
+            body,
+            var,
+            escaped: false,
+        };
+        uses.stmts(body);
+        uses.escaped
+    }
+
+    fn is_var(&self, expr: &TirExpr) -> bool {
+        matches!(&expr.kind, TirExprKind::Var(var) if *var == self.var)
+    }
+
+    fn stmts(&mut self, stmts: &[TirStmt]) {
+        for (index, stmt) in stmts.iter().enumerate() {
+            if !self.is_loop_iterator(stmt, &stmts[index + 1..]) {
+                self.stmt(stmt);
+            }
+        }
+    }
+
+    fn stmt(&mut self, stmt: &TirStmt) {
+        match stmt {
+            TirStmt::Let { init, .. } => self.expr(init),
+            TirStmt::Assign { target, value } => {
+                if let TirLValue::Field { object, .. } = target {
+                    self.object(object);
+                }
+                self.expr(value);
+            }
+            TirStmt::AugAssign { value, .. } => self.expr(value),
+            TirStmt::Expr(expr)
+            | TirStmt::Return(Some(expr))
+            | TirStmt::Raise { exc: Some(expr) }
+            | TirStmt::Yield(expr) => self.expr(expr),
+            TirStmt::Return(None) | TirStmt::Raise { exc: None } => {}
+            TirStmt::If {
+                cond,
+                then_body,
+                else_body,
+            } => {
+                self.expr(cond);
+                self.stmts(then_body);
+                self.stmts(else_body);
+            }
+            TirStmt::While { cond, body } => {
+                self.expr(cond);
+                self.stmts(body);
+            }
+            TirStmt::Try {
+                body,
+                handlers,
+                orelse,
+                finalbody,
+            } => {
+                self.stmts(body);
+                for handler in handlers {
+                    self.stmts(&handler.body);
+                }
+                self.stmts(orelse);
+                self.stmts(finalbody);
+            }
+        }
+    }
+
+    /// The object of a field access or assignment
+    fn object(&mut self, object: &TirExpr) {
+        if !self.is_var(object) {
+            self.expr(object);
+        }
+    }
+
+    fn expr(&mut self, expr: &TirExpr) {
+        match &expr.kind {
+            TirExprKind::Var(var) => {
+                if *var == self.var {
+                    self.escaped = true;
+                }
+            }
+            TirExprKind::FieldAccess { object, .. } => self.object(object),
+            // Slicing copies the items
+            TirExprKind::Slice {
+                object,
+                start,
+                stop,
+                step,
+                ..
+            } => {
+                self.object(object);
+                for bound in [start, stop, step].into_iter().flatten() {
+                    self.expr(bound);
+                }
+            }
+            TirExprKind::Call { func, args } => self.call(&[*func], args),
+            // Any override of the method may be the one called
+            TirExprKind::VirtualCall { func, slot, args } => {
+                let implementations = self.program.implementations(*func, *slot);
+                self.call(&implementations, args);
+            }
+            TirExprKind::Block { stmts, result } => {
+                self.stmts(stmts);
+                self.expr(result);
+            }
+            _ => {
+                for child in expr_children(expr) {
+                    self.expr(child);
+                }
+            }
+        }
+    }
+
+    /// A call of one of `funcs`
+    fn call(&mut self, funcs: &[FuncId], args: &[TirExpr]) {
+        for (position, arg) in args.iter().enumerate() {
+            if !self.is_var(arg) {
+                self.expr(arg);
+            } else if !funcs
+                .iter()
+                .all(|func| self.call_keeps_arg(*func, position))
+            {
+                self.escaped = true;
+            }
+        }
+    }
+
+    /// Whether passing the variable as argument `position` of `func` keeps
+    /// it from escaping
+    fn call_keeps_arg(&self, func: FuncId, position: usize) -> bool {
+        match &self.program.function(func).runtime_name {
+            Some(name) => list_method_keeps_list(name, position),
+            None => self.escaping[func.index()].get(position) == Some(&false),
+        }
+    }
+
+    /// `it = var.__iter__()` starting a `for` loop over the list: the
+    /// iterator is only advanced and freed by the statements that follow it
+    /// (the loop), which do not reassign the variable
+    fn is_loop_iterator(&self, stmt: &TirStmt, rest: &[TirStmt]) -> bool {
+        let TirStmt::Let { local, init, .. } = stmt else {
+            return false;
+        };
+        let TirExprKind::Call { func, args } = &init.kind else {
+            return false;
+        };
+        let runtime_name = self.program.function(*func).runtime_name.as_deref();
+        if runtime_name != Some("__pyc___builtin___list___iter__")
+            || !args.first().is_some_and(|arg| self.is_var(arg))
+        {
+            return false;
+        }
+        let iter = VarRef::Local(*local);
+        let everywhere = Mentions::of(self.program, self.body, iter);
+        let in_loop = Mentions::of(self.program, rest, iter);
+        everywhere.writes == 1
+            && everywhere.reads == in_loop.reads
+            && in_loop.reads == in_loop.advances
+            && Mentions::of(self.program, rest, self.var).writes == 0
+    }
+}
+
+/// How often a variable appears in some statements
+#[derive(Debug, Default)]
+struct Mentions {
+    reads: usize,
+    writes: usize,
+    /// Reads as the iterator of a list iterator method
+    advances: usize,
+}
+
+impl Mentions {
+    fn of(program: &TirProgram, stmts: &[TirStmt], var: VarRef) -> Self {
+        let mut mentions = Mentions::default();
+        let is_var = |expr: &TirExpr| matches!(&expr.kind, TirExprKind::Var(v) if *v == var);
+        for_each_node(stmts, &mut |node| match node {
+            Node::Stmt(TirStmt::Let { local, .. }) if VarRef::Local(*local) == var => {
+                mentions.writes += 1;
+            }
+            Node::Stmt(TirStmt::Assign {
+                target: TirLValue::Var(target),
+                ..
+            })
+            | Node::Stmt(TirStmt::AugAssign { target, .. })
+                if *target == var =>
+            {
+                mentions.writes += 1;
+            }
+            Node::Stmt(TirStmt::Try { handlers, .. }) => {
+                mentions.writes += handlers
+                    .iter()
+                    .filter(|handler| handler.local.map(VarRef::Local) == Some(var))
+                    .count();
+            }
+            Node::Expr(expr) if is_var(expr) => mentions.reads += 1,
+            Node::Expr(TirExpr {
+                kind: TirExprKind::Call { func, args },
+                ..
+            }) if args.first().is_some_and(is_var) => {
+                let runtime_name = program.function(*func).runtime_name.as_deref();
+                if runtime_name
+                    .is_some_and(|name| name.starts_with("__pyc___builtin___list_iterator"))
+                {
+                    mentions.advances += 1;
+                }
+            }
+            _ => {}
+        });
+        mentions
+    }
+}
+
+/// A statement or expression found by `for_each_node`
+pub(super) enum Node<'a> {
+    Stmt(&'a TirStmt),
+    Expr(&'a TirExpr),
+}
+
+/// Visit every statement and expression in `stmts`, including those nested
+/// in bodies and block expressions
+pub(super) fn for_each_node<'a>(stmts: &'a [TirStmt], visit: &mut dyn FnMut(Node<'a>)) {
+    for stmt in stmts {
+        visit(Node::Stmt(stmt));
+        let (exprs, bodies): (Vec<&TirExpr>, Vec<&[TirStmt]>) = match stmt {
+            TirStmt::Let { init, .. } => (vec![init], vec![]),
+            TirStmt::Assign { target, value } => match target {
+                TirLValue::Field { object, .. } => (vec![&**object, value], vec![]),
+                TirLValue::Var(_) => (vec![value], vec![]),
+            },
+            TirStmt::AugAssign { value, .. } => (vec![value], vec![]),
+            TirStmt::Expr(expr)
+            | TirStmt::Return(Some(expr))
+            | TirStmt::Raise { exc: Some(expr) }
+            | TirStmt::Yield(expr) => (vec![expr], vec![]),
+            TirStmt::Return(None) | TirStmt::Raise { exc: None } => (vec![], vec![]),
+            TirStmt::If {
+                cond,
+                then_body,
+                else_body,
+            } => (vec![cond], vec![then_body.as_slice(), else_body.as_slice()]),
+            TirStmt::While { cond, body } => (vec![cond], vec![body.as_slice()]),
+            TirStmt::Try {
+                body,
+                handlers,
+                orelse,
+                finalbody,
+            } => {
+                let mut bodies = vec![body.as_slice()];
+                bodies.extend(handlers.iter().map(|handler| handler.body.as_slice()));
+                bodies.push(orelse);
+                bodies.push(finalbody);
+                (vec![], bodies)
+            }
+        };
+        for expr in exprs {
+            for_each_expr(expr, visit);
+        }
+        for body in bodies {
+            for_each_node(body, visit);
+        }
+    }
+}
+
+fn for_each_expr<'a>(expr: &'a TirExpr, visit: &mut dyn FnMut(Node<'a>)) {
+    visit(Node::Expr(expr));
+    if let TirExprKind::Block { stmts, .. } = &expr.kind {
+        for_each_node(stmts, visit);
+    }
+    for child in expr_children(expr) {
+        for_each_expr(child, visit);
+    }
+}
+
+/// The subexpressions of an expression (for a block, just its result)
+pub(super) fn expr_children(expr: &TirExpr) -> Vec<&TirExpr> {
+    match &expr.kind {
+        TirExprKind::Constant(_) | TirExprKind::Var(_) | TirExprKind::Bytes { .. } => vec![],
+        TirExprKind::BinOp { left, right, .. } | TirExprKind::Compare { left, right, .. } => {
+            vec![&**left, &**right]
+        }
+        TirExprKind::UnaryOp { operand, .. } => vec![&**operand],
+        TirExprKind::BoolOp { values: exprs, .. }
+        | TirExprKind::Call { args: exprs, .. }
+        | TirExprKind::VirtualCall { args: exprs, .. }
+        | TirExprKind::Construct { args: exprs, .. }
+        | TirExprKind::List {
+            elements: exprs, ..
+        }
+        | TirExprKind::Set {
+            elements: exprs, ..
+        }
+        | TirExprKind::Tuple { elements: exprs }
+        | TirExprKind::MathIntrinsic { args: exprs, .. }
+        | TirExprKind::LlvmIntrinsic { args: exprs, .. } => exprs.iter().collect(),
+        TirExprKind::Range { start, stop, step } => {
+            [start.as_deref(), Some(&**stop), step.as_deref()]
+                .into_iter()
+                .flatten()
+                .collect()
+        }
+        TirExprKind::FieldAccess { object, .. } => vec![&**object],
+        TirExprKind::Dict { entries, .. } => entries
+            .iter()
+            .flat_map(|(key, value)| [key, value])
+            .collect(),
+        TirExprKind::TupleGet { tuple, .. } => vec![&**tuple],
+        TirExprKind::Slice {
+            object,
+            start,
+            stop,
+            step,
+            ..
+        } => [
+            Some(&**object),
+            start.as_deref(),
+            stop.as_deref(),
+            step.as_deref(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect(),
+        TirExprKind::Block { result, .. } => vec![&**result],
+        TirExprKind::WrapOptional { value } | TirExprKind::UnwrapOptional { value } => {
+            vec![&**value]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::driver::build_modules;
+    use crate::tir::lower_to_tir;
+    use std::fs;
+    use tempfile::TempDir;
+
+    const POINT: &str = r#"
+class Point:
+    x: int
+    y: int
+
+    def __init__(self, x: int, y: int) -> None:
+        self.x = x
+        self.y = y
+
+    def norm(self) -> int:
+        return self.x * self.x + self.y * self.y
+
+    def same(self) -> "Point":
+        return self
+
+def area(p: Point) -> int:
+    return p.x * p.y
+
+points: list[Point] = []
+
+def keep(p: Point) -> None:
+    points.append(p)
+"#;
+
+    /// The names of the stack-allocated locals of function `name` in `source`
+    fn stack_local_names(source: &str, name: &str) -> Vec<String> {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("main.py");
+        fs::write(&path, format!("{}{}", POINT, source)).unwrap();
+        let (modules, entry) = build_modules(&path, temp_dir.path()).unwrap();
+        let program = lower_to_tir(modules, entry).unwrap();
+        let stack_locals = find_stack_locals(&program);
+        let func = program
+            .functions
+            .iter()
+            .find(|func| func.name == name)
+            .unwrap();
+        stack_locals
+            .function(func.id)
+            .iter()
+            .map(|local| func.locals[local.index()].0.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_instance_used_in_loop() {
+        let source = r#"
+def total(n: int) -> int:
+    s: int = 0
+    for i in range(n):
+        p: Point = Point(i, i + 1)
+        p.x = p.x + area(p)
+        s += p.norm()
+    return s
+"#;
+        assert_eq!(stack_local_names(source, "total"), vec!["p"]);
+    }
+
+    #[test]
+    fn test_escaping_instances() {
+        let source = r#"
+def made() -> Point:
+    p: Point = Point(1, 2)
+    return p
+
+def kept() -> int:
+    p: Point = Point(1, 2)
+    keep(p)
+    return p.x
+
+def aliased() -> int:
+    p: Point = Point(1, 2)
+    q: Point = p.same()
+    return q.x
+
+def listed() -> int:
+    p: Point = Point(1, 2)
+    items: list[Point] = [p]
+    return len(items)
+"#;
+        assert!(stack_local_names(source, "made").is_empty());
+        assert!(stack_local_names(source, "kept").is_empty());
+        assert!(stack_local_names(source, "aliased").is_empty());
+        // The list only holds the instance, it does not escape itself
+        assert_eq!(stack_local_names(source, "listed"), vec!["items"]);
+    }
+
+    #[test]
+    fn test_overridden_method_arguments() {
+        let source = r#"
+class Reader:
+    def read(self, p: Point) -> int:
+        return p.x
+
+class YReader(Reader):
+    def read(self, p: Point) -> int:
+        return p.y
+
+class Keeper(Reader):
+    def read(self, p: Point) -> int:
+        keep(p)
+        return 0
+
+def read_y(r: YReader) -> int:
+    p: Point = Point(1, 2)
+    return r.read(p)
+
+def read_any(r: Reader) -> int:
+    p: Point = Point(1, 2)
+    return r.read(p)
+"#;
+        assert_eq!(stack_local_names(source, "read_y"), vec!["p"]);
+        // Keeper.read may run, and it keeps the instance
+        assert!(stack_local_names(source, "read_any").is_empty());
+    }
+
+    #[test]
+    fn test_list_iterated_in_loop() {
+        let source = r#"
+def sums(n: int) -> int:
+    total: int = 0
+    for i in range(n):
+        row: list[int] = [i, i + 1, i + 2]
+        row.append(len(row))
+        for v in row:
+            total += v
+    return total
+
+def rebinds() -> int:
+    row: list[int] = [1, 2]
+    total: int = 0
+    for v in row:
+        total += v
+        row = [v]
+    return total
+"#;
+        assert_eq!(stack_local_names(source, "sums"), vec!["row"]);
+        // The loop keeps iterating the first list after the second one is built
+        assert!(stack_local_names(source, "rebinds").is_empty());
+    }
+}
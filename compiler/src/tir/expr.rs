@@ -3,8 +3,10 @@
 //! Expressions in TIR carry their type directly and use resolved IDs for all references.
 
 use crate::ast::{BinOperator, BoolOp, CompareOp, UnaryOp};
+use crate::span::Span;
 
 use super::ids::{ClassId, FieldId, FuncId, GlobalId, LocalId, ModuleId};
+use super::stmt::TirStmt;
 use super::types::TirType;
 
 /// TIR-specific constant type.
@@ -40,6 +42,78 @@ pub enum VarRef {
     SelfRef,
 }
 
+/// A `math` module function that lowers to an LLVM floating-point intrinsic
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MathFunc {
+    Sqrt,
+    Sin,
+    Cos,
+    Log,
+    Exp,
+    Floor,
+    Ceil,
+    Pow,
+}
+
+impl MathFunc {
+    /// The function named `name` in the `math` module, if it is an intrinsic
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "sqrt" => Some(MathFunc::Sqrt),
+            "sin" => Some(MathFunc::Sin),
+            "cos" => Some(MathFunc::Cos),
+            "log" => Some(MathFunc::Log),
+            "exp" => Some(MathFunc::Exp),
+            "floor" => Some(MathFunc::Floor),
+            "ceil" => Some(MathFunc::Ceil),
+            "pow" => Some(MathFunc::Pow),
+            _ => None,
+        }
+    }
+
+    /// The function's name in the `math` module
+    pub fn name(self) -> &'static str {
+        match self {
+            MathFunc::Sqrt => "sqrt",
+            MathFunc::Sin => "sin",
+            MathFunc::Cos => "cos",
+            MathFunc::Log => "log",
+            MathFunc::Exp => "exp",
+            MathFunc::Floor => "floor",
+            MathFunc::Ceil => "ceil",
+            MathFunc::Pow => "pow",
+        }
+    }
+
+    /// The LLVM intrinsic implementing the function on f64
+    pub fn llvm_name(self) -> &'static str {
+        match self {
+            MathFunc::Sqrt => "llvm.sqrt",
+            MathFunc::Sin => "llvm.sin",
+            MathFunc::Cos => "llvm.cos",
+            MathFunc::Log => "llvm.log",
+            MathFunc::Exp => "llvm.exp",
+            MathFunc::Floor => "llvm.floor",
+            MathFunc::Ceil => "llvm.ceil",
+            MathFunc::Pow => "llvm.pow",
+        }
+    }
+
+    /// Number of float arguments
+    pub fn arity(self) -> usize {
+        match self {
+            MathFunc::Pow => 2,
+            _ => 1,
+        }
+    }
+
+    /// floor and ceil return int, like Python's. The intrinsic itself gives
+    /// the rounded float, which lowering converts
+    pub fn returns_int(self) -> bool {
+        matches!(self, MathFunc::Floor | MathFunc::Ceil)
+    }
+}
+
 /// Typed expression with embedded type information.
 #[derive(Debug, Clone)]
 pub struct TirExpr {
@@ -47,12 +121,25 @@ pub struct TirExpr {
     pub kind: TirExprKind,
     /// The resolved type of this expression
     pub ty: TirType,
+    /// Where the statement the expression belongs to starts in the source;
+    /// unknown for code the compiler generates
+    pub span: Span,
 }
 
 impl TirExpr {
-    /// Create a new TirExpr with the given kind and type
+    /// Create a new TirExpr with the given kind and type, at an unknown
+    /// position
     pub fn new(kind: TirExprKind, ty: TirType) -> Self {
-        TirExpr { kind, ty }
+        TirExpr {
+            kind,
+            ty,
+            span: Span::default(),
+        }
+    }
+
+    /// The expression, at `span`
+    pub fn at(self, span: Span) -> Self {
+        TirExpr { span, ..self }
     }
 }
 
@@ -91,6 +178,15 @@ pub enum TirExprKind {
     /// For method calls, args[0] is the receiver (self)
     Call { func: FuncId, args: Vec<TirExpr> },
 
+    /// Call of an overridden method, dispatched on the class of the receiver
+    /// (args[0]): `func` is the implementation of the receiver's declared
+    /// class, and `slot` the method's index in its vtable
+    VirtualCall {
+        func: FuncId,
+        slot: usize,
+        args: Vec<TirExpr>,
+    },
+
     /// Class constructor: ClassName(args)
     /// Note: range() uses TirExprKind::Range instead
     Construct { class: ClassId, args: Vec<TirExpr> },
@@ -119,6 +215,60 @@ pub enum TirExprKind {
         elem_ty: TirType,
     },
 
+    /// Dict literal: {k1: v1, k2: v2}
+    Dict {
+        entries: Vec<(TirExpr, TirExpr)>,
+        key_ty: TirType,
+        value_ty: TirType,
+    },
+
+    /// Set literal: {a, b, c}
+    Set {
+        elements: Vec<TirExpr>,
+        elem_ty: TirType,
+    },
+
+    /// Tuple literal: (a, b, c); element types are carried by the expression type
+    Tuple { elements: Vec<TirExpr> },
+
+    /// Tuple element with a constant, in-range index: t[0]
+    TupleGet { tuple: Box<TirExpr>, index: usize },
+
+    /// `math` function call: an LLVM intrinsic on the arguments converted to
+    /// float; the result is converted to int when the expression type is int
+    MathIntrinsic { func: MathFunc, args: Vec<TirExpr> },
+
+    /// `__intrinsic__("llvm.ctpop.i64", x)`: a call of the named LLVM
+    /// intrinsic (see tir/llvm_intrinsic.rs), which takes and returns bools
+    /// as i1
+    LlvmIntrinsic { name: String, args: Vec<TirExpr> },
+
+    /// Slice of a str, list, bytes or bytearray: obj[start:stop:step]
+    /// Missing bounds are passed to `__slice__` as SLICE_NONE (i64::MIN) and
+    /// the runtime picks Python's defaults for them; a missing step is 1
+    Slice {
+        /// The container's `__slice__` runtime method
+        func: FuncId,
+        object: Box<TirExpr>,
+        start: Option<Box<TirExpr>>,
+        stop: Option<Box<TirExpr>>,
+        step: Option<Box<TirExpr>>,
+    },
+
     /// Bytes literal: b"hello"
     Bytes { data: Vec<u8> },
+
+    /// Statements run for their effects, then the result expression
+    /// (comprehensions desugar to a container-building loop in a block)
+    Block {
+        stmts: Vec<TirStmt>,
+        result: Box<TirExpr>,
+    },
+
+    /// A value passed where its Optional type is expected: boxed when it is
+    /// an int, float or bool, and otherwise already the Optional's pointer
+    WrapOptional { value: Box<TirExpr> },
+
+    /// The value of an Optional known not to be None (unboxed if needed)
+    UnwrapOptional { value: Box<TirExpr> },
 }
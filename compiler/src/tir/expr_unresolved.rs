@@ -8,9 +8,11 @@
 //! via the resolve module.
 
 use crate::ast::{BinOperator, BoolOp, CompareOp, Constant, UnaryOp};
+use crate::span::Span;
 
-use super::expr::VarRef; // VarRef is shared between resolved and unresolved TIR
+use super::expr::{MathFunc, VarRef}; // Shared between resolved and unresolved TIR
 use super::ids::{ClassId, FieldId, FuncId};
+use super::stmt_unresolved::TirStmtUnresolved;
 use super::types_unresolved::TirTypeUnresolved;
 
 /// Typed expression with embedded type information (unresolved version).
@@ -21,12 +23,64 @@ pub struct TirExprUnresolved {
     pub kind: TirExprKindUnresolved,
     /// The type of this expression (may contain TypeVar)
     pub ty: TirTypeUnresolved,
+    /// Where the statement the expression belongs to starts in the source
+    pub span: Span,
 }
 
 impl TirExprUnresolved {
-    /// Create a new TirExprUnresolved with the given kind and type
+    /// Create a new TirExprUnresolved with the given kind and type, at an
+    /// unknown position (lowering sets the statement's)
     pub fn new(kind: TirExprKindUnresolved, ty: TirTypeUnresolved) -> Self {
-        TirExprUnresolved { kind, ty }
+        TirExprUnresolved {
+            kind,
+            ty,
+            span: Span::default(),
+        }
+    }
+
+    /// Convert a value to the representation of a compatible `expected`
+    /// type: a value (or None) passed where an Optional is expected becomes
+    /// an Optional. Other values are left as they are.
+    pub fn coerce_to(&mut self, expected: &TirTypeUnresolved) {
+        let TirTypeUnresolved::Optional(_) = expected else {
+            return;
+        };
+        if matches!(self.ty, TirTypeUnresolved::Optional(_))
+            || !self.ty.is_compatible_with(expected)
+        {
+            return;
+        }
+        let placeholder = TirExprUnresolved::new(
+            TirExprKindUnresolved::Constant(Constant::None),
+            TirTypeUnresolved::Void,
+        );
+        let value = std::mem::replace(self, placeholder);
+        *self = match value.ty {
+            TirTypeUnresolved::Void | TirTypeUnresolved::NoneType => {
+                // The null pointer of the Optional type (after evaluating a
+                // call or a None-typed variable)
+                let none = TirExprUnresolved::new(
+                    TirExprKindUnresolved::Constant(Constant::None),
+                    expected.clone(),
+                );
+                match value.kind {
+                    TirExprKindUnresolved::Constant(Constant::None) => none,
+                    _ => TirExprUnresolved::new(
+                        TirExprKindUnresolved::Block {
+                            stmts: vec![TirStmtUnresolved::Expr(value)],
+                            result: Box::new(none),
+                        },
+                        expected.clone(),
+                    ),
+                }
+            }
+            _ => TirExprUnresolved::new(
+                TirExprKindUnresolved::WrapOptional {
+                    value: Box::new(value),
+                },
+                expected.clone(),
+            ),
+        };
     }
 }
 
@@ -73,6 +127,13 @@ pub enum TirExprKindUnresolved {
         args: Vec<TirExprUnresolved>,
     },
 
+    /// Call of an overridden method, dispatched on the class of the receiver
+    VirtualCall {
+        func: FuncId,
+        slot: usize,
+        args: Vec<TirExprUnresolved>,
+    },
+
     /// Class constructor: ClassName(args)
     Construct {
         class: ClassId,
@@ -93,6 +154,64 @@ pub enum TirExprKindUnresolved {
         elem_ty: TirTypeUnresolved,
     },
 
+    /// Dict literal: {k1: v1, k2: v2}
+    /// The key_ty/value_ty may contain TypeVar for empty dicts
+    Dict {
+        entries: Vec<(TirExprUnresolved, TirExprUnresolved)>,
+        key_ty: TirTypeUnresolved,
+        value_ty: TirTypeUnresolved,
+    },
+
+    /// Set literal: {a, b, c}
+    Set {
+        elements: Vec<TirExprUnresolved>,
+        elem_ty: TirTypeUnresolved,
+    },
+
+    /// Tuple literal: (a, b, c); element types are carried by the expression type
+    Tuple { elements: Vec<TirExprUnresolved> },
+
+    /// Tuple element with a constant, in-range index: t[0]
+    TupleGet {
+        tuple: Box<TirExprUnresolved>,
+        index: usize,
+    },
+
+    /// `math` function call lowered to an LLVM intrinsic
+    MathIntrinsic {
+        func: MathFunc,
+        args: Vec<TirExprUnresolved>,
+    },
+
+    /// `__intrinsic__(name, ...)` call of a named LLVM intrinsic
+    LlvmIntrinsic {
+        name: String,
+        args: Vec<TirExprUnresolved>,
+    },
+
+    /// Slice of a str, list, bytes or bytearray: obj[start:stop:step]
+    /// `func` is the container's `__slice__`; missing bounds are None
+    Slice {
+        func: FuncId,
+        object: Box<TirExprUnresolved>,
+        start: Option<Box<TirExprUnresolved>>,
+        stop: Option<Box<TirExprUnresolved>>,
+        step: Option<Box<TirExprUnresolved>>,
+    },
+
     /// Bytes literal: b"hello"
     Bytes { data: Vec<u8> },
+
+    /// Statements run for their effects, then the result expression
+    /// (comprehensions desugar to a container-building loop in a block)
+    Block {
+        stmts: Vec<TirStmtUnresolved>,
+        result: Box<TirExprUnresolved>,
+    },
+
+    /// A value passed where its Optional type is expected
+    WrapOptional { value: Box<TirExprUnresolved> },
+
+    /// The value of an Optional known not to be None
+    UnwrapOptional { value: Box<TirExprUnresolved> },
 }
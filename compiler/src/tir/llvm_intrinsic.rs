@@ -0,0 +1,55 @@
+//! LLVM intrinsics called by name
+//!
+//! `__intrinsic__("llvm.ctpop.i64", x)` calls the LLVM intrinsic of that
+//! name, for kernels that need an instruction Python has no operator for.
+//! The type components ending the name (`.i64`, `.f64`) are the types an
+//! overloaded intrinsic is instantiated at, as in LLVM IR. The intrinsic's
+//! parameters and result must be `i64` (int), `double` (float) or `i1`
+//! (bool), or the result `void` (None).
+
+use inkwell::context::ContextRef;
+use inkwell::intrinsics::Intrinsic;
+use inkwell::module::Module;
+use inkwell::types::BasicTypeEnum;
+use inkwell::values::FunctionValue;
+
+/// The LLVM type a component of an intrinsic name names, if any
+fn overload_type<'ctx>(context: &ContextRef<'ctx>, component: &str) -> Option<BasicTypeEnum<'ctx>> {
+    match component {
+        "f32" => Some(context.f32_type().into()),
+        "f64" => Some(context.f64_type().into()),
+        _ => {
+            let width: u32 = component.strip_prefix('i')?.parse().ok()?;
+            matches!(width, 1 | 8 | 16 | 32 | 64 | 128)
+                .then(|| context.custom_width_int_type(width).into())
+        }
+    }
+}
+
+/// Declare the intrinsic `name` in `module`: an intrinsic that is not
+/// overloaded by its full name, and an overloaded one at the types its name
+/// ends with. None for names that are no intrinsic.
+pub fn declare_llvm_intrinsic<'ctx>(
+    module: &Module<'ctx>,
+    name: &str,
+) -> Option<FunctionValue<'ctx>> {
+    if let Some(intrinsic) = Intrinsic::find(name).filter(|i| !i.is_overloaded()) {
+        return intrinsic.get_declaration(module, &[]);
+    }
+
+    let context = module.get_context();
+    let mut components: Vec<&str> = name.split('.').collect();
+    let mut overloads = Vec::new();
+    while let Some(ty) = components
+        .last()
+        .and_then(|component| overload_type(&context, component))
+    {
+        overloads.insert(0, ty);
+        components.pop();
+    }
+    let intrinsic = Intrinsic::find(&components.join("."))?;
+    if !intrinsic.is_overloaded() || overloads.is_empty() {
+        return None;
+    }
+    intrinsic.get_declaration(module, &overloads)
+}
@@ -1,37 +1,48 @@
 //! AST to TIR lowering
 //!
 //! Lowers the AST to TIR after type checking. This is a multi-pass process:
-//! 1. Collect all definitions and assign numeric IDs
-//! 2. Build per-module import resolution
-//! 3. Lower function/method bodies with resolved references
+//! 1. Order the modules by their imports (see init_order.rs)
+//! 2. Collect all definitions and assign numeric IDs
+//! 3. Build per-module import resolution
+//! 4. Lower function/method bodies with resolved references
+//! 5. Check that no module init uses a global not initialized yet
 
 #[macro_use]
 mod utils;
 
 mod body_lowerer;
 mod builtins;
+mod closures;
 mod constraints;
 mod expr_lowering;
+mod generators;
+mod init_order;
+mod intrinsics;
+mod list_methods;
+mod optional;
 mod passes;
 mod scope;
+mod sorting;
 mod stmt_lowering;
 mod symbols;
 
 use body_lowerer::BodyLowerer;
-use passes::{BodyLoweringPass, DefinitionCollector, ScopeBuilder};
-use std::collections::HashMap;
+use passes::{BodyLoweringPass, DefinitionCollector, LintCollector, ScopeBuilder};
+use std::collections::{HashMap, HashSet};
 use symbols::{ClassKey, GlobalSymbols};
 
 // Re-export GlobalSymbols for use by the resolve module
 pub(crate) use symbols::GlobalSymbols as GlobalSymbolsInternal;
 
 use crate::ast::{Expr, Module, ModuleName, Stmt};
-use crate::error::Result;
+use crate::error::{CompilerError, ErrorCollector, Result};
 
 use super::decls::{TirClass, TirFunction};
 use super::expr::VarRef;
+use super::expr_unresolved::TirExprUnresolved;
 use super::ids::{FuncId, GlobalId};
 use super::program::{TirGlobal, TirModule, TirProgram};
+use super::stmt::TirStmt;
 use super::stmt_unresolved::TirStmtUnresolved;
 use super::types::TirType;
 use super::types_unresolved::TirTypeUnresolved;
@@ -41,12 +52,14 @@ pub fn lower_to_tir(
     entry_name: ModuleName,
 ) -> Result<TirProgram> {
     let mut symbols = GlobalSymbols::new();
-    let mut module_order: Vec<ModuleName> = modules.keys().cloned().collect();
-    module_order.sort_by(|a, b| a.0.cmp(&b.0));
+    // Module ids follow the order the inits run in
+    let order = init_order::init_order(&modules, &entry_name);
+    let module_order = order.modules;
 
     // Pre-create builtin classes that can be used as base classes
-    // This ensures Exception is available when user classes inherit from it
-    symbols.get_or_create_exception_class();
+    // This ensures Exception and the builtin exceptions below it are
+    // available when user classes inherit from them
+    symbols.register_builtin_exceptions();
 
     // Collect all definitions (types, functions, methods, fields, globals)
     let mut collector = DefinitionCollector::new(&mut symbols);
@@ -54,13 +67,19 @@ pub fn lower_to_tir(
 
     // Build per-module scopes
     let scope_builder = ScopeBuilder::new(&symbols);
-    let module_scopes = scope_builder.build_all(&modules, &module_order);
+    let module_scopes = scope_builder.build_all(&modules, &module_order)?;
 
-    // Lower all function/method bodies
+    // Lower all function/method bodies, then the module bodies, reporting
+    // the errors of all of them
+    let mut errors = ErrorCollector::new();
     let mut body_pass = BodyLoweringPass::new(&mut symbols, &module_scopes);
-    let (mut tir_functions, mut tir_classes) = body_pass.run(&modules, &module_order)?;
+    let (mut tir_functions, mut tir_classes) = errors
+        .try_collect(body_pass.run(&modules, &module_order))
+        .unwrap_or_default();
 
     let mut tir_modules: Vec<TirModule> = Vec::new();
+    let mut exports: Vec<(String, FuncId)> = Vec::new();
+    let mut externs: Vec<(String, FuncId)> = Vec::new();
 
     // Build modules with init bodies
     for ast_mod_id in &module_order {
@@ -78,9 +97,20 @@ pub fn lower_to_tir(
 
         for stmt in &module.body {
             match stmt {
-                Stmt::FunctionDef { name, .. } => {
+                Stmt::FunctionDef {
+                    name,
+                    exported,
+                    extern_name,
+                    ..
+                } => {
                     if let Some(&func_id) = lowerer.symbols.functions.get(&(mod_id, name.clone())) {
                         functions.push(func_id);
+                        if *exported {
+                            exports.push((name.clone(), func_id));
+                        }
+                        if extern_name.is_some() {
+                            externs.push((name.clone(), func_id));
+                        }
                     }
                 }
                 Stmt::ClassDef { name, .. } => {
@@ -92,30 +122,50 @@ pub fn lower_to_tir(
                 Stmt::Assign {
                     target: Expr::Name(name),
                     value,
-                    type_annotation,
+                    span,
+                    ..
                 } => {
                     if let Some(&global_id) = lowerer.symbols.globals.get(&(mod_id, name.clone())) {
-                        let ty = type_annotation
-                            .as_ref()
-                            .map(|ann| lowerer.convert_annotation(ann))
-                            .unwrap_or(TirTypeUnresolved::Int);
-                        globals_unresolved.push((global_id, name.clone(), ty));
+                        // The type definition collection gave the global
+                        let declared = lowerer
+                            .symbols
+                            .global_types
+                            .get(&(mod_id, global_id))
+                            .map(TirTypeUnresolved::from_tir_type);
+                        let ty = declared.clone().unwrap_or(TirTypeUnresolved::Int);
+                        if !globals_unresolved.iter().any(|(id, _, _)| *id == global_id) {
+                            globals_unresolved.push((global_id, name.clone(), ty.clone()));
+                        }
 
                         // Add init statement (unresolved)
-                        let value_expr = lowerer.lower_expr(value)?;
-                        init_body_unresolved.push(TirStmtUnresolved::Assign {
+                        lowerer.span = *span;
+                        let value_expr = lowerer
+                            .lower_expr_expecting(value, declared.as_ref())
+                            .and_then(|value_expr| {
+                                check_global_value(name, &ty, declared.is_some(), value_expr)
+                            });
+                        let value_expr = match value_expr {
+                            Ok(value_expr) => value_expr,
+                            Err(e) => {
+                                lowerer.errors.push(e.at(*span).in_file(&module.path));
+                                continue;
+                            }
+                        };
+                        let mut init = TirStmtUnresolved::Assign {
                             target: super::stmt_unresolved::TirLValueUnresolved::Var(
                                 VarRef::Global(mod_id, global_id),
                             ),
                             value: value_expr,
-                        });
+                        };
+                        init.locate(*span);
+                        init_body_unresolved.push(init);
                     }
                 }
                 _ => {
                     // Other statements at module level go into init
                     // This includes Stmt::Expr which needs to go through lower_stmt
                     // for proper print() expansion
-                    init_body_unresolved.extend(lowerer.lower_stmt(stmt)?);
+                    init_body_unresolved.extend(lowerer.lower_block(std::slice::from_ref(stmt)));
                 }
             }
         }
@@ -123,42 +173,29 @@ pub fn lower_to_tir(
         // Extract data from lowerer before dropping it
         let constraints = lowerer.constraints.constraints.clone();
         let init_locals_unresolved = lowerer.locals.clone();
+        let init_errors = std::mem::take(&mut lowerer.errors);
         drop(lowerer); // Explicitly drop to release mutable borrow on symbols
+        if init_errors.has_errors() {
+            errors.push(init_errors.into_result().unwrap_err());
+            continue;
+        }
 
-        // Solve type constraints for module init
-        let mut solver = constraints::ConstraintSolver::new(&symbols);
-        solver.solve(&constraints)?;
-        let substitutions = solver.get_substitutions().clone();
-
-        // Resolve globals
-        let globals: Vec<TirGlobal> = globals_unresolved
-            .into_iter()
-            .map(|(id, name, ty)| {
-                let resolved_ty = super::resolve::resolve_type(&ty, &substitutions, &mut symbols)?;
-                Ok(TirGlobal {
-                    id,
-                    name,
-                    ty: resolved_ty,
-                })
-            })
-            .collect::<Result<Vec<_>>>()?;
-
-        // Resolve the init body
-        let init_body =
-            super::resolve::resolve_body(init_body_unresolved, &substitutions, &mut symbols)?;
-
-        // Resolve init locals
-        let init_locals: Vec<(String, TirType)> = init_locals_unresolved
-            .into_iter()
-            .map(|(name, ty)| {
-                let resolved_ty = super::resolve::resolve_type(&ty, &substitutions, &mut symbols)?;
-                Ok((name, resolved_ty))
-            })
-            .collect::<Result<Vec<_>>>()?;
+        let resolved = resolve_module_init(
+            &constraints,
+            globals_unresolved,
+            init_body_unresolved,
+            init_locals_unresolved,
+            &mut symbols,
+        )
+        .map_err(|e| e.in_file(&module.path));
+        let Some((globals, init_body, init_locals)) = errors.try_collect(resolved) else {
+            continue;
+        };
 
         tir_modules.push(TirModule {
             id: mod_id,
             name: ast_mod_id.0.clone(),
+            path: module.path.clone(),
             globals,
             functions,
             classes,
@@ -167,9 +204,22 @@ pub fn lower_to_tir(
         });
     }
 
+    errors.into_result()?;
+
     // Sort modules by ID
     tir_modules.sort_by_key(|m| m.id.0);
 
+    let exports = check_exports(&symbols, exports)?;
+    let externs = check_externs(&symbols, externs)?;
+
+    // Lower the generic class instances and protocol function
+    // specializations only module code uses
+    let mut errors = ErrorCollector::new();
+    let mut instance_pass = BodyLoweringPass::new(&mut symbols, &module_scopes);
+    let (instance_functions, instance_classes) = instance_pass.lower_instantiations(&mut errors);
+    errors.into_result()?;
+    tir_classes.extend(instance_classes);
+
     // Build TirClass for all built-in classes
     for (key, &class_id) in &symbols.classes {
         if key.qualified_name.starts_with("__builtin__.") {
@@ -178,10 +228,12 @@ pub fn lower_to_tir(
             tir_classes.push(TirClass {
                 id: class_id,
                 qualified_name: class_data.qualified_name.clone(),
-                parent: None, // Builtin classes don't have parents
+                // The builtin exceptions derive from Exception
+                parent: class_data.parent,
                 inherited_fields: vec![],
                 fields: vec![],
                 methods,
+                vtable: vec![],
                 type_params: key.type_params.clone(),
             });
         }
@@ -202,7 +254,12 @@ pub fn lower_to_tir(
         body: Vec::new(),
         class: None,
         runtime_name: None,
+        location: None,
     });
+    for tir_func in instance_functions {
+        let idx = tir_func.id.index();
+        tir_functions[idx] = tir_func;
+    }
 
     // Fill in TirFunction entries for all runtime functions at their correct indices
     // These are stub functions that indicate external runtime functions
@@ -233,15 +290,161 @@ pub fn lower_to_tir(
             body: vec![],
             class: None,
             runtime_name: Some(actual_runtime_name),
+            location: None,
         };
     }
 
     let entry_mod_id = symbols.modules[&entry_name.0];
+    let warnings = LintCollector::default().run(&modules, &module_order);
 
-    Ok(TirProgram {
+    let program = TirProgram {
         functions: tir_functions,
         classes: tir_classes,
         modules: tir_modules,
         entry: entry_mod_id,
-    })
+        exports,
+        externs,
+        warnings,
+    };
+    init_order::check_init_order(&program, &order.cycles)?;
+    Ok(program)
+}
+
+/// Check the value assigned to the global `name` of type `ty` at the top
+/// level of its module; a global whose type is not `declared` is an int
+fn check_global_value(
+    name: &str,
+    ty: &TirTypeUnresolved,
+    declared: bool,
+    mut value: TirExprUnresolved,
+) -> Result<TirExprUnresolved> {
+    if !declared && value.ty != TirTypeUnresolved::Int {
+        return Err(CompilerError::TypeErrorSimple(format!(
+            "Cannot tell the type of global '{}' from its value of type {:?}",
+            name, value.ty
+        ))
+        .with_help(format!(
+            "annotate it where it is first assigned: {}: <type> = ...",
+            name
+        )));
+    }
+    if !value.ty.is_compatible_with(ty) {
+        return Err(CompilerError::TypeErrorSimple(format!(
+            "Cannot assign {:?} to global '{}' of type {:?}",
+            value.ty, name, ty
+        )));
+    }
+    value.coerce_to(ty);
+    Ok(value)
+}
+
+/// Solve the type constraints of a module's initializer, and resolve the
+/// types of its globals, body and locals
+fn resolve_module_init(
+    constraints: &[constraints::Constraint],
+    globals: Vec<(GlobalId, String, TirTypeUnresolved)>,
+    init_body: Vec<TirStmtUnresolved>,
+    init_locals: Vec<(String, TirTypeUnresolved)>,
+    symbols: &mut GlobalSymbols,
+) -> Result<(Vec<TirGlobal>, Vec<TirStmt>, Vec<(String, TirType)>)> {
+    let mut solver = constraints::ConstraintSolver::new(symbols);
+    solver.solve(constraints)?;
+    let substitutions = solver.get_substitutions().clone();
+
+    // Resolve globals
+    let globals = globals
+        .into_iter()
+        .map(|(id, name, ty)| {
+            let resolved_ty = super::resolve::resolve_type(&ty, &substitutions, symbols)?;
+            Ok(TirGlobal {
+                id,
+                name,
+                ty: resolved_ty,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    // Resolve the init body
+    let init_body = super::resolve::resolve_body(init_body, &substitutions, symbols)?;
+
+    // Resolve init locals
+    let init_locals = init_locals
+        .into_iter()
+        .map(|(name, ty)| {
+            let resolved_ty = super::resolve::resolve_type(&ty, &substitutions, symbols)?;
+            Ok((name, resolved_ty))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok((globals, init_body, init_locals))
+}
+
+/// Check that the `@export` functions can be called from C: their
+/// parameters and return values are ints, floats and bools, and no two
+/// share a name
+fn check_exports(symbols: &GlobalSymbols, exports: Vec<(String, FuncId)>) -> Result<Vec<FuncId>> {
+    let mut names = HashSet::new();
+    let mut errors = ErrorCollector::new();
+    for (name, func_id) in &exports {
+        if !names.insert(name.as_str()) {
+            errors.push(CompilerError::UnsupportedFeature(format!(
+                "More than one function is exported as '{}'",
+                name
+            )));
+            continue;
+        }
+        if symbols.protocol_functions.contains_key(func_id) {
+            errors.push(CompilerError::UnsupportedFeature(format!(
+                "Function '{}' cannot be exported: it takes a protocol",
+                name
+            )));
+            continue;
+        }
+        let (params, return_type) = symbols.get_func_signature(*func_id);
+        let returned = Some(return_type).filter(|ty| **ty != TirType::Void);
+        if let Some(ty) = params
+            .iter()
+            .chain(returned)
+            .find(|ty| !matches!(ty, TirType::Int | TirType::Float | TirType::Bool))
+        {
+            errors.push(CompilerError::TypeErrorSimple(format!(
+                "Exported function '{}' can only take and return int, float and bool, not {}",
+                name,
+                symbols.type_name(ty)
+            )));
+        }
+    }
+    errors.into_result_with(exports.into_iter().map(|(_, func_id)| func_id).collect())
+}
+
+/// Check that the `@extern` functions can be called as C functions: they
+/// take ints, floats and bools, and strs and bytes (passed as pointers to
+/// their data), and return ints, floats, bools or None
+fn check_externs(symbols: &GlobalSymbols, externs: Vec<(String, FuncId)>) -> Result<Vec<FuncId>> {
+    let mut errors = ErrorCollector::new();
+    for (name, func_id) in &externs {
+        let (params, return_type) = symbols.get_func_signature(*func_id);
+        let is_pointer = |ty: &TirType| match ty {
+            TirType::Class(class_id) => matches!(
+                symbols.class_data[class_id.index()].qualified_name.as_str(),
+                "__builtin__.str" | "__builtin__.bytes"
+            ),
+            _ => false,
+        };
+        let is_scalar = |ty: &TirType| matches!(ty, TirType::Int | TirType::Float | TirType::Bool);
+        if let Some(ty) = params.iter().find(|ty| !is_scalar(ty) && !is_pointer(ty)) {
+            errors.push(CompilerError::TypeErrorSimple(format!(
+                "Extern function '{}' can only take int, float, bool, str and bytes, not {}",
+                name,
+                symbols.type_name(ty)
+            )));
+        } else if !is_scalar(return_type) && *return_type != TirType::Void {
+            errors.push(CompilerError::TypeErrorSimple(format!(
+                "Extern function '{}' can only return int, float, bool and None, not {}",
+                name,
+                symbols.type_name(return_type)
+            )));
+        }
+    }
+    errors.into_result_with(externs.into_iter().map(|(_, func_id)| func_id).collect())
 }
@@ -1,11 +1,19 @@
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet};
 
 use crate::ast;
+use crate::diagnostics;
+use crate::error::{CompilerError, ErrorCollector, Result};
+use crate::span::Span;
 use crate::tir::expr::VarRef;
-use crate::tir::ids::{ClassId, LocalId};
+use crate::tir::expr_unresolved::TirExprUnresolved;
+use crate::tir::ids::{ClassId, FuncId, GlobalId, LocalId, ModuleId};
+use crate::tir::stmt_unresolved::TirStmtUnresolved;
+use crate::tir::types::TirType;
 use crate::tir::types_unresolved::TirTypeUnresolved;
 
-use super::constraints::ConstraintSet;
+use super::closures::{LiftedFunction, NestedFunction};
+use super::constraints::{Constraint, ConstraintOrigin, ConstraintSet};
+use super::passes::{instantiate_generic_class, specialize_protocol_function};
 use super::scope::ModuleScope;
 use super::symbols::GlobalSymbols;
 
@@ -17,12 +25,24 @@ pub(crate) struct BodyLowerer<'a> {
     /// Current class (if in a method)
     pub(crate) current_class: Option<ClassId>,
 
+    /// The `cls` parameter of a class method, which names its class
+    pub(crate) class_param: Option<(String, ClassId)>,
+
+    /// Qualified name of the function being lowered (None for module init)
+    pub(crate) function_name: Option<String>,
+
     /// Expected return type for the current function
     pub(crate) return_type: TirTypeUnresolved,
 
+    /// Element type yielded by the current function, if it is a generator
+    pub(crate) yield_type: Option<TirTypeUnresolved>,
+
     /// Local variables: (name, type)
     pub(crate) locals: Vec<(String, TirTypeUnresolved)>,
 
+    /// Where each local was first bound, indexed by LocalId
+    pub(crate) local_spans: Vec<Span>,
+
     /// Local variable name -> LocalId
     pub(crate) local_names: HashMap<String, LocalId>,
 
@@ -40,6 +60,39 @@ pub(crate) struct BodyLowerer<'a> {
 
     /// Type constraints collected during lowering (for type inference)
     pub(crate) constraints: ConstraintSet,
+
+    /// Nested functions callable by name from this body
+    pub(crate) nested_functions: HashMap<String, NestedFunction>,
+
+    /// Nested functions defined in this body, lowered after it
+    pub(crate) lifted_functions: Vec<LiftedFunction>,
+
+    /// Variables of this function kept in cells because nested functions rebind them
+    pub(crate) cell_vars: BTreeSet<String>,
+
+    /// Module variables this function declared `global`
+    pub(crate) declared_globals: HashSet<String>,
+
+    /// Variables and attributes (as dotted paths) known not to be None, with
+    /// the depth of the scope the check was made in
+    pub(crate) not_none: HashMap<String, usize>,
+
+    /// Type parameter bindings of the generic class instance whose method
+    /// this is (see `GenericInstance::type_args`)
+    pub(crate) type_args: HashMap<String, TirType>,
+
+    /// The generic class instance the call being lowered constructs, taken
+    /// from the type expected of it (`s: Stack[int] = Stack()`)
+    pub(crate) generic_target: Option<ClassId>,
+
+    /// Where the statement being lowered starts
+    pub(crate) span: Span,
+
+    /// Errors of the statements left out of the body (see `lower_block`)
+    pub(crate) errors: ErrorCollector,
+
+    /// Names bound to placeholders because the statement binding them failed
+    pub(crate) placeholders: BTreeSet<String>,
 }
 
 impl<'a> BodyLowerer<'a> {
@@ -53,14 +106,28 @@ impl<'a> BodyLowerer<'a> {
             symbols,
             scope,
             current_class,
+            class_param: None,
+            function_name: None,
             return_type,
+            yield_type: None,
             locals: Vec::new(),
+            local_spans: Vec::new(),
             local_names: HashMap::new(),
             params: HashMap::new(),
             param_types: Vec::new(),
             scopes: vec![HashMap::new()],
             next_local_id: 0,
             constraints: ConstraintSet::new(),
+            nested_functions: HashMap::new(),
+            lifted_functions: Vec::new(),
+            cell_vars: BTreeSet::new(),
+            declared_globals: HashSet::new(),
+            not_none: HashMap::new(),
+            type_args: HashMap::new(),
+            generic_target: None,
+            span: Span::default(),
+            errors: ErrorCollector::new(),
+            placeholders: BTreeSet::new(),
         }
     }
 
@@ -74,12 +141,31 @@ impl<'a> BodyLowerer<'a> {
         let id = LocalId(self.next_local_id);
         self.next_local_id += 1;
         self.locals.push((name.to_string(), ty));
+        self.local_spans.push(self.span);
         self.local_names.insert(name.to_string(), id);
         // Also add to current scope for shadowing
         self.scopes.last_mut().unwrap().insert(name.to_string(), id);
         id
     }
 
+    /// Store `value` in a fresh local so it is evaluated once; returns its name
+    pub(crate) fn bind_temp(
+        &mut self,
+        prefix: &str,
+        value: TirExprUnresolved,
+        stmts: &mut Vec<TirStmtUnresolved>,
+    ) -> String {
+        let name = format!("{}_{}", prefix, self.next_local_id);
+        let ty = value.ty.clone();
+        let local = self.alloc_local(&name, ty.clone());
+        stmts.push(TirStmtUnresolved::Let {
+            local,
+            ty,
+            init: value,
+        });
+        name
+    }
+
     pub(crate) fn enter_scope(&mut self) {
         self.scopes.push(HashMap::new());
     }
@@ -90,6 +176,23 @@ impl<'a> BodyLowerer<'a> {
         for (name, _) in scope {
             self.local_names.remove(&name);
         }
+        // None checks made in the block no longer hold after it
+        let depth = self.scopes.len();
+        self.not_none.retain(|_, scope_depth| *scope_depth <= depth);
+    }
+
+    /// Unbind a local variable (`del x`); later uses of the name no longer
+    /// resolve to it
+    pub(crate) fn remove_local(&mut self, name: &str) {
+        self.local_names.remove(name);
+        if let Some(scope) = self
+            .scopes
+            .iter_mut()
+            .rev()
+            .find(|scope| scope.contains_key(name))
+        {
+            scope.remove(name);
+        }
     }
 
     pub(crate) fn resolve_var(&self, name: &str) -> Option<(VarRef, TirTypeUnresolved)> {
@@ -114,19 +217,187 @@ impl<'a> BodyLowerer<'a> {
 
         // Check module globals
         if let Some(&(mod_id, global_id)) = self.scope.globals.get(name) {
-            // Look up the global's type from symbols (and convert to unresolved)
-            let ty = self
-                .symbols
-                .global_types
-                .get(&(mod_id, global_id))
-                .map(TirTypeUnresolved::from_tir_type)
-                .unwrap_or(TirTypeUnresolved::Int);
+            let ty = self.global_type(mod_id, global_id);
             return Some((VarRef::Global(mod_id, global_id), ty));
         }
 
         None
     }
 
+    /// The type of a module global: the one definition collection gave it,
+    /// or int (which its module's init checks its value is)
+    pub(crate) fn global_type(&self, mod_id: ModuleId, global_id: GlobalId) -> TirTypeUnresolved {
+        self.symbols
+            .global_types
+            .get(&(mod_id, global_id))
+            .map_or(TirTypeUnresolved::Int, TirTypeUnresolved::from_tir_type)
+    }
+
+    /// The error for a use of `name`, which names nothing, suggesting the
+    /// closest name in scope
+    pub(crate) fn undefined_variable(&self, name: &str) -> CompilerError {
+        let in_scope = self
+            .local_names
+            .keys()
+            .chain(self.params.keys())
+            .chain(self.nested_functions.keys())
+            .chain(self.scope.globals.keys())
+            .chain(self.scope.functions.keys())
+            .chain(self.scope.classes.keys())
+            .chain(self.scope.module_aliases.keys())
+            .map(String::as_str)
+            .chain(self.current_class.map(|_| "self"));
+        let error = CompilerError::UndefinedVariable(name.to_string());
+        match diagnostics::suggest(name, in_scope) {
+            Some(similar) => error.with_help(format!(
+                "a name with a similar spelling exists: '{}'",
+                similar
+            )),
+            None => error,
+        }
+    }
+
+    /// Label the statement that bound `var_ref` with `message`, if it is a
+    /// local bound at another statement
+    pub(crate) fn label_binding(
+        &self,
+        error: CompilerError,
+        var_ref: &VarRef,
+        message: String,
+    ) -> CompilerError {
+        match var_ref {
+            VarRef::Local(local) => {
+                let span = self.local_spans[local.index()];
+                if span.is_known() && span != self.span {
+                    return error.with_label(span, message);
+                }
+                error
+            }
+            _ => error,
+        }
+    }
+
+    /// The class a name refers to in a call such as `Name(...)` or
+    /// `Name.method(...)`: a class of the module, or a class method's `cls`
+    pub(crate) fn class_named(&self, name: &str) -> Option<ClassId> {
+        if let Some((cls, class_id)) = &self.class_param {
+            if cls == name {
+                return Some(*class_id);
+            }
+        }
+        self.scope.classes.get(name).copied()
+    }
+
+    /// The class a call of `class_id` constructs. For a generic class, that
+    /// is the instance expected of the call, or in the methods of one of its
+    /// instances, that instance.
+    pub(crate) fn constructed_class(&mut self, class_id: ClassId, name: &str) -> Result<ClassId> {
+        if self.symbols.protocols.contains_key(&class_id) {
+            return Err(CompilerError::TypeErrorSimple(format!(
+                "Protocol '{}' cannot be instantiated",
+                name
+            )));
+        }
+        let unimplemented = self.symbols.unimplemented_methods(class_id);
+        if !unimplemented.is_empty() {
+            return Err(CompilerError::TypeErrorSimple(format!(
+                "Cannot instantiate abstract class '{}' with abstract method(s) '{}'",
+                name,
+                unimplemented.join("', '")
+            )));
+        }
+        if !self.symbols.generic_classes.contains_key(&class_id) {
+            return Ok(class_id);
+        }
+        let instance_of = |instance: &ClassId| {
+            self.symbols
+                .generic_instances
+                .get(instance)
+                .is_some_and(|instance| instance.template == class_id)
+        };
+        if let Some(instance) = self.generic_target.take().filter(instance_of) {
+            return Ok(instance);
+        }
+        if let Some(instance) = self.current_class.filter(instance_of) {
+            return Ok(instance);
+        }
+        Err(CompilerError::TypeErrorSimple(format!(
+            "Cannot infer the type arguments of generic class '{}'; annotate the variable it is assigned to (x: {}[int] = {}())",
+            name, name, name
+        )))
+    }
+
+    /// Reject a direct call of an abstract method (through `super()`, or on
+    /// a receiver no subclass of whose class overrides it), which would
+    /// always fail.
+    pub(crate) fn check_not_abstract(&self, class_id: ClassId, method_name: &str) -> Result<()> {
+        let is_abstract = self
+            .symbols
+            .resolve_method(class_id, method_name)
+            .is_some_and(|(_, func_id)| self.symbols.abstract_methods.contains(&func_id));
+        if is_abstract {
+            return Err(CompilerError::TypeErrorSimple(format!(
+                "Cannot call abstract method '{}.{}', which has no implementation",
+                self.symbols.type_name(&TirType::Class(class_id)),
+                method_name
+            )));
+        }
+        Ok(())
+    }
+
+    /// The function a call of `func_id` with `args` calls. For a function
+    /// taking protocols, that is its specialization for the classes passed
+    /// for them, each of which must implement its protocol.
+    pub(crate) fn specialized_callee(
+        &mut self,
+        func_id: FuncId,
+        name: &str,
+        args: &[TirExprUnresolved],
+    ) -> Result<FuncId> {
+        let params = &self.symbols.get_func_signature(func_id).0;
+        if !self.symbols.protocol_functions.contains_key(&func_id) || params.len() != args.len() {
+            return Ok(func_id);
+        }
+
+        let mut specialized = params.clone();
+        for (i, (param, arg)) in specialized.iter_mut().zip(args).enumerate() {
+            let TirType::Class(protocol) = *param else {
+                continue;
+            };
+            if !self.symbols.protocols.contains_key(&protocol) {
+                continue;
+            }
+            let Some(class_id) = arg
+                .ty
+                .class_id()
+                .filter(|class_id| !self.symbols.protocols.contains_key(class_id))
+            else {
+                return Err(CompilerError::TypeErrorSimple(format!(
+                    "Argument {} to function '{}': expected a class implementing protocol '{}', got {:?}",
+                    i + 1,
+                    name,
+                    self.symbols.type_name(param),
+                    arg.ty
+                )));
+            };
+            self.constraints.add_constraint(Constraint::Implements {
+                ty: arg.ty.clone(),
+                protocol,
+                origin: ConstraintOrigin::Argument {
+                    function: name.to_string(),
+                    position: i + 1,
+                    span: self.span,
+                },
+            });
+            *param = TirType::Class(class_id);
+        }
+        Ok(specialize_protocol_function(
+            self.symbols,
+            func_id,
+            specialized,
+        ))
+    }
+
     pub(crate) fn convert_annotation(&mut self, annot: &ast::TypeAnnotation) -> TirTypeUnresolved {
         match annot {
             ast::TypeAnnotation::Int => TirTypeUnresolved::Int,
@@ -144,6 +415,10 @@ impl<'a> BodyLowerer<'a> {
                 let class_id = self.symbols.get_or_create_bytearray_class();
                 TirTypeUnresolved::Class(class_id)
             }
+            ast::TypeAnnotation::TextIO => {
+                let class_id = self.symbols.get_or_create_file_class();
+                TirTypeUnresolved::Class(class_id)
+            }
             ast::TypeAnnotation::List(inner) => {
                 let elem_ty = self.convert_annotation(inner);
                 let class_id = self
@@ -151,7 +426,42 @@ impl<'a> BodyLowerer<'a> {
                     .get_or_create_list_class(&elem_ty.to_tir_type());
                 TirTypeUnresolved::Class(class_id)
             }
+            ast::TypeAnnotation::Dict(key, value) => {
+                let key_ty = self.convert_annotation(key);
+                let value_ty = self.convert_annotation(value);
+                let class_id = self
+                    .symbols
+                    .get_or_create_dict_class(&key_ty.to_tir_type(), &value_ty.to_tir_type());
+                TirTypeUnresolved::Class(class_id)
+            }
+            ast::TypeAnnotation::Set(inner) => {
+                let elem_ty = self.convert_annotation(inner);
+                let class_id = self.symbols.get_or_create_set_class(&elem_ty.to_tir_type());
+                TirTypeUnresolved::Class(class_id)
+            }
+            ast::TypeAnnotation::FrozenSet(inner) => {
+                let elem_ty = self.convert_annotation(inner);
+                let class_id = self
+                    .symbols
+                    .get_or_create_frozenset_class(&elem_ty.to_tir_type());
+                TirTypeUnresolved::Class(class_id)
+            }
+            ast::TypeAnnotation::Tuple(elems) => TirTypeUnresolved::Tuple(
+                elems
+                    .iter()
+                    .map(|elem| self.convert_annotation(elem))
+                    .collect(),
+            ),
+            ast::TypeAnnotation::Optional(inner) => {
+                TirTypeUnresolved::Optional(Box::new(self.convert_annotation(inner)))
+            }
+            ast::TypeAnnotation::Iterator(_) => {
+                unreachable!("Iterator annotations only appear as generator return types")
+            }
             ast::TypeAnnotation::ClassName(name) => {
+                if let Some(ty) = self.type_args.get(name) {
+                    return TirTypeUnresolved::from_tir_type(ty);
+                }
                 // Look up class in scope
                 if let Some(&class_id) = self.scope.classes.get(name) {
                     TirTypeUnresolved::Class(class_id)
@@ -163,6 +473,23 @@ impl<'a> BodyLowerer<'a> {
                     panic!("Class '{}' not found during lowering", name)
                 }
             }
+            ast::TypeAnnotation::Generic { name, args } => {
+                let args: Vec<TirType> = args
+                    .iter()
+                    .map(|arg| self.convert_annotation(arg).to_tir_type())
+                    .collect();
+                let template = self
+                    .scope
+                    .classes
+                    .get(name)
+                    .copied()
+                    .or_else(|| self.symbols.find_class_by_name(name))
+                    .filter(|class_id| self.symbols.generic_classes.contains_key(class_id))
+                    .unwrap_or_else(|| {
+                        panic!("Generic class '{}' not found during lowering", name)
+                    });
+                TirTypeUnresolved::Class(instantiate_generic_class(self.symbols, template, args))
+            }
         }
     }
 }
@@ -13,6 +13,7 @@ impl GlobalSymbols {
 
         let str_class_id = self.get_or_create_str_class();
         let str_type = TirType::Class(str_class_id);
+        let bytes_type = TirType::Class(self.get_or_create_bytes_class());
 
         register_methods!(self, class_id, "bytearray",
             shared "append" => (vec![TirType::Int], TirType::Void),
@@ -21,8 +22,20 @@ impl GlobalSymbols {
             shared "__repr__" => (vec![], str_type),
             shared "__getitem__" => (vec![TirType::Int], TirType::Int),
             shared "__setitem__" => (vec![TirType::Int, TirType::Int], TirType::Void),
+            shared "__slice__" => (vec![TirType::Int, TirType::Int, TirType::Int], TirType::Class(class_id)),
+            shared "__setslice__" => (vec![TirType::Int, TirType::Int, TirType::Int, bytes_type.clone()], TirType::Void),
+            shared "extend" => (vec![bytes_type.clone()], TirType::Void),
+            shared "__iadd__" => (vec![bytes_type], TirType::Class(class_id)),
         );
 
         class_id
     }
+
+    /// Check if a ClassId is the bytearray class
+    pub(crate) fn is_bytearray_class(&self, class_id: ClassId) -> bool {
+        self.class_data
+            .get(class_id.index())
+            .map(|c| c.qualified_name == "__builtin__.bytearray")
+            .unwrap_or(false)
+    }
 }
@@ -13,12 +13,33 @@ impl GlobalSymbols {
 
         let str_class_id = self.get_or_create_str_class();
         let str_type = TirType::Class(str_class_id);
+        let bytes_type = TirType::Class(class_id);
+        let bytes_list_type = TirType::Class(self.get_or_create_list_class(&bytes_type));
 
         register_methods!(self, class_id, "bytes",
             shared "__len__" => (vec![], TirType::Int),
             shared "__str__" => (vec![], str_type.clone()),
-            shared "__repr__" => (vec![], str_type),
+            shared "__repr__" => (vec![], str_type.clone()),
             shared "__getitem__" => (vec![TirType::Int], TirType::Int),
+            shared "__slice__" => (vec![TirType::Int, TirType::Int, TirType::Int], bytes_type.clone()),
+            shared "__add__" => (vec![bytes_type.clone()], bytes_type.clone()),
+            shared "decode" => (vec![], str_type.clone()),
+            shared "hex" => (vec![], str_type),
+
+            // Searching
+            shared "find" => (vec![bytes_type.clone()], TirType::Int),
+            shared "count" => (vec![bytes_type.clone()], TirType::Int),
+            shared "__contains__" => (vec![bytes_type.clone()], TirType::Bool),
+            shared "startswith" => (vec![bytes_type.clone()], TirType::Bool),
+            shared "endswith" => (vec![bytes_type.clone()], TirType::Bool),
+
+            // New bytes from old
+            shared "strip" => (vec![], bytes_type.clone()),
+            shared "replace" => (vec![bytes_type.clone(), bytes_type.clone()], bytes_type.clone()),
+            shared "split" => (vec![bytes_type.clone()], bytes_list_type.clone()),
+            // split() without a separator
+            shared "__split_whitespace__" => (vec![], bytes_list_type.clone()),
+            shared "join" => (vec![bytes_list_type], bytes_type),
         );
 
         class_id
@@ -0,0 +1,54 @@
+//! Dict built-in class implementation
+
+use crate::tir::ids::ClassId;
+use crate::tir::types::TirType;
+
+use super::super::symbols::{ClassKey, GlobalSymbols};
+
+impl GlobalSymbols {
+    /// Get or create a ClassId for a dict type with the given key and value types.
+    /// Each unique dict[K, V] gets its own ClassId.
+    pub(crate) fn get_or_create_dict_class(
+        &mut self,
+        key_type: &TirType,
+        value_type: &TirType,
+    ) -> ClassId {
+        // Check cache first using ClassKey
+        let key = ClassKey::builtin_generic("dict", vec![key_type.clone(), value_type.clone()]);
+        if let Some(&class_id) = self.classes.get(&key) {
+            return class_id;
+        }
+
+        // Allocate new class for this dict type
+        let class_id = self.alloc_class();
+        self.classes.insert(key, class_id);
+        self.class_data[class_id.index()].qualified_name = "__builtin__.dict".to_string();
+        self.class_data[class_id.index()].type_params = vec![key_type.clone(), value_type.clone()];
+
+        let str_class_id = self.get_or_create_str_class();
+        let str_type = TirType::Class(str_class_id);
+
+        // keys()/values()/items() return fresh lists; iterating a dict walks its keys
+        let key_list_type = TirType::Class(self.get_or_create_list_class(key_type));
+        let value_list_type = TirType::Class(self.get_or_create_list_class(value_type));
+        let item_type = TirType::Tuple(vec![key_type.clone(), value_type.clone()]);
+        let item_list_type = TirType::Class(self.get_or_create_list_class(&item_type));
+        let key_iter_type = TirType::Class(self.get_or_create_list_iterator_class(key_type));
+
+        register_methods!(self, class_id, "dict",
+            unique "__getitem__" => (vec![key_type.clone()], value_type.clone()),
+            unique "__setitem__" => (vec![key_type.clone(), value_type.clone()], TirType::Void),
+            unique "__delitem__" => (vec![key_type.clone()], TirType::Void),
+            unique "__contains__" => (vec![key_type.clone()], TirType::Bool),
+            shared "__len__" => (vec![], TirType::Int),
+            shared "__str__" => (vec![], str_type.clone()),
+            shared "__repr__" => (vec![], str_type),
+            unique "keys" => (vec![], key_list_type),
+            unique "values" => (vec![], value_list_type),
+            unique "items" => (vec![], item_list_type),
+            unique "__iter__" => (vec![], key_iter_type),
+        );
+
+        class_id
+    }
+}
@@ -1,10 +1,52 @@
 //! Exception built-in class implementation
+//!
+//! Besides Exception itself, the exceptions Python defines below it are
+//! builtin classes, each a subclass of its Python base. Like a user
+//! exception class, an instance is the runtime's Exception struct, made by
+//! `__pyc_exception_new` with the class's name and those of its bases, which
+//! is how the runtime raises them too (`ValueError` with
+//! `"Exception"`, `KeyError` with `"LookupError,Exception"`), so that an
+//! `except` clause naming a class catches its subclasses whoever raised
+//! them.
 
-use crate::tir::ids::ClassId;
+use crate::tir::ids::{ClassId, FuncId};
 use crate::tir::types::TirType;
 
 use super::super::symbols::{ClassKey, GlobalSymbols};
 
+/// The builtin exception classes below Exception, each with its base, every
+/// base before the classes deriving from it
+const EXCEPTION_HIERARCHY: &[(&str, &str)] = &[
+    ("ArithmeticError", "Exception"),
+    ("OverflowError", "ArithmeticError"),
+    ("ZeroDivisionError", "ArithmeticError"),
+    ("AssertionError", "Exception"),
+    ("AttributeError", "Exception"),
+    ("EOFError", "Exception"),
+    ("ImportError", "Exception"),
+    ("ModuleNotFoundError", "ImportError"),
+    ("LookupError", "Exception"),
+    ("IndexError", "LookupError"),
+    ("KeyError", "LookupError"),
+    ("MemoryError", "Exception"),
+    ("NameError", "Exception"),
+    ("OSError", "Exception"),
+    ("FileExistsError", "OSError"),
+    ("FileNotFoundError", "OSError"),
+    ("IsADirectoryError", "OSError"),
+    ("NotADirectoryError", "OSError"),
+    ("PermissionError", "OSError"),
+    ("TimeoutError", "OSError"),
+    ("RuntimeError", "Exception"),
+    ("NotImplementedError", "RuntimeError"),
+    ("RecursionError", "RuntimeError"),
+    ("TypeError", "Exception"),
+    ("ValueError", "Exception"),
+    ("UnicodeError", "ValueError"),
+    ("UnicodeDecodeError", "UnicodeError"),
+    ("UnicodeEncodeError", "UnicodeError"),
+];
+
 impl GlobalSymbols {
     /// Get or create the ClassId for Exception type.
     pub(crate) fn get_or_create_exception_class(&mut self) -> ClassId {
@@ -14,17 +56,65 @@ impl GlobalSymbols {
         let str_class_id = self.get_or_create_str_class();
         let str_type = TirType::Class(str_class_id);
         let exc_type = TirType::Class(class_id);
+        let chained_type = TirType::Optional(Box::new(exc_type.clone()));
 
         register_methods!(self, class_id, "Exception",
             // __init__ takes a string message and returns the exception
             shared "__init__" => (vec![str_type.clone()], exc_type),
             shared "__str__" => (vec![], str_type.clone()),
             shared "__repr__" => (vec![], str_type),
+            // The exception `raise ... from` named, and the one being
+            // handled when it was raised
+            shared "__cause__" => (vec![], chained_type.clone()),
+            shared "__context__" => (vec![], chained_type),
         );
+        for name in ["__cause__", "__context__"] {
+            let (_, func_id) = self.methods[&(class_id, name.to_string())];
+            self.properties.insert(func_id);
+        }
 
         class_id
     }
 
+    /// Get the FuncId of `raise exc from cause`, which records the cause
+    /// (an exception, or None) in the exception and returns it
+    pub(crate) fn get_exception_with_cause_func(&mut self) -> FuncId {
+        let exc_type = TirType::Class(self.get_or_create_exception_class());
+        self.get_or_create_runtime_func(
+            "__pyc_exception_with_cause",
+            vec![
+                exc_type.clone(),
+                TirType::Optional(Box::new(exc_type.clone())),
+            ],
+            exc_type,
+        )
+    }
+
+    /// Create the builtin exception classes below Exception. They have no
+    /// methods of their own: those of Exception work on any exception.
+    pub(crate) fn register_builtin_exceptions(&mut self) {
+        self.get_or_create_exception_class();
+        self.get_or_create_stop_iteration_class();
+        for &(name, base) in EXCEPTION_HIERARCHY {
+            let key = ClassKey::builtin(name);
+            if self.classes.contains_key(&key) {
+                continue;
+            }
+            let parent_id = self.classes[&ClassKey::builtin(base)];
+            let class_id = self.alloc_class();
+            self.classes.insert(key, class_id);
+            self.class_data[class_id.index()].qualified_name = format!("__builtin__.{}", name);
+            self.set_parent(class_id, parent_id);
+        }
+    }
+
+    /// The builtin exception class `name` names (`Exception`, `ValueError`),
+    /// if it names one
+    pub(crate) fn builtin_exception_class(&self, name: &str) -> Option<ClassId> {
+        let &class_id = self.classes.get(&ClassKey::builtin(name))?;
+        self.is_exception_class(class_id).then_some(class_id)
+    }
+
     /// Get or create the ClassId for StopIteration exception type.
     /// StopIteration inherits from Exception and is raised by __next__ when iteration is complete.
     pub(crate) fn get_or_create_stop_iteration_class(&mut self) -> ClassId {
@@ -0,0 +1,35 @@
+//! File built-in class implementation (the object returned by open())
+
+use crate::tir::ids::ClassId;
+use crate::tir::types::TirType;
+
+use super::super::symbols::{ClassKey, GlobalSymbols};
+
+impl GlobalSymbols {
+    /// Get or create the ClassId for the file type returned by open().
+    /// Files are text-mode only; reads and writes go through the C runtime's
+    /// wrappers around the open/read/write/close syscalls.
+    pub(crate) fn get_or_create_file_class(&mut self) -> ClassId {
+        let key = ClassKey::builtin("file");
+        let class_id = init_builtin_class!(self, key, "file");
+
+        let file_type = TirType::Class(class_id);
+        let str_class_id = self.get_or_create_str_class();
+        let str_type = TirType::Class(str_class_id);
+
+        register_methods!(self, class_id, "file",
+            // read() returns everything up to the end of the file
+            shared "read" => (vec![], str_type.clone()),
+            // readline() keeps the trailing newline; "" means end of file
+            shared "readline" => (vec![], str_type.clone()),
+            // write() returns the number of characters written
+            shared "write" => (vec![str_type], TirType::Int),
+            shared "close" => (vec![], TirType::Void),
+            // Context manager protocol: `with open(...) as f:` closes f on exit
+            shared "__enter__" => (vec![], file_type),
+            shared "__exit__" => (vec![], TirType::Void),
+        );
+
+        class_id
+    }
+}
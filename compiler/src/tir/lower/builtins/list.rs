@@ -28,17 +28,45 @@ impl GlobalSymbols {
         let list_iter_class_id = self.get_or_create_list_iterator_class(element_type);
         let list_iter_type = TirType::Class(list_iter_class_id);
 
+        // Sort orders are lists of indices (list[int] is cached above, so this
+        // does not recurse for list[int] itself)
+        let index_list_type = TirType::Class(self.get_or_create_list_class(&TirType::Int));
+
+        // Methods passing items go to list_float_* and list_bool_* for those
+        // items, so floats and bools are not reinterpreted as i64 slots
+        let typed = format!("list{}", element_type.list_item_suffix());
+
         // For generic list[T]:
         // - unique: methods with type-dependent signatures (need separate FuncId per T)
         // - shared: methods with fixed signatures (reuse same FuncId across all list types)
         register_methods!(self, class_id, "list",
-            unique "append" => (vec![element_type.clone()], TirType::Void),
+            unique(typed) "append" => (vec![element_type.clone()], TirType::Void),
+            unique(typed) "pop" => (vec![TirType::Int], element_type.clone()),
+            unique(typed) "insert" => (vec![TirType::Int, element_type.clone()], TirType::Void),
+            unique "extend" => (vec![TirType::Class(class_id)], TirType::Void),
+            shared "reverse" => (vec![], TirType::Void),
+            shared "clear" => (vec![], TirType::Void),
+            // The searches also take the items' DICT_KIND_* (see list_methods.rs)
+            unique(typed) "remove" => (vec![element_type.clone(), TirType::Int], TirType::Void),
+            unique(typed) "index" => (vec![element_type.clone(), TirType::Int, TirType::Int, TirType::Int], TirType::Int),
+            unique(typed) "count" => (vec![element_type.clone(), TirType::Int], TirType::Int),
             shared "__len__" => (vec![], TirType::Int),
             shared "__str__" => (vec![], str_type.clone()),
             shared "__repr__" => (vec![], str_type),
-            unique "__getitem__" => (vec![TirType::Int], element_type.clone()),
-            unique "__setitem__" => (vec![TirType::Int, element_type.clone()], TirType::Void),
+            unique(typed) "__getitem__" => (vec![TirType::Int], element_type.clone()),
+            unique(typed) "__setitem__" => (vec![TirType::Int, element_type.clone()], TirType::Void),
+            shared "__delitem__" => (vec![TirType::Int], TirType::Void),
             unique "__iter__" => (vec![], list_iter_type),
+            unique "__slice__" => (vec![TirType::Int, TirType::Int, TirType::Int], TirType::Class(class_id)),
+            unique "__unpack__" => (vec![TirType::Int, TirType::Int], TirType::Class(class_id)),
+            unique "__add__" => (vec![TirType::Class(class_id)], TirType::Class(class_id)),
+            unique "__iadd__" => (vec![TirType::Class(class_id)], TirType::Class(class_id)),
+            // Takes the items' DICT_KIND_* too (see list_methods.rs)
+            unique "__eq__" => (vec![TirType::Class(class_id), TirType::Int], TirType::Bool),
+            unique "__mul__" => (vec![TirType::Int], TirType::Class(class_id)),
+            unique "__rmul__" => (vec![TirType::Int], TirType::Class(class_id)),
+            shared "__argsort__" => (vec![TirType::Int, TirType::Bool], index_list_type.clone()),
+            shared "__permute__" => (vec![index_list_type], TirType::Void),
         );
 
         class_id
@@ -27,9 +27,10 @@ impl GlobalSymbols {
         // - __iter__ returns self (iterator is its own iterator)
         // - __next__ returns the next element or raises StopIteration
         // - __dealloc__ deallocates the iterator (called at end of for-loop)
+        let typed = format!("list_iterator{}", element_type.list_item_suffix());
         register_methods!(self, class_id, "list_iterator",
             unique "__iter__" => (vec![], iter_type),
-            unique "__next__" => (vec![], element_type.clone()),
+            unique(typed) "__next__" => (vec![], element_type.clone()),
             shared "__dealloc__" => (vec![], TirType::Void),
         );
 
@@ -1,7 +1,7 @@
 //! Built-in class definitions for GlobalSymbols
 //!
 //! This module contains the implementation of built-in Python types
-//! (list, bytearray, bytes, str) as separate files for better organization.
+//! (list, dict, set, bytearray, bytes, str, file) as separate files for better organization.
 
 /// Register methods on a builtin class with auto-incrementing MethodId.
 /// Supports both shared and unique methods for generic types.
@@ -21,12 +21,41 @@
 ///     shared "__len__" => (vec![], TirType::Int),
 /// );
 /// ```
+///
+/// A unique method can name the runtime class implementing it, for element
+/// types with their own C functions (`__pyc___builtin___list_float_append`):
+/// ```ignore
+/// register_methods!(self, class_id, "list",
+///     unique(typed_name) "append" => (vec![element_type.clone()], TirType::Void),
+/// );
+/// ```
 macro_rules! register_methods {
     // Entry point - start counting from 0
     ($self:expr, $class_id:expr, $class_name:expr, $($rest:tt)*) => {
         register_methods!(@impl $self, $class_id, $class_name, 0, $($rest)*)
     };
 
+    // Register a UNIQUE method implemented by another runtime class
+    (@impl $self:expr, $class_id:expr, $class_name:expr, $idx:expr,
+        unique($runtime_class:expr) $method_name:expr => ($params:expr, $ret_type:expr) $(, $($rest:tt)*)?) => {
+        {
+            let runtime_name = format!("__pyc___builtin___{}_{}", $runtime_class, $method_name);
+            let func_id = $self.alloc_func($params, $ret_type);
+            $self.builtin_runtime_funcs
+                .insert(format!("{}_{}", runtime_name, $class_id.0), func_id);
+            $self.runtime_func_names
+                .insert(func_id, runtime_name);
+            $self.methods.insert(
+                ($class_id, $method_name.to_string()),
+                ($crate::tir::ids::MethodId($idx), func_id),
+            );
+            $self.class_data[$class_id.index()]
+                .methods
+                .push(($method_name.to_string(), func_id));
+        }
+        $(register_methods!(@impl $self, $class_id, $class_name, $idx + 1, $($rest)*);)?
+    };
+
     // Register a UNIQUE method (for generic types with type-dependent signatures)
     (@impl $self:expr, $class_id:expr, $class_name:expr, $idx:expr,
         unique $method_name:expr => ($params:expr, $ret_type:expr) $(, $($rest:tt)*)?) => {
@@ -109,10 +138,13 @@ macro_rules! init_builtin_class {
 
 mod bytearray;
 mod bytes;
+mod dict;
 mod exception;
+mod file;
 mod list;
 mod list_iterator;
 mod range;
+mod set;
 mod str_class;
 
 // Re-export nothing - all methods are impl blocks on GlobalSymbols
@@ -0,0 +1,98 @@
+//! Set and frozenset built-in class implementations
+
+use crate::tir::ids::ClassId;
+use crate::tir::types::TirType;
+
+use super::super::symbols::{ClassKey, GlobalSymbols};
+
+impl GlobalSymbols {
+    /// Get or create a ClassId for a set type with the given element type.
+    /// Each unique set[T] gets its own ClassId.
+    pub(crate) fn get_or_create_set_class(&mut self, element_type: &TirType) -> ClassId {
+        // Check cache first using ClassKey
+        let key = ClassKey::builtin_generic("set", vec![element_type.clone()]);
+        if let Some(&class_id) = self.classes.get(&key) {
+            return class_id;
+        }
+
+        // Allocate new class for this set type
+        let class_id = self.alloc_class();
+        self.classes.insert(key, class_id);
+        self.class_data[class_id.index()].qualified_name = "__builtin__.set".to_string();
+        self.class_data[class_id.index()].type_params = vec![element_type.clone()];
+
+        let set_type = TirType::Class(class_id);
+        let str_class_id = self.get_or_create_str_class();
+        let str_type = TirType::Class(str_class_id);
+
+        // Iterating a set walks a snapshot of its elements
+        let iter_type = TirType::Class(self.get_or_create_list_iterator_class(element_type));
+
+        register_methods!(self, class_id, "set",
+            unique "add" => (vec![element_type.clone()], TirType::Void),
+            unique "remove" => (vec![element_type.clone()], TirType::Void),
+            unique "discard" => (vec![element_type.clone()], TirType::Void),
+            unique "__contains__" => (vec![element_type.clone()], TirType::Bool),
+            shared "__len__" => (vec![], TirType::Int),
+            unique "__or__" => (vec![set_type.clone()], set_type.clone()),
+            unique "__and__" => (vec![set_type.clone()], set_type.clone()),
+            unique "__sub__" => (vec![set_type.clone()], set_type.clone()),
+            unique "__xor__" => (vec![set_type.clone()], set_type.clone()),
+            unique "union" => (vec![set_type.clone()], set_type.clone()),
+            unique "intersection" => (vec![set_type.clone()], set_type.clone()),
+            unique "update" => (vec![set_type.clone()], TirType::Void),
+            unique "__iter__" => (vec![], iter_type),
+            shared "__str__" => (vec![], str_type.clone()),
+            shared "__repr__" => (vec![], str_type),
+        );
+
+        class_id
+    }
+
+    /// Get or create a ClassId for a frozenset type with the given element type.
+    /// frozenset[T] shares the set runtime but has no mutating methods.
+    pub(crate) fn get_or_create_frozenset_class(&mut self, element_type: &TirType) -> ClassId {
+        // Check cache first using ClassKey
+        let key = ClassKey::builtin_generic("frozenset", vec![element_type.clone()]);
+        if let Some(&class_id) = self.classes.get(&key) {
+            return class_id;
+        }
+
+        // Allocate new class for this frozenset type
+        let class_id = self.alloc_class();
+        self.classes.insert(key, class_id);
+        self.class_data[class_id.index()].qualified_name = "__builtin__.frozenset".to_string();
+        self.class_data[class_id.index()].type_params = vec![element_type.clone()];
+
+        let frozenset_type = TirType::Class(class_id);
+        let str_class_id = self.get_or_create_str_class();
+        let str_type = TirType::Class(str_class_id);
+        let iter_type = TirType::Class(self.get_or_create_list_iterator_class(element_type));
+
+        register_methods!(self, class_id, "frozenset",
+            unique "__contains__" => (vec![element_type.clone()], TirType::Bool),
+            shared "__len__" => (vec![], TirType::Int),
+            unique "__or__" => (vec![frozenset_type.clone()], frozenset_type.clone()),
+            unique "__and__" => (vec![frozenset_type.clone()], frozenset_type.clone()),
+            unique "__sub__" => (vec![frozenset_type.clone()], frozenset_type.clone()),
+            unique "__xor__" => (vec![frozenset_type.clone()], frozenset_type.clone()),
+            unique "union" => (vec![frozenset_type.clone()], frozenset_type.clone()),
+            unique "intersection" => (vec![frozenset_type.clone()], frozenset_type.clone()),
+            unique "__iter__" => (vec![], iter_type),
+            shared "__str__" => (vec![], str_type.clone()),
+            shared "__repr__" => (vec![], str_type),
+        );
+
+        class_id
+    }
+
+    /// Check if a class is a set[T] or frozenset[T] instance
+    pub(crate) fn is_set_class(&self, class_id: ClassId) -> bool {
+        self.class_data
+            .get(class_id.index())
+            .map(|c| {
+                c.qualified_name == "__builtin__.set" || c.qualified_name == "__builtin__.frozenset"
+            })
+            .unwrap_or(false)
+    }
+}
@@ -12,6 +12,7 @@ impl GlobalSymbols {
         let class_id = init_builtin_class!(self, key, "str");
 
         let str_type = TirType::Class(class_id);
+        let bytes_type = TirType::Class(self.get_or_create_bytes_class());
 
         register_methods!(self, class_id, "str",
             // Core methods
@@ -19,6 +20,11 @@ impl GlobalSymbols {
             shared "__str__" => (vec![], str_type.clone()),
             shared "__repr__" => (vec![], str_type.clone()),
             shared "__getitem__" => (vec![TirType::Int], TirType::Int),
+            shared "__slice__" => (vec![TirType::Int, TirType::Int, TirType::Int], str_type.clone()),
+
+            // Repetition: s * n and n * s
+            shared "__mul__" => (vec![TirType::Int], str_type.clone()),
+            shared "__rmul__" => (vec![TirType::Int], str_type.clone()),
 
             // Case conversion methods (Phase 3)
             shared "lower" => (vec![], str_type.clone()),
@@ -29,6 +35,7 @@ impl GlobalSymbols {
 
             // String search methods (Phase 3)
             shared "find" => (vec![str_type.clone()], TirType::Int),
+            shared "__contains__" => (vec![str_type.clone()], TirType::Bool),
             shared "startswith" => (vec![str_type.clone()], TirType::Bool),
             shared "endswith" => (vec![str_type.clone()], TirType::Bool),
 
@@ -39,6 +46,9 @@ impl GlobalSymbols {
             shared "isalpha" => (vec![], TirType::Bool),
             shared "isdigit" => (vec![], TirType::Bool),
             shared "isspace" => (vec![], TirType::Bool),
+
+            // UTF-8 encoding
+            shared "encode" => (vec![], bytes_type),
         );
 
         class_id
@@ -0,0 +1,488 @@
+//! Nested functions
+//!
+//! A `def` inside a function body is lambda-lifted into a top-level function.
+//! The enclosing variables its body reads are passed as extra leading
+//! parameters, filled in with their current values at every call site:
+//!
+//! ```text
+//! def outer(n: int) -> int:               def outer(n: int) -> int:
+//!     k = 2                                   k = 2
+//!     def scale(x: int) -> int:               return outer.scale(k, n)
+//!         return x * k
+//!     return scale(n)                     def outer.scale(k: int, x: int) -> int:
+//!                                             return x * k
+//! ```
+//!
+//! Nested functions are not first-class values: they can be called by name
+//! from the enclosing body, from their own body and from nested functions
+//! defined after them. A nested function calling another one captures that
+//! function's variables too, so it can pass them along.
+//!
+//! A variable that a nested function declares `nonlocal` is kept in a heap
+//! cell (a synthesized class with a single `value` field) by the function
+//! that binds it, so every function sharing it sees the rebinding. `global`
+//! declarations let a function rebind module variables; without one, assigning
+//! to a module variable's name inside a function creates a local instead.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use crate::ast::{
+    assigned_names, global_names, nested_function_bodies, nonlocal_names, referenced_names, Arg,
+    Stmt, TypeAnnotation,
+};
+use crate::error::{CompilerError, ErrorCollector, Result};
+use crate::tir::expr::VarRef;
+use crate::tir::expr_unresolved::{TirExprKindUnresolved, TirExprUnresolved};
+use crate::tir::ids::{ClassId, FieldId, FuncId};
+use crate::tir::stmt_unresolved::{TirLValueUnresolved, TirStmtUnresolved};
+use crate::tir::types::TirType;
+use crate::tir::types_unresolved::TirTypeUnresolved;
+
+use super::body_lowerer::BodyLowerer;
+use super::passes::collect_defaults;
+use super::symbols::GlobalSymbols;
+
+/// The single field of a cell class
+const CELL_VALUE_FIELD: FieldId = FieldId(0);
+
+/// A nested function that can be called by name from the current body
+#[derive(Debug, Clone)]
+pub(crate) struct NestedFunction {
+    pub(crate) func_id: FuncId,
+    /// Enclosing variables passed ahead of the declared arguments
+    pub(crate) captures: Vec<String>,
+}
+
+/// A lifted nested function whose body has not been lowered yet
+#[derive(Debug, Clone)]
+pub(crate) struct LiftedFunction {
+    pub(crate) name: String,
+    pub(crate) qualified_name: String,
+    /// Line of the `def`
+    pub(crate) line: u32,
+    pub(crate) func_id: FuncId,
+    /// Captured variables, which become the leading parameters
+    pub(crate) captures: Vec<(String, TirType)>,
+    pub(crate) args: Vec<Arg>,
+    pub(crate) return_type: Option<TypeAnnotation>,
+    pub(crate) body: Vec<Stmt>,
+    /// Nested functions callable from the body (itself and earlier siblings)
+    pub(crate) visible: HashMap<String, NestedFunction>,
+}
+
+impl GlobalSymbols {
+    /// Get the cell class holding a value of type `value_ty`.
+    pub(crate) fn get_or_create_cell_class(&mut self, value_ty: &TirType) -> ClassId {
+        if let Some((&class_id, _)) = self.cell_classes.iter().find(|(_, ty)| *ty == value_ty) {
+            return class_id;
+        }
+
+        let class_id = self.alloc_class();
+        self.cell_classes.insert(class_id, value_ty.clone());
+        self.fields
+            .insert((class_id, "value".to_string()), CELL_VALUE_FIELD);
+        let class_data = &mut self.class_data[class_id.index()];
+        class_data.qualified_name = format!("<cell#{}>", class_id.0);
+        class_data.fields = vec![("value".to_string(), value_ty.clone())];
+        class_id
+    }
+}
+
+impl<'a> BodyLowerer<'a> {
+    /// Lift `def name(args) -> return_type: body` out of the current function.
+    /// The definition itself produces no statements; its body is lowered by
+    /// the body lowering pass once the enclosing function is done.
+    pub(crate) fn lower_function_def(
+        &mut self,
+        name: &str,
+        args: &[Arg],
+        return_type: Option<&TypeAnnotation>,
+        body: &[Stmt],
+        line: u32,
+    ) -> Result<Vec<TirStmtUnresolved>> {
+        let Some(enclosing_name) = self.function_name.clone() else {
+            return Err(CompilerError::UnsupportedFeature(format!(
+                "Function '{}' must be defined at module level or inside a function",
+                name
+            )));
+        };
+        if let Some(TypeAnnotation::Iterator(_)) = return_type {
+            return Err(CompilerError::UnsupportedFeature(format!(
+                "Nested generator function '{}' is not supported",
+                name
+            )));
+        }
+
+        let captures = self.collect_captures(name, args, body)?;
+
+        // Signature: captured variables first, then the declared parameters
+        let declared_params: Vec<TirType> = args
+            .iter()
+            .filter_map(|arg| arg.annotation.as_ref())
+            .map(|ann| self.convert_annotation(ann).to_tir_type())
+            .collect();
+        let ret_ty = return_type
+            .map(|ann| self.convert_annotation(ann).to_tir_type())
+            .unwrap_or(TirType::Void);
+
+        let mut errors = ErrorCollector::new();
+        let declared_defaults =
+            collect_defaults(self.symbols, args, &declared_params, name, &mut errors);
+        errors.into_result()?;
+
+        let params = captures
+            .iter()
+            .map(|(_, ty)| ty.clone())
+            .chain(declared_params)
+            .collect();
+        let func_id = self.symbols.alloc_func(params, ret_ty);
+        if let Some(declared_defaults) = declared_defaults {
+            let mut defaults = vec![None; captures.len()];
+            defaults.extend(declared_defaults);
+            self.symbols.func_defaults.insert(func_id, defaults);
+        }
+
+        // Visible from here on, including inside its own body
+        self.nested_functions.insert(
+            name.to_string(),
+            NestedFunction {
+                func_id,
+                captures: captures.iter().map(|(name, _)| name.clone()).collect(),
+            },
+        );
+        self.lifted_functions.push(LiftedFunction {
+            name: name.to_string(),
+            qualified_name: format!("{}.{}", enclosing_name, name),
+            line,
+            func_id,
+            captures,
+            args: args.to_vec(),
+            return_type: return_type.cloned(),
+            body: body.to_vec(),
+            visible: self.nested_functions.clone(),
+        });
+
+        Ok(vec![])
+    }
+
+    /// Determine the enclosing variables a nested function body refers to,
+    /// including those needed to call the nested functions it uses.
+    fn collect_captures(
+        &mut self,
+        name: &str,
+        args: &[Arg],
+        body: &[Stmt],
+    ) -> Result<Vec<(String, TirType)>> {
+        // Parameters and names the body binds itself are its own locals,
+        // unless declared nonlocal; names declared global are never captured
+        let nonlocals = nonlocal_names(body);
+        let assigned = assigned_names(body);
+        let globals = global_names(body);
+        let own_names: HashSet<&str> = args
+            .iter()
+            .map(|arg| arg.name.as_str())
+            .chain(assigned.difference(&nonlocals).map(String::as_str))
+            .chain(globals.iter().map(String::as_str))
+            .collect();
+
+        let mut captured_names: Vec<String> = Vec::new();
+        for referenced in referenced_names(body) {
+            if own_names.contains(referenced.as_str()) {
+                continue;
+            }
+            if let Some(callee) = self.nested_functions.get(&referenced) {
+                for captured in &callee.captures {
+                    if own_names.contains(captured.as_str()) {
+                        return Err(CompilerError::TypeErrorSimple(format!(
+                            "Nested function '{}' calls '{}', which captures '{}', but '{}' is local to '{}'",
+                            name, referenced, captured, captured, name
+                        )));
+                    }
+                    captured_names.push(captured.clone());
+                }
+                continue;
+            }
+            captured_names.push(referenced);
+        }
+
+        let mut seen = HashSet::new();
+        let mut captures = Vec::new();
+        for captured in captured_names {
+            if !seen.insert(captured.clone()) {
+                continue;
+            }
+            // Module globals are accessed directly; unknown names are reported
+            // when the body is lowered
+            let Some((VarRef::Local(_) | VarRef::Param(_) | VarRef::SelfRef, ty)) =
+                self.resolve_var(&captured)
+            else {
+                continue;
+            };
+            if !is_fully_inferred(&ty) {
+                return Err(CompilerError::TypeErrorSimple(format!(
+                    "Nested function '{}' captures '{}' before its type is known",
+                    name, captured
+                )));
+            }
+            captures.push((captured, ty.to_tir_type()));
+        }
+        Ok(captures)
+    }
+
+    /// Lower a call to a nested function, passing its captured variables first.
+    pub(crate) fn lower_nested_call(
+        &mut self,
+        name: &str,
+        nested: &NestedFunction,
+        lowered_args: Vec<TirExprUnresolved>,
+    ) -> Result<TirExprUnresolved> {
+        // Captured cells are passed as is, not their current value
+        let mut call_args = Vec::new();
+        for captured in &nested.captures {
+            let (var_ref, ty) = self
+                .resolve_var(captured)
+                .ok_or_else(|| CompilerError::UndefinedVariable(captured.clone()))?;
+            call_args.push(TirExprUnresolved::new(
+                TirExprKindUnresolved::Var(var_ref),
+                ty,
+            ));
+        }
+        call_args.extend(lowered_args);
+        self.fill_default_args(nested.func_id, &mut call_args)?;
+
+        let capture_count = nested.captures.len();
+        let (param_tys, ret_ty) = self.symbols.get_func_signature(nested.func_id);
+        if call_args.len() != param_tys.len() {
+            return Err(CompilerError::TypeErrorSimple(format!(
+                "Function '{}' expects {} arguments, got {}",
+                name,
+                param_tys.len() - capture_count,
+                call_args.len() - capture_count
+            )));
+        }
+        for (i, (arg, param_ty)) in call_args
+            .iter_mut()
+            .zip(param_tys.iter())
+            .enumerate()
+            .skip(capture_count)
+        {
+            let param_ty_unresolved = TirTypeUnresolved::from_tir_type(param_ty);
+            if !arg.ty.is_compatible_with(&param_ty_unresolved) {
+                return Err(CompilerError::TypeErrorSimple(format!(
+                    "Argument {} to function '{}': expected {:?}, got {:?}",
+                    i - capture_count + 1,
+                    name,
+                    param_ty,
+                    arg.ty
+                )));
+            }
+            arg.coerce_to(&param_ty_unresolved);
+        }
+
+        Ok(TirExprUnresolved::new(
+            TirExprKindUnresolved::Call {
+                func: nested.func_id,
+                args: call_args,
+            },
+            TirTypeUnresolved::from_tir_type(ret_ty),
+        ))
+    }
+}
+
+impl<'a> BodyLowerer<'a> {
+    /// Handle `global names`: assignments to them in this function rebind the
+    /// module variables. A no-op at module level.
+    pub(crate) fn lower_global(&mut self, names: &[String]) -> Result<Vec<TirStmtUnresolved>> {
+        if self.function_name.is_none() {
+            return Ok(vec![]);
+        }
+        for name in names {
+            if self.local_names.contains_key(name) || self.params.contains_key(name) {
+                return Err(CompilerError::TypeErrorSimple(format!(
+                    "Name '{}' is assigned to before global declaration",
+                    name
+                )));
+            }
+            if !self.scope.globals.contains_key(name) {
+                return Err(CompilerError::TypeErrorSimple(format!(
+                    "Global declaration of '{}', which is not a module variable",
+                    name
+                )));
+            }
+            self.declared_globals.insert(name.clone());
+        }
+        Ok(vec![])
+    }
+
+    /// Handle `nonlocal names`: they must be cells captured from an enclosing
+    /// function, so assignments to them are seen there.
+    pub(crate) fn lower_nonlocal(&mut self, names: &[String]) -> Result<Vec<TirStmtUnresolved>> {
+        if self.function_name.is_none() {
+            return Err(CompilerError::TypeErrorSimple(
+                "Nonlocal declaration not allowed at module level".to_string(),
+            ));
+        }
+        for name in names {
+            let is_captured_cell = match self.resolve_var(name) {
+                Some((VarRef::Param(_), ty)) => self.cell_value_type(&ty).is_some(),
+                _ => false,
+            };
+            if !is_captured_cell {
+                return Err(CompilerError::TypeErrorSimple(format!(
+                    "No binding for nonlocal '{}' in an enclosing function (it must be assigned there before this function is defined)",
+                    name
+                )));
+            }
+        }
+        Ok(vec![])
+    }
+
+    /// Whether assigning to `var_ref` from this body rebinds it: module
+    /// variables can only be rebound from a function after a global declaration.
+    pub(crate) fn can_rebind(&self, name: &str, var_ref: &VarRef) -> bool {
+        !matches!(var_ref, VarRef::Global(..))
+            || self.function_name.is_none()
+            || self.declared_globals.contains(name)
+    }
+
+    /// The cell class and value type of a variable holding a cell, if it does
+    pub(crate) fn cell_value_type(
+        &self,
+        ty: &TirTypeUnresolved,
+    ) -> Option<(ClassId, TirTypeUnresolved)> {
+        let class_id = ty.class_id()?;
+        self.symbols
+            .cell_classes
+            .get(&class_id)
+            .map(|value_ty| (class_id, TirTypeUnresolved::from_tir_type(value_ty)))
+    }
+
+    /// Read a variable: the value inside its cell if it holds one.
+    pub(crate) fn load_var(&self, var_ref: VarRef, ty: TirTypeUnresolved) -> TirExprUnresolved {
+        let var = TirExprUnresolved::new(TirExprKindUnresolved::Var(var_ref), ty);
+        match self.cell_value_type(&var.ty) {
+            Some((class_id, value_ty)) => TirExprUnresolved::new(
+                TirExprKindUnresolved::FieldAccess {
+                    object: Box::new(var),
+                    class: class_id,
+                    field: CELL_VALUE_FIELD,
+                },
+                value_ty,
+            ),
+            None => var,
+        }
+    }
+
+    /// Store into the cell held by `var_ref`.
+    pub(crate) fn store_cell(
+        &self,
+        var_ref: VarRef,
+        cell_ty: TirTypeUnresolved,
+        value: TirExprUnresolved,
+    ) -> TirStmtUnresolved {
+        let class_id = cell_ty.class_id().expect("cells are class instances");
+        TirStmtUnresolved::Assign {
+            target: TirLValueUnresolved::Field {
+                object: Box::new(TirExprUnresolved::new(
+                    TirExprKindUnresolved::Var(var_ref),
+                    cell_ty,
+                )),
+                class: class_id,
+                field: CELL_VALUE_FIELD,
+            },
+            value,
+        }
+    }
+
+    /// Bind a new local `name` to a fresh cell holding `value`.
+    pub(crate) fn new_cell(
+        &mut self,
+        name: &str,
+        value: TirExprUnresolved,
+        ty: TirTypeUnresolved,
+    ) -> Result<Vec<TirStmtUnresolved>> {
+        if !is_fully_inferred(&ty) {
+            return Err(CompilerError::TypeErrorSimple(format!(
+                "Nonlocal variable '{}' needs a type annotation",
+                name
+            )));
+        }
+        let class_id = self.symbols.get_or_create_cell_class(&ty.to_tir_type());
+        let cell_ty = TirTypeUnresolved::Class(class_id);
+        let local_id = self.alloc_local(name, cell_ty.clone());
+        Ok(vec![
+            TirStmtUnresolved::Let {
+                local: local_id,
+                ty: cell_ty.clone(),
+                init: TirExprUnresolved::new(
+                    TirExprKindUnresolved::Construct {
+                        class: class_id,
+                        args: vec![],
+                    },
+                    cell_ty.clone(),
+                ),
+            },
+            self.store_cell(VarRef::Local(local_id), cell_ty, value),
+        ])
+    }
+
+    /// Move the parameters that are cell variables into cells, shadowing the
+    /// parameters. Runs before the body.
+    pub(crate) fn box_cell_params(&mut self) -> Result<Vec<TirStmtUnresolved>> {
+        let mut params: Vec<(String, u32)> = self
+            .params
+            .iter()
+            .filter(|(name, _)| self.cell_vars.contains(*name))
+            .map(|(name, &index)| (name.clone(), index))
+            .collect();
+        params.sort_by_key(|(_, index)| *index);
+
+        let mut stmts = Vec::new();
+        for (name, index) in params {
+            let ty = self.param_types[index as usize].clone();
+            let value = TirExprUnresolved::new(
+                TirExprKindUnresolved::Var(VarRef::Param(index)),
+                ty.clone(),
+            );
+            stmts.extend(self.new_cell(&name, value, ty)?);
+        }
+        Ok(stmts)
+    }
+}
+
+/// The variables of a function that nested functions declare `nonlocal`, and
+/// which therefore have to live in cells.
+pub(crate) fn cell_variables(args: &[Arg], body: &[Stmt]) -> BTreeSet<String> {
+    let globals = global_names(body);
+    let nonlocals = nonlocal_names(body);
+    let bound: BTreeSet<String> = args
+        .iter()
+        .map(|arg| arg.name.clone())
+        .chain(assigned_names(body))
+        .filter(|name| !globals.contains(name) && !nonlocals.contains(name))
+        .collect();
+    nested_nonlocal_names(body)
+        .into_iter()
+        .filter(|name| bound.contains(name))
+        .collect()
+}
+
+/// Names declared `nonlocal` by the functions nested in `body`, at any depth
+fn nested_nonlocal_names(body: &[Stmt]) -> BTreeSet<String> {
+    let mut names = BTreeSet::new();
+    for nested_body in nested_function_bodies(body) {
+        names.extend(nonlocal_names(nested_body));
+        names.extend(nested_nonlocal_names(nested_body));
+    }
+    names
+}
+
+/// Whether a type contains no type variables left to infer
+fn is_fully_inferred(ty: &TirTypeUnresolved) -> bool {
+    match ty {
+        TirTypeUnresolved::TypeVar(_) => false,
+        TirTypeUnresolved::Tuple(elems) => elems.iter().all(is_fully_inferred),
+        TirTypeUnresolved::Optional(inner) => is_fully_inferred(inner),
+        _ => true,
+    }
+}
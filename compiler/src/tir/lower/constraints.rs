@@ -18,6 +18,9 @@
 //! 3. **Pass 3**: Apply substitutions and complete lowering
 
 use crate::error::{CompilerError, Result};
+use crate::span::Span;
+use crate::tir::ids::ClassId;
+use crate::tir::types::TirType;
 use crate::tir::types_unresolved::TirTypeUnresolved;
 use std::collections::HashMap;
 use std::fmt;
@@ -28,14 +31,36 @@ use super::symbols::GlobalSymbols;
 #[derive(Debug, Clone)]
 pub enum ConstraintOrigin {
     /// Method call on container
-    MethodCall { method_name: String, line: usize },
+    MethodCall { method_name: String, span: Span },
+    /// Argument passed for a protocol parameter
+    Argument {
+        function: String,
+        position: usize,
+        span: Span,
+    },
+}
+
+impl ConstraintOrigin {
+    /// Where the statement the constraint comes from starts
+    pub fn span(&self) -> Span {
+        match self {
+            ConstraintOrigin::MethodCall { span, .. } | ConstraintOrigin::Argument { span, .. } => {
+                *span
+            }
+        }
+    }
 }
 
 impl fmt::Display for ConstraintOrigin {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            ConstraintOrigin::MethodCall { method_name, line } => {
-                write!(f, "method call '{}' at line {}", method_name, line)
+            ConstraintOrigin::MethodCall { method_name, .. } => {
+                write!(f, "method call '{}'", method_name)
+            }
+            ConstraintOrigin::Argument {
+                function, position, ..
+            } => {
+                write!(f, "argument {} to '{}'", position, function)
             }
         }
     }
@@ -51,6 +76,14 @@ pub enum Constraint {
         element: TirTypeUnresolved,
         origin: ConstraintOrigin,
     },
+    /// Protocol constraint: the class provides every method of the protocol,
+    /// with the same signature
+    /// Generated from passing an argument for a protocol parameter
+    Implements {
+        ty: TirTypeUnresolved,
+        protocol: ClassId,
+        origin: ConstraintOrigin,
+    },
 }
 
 /// Collection of type constraints generated during lowering
@@ -114,7 +147,16 @@ impl<'a> ConstraintSolver<'a> {
                     element,
                     origin,
                 } => {
-                    self.unify_element_type(container, element, origin)?;
+                    self.unify_element_type(container, element, origin)
+                        .map_err(|e| e.at(origin.span()))?;
+                }
+                Constraint::Implements {
+                    ty,
+                    protocol,
+                    origin,
+                } => {
+                    self.check_implements(ty, *protocol, origin)
+                        .map_err(|e| e.at(origin.span()))?;
                 }
             }
         }
@@ -137,6 +179,10 @@ impl<'a> ConstraintSolver<'a> {
             (TirTypeUnresolved::Int, TirTypeUnresolved::Int) => Ok(()),
             (TirTypeUnresolved::Bool, TirTypeUnresolved::Bool) => Ok(()),
             (TirTypeUnresolved::Void, TirTypeUnresolved::Void) => Ok(()),
+            // None is a value of every Optional type
+            (TirTypeUnresolved::NoneType, TirTypeUnresolved::NoneType)
+            | (TirTypeUnresolved::NoneType, TirTypeUnresolved::Optional(_))
+            | (TirTypeUnresolved::Optional(_), TirTypeUnresolved::NoneType) => Ok(()),
             (TirTypeUnresolved::Class(c1), TirTypeUnresolved::Class(c2)) if c1 == c2 => {
                 // Same class - check if type parameters match
                 let type_params1 = self.symbols.get_type_params(*c1);
@@ -157,6 +203,20 @@ impl<'a> ConstraintSolver<'a> {
                 Ok(())
             }
 
+            (TirTypeUnresolved::Tuple(elems1), TirTypeUnresolved::Tuple(elems2))
+                if elems1.len() == elems2.len() =>
+            {
+                // Unify element-wise
+                for (t1, t2) in elems1.iter().zip(elems2.iter()) {
+                    self.unify(t1, t2, origin)?;
+                }
+                Ok(())
+            }
+
+            (TirTypeUnresolved::Optional(inner1), TirTypeUnresolved::Optional(inner2)) => {
+                self.unify(inner1, inner2, origin)
+            }
+
             // TypeVar unification - bind the type variable
             (TirTypeUnresolved::TypeVar(id), t) | (t, TirTypeUnresolved::TypeVar(id)) => {
                 // If both are the same type variable, nothing to do
@@ -224,6 +284,66 @@ impl<'a> ConstraintSolver<'a> {
         }
     }
 
+    /// Check structurally that a class provides the methods of a protocol
+    fn check_implements(
+        &self,
+        ty: &TirTypeUnresolved,
+        protocol: ClassId,
+        origin: &ConstraintOrigin,
+    ) -> Result<()> {
+        let protocol_name = self.symbols.type_name(&TirType::Class(protocol));
+        let TirTypeUnresolved::Class(class_id) = ty.substitute(&self.substitutions) else {
+            return Err(CompilerError::TypeErrorSimple(format!(
+                "Expected a class implementing protocol '{}', got {:?} (at {})",
+                protocol_name, ty, origin
+            )));
+        };
+        let class_name = self.symbols.type_name(&TirType::Class(class_id));
+
+        for (method_name, params, ret_ty) in &self.symbols.protocols[&protocol].methods {
+            let func_id = self
+                .symbols
+                .resolve_method(class_id, method_name)
+                .map(|(_, func_id)| func_id)
+                .filter(|func_id| {
+                    !self.symbols.static_methods.contains(func_id)
+                        && !self.symbols.properties.contains(func_id)
+                });
+            let Some(func_id) = func_id else {
+                return Err(CompilerError::TypeErrorSimple(format!(
+                    "'{}' does not implement protocol '{}': it has no method '{}' (at {})",
+                    class_name, protocol_name, method_name, origin
+                )));
+            };
+            let (class_params, class_ret_ty) = self.symbols.get_func_signature(func_id);
+            if class_params != params || class_ret_ty != ret_ty {
+                return Err(CompilerError::TypeErrorSimple(format!(
+                    "'{}' does not implement protocol '{}': method '{}' has signature {}, expected {} (at {})",
+                    class_name,
+                    protocol_name,
+                    method_name,
+                    self.signature_text(class_params, class_ret_ty),
+                    self.signature_text(params, ret_ty),
+                    origin
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// A method signature as written in Python, without `self`
+    fn signature_text(&self, params: &[TirType], ret_ty: &TirType) -> String {
+        let params: Vec<String> = params
+            .iter()
+            .map(|param| self.symbols.type_name(param))
+            .collect();
+        format!(
+            "({}) -> {}",
+            params.join(", "),
+            self.symbols.type_name(ret_ty)
+        )
+    }
+
     /// Get the final substitutions after solving
     pub fn get_substitutions(&self) -> &HashMap<u32, TirTypeUnresolved> {
         &self.substitutions
@@ -259,7 +379,7 @@ mod tests {
         let mut solver = ConstraintSolver::new(&symbols);
         let origin = ConstraintOrigin::MethodCall {
             method_name: "test".to_string(),
-            line: 1,
+            span: Span::new(1, 1),
         };
 
         // Same types should unify
@@ -282,7 +402,7 @@ mod tests {
         let mut solver = ConstraintSolver::new(&symbols);
         let origin = ConstraintOrigin::MethodCall {
             method_name: "test".to_string(),
-            line: 1,
+            span: Span::new(1, 1),
         };
 
         let t_var = TirTypeUnresolved::TypeVar(0);
@@ -295,6 +415,29 @@ mod tests {
         assert_eq!(solver.substitutions.get(&0), Some(&TirTypeUnresolved::Int));
     }
 
+    #[test]
+    fn test_unify_tuple() {
+        let symbols = GlobalSymbols::new();
+        let mut solver = ConstraintSolver::new(&symbols);
+        let origin = ConstraintOrigin::MethodCall {
+            method_name: "test".to_string(),
+            span: Span::new(1, 1),
+        };
+
+        // tuple[T0, bool] with tuple[int, bool] binds T0 to int
+        let t_tuple =
+            TirTypeUnresolved::Tuple(vec![TirTypeUnresolved::TypeVar(0), TirTypeUnresolved::Bool]);
+        let concrete =
+            TirTypeUnresolved::Tuple(vec![TirTypeUnresolved::Int, TirTypeUnresolved::Bool]);
+        solver.unify(&t_tuple, &concrete, &origin).unwrap();
+        assert_eq!(solver.substitutions.get(&0), Some(&TirTypeUnresolved::Int));
+
+        // Different arities do not unify
+        let pair = TirTypeUnresolved::Tuple(vec![TirTypeUnresolved::Int, TirTypeUnresolved::Int]);
+        let single = TirTypeUnresolved::Tuple(vec![TirTypeUnresolved::Int]);
+        assert!(solver.unify(&pair, &single, &origin).is_err());
+    }
+
     #[test]
     fn test_occurs_check() {
         // TODO: Rewrite this test once we have Class(ClassId) with type_params
@@ -303,7 +446,7 @@ mod tests {
         let mut solver = ConstraintSolver::new(&symbols);
         let origin = ConstraintOrigin::MethodCall {
             method_name: "test".to_string(),
-            line: 1,
+            span: Span::new(1, 1),
         };
 
         let t_var = TirTypeUnresolved::TypeVar(0);
@@ -314,6 +457,54 @@ mod tests {
         assert!(solver.unify(&t_var, &t_var, &origin).is_ok());
     }
 
+    #[test]
+    fn test_check_implements() {
+        use crate::tir::ids::MethodId;
+
+        let mut symbols = GlobalSymbols::new();
+        let protocol = symbols.alloc_class();
+        symbols.class_data[protocol.index()].qualified_name = "test.Sized".to_string();
+        symbols.protocols.insert(
+            protocol,
+            super::super::symbols::Protocol {
+                methods: vec![("size".to_string(), vec![], TirType::Int)],
+            },
+        );
+
+        // A class with `def size(self) -> int` implements it; one whose
+        // `size` returns bool, or has no `size`, does not
+        let mut classes = Vec::new();
+        for ret_ty in [Some(TirType::Int), Some(TirType::Bool), None] {
+            let class_id = symbols.alloc_class();
+            symbols.class_data[class_id.index()].qualified_name = "test.Box".to_string();
+            if let Some(ret_ty) = ret_ty {
+                let func_id = symbols.alloc_func(vec![], ret_ty);
+                symbols
+                    .methods
+                    .insert((class_id, "size".to_string()), (MethodId(0), func_id));
+            }
+            classes.push(class_id);
+        }
+
+        let solver = ConstraintSolver::new(&symbols);
+        let origin = ConstraintOrigin::Argument {
+            function: "test".to_string(),
+            position: 1,
+            span: Span::new(1, 1),
+        };
+        let implements = |class_id| {
+            solver
+                .check_implements(&TirTypeUnresolved::Class(class_id), protocol, &origin)
+                .is_ok()
+        };
+        assert!(implements(classes[0]));
+        assert!(!implements(classes[1]));
+        assert!(!implements(classes[2]));
+        assert!(solver
+            .check_implements(&TirTypeUnresolved::Int, protocol, &origin)
+            .is_err());
+    }
+
     #[test]
     fn test_substitute() {
         let mut substitutions = HashMap::new();
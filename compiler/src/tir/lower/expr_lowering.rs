@@ -1,12 +1,146 @@
-use crate::ast::{BoolOp, Constant, Expr, UnaryOp};
+use crate::ast::{
+    target_path, BinOperator, BoolOp, CompareOp, Constant, Expr, FStringPart, Keyword, Stmt,
+    UnaryOp,
+};
 use crate::error::{CompilerError, Result};
+use crate::span::Span;
 use crate::tir::expr::VarRef;
 use crate::tir::expr_unresolved::{TirExprKindUnresolved, TirExprUnresolved};
+use crate::tir::ids::{ClassId, FuncId};
+use crate::tir::stmt_unresolved::TirStmtUnresolved;
 use crate::tir::types_unresolved::TirTypeUnresolved;
 
 use super::body_lowerer::BodyLowerer;
+use super::stmt_lowering::ComprehensionElt;
 
 impl<'a> BodyLowerer<'a> {
+    /// Lower an expression whose type is already known from context (an annotation
+    /// or the variable being assigned). Empty `[]`, `{}`, `set()` and `frozenset()`
+    /// take their element types from `expected`, also inside tuple literals;
+    /// everything else lowers as usual.
+    pub(crate) fn lower_expr_expecting(
+        &mut self,
+        expr: &Expr,
+        expected: Option<&TirTypeUnresolved>,
+    ) -> Result<TirExprUnresolved> {
+        // Tuple literals pass each element its own expected type
+        if let (Expr::Tuple { elts }, Some(TirTypeUnresolved::Tuple(elem_types))) = (expr, expected)
+        {
+            if elts.len() == elem_types.len() {
+                let mut elements = Vec::new();
+                for (elt, elem_ty) in elts.iter().zip(elem_types) {
+                    elements.push(self.lower_expr_expecting(elt, Some(elem_ty))?);
+                }
+                let actual_types = elements.iter().map(|e| e.ty.clone()).collect();
+                return Ok(TirExprUnresolved::new(
+                    TirExprKindUnresolved::Tuple { elements },
+                    TirTypeUnresolved::Tuple(actual_types),
+                ));
+            }
+        }
+
+        // A value expected to be Optional is lowered as its value type, then wrapped
+        if let Some(optional @ TirTypeUnresolved::Optional(inner)) = expected {
+            let mut value = self.lower_expr_expecting(expr, Some(inner.as_ref()))?;
+            value.coerce_to(optional);
+            return Ok(value);
+        }
+
+        let Some(TirTypeUnresolved::Class(class_id)) = expected else {
+            return self.lower_expr(expr);
+        };
+        let qualified_name = self.symbols.class_data[class_id.index()]
+            .qualified_name
+            .clone();
+        let type_params = self.symbols.get_type_params(*class_id);
+
+        // A generic class called where one of its instances is expected
+        // constructs that instance
+        if let Some(instance) = self.symbols.generic_instances.get(class_id) {
+            if let Expr::Call { func, .. } = expr {
+                if let Expr::Name(name) = func.as_ref() {
+                    if self.class_named(name) == Some(instance.template) {
+                        self.generic_target = Some(*class_id);
+                        return self.lower_expr(expr);
+                    }
+                }
+            }
+        }
+
+        // The items of a list of Optionals may be values or None
+        if let (Expr::List { elts }, "__builtin__.list") = (expr, qualified_name.as_str()) {
+            if let [elem_ty @ TirTypeUnresolved::Optional(_)] = type_params.as_slice() {
+                let mut elements = Vec::new();
+                for elt in elts {
+                    elements.push(self.lower_expr_expecting(elt, Some(elem_ty))?);
+                }
+                if !elements.is_empty() {
+                    return self.lower_list_of(elements);
+                }
+            }
+        }
+
+        let kind = match (expr, qualified_name.as_str()) {
+            (Expr::List { elts }, "__builtin__.list") if elts.is_empty() => {
+                TirExprKindUnresolved::List {
+                    elements: vec![],
+                    elem_ty: type_params[0].clone(),
+                }
+            }
+            (Expr::Dict { keys, .. }, "__builtin__.dict") if keys.is_empty() => {
+                TirExprKindUnresolved::Dict {
+                    entries: vec![],
+                    key_ty: type_params[0].clone(),
+                    value_ty: type_params[1].clone(),
+                }
+            }
+            (
+                Expr::Call {
+                    func,
+                    args,
+                    keywords,
+                },
+                "__builtin__.set" | "__builtin__.frozenset",
+            ) if args.is_empty()
+                && keywords.is_empty()
+                && matches!(func.as_ref(), Expr::Name(name)
+                        if qualified_name.strip_prefix("__builtin__.") == Some(name.as_str())) =>
+            {
+                TirExprKindUnresolved::Construct {
+                    class: *class_id,
+                    args: vec![],
+                }
+            }
+            _ => return self.lower_expr(expr),
+        };
+        Ok(TirExprUnresolved::new(
+            kind,
+            TirTypeUnresolved::Class(*class_id),
+        ))
+    }
+
+    /// Reject dict keys and set elements whose type is mutable (and therefore unhashable in Python)
+    pub(crate) fn check_hashable(&self, key_ty: &TirTypeUnresolved) -> Result<()> {
+        if let Some(class_id) = key_ty.class_id() {
+            let qualified_name = &self.symbols.class_data[class_id.index()].qualified_name;
+            if let Some(name @ ("list" | "dict" | "set" | "bytearray")) =
+                qualified_name.strip_prefix("__builtin__.")
+            {
+                return Err(CompilerError::TypeErrorSimple(format!(
+                    "unhashable type: '{}'",
+                    name
+                )));
+            }
+        }
+        // A tuple is hashable when all of its elements are
+        if let TirTypeUnresolved::Tuple(elems) = key_ty {
+            for elem in elems {
+                self.check_hashable(elem)?;
+            }
+        }
+        Ok(())
+    }
+
     pub(crate) fn lower_expr(&mut self, expr: &Expr) -> Result<TirExprUnresolved> {
         match expr {
             Expr::Constant(c) => {
@@ -29,7 +163,7 @@ impl<'a> BodyLowerer<'a> {
                             }
                             Constant::Bool(_) => TirTypeUnresolved::Bool,
                             Constant::Bytes(_) => unreachable!(),
-                            Constant::None => TirTypeUnresolved::Void,
+                            Constant::None => TirTypeUnresolved::NoneType,
                         };
                         Ok(TirExprUnresolved::new(
                             TirExprKindUnresolved::Constant(c.clone()),
@@ -42,20 +176,21 @@ impl<'a> BodyLowerer<'a> {
             Expr::Name(name) => {
                 // Try to resolve as variable
                 if let Some((var_ref, ty)) = self.resolve_var(name) {
-                    return Ok(TirExprUnresolved::new(
-                        TirExprKindUnresolved::Var(var_ref),
-                        ty,
-                    ));
+                    let value = self.load_var(var_ref, ty);
+                    return Ok(self.narrowed(Some(name.clone()), value));
+                }
+
+                // A name imported from an intrinsic module (from sys import argv)
+                if let Some((module, member)) = self.intrinsic_name(name) {
+                    return self.lower_intrinsic_value(module, &member);
                 }
 
                 // Not a variable - might be a function or class reference
                 // These are handled in Call expressions
-                Err(CompilerError::UndefinedVariable(name.clone()))
+                Err(self.undefined_variable(name))
             }
 
             Expr::BinOp { left, op, right } => {
-                use crate::ast::BinOperator;
-
                 let left_expr = self.lower_expr(left)?;
                 let right_expr = self.lower_expr(right)?;
 
@@ -78,6 +213,38 @@ impl<'a> BodyLowerer<'a> {
                     }
                 }
 
+                // Operators on class instances dispatch to their dunder methods
+                // (e.g. set | set -> set.__or__), falling back to the right
+                // operand's reflected method (2 * v -> v.__rmul__(2))
+                let dunder = binop_dunder(*op);
+                if let Some(class_id) = left_expr.ty.class_id() {
+                    if self.symbols.resolve_method(class_id, dunder).is_some() {
+                        return call_dunder_method!(
+                            self.symbols,
+                            &left_expr.ty,
+                            dunder,
+                            vec![left_expr, right_expr]
+                        );
+                    }
+                }
+                if let Some(class_id) = right_expr.ty.class_id() {
+                    let reflected = format!("__r{}", &dunder[2..]);
+                    if self.symbols.resolve_method(class_id, &reflected).is_some() {
+                        return self.call_reflected(&reflected, left_expr, right_expr);
+                    }
+                }
+                if let Some(class_id) = left_expr.ty.class_id().or(right_expr.ty.class_id()) {
+                    if self.is_user_class(class_id) {
+                        return Err(CompilerError::TypeErrorSimple(format!(
+                            "Unsupported operand types for {}: {:?} and {:?} (define {})",
+                            binop_symbol(*op),
+                            left_expr.ty,
+                            right_expr.ty,
+                            dunder
+                        )));
+                    }
+                }
+
                 // Check that both operands are numeric
                 if !left_expr.ty.is_numeric() {
                     return Err(CompilerError::TypeErrorSimple(format!(
@@ -129,23 +296,7 @@ impl<'a> BodyLowerer<'a> {
                 if ops.len() == 1 && comparators.len() == 1 {
                     // Single comparison
                     let right_expr = self.lower_expr(&comparators[0])?;
-
-                    // Check that operands are compatible for comparison
-                    if !left_expr.ty.is_compatible_with(&right_expr.ty) {
-                        return Err(CompilerError::TypeErrorSimple(format!(
-                            "Cannot compare {:?} with {:?}",
-                            left_expr.ty, right_expr.ty
-                        )));
-                    }
-
-                    Ok(TirExprUnresolved::new(
-                        TirExprKindUnresolved::Compare {
-                            left: Box::new(left_expr),
-                            op: ops[0],
-                            right: Box::new(right_expr),
-                        },
-                        TirTypeUnresolved::Bool,
-                    ))
+                    self.lower_comparison(left_expr, ops[0], right_expr)
                 } else {
                     // Chained comparison - desugar to AND of comparisons
                     // a < b < c  =>  (a < b) and (b < c)
@@ -156,24 +307,7 @@ impl<'a> BodyLowerer<'a> {
 
                     for (op, comp) in ops.iter().zip(comparators.iter()) {
                         let right_expr = self.lower_expr(comp)?;
-
-                        // Check that operands are compatible
-                        if !current_left.ty.is_compatible_with(&right_expr.ty) {
-                            return Err(CompilerError::TypeErrorSimple(format!(
-                                "Cannot compare {:?} with {:?}",
-                                current_left.ty, right_expr.ty
-                            )));
-                        }
-
-                        let cmp = TirExprUnresolved::new(
-                            TirExprKindUnresolved::Compare {
-                                left: Box::new(current_left),
-                                op: *op,
-                                right: Box::new(right_expr.clone()),
-                            },
-                            TirTypeUnresolved::Bool,
-                        );
-
+                        let cmp = self.lower_comparison(current_left, *op, right_expr.clone())?;
                         comparisons.push(cmp);
 
                         // For next iteration, use the right side as the new left
@@ -191,62 +325,150 @@ impl<'a> BodyLowerer<'a> {
                 }
             }
 
-            Expr::Call { func, args } => self.lower_call(func, args),
+            Expr::Call {
+                func,
+                args,
+                keywords,
+            } => self.lower_call(func, args, keywords),
 
             Expr::List { elts } => {
-                if elts.is_empty() {
-                    // Empty list: create list[TypeVar] with a fresh type variable
-                    // The element type will be inferred from usage (e.g., append calls)
-                    let elem_type_var = self.constraints.fresh_type_var();
+                let mut elements = Vec::new();
+                for elt in elts {
+                    elements.push(self.lower_expr(elt)?);
+                }
+                self.lower_list_of(elements)
+            }
 
-                    // Get or create list class with this type parameter
-                    // Convert TirTypeUnresolved to TirType for the symbols API
-                    let elem_type_var_tir = elem_type_var.to_tir_type();
-                    let list_class_id = self.symbols.get_or_create_list_class(&elem_type_var_tir);
+            Expr::Dict { keys, values } => {
+                if keys.is_empty() {
+                    // An empty dict gets its key/value types from an annotation
+                    // (see lower_expr_expecting); there is nothing to infer them from here
+                    return Err(CompilerError::TypeErrorSimple(
+                        "Cannot infer the type of an empty dict; annotate it, e.g. d: dict[str, int] = {}"
+                            .to_string(),
+                    ));
+                }
 
-                    // Note: get_or_create_list_class already sets type_params via ClassKey
+                // Infer key and value types from the first entry
+                let mut entries = Vec::new();
+                let mut key_ty = TirTypeUnresolved::Int;
+                let mut value_ty = TirTypeUnresolved::Int;
+
+                for (i, (key, value)) in keys.iter().zip(values).enumerate() {
+                    let key_expr = self.lower_expr(key)?;
+                    let value_expr = self.lower_expr(value)?;
+                    if i == 0 {
+                        key_ty = key_expr.ty.clone();
+                        value_ty = value_expr.ty.clone();
+                    } else if !key_expr.ty.is_compatible_with(&key_ty) {
+                        return Err(CompilerError::TypeErrorSimple(format!(
+                            "Dict key type mismatch at entry {}: expected {:?}, got {:?}",
+                            i, key_ty, key_expr.ty
+                        )));
+                    } else if !value_expr.ty.is_compatible_with(&value_ty) {
+                        return Err(CompilerError::TypeErrorSimple(format!(
+                            "Dict value type mismatch at entry {}: expected {:?}, got {:?}",
+                            i, value_ty, value_expr.ty
+                        )));
+                    }
+                    entries.push((key_expr, value_expr));
+                }
 
-                    // Create empty list literal
-                    Ok(TirExprUnresolved::new(
-                        TirExprKindUnresolved::List {
-                            elements: vec![],
-                            elem_ty: elem_type_var,
-                        },
-                        TirTypeUnresolved::Class(list_class_id),
-                    ))
-                } else {
-                    // Non-empty list: infer element type from first element
-                    let mut elements = Vec::new();
-                    let mut elem_ty = TirTypeUnresolved::Int; // Will be overwritten by first element
-
-                    for (i, elt) in elts.iter().enumerate() {
-                        let elt_expr = self.lower_expr(elt)?;
-                        if i == 0 {
-                            elem_ty = elt_expr.ty.clone();
-                        } else if !elt_expr.ty.is_compatible_with(&elem_ty) {
-                            return Err(CompilerError::TypeErrorSimple(format!(
-                                "List element type mismatch at index {}: expected {:?}, got {:?}",
-                                i, elem_ty, elt_expr.ty
-                            )));
-                        }
-                        elements.push(elt_expr);
+                self.check_hashable(&key_ty)?;
+                let dict_class_id = self
+                    .symbols
+                    .get_or_create_dict_class(&key_ty.to_tir_type(), &value_ty.to_tir_type());
+                Ok(TirExprUnresolved::new(
+                    TirExprKindUnresolved::Dict {
+                        entries,
+                        key_ty,
+                        value_ty,
+                    },
+                    TirTypeUnresolved::Class(dict_class_id),
+                ))
+            }
+
+            Expr::Set { elts } => {
+                // Set literals are never empty ({} is a dict); infer from the first element
+                let mut elements = Vec::new();
+                let mut elem_ty = TirTypeUnresolved::Int;
+
+                for (i, elt) in elts.iter().enumerate() {
+                    let elt_expr = self.lower_expr(elt)?;
+                    if i == 0 {
+                        elem_ty = elt_expr.ty.clone();
+                    } else if !elt_expr.ty.is_compatible_with(&elem_ty) {
+                        return Err(CompilerError::TypeErrorSimple(format!(
+                            "Set element type mismatch at index {}: expected {:?}, got {:?}",
+                            i, elem_ty, elt_expr.ty
+                        )));
                     }
+                    elements.push(elt_expr);
+                }
 
-                    let list_class_id = self
-                        .symbols
-                        .get_or_create_list_class(&elem_ty.to_tir_type());
-                    Ok(TirExprUnresolved::new(
-                        TirExprKindUnresolved::List {
-                            elements,
-                            elem_ty: elem_ty.clone(),
-                        },
-                        TirTypeUnresolved::Class(list_class_id),
-                    ))
+                self.check_hashable(&elem_ty)?;
+                let set_class_id = self.symbols.get_or_create_set_class(&elem_ty.to_tir_type());
+                Ok(TirExprUnresolved::new(
+                    TirExprKindUnresolved::Set { elements, elem_ty },
+                    TirTypeUnresolved::Class(set_class_id),
+                ))
+            }
+
+            Expr::Tuple { elts } => {
+                let mut elements = Vec::new();
+                for elt in elts {
+                    elements.push(self.lower_expr(elt)?);
                 }
+                let elem_types = elements.iter().map(|e| e.ty.clone()).collect();
+                Ok(TirExprUnresolved::new(
+                    TirExprKindUnresolved::Tuple { elements },
+                    TirTypeUnresolved::Tuple(elem_types),
+                ))
             }
 
             Expr::Subscript { value, index } => {
                 let container_expr = self.lower_expr(value)?;
+
+                if let Expr::Slice { lower, upper, step } = index.as_ref() {
+                    return self.lower_slice(container_expr, lower, upper, step);
+                }
+
+                // Tuple elements have different types, so the index must be known statically
+                if let TirTypeUnresolved::Tuple(elem_types) = &container_expr.ty {
+                    let index = match index.as_ref() {
+                        Expr::Constant(Constant::Int(n)) => Some(*n),
+                        Expr::UnaryOp {
+                            op: UnaryOp::USub,
+                            operand,
+                        } => match operand.as_ref() {
+                            Expr::Constant(Constant::Int(n)) => Some(-*n),
+                            _ => None,
+                        },
+                        _ => None,
+                    }
+                    .ok_or_else(|| {
+                        CompilerError::TypeErrorSimple(
+                            "Tuple index must be an integer constant".to_string(),
+                        )
+                    })?;
+                    let len = elem_types.len() as i64;
+                    let normalized = if index < 0 { index + len } else { index };
+                    if normalized < 0 || normalized >= len {
+                        return Err(CompilerError::TypeErrorSimple(format!(
+                            "tuple index {} out of range for a tuple of length {}",
+                            index, len
+                        )));
+                    }
+                    let elem_ty = elem_types[normalized as usize].clone();
+                    return Ok(TirExprUnresolved::new(
+                        TirExprKindUnresolved::TupleGet {
+                            tuple: Box::new(container_expr),
+                            index: normalized as usize,
+                        },
+                        elem_ty,
+                    ));
+                }
+
                 let index_expr = self.lower_expr(index)?;
 
                 // Look up __getitem__ method and convert to a Call
@@ -258,12 +480,75 @@ impl<'a> BodyLowerer<'a> {
                 )
             }
 
-            Expr::Attribute { value, attr } => self.lower_attribute(value, attr),
+            Expr::Slice { .. } => Err(CompilerError::UnsupportedFeature(
+                "Slices are only supported as subscripts (e.g., s[1:5])".to_string(),
+            )),
+
+            Expr::Starred { .. } => Err(CompilerError::UnsupportedFeature(
+                "Starred expressions are only supported as unpacking targets and call arguments"
+                    .to_string(),
+            )),
+
+            Expr::Attribute { value, attr } => {
+                let field = self.lower_attribute(value, attr)?;
+                Ok(self.narrowed(target_path(expr), field))
+            }
+
+            Expr::ListComp { elt, generators } => {
+                self.lower_comprehension(ComprehensionElt::List(elt), generators)
+            }
+
+            Expr::SetComp { elt, generators } => {
+                self.lower_comprehension(ComprehensionElt::Set(elt), generators)
+            }
+
+            Expr::DictComp {
+                key,
+                value,
+                generators,
+            } => self.lower_comprehension(ComprehensionElt::Dict(key, value), generators),
+
+            Expr::FString { parts } => {
+                let str_type = TirTypeUnresolved::Class(self.symbols.get_or_create_str_class());
+                let mut result: Option<TirExprUnresolved> = None;
+                for part in parts {
+                    let piece = match part {
+                        FStringPart::Literal(text) => TirExprUnresolved::new(
+                            TirExprKindUnresolved::Constant(Constant::Str(text.clone())),
+                            str_type.clone(),
+                        ),
+                        FStringPart::Value { value, repr } => {
+                            let lowered = self.lower_expr(value)?;
+                            self.lower_str_conversion(lowered, *repr)?
+                        }
+                    };
+                    // Concatenate left to right with str.__add__
+                    result = Some(match result {
+                        None => piece,
+                        Some(left) => TirExprUnresolved::new(
+                            TirExprKindUnresolved::BinOp {
+                                left: Box::new(left),
+                                op: BinOperator::Add,
+                                right: Box::new(piece),
+                            },
+                            str_type.clone(),
+                        ),
+                    });
+                }
+                Ok(result.unwrap_or_else(|| {
+                    TirExprUnresolved::new(
+                        TirExprKindUnresolved::Constant(Constant::Str(String::new())),
+                        str_type,
+                    )
+                }))
+            }
 
             Expr::BoolOp { op, values } => {
                 let mut lowered_values = Vec::new();
-                for val in values {
-                    let lowered = self.lower_expr(val)?;
+                for mut lowered in self.lower_bool_operands(*op, values)? {
+                    if lowered.ty.optional_inner().is_some() {
+                        lowered = self.lower_optional_bool(lowered)?;
+                    }
                     // Accept both Bool and Int (for truthiness)
                     if !lowered.ty.is_boolean() && !lowered.ty.is_numeric() {
                         return Err(CompilerError::TypeErrorSimple(format!(
@@ -284,10 +569,15 @@ impl<'a> BodyLowerer<'a> {
             }
 
             Expr::UnaryOp { op, operand } => {
-                let operand_expr = self.lower_expr(operand)?;
+                let mut operand_expr = self.lower_expr(operand)?;
 
                 match op {
                     UnaryOp::Not => {
+                        if operand_expr.ty.optional_inner().is_some() {
+                            operand_expr = self.lower_optional_bool(operand_expr)?;
+                        } else if operand_expr.ty == TirTypeUnresolved::NoneType {
+                            operand_expr = self.lower_bool_conversion(operand_expr)?;
+                        }
                         // not requires boolean or numeric (truthy)
                         if !operand_expr.ty.is_boolean() && !operand_expr.ty.is_numeric() {
                             return Err(CompilerError::TypeErrorSimple(format!(
@@ -304,6 +594,17 @@ impl<'a> BodyLowerer<'a> {
                         ))
                     }
                     UnaryOp::USub => {
+                        if let Some(class_id) = operand_expr.ty.class_id() {
+                            if self.symbols.resolve_method(class_id, "__neg__").is_some() {
+                                let ty = operand_expr.ty.clone();
+                                return call_dunder_method!(
+                                    self.symbols,
+                                    &ty,
+                                    "__neg__",
+                                    vec![operand_expr]
+                                );
+                            }
+                        }
                         if !operand_expr.ty.is_numeric() {
                             return Err(CompilerError::TypeErrorSimple(format!(
                                 "Unary minus requires numeric operand, got {:?}",
@@ -325,12 +626,1028 @@ impl<'a> BodyLowerer<'a> {
         }
     }
 
-    fn lower_call(&mut self, func: &Expr, args: &[Expr]) -> Result<TirExprUnresolved> {
+    /// Lower a `str()`, `int()`, `float()` or `bool()` call. Primitives convert
+    /// directly (parsing a str goes through the runtime, which raises ValueError),
+    /// classes use `__int__`/`__float__`/`__bool__`.
+    fn lower_conversion_call(
+        &mut self,
+        name: &str,
+        args: Vec<TirExprUnresolved>,
+    ) -> Result<TirExprUnresolved> {
+        let max_args = if name == "int" { 2 } else { 1 };
+        if args.len() > max_args {
+            return Err(CompilerError::TypeErrorSimple(format!(
+                "{}() takes at most {} argument{}",
+                name,
+                max_args,
+                if max_args == 1 { "" } else { "s" }
+            )));
+        }
+
+        let mut args = args.into_iter();
+        let Some(value) = args.next() else {
+            // The zero-argument forms produce the type's default value
+            let str_class_id = self.symbols.get_or_create_str_class();
+            let (constant, ty) = match name {
+                "str" => (
+                    Constant::Str(String::new()),
+                    TirTypeUnresolved::Class(str_class_id),
+                ),
+                "int" => (Constant::Int(0), TirTypeUnresolved::Int),
+                "float" => (Constant::Float(0.0), TirTypeUnresolved::Float),
+                _ => (Constant::Bool(false), TirTypeUnresolved::Bool),
+            };
+            return Ok(TirExprUnresolved::new(
+                TirExprKindUnresolved::Constant(constant),
+                ty,
+            ));
+        };
+        let base = args.next();
+
+        match name {
+            "str" => self.lower_str_conversion(value, false),
+            "int" => self.lower_int_conversion(value, base),
+            "float" => self.lower_float_conversion(value),
+            _ => self.lower_bool_conversion(value),
+        }
+    }
+
+    /// Lower `int(value)` or `int(value, base)`; a base is only accepted for str
+    fn lower_int_conversion(
+        &mut self,
+        value: TirExprUnresolved,
+        base: Option<TirExprUnresolved>,
+    ) -> Result<TirExprUnresolved> {
+        let str_class_id = self.symbols.get_or_create_str_class();
+        let is_str = value.ty == TirTypeUnresolved::Class(str_class_id);
+        if base.is_some() && !is_str {
+            return Err(CompilerError::TypeErrorSimple(
+                "int() can't convert non-string with explicit base".to_string(),
+            ));
+        }
+
+        let (func, args) = match &value.ty {
+            TirTypeUnresolved::Int => return Ok(value),
+            TirTypeUnresolved::Bool => (self.symbols.get_int_from_bool_func(), vec![value]),
+            TirTypeUnresolved::Float => (self.symbols.get_int_from_float_func(), vec![value]),
+            TirTypeUnresolved::Class(_) if is_str => {
+                let base = base.unwrap_or_else(|| {
+                    TirExprUnresolved::new(
+                        TirExprKindUnresolved::Constant(Constant::Int(10)),
+                        TirTypeUnresolved::Int,
+                    )
+                });
+                if base.ty != TirTypeUnresolved::Int {
+                    return Err(CompilerError::TypeErrorSimple(format!(
+                        "int() base must be int, got {:?}",
+                        base.ty
+                    )));
+                }
+                (self.symbols.get_int_from_str_func(), vec![value, base])
+            }
+            TirTypeUnresolved::Class(_) => {
+                let ty = value.ty.clone();
+                let call = call_dunder_method!(self.symbols, &ty, "__int__", vec![value])?;
+                if call.ty != TirTypeUnresolved::Int {
+                    return Err(CompilerError::TypeErrorSimple(format!(
+                        "__int__ returned non-int (type {:?})",
+                        call.ty
+                    )));
+                }
+                return Ok(call);
+            }
+            _ => {
+                return Err(CompilerError::TypeErrorSimple(format!(
+                    "int() argument must be a string or a number, not {:?}",
+                    value.ty
+                )))
+            }
+        };
+
+        Ok(TirExprUnresolved::new(
+            TirExprKindUnresolved::Call { func, args },
+            TirTypeUnresolved::Int,
+        ))
+    }
+
+    /// Lower `hex(value)`, `oct(value)` or `bin(value)`. Classes are converted
+    /// with `__index__`, as in Python.
+    fn lower_radix_call(
+        &mut self,
+        name: &str,
+        args: Vec<TirExprUnresolved>,
+    ) -> Result<TirExprUnresolved> {
+        if args.len() != 1 {
+            return Err(CompilerError::TypeErrorSimple(format!(
+                "{}() takes exactly one argument ({} given)",
+                name,
+                args.len()
+            )));
+        }
+        let value = args.into_iter().next().unwrap();
+        let value = match &value.ty {
+            TirTypeUnresolved::Int => value,
+            TirTypeUnresolved::Bool => TirExprUnresolved::new(
+                TirExprKindUnresolved::Call {
+                    func: self.symbols.get_int_from_bool_func(),
+                    args: vec![value],
+                },
+                TirTypeUnresolved::Int,
+            ),
+            TirTypeUnresolved::Class(class_id)
+                if self
+                    .symbols
+                    .resolve_method(*class_id, "__index__")
+                    .is_some() =>
+            {
+                let ty = value.ty.clone();
+                let call = call_dunder_method!(self.symbols, &ty, "__index__", vec![value])?;
+                if call.ty != TirTypeUnresolved::Int {
+                    return Err(CompilerError::TypeErrorSimple(format!(
+                        "__index__ returned non-int (type {:?})",
+                        call.ty
+                    )));
+                }
+                call
+            }
+            _ => {
+                return Err(CompilerError::TypeErrorSimple(format!(
+                    "{}() argument must be an integer, not {:?}",
+                    name, value.ty
+                )))
+            }
+        };
+        let base = match name {
+            "hex" => 16,
+            "oct" => 8,
+            _ => 2,
+        };
+
+        let str_class_id = self.symbols.get_or_create_str_class();
+        Ok(TirExprUnresolved::new(
+            TirExprKindUnresolved::Call {
+                func: self.symbols.get_int_to_str_radix_func(),
+                args: vec![
+                    value,
+                    TirExprUnresolved::new(
+                        TirExprKindUnresolved::Constant(Constant::Int(base)),
+                        TirTypeUnresolved::Int,
+                    ),
+                ],
+            },
+            TirTypeUnresolved::Class(str_class_id),
+        ))
+    }
+
+    /// Lower `divmod(a, b)` to `(a // b, a % b)`, evaluating each operand
+    /// once. Class instances use `__divmod__`.
+    fn lower_divmod(&mut self, args: Vec<TirExprUnresolved>) -> Result<TirExprUnresolved> {
+        let Ok([left, right]) = <[TirExprUnresolved; 2]>::try_from(args) else {
+            return Err(CompilerError::TypeErrorSimple(
+                "divmod() takes exactly two arguments".to_string(),
+            ));
+        };
+        if let Some(class_id) = left.ty.class_id() {
+            if self
+                .symbols
+                .resolve_method(class_id, "__divmod__")
+                .is_some()
+            {
+                let ty = left.ty.clone();
+                return call_dunder_method!(self.symbols, &ty, "__divmod__", vec![left, right]);
+            }
+        }
+        if !left.ty.is_numeric() || !right.ty.is_numeric() {
+            return Err(CompilerError::TypeErrorSimple(format!(
+                "Unsupported operand types for divmod(): {:?} and {:?}",
+                left.ty, right.ty
+            )));
+        }
+
+        let result_ty =
+            if left.ty == TirTypeUnresolved::Float || right.ty == TirTypeUnresolved::Float {
+                TirTypeUnresolved::Float
+            } else {
+                TirTypeUnresolved::Int
+            };
+        let mut stmts = Vec::new();
+        let mut operands = Vec::new();
+        for (name, operand) in [("_divmod_a", left), ("_divmod_b", right)] {
+            let ty = operand.ty.clone();
+            let local = self.alloc_local(&format!("{}_{}", name, self.next_local_id), ty.clone());
+            stmts.push(TirStmtUnresolved::Let {
+                local,
+                ty: ty.clone(),
+                init: operand,
+            });
+            operands.push(TirExprUnresolved::new(
+                TirExprKindUnresolved::Var(VarRef::Local(local)),
+                ty,
+            ));
+        }
+        let elements: Vec<TirExprUnresolved> = [BinOperator::FloorDiv, BinOperator::Mod]
+            .into_iter()
+            .map(|op| {
+                TirExprUnresolved::new(
+                    TirExprKindUnresolved::BinOp {
+                        left: Box::new(operands[0].clone()),
+                        op,
+                        right: Box::new(operands[1].clone()),
+                    },
+                    result_ty.clone(),
+                )
+            })
+            .collect();
+        let tuple_ty = TirTypeUnresolved::Tuple(vec![result_ty.clone(), result_ty]);
+        Ok(TirExprUnresolved::new(
+            TirExprKindUnresolved::Block {
+                stmts,
+                result: Box::new(TirExprUnresolved::new(
+                    TirExprKindUnresolved::Tuple { elements },
+                    tuple_ty.clone(),
+                )),
+            },
+            tuple_ty,
+        ))
+    }
+
+    /// Lower `round(x)` or `round(x, ndigits)`. Without `ndigits` the result
+    /// is an int; with it, the type of `x`. Class instances use `__round__`.
+    fn lower_round(&mut self, args: Vec<TirExprUnresolved>) -> Result<TirExprUnresolved> {
+        if args.is_empty() || args.len() > 2 {
+            return Err(CompilerError::TypeErrorSimple(format!(
+                "round() takes 1 or 2 arguments ({} given)",
+                args.len()
+            )));
+        }
+        let mut args = args.into_iter();
+        let value = args.next().unwrap();
+        let ndigits = args.next();
+        if let Some(ndigits) = &ndigits {
+            if !matches!(ndigits.ty, TirTypeUnresolved::Int | TirTypeUnresolved::Bool) {
+                return Err(CompilerError::TypeErrorSimple(format!(
+                    "round() ndigits must be an integer, not {:?}",
+                    ndigits.ty
+                )));
+            }
+        }
+
+        match &value.ty {
+            TirTypeUnresolved::Int | TirTypeUnresolved::Bool => {
+                let value = self.lower_int_conversion(value, None)?;
+                let Some(ndigits) = ndigits else {
+                    return Ok(value);
+                };
+                let ndigits = self.lower_int_conversion(ndigits, None)?;
+                Ok(TirExprUnresolved::new(
+                    TirExprKindUnresolved::Call {
+                        func: self.symbols.get_int_round_func(),
+                        args: vec![value, ndigits],
+                    },
+                    TirTypeUnresolved::Int,
+                ))
+            }
+            TirTypeUnresolved::Float => {
+                let (ndigits, to_int) = match ndigits {
+                    Some(ndigits) => (self.lower_int_conversion(ndigits, None)?, false),
+                    None => (
+                        TirExprUnresolved::new(
+                            TirExprKindUnresolved::Constant(Constant::Int(0)),
+                            TirTypeUnresolved::Int,
+                        ),
+                        true,
+                    ),
+                };
+                let rounded = TirExprUnresolved::new(
+                    TirExprKindUnresolved::Call {
+                        func: self.symbols.get_float_round_func(),
+                        args: vec![value, ndigits],
+                    },
+                    TirTypeUnresolved::Float,
+                );
+                if to_int {
+                    self.lower_int_conversion(rounded, None)
+                } else {
+                    Ok(rounded)
+                }
+            }
+            TirTypeUnresolved::Class(class_id)
+                if self
+                    .symbols
+                    .resolve_method(*class_id, "__round__")
+                    .is_some() =>
+            {
+                let ty = value.ty.clone();
+                let mut args = vec![value];
+                args.extend(ndigits);
+                call_dunder_method!(self.symbols, &ty, "__round__", args)
+            }
+            _ => Err(CompilerError::TypeErrorSimple(format!(
+                "type {:?} doesn't define __round__ method",
+                value.ty
+            ))),
+        }
+    }
+
+    /// Lower a method call on an int, float or bool: the conversion dunders,
+    /// `__round__` and `is_integer()`
+    fn lower_number_method(
+        &mut self,
+        receiver: TirExprUnresolved,
+        attr: &str,
+        mut args: Vec<TirExprUnresolved>,
+    ) -> Result<TirExprUnresolved> {
+        let expected_args = match attr {
+            "__round__" => 0..=1,
+            _ => 0..=0,
+        };
+        if !expected_args.contains(&args.len()) {
+            return Err(CompilerError::TypeErrorSimple(format!(
+                "{}() takes {} arguments ({} given)",
+                attr,
+                expected_args.end(),
+                args.len()
+            )));
+        }
+
+        match attr {
+            "__int__" => self.lower_int_conversion(receiver, None),
+            "__float__" => self.lower_float_conversion(receiver),
+            "__round__" => {
+                args.insert(0, receiver);
+                self.lower_round(args)
+            }
+            "is_integer" if receiver.ty == TirTypeUnresolved::Float => Ok(TirExprUnresolved::new(
+                TirExprKindUnresolved::Call {
+                    func: self.symbols.get_float_is_integer_func(),
+                    args: vec![receiver],
+                },
+                TirTypeUnresolved::Bool,
+            )),
+            // Every int is an integer, but the receiver is still evaluated
+            "is_integer" if receiver.ty == TirTypeUnresolved::Int => Ok(TirExprUnresolved::new(
+                TirExprKindUnresolved::Block {
+                    stmts: vec![TirStmtUnresolved::Expr(receiver)],
+                    result: Box::new(TirExprUnresolved::new(
+                        TirExprKindUnresolved::Constant(Constant::Bool(true)),
+                        TirTypeUnresolved::Bool,
+                    )),
+                },
+                TirTypeUnresolved::Bool,
+            )),
+            _ => Err(CompilerError::TypeErrorSimple(format!(
+                "{:?} has no method '{}'",
+                receiver.ty, attr
+            ))),
+        }
+    }
+
+    /// Lower `float(value)`
+    fn lower_float_conversion(&mut self, value: TirExprUnresolved) -> Result<TirExprUnresolved> {
+        let str_class_id = self.symbols.get_or_create_str_class();
+        let arg = match &value.ty {
+            TirTypeUnresolved::Float => return Ok(value),
+            TirTypeUnresolved::Int => value,
+            TirTypeUnresolved::Bool => TirExprUnresolved::new(
+                TirExprKindUnresolved::Call {
+                    func: self.symbols.get_int_from_bool_func(),
+                    args: vec![value],
+                },
+                TirTypeUnresolved::Int,
+            ),
+            TirTypeUnresolved::Class(class_id) if *class_id == str_class_id => {
+                return Ok(TirExprUnresolved::new(
+                    TirExprKindUnresolved::Call {
+                        func: self.symbols.get_float_from_str_func(),
+                        args: vec![value],
+                    },
+                    TirTypeUnresolved::Float,
+                ));
+            }
+            TirTypeUnresolved::Class(_) => {
+                let ty = value.ty.clone();
+                let call = call_dunder_method!(self.symbols, &ty, "__float__", vec![value])?;
+                if call.ty != TirTypeUnresolved::Float {
+                    return Err(CompilerError::TypeErrorSimple(format!(
+                        "__float__ returned non-float (type {:?})",
+                        call.ty
+                    )));
+                }
+                return Ok(call);
+            }
+            _ => {
+                return Err(CompilerError::TypeErrorSimple(format!(
+                    "float() argument must be a string or a number, not {:?}",
+                    value.ty
+                )))
+            }
+        };
+
+        Ok(TirExprUnresolved::new(
+            TirExprKindUnresolved::Call {
+                func: self.symbols.get_float_from_int_func(),
+                args: vec![arg],
+            },
+            TirTypeUnresolved::Float,
+        ))
+    }
+
+    /// Lower `bool(value)`. Numbers compare against zero; classes use `__bool__`,
+    /// then `__len__`, and are otherwise always true.
+    pub(crate) fn lower_bool_conversion(
+        &mut self,
+        value: TirExprUnresolved,
+    ) -> Result<TirExprUnresolved> {
+        let zero = match &value.ty {
+            TirTypeUnresolved::Bool => return Ok(value),
+            TirTypeUnresolved::Int => Constant::Int(0),
+            TirTypeUnresolved::Float => Constant::Float(0.0),
+            TirTypeUnresolved::Tuple(elements) => {
+                let non_empty = !elements.is_empty();
+                return Ok(Self::evaluate_then_bool(value, non_empty));
+            }
+            TirTypeUnresolved::Optional(_) => return self.lower_optional_bool(value),
+            TirTypeUnresolved::NoneType => return Ok(Self::evaluate_then_bool(value, false)),
+            TirTypeUnresolved::Class(class_id) => {
+                let class_id = *class_id;
+                let ty = value.ty.clone();
+                if self.symbols.resolve_method(class_id, "__bool__").is_some() {
+                    let call = call_dunder_method!(self.symbols, &ty, "__bool__", vec![value])?;
+                    if call.ty != TirTypeUnresolved::Bool {
+                        return Err(CompilerError::TypeErrorSimple(format!(
+                            "__bool__ should return bool, returned {:?}",
+                            call.ty
+                        )));
+                    }
+                    return Ok(call);
+                }
+                if self.symbols.resolve_method(class_id, "__len__").is_none() {
+                    return Ok(Self::evaluate_then_bool(value, true));
+                }
+                let len = call_dunder_method!(
+                    self.symbols,
+                    &ty,
+                    "__len__",
+                    vec![value],
+                    TirTypeUnresolved::Int
+                )?;
+                return Ok(Self::compare_not_equal(len, Constant::Int(0)));
+            }
+            _ => {
+                return Err(CompilerError::TypeErrorSimple(format!(
+                    "bool() argument of type {:?} is not supported",
+                    value.ty
+                )))
+            }
+        };
+        Ok(Self::compare_not_equal(value, zero))
+    }
+
+    /// `value != zero`, typed as bool
+    fn compare_not_equal(value: TirExprUnresolved, zero: Constant) -> TirExprUnresolved {
+        let zero_ty = value.ty.clone();
+        TirExprUnresolved::new(
+            TirExprKindUnresolved::Compare {
+                left: Box::new(value),
+                op: CompareOp::NotEq,
+                right: Box::new(TirExprUnresolved::new(
+                    TirExprKindUnresolved::Constant(zero),
+                    zero_ty,
+                )),
+            },
+            TirTypeUnresolved::Bool,
+        )
+    }
+
+    /// Evaluate `value` for its effects, then produce a constant truth value
+    fn evaluate_then_bool(value: TirExprUnresolved, truth: bool) -> TirExprUnresolved {
+        TirExprUnresolved::new(
+            TirExprKindUnresolved::Block {
+                stmts: vec![TirStmtUnresolved::Expr(value)],
+                result: Box::new(TirExprUnresolved::new(
+                    TirExprKindUnresolved::Constant(Constant::Bool(truth)),
+                    TirTypeUnresolved::Bool,
+                )),
+            },
+            TirTypeUnresolved::Bool,
+        )
+    }
+
+    /// Lower one `left op right` comparison. `==` and `!=` on a class that
+    /// defines `__eq__` call it (`!=` negates it unless `__ne__` is defined);
+    /// `<`, `<=`, `>` and `>=` call `__lt__` and friends, or the reflected
+    /// method of the right operand (`a < b` -> `b.__gt__(a)`).
+    fn lower_comparison(
+        &mut self,
+        left: TirExprUnresolved,
+        op: CompareOp,
+        right: TirExprUnresolved,
+    ) -> Result<TirExprUnresolved> {
+        if matches!(op, CompareOp::In | CompareOp::NotIn) {
+            let contains = self.lower_membership(left, right)?;
+            if op == CompareOp::In {
+                return Ok(contains);
+            }
+            return Ok(TirExprUnresolved::new(
+                TirExprKindUnresolved::UnaryOp {
+                    op: UnaryOp::Not,
+                    operand: Box::new(contains),
+                },
+                TirTypeUnresolved::Bool,
+            ));
+        }
+        if matches!(op, CompareOp::Is | CompareOp::IsNot) {
+            return Self::lower_identity(left, op, right);
+        }
+
+        // Check that operands are compatible for comparison
+        if !left.ty.is_compatible_with(&right.ty) {
+            return Err(CompilerError::TypeErrorSimple(format!(
+                "Cannot compare {:?} with {:?}",
+                left.ty, right.ty
+            )));
+        }
+        if matches!(left.ty, TirTypeUnresolved::Tuple(_)) {
+            return Err(CompilerError::UnsupportedFeature(
+                "Comparing tuples is not supported yet".to_string(),
+            ));
+        }
+
+        let is_list = left.ty.class_id().is_some_and(|class_id| {
+            self.symbols.class_data[class_id.index()].qualified_name == "__builtin__.list"
+        });
+        if is_list && matches!(op, CompareOp::Eq | CompareOp::NotEq) {
+            return self.lower_list_equality(left, op, right);
+        }
+        if let (TirTypeUnresolved::Class(class_id), CompareOp::Eq | CompareOp::NotEq) =
+            (&left.ty, op)
+        {
+            let class_id = *class_id;
+            let has_ne = self.symbols.resolve_method(class_id, "__ne__").is_some();
+            if op == CompareOp::NotEq && has_ne {
+                let ty = left.ty.clone();
+                return call_dunder_method!(self.symbols, &ty, "__ne__", vec![left, right]);
+            }
+            if self.symbols.resolve_method(class_id, "__eq__").is_some() {
+                let ty = left.ty.clone();
+                let eq = call_dunder_method!(self.symbols, &ty, "__eq__", vec![left, right])?;
+                if op == CompareOp::Eq {
+                    return Ok(eq);
+                }
+                return Ok(TirExprUnresolved::new(
+                    TirExprKindUnresolved::UnaryOp {
+                        op: UnaryOp::Not,
+                        operand: Box::new(eq),
+                    },
+                    TirTypeUnresolved::Bool,
+                ));
+            }
+        }
+
+        if let Some((dunder, reflected)) = compare_dunders(op) {
+            if let Some(class_id) = left.ty.class_id() {
+                if self.symbols.resolve_method(class_id, dunder).is_some() {
+                    let ty = left.ty.clone();
+                    return call_dunder_method!(self.symbols, &ty, dunder, vec![left, right]);
+                }
+                if self.symbols.resolve_method(class_id, reflected).is_some() {
+                    return self.call_reflected(reflected, left, right);
+                }
+                if self.is_user_class(class_id) {
+                    return Err(CompilerError::TypeErrorSimple(format!(
+                        "Cannot order {:?} values (define {})",
+                        left.ty, dunder
+                    )));
+                }
+            }
+        }
+
+        Ok(TirExprUnresolved::new(
+            TirExprKindUnresolved::Compare {
+                left: Box::new(left),
+                op,
+                right: Box::new(right),
+            },
+            TirTypeUnresolved::Bool,
+        ))
+    }
+
+    /// `left is right`: the same object for class values, the same value for
+    /// ints, floats and bools. Operands of different types are never
+    /// identical (`1 is 1.0` is false), and only `None` itself, or an Optional
+    /// holding None, is `None`.
+    fn lower_identity(
+        left: TirExprUnresolved,
+        op: CompareOp,
+        right: TirExprUnresolved,
+    ) -> Result<TirExprUnresolved> {
+        // A call of a function without a return value gives None too
+        let is_none = |expr: &TirExprUnresolved| {
+            matches!(
+                expr.ty,
+                TirTypeUnresolved::NoneType | TirTypeUnresolved::Void
+            )
+        };
+        let is_optional = |expr: &TirExprUnresolved| expr.ty.optional_inner().is_some();
+        let comparable = if is_none(&left) || is_none(&right) {
+            is_optional(&left) || is_optional(&right)
+        } else if left.ty.is_numeric() && right.ty.is_numeric() {
+            left.ty == right.ty
+        } else {
+            left.ty.is_compatible_with(&right.ty) || right.ty.is_compatible_with(&left.ty)
+        };
+        if !comparable {
+            let identical = is_none(&left) && is_none(&right);
+            return Ok(TirExprUnresolved::new(
+                TirExprKindUnresolved::Block {
+                    stmts: vec![
+                        TirStmtUnresolved::Expr(left),
+                        TirStmtUnresolved::Expr(right),
+                    ],
+                    result: Box::new(TirExprUnresolved::new(
+                        TirExprKindUnresolved::Constant(Constant::Bool(
+                            identical == (op == CompareOp::Is),
+                        )),
+                        TirTypeUnresolved::Bool,
+                    )),
+                },
+                TirTypeUnresolved::Bool,
+            ));
+        }
+        // A boxed Optional value is a new object, whose identity means nothing
+        let boxed = [&left, &right].into_iter().find(|expr| {
+            matches!(
+                expr.ty.optional_inner(),
+                Some(TirTypeUnresolved::Int | TirTypeUnresolved::Float | TirTypeUnresolved::Bool)
+            )
+        });
+        if let (Some(boxed), false) = (boxed, is_none(&left) || is_none(&right)) {
+            return Err(CompilerError::TypeErrorSimple(format!(
+                "'is' on {:?} values can only test for None",
+                boxed.ty
+            )));
+        }
+        Ok(TirExprUnresolved::new(
+            TirExprKindUnresolved::Compare {
+                left: Box::new(left),
+                op,
+                right: Box::new(right),
+            },
+            TirTypeUnresolved::Bool,
+        ))
+    }
+
+    /// `item in container`: `container.__contains__(item)` when defined, else
+    /// a linear search of what iterating the container yields. A tuple is
+    /// searched element by element.
+    fn lower_membership(
+        &mut self,
+        item: TirExprUnresolved,
+        container: TirExprUnresolved,
+    ) -> Result<TirExprUnresolved> {
+        if let Some(class_id) = container.ty.class_id() {
+            if self
+                .symbols
+                .resolve_method(class_id, "__contains__")
+                .is_some()
+            {
+                return self.call_reflected("__contains__", item, container);
+            }
+            if self.symbols.resolve_method(class_id, "__iter__").is_none() {
+                return Err(CompilerError::TypeErrorSimple(format!(
+                    "Argument of type {:?} does not support 'in' (define __contains__ or __iter__)",
+                    container.ty
+                )));
+            }
+        } else if !matches!(container.ty, TirTypeUnresolved::Tuple(_)) {
+            return Err(CompilerError::TypeErrorSimple(format!(
+                "Argument of type {:?} does not support 'in'",
+                container.ty
+            )));
+        }
+
+        let mut stmts = Vec::new();
+        let item_name = self.bind_temp("_in_item", item, &mut stmts);
+        let item = Expr::Name(item_name);
+
+        // item == t[0] or item == t[1] or ...
+        if let TirTypeUnresolved::Tuple(elem_types) = &container.ty {
+            let count = elem_types.len();
+            let tuple_name = self.bind_temp("_in_tuple", container, &mut stmts);
+            let matches = (0..count)
+                .map(|i| Expr::Compare {
+                    left: Box::new(item.clone()),
+                    ops: vec![CompareOp::Eq],
+                    comparators: vec![Expr::Subscript {
+                        value: Box::new(Expr::Name(tuple_name.clone())),
+                        index: Box::new(Expr::Constant(Constant::Int(i as i64))),
+                    }],
+                })
+                .collect::<Vec<_>>();
+            let result = match matches.len() {
+                0 => Expr::Constant(Constant::Bool(false)),
+                1 => matches.into_iter().next().unwrap(),
+                _ => Expr::BoolOp {
+                    op: BoolOp::Or,
+                    values: matches,
+                },
+            };
+            let result = self.lower_expr(&result)?;
+            return Ok(TirExprUnresolved::new(
+                TirExprKindUnresolved::Block {
+                    stmts,
+                    result: Box::new(result),
+                },
+                TirTypeUnresolved::Bool,
+            ));
+        }
+
+        // found = False
+        // for x in container:
+        //     if not found and x == item:
+        //         found = True
+        // (there is no `break`, so the rest of the items are still iterated)
+        let container_name = self.bind_temp("_in_container", container, &mut stmts);
+        let found = format!("_in_found_{}", self.next_local_id);
+        let candidate = format!("_in_candidate_{}", self.next_local_id);
+        let search = [
+            Stmt::Assign {
+                target: Expr::Name(found.clone()),
+                value: Expr::Constant(Constant::Bool(false)),
+                type_annotation: None,
+                span: Span::default(),
+            },
+            Stmt::For {
+                target: candidate.clone(),
+                iter: Expr::Name(container_name),
+                body: vec![Stmt::If {
+                    test: Expr::BoolOp {
+                        op: BoolOp::And,
+                        values: vec![
+                            Expr::UnaryOp {
+                                op: UnaryOp::Not,
+                                operand: Box::new(Expr::Name(found.clone())),
+                            },
+                            Expr::Compare {
+                                left: Box::new(Expr::Name(candidate)),
+                                ops: vec![CompareOp::Eq],
+                                comparators: vec![item],
+                            },
+                        ],
+                    },
+                    body: vec![Stmt::Assign {
+                        target: Expr::Name(found.clone()),
+                        value: Expr::Constant(Constant::Bool(true)),
+                        type_annotation: None,
+                        span: Span::default(),
+                    }],
+                    orelse: vec![],
+                    span: Span::default(),
+                }],
+                orelse: vec![],
+                span: Span::default(),
+            },
+        ];
+        for stmt in &search {
+            stmts.extend(self.lower_stmt(stmt)?);
+        }
+        let result = self.lower_expr(&Expr::Name(found))?;
+        Ok(TirExprUnresolved::new(
+            TirExprKindUnresolved::Block {
+                stmts,
+                result: Box::new(result),
+            },
+            TirTypeUnresolved::Bool,
+        ))
+    }
+
+    /// `right.<method>(left)`, with `left` still evaluated first
+    fn call_reflected(
+        &mut self,
+        method: &str,
+        left: TirExprUnresolved,
+        right: TirExprUnresolved,
+    ) -> Result<TirExprUnresolved> {
+        let left_ty = left.ty.clone();
+        let local = self.alloc_local(&format!("_lhs_{}", self.next_local_id), left_ty.clone());
+        let left_var = TirExprUnresolved::new(
+            TirExprKindUnresolved::Var(VarRef::Local(local)),
+            left_ty.clone(),
+        );
+        let right_ty = right.ty.clone();
+        let call = call_dunder_method!(self.symbols, &right_ty, method, vec![right, left_var])?;
+        let ty = call.ty.clone();
+        Ok(TirExprUnresolved::new(
+            TirExprKindUnresolved::Block {
+                stmts: vec![TirStmtUnresolved::Let {
+                    local,
+                    ty: left_ty,
+                    init: left,
+                }],
+                result: Box::new(call),
+            },
+            ty,
+        ))
+    }
+
+    /// Whether a class was defined in the program rather than by the runtime
+    fn is_user_class(&self, class_id: ClassId) -> bool {
+        !self.symbols.class_data[class_id.index()]
+            .qualified_name
+            .starts_with("__builtin__.")
+    }
+
+    /// Convert a lowered value to a str, as `str(value)` (or `repr(value)` when
+    /// `repr` is set) would. Primitives use the runtime formatters, classes their
+    /// `__str__`/`__repr__`, falling back to `<Name object>`.
+    pub(crate) fn lower_str_conversion(
+        &mut self,
+        value: TirExprUnresolved,
+        repr: bool,
+    ) -> Result<TirExprUnresolved> {
+        let str_class_id = self.symbols.get_or_create_str_class();
+        let str_type = TirTypeUnresolved::Class(str_class_id);
+
+        let func = match &value.ty {
+            TirTypeUnresolved::Int => self.symbols.get_int_str_func(),
+            TirTypeUnresolved::Float => self.symbols.get_float_str_func(),
+            TirTypeUnresolved::Bool => self.symbols.get_bool_str_func(),
+            TirTypeUnresolved::Tuple(_) => self.symbols.get_tuple_repr_func(),
+            TirTypeUnresolved::Optional(_) => return self.lower_optional_str(value, repr),
+            TirTypeUnresolved::Class(class_id) => {
+                if *class_id == str_class_id && !repr {
+                    return Ok(value);
+                }
+                let method_names: &[&str] = if repr {
+                    &["__repr__"]
+                } else {
+                    &["__str__", "__repr__"]
+                };
+                let found = method_names
+                    .iter()
+                    .find_map(|name| self.symbols.resolve_method(*class_id, name));
+                match found {
+                    Some((_method_id, func_id)) => func_id,
+                    None => {
+                        // No __str__ or __repr__ - use the default string
+                        let class_name = &self.symbols.class_data[class_id.index()].qualified_name;
+                        let simple_name = class_name.rsplit('.').next().unwrap_or(class_name);
+                        return Ok(TirExprUnresolved::new(
+                            TirExprKindUnresolved::Constant(Constant::Str(format!(
+                                "<{} object>",
+                                simple_name
+                            ))),
+                            str_type,
+                        ));
+                    }
+                }
+            }
+            TirTypeUnresolved::NoneType => {
+                return Ok(TirExprUnresolved::new(
+                    TirExprKindUnresolved::Block {
+                        stmts: vec![TirStmtUnresolved::Expr(value)],
+                        result: Box::new(TirExprUnresolved::new(
+                            TirExprKindUnresolved::Constant(Constant::Str("None".to_string())),
+                            str_type.clone(),
+                        )),
+                    },
+                    str_type,
+                ))
+            }
+            TirTypeUnresolved::Void => {
+                return Err(CompilerError::TypeErrorSimple(
+                    "Cannot convert a None-valued expression to str".to_string(),
+                ))
+            }
+            TirTypeUnresolved::TypeVar(_) => {
+                unreachable!("Bare TypeVar in expression type - should be wrapped in Class")
+            }
+        };
+
+        Ok(TirExprUnresolved::new(
+            TirExprKindUnresolved::Call {
+                func,
+                args: vec![value],
+            },
+            str_type,
+        ))
+    }
+
+    /// Build a list literal from lowered elements, which must share one type.
+    /// An empty list gets a fresh element type variable, inferred from usage
+    /// (e.g., append calls).
+    pub(crate) fn lower_list_of(
+        &mut self,
+        elements: Vec<TirExprUnresolved>,
+    ) -> Result<TirExprUnresolved> {
+        let elem_ty = match elements.first() {
+            Some(first) => first.ty.clone(),
+            None => self.constraints.fresh_type_var(),
+        };
+        for (i, element) in elements.iter().enumerate().skip(1) {
+            if !element.ty.is_compatible_with(&elem_ty) {
+                return Err(CompilerError::TypeErrorSimple(format!(
+                    "List element type mismatch at index {}: expected {:?}, got {:?}",
+                    i, elem_ty, element.ty
+                )));
+            }
+        }
+
+        let list_class_id = self
+            .symbols
+            .get_or_create_list_class(&elem_ty.to_tir_type());
+        Ok(TirExprUnresolved::new(
+            TirExprKindUnresolved::List { elements, elem_ty },
+            TirTypeUnresolved::Class(list_class_id),
+        ))
+    }
+
+    /// Lower `container[lower:upper:step]` to a call of the container's `__slice__`.
+    /// The result has the container's type.
+    fn lower_slice(
+        &mut self,
+        container_expr: TirExprUnresolved,
+        lower: &Option<Box<Expr>>,
+        upper: &Option<Box<Expr>>,
+        step: &Option<Box<Expr>>,
+    ) -> Result<TirExprUnresolved> {
+        let func = container_expr
+            .ty
+            .class_id()
+            .and_then(|class_id| self.symbols.resolve_method(class_id, "__slice__"))
+            .map(|(_method_id, func_id)| func_id)
+            .ok_or_else(|| {
+                CompilerError::TypeErrorSimple(format!(
+                    "Type {:?} does not support slicing",
+                    container_expr.ty
+                ))
+            })?;
+
+        let mut lower_bound =
+            |bound: &Option<Box<Expr>>| -> Result<Option<Box<TirExprUnresolved>>> {
+                let Some(bound) = bound else {
+                    return Ok(None);
+                };
+                let bound_expr = self.lower_expr(bound)?;
+                if bound_expr.ty != TirTypeUnresolved::Int {
+                    return Err(CompilerError::TypeErrorSimple(format!(
+                        "Slice indices must be integers, got {:?}",
+                        bound_expr.ty
+                    )));
+                }
+                Ok(Some(Box::new(bound_expr)))
+            };
+        let start = lower_bound(lower)?;
+        let stop = lower_bound(upper)?;
+        let step = lower_bound(step)?;
+
+        let ty = container_expr.ty.clone();
+        Ok(TirExprUnresolved::new(
+            TirExprKindUnresolved::Slice {
+                func,
+                object: Box::new(container_expr),
+                start,
+                stop,
+                step,
+            },
+            ty,
+        ))
+    }
+
+    /// Append the default values of trailing parameters the caller left out.
+    /// Stops at the first missing parameter without a default so the arity
+    /// check reports it.
+    pub(crate) fn fill_default_args(
+        &mut self,
+        func_id: FuncId,
+        args: &mut Vec<TirExprUnresolved>,
+    ) -> Result<()> {
+        let Some(defaults) = self.symbols.func_defaults.get(&func_id) else {
+            return Ok(());
+        };
+        let missing: Vec<Constant> = defaults
+            .iter()
+            .skip(args.len())
+            .map_while(|default| default.clone())
+            .collect();
+        for value in missing {
+            args.push(self.lower_expr(&Expr::Constant(value))?);
+        }
+        Ok(())
+    }
+
+    fn lower_call(
+        &mut self,
+        func: &Expr,
+        args: &[Expr],
+        keywords: &[Keyword],
+    ) -> Result<TirExprUnresolved> {
         // Handle super().__method__(...) calls
         if let Expr::Attribute { value, attr } = func {
             if let Expr::Call {
                 func: super_func,
                 args: super_args,
+                ..
             } = value.as_ref()
             {
                 if let Expr::Name(name) = super_func.as_ref() {
@@ -342,14 +1659,127 @@ impl<'a> BodyLowerer<'a> {
             }
         }
 
-        // Lower arguments first
+        // Only sorted() and list.sort() take keyword arguments
+        let sort_call = match func {
+            Expr::Name(name) => name == "sorted",
+            Expr::Attribute { attr, .. } => attr == "sort",
+            _ => false,
+        };
+        if let Some(keyword) = keywords.first().filter(|_| !sort_call) {
+            return Err(CompilerError::UnsupportedFeature(format!(
+                "Keyword argument '{}' is not supported",
+                keyword.arg
+            )));
+        }
+
+        // __intrinsic__("llvm.name", ...) calls an LLVM intrinsic
+        if matches!(func, Expr::Name(name) if name == "__intrinsic__") {
+            return self.lower_llvm_intrinsic(args);
+        }
+
+        // map()/filter() take a function, which is applied per item rather than lowered as a value
+        if let Some(call) = self.as_map_filter_call(func, args, keywords)? {
+            return self.lower_map_filter(call);
+        }
+
+        // Lower arguments first; a call among them does not construct the
+        // generic class instance expected of this one
+        let generic_target = self.generic_target.take();
+        let (prelude, lowered_args) = self.lower_call_args(args)?;
+        self.generic_target = generic_target;
+        let call = self.lower_call_with_args(func, lowered_args, keywords);
+        self.generic_target = None;
+        let call = call?;
+        if prelude.is_empty() {
+            return Ok(call);
+        }
+        let ty = call.ty.clone();
+        Ok(TirExprUnresolved::new(
+            TirExprKindUnresolved::Block {
+                stmts: prelude,
+                result: Box::new(call),
+            },
+            ty,
+        ))
+    }
+
+    /// Lower call arguments, expanding each `*args` tuple into its elements.
+    /// When an argument is starred, every argument is first stored in a
+    /// temporary (returned as statements to run before the call) so each is
+    /// still evaluated once, left to right.
+    fn lower_call_args(
+        &mut self,
+        args: &[Expr],
+    ) -> Result<(Vec<TirStmtUnresolved>, Vec<TirExprUnresolved>)> {
+        let has_starred = args.iter().any(|arg| matches!(arg, Expr::Starred { .. }));
+        let mut prelude = Vec::new();
         let mut lowered_args = Vec::new();
         for arg in args {
-            lowered_args.push(self.lower_expr(arg)?);
+            let Expr::Starred { value } = arg else {
+                let arg_expr = self.lower_expr(arg)?;
+                if has_starred {
+                    lowered_args.push(self.store_call_arg(arg_expr, &mut prelude));
+                } else {
+                    lowered_args.push(arg_expr);
+                }
+                continue;
+            };
+
+            let value_expr = self.lower_expr(value)?;
+            let TirTypeUnresolved::Tuple(elem_types) = value_expr.ty.clone() else {
+                return Err(CompilerError::TypeErrorSimple(format!(
+                    "Starred call argument must be a tuple, got {:?}",
+                    value_expr.ty
+                )));
+            };
+            let tuple_var = self.store_call_arg(value_expr, &mut prelude);
+            for (index, elem_ty) in elem_types.into_iter().enumerate() {
+                lowered_args.push(TirExprUnresolved::new(
+                    TirExprKindUnresolved::TupleGet {
+                        tuple: Box::new(tuple_var.clone()),
+                        index,
+                    },
+                    elem_ty,
+                ));
+            }
+        }
+        Ok((prelude, lowered_args))
+    }
+
+    /// Store a call argument in a fresh local and return a reference to it
+    pub(crate) fn store_call_arg(
+        &mut self,
+        value: TirExprUnresolved,
+        prelude: &mut Vec<TirStmtUnresolved>,
+    ) -> TirExprUnresolved {
+        let ty = value.ty.clone();
+        let local = self.alloc_local(&format!("_call_arg_{}", self.next_local_id), ty.clone());
+        prelude.push(TirStmtUnresolved::Let {
+            local,
+            ty: ty.clone(),
+            init: value,
+        });
+        TirExprUnresolved::new(TirExprKindUnresolved::Var(VarRef::Local(local)), ty)
+    }
+
+    fn lower_call_with_args(
+        &mut self,
+        func: &Expr,
+        mut lowered_args: Vec<TirExprUnresolved>,
+        keywords: &[Keyword],
+    ) -> Result<TirExprUnresolved> {
+        // Functions of intrinsic modules (sys.exit) lower to runtime calls
+        if let Some((module, member)) = self.intrinsic_member(func) {
+            return self.lower_intrinsic_call(module, &member, lowered_args);
         }
 
         // Handle builtins
         if let Expr::Name(name) = func {
+            // Nested functions shadow everything at module level
+            if let Some(nested) = self.nested_functions.get(name).cloned() {
+                return self.lower_nested_call(name, &nested, lowered_args);
+            }
+
             // Note: print() is handled at statement level in stmt_lowering.rs
             // If print is used as an expression (e.g., x = print("hi")), it will error below
 
@@ -359,8 +1789,17 @@ impl<'a> BodyLowerer<'a> {
                         "len() takes exactly one argument".to_string(),
                     ));
                 }
-                // Check if argument is a class with __len__ method
                 let receiver = lowered_args.into_iter().next().unwrap();
+                if let TirTypeUnresolved::Tuple(_) = receiver.ty {
+                    return Ok(TirExprUnresolved::new(
+                        TirExprKindUnresolved::Call {
+                            func: self.symbols.get_tuple_len_func(),
+                            args: vec![receiver],
+                        },
+                        TirTypeUnresolved::Int,
+                    ));
+                }
+                // Check if argument is a class with __len__ method
                 return call_dunder_method!(
                     self.symbols,
                     &receiver.ty,
@@ -420,22 +1859,87 @@ impl<'a> BodyLowerer<'a> {
                 return call_dunder_method!(self.symbols, &receiver.ty, "__next__", vec![receiver]);
             }
 
-            // Check if it's an Exception constructor
-            if name == "Exception" {
-                let class_id = self.symbols.get_or_create_exception_class();
-                // Exception() can take 0 or 1 argument (message)
-                if lowered_args.len() > 1 {
-                    return Err(CompilerError::TypeErrorSimple(
-                        "Exception() takes at most 1 argument (message)".to_string(),
+            // sorted() builtin - sorts a copy of the iterable into a new list
+            if name == "sorted" {
+                return self.lower_sorted(lowered_args, keywords);
+            }
+
+            // str() / int() / float() / bool() conversions
+            if matches!(name.as_str(), "str" | "int" | "float" | "bool") {
+                return self.lower_conversion_call(name, lowered_args);
+            }
+
+            // hex() / oct() / bin() format an int with its base prefix
+            if matches!(name.as_str(), "hex" | "oct" | "bin") {
+                return self.lower_radix_call(name, lowered_args);
+            }
+
+            // divmod(a, b) -> (a // b, a % b)
+            if name == "divmod" {
+                return self.lower_divmod(lowered_args);
+            }
+
+            // round(x) -> int, round(x, ndigits) -> type of x
+            if name == "round" {
+                return self.lower_round(lowered_args);
+            }
+
+            // open(path, mode="r") builtin - opens a text file
+            if name == "open" {
+                if lowered_args.is_empty() || lowered_args.len() > 2 {
+                    return Err(CompilerError::TypeErrorSimple(format!(
+                        "open() takes 1 or 2 arguments ({} given)",
+                        lowered_args.len()
+                    )));
+                }
+                let str_class_id = self.symbols.get_or_create_str_class();
+                let str_ty = TirTypeUnresolved::Class(str_class_id);
+                if lowered_args.len() == 1 {
+                    lowered_args.push(TirExprUnresolved::new(
+                        TirExprKindUnresolved::Constant(Constant::Str("r".to_string())),
+                        str_ty.clone(),
                     ));
                 }
+                for (arg, what) in lowered_args.iter().zip(["path", "mode"]) {
+                    if arg.ty != str_ty {
+                        return Err(CompilerError::TypeErrorSimple(format!(
+                            "open() {} must be str, got {:?}",
+                            what, arg.ty
+                        )));
+                    }
+                }
+                let func = self.symbols.get_open_func();
+                let file_class_id = self.symbols.get_or_create_file_class();
+                return Ok(TirExprUnresolved::new(
+                    TirExprKindUnresolved::Call {
+                        func,
+                        args: lowered_args,
+                    },
+                    TirTypeUnresolved::Class(file_class_id),
+                ));
+            }
+
+            // Check if it's the constructor of a builtin exception (Exception,
+            // ValueError, ...) no class of the program shadows
+            if let Some(class_id) = self
+                .symbols
+                .builtin_exception_class(name)
+                .filter(|_| self.class_named(name).is_none())
+            {
+                // An exception can take 0 or 1 argument (message)
+                if lowered_args.len() > 1 {
+                    return Err(CompilerError::TypeErrorSimple(format!(
+                        "{}() takes at most 1 argument (message)",
+                        name
+                    )));
+                }
                 // If there's an argument, it must be a string
                 if lowered_args.len() == 1 {
                     let str_class_id = self.symbols.get_or_create_str_class();
                     if lowered_args[0].ty != TirTypeUnresolved::Class(str_class_id) {
                         return Err(CompilerError::TypeErrorSimple(format!(
-                            "Exception() argument must be a string, got {:?}",
-                            lowered_args[0].ty
+                            "{}() argument must be a string, got {:?}",
+                            name, lowered_args[0].ty
                         )));
                     }
                 }
@@ -448,6 +1952,78 @@ impl<'a> BodyLowerer<'a> {
                 ));
             }
 
+            // set(iterable) / frozenset(iterable) - element type comes from the argument;
+            // the empty forms need an annotation (see lower_expr_expecting)
+            if name == "set" || name == "frozenset" {
+                if lowered_args.len() != 1 {
+                    return Err(CompilerError::TypeErrorSimple(format!(
+                        "{}() without arguments needs a type annotation, e.g. s: {}[int] = {}()",
+                        name, name, name
+                    )));
+                }
+                let elem_ty = match lowered_args[0].ty.class_id() {
+                    Some(arg_class_id)
+                        if self.symbols.is_set_class(arg_class_id)
+                            || self.symbols.class_data[arg_class_id.index()].qualified_name
+                                == "__builtin__.list" =>
+                    {
+                        self.symbols.class_data[arg_class_id.index()].type_params[0].clone()
+                    }
+                    _ => {
+                        return Err(CompilerError::TypeErrorSimple(format!(
+                            "{}() argument must be a list, set or frozenset, got {:?}",
+                            name, lowered_args[0].ty
+                        )));
+                    }
+                };
+                self.check_hashable(&TirTypeUnresolved::from_tir_type(&elem_ty))?;
+                let class_id = if name == "set" {
+                    self.symbols.get_or_create_set_class(&elem_ty)
+                } else {
+                    self.symbols.get_or_create_frozenset_class(&elem_ty)
+                };
+                return Ok(TirExprUnresolved::new(
+                    TirExprKindUnresolved::Construct {
+                        class: class_id,
+                        args: lowered_args,
+                    },
+                    TirTypeUnresolved::Class(class_id),
+                ));
+            }
+
+            // bytes() is empty, bytes(b) is b itself and bytes(ba) copies a bytearray
+            if name == "bytes" {
+                if lowered_args.len() > 1 {
+                    return Err(CompilerError::TypeErrorSimple(
+                        "bytes() takes at most 1 argument".to_string(),
+                    ));
+                }
+                let Some(arg) = lowered_args.pop() else {
+                    let class_id = self.symbols.get_or_create_bytes_class();
+                    return Ok(TirExprUnresolved::new(
+                        TirExprKindUnresolved::Bytes { data: vec![] },
+                        TirTypeUnresolved::Class(class_id),
+                    ));
+                };
+                return match arg.ty.class_id() {
+                    Some(class_id) if self.symbols.is_bytes_class(class_id) => Ok(arg),
+                    Some(class_id) if self.symbols.is_bytearray_class(class_id) => {
+                        let bytes_class_id = self.symbols.get_or_create_bytes_class();
+                        Ok(TirExprUnresolved::new(
+                            TirExprKindUnresolved::Call {
+                                func: self.symbols.get_bytes_from_bytearray_func(),
+                                args: vec![arg],
+                            },
+                            TirTypeUnresolved::Class(bytes_class_id),
+                        ))
+                    }
+                    _ => Err(CompilerError::TypeErrorSimple(format!(
+                        "bytes() argument must be bytes or bytearray, got {:?}",
+                        arg.ty
+                    ))),
+                };
+            }
+
             // Check if it's a bytearray constructor
             if name == "bytearray" {
                 let class_id = self.symbols.get_or_create_bytearray_class();
@@ -480,7 +2056,8 @@ impl<'a> BodyLowerer<'a> {
             }
 
             // Check if it's a class constructor
-            if let Some(&class_id) = self.scope.classes.get(name) {
+            if let Some(class_id) = self.class_named(name) {
+                let class_id = self.constructed_class(class_id, name)?;
                 // Check if class has an __init__ method
                 if let Some(&(_, init_func_id)) = self
                     .symbols
@@ -488,6 +2065,7 @@ impl<'a> BodyLowerer<'a> {
                     .get(&(class_id, "__init__".to_string()))
                 {
                     // Get __init__ signature and type check arguments
+                    self.fill_default_args(init_func_id, &mut lowered_args)?;
                     let (param_tys, _) = self.symbols.get_func_signature(init_func_id);
                     if lowered_args.len() != param_tys.len() {
                         return Err(CompilerError::TypeErrorSimple(format!(
@@ -498,7 +2076,7 @@ impl<'a> BodyLowerer<'a> {
                         )));
                     }
                     for (i, (arg, param_ty)) in
-                        lowered_args.iter().zip(param_tys.iter()).enumerate()
+                        lowered_args.iter_mut().zip(param_tys.iter()).enumerate()
                     {
                         let param_ty_unresolved = TirTypeUnresolved::from_tir_type(param_ty);
                         if !arg.ty.is_compatible_with(&param_ty_unresolved) {
@@ -510,6 +2088,7 @@ impl<'a> BodyLowerer<'a> {
                                 arg.ty
                             )));
                         }
+                        arg.coerce_to(&param_ty_unresolved);
                     }
                 } else {
                     // No __init__ defined on this class - check if it's an Exception subclass
@@ -549,6 +2128,8 @@ impl<'a> BodyLowerer<'a> {
 
             // Check if it's a function
             if let Some(&func_id) = self.scope.functions.get(name) {
+                self.fill_default_args(func_id, &mut lowered_args)?;
+                let func_id = self.specialized_callee(func_id, name, &lowered_args)?;
                 let (param_tys, ret_ty) = self.symbols.get_func_signature(func_id);
 
                 // Type check arguments against parameters
@@ -560,7 +2141,9 @@ impl<'a> BodyLowerer<'a> {
                         lowered_args.len()
                     )));
                 }
-                for (i, (arg, param_ty)) in lowered_args.iter().zip(param_tys.iter()).enumerate() {
+                for (i, (arg, param_ty)) in
+                    lowered_args.iter_mut().zip(param_tys.iter()).enumerate()
+                {
                     let param_ty_unresolved = TirTypeUnresolved::from_tir_type(param_ty);
                     if !arg.ty.is_compatible_with(&param_ty_unresolved) {
                         return Err(CompilerError::TypeErrorSimple(format!(
@@ -571,6 +2154,7 @@ impl<'a> BodyLowerer<'a> {
                             arg.ty
                         )));
                     }
+                    arg.coerce_to(&param_ty_unresolved);
                 }
 
                 return Ok(TirExprUnresolved::new(
@@ -590,6 +2174,12 @@ impl<'a> BodyLowerer<'a> {
                 if let Some(&mod_id) = self.scope.module_aliases.get(mod_name) {
                     // It's a module reference
                     if let Some(&func_id) = self.symbols.functions.get(&(mod_id, attr.clone())) {
+                        self.fill_default_args(func_id, &mut lowered_args)?;
+                        let func_id = self.specialized_callee(
+                            func_id,
+                            &format!("{}.{}", mod_name, attr),
+                            &lowered_args,
+                        )?;
                         let (param_tys, ret_ty) = self.symbols.get_func_signature(func_id);
 
                         // Type check arguments
@@ -603,7 +2193,7 @@ impl<'a> BodyLowerer<'a> {
                             )));
                         }
                         for (i, (arg, param_ty)) in
-                            lowered_args.iter().zip(param_tys.iter()).enumerate()
+                            lowered_args.iter_mut().zip(param_tys.iter()).enumerate()
                         {
                             let param_ty_unresolved = TirTypeUnresolved::from_tir_type(param_ty);
                             if !arg.ty.is_compatible_with(&param_ty_unresolved) {
@@ -616,6 +2206,7 @@ impl<'a> BodyLowerer<'a> {
                                     arg.ty
                                 )));
                             }
+                            arg.coerce_to(&param_ty_unresolved);
                         }
 
                         return Ok(TirExprUnresolved::new(
@@ -629,14 +2220,97 @@ impl<'a> BodyLowerer<'a> {
                 }
             }
 
+            // ClassName.method(...) calls a static or class method
+            if let Expr::Name(class_name) = value.as_ref() {
+                if self.resolve_var(class_name).is_none() {
+                    if let Some(class_id) = self.class_named(class_name) {
+                        return self.lower_static_method_call(
+                            class_id,
+                            class_name,
+                            attr,
+                            lowered_args,
+                        );
+                    }
+                }
+            }
+
             // It's an actual method call
             let receiver = self.lower_expr(value)?;
 
+            // list.sort() - sorts in place, optionally by key
+            let is_list = receiver.ty.class_id().is_some_and(|class_id| {
+                self.symbols.class_data[class_id.index()].qualified_name == "__builtin__.list"
+            });
+            if attr == "sort" && is_list {
+                return self.lower_list_sort(receiver, lowered_args, keywords);
+            }
+            if is_list {
+                self.complete_list_method_args(&receiver.ty, attr, &mut lowered_args)?;
+            }
+            // bytes.split() splits on whitespace, a runtime function of its own
+            let is_bytes = receiver
+                .ty
+                .class_id()
+                .is_some_and(|class_id| self.symbols.is_bytes_class(class_id));
+            let attr = if attr == "split" && is_bytes && lowered_args.is_empty() {
+                "__split_whitespace__"
+            } else {
+                attr.as_str()
+            };
+            if let Some(keyword) = keywords.first() {
+                return Err(CompilerError::UnsupportedFeature(format!(
+                    "Keyword argument '{}' is not supported",
+                    keyword.arg
+                )));
+            }
+
+            // int, float and bool have a few methods of their own
+            if matches!(
+                receiver.ty,
+                TirTypeUnresolved::Int | TirTypeUnresolved::Float | TirTypeUnresolved::Bool
+            ) {
+                return self.lower_number_method(receiver, attr, lowered_args);
+            }
+
             // Get the class ID from receiver type
             let receiver_class_id = receiver.ty.class_id();
             if let Some(class_id) = receiver_class_id {
                 // Look up the method (including inherited methods)
                 if let Some((_method_id, func_id)) = self.symbols.resolve_method(class_id, attr) {
+                    // A call dispatched on the receiver's class runs an override
+                    if !self.symbols.is_overridden(class_id, attr) {
+                        self.check_not_abstract(class_id, attr)?;
+                    }
+                    if self.symbols.properties.contains(&func_id) {
+                        return Err(CompilerError::TypeErrorSimple(format!(
+                            "'{}' is a property and cannot be called",
+                            attr
+                        )));
+                    }
+
+                    // A static method called on an instance ignores it, but the
+                    // receiver is still evaluated
+                    if self.symbols.static_methods.contains(&func_id) {
+                        let qualified_name =
+                            &self.symbols.class_data[class_id.index()].qualified_name;
+                        let class_name = qualified_name.rsplit('.').next().unwrap().to_string();
+                        let call = self.lower_static_method_call(
+                            class_id,
+                            &class_name,
+                            attr,
+                            lowered_args,
+                        )?;
+                        let ty = call.ty.clone();
+                        return Ok(TirExprUnresolved::new(
+                            TirExprKindUnresolved::Block {
+                                stmts: vec![TirStmtUnresolved::Expr(receiver)],
+                                result: Box::new(call),
+                            },
+                            ty,
+                        ));
+                    }
+
+                    self.fill_default_args(func_id, &mut lowered_args)?;
                     let (param_tys, ret_ty) = self.symbols.get_func_signature(func_id);
 
                     // Type check arguments against parameters
@@ -649,7 +2323,7 @@ impl<'a> BodyLowerer<'a> {
                         )));
                     }
                     for (i, (arg, param_ty)) in
-                        lowered_args.iter().zip(param_tys.iter()).enumerate()
+                        lowered_args.iter_mut().zip(param_tys.iter()).enumerate()
                     {
                         let param_ty_unresolved = TirTypeUnresolved::from_tir_type(param_ty);
                         if !arg.ty.is_compatible_with(&param_ty_unresolved) {
@@ -661,6 +2335,7 @@ impl<'a> BodyLowerer<'a> {
                                 arg.ty
                             )));
                         }
+                        arg.coerce_to(&param_ty_unresolved);
                     }
 
                     // Create Call with receiver as first argument
@@ -683,7 +2358,7 @@ impl<'a> BodyLowerer<'a> {
                                     element: call_args[1].ty.clone(),
                                     origin: ConstraintOrigin::MethodCall {
                                         method_name: "append".to_string(),
-                                        line: 0, // TODO: track line numbers from AST
+                                        span: self.span,
                                     },
                                 });
                             }
@@ -691,23 +2366,113 @@ impl<'a> BodyLowerer<'a> {
                     }
 
                     return Ok(TirExprUnresolved::new(
-                        TirExprKindUnresolved::Call {
-                            func: func_id,
-                            args: call_args,
-                        },
+                        self.symbols.method_call(class_id, attr, func_id, call_args),
                         TirTypeUnresolved::from_tir_type(ret_ty),
                     ));
                 }
             }
         }
 
+        // Anything else callable is an object with a __call__ method, e.g. a
+        // variable, a field or the result of another call
+        let is_unknown_name = matches!(func, Expr::Name(name) if self.resolve_var(name).is_none());
+        if !is_unknown_name {
+            let callee = self.lower_expr(func)?;
+            return self.lower_instance_call(callee, lowered_args);
+        }
+
         Err(CompilerError::TypeErrorSimple(format!(
             "Cannot lower call to {:?}",
             func
         )))
     }
 
+    /// `obj(args)`: call `obj.__call__(args)`
+    fn lower_instance_call(
+        &mut self,
+        callee: TirExprUnresolved,
+        mut lowered_args: Vec<TirExprUnresolved>,
+    ) -> Result<TirExprUnresolved> {
+        let Some((_, func_id)) = callee
+            .ty
+            .class_id()
+            .and_then(|class_id| self.symbols.resolve_method(class_id, "__call__"))
+        else {
+            return Err(CompilerError::TypeErrorSimple(format!(
+                "Object of type {:?} is not callable",
+                callee.ty
+            )));
+        };
+        self.fill_default_args(func_id, &mut lowered_args)?;
+        let ty = callee.ty.clone();
+        let mut call_args = vec![callee];
+        call_args.extend(lowered_args);
+        call_dunder_method!(self.symbols, &ty, "__call__", call_args)
+    }
+
+    /// Lower `ClassName.method(args)`, a call without a receiver, which only
+    /// `@staticmethod` and `@classmethod` methods allow
+    fn lower_static_method_call(
+        &mut self,
+        class_id: ClassId,
+        class_name: &str,
+        attr: &str,
+        mut lowered_args: Vec<TirExprUnresolved>,
+    ) -> Result<TirExprUnresolved> {
+        let Some((_, func_id)) = self.symbols.resolve_method(class_id, attr) else {
+            return Err(CompilerError::TypeErrorSimple(format!(
+                "Class '{}' has no method '{}'",
+                class_name, attr
+            )));
+        };
+        if !self.symbols.static_methods.contains(&func_id) {
+            return Err(CompilerError::TypeErrorSimple(format!(
+                "Method '{}.{}' needs an instance (only static and class methods can be called on the class)",
+                class_name, attr
+            )));
+        }
+
+        self.fill_default_args(func_id, &mut lowered_args)?;
+        let (param_tys, ret_ty) = self.symbols.get_func_signature(func_id);
+        if lowered_args.len() != param_tys.len() {
+            return Err(CompilerError::TypeErrorSimple(format!(
+                "Method '{}.{}' expects {} arguments, got {}",
+                class_name,
+                attr,
+                param_tys.len(),
+                lowered_args.len()
+            )));
+        }
+        for (i, (arg, param_ty)) in lowered_args.iter_mut().zip(param_tys.iter()).enumerate() {
+            let param_ty_unresolved = TirTypeUnresolved::from_tir_type(param_ty);
+            if !arg.ty.is_compatible_with(&param_ty_unresolved) {
+                return Err(CompilerError::TypeErrorSimple(format!(
+                    "Argument {} to method '{}.{}': expected {:?}, got {:?}",
+                    i + 1,
+                    class_name,
+                    attr,
+                    param_ty,
+                    arg.ty
+                )));
+            }
+            arg.coerce_to(&param_ty_unresolved);
+        }
+
+        Ok(TirExprUnresolved::new(
+            TirExprKindUnresolved::Call {
+                func: func_id,
+                args: lowered_args,
+            },
+            TirTypeUnresolved::from_tir_type(ret_ty),
+        ))
+    }
+
     fn lower_attribute(&mut self, value: &Expr, attr: &str) -> Result<TirExprUnresolved> {
+        // Members of intrinsic modules (sys.argv) come from the runtime
+        if let Some((module, member)) = self.intrinsic_attribute(value, attr) {
+            return self.lower_intrinsic_value(module, &member);
+        }
+
         // Check if value is a module alias (for module.global access)
         if let Expr::Name(mod_name) = value {
             if let Some(&mod_id) = self.scope.module_aliases.get(mod_name) {
@@ -715,7 +2480,7 @@ impl<'a> BodyLowerer<'a> {
                 if let Some(&global_id) = self.symbols.globals.get(&(mod_id, attr.to_string())) {
                     return Ok(TirExprUnresolved::new(
                         TirExprKindUnresolved::Var(VarRef::Global(mod_id, global_id)),
-                        TirTypeUnresolved::Int, // TODO: track global types properly
+                        self.global_type(mod_id, global_id),
                     ));
                 }
                 return Err(CompilerError::TypeErrorSimple(format!(
@@ -727,6 +2492,20 @@ impl<'a> BodyLowerer<'a> {
 
         let receiver = self.lower_expr(value)?;
 
+        // Reading a property calls its getter
+        if let Some(class_id) = receiver.ty.class_id() {
+            if let Some(func_id) = self.symbols.resolve_property(class_id, attr) {
+                let ret_ty = &self.symbols.get_func_signature(func_id).1;
+                return Ok(TirExprUnresolved::new(
+                    TirExprKindUnresolved::Call {
+                        func: func_id,
+                        args: vec![receiver],
+                    },
+                    TirTypeUnresolved::from_tir_type(ret_ty),
+                ));
+            }
+        }
+
         // Get field from class
         if let Some(class_id) = receiver.ty.class_id() {
             if let Some(&field_id) = self.symbols.fields.get(&(class_id, attr.to_string())) {
@@ -790,6 +2569,7 @@ impl<'a> BodyLowerer<'a> {
                     method_name
                 ))
             })?;
+        self.check_not_abstract(parent_id, method_name)?;
 
         // Lower arguments
         let mut lowered_args = Vec::new();
@@ -798,6 +2578,7 @@ impl<'a> BodyLowerer<'a> {
         }
 
         // Get method signature and type check
+        self.fill_default_args(func_id, &mut lowered_args)?;
         let (param_tys, ret_ty) = self.symbols.get_func_signature(func_id);
 
         if lowered_args.len() != param_tys.len() {
@@ -809,7 +2590,7 @@ impl<'a> BodyLowerer<'a> {
             )));
         }
 
-        for (i, (arg, param_ty)) in lowered_args.iter().zip(param_tys.iter()).enumerate() {
+        for (i, (arg, param_ty)) in lowered_args.iter_mut().zip(param_tys.iter()).enumerate() {
             let param_ty_unresolved = TirTypeUnresolved::from_tir_type(param_ty);
             if !arg.ty.is_compatible_with(&param_ty_unresolved) {
                 return Err(CompilerError::TypeErrorSimple(format!(
@@ -820,6 +2601,7 @@ impl<'a> BodyLowerer<'a> {
                     arg.ty
                 )));
             }
+            arg.coerce_to(&param_ty_unresolved);
         }
 
         // Create Call with self as first argument (self is passed through to parent)
@@ -839,3 +2621,57 @@ impl<'a> BodyLowerer<'a> {
         ))
     }
 }
+
+/// Dunder method implementing a binary operator on class instances; the
+/// reflected (`__radd__`) and in-place (`__iadd__`) forms share its suffix
+pub(crate) fn binop_dunder(op: BinOperator) -> &'static str {
+    match op {
+        BinOperator::Add => "__add__",
+        BinOperator::Sub => "__sub__",
+        BinOperator::Mult => "__mul__",
+        BinOperator::Div => "__truediv__",
+        BinOperator::FloorDiv => "__floordiv__",
+        BinOperator::Mod => "__mod__",
+        BinOperator::Pow => "__pow__",
+        BinOperator::LShift => "__lshift__",
+        BinOperator::RShift => "__rshift__",
+        BinOperator::BitOr => "__or__",
+        BinOperator::BitXor => "__xor__",
+        BinOperator::BitAnd => "__and__",
+    }
+}
+
+/// Source spelling of a binary operator, for error messages
+fn binop_symbol(op: BinOperator) -> &'static str {
+    match op {
+        BinOperator::Add => "+",
+        BinOperator::Sub => "-",
+        BinOperator::Mult => "*",
+        BinOperator::Div => "/",
+        BinOperator::FloorDiv => "//",
+        BinOperator::Mod => "%",
+        BinOperator::Pow => "**",
+        BinOperator::LShift => "<<",
+        BinOperator::RShift => ">>",
+        BinOperator::BitOr => "|",
+        BinOperator::BitXor => "^",
+        BinOperator::BitAnd => "&",
+    }
+}
+
+/// Dunder method implementing an ordering comparison, and the reflected one
+/// the right operand can implement instead
+fn compare_dunders(op: CompareOp) -> Option<(&'static str, &'static str)> {
+    match op {
+        CompareOp::Lt => Some(("__lt__", "__gt__")),
+        CompareOp::LtE => Some(("__le__", "__ge__")),
+        CompareOp::Gt => Some(("__gt__", "__lt__")),
+        CompareOp::GtE => Some(("__ge__", "__le__")),
+        CompareOp::Eq
+        | CompareOp::NotEq
+        | CompareOp::In
+        | CompareOp::NotIn
+        | CompareOp::Is
+        | CompareOp::IsNot => None,
+    }
+}
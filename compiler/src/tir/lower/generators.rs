@@ -0,0 +1,866 @@
+//! Generator functions
+//!
+//! A generator function is lowered into a synthesized iterator class. Calling
+//! the function constructs an instance holding the arguments; `__next__` runs
+//! the body as a state machine that keeps its locals in fields and resumes
+//! right after the yield that suspended it:
+//!
+//! ```text
+//! def count(n: int) -> Iterator[int]:      class count.<generator>:
+//!     i = 0                                    <state>: int   # 0 = start, k = after yield k, -1 = done
+//!     while i < n:                             <value>: int   # last yielded value
+//!         yield i                              n: int
+//!         i += 1                               i#0: int
+//! ```
+//!
+//! Every statement of the body is guarded so that on resumption only the path
+//! leading to the suspended yield re-runs: conditions of enclosing `if`/`while`
+//! statements are forced true and everything before the yield is skipped.
+
+use crate::ast::{BoolOp, CompareOp};
+use crate::error::{CompilerError, Result};
+use crate::tir::decls::{TirClass, TirFunction};
+use crate::tir::expr::{TirConstant, TirExpr, TirExprKind, VarRef};
+use crate::tir::ids::{ClassId, FieldId, LocalId, MethodId};
+use crate::tir::stmt::{TirExceptHandler, TirLValue, TirStmt};
+use crate::tir::types::TirType;
+
+use super::symbols::GlobalSymbols;
+
+/// Field holding the resume point: 0 before the first call, k after yield k, -1 when done
+const STATE_FIELD: FieldId = FieldId(0);
+/// Field holding the most recently yielded value
+const VALUE_FIELD: FieldId = FieldId(1);
+/// Fields before the generator function's parameters
+const HEADER_FIELDS: u32 = 2;
+
+/// `__next__` local tracking what the body is doing (one of the modes below)
+const MODE_LOCAL: LocalId = LocalId(0);
+/// `__next__` local binding an exception escaping the body
+const EXC_LOCAL: LocalId = LocalId(1);
+
+/// Executing statements normally
+const RUNNING: i64 = 0;
+/// Skipping ahead to the yield recorded in `<state>`
+const RESUMING: i64 = 1;
+/// A value was yielded; skipping the rest of the body
+const SUSPENDING: i64 = 2;
+/// The body returned
+const FINISHED: i64 = 3;
+/// The body raised; the exception propagates to the caller
+const FAILED: i64 = 4;
+
+impl GlobalSymbols {
+    /// Register the iterator class of a generator function. Its fields are the
+    /// generator state followed by the parameters; the body's locals are
+    /// appended once the body has been lowered (see `build_generator`).
+    pub(crate) fn register_generator_class(
+        &mut self,
+        func_qualified_name: &str,
+        params: Vec<(String, TirType)>,
+        yield_ty: TirType,
+    ) -> ClassId {
+        let class_id = self.alloc_class();
+        let class_ty = TirType::Class(class_id);
+        let param_tys: Vec<TirType> = params.iter().map(|(_, ty)| ty.clone()).collect();
+
+        let mut fields = vec![
+            ("<state>".to_string(), TirType::Int),
+            ("<value>".to_string(), yield_ty.clone()),
+        ];
+        fields.extend(params);
+        for (idx, (field_name, _)) in fields.iter().enumerate() {
+            self.fields
+                .insert((class_id, field_name.clone()), FieldId(idx as u32));
+        }
+
+        let methods = [
+            ("__init__", param_tys, TirType::Void),
+            ("__iter__", vec![], class_ty),
+            ("__next__", vec![], yield_ty),
+        ];
+        for (idx, (method_name, method_params, ret_ty)) in methods.into_iter().enumerate() {
+            let func_id = self.alloc_func(method_params, ret_ty);
+            self.methods.insert(
+                (class_id, method_name.to_string()),
+                (MethodId(idx as u32), func_id),
+            );
+            self.class_data[class_id.index()]
+                .methods
+                .push((method_name.to_string(), func_id));
+        }
+
+        let class_data = &mut self.class_data[class_id.index()];
+        class_data.qualified_name = format!("{}.<generator>", func_qualified_name);
+        class_data.fields = fields;
+
+        class_id
+    }
+}
+
+/// Turn a lowered generator function (whose body still contains `TirStmt::Yield`)
+/// into the function that constructs its iterator plus the iterator's methods.
+pub(crate) fn build_generator(
+    symbols: &mut GlobalSymbols,
+    func: TirFunction,
+    class_id: ClassId,
+) -> Result<(Vec<TirFunction>, TirClass)> {
+    let class_ty = TirType::Class(class_id);
+
+    // Except-handler bindings can't live in fields (the handler stores the
+    // exception into a local), so they stay locals of __next__
+    let mut handler_locals = Vec::new();
+    collect_handler_locals(&func.body, &mut handler_locals);
+
+    let exception_class = symbols.get_or_create_exception_class();
+    let mut next_locals = vec![
+        ("<mode>".to_string(), TirType::Int),
+        ("<exc>".to_string(), TirType::Class(exception_class)),
+    ];
+    let mut slots = Vec::with_capacity(func.locals.len());
+    let mut local_fields = Vec::new();
+    let first_local_field = HEADER_FIELDS as usize + func.params.len();
+    for (idx, (local_name, ty)) in func.locals.iter().enumerate() {
+        if handler_locals.contains(&LocalId(idx as u32)) {
+            slots.push(Slot::Local(LocalId(next_locals.len() as u32)));
+            next_locals.push((local_name.clone(), ty.clone()));
+        } else {
+            let field = FieldId((first_local_field + local_fields.len()) as u32);
+            slots.push(Slot::Field(field));
+            local_fields.push((format!("{}#{}", local_name, idx), ty.clone()));
+        }
+    }
+    for (idx, (field_name, _)) in local_fields.iter().enumerate() {
+        symbols.fields.insert(
+            (class_id, field_name.clone()),
+            FieldId((first_local_field + idx) as u32),
+        );
+    }
+    symbols.class_data[class_id.index()]
+        .fields
+        .extend(local_fields);
+    let class_data = symbols.class_data[class_id.index()].clone();
+
+    let stop_iteration_class = symbols.get_or_create_stop_iteration_class();
+    let method = |name: &str| symbols.methods[&(class_id, name.to_string())].1;
+    let (init_id, iter_id, next_id) = (method("__init__"), method("__iter__"), method("__next__"));
+
+    let mut machine = StateMachine {
+        class_id,
+        field_types: class_data.fields.iter().map(|(_, ty)| ty.clone()).collect(),
+        slots,
+        locals: next_locals,
+        yields: 0,
+    };
+    let param_var = |idx: usize, ty: &TirType| {
+        TirExpr::new(TirExprKind::Var(VarRef::Param(idx as u32)), ty.clone())
+    };
+
+    // def gen(args): return gen.<generator>(args)
+    let construct = TirExpr::new(
+        TirExprKind::Construct {
+            class: class_id,
+            args: func
+                .params
+                .iter()
+                .enumerate()
+                .map(|(idx, (_, ty))| param_var(idx, ty))
+                .collect(),
+        },
+        class_ty.clone(),
+    );
+    let constructor = TirFunction {
+        id: func.id,
+        name: func.name.clone(),
+        qualified_name: func.qualified_name.clone(),
+        params: func.params.clone(),
+        return_type: class_ty.clone(),
+        locals: vec![],
+        body: vec![TirStmt::Return(Some(construct))],
+        class: None,
+        runtime_name: None,
+        location: func.location,
+    };
+
+    // __init__(self, args): store the arguments, starting before the first statement
+    let mut init_body = vec![machine.set_field(STATE_FIELD, int_const(0))];
+    for (idx, (_, ty)) in func.params.iter().enumerate() {
+        init_body.push(machine.set_field(param_field(idx), param_var(idx, ty)));
+    }
+    let init = TirFunction {
+        id: init_id,
+        name: "__init__".to_string(),
+        qualified_name: format!("{}.__init__", class_data.qualified_name),
+        params: func.params.clone(),
+        return_type: TirType::Void,
+        locals: vec![],
+        body: init_body,
+        class: Some(class_id),
+        runtime_name: None,
+        location: None,
+    };
+
+    // __iter__(self): return self
+    let iter = TirFunction {
+        id: iter_id,
+        name: "__iter__".to_string(),
+        qualified_name: format!("{}.__iter__", class_data.qualified_name),
+        params: vec![],
+        return_type: class_ty.clone(),
+        locals: vec![],
+        body: vec![TirStmt::Return(Some(machine.self_expr()))],
+        class: Some(class_id),
+        runtime_name: None,
+        location: None,
+    };
+
+    // __next__(self):
+    //     <mode> = RUNNING, or RESUMING / FINISHED depending on <state>
+    //     try:
+    //         <guarded body>
+    //     except Exception as <exc>:
+    //         self.<state> = -1
+    //         <mode> = FAILED
+    //         raise <exc>
+    //     if <mode> == RUNNING or <mode> == FINISHED:
+    //         self.<state> = -1
+    //         raise StopIteration()
+    //     return self.<value>
+    let yield_ty = machine.field_types[VALUE_FIELD.index()].clone();
+    let state = machine.field(STATE_FIELD);
+    let body = machine.transform_body(func.body)?;
+    let stop_iteration = TirExpr::new(
+        TirExprKind::Construct {
+            class: stop_iteration_class,
+            args: vec![],
+        },
+        TirType::Class(stop_iteration_class),
+    );
+    let next_body = vec![
+        TirStmt::Let {
+            local: MODE_LOCAL,
+            ty: TirType::Int,
+            init: int_const(RUNNING),
+        },
+        TirStmt::If {
+            cond: compare(state.clone(), CompareOp::Gt, int_const(0)),
+            then_body: vec![set_mode(RESUMING)],
+            else_body: vec![],
+        },
+        TirStmt::If {
+            cond: compare(state, CompareOp::Lt, int_const(0)),
+            then_body: vec![set_mode(FINISHED)],
+            else_body: vec![],
+        },
+        TirStmt::Try {
+            body,
+            handlers: vec![TirExceptHandler {
+                exc_class: None,
+                local: Some(EXC_LOCAL),
+                body: vec![
+                    machine.set_field(STATE_FIELD, int_const(-1)),
+                    set_mode(FAILED),
+                    TirStmt::Raise {
+                        exc: Some(TirExpr::new(
+                            TirExprKind::Var(VarRef::Local(EXC_LOCAL)),
+                            TirType::Class(exception_class),
+                        )),
+                    },
+                ],
+            }],
+            orelse: vec![],
+            finalbody: vec![],
+        },
+        TirStmt::If {
+            cond: bool_op(BoolOp::Or, vec![mode_is(RUNNING), mode_is(FINISHED)]),
+            then_body: vec![
+                machine.set_field(STATE_FIELD, int_const(-1)),
+                TirStmt::Raise {
+                    exc: Some(stop_iteration),
+                },
+            ],
+            else_body: vec![],
+        },
+        TirStmt::Return(Some(machine.field(VALUE_FIELD))),
+    ];
+    let next = TirFunction {
+        id: next_id,
+        name: "__next__".to_string(),
+        qualified_name: format!("{}.__next__", class_data.qualified_name),
+        params: vec![],
+        return_type: yield_ty,
+        locals: machine.locals,
+        body: next_body,
+        class: Some(class_id),
+        runtime_name: None,
+        location: func.location,
+    };
+
+    Ok((vec![constructor, init, iter, next], class_data))
+}
+
+/// Where a local of the generator body lives inside `__next__`
+#[derive(Clone, Copy)]
+enum Slot {
+    Field(FieldId),
+    Local(LocalId),
+}
+
+/// Rewrites a generator body into the body of `__next__`
+struct StateMachine {
+    class_id: ClassId,
+    /// Types of the generator class fields, by FieldId
+    field_types: Vec<TirType>,
+    /// New home of each local of the generator body, by LocalId
+    slots: Vec<Slot>,
+    /// Locals of `__next__`
+    locals: Vec<(String, TirType)>,
+    /// Number of yields numbered so far (yield k resumes at state k)
+    yields: i64,
+}
+
+impl StateMachine {
+    /// Guard every statement so it runs normally, or, when resuming, only if it
+    /// contains the yield to resume at
+    fn transform_body(&mut self, stmts: Vec<TirStmt>) -> Result<Vec<TirStmt>> {
+        let mut result = Vec::new();
+        for stmt in stmts {
+            if !suspends(&stmt) {
+                result.push(TirStmt::If {
+                    cond: mode_is(RUNNING),
+                    then_body: self.rewrite_stmt(stmt),
+                    else_body: vec![],
+                });
+                continue;
+            }
+
+            let first = self.yields + 1;
+            let then_body = self.transform_stmt(stmt)?;
+            let cond = match self.resuming_into(first) {
+                Some(resuming) => bool_op(BoolOp::Or, vec![mode_is(RUNNING), resuming]),
+                None => mode_is(RUNNING),
+            };
+            result.push(TirStmt::If {
+                cond,
+                then_body,
+                else_body: vec![],
+            });
+        }
+        Ok(result)
+    }
+
+    /// Transform a statement that yields or returns
+    fn transform_stmt(&mut self, stmt: TirStmt) -> Result<Vec<TirStmt>> {
+        match stmt {
+            TirStmt::Yield(value) => {
+                // Resuming here: carry on normally after the yield.
+                // Otherwise save the value and where to resume, then suspend.
+                self.yields += 1;
+                let value = self.rewrite_expr(value);
+                Ok(vec![TirStmt::If {
+                    cond: mode_is(RESUMING),
+                    then_body: vec![set_mode(RUNNING)],
+                    else_body: vec![
+                        self.set_field(VALUE_FIELD, value),
+                        self.set_field(STATE_FIELD, int_const(self.yields)),
+                        set_mode(SUSPENDING),
+                    ],
+                }])
+            }
+
+            TirStmt::Return(_) => Ok(vec![set_mode(FINISHED)]),
+
+            TirStmt::If {
+                cond,
+                then_body,
+                else_body,
+            } => {
+                let first = self.yields + 1;
+                let then_body = self.transform_body(then_body)?;
+                let then_resuming = self.resuming_into(first);
+                let else_body = self.transform_body(else_body)?;
+                let cond = self.rewrite_expr(cond);
+                let cond = match (then_resuming, self.resuming_into(first)) {
+                    // Resuming into the then branch forces the condition true,
+                    // resuming into the else branch forces it false
+                    (Some(resuming), _) => bool_op(
+                        BoolOp::Or,
+                        vec![resuming, bool_op(BoolOp::And, vec![mode_is(RUNNING), cond])],
+                    ),
+                    (None, Some(_)) => bool_op(BoolOp::And, vec![mode_is(RUNNING), cond]),
+                    (None, None) => cond,
+                };
+                Ok(vec![TirStmt::If {
+                    cond,
+                    then_body,
+                    else_body,
+                }])
+            }
+
+            TirStmt::While { cond, body } => {
+                // The loop also has to stop once the body suspends or returns
+                let first = self.yields + 1;
+                let body = self.transform_body(body)?;
+                let running = bool_op(BoolOp::And, vec![mode_is(RUNNING), self.rewrite_expr(cond)]);
+                let cond = match self.resuming_into(first) {
+                    Some(resuming) => bool_op(BoolOp::Or, vec![resuming, running]),
+                    None => running,
+                };
+                Ok(vec![TirStmt::While { cond, body }])
+            }
+
+            TirStmt::Try {
+                body,
+                handlers,
+                orelse,
+                finalbody,
+            } => {
+                if handlers.iter().any(|h| h.body.iter().any(contains_yield))
+                    || finalbody.iter().any(contains_yield)
+                {
+                    return Err(CompilerError::UnsupportedFeature(
+                        "yield inside an except or finally block is not supported".to_string(),
+                    ));
+                }
+                let body = self.transform_body(body)?;
+                let handlers = handlers
+                    .into_iter()
+                    .map(|handler| {
+                        Ok(TirExceptHandler {
+                            exc_class: handler.exc_class,
+                            local: handler.local.map(|local| self.local_slot(local)),
+                            body: self.transform_body(handler.body)?,
+                        })
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                // Guarded, so neither runs after the body suspends
+                let orelse = self.transform_body(orelse)?;
+                let finalbody = self.transform_body(finalbody)?;
+                Ok(vec![TirStmt::Try {
+                    body,
+                    handlers,
+                    orelse,
+                    finalbody,
+                }])
+            }
+
+            _ => unreachable!("only compound statements, yield and return can suspend"),
+        }
+    }
+
+    /// Condition for resuming at one of the yields numbered since `first`, if any
+    fn resuming_into(&self, first: i64) -> Option<TirExpr> {
+        let last = self.yields;
+        if last < first {
+            return None;
+        }
+        let state = self.field(STATE_FIELD);
+        let mut conds = vec![mode_is(RESUMING)];
+        if first == last {
+            conds.push(compare(state, CompareOp::Eq, int_const(first)));
+        } else {
+            conds.push(compare(state.clone(), CompareOp::GtE, int_const(first)));
+            conds.push(compare(state, CompareOp::LtE, int_const(last)));
+        }
+        Some(bool_op(BoolOp::And, conds))
+    }
+
+    /// Rewrite a statement that neither yields nor returns, moving its
+    /// variables into fields
+    fn rewrite_stmt(&mut self, stmt: TirStmt) -> Vec<TirStmt> {
+        match stmt {
+            TirStmt::Let { local, ty, init } => {
+                let init = self.rewrite_expr(init);
+                match self.slots[local.index()] {
+                    Slot::Field(field) => vec![self.set_field(field, init)],
+                    Slot::Local(local) => vec![TirStmt::Let { local, ty, init }],
+                }
+            }
+            TirStmt::Assign { target, value } => {
+                let target = match target {
+                    TirLValue::Var(var) => self.rewrite_lvalue(var),
+                    TirLValue::Field {
+                        object,
+                        class,
+                        field,
+                    } => TirLValue::Field {
+                        object: Box::new(self.rewrite_expr(*object)),
+                        class,
+                        field,
+                    },
+                };
+                let value = self.rewrite_expr(value);
+                vec![TirStmt::Assign { target, value }]
+            }
+            TirStmt::AugAssign { target, op, value } => {
+                let value = self.rewrite_expr(value);
+                match self.rewrite_lvalue(target) {
+                    TirLValue::Var(target) => vec![TirStmt::AugAssign { target, op, value }],
+                    // Fields can't be updated in place: go through a temporary
+                    //     tmp = self.x; tmp op= value; self.x = tmp
+                    TirLValue::Field { field, .. } => {
+                        let ty = self.field_types[field.index()].clone();
+                        let tmp = LocalId(self.locals.len() as u32);
+                        self.locals.push(("<tmp>".to_string(), ty.clone()));
+                        vec![
+                            TirStmt::Let {
+                                local: tmp,
+                                ty: ty.clone(),
+                                init: self.field(field),
+                            },
+                            TirStmt::AugAssign {
+                                target: VarRef::Local(tmp),
+                                op,
+                                value,
+                            },
+                            self.set_field(
+                                field,
+                                TirExpr::new(TirExprKind::Var(VarRef::Local(tmp)), ty),
+                            ),
+                        ]
+                    }
+                }
+            }
+            TirStmt::Expr(expr) => vec![TirStmt::Expr(self.rewrite_expr(expr))],
+            TirStmt::If {
+                cond,
+                then_body,
+                else_body,
+            } => vec![TirStmt::If {
+                cond: self.rewrite_expr(cond),
+                then_body: self.rewrite_body(then_body),
+                else_body: self.rewrite_body(else_body),
+            }],
+            TirStmt::While { cond, body } => vec![TirStmt::While {
+                cond: self.rewrite_expr(cond),
+                body: self.rewrite_body(body),
+            }],
+            TirStmt::Try {
+                body,
+                handlers,
+                orelse,
+                finalbody,
+            } => vec![TirStmt::Try {
+                body: self.rewrite_body(body),
+                handlers: handlers
+                    .into_iter()
+                    .map(|handler| TirExceptHandler {
+                        exc_class: handler.exc_class,
+                        local: handler.local.map(|local| self.local_slot(local)),
+                        body: self.rewrite_body(handler.body),
+                    })
+                    .collect(),
+                orelse: self.rewrite_body(orelse),
+                finalbody: self.rewrite_body(finalbody),
+            }],
+            TirStmt::Raise { exc } => vec![TirStmt::Raise {
+                exc: exc.map(|e| self.rewrite_expr(e)),
+            }],
+            TirStmt::Return(_) | TirStmt::Yield(_) => {
+                unreachable!("statements that suspend are transformed, not rewritten")
+            }
+        }
+    }
+
+    fn rewrite_body(&mut self, stmts: Vec<TirStmt>) -> Vec<TirStmt> {
+        stmts
+            .into_iter()
+            .flat_map(|stmt| self.rewrite_stmt(stmt))
+            .collect()
+    }
+
+    fn rewrite_lvalue(&self, var: VarRef) -> TirLValue {
+        let field = match var {
+            VarRef::Local(local) => match self.slots[local.index()] {
+                Slot::Field(field) => field,
+                Slot::Local(local) => return TirLValue::Var(VarRef::Local(local)),
+            },
+            VarRef::Param(idx) => param_field(idx as usize),
+            VarRef::Global(..) | VarRef::SelfRef => return TirLValue::Var(var),
+        };
+        TirLValue::Field {
+            object: Box::new(self.self_expr()),
+            class: self.class_id,
+            field,
+        }
+    }
+
+    fn local_slot(&self, local: LocalId) -> LocalId {
+        match self.slots[local.index()] {
+            Slot::Local(local) => local,
+            Slot::Field(_) => unreachable!("except-handler bindings stay locals"),
+        }
+    }
+
+    /// Rewrite variable references in an expression into field accesses
+    fn rewrite_expr(&mut self, expr: TirExpr) -> TirExpr {
+        let (ty, span) = (expr.ty, expr.span);
+        let kind = match expr.kind {
+            TirExprKind::Var(var) => match self.rewrite_lvalue(var) {
+                TirLValue::Var(var) => TirExprKind::Var(var),
+                TirLValue::Field {
+                    object,
+                    class,
+                    field,
+                } => TirExprKind::FieldAccess {
+                    object,
+                    class,
+                    field,
+                },
+            },
+            TirExprKind::BinOp { left, op, right } => TirExprKind::BinOp {
+                left: Box::new(self.rewrite_expr(*left)),
+                op,
+                right: Box::new(self.rewrite_expr(*right)),
+            },
+            TirExprKind::Compare { left, op, right } => TirExprKind::Compare {
+                left: Box::new(self.rewrite_expr(*left)),
+                op,
+                right: Box::new(self.rewrite_expr(*right)),
+            },
+            TirExprKind::BoolOp { op, values } => TirExprKind::BoolOp {
+                op,
+                values: self.rewrite_exprs(values),
+            },
+            TirExprKind::UnaryOp { op, operand } => TirExprKind::UnaryOp {
+                op,
+                operand: Box::new(self.rewrite_expr(*operand)),
+            },
+            TirExprKind::Call { func, args } => TirExprKind::Call {
+                func,
+                args: self.rewrite_exprs(args),
+            },
+            TirExprKind::VirtualCall { func, slot, args } => TirExprKind::VirtualCall {
+                func,
+                slot,
+                args: self.rewrite_exprs(args),
+            },
+            TirExprKind::Construct { class, args } => TirExprKind::Construct {
+                class,
+                args: self.rewrite_exprs(args),
+            },
+            TirExprKind::Range { start, stop, step } => TirExprKind::Range {
+                start: start.map(|e| Box::new(self.rewrite_expr(*e))),
+                stop: Box::new(self.rewrite_expr(*stop)),
+                step: step.map(|e| Box::new(self.rewrite_expr(*e))),
+            },
+            TirExprKind::FieldAccess {
+                object,
+                class,
+                field,
+            } => TirExprKind::FieldAccess {
+                object: Box::new(self.rewrite_expr(*object)),
+                class,
+                field,
+            },
+            TirExprKind::List { elements, elem_ty } => TirExprKind::List {
+                elements: self.rewrite_exprs(elements),
+                elem_ty,
+            },
+            TirExprKind::Dict {
+                entries,
+                key_ty,
+                value_ty,
+            } => TirExprKind::Dict {
+                entries: entries
+                    .into_iter()
+                    .map(|(k, v)| (self.rewrite_expr(k), self.rewrite_expr(v)))
+                    .collect(),
+                key_ty,
+                value_ty,
+            },
+            TirExprKind::Set { elements, elem_ty } => TirExprKind::Set {
+                elements: self.rewrite_exprs(elements),
+                elem_ty,
+            },
+            TirExprKind::Tuple { elements } => TirExprKind::Tuple {
+                elements: self.rewrite_exprs(elements),
+            },
+            TirExprKind::TupleGet { tuple, index } => TirExprKind::TupleGet {
+                tuple: Box::new(self.rewrite_expr(*tuple)),
+                index,
+            },
+            TirExprKind::WrapOptional { value } => TirExprKind::WrapOptional {
+                value: Box::new(self.rewrite_expr(*value)),
+            },
+            TirExprKind::UnwrapOptional { value } => TirExprKind::UnwrapOptional {
+                value: Box::new(self.rewrite_expr(*value)),
+            },
+            TirExprKind::MathIntrinsic { func, args } => TirExprKind::MathIntrinsic {
+                func,
+                args: self.rewrite_exprs(args),
+            },
+            TirExprKind::LlvmIntrinsic { name, args } => TirExprKind::LlvmIntrinsic {
+                name,
+                args: self.rewrite_exprs(args),
+            },
+            TirExprKind::Slice {
+                func,
+                object,
+                start,
+                stop,
+                step,
+            } => TirExprKind::Slice {
+                func,
+                object: Box::new(self.rewrite_expr(*object)),
+                start: start.map(|e| Box::new(self.rewrite_expr(*e))),
+                stop: stop.map(|e| Box::new(self.rewrite_expr(*e))),
+                step: step.map(|e| Box::new(self.rewrite_expr(*e))),
+            },
+            TirExprKind::Block { stmts, result } => TirExprKind::Block {
+                stmts: self.rewrite_body(stmts),
+                result: Box::new(self.rewrite_expr(*result)),
+            },
+            kind @ (TirExprKind::Constant(_) | TirExprKind::Bytes { .. }) => kind,
+        };
+        TirExpr::new(kind, ty).at(span)
+    }
+
+    fn rewrite_exprs(&mut self, exprs: Vec<TirExpr>) -> Vec<TirExpr> {
+        exprs.into_iter().map(|e| self.rewrite_expr(e)).collect()
+    }
+
+    fn self_expr(&self) -> TirExpr {
+        TirExpr::new(
+            TirExprKind::Var(VarRef::SelfRef),
+            TirType::Class(self.class_id),
+        )
+    }
+
+    fn field(&self, field: FieldId) -> TirExpr {
+        TirExpr::new(
+            TirExprKind::FieldAccess {
+                object: Box::new(self.self_expr()),
+                class: self.class_id,
+                field,
+            },
+            self.field_types[field.index()].clone(),
+        )
+    }
+
+    fn set_field(&self, field: FieldId, value: TirExpr) -> TirStmt {
+        TirStmt::Assign {
+            target: TirLValue::Field {
+                object: Box::new(self.self_expr()),
+                class: self.class_id,
+                field,
+            },
+            value,
+        }
+    }
+}
+
+/// Field holding parameter `idx` of the generator function
+fn param_field(idx: usize) -> FieldId {
+    FieldId(HEADER_FIELDS + idx as u32)
+}
+
+/// Whether a statement yields or returns, so its parts need guarding
+fn suspends(stmt: &TirStmt) -> bool {
+    match stmt {
+        TirStmt::Yield(_) | TirStmt::Return(_) => true,
+        TirStmt::If {
+            then_body,
+            else_body,
+            ..
+        } => then_body.iter().chain(else_body).any(suspends),
+        TirStmt::While { body, .. } => body.iter().any(suspends),
+        TirStmt::Try {
+            body,
+            handlers,
+            orelse,
+            finalbody,
+        } => {
+            body.iter().chain(orelse).chain(finalbody).any(suspends)
+                || handlers.iter().any(|h| h.body.iter().any(suspends))
+        }
+        _ => false,
+    }
+}
+
+fn contains_yield(stmt: &TirStmt) -> bool {
+    match stmt {
+        TirStmt::Yield(_) => true,
+        TirStmt::If {
+            then_body,
+            else_body,
+            ..
+        } => then_body.iter().chain(else_body).any(contains_yield),
+        TirStmt::While { body, .. } => body.iter().any(contains_yield),
+        TirStmt::Try {
+            body,
+            handlers,
+            orelse,
+            finalbody,
+        } => {
+            body.iter()
+                .chain(orelse)
+                .chain(finalbody)
+                .any(contains_yield)
+                || handlers.iter().any(|h| h.body.iter().any(contains_yield))
+        }
+        _ => false,
+    }
+}
+
+fn collect_handler_locals(stmts: &[TirStmt], locals: &mut Vec<LocalId>) {
+    for stmt in stmts {
+        match stmt {
+            TirStmt::If {
+                then_body,
+                else_body,
+                ..
+            } => {
+                collect_handler_locals(then_body, locals);
+                collect_handler_locals(else_body, locals);
+            }
+            TirStmt::While { body, .. } => collect_handler_locals(body, locals),
+            TirStmt::Try {
+                body,
+                handlers,
+                orelse,
+                finalbody,
+            } => {
+                collect_handler_locals(body, locals);
+                for handler in handlers {
+                    locals.extend(handler.local);
+                    collect_handler_locals(&handler.body, locals);
+                }
+                collect_handler_locals(orelse, locals);
+                collect_handler_locals(finalbody, locals);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn int_const(value: i64) -> TirExpr {
+    TirExpr::new(TirExprKind::Constant(TirConstant::Int(value)), TirType::Int)
+}
+
+fn compare(left: TirExpr, op: CompareOp, right: TirExpr) -> TirExpr {
+    TirExpr::new(
+        TirExprKind::Compare {
+            left: Box::new(left),
+            op,
+            right: Box::new(right),
+        },
+        TirType::Bool,
+    )
+}
+
+fn bool_op(op: BoolOp, values: Vec<TirExpr>) -> TirExpr {
+    TirExpr::new(TirExprKind::BoolOp { op, values }, TirType::Bool)
+}
+
+fn mode_is(mode: i64) -> TirExpr {
+    let mode_var = TirExpr::new(TirExprKind::Var(VarRef::Local(MODE_LOCAL)), TirType::Int);
+    compare(mode_var, CompareOp::Eq, int_const(mode))
+}
+
+fn set_mode(mode: i64) -> TirStmt {
+    TirStmt::Assign {
+        target: TirLValue::Var(VarRef::Local(MODE_LOCAL)),
+        value: int_const(mode),
+    }
+}
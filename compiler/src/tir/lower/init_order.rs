@@ -0,0 +1,207 @@
+//! Module initialization order
+//!
+//! Python runs a module's top-level code when it is first imported, so a
+//! module's init runs after the inits of the modules it imports: modules are
+//! numbered by a depth-first walk of the imports from the entry, in the
+//! order the import statements come, each after the modules it imports, and
+//! codegen calls the inits in the order of their ids.
+//!
+//! Modules that import each other, directly or through others, form a cycle
+//! and cannot all run after each other: the walk reaches a module again
+//! while still walking its imports, and leaves it to run after the module
+//! importing it. Their functions may still call each other, since a
+//! function only runs when called, but a module's top-level code must not
+//! use a global of a module initialized after it, nor call a function that
+//! does: the global is not set yet. That is reported as an error naming the
+//! cycle, where Python would fail on a partially initialized module.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::ast::{Module, ModuleName};
+use crate::error::{CompilerError, ErrorCollector, Result};
+use crate::span::Span;
+use crate::tir::escape::{for_each_node, Node};
+use crate::tir::expr::{TirExprKind, VarRef};
+use crate::tir::ids::{FuncId, GlobalId, ModuleId};
+use crate::tir::stmt::TirStmt;
+use crate::tir::TirProgram;
+
+/// The modules in the order their inits run, and the import cycles among
+/// them, each as the modules from one back to itself (`a → b → a`)
+pub(super) struct InitOrder {
+    pub(super) modules: Vec<ModuleName>,
+    pub(super) cycles: Vec<Vec<ModuleName>>,
+}
+
+/// Order the modules from `entry` by their imports; modules it does not
+/// reach come last, by name
+pub(super) fn init_order(modules: &HashMap<ModuleName, Module>, entry: &ModuleName) -> InitOrder {
+    let mut walk = Walk {
+        modules,
+        active: Vec::new(),
+        done: HashSet::new(),
+        order: InitOrder {
+            modules: Vec::new(),
+            cycles: Vec::new(),
+        },
+    };
+    walk.visit(entry);
+    let mut unreached: Vec<&ModuleName> = modules
+        .keys()
+        .filter(|name| !walk.done.contains(*name))
+        .collect();
+    unreached.sort_by(|a, b| a.0.cmp(&b.0));
+    for name in unreached {
+        walk.visit(name);
+    }
+    walk.order
+}
+
+/// The depth-first walk of the imports
+struct Walk<'m> {
+    modules: &'m HashMap<ModuleName, Module>,
+    /// The modules whose imports are being walked, outermost first
+    active: Vec<ModuleName>,
+    done: HashSet<ModuleName>,
+    order: InitOrder,
+}
+
+impl Walk<'_> {
+    fn visit(&mut self, name: &ModuleName) {
+        if self.done.contains(name) {
+            return;
+        }
+        if let Some(start) = self.active.iter().position(|active| active == name) {
+            let mut cycle = self.active[start..].to_vec();
+            cycle.push(name.clone());
+            self.order.cycles.push(cycle);
+            return;
+        }
+        let Some(module) = self.modules.get(name) else {
+            return;
+        };
+        self.active.push(name.clone());
+        for import in &module.imports {
+            self.visit(&import.module_id);
+        }
+        self.active.pop();
+        self.done.insert(name.clone());
+        self.order.modules.push(name.clone());
+    }
+}
+
+/// Fail if the init of a module uses a global of a module initialized after
+/// it, itself or through the functions it calls
+pub(super) fn check_init_order(program: &TirProgram, cycles: &[Vec<ModuleName>]) -> Result<()> {
+    let mut errors = ErrorCollector::new();
+    for module in &program.modules {
+        let Some((span, (owner, global))) = global_reads(program, &module.init_body)
+            .into_iter()
+            .find(|(_, (owner, _))| owner.0 > module.id.0)
+        else {
+            continue;
+        };
+        let owner = program.module(owner);
+        let name = owner
+            .globals
+            .iter()
+            .find(|candidate| candidate.id == global)
+            .map_or("?", |global| global.name.as_str());
+        let cycle = cycles
+            .iter()
+            .find(|cycle| {
+                cycle.iter().any(|m| m.0 == module.name) && cycle.iter().any(|m| m.0 == owner.name)
+            })
+            .map(|cycle| {
+                let names: Vec<&str> = cycle.iter().map(|m| m.0.as_str()).collect();
+                names.join(" → ")
+            })
+            .unwrap_or_else(|| format!("{} → {}", module.name, owner.name));
+        let error = CompilerError::CircularImport(format!(
+            "{}: module '{}' uses '{}' of module '{}' before '{}' is initialized",
+            cycle, module.name, name, owner.name, owner.name
+        ))
+        .with_help(format!(
+            "'{}' runs its top-level code after '{}'; use '{}' only in functions called once both are initialized",
+            owner.name, module.name, name
+        ));
+        errors.push(error.at(span).in_file(&module.path));
+    }
+    errors.into_result()
+}
+
+/// The globals `body` reads, itself or through the functions it calls, each
+/// with where in `body` it is read or the call reading it is made
+fn global_reads(program: &TirProgram, body: &[TirStmt]) -> Vec<(Span, (ModuleId, GlobalId))> {
+    let mut reads = Vec::new();
+    let mut calls = Vec::new();
+    collect_uses(program, body, &mut reads, &mut calls);
+
+    // Each function called, with where the call in `body` it is reached
+    // from is
+    let mut reached = HashSet::new();
+    let mut pending = calls;
+    while let Some((span, func)) = pending.pop() {
+        if !reached.insert(func) {
+            continue;
+        }
+        let mut func_reads = Vec::new();
+        let mut func_calls = Vec::new();
+        collect_uses(
+            program,
+            &program.function(func).body,
+            &mut func_reads,
+            &mut func_calls,
+        );
+        reads.extend(func_reads.into_iter().map(|(_, global)| (span, global)));
+        pending.extend(func_calls.into_iter().map(|(_, callee)| (span, callee)));
+    }
+    reads
+}
+
+/// Collect the globals `body` reads and the functions it calls (a
+/// construction calling the class's `__init__`), each where it is: at the
+/// innermost statement or expression around it whose position is known
+fn collect_uses(
+    program: &TirProgram,
+    body: &[TirStmt],
+    reads: &mut Vec<(Span, (ModuleId, GlobalId))>,
+    calls: &mut Vec<(Span, FuncId)>,
+) {
+    let mut at = Span::default();
+    for_each_node(body, &mut |node| {
+        match node {
+            Node::Stmt(stmt) => at = stmt.span().unwrap_or(at),
+            Node::Expr(expr) if expr.span.is_known() => at = expr.span,
+            Node::Expr(_) => {}
+        }
+        match node {
+            Node::Stmt(TirStmt::AugAssign {
+                target: VarRef::Global(module, global),
+                ..
+            }) => reads.push((at, (*module, *global))),
+            Node::Stmt(_) => {}
+            Node::Expr(expr) => match &expr.kind {
+                TirExprKind::Var(VarRef::Global(module, global)) => {
+                    reads.push((at, (*module, *global)))
+                }
+                TirExprKind::Call { func, .. } | TirExprKind::VirtualCall { func, .. } => {
+                    calls.push((at, *func))
+                }
+                TirExprKind::Construct { class, .. } => {
+                    let init = program
+                        .classes
+                        .iter()
+                        .find(|candidate| candidate.id == *class)
+                        .and_then(|class| {
+                            class.methods.iter().find(|(name, _)| name == "__init__")
+                        });
+                    if let Some((_, func)) = init {
+                        calls.push((at, *func));
+                    }
+                }
+                _ => {}
+            },
+        }
+    });
+}
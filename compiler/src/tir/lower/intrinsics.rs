@@ -0,0 +1,483 @@
+//! Intrinsic modules (`sys`, `os`, `math`, `time`, `random`, `dataclasses`,
+//! `abc`)
+//!
+//! Modules the compiler provides itself have no source file and no TIR
+//! definitions. Each use of one of their members lowers straight to a call
+//! into the C runtime, whether it is reached through the module
+//! (`sys.exit(1)` after `import sys`) or imported by name (`exit(1)` after
+//! `from sys import exit`). `math` functions lower to LLVM intrinsics instead,
+//! and its constants to float literals. `dataclasses.dataclass` is only
+//! meaningful as a class decorator, which the AST converter expands, and
+//! `abc.ABC` and `abc.abstractmethod` as the base and method decorator the
+//! converter recognizes.
+//!
+//! `__intrinsic__(name, ...)`, the escape hatch to LLVM intrinsics that no
+//! module provides, is lowered here too.
+
+use inkwell::attributes::{Attribute, AttributeLoc};
+use inkwell::context::Context;
+use inkwell::types::BasicMetadataTypeEnum;
+
+use crate::ast::{Constant, Expr, IntrinsicModule};
+use crate::error::{CompilerError, Result};
+use crate::tir::expr::MathFunc;
+use crate::tir::expr_unresolved::{TirExprKindUnresolved, TirExprUnresolved};
+use crate::tir::ids::FuncId;
+use crate::tir::llvm_intrinsic::declare_llvm_intrinsic;
+use crate::tir::types_unresolved::TirTypeUnresolved;
+
+use super::body_lowerer::BodyLowerer;
+
+impl<'a> BodyLowerer<'a> {
+    /// The intrinsic module member a call target names, if any
+    pub(crate) fn intrinsic_member(&self, func: &Expr) -> Option<(IntrinsicModule, String)> {
+        match func {
+            Expr::Attribute { value, attr } => self.intrinsic_attribute(value, attr),
+            Expr::Name(name) => self.intrinsic_name(name),
+            _ => None,
+        }
+    }
+
+    /// `module.attr` where `module` is an imported intrinsic module that no
+    /// variable shadows
+    pub(crate) fn intrinsic_attribute(
+        &self,
+        value: &Expr,
+        attr: &str,
+    ) -> Option<(IntrinsicModule, String)> {
+        let Expr::Name(name) = value else {
+            return None;
+        };
+        if self.resolve_var(name).is_some() {
+            return None;
+        }
+        let module = *self.scope.intrinsic_modules.get(name)?;
+        Some((module, attr.to_string()))
+    }
+
+    /// A name imported from an intrinsic module that no variable or nested
+    /// function shadows
+    pub(crate) fn intrinsic_name(&self, name: &str) -> Option<(IntrinsicModule, String)> {
+        if self.resolve_var(name).is_some() || self.nested_functions.contains_key(name) {
+            return None;
+        }
+        self.scope.intrinsic_names.get(name).cloned()
+    }
+
+    /// Lower a call to an intrinsic module function
+    pub(crate) fn lower_intrinsic_call(
+        &mut self,
+        module: IntrinsicModule,
+        member: &str,
+        args: Vec<TirExprUnresolved>,
+    ) -> Result<TirExprUnresolved> {
+        if let (IntrinsicModule::Math, Some(func)) = (module, MathFunc::from_name(member)) {
+            return self.lower_math_call(func, member, args);
+        }
+        match (module, member) {
+            (IntrinsicModule::Sys, "exit") => self.lower_sys_exit(args),
+            (IntrinsicModule::Os, "getenv") => self.lower_os_getenv(args),
+            (IntrinsicModule::Os, "getcwd") => {
+                self.check_intrinsic_arity(module, member, &args, 0)?;
+                let func = self.symbols.get_os_getcwd_func();
+                Ok(self.runtime_call(func, args))
+            }
+            (IntrinsicModule::Time, "time" | "monotonic") => {
+                self.check_intrinsic_arity(module, member, &args, 0)?;
+                let func = self.symbols.get_time_clock_func(member);
+                Ok(self.runtime_call(func, args))
+            }
+            (IntrinsicModule::Time, "sleep") => self.lower_time_sleep(args),
+            (IntrinsicModule::Random, "random") => {
+                self.check_intrinsic_arity(module, member, &args, 0)?;
+                let func = self.symbols.get_random_random_func();
+                Ok(self.runtime_call(func, args))
+            }
+            (IntrinsicModule::Random, "randint") => {
+                self.check_intrinsic_arity(module, member, &args, 2)?;
+                self.check_int_args(module, member, &args)?;
+                let func = self.symbols.get_random_randint_func();
+                Ok(self.runtime_call(func, args))
+            }
+            (IntrinsicModule::Random, "seed") => self.lower_random_seed(args),
+            (IntrinsicModule::Random, "choice") => self.lower_random_choice(args),
+            _ => {
+                // Check the member exists before reporting that it is not callable
+                if Self::intrinsic_constant(module, member).is_none() {
+                    self.intrinsic_value_func(module, member)?;
+                }
+                Err(CompilerError::TypeErrorSimple(format!(
+                    "'{}.{}' is not callable",
+                    module.name(),
+                    member
+                )))
+            }
+        }
+    }
+
+    /// Lower a use of an intrinsic module member as a value
+    pub(crate) fn lower_intrinsic_value(
+        &mut self,
+        module: IntrinsicModule,
+        member: &str,
+    ) -> Result<TirExprUnresolved> {
+        if let Some(value) = Self::intrinsic_constant(module, member) {
+            return Ok(TirExprUnresolved::new(
+                TirExprKindUnresolved::Constant(Constant::Float(value)),
+                TirTypeUnresolved::Float,
+            ));
+        }
+        let func = self.intrinsic_value_func(module, member)?;
+        Ok(self.runtime_call(func, vec![]))
+    }
+
+    /// The value of `module.member` when it is a float constant
+    fn intrinsic_constant(module: IntrinsicModule, member: &str) -> Option<f64> {
+        match (module, member) {
+            (IntrinsicModule::Math, "pi") => Some(std::f64::consts::PI),
+            (IntrinsicModule::Math, "e") => Some(std::f64::consts::E),
+            _ => None,
+        }
+    }
+
+    /// The runtime function that produces the value of `module.member`
+    fn intrinsic_value_func(&mut self, module: IntrinsicModule, member: &str) -> Result<FuncId> {
+        match (module, member) {
+            (IntrinsicModule::Sys, "argv") => Ok(self.symbols.get_sys_argv_func()),
+            (IntrinsicModule::Sys, "stdin" | "stdout" | "stderr") => {
+                Ok(self.symbols.get_sys_stream_func(member))
+            }
+            (IntrinsicModule::Os, "environ") => Ok(self.symbols.get_os_environ_func()),
+            (IntrinsicModule::Dataclasses, "dataclass") => Err(CompilerError::UnsupportedFeature(
+                "dataclasses.dataclass can only be used as a class decorator".to_string(),
+            )),
+            (IntrinsicModule::Abc, "ABC" | "abstractmethod") => {
+                Err(CompilerError::UnsupportedFeature(format!(
+                    "abc.{} can only be used as a class base or method decorator",
+                    member
+                )))
+            }
+            _ if module.members().contains(&member) => Err(CompilerError::UnsupportedFeature(
+                format!("{}.{} can only be called", module.name(), member),
+            )),
+            _ => Err(CompilerError::TypeErrorSimple(format!(
+                "module '{}' has no attribute '{}'",
+                module.name(),
+                member
+            ))),
+        }
+    }
+
+    fn check_intrinsic_arity(
+        &self,
+        module: IntrinsicModule,
+        member: &str,
+        args: &[TirExprUnresolved],
+        expected: usize,
+    ) -> Result<()> {
+        if args.len() != expected {
+            return Err(CompilerError::TypeErrorSimple(format!(
+                "{}.{}() takes {} arguments ({} given)",
+                module.name(),
+                member,
+                expected,
+                args.len()
+            )));
+        }
+        Ok(())
+    }
+
+    fn check_int_args(
+        &self,
+        module: IntrinsicModule,
+        member: &str,
+        args: &[TirExprUnresolved],
+    ) -> Result<()> {
+        for arg in args {
+            if arg.ty != TirTypeUnresolved::Int {
+                return Err(CompilerError::TypeErrorSimple(format!(
+                    "{}.{}() arguments must be int, got {:?}",
+                    module.name(),
+                    member,
+                    arg.ty
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// A call to a runtime function, typed by its signature
+    fn runtime_call(&self, func: FuncId, args: Vec<TirExprUnresolved>) -> TirExprUnresolved {
+        let (_, ret_ty) = self.symbols.get_func_signature(func);
+        let ty = TirTypeUnresolved::from_tir_type(ret_ty);
+        TirExprUnresolved::new(TirExprKindUnresolved::Call { func, args }, ty)
+    }
+
+    // ========================================================================
+    // sys
+    // ========================================================================
+
+    /// sys.exit(), sys.exit(code) or sys.exit(message)
+    fn lower_sys_exit(&mut self, mut args: Vec<TirExprUnresolved>) -> Result<TirExprUnresolved> {
+        if args.len() > 1 {
+            return Err(CompilerError::TypeErrorSimple(format!(
+                "sys.exit() takes at most 1 argument ({} given)",
+                args.len()
+            )));
+        }
+        if args.is_empty() {
+            args.push(TirExprUnresolved::new(
+                TirExprKindUnresolved::Constant(Constant::Int(0)),
+                TirTypeUnresolved::Int,
+            ));
+        }
+        let str_class_id = self.symbols.get_or_create_str_class();
+        let func = match &args[0].ty {
+            TirTypeUnresolved::Int => self.symbols.get_sys_exit_func(),
+            TirTypeUnresolved::Class(class_id) if *class_id == str_class_id => {
+                self.symbols.get_sys_exit_message_func()
+            }
+            other => {
+                return Err(CompilerError::TypeErrorSimple(format!(
+                    "sys.exit() argument must be an int exit status or a str message, got {:?}",
+                    other
+                )))
+            }
+        };
+        Ok(self.runtime_call(func, args))
+    }
+
+    // ========================================================================
+    // os
+    // ========================================================================
+
+    /// os.getenv(key, default). There is no None, so the default is required.
+    fn lower_os_getenv(&mut self, args: Vec<TirExprUnresolved>) -> Result<TirExprUnresolved> {
+        if args.len() == 1 {
+            return Err(CompilerError::TypeErrorSimple(
+                "os.getenv() needs a default for unset variables, e.g. os.getenv(key, \"\")"
+                    .to_string(),
+            ));
+        }
+        self.check_intrinsic_arity(IntrinsicModule::Os, "getenv", &args, 2)?;
+        let str_ty = TirTypeUnresolved::Class(self.symbols.get_or_create_str_class());
+        for (arg, what) in args.iter().zip(["key", "default"]) {
+            if arg.ty != str_ty {
+                return Err(CompilerError::TypeErrorSimple(format!(
+                    "os.getenv() {} must be str, got {:?}",
+                    what, arg.ty
+                )));
+            }
+        }
+        let func = self.symbols.get_os_getenv_func();
+        Ok(self.runtime_call(func, args))
+    }
+
+    // ========================================================================
+    // math
+    // ========================================================================
+
+    /// math.sqrt(x), math.pow(x, y), ...: int arguments are converted to float.
+    /// floor and ceil give an int: an int argument as it is, a float one
+    /// rounded and converted as int() does, so NaN and infinities raise
+    fn lower_math_call(
+        &mut self,
+        func: MathFunc,
+        member: &str,
+        mut args: Vec<TirExprUnresolved>,
+    ) -> Result<TirExprUnresolved> {
+        self.check_intrinsic_arity(IntrinsicModule::Math, member, &args, func.arity())?;
+        for arg in &args {
+            if !matches!(arg.ty, TirTypeUnresolved::Int | TirTypeUnresolved::Float) {
+                return Err(CompilerError::TypeErrorSimple(format!(
+                    "math.{}() argument must be int or float, got {:?}",
+                    member, arg.ty
+                )));
+            }
+        }
+        if func.returns_int() && args[0].ty == TirTypeUnresolved::Int {
+            return Ok(args.remove(0));
+        }
+        let rounded = TirExprUnresolved::new(
+            TirExprKindUnresolved::MathIntrinsic { func, args },
+            TirTypeUnresolved::Float,
+        );
+        if func.returns_int() {
+            let to_int = self.symbols.get_int_from_float_func();
+            return Ok(self.runtime_call(to_int, vec![rounded]));
+        }
+        Ok(rounded)
+    }
+
+    // ========================================================================
+    // __intrinsic__
+    // ========================================================================
+
+    /// __intrinsic__("llvm.ctpop.i64", x): a call of an LLVM intrinsic,
+    /// whose declaration gives the types of its arguments and result
+    pub(crate) fn lower_llvm_intrinsic(&mut self, args: &[Expr]) -> Result<TirExprUnresolved> {
+        let Some((Expr::Constant(Constant::Str(name)), args)) = args.split_first() else {
+            return Err(CompilerError::TypeErrorSimple(
+                "__intrinsic__() takes the name of an LLVM intrinsic as a string literal first"
+                    .to_string(),
+            ));
+        };
+        let args = args
+            .iter()
+            .map(|arg| self.lower_expr(arg))
+            .collect::<Result<Vec<_>>>()?;
+
+        // The intrinsic's signature, from its declaration in a scratch module
+        let context = Context::create();
+        let module = context.create_module("intrinsic");
+        let declaration = declare_llvm_intrinsic(&module, name).ok_or_else(|| {
+            CompilerError::UnsupportedFeature(format!(
+                "Unknown LLVM intrinsic '{}' (an overloaded one is named with its types, as in llvm.ctpop.i64)",
+                name
+            ))
+        })?;
+        let fn_type = declaration.get_type();
+        let unsupported = || {
+            CompilerError::UnsupportedFeature(format!(
+                "LLVM intrinsic '{}' takes or returns a type other than i64, double and i1",
+                name
+            ))
+        };
+
+        let params = fn_type.get_param_types();
+        if params.len() != args.len() {
+            return Err(CompilerError::TypeErrorSimple(format!(
+                "{}() takes {} arguments ({} given)",
+                name,
+                params.len(),
+                args.len()
+            )));
+        }
+        let immarg = Attribute::get_named_enum_kind_id("immarg");
+        for (i, (param, arg)) in params.iter().zip(&args).enumerate() {
+            let expected = python_type(*param).ok_or_else(unsupported)?;
+            if arg.ty != expected {
+                return Err(CompilerError::TypeErrorSimple(format!(
+                    "{}() argument {} must be {:?}, got {:?}",
+                    name,
+                    i + 1,
+                    expected,
+                    arg.ty
+                )));
+            }
+            let is_immediate = declaration
+                .get_enum_attribute(AttributeLoc::Param(i as u32), immarg)
+                .is_some();
+            if is_immediate && !matches!(arg.kind, TirExprKindUnresolved::Constant(_)) {
+                return Err(CompilerError::TypeErrorSimple(format!(
+                    "{}() argument {} must be a constant",
+                    name,
+                    i + 1
+                )));
+            }
+        }
+
+        let ty = match fn_type.get_return_type() {
+            Some(ret) => python_type(ret.into()).ok_or_else(unsupported)?,
+            None => TirTypeUnresolved::Void,
+        };
+        Ok(TirExprUnresolved::new(
+            TirExprKindUnresolved::LlvmIntrinsic {
+                name: name.clone(),
+                args,
+            },
+            ty,
+        ))
+    }
+
+    // ========================================================================
+    // time
+    // ========================================================================
+
+    /// time.sleep(seconds), with an int duration converted to float
+    fn lower_time_sleep(&mut self, args: Vec<TirExprUnresolved>) -> Result<TirExprUnresolved> {
+        self.check_intrinsic_arity(IntrinsicModule::Time, "sleep", &args, 1)?;
+        let seconds = args.into_iter().next().unwrap();
+        let seconds = match seconds.ty {
+            TirTypeUnresolved::Float => seconds,
+            TirTypeUnresolved::Int => {
+                let func = self.symbols.get_float_from_int_func();
+                self.runtime_call(func, vec![seconds])
+            }
+            other => {
+                return Err(CompilerError::TypeErrorSimple(format!(
+                    "time.sleep() argument must be int or float, got {:?}",
+                    other
+                )))
+            }
+        };
+        let func = self.symbols.get_time_sleep_func();
+        Ok(self.runtime_call(func, vec![seconds]))
+    }
+
+    // ========================================================================
+    // random
+    // ========================================================================
+
+    /// random.seed(n), or random.seed() to reseed from the clock
+    fn lower_random_seed(&mut self, args: Vec<TirExprUnresolved>) -> Result<TirExprUnresolved> {
+        if args.is_empty() {
+            let func = self.symbols.get_random_reseed_func();
+            return Ok(self.runtime_call(func, args));
+        }
+        self.check_intrinsic_arity(IntrinsicModule::Random, "seed", &args, 1)?;
+        self.check_int_args(IntrinsicModule::Random, "seed", &args)?;
+        let func = self.symbols.get_random_seed_func();
+        Ok(self.runtime_call(func, args))
+    }
+
+    /// random.choice(seq) on a list or str: `seq[<random index below len(seq)>]`,
+    /// with the sequence evaluated once
+    fn lower_random_choice(&mut self, args: Vec<TirExprUnresolved>) -> Result<TirExprUnresolved> {
+        self.check_intrinsic_arity(IntrinsicModule::Random, "choice", &args, 1)?;
+        let seq = args.into_iter().next().unwrap();
+        let str_class_id = self.symbols.get_or_create_str_class();
+        let is_sequence = seq.ty.class_id().is_some_and(|class_id| {
+            class_id == str_class_id
+                || self.symbols.class_data[class_id.index()].qualified_name == "__builtin__.list"
+        });
+        if !is_sequence {
+            return Err(CompilerError::TypeErrorSimple(format!(
+                "random.choice() argument must be a list or str, got {:?}",
+                seq.ty
+            )));
+        }
+
+        let mut stmts = Vec::new();
+        let seq = self.store_call_arg(seq, &mut stmts);
+        let seq_ty = seq.ty.clone();
+        let len = call_dunder_method!(self.symbols, &seq_ty, "__len__", vec![seq.clone()])?;
+        let index_func = self.symbols.get_random_index_func();
+        let index = self.runtime_call(index_func, vec![len]);
+        let item = call_dunder_method!(self.symbols, &seq_ty, "__getitem__", vec![seq, index])?;
+        let ty = item.ty.clone();
+        Ok(TirExprUnresolved::new(
+            TirExprKindUnresolved::Block {
+                stmts,
+                result: Box::new(item),
+            },
+            ty,
+        ))
+    }
+}
+
+/// The Python type of the values an LLVM intrinsic takes or returns as
+/// `ty`: int for i64, float for double and bool for i1
+fn python_type(ty: BasicMetadataTypeEnum<'_>) -> Option<TirTypeUnresolved> {
+    match ty {
+        BasicMetadataTypeEnum::IntType(int) => match int.get_bit_width() {
+            64 => Some(TirTypeUnresolved::Int),
+            1 => Some(TirTypeUnresolved::Bool),
+            _ => None,
+        },
+        BasicMetadataTypeEnum::FloatType(float) if float == float.get_context().f64_type() => {
+            Some(TirTypeUnresolved::Float)
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,244 @@
+//! List methods that need more than a plain runtime call
+//!
+//! `pop()` defaults to the last item and `index()` to the whole list. The
+//! searches (`index`, `count`, `remove`) and `==` compare the list's i64 slots
+//! in the runtime, which needs the items' `DICT_KIND_*` (runtime.h), so it is
+//! passed after the value:
+//!
+//! ```text
+//! xs.pop()              xs.pop(-1)
+//! xs.index(v)           xs.index(v, <kind>, 0, <i64 max>)
+//! xs.index(v, i, j)     xs.index(v, <kind>, i, j)
+//! xs.count(v)           xs.count(v, <kind>)
+//! xs.remove(v)          xs.remove(v, <kind>)
+//! xs == ys              xs.__eq__(ys, <kind>)
+//! ```
+//!
+//! Items compare like dict keys: by value for int, float, bool, str, bytes and
+//! tuples, and by identity for other objects. Items with their own `__eq__`
+//! (such as nested lists) cannot be compared in the runtime; `==` compares
+//! them one by one instead, as it does items whose type is still being
+//! inferred, and the searches reject them.
+
+use crate::ast::{BoolOp, CompareOp, Constant, Expr, Stmt, UnaryOp};
+use crate::error::{CompilerError, Result};
+use crate::span::Span;
+use crate::tir::expr_unresolved::{TirExprKindUnresolved, TirExprUnresolved};
+use crate::tir::types_unresolved::TirTypeUnresolved;
+
+use super::body_lowerer::BodyLowerer;
+
+impl<'a> BodyLowerer<'a> {
+    /// Add the defaults and item kind that the list method `attr` takes in
+    /// the runtime to the arguments of a call on a list of type `list_ty`
+    pub(crate) fn complete_list_method_args(
+        &mut self,
+        list_ty: &TirTypeUnresolved,
+        attr: &str,
+        args: &mut Vec<TirExprUnresolved>,
+    ) -> Result<()> {
+        let (min_args, max_args) = match attr {
+            "pop" => (0, 1),
+            "index" => (1, 3),
+            "count" | "remove" => (1, 1),
+            _ => return Ok(()),
+        };
+        if args.len() < min_args || args.len() > max_args {
+            return Err(CompilerError::TypeErrorSimple(format!(
+                "list.{}() takes {} to {} arguments ({} given)",
+                attr,
+                min_args,
+                max_args,
+                args.len()
+            )));
+        }
+
+        if attr == "pop" {
+            if args.is_empty() {
+                args.push(int_constant(-1));
+            }
+            return Ok(());
+        }
+
+        // The value searched for has the item type, if that is not known yet
+        let item_ty = self
+            .list_item_type(list_ty)
+            .unwrap_or_else(|| args[0].ty.clone());
+        let Some(kind) = self.value_kind(&item_ty) else {
+            return Err(CompilerError::UnsupportedFeature(format!(
+                "list.{}() on items that define __eq__",
+                attr
+            )));
+        };
+        args.insert(1, int_constant(kind));
+        if attr == "index" {
+            if args.len() == 2 {
+                args.push(int_constant(0));
+            }
+            if args.len() == 3 {
+                args.push(int_constant(i64::MAX));
+            }
+        }
+        Ok(())
+    }
+
+    /// Lower `left == right` (or `!=`) for two lists of the same type
+    pub(crate) fn lower_list_equality(
+        &mut self,
+        left: TirExprUnresolved,
+        op: CompareOp,
+        right: TirExprUnresolved,
+    ) -> Result<TirExprUnresolved> {
+        let kind = self
+            .list_item_type(&left.ty)
+            .or_else(|| self.list_item_type(&right.ty))
+            .and_then(|item_ty| self.value_kind(&item_ty));
+        let eq = match kind {
+            Some(kind) => {
+                let ty = left.ty.clone();
+                call_dunder_method!(
+                    self.symbols,
+                    &ty,
+                    "__eq__",
+                    vec![left, right, int_constant(kind)]
+                )?
+            }
+            None => self.lower_itemwise_equality(left, right)?,
+        };
+        if op == CompareOp::Eq {
+            return Ok(eq);
+        }
+        Ok(TirExprUnresolved::new(
+            TirExprKindUnresolved::UnaryOp {
+                op: UnaryOp::Not,
+                operand: Box::new(eq),
+            },
+            TirTypeUnresolved::Bool,
+        ))
+    }
+
+    /// Compare two lists item by item with `==`:
+    ///
+    /// ```text
+    /// same = len(left) == len(right)
+    /// for i in range(len(left)):
+    ///     if same and not left[i] == right[i]:
+    ///         same = False
+    /// ```
+    fn lower_itemwise_equality(
+        &mut self,
+        left: TirExprUnresolved,
+        right: TirExprUnresolved,
+    ) -> Result<TirExprUnresolved> {
+        let mut stmts = Vec::new();
+        let left = Expr::Name(self.bind_temp("_eq_left", left, &mut stmts));
+        let right = Expr::Name(self.bind_temp("_eq_right", right, &mut stmts));
+        let same = format!("_eq_same_{}", self.next_local_id);
+        let index = format!("_eq_index_{}", self.next_local_id);
+        let len = |list: &Expr| Expr::Call {
+            func: Box::new(Expr::Name("len".to_string())),
+            args: vec![list.clone()],
+            keywords: vec![],
+        };
+        let item = |list: &Expr| Expr::Subscript {
+            value: Box::new(list.clone()),
+            index: Box::new(Expr::Name(index.clone())),
+        };
+        let compare = [
+            Stmt::Assign {
+                target: Expr::Name(same.clone()),
+                value: Expr::Compare {
+                    left: Box::new(len(&left)),
+                    ops: vec![CompareOp::Eq],
+                    comparators: vec![len(&right)],
+                },
+                type_annotation: None,
+                span: Span::default(),
+            },
+            Stmt::For {
+                target: index.clone(),
+                iter: Expr::Call {
+                    func: Box::new(Expr::Name("range".to_string())),
+                    args: vec![len(&left)],
+                    keywords: vec![],
+                },
+                body: vec![Stmt::If {
+                    test: Expr::BoolOp {
+                        op: BoolOp::And,
+                        values: vec![
+                            Expr::Name(same.clone()),
+                            Expr::UnaryOp {
+                                op: UnaryOp::Not,
+                                operand: Box::new(Expr::Compare {
+                                    left: Box::new(item(&left)),
+                                    ops: vec![CompareOp::Eq],
+                                    comparators: vec![item(&right)],
+                                }),
+                            },
+                        ],
+                    },
+                    body: vec![Stmt::Assign {
+                        target: Expr::Name(same.clone()),
+                        value: Expr::Constant(Constant::Bool(false)),
+                        type_annotation: None,
+                        span: Span::default(),
+                    }],
+                    orelse: vec![],
+                    span: Span::default(),
+                }],
+                orelse: vec![],
+                span: Span::default(),
+            },
+        ];
+        for stmt in &compare {
+            stmts.extend(self.lower_stmt(stmt)?);
+        }
+        let result = self.lower_expr(&Expr::Name(same))?;
+        Ok(TirExprUnresolved::new(
+            TirExprKindUnresolved::Block {
+                stmts,
+                result: Box::new(result),
+            },
+            TirTypeUnresolved::Bool,
+        ))
+    }
+
+    /// The item type of a list, unless it is still being inferred
+    fn list_item_type(&self, list_ty: &TirTypeUnresolved) -> Option<TirTypeUnresolved> {
+        let class_id = list_ty.class_id()?;
+        match self.symbols.get_type_params(class_id).into_iter().next()? {
+            TirTypeUnresolved::TypeVar(_) => None,
+            item_ty => Some(item_ty),
+        }
+    }
+
+    /// The runtime `DICT_KIND_*` of values of type `ty`, or `None` for
+    /// classes defining `__eq__`, which the runtime cannot call
+    fn value_kind(&mut self, ty: &TirTypeUnresolved) -> Option<i64> {
+        match ty {
+            TirTypeUnresolved::Int => Some(0),
+            TirTypeUnresolved::Float => Some(1),
+            TirTypeUnresolved::Bool => Some(2),
+            TirTypeUnresolved::Tuple(_) => Some(6),
+            TirTypeUnresolved::Class(class_id) => {
+                match self.symbols.class_data[class_id.index()]
+                    .qualified_name
+                    .as_str()
+                {
+                    "__builtin__.str" => Some(3),
+                    "__builtin__.bytes" => Some(4),
+                    _ if self.symbols.resolve_method(*class_id, "__eq__").is_some() => None,
+                    _ => Some(5),
+                }
+            }
+            _ => Some(5),
+        }
+    }
+}
+
+fn int_constant(value: i64) -> TirExprUnresolved {
+    TirExprUnresolved::new(
+        TirExprKindUnresolved::Constant(Constant::Int(value)),
+        TirTypeUnresolved::Int,
+    )
+}
@@ -0,0 +1,226 @@
+//! Optional values: narrowing after None checks, and conversions
+//!
+//! After `if x is not None:` (or an `if x is None:` branch that returns or
+//! raises), reads of `x` have the inner type of its `Optional`. Narrowing is
+//! tracked per dotted path, so `self.next` narrows like a variable does. It
+//! lasts until the path (or an object on it) is assigned, or until the block
+//! the check was made in ends.
+
+use crate::ast::{target_path, BoolOp, CompareOp, Constant, Expr, UnaryOp};
+use crate::error::Result;
+use crate::tir::expr::VarRef;
+use crate::tir::expr_unresolved::{TirExprKindUnresolved, TirExprUnresolved};
+use crate::tir::stmt_unresolved::{TirLValueUnresolved, TirStmtUnresolved};
+use crate::tir::types_unresolved::TirTypeUnresolved;
+
+use super::body_lowerer::BodyLowerer;
+
+/// The paths a condition proves are not None: (when it is true, when it is false)
+pub(crate) fn condition_narrowings(test: &Expr) -> (Vec<String>, Vec<String>) {
+    match test {
+        Expr::Compare {
+            left,
+            ops,
+            comparators,
+        } if ops.len() == 1 => {
+            let path = match (left.as_ref(), &comparators[0]) {
+                (Expr::Constant(Constant::None), other)
+                | (other, Expr::Constant(Constant::None)) => target_path(other),
+                _ => None,
+            };
+            match (path, ops[0]) {
+                (Some(path), CompareOp::IsNot) => (vec![path], vec![]),
+                (Some(path), CompareOp::Is) => (vec![], vec![path]),
+                _ => (vec![], vec![]),
+            }
+        }
+        Expr::UnaryOp {
+            op: UnaryOp::Not,
+            operand,
+        } => {
+            let (when_true, when_false) = condition_narrowings(operand);
+            (when_false, when_true)
+        }
+        Expr::BoolOp {
+            op: BoolOp::And,
+            values,
+        } => (
+            values
+                .iter()
+                .flat_map(|value| condition_narrowings(value).0)
+                .collect(),
+            vec![],
+        ),
+        Expr::BoolOp {
+            op: BoolOp::Or,
+            values,
+        } => (
+            vec![],
+            values
+                .iter()
+                .flat_map(|value| condition_narrowings(value).1)
+                .collect(),
+        ),
+        // `if x:` - a None value is falsy
+        _ => (target_path(test).into_iter().collect(), vec![]),
+    }
+}
+
+impl<'a> BodyLowerer<'a> {
+    /// Record that `paths` are not None until the current block ends. Cell
+    /// variables are left alone, as nested functions may rebind them.
+    pub(crate) fn narrow(&mut self, paths: Vec<String>) {
+        let depth = self.scopes.len();
+        for path in paths {
+            let root = path.split('.').next().unwrap_or(&path);
+            if !self.cell_vars.contains(root) {
+                self.not_none.insert(path, depth);
+            }
+        }
+    }
+
+    /// Forget what is known about `path` and everything reached through it,
+    /// as it is being assigned
+    pub(crate) fn forget_narrowing(&mut self, path: &str) {
+        let prefix = format!("{}.", path);
+        self.not_none
+            .retain(|known, _| known != path && !known.starts_with(&prefix));
+    }
+
+    /// A read of `path`: its inner value when it is an Optional known not None
+    pub(crate) fn narrowed(
+        &self,
+        path: Option<String>,
+        value: TirExprUnresolved,
+    ) -> TirExprUnresolved {
+        match (path, value.ty.optional_inner()) {
+            (Some(path), Some(inner)) if self.not_none.contains_key(&path) => {
+                let inner = inner.clone();
+                TirExprUnresolved::new(
+                    TirExprKindUnresolved::UnwrapOptional {
+                        value: Box::new(value),
+                    },
+                    inner,
+                )
+            }
+            _ => value,
+        }
+    }
+
+    /// Lower the operands of `and`/`or`; each sees the narrowing of those
+    /// before it (`x is not None and x > 0`)
+    pub(crate) fn lower_bool_operands(
+        &mut self,
+        op: BoolOp,
+        values: &[Expr],
+    ) -> Result<Vec<TirExprUnresolved>> {
+        let saved = self.not_none.clone();
+        let mut lowered = Vec::new();
+        for value in values {
+            lowered.push(self.lower_expr(value)?);
+            let (when_true, when_false) = condition_narrowings(value);
+            self.narrow(match op {
+                BoolOp::And => when_true,
+                BoolOp::Or => when_false,
+            });
+        }
+        self.not_none = saved;
+        Ok(lowered)
+    }
+
+    /// `bool(value)` of an Optional: it is not None, and its value is truthy
+    pub(crate) fn lower_optional_bool(
+        &mut self,
+        value: TirExprUnresolved,
+    ) -> Result<TirExprUnresolved> {
+        let mut stmts = Vec::new();
+        let (present, unwrapped) = self.bind_optional(value, &mut stmts);
+        let truthy = self.lower_bool_conversion(unwrapped)?;
+        Ok(TirExprUnresolved::new(
+            TirExprKindUnresolved::Block {
+                stmts,
+                result: Box::new(TirExprUnresolved::new(
+                    TirExprKindUnresolved::BoolOp {
+                        op: BoolOp::And,
+                        values: vec![present, truthy],
+                    },
+                    TirTypeUnresolved::Bool,
+                )),
+            },
+            TirTypeUnresolved::Bool,
+        ))
+    }
+
+    /// `str(value)` of an Optional: "None", or the str of its value
+    pub(crate) fn lower_optional_str(
+        &mut self,
+        value: TirExprUnresolved,
+        repr: bool,
+    ) -> Result<TirExprUnresolved> {
+        let mut stmts = Vec::new();
+        let (present, unwrapped) = self.bind_optional(value, &mut stmts);
+        let text = self.lower_str_conversion(unwrapped, repr)?;
+        let str_ty = text.ty.clone();
+        let none_text = TirExprUnresolved::new(
+            TirExprKindUnresolved::Constant(Constant::Str("None".to_string())),
+            str_ty.clone(),
+        );
+        let result_name = self.bind_temp("_opt_str", none_text, &mut stmts);
+        let (result, _) = self.resolve_var(&result_name).unwrap();
+        stmts.push(TirStmtUnresolved::If {
+            cond: present,
+            then_body: vec![TirStmtUnresolved::Assign {
+                target: TirLValueUnresolved::Var(result),
+                value: text,
+            }],
+            else_body: vec![],
+        });
+        Ok(TirExprUnresolved::new(
+            TirExprKindUnresolved::Block {
+                stmts,
+                result: Box::new(TirExprUnresolved::new(
+                    TirExprKindUnresolved::Var(result),
+                    str_ty.clone(),
+                )),
+            },
+            str_ty,
+        ))
+    }
+
+    /// Store an Optional in a temporary; returns `tmp is not None` and the
+    /// unwrapped `tmp`, to be used only when it is not None
+    fn bind_optional(
+        &mut self,
+        value: TirExprUnresolved,
+        stmts: &mut Vec<TirStmtUnresolved>,
+    ) -> (TirExprUnresolved, TirExprUnresolved) {
+        let ty = value.ty.clone();
+        let inner = ty
+            .optional_inner()
+            .cloned()
+            .unwrap_or(TirTypeUnresolved::Void);
+        let name = self.bind_temp("_opt", value, stmts);
+        let (var_ref, _) = self.resolve_var(&name).unwrap();
+        let load = |var_ref: VarRef| {
+            TirExprUnresolved::new(TirExprKindUnresolved::Var(var_ref), ty.clone())
+        };
+        let present = TirExprUnresolved::new(
+            TirExprKindUnresolved::Compare {
+                left: Box::new(load(var_ref)),
+                op: CompareOp::IsNot,
+                right: Box::new(TirExprUnresolved::new(
+                    TirExprKindUnresolved::Constant(Constant::None),
+                    TirTypeUnresolved::NoneType,
+                )),
+            },
+            TirTypeUnresolved::Bool,
+        );
+        let unwrapped = TirExprUnresolved::new(
+            TirExprKindUnresolved::UnwrapOptional {
+                value: Box::new(load(var_ref)),
+            },
+            inner,
+        );
+        (present, unwrapped)
+    }
+}
@@ -5,9 +5,10 @@
 
 use std::collections::HashMap;
 
-use crate::ast::{Arg, ClassBodyItem, Module, ModuleName, Stmt};
+use crate::ast::{Arg, ClassBodyItem, MethodKind, Module, ModuleName, Stmt, TypeAnnotation};
 use crate::error::{ErrorCollector, Result};
 use crate::tir::decls::{TirClass, TirFunction};
+use crate::tir::expr::{TirConstant, TirExpr, TirExprKind};
 use crate::tir::ids::{ClassId, FuncId, ModuleId};
 use crate::tir::stmt::TirStmt;
 use crate::tir::stmt_unresolved::TirStmtUnresolved;
@@ -15,15 +16,23 @@ use crate::tir::types::TirType;
 use crate::tir::types_unresolved::TirTypeUnresolved;
 
 use super::super::body_lowerer::BodyLowerer;
+use super::super::closures::{cell_variables, LiftedFunction};
 use super::super::constraints;
+use super::super::generators::build_generator;
 use super::super::scope::ModuleScope;
 use super::super::symbols::{ClassKey, GlobalSymbols};
-use super::convert_annotation_simple;
+use super::convert_annotation_with;
 
 /// Orchestrates body lowering for all functions and methods.
 pub struct BodyLoweringPass<'a> {
     symbols: &'a mut GlobalSymbols,
     module_scopes: &'a HashMap<ModuleId, ModuleScope>,
+    /// Lowered nested functions, whose FuncIds are allocated during lowering
+    lifted_functions: Vec<TirFunction>,
+    /// Type parameter bindings of the generic class instance being lowered
+    type_args: HashMap<String, TirType>,
+    /// Parameter types of the protocol function specialization being lowered
+    param_types: HashMap<String, TirType>,
 }
 
 impl<'a> BodyLoweringPass<'a> {
@@ -34,6 +43,9 @@ impl<'a> BodyLoweringPass<'a> {
         Self {
             symbols,
             module_scopes,
+            lifted_functions: Vec::new(),
+            type_args: HashMap::new(),
+            param_types: HashMap::new(),
         }
     }
 
@@ -48,20 +60,20 @@ impl<'a> BodyLoweringPass<'a> {
         let mut errors = ErrorCollector::new();
 
         // Initialize function vector with placeholders
-        let mut tir_functions: Vec<TirFunction> = vec![
-            TirFunction {
-                id: FuncId(0),
-                name: String::new(),
-                qualified_name: String::new(),
-                params: Vec::new(),
-                return_type: TirType::Void,
-                locals: Vec::new(),
-                body: Vec::new(),
-                class: None,
-                runtime_name: None,
-            };
-            self.symbols.next_func_id as usize
-        ];
+        let placeholder = TirFunction {
+            id: FuncId(0),
+            name: String::new(),
+            qualified_name: String::new(),
+            params: Vec::new(),
+            return_type: TirType::Void,
+            locals: Vec::new(),
+            body: Vec::new(),
+            class: None,
+            runtime_name: None,
+            location: None,
+        };
+        let mut tir_functions: Vec<TirFunction> =
+            vec![placeholder.clone(); self.symbols.next_func_id as usize];
 
         // Build TirClass entries
         let mut tir_classes = self.build_classes(modules, module_order);
@@ -78,6 +90,7 @@ impl<'a> BodyLoweringPass<'a> {
                 mod_id,
                 scope,
                 &mut tir_functions,
+                &mut tir_classes,
                 &mut errors,
             );
 
@@ -91,6 +104,34 @@ impl<'a> BodyLoweringPass<'a> {
             );
         }
 
+        // Lower the generic class instances and protocol function
+        // specializations used so far, then add them and the lifted nested
+        // functions
+        let (instance_functions, instance_classes) = self.lower_instantiations(&mut errors);
+        tir_classes.extend(instance_classes);
+        for tir_func in instance_functions {
+            let idx = tir_func.id.index();
+            if idx >= tir_functions.len() {
+                tir_functions.resize(idx + 1, placeholder.clone());
+            }
+            tir_functions[idx] = tir_func;
+        }
+
+        // Add the cell classes of variables shared with nested functions
+        for &class_id in self.symbols.cell_classes.keys() {
+            let class_data = &self.symbols.class_data[class_id.index()];
+            tir_classes.push(TirClass {
+                id: class_id,
+                qualified_name: class_data.qualified_name.clone(),
+                parent: None,
+                inherited_fields: vec![],
+                fields: class_data.fields.clone(),
+                methods: vec![],
+                vtable: vec![],
+                type_params: vec![],
+            });
+        }
+
         // Sort classes by ID
         tir_classes.sort_by_key(|c| c.id.0);
 
@@ -121,6 +162,7 @@ impl<'a> BodyLoweringPass<'a> {
                         inherited_fields: class_data.inherited_fields.clone(),
                         fields: class_data.fields.clone(),
                         methods: class_data.methods.clone(),
+                        vtable: class_data.vtable.clone(),
                         type_params: vec![],
                     });
                 }
@@ -131,6 +173,8 @@ impl<'a> BodyLoweringPass<'a> {
     }
 
     /// Lower all function bodies in a module.
+    /// Generator functions also produce their iterator class.
+    #[allow(clippy::too_many_arguments)]
     fn lower_module_functions(
         &mut self,
         module: &Module,
@@ -138,6 +182,7 @@ impl<'a> BodyLoweringPass<'a> {
         mod_id: ModuleId,
         scope: &ModuleScope,
         tir_functions: &mut [TirFunction],
+        tir_classes: &mut Vec<TirClass>,
         errors: &mut ErrorCollector,
     ) {
         for stmt in &module.body {
@@ -146,11 +191,79 @@ impl<'a> BodyLoweringPass<'a> {
                 args,
                 return_type,
                 body,
+                extern_name,
+                span,
+                ..
             } = stmt
             {
+                let location = Some((mod_id, span.line));
                 let func_id = self.symbols.functions[&(mod_id, name.clone())];
                 let qualified_name = format!("{}.{}", ast_mod_id.0, name);
 
+                // An extern function is the C function it names, which is
+                // called like a runtime function
+                if let Some(c_name) = extern_name {
+                    let (params, return_type) = self.symbols.get_func_signature(func_id).clone();
+                    tir_functions[func_id.index()] = TirFunction {
+                        id: func_id,
+                        name: name.clone(),
+                        qualified_name,
+                        params: args
+                            .iter()
+                            .map(|arg| arg.name.clone())
+                            .zip(params)
+                            .collect(),
+                        return_type,
+                        locals: Vec::new(),
+                        body: Vec::new(),
+                        class: None,
+                        runtime_name: Some(c_name.clone()),
+                        location: None,
+                    };
+                    continue;
+                }
+
+                // A function taking protocols is lowered per specialization;
+                // it is itself never called
+                if self.symbols.protocol_functions.contains_key(&func_id) {
+                    tir_functions[func_id.index()] = TirFunction {
+                        id: func_id,
+                        name: name.clone(),
+                        qualified_name,
+                        params: Vec::new(),
+                        return_type: TirType::Void,
+                        locals: Vec::new(),
+                        body: Vec::new(),
+                        class: None,
+                        runtime_name: None,
+                        location: None,
+                    };
+                    continue;
+                }
+
+                if let Some(TypeAnnotation::Iterator(_)) = return_type {
+                    match self.lower_generator(
+                        name,
+                        &qualified_name,
+                        args,
+                        body,
+                        func_id,
+                        mod_id,
+                        scope,
+                        location,
+                    ) {
+                        Ok((generator_funcs, generator_class)) => {
+                            for tir_func in generator_funcs {
+                                let idx = tir_func.id.index();
+                                tir_functions[idx] = tir_func;
+                            }
+                            tir_classes.push(generator_class);
+                        }
+                        Err(e) => errors.push(e),
+                    }
+                    continue;
+                }
+
                 match self.lower_function_body(
                     name,
                     &qualified_name,
@@ -161,14 +274,67 @@ impl<'a> BodyLoweringPass<'a> {
                     mod_id,
                     scope,
                     None,
+                    None,
+                    None,
+                    None,
                 ) {
-                    Ok(tir_func) => tir_functions[func_id.index()] = tir_func,
+                    Ok(tir_func) => {
+                        tir_functions[func_id.index()] = TirFunction {
+                            location,
+                            ..tir_func
+                        }
+                    }
                     Err(e) => errors.push(e),
                 }
             }
         }
     }
 
+    /// Lower a generator function into the function constructing its iterator
+    /// and the iterator class methods.
+    #[allow(clippy::too_many_arguments)]
+    fn lower_generator(
+        &mut self,
+        name: &str,
+        qualified_name: &str,
+        args: &[Arg],
+        body: &[Stmt],
+        func_id: FuncId,
+        mod_id: ModuleId,
+        scope: &ModuleScope,
+        location: Option<(ModuleId, u32)>,
+    ) -> Result<(Vec<TirFunction>, TirClass)> {
+        let TirType::Class(class_id) = self.symbols.get_func_signature(func_id).1 else {
+            unreachable!("generator functions return their iterator class")
+        };
+        let (_, next_id) = self.symbols.methods[&(class_id, "__next__".to_string())];
+        let yield_ty = self.symbols.get_func_signature(next_id).1.clone();
+
+        // The body itself returns nothing; yields are checked against the element type
+        let tir_func = self.lower_function_body(
+            name,
+            qualified_name,
+            args,
+            None,
+            body,
+            func_id,
+            mod_id,
+            scope,
+            None,
+            None,
+            Some(&yield_ty),
+            None,
+        )?;
+        build_generator(
+            self.symbols,
+            TirFunction {
+                location,
+                ..tir_func
+            },
+            class_id,
+        )
+    }
+
     /// Lower all method bodies in a module.
     fn lower_module_methods(
         &mut self,
@@ -181,41 +347,292 @@ impl<'a> BodyLoweringPass<'a> {
     ) {
         for stmt in &module.body {
             if let Stmt::ClassDef { name, body, .. } = stmt {
-                let key = ClassKey::simple(format!("{}.{}", ast_mod_id.0, name));
-                let class_id = self.symbols.classes[&key];
+                let qualified_name = format!("{}.{}", ast_mod_id.0, name);
+                let class_id = self.symbols.classes[&ClassKey::simple(qualified_name.clone())];
+
+                // A generic class is lowered per instantiation, and a
+                // protocol has no method bodies
+                if self.symbols.generic_classes.contains_key(&class_id)
+                    || self.symbols.protocols.contains_key(&class_id)
+                {
+                    continue;
+                }
+                let methods = self.lower_class_methods(
+                    body,
+                    class_id,
+                    &qualified_name,
+                    mod_id,
+                    scope,
+                    errors,
+                );
+                for tir_func in methods {
+                    let idx = tir_func.id.index();
+                    tir_functions[idx] = tir_func;
+                }
+            }
+        }
+    }
 
-                for item in body {
-                    if let ClassBodyItem::MethodDef {
-                        name: method_name,
-                        args,
-                        return_type,
-                        body: method_body,
-                    } = item
-                    {
-                        let (_, func_id) = self.symbols.methods[&(class_id, method_name.clone())];
-                        let qualified_name = format!("{}.{}.{}", ast_mod_id.0, name, method_name);
+    /// Lower the methods of the generic class instances and the protocol
+    /// function specializations not lowered yet, including those lowering
+    /// them uses.
+    /// Returns their functions (with the nested functions lifted so far) and
+    /// the instance classes.
+    pub fn lower_instantiations(
+        &mut self,
+        errors: &mut ErrorCollector,
+    ) -> (Vec<TirFunction>, Vec<TirClass>) {
+        let mut tir_functions = Vec::new();
+        let mut tir_classes = Vec::new();
+        let module_scopes = self.module_scopes;
 
-                        // Skip self parameter for methods
-                        let method_args: Vec<_> = args.iter().skip(1).cloned().collect();
+        loop {
+            self.lower_pending_instances(&mut tir_functions, &mut tir_classes, errors);
+            let Some(func_id) = self.symbols.pending_specializations.pop() else {
+                break;
+            };
+            match self.lower_specialization(func_id, module_scopes) {
+                Ok(tir_func) => tir_functions.push(tir_func),
+                Err(e) => errors.push(e),
+            }
+        }
 
-                        match self.lower_function_body(
+        tir_functions.append(&mut self.lifted_functions);
+        (tir_functions, tir_classes)
+    }
+
+    /// Lower the methods of the generic class instances not lowered yet
+    fn lower_pending_instances(
+        &mut self,
+        tir_functions: &mut Vec<TirFunction>,
+        tir_classes: &mut Vec<TirClass>,
+        errors: &mut ErrorCollector,
+    ) {
+        let module_scopes = self.module_scopes;
+
+        while let Some(class_id) = self.symbols.pending_instances.pop() {
+            let instance = self.symbols.generic_instances.get_mut(&class_id).unwrap();
+            if let Some(error) = instance.error.take() {
+                errors.push(error);
+            }
+            self.type_args = instance.type_args.clone();
+            let generic = &self.symbols.generic_classes[&instance.template];
+            let (mod_id, body) = (generic.module, generic.body.clone());
+            let qualified_name = self.symbols.class_data[class_id.index()]
+                .qualified_name
+                .clone();
+
+            tir_functions.extend(self.lower_class_methods(
+                &body,
+                class_id,
+                &qualified_name,
+                mod_id,
+                &module_scopes[&mod_id],
+                errors,
+            ));
+            self.type_args.clear();
+
+            let class_data = &self.symbols.class_data[class_id.index()];
+            tir_classes.push(TirClass {
+                id: class_id,
+                qualified_name,
+                parent: None,
+                inherited_fields: vec![],
+                fields: class_data.fields.clone(),
+                methods: class_data.methods.clone(),
+                vtable: vec![],
+                type_params: vec![],
+            });
+        }
+    }
+
+    /// Lower a function taking protocols with the parameter types of one of
+    /// its specializations
+    fn lower_specialization(
+        &mut self,
+        func_id: FuncId,
+        module_scopes: &HashMap<ModuleId, ModuleScope>,
+    ) -> Result<TirFunction> {
+        let specialization = &self.symbols.specializations[&func_id];
+        let qualified_name = specialization.qualified_name.clone();
+        let function = &self.symbols.protocol_functions[&specialization.template];
+        let (mod_id, line, args, return_type, body) = (
+            function.module,
+            function.line,
+            function.args.clone(),
+            function.return_type.clone(),
+            function.body.clone(),
+        );
+        let name = function
+            .name
+            .rsplit('.')
+            .next()
+            .unwrap_or_default()
+            .to_string();
+
+        let params = &self.symbols.get_func_signature(func_id).0;
+        self.param_types = args
+            .iter()
+            .filter(|arg| arg.annotation.is_some())
+            .map(|arg| arg.name.clone())
+            .zip(params.iter().cloned())
+            .collect();
+        self.lower_function_body(
+            &name,
+            &qualified_name,
+            &args,
+            return_type.as_ref(),
+            &body,
+            func_id,
+            mod_id,
+            &module_scopes[&mod_id],
+            None,
+            None,
+            None,
+            None,
+        )
+        .map(|tir_func| TirFunction {
+            location: Some((mod_id, line)),
+            ..tir_func
+        })
+    }
+
+    /// Lower the methods of a class body.
+    fn lower_class_methods(
+        &mut self,
+        body: &[ClassBodyItem],
+        class_id: ClassId,
+        class_name: &str,
+        mod_id: ModuleId,
+        scope: &ModuleScope,
+        errors: &mut ErrorCollector,
+    ) -> Vec<TirFunction> {
+        let mut tir_functions = Vec::new();
+        for item in body {
+            if let ClassBodyItem::MethodDef {
+                name: method_name,
+                args,
+                return_type,
+                body: method_body,
+                kind,
+                span,
+            } = item
+            {
+                // Methods generated for dataclasses have no line of their own
+                let location = span.is_known().then_some((mod_id, span.line));
+                let (_, func_id) = self.symbols.methods[&(class_id, method_name.clone())];
+                let qualified_name = format!("{}.{}", class_name, method_name);
+
+                if *kind == MethodKind::Abstract {
+                    tir_functions.push(TirFunction {
+                        location,
+                        ..self.abstract_method(
+                            func_id,
                             method_name,
                             &qualified_name,
-                            &method_args,
-                            return_type.as_ref(),
-                            method_body,
-                            func_id,
-                            mod_id,
-                            scope,
-                            Some(class_id),
-                        ) {
-                            Ok(tir_func) => tir_functions[func_id.index()] = tir_func,
-                            Err(e) => errors.push(e),
-                        }
+                            &args[1..],
+                            class_id,
+                        )
+                    });
+                    continue;
+                }
+
+                // Only instance methods take self; a static method keeps
+                // all its parameters, and a class method's 'cls' names
+                // the class itself
+                let (method_args, owner, class_param) = match kind {
+                    MethodKind::Instance | MethodKind::Property | MethodKind::Setter => {
+                        (&args[1..], Some(class_id), None)
+                    }
+                    MethodKind::Static => (&args[..], None, None),
+                    MethodKind::Class => {
+                        (&args[1..], None, Some((args[0].name.as_str(), class_id)))
                     }
+                    MethodKind::Abstract => unreachable!("abstract methods have no body"),
+                };
+
+                match self.lower_function_body(
+                    method_name,
+                    &qualified_name,
+                    method_args,
+                    return_type.as_ref(),
+                    method_body,
+                    func_id,
+                    mod_id,
+                    scope,
+                    owner,
+                    class_param,
+                    None,
+                    None,
+                ) {
+                    Ok(tir_func) => tir_functions.push(TirFunction {
+                        location,
+                        ..tir_func
+                    }),
+                    Err(e) => errors.push(e),
                 }
             }
         }
+        tir_functions
+    }
+
+    /// The function of an abstract method, which raises if it is ever run.
+    /// Calls naming it are rejected, but an operator may still reach it
+    /// through a variable of its class.
+    fn abstract_method(
+        &mut self,
+        func_id: FuncId,
+        name: &str,
+        qualified_name: &str,
+        args: &[Arg],
+        class_id: ClassId,
+    ) -> TirFunction {
+        let (params, return_type) = self.symbols.get_func_signature(func_id).clone();
+        let str_class = self.symbols.get_or_create_str_class();
+        let exception_class = self.symbols.get_or_create_exception_class();
+
+        let message = format!(
+            "abstract method '{}.{}' called",
+            self.symbols.type_name(&TirType::Class(class_id)),
+            name
+        );
+        let exc = TirExpr::new(
+            TirExprKind::Construct {
+                class: exception_class,
+                args: vec![TirExpr::new(
+                    TirExprKind::Constant(TirConstant::Str(message)),
+                    TirType::Class(str_class),
+                )],
+            },
+            TirType::Class(exception_class),
+        );
+        // Never reached, but the function must still return its type
+        let value = match &return_type {
+            TirType::Void => None,
+            TirType::Int => Some(TirConstant::Int(0)),
+            TirType::Float => Some(TirConstant::Float(0.0)),
+            TirType::Bool => Some(TirConstant::Bool(false)),
+            _ => Some(TirConstant::None),
+        }
+        .map(|value| TirExpr::new(TirExprKind::Constant(value), return_type.clone()));
+
+        TirFunction {
+            id: func_id,
+            name: name.to_string(),
+            qualified_name: qualified_name.to_string(),
+            params: args
+                .iter()
+                .filter(|arg| arg.annotation.is_some())
+                .map(|arg| arg.name.clone())
+                .zip(params)
+                .collect(),
+            return_type,
+            locals: Vec::new(),
+            body: vec![TirStmt::Raise { exc: Some(exc) }, TirStmt::Return(value)],
+            class: Some(class_id),
+            runtime_name: None,
+            location: None,
+        }
     }
 
     /// Lower a single function or method body.
@@ -231,44 +648,72 @@ impl<'a> BodyLoweringPass<'a> {
         mod_id: ModuleId,
         scope: &ModuleScope,
         class_id: Option<ClassId>,
+        class_param: Option<(&str, ClassId)>,
+        yield_type: Option<&TirType>,
+        nested: Option<&LiftedFunction>,
     ) -> Result<TirFunction> {
+        let param_types = std::mem::take(&mut self.param_types);
+
         // Compute return type
         let ret_ty = return_type
-            .map(|ann| convert_annotation_simple(ann, self.symbols, mod_id))
+            .map(|ann| convert_annotation_with(ann, self.symbols, mod_id, &self.type_args))
             .unwrap_or(TirType::Void);
         let ret_ty_unresolved = TirTypeUnresolved::from_tir_type(&ret_ty);
 
         // Create body lowerer
         let mut lowerer = BodyLowerer::new(self.symbols, scope, class_id, ret_ty_unresolved);
+        lowerer.yield_type = yield_type.map(TirTypeUnresolved::from_tir_type);
+        lowerer.function_name = Some(qualified_name.to_string());
+        lowerer.class_param = class_param.map(|(name, class_id)| (name.to_string(), class_id));
+        lowerer.type_args = self.type_args.clone();
+
+        // A nested function takes its captured variables first
+        let mut param_names = Vec::new();
+        if let Some(nested) = nested {
+            for (captured, ty) in &nested.captures {
+                lowerer.add_param(captured, TirTypeUnresolved::from_tir_type(ty));
+                param_names.push(captured.clone());
+            }
+            lowerer.nested_functions = nested.visible.clone();
+        }
+        param_names.extend(args.iter().map(|arg| arg.name.clone()));
 
         // Add parameters
         for arg in args {
             if let Some(annot) = &arg.annotation {
-                let ty = lowerer.convert_annotation(annot);
+                let ty = match param_types.get(&arg.name) {
+                    Some(ty) => TirTypeUnresolved::from_tir_type(ty),
+                    None => lowerer.convert_annotation(annot),
+                };
                 lowerer.add_param(&arg.name, ty);
             }
         }
 
-        // Lower body statements
-        let mut tir_body_unresolved: Vec<TirStmtUnresolved> = Vec::new();
-        for stmt in body {
-            tir_body_unresolved.extend(lowerer.lower_stmt(stmt)?);
-        }
+        // Lower body statements, after moving parameters shared with nested
+        // functions into cells
+        lowerer.cell_vars = cell_variables(args, body);
+        let mut tir_body_unresolved: Vec<TirStmtUnresolved> = lowerer.box_cell_params()?;
+        tir_body_unresolved.extend(lowerer.lower_block(body));
+        std::mem::take(&mut lowerer.errors).into_result()?;
 
         // Extract data before dropping lowerer
         let constraints = lowerer.constraints.constraints.clone();
         let locals_unresolved = lowerer.locals.clone();
         let param_types_unresolved = lowerer.param_types.clone();
+        let lifted = std::mem::take(&mut lowerer.lifted_functions);
         drop(lowerer);
 
         // Solve type constraints
         let mut solver = constraints::ConstraintSolver::new(self.symbols);
-        solver.solve(&constraints)?;
+        solver
+            .solve(&constraints)
+            .map_err(|e| e.in_file(&scope.path))?;
         let substitutions = solver.get_substitutions().clone();
 
         // Resolve body
         let tir_body =
-            crate::tir::resolve::resolve_body(tir_body_unresolved, &substitutions, self.symbols)?;
+            crate::tir::resolve::resolve_body(tir_body_unresolved, &substitutions, self.symbols)
+                .map_err(|e| e.in_file(&scope.path))?;
 
         // Resolve parameter types
         let resolved_params: Vec<(String, TirType)> = param_types_unresolved
@@ -277,7 +722,7 @@ impl<'a> BodyLoweringPass<'a> {
             .map(|(i, ty)| {
                 let resolved_ty =
                     crate::tir::resolve::resolve_type(ty, &substitutions, self.symbols)?;
-                Ok((args[i].name.clone(), resolved_ty))
+                Ok((param_names[i].clone(), resolved_ty))
             })
             .collect::<Result<Vec<_>>>()?;
 
@@ -308,11 +753,34 @@ impl<'a> BodyLoweringPass<'a> {
             body: tir_body,
             class: class_id,
             runtime_name: None,
+            location: None,
         };
 
         // Validate return paths
         validate_return_paths(&tir_func)?;
 
+        // Lower the nested functions defined in this body
+        for nested in &lifted {
+            let nested_func = self.lower_function_body(
+                &nested.name,
+                &nested.qualified_name,
+                &nested.args,
+                nested.return_type.as_ref(),
+                &nested.body,
+                nested.func_id,
+                mod_id,
+                scope,
+                None,
+                None,
+                None,
+                Some(nested),
+            )?;
+            self.lifted_functions.push(TirFunction {
+                location: Some((mod_id, nested.line)),
+                ..nested_func
+            });
+        }
+
         Ok(tir_func)
     }
 }
@@ -1,20 +1,39 @@
 //! Definition Collection
 //!
 //! Collects all definitions (classes, functions, globals) and assigns numeric IDs.
-//! This runs in four phases:
+//! This runs in seven phases:
 //! 1. `register_types` - Allocate module and class IDs
 //! 2. `resolve_inheritance` - Link parent classes
-//! 3. `collect_signatures` - Gather function/method signatures and fields
-//! 4. `finalize_field_layout` - Compute field indices with inheritance
+//! 3. `check_type_uses` - Check the type arguments given to generic classes,
+//!    and that protocols only annotate function parameters
+//! 4. `collect_signatures` - Gather function/method signatures and fields
+//! 5. `collect_init_fields` - Add the fields `__init__` assigns without the
+//!    class body declaring them, and check that a subclass's `__init__`
+//!    sets the inherited ones
+//! 6. `finalize_field_layout` - Compute field indices with inheritance
+//! 7. `build_vtables` - Give the overridden methods of each class hierarchy
+//!    their vtable slots, checking that overrides keep the method's types
+//!
+//! Generic classes are instantiated on demand, whenever an annotation gives
+//! one its type arguments (see [`instantiate_generic_class`]). Likewise,
+//! functions taking protocols are specialized for the classes passed to them
+//! (see [`specialize_protocol_function`]).
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use crate::ast::{self, ClassBodyItem, Constant, Expr, Module, ModuleName, Stmt};
+use crate::ast::{
+    self, assigned_paths, calls_super_init, target_path, walk_block, BinOperator, ClassBodyItem,
+    Constant, Expr, MethodKind, Module, ModuleName, Stmt,
+};
 use crate::error::{CompilerError, ErrorCollector, Result};
-use crate::tir::ids::{ClassId, FieldId, GlobalId, MethodId, ModuleId};
+use crate::span::Span;
+use crate::tir::ids::{ClassId, FieldId, FuncId, GlobalId, MethodId, ModuleId};
 use crate::tir::types::TirType;
 
-use super::super::symbols::{ClassKey, GlobalSymbols};
+use super::super::symbols::{
+    ClassKey, GenericClass, GenericInstance, GlobalSymbols, Protocol, ProtocolFunction,
+    ProtocolSpecialization,
+};
 
 /// Collects all definitions from AST modules and registers them in the symbol table.
 pub struct DefinitionCollector<'a> {
@@ -23,6 +42,14 @@ pub struct DefinitionCollector<'a> {
     class_bases: HashMap<ClassId, Option<String>>,
 }
 
+/// Where a class is defined, for the phases that go back to its body
+struct ClassSource<'m> {
+    module: &'m Module,
+    mod_id: ModuleId,
+    body: &'m [ClassBodyItem],
+    span: Span,
+}
+
 impl<'a> DefinitionCollector<'a> {
     pub fn new(symbols: &'a mut GlobalSymbols) -> Self {
         Self {
@@ -31,17 +58,24 @@ impl<'a> DefinitionCollector<'a> {
         }
     }
 
-    /// Run all definition collection phases.
+    /// Run all definition collection phases. A phase that finds errors
+    /// leaves out what they are in (a class whose base is undefined has no
+    /// parent), and the later phases still run, so that the errors of all
+    /// of them are reported.
     pub fn run(
         &mut self,
         modules: &HashMap<ModuleName, Module>,
         module_order: &[ModuleName],
     ) -> Result<()> {
+        let mut errors = ErrorCollector::new();
         self.register_types(modules, module_order);
-        self.resolve_inheritance()?;
-        self.collect_signatures(modules, module_order);
+        errors.try_collect(self.resolve_inheritance());
+        errors.try_collect(self.check_type_uses(modules, module_order));
+        errors.try_collect(self.collect_signatures(modules, module_order));
+        errors.try_collect(self.collect_init_fields(modules, module_order));
         self.finalize_field_layout(modules, module_order);
-        Ok(())
+        errors.try_collect(self.build_vtables());
+        errors.into_result()
     }
 
     /// Phase 1: Register all module and class IDs.
@@ -53,14 +87,44 @@ impl<'a> DefinitionCollector<'a> {
     ) {
         for ast_mod_id in module_order {
             let module = &modules[ast_mod_id];
-            let _mod_id = self.symbols.alloc_module(&ast_mod_id.0);
+            let mod_id = self.symbols.alloc_module(&ast_mod_id.0);
 
             for stmt in &module.body {
-                if let Stmt::ClassDef { name, base, .. } = stmt {
+                if let Stmt::ClassDef {
+                    name,
+                    base,
+                    type_params,
+                    is_protocol,
+                    body,
+                    ..
+                } = stmt
+                {
                     let class_id = self.symbols.alloc_class();
-                    let key = ClassKey::simple(format!("{}.{}", ast_mod_id.0, name));
+                    let qualified_name = format!("{}.{}", ast_mod_id.0, name);
+                    let key = ClassKey::simple(qualified_name.clone());
                     self.symbols.classes.insert(key, class_id);
+                    self.symbols.class_data[class_id.index()].qualified_name =
+                        qualified_name.clone();
                     self.class_bases.insert(class_id, base.clone());
+                    if *is_protocol {
+                        self.symbols.protocols.insert(
+                            class_id,
+                            Protocol {
+                                methods: Vec::new(),
+                            },
+                        );
+                    }
+                    if !type_params.is_empty() {
+                        self.symbols.generic_classes.insert(
+                            class_id,
+                            GenericClass {
+                                module: mod_id,
+                                name: qualified_name,
+                                type_params: type_params.clone(),
+                                body: body.clone(),
+                            },
+                        );
+                    }
                 }
             }
         }
@@ -74,7 +138,22 @@ impl<'a> DefinitionCollector<'a> {
 
         for (&class_id, base_name_opt) in &self.class_bases.clone() {
             if let Some(base_name) = base_name_opt {
-                match self.symbols.find_class_by_name(base_name) {
+                // A class of the same module first, which may shadow a
+                // builtin exception of the same name
+                let qualified_name = &self.symbols.class_data[class_id.index()].qualified_name;
+                let own_module = qualified_name
+                    .rsplit_once('.')
+                    .and_then(|(module, _)| self.symbols.modules.get(module));
+                let base = own_module
+                    .and_then(|&mod_id| self.symbols.lookup_class(mod_id, base_name))
+                    .or_else(|| self.symbols.find_class_by_name(base_name));
+                match base {
+                    Some(parent_id) if self.symbols.generic_classes.contains_key(&parent_id) => {
+                        errors.push(CompilerError::TypeErrorSimple(format!(
+                            "Generic class '{}' cannot be subclassed",
+                            base_name
+                        )));
+                    }
                     Some(parent_id) => {
                         self.symbols.set_parent(class_id, parent_id);
                     }
@@ -91,28 +170,184 @@ impl<'a> DefinitionCollector<'a> {
         errors.into_result()
     }
 
-    /// Phase 3: Collect all function signatures, method signatures, fields, and globals.
+    /// Phase 3: Check that each generic class is given as many type arguments
+    /// as it has type parameters, and that no other class is given any.
+    /// Protocols may only be the type of a parameter of a module function,
+    /// which is specialized for each class passed for it.
+    fn check_type_uses(
+        &self,
+        modules: &HashMap<ModuleName, Module>,
+        module_order: &[ModuleName],
+    ) -> Result<()> {
+        let mut errors = ErrorCollector::new();
+
+        for ast_mod_id in module_order {
+            let mod_id = self.symbols.modules[&ast_mod_id.0];
+            let module = &modules[ast_mod_id];
+            for stmt in &module.body {
+                let start = errors.len();
+                for annotation in ast::annotations(std::slice::from_ref(stmt)) {
+                    self.check_generic_annotation(annotation, mod_id, &mut errors);
+                }
+
+                let checked = match stmt {
+                    // Not a generator, whose parameters are stored in its iterator
+                    Stmt::FunctionDef {
+                        args,
+                        return_type,
+                        body,
+                        ..
+                    } if !matches!(return_type, Some(ast::TypeAnnotation::Iterator(_))) => {
+                        let mut checked: Vec<&ast::TypeAnnotation> = args
+                            .iter()
+                            .filter_map(|arg| arg.annotation.as_ref())
+                            .filter(|annot| self.protocol_named(annot, mod_id).is_none())
+                            .collect();
+                        checked.extend(return_type);
+                        checked.extend(ast::annotations(body));
+                        checked
+                    }
+                    _ => ast::annotations(std::slice::from_ref(stmt)),
+                };
+                for annotation in checked {
+                    self.check_protocol_annotation(annotation, mod_id, &mut errors);
+                }
+                errors.locate_since(start, stmt.span(), &module.path);
+            }
+        }
+
+        errors.into_result()
+    }
+
+    /// The protocol an annotation names, if any
+    fn protocol_named(&self, annot: &ast::TypeAnnotation, mod_id: ModuleId) -> Option<ClassId> {
+        let ast::TypeAnnotation::ClassName(name) = annot else {
+            return None;
+        };
+        self.symbols
+            .lookup_class(mod_id, name)
+            .or_else(|| self.symbols.find_class_by_name(name))
+            .filter(|class_id| self.symbols.protocols.contains_key(class_id))
+    }
+
+    fn check_protocol_annotation(
+        &self,
+        annot: &ast::TypeAnnotation,
+        mod_id: ModuleId,
+        errors: &mut ErrorCollector,
+    ) {
+        match annot {
+            ast::TypeAnnotation::List(inner)
+            | ast::TypeAnnotation::Set(inner)
+            | ast::TypeAnnotation::FrozenSet(inner)
+            | ast::TypeAnnotation::Iterator(inner)
+            | ast::TypeAnnotation::Optional(inner) => {
+                self.check_protocol_annotation(inner, mod_id, errors)
+            }
+            ast::TypeAnnotation::Dict(key, value) => {
+                self.check_protocol_annotation(key, mod_id, errors);
+                self.check_protocol_annotation(value, mod_id, errors);
+            }
+            ast::TypeAnnotation::Tuple(args) | ast::TypeAnnotation::Generic { args, .. } => {
+                for arg in args {
+                    self.check_protocol_annotation(arg, mod_id, errors);
+                }
+            }
+            ast::TypeAnnotation::ClassName(name) => {
+                if self.protocol_named(annot, mod_id).is_some() {
+                    errors.push(CompilerError::TypeErrorSimple(format!(
+                        "Protocol '{}' can only be the type of a parameter of a module function",
+                        name
+                    )));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn check_generic_annotation(
+        &self,
+        annot: &ast::TypeAnnotation,
+        mod_id: ModuleId,
+        errors: &mut ErrorCollector,
+    ) {
+        match annot {
+            ast::TypeAnnotation::List(inner)
+            | ast::TypeAnnotation::Set(inner)
+            | ast::TypeAnnotation::FrozenSet(inner)
+            | ast::TypeAnnotation::Iterator(inner)
+            | ast::TypeAnnotation::Optional(inner) => {
+                self.check_generic_annotation(inner, mod_id, errors)
+            }
+            ast::TypeAnnotation::Dict(key, value) => {
+                self.check_generic_annotation(key, mod_id, errors);
+                self.check_generic_annotation(value, mod_id, errors);
+            }
+            ast::TypeAnnotation::Tuple(args) | ast::TypeAnnotation::Generic { args, .. } => {
+                if let ast::TypeAnnotation::Generic { name, .. } = annot {
+                    let generic = self
+                        .symbols
+                        .lookup_class(mod_id, name)
+                        .or_else(|| self.symbols.find_class_by_name(name))
+                        .and_then(|class_id| self.symbols.generic_classes.get(&class_id));
+                    match generic {
+                        Some(generic) if generic.type_params.len() != args.len() => {
+                            errors.push(CompilerError::TypeErrorSimple(format!(
+                                "Generic class '{}' takes {} type argument(s), got {}",
+                                name,
+                                generic.type_params.len(),
+                                args.len()
+                            )));
+                        }
+                        Some(_) => {}
+                        None => errors.push(CompilerError::TypeErrorSimple(format!(
+                            "'{}' is not a generic class",
+                            name
+                        ))),
+                    }
+                }
+                for arg in args {
+                    self.check_generic_annotation(arg, mod_id, errors);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Phase 4: Collect all function signatures, method signatures, fields, and globals.
+    /// Collects all default value errors instead of stopping at the first one.
     fn collect_signatures(
         &mut self,
         modules: &HashMap<ModuleName, Module>,
         module_order: &[ModuleName],
-    ) {
+    ) -> Result<()> {
+        let mut errors = ErrorCollector::new();
+
         for ast_mod_id in module_order {
             let module = &modules[ast_mod_id];
             let mod_id = self.symbols.modules[&ast_mod_id.0];
 
-            self.collect_functions(module, mod_id);
-            self.collect_class_members(module, ast_mod_id, mod_id);
+            self.collect_functions(module, mod_id, &mut errors);
+            self.collect_class_members(module, ast_mod_id, mod_id, &mut errors);
             self.collect_globals(module, mod_id);
         }
+
+        errors.into_result()
     }
 
-    fn collect_functions(&mut self, module: &Module, mod_id: ModuleId) {
+    fn collect_functions(
+        &mut self,
+        module: &Module,
+        mod_id: ModuleId,
+        errors: &mut ErrorCollector,
+    ) {
         for stmt in &module.body {
             if let Stmt::FunctionDef {
                 name,
                 args,
                 return_type,
+                body,
+                span,
                 ..
             } = stmt
             {
@@ -121,14 +356,54 @@ impl<'a> DefinitionCollector<'a> {
                     .filter_map(|arg| arg.annotation.as_ref())
                     .map(|ann| convert_annotation_simple(ann, self.symbols, mod_id))
                     .collect();
-                let ret_ty = return_type
-                    .as_ref()
-                    .map(|ann| convert_annotation_simple(ann, self.symbols, mod_id))
-                    .unwrap_or(TirType::Void);
+                let ret_ty = match return_type {
+                    // A generator function returns an instance of its iterator class
+                    Some(ast::TypeAnnotation::Iterator(elem)) => {
+                        let yield_ty = convert_annotation_simple(elem, self.symbols, mod_id);
+                        let param_fields = args
+                            .iter()
+                            .filter(|arg| arg.annotation.is_some())
+                            .map(|arg| arg.name.clone())
+                            .zip(params.iter().cloned())
+                            .collect();
+                        let qualified_name =
+                            format!("{}.{}", self.symbols.module_names[&mod_id], name);
+                        let class_id = self.symbols.register_generator_class(
+                            &qualified_name,
+                            param_fields,
+                            yield_ty,
+                        );
+                        TirType::Class(class_id)
+                    }
+                    Some(ann) => convert_annotation_simple(ann, self.symbols, mod_id),
+                    None => TirType::Void,
+                };
+                let start = errors.len();
+                let defaults = collect_defaults(self.symbols, args, &params, name, errors);
+                errors.locate_since(start, *span, &module.path);
+                let takes_protocol = params.iter().any(|param| {
+                    matches!(param, TirType::Class(class_id) if self.symbols.protocols.contains_key(class_id))
+                });
                 let func_id = self.symbols.alloc_func(params, ret_ty);
+                if let Some(defaults) = defaults {
+                    self.symbols.func_defaults.insert(func_id, defaults);
+                }
                 self.symbols
                     .functions
                     .insert((mod_id, name.clone()), func_id);
+                if takes_protocol {
+                    self.symbols.protocol_functions.insert(
+                        func_id,
+                        ProtocolFunction {
+                            module: mod_id,
+                            name: format!("{}.{}", self.symbols.module_names[&mod_id], name),
+                            line: span.line,
+                            args: args.clone(),
+                            return_type: return_type.clone(),
+                            body: body.clone(),
+                        },
+                    );
+                }
             }
         }
     }
@@ -138,43 +413,54 @@ impl<'a> DefinitionCollector<'a> {
         module: &Module,
         ast_mod_id: &ModuleName,
         mod_id: ModuleId,
+        errors: &mut ErrorCollector,
     ) {
         for stmt in &module.body {
-            if let Stmt::ClassDef { name, body, .. } = stmt {
+            if let Stmt::ClassDef {
+                name, body, span, ..
+            } = stmt
+            {
                 let key = ClassKey::simple(format!("{}.{}", ast_mod_id.0, name));
                 let class_id = self.symbols.classes[&key];
 
-                // Collect own fields
-                for item in body {
-                    if let ClassBodyItem::FieldDef {
-                        name: field_name,
-                        annotation,
-                    } = item
-                    {
-                        let field_ty = convert_annotation_simple(annotation, self.symbols, mod_id);
-                        self.symbols.class_data[class_id.index()]
-                            .fields
-                            .push((field_name.clone(), field_ty));
-                    }
+                // A generic class is collected per instantiation
+                if self.symbols.generic_classes.contains_key(&class_id) {
+                    continue;
                 }
-
-                // Collect methods
-                self.collect_methods(body, class_id, mod_id);
+                if self.symbols.protocols.contains_key(&class_id) {
+                    self.collect_protocol_methods(body, class_id, mod_id);
+                    continue;
+                }
+                let start = errors.len();
+                collect_class_body(
+                    self.symbols,
+                    body,
+                    class_id,
+                    mod_id,
+                    &HashMap::new(),
+                    errors,
+                );
+                errors.locate_since(start, *span, &module.path);
             }
         }
     }
 
-    fn collect_methods(&mut self, body: &[ClassBodyItem], class_id: ClassId, mod_id: ModuleId) {
-        let mut method_idx = 0u32;
+    /// Record the method signatures a protocol requires
+    fn collect_protocol_methods(
+        &mut self,
+        body: &[ClassBodyItem],
+        class_id: ClassId,
+        mod_id: ModuleId,
+    ) {
+        let mut methods = Vec::new();
         for item in body {
             if let ClassBodyItem::MethodDef {
-                name: method_name,
+                name,
                 args,
                 return_type,
                 ..
             } = item
             {
-                // Skip 'self' parameter
                 let params: Vec<TirType> = args
                     .iter()
                     .skip(1)
@@ -185,60 +471,138 @@ impl<'a> DefinitionCollector<'a> {
                     .as_ref()
                     .map(|ann| convert_annotation_simple(ann, self.symbols, mod_id))
                     .unwrap_or(TirType::Void);
-
-                let func_id = self.symbols.alloc_func(params, ret_ty);
-                let method_id = MethodId(method_idx);
-                method_idx += 1;
-
-                self.symbols
-                    .methods
-                    .insert((class_id, method_name.clone()), (method_id, func_id));
-                self.symbols.class_data[class_id.index()]
-                    .methods
-                    .push((method_name.clone(), func_id));
+                methods.push((name.clone(), params, ret_ty));
             }
         }
+        self.symbols.protocols.get_mut(&class_id).unwrap().methods = methods;
     }
 
+    /// Record the module's globals: each name assigned at the top level, with
+    /// the type it is first annotated with, or else that of the first value
+    /// assigned to it whose type `infer_value_type` tells. A global with
+    /// neither is an int, which its module init checks.
     fn collect_globals(&mut self, module: &Module, mod_id: ModuleId) {
         let mut global_idx = 0u32;
+        let mut annotated = HashSet::new();
+        // The types of the globals so far, which later values may use
+        let mut names = HashMap::new();
         for stmt in &module.body {
             if let Stmt::Assign {
                 target: Expr::Name(name),
                 type_annotation,
                 value,
+                ..
             } = stmt
             {
-                let global_id = GlobalId(global_idx);
-                global_idx += 1;
-                self.symbols
-                    .globals
-                    .insert((mod_id, name.clone()), global_id);
-
-                let ty = if let Some(annot) = type_annotation {
-                    convert_annotation_simple(annot, self.symbols, mod_id)
-                } else {
-                    // Infer type from constant value
-                    match value {
-                        Expr::Constant(Constant::Int(_)) => TirType::Int,
-                        Expr::Constant(Constant::Str(_)) => {
-                            let class_id = self.symbols.get_or_create_str_class();
-                            TirType::Class(class_id)
-                        }
-                        Expr::Constant(Constant::Bool(_)) => TirType::Bool,
-                        Expr::Constant(Constant::Bytes(_)) => {
-                            let class_id = self.symbols.get_or_create_bytes_class();
-                            TirType::Class(class_id)
-                        }
-                        _ => TirType::Int,
+                let global_id = match self.symbols.globals.get(&(mod_id, name.clone())) {
+                    Some(&global_id) => global_id,
+                    None => {
+                        let global_id = GlobalId(global_idx);
+                        global_idx += 1;
+                        self.symbols
+                            .globals
+                            .insert((mod_id, name.clone()), global_id);
+                        global_id
+                    }
+                };
+
+                let ty = match type_annotation {
+                    Some(_) if annotated.contains(&global_id) => continue,
+                    Some(annot) => {
+                        annotated.insert(global_id);
+                        convert_annotation_simple(annot, self.symbols, mod_id)
                     }
+                    None if names.contains_key(name) => continue,
+                    None => match infer_value_type(self.symbols, value, mod_id, &names) {
+                        Some(ty) => ty,
+                        None => continue,
+                    },
                 };
+                names.insert(name.clone(), ty.clone());
                 self.symbols.global_types.insert((mod_id, global_id), ty);
             }
         }
     }
 
-    /// Phase 4: Compute final field layout including inherited fields.
+    /// Phase 5: Add to each class the fields its `__init__` assigns without
+    /// declaring them (see [`collect_init_fields`]), base classes first, so
+    /// that a subclass assigning an inherited field uses that one, and check
+    /// that the subclasses set their inherited fields ([`check_super_init`])
+    fn collect_init_fields(
+        &mut self,
+        modules: &HashMap<ModuleName, Module>,
+        module_order: &[ModuleName],
+    ) -> Result<()> {
+        let mut errors = ErrorCollector::new();
+        let mut classes = Vec::new();
+        for ast_mod_id in module_order {
+            let module = &modules[ast_mod_id];
+            let mod_id = self.symbols.modules[&ast_mod_id.0];
+            for stmt in &module.body {
+                if let Stmt::ClassDef {
+                    name, body, span, ..
+                } = stmt
+                {
+                    let key = ClassKey::simple(format!("{}.{}", ast_mod_id.0, name));
+                    let class_id = self.symbols.classes[&key];
+                    // A generic class gets its fields per instantiation
+                    if self.symbols.generic_classes.contains_key(&class_id)
+                        || self.symbols.protocols.contains_key(&class_id)
+                    {
+                        continue;
+                    }
+                    classes.push((
+                        class_id,
+                        ClassSource {
+                            module,
+                            mod_id,
+                            body,
+                            span: *span,
+                        },
+                    ));
+                }
+            }
+        }
+
+        let sources: HashMap<ClassId, &ClassSource> =
+            classes.iter().map(|(id, source)| (*id, source)).collect();
+        let mut done = HashSet::new();
+        for (class_id, _) in &classes {
+            self.collect_class_init_fields(*class_id, &sources, &mut done, &mut errors);
+        }
+        errors.into_result()
+    }
+
+    fn collect_class_init_fields(
+        &mut self,
+        class_id: ClassId,
+        sources: &HashMap<ClassId, &ClassSource>,
+        done: &mut HashSet<ClassId>,
+        errors: &mut ErrorCollector,
+    ) {
+        if !done.insert(class_id) {
+            return;
+        }
+        let Some(source) = sources.get(&class_id) else {
+            return;
+        };
+        if let Some(parent) = self.symbols.class_data[class_id.index()].parent {
+            self.collect_class_init_fields(parent, sources, done, errors);
+        }
+        let start = errors.len();
+        collect_init_fields(
+            self.symbols,
+            source.body,
+            class_id,
+            source.mod_id,
+            &HashMap::new(),
+            errors,
+        );
+        check_super_init(self.symbols, source.body, class_id, errors);
+        errors.locate_since(start, source.span, &source.module.path);
+    }
+
+    /// Phase 6: Compute final field layout including inherited fields.
     /// Assigns field IDs with proper offsets accounting for inheritance.
     fn finalize_field_layout(
         &mut self,
@@ -307,6 +671,619 @@ impl<'a> DefinitionCollector<'a> {
             }
         }
     }
+
+    /// Phase 7: Build the vtable of each class, parents before their
+    /// subclasses. Exceptions are runtime objects with no vtable, and
+    /// generic classes and protocols have no subclasses.
+    ///
+    /// An override is called through its parent's method, so it must take
+    /// and return the same types, except that a class may stand for another.
+    fn build_vtables(&mut self) -> Result<()> {
+        let mut errors = ErrorCollector::new();
+        let mut classes: Vec<ClassId> = self
+            .class_bases
+            .keys()
+            .copied()
+            .filter(|class_id| {
+                !self.symbols.generic_classes.contains_key(class_id)
+                    && !self.symbols.protocols.contains_key(class_id)
+                    && !self.symbols.is_exception_subclass(*class_id)
+            })
+            .collect();
+        let depth = |mut class_id: ClassId| {
+            let mut depth = 0;
+            while let Some(parent_id) = self.symbols.class_data[class_id.index()].parent {
+                class_id = parent_id;
+                depth += 1;
+            }
+            depth
+        };
+        classes.sort_by_key(|&class_id| depth(class_id));
+        for class_id in classes {
+            let vtable = self.symbols.build_vtable(class_id);
+            if let Some(parent_id) = self.symbols.class_data[class_id.index()].parent {
+                let parent = &self.symbols.class_data[parent_id.index()];
+                for ((name, func_id), (_, overridden)) in vtable.iter().zip(&parent.vtable) {
+                    if !self.symbols.same_calling_types(*func_id, *overridden) {
+                        errors.push(CompilerError::TypeErrorSimple(format!(
+                            "Method '{}.{}' must take and return the same types as '{}.{}', which it overrides",
+                            self.symbols.type_name(&TirType::Class(class_id)),
+                            name,
+                            self.symbols.type_name(&TirType::Class(parent_id)),
+                            name
+                        )));
+                    }
+                }
+            }
+            self.symbols.class_data[class_id.index()].vtable = vtable;
+        }
+        errors.into_result()
+    }
+}
+
+/// The class of a generic class given type arguments, whose fields and
+/// method signatures are collected the first time it is used. Its method
+/// bodies are lowered later, from the pending instances.
+pub fn instantiate_generic_class(
+    symbols: &mut GlobalSymbols,
+    template: ClassId,
+    args: Vec<TirType>,
+) -> ClassId {
+    let generic = &symbols.generic_classes[&template];
+    let key = ClassKey::new(generic.name.clone(), args.clone());
+    if let Some(&class_id) = symbols.classes.get(&key) {
+        return class_id;
+    }
+    let (name, mod_id, body) = (generic.name.clone(), generic.module, generic.body.clone());
+    let mut type_args: HashMap<String, TirType> = generic
+        .type_params
+        .iter()
+        .cloned()
+        .zip(args.iter().cloned())
+        .collect();
+
+    // Registered before its body is collected, which may refer to it again
+    let class_id = symbols.alloc_class();
+    symbols.classes.insert(key, class_id);
+    let arg_names: Vec<String> = args.iter().map(|arg| symbols.type_name(arg)).collect();
+    let mut qualified_name = format!("{}[{}]", name, arg_names.join(", "));
+    if symbols
+        .class_data
+        .iter()
+        .any(|class| class.qualified_name == qualified_name)
+    {
+        // Argument classes of the same name from different modules
+        qualified_name = format!("{}#{}", qualified_name, class_id.0);
+    }
+    symbols.class_data[class_id.index()].qualified_name = qualified_name;
+    let simple_name = name.rsplit('.').next().unwrap_or(&name).to_string();
+    type_args.insert(simple_name, TirType::Class(class_id));
+
+    let mut errors = ErrorCollector::new();
+    collect_class_body(symbols, &body, class_id, mod_id, &type_args, &mut errors);
+    collect_init_fields(symbols, &body, class_id, mod_id, &type_args, &mut errors);
+    for (idx, (field_name, _)) in symbols.class_data[class_id.index()]
+        .fields
+        .clone()
+        .iter()
+        .enumerate()
+    {
+        symbols
+            .fields
+            .insert((class_id, field_name.clone()), FieldId(idx as u32));
+    }
+
+    symbols.generic_instances.insert(
+        class_id,
+        GenericInstance {
+            template,
+            type_args,
+            error: errors.into_result().err(),
+        },
+    );
+    symbols.pending_instances.push(class_id);
+    class_id
+}
+
+/// The function specialized from a function taking protocols for the
+/// classes passed for them: `params` are its parameter types, with each
+/// protocol replaced by a class. Its body is lowered later, from the
+/// pending specializations.
+pub fn specialize_protocol_function(
+    symbols: &mut GlobalSymbols,
+    template: FuncId,
+    params: Vec<TirType>,
+) -> FuncId {
+    let key = (template, params.clone());
+    if let Some(&func_id) = symbols.specialized_functions.get(&key) {
+        return func_id;
+    }
+
+    let (template_params, ret_ty) = symbols.get_func_signature(template);
+    let arg_names: Vec<String> = template_params
+        .iter()
+        .zip(&params)
+        .filter(|(template_param, param)| template_param != param)
+        .map(|(_, param)| symbols.type_name(param))
+        .collect();
+    let ret_ty = ret_ty.clone();
+    let name = &symbols.protocol_functions[&template].name;
+    let mut qualified_name = format!("{}[{}]", name, arg_names.join(", "));
+
+    let defaults = symbols.func_defaults.get(&template).cloned();
+    let func_id = symbols.alloc_func(params, ret_ty);
+    if let Some(defaults) = defaults {
+        symbols.func_defaults.insert(func_id, defaults);
+    }
+    if symbols
+        .specializations
+        .values()
+        .any(|specialization| specialization.qualified_name == qualified_name)
+    {
+        // Argument classes of the same name from different modules
+        qualified_name = format!("{}#{}", qualified_name, func_id.0);
+    }
+    symbols.specialized_functions.insert(key, func_id);
+    symbols.specializations.insert(
+        func_id,
+        ProtocolSpecialization {
+            template,
+            qualified_name,
+        },
+    );
+    symbols.pending_specializations.push(func_id);
+    func_id
+}
+
+/// Collect the fields and method signatures of a class body, with
+/// `type_args` binding the type parameters of a generic class
+fn collect_class_body(
+    symbols: &mut GlobalSymbols,
+    body: &[ClassBodyItem],
+    class_id: ClassId,
+    mod_id: ModuleId,
+    type_args: &HashMap<String, TirType>,
+    errors: &mut ErrorCollector,
+) {
+    // Collect own fields
+    for item in body {
+        if let ClassBodyItem::FieldDef {
+            name: field_name,
+            annotation,
+        } = item
+        {
+            let field_ty = convert_annotation_with(annotation, symbols, mod_id, type_args);
+            symbols.class_data[class_id.index()]
+                .fields
+                .push((field_name.clone(), field_ty));
+        }
+    }
+
+    // Collect methods
+    let mut method_idx = 0u32;
+    for item in body {
+        if let ClassBodyItem::MethodDef {
+            name: method_name,
+            args,
+            return_type,
+            kind,
+            ..
+        } = item
+        {
+            // Skip the 'self' (or 'cls') parameter
+            let skip = usize::from(*kind != MethodKind::Static);
+            let params: Vec<TirType> = args
+                .iter()
+                .skip(skip)
+                .filter_map(|arg| arg.annotation.as_ref())
+                .map(|ann| convert_annotation_with(ann, symbols, mod_id, type_args))
+                .collect();
+            let ret_ty = return_type
+                .as_ref()
+                .map(|ann| convert_annotation_with(ann, symbols, mod_id, type_args))
+                .unwrap_or(TirType::Void);
+
+            let defaults = collect_defaults(
+                symbols,
+                args.get(skip..).unwrap_or(&[]),
+                &params,
+                method_name,
+                errors,
+            );
+            let func_id = symbols.alloc_func(params, ret_ty);
+            if let Some(defaults) = defaults {
+                symbols.func_defaults.insert(func_id, defaults);
+            }
+            match kind {
+                MethodKind::Static | MethodKind::Class => {
+                    symbols.static_methods.insert(func_id);
+                }
+                MethodKind::Property => {
+                    symbols.properties.insert(func_id);
+                }
+                MethodKind::Abstract => {
+                    symbols.abstract_methods.insert(func_id);
+                }
+                MethodKind::Instance | MethodKind::Setter => {}
+            }
+            let method_id = MethodId(method_idx);
+            method_idx += 1;
+
+            symbols
+                .methods
+                .insert((class_id, method_name.clone()), (method_id, func_id));
+            symbols.class_data[class_id.index()]
+                .methods
+                .push((method_name.clone(), func_id));
+        }
+    }
+}
+
+/// Add to a class the fields its `__init__` assigns (`self.x = value`)
+/// that neither its body nor a base class declares, in the order it first
+/// assigns them. Each gets the type of the first value assigned to it, or
+/// the annotation of that assignment (`self.x: T = value`); a value whose
+/// type `infer_value_type` does not tell is an error asking for one.
+fn collect_init_fields(
+    symbols: &mut GlobalSymbols,
+    body: &[ClassBodyItem],
+    class_id: ClassId,
+    mod_id: ModuleId,
+    type_args: &HashMap<String, TirType>,
+    errors: &mut ErrorCollector,
+) {
+    let Some((args, init_body)) = body.iter().find_map(|item| match item {
+        ClassBodyItem::MethodDef {
+            name,
+            args,
+            body,
+            kind: MethodKind::Instance,
+            ..
+        } if name == "__init__" => Some((args, body)),
+        _ => None,
+    }) else {
+        return;
+    };
+    let Some(self_name) = args.first().map(|arg| arg.name.as_str()) else {
+        return;
+    };
+
+    let mut known: HashSet<String> = symbols
+        .collect_inherited_fields(class_id)
+        .into_iter()
+        .chain(symbols.class_data[class_id.index()].fields.clone())
+        .map(|(name, _)| name)
+        .collect();
+    // The types of the parameters, the variables and the fields so far
+    let mut names: HashMap<String, TirType> = HashMap::new();
+    for arg in args.iter().skip(1) {
+        if let Some(annotation) = &arg.annotation {
+            let ty = convert_annotation_with(annotation, symbols, mod_id, type_args);
+            names.insert(arg.name.clone(), ty);
+        }
+    }
+
+    walk_block(init_body, &mut |stmt| {
+        let Stmt::Assign {
+            target,
+            value,
+            type_annotation,
+            span,
+        } = stmt
+        else {
+            return;
+        };
+        // `a, self.b = x, y` assigns each target its value
+        let pairs: Vec<(&Expr, &Expr)> = match (target, value) {
+            (Expr::Tuple { elts: targets }, Expr::Tuple { elts: values })
+                if targets.len() == values.len() =>
+            {
+                targets.iter().zip(values).collect()
+            }
+            _ => vec![(target, value)],
+        };
+        for (target, value) in pairs {
+            let ty = match type_annotation {
+                Some(annotation) => Some(convert_annotation_with(
+                    annotation, symbols, mod_id, type_args,
+                )),
+                None => infer_value_type(symbols, value, mod_id, &names),
+            };
+            let field = match target {
+                Expr::Name(name) => {
+                    if let Some(ty) = ty {
+                        names.entry(name.clone()).or_insert(ty);
+                    }
+                    continue;
+                }
+                Expr::Attribute {
+                    value: object,
+                    attr,
+                } if matches!(object.as_ref(), Expr::Name(name) if name == self_name) => attr,
+                _ => continue,
+            };
+            // A declared field, or a property (assigned through its setter)
+            if known.contains(field) || symbols.resolve_method(class_id, field).is_some() {
+                continue;
+            }
+            known.insert(field.clone());
+            let Some(ty) = ty else {
+                let error = CompilerError::TypeErrorSimple(format!(
+                    "Cannot tell the type of field '{}' from the value '__init__' assigns it",
+                    field
+                ))
+                .with_help(format!(
+                    "annotate the assignment (self.{}: <type> = ...) or declare the field in the class body",
+                    field
+                ));
+                errors.push(error.at(*span));
+                continue;
+            };
+            names.insert(format!("{}.{}", self_name, field), ty.clone());
+            symbols.class_data[class_id.index()]
+                .fields
+                .push((field.clone(), ty));
+        }
+    });
+}
+
+/// Fail if a subclass's `__init__` neither calls `super().__init__(...)`
+/// nor assigns each inherited field itself: the fields the base's
+/// `__init__` would assign are otherwise never set
+fn check_super_init(
+    symbols: &GlobalSymbols,
+    body: &[ClassBodyItem],
+    class_id: ClassId,
+    errors: &mut ErrorCollector,
+) {
+    let Some(parent) = symbols.class_data[class_id.index()].parent else {
+        return;
+    };
+    let Some((args, init_body, span)) = body.iter().find_map(|item| match item {
+        ClassBodyItem::MethodDef {
+            name,
+            args,
+            body,
+            kind: MethodKind::Instance,
+            span,
+            ..
+        } if name == "__init__" => Some((args, body, span)),
+        _ => None,
+    }) else {
+        return;
+    };
+    let Some(self_name) = args.first().map(|arg| arg.name.as_str()) else {
+        return;
+    };
+    if symbols.resolve_method(parent, "__init__").is_none() || calls_super_init(init_body) {
+        return;
+    }
+
+    let assigned = assigned_paths(init_body);
+    let unset: Vec<String> = symbols
+        .collect_inherited_fields(class_id)
+        .into_iter()
+        .filter(|(name, _)| !assigned.contains(&format!("{}.{}", self_name, name)))
+        .map(|(name, _)| format!("'{}'", name))
+        .collect();
+    if unset.is_empty() {
+        return;
+    }
+    let qualified_name = &symbols.class_data[class_id.index()].qualified_name;
+    let class_name = qualified_name.rsplit('.').next().unwrap_or(qualified_name);
+    let error = CompilerError::TypeErrorSimple(format!(
+        "'{}.__init__' does not call super().__init__(), so inherited field{} {} {} never set",
+        class_name,
+        if unset.len() == 1 { "" } else { "s" },
+        unset.join(", "),
+        if unset.len() == 1 { "is" } else { "are" },
+    ))
+    .with_help("call super().__init__(...) in __init__, or assign the fields there");
+    errors.push(error.at(*span));
+}
+
+/// The type of `value`, for the values whose type is plain before
+/// lowering: literals, operators on them, the variables `names` gives the
+/// types of, and calls of the module's functions, of classes and of the
+/// builtin conversions
+fn infer_value_type(
+    symbols: &mut GlobalSymbols,
+    value: &Expr,
+    mod_id: ModuleId,
+    names: &HashMap<String, TirType>,
+) -> Option<TirType> {
+    let str_type = TirType::Class(symbols.get_or_create_str_class());
+    match value {
+        Expr::Constant(Constant::Int(_)) => Some(TirType::Int),
+        Expr::Constant(Constant::Float(_)) => Some(TirType::Float),
+        Expr::Constant(Constant::Bool(_)) => Some(TirType::Bool),
+        Expr::Constant(Constant::Str(_)) | Expr::FString { .. } => Some(str_type),
+        Expr::Constant(Constant::Bytes(_)) => {
+            Some(TirType::Class(symbols.get_or_create_bytes_class()))
+        }
+        Expr::Constant(Constant::None) => None,
+        Expr::UnaryOp {
+            op: ast::UnaryOp::USub,
+            operand,
+        } => infer_value_type(symbols, operand, mod_id, names)
+            .filter(|ty| matches!(ty, TirType::Int | TirType::Float)),
+        Expr::UnaryOp {
+            op: ast::UnaryOp::Not,
+            ..
+        }
+        | Expr::Compare { .. } => Some(TirType::Bool),
+        Expr::BinOp { left, op, right } => {
+            let left = infer_value_type(symbols, left, mod_id, names)?;
+            let right = infer_value_type(symbols, right, mod_id, names)?;
+            let arithmetic = !matches!(
+                op,
+                BinOperator::LShift
+                    | BinOperator::RShift
+                    | BinOperator::BitOr
+                    | BinOperator::BitXor
+                    | BinOperator::BitAnd
+            );
+            match (left, *op, right) {
+                (TirType::Int, BinOperator::Div, TirType::Int) => Some(TirType::Float),
+                (TirType::Int, _, TirType::Int) => Some(TirType::Int),
+                (TirType::Int | TirType::Float, _, TirType::Int | TirType::Float) if arithmetic => {
+                    Some(TirType::Float)
+                }
+                (left, BinOperator::Add, right) if left == str_type && right == str_type => {
+                    Some(str_type)
+                }
+                (text, BinOperator::Mult, TirType::Int)
+                | (TirType::Int, BinOperator::Mult, text)
+                    if text == str_type =>
+                {
+                    Some(str_type)
+                }
+                _ => None,
+            }
+        }
+        Expr::List { elts } => {
+            let elem = infer_element_type(symbols, elts, mod_id, names)?;
+            Some(TirType::Class(symbols.get_or_create_list_class(&elem)))
+        }
+        Expr::Set { elts } => {
+            let elem = infer_element_type(symbols, elts, mod_id, names)?;
+            Some(TirType::Class(symbols.get_or_create_set_class(&elem)))
+        }
+        Expr::Dict { keys, values } => {
+            let key = infer_element_type(symbols, keys, mod_id, names)?;
+            let value = infer_element_type(symbols, values, mod_id, names)?;
+            Some(TirType::Class(
+                symbols.get_or_create_dict_class(&key, &value),
+            ))
+        }
+        Expr::Tuple { elts } => elts
+            .iter()
+            .map(|elt| infer_value_type(symbols, elt, mod_id, names))
+            .collect::<Option<Vec<_>>>()
+            .map(TirType::Tuple),
+        Expr::Name(_) | Expr::Attribute { .. } => {
+            target_path(value).and_then(|path| names.get(&path).cloned())
+        }
+        Expr::Call { func, .. } => {
+            let Expr::Name(name) = func.as_ref() else {
+                return None;
+            };
+            if let Some(&func_id) = symbols.functions.get(&(mod_id, name.clone())) {
+                let ret_ty = &symbols.get_func_signature(func_id).1;
+                return Some(ret_ty.clone()).filter(|ty| *ty != TirType::Void);
+            }
+            match name.as_str() {
+                "str" | "repr" => return Some(str_type),
+                "int" | "len" => return Some(TirType::Int),
+                "float" => return Some(TirType::Float),
+                "bool" => return Some(TirType::Bool),
+                _ => {}
+            }
+            // The class an annotation of the name would be
+            let class_id = symbols
+                .lookup_class(mod_id, name)
+                .or_else(|| symbols.find_class_by_name(name))?;
+            Some(TirType::Class(class_id)).filter(|_| {
+                !symbols.generic_classes.contains_key(&class_id)
+                    && !symbols.protocols.contains_key(&class_id)
+            })
+        }
+        _ => None,
+    }
+}
+
+/// The type all of `elts` have, when it can be told and there is one
+fn infer_element_type(
+    symbols: &mut GlobalSymbols,
+    elts: &[Expr],
+    mod_id: ModuleId,
+    names: &HashMap<String, TirType>,
+) -> Option<TirType> {
+    let mut types = elts
+        .iter()
+        .map(|elt| infer_value_type(symbols, elt, mod_id, names))
+        .collect::<Option<Vec<_>>>()?
+        .into_iter();
+    let first = types.next()?;
+    types.all(|ty| ty == first).then_some(first)
+}
+
+/// Evaluate the default values of the annotated parameters in `args`.
+/// Returns one entry per signature param, or `None` if no param has a default.
+pub fn collect_defaults(
+    symbols: &mut GlobalSymbols,
+    args: &[ast::Arg],
+    params: &[TirType],
+    func_name: &str,
+    errors: &mut ErrorCollector,
+) -> Option<Vec<Option<Constant>>> {
+    let annotated = args.iter().filter(|arg| arg.annotation.is_some());
+    let mut defaults = Vec::with_capacity(params.len());
+    for (arg, param_ty) in annotated.zip(params) {
+        let default =
+            arg.default
+                .as_ref()
+                .and_then(|expr| match convert_default(symbols, expr, param_ty) {
+                    Ok(value) => Some(value),
+                    Err(msg) => {
+                        errors.push(CompilerError::TypeErrorSimple(format!(
+                            "Default value of parameter '{}' in '{}': {}",
+                            arg.name, func_name, msg
+                        )));
+                        None
+                    }
+                });
+        defaults.push(default);
+    }
+
+    if defaults.iter().any(Option::is_some) {
+        Some(defaults)
+    } else {
+        None
+    }
+}
+
+/// Check a default value expression against its parameter type.
+/// Only constants (and negated numeric constants) are supported; int
+/// defaults of float parameters are widened.
+fn convert_default(
+    symbols: &mut GlobalSymbols,
+    expr: &Expr,
+    param_ty: &TirType,
+) -> std::result::Result<Constant, String> {
+    let value = match expr {
+        Expr::Constant(value) => value.clone(),
+        Expr::UnaryOp {
+            op: ast::UnaryOp::USub,
+            operand,
+        } => match operand.as_ref() {
+            Expr::Constant(Constant::Int(n)) => Constant::Int(n.wrapping_neg()),
+            Expr::Constant(Constant::Float(f)) => Constant::Float(-f),
+            _ => return Err("must be a constant".to_string()),
+        },
+        _ => return Err("must be a constant".to_string()),
+    };
+
+    // An Optional parameter takes None or a default of its value type
+    let param_ty = match param_ty {
+        TirType::Optional(_) if value == Constant::None => return Ok(value),
+        TirType::Optional(inner) => inner.as_ref(),
+        _ => param_ty,
+    };
+    let str_class = symbols.get_or_create_str_class();
+    let bytes_class = symbols.get_or_create_bytes_class();
+    match (value, param_ty) {
+        (value @ Constant::Int(_), TirType::Int)
+        | (value @ Constant::Float(_), TirType::Float)
+        | (value @ Constant::Bool(_), TirType::Bool) => Ok(value),
+        (Constant::Int(n), TirType::Float) => Ok(Constant::Float(n as f64)),
+        (value @ Constant::Str(_), TirType::Class(id)) if *id == str_class => Ok(value),
+        (value @ Constant::Bytes(_), TirType::Class(id)) if *id == bytes_class => Ok(value),
+        (value, _) => Err(format!(
+            "{:?} does not match parameter type {:?}",
+            value, param_ty
+        )),
+    }
 }
 
 /// Convert a type annotation to TirType without full scope resolution.
@@ -315,6 +1292,17 @@ pub fn convert_annotation_simple(
     annot: &ast::TypeAnnotation,
     symbols: &mut GlobalSymbols,
     current_mod: ModuleId,
+) -> TirType {
+    convert_annotation_with(annot, symbols, current_mod, &HashMap::new())
+}
+
+/// Convert a type annotation in which the names in `type_args` (the type
+/// parameters of a generic class instance) stand for their types
+pub fn convert_annotation_with(
+    annot: &ast::TypeAnnotation,
+    symbols: &mut GlobalSymbols,
+    current_mod: ModuleId,
+    type_args: &HashMap<String, TirType>,
 ) -> TirType {
     match annot {
         ast::TypeAnnotation::Int => TirType::Int,
@@ -332,12 +1320,47 @@ pub fn convert_annotation_simple(
             let class_id = symbols.get_or_create_bytearray_class();
             TirType::Class(class_id)
         }
+        ast::TypeAnnotation::TextIO => {
+            let class_id = symbols.get_or_create_file_class();
+            TirType::Class(class_id)
+        }
         ast::TypeAnnotation::List(inner) => {
-            let elem_ty = convert_annotation_simple(inner, symbols, current_mod);
+            let elem_ty = convert_annotation_with(inner, symbols, current_mod, type_args);
             let class_id = symbols.get_or_create_list_class(&elem_ty);
             TirType::Class(class_id)
         }
+        ast::TypeAnnotation::Dict(key, value) => {
+            let key_ty = convert_annotation_with(key, symbols, current_mod, type_args);
+            let value_ty = convert_annotation_with(value, symbols, current_mod, type_args);
+            let class_id = symbols.get_or_create_dict_class(&key_ty, &value_ty);
+            TirType::Class(class_id)
+        }
+        ast::TypeAnnotation::Set(inner) => {
+            let elem_ty = convert_annotation_with(inner, symbols, current_mod, type_args);
+            let class_id = symbols.get_or_create_set_class(&elem_ty);
+            TirType::Class(class_id)
+        }
+        ast::TypeAnnotation::FrozenSet(inner) => {
+            let elem_ty = convert_annotation_with(inner, symbols, current_mod, type_args);
+            let class_id = symbols.get_or_create_frozenset_class(&elem_ty);
+            TirType::Class(class_id)
+        }
+        ast::TypeAnnotation::Tuple(elems) => TirType::Tuple(
+            elems
+                .iter()
+                .map(|elem| convert_annotation_with(elem, symbols, current_mod, type_args))
+                .collect(),
+        ),
+        ast::TypeAnnotation::Optional(inner) => TirType::Optional(Box::new(
+            convert_annotation_with(inner, symbols, current_mod, type_args),
+        )),
+        ast::TypeAnnotation::Iterator(_) => {
+            unreachable!("Iterator annotations only appear as generator return types")
+        }
         ast::TypeAnnotation::ClassName(name) => {
+            if let Some(ty) = type_args.get(name) {
+                return ty.clone();
+            }
             // First try current module, then global lookup
             if let Some(class_id) = symbols.lookup_class(current_mod, name) {
                 return TirType::Class(class_id);
@@ -348,5 +1371,22 @@ pub fn convert_annotation_simple(
             // Forward reference placeholder
             TirType::Class(ClassId(u32::MAX))
         }
+        ast::TypeAnnotation::Generic { name, args } => {
+            let args: Vec<TirType> = args
+                .iter()
+                .map(|arg| convert_annotation_with(arg, symbols, current_mod, type_args))
+                .collect();
+            let template = symbols
+                .lookup_class(current_mod, name)
+                .or_else(|| symbols.find_class_by_name(name))
+                .filter(|class_id| symbols.generic_classes.contains_key(class_id));
+            match template {
+                Some(template) => {
+                    TirType::Class(instantiate_generic_class(symbols, template, args))
+                }
+                // Reported by the generic use check
+                None => TirType::Class(ClassId(u32::MAX)),
+            }
+        }
     }
 }
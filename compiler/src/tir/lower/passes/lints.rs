@@ -0,0 +1,425 @@
+//! Lints
+//!
+//! Finds the warnings of [`crate::lint`] in the AST of each module once it
+//! has been lowered without errors: imports the module never uses, variables
+//! a function assigns but never reads, statements after a `return` or
+//! `raise`, and definitions, parameters and variables named like a builtin.
+
+use std::collections::{BTreeSet, HashMap};
+
+use crate::ast::{
+    always_exits, annotations, expr_names, global_names, nonlocal_names, walk_block, Arg,
+    ClassBodyItem, Expr, ImportKind, IntrinsicModule, Module, ModuleName, Stmt, TypeAnnotation,
+};
+use crate::lint::{Lint, Warning, BUILTIN_NAMES};
+use crate::span::Span;
+
+/// Collects the warnings of all modules.
+#[derive(Default)]
+pub struct LintCollector {
+    warnings: Vec<Warning>,
+}
+
+impl LintCollector {
+    /// Check every module, returning the warnings in source order.
+    pub fn run(
+        mut self,
+        modules: &HashMap<ModuleName, Module>,
+        module_order: &[ModuleName],
+    ) -> Vec<Warning> {
+        for ast_mod_id in module_order {
+            let module = &modules[ast_mod_id];
+            self.check_imports(module);
+            self.check_scope(module, &module.body, false);
+            self.check_reachability(module, &module.body);
+        }
+
+        // Methods the compiler generates have no place in the source
+        self.warnings.retain(|warning| warning.span.is_known());
+        self.warnings.sort();
+        self.warnings
+    }
+
+    fn warn(&mut self, module: &Module, span: Span, lint: Lint, message: String) {
+        self.warnings.push(Warning {
+            path: module.path.clone(),
+            span,
+            lint,
+            message,
+        });
+    }
+
+    /// Warn about the imports nothing in the module refers to.
+    fn check_imports(&mut self, module: &Module) {
+        let mut used = BTreeSet::new();
+        collect_reads(&module.body, &mut used);
+        for annotation in annotations(&module.body) {
+            collect_annotation_names(annotation, &mut used);
+        }
+
+        let mut imported = Vec::new();
+        for import in &module.imports {
+            imported.extend(bound_imports(
+                &import.kind,
+                &import.source_name,
+                import.span,
+            ));
+        }
+        for import in &module.intrinsic_imports {
+            // Their decorators and base classes are consumed by the converter
+            if matches!(
+                import.module,
+                IntrinsicModule::Dataclasses | IntrinsicModule::Abc
+            ) {
+                continue;
+            }
+            imported.extend(bound_imports(
+                &import.kind,
+                import.module.name(),
+                import.span,
+            ));
+        }
+
+        for (bound, shown, span) in imported {
+            if !used.contains(&bound) {
+                self.warn(
+                    module,
+                    span,
+                    Lint::UnusedImport,
+                    format!("unused import '{}'", shown),
+                );
+            }
+        }
+    }
+
+    /// Check the definitions and assignments of the module body, or of a
+    /// function's body, whose variables must also be read.
+    fn check_scope(&mut self, module: &Module, body: &[Stmt], in_function: bool) {
+        let mut definitions: Vec<(&str, Span)> = Vec::new();
+        let mut variables: Vec<(&str, Span)> = Vec::new();
+        let mut functions: Vec<(&[Arg], &[Stmt], Span)> = Vec::new();
+        walk_block(body, &mut |stmt| match stmt {
+            Stmt::FunctionDef {
+                name,
+                args,
+                body,
+                span,
+                ..
+            } => {
+                definitions.push((name.as_str(), *span));
+                functions.push((args.as_slice(), body.as_slice(), *span));
+            }
+            Stmt::ClassDef {
+                name, body, span, ..
+            } => {
+                definitions.push((name.as_str(), *span));
+                for item in body {
+                    if let ClassBodyItem::MethodDef {
+                        args, body, span, ..
+                    } = item
+                    {
+                        functions.push((args.as_slice(), body.as_slice(), *span));
+                    }
+                }
+            }
+            Stmt::Assign { target, span, .. } => {
+                let mut names = Vec::new();
+                collect_bound_names(target, &mut names);
+                variables.extend(names.into_iter().map(|name| (name, *span)));
+            }
+            Stmt::For { target, span, .. } => definitions.push((target.as_str(), *span)),
+            _ => {}
+        });
+
+        let mut seen = BTreeSet::new();
+        for &(name, span) in definitions.iter().chain(&variables) {
+            if BUILTIN_NAMES.contains(&name) && seen.insert(name) {
+                self.warn(
+                    module,
+                    span,
+                    Lint::ShadowedBuiltin,
+                    format!("'{}' shadows a builtin", name),
+                );
+            }
+        }
+
+        if in_function {
+            let mut reads = BTreeSet::new();
+            collect_reads(body, &mut reads);
+            let declared: BTreeSet<String> = global_names(body)
+                .into_iter()
+                .chain(nonlocal_names(body))
+                .collect();
+            let mut seen = BTreeSet::new();
+            for (name, span) in variables {
+                if !name.starts_with('_')
+                    && !reads.contains(name)
+                    && !declared.contains(name)
+                    && seen.insert(name)
+                {
+                    self.warn(
+                        module,
+                        span,
+                        Lint::UnusedVariable,
+                        format!("unused variable '{}'", name),
+                    );
+                }
+            }
+        }
+
+        for (args, body, span) in functions {
+            for arg in args {
+                if BUILTIN_NAMES.contains(&arg.name.as_str()) {
+                    self.warn(
+                        module,
+                        span,
+                        Lint::ShadowedBuiltin,
+                        format!("parameter '{}' shadows a builtin", arg.name),
+                    );
+                }
+            }
+            self.check_scope(module, body, true);
+        }
+    }
+
+    /// Warn at the first statement of each block that follows one that
+    /// always returns or raises.
+    fn check_reachability(&mut self, module: &Module, body: &[Stmt]) {
+        if let Some(i) = body
+            .iter()
+            .position(|stmt| always_exits(std::slice::from_ref(stmt)))
+        {
+            if let Some(next) = body.get(i + 1) {
+                self.warn(
+                    module,
+                    next.span(),
+                    Lint::UnreachableCode,
+                    "unreachable code".to_string(),
+                );
+            }
+        }
+
+        for stmt in body {
+            match stmt {
+                Stmt::FunctionDef { body, .. } | Stmt::With { body, .. } => {
+                    self.check_reachability(module, body)
+                }
+                Stmt::ClassDef { body, .. } => {
+                    for item in body {
+                        if let ClassBodyItem::MethodDef { body, .. } = item {
+                            self.check_reachability(module, body);
+                        }
+                    }
+                }
+                Stmt::If { body, orelse, .. }
+                | Stmt::While { body, orelse, .. }
+                | Stmt::For { body, orelse, .. } => {
+                    self.check_reachability(module, body);
+                    self.check_reachability(module, orelse);
+                }
+                Stmt::Try {
+                    body,
+                    handlers,
+                    orelse,
+                    finalbody,
+                    ..
+                } => {
+                    self.check_reachability(module, body);
+                    for handler in handlers {
+                        self.check_reachability(module, &handler.body);
+                    }
+                    self.check_reachability(module, orelse);
+                    self.check_reachability(module, finalbody);
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// The names an import binds, each with the name to show for it and where
+/// the import is
+fn bound_imports(kind: &ImportKind, source_name: &str, span: Span) -> Vec<(String, String, Span)> {
+    match kind {
+        // `import app.utils` binds `app`
+        ImportKind::Module { alias: None } => {
+            vec![(root_name(source_name), source_name.to_string(), span)]
+        }
+        ImportKind::Module { alias: Some(alias) } => vec![(alias.clone(), alias.clone(), span)],
+        ImportKind::Names(names) => names
+            .iter()
+            .map(|name| {
+                let local_name = name.alias.as_ref().unwrap_or(&name.name);
+                (local_name.clone(), local_name.clone(), span)
+            })
+            .collect(),
+        ImportKind::Star => Vec::new(),
+    }
+}
+
+/// The variable a possibly dotted name starts with (`app` of `app.utils`)
+fn root_name(name: &str) -> String {
+    name.split('.').next().unwrap_or(name).to_string()
+}
+
+/// Collect the variables an assignment target binds
+fn collect_bound_names<'e>(target: &'e Expr, names: &mut Vec<&'e str>) {
+    match target {
+        Expr::Name(name) => names.push(name),
+        Expr::Tuple { elts } => {
+            for elt in elts {
+                collect_bound_names(elt, names);
+            }
+        }
+        Expr::Starred { value } => collect_bound_names(value, names),
+        _ => {}
+    }
+}
+
+/// Collect the names an assignment target reads: those of the objects whose
+/// attributes or items it assigns, not the variables it binds
+fn collect_target_reads(target: &Expr, reads: &mut BTreeSet<String>) {
+    match target {
+        Expr::Name(_) => {}
+        Expr::Tuple { elts } => {
+            for elt in elts {
+                collect_target_reads(elt, reads);
+            }
+        }
+        Expr::Starred { value } => collect_target_reads(value, reads),
+        _ => reads.extend(expr_names(target)),
+    }
+}
+
+/// Collect the names a block of statements reads, including in nested
+/// blocks, functions and classes; a variable only assigned is not read
+fn collect_reads(body: &[Stmt], reads: &mut BTreeSet<String>) {
+    fn defaults(args: &[Arg], reads: &mut BTreeSet<String>) {
+        for default in args.iter().filter_map(|arg| arg.default.as_ref()) {
+            reads.extend(expr_names(default));
+        }
+    }
+
+    for stmt in body {
+        match stmt {
+            Stmt::FunctionDef { args, body, .. } => {
+                defaults(args, reads);
+                collect_reads(body, reads);
+            }
+            Stmt::ClassDef { base, body, .. } => {
+                reads.extend(base.as_deref().map(root_name));
+                for item in body {
+                    if let ClassBodyItem::MethodDef { args, body, .. } = item {
+                        defaults(args, reads);
+                        collect_reads(body, reads);
+                    }
+                }
+            }
+            Stmt::If {
+                test, body, orelse, ..
+            }
+            | Stmt::While {
+                test, body, orelse, ..
+            } => {
+                reads.extend(expr_names(test));
+                collect_reads(body, reads);
+                collect_reads(orelse, reads);
+            }
+            Stmt::For {
+                iter, body, orelse, ..
+            } => {
+                reads.extend(expr_names(iter));
+                collect_reads(body, reads);
+                collect_reads(orelse, reads);
+            }
+            Stmt::Return { value, .. } => {
+                if let Some(value) = value {
+                    reads.extend(expr_names(value));
+                }
+            }
+            Stmt::Raise { exc, cause, .. } => {
+                for value in exc.iter().chain(cause) {
+                    reads.extend(expr_names(value));
+                }
+            }
+            Stmt::Assign { target, value, .. } => {
+                collect_target_reads(target, reads);
+                reads.extend(expr_names(value));
+            }
+            Stmt::AugAssign { target, value, .. } => {
+                reads.insert(target.clone());
+                reads.extend(expr_names(value));
+            }
+            Stmt::Expr { value, .. } | Stmt::Yield { value, .. } => reads.extend(expr_names(value)),
+            Stmt::Delete { targets, .. } => {
+                for target in targets {
+                    reads.extend(expr_names(target));
+                }
+            }
+            Stmt::Try {
+                body,
+                handlers,
+                orelse,
+                finalbody,
+                ..
+            } => {
+                collect_reads(body, reads);
+                for handler in handlers {
+                    reads.extend(handler.exc_type.as_deref().map(root_name));
+                    collect_reads(&handler.body, reads);
+                }
+                collect_reads(orelse, reads);
+                collect_reads(finalbody, reads);
+            }
+            Stmt::With {
+                context,
+                target,
+                body,
+                ..
+            } => {
+                reads.extend(expr_names(context));
+                if let Some(target) = target {
+                    collect_target_reads(target, reads);
+                }
+                collect_reads(body, reads);
+            }
+            Stmt::Global { .. } | Stmt::Nonlocal { .. } => {}
+        }
+    }
+}
+
+/// Collect the class names an annotation refers to
+fn collect_annotation_names(annotation: &TypeAnnotation, names: &mut BTreeSet<String>) {
+    match annotation {
+        TypeAnnotation::ClassName(name) => {
+            names.insert(root_name(name));
+        }
+        TypeAnnotation::Generic { name, args } => {
+            names.insert(root_name(name));
+            for arg in args {
+                collect_annotation_names(arg, names);
+            }
+        }
+        TypeAnnotation::List(inner)
+        | TypeAnnotation::Set(inner)
+        | TypeAnnotation::FrozenSet(inner)
+        | TypeAnnotation::Iterator(inner)
+        | TypeAnnotation::Optional(inner) => collect_annotation_names(inner, names),
+        TypeAnnotation::Dict(key, value) => {
+            collect_annotation_names(key, names);
+            collect_annotation_names(value, names);
+        }
+        TypeAnnotation::Tuple(elts) => {
+            for elt in elts {
+                collect_annotation_names(elt, names);
+            }
+        }
+        TypeAnnotation::Int
+        | TypeAnnotation::Float
+        | TypeAnnotation::Str
+        | TypeAnnotation::Bool
+        | TypeAnnotation::Bytes
+        | TypeAnnotation::ByteArray
+        | TypeAnnotation::TextIO => {}
+    }
+}
@@ -4,11 +4,17 @@
 //! - Definition collection: Register types and collect all signatures
 //! - Scope building: Build per-module scopes with import resolution
 //! - Body lowering: Lower function and method bodies to TIR
+//! - Lints: Find the warnings of the lowered program
 
 mod bodies;
 mod definitions;
+mod lints;
 mod scopes;
 
 pub use bodies::BodyLoweringPass;
-pub use definitions::{convert_annotation_simple, DefinitionCollector};
+pub use definitions::{
+    collect_defaults, convert_annotation_simple, convert_annotation_with,
+    instantiate_generic_class, specialize_protocol_function, DefinitionCollector,
+};
+pub use lints::LintCollector;
 pub use scopes::ScopeBuilder;
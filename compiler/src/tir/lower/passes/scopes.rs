@@ -3,14 +3,27 @@
 //! Builds per-module scopes with local definitions and import resolution.
 //! After this phase, each module has a scope mapping local names to their definitions.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use crate::ast::{Expr, ImportAlias, ImportKind, Module, ModuleName, Stmt};
-use crate::tir::ids::ModuleId;
+use crate::ast::{
+    Expr, ImportAlias, ImportInfo, ImportKind, IntrinsicModule, Module, ModuleName, Stmt,
+};
+use crate::diagnostics;
+use crate::error::{CompilerError, ErrorCollector, Result};
+use crate::tir::ids::{ClassId, FuncId, GlobalId, ModuleId};
 
 use super::super::scope::ModuleScope;
 use super::super::symbols::{ClassKey, GlobalSymbols};
 
+/// What a name imported from a module is bound to
+enum Binding {
+    Function(FuncId),
+    Class(ClassId),
+    Global(ModuleId, GlobalId),
+    Module(ModuleId),
+    Intrinsic(IntrinsicModule, String),
+}
+
 /// Builds module scopes from AST modules and symbol tables.
 pub struct ScopeBuilder<'a> {
     symbols: &'a GlobalSymbols,
@@ -22,38 +35,42 @@ impl<'a> ScopeBuilder<'a> {
     }
 
     /// Build scopes for all modules.
-    /// Returns a map from module ID to its scope.
+    /// Returns a map from module ID to its scope, or the errors of the
+    /// imports that name nothing.
     pub fn build_all(
         &self,
         modules: &HashMap<ModuleName, Module>,
         module_order: &[ModuleName],
-    ) -> HashMap<ModuleId, ModuleScope> {
+    ) -> Result<HashMap<ModuleId, ModuleScope>> {
         let mut module_scopes = HashMap::new();
+        let mut errors = ErrorCollector::new();
 
         for ast_mod_id in module_order {
             let module = &modules[ast_mod_id];
             let mod_id = self.symbols.modules[&ast_mod_id.0];
-            let scope = self.build_module_scope(module, ast_mod_id, mod_id);
+            let scope = self.build_module_scope(modules, module, ast_mod_id, mod_id, &mut errors);
             module_scopes.insert(mod_id, scope);
         }
 
-        module_scopes
+        errors.into_result_with(module_scopes)
     }
 
     /// Build a scope for a single module.
     fn build_module_scope(
         &self,
+        modules: &HashMap<ModuleName, Module>,
         module: &Module,
         ast_mod_id: &ModuleName,
         mod_id: ModuleId,
+        errors: &mut ErrorCollector,
     ) -> ModuleScope {
-        let mut scope = ModuleScope::new(mod_id);
+        let mut scope = ModuleScope::new(mod_id, module.path.clone());
 
         // Add local definitions
         self.add_local_definitions(&mut scope, module, ast_mod_id, mod_id);
 
         // Resolve imports
-        self.resolve_imports(&mut scope, module);
+        self.resolve_imports(&mut scope, modules, module, errors);
 
         scope
     }
@@ -93,7 +110,34 @@ impl<'a> ScopeBuilder<'a> {
     }
 
     /// Resolve import statements and add imported symbols to scope.
-    fn resolve_imports(&self, scope: &mut ModuleScope, module: &Module) {
+    fn resolve_imports(
+        &self,
+        scope: &mut ModuleScope,
+        modules: &HashMap<ModuleName, Module>,
+        module: &Module,
+        errors: &mut ErrorCollector,
+    ) {
+        for import in &module.intrinsic_imports {
+            match &import.kind {
+                ImportKind::Module { alias } => {
+                    let local_name = alias.as_deref().unwrap_or(import.module.name());
+                    scope
+                        .intrinsic_modules
+                        .insert(local_name.to_string(), import.module);
+                }
+                ImportKind::Names(names) => {
+                    for name_alias in names {
+                        let local_name = name_alias.alias.as_ref().unwrap_or(&name_alias.name);
+                        scope
+                            .intrinsic_names
+                            .insert(local_name.clone(), (import.module, name_alias.name.clone()));
+                    }
+                }
+                // Rejected when the import is converted
+                ImportKind::Star => {}
+            }
+        }
+
         for import in &module.imports {
             let Some(&imported_mod_id) = self.symbols.modules.get(&import.module_id.0) else {
                 continue;
@@ -104,10 +148,10 @@ impl<'a> ScopeBuilder<'a> {
                     self.resolve_module_import(scope, imported_mod_id, alias, &import.module_id.0);
                 }
                 ImportKind::Names(names) => {
-                    self.resolve_named_imports(scope, imported_mod_id, names, &import.module_id.0);
+                    self.resolve_named_imports(scope, modules, import, names, errors);
                 }
                 ImportKind::Star => {
-                    self.resolve_star_import(scope, imported_mod_id, &import.module_id.0);
+                    self.resolve_star_import(scope, modules, import, errors);
                 }
             }
         }
@@ -125,79 +169,238 @@ impl<'a> ScopeBuilder<'a> {
         scope.module_aliases.insert(local_name, imported_mod_id);
     }
 
-    /// Handle `from module import name1, name2 as alias`.
+    /// Handle `from module import name1, name2 as alias`: each name is
+    /// bound to what the module defines, or imports, under it. A name the
+    /// module has nothing under is an error at the import.
     fn resolve_named_imports(
         &self,
         scope: &mut ModuleScope,
-        imported_mod_id: ModuleId,
+        modules: &HashMap<ModuleName, Module>,
+        import: &ImportInfo,
         names: &[ImportAlias],
-        module_name: &str,
+        errors: &mut ErrorCollector,
     ) {
         for name_alias in names {
             let local_name = name_alias.alias.as_ref().unwrap_or(&name_alias.name);
-
-            // Try function first
-            if let Some(&func_id) = self
-                .symbols
-                .functions
-                .get(&(imported_mod_id, name_alias.name.clone()))
-            {
-                scope.functions.insert(local_name.clone(), func_id);
-                continue;
+            let mut seen = HashSet::new();
+            match self.exported(modules, &import.module_id, &name_alias.name, &mut seen) {
+                Some(binding) => bind(scope, local_name.clone(), binding),
+                None => {
+                    let error = self.import_name_not_found(modules, import, &name_alias.name);
+                    errors.push(error.in_file(&scope.path));
+                }
             }
+        }
+    }
 
-            // Try class
-            let class_key = ClassKey::simple(format!("{}.{}", module_name, name_alias.name));
-            if let Some(&class_id) = self.symbols.classes.get(&class_key) {
-                scope.classes.insert(local_name.clone(), class_id);
-                continue;
-            }
+    /// What `module` binds `name` to: a function, class or global it
+    /// defines, or else what it imports under that name, as Python's
+    /// `from module import name` finds it. `seen` holds the modules looked
+    /// in already, so that modules importing from each other end the search.
+    fn exported(
+        &self,
+        modules: &HashMap<ModuleName, Module>,
+        module_name: &ModuleName,
+        name: &str,
+        seen: &mut HashSet<ModuleName>,
+    ) -> Option<Binding> {
+        if !seen.insert(module_name.clone()) {
+            return None;
+        }
+        let mod_id = *self.symbols.modules.get(&module_name.0)?;
+
+        if let Some(&func_id) = self.symbols.functions.get(&(mod_id, name.to_string())) {
+            return Some(Binding::Function(func_id));
+        }
+        let class_key = ClassKey::simple(format!("{}.{}", module_name.0, name));
+        if let Some(&class_id) = self.symbols.classes.get(&class_key) {
+            return Some(Binding::Class(class_id));
+        }
+        if let Some(&global_id) = self.symbols.globals.get(&(mod_id, name.to_string())) {
+            return Some(Binding::Global(mod_id, global_id));
+        }
 
-            // Try global
-            if let Some(&global_id) = self
-                .symbols
-                .globals
-                .get(&(imported_mod_id, name_alias.name.clone()))
-            {
-                scope
-                    .globals
-                    .insert(local_name.clone(), (imported_mod_id, global_id));
+        let module = modules.get(module_name)?;
+        for import in &module.imports {
+            match &import.kind {
+                ImportKind::Module { alias }
+                    if alias.as_ref().unwrap_or(&import.source_name) == name =>
+                {
+                    let &imported = self.symbols.modules.get(&import.module_id.0)?;
+                    return Some(Binding::Module(imported));
+                }
+                ImportKind::Names(names) => {
+                    let Some(imported) = names.iter().find(|alias| bound_name(alias) == name)
+                    else {
+                        continue;
+                    };
+                    return self.exported(modules, &import.module_id, &imported.name, seen);
+                }
+                ImportKind::Star => {
+                    let names = star_names(modules, &import.module_id, &mut HashSet::new());
+                    if names.iter().any(|star_name| star_name == name) {
+                        return self.exported(modules, &import.module_id, name, seen);
+                    }
+                }
+                _ => {}
             }
         }
+        module.intrinsic_imports.iter().find_map(|import| {
+            let ImportKind::Names(names) = &import.kind else {
+                return None;
+            };
+            names
+                .iter()
+                .find(|alias| bound_name(alias) == name)
+                .map(|alias| Binding::Intrinsic(import.module, alias.name.clone()))
+        })
     }
 
-    /// Handle `from module import *`.
+    /// The error for importing `name` from the module of `import`, which
+    /// binds nothing under it, suggesting the closest name it defines
+    fn import_name_not_found(
+        &self,
+        modules: &HashMap<ModuleName, Module>,
+        import: &ImportInfo,
+        name: &str,
+    ) -> CompilerError {
+        let error = CompilerError::ImportNameNotFound {
+            module: import.source_name.clone(),
+            name: name.to_string(),
+        }
+        .at(import.span);
+        let defined = modules
+            .get(&import.module_id)
+            .map(|module| module.body.as_slice())
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|stmt| match stmt {
+                Stmt::FunctionDef { name, .. } | Stmt::ClassDef { name, .. } => Some(name.as_str()),
+                Stmt::Assign {
+                    target: Expr::Name(name),
+                    ..
+                } => Some(name.as_str()),
+                _ => None,
+            });
+        match diagnostics::suggest(name, defined) {
+            Some(similar) => error.with_help(format!(
+                "the module defines a name with a similar spelling: '{}'",
+                similar
+            )),
+            None => error,
+        }
+    }
+
+    /// Handle `from module import *`: the names the module's `__all__`
+    /// lists, or else every name it defines or imports that does not start
+    /// with an underscore, as Python imports them.
     fn resolve_star_import(
         &self,
         scope: &mut ModuleScope,
-        imported_mod_id: ModuleId,
-        module_name: &str,
+        modules: &HashMap<ModuleName, Module>,
+        import: &ImportInfo,
+        errors: &mut ErrorCollector,
     ) {
-        // Import all public functions
-        for (&(m, ref name), &func_id) in &self.symbols.functions {
-            if m == imported_mod_id && !name.starts_with('_') {
-                scope.functions.insert(name.clone(), func_id);
+        let Some(module) = modules.get(&import.module_id) else {
+            return;
+        };
+        let listed = module.all.is_some();
+        for name in star_names(modules, &import.module_id, &mut HashSet::new()) {
+            let mut seen = HashSet::new();
+            match self.exported(modules, &import.module_id, &name, &mut seen) {
+                Some(binding) => bind(scope, name, binding),
+                // What else the module binds cannot be imported (`import
+                // sys` binds a module the compiler provides)
+                None if !listed => {}
+                None => {
+                    let error = CompilerError::ImportNameNotFound {
+                        module: import.source_name.clone(),
+                        name,
+                    }
+                    .at(import.span)
+                    .with_help("the name is listed in the module's __all__");
+                    errors.push(error.in_file(&scope.path));
+                }
             }
         }
+    }
+}
 
-        // Import all public classes
-        let prefix = format!("{}.", module_name);
-        for (key, &class_id) in &self.symbols.classes {
-            if key.qualified_name.starts_with(&prefix) && key.type_params.is_empty() {
-                let class_name = &key.qualified_name[prefix.len()..];
-                if !class_name.starts_with('_') {
-                    scope.classes.insert(class_name.to_string(), class_id);
-                }
-            }
+/// Bind `name` in `scope` to what it was imported as
+fn bind(scope: &mut ModuleScope, name: String, binding: Binding) {
+    match binding {
+        Binding::Function(func_id) => {
+            scope.functions.insert(name, func_id);
         }
+        Binding::Class(class_id) => {
+            scope.classes.insert(name, class_id);
+        }
+        Binding::Global(mod_id, global_id) => {
+            scope.globals.insert(name, (mod_id, global_id));
+        }
+        Binding::Module(mod_id) => {
+            scope.module_aliases.insert(name, mod_id);
+        }
+        Binding::Intrinsic(module, member) => {
+            scope.intrinsic_names.insert(name, (module, member));
+        }
+    }
+}
+
+/// The name an imported name is bound to: its alias, if it has one
+fn bound_name(name: &ImportAlias) -> &String {
+    name.alias.as_ref().unwrap_or(&name.name)
+}
 
-        // Import all public globals
-        for (&(m, ref name), &global_id) in &self.symbols.globals {
-            if m == imported_mod_id && !name.starts_with('_') {
-                scope
-                    .globals
-                    .insert(name.clone(), (imported_mod_id, global_id));
+/// The names `from module import *` imports: those its `__all__` lists, or
+/// else those it defines or imports (star imports included), but for the
+/// ones starting with an underscore. `seen` holds the modules looked in
+/// already, so that modules star-importing each other end the search.
+fn star_names(
+    modules: &HashMap<ModuleName, Module>,
+    module_name: &ModuleName,
+    seen: &mut HashSet<ModuleName>,
+) -> Vec<String> {
+    let Some(module) = modules.get(module_name) else {
+        return Vec::new();
+    };
+    if let Some(all) = &module.all {
+        return all.clone();
+    }
+    if !seen.insert(module_name.clone()) {
+        return Vec::new();
+    }
+
+    let defined = module.body.iter().filter_map(|stmt| match stmt {
+        Stmt::FunctionDef { name, .. } | Stmt::ClassDef { name, .. } => Some(name.clone()),
+        Stmt::Assign {
+            target: Expr::Name(name),
+            ..
+        } => Some(name.clone()),
+        _ => None,
+    });
+    let mut imported = Vec::new();
+    for import in &module.imports {
+        match &import.kind {
+            ImportKind::Module { alias } => {
+                imported.push(alias.clone().unwrap_or_else(|| import.source_name.clone()))
             }
+            ImportKind::Names(names) => imported.extend(names.iter().map(bound_name).cloned()),
+            ImportKind::Star => imported.extend(star_names(modules, &import.module_id, seen)),
+        }
+    }
+    for import in &module.intrinsic_imports {
+        if let ImportKind::Names(names) = &import.kind {
+            imported.extend(names.iter().map(bound_name).cloned());
         }
     }
+
+    let mut names: Vec<String> = defined
+        .chain(imported)
+        // `import app.utils` binds `app`, a package, which is not a module
+        .filter(|name| !name.starts_with('_') && !name.contains('.'))
+        .collect();
+    names.sort();
+    names.dedup();
+    names
 }
@@ -1,9 +1,14 @@
 use std::collections::HashMap;
+use std::path::PathBuf;
 
+use crate::ast::IntrinsicModule;
 use crate::tir::ids::{ClassId, FuncId, GlobalId, ModuleId};
 
 /// Per-module scope for name resolution
 pub(crate) struct ModuleScope {
+    /// The module's source file, which errors point into
+    pub(crate) path: PathBuf,
+
     /// Local function names -> FuncId
     pub(crate) functions: HashMap<String, FuncId>,
 
@@ -15,15 +20,25 @@ pub(crate) struct ModuleScope {
 
     /// Module alias names -> ModuleId (for `import x` or `import x as y`)
     pub(crate) module_aliases: HashMap<String, ModuleId>,
+
+    /// Intrinsic module alias names -> module (for `import sys`)
+    pub(crate) intrinsic_modules: HashMap<String, IntrinsicModule>,
+
+    /// Names imported from intrinsic modules -> (module, member name)
+    /// (for `from sys import argv`)
+    pub(crate) intrinsic_names: HashMap<String, (IntrinsicModule, String)>,
 }
 
 impl ModuleScope {
-    pub(crate) fn new(_mod_id: ModuleId) -> Self {
+    pub(crate) fn new(_mod_id: ModuleId, path: PathBuf) -> Self {
         ModuleScope {
+            path,
             functions: HashMap::new(),
             classes: HashMap::new(),
             globals: HashMap::new(),
             module_aliases: HashMap::new(),
+            intrinsic_modules: HashMap::new(),
+            intrinsic_names: HashMap::new(),
         }
     }
 }
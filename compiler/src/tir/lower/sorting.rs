@@ -0,0 +1,201 @@
+//! sorted() and list.sort()
+//!
+//! Sorting happens in the runtime on the list's i64 slots. The runtime computes
+//! the stable order of a list of keys, and the list is then permuted to match.
+//! A `key` function is applied by building the keys with a comprehension, so it
+//! can be anything callable by name (a function, a nested function, a builtin
+//! such as `len`, or a class):
+//!
+//! ```text
+//! xs.sort(key=f, reverse=r)      _sort_list = xs
+//!                                _sort_keys = [f(_sort_item) for _sort_item in _sort_list]
+//!                                _sort_list.__permute__(_sort_keys.__argsort__(<kind>, r))
+//!
+//! sorted(it, key=f)              _sort_src = it
+//!                                _sort_list = [_sort_item for _sort_item in _sort_src]
+//!                                ... sorted in place as above ...
+//!                                <value: _sort_list>
+//! ```
+//!
+//! Without a key the list is its own list of keys. Keys must be int, float,
+//! bool or str; `<kind>` is their `DICT_KIND_*` (runtime.h), which tells the
+//! runtime how to compare them.
+
+use crate::ast::{Comprehension, Constant, Expr, Keyword};
+use crate::error::{CompilerError, Result};
+use crate::tir::expr_unresolved::{TirExprKindUnresolved, TirExprUnresolved};
+use crate::tir::stmt_unresolved::TirStmtUnresolved;
+use crate::tir::types_unresolved::TirTypeUnresolved;
+
+use super::body_lowerer::BodyLowerer;
+use super::stmt_lowering::ComprehensionElt;
+
+impl<'a> BodyLowerer<'a> {
+    /// Lower `sorted(iterable, key=..., reverse=...)`: copy the items into a
+    /// new list and sort that in place
+    pub(crate) fn lower_sorted(
+        &mut self,
+        args: Vec<TirExprUnresolved>,
+        keywords: &[Keyword],
+    ) -> Result<TirExprUnresolved> {
+        if args.len() != 1 {
+            return Err(CompilerError::TypeErrorSimple(format!(
+                "sorted() takes exactly one positional argument ({} given)",
+                args.len()
+            )));
+        }
+        let iterable = args.into_iter().next().unwrap();
+
+        let mut stmts = Vec::new();
+        let source = self.bind_temp("_sort_src", iterable, &mut stmts);
+        let copy = self.lower_map_comprehension(None, &source)?;
+        let list = self.bind_temp("_sort_list", copy, &mut stmts);
+
+        let sort = self.lower_sort_in_place(&list, keywords)?;
+        stmts.push(TirStmtUnresolved::Expr(sort));
+
+        let result = self.lower_expr(&Expr::Name(list))?;
+        let ty = result.ty.clone();
+        Ok(TirExprUnresolved::new(
+            TirExprKindUnresolved::Block {
+                stmts,
+                result: Box::new(result),
+            },
+            ty,
+        ))
+    }
+
+    /// Lower `xs.sort(key=..., reverse=...)` on an already-lowered list
+    pub(crate) fn lower_list_sort(
+        &mut self,
+        receiver: TirExprUnresolved,
+        args: Vec<TirExprUnresolved>,
+        keywords: &[Keyword],
+    ) -> Result<TirExprUnresolved> {
+        if !args.is_empty() {
+            return Err(CompilerError::TypeErrorSimple(
+                "sort() takes no positional arguments".to_string(),
+            ));
+        }
+
+        let mut stmts = Vec::new();
+        let list = self.bind_temp("_sort_list", receiver, &mut stmts);
+        let sort = self.lower_sort_in_place(&list, keywords)?;
+        Ok(TirExprUnresolved::new(
+            TirExprKindUnresolved::Block {
+                stmts,
+                result: Box::new(sort),
+            },
+            TirTypeUnresolved::Void,
+        ))
+    }
+
+    /// Sort the list bound to `list` in place, honoring the `key` and
+    /// `reverse` keyword arguments
+    fn lower_sort_in_place(
+        &mut self,
+        list: &str,
+        keywords: &[Keyword],
+    ) -> Result<TirExprUnresolved> {
+        let mut key = None;
+        let mut reverse = None;
+        for keyword in keywords {
+            match keyword.arg.as_str() {
+                // key=None is the same as no key
+                "key" if matches!(keyword.value, Expr::Constant(Constant::None)) => key = None,
+                "key" => key = Some(&keyword.value),
+                "reverse" => {
+                    let value = self.lower_expr(&keyword.value)?;
+                    if value.ty != TirTypeUnresolved::Bool {
+                        return Err(CompilerError::TypeErrorSimple(format!(
+                            "sort() reverse must be bool, got {:?}",
+                            value.ty
+                        )));
+                    }
+                    reverse = Some(value);
+                }
+                other => {
+                    return Err(CompilerError::TypeErrorSimple(format!(
+                        "'{}' is an invalid keyword argument for sort()",
+                        other
+                    )))
+                }
+            }
+        }
+
+        let list_expr = self.lower_expr(&Expr::Name(list.to_string()))?;
+        let keys = match key {
+            Some(key) => self.lower_map_comprehension(Some(key), list)?,
+            None => list_expr.clone(),
+        };
+        let kind = self.sort_key_kind(&keys.ty)?;
+        let reverse = reverse.unwrap_or_else(|| {
+            TirExprUnresolved::new(
+                TirExprKindUnresolved::Constant(Constant::Bool(false)),
+                TirTypeUnresolved::Bool,
+            )
+        });
+
+        let keys_ty = keys.ty.clone();
+        let kind = TirExprUnresolved::new(
+            TirExprKindUnresolved::Constant(Constant::Int(kind)),
+            TirTypeUnresolved::Int,
+        );
+        let order = call_dunder_method!(
+            self.symbols,
+            &keys_ty,
+            "__argsort__",
+            vec![keys, kind, reverse]
+        )?;
+        let list_ty = list_expr.ty.clone();
+        call_dunder_method!(
+            self.symbols,
+            &list_ty,
+            "__permute__",
+            vec![list_expr, order]
+        )
+    }
+
+    /// `[f(_sort_item) for _sort_item in source]`, or a plain copy of `source`
+    /// when there is no function
+    fn lower_map_comprehension(
+        &mut self,
+        func: Option<&Expr>,
+        source: &str,
+    ) -> Result<TirExprUnresolved> {
+        let item = Expr::Name(format!("_sort_item_{}", self.next_local_id));
+        let elt = match func {
+            Some(func) => Expr::Call {
+                func: Box::new(func.clone()),
+                args: vec![item.clone()],
+                keywords: vec![],
+            },
+            None => item.clone(),
+        };
+        let generators = [Comprehension {
+            target: item,
+            iter: Expr::Name(source.to_string()),
+            ifs: vec![],
+        }];
+        self.lower_comprehension(ComprehensionElt::List(&elt), &generators)
+    }
+
+    /// The runtime `DICT_KIND_*` of a list's items, which must be sortable keys
+    fn sort_key_kind(&mut self, list_ty: &TirTypeUnresolved) -> Result<i64> {
+        let str_class_id = self.symbols.get_or_create_str_class();
+        let elem_ty = list_ty
+            .class_id()
+            .and_then(|class_id| self.symbols.get_type_params(class_id).into_iter().next())
+            .unwrap_or_else(|| list_ty.clone());
+        match elem_ty {
+            TirTypeUnresolved::Int => Ok(0),
+            TirTypeUnresolved::Float => Ok(1),
+            TirTypeUnresolved::Bool => Ok(2),
+            TirTypeUnresolved::Class(class_id) if class_id == str_class_id => Ok(3),
+            other => Err(CompilerError::TypeErrorSimple(format!(
+                "Cannot sort by keys of type {:?}; keys must be int, float, bool or str",
+                other
+            ))),
+        }
+    }
+}
@@ -1,140 +1,180 @@
-use crate::ast::{Constant, Expr, Stmt, UnaryOp};
+use crate::ast::{
+    always_exits, assigned_names, assigned_paths, property_setter_name, referenced_names,
+    target_path, BinOperator, CompareOp, Comprehension, Constant, Expr, Keyword, Stmt,
+    TypeAnnotation, UnaryOp,
+};
 use crate::error::{CompilerError, Result};
+use crate::span::Span;
 use crate::tir::expr::VarRef;
 use crate::tir::expr_unresolved::{TirExprKindUnresolved, TirExprUnresolved};
+use crate::tir::ids::{ClassId, LocalId};
 use crate::tir::stmt_unresolved::{
     TirExceptHandlerUnresolved, TirLValueUnresolved, TirStmtUnresolved,
 };
+use crate::tir::types::TirType;
 use crate::tir::types_unresolved::TirTypeUnresolved;
 
 use super::body_lowerer::BodyLowerer;
+use super::expr_lowering::binop_dunder;
+use super::optional::condition_narrowings;
+
+/// What a comprehension collects on each iteration
+#[derive(Clone, Copy)]
+pub(crate) enum ComprehensionElt<'e> {
+    /// [elt for ...]
+    List(&'e Expr),
+    /// {elt for ...}
+    Set(&'e Expr),
+    /// {key: value for ...}
+    Dict(&'e Expr, &'e Expr),
+}
+
+/// A `map(func, iterable)` or `filter(func, iterable)` call
+pub(crate) struct MapFilterCall<'e> {
+    /// Applied to each item (map) or tested on it; `filter(None, xs)` tests the item itself
+    func: Option<&'e Expr>,
+    iterable: &'e Expr,
+    filter: bool,
+}
 
 impl<'a> BodyLowerer<'a> {
+    /// Lower a statement; the code it lowers to, and errors in it, are placed
+    /// at its position (or the enclosing statement's, for one the compiler
+    /// generated)
     pub(crate) fn lower_stmt(&mut self, stmt: &Stmt) -> Result<Vec<TirStmtUnresolved>> {
+        let outer = self.span;
+        if stmt.span().is_known() {
+            self.span = stmt.span();
+        }
+        let span = self.span;
+        let lowered = self.lower_stmt_kind(stmt);
+        self.span = outer;
+
+        let mut lowered = lowered.map_err(|e| e.at(span).in_file(&self.scope.path))?;
+        for tir_stmt in &mut lowered {
+            tir_stmt.locate(span);
+        }
+        Ok(lowered)
+    }
+
+    /// Lower a block of statements written in the source. A statement that
+    /// fails is left out and its error collected (see `recover`), so that
+    /// the errors of the whole body are reported at once.
+    pub(crate) fn lower_block(&mut self, body: &[Stmt]) -> Vec<TirStmtUnresolved> {
+        let mut lowered = Vec::new();
+        for stmt in body {
+            let depth = self.scopes.len();
+            match self.lower_stmt(stmt) {
+                Ok(stmts) => lowered.extend(stmts),
+                Err(error) => {
+                    // Leave the blocks the statement failed in
+                    while self.scopes.len() > depth {
+                        self.exit_scope();
+                    }
+                    self.recover(stmt, error);
+                }
+            }
+        }
+        lowered
+    }
+
+    /// Collect the error of a statement that failed to lower, and bind the
+    /// names it would have bound: to their declared type if annotated, else
+    /// to placeholders of unknown type. Errors in statements using a
+    /// placeholder are likely caused by the first one, and are not reported.
+    fn recover(&mut self, stmt: &Stmt, error: CompilerError) {
+        if let Stmt::Assign {
+            target: Expr::Name(name),
+            type_annotation: Some(annot),
+            ..
+        } = stmt
+        {
+            if self.resolve_var(name).is_none() {
+                let ty = self.convert_annotation(annot);
+                self.alloc_local(name, ty);
+            }
+        }
+        let stmt = std::slice::from_ref(stmt);
+        if referenced_names(stmt).is_disjoint(&self.placeholders) {
+            self.errors.push(error);
+        }
+        for name in assigned_names(stmt) {
+            if self.resolve_var(&name).is_none() {
+                let ty = self.constraints.fresh_type_var();
+                self.alloc_local(&name, ty);
+                self.placeholders.insert(name);
+            }
+        }
+    }
+
+    fn lower_stmt_kind(&mut self, stmt: &Stmt) -> Result<Vec<TirStmtUnresolved>> {
         match stmt {
             Stmt::Assign {
                 target,
                 value,
                 type_annotation,
+                ..
             } => {
-                let value_expr = self.lower_expr(value)?;
-
-                match target {
+                // The target's type (existing variable or annotation) types empty literals
+                let value_expr = match target {
                     Expr::Name(name) => {
-                        // Check if this is a new variable or existing
-                        if let Some((var_ref, var_ty)) = self.resolve_var(name) {
-                            // Existing variable - check type compatibility
-                            if !value_expr.ty.is_compatible_with(&var_ty) {
-                                return Err(CompilerError::TypeErrorSimple(format!(
-                                    "Cannot assign {:?} to variable '{}' of type {:?}",
-                                    value_expr.ty, name, var_ty
-                                )));
-                            }
-                            Ok(vec![TirStmtUnresolved::Assign {
-                                target: TirLValueUnresolved::Var(var_ref),
-                                value: value_expr,
-                            }])
-                        } else {
-                            // New variable - create Let
-                            let ty = if let Some(annot) = type_annotation {
-                                let declared_ty = self.convert_annotation(annot);
-                                // Check that value type matches declared type
-                                if !value_expr.ty.is_compatible_with(&declared_ty) {
-                                    return Err(CompilerError::TypeErrorSimple(format!(
-                                        "Cannot assign {:?} to variable of type {:?}",
-                                        value_expr.ty, declared_ty
-                                    )));
-                                }
-                                declared_ty
-                            } else {
-                                value_expr.ty.clone()
-                            };
-                            let local_id = self.alloc_local(name, ty.clone());
-                            Ok(vec![TirStmtUnresolved::Let {
-                                local: local_id,
-                                ty,
-                                init: value_expr,
-                            }])
-                        }
-                    }
-
-                    Expr::Attribute {
-                        value: obj,
-                        attr: field,
-                    } => {
-                        let obj_expr = self.lower_expr(obj)?;
-                        if let Some(class_id) = obj_expr.ty.class_id() {
-                            if let Some(&field_id) =
-                                self.symbols.fields.get(&(class_id, field.clone()))
-                            {
-                                // Get field type - check inherited_fields first, then own fields
-                                let class_data = &self.symbols.class_data[class_id.index()];
-                                let inherited_count = class_data.inherited_fields.len();
-                                let field_idx = field_id.index();
-
-                                let field_ty = if field_idx < inherited_count {
-                                    // It's an inherited field
-                                    TirTypeUnresolved::from_tir_type(
-                                        &class_data.inherited_fields[field_idx].1,
-                                    )
-                                } else {
-                                    // It's an own field
-                                    TirTypeUnresolved::from_tir_type(
-                                        &class_data.fields[field_idx - inherited_count].1,
-                                    )
-                                };
-
-                                // Check compatibility
-                                if !value_expr.ty.is_compatible_with(&field_ty) {
-                                    return Err(CompilerError::TypeErrorSimple(format!(
-                                        "Cannot assign {:?} to field '{}' of type {:?}",
-                                        value_expr.ty, field, field_ty
-                                    )));
-                                }
-
-                                return Ok(vec![TirStmtUnresolved::Assign {
-                                    target: TirLValueUnresolved::Field {
-                                        object: Box::new(obj_expr),
-                                        class: class_id,
-                                        field: field_id,
-                                    },
-                                    value: value_expr,
-                                }]);
-                            }
-                        }
-                        Err(CompilerError::TypeErrorSimple(format!(
-                            "Cannot assign to field {}",
-                            field
-                        )))
-                    }
-
-                    Expr::Subscript {
-                        value: container,
-                        index,
-                    } => {
-                        let container_expr = self.lower_expr(container)?;
-                        let index_expr = self.lower_expr(index)?;
-
-                        // Look up __setitem__ method and convert to a Call expression statement
-                        let setitem_call = call_dunder_method!(
-                            self.symbols,
-                            &container_expr.ty,
-                            "__setitem__",
-                            vec![container_expr, index_expr, value_expr]
-                        )?;
-                        Ok(vec![TirStmtUnresolved::Expr(setitem_call)])
+                        let expected = match self.resolve_var(name) {
+                            Some((_, var_ty)) => Some(
+                                self.cell_value_type(&var_ty)
+                                    .map_or(var_ty, |(_, value_ty)| value_ty),
+                            ),
+                            None => type_annotation
+                                .as_ref()
+                                .map(|annot| self.convert_annotation(annot)),
+                        };
+                        self.lower_expr_expecting(value, expected.as_ref())?
                     }
+                    _ => self.lower_expr(value)?,
+                };
 
-                    _ => Err(CompilerError::UnsupportedFeature(format!(
-                        "Unsupported assignment target: {:?}",
-                        target
-                    ))),
-                }
+                self.lower_assign_target(target, value_expr, type_annotation.as_ref())
             }
 
-            Stmt::AugAssign { target, op, value } => {
-                let value_expr = self.lower_expr(value)?;
+            Stmt::AugAssign { target, op, value, .. } => {
+                self.forget_narrowing(target);
                 if let Some((var_ref, var_ty)) = self.resolve_var(target) {
+                    if !self.can_rebind(target, &var_ref) {
+                        return Err(CompilerError::TypeErrorSimple(format!(
+                            "Cannot modify module variable '{}' without a global declaration",
+                            target
+                        )));
+                    }
+                    // A variable in a cell is updated through its value, and a
+                    // class instance through its operator methods:
+                    // x = x.__iadd__(value) if defined, else x = x + value
+                    if let Some(class_id) = var_ty.class_id() {
+                        let inplace = format!("__i{}", &binop_dunder(*op)[2..]);
+                        let new_value = if self.cell_value_type(&var_ty).is_none()
+                            && self.symbols.resolve_method(class_id, &inplace).is_some()
+                        {
+                            Expr::Call {
+                                func: Box::new(Expr::Attribute {
+                                    value: Box::new(Expr::Name(target.clone())),
+                                    attr: inplace,
+                                }),
+                                args: vec![value.clone()],
+                                keywords: vec![],
+                            }
+                        } else {
+                            Expr::BinOp {
+                                left: Box::new(Expr::Name(target.clone())),
+                                op: *op,
+                                right: Box::new(value.clone()),
+                            }
+                        };
+                        return self.lower_stmt(&Stmt::Assign {
+                            target: Expr::Name(target.clone()),
+                            value: new_value,
+                            type_annotation: None,
+                            span: Span::default(),
+                        });
+                    }
+                    let value_expr = self.lower_expr(value)?;
                     // Check that both target and value are numeric for augmented assignment
                     if !var_ty.is_numeric() {
                         return Err(CompilerError::TypeErrorSimple(format!(
@@ -154,15 +194,25 @@ impl<'a> BodyLowerer<'a> {
                         value: value_expr,
                     }])
                 } else {
-                    Err(CompilerError::UndefinedVariable(target.clone()))
+                    Err(self.undefined_variable(target))
                 }
             }
 
-            Stmt::Expr { value } => {
+            Stmt::Expr { value, .. } => {
                 // Check if this is a print() call - expand at statement level
-                if let Expr::Call { func, args } = value {
+                if let Expr::Call {
+                    func,
+                    args,
+                    keywords,
+                } = value
+                {
                     if let Expr::Name(name) = &**func {
                         if name == "print" {
+                            if !keywords.is_empty() {
+                                return Err(CompilerError::UnsupportedFeature(
+                                    "print() does not support keyword arguments".to_string(),
+                                ));
+                            }
                             return self.expand_print_stmt(args);
                         }
                     }
@@ -171,8 +221,36 @@ impl<'a> BodyLowerer<'a> {
                 Ok(vec![TirStmtUnresolved::Expr(expr)])
             }
 
-            Stmt::Return { value } => {
-                let expr = value.as_ref().map(|v| self.lower_expr(v)).transpose()?;
+            Stmt::Return { value, .. } => {
+                if value.is_some() && self.yield_type.is_some() {
+                    return Err(CompilerError::UnsupportedFeature(
+                        "Returning a value from a generator is not supported".to_string(),
+                    ));
+                }
+                let return_type = self.return_type.clone();
+                let mut expr = value
+                    .as_ref()
+                    .map(|v| self.lower_expr_expecting(v, Some(&return_type)))
+                    .transpose()?;
+
+                // `return None` from a function without a return value, and a
+                // bare `return` from one returning an Optional, are plain returns
+                if return_type == TirTypeUnresolved::Void
+                    && expr
+                        .as_ref()
+                        .is_some_and(|e| e.ty == TirTypeUnresolved::NoneType)
+                {
+                    return Ok(vec![
+                        TirStmtUnresolved::Expr(expr.unwrap()),
+                        TirStmtUnresolved::Return(None),
+                    ]);
+                }
+                if expr.is_none() && return_type.optional_inner().is_some() {
+                    expr = Some(TirExprUnresolved::new(
+                        TirExprKindUnresolved::Constant(Constant::None),
+                        return_type,
+                    ));
+                }
 
                 // Check return type compatibility
                 match (&expr, &self.return_type) {
@@ -200,23 +278,29 @@ impl<'a> BodyLowerer<'a> {
                 Ok(vec![TirStmtUnresolved::Return(expr)])
             }
 
-            Stmt::If { test, body, orelse } => {
-                let cond = self.lower_expr(test)?;
+            Stmt::If { test, body, orelse, .. } => {
+                let cond = self.lower_condition(test)?;
+                let (when_true, when_false) = condition_narrowings(test);
 
                 self.enter_scope();
-                let mut then_body = Vec::new();
-                for stmt in body {
-                    then_body.extend(self.lower_stmt(stmt)?);
-                }
+                self.narrow(when_true.clone());
+                let then_body = self.lower_block(body);
                 self.exit_scope();
 
                 self.enter_scope();
-                let mut else_body = Vec::new();
-                for stmt in orelse {
-                    else_body.extend(self.lower_stmt(stmt)?);
-                }
+                self.narrow(when_false.clone());
+                let else_body = self.lower_block(orelse);
                 self.exit_scope();
 
+                // A branch that returns or raises leaves the other's narrowing
+                // in place (if x is None: return ...)
+                if always_exits(body) {
+                    self.narrow(when_false);
+                }
+                if always_exits(orelse) {
+                    self.narrow(when_true);
+                }
+
                 Ok(vec![TirStmtUnresolved::If {
                     cond,
                     then_body,
@@ -224,165 +308,41 @@ impl<'a> BodyLowerer<'a> {
                 }])
             }
 
-            Stmt::While { test, body } => {
-                let cond = self.lower_expr(test)?;
+            Stmt::While { test, body, orelse, .. } => {
+                // The body runs again after its own assignments
+                for path in assigned_paths(body) {
+                    self.forget_narrowing(&path);
+                }
+                let cond = self.lower_condition(test)?;
 
                 self.enter_scope();
-                let mut loop_body = Vec::new();
-                for stmt in body {
-                    loop_body.extend(self.lower_stmt(stmt)?);
-                }
+                self.narrow(condition_narrowings(test).0);
+                let loop_body = self.lower_block(body);
                 self.exit_scope();
 
-                Ok(vec![TirStmtUnresolved::While {
+                let mut result = vec![TirStmtUnresolved::While {
                     cond,
                     body: loop_body,
-                }])
-            }
-
-            Stmt::For { target, iter, body } => {
-                // Desugar for loop:
-                //   for target in iter:
-                //       <body>
-                // becomes:
-                //   _iter = iter.__iter__()
-                //   _done = False
-                //   try:
-                //       while not _done:
-                //           try:
-                //               target = _iter.__next__()
-                //               <body>
-                //           except StopIteration:
-                //               _done = True
-                //   finally:
-                //       _iter.__dealloc__()
-
-                let mut result = Vec::new();
-
-                // Lower the iterable expression
-                let iterable_expr = self.lower_expr(iter)?;
-
-                // Call __iter__ on the iterable
-                let iter_call = call_dunder_method!(
-                    self.symbols,
-                    &iterable_expr.ty,
-                    "__iter__",
-                    vec![iterable_expr]
-                )?;
-                let iter_ty = iter_call.ty.clone();
-
-                // Create unique names for temporaries using local counter
-                let iter_name = format!("_for_iter_{}", self.next_local_id);
-                let done_name = format!("_for_done_{}", self.next_local_id);
-
-                // Allocate _iter local and initialize it
-                let iter_local_id = self.alloc_local(&iter_name, iter_ty.clone());
-                result.push(TirStmtUnresolved::Let {
-                    local: iter_local_id,
-                    ty: iter_ty.clone(),
-                    init: iter_call,
-                });
-
-                // Allocate _done local and initialize to False
-                let done_local_id = self.alloc_local(&done_name, TirTypeUnresolved::Bool);
-                result.push(TirStmtUnresolved::Let {
-                    local: done_local_id,
-                    ty: TirTypeUnresolved::Bool,
-                    init: TirExprUnresolved::new(
-                        TirExprKindUnresolved::Constant(Constant::Bool(false)),
-                        TirTypeUnresolved::Bool,
-                    ),
-                });
-
-                // Get StopIteration class for exception handling
-                let stop_iteration_class = self.symbols.get_or_create_stop_iteration_class();
-
-                // Build the while loop body with try/except
-                self.enter_scope();
-
-                // Call __next__ on the iterator
-                let iter_var = TirExprUnresolved::new(
-                    TirExprKindUnresolved::Var(VarRef::Local(iter_local_id)),
-                    iter_ty.clone(),
-                );
-                let next_call = call_dunder_method!(
-                    self.symbols,
-                    &iter_ty,
-                    "__next__",
-                    vec![iter_var.clone()]
-                )?;
-                let elem_ty = next_call.ty.clone();
-
-                // Allocate the loop target variable
-                let target_local_id = self.alloc_local(target, elem_ty.clone());
-
-                // Build the try body: target = _iter.__next__() followed by loop body
-                let mut try_body = vec![TirStmtUnresolved::Let {
-                    local: target_local_id,
-                    ty: elem_ty,
-                    init: next_call,
                 }];
+                result.extend(self.lower_loop_else(orelse)?);
+                Ok(result)
+            }
 
-                // Lower the actual for loop body
-                for stmt in body {
-                    try_body.extend(self.lower_stmt(stmt)?);
+            Stmt::For {
+                target,
+                iter,
+                body,
+                orelse,
+                ..
+            } => {
+                self.forget_narrowing(target);
+                for path in assigned_paths(body) {
+                    self.forget_narrowing(&path);
                 }
-
-                // Build the except handler: _done = True
-                let except_handler = TirExceptHandlerUnresolved {
-                    exc_class: Some(stop_iteration_class),
-                    local: None,
-                    body: vec![TirStmtUnresolved::Assign {
-                        target: TirLValueUnresolved::Var(VarRef::Local(done_local_id)),
-                        value: TirExprUnresolved::new(
-                            TirExprKindUnresolved::Constant(Constant::Bool(true)),
-                            TirTypeUnresolved::Bool,
-                        ),
-                    }],
-                };
-
-                // Build the inner try statement for __next__ call
-                let inner_try_stmt = TirStmtUnresolved::Try {
-                    body: try_body,
-                    handlers: vec![except_handler],
-                    orelse: vec![],
-                    finalbody: vec![],
-                };
-
-                self.exit_scope();
-
-                // Build while condition: not _done
-                let done_var = TirExprUnresolved::new(
-                    TirExprKindUnresolved::Var(VarRef::Local(done_local_id)),
-                    TirTypeUnresolved::Bool,
-                );
-                let while_cond = TirExprUnresolved::new(
-                    TirExprKindUnresolved::UnaryOp {
-                        op: UnaryOp::Not,
-                        operand: Box::new(done_var),
-                    },
-                    TirTypeUnresolved::Bool,
-                );
-
-                // Build the while loop
-                let while_stmt = TirStmtUnresolved::While {
-                    cond: while_cond,
-                    body: vec![inner_try_stmt],
-                };
-
-                // Build the finally block to deallocate the iterator
-                let dealloc_call =
-                    call_dunder_method!(self.symbols, &iter_ty, "__dealloc__", vec![iter_var])?;
-                let finally_body = vec![TirStmtUnresolved::Expr(dealloc_call)];
-
-                // Wrap the while loop in try-finally to ensure iterator cleanup
-                result.push(TirStmtUnresolved::Try {
-                    body: vec![while_stmt],
-                    handlers: vec![],
-                    orelse: vec![],
-                    finalbody: finally_body,
-                });
-
+                let target = Expr::Name(target.clone());
+                let mut result =
+                    self.lower_for_loop(&target, iter, &mut |this| Ok(this.lower_block(body)))?;
+                result.extend(self.lower_loop_else(orelse)?);
                 Ok(result)
             }
 
@@ -391,13 +351,11 @@ impl<'a> BodyLowerer<'a> {
                 handlers,
                 orelse,
                 finalbody,
+                ..
             } => {
                 // Lower try body
                 self.enter_scope();
-                let mut tir_body = Vec::new();
-                for stmt in body {
-                    tir_body.extend(self.lower_stmt(stmt)?);
-                }
+                let tir_body = self.lower_block(body);
                 self.exit_scope();
 
                 // Lower exception handlers
@@ -421,10 +379,7 @@ impl<'a> BodyLowerer<'a> {
                     };
 
                     // Lower handler body
-                    let mut handler_body = Vec::new();
-                    for stmt in &handler.body {
-                        handler_body.extend(self.lower_stmt(stmt)?);
-                    }
+                    let handler_body = self.lower_block(&handler.body);
 
                     self.exit_scope();
 
@@ -437,18 +392,12 @@ impl<'a> BodyLowerer<'a> {
 
                 // Lower else clause
                 self.enter_scope();
-                let mut tir_orelse = Vec::new();
-                for stmt in orelse {
-                    tir_orelse.extend(self.lower_stmt(stmt)?);
-                }
+                let tir_orelse = self.lower_block(orelse);
                 self.exit_scope();
 
                 // Lower finally clause
                 self.enter_scope();
-                let mut tir_finalbody = Vec::new();
-                for stmt in finalbody {
-                    tir_finalbody.extend(self.lower_stmt(stmt)?);
-                }
+                let tir_finalbody = self.lower_block(finalbody);
                 self.exit_scope();
 
                 Ok(vec![TirStmtUnresolved::Try {
@@ -459,14 +408,1359 @@ impl<'a> BodyLowerer<'a> {
                 }])
             }
 
-            Stmt::Raise { exc } => {
-                let tir_exc = exc.as_ref().map(|e| self.lower_expr(e)).transpose()?;
+            Stmt::With {
+                context,
+                target,
+                body,
+                ..
+            } => self.lower_with(context, target.as_ref(), body),
+
+            Stmt::Raise { exc, cause, .. } => {
+                let mut tir_exc = exc.as_ref().map(|e| self.lower_expr(e)).transpose()?;
+                // Python only parses `from` after an exception
+                if let (Some(exc), Some(cause)) = (tir_exc.take(), cause) {
+                    tir_exc = Some(self.lower_raise_cause(exc, cause)?);
+                }
                 Ok(vec![TirStmtUnresolved::Raise { exc: tir_exc }])
             }
 
-            // Skip function and class definitions - they're handled at module level
-            Stmt::FunctionDef { .. } | Stmt::ClassDef { .. } => Ok(vec![]),
+            Stmt::Yield { value, .. } => {
+                let Some(yield_type) = self.yield_type.clone() else {
+                    return Err(CompilerError::TypeErrorSimple(
+                        "'yield' outside of a generator function".to_string(),
+                    ));
+                };
+                let expr = self.lower_expr_expecting(value, Some(&yield_type))?;
+                if !expr.ty.is_compatible_with(&yield_type) {
+                    return Err(CompilerError::TypeErrorSimple(format!(
+                        "Yield type mismatch: expected {:?}, got {:?}",
+                        yield_type, expr.ty
+                    )));
+                }
+                Ok(vec![TirStmtUnresolved::Yield(expr)])
+            }
+
+            Stmt::FunctionDef {
+                name,
+                exported,
+                extern_name,
+                ..
+            } if *exported || extern_name.is_some() => {
+                Err(CompilerError::UnsupportedFeature(format!(
+                    "Function '{}' is nested: only module-level functions can be @export or @extern",
+                    name
+                )))
+            }
+            Stmt::FunctionDef {
+                name,
+                args,
+                return_type,
+                body,
+                span,
+                ..
+            } => self.lower_function_def(name, args, return_type.as_ref(), body, span.line),
+
+            // Skip class definitions - they're handled at module level
+            Stmt::ClassDef { .. } => Ok(vec![]),
+
+            Stmt::Global { names, .. } => self.lower_global(names),
+            Stmt::Nonlocal { names, .. } => self.lower_nonlocal(names),
+
+            Stmt::Delete { targets, .. } => {
+                let mut result = Vec::new();
+                for target in targets {
+                    if let Some(path) = target_path(target) {
+                        self.forget_narrowing(&path);
+                    }
+                    result.extend(self.lower_delete_target(target)?);
+                }
+                Ok(result)
+            }
+        }
+    }
+
+    /// Lower the test of an `if` or `while`; an Optional is true when it is
+    /// not None and its value is truthy
+    fn lower_condition(&mut self, test: &Expr) -> Result<TirExprUnresolved> {
+        let cond = self.lower_expr(test)?;
+        if cond.ty.optional_inner().is_some() {
+            return self.lower_optional_bool(cond);
+        }
+        Ok(cond)
+    }
+
+    /// Lower the `else` clause of a `while` or `for` loop. There is no `break`,
+    /// so every loop that finishes without raising runs its `else` clause,
+    /// which is therefore emitted directly after the loop. It stays outside the
+    /// loop's StopIteration handler, so a StopIteration raised by the clause
+    /// propagates as in CPython.
+    fn lower_loop_else(&mut self, orelse: &[Stmt]) -> Result<Vec<TirStmtUnresolved>> {
+        self.enter_scope();
+        let else_body = self.lower_block(orelse);
+        self.exit_scope();
+        Ok(else_body)
+    }
+
+    /// Lower `for target in iter: <body>`, where `lower_body` produces the
+    /// body statements once the target is bound in the loop's scope
+    fn lower_for_loop(
+        &mut self,
+        target: &Expr,
+        iter: &Expr,
+        lower_body: &mut dyn FnMut(&mut Self) -> Result<Vec<TirStmtUnresolved>>,
+    ) -> Result<Vec<TirStmtUnresolved>> {
+        // Desugar for loop:
+        //   for target in iter:
+        //       <body>
+        // becomes:
+        //   _iter = iter.__iter__()
+        //   _done = False
+        //   try:
+        //       while not _done:
+        //           try:
+        //               target = _iter.__next__()
+        //               <body>
+        //           except StopIteration:
+        //               _done = True
+        //   finally:
+        //       _iter.__dealloc__()   (only if the iterator defines it)
+
+        // Iterating map()/filter() directly applies the function lazily, one item per iteration
+        if let Expr::Call {
+            func,
+            args,
+            keywords,
+        } = iter
+        {
+            if let Some(call) = self.as_map_filter_call(func, args, keywords)? {
+                let (elt, generator) = self.map_filter_comprehension(&call);
+                return self.lower_for_loop(&generator.target, &generator.iter, &mut |this| {
+                    let conds = generator
+                        .ifs
+                        .iter()
+                        .map(|cond| this.lower_expr(cond))
+                        .collect::<Result<Vec<_>>>()?;
+                    let value = this.lower_expr(&elt)?;
+                    let mut body = this.bind_loop_target(target, value)?;
+                    body.extend(lower_body(this)?);
+                    for cond in conds.into_iter().rev() {
+                        body = vec![TirStmtUnresolved::If {
+                            cond,
+                            then_body: body,
+                            else_body: vec![],
+                        }];
+                    }
+                    Ok(body)
+                });
+            }
+        }
+
+        let mut result = Vec::new();
+
+        // Lower the iterable expression
+        let iterable_expr = self.lower_expr(iter)?;
+
+        // range() with a constant step needs no range object: count in a local
+        if let Some(step) = self.constant_range_step(&iterable_expr) {
+            let TirExprKindUnresolved::Construct { args, .. } = iterable_expr.kind else {
+                unreachable!("constant_range_step only accepts range() constructions")
+            };
+            return self.lower_range_loop(target, args, step, lower_body);
+        }
+
+        // Call __iter__ on the iterable
+        let iter_call = call_dunder_method!(
+            self.symbols,
+            &iterable_expr.ty,
+            "__iter__",
+            vec![iterable_expr]
+        )?;
+        let iter_ty = iter_call.ty.clone();
+
+        // Create unique names for temporaries using local counter
+        let iter_name = format!("_for_iter_{}", self.next_local_id);
+        let done_name = format!("_for_done_{}", self.next_local_id);
+
+        // Allocate _iter local and initialize it
+        let iter_local_id = self.alloc_local(&iter_name, iter_ty.clone());
+        result.push(TirStmtUnresolved::Let {
+            local: iter_local_id,
+            ty: iter_ty.clone(),
+            init: iter_call,
+        });
+
+        // Allocate _done local and initialize to False
+        let done_local_id = self.alloc_local(&done_name, TirTypeUnresolved::Bool);
+        result.push(TirStmtUnresolved::Let {
+            local: done_local_id,
+            ty: TirTypeUnresolved::Bool,
+            init: TirExprUnresolved::new(
+                TirExprKindUnresolved::Constant(Constant::Bool(false)),
+                TirTypeUnresolved::Bool,
+            ),
+        });
+
+        // Get StopIteration class for exception handling
+        let stop_iteration_class = self.symbols.get_or_create_stop_iteration_class();
+
+        // Build the while loop body with try/except
+        self.enter_scope();
+
+        // Call __next__ on the iterator
+        let iter_var = TirExprUnresolved::new(
+            TirExprKindUnresolved::Var(VarRef::Local(iter_local_id)),
+            iter_ty.clone(),
+        );
+        let next_call =
+            call_dunder_method!(self.symbols, &iter_ty, "__next__", vec![iter_var.clone()])?;
+
+        // Build the try body: target = _iter.__next__() followed by loop body
+        let mut try_body = self.bind_loop_target(target, next_call)?;
+        try_body.extend(lower_body(self)?);
+
+        // Build the except handler: _done = True
+        let except_handler = TirExceptHandlerUnresolved {
+            exc_class: Some(stop_iteration_class),
+            local: None,
+            body: vec![TirStmtUnresolved::Assign {
+                target: TirLValueUnresolved::Var(VarRef::Local(done_local_id)),
+                value: TirExprUnresolved::new(
+                    TirExprKindUnresolved::Constant(Constant::Bool(true)),
+                    TirTypeUnresolved::Bool,
+                ),
+            }],
+        };
+
+        // Build the inner try statement for __next__ call
+        let inner_try_stmt = TirStmtUnresolved::Try {
+            body: try_body,
+            handlers: vec![except_handler],
+            orelse: vec![],
+            finalbody: vec![],
+        };
+
+        self.exit_scope();
+
+        // Build while condition: not _done
+        let done_var = TirExprUnresolved::new(
+            TirExprKindUnresolved::Var(VarRef::Local(done_local_id)),
+            TirTypeUnresolved::Bool,
+        );
+        let while_cond = TirExprUnresolved::new(
+            TirExprKindUnresolved::UnaryOp {
+                op: UnaryOp::Not,
+                operand: Box::new(done_var),
+            },
+            TirTypeUnresolved::Bool,
+        );
+
+        // Build the while loop
+        let while_stmt = TirStmtUnresolved::While {
+            cond: while_cond,
+            body: vec![inner_try_stmt],
+        };
+
+        // Iterators without __dealloc__ (e.g. generators) need no cleanup
+        let has_dealloc = match &iter_ty {
+            TirTypeUnresolved::Class(class_id) => self
+                .symbols
+                .resolve_method(*class_id, "__dealloc__")
+                .is_some(),
+            _ => false,
+        };
+        if !has_dealloc {
+            result.push(while_stmt);
+            return Ok(result);
+        }
+
+        // Build the finally block to deallocate the iterator
+        let dealloc_call =
+            call_dunder_method!(self.symbols, &iter_ty, "__dealloc__", vec![iter_var])?;
+        let finally_body = vec![TirStmtUnresolved::Expr(dealloc_call)];
+
+        // Wrap the while loop in try-finally to ensure iterator cleanup
+        result.push(TirStmtUnresolved::Try {
+            body: vec![while_stmt],
+            handlers: vec![],
+            orelse: vec![],
+            finalbody: finally_body,
+        });
+
+        Ok(result)
+    }
+
+    /// The step of a `range(...)` call whose step is absent or a nonzero
+    /// int constant
+    fn constant_range_step(&mut self, iterable: &TirExprUnresolved) -> Option<i64> {
+        let TirExprKindUnresolved::Construct { class, args } = &iterable.kind else {
+            return None;
+        };
+        if *class != self.symbols.get_or_create_range_class() {
+            return None;
         }
+        let Some(step) = args.get(2) else {
+            return Some(1);
+        };
+        let step = match &step.kind {
+            TirExprKindUnresolved::Constant(Constant::Int(n)) => *n,
+            TirExprKindUnresolved::UnaryOp {
+                op: UnaryOp::USub,
+                operand,
+            } => match &operand.kind {
+                TirExprKindUnresolved::Constant(Constant::Int(n)) => n.checked_neg()?,
+                _ => return None,
+            },
+            _ => return None,
+        };
+        (step != 0).then_some(step)
+    }
+
+    /// Lower `for target in range(start, stop, step)` with a constant step
+    /// (`args` are the range() arguments) to a counted loop that allocates
+    /// nothing and raises no StopIteration:
+    ///   _for_index = start
+    ///   _for_stop = stop
+    ///   while _for_index < _for_stop:     (> for a negative step)
+    ///       target = _for_index
+    ///       if _for_index > MAX - step:   (< MIN - step for a negative step)
+    ///           _for_index = _for_stop
+    ///       else:
+    ///           _for_index += step
+    ///       <body>
+    /// An index within `step` of the int range's end has no next value, and
+    /// adding `step` to it would wrap around (or raise with `--checked-int`)
+    fn lower_range_loop(
+        &mut self,
+        target: &Expr,
+        mut args: Vec<TirExprUnresolved>,
+        step: i64,
+        lower_body: &mut dyn FnMut(&mut Self) -> Result<Vec<TirStmtUnresolved>>,
+    ) -> Result<Vec<TirStmtUnresolved>> {
+        let int_constant = |n: i64| {
+            TirExprUnresolved::new(
+                TirExprKindUnresolved::Constant(Constant::Int(n)),
+                TirTypeUnresolved::Int,
+            )
+        };
+        args.truncate(2);
+        let stop = args.pop().expect("range() has a stop argument");
+        let start = args.pop().unwrap_or_else(|| int_constant(0));
+
+        let index_name = format!("_for_index_{}", self.next_local_id);
+        let stop_name = format!("_for_stop_{}", self.next_local_id);
+        let index_local_id = self.alloc_local(&index_name, TirTypeUnresolved::Int);
+        let stop_local_id = self.alloc_local(&stop_name, TirTypeUnresolved::Int);
+        let mut result = vec![
+            TirStmtUnresolved::Let {
+                local: index_local_id,
+                ty: TirTypeUnresolved::Int,
+                init: start,
+            },
+            TirStmtUnresolved::Let {
+                local: stop_local_id,
+                ty: TirTypeUnresolved::Int,
+                init: stop,
+            },
+        ];
+
+        let index_var = TirExprUnresolved::new(
+            TirExprKindUnresolved::Var(VarRef::Local(index_local_id)),
+            TirTypeUnresolved::Int,
+        );
+        let stop_var = TirExprUnresolved::new(
+            TirExprKindUnresolved::Var(VarRef::Local(stop_local_id)),
+            TirTypeUnresolved::Int,
+        );
+
+        // The index moves on before the body runs, so the body sees the
+        // target's own copy
+        self.enter_scope();
+        let mut body = self.bind_loop_target(target, index_var.clone())?;
+        let (last_op, last) = if step > 0 {
+            (CompareOp::Gt, i64::MAX - step)
+        } else {
+            (CompareOp::Lt, i64::MIN - step)
+        };
+        let is_last = TirExprUnresolved::new(
+            TirExprKindUnresolved::Compare {
+                left: Box::new(index_var.clone()),
+                op: last_op,
+                right: Box::new(int_constant(last)),
+            },
+            TirTypeUnresolved::Bool,
+        );
+        body.push(TirStmtUnresolved::If {
+            cond: is_last,
+            then_body: vec![TirStmtUnresolved::Assign {
+                target: TirLValueUnresolved::Var(VarRef::Local(index_local_id)),
+                value: stop_var.clone(),
+            }],
+            else_body: vec![TirStmtUnresolved::AugAssign {
+                target: VarRef::Local(index_local_id),
+                op: BinOperator::Add,
+                value: int_constant(step),
+            }],
+        });
+        body.extend(lower_body(self)?);
+        self.exit_scope();
+
+        let cond = TirExprUnresolved::new(
+            TirExprKindUnresolved::Compare {
+                left: Box::new(index_var),
+                op: if step > 0 {
+                    CompareOp::Lt
+                } else {
+                    CompareOp::Gt
+                },
+                right: Box::new(stop_var),
+            },
+            TirTypeUnresolved::Bool,
+        );
+        result.push(TirStmtUnresolved::While { cond, body });
+        Ok(result)
+    }
+
+    /// Lower `with context as target: <body>`:
+    ///   _with_mgr = context
+    ///   target = _with_mgr.__enter__()
+    ///   _with_exited = False
+    ///   try:
+    ///       <body>
+    ///   except BaseException as _with_exc:
+    ///       _with_exited = True
+    ///       if not _with_mgr.__exit__(None, _with_exc, None):
+    ///           raise _with_exc
+    ///   finally:
+    ///       if not _with_exited:
+    ///           _with_mgr.__exit__(None, None, None)
+    /// `__exit__` either takes (exc_type, exc_value, traceback) with class-typed
+    /// parameters, or none at all (unannotated parameters are not part of the
+    /// signature). If it returns None, the exception always propagates.
+    fn lower_with(
+        &mut self,
+        context: &Expr,
+        target: Option<&Expr>,
+        body: &[Stmt],
+    ) -> Result<Vec<TirStmtUnresolved>> {
+        let mut result = Vec::new();
+
+        let mgr_expr = self.lower_expr(context)?;
+        let mgr_ty = mgr_expr.ty.clone();
+        let Some((_method_id, exit_func)) = mgr_ty
+            .class_id()
+            .and_then(|class_id| self.symbols.resolve_method(class_id, "__exit__"))
+        else {
+            return Err(CompilerError::TypeErrorSimple(format!(
+                "with requires a context manager defining __enter__ and __exit__, got {:?}",
+                mgr_ty
+            )));
+        };
+
+        // Check the __exit__ signature up front
+        let exc_class_id = self.symbols.get_or_create_exception_class();
+        let exc_ty = TirTypeUnresolved::Class(exc_class_id);
+        let (exit_params, exit_ret) = self.symbols.get_func_signature(exit_func).clone();
+        let exit_params: Vec<TirTypeUnresolved> = exit_params
+            .iter()
+            .map(TirTypeUnresolved::from_tir_type)
+            .collect();
+        match exit_params.as_slice() {
+            [] => {}
+            [exc_type, exc_value, traceback]
+                if [exc_type, exc_value, traceback]
+                    .iter()
+                    .all(|ty| ty.class_id().is_some())
+                    && exc_ty.is_compatible_with(exc_value) => {}
+            _ => {
+                return Err(CompilerError::TypeErrorSimple(format!(
+                    "__exit__ must take (exc_type, exc_value, traceback) with an Exception \
+                     exc_value, or no parameters, got {:?}",
+                    exit_params
+                )));
+            }
+        }
+        let suppresses = match &exit_ret {
+            TirType::Void => false,
+            TirType::Bool => true,
+            other => {
+                return Err(CompilerError::TypeErrorSimple(format!(
+                    "__exit__ must return bool or None, got {:?}",
+                    other
+                )));
+            }
+        };
+
+        // _with_mgr = context
+        let mgr_name = format!("_with_mgr_{}", self.next_local_id);
+        let mgr_local_id = self.alloc_local(&mgr_name, mgr_ty.clone());
+        result.push(TirStmtUnresolved::Let {
+            local: mgr_local_id,
+            ty: mgr_ty.clone(),
+            init: mgr_expr,
+        });
+        let mgr_var = TirExprUnresolved::new(
+            TirExprKindUnresolved::Var(VarRef::Local(mgr_local_id)),
+            mgr_ty.clone(),
+        );
+
+        // target = _with_mgr.__enter__()
+        let enter_call =
+            call_dunder_method!(self.symbols, &mgr_ty, "__enter__", vec![mgr_var.clone()])?;
+        match target {
+            Some(target) => result.extend(self.lower_assign_target(target, enter_call, None)?),
+            None => result.push(TirStmtUnresolved::Expr(enter_call)),
+        }
+
+        // _with_exited = False
+        let exited_name = format!("_with_exited_{}", self.next_local_id);
+        let exited_local_id = self.alloc_local(&exited_name, TirTypeUnresolved::Bool);
+        result.push(TirStmtUnresolved::Let {
+            local: exited_local_id,
+            ty: TirTypeUnresolved::Bool,
+            init: TirExprUnresolved::new(
+                TirExprKindUnresolved::Constant(Constant::Bool(false)),
+                TirTypeUnresolved::Bool,
+            ),
+        });
+        let exited_var = TirExprUnresolved::new(
+            TirExprKindUnresolved::Var(VarRef::Local(exited_local_id)),
+            TirTypeUnresolved::Bool,
+        );
+
+        // _with_mgr.__exit__(None, exc, None), or _with_mgr.__exit__()
+        let exit_call = |exc: Option<TirExprUnresolved>| {
+            let mut args = vec![mgr_var.clone()];
+            if !exit_params.is_empty() {
+                let none = |ty: &TirTypeUnresolved| {
+                    TirExprUnresolved::new(
+                        TirExprKindUnresolved::Constant(Constant::None),
+                        ty.clone(),
+                    )
+                };
+                args.push(none(&exit_params[0]));
+                args.push(exc.unwrap_or_else(|| none(&exit_params[1])));
+                args.push(none(&exit_params[2]));
+            }
+            TirExprUnresolved::new(
+                TirExprKindUnresolved::Call {
+                    func: exit_func,
+                    args,
+                },
+                TirTypeUnresolved::from_tir_type(&exit_ret),
+            )
+        };
+
+        self.enter_scope();
+        let try_body = self.lower_block(body);
+        self.exit_scope();
+
+        // except BaseException as _with_exc: hand the exception to __exit__
+        self.enter_scope();
+        let exc_name = format!("_with_exc_{}", self.next_local_id);
+        let exc_local_id = self.alloc_local(&exc_name, exc_ty.clone());
+        self.exit_scope();
+        let exc_var = TirExprUnresolved::new(
+            TirExprKindUnresolved::Var(VarRef::Local(exc_local_id)),
+            exc_ty,
+        );
+        let reraise = TirStmtUnresolved::Raise {
+            exc: Some(exc_var.clone()),
+        };
+        let mut handler_body = vec![TirStmtUnresolved::Assign {
+            target: TirLValueUnresolved::Var(VarRef::Local(exited_local_id)),
+            value: TirExprUnresolved::new(
+                TirExprKindUnresolved::Constant(Constant::Bool(true)),
+                TirTypeUnresolved::Bool,
+            ),
+        }];
+        if suppresses {
+            handler_body.push(TirStmtUnresolved::If {
+                cond: TirExprUnresolved::new(
+                    TirExprKindUnresolved::UnaryOp {
+                        op: UnaryOp::Not,
+                        operand: Box::new(exit_call(Some(exc_var))),
+                    },
+                    TirTypeUnresolved::Bool,
+                ),
+                then_body: vec![reraise],
+                else_body: vec![],
+            });
+        } else {
+            handler_body.push(TirStmtUnresolved::Expr(exit_call(Some(exc_var))));
+            handler_body.push(reraise);
+        }
+
+        // finally: exit normally unless the handler already did
+        let finally_body = vec![TirStmtUnresolved::If {
+            cond: TirExprUnresolved::new(
+                TirExprKindUnresolved::UnaryOp {
+                    op: UnaryOp::Not,
+                    operand: Box::new(exited_var),
+                },
+                TirTypeUnresolved::Bool,
+            ),
+            then_body: vec![TirStmtUnresolved::Expr(exit_call(None))],
+            else_body: vec![],
+        }];
+
+        result.push(TirStmtUnresolved::Try {
+            body: try_body,
+            handlers: vec![TirExceptHandlerUnresolved {
+                exc_class: None,
+                local: Some(exc_local_id),
+                body: handler_body,
+            }],
+            orelse: vec![],
+            finalbody: finally_body,
+        });
+
+        Ok(result)
+    }
+
+    /// Lower a comprehension into a block that builds the container:
+    ///   [elt for x in xs if cond for y in ys]
+    /// becomes
+    ///   _comp = []
+    ///   for x in xs:
+    ///       if cond:
+    ///           for y in ys:
+    ///               _comp.append(elt)
+    ///   <value: _comp>
+    /// Comprehension variables are not visible after the expression.
+    pub(crate) fn lower_comprehension(
+        &mut self,
+        elt: ComprehensionElt<'_>,
+        generators: &[Comprehension],
+    ) -> Result<TirExprUnresolved> {
+        let outer_names = self.local_names.clone();
+
+        // The container type is only known once the element has been lowered
+        // inside the innermost loop, so it is created there
+        let mut container: Option<(LocalId, TirTypeUnresolved, TirExprKindUnresolved)> = None;
+        let mut emit = |this: &mut Self| -> Result<Vec<TirStmtUnresolved>> {
+            let (method, args, class_id, empty) = match elt {
+                ComprehensionElt::List(elt) => {
+                    let elt_expr = this.lower_expr(elt)?;
+                    let elem_ty = elt_expr.ty.clone();
+                    let class_id = this
+                        .symbols
+                        .get_or_create_list_class(&elem_ty.to_tir_type());
+                    let empty = TirExprKindUnresolved::List {
+                        elements: vec![],
+                        elem_ty,
+                    };
+                    ("append", vec![elt_expr], class_id, empty)
+                }
+                ComprehensionElt::Set(elt) => {
+                    let elt_expr = this.lower_expr(elt)?;
+                    let elem_ty = elt_expr.ty.clone();
+                    this.check_hashable(&elem_ty)?;
+                    let class_id = this.symbols.get_or_create_set_class(&elem_ty.to_tir_type());
+                    let empty = TirExprKindUnresolved::Set {
+                        elements: vec![],
+                        elem_ty,
+                    };
+                    ("add", vec![elt_expr], class_id, empty)
+                }
+                ComprehensionElt::Dict(key, value) => {
+                    let key_expr = this.lower_expr(key)?;
+                    let value_expr = this.lower_expr(value)?;
+                    let key_ty = key_expr.ty.clone();
+                    let value_ty = value_expr.ty.clone();
+                    this.check_hashable(&key_ty)?;
+                    let class_id = this
+                        .symbols
+                        .get_or_create_dict_class(&key_ty.to_tir_type(), &value_ty.to_tir_type());
+                    let empty = TirExprKindUnresolved::Dict {
+                        entries: vec![],
+                        key_ty,
+                        value_ty,
+                    };
+                    ("__setitem__", vec![key_expr, value_expr], class_id, empty)
+                }
+            };
+
+            let container_ty = TirTypeUnresolved::Class(class_id);
+            let comp_name = format!("_comp_{}", this.next_local_id);
+            let comp_local_id = this.alloc_local(&comp_name, container_ty.clone());
+            container = Some((comp_local_id, container_ty.clone(), empty));
+
+            let mut call_args = vec![TirExprUnresolved::new(
+                TirExprKindUnresolved::Var(VarRef::Local(comp_local_id)),
+                container_ty.clone(),
+            )];
+            call_args.extend(args);
+            let call = call_dunder_method!(this.symbols, &container_ty, method, call_args)?;
+            Ok(vec![TirStmtUnresolved::Expr(call)])
+        };
+
+        let loops = self.lower_comprehension_loops(generators, &mut emit)?;
+        self.local_names = outer_names;
+
+        let (comp_local_id, container_ty, empty) =
+            container.expect("comprehension element is lowered exactly once");
+        let mut stmts = vec![TirStmtUnresolved::Let {
+            local: comp_local_id,
+            ty: container_ty.clone(),
+            init: TirExprUnresolved::new(empty, container_ty.clone()),
+        }];
+        stmts.extend(loops);
+
+        let result = TirExprUnresolved::new(
+            TirExprKindUnresolved::Var(VarRef::Local(comp_local_id)),
+            container_ty.clone(),
+        );
+        Ok(TirExprUnresolved::new(
+            TirExprKindUnresolved::Block {
+                stmts,
+                result: Box::new(result),
+            },
+            container_ty,
+        ))
+    }
+
+    /// Recognize `map(f, xs)` and `filter(f, xs)`; a nested function of the
+    /// same name shadows them
+    pub(crate) fn as_map_filter_call<'e>(
+        &self,
+        func: &Expr,
+        args: &'e [Expr],
+        keywords: &[Keyword],
+    ) -> Result<Option<MapFilterCall<'e>>> {
+        let Expr::Name(name) = func else {
+            return Ok(None);
+        };
+        if !matches!(name.as_str(), "map" | "filter") || self.nested_functions.contains_key(name) {
+            return Ok(None);
+        }
+        if let Some(keyword) = keywords.first() {
+            return Err(CompilerError::UnsupportedFeature(format!(
+                "Keyword argument '{}' is not supported",
+                keyword.arg
+            )));
+        }
+        if name == "map" && args.len() > 2 {
+            return Err(CompilerError::UnsupportedFeature(
+                "map() over several iterables is not supported".to_string(),
+            ));
+        }
+        if args.len() != 2 {
+            return Err(CompilerError::TypeErrorSimple(format!(
+                "{}() takes exactly 2 arguments ({} given)",
+                name,
+                args.len()
+            )));
+        }
+        if args.iter().any(|arg| matches!(arg, Expr::Starred { .. })) {
+            return Err(CompilerError::UnsupportedFeature(format!(
+                "Starred arguments to {}() are not supported",
+                name
+            )));
+        }
+
+        let filter = name == "filter";
+        let func = match &args[0] {
+            Expr::Constant(Constant::None) if filter => None,
+            func => Some(func),
+        };
+        Ok(Some(MapFilterCall {
+            func,
+            iterable: &args[1],
+            filter,
+        }))
+    }
+
+    /// `map()`/`filter()` used as a value rather than iterated directly by a
+    /// loop: the items are computed up front and an iterator over them returned
+    pub(crate) fn lower_map_filter(
+        &mut self,
+        call: MapFilterCall<'_>,
+    ) -> Result<TirExprUnresolved> {
+        let (elt, generator) = self.map_filter_comprehension(&call);
+        let items = self.lower_comprehension(
+            ComprehensionElt::List(&elt),
+            std::slice::from_ref(&generator),
+        )?;
+        let items_ty = items.ty.clone();
+        call_dunder_method!(self.symbols, &items_ty, "__iter__", vec![items])
+    }
+
+    /// The comprehension equivalent of a map/filter call:
+    ///   map(f, xs)     ->  f(_map_item) for _map_item in xs
+    ///   filter(f, xs)  ->  _map_item for _map_item in xs if bool(f(_map_item))
+    fn map_filter_comprehension(&self, call: &MapFilterCall<'_>) -> (Expr, Comprehension) {
+        let item = Expr::Name(format!("_map_item_{}", self.next_local_id));
+        let apply = |arg: Expr| match call.func {
+            Some(func) => Expr::Call {
+                func: Box::new(func.clone()),
+                args: vec![arg],
+                keywords: vec![],
+            },
+            None => arg,
+        };
+        let (elt, ifs) = if call.filter {
+            // bool() gives the truthiness of whatever the predicate returns
+            let cond = Expr::Call {
+                func: Box::new(Expr::Name("bool".to_string())),
+                args: vec![apply(item.clone())],
+                keywords: vec![],
+            };
+            (item.clone(), vec![cond])
+        } else {
+            (apply(item.clone()), vec![])
+        };
+        let generator = Comprehension {
+            target: item,
+            iter: call.iterable.clone(),
+            ifs,
+        };
+        (elt, generator)
+    }
+
+    /// Nest one for loop per generator, with its `if` clauses guarding the
+    /// rest; `emit` produces the statements of the innermost body
+    fn lower_comprehension_loops(
+        &mut self,
+        generators: &[Comprehension],
+        emit: &mut dyn FnMut(&mut Self) -> Result<Vec<TirStmtUnresolved>>,
+    ) -> Result<Vec<TirStmtUnresolved>> {
+        let Some((generator, rest)) = generators.split_first() else {
+            return emit(self);
+        };
+
+        self.lower_for_loop(&generator.target, &generator.iter, &mut |this| {
+            let conds = generator
+                .ifs
+                .iter()
+                .map(|cond| this.lower_expr(cond))
+                .collect::<Result<Vec<_>>>()?;
+            let mut body = this.lower_comprehension_loops(rest, emit)?;
+            for cond in conds.into_iter().rev() {
+                body = vec![TirStmtUnresolved::If {
+                    cond,
+                    then_body: body,
+                    else_body: vec![],
+                }];
+            }
+            Ok(body)
+        })
+    }
+
+    /// Bind an already-lowered value to an assignment target
+    /// (variable, field, subscript, or a tuple of targets to unpack into)
+    fn lower_assign_target(
+        &mut self,
+        target: &Expr,
+        mut value_expr: TirExprUnresolved,
+        type_annotation: Option<&TypeAnnotation>,
+    ) -> Result<Vec<TirStmtUnresolved>> {
+        if let Some(path) = target_path(target) {
+            self.forget_narrowing(&path);
+        }
+        match target {
+            Expr::Name(name) => {
+                // Check if this is a new variable or existing; assigning to a
+                // module variable without a global declaration creates a local
+                let existing = self
+                    .resolve_var(name)
+                    .filter(|(var_ref, _)| self.can_rebind(name, var_ref));
+                if let Some((var_ref, var_ty)) = existing {
+                    // Variable shared with nested functions - store into its cell
+                    if let Some((_, value_ty)) = self.cell_value_type(&var_ty) {
+                        if !value_expr.ty.is_compatible_with(&value_ty) {
+                            let error = CompilerError::TypeErrorSimple(format!(
+                                "Cannot assign {:?} to variable '{}' of type {:?}",
+                                value_expr.ty, name, value_ty
+                            ));
+                            let label = format!("'{}' gets its type here", name);
+                            return Err(self.label_binding(error, &var_ref, label));
+                        }
+                        value_expr.coerce_to(&value_ty);
+                        return Ok(vec![self.store_cell(var_ref, var_ty, value_expr)]);
+                    }
+                    // Existing variable - check type compatibility
+                    if !value_expr.ty.is_compatible_with(&var_ty) {
+                        let error = CompilerError::TypeErrorSimple(format!(
+                            "Cannot assign {:?} to variable '{}' of type {:?}",
+                            value_expr.ty, name, var_ty
+                        ));
+                        let label = format!("'{}' gets its type here", name);
+                        return Err(self.label_binding(error, &var_ref, label));
+                    }
+                    value_expr.coerce_to(&var_ty);
+                    Ok(vec![TirStmtUnresolved::Assign {
+                        target: TirLValueUnresolved::Var(var_ref),
+                        value: value_expr,
+                    }])
+                } else {
+                    // New variable - create Let
+                    let ty = if let Some(annot) = type_annotation {
+                        let declared_ty = self.convert_annotation(annot);
+                        // Check that value type matches declared type
+                        if !value_expr.ty.is_compatible_with(&declared_ty) {
+                            return Err(CompilerError::TypeErrorSimple(format!(
+                                "Cannot assign {:?} to variable of type {:?}",
+                                value_expr.ty, declared_ty
+                            )));
+                        }
+                        value_expr.coerce_to(&declared_ty);
+                        declared_ty
+                    } else {
+                        value_expr.ty.clone()
+                    };
+                    if self.cell_vars.contains(name) {
+                        return self.new_cell(name, value_expr, ty);
+                    }
+                    let local_id = self.alloc_local(name, ty.clone());
+                    Ok(vec![TirStmtUnresolved::Let {
+                        local: local_id,
+                        ty,
+                        init: value_expr,
+                    }])
+                }
+            }
+
+            Expr::Attribute {
+                value: obj,
+                attr: field,
+            } => {
+                let obj_expr = self.lower_expr(obj)?;
+                if let Some(class_id) = obj_expr.ty.class_id() {
+                    // Assigning a property calls its setter
+                    if self.symbols.resolve_property(class_id, field).is_some() {
+                        return self.lower_property_set(obj_expr, class_id, field, value_expr);
+                    }
+                    if let Some(&field_id) = self.symbols.fields.get(&(class_id, field.clone())) {
+                        // Get field type - check inherited_fields first, then own fields
+                        let class_data = &self.symbols.class_data[class_id.index()];
+                        let inherited_count = class_data.inherited_fields.len();
+                        let field_idx = field_id.index();
+
+                        let field_ty = if field_idx < inherited_count {
+                            // It's an inherited field
+                            TirTypeUnresolved::from_tir_type(
+                                &class_data.inherited_fields[field_idx].1,
+                            )
+                        } else {
+                            // It's an own field
+                            TirTypeUnresolved::from_tir_type(
+                                &class_data.fields[field_idx - inherited_count].1,
+                            )
+                        };
+
+                        // Check compatibility
+                        if !value_expr.ty.is_compatible_with(&field_ty) {
+                            return Err(CompilerError::TypeErrorSimple(format!(
+                                "Cannot assign {:?} to field '{}' of type {:?}",
+                                value_expr.ty, field, field_ty
+                            )));
+                        }
+                        value_expr.coerce_to(&field_ty);
+
+                        return Ok(vec![TirStmtUnresolved::Assign {
+                            target: TirLValueUnresolved::Field {
+                                object: Box::new(obj_expr),
+                                class: class_id,
+                                field: field_id,
+                            },
+                            value: value_expr,
+                        }]);
+                    }
+                }
+                Err(CompilerError::TypeErrorSimple(format!(
+                    "Cannot assign to field {}",
+                    field
+                )))
+            }
+
+            Expr::Subscript {
+                value: container,
+                index,
+            } => {
+                let container_expr = self.lower_expr(container)?;
+                if let Expr::Slice { lower, upper, step } = index.as_ref() {
+                    return self.lower_slice_assign(container_expr, lower, upper, step, value_expr);
+                }
+                let index_expr = self.lower_expr(index)?;
+
+                // Look up __setitem__ method and convert to a Call expression statement
+                let setitem_call = call_dunder_method!(
+                    self.symbols,
+                    &container_expr.ty,
+                    "__setitem__",
+                    vec![container_expr, index_expr, value_expr]
+                )?;
+                Ok(vec![TirStmtUnresolved::Expr(setitem_call)])
+            }
+
+            Expr::Tuple { elts } => self.lower_unpack(elts, value_expr, |this, elt, elem_expr| {
+                this.lower_assign_target(elt, elem_expr, None)
+            }),
+
+            _ => Err(CompilerError::UnsupportedFeature(format!(
+                "Unsupported assignment target: {:?}",
+                target
+            ))),
+        }
+    }
+
+    /// `container[start:stop:step] = value`:
+    /// `container.__setslice__(start, stop, step, value)`, with missing
+    /// bounds passed as the runtime's `SLICE_NONE` and a missing step as 1
+    fn lower_slice_assign(
+        &mut self,
+        container_expr: TirExprUnresolved,
+        lower: &Option<Box<Expr>>,
+        upper: &Option<Box<Expr>>,
+        step: &Option<Box<Expr>>,
+        value_expr: TirExprUnresolved,
+    ) -> Result<Vec<TirStmtUnresolved>> {
+        let supported = container_expr.ty.class_id().is_some_and(|class_id| {
+            self.symbols
+                .resolve_method(class_id, "__setslice__")
+                .is_some()
+        });
+        if !supported {
+            return Err(CompilerError::UnsupportedFeature(
+                "Slice assignment is not supported".to_string(),
+            ));
+        }
+
+        let mut args = vec![container_expr];
+        for (bound, missing) in [(lower, i64::MIN), (upper, i64::MIN), (step, 1)] {
+            let bound_expr = match bound {
+                Some(bound) => self.lower_expr(bound)?,
+                None => TirExprUnresolved::new(
+                    TirExprKindUnresolved::Constant(Constant::Int(missing)),
+                    TirTypeUnresolved::Int,
+                ),
+            };
+            if bound_expr.ty != TirTypeUnresolved::Int {
+                return Err(CompilerError::TypeErrorSimple(format!(
+                    "Slice indices must be integers, got {:?}",
+                    bound_expr.ty
+                )));
+            }
+            args.push(bound_expr);
+        }
+        args.push(value_expr);
+
+        let ty = args[0].ty.clone();
+        let setslice_call = call_dunder_method!(self.symbols, &ty, "__setslice__", args)?;
+        Ok(vec![TirStmtUnresolved::Expr(setslice_call)])
+    }
+
+    /// `obj.name = value` for a property: `obj.<name setter>(value)`
+    fn lower_property_set(
+        &mut self,
+        obj_expr: TirExprUnresolved,
+        class_id: ClassId,
+        name: &str,
+        mut value_expr: TirExprUnresolved,
+    ) -> Result<Vec<TirStmtUnresolved>> {
+        let Some((_, setter_id)) = self
+            .symbols
+            .resolve_method(class_id, &property_setter_name(name))
+        else {
+            return Err(CompilerError::TypeErrorSimple(format!(
+                "Cannot assign to property '{}': it has no setter",
+                name
+            )));
+        };
+        let (param_tys, ret_ty) = self.symbols.get_func_signature(setter_id);
+        let value_ty = TirTypeUnresolved::from_tir_type(&param_tys[0]);
+        if !value_expr.ty.is_compatible_with(&value_ty) {
+            return Err(CompilerError::TypeErrorSimple(format!(
+                "Cannot assign {:?} to property '{}' of type {:?}",
+                value_expr.ty, name, value_ty
+            )));
+        }
+        value_expr.coerce_to(&value_ty);
+        let ret_ty = TirTypeUnresolved::from_tir_type(ret_ty);
+        Ok(vec![TirStmtUnresolved::Expr(TirExprUnresolved::new(
+            TirExprKindUnresolved::Call {
+                func: setter_id,
+                args: vec![obj_expr, value_expr],
+            },
+            ret_ty,
+        ))])
+    }
+
+    /// Lower one target of a `del` statement: `del container[index]` calls the
+    /// container's `__delitem__`, and `del name` unbinds a local variable,
+    /// deallocating its value when its class defines `__dealloc__` (as at the
+    /// end of a for loop)
+    fn lower_delete_target(&mut self, target: &Expr) -> Result<Vec<TirStmtUnresolved>> {
+        match target {
+            Expr::Name(name) => {
+                let (var_ref, var_ty) = self
+                    .resolve_var(name)
+                    .ok_or_else(|| self.undefined_variable(name))?;
+                if !matches!(var_ref, VarRef::Local(_)) || self.cell_vars.contains(name) {
+                    return Err(CompilerError::UnsupportedFeature(format!(
+                        "Cannot delete '{}': only local variables not shared with nested functions can be deleted",
+                        name
+                    )));
+                }
+                self.remove_local(name);
+
+                let has_dealloc = var_ty
+                    .class_id()
+                    .and_then(|class_id| self.symbols.resolve_method(class_id, "__dealloc__"))
+                    .is_some();
+                if !has_dealloc {
+                    return Ok(vec![]);
+                }
+                let var = TirExprUnresolved::new(TirExprKindUnresolved::Var(var_ref), var_ty);
+                let dealloc_call =
+                    call_dunder_method!(self.symbols, &var.ty, "__dealloc__", vec![var])?;
+                Ok(vec![TirStmtUnresolved::Expr(dealloc_call)])
+            }
+
+            Expr::Subscript { value, index } => {
+                if matches!(index.as_ref(), Expr::Slice { .. }) {
+                    return Err(CompilerError::UnsupportedFeature(
+                        "Deleting a slice is not supported".to_string(),
+                    ));
+                }
+                let container_expr = self.lower_expr(value)?;
+                let index_expr = self.lower_expr(index)?;
+                let delitem_call = call_dunder_method!(
+                    self.symbols,
+                    &container_expr.ty,
+                    "__delitem__",
+                    vec![container_expr, index_expr]
+                )?;
+                Ok(vec![TirStmtUnresolved::Expr(delitem_call)])
+            }
+
+            Expr::Tuple { elts } | Expr::List { elts } => {
+                let mut result = Vec::new();
+                for elt in elts {
+                    result.extend(self.lower_delete_target(elt)?);
+                }
+                Ok(result)
+            }
+
+            _ => Err(CompilerError::UnsupportedFeature(format!(
+                "Unsupported del target: {:?}",
+                target
+            ))),
+        }
+    }
+
+    /// Bind a loop iteration's value to the loop target: a fresh variable that
+    /// shadows any outer one of the same name, or a tuple of targets to unpack into
+    fn bind_loop_target(
+        &mut self,
+        target: &Expr,
+        value_expr: TirExprUnresolved,
+    ) -> Result<Vec<TirStmtUnresolved>> {
+        match target {
+            Expr::Name(name) => {
+                let ty = value_expr.ty.clone();
+                let local_id = self.alloc_local(name, ty.clone());
+                Ok(vec![TirStmtUnresolved::Let {
+                    local: local_id,
+                    ty,
+                    init: value_expr,
+                }])
+            }
+            Expr::Tuple { elts } => self.lower_unpack(elts, value_expr, Self::bind_loop_target),
+            _ => Err(CompilerError::UnsupportedFeature(format!(
+                "Unsupported loop target: {:?}",
+                target
+            ))),
+        }
+    }
+
+    /// Unpack a tuple (or, with a starred target, a list) value into `targets`,
+    /// binding each element with `bind`
+    fn lower_unpack(
+        &mut self,
+        targets: &[Expr],
+        value_expr: TirExprUnresolved,
+        bind: fn(&mut Self, &Expr, TirExprUnresolved) -> Result<Vec<TirStmtUnresolved>>,
+    ) -> Result<Vec<TirStmtUnresolved>> {
+        // Unpacking: a, b = value becomes
+        //   _unpack = value
+        //   a = _unpack[0]
+        //   b = _unpack[1]
+        // so the whole right-hand side is evaluated before any target is bound
+        let mut starred = targets
+            .iter()
+            .enumerate()
+            .filter(|(_, target)| matches!(target, Expr::Starred { .. }))
+            .map(|(index, _)| index);
+        let star_index = starred.next();
+        if starred.next().is_some() {
+            return Err(CompilerError::TypeErrorSimple(
+                "Multiple starred targets in unpacking".to_string(),
+            ));
+        }
+
+        if let (Some(star_index), TirTypeUnresolved::Class(class_id)) = (star_index, &value_expr.ty)
+        {
+            if self.symbols.class_data[class_id.index()].qualified_name == "__builtin__.list" {
+                return self.lower_list_unpack(targets, star_index, value_expr, bind);
+            }
+        }
+        let TirTypeUnresolved::Tuple(elem_types) = value_expr.ty.clone() else {
+            return Err(CompilerError::TypeErrorSimple(format!(
+                "Cannot unpack non-tuple type {:?}",
+                value_expr.ty
+            )));
+        };
+        // Targets other than the starred one each take exactly one element
+        let fixed = if star_index.is_some() {
+            targets.len() - 1
+        } else {
+            targets.len()
+        };
+        if star_index.is_none() && elem_types.len() != targets.len() {
+            return Err(CompilerError::TypeErrorSimple(format!(
+                "Cannot unpack a tuple of length {} into {} targets",
+                elem_types.len(),
+                targets.len()
+            )));
+        }
+        if elem_types.len() < fixed {
+            return Err(CompilerError::TypeErrorSimple(format!(
+                "Cannot unpack a tuple of length {} into at least {} targets",
+                elem_types.len(),
+                fixed
+            )));
+        }
+
+        let unpack_name = format!("_unpack_{}", self.next_local_id);
+        let tuple_ty = value_expr.ty.clone();
+        let unpack_local_id = self.alloc_local(&unpack_name, tuple_ty.clone());
+        let mut result = vec![TirStmtUnresolved::Let {
+            local: unpack_local_id,
+            ty: tuple_ty.clone(),
+            init: value_expr,
+        }];
+
+        let tuple_get = |index: usize| {
+            let tuple_var = TirExprUnresolved::new(
+                TirExprKindUnresolved::Var(VarRef::Local(unpack_local_id)),
+                tuple_ty.clone(),
+            );
+            TirExprUnresolved::new(
+                TirExprKindUnresolved::TupleGet {
+                    tuple: Box::new(tuple_var),
+                    index,
+                },
+                elem_types[index].clone(),
+            )
+        };
+
+        // The starred target takes the elements between the leading and
+        // trailing targets, collected into a list
+        let rest_len = elem_types.len() - fixed;
+        let mut index = 0;
+        for target in targets {
+            if let Expr::Starred { value } = target {
+                let elements: Vec<_> = (index..index + rest_len).map(&tuple_get).collect();
+                index += rest_len;
+                let rest_list = self.lower_list_of(elements)?;
+                result.extend(bind(self, value, rest_list)?);
+            } else {
+                result.extend(bind(self, target, tuple_get(index))?);
+                index += 1;
+            }
+        }
+        Ok(result)
+    }
+
+    /// Unpack a list into targets around one starred target:
+    ///   a, *rest, z = value
+    /// becomes
+    ///   _unpack = value
+    ///   _rest = _unpack.__unpack__(1, 1)   (fails if there are fewer than 2 items)
+    ///   a = _unpack[0]
+    ///   rest = _rest
+    ///   z = _unpack[1 + len(_rest)]
+    fn lower_list_unpack(
+        &mut self,
+        targets: &[Expr],
+        star_index: usize,
+        value_expr: TirExprUnresolved,
+        bind: fn(&mut Self, &Expr, TirExprUnresolved) -> Result<Vec<TirStmtUnresolved>>,
+    ) -> Result<Vec<TirStmtUnresolved>> {
+        let list_ty = value_expr.ty.clone();
+        let unpack_name = format!("_unpack_{}", self.next_local_id);
+        let unpack_local_id = self.alloc_local(&unpack_name, list_ty.clone());
+        let mut result = vec![TirStmtUnresolved::Let {
+            local: unpack_local_id,
+            ty: list_ty.clone(),
+            init: value_expr,
+        }];
+        let list_var = || {
+            TirExprUnresolved::new(
+                TirExprKindUnresolved::Var(VarRef::Local(unpack_local_id)),
+                list_ty.clone(),
+            )
+        };
+        let int_const = |value: usize| {
+            TirExprUnresolved::new(
+                TirExprKindUnresolved::Constant(Constant::Int(value as i64)),
+                TirTypeUnresolved::Int,
+            )
+        };
+
+        let after = targets.len() - star_index - 1;
+        let split_call = call_dunder_method!(
+            self.symbols,
+            &list_ty,
+            "__unpack__",
+            vec![list_var(), int_const(star_index), int_const(after)]
+        )?;
+        let rest_name = format!("_unpack_rest_{}", self.next_local_id);
+        let rest_local_id = self.alloc_local(&rest_name, list_ty.clone());
+        result.push(TirStmtUnresolved::Let {
+            local: rest_local_id,
+            ty: list_ty.clone(),
+            init: split_call,
+        });
+        let rest_var = TirExprUnresolved::new(
+            TirExprKindUnresolved::Var(VarRef::Local(rest_local_id)),
+            list_ty.clone(),
+        );
+
+        for (index, target) in targets.iter().enumerate() {
+            let (target, elem_expr) = if let Expr::Starred { value } = target {
+                (value.as_ref(), rest_var.clone())
+            } else {
+                let position = if index < star_index {
+                    int_const(index)
+                } else {
+                    let rest_len = call_dunder_method!(
+                        self.symbols,
+                        &list_ty,
+                        "__len__",
+                        vec![rest_var.clone()]
+                    )?;
+                    TirExprUnresolved::new(
+                        TirExprKindUnresolved::BinOp {
+                            left: Box::new(int_const(index - 1)),
+                            op: BinOperator::Add,
+                            right: Box::new(rest_len),
+                        },
+                        TirTypeUnresolved::Int,
+                    )
+                };
+                let elem_expr = call_dunder_method!(
+                    self.symbols,
+                    &list_ty,
+                    "__getitem__",
+                    vec![list_var(), position]
+                )?;
+                (target, elem_expr)
+            };
+            result.extend(bind(self, target, elem_expr)?);
+        }
+        Ok(result)
     }
 
     /// Expand print(args...) into multiple TIR statements
@@ -489,9 +1783,6 @@ impl<'a> BodyLowerer<'a> {
         let write_space_func = self.symbols.get_write_space_func();
         let write_newline_func = self.symbols.get_write_newline_func();
 
-        let str_class_id = self.symbols.get_or_create_str_class();
-        let str_type = TirTypeUnresolved::Class(str_class_id);
-
         for (i, arg) in args.iter().enumerate() {
             // Add space separator between arguments
             if i > 0 {
@@ -530,39 +1821,13 @@ impl<'a> BodyLowerer<'a> {
                     },
                     TirTypeUnresolved::Void,
                 )),
-                TirTypeUnresolved::Class(class_id) => {
-                    // For classes, call __str__ or __repr__ to convert to String*
-                    let str_expr = if let Some((_method_id, func_id)) =
-                        self.symbols.resolve_method(*class_id, "__str__")
-                    {
-                        TirExprUnresolved::new(
-                            TirExprKindUnresolved::Call {
-                                func: func_id,
-                                args: vec![lowered_arg],
-                            },
-                            str_type.clone(),
-                        )
-                    } else if let Some((_method_id, func_id)) =
-                        self.symbols.resolve_method(*class_id, "__repr__")
-                    {
-                        TirExprUnresolved::new(
-                            TirExprKindUnresolved::Call {
-                                func: func_id,
-                                args: vec![lowered_arg],
-                            },
-                            str_type.clone(),
-                        )
-                    } else {
-                        // No __str__ or __repr__ - create default string
-                        let class_name = &self.symbols.class_data[class_id.index()].qualified_name;
-                        let simple_name = class_name.rsplit('.').next().unwrap_or(class_name);
-                        let default_str = format!("<{} object>", simple_name);
-                        TirExprUnresolved::new(
-                            TirExprKindUnresolved::Constant(Constant::Str(default_str)),
-                            str_type.clone(),
-                        )
-                    };
-
+                TirTypeUnresolved::Class(_)
+                | TirTypeUnresolved::Tuple(_)
+                | TirTypeUnresolved::Optional(_)
+                | TirTypeUnresolved::NoneType => {
+                    // Convert to String* via __str__/__repr__ (or the tuple repr,
+                    // or "None" for an Optional that is None)
+                    let str_expr = self.lower_str_conversion(lowered_arg, false)?;
                     TirStmtUnresolved::Expr(TirExprUnresolved::new(
                         TirExprKindUnresolved::Call {
                             func: write_string_func,
@@ -597,30 +1862,56 @@ impl<'a> BodyLowerer<'a> {
         Ok(stmts)
     }
 
-    /// Resolve an exception class name to a ClassId
-    fn resolve_exception_class(&mut self, name: &str) -> Result<crate::tir::ids::ClassId> {
-        // Check for base Exception class first
-        if name == "Exception" {
-            return Ok(self.symbols.get_or_create_exception_class());
-        }
-
-        // Check if there's a user-defined class with this name in scope
-        if let Some(&class_id) = self.scope.classes.get(name) {
-            // Verify it's actually an exception subclass
-            if self.symbols.is_exception_subclass(class_id) {
-                return Ok(class_id);
-            }
+    /// `exc` with the cause of `raise exc from cause` recorded in it: an
+    /// exception, or None to hide the one being handled
+    fn lower_raise_cause(
+        &mut self,
+        exc: TirExprUnresolved,
+        cause: &Expr,
+    ) -> Result<TirExprUnresolved> {
+        let cause = self.lower_expr(cause)?;
+        let is_exception = match &cause.ty {
+            TirTypeUnresolved::NoneType => true,
+            TirTypeUnresolved::Class(class_id) => self.symbols.is_exception_class(*class_id),
+            _ => false,
+        };
+        if !is_exception {
+            return Err(CompilerError::TypeErrorSimple(format!(
+                "Exception cause must be an exception or None, got {:?}",
+                cause.ty
+            )));
         }
+        let func = self.symbols.get_exception_with_cause_func();
+        let ty = exc.ty.clone();
+        Ok(TirExprUnresolved::new(
+            TirExprKindUnresolved::Call {
+                func,
+                args: vec![exc, cause],
+            },
+            ty,
+        ))
+    }
 
-        // Try to find the class by name in the global symbol table
-        if let Some(class_id) = self.symbols.find_class_by_name(name) {
-            if self.symbols.is_exception_subclass(class_id) {
-                return Ok(class_id);
-            }
+    /// Resolve the class an `except` clause names: a class in scope, which
+    /// may shadow a builtin exception, or a builtin exception
+    fn resolve_exception_class(&mut self, name: &str) -> Result<ClassId> {
+        let class_id = self
+            .scope
+            .classes
+            .get(name)
+            .copied()
+            .or_else(|| self.symbols.builtin_exception_class(name))
+            .or_else(|| self.symbols.find_class_by_name(name));
+        match class_id {
+            Some(class_id) if self.symbols.is_exception_class(class_id) => Ok(class_id),
+            Some(_) => Err(CompilerError::TypeErrorSimple(format!(
+                "Cannot catch '{}': catching classes that do not inherit from Exception is not allowed",
+                name
+            ))),
+            None => Err(CompilerError::TypeErrorSimple(format!(
+                "Undefined exception class: '{}'",
+                name
+            ))),
         }
-
-        // Fall back to base Exception class if not found
-        // This allows catching any exception type
-        Ok(self.symbols.get_or_create_exception_class())
     }
 }
@@ -1,6 +1,9 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
+use crate::ast::{Arg, ClassBodyItem, Constant, Stmt, TypeAnnotation};
+use crate::error::CompilerError;
 use crate::tir::decls::TirClass;
+use crate::tir::expr_unresolved::{TirExprKindUnresolved, TirExprUnresolved};
 use crate::tir::ids::{ClassId, FieldId, FuncId, GlobalId, MethodId, ModuleId};
 use crate::tir::types::TirType;
 
@@ -39,6 +42,50 @@ impl ClassKey {
     }
 }
 
+/// A generic class (`class Stack(Generic[T])`). Its own ClassId has no
+/// members; each use with type arguments is a separate class, collected and
+/// lowered from this body with the type parameters bound.
+pub(crate) struct GenericClass {
+    pub(crate) module: ModuleId,
+    pub(crate) name: String,
+    pub(crate) type_params: Vec<String>,
+    pub(crate) body: Vec<ClassBodyItem>,
+}
+
+/// A generic class instantiated with type arguments (`Stack[int]`)
+pub(crate) struct GenericInstance {
+    pub(crate) template: ClassId,
+    /// Type parameter name -> type argument; the class's own name is bound
+    /// to the instance, so a bare `Stack` in its body means `Stack[T]`
+    pub(crate) type_args: HashMap<String, TirType>,
+    /// Errors found while collecting its signatures, reported when it is lowered
+    pub(crate) error: Option<CompilerError>,
+}
+
+/// A protocol (`class Shape(Protocol)`): the methods a class must provide,
+/// with their signatures (excluding `self`), to be passed for it
+pub(crate) struct Protocol {
+    pub(crate) methods: Vec<(String, Vec<TirType>, TirType)>,
+}
+
+/// A module function taking protocol parameters. Its own FuncId is never
+/// called; each call is to a copy specialized for the classes passed for
+/// the protocols, lowered from this definition.
+pub(crate) struct ProtocolFunction {
+    pub(crate) module: ModuleId,
+    pub(crate) name: String,
+    pub(crate) line: u32,
+    pub(crate) args: Vec<Arg>,
+    pub(crate) return_type: Option<TypeAnnotation>,
+    pub(crate) body: Vec<Stmt>,
+}
+
+/// A protocol function specialized for the classes of its arguments
+pub(crate) struct ProtocolSpecialization {
+    pub(crate) template: FuncId,
+    pub(crate) qualified_name: String,
+}
+
 /// Global symbol table built during pass 1
 pub(crate) struct GlobalSymbols {
     /// Module name -> ModuleId
@@ -69,9 +116,50 @@ pub(crate) struct GlobalSymbols {
     /// Function signatures: FuncId -> (param types, return type)
     pub(crate) func_signatures: Vec<(Vec<TirType>, TirType)>,
 
+    /// Default parameter values: FuncId -> one entry per signature param
+    /// (only present for functions with at least one default)
+    pub(crate) func_defaults: HashMap<FuncId, Vec<Option<Constant>>>,
+
+    /// Methods called without a receiver: `@staticmethod`s, and `@classmethod`s
+    /// (whose `cls` is bound at compile time to the defining class)
+    pub(crate) static_methods: HashSet<FuncId>,
+
+    /// `@property` getters, called when the attribute is read
+    pub(crate) properties: HashSet<FuncId>,
+
+    /// Abstract methods, which subclasses must override
+    pub(crate) abstract_methods: HashSet<FuncId>,
+
+    /// Cell classes (holding variables shared through `nonlocal`): ClassId -> value type
+    pub(crate) cell_classes: HashMap<ClassId, TirType>,
+
     /// Class data: ClassId -> TirClass (fields and methods)
     pub(crate) class_data: Vec<TirClass>,
 
+    /// Generic classes: template ClassId -> definition
+    pub(crate) generic_classes: HashMap<ClassId, GenericClass>,
+
+    /// Instances of generic classes: ClassId -> how it was instantiated
+    pub(crate) generic_instances: HashMap<ClassId, GenericInstance>,
+
+    /// Instances of generic classes whose methods are still to be lowered
+    pub(crate) pending_instances: Vec<ClassId>,
+
+    /// Protocols: ClassId -> required methods
+    pub(crate) protocols: HashMap<ClassId, Protocol>,
+
+    /// Functions taking protocol parameters: template FuncId -> definition
+    pub(crate) protocol_functions: HashMap<FuncId, ProtocolFunction>,
+
+    /// (template FuncId, param types with the protocols replaced) -> specialized FuncId
+    pub(crate) specialized_functions: HashMap<(FuncId, Vec<TirType>), FuncId>,
+
+    /// Specialized FuncId -> how it was specialized
+    pub(crate) specializations: HashMap<FuncId, ProtocolSpecialization>,
+
+    /// Specialized functions whose bodies are still to be lowered
+    pub(crate) pending_specializations: Vec<FuncId>,
+
     /// Counters for ID allocation
     pub(crate) next_func_id: u32,
     pub(crate) next_class_id: u32,
@@ -98,7 +186,20 @@ impl GlobalSymbols {
             globals: HashMap::new(),
             global_types: HashMap::new(),
             func_signatures: Vec::new(),
+            func_defaults: HashMap::new(),
+            static_methods: HashSet::new(),
+            properties: HashSet::new(),
+            abstract_methods: HashSet::new(),
+            cell_classes: HashMap::new(),
             class_data: Vec::new(),
+            generic_classes: HashMap::new(),
+            generic_instances: HashMap::new(),
+            pending_instances: Vec::new(),
+            protocols: HashMap::new(),
+            protocol_functions: HashMap::new(),
+            specialized_functions: HashMap::new(),
+            specializations: HashMap::new(),
+            pending_specializations: Vec::new(),
             next_func_id: 0,
             next_class_id: 0,
             next_module_id: 0,
@@ -132,6 +233,7 @@ impl GlobalSymbols {
             inherited_fields: Vec::new(),
             fields: Vec::new(),
             methods: Vec::new(),
+            vtable: Vec::new(),
             type_params: vec![],
         });
         id
@@ -189,6 +291,35 @@ impl GlobalSymbols {
         None
     }
 
+    /// The name of a type as an annotation would write it (`int`,
+    /// `list[str]`, `Stack[int]`), used to name generic class instances
+    pub(crate) fn type_name(&self, ty: &TirType) -> String {
+        match ty {
+            TirType::Int => "int".to_string(),
+            TirType::Float => "float".to_string(),
+            TirType::Bool => "bool".to_string(),
+            TirType::Void | TirType::NoneType => "None".to_string(),
+            TirType::Class(class_id) => {
+                let class = &self.class_data[class_id.index()];
+                let name = class.qualified_name.rsplit('.').next().unwrap_or_default();
+                if class.type_params.is_empty() {
+                    return name.to_string();
+                }
+                let params: Vec<String> = class
+                    .type_params
+                    .iter()
+                    .map(|param| self.type_name(param))
+                    .collect();
+                format!("{}[{}]", name, params.join(", "))
+            }
+            TirType::Tuple(elems) => {
+                let elems: Vec<String> = elems.iter().map(|elem| self.type_name(elem)).collect();
+                format!("tuple[{}]", elems.join(", "))
+            }
+            TirType::Optional(inner) => format!("Optional[{}]", self.type_name(inner)),
+        }
+    }
+
     /// Set the parent class for a class
     pub(crate) fn set_parent(&mut self, class_id: ClassId, parent_id: ClassId) {
         self.class_data[class_id.index()].parent = Some(parent_id);
@@ -222,6 +353,126 @@ impl GlobalSymbols {
         None
     }
 
+    /// Whether `class_id` is `base` or one of its subclasses
+    pub(crate) fn is_subclass(&self, class_id: ClassId, base: ClassId) -> bool {
+        let mut current = Some(class_id);
+        while let Some(id) = current {
+            if id == base {
+                return true;
+            }
+            current = self.class_data[id.index()].parent;
+        }
+        false
+    }
+
+    /// Whether a subclass of `class_id` defines a method `name` of its own,
+    /// so that a call of `name` on a `class_id` may run another method
+    pub(crate) fn is_overridden(&self, class_id: ClassId, name: &str) -> bool {
+        self.class_data.iter().any(|class| {
+            class.id != class_id
+                && self.is_subclass(class.id, class_id)
+                && class.get_method(name).is_some()
+        })
+    }
+
+    /// A call of method `name`, found as `func_id` on the receiver's class
+    /// `class_id`: dispatched on the class of the receiver (`args[0]`) when
+    /// a subclass overrides it, and otherwise direct
+    pub(crate) fn method_call(
+        &self,
+        class_id: ClassId,
+        name: &str,
+        func_id: FuncId,
+        args: Vec<TirExprUnresolved>,
+    ) -> TirExprKindUnresolved {
+        match self.class_data[class_id.index()].vtable_slot(name) {
+            Some(slot) if self.is_overridden(class_id, name) => {
+                TirExprKindUnresolved::VirtualCall {
+                    func: func_id,
+                    slot,
+                    args,
+                }
+            }
+            _ => TirExprKindUnresolved::Call {
+                func: func_id,
+                args,
+            },
+        }
+    }
+
+    /// Whether a method is called on an instance with `obj.name()`, and can
+    /// therefore be dispatched on the instance's class: `__init__`, static
+    /// and class methods, and property getters and setters are not
+    fn is_dispatched_method(&self, name: &str, func_id: FuncId) -> bool {
+        name != "__init__"
+            && !name.contains('.')
+            && !self.static_methods.contains(&func_id)
+            && !self.properties.contains(&func_id)
+    }
+
+    /// Whether two methods can be called the same way: they take and return
+    /// the same types, or at least classes where one has a class
+    pub(crate) fn same_calling_types(&self, a: FuncId, b: FuncId) -> bool {
+        let same = |a: &TirType, b: &TirType| {
+            a == b || matches!((a, b), (TirType::Class(_), TirType::Class(_)))
+        };
+        let (a_params, a_ret) = self.get_func_signature(a);
+        let (b_params, b_ret) = self.get_func_signature(b);
+        a_params.len() == b_params.len()
+            && a_params.iter().zip(b_params).all(|(a, b)| same(a, b))
+            && same(a_ret, b_ret)
+    }
+
+    /// The vtable of a class: its parent's, with the methods it overrides
+    /// replaced, then its own methods that a subclass overrides. The
+    /// parent's vtable must already be built.
+    pub(crate) fn build_vtable(&self, class_id: ClassId) -> Vec<(String, FuncId)> {
+        let class = &self.class_data[class_id.index()];
+        let mut vtable = match class.parent {
+            Some(parent_id) => self.class_data[parent_id.index()].vtable.clone(),
+            None => Vec::new(),
+        };
+        for (name, func_id) in &class.methods {
+            if !self.is_dispatched_method(name, *func_id) {
+                continue;
+            }
+            match vtable.iter_mut().find(|(slot, _)| slot == name) {
+                Some(slot) => slot.1 = *func_id,
+                None if self.is_overridden(class_id, name) => {
+                    vtable.push((name.clone(), *func_id));
+                }
+                None => {}
+            }
+        }
+        vtable
+    }
+
+    /// Look up a `@property` getter in a class and its parent chain
+    pub(crate) fn resolve_property(&self, class_id: ClassId, name: &str) -> Option<FuncId> {
+        self.resolve_method(class_id, name)
+            .map(|(_, func_id)| func_id)
+            .filter(|func_id| self.properties.contains(func_id))
+    }
+
+    /// The abstract methods a class leaves unimplemented, whether declared
+    /// in it or inherited
+    pub(crate) fn unimplemented_methods(&self, class_id: ClassId) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        let mut current = Some(class_id);
+        while let Some(id) = current {
+            for (name, _) in &self.class_data[id.index()].methods {
+                let is_abstract = self
+                    .resolve_method(class_id, name)
+                    .is_some_and(|(_, func_id)| self.abstract_methods.contains(&func_id));
+                if is_abstract && !names.contains(name) {
+                    names.push(name.clone());
+                }
+            }
+            current = self.class_data[id.index()].parent;
+        }
+        names
+    }
+
     /// Check if a class inherits from Exception (directly or indirectly)
     pub(crate) fn is_exception_subclass(&self, class_id: ClassId) -> bool {
         let mut current = self.class_data[class_id.index()].parent;
@@ -234,6 +485,12 @@ impl GlobalSymbols {
         false
     }
 
+    /// Check if a class is Exception or inherits from it
+    pub(crate) fn is_exception_class(&self, class_id: ClassId) -> bool {
+        self.class_data[class_id.index()].qualified_name == "__builtin__.Exception"
+            || self.is_exception_subclass(class_id)
+    }
+
     // ============================================================
     // Type parameter helpers (for generic containers)
     // ============================================================
@@ -322,6 +579,53 @@ impl GlobalSymbols {
         )
     }
 
+    /// Get the FuncId for int.__str__ (formats an int as a String*)
+    pub(crate) fn get_int_str_func(&mut self) -> FuncId {
+        let str_class_id = self.get_or_create_str_class();
+        self.get_or_create_runtime_func(
+            "__pyc___builtin___int___str__",
+            vec![TirType::Int],
+            TirType::Class(str_class_id),
+        )
+    }
+
+    /// Get the FuncId for bool.__str__ (formats a bool as a String*)
+    pub(crate) fn get_bool_str_func(&mut self) -> FuncId {
+        let str_class_id = self.get_or_create_str_class();
+        self.get_or_create_runtime_func(
+            "__pyc___builtin___bool___str__",
+            vec![TirType::Bool],
+            TirType::Class(str_class_id),
+        )
+    }
+
+    /// Get the FuncId for float.__str__ (formats a float as a String*)
+    pub(crate) fn get_float_str_func(&mut self) -> FuncId {
+        let str_class_id = self.get_or_create_str_class();
+        self.get_or_create_runtime_func(
+            "__pyc___builtin___float___str__",
+            vec![TirType::Float],
+            TirType::Class(str_class_id),
+        )
+    }
+
+    /// Get the FuncId for tuple.__repr__
+    /// Like shared methods, the tuple receiver is not part of the params; the runtime
+    /// renders any tuple from the element kinds stored in it.
+    pub(crate) fn get_tuple_repr_func(&mut self) -> FuncId {
+        let str_class_id = self.get_or_create_str_class();
+        self.get_or_create_runtime_func(
+            "__pyc___builtin___tuple___repr__",
+            vec![],
+            TirType::Class(str_class_id),
+        )
+    }
+
+    /// Get the FuncId for tuple.__len__ (receiver not in params, as for tuple.__repr__)
+    pub(crate) fn get_tuple_len_func(&mut self) -> FuncId {
+        self.get_or_create_runtime_func("__pyc___builtin___tuple___len__", vec![], TirType::Int)
+    }
+
     /// Get the FuncId for write_string_impl (prints String* without newline)
     pub(crate) fn get_write_string_func(&mut self) -> FuncId {
         let str_class_id = self.get_or_create_str_class();
@@ -341,4 +645,235 @@ impl GlobalSymbols {
     pub(crate) fn get_write_newline_func(&mut self) -> FuncId {
         self.get_or_create_runtime_func("write_newline_impl", vec![], TirType::Void)
     }
+
+    // ============================================================
+    // Conversion runtime function helpers (int(), float())
+    // ============================================================
+
+    /// Get the FuncId for hex()/oct()/bin(), formatting an int in base 16, 8 or 2
+    pub(crate) fn get_int_to_str_radix_func(&mut self) -> FuncId {
+        let str_class_id = self.get_or_create_str_class();
+        self.get_or_create_runtime_func(
+            "int64_to_str_radix_impl",
+            vec![TirType::Int, TirType::Int],
+            TirType::Class(str_class_id),
+        )
+    }
+
+    /// Get the FuncId for int(str, base), which raises ValueError on a bad literal
+    pub(crate) fn get_int_from_str_func(&mut self) -> FuncId {
+        let str_class_id = self.get_or_create_str_class();
+        self.get_or_create_runtime_func(
+            "__pyc___builtin___int_from_str",
+            vec![TirType::Class(str_class_id), TirType::Int],
+            TirType::Int,
+        )
+    }
+
+    /// Get the FuncId for int(float), truncating toward zero
+    pub(crate) fn get_int_from_float_func(&mut self) -> FuncId {
+        self.get_or_create_runtime_func(
+            "__pyc___builtin___int_from_float",
+            vec![TirType::Float],
+            TirType::Int,
+        )
+    }
+
+    /// Get the FuncId for int(bool)
+    pub(crate) fn get_int_from_bool_func(&mut self) -> FuncId {
+        self.get_or_create_runtime_func(
+            "__pyc___builtin___int_from_bool",
+            vec![TirType::Bool],
+            TirType::Int,
+        )
+    }
+
+    /// Get the FuncId for float(str), which raises ValueError on a bad literal
+    pub(crate) fn get_float_from_str_func(&mut self) -> FuncId {
+        let str_class_id = self.get_or_create_str_class();
+        self.get_or_create_runtime_func(
+            "__pyc___builtin___float_from_str",
+            vec![TirType::Class(str_class_id)],
+            TirType::Float,
+        )
+    }
+
+    /// Get the FuncId for float(int)
+    pub(crate) fn get_float_from_int_func(&mut self) -> FuncId {
+        self.get_or_create_runtime_func(
+            "__pyc___builtin___float_from_int",
+            vec![TirType::Int],
+            TirType::Float,
+        )
+    }
+
+    /// Get the FuncId for round(float, ndigits)
+    pub(crate) fn get_float_round_func(&mut self) -> FuncId {
+        self.get_or_create_runtime_func(
+            "__pyc___builtin___float_round",
+            vec![TirType::Float, TirType::Int],
+            TirType::Float,
+        )
+    }
+
+    /// Get the FuncId for round(int, ndigits), which raises OverflowError
+    /// when the result does not fit
+    pub(crate) fn get_int_round_func(&mut self) -> FuncId {
+        self.get_or_create_runtime_func(
+            "__pyc___builtin___int_round",
+            vec![TirType::Int, TirType::Int],
+            TirType::Int,
+        )
+    }
+
+    /// Get the FuncId for float.is_integer()
+    pub(crate) fn get_float_is_integer_func(&mut self) -> FuncId {
+        self.get_or_create_runtime_func(
+            "__pyc___builtin___float_is_integer",
+            vec![TirType::Float],
+            TirType::Bool,
+        )
+    }
+
+    /// Get the FuncId for bytes(bytearray), which copies the bytes
+    pub(crate) fn get_bytes_from_bytearray_func(&mut self) -> FuncId {
+        let bytearray_type = TirType::Class(self.get_or_create_bytearray_class());
+        let bytes_type = TirType::Class(self.get_or_create_bytes_class());
+        self.get_or_create_runtime_func(
+            "__pyc___builtin___bytes_from_bytearray",
+            vec![bytearray_type],
+            bytes_type,
+        )
+    }
+
+    // ============================================================
+    // File runtime function helpers (open())
+    // ============================================================
+
+    /// Get the FuncId for open(path, mode), which raises OSError if the file
+    /// cannot be opened
+    pub(crate) fn get_open_func(&mut self) -> FuncId {
+        let str_class_id = self.get_or_create_str_class();
+        let file_class_id = self.get_or_create_file_class();
+        self.get_or_create_runtime_func(
+            "__pyc___builtin___open",
+            vec![TirType::Class(str_class_id), TirType::Class(str_class_id)],
+            TirType::Class(file_class_id),
+        )
+    }
+
+    // ============================================================
+    // sys module runtime function helpers
+    // ============================================================
+
+    /// Get the FuncId for sys.argv, the program's command-line arguments
+    pub(crate) fn get_sys_argv_func(&mut self) -> FuncId {
+        let str_class_id = self.get_or_create_str_class();
+        let list_class_id = self.get_or_create_list_class(&TirType::Class(str_class_id));
+        self.get_or_create_runtime_func("__pyc_sys_argv", vec![], TirType::Class(list_class_id))
+    }
+
+    /// Get the FuncId for sys.exit(code)
+    pub(crate) fn get_sys_exit_func(&mut self) -> FuncId {
+        self.get_or_create_runtime_func("__pyc_sys_exit", vec![TirType::Int], TirType::Void)
+    }
+
+    /// Get the FuncId for sys.exit(message), which prints the message to
+    /// stderr and exits with status 1
+    pub(crate) fn get_sys_exit_message_func(&mut self) -> FuncId {
+        let str_class_id = self.get_or_create_str_class();
+        self.get_or_create_runtime_func(
+            "__pyc_sys_exit_message",
+            vec![TirType::Class(str_class_id)],
+            TirType::Void,
+        )
+    }
+
+    /// Get the FuncId for sys.stdin, sys.stdout or sys.stderr (`stream` is
+    /// the attribute name), a file wrapping that standard stream
+    pub(crate) fn get_sys_stream_func(&mut self, stream: &str) -> FuncId {
+        let file_class_id = self.get_or_create_file_class();
+        self.get_or_create_runtime_func(
+            &format!("__pyc_sys_{}", stream),
+            vec![],
+            TirType::Class(file_class_id),
+        )
+    }
+
+    // ============================================================
+    // os module runtime function helpers
+    // ============================================================
+
+    /// Get the FuncId for os.getenv(key, default)
+    pub(crate) fn get_os_getenv_func(&mut self) -> FuncId {
+        let str_class_id = self.get_or_create_str_class();
+        self.get_or_create_runtime_func(
+            "__pyc_os_getenv",
+            vec![TirType::Class(str_class_id), TirType::Class(str_class_id)],
+            TirType::Class(str_class_id),
+        )
+    }
+
+    /// Get the FuncId for os.environ, a dict[str, str] of the environment
+    pub(crate) fn get_os_environ_func(&mut self) -> FuncId {
+        let str_type = TirType::Class(self.get_or_create_str_class());
+        let dict_class_id = self.get_or_create_dict_class(&str_type, &str_type);
+        self.get_or_create_runtime_func("__pyc_os_environ", vec![], TirType::Class(dict_class_id))
+    }
+
+    /// Get the FuncId for os.getcwd(), which raises OSError if it fails
+    pub(crate) fn get_os_getcwd_func(&mut self) -> FuncId {
+        let str_class_id = self.get_or_create_str_class();
+        self.get_or_create_runtime_func("__pyc_os_getcwd", vec![], TirType::Class(str_class_id))
+    }
+
+    // ============================================================
+    // time module runtime function helpers
+    // ============================================================
+
+    /// Get the FuncId for time.time() or time.monotonic() (`clock` is the
+    /// function name), seconds as a float
+    pub(crate) fn get_time_clock_func(&mut self, clock: &str) -> FuncId {
+        self.get_or_create_runtime_func(&format!("__pyc_time_{}", clock), vec![], TirType::Float)
+    }
+
+    /// Get the FuncId for time.sleep(seconds), which raises ValueError for a
+    /// negative or NaN duration
+    pub(crate) fn get_time_sleep_func(&mut self) -> FuncId {
+        self.get_or_create_runtime_func("__pyc_time_sleep", vec![TirType::Float], TirType::Void)
+    }
+
+    // ============================================================
+    // random module runtime function helpers
+    // ============================================================
+
+    /// Get the FuncId for random.seed(n)
+    pub(crate) fn get_random_seed_func(&mut self) -> FuncId {
+        self.get_or_create_runtime_func("__pyc_random_seed", vec![TirType::Int], TirType::Void)
+    }
+
+    /// Get the FuncId for random.seed(), which reseeds from the clock
+    pub(crate) fn get_random_reseed_func(&mut self) -> FuncId {
+        self.get_or_create_runtime_func("__pyc_random_reseed", vec![], TirType::Void)
+    }
+
+    /// Get the FuncId for random.random(), a float in [0.0, 1.0)
+    pub(crate) fn get_random_random_func(&mut self) -> FuncId {
+        self.get_or_create_runtime_func("__pyc_random_random", vec![], TirType::Float)
+    }
+
+    /// Get the FuncId for random.randint(a, b), which raises ValueError if b < a
+    pub(crate) fn get_random_randint_func(&mut self) -> FuncId {
+        self.get_or_create_runtime_func(
+            "__pyc_random_randint",
+            vec![TirType::Int, TirType::Int],
+            TirType::Int,
+        )
+    }
+
+    /// Get the FuncId for the index random.choice() picks from a sequence of
+    /// the given length, which raises IndexError for an empty sequence
+    pub(crate) fn get_random_index_func(&mut self) -> FuncId {
+        self.get_or_create_runtime_func("__pyc_random_index", vec![TirType::Int], TirType::Int)
+    }
 }
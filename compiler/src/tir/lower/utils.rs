@@ -1,7 +1,8 @@
 /// Macro to call a dunder method on a class type.
 ///
 /// This macro matches on a TirTypeUnresolved, looks up a method (like `__len__`, `__getitem__`, `__setitem__`)
-/// on a class, checks argument types, and generates a Call expression if found.
+/// on a class, checks argument types, and generates a Call expression if found
+/// (a VirtualCall if a subclass overrides the method).
 ///
 /// # Usage
 ///
@@ -27,7 +28,7 @@ macro_rules! call_dunder_method {
                     $symbols.resolve_method(*class_id, $method_name)
                 {
                     let (param_tys, return_ty) = $symbols.get_func_signature(func_id);
-                    let args_vec: Vec<TirExprUnresolved> = $args;
+                    let mut args_vec: Vec<TirExprUnresolved> = $args;
 
                     // Check argument count (args includes self, params does not)
                     if args_vec.len() - 1 != param_tys.len() {
@@ -40,8 +41,11 @@ macro_rules! call_dunder_method {
                     } else {
                         // Check argument types (skip self which is args[0])
                         let mut type_error: Option<String> = None;
-                        for (i, (arg, param_ty)) in
-                            args_vec.iter().skip(1).zip(param_tys.iter()).enumerate()
+                        for (i, (arg, param_ty)) in args_vec
+                            .iter_mut()
+                            .skip(1)
+                            .zip(param_tys.iter())
+                            .enumerate()
                         {
                             let param_ty_unresolved = TirTypeUnresolved::from_tir_type(param_ty);
                             if !arg.ty.is_compatible_with(&param_ty_unresolved) {
@@ -54,16 +58,14 @@ macro_rules! call_dunder_method {
                                 ));
                                 break;
                             }
+                            arg.coerce_to(&param_ty_unresolved);
                         }
 
                         if let Some(err) = type_error {
                             Err(CompilerError::TypeErrorSimple(err))
                         } else {
                             Ok(TirExprUnresolved::new(
-                                TirExprKindUnresolved::Call {
-                                    func: func_id,
-                                    args: args_vec,
-                                },
+                                $symbols.method_call(*class_id, $method_name, func_id, args_vec),
                                 TirTypeUnresolved::from_tir_type(return_ty),
                             ))
                         }
@@ -94,7 +96,7 @@ macro_rules! call_dunder_method {
                     $symbols.resolve_method(*class_id, $method_name)
                 {
                     let (param_tys, _) = $symbols.get_func_signature(func_id);
-                    let args_vec: Vec<TirExprUnresolved> = $args;
+                    let mut args_vec: Vec<TirExprUnresolved> = $args;
 
                     // Check argument count (args includes self, params does not)
                     if args_vec.len() - 1 != param_tys.len() {
@@ -107,8 +109,11 @@ macro_rules! call_dunder_method {
                     } else {
                         // Check argument types (skip self which is args[0])
                         let mut type_error: Option<String> = None;
-                        for (i, (arg, param_ty)) in
-                            args_vec.iter().skip(1).zip(param_tys.iter()).enumerate()
+                        for (i, (arg, param_ty)) in args_vec
+                            .iter_mut()
+                            .skip(1)
+                            .zip(param_tys.iter())
+                            .enumerate()
                         {
                             let param_ty_unresolved = TirTypeUnresolved::from_tir_type(param_ty);
                             if !arg.ty.is_compatible_with(&param_ty_unresolved) {
@@ -121,16 +126,14 @@ macro_rules! call_dunder_method {
                                 ));
                                 break;
                             }
+                            arg.coerce_to(&param_ty_unresolved);
                         }
 
                         if let Some(err) = type_error {
                             Err(CompilerError::TypeErrorSimple(err))
                         } else {
                             Ok(TirExprUnresolved::new(
-                                TirExprKindUnresolved::Call {
-                                    func: func_id,
-                                    args: args_vec,
-                                },
+                                $symbols.method_call(*class_id, $method_name, func_id, args_vec),
                                 $return_ty,
                             ))
                         }
@@ -19,10 +19,14 @@
 
 pub mod decls;
 pub mod decls_unresolved;
+pub mod escape;
 pub mod expr;
 pub mod expr_unresolved;
 pub mod ids;
+pub mod llvm_intrinsic;
 pub mod lower;
+pub mod opt;
+pub mod printer;
 pub mod program;
 pub mod program_unresolved;
 pub mod resolve;
@@ -0,0 +1,155 @@
+//! Unused local removal
+//!
+//! A local that is never read is dropped from its function's local table.
+//! Its assignments keep only what their value does: a value with side
+//! effects (a call, an allocation, an operation that may raise) is still
+//! evaluated as an expression statement, and any other assignment goes
+//! away. An `except ... as e` whose `e` is never read stops binding it.
+//!
+//! Removing an assignment can leave the locals it read unread in turn, so
+//! this repeats until every remaining local is read. The remaining locals
+//! are then renumbered.
+
+use crate::ast::{BinOperator, CompareOp, UnaryOp};
+use crate::tir::escape::expr_children;
+use crate::tir::expr::{TirExpr, TirExprKind, VarRef};
+use crate::tir::ids::LocalId;
+use crate::tir::stmt::{TirLValue, TirStmt};
+use crate::tir::types::TirType;
+
+use super::{walk_body, Node};
+
+/// Remove the locals of a function (or module initializer) that are never read
+pub(super) fn strip_unused_locals(
+    body: &mut Vec<TirStmt>,
+    locals: &mut Vec<(String, TirType)>,
+    checked_int: bool,
+) {
+    let mut read = reads(body, locals.len());
+    while drop_writes(body, &read, checked_int) {
+        read = reads(body, locals.len());
+    }
+
+    let mut renumbered = vec![None; locals.len()];
+    let mut kept = Vec::new();
+    for (index, local) in std::mem::take(locals).into_iter().enumerate() {
+        if read[index] {
+            renumbered[index] = Some(LocalId(kept.len() as u32));
+            kept.push(local);
+        }
+    }
+    *locals = kept;
+
+    let renumber = |local: &mut LocalId| {
+        *local = renumbered[local.index()].expect("unread locals are no longer assigned");
+    };
+    walk_body(body, &mut |node: Node<'_>| match node {
+        Node::Stmt(TirStmt::Let { local, .. })
+        | Node::Stmt(TirStmt::Assign {
+            target: TirLValue::Var(VarRef::Local(local)),
+            ..
+        })
+        | Node::Stmt(TirStmt::AugAssign {
+            target: VarRef::Local(local),
+            ..
+        })
+        | Node::Expr(TirExpr {
+            kind: TirExprKind::Var(VarRef::Local(local)),
+            ..
+        }) => renumber(local),
+        Node::Stmt(TirStmt::Try { handlers, .. }) => {
+            for local in handlers
+                .iter_mut()
+                .filter_map(|handler| handler.local.as_mut())
+            {
+                renumber(local);
+            }
+        }
+        _ => {}
+    });
+}
+
+/// Which locals are read; `x += ...` reads `x`
+fn reads(body: &mut Vec<TirStmt>, local_count: usize) -> Vec<bool> {
+    let mut read = vec![false; local_count];
+    walk_body(body, &mut |node: Node<'_>| match node {
+        Node::Stmt(TirStmt::AugAssign {
+            target: VarRef::Local(local),
+            ..
+        })
+        | Node::Expr(TirExpr {
+            kind: TirExprKind::Var(VarRef::Local(local)),
+            ..
+        }) => read[local.index()] = true,
+        _ => {}
+    });
+    read
+}
+
+/// Remove the assignments to unread locals, returning whether there were any
+fn drop_writes(body: &mut Vec<TirStmt>, read: &[bool], checked_int: bool) -> bool {
+    let unread = |local: &LocalId| !read[local.index()];
+    let mut changed = false;
+    walk_body(body, &mut |node: Node<'_>| match node {
+        Node::Body(stmts) => {
+            *stmts = std::mem::take(stmts)
+                .into_iter()
+                .filter_map(|stmt| match stmt {
+                    TirStmt::Let { local, init, .. } if unread(&local) => {
+                        changed = true;
+                        (!is_pure(&init, checked_int)).then_some(TirStmt::Expr(init))
+                    }
+                    TirStmt::Assign {
+                        target: TirLValue::Var(VarRef::Local(local)),
+                        value,
+                    } if unread(&local) => {
+                        changed = true;
+                        (!is_pure(&value, checked_int)).then_some(TirStmt::Expr(value))
+                    }
+                    stmt => Some(stmt),
+                })
+                .collect();
+        }
+        Node::Stmt(TirStmt::Try { handlers, .. }) => {
+            for handler in handlers {
+                if handler.local.as_ref().is_some_and(unread) {
+                    handler.local = None;
+                    changed = true;
+                }
+            }
+        }
+        _ => {}
+    });
+    changed
+}
+
+/// Whether evaluating an expression has no effect besides its value: it
+/// calls and allocates nothing and cannot raise
+fn is_pure(expr: &TirExpr, checked_int: bool) -> bool {
+    // With --checked-int, int arithmetic may raise OverflowError
+    let may_overflow = checked_int && expr.ty == TirType::Int;
+    let pure = match &expr.kind {
+        TirExprKind::Constant(_) | TirExprKind::Var(_) => true,
+        TirExprKind::BinOp { op, .. } => match op {
+            BinOperator::Add | BinOperator::Sub | BinOperator::Mult => {
+                matches!(expr.ty, TirType::Int | TirType::Float) && !may_overflow
+            }
+            BinOperator::LShift => expr.ty == TirType::Int && !may_overflow,
+            BinOperator::RShift
+            | BinOperator::BitOr
+            | BinOperator::BitXor
+            | BinOperator::BitAnd => expr.ty == TirType::Int,
+            BinOperator::Div | BinOperator::FloorDiv | BinOperator::Mod | BinOperator::Pow => false,
+        },
+        TirExprKind::Compare { left, op, .. } => {
+            matches!(op, CompareOp::Is | CompareOp::IsNot)
+                || matches!(left.ty, TirType::Int | TirType::Float | TirType::Bool)
+        }
+        TirExprKind::UnaryOp { op, .. } => *op == UnaryOp::Not || !may_overflow,
+        TirExprKind::BoolOp { .. } => true,
+        _ => false,
+    };
+    pure && expr_children(expr)
+        .into_iter()
+        .all(|child| is_pure(child, checked_int))
+}
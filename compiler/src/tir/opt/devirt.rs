@@ -0,0 +1,92 @@
+//! Devirtualization
+//!
+//! A virtual call whose receiver is known to be an instance of exactly one
+//! class calls that class's implementation directly. The receiver's class
+//! is exact when it is constructed in place (`Dog().speak()`), or when it
+//! is a local that every assignment constructs the same class into:
+//!
+//! ```python
+//! pet: Animal = Dog()
+//! pet.speak()  # calls Dog.speak
+//! ```
+//!
+//! Calls of methods that no subclass of the receiver's declared class
+//! overrides are made direct when they are lowered.
+
+use crate::tir::decls::TirClass;
+use crate::tir::expr::{TirExpr, TirExprKind, VarRef};
+use crate::tir::ids::ClassId;
+use crate::tir::stmt::{TirLValue, TirStmt};
+
+use super::{walk_body, Node};
+
+/// What the assignments of a local construct
+#[derive(Debug, Clone, Copy)]
+enum Assigned {
+    Nothing,
+    Exactly(ClassId),
+    Anything,
+}
+
+impl Assigned {
+    fn add(&mut self, value: &TirExpr) {
+        *self = match (*self, &value.kind) {
+            (Assigned::Nothing, TirExprKind::Construct { class, .. }) => Assigned::Exactly(*class),
+            (Assigned::Exactly(exact), TirExprKind::Construct { class, .. }) if exact == *class => {
+                Assigned::Exactly(exact)
+            }
+            _ => Assigned::Anything,
+        };
+    }
+}
+
+/// Turn the virtual calls on receivers of an exact class into direct calls
+pub(super) fn devirtualize(body: &mut Vec<TirStmt>, local_count: usize, classes: &[TirClass]) {
+    let assigned = assignments(body, local_count);
+    let exact_class = |receiver: &TirExpr| match &receiver.kind {
+        TirExprKind::Construct { class, .. } => Some(*class),
+        TirExprKind::Var(VarRef::Local(local)) => match assigned[local.index()] {
+            Assigned::Exactly(class) => Some(class),
+            _ => None,
+        },
+        _ => None,
+    };
+    walk_body(body, &mut |node: Node<'_>| {
+        let Node::Expr(expr) = node else {
+            return;
+        };
+        let TirExprKind::VirtualCall { slot, args, .. } = &mut expr.kind else {
+            return;
+        };
+        let Some(class) = args.first().and_then(exact_class) else {
+            return;
+        };
+        let func = classes[class.index()].vtable[*slot].1;
+        let args = std::mem::take(args);
+        expr.kind = TirExprKind::Call { func, args };
+    });
+}
+
+/// What each local is assigned; `x += ...` and `except ... as x` assign
+/// anything
+fn assignments(body: &mut Vec<TirStmt>, local_count: usize) -> Vec<Assigned> {
+    let mut assigned = vec![Assigned::Nothing; local_count];
+    walk_body(body, &mut |node: Node<'_>| match node {
+        Node::Stmt(TirStmt::Let { local, init, .. }) => assigned[local.index()].add(init),
+        Node::Stmt(TirStmt::Assign {
+            target: TirLValue::Var(VarRef::Local(local)),
+            value,
+        }) => assigned[local.index()].add(value),
+        Node::Stmt(TirStmt::AugAssign {
+            target: VarRef::Local(local),
+            ..
+        }) => assigned[local.index()] = Assigned::Anything,
+        Node::Stmt(TirStmt::Try { handlers, .. }) => {
+            for local in handlers.iter().filter_map(|handler| handler.local) {
+                assigned[local.index()] = Assigned::Anything;
+            }
+        }
+        _ => {}
+    });
+    assigned
+}
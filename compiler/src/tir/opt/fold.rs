@@ -0,0 +1,324 @@
+//! Constant folding
+//!
+//! Operations on int, float and bool constants are replaced by their result,
+//! computed the way the generated code would compute it: int arithmetic
+//! wraps around, unless `--checked-int` is on and the operation overflows,
+//! in which case it is left for the program to raise on. Operations that
+//! raise (dividing by zero) or whose result LLVM leaves undefined (shifting
+//! by a negative count or by 64 or more) are not folded, nor is `**`.
+//!
+//! `and`/`or` drop the constant operands that cannot decide the result and
+//! stop at the first one that does. Then, in every block:
+//!
+//! - an `if` on a constant is replaced by the branch it takes
+//! - a `while` on a false constant is removed
+//! - statements after a `return` or `raise` are removed
+
+use std::cmp::Ordering;
+
+use crate::ast::{BinOperator, BoolOp, CompareOp, UnaryOp};
+use crate::tir::expr::{TirConstant, TirExpr, TirExprKind};
+use crate::tir::stmt::{TirLValue, TirStmt};
+use crate::tir::types::TirType;
+
+use super::expr_children_mut;
+
+pub(super) struct Folder {
+    checked_int: bool,
+}
+
+impl Folder {
+    pub(super) fn new(checked_int: bool) -> Self {
+        Folder { checked_int }
+    }
+
+    /// Fold a block, splicing in the branches taken on constants
+    pub(super) fn fold_body(&self, body: &mut Vec<TirStmt>) {
+        let mut folded = Vec::with_capacity(body.len());
+        for mut stmt in std::mem::take(body) {
+            self.fold_stmt(&mut stmt);
+            let taken = match &stmt {
+                TirStmt::If { cond, .. } => truthiness(cond),
+                TirStmt::While { cond, .. } if truthiness(cond) == Some(false) => continue,
+                _ => None,
+            };
+            match (stmt, taken) {
+                (TirStmt::If { then_body, .. }, Some(true)) => folded.extend(then_body),
+                (TirStmt::If { else_body, .. }, Some(false)) => folded.extend(else_body),
+                (stmt, _) => folded.push(stmt),
+            }
+            if matches!(
+                folded.last(),
+                Some(TirStmt::Return(_) | TirStmt::Raise { .. })
+            ) {
+                break;
+            }
+        }
+        *body = folded;
+    }
+
+    fn fold_stmt(&self, stmt: &mut TirStmt) {
+        match stmt {
+            TirStmt::Let { init: expr, .. }
+            | TirStmt::AugAssign { value: expr, .. }
+            | TirStmt::Expr(expr)
+            | TirStmt::Return(Some(expr))
+            | TirStmt::Raise { exc: Some(expr) }
+            | TirStmt::Yield(expr) => self.fold_expr(expr),
+            TirStmt::Assign { target, value } => {
+                if let TirLValue::Field { object, .. } = target {
+                    self.fold_expr(object);
+                }
+                self.fold_expr(value);
+            }
+            TirStmt::Return(None) | TirStmt::Raise { exc: None } => {}
+            TirStmt::If {
+                cond,
+                then_body,
+                else_body,
+            } => {
+                self.fold_expr(cond);
+                self.fold_body(then_body);
+                self.fold_body(else_body);
+            }
+            TirStmt::While { cond, body } => {
+                self.fold_expr(cond);
+                self.fold_body(body);
+            }
+            TirStmt::Try {
+                body,
+                handlers,
+                orelse,
+                finalbody,
+            } => {
+                self.fold_body(body);
+                for handler in handlers {
+                    self.fold_body(&mut handler.body);
+                }
+                self.fold_body(orelse);
+                self.fold_body(finalbody);
+            }
+        }
+    }
+
+    fn fold_expr(&self, expr: &mut TirExpr) {
+        if let TirExprKind::Block { stmts, .. } = &mut expr.kind {
+            self.fold_body(stmts);
+        }
+        for child in expr_children_mut(expr) {
+            self.fold_expr(child);
+        }
+
+        if let TirExprKind::BoolOp { op, values } = &mut expr.kind {
+            if let Some(folded) = fold_boolop(*op, values) {
+                *expr = folded;
+            }
+            return;
+        }
+        let folded = match &expr.kind {
+            TirExprKind::BinOp { left, op, right } => {
+                self.fold_binop(constant(left), *op, constant(right), &expr.ty)
+            }
+            TirExprKind::Compare { left, op, right } => {
+                fold_compare(constant(left), *op, constant(right)).map(TirConstant::Bool)
+            }
+            TirExprKind::UnaryOp { op, operand } => self.fold_unary(*op, operand),
+            _ => None,
+        };
+        if let Some(folded) = folded {
+            expr.kind = TirExprKind::Constant(folded);
+        }
+    }
+
+    fn fold_binop(
+        &self,
+        left: Option<&TirConstant>,
+        op: BinOperator,
+        right: Option<&TirConstant>,
+        ty: &TirType,
+    ) -> Option<TirConstant> {
+        match (left?, right?, ty) {
+            (TirConstant::Int(a), TirConstant::Int(b), TirType::Int) => {
+                self.fold_int_binop(*a, op, *b).map(TirConstant::Int)
+            }
+            // int / int is a float
+            (TirConstant::Int(a), TirConstant::Int(b), TirType::Float) => match op {
+                BinOperator::Div if *b != 0 => Some(TirConstant::Float(*a as f64 / *b as f64)),
+                _ => None,
+            },
+            (left, right, TirType::Float) => {
+                fold_float_binop(as_float(left)?, op, as_float(right)?).map(TirConstant::Float)
+            }
+            _ => None,
+        }
+    }
+
+    fn fold_int_binop(&self, a: i64, op: BinOperator, b: i64) -> Option<i64> {
+        let arithmetic = |checked: fn(i64, i64) -> Option<i64>, wrapping: fn(i64, i64) -> i64| {
+            if self.checked_int {
+                checked(a, b)
+            } else {
+                Some(wrapping(a, b))
+            }
+        };
+        match op {
+            BinOperator::Add => arithmetic(i64::checked_add, i64::wrapping_add),
+            BinOperator::Sub => arithmetic(i64::checked_sub, i64::wrapping_sub),
+            BinOperator::Mult => arithmetic(i64::checked_mul, i64::wrapping_mul),
+            BinOperator::FloorDiv if b == 0 => None,
+            BinOperator::FloorDiv if self.checked_int && a == i64::MIN && b == -1 => None,
+            BinOperator::FloorDiv => Some(floor_divmod(a, b).0),
+            BinOperator::Mod if b == 0 => None,
+            BinOperator::Mod => Some(floor_divmod(a, b).1),
+            // Bits shifted out raise OverflowError with --checked-int
+            BinOperator::LShift if self.checked_int && (0..64).contains(&b) => {
+                Some(a << b).filter(|shifted| shifted >> b == a)
+            }
+            BinOperator::LShift if (0..64).contains(&b) => Some(a << b),
+            BinOperator::RShift if (0..64).contains(&b) => Some(a >> b),
+            BinOperator::BitOr => Some(a | b),
+            BinOperator::BitXor => Some(a ^ b),
+            BinOperator::BitAnd => Some(a & b),
+            BinOperator::LShift | BinOperator::RShift | BinOperator::Div | BinOperator::Pow => None,
+        }
+    }
+
+    fn fold_unary(&self, op: UnaryOp, operand: &TirExpr) -> Option<TirConstant> {
+        match (op, constant(operand)?) {
+            (UnaryOp::Not, _) => truthiness(operand).map(|truth| TirConstant::Bool(!truth)),
+            (UnaryOp::USub, TirConstant::Int(n)) if self.checked_int => {
+                n.checked_neg().map(TirConstant::Int)
+            }
+            (UnaryOp::USub, TirConstant::Int(n)) => Some(TirConstant::Int(n.wrapping_neg())),
+            (UnaryOp::USub, TirConstant::Float(f)) => Some(TirConstant::Float(-f)),
+            (UnaryOp::USub, _) => None,
+        }
+    }
+}
+
+/// Simplify `and`/`or`, returning the expression that replaces it if it
+/// reduces to a constant or a single bool operand
+fn fold_boolop(op: BoolOp, values: &mut Vec<TirExpr>) -> Option<TirExpr> {
+    // The truth value that decides the result: false for `and`, true for `or`
+    let deciding = op == BoolOp::Or;
+    let mut kept = Vec::with_capacity(values.len());
+    for value in values.drain(..) {
+        match truthiness(&value) {
+            Some(truth) if truth != deciding => {}
+            Some(_) => {
+                kept.push(value);
+                break;
+            }
+            None => kept.push(value),
+        }
+    }
+
+    let result = match kept.len() {
+        0 => Some(bool_constant(!deciding)),
+        1 => match truthiness(&kept[0]) {
+            Some(truth) => Some(bool_constant(truth)),
+            None if kept[0].ty == TirType::Bool => kept.pop(),
+            None => None,
+        },
+        _ => None,
+    };
+    *values = kept;
+    result
+}
+
+/// Compare two constants, as ints, bools or (mixed with an int) floats;
+/// NaN is never folded
+fn fold_compare(
+    left: Option<&TirConstant>,
+    op: CompareOp,
+    right: Option<&TirConstant>,
+) -> Option<bool> {
+    let (left, right) = (left?, right?);
+    let ordering = match (left, right) {
+        (TirConstant::Int(a), TirConstant::Int(b)) => a.cmp(b),
+        (TirConstant::Bool(a), TirConstant::Bool(b)) => a.cmp(b),
+        _ => as_float(left)?.partial_cmp(&as_float(right)?)?,
+    };
+    match op {
+        CompareOp::Eq => Some(ordering == Ordering::Equal),
+        CompareOp::NotEq => Some(ordering != Ordering::Equal),
+        CompareOp::Lt => Some(ordering == Ordering::Less),
+        CompareOp::LtE => Some(ordering != Ordering::Greater),
+        CompareOp::Gt => Some(ordering == Ordering::Greater),
+        CompareOp::GtE => Some(ordering != Ordering::Less),
+        CompareOp::In | CompareOp::NotIn | CompareOp::Is | CompareOp::IsNot => None,
+    }
+}
+
+fn fold_float_binop(a: f64, op: BinOperator, b: f64) -> Option<f64> {
+    match op {
+        BinOperator::Add => Some(a + b),
+        BinOperator::Sub => Some(a - b),
+        BinOperator::Mult => Some(a * b),
+        BinOperator::Div | BinOperator::FloorDiv | BinOperator::Mod if b == 0.0 => None,
+        BinOperator::Div => Some(a / b),
+        BinOperator::FloorDiv => Some((a / b).floor()),
+        BinOperator::Mod => {
+            // The remainder takes the sign of the divisor
+            let rem = a % b;
+            if rem != 0.0 && (rem < 0.0) != (b < 0.0) {
+                Some(rem + b)
+            } else {
+                Some(rem)
+            }
+        }
+        BinOperator::Pow
+        | BinOperator::LShift
+        | BinOperator::RShift
+        | BinOperator::BitOr
+        | BinOperator::BitXor
+        | BinOperator::BitAnd => None,
+    }
+}
+
+/// Python's floor division and modulo of a nonzero divisor, where
+/// `i64::MIN // -1` wraps around as in the generated code
+fn floor_divmod(a: i64, b: i64) -> (i64, i64) {
+    if b == -1 {
+        return (a.wrapping_neg(), 0);
+    }
+    let (quot, rem) = (a / b, a % b);
+    if rem != 0 && (rem < 0) != (b < 0) {
+        (quot - 1, rem + b)
+    } else {
+        (quot, rem)
+    }
+}
+
+fn constant(expr: &TirExpr) -> Option<&TirConstant> {
+    match &expr.kind {
+        TirExprKind::Constant(constant) => Some(constant),
+        _ => None,
+    }
+}
+
+fn as_float(constant: &TirConstant) -> Option<f64> {
+    match constant {
+        TirConstant::Int(n) => Some(*n as f64),
+        TirConstant::Float(f) => Some(*f),
+        _ => None,
+    }
+}
+
+fn bool_constant(value: bool) -> TirExpr {
+    TirExpr::new(
+        TirExprKind::Constant(TirConstant::Bool(value)),
+        TirType::Bool,
+    )
+}
+
+/// The truth value of a constant int, float, bool or None
+fn truthiness(expr: &TirExpr) -> Option<bool> {
+    match constant(expr)? {
+        TirConstant::Bool(b) => Some(*b),
+        TirConstant::Int(n) => Some(*n != 0),
+        TirConstant::Float(f) => Some(*f != 0.0),
+        TirConstant::None => Some(false),
+        TirConstant::Str(_) => None,
+    }
+}
@@ -0,0 +1,46 @@
+//! Inlining of small functions
+//!
+//! Getters, setters, small dunder methods and other one-liners cost more to
+//! call than to run. Instead of splicing their bodies into their callers
+//! here, codegen marks them `alwaysinline` and LLVM inlines every call to
+//! them when the module is optimized, so object-oriented code can use
+//! accessors without paying for the calls.
+//!
+//! A function is small when its body has at most [`INLINE_BUDGET`]
+//! statements and expressions, no loop, `try` or comprehension, and it does
+//! not call itself.
+
+use crate::tir::decls::TirFunction;
+use crate::tir::escape::{for_each_node, Node};
+use crate::tir::expr::{TirExpr, TirExprKind};
+use crate::tir::stmt::TirStmt;
+
+/// Statements and expressions a function may have and still be inlined
+pub const INLINE_BUDGET: usize = 16;
+
+/// Whether every call to `func` should be inlined
+pub fn is_small_function(func: &TirFunction) -> bool {
+    if func.runtime_name.is_some() {
+        return false;
+    }
+    let mut size = 0;
+    let mut inlinable = true;
+    for_each_node(&func.body, &mut |node| {
+        size += 1;
+        match node {
+            Node::Stmt(TirStmt::While { .. } | TirStmt::Try { .. })
+            | Node::Expr(TirExpr {
+                kind: TirExprKind::Block { .. },
+                ..
+            }) => inlinable = false,
+            Node::Expr(TirExpr {
+                kind:
+                    TirExprKind::Call { func: callee, .. }
+                    | TirExprKind::VirtualCall { func: callee, .. },
+                ..
+            }) if *callee == func.id => inlinable = false,
+            _ => {}
+        }
+    });
+    inlinable && size <= INLINE_BUDGET
+}
@@ -0,0 +1,426 @@
+//! TIR optimization passes
+//!
+//! Run between lowering and codegen when the program is compiled with
+//! `--opt-level=1` or higher:
+//!
+//! - [`fold`]: constant folding and removal of branches that are never taken
+//! - [`devirt`]: direct calls of the methods of receivers of an exact class
+//! - [`dce`]: removal of locals that are never read
+//! - [`inline`]: the small functions codegen asks LLVM to inline
+//!
+//! Folding runs first, since a branch it removes may hold the only read of
+//! a local, or the only assignment keeping a local's class from being
+//! exact. Inlining is decided on the optimized functions.
+
+mod dce;
+mod devirt;
+mod fold;
+mod inline;
+
+pub use inline::is_small_function;
+
+use super::expr::{TirExpr, TirExprKind};
+use super::program::TirProgram;
+use super::stmt::{TirLValue, TirStmt};
+
+/// Optimize every function and module initializer of the program
+///
+/// With `checked_int`, int arithmetic that would overflow is left for the
+/// program to raise OverflowError on instead of being folded.
+pub fn optimize(program: &mut TirProgram, checked_int: bool) {
+    let folder = fold::Folder::new(checked_int);
+    for func in &mut program.functions {
+        if func.runtime_name.is_some() {
+            continue;
+        }
+        folder.fold_body(&mut func.body);
+        devirt::devirtualize(&mut func.body, func.locals.len(), &program.classes);
+        dce::strip_unused_locals(&mut func.body, &mut func.locals, checked_int);
+    }
+    for module in &mut program.modules {
+        folder.fold_body(&mut module.init_body);
+        devirt::devirtualize(
+            &mut module.init_body,
+            module.init_locals.len(),
+            &program.classes,
+        );
+        dce::strip_unused_locals(&mut module.init_body, &mut module.init_locals, checked_int);
+    }
+}
+
+/// A node reached while walking a block
+enum Node<'a> {
+    Body(&'a mut Vec<TirStmt>),
+    Stmt(&'a mut TirStmt),
+    Expr(&'a mut TirExpr),
+}
+
+/// Visit a block, then each of its statements and everything nested in
+/// them; a node is visited before its children, so a visitor rewriting a
+/// block or statement has its children walked in their new form
+fn walk_body(body: &mut Vec<TirStmt>, visit: &mut dyn FnMut(Node<'_>)) {
+    visit(Node::Body(body));
+    for stmt in body.iter_mut() {
+        walk_stmt(stmt, visit);
+    }
+}
+
+fn walk_stmt(stmt: &mut TirStmt, visit: &mut dyn FnMut(Node<'_>)) {
+    visit(Node::Stmt(stmt));
+    match stmt {
+        TirStmt::Let { init: expr, .. }
+        | TirStmt::AugAssign { value: expr, .. }
+        | TirStmt::Expr(expr)
+        | TirStmt::Return(Some(expr))
+        | TirStmt::Raise { exc: Some(expr) }
+        | TirStmt::Yield(expr) => walk_expr(expr, visit),
+        TirStmt::Assign { target, value } => {
+            if let TirLValue::Field { object, .. } = target {
+                walk_expr(object, visit);
+            }
+            walk_expr(value, visit);
+        }
+        TirStmt::Return(None) | TirStmt::Raise { exc: None } => {}
+        TirStmt::If {
+            cond,
+            then_body,
+            else_body,
+        } => {
+            walk_expr(cond, visit);
+            walk_body(then_body, visit);
+            walk_body(else_body, visit);
+        }
+        TirStmt::While { cond, body } => {
+            walk_expr(cond, visit);
+            walk_body(body, visit);
+        }
+        TirStmt::Try {
+            body,
+            handlers,
+            orelse,
+            finalbody,
+        } => {
+            walk_body(body, visit);
+            for handler in handlers {
+                walk_body(&mut handler.body, visit);
+            }
+            walk_body(orelse, visit);
+            walk_body(finalbody, visit);
+        }
+    }
+}
+
+fn walk_expr(expr: &mut TirExpr, visit: &mut dyn FnMut(Node<'_>)) {
+    visit(Node::Expr(expr));
+    if let TirExprKind::Block { stmts, .. } = &mut expr.kind {
+        walk_body(stmts, visit);
+    }
+    for child in expr_children_mut(expr) {
+        walk_expr(child, visit);
+    }
+}
+
+/// The subexpressions of an expression (for a block, just its result)
+fn expr_children_mut(expr: &mut TirExpr) -> Vec<&mut TirExpr> {
+    match &mut expr.kind {
+        TirExprKind::Constant(_) | TirExprKind::Var(_) | TirExprKind::Bytes { .. } => vec![],
+        TirExprKind::BinOp { left, right, .. } | TirExprKind::Compare { left, right, .. } => {
+            vec![&mut **left, &mut **right]
+        }
+        TirExprKind::UnaryOp { operand, .. } => vec![&mut **operand],
+        TirExprKind::BoolOp { values: exprs, .. }
+        | TirExprKind::Call { args: exprs, .. }
+        | TirExprKind::VirtualCall { args: exprs, .. }
+        | TirExprKind::Construct { args: exprs, .. }
+        | TirExprKind::List {
+            elements: exprs, ..
+        }
+        | TirExprKind::Set {
+            elements: exprs, ..
+        }
+        | TirExprKind::Tuple { elements: exprs }
+        | TirExprKind::MathIntrinsic { args: exprs, .. }
+        | TirExprKind::LlvmIntrinsic { args: exprs, .. } => exprs.iter_mut().collect(),
+        TirExprKind::Range { start, stop, step } => {
+            [start.as_deref_mut(), Some(&mut **stop), step.as_deref_mut()]
+                .into_iter()
+                .flatten()
+                .collect()
+        }
+        TirExprKind::FieldAccess { object, .. } => vec![&mut **object],
+        TirExprKind::Dict { entries, .. } => entries
+            .iter_mut()
+            .flat_map(|(key, value)| [key, value])
+            .collect(),
+        TirExprKind::TupleGet { tuple, .. } => vec![&mut **tuple],
+        TirExprKind::Slice {
+            object,
+            start,
+            stop,
+            step,
+            ..
+        } => [
+            Some(&mut **object),
+            start.as_deref_mut(),
+            stop.as_deref_mut(),
+            step.as_deref_mut(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect(),
+        TirExprKind::Block { result, .. } => vec![&mut **result],
+        TirExprKind::WrapOptional { value } | TirExprKind::UnwrapOptional { value } => {
+            vec![&mut **value]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::driver::build_modules;
+    use crate::tir::expr::{TirConstant, VarRef};
+    use crate::tir::ids::LocalId;
+    use crate::tir::lower_to_tir;
+    use crate::tir::TirFunction;
+    use std::fs;
+    use tempfile::TempDir;
+
+    /// The program of `source`, optimized
+    fn optimized_program(source: &str, checked_int: bool) -> TirProgram {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("main.py");
+        fs::write(&path, source).unwrap();
+        let (modules, entry) = build_modules(&path, temp_dir.path()).unwrap();
+        let mut program = lower_to_tir(modules, entry).unwrap();
+        optimize(&mut program, checked_int);
+        program
+    }
+
+    /// Function `name` of `source`, optimized
+    fn optimized(source: &str, name: &str, checked_int: bool) -> TirFunction {
+        optimized_program(source, checked_int)
+            .functions
+            .into_iter()
+            .find(|func| func.name == name)
+            .unwrap()
+    }
+
+    fn returned_constant(func: &TirFunction) -> Option<TirConstant> {
+        match func.body.as_slice() {
+            [TirStmt::Return(Some(TirExpr {
+                kind: TirExprKind::Constant(constant),
+                ..
+            }))] => Some(constant.clone()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_fold_constants() {
+        let source = r#"
+def arithmetic() -> int:
+    return (2 * 3 + -1) // 2 - 7 % -3
+
+def compare() -> bool:
+    return 1 < 2.5 and not 0 or False
+
+def overflow() -> int:
+    return 9223372036854775807 + 1
+"#;
+        let arithmetic = optimized(source, "arithmetic", false);
+        assert_eq!(returned_constant(&arithmetic), Some(TirConstant::Int(4)));
+        let compare = optimized(source, "compare", false);
+        assert_eq!(returned_constant(&compare), Some(TirConstant::Bool(true)));
+        // Wraps around, as the generated code would
+        let overflow = optimized(source, "overflow", false);
+        assert_eq!(
+            returned_constant(&overflow),
+            Some(TirConstant::Int(i64::MIN))
+        );
+        // Left to raise OverflowError at run time
+        let overflow = optimized(source, "overflow", true);
+        assert_eq!(returned_constant(&overflow), None);
+    }
+
+    #[test]
+    fn test_remove_dead_branches() {
+        let source = r#"
+def branches(n: int) -> int:
+    if False:
+        return 1
+    while 0:
+        print(n)
+    if 1 + 1 == 2:
+        return n
+    else:
+        print(n)
+    return 0
+"#;
+        let func = optimized(source, "branches", false);
+        assert!(matches!(
+            func.body.as_slice(),
+            [TirStmt::Return(Some(TirExpr {
+                kind: TirExprKind::Var(VarRef::Param(0)),
+                ..
+            }))]
+        ));
+    }
+
+    #[test]
+    fn test_strip_unused_locals() {
+        let source = r#"
+def side(n: int) -> int:
+    return n
+
+def temporaries(n: int) -> int:
+    a: int = n * 2
+    b: int = a + 1
+    c: int = side(n)
+    d: int = n
+    return d
+"#;
+        let func = optimized(source, "temporaries", false);
+        let names: Vec<_> = func.locals.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, vec!["d"]);
+        // The call is kept for its effects, and `d` is renumbered
+        assert!(matches!(
+            func.body.as_slice(),
+            [
+                TirStmt::Expr(TirExpr {
+                    kind: TirExprKind::Call { .. },
+                    ..
+                }),
+                TirStmt::Let {
+                    local: LocalId(0),
+                    ..
+                },
+                TirStmt::Return(Some(_)),
+            ]
+        ));
+    }
+
+    #[test]
+    fn test_small_functions() {
+        let source = r#"
+class Counter:
+    count: int
+
+    def __init__(self) -> None:
+        self.count = 0
+
+    def current(self) -> int:
+        return self.count
+
+    def advance(self, by: int) -> None:
+        self.count = self.count + by
+
+def fact(n: int) -> int:
+    if n <= 1:
+        return 1
+    return n * fact(n - 1)
+
+def total(n: int) -> int:
+    s: int = 0
+    for i in range(n):
+        s += i
+    return s
+"#;
+        let small = |name| is_small_function(&optimized(source, name, false));
+        assert!(small("current"));
+        assert!(small("advance"));
+        // Recursive, and looping
+        assert!(!small("fact"));
+        assert!(!small("total"));
+    }
+
+    #[test]
+    fn test_devirtualize() {
+        let source = r#"
+class Animal:
+    def speak(self) -> str:
+        return "..."
+
+    def kind(self) -> str:
+        return "animal"
+
+class Dog(Animal):
+    def speak(self) -> str:
+        return "woof"
+
+class Puppy(Dog):
+    def speak(self) -> str:
+        return "yip"
+
+def declared(pet: Animal, dog: Dog) -> str:
+    return pet.speak() + pet.kind() + dog.speak()
+
+def leaf(puppy: Puppy) -> str:
+    return puppy.speak()
+
+def exact() -> str:
+    pet: Animal = Dog()
+    return pet.speak() + Puppy().speak()
+
+def reassigned(flag: bool) -> str:
+    pet: Animal = Dog()
+    if flag:
+        pet = Puppy()
+    return pet.speak()
+"#;
+        let mut program = optimized_program(source, false);
+        let method = |program: &TirProgram, qualified_name: &str| {
+            program
+                .functions
+                .iter()
+                .find(|func| func.qualified_name == qualified_name)
+                .unwrap()
+                .id
+        };
+        let vtable = |program: &TirProgram, name: &str| {
+            program
+                .classes
+                .iter()
+                .find(|class| class.qualified_name == name)
+                .unwrap()
+                .vtable
+                .clone()
+        };
+        // Only the overridden method has a slot, filled in by each class
+        let speak = |class: &str| vec![("speak".to_string(), method(&program, class))];
+        assert_eq!(vtable(&program, "main.Animal"), speak("main.Animal.speak"));
+        assert_eq!(vtable(&program, "main.Dog"), speak("main.Dog.speak"));
+        assert_eq!(vtable(&program, "main.Puppy"), speak("main.Puppy.speak"));
+
+        let puppy_speak = method(&program, "main.Puppy.speak");
+        let dog_speak = method(&program, "main.Dog.speak");
+        let mut calls = |name: &str| {
+            let func = program
+                .functions
+                .iter_mut()
+                .find(|func| func.name == name)
+                .unwrap();
+            let (mut virtual_calls, mut direct_calls) = (0, Vec::new());
+            walk_body(&mut func.body, &mut |node| match node {
+                Node::Expr(TirExpr {
+                    kind: TirExprKind::VirtualCall { .. },
+                    ..
+                }) => virtual_calls += 1,
+                Node::Expr(TirExpr {
+                    kind: TirExprKind::Call { func, .. },
+                    ..
+                }) => direct_calls.push(*func),
+                _ => {}
+            });
+            (virtual_calls, direct_calls)
+        };
+        // `kind` is final, as is `speak` on a Puppy
+        assert_eq!(calls("declared").0, 2);
+        assert_eq!(calls("leaf"), (0, vec![puppy_speak]));
+        // Receivers of an exact class
+        let (virtual_calls, direct_calls) = calls("exact");
+        assert_eq!(virtual_calls, 0);
+        assert!(direct_calls.contains(&dog_speak) && direct_calls.contains(&puppy_speak));
+        assert_eq!(calls("reassigned").0, 1);
+    }
+}
@@ -0,0 +1,657 @@
+//! Textual form of a TIR program (`--emit-tir`)
+//!
+//! A resolved program prints as Python-like text in which every type is
+//! spelled out: the signature and locals of each function, the fields,
+//! methods and vtable of each class, and the type of each `let`. Functions
+//! and classes are named by their qualified names rather than their IDs,
+//! and the runtime's own classes and functions are left out, so the text of
+//! a program only changes when its lowering does:
+//!
+//! ```text
+//! module app (entry)
+//!     global total: int
+//!
+//! init app:
+//!     app.total = app.add(1, 2)
+//!
+//! def app.add(a: int, b: int) -> int:
+//!     return (a + b)
+//! ```
+
+use std::fmt::{self, Write};
+
+use crate::ast::{BinOperator, BoolOp, CompareOp, UnaryOp};
+
+use super::decls::{TirClass, TirFunction};
+use super::expr::{TirConstant, TirExpr, TirExprKind, VarRef};
+use super::ids::{ClassId, FieldId, FuncId};
+use super::program::{TirModule, TirProgram};
+use super::stmt::{TirLValue, TirStmt};
+use super::types::TirType;
+
+const INDENT: &str = "    ";
+
+impl fmt::Display for TirProgram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut printer = Printer::new(self);
+        printer.program();
+        f.write_str(printer.out.trim_end())
+    }
+}
+
+/// Writes a program, one item at a time, keeping the indentation and the
+/// variables of the function being written
+struct Printer<'a> {
+    program: &'a TirProgram,
+    out: String,
+    indent: usize,
+    params: &'a [(String, TirType)],
+    locals: &'a [(String, TirType)],
+}
+
+impl<'a> Printer<'a> {
+    fn new(program: &'a TirProgram) -> Self {
+        Printer {
+            program,
+            out: String::new(),
+            indent: 0,
+            params: &[],
+            locals: &[],
+        }
+    }
+
+    fn program(&mut self) {
+        let program = self.program;
+        for module in &program.modules {
+            self.module(module);
+        }
+        for class in &program.classes {
+            if !class.qualified_name.starts_with("__builtin__.") {
+                self.class(class);
+            }
+        }
+        for function in &program.functions {
+            // Runtime functions have no body to print, and IDs allocated
+            // for nothing are left empty
+            let is_extern = program.externs.contains(&function.id);
+            if (function.runtime_name.is_none() || is_extern) && !function.qualified_name.is_empty()
+            {
+                self.function(function, is_extern);
+            }
+        }
+    }
+
+    fn module(&mut self, module: &'a TirModule) {
+        let entry = if module.id == self.program.entry {
+            " (entry)"
+        } else {
+            ""
+        };
+        self.line(format_args!("module {}{}", module.name, entry));
+        self.indent += 1;
+        for global in &module.globals {
+            self.line(format_args!(
+                "global {}: {}",
+                global.name,
+                self.program.type_name(&global.ty)
+            ));
+        }
+        self.indent -= 1;
+        self.out.push('\n');
+
+        self.line(format_args!("init {}:", module.name));
+        self.params = &[];
+        self.locals = &module.init_locals;
+        self.indent += 1;
+        self.local_decls();
+        self.block(&module.init_body);
+        self.indent -= 1;
+        self.out.push('\n');
+    }
+
+    fn class(&mut self, class: &TirClass) {
+        match class.parent {
+            Some(parent) => self.line(format_args!(
+                "class {}({}):",
+                class.qualified_name,
+                self.program.class(parent).qualified_name
+            )),
+            None => self.line(format_args!("class {}:", class.qualified_name)),
+        }
+        self.indent += 1;
+        for (name, ty) in &class.inherited_fields {
+            self.line(format_args!(
+                "inherited {}: {}",
+                name,
+                self.program.type_name(ty)
+            ));
+        }
+        for (name, ty) in &class.fields {
+            self.line(format_args!(
+                "field {}: {}",
+                name,
+                self.program.type_name(ty)
+            ));
+        }
+        for (name, func) in &class.methods {
+            self.line(format_args!("method {} = {}", name, self.func_name(*func)));
+        }
+        for (slot, (name, func)) in class.vtable.iter().enumerate() {
+            self.line(format_args!(
+                "vtable {} {} = {}",
+                slot,
+                name,
+                self.func_name(*func)
+            ));
+        }
+        if class.all_fields().next().is_none() && class.methods.is_empty() {
+            self.line(format_args!("pass"));
+        }
+        self.indent -= 1;
+        self.out.push('\n');
+    }
+
+    fn function(&mut self, function: &'a TirFunction, is_extern: bool) {
+        let params: Vec<String> = function
+            .params
+            .iter()
+            .map(|(name, ty)| format!("{}: {}", name, self.program.type_name(ty)))
+            .collect();
+        let signature = format!(
+            "def {}({}) -> {}",
+            function.qualified_name,
+            params.join(", "),
+            self.program.type_name(&function.return_type)
+        );
+        if is_extern {
+            let c_name = function.runtime_name.as_deref().unwrap_or_default();
+            self.line(format_args!("extern {:?} {}", c_name, signature));
+            self.out.push('\n');
+            return;
+        }
+        let export = if self.program.exports.contains(&function.id) {
+            "export "
+        } else {
+            ""
+        };
+        self.line(format_args!("{}{}:", export, signature));
+
+        self.params = &function.params;
+        self.locals = &function.locals;
+        self.indent += 1;
+        self.local_decls();
+        self.block(&function.body);
+        self.indent -= 1;
+        self.out.push('\n');
+    }
+
+    /// The `local` declarations of the function being written
+    fn local_decls(&mut self) {
+        for (name, ty) in self.locals {
+            self.line(format_args!(
+                "local {}: {}",
+                name,
+                self.program.type_name(ty)
+            ));
+        }
+    }
+
+    /// A statement body, which is `pass` when empty
+    fn block(&mut self, stmts: &[TirStmt]) {
+        if stmts.is_empty() {
+            self.line(format_args!("pass"));
+        }
+        for stmt in stmts {
+            self.stmt(stmt);
+        }
+    }
+
+    /// An indented statement body under a header line
+    fn nested(&mut self, header: fmt::Arguments<'_>, stmts: &[TirStmt]) {
+        self.line(header);
+        self.indent += 1;
+        self.block(stmts);
+        self.indent -= 1;
+    }
+
+    fn stmt(&mut self, stmt: &TirStmt) {
+        self.start_line();
+        match stmt {
+            TirStmt::Let { local, ty, init } => {
+                let name = &self.locals[local.index()].0;
+                write!(self.out, "let {}: {} = ", name, self.program.type_name(ty)).unwrap();
+                self.expr(init);
+            }
+            TirStmt::Assign { target, value } => {
+                match target {
+                    TirLValue::Var(var) => self.var(*var),
+                    TirLValue::Field {
+                        object,
+                        class,
+                        field,
+                    } => self.field(object, *class, *field),
+                }
+                self.out.push_str(" = ");
+                self.expr(value);
+            }
+            TirStmt::AugAssign { target, op, value } => {
+                self.var(*target);
+                write!(self.out, " {}= ", bin_op(*op)).unwrap();
+                self.expr(value);
+            }
+            TirStmt::Expr(expr) => self.expr(expr),
+            TirStmt::Return(value) => {
+                self.out.push_str("return");
+                if let Some(value) = value {
+                    self.out.push(' ');
+                    self.expr(value);
+                }
+            }
+            TirStmt::If {
+                cond,
+                then_body,
+                else_body,
+            } => {
+                self.out.push_str("if ");
+                self.expr(cond);
+                self.out.push_str(":\n");
+                self.indent += 1;
+                self.block(then_body);
+                self.indent -= 1;
+                if !else_body.is_empty() {
+                    self.nested(format_args!("else:"), else_body);
+                }
+                return;
+            }
+            TirStmt::While { cond, body } => {
+                self.out.push_str("while ");
+                self.expr(cond);
+                self.out.push_str(":\n");
+                self.indent += 1;
+                self.block(body);
+                self.indent -= 1;
+                return;
+            }
+            TirStmt::Try {
+                body,
+                handlers,
+                orelse,
+                finalbody,
+            } => {
+                self.out.push_str("try:\n");
+                self.indent += 1;
+                self.block(body);
+                self.indent -= 1;
+                for handler in handlers {
+                    let class = handler
+                        .exc_class
+                        .map(|class| format!(" {}", self.program.class_name(class)))
+                        .unwrap_or_default();
+                    let binding = handler
+                        .local
+                        .map(|local| format!(" as {}", self.locals[local.index()].0))
+                        .unwrap_or_default();
+                    self.nested(format_args!("except{}{}:", class, binding), &handler.body);
+                }
+                if !orelse.is_empty() {
+                    self.nested(format_args!("else:"), orelse);
+                }
+                if !finalbody.is_empty() {
+                    self.nested(format_args!("finally:"), finalbody);
+                }
+                return;
+            }
+            TirStmt::Raise { exc } => {
+                self.out.push_str("raise");
+                if let Some(exc) = exc {
+                    self.out.push(' ');
+                    self.expr(exc);
+                }
+            }
+            TirStmt::Yield(value) => {
+                self.out.push_str("yield ");
+                self.expr(value);
+            }
+        }
+        self.out.push('\n');
+    }
+
+    fn expr(&mut self, expr: &TirExpr) {
+        match &expr.kind {
+            TirExprKind::Constant(constant) => match constant {
+                TirConstant::Int(value) => write!(self.out, "{}", value).unwrap(),
+                TirConstant::Float(value) => write!(self.out, "{:?}", value).unwrap(),
+                TirConstant::Str(value) => write!(self.out, "{:?}", value).unwrap(),
+                TirConstant::Bool(true) => self.out.push_str("True"),
+                TirConstant::Bool(false) => self.out.push_str("False"),
+                TirConstant::None => self.out.push_str("None"),
+            },
+            TirExprKind::Var(var) => self.var(*var),
+            TirExprKind::BinOp { left, op, right } => {
+                self.out.push('(');
+                self.expr(left);
+                write!(self.out, " {} ", bin_op(*op)).unwrap();
+                self.expr(right);
+                self.out.push(')');
+            }
+            TirExprKind::Compare { left, op, right } => {
+                self.out.push('(');
+                self.expr(left);
+                write!(self.out, " {} ", compare_op(*op)).unwrap();
+                self.expr(right);
+                self.out.push(')');
+            }
+            TirExprKind::BoolOp { op, values } => {
+                let op = match op {
+                    BoolOp::And => " and ",
+                    BoolOp::Or => " or ",
+                };
+                self.out.push('(');
+                self.exprs(values, op);
+                self.out.push(')');
+            }
+            TirExprKind::UnaryOp { op, operand } => {
+                self.out.push_str(match op {
+                    UnaryOp::Not => "(not ",
+                    UnaryOp::USub => "(-",
+                });
+                self.expr(operand);
+                self.out.push(')');
+            }
+            TirExprKind::Call { func, args } => {
+                self.out.push_str(self.func_name(*func));
+                self.args(args);
+            }
+            TirExprKind::VirtualCall { func, slot, args } => {
+                write!(self.out, "virtual[{}] {}", slot, self.func_name(*func)).unwrap();
+                self.args(args);
+            }
+            TirExprKind::Construct { class, args } => {
+                write!(self.out, "new {}", self.program.class_name(*class)).unwrap();
+                self.args(args);
+            }
+            TirExprKind::Range { start, stop, step } => {
+                self.out.push_str("range(");
+                match start {
+                    Some(start) => self.expr(start),
+                    None => self.out.push('0'),
+                }
+                self.out.push_str(", ");
+                self.expr(stop);
+                self.out.push_str(", ");
+                match step {
+                    Some(step) => self.expr(step),
+                    None => self.out.push('1'),
+                }
+                self.out.push(')');
+            }
+            TirExprKind::FieldAccess {
+                object,
+                class,
+                field,
+            } => self.field(object, *class, *field),
+            TirExprKind::List { elements, .. } => {
+                self.out.push('[');
+                self.exprs(elements, ", ");
+                self.out.push(']');
+            }
+            TirExprKind::Dict { entries, .. } => {
+                self.out.push('{');
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    if i > 0 {
+                        self.out.push_str(", ");
+                    }
+                    self.expr(key);
+                    self.out.push_str(": ");
+                    self.expr(value);
+                }
+                self.out.push('}');
+            }
+            TirExprKind::Set { elements, .. } if elements.is_empty() => {
+                self.out.push_str("set()");
+            }
+            TirExprKind::Set { elements, .. } => {
+                self.out.push('{');
+                self.exprs(elements, ", ");
+                self.out.push('}');
+            }
+            TirExprKind::Tuple { elements } => {
+                self.out.push('(');
+                self.exprs(elements, ", ");
+                if elements.len() == 1 {
+                    self.out.push(',');
+                }
+                self.out.push(')');
+            }
+            TirExprKind::TupleGet { tuple, index } => {
+                self.expr(tuple);
+                write!(self.out, "[{}]", index).unwrap();
+            }
+            TirExprKind::MathIntrinsic { func, args } => {
+                write!(self.out, "math.{}", func.name()).unwrap();
+                self.args(args);
+            }
+            TirExprKind::LlvmIntrinsic { name, args } => {
+                write!(self.out, "__intrinsic__({:?}", name).unwrap();
+                for arg in args {
+                    self.out.push_str(", ");
+                    self.expr(arg);
+                }
+                self.out.push(')');
+            }
+            TirExprKind::Slice {
+                object,
+                start,
+                stop,
+                step,
+                ..
+            } => {
+                self.expr(object);
+                self.out.push('[');
+                for (i, bound) in [start, stop, step].into_iter().enumerate() {
+                    if i > 0 {
+                        self.out.push(':');
+                    }
+                    if let Some(bound) = bound {
+                        self.expr(bound);
+                    }
+                }
+                self.out.push(']');
+            }
+            TirExprKind::Bytes { data } => self.out.push_str(&bytes_literal(data)),
+            TirExprKind::Block { stmts, result } => {
+                self.out.push_str("block {\n");
+                self.indent += 1;
+                for stmt in stmts {
+                    self.stmt(stmt);
+                }
+                self.start_line();
+                self.expr(result);
+                self.out.push('\n');
+                self.indent -= 1;
+                self.start_line();
+                self.out.push('}');
+            }
+            TirExprKind::WrapOptional { value } => {
+                self.out.push_str("wrap(");
+                self.expr(value);
+                self.out.push(')');
+            }
+            TirExprKind::UnwrapOptional { value } => {
+                self.out.push_str("unwrap(");
+                self.expr(value);
+                self.out.push(')');
+            }
+        }
+    }
+
+    /// Expressions separated by `separator`
+    fn exprs(&mut self, exprs: &[TirExpr], separator: &str) {
+        for (i, expr) in exprs.iter().enumerate() {
+            if i > 0 {
+                self.out.push_str(separator);
+            }
+            self.expr(expr);
+        }
+    }
+
+    /// A parenthesized argument list
+    fn args(&mut self, args: &[TirExpr]) {
+        self.out.push('(');
+        self.exprs(args, ", ");
+        self.out.push(')');
+    }
+
+    fn var(&mut self, var: VarRef) {
+        match var {
+            VarRef::Local(local) => self.out.push_str(&self.locals[local.index()].0),
+            VarRef::Param(index) => self.out.push_str(&self.params[index as usize].0),
+            VarRef::Global(module, global) => {
+                let module = self.program.module(module);
+                write!(
+                    self.out,
+                    "{}.{}",
+                    module.name,
+                    module.globals[global.index()].name
+                )
+                .unwrap();
+            }
+            VarRef::SelfRef => self.out.push_str("self"),
+        }
+    }
+
+    fn field(&mut self, object: &TirExpr, class: ClassId, field: FieldId) {
+        self.expr(object);
+        let name = self
+            .program
+            .class(class)
+            .all_fields()
+            .nth(field.index())
+            .map_or("?", |(name, _)| name.as_str());
+        write!(self.out, ".{}", name).unwrap();
+    }
+
+    fn func_name(&self, func: FuncId) -> &'a str {
+        &self.program.function(func).qualified_name
+    }
+
+    fn start_line(&mut self) {
+        for _ in 0..self.indent {
+            self.out.push_str(INDENT);
+        }
+    }
+
+    fn line(&mut self, text: fmt::Arguments<'_>) {
+        self.start_line();
+        self.out.write_fmt(text).unwrap();
+        self.out.push('\n');
+    }
+}
+
+fn bin_op(op: BinOperator) -> &'static str {
+    match op {
+        BinOperator::Add => "+",
+        BinOperator::Sub => "-",
+        BinOperator::Mult => "*",
+        BinOperator::Div => "/",
+        BinOperator::FloorDiv => "//",
+        BinOperator::Mod => "%",
+        BinOperator::Pow => "**",
+        BinOperator::LShift => "<<",
+        BinOperator::RShift => ">>",
+        BinOperator::BitOr => "|",
+        BinOperator::BitXor => "^",
+        BinOperator::BitAnd => "&",
+    }
+}
+
+fn compare_op(op: CompareOp) -> &'static str {
+    match op {
+        CompareOp::Eq => "==",
+        CompareOp::NotEq => "!=",
+        CompareOp::Lt => "<",
+        CompareOp::LtE => "<=",
+        CompareOp::Gt => ">",
+        CompareOp::GtE => ">=",
+        CompareOp::In => "in",
+        CompareOp::NotIn => "not in",
+        CompareOp::Is => "is",
+        CompareOp::IsNot => "is not",
+    }
+}
+
+/// A bytes literal as Python writes it: `b'a\x00'`
+fn bytes_literal(data: &[u8]) -> String {
+    let mut literal = String::from("b'");
+    for &byte in data {
+        match byte {
+            b'\\' => literal.push_str("\\\\"),
+            b'\'' => literal.push_str("\\'"),
+            b'\n' => literal.push_str("\\n"),
+            b'\r' => literal.push_str("\\r"),
+            b'\t' => literal.push_str("\\t"),
+            0x20..=0x7e => literal.push(byte as char),
+            _ => write!(literal, "\\x{:02x}", byte).unwrap(),
+        }
+    }
+    literal.push('\'');
+    literal
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::driver::build_modules;
+    use crate::tir::lower_to_tir;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn print_source(source: &str) -> String {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("main.py");
+        fs::write(&path, source).unwrap();
+        let (modules, entry) = build_modules(&path, temp_dir.path()).unwrap();
+        lower_to_tir(modules, entry).unwrap().to_string()
+    }
+
+    #[test]
+    fn test_function_text() {
+        let text = print_source(
+            r#"
+def scale(x: float, n: int) -> float:
+    if n > 0:
+        return x * 2.0
+    return x
+
+scale(1.5, 2)
+"#,
+        );
+        assert!(text.starts_with("module main (entry)\n"));
+        assert!(text.contains(
+            "def main.scale(x: float, n: int) -> float:\n    \
+             if (n > 0):\n        \
+             return (x * 2.0)\n    \
+             return x\n"
+        ));
+    }
+
+    #[test]
+    fn test_stable_text() {
+        let source = r#"
+class Box:
+    def __init__(self, items: list[int]) -> None:
+        self.items = items
+
+b = Box([1, 2, 3])
+print(len(b.items), b"\x00a'")
+"#;
+        let text = print_source(source);
+        assert!(text.contains("    field items: list[int]\n"));
+        assert_eq!(text, print_source(source));
+    }
+
+    #[test]
+    fn test_bytes_literal() {
+        assert_eq!(bytes_literal(b"a'\\\n\x00\xff"), r"b'a\'\\\n\x00\xff'");
+    }
+}
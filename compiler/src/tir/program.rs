@@ -2,6 +2,10 @@
 //!
 //! The program contains all modules, functions, and classes indexed by their IDs.
 
+use std::path::PathBuf;
+
+use crate::lint::Warning;
+
 use super::decls::{TirClass, TirFunction};
 use super::ids::{ClassId, FuncId, GlobalId, ModuleId};
 use super::stmt::TirStmt;
@@ -29,6 +33,9 @@ pub struct TirModule {
     /// Module name (e.g., "mypackage.submodule")
     pub name: String,
 
+    /// Source file the module was compiled from
+    pub path: PathBuf,
+
     /// Global variables defined in this module
     pub globals: Vec<TirGlobal>,
 
@@ -60,6 +67,17 @@ pub struct TirProgram {
 
     /// Entry module ID
     pub entry: ModuleId,
+
+    /// Functions decorated with `@export`, which get C symbols of their own
+    /// names
+    pub exports: Vec<FuncId>,
+
+    /// Functions declared with `@extern`, which are C functions called
+    /// through their `runtime_name`
+    pub externs: Vec<FuncId>,
+
+    /// Warnings found while lowering, in source order
+    pub warnings: Vec<Warning>,
 }
 
 impl TirProgram {
@@ -77,4 +95,86 @@ impl TirProgram {
     pub fn module(&self, id: ModuleId) -> &TirModule {
         &self.modules[id.index()]
     }
+
+    /// A class as a type annotation names it: the runtime's classes without
+    /// their `__builtin__` module, and with their type parameters
+    pub fn class_name(&self, class: ClassId) -> String {
+        let class = self.class(class);
+        let name = class
+            .qualified_name
+            .strip_prefix("__builtin__.")
+            .unwrap_or(&class.qualified_name);
+        if class.type_params.is_empty() {
+            return name.to_string();
+        }
+        let params: Vec<String> = class
+            .type_params
+            .iter()
+            .map(|param| self.type_name(param))
+            .collect();
+        format!("{}[{}]", name, params.join(", "))
+    }
+
+    /// A type as an annotation names it
+    pub fn type_name(&self, ty: &TirType) -> String {
+        match ty {
+            TirType::Int => "int".to_string(),
+            TirType::Float => "float".to_string(),
+            TirType::Bool => "bool".to_string(),
+            TirType::Void | TirType::NoneType => "None".to_string(),
+            TirType::Class(class) => self.class_name(*class),
+            TirType::Tuple(elems) => {
+                let elems: Vec<String> = elems.iter().map(|elem| self.type_name(elem)).collect();
+                format!("tuple[{}]", elems.join(", "))
+            }
+            TirType::Optional(inner) => format!("Optional[{}]", self.type_name(inner)),
+        }
+    }
+
+    /// Whether `id` is `base` or one of its subclasses
+    pub fn is_subclass(&self, id: ClassId, base: ClassId) -> bool {
+        let mut current = Some(id);
+        while let Some(class_id) = current {
+            if class_id == base {
+                return true;
+            }
+            current = self.class(class_id).parent;
+        }
+        false
+    }
+
+    /// The class at the top of a class's hierarchy
+    fn root_class(&self, mut id: ClassId) -> ClassId {
+        while let Some(parent) = self.class(id).parent {
+            id = parent;
+        }
+        id
+    }
+
+    /// Whether instances of a class start with a pointer to their class's
+    /// vtable, which they do when any class of its hierarchy has a vtable
+    /// (so that the pointer is at the same place in all of them)
+    pub fn has_vtable_pointer(&self, id: ClassId) -> bool {
+        let root = self.root_class(id);
+        self.classes
+            .iter()
+            .any(|class| !class.vtable.is_empty() && self.root_class(class.id) == root)
+    }
+
+    /// The methods a virtual call of method `func` through vtable `slot` may
+    /// run: `func`, and its overrides in the subclasses of its class
+    pub fn implementations(&self, func: FuncId, slot: usize) -> Vec<FuncId> {
+        let Some(owner) = self.function(func).class else {
+            return vec![func];
+        };
+        let mut implementations = vec![func];
+        for class in &self.classes {
+            if let Some(&(_, implementation)) = class.vtable.get(slot) {
+                if self.is_subclass(class.id, owner) && !implementations.contains(&implementation) {
+                    implementations.push(implementation);
+                }
+            }
+        }
+        implementations
+    }
 }
@@ -44,7 +44,7 @@ fn resolve_stmt(
     match stmt {
         TirStmtUnresolved::Let { local, ty, init } => Ok(TirStmt::Let {
             local,
-            ty: resolve_type(&ty, substitutions, symbols)?,
+            ty: resolve_type(&ty, substitutions, symbols).map_err(|e| e.at(init.span))?,
             init: resolve_expr(init, substitutions, symbols)?,
         }),
         TirStmtUnresolved::Assign { target, value } => Ok(TirStmt::Assign {
@@ -99,6 +99,9 @@ fn resolve_stmt(
                 .map(|e| resolve_expr(e, substitutions, symbols))
                 .transpose()?,
         }),
+        TirStmtUnresolved::Yield(expr) => {
+            Ok(TirStmt::Yield(resolve_expr(expr, substitutions, symbols)?))
+        }
     }
 }
 
@@ -135,11 +138,23 @@ fn resolve_lvalue(
     }
 }
 
-/// Resolve an expression
+/// Resolve an expression, keeping its position; errors inside it are
+/// reported there
 fn resolve_expr(
     expr: TirExprUnresolved,
     substitutions: &HashMap<u32, TirTypeUnresolved>,
     symbols: &mut GlobalSymbols,
+) -> Result<TirExpr> {
+    let span = expr.span;
+    resolve_expr_kind(expr, substitutions, symbols)
+        .map(|resolved| resolved.at(span))
+        .map_err(|e| e.at(span))
+}
+
+fn resolve_expr_kind(
+    expr: TirExprUnresolved,
+    substitutions: &HashMap<u32, TirTypeUnresolved>,
+    symbols: &mut GlobalSymbols,
 ) -> Result<TirExpr> {
     let resolved_ty = resolve_type(&expr.ty, substitutions, symbols)?;
 
@@ -196,6 +211,17 @@ fn resolve_expr(
                 args: resolved_args,
             }
         }
+        TirExprKindUnresolved::VirtualCall { func, slot, args } => {
+            let resolved_args = args
+                .into_iter()
+                .map(|arg| resolve_expr(arg, substitutions, symbols))
+                .collect::<Result<Vec<_>>>()?;
+            TirExprKind::VirtualCall {
+                func,
+                slot,
+                args: resolved_args,
+            }
+        }
         TirExprKindUnresolved::Construct { class, args } => {
             // Check if this is a range() construction
             let class_data = &symbols.class_data[class.index()];
@@ -266,7 +292,91 @@ fn resolve_expr(
                 elem_ty: resolve_type(&elem_ty, substitutions, symbols)?,
             }
         }
+        TirExprKindUnresolved::Dict {
+            entries,
+            key_ty,
+            value_ty,
+        } => {
+            let resolved_entries = entries
+                .into_iter()
+                .map(|(key, value)| {
+                    Ok((
+                        resolve_expr(key, substitutions, symbols)?,
+                        resolve_expr(value, substitutions, symbols)?,
+                    ))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            TirExprKind::Dict {
+                entries: resolved_entries,
+                key_ty: resolve_type(&key_ty, substitutions, symbols)?,
+                value_ty: resolve_type(&value_ty, substitutions, symbols)?,
+            }
+        }
+        TirExprKindUnresolved::Set { elements, elem_ty } => {
+            let resolved_elements = elements
+                .into_iter()
+                .map(|elem| resolve_expr(elem, substitutions, symbols))
+                .collect::<Result<Vec<_>>>()?;
+            TirExprKind::Set {
+                elements: resolved_elements,
+                elem_ty: resolve_type(&elem_ty, substitutions, symbols)?,
+            }
+        }
+        TirExprKindUnresolved::Tuple { elements } => TirExprKind::Tuple {
+            elements: elements
+                .into_iter()
+                .map(|elem| resolve_expr(elem, substitutions, symbols))
+                .collect::<Result<Vec<_>>>()?,
+        },
+        TirExprKindUnresolved::WrapOptional { value } => TirExprKind::WrapOptional {
+            value: Box::new(resolve_expr(*value, substitutions, symbols)?),
+        },
+        TirExprKindUnresolved::UnwrapOptional { value } => TirExprKind::UnwrapOptional {
+            value: Box::new(resolve_expr(*value, substitutions, symbols)?),
+        },
+        TirExprKindUnresolved::TupleGet { tuple, index } => TirExprKind::TupleGet {
+            tuple: Box::new(resolve_expr(*tuple, substitutions, symbols)?),
+            index,
+        },
+        TirExprKindUnresolved::MathIntrinsic { func, args } => TirExprKind::MathIntrinsic {
+            func,
+            args: args
+                .into_iter()
+                .map(|arg| resolve_expr(arg, substitutions, symbols))
+                .collect::<Result<Vec<_>>>()?,
+        },
+        TirExprKindUnresolved::LlvmIntrinsic { name, args } => TirExprKind::LlvmIntrinsic {
+            name,
+            args: args
+                .into_iter()
+                .map(|arg| resolve_expr(arg, substitutions, symbols))
+                .collect::<Result<Vec<_>>>()?,
+        },
+        TirExprKindUnresolved::Slice {
+            func,
+            object,
+            start,
+            stop,
+            step,
+        } => {
+            let mut resolve_bound = |bound: Option<Box<TirExprUnresolved>>| {
+                bound
+                    .map(|e| resolve_expr(*e, substitutions, symbols).map(Box::new))
+                    .transpose()
+            };
+            TirExprKind::Slice {
+                func,
+                object: Box::new(resolve_expr(*object, substitutions, symbols)?),
+                start: resolve_bound(start)?,
+                stop: resolve_bound(stop)?,
+                step: resolve_bound(step)?,
+            }
+        }
         TirExprKindUnresolved::Bytes { data } => TirExprKind::Bytes { data },
+        TirExprKindUnresolved::Block { stmts, result } => TirExprKind::Block {
+            stmts: resolve_body(stmts, substitutions, symbols)?,
+            result: Box::new(resolve_expr(*result, substitutions, symbols)?),
+        },
     };
 
     Ok(TirExpr::new(resolved_kind, resolved_ty))
@@ -292,6 +402,7 @@ pub(crate) fn resolve_type(
         TirTypeUnresolved::Float => Ok(TirType::Float),
         TirTypeUnresolved::Bool => Ok(TirType::Bool),
         TirTypeUnresolved::Void => Ok(TirType::Void),
+        TirTypeUnresolved::NoneType => Ok(TirType::NoneType),
         TirTypeUnresolved::Class(class_id) => {
             // For classes, we need to substitute type_params in ClassData
             // and validate that all type parameters are resolved
@@ -310,6 +421,17 @@ pub(crate) fn resolve_type(
 
             Ok(TirType::Class(class_id))
         }
+        TirTypeUnresolved::Tuple(elems) => Ok(TirType::Tuple(
+            elems
+                .iter()
+                .map(|t| resolve_type(t, substitutions, symbols))
+                .collect::<Result<Vec<_>>>()?,
+        )),
+        TirTypeUnresolved::Optional(inner) => Ok(TirType::Optional(Box::new(resolve_type(
+            &inner,
+            substitutions,
+            symbols,
+        )?))),
         TirTypeUnresolved::TypeVar(id) => {
             // This is the critical error: TypeVar not resolved
             // This means constraint solving couldn't infer the type
@@ -3,6 +3,7 @@
 //! Statements in TIR use resolved references and embedded types.
 
 use crate::ast::BinOperator;
+use crate::span::Span;
 
 use super::expr::{TirExpr, VarRef};
 use super::ids::{ClassId, FieldId, LocalId};
@@ -84,4 +85,86 @@ pub enum TirStmt {
     Raise {
         exc: Option<TirExpr>, // None for bare 'raise' (re-raise)
     },
+
+    /// Yield a value from a generator function.
+    /// Only present until the body is rewritten into the generator's `__next__`.
+    Yield(TirExpr),
+}
+
+impl TirStmt {
+    /// Where the statement starts in the source, from the expression it
+    /// evaluates first; None for statements without one
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            TirStmt::Let { init: value, .. }
+            | TirStmt::Assign { value, .. }
+            | TirStmt::AugAssign { value, .. }
+            | TirStmt::Expr(value)
+            | TirStmt::Return(Some(value))
+            | TirStmt::If { cond: value, .. }
+            | TirStmt::While { cond: value, .. }
+            | TirStmt::Raise { exc: Some(value) }
+            | TirStmt::Yield(value) => Some(value.span).filter(|span| span.is_known()),
+            TirStmt::Return(None) | TirStmt::Raise { exc: None } | TirStmt::Try { .. } => None,
+        }
+    }
+
+    /// Whether a return in the statement leaves a try block whose finally
+    /// block has a body, which must run first
+    pub fn returns_through_finally(&self) -> bool {
+        match self {
+            TirStmt::If {
+                then_body,
+                else_body,
+                ..
+            } => then_body
+                .iter()
+                .chain(else_body)
+                .any(TirStmt::returns_through_finally),
+            TirStmt::While { body, .. } => body.iter().any(TirStmt::returns_through_finally),
+            TirStmt::Try {
+                body,
+                handlers,
+                orelse,
+                finalbody,
+            } => {
+                let mut guarded = body
+                    .iter()
+                    .chain(handlers.iter().flat_map(|handler| &handler.body))
+                    .chain(orelse);
+                if finalbody.is_empty() {
+                    guarded.any(TirStmt::returns_through_finally)
+                } else {
+                    guarded.any(TirStmt::returns)
+                        || finalbody.iter().any(TirStmt::returns_through_finally)
+                }
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether the statement is or contains a return
+    fn returns(&self) -> bool {
+        match self {
+            TirStmt::Return(_) => true,
+            TirStmt::If {
+                then_body,
+                else_body,
+                ..
+            } => then_body.iter().chain(else_body).any(TirStmt::returns),
+            TirStmt::While { body, .. } => body.iter().any(TirStmt::returns),
+            TirStmt::Try {
+                body,
+                handlers,
+                orelse,
+                finalbody,
+            } => body
+                .iter()
+                .chain(handlers.iter().flat_map(|handler| &handler.body))
+                .chain(orelse)
+                .chain(finalbody)
+                .any(TirStmt::returns),
+            _ => false,
+        }
+    }
 }
@@ -8,6 +8,7 @@
 //! via the resolve module.
 
 use crate::ast::BinOperator;
+use crate::span::Span;
 
 use super::expr::VarRef; // VarRef is shared between resolved and unresolved TIR
 use super::expr_unresolved::TirExprUnresolved;
@@ -95,4 +96,65 @@ pub enum TirStmtUnresolved {
     Raise {
         exc: Option<TirExprUnresolved>, // None for bare 'raise' (re-raise)
     },
+
+    /// Yield a value from a generator function
+    Yield(TirExprUnresolved),
+}
+
+impl TirStmtUnresolved {
+    /// Place the statement at `span`: its expressions, and those of the
+    /// statements nested in it, whose position lowering left unknown
+    pub fn locate(&mut self, span: Span) {
+        let locate_expr = |expr: &mut TirExprUnresolved| {
+            if !expr.span.is_known() {
+                expr.span = span;
+            }
+        };
+        let locate_body = |body: &mut Vec<TirStmtUnresolved>| {
+            for stmt in body {
+                stmt.locate(span);
+            }
+        };
+        match self {
+            TirStmtUnresolved::Let { init: value, .. }
+            | TirStmtUnresolved::AugAssign { value, .. }
+            | TirStmtUnresolved::Expr(value)
+            | TirStmtUnresolved::Return(Some(value))
+            | TirStmtUnresolved::Raise { exc: Some(value) }
+            | TirStmtUnresolved::Yield(value) => locate_expr(value),
+            TirStmtUnresolved::Assign { target, value } => {
+                if let TirLValueUnresolved::Field { object, .. } = target {
+                    locate_expr(object);
+                }
+                locate_expr(value);
+            }
+            TirStmtUnresolved::If {
+                cond,
+                then_body,
+                else_body,
+            } => {
+                locate_expr(cond);
+                locate_body(then_body);
+                locate_body(else_body);
+            }
+            TirStmtUnresolved::While { cond, body } => {
+                locate_expr(cond);
+                locate_body(body);
+            }
+            TirStmtUnresolved::Try {
+                body,
+                handlers,
+                orelse,
+                finalbody,
+            } => {
+                locate_body(body);
+                for handler in handlers {
+                    locate_body(&mut handler.body);
+                }
+                locate_body(orelse);
+                locate_body(finalbody);
+            }
+            TirStmtUnresolved::Return(None) | TirStmtUnresolved::Raise { exc: None } => {}
+        }
+    }
 }
@@ -31,10 +31,22 @@ pub enum TirType {
     /// Void type (for functions with no return value)
     Void,
 
+    /// The type of `None` as a value: always a null pointer
+    NoneType,
+
     /// Class instance type - resolved to ClassId
     /// This includes both user-defined classes and built-in types (str, list, bytes, bytearray).
     /// For list types, the ClassId corresponds to a TirClass with is_builtin=true.
     Class(ClassId),
+
+    /// Fixed-arity tuple type: tuple[int, str]
+    /// Tuples are heap objects; each element occupies an i64 slot like container elements.
+    Tuple(Vec<TirType>),
+
+    /// Optional type: the inner type or None
+    /// A pointer that is null for None; class and tuple values are their own
+    /// pointer, while int, float and bool values are boxed in an i64 slot.
+    Optional(Box<TirType>),
 }
 
 impl Hash for TirType {
@@ -45,7 +57,23 @@ impl Hash for TirType {
             TirType::Float => {}
             TirType::Bool => {}
             TirType::Void => {}
+            TirType::NoneType => {}
             TirType::Class(id) => id.hash(state),
+            TirType::Tuple(elems) => elems.hash(state),
+            TirType::Optional(inner) => inner.hash(state),
+        }
+    }
+}
+
+impl TirType {
+    /// Suffix of the runtime list functions taking and returning items of
+    /// this type: `_float` and `_bool` have typed C signatures, and every
+    /// other item is passed as an i64 slot by the plain `list_*` functions
+    pub fn list_item_suffix(&self) -> &'static str {
+        match self {
+            TirType::Float => "_float",
+            TirType::Bool => "_bool",
+            _ => "",
         }
     }
 }
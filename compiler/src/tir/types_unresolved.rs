@@ -28,11 +28,21 @@ pub enum TirTypeUnresolved {
     /// Void type (for functions with no return value)
     Void,
 
+    /// The type of `None` as a value (`x = None`), a null pointer. Unlike
+    /// Void, which is the absence of a value, it can be stored and passed.
+    NoneType,
+
     /// Class instance type - resolved to ClassId
     /// This includes both user-defined classes and built-in types (str, list, bytes, bytearray).
     /// For generic containers, the ClassId corresponds to a class whose type_params may contain TypeVars.
     Class(ClassId),
 
+    /// Fixed-arity tuple type: tuple[int, str]
+    Tuple(Vec<TirTypeUnresolved>),
+
+    /// Optional type: the inner type or None
+    Optional(Box<TirTypeUnresolved>),
+
     /// Type variable - unresolved type during type inference
     /// The u32 is a unique type variable ID
     /// This must be resolved via constraint solving before conversion to TirType
@@ -73,13 +83,49 @@ impl TirTypeUnresolved {
             (TirTypeUnresolved::Int, TirTypeUnresolved::Float) => true,
             (TirTypeUnresolved::Float, TirTypeUnresolved::Int) => true,
             (TirTypeUnresolved::Bool, TirTypeUnresolved::Bool) => true,
+            (TirTypeUnresolved::NoneType, TirTypeUnresolved::NoneType) => true,
             (TirTypeUnresolved::Class(a), TirTypeUnresolved::Class(b)) => a == b,
+            // Tuple elements live in i64 slots, so int/float promotion does not apply inside them
+            (TirTypeUnresolved::Tuple(a), TirTypeUnresolved::Tuple(b)) => {
+                a.len() == b.len()
+                    && a.iter().zip(b).all(|(x, y)| {
+                        x == y
+                            || matches!(x, TirTypeUnresolved::TypeVar(_))
+                            || matches!(y, TirTypeUnresolved::TypeVar(_))
+                            || (matches!(x, TirTypeUnresolved::Tuple(_)) && x.is_compatible_with(y))
+                    })
+            }
+            // Optional values are boxed in i64 slots, so int/float promotion
+            // does not apply inside them either
+            (TirTypeUnresolved::Optional(a), TirTypeUnresolved::Optional(b)) => {
+                a.is_compatible_with(b) && !a.is_numeric_promotion_of(b)
+            }
+            (
+                TirTypeUnresolved::Void | TirTypeUnresolved::NoneType,
+                TirTypeUnresolved::Optional(_),
+            ) => true,
+            (value, TirTypeUnresolved::Optional(inner)) => {
+                value.is_compatible_with(inner) && !value.is_numeric_promotion_of(inner)
+            }
             // Type variables are compatible with anything (will be unified during constraint solving)
             (TirTypeUnresolved::TypeVar(_), _) | (_, TirTypeUnresolved::TypeVar(_)) => true,
             _ => false,
         }
     }
 
+    /// Whether assigning this type to `target` would promote int to float (or back)
+    fn is_numeric_promotion_of(&self, target: &TirTypeUnresolved) -> bool {
+        self.is_numeric() && target.is_numeric() && self != target
+    }
+
+    /// The type of the value when this is an Optional type
+    pub fn optional_inner(&self) -> Option<&TirTypeUnresolved> {
+        match self {
+            TirTypeUnresolved::Optional(inner) => Some(inner),
+            _ => None,
+        }
+    }
+
     /// Apply type variable substitutions recursively
     /// This resolves TypeVars to their inferred concrete types after constraint solving
     ///
@@ -94,6 +140,12 @@ impl TirTypeUnresolved {
                     .map(|t| t.substitute(substitutions))
                     .unwrap_or_else(|| self.clone())
             }
+            TirTypeUnresolved::Tuple(elems) => TirTypeUnresolved::Tuple(
+                elems.iter().map(|t| t.substitute(substitutions)).collect(),
+            ),
+            TirTypeUnresolved::Optional(inner) => {
+                TirTypeUnresolved::Optional(Box::new(inner.substitute(substitutions)))
+            }
             _ => self.clone(),
         }
     }
@@ -107,7 +159,14 @@ impl TirTypeUnresolved {
             super::types::TirType::Float => TirTypeUnresolved::Float,
             super::types::TirType::Bool => TirTypeUnresolved::Bool,
             super::types::TirType::Void => TirTypeUnresolved::Void,
+            super::types::TirType::NoneType => TirTypeUnresolved::NoneType,
             super::types::TirType::Class(id) => TirTypeUnresolved::Class(*id),
+            super::types::TirType::Tuple(elems) => {
+                TirTypeUnresolved::Tuple(elems.iter().map(Self::from_tir_type).collect())
+            }
+            super::types::TirType::Optional(inner) => {
+                TirTypeUnresolved::Optional(Box::new(Self::from_tir_type(inner)))
+            }
         }
     }
 
@@ -120,7 +179,14 @@ impl TirTypeUnresolved {
             TirTypeUnresolved::Float => super::types::TirType::Float,
             TirTypeUnresolved::Bool => super::types::TirType::Bool,
             TirTypeUnresolved::Void => super::types::TirType::Void,
+            TirTypeUnresolved::NoneType => super::types::TirType::NoneType,
             TirTypeUnresolved::Class(id) => super::types::TirType::Class(*id),
+            TirTypeUnresolved::Tuple(elems) => {
+                super::types::TirType::Tuple(elems.iter().map(|t| t.to_tir_type()).collect())
+            }
+            TirTypeUnresolved::Optional(inner) => {
+                super::types::TirType::Optional(Box::new(inner.to_tir_type()))
+            }
             TirTypeUnresolved::TypeVar(id) => {
                 panic!(
                     "Cannot convert TypeVar({}) to TirType - use resolve::resolve_type() instead",
@@ -137,6 +203,8 @@ impl TirTypeUnresolved {
     pub fn contains_type_var(&self, var_id: u32) -> bool {
         match self {
             TirTypeUnresolved::TypeVar(id) => *id == var_id,
+            TirTypeUnresolved::Tuple(elems) => elems.iter().any(|t| t.contains_type_var(var_id)),
+            TirTypeUnresolved::Optional(inner) => inner.contains_type_var(var_id),
             _ => false,
         }
     }
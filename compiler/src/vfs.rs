@@ -0,0 +1,222 @@
+//! Where modules are read from
+//!
+//! Imports are resolved and modules read through a [`FileSystem`], so the
+//! compiler can work on sources that are not on disk: `Compiler::compile_source`
+//! compiles a string, and `Compiler::with_file_system` hands it a
+//! [`MemoryFileSystem`] of modules to resolve imports against, for tests,
+//! REPLs and build tools that keep their sources in memory. Only the output
+//! (and the object file it is linked from) is written to disk.
+
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+/// The files modules are read from
+pub trait FileSystem: Send + Sync {
+    /// The contents of the file at `path`
+    fn read_to_string(&self, path: &Path) -> io::Result<String>;
+
+    /// Whether there is a file at `path`
+    fn is_file(&self, path: &Path) -> bool;
+
+    /// Whether there is a directory at `path`
+    fn is_dir(&self, path: &Path) -> bool;
+
+    /// The absolute form of `path`, without `.` and `..` components
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf>;
+}
+
+/// The files on disk
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DiskFileSystem;
+
+impl FileSystem for DiskFileSystem {
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        fs::read_to_string(path)
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        path.is_file()
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        path.is_dir()
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        path.canonicalize()
+    }
+}
+
+/// Files held in memory, by absolute path; a directory is any path some
+/// file is under
+#[derive(Debug, Default, Clone)]
+pub struct MemoryFileSystem {
+    files: HashMap<PathBuf, String>,
+}
+
+impl MemoryFileSystem {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the file at `path`, replacing the one there; a relative path is
+    /// taken from the current directory, as it is on disk
+    pub fn insert(&mut self, path: impl AsRef<Path>, source: impl Into<String>) {
+        self.files.insert(absolute(path.as_ref()), source.into());
+    }
+
+    /// Add the file at `path`, as `insert` does
+    pub fn with_file(mut self, path: impl AsRef<Path>, source: impl Into<String>) -> Self {
+        self.insert(path, source);
+        self
+    }
+}
+
+impl FileSystem for MemoryFileSystem {
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        self.files.get(&absolute(path)).cloned().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("No file {} in memory", path.display()),
+            )
+        })
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        self.files.contains_key(&absolute(path))
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        let dir = absolute(path);
+        self.files
+            .keys()
+            .any(|file| file != &dir && file.starts_with(&dir))
+    }
+
+    /// Paths in memory have no symlinks to resolve: a path is canonical
+    /// once it is absolute
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        if self.is_file(path) || self.is_dir(path) {
+            Ok(absolute(path))
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("No file or directory {} in memory", path.display()),
+            ))
+        }
+    }
+}
+
+/// Files in memory over those of another file system, which they shadow
+pub struct OverlayFileSystem {
+    upper: MemoryFileSystem,
+    lower: Arc<dyn FileSystem>,
+}
+
+impl OverlayFileSystem {
+    pub fn new(upper: MemoryFileSystem, lower: Arc<dyn FileSystem>) -> Self {
+        OverlayFileSystem { upper, lower }
+    }
+}
+
+impl FileSystem for OverlayFileSystem {
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        if self.upper.is_file(path) {
+            self.upper.read_to_string(path)
+        } else {
+            self.lower.read_to_string(path)
+        }
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        self.upper.is_file(path) || self.lower.is_file(path)
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        self.upper.is_dir(path) || self.lower.is_dir(path)
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        self.lower
+            .canonicalize(path)
+            .or_else(|_| self.upper.canonicalize(path))
+    }
+}
+
+/// `path` taken from the current directory (or the root, if there is
+/// none), without `.` and `..` components
+fn absolute(path: &Path) -> PathBuf {
+    let mut absolute = if path.is_absolute() {
+        PathBuf::new()
+    } else {
+        env::current_dir().unwrap_or_else(|_| PathBuf::from("/"))
+    };
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                absolute.pop();
+            }
+            other => absolute.push(other),
+        }
+    }
+    absolute
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_memory_file_system() {
+        let files = MemoryFileSystem::new()
+            .with_file("/src/app/main.py", "import util\n")
+            .with_file("/src/app/./lib/../util.py", "x: int = 1\n");
+
+        assert_eq!(
+            files.read_to_string(Path::new("/src/app/util.py")).unwrap(),
+            "x: int = 1\n"
+        );
+        assert!(files.is_file(Path::new("/src/app/main.py")));
+        assert!(files.is_dir(Path::new("/src/app")));
+        assert!(files.is_dir(Path::new("/src")));
+        assert!(!files.is_dir(Path::new("/src/app/main.py")));
+        assert!(!files.is_file(Path::new("/src/app/lib/util.py")));
+        assert!(files.read_to_string(Path::new("/src/other.py")).is_err());
+        assert_eq!(
+            files
+                .canonicalize(Path::new("/src/app/../app/main.py"))
+                .unwrap(),
+            PathBuf::from("/src/app/main.py")
+        );
+        assert!(files.canonicalize(Path::new("/src/other.py")).is_err());
+    }
+
+    #[test]
+    fn test_overlay_file_system_shadows_disk() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path().canonicalize().unwrap();
+        fs::write(dir.join("main.py"), "on disk\n").unwrap();
+        fs::write(dir.join("util.py"), "util on disk\n").unwrap();
+
+        let files = OverlayFileSystem::new(
+            MemoryFileSystem::new().with_file(dir.join("main.py"), "in memory\n"),
+            Arc::new(DiskFileSystem),
+        );
+        assert_eq!(
+            files.read_to_string(&dir.join("main.py")).unwrap(),
+            "in memory\n"
+        );
+        assert_eq!(
+            files.read_to_string(&dir.join("util.py")).unwrap(),
+            "util on disk\n"
+        );
+        assert!(files.is_dir(&dir));
+        assert!(!files.is_file(&dir.join("other.py")));
+    }
+}
@@ -7,6 +7,33 @@ const MUSL_VERSION: &str = "1.2.5";
 const ICU_VERSION: &str = "74.2";
 const ICU_VERSION_UNDERSCORE: &str = "74_2"; // for download URL
 
+/// Tool path from LLVM_SYS_211_PREFIX, or from PATH when it is unset
+fn llvm_tool(name: &str) -> String {
+    match env::var("LLVM_SYS_211_PREFIX") {
+        Ok(llvm_prefix) => format!("{}/bin/{}", llvm_prefix, name),
+        Err(_) => name.to_string(),
+    }
+}
+
+/// CC, AR, RANLIB and CFLAGS for building musl for a cross target (riscv64,
+/// aarch64) with clang
+fn musl_cross_env(target: &str) -> [(&'static str, String); 4] {
+    let cflags = if target == "riscv64" {
+        "-mabi=lp64d -O2"
+    } else {
+        "-O2"
+    };
+    [
+        (
+            "CC",
+            format!("{} --target={}-linux-musl", llvm_tool("clang"), target),
+        ),
+        ("AR", llvm_tool("llvm-ar")),
+        ("RANLIB", llvm_tool("llvm-ranlib")),
+        ("CFLAGS", cflags.to_string()),
+    ]
+}
+
 /// Get the musl prefix for a target, either from environment variable or by building it
 ///
 /// This checks for MUSL_{TARGET}_PREFIX environment variable first (set by Docker/CI).
@@ -126,30 +153,9 @@ fn download_and_build_musl(out_path: &Path, target: &str) -> PathBuf {
             .arg("--enable-static");
 
         // Set CC and AR environment variables for configure script
-        if target == "riscv64" {
-            // For RISC-V cross-compilation, use clang with target flag
-            let clang_cmd = if let Ok(llvm_prefix) = env::var("LLVM_SYS_211_PREFIX") {
-                format!("{}/bin/clang --target=riscv64-linux-musl", llvm_prefix)
-            } else {
-                "clang --target=riscv64-linux-musl".to_string()
-            };
-
-            let ar_cmd = if let Ok(llvm_prefix) = env::var("LLVM_SYS_211_PREFIX") {
-                format!("{}/bin/llvm-ar", llvm_prefix)
-            } else {
-                "llvm-ar".to_string()
-            };
-
-            let ranlib_cmd = if let Ok(llvm_prefix) = env::var("LLVM_SYS_211_PREFIX") {
-                format!("{}/bin/llvm-ranlib", llvm_prefix)
-            } else {
-                "llvm-ranlib".to_string()
-            };
-
-            cmd.env("CC", clang_cmd);
-            cmd.env("AR", ar_cmd);
-            cmd.env("RANLIB", ranlib_cmd);
-            cmd.env("CFLAGS", "-mabi=lp64d -O2");
+        if target != "x86_64" {
+            // For cross-compilation, use clang with target flag
+            cmd.envs(musl_cross_env(target));
         } else {
             // For x86_64, use system gcc
             cmd.env("CC", "gcc");
@@ -176,29 +182,8 @@ fn download_and_build_musl(out_path: &Path, target: &str) -> PathBuf {
     let mut cmd = Command::new("make");
     cmd.current_dir(&build_dir).arg(format!("-j{}", num_cpus));
 
-    if target == "riscv64" {
-        let clang_cmd = if let Ok(llvm_prefix) = env::var("LLVM_SYS_211_PREFIX") {
-            format!("{}/bin/clang --target=riscv64-linux-musl", llvm_prefix)
-        } else {
-            "clang --target=riscv64-linux-musl".to_string()
-        };
-
-        let ar_cmd = if let Ok(llvm_prefix) = env::var("LLVM_SYS_211_PREFIX") {
-            format!("{}/bin/llvm-ar", llvm_prefix)
-        } else {
-            "llvm-ar".to_string()
-        };
-
-        let ranlib_cmd = if let Ok(llvm_prefix) = env::var("LLVM_SYS_211_PREFIX") {
-            format!("{}/bin/llvm-ranlib", llvm_prefix)
-        } else {
-            "llvm-ranlib".to_string()
-        };
-
-        cmd.env("CC", clang_cmd);
-        cmd.env("AR", ar_cmd);
-        cmd.env("RANLIB", ranlib_cmd);
-        cmd.env("CFLAGS", "-mabi=lp64d -O2");
+    if target != "x86_64" {
+        cmd.envs(musl_cross_env(target));
     } else {
         cmd.env("AR", "ar");
         cmd.env("RANLIB", "ranlib");
@@ -291,17 +276,21 @@ fn get_icu_prefix(out_path: &Path, target: &str, musl_prefix: &Path) -> PathBuf
         }
     }
 
-    // For RISC-V, cross-compiling ICU requires a C++ standard library that works with musl.
-    // Since GCC's libstdc++ depends on glibc-specific features and LLVM's libc++ isn't
-    // available for RISC-V musl, we skip ICU for RISC-V by default.
-    // Users can provide pre-built ICU via ICU_RISCV64_PREFIX environment variable.
-    if target == "riscv64" {
-        eprintln!("NOTE: ICU for RISC-V requires pre-built libraries due to C++ stdlib issues.");
-        eprintln!("RISC-V binaries will use ASCII-only string operations.");
+    // For RISC-V and aarch64, cross-compiling ICU requires a C++ standard library that
+    // works with musl. Since GCC's libstdc++ depends on glibc-specific features and LLVM's
+    // libc++ isn't available for those musl targets, we skip ICU for them by default.
+    // Users can provide pre-built ICU via the ICU_{TARGET}_PREFIX environment variable.
+    if target != "x86_64" {
+        eprintln!(
+            "NOTE: ICU for {} requires pre-built libraries due to C++ stdlib issues.",
+            target
+        );
+        eprintln!("{} binaries will use ASCII-only string operations.", target);
         eprintln!(
-            "To enable full Unicode, set ICU_RISCV64_PREFIX to a pre-built ICU installation."
+            "To enable full Unicode, set {} to a pre-built ICU installation.",
+            env_var
         );
-        return out_path.join("icu-riscv64-placeholder");
+        return out_path.join(format!("icu-{}-placeholder", target));
     }
 
     // Fall back to downloading and building ICU from source (x86_64 only)
@@ -642,13 +631,25 @@ fn build_runtime_for_target(
 ) -> PathBuf {
     let c_files = [
         "src/list.c",
+        "src/dict.c",
+        "src/set.c",
+        "src/tuple.c",
         "src/builtins.c",
         "src/class.c",
+        "src/refcount.c",
+        "src/gc.c",
+        "src/alloc.c",
         "src/bytearray.c",
         "src/str.c",
         "src/bytes.c",
         "src/exception.c",
         "src/range.c",
+        "src/file.c",
+        "src/sys.c",
+        "src/os.c",
+        "src/time.c",
+        "src/random.c",
+        "src/embed.c",
         "src/glibc_compat.c", // Compatibility shims for glibc functions (needed for system ICU)
     ];
 
@@ -667,6 +668,7 @@ fn build_runtime_for_target(
     // Get clang target flag for cross-compilation
     let clang_target = match target {
         "riscv64" => Some("--target=riscv64-linux-musl"),
+        "aarch64" => Some("--target=aarch64-linux-musl"),
         "x86_64" => None,
         _ => panic!("Unsupported target: {}", target),
     };
@@ -679,12 +681,8 @@ fn build_runtime_for_target(
             target
         ));
 
-        let clang_path = if target == "riscv64" {
-            if let Ok(llvm_prefix) = env::var("LLVM_SYS_211_PREFIX") {
-                format!("{}/bin/clang", llvm_prefix)
-            } else {
-                "clang".to_string()
-            }
+        let clang_path = if target != "x86_64" {
+            llvm_tool("clang")
         } else {
             "clang".to_string()
         };
@@ -727,12 +725,8 @@ fn build_runtime_for_target(
     // Link all bitcode files into a single runtime-{target}.o file
     let output_file = out_path.join(format!("runtime-{}.o", target));
 
-    let llvm_link_path = if target == "riscv64" {
-        if let Ok(llvm_prefix) = env::var("LLVM_SYS_211_PREFIX") {
-            format!("{}/bin/llvm-link", llvm_prefix)
-        } else {
-            "llvm-link".to_string()
-        }
+    let llvm_link_path = if target != "x86_64" {
+        llvm_tool("llvm-link")
     } else {
         "llvm-link".to_string()
     };
@@ -758,10 +752,10 @@ fn main() {
     let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
     let manifest_path = PathBuf::from(&manifest_dir);
 
-    // Build/locate musl and ICU for both architectures
-    let targets = ["x86_64", "riscv64"];
+    // Build/locate musl and ICU for every architecture
+    let targets = ["x86_64", "riscv64", "aarch64"];
 
-    eprintln!("Setting up musl C runtime and ICU for x86_64 and riscv64...");
+    eprintln!("Setting up musl C runtime and ICU for x86_64, riscv64 and aarch64...");
 
     for target in &targets {
         // Get musl prefix (either from env var or by building)
@@ -779,7 +773,7 @@ fn main() {
         // Determine ICU lib and include directories based on prefix
         let (icu_lib_dir, icu_include_dir) = if icu_prefix.to_string_lossy().contains("placeholder")
         {
-            // Placeholder path for targets without ICU (e.g., RISC-V)
+            // Placeholder path for targets without ICU (e.g., RISC-V, aarch64)
             (icu_prefix.clone(), icu_prefix.clone())
         } else if icu_prefix.to_str() == Some("/usr") {
             // System ICU has arch-specific lib directory
@@ -818,8 +812,17 @@ fn main() {
 
     // Tell cargo to rerun if C files change
     println!("cargo:rerun-if-changed=src/list.c");
+    println!("cargo:rerun-if-changed=src/dict.c");
+    println!("cargo:rerun-if-changed=src/set.c");
+    println!("cargo:rerun-if-changed=src/tuple.c");
     println!("cargo:rerun-if-changed=src/builtins.c");
     println!("cargo:rerun-if-changed=src/class.c");
+    println!("cargo:rerun-if-changed=src/refcount.c");
+    println!("cargo:rerun-if-changed=src/refcount.h");
+    println!("cargo:rerun-if-changed=src/gc.c");
+    println!("cargo:rerun-if-changed=src/gc.h");
+    println!("cargo:rerun-if-changed=src/alloc.c");
+    println!("cargo:rerun-if-changed=src/alloc.h");
     println!("cargo:rerun-if-changed=src/bytearray.c");
     println!("cargo:rerun-if-changed=src/str.c");
     println!("cargo:rerun-if-changed=src/bytes.c");
@@ -832,12 +835,21 @@ fn main() {
     println!("cargo:rerun-if-changed=src/exception.c");
     println!("cargo:rerun-if-changed=src/exception.h");
     println!("cargo:rerun-if-changed=src/range.c");
+    println!("cargo:rerun-if-changed=src/file.c");
+    println!("cargo:rerun-if-changed=src/sys.c");
+    println!("cargo:rerun-if-changed=src/os.c");
+    println!("cargo:rerun-if-changed=src/time.c");
+    println!("cargo:rerun-if-changed=src/random.c");
+    println!("cargo:rerun-if-changed=src/embed.c");
+    println!("cargo:rerun-if-changed=src/embed.h");
 
     // Rerun if musl environment variables change
     println!("cargo:rerun-if-env-changed=MUSL_X86_64_PREFIX");
     println!("cargo:rerun-if-env-changed=MUSL_RISCV64_PREFIX");
+    println!("cargo:rerun-if-env-changed=MUSL_AARCH64_PREFIX");
 
     // Rerun if ICU environment variables change
     println!("cargo:rerun-if-env-changed=ICU_X86_64_PREFIX");
     println!("cargo:rerun-if-env-changed=ICU_RISCV64_PREFIX");
+    println!("cargo:rerun-if-env-changed=ICU_AARCH64_PREFIX");
 }
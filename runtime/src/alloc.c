@@ -0,0 +1,116 @@
+#include "alloc.h"
+#include "io.h"
+#include <stdlib.h>
+#include <string.h>
+
+// Chunks are at least this big; larger requests get a chunk of their own
+#define ARENA_CHUNK_SIZE ((size_t)1 << 20)
+
+// Every block starts with a header holding its size, so that rt_realloc
+// knows how much to copy. It also keeps blocks 16-byte aligned, like malloc.
+#define ARENA_HEADER_SIZE ((size_t)16)
+
+typedef struct ArenaChunk {
+    struct ArenaChunk* prev;
+    size_t used;
+    size_t capacity;
+    // Blocks follow, starting ARENA_HEADER_SIZE bytes in
+} ArenaChunk;
+
+static ArenaChunk* arena_current = NULL;
+static void* arena_last = NULL;  // Most recent block, which can grow in place
+
+static size_t arena_round(size_t size) {
+    return (size + 15) & ~(size_t)15;
+}
+
+static char* arena_chunk_data(ArenaChunk* chunk) {
+    return (char*)chunk + arena_round(sizeof(ArenaChunk));
+}
+
+static size_t* arena_block_size(void* ptr) {
+    return (size_t*)((char*)ptr - ARENA_HEADER_SIZE);
+}
+
+static void arena_release(void) {
+    while (arena_current != NULL) {
+        ArenaChunk* prev = arena_current->prev;
+        free(arena_current);
+        arena_current = prev;
+    }
+}
+
+static void* arena_alloc(size_t size) {
+    size_t needed = ARENA_HEADER_SIZE + arena_round(size > 0 ? size : 1);
+    if (arena_current == NULL || arena_current->capacity - arena_current->used < needed) {
+        size_t capacity = needed > ARENA_CHUNK_SIZE ? needed : ARENA_CHUNK_SIZE;
+        ArenaChunk* chunk = (ArenaChunk*)malloc(arena_round(sizeof(ArenaChunk)) + capacity);
+        if (chunk == NULL) {
+            rt_panic("Failed to allocate memory for the arena");
+        }
+        if (arena_current == NULL) {
+            atexit(arena_release);
+        }
+        chunk->prev = arena_current;
+        chunk->used = 0;
+        chunk->capacity = capacity;
+        arena_current = chunk;
+    }
+    char* block = arena_chunk_data(arena_current) + arena_current->used + ARENA_HEADER_SIZE;
+    arena_current->used += needed;
+    *arena_block_size(block) = size;
+    arena_last = block;
+    return block;
+}
+
+// ============================================================================
+// Allocation functions
+// ============================================================================
+
+void* rt_malloc(size_t size) {
+    if (__pyc_arena_alloc) {
+        return arena_alloc(size);
+    }
+    return malloc(size);
+}
+
+void* rt_calloc(size_t count, size_t size) {
+    if (__pyc_arena_alloc) {
+        // Chunks are not zeroed
+        void* block = arena_alloc(count * size);
+        memset(block, 0, count * size);
+        return block;
+    }
+    return calloc(count, size);
+}
+
+void* rt_realloc(void* ptr, size_t size) {
+    if (!__pyc_arena_alloc) {
+        return realloc(ptr, size);
+    }
+    if (ptr == NULL) {
+        return arena_alloc(size);
+    }
+    size_t old_size = *arena_block_size(ptr);
+    // The latest block grows in place while its chunk has room, which keeps
+    // a list being appended to from copying itself each time
+    if (ptr == arena_last) {
+        size_t old_needed = arena_round(old_size > 0 ? old_size : 1);
+        size_t new_needed = arena_round(size > 0 ? size : 1);
+        if (new_needed <= old_needed ||
+            arena_current->capacity - arena_current->used >= new_needed - old_needed) {
+            arena_current->used = arena_current->used - old_needed + new_needed;
+            *arena_block_size(ptr) = size;
+            return ptr;
+        }
+    }
+    void* block = arena_alloc(size);
+    memcpy(block, ptr, old_size < size ? old_size : size);
+    return block;
+}
+
+void rt_free(void* ptr) {
+    if (!__pyc_arena_alloc) {
+        free(ptr);
+    }
+}
@@ -0,0 +1,22 @@
+#ifndef ALLOC_H
+#define ALLOC_H
+
+#include "types.h"
+
+// ============================================================================
+// Runtime memory allocation
+// Every allocation the runtime makes goes through these. By default they are
+// malloc, calloc, realloc and free; with pycc --alloc=arena memory comes from
+// a growable bump arena instead, rt_free does nothing and the arena is only
+// released at exit. Allocation is much cheaper, but nothing is reused.
+// ============================================================================
+
+void* rt_malloc(size_t size);
+void* rt_calloc(size_t count, size_t size);
+void* rt_realloc(void* ptr, size_t size);
+void rt_free(void* ptr);
+
+// Emitted by the compiler: 1 for pycc --alloc=arena
+extern const int8_t __pyc_arena_alloc;
+
+#endif // ALLOC_H
@@ -1,5 +1,8 @@
 #include "runtime.h"
+#include <ctype.h>
+#include <math.h>
 #include <stdio.h>
+#include <stdlib.h>
 #include <string.h>
 
 // ============================================================================
@@ -22,10 +25,289 @@ void __pyc___builtin___bool___print__(int8_t value) {
 
 void __pyc___builtin___float___print__(double value) {
     char buffer[32];
-    snprintf(buffer, sizeof(buffer), "%g", value);
+    float_to_str_impl(value, buffer);
     write_stdout(buffer, strlen(buffer));
 }
 
+// ============================================================================
+// Built-in string conversions (f-string replacement fields)
+// ============================================================================
+
+String* __pyc___builtin___int___str__(int64_t value) {
+    char buffer[21];
+    snprintf(buffer, sizeof(buffer), "%ld", value);
+    return STR_METHOD(from_literal)(buffer, (int64_t)strlen(buffer));
+}
+
+String* __pyc___builtin___bool___str__(int8_t value) {
+    if (value) {
+        return STR_METHOD(from_literal)("True", 4);
+    }
+    return STR_METHOD(from_literal)("False", 5);
+}
+
+String* __pyc___builtin___float___str__(double value) {
+    char buffer[32];
+    float_to_str_impl(value, buffer);
+    return STR_METHOD(from_literal)(buffer, (int64_t)strlen(buffer));
+}
+
+// ============================================================================
+// Built-in numeric conversions (int(), float())
+// ============================================================================
+
+static void raise_conversion_error(const char* type_name, const char* parent_types,
+                                   String* message) {
+    __pyc_raise(__pyc_exception_new(
+        STR_METHOD(from_literal)(type_name, (int64_t)strlen(type_name)),
+        message,
+        STR_METHOD(from_literal)(parent_types, (int64_t)strlen(parent_types))));
+}
+
+// ValueError whose message ends with the repr of the rejected string
+static void raise_invalid_literal(const char* prefix, String* s) {
+    String* message = STR_METHOD(__add__)(
+        STR_METHOD(from_literal)(prefix, (int64_t)strlen(prefix)), STR_METHOD(__repr__)(s));
+    raise_conversion_error("ValueError", "Exception", message);
+}
+
+// Find the non-whitespace part of a string, as int() and float() ignore
+// surrounding whitespace
+static void strip_whitespace(String* s, const char** start, const char** end) {
+    *start = string_data(s);
+    *end = *start + string_len(s);
+    while (*start < *end && isspace((unsigned char)**start)) {
+        (*start)++;
+    }
+    while (*end > *start && isspace((unsigned char)(*end)[-1])) {
+        (*end)--;
+    }
+}
+
+static int digit_value(char c) {
+    if (c >= '0' && c <= '9') {
+        return c - '0';
+    }
+    c = (char)tolower((unsigned char)c);
+    if (c >= 'a' && c <= 'z') {
+        return c - 'a' + 10;
+    }
+    return -1;
+}
+
+// int(s, base): an optional sign, an optional 0x/0o/0b prefix matching the
+// base (base 0 takes the base from the prefix) and digits, optionally
+// separated by single underscores
+int64_t __pyc___builtin___int_from_str(String* s, int64_t base) {
+    if (base != 0 && (base < 2 || base > 36)) {
+        const char* message = "int() base must be >= 2 and <= 36, or 0";
+        raise_conversion_error("ValueError", "Exception",
+                               STR_METHOD(from_literal)(message, (int64_t)strlen(message)));
+        return 0;
+    }
+
+    const char* p;
+    const char* end;
+    strip_whitespace(s, &p, &end);
+
+    int negative = 0;
+    if (p < end && (*p == '+' || *p == '-')) {
+        negative = *p == '-';
+        p++;
+    }
+
+    int64_t digit_base = base;
+    if (end - p >= 2 && p[0] == '0') {
+        char marker = (char)tolower((unsigned char)p[1]);
+        int64_t prefix_base = marker == 'x' ? 16 : marker == 'o' ? 8 : marker == 'b' ? 2 : 0;
+        if (prefix_base != 0 && (base == 0 || base == prefix_base)) {
+            digit_base = prefix_base;
+            p += 2;
+            if (p < end && *p == '_') {
+                p++;
+            }
+        }
+    }
+    if (digit_base == 0) {
+        digit_base = 10;
+    }
+
+    // Magnitude limit: INT64_MIN has no positive counterpart
+    uint64_t limit = negative ? (uint64_t)INT64_MAX + 1 : (uint64_t)INT64_MAX;
+    uint64_t value = 0;
+    int64_t digits = 0;
+    int after_underscore = 0;
+    for (; p < end; p++) {
+        if (*p == '_') {
+            if (digits == 0 || after_underscore) {
+                break;
+            }
+            after_underscore = 1;
+            continue;
+        }
+        int digit = digit_value(*p);
+        if (digit < 0 || digit >= digit_base) {
+            break;
+        }
+        if (value > (limit - (uint64_t)digit) / (uint64_t)digit_base) {
+            const char* message = "int too large to convert to a 64-bit integer";
+            raise_conversion_error("OverflowError", "ArithmeticError,Exception",
+                                   STR_METHOD(from_literal)(message, (int64_t)strlen(message)));
+            return 0;
+        }
+        value = value * (uint64_t)digit_base + (uint64_t)digit;
+        digits++;
+        after_underscore = 0;
+    }
+    if (p != end || digits == 0 || after_underscore) {
+        char prefix[64];
+        snprintf(prefix, sizeof(prefix), "invalid literal for int() with base %ld: ", base);
+        raise_invalid_literal(prefix, s);
+        return 0;
+    }
+    return negative ? (int64_t)(0 - value) : (int64_t)value;
+}
+
+// int(x) for a float truncates toward zero
+int64_t __pyc___builtin___int_from_float(double value) {
+    if (isnan(value)) {
+        const char* message = "cannot convert float NaN to integer";
+        raise_conversion_error("ValueError", "Exception",
+                               STR_METHOD(from_literal)(message, (int64_t)strlen(message)));
+        return 0;
+    }
+    if (isinf(value)) {
+        const char* message = "cannot convert float infinity to integer";
+        raise_conversion_error("OverflowError", "ArithmeticError,Exception",
+                               STR_METHOD(from_literal)(message, (int64_t)strlen(message)));
+        return 0;
+    }
+    // 2^63 is exact as a double; anything at or beyond it does not fit
+    if (value >= 9223372036854775808.0 || value < -9223372036854775808.0) {
+        const char* message = "int too large to convert to a 64-bit integer";
+        raise_conversion_error("OverflowError", "ArithmeticError,Exception",
+                               STR_METHOD(from_literal)(message, (int64_t)strlen(message)));
+        return 0;
+    }
+    return (int64_t)value;
+}
+
+int64_t __pyc___builtin___int_from_bool(int8_t value) {
+    return value ? 1 : 0;
+}
+
+// float(s): decimal notation, "inf"/"infinity"/"nan" in any case, with an
+// optional sign and single underscores between digits
+double __pyc___builtin___float_from_str(String* s) {
+    const char* p;
+    const char* end;
+    strip_whitespace(s, &p, &end);
+
+    // Copy without underscores so strtod sees a plain literal
+    char* buffer = (char*)rt_malloc((size_t)(end - p) + 1);
+    if (buffer == NULL) {
+        rt_panic("Failed to allocate memory for float conversion");
+    }
+    int64_t len = 0;
+    int valid = p < end;
+    for (const char* c = p; c < end; c++) {
+        if (*c == '_') {
+            // Underscores must sit between two digits
+            if (c == p || c + 1 == end || !isdigit((unsigned char)c[-1]) ||
+                !isdigit((unsigned char)c[1])) {
+                valid = 0;
+            }
+            continue;
+        }
+        // strtod also reads hexadecimal floats, which float() rejects
+        if (*c == 'x' || *c == 'X') {
+            valid = 0;
+        }
+        buffer[len++] = *c;
+    }
+    buffer[len] = '\0';
+
+    char* parsed_end = NULL;
+    double value = strtod(buffer, &parsed_end);
+    if (parsed_end != buffer + len) {
+        valid = 0;
+    }
+    rt_free(buffer);
+
+    if (!valid) {
+        raise_invalid_literal("could not convert string to float: ", s);
+        return 0.0;
+    }
+    return value;
+}
+
+double __pyc___builtin___float_from_int(int64_t value) {
+    return (double)value;
+}
+
+// round(x, ndigits) for a float: the nearest multiple of 10**-ndigits, ties
+// to even, judged on the exact binary value as CPython does (so
+// round(2.675, 2) is 2.67)
+double __pyc___builtin___float_round(double value, int64_t ndigits) {
+    if (!isfinite(value) || value == 0.0 || ndigits > 323) {
+        return value;
+    }
+    if (ndigits < -308) {
+        return copysign(0.0, value);
+    }
+    if (ndigits >= 0) {
+        // printf rounds the exact value correctly; at most 309 integer digits,
+        // the point and 323 decimals
+        char buffer[700];
+        snprintf(buffer, sizeof(buffer), "%.*f", (int)ndigits, value);
+        return strtod(buffer, NULL);
+    }
+    double scale = pow(10.0, (double)-ndigits);
+    double rounded = rint(value / scale) * scale;
+    if (isinf(rounded)) {
+        const char* message = "rounded value too large to represent";
+        raise_conversion_error("OverflowError", "ArithmeticError,Exception",
+                               STR_METHOD(from_literal)(message, (int64_t)strlen(message)));
+        return value;
+    }
+    return rounded;
+}
+
+// round(x, ndigits) for an int: unchanged unless ndigits is negative, when it
+// is rounded to a multiple of 10**-ndigits, ties to even
+int64_t __pyc___builtin___int_round(int64_t value, int64_t ndigits) {
+    if (ndigits >= 0) {
+        return value;
+    }
+    // Every int64 is below half of 10**20
+    if (ndigits < -19) {
+        return 0;
+    }
+    uint64_t scale = 1;
+    for (int64_t i = 0; i < -ndigits; i++) {
+        scale *= 10;
+    }
+    uint64_t magnitude = value < 0 ? 0 - (uint64_t)value : (uint64_t)value;
+    uint64_t quotient = magnitude / scale;
+    uint64_t remainder = magnitude % scale;
+    if (remainder > scale / 2 || (remainder == scale / 2 && (quotient & 1))) {
+        quotient++;
+    }
+    uint64_t limit = value < 0 ? (uint64_t)INT64_MAX + 1 : (uint64_t)INT64_MAX;
+    if (quotient > limit / scale) {
+        const char* message = "int too large to convert to a 64-bit integer";
+        raise_conversion_error("OverflowError", "ArithmeticError,Exception",
+                               STR_METHOD(from_literal)(message, (int64_t)strlen(message)));
+        return 0;
+    }
+    uint64_t rounded = quotient * scale;
+    return value < 0 ? (int64_t)(0 - rounded) : (int64_t)rounded;
+}
+
+int8_t __pyc___builtin___float_is_integer(double value) {
+    return isfinite(value) && value == floor(value);
+}
+
 // ============================================================================
 // I/O helper functions for compiler use
 // ============================================================================
@@ -40,6 +322,12 @@ void write_string_impl(String* str) {
     }
 }
 
+void write_stderr_string_impl(String* str) {
+    if (str != NULL) {
+        write_stderr(str->data, (size_t)str->len);
+    }
+}
+
 void write_char_impl(char c) {
     write_char(c);
 }
@@ -56,3 +344,101 @@ char* int64_to_str_impl(int64_t value, char* buffer) {
     snprintf(buffer, 21, "%ld", value);
     return buffer;
 }
+
+// repr() of a float as CPython writes it: the fewest digits that read back
+// as the same value, in positional notation ("3.0", "0.0001") for decimal
+// exponents from -4 to 15 and scientific notation ("1e+16", "1.5e-05")
+// otherwise. The buffer needs room for 32 bytes.
+char* float_to_str_impl(double value, char* buffer) {
+    if (isnan(value)) {
+        strcpy(buffer, "nan");
+        return buffer;
+    }
+    if (isinf(value)) {
+        strcpy(buffer, value < 0 ? "-inf" : "inf");
+        return buffer;
+    }
+
+    // "-d.ddde+XX" with the shortest precision that round-trips
+    char scientific[32];
+    for (int precision = 0; precision < 17; precision++) {
+        snprintf(scientific, sizeof(scientific), "%.*e", precision, value);
+        if (strtod(scientific, NULL) == value) {
+            break;
+        }
+    }
+
+    char digits[20];
+    int ndigits = 0;
+    const char* p = scientific;
+    int negative = *p == '-';
+    if (negative) {
+        p++;
+    }
+    for (; *p != 'e'; p++) {
+        if (*p != '.') {
+            digits[ndigits++] = *p;
+        }
+    }
+    int exponent = atoi(p + 1);
+    while (ndigits > 1 && digits[ndigits - 1] == '0') {
+        ndigits--;
+    }
+
+    char* out = buffer;
+    if (negative) {
+        *out++ = '-';
+    }
+    if (exponent < -4 || exponent >= 16) {
+        *out++ = digits[0];
+        if (ndigits > 1) {
+            *out++ = '.';
+            memcpy(out, digits + 1, (size_t)(ndigits - 1));
+            out += ndigits - 1;
+        }
+        snprintf(out, 13, "e%c%02d", exponent < 0 ? '-' : '+', abs(exponent));
+        return buffer;
+    }
+    if (exponent < 0) {
+        *out++ = '0';
+        *out++ = '.';
+        for (int i = -1; i > exponent; i--) {
+            *out++ = '0';
+        }
+        memcpy(out, digits, (size_t)ndigits);
+        out += ndigits;
+    } else {
+        for (int i = 0; i <= exponent; i++) {
+            *out++ = i < ndigits ? digits[i] : '0';
+        }
+        *out++ = '.';
+        if (ndigits > exponent + 1) {
+            memcpy(out, digits + exponent + 1, (size_t)(ndigits - exponent - 1));
+            out += ndigits - exponent - 1;
+        } else {
+            *out++ = '0';
+        }
+    }
+    *out = '\0';
+    return buffer;
+}
+
+// hex(), oct() and bin(): the value in base 16, 8 or 2 with its 0x/0o/0b
+// prefix, the sign before the prefix as in CPython ("-0x1f")
+String* int64_to_str_radix_impl(int64_t value, int64_t base) {
+    char buffer[68];  // sign, prefix and 64 binary digits
+    char* end = buffer + sizeof(buffer);
+    char* p = end;
+    // Negate as unsigned so INT64_MIN does not overflow
+    uint64_t magnitude = value < 0 ? -(uint64_t)value : (uint64_t)value;
+    do {
+        *--p = "0123456789abcdef"[magnitude % (uint64_t)base];
+        magnitude /= (uint64_t)base;
+    } while (magnitude != 0);
+    *--p = base == 16 ? 'x' : base == 8 ? 'o' : 'b';
+    *--p = '0';
+    if (value < 0) {
+        *--p = '-';
+    }
+    return STR_METHOD(from_literal)(p, (int64_t)(end - p));
+}
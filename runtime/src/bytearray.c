@@ -3,14 +3,14 @@
 #include <string.h>
 
 ByteArray* BYTEARRAY_METHOD(__init__)(void) {
-    ByteArray* ba = (ByteArray*)malloc(sizeof(ByteArray));
+    ByteArray* ba = (ByteArray*)rt_malloc(sizeof(ByteArray));
     if (ba == NULL) {
         rt_panic("Failed to allocate memory for bytearray");
     }
 
     ba->cap = 8;
     ba->len = 0;
-    ba->data = (uint8_t*)malloc(ba->cap);
+    ba->data = (uint8_t*)rt_malloc(ba->cap);
 
     if (ba->data == NULL) {
         rt_panic("Failed to allocate memory for bytearray data");
@@ -29,7 +29,7 @@ void BYTEARRAY_METHOD(append)(ByteArray* ba, int64_t value) {
 
     if (ba->len == ba->cap) {
         ba->cap *= 2;
-        uint8_t* new_data = (uint8_t*)realloc(ba->data, ba->cap);
+        uint8_t* new_data = (uint8_t*)rt_realloc(ba->data, ba->cap);
         if (new_data == NULL) {
             rt_panic("Failed to reallocate memory for bytearray");
         }
@@ -43,8 +43,12 @@ int64_t BYTEARRAY_METHOD(__getitem__)(ByteArray* ba, int64_t index) {
     if (ba == NULL) {
         rt_panic("Cannot get from NULL bytearray");
     }
-    if (index < 0 || index >= ba->len) {
-        rt_panic_index("Index out of bounds", index, ba->len);
+    if (index < 0) {
+        index += ba->len;
+    }
+    if (__pyc_runtime_checks && (index < 0 || index >= ba->len)) {
+        __pyc_raise_index_error("bytearray index out of range");
+        return 0;
     }
     return (int64_t)ba->data[index];
 }
@@ -53,8 +57,12 @@ void BYTEARRAY_METHOD(__setitem__)(ByteArray* ba, int64_t index, int64_t value)
     if (ba == NULL) {
         rt_panic("Cannot set in NULL bytearray");
     }
-    if (index < 0 || index >= ba->len) {
-        rt_panic_index("Index out of bounds", index, ba->len);
+    if (index < 0) {
+        index += ba->len;
+    }
+    if (__pyc_runtime_checks && (index < 0 || index >= ba->len)) {
+        __pyc_raise_index_error("bytearray index out of range");
+        return;
     }
     if (value < 0 || value > 255) {
         rt_panic("bytearray value out of range (0-255)");
@@ -69,10 +77,103 @@ int64_t BYTEARRAY_METHOD(__len__)(ByteArray* ba) {
     return ba->len;
 }
 
+ByteArray* BYTEARRAY_METHOD(__slice__)(ByteArray* ba, int64_t start, int64_t stop, int64_t step) {
+    if (ba == NULL) {
+        rt_panic("Cannot slice NULL bytearray");
+    }
+    int64_t count = rt_slice_indices(ba->len, &start, &stop, step);
+    ByteArray* result = BYTEARRAY_METHOD(__init__)();
+    for (int64_t i = 0; i < count; i++) {
+        BYTEARRAY_METHOD(append)(result, ba->data[start + i * step]);
+    }
+    return result;
+}
+
+// Make room for at least cap bytes
+static void bytearray_reserve(ByteArray* ba, int64_t cap) {
+    if (cap <= ba->cap) {
+        return;
+    }
+    int64_t new_cap = ba->cap * 2 > cap ? ba->cap * 2 : cap;
+    uint8_t* new_data = (uint8_t*)rt_realloc(ba->data, new_cap);
+    if (new_data == NULL) {
+        rt_panic("Failed to reallocate memory for bytearray");
+    }
+    ba->data = new_data;
+    ba->cap = new_cap;
+}
+
+void BYTEARRAY_METHOD(__setslice__)(ByteArray* ba, int64_t start, int64_t stop, int64_t step,
+                                    Bytes* value) {
+    if (ba == NULL) {
+        rt_panic("Cannot assign to a slice of NULL bytearray");
+    }
+    int64_t value_len = value ? value->len : 0;
+    int64_t count = rt_slice_indices(ba->len, &start, &stop, step);
+
+    if (step != 1) {
+        if (value_len != count) {
+            char message[128];
+            int len = snprintf(message, sizeof(message),
+                               "attempt to assign bytes of size %ld to extended slice of size %ld",
+                               value_len, count);
+            __pyc_raise(__pyc_exception_new(
+                STR_METHOD(from_literal)("ValueError", 10),
+                STR_METHOD(from_literal)(message, len),
+                STR_METHOD(from_literal)("Exception", 9)));
+            return;
+        }
+        for (int64_t i = 0; i < count; i++) {
+            ba->data[start + i * step] = value->data[i];
+        }
+        return;
+    }
+
+    // Replace the count bytes at start with the value, moving the tail
+    int64_t new_len = ba->len - count + value_len;
+    bytearray_reserve(ba, new_len);
+    memmove(ba->data + start + value_len, ba->data + start + count,
+            ba->len - start - count);
+    if (value_len > 0) {
+        memcpy(ba->data + start, value->data, value_len);
+    }
+    ba->len = new_len;
+}
+
+void BYTEARRAY_METHOD(extend)(ByteArray* ba, Bytes* value) {
+    if (ba == NULL) {
+        rt_panic("Cannot extend NULL bytearray");
+    }
+    if (value == NULL || value->len == 0) {
+        return;
+    }
+    bytearray_reserve(ba, ba->len + value->len);
+    memcpy(ba->data + ba->len, value->data, value->len);
+    ba->len += value->len;
+}
+
+ByteArray* BYTEARRAY_METHOD(__iadd__)(ByteArray* ba, Bytes* value) {
+    BYTEARRAY_METHOD(extend)(ba, value);
+    return ba;
+}
+
+ByteArray* __pyc___builtin___bytearray_from_bytes(Bytes* value) {
+    ByteArray* ba = BYTEARRAY_METHOD(__init__)();
+    BYTEARRAY_METHOD(extend)(ba, value);
+    return ba;
+}
+
+Bytes* __pyc___builtin___bytes_from_bytearray(ByteArray* ba) {
+    if (ba == NULL) {
+        return BYTES_METHOD(__init__)(NULL, 0);
+    }
+    return BYTES_METHOD(__init__)(ba->data, ba->len);
+}
+
 void BYTEARRAY_METHOD(free)(ByteArray* ba) {
     if (ba != NULL) {
-        free(ba->data);
-        free(ba);
+        rt_free(ba->data);
+        rt_free(ba);
     }
 }
 
@@ -94,7 +195,7 @@ String* BYTEARRAY_METHOD(__repr__)(ByteArray* ba) {
         }
     }
 
-    String* result = (String*)malloc(sizeof(String) + out_len + 1);
+    String* result = str_alloc(out_len);
     if (result == NULL) return NULL;
 
     result->len = out_len;
@@ -1,4 +1,4 @@
-#include "bytes.h"
+#include "runtime.h"
 #include <stdlib.h>
 #include <string.h>
 
@@ -9,7 +9,7 @@
 Bytes* BYTES_METHOD(__init__)(const uint8_t* data, int64_t len) {
     if (len < 0) return NULL;
 
-    Bytes* b = (Bytes*)malloc(sizeof(Bytes) + len);
+    Bytes* b = (Bytes*)rt_malloc(sizeof(Bytes) + len);
     if (b == NULL) return NULL;
 
     b->len = len;
@@ -20,7 +20,7 @@ Bytes* BYTES_METHOD(__init__)(const uint8_t* data, int64_t len) {
 }
 
 void BYTES_METHOD(free)(Bytes* b) {
-    free(b);
+    rt_free(b);
 }
 
 int64_t BYTES_METHOD(__len__)(Bytes* b) {
@@ -28,12 +28,203 @@ int64_t BYTES_METHOD(__len__)(Bytes* b) {
 }
 
 int64_t BYTES_METHOD(__getitem__)(Bytes* b, int64_t index) {
-    if (b == NULL || index < 0 || index >= b->len) {
+    if (b == NULL) {
+        return -1;
+    }
+    if (index < 0) {
+        index += b->len;
+    }
+    if (__pyc_runtime_checks && (index < 0 || index >= b->len)) {
+        __pyc_raise_index_error("index out of range");
         return -1;
     }
     return (int64_t)b->data[index];
 }
 
+Bytes* BYTES_METHOD(__slice__)(Bytes* b, int64_t start, int64_t stop, int64_t step) {
+    int64_t count = rt_slice_indices(b ? b->len : 0, &start, &stop, step);
+    Bytes* result = BYTES_METHOD(__init__)(NULL, count);
+    if (result == NULL) return NULL;
+    for (int64_t i = 0; i < count; i++) {
+        result->data[i] = b->data[start + i * step];
+    }
+    return result;
+}
+
+Bytes* BYTES_METHOD(__add__)(Bytes* a, Bytes* b) {
+    int64_t a_len = a ? a->len : 0;
+    int64_t b_len = b ? b->len : 0;
+    Bytes* result = BYTES_METHOD(__init__)(NULL, a_len + b_len);
+    if (result == NULL) return NULL;
+    if (a_len > 0) memcpy(result->data, a->data, a_len);
+    if (b_len > 0) memcpy(result->data + a_len, b->data, b_len);
+    return result;
+}
+
+// Index of the first occurrence of sub in b at or after start, or -1
+static int64_t bytes_find_from(Bytes* b, Bytes* sub, int64_t start) {
+    int64_t len = b ? b->len : 0;
+    int64_t sub_len = sub ? sub->len : 0;
+    for (int64_t i = start; i + sub_len <= len; i++) {
+        if (sub_len == 0 || memcmp(b->data + i, sub->data, sub_len) == 0) {
+            return i;
+        }
+    }
+    return -1;
+}
+
+int64_t BYTES_METHOD(find)(Bytes* b, Bytes* sub) {
+    return bytes_find_from(b, sub, 0);
+}
+
+int64_t BYTES_METHOD(count)(Bytes* b, Bytes* sub) {
+    int64_t len = b ? b->len : 0;
+    int64_t sub_len = sub ? sub->len : 0;
+    if (sub_len == 0) {
+        return len + 1;  // Between every byte and at both ends
+    }
+    int64_t count = 0;
+    int64_t i = bytes_find_from(b, sub, 0);
+    while (i >= 0) {
+        count++;
+        i = bytes_find_from(b, sub, i + sub_len);
+    }
+    return count;
+}
+
+int8_t BYTES_METHOD(__contains__)(Bytes* b, Bytes* sub) {
+    return bytes_find_from(b, sub, 0) >= 0;
+}
+
+int8_t BYTES_METHOD(startswith)(Bytes* b, Bytes* prefix) {
+    int64_t len = b ? b->len : 0;
+    int64_t prefix_len = prefix ? prefix->len : 0;
+    if (prefix_len > len) return 0;
+    return prefix_len == 0 || memcmp(b->data, prefix->data, prefix_len) == 0;
+}
+
+int8_t BYTES_METHOD(endswith)(Bytes* b, Bytes* suffix) {
+    int64_t len = b ? b->len : 0;
+    int64_t suffix_len = suffix ? suffix->len : 0;
+    if (suffix_len > len) return 0;
+    return suffix_len == 0 || memcmp(b->data + (len - suffix_len), suffix->data, suffix_len) == 0;
+}
+
+// The whitespace bytes.split() and bytes.strip() remove: space, \t, \n, \v, \f, \r
+static int is_ascii_space(uint8_t c) {
+    return c == ' ' || (c >= '\t' && c <= '\r');
+}
+
+Bytes* BYTES_METHOD(strip)(Bytes* b) {
+    int64_t start = 0;
+    int64_t end = b ? b->len : 0;
+    while (start < end && is_ascii_space(b->data[start])) start++;
+    while (end > start && is_ascii_space(b->data[end - 1])) end--;
+    return BYTES_METHOD(__init__)(b ? b->data + start : NULL, end - start);
+}
+
+Bytes* BYTES_METHOD(replace)(Bytes* b, Bytes* old, Bytes* new_bytes) {
+    int64_t len = b ? b->len : 0;
+    int64_t old_len = old ? old->len : 0;
+    int64_t new_len = new_bytes ? new_bytes->len : 0;
+    int64_t matches = BYTES_METHOD(count)(b, old);
+    Bytes* result = BYTES_METHOD(__init__)(NULL, len + matches * (new_len - old_len));
+    if (result == NULL) return NULL;
+
+    int64_t pos = 0;
+    int64_t i = 0;
+    for (int64_t m = 0; m < matches; m++) {
+        // An empty old matches before every byte and at the end
+        int64_t at = old_len == 0 ? i : bytes_find_from(b, old, i);
+        memcpy(result->data + pos, b->data + i, at - i);
+        pos += at - i;
+        memcpy(result->data + pos, new_bytes->data, new_len);
+        pos += new_len;
+        i = at + old_len;
+        if (old_len == 0 && i < len) {
+            result->data[pos++] = b->data[i++];
+        }
+    }
+    memcpy(result->data + pos, b->data + i, len - i);
+    return result;
+}
+
+String* BYTES_METHOD(hex)(Bytes* b) {
+    int64_t len = b ? b->len : 0;
+    char* buffer = (char*)rt_malloc(len * 2 + 1);
+    if (buffer == NULL) return NULL;
+    for (int64_t i = 0; i < len; i++) {
+        buffer[2 * i] = "0123456789abcdef"[b->data[i] >> 4];
+        buffer[2 * i + 1] = "0123456789abcdef"[b->data[i] & 0xf];
+    }
+    String* result = STR_METHOD(from_literal)(buffer, len * 2);
+    rt_free(buffer);
+    return result;
+}
+
+List* BYTES_METHOD(split)(Bytes* b, Bytes* sep) {
+    if (sep == NULL || sep->len == 0) {
+        __pyc_raise(__pyc_exception_new(
+            STR_METHOD(from_literal)("ValueError", 10),
+            STR_METHOD(from_literal)("empty separator", 15),
+            STR_METHOD(from_literal)("Exception", 9)));
+        return LIST_METHOD(__init__)(DICT_KIND_BYTES);
+    }
+    List* parts = LIST_METHOD(__init__)(DICT_KIND_BYTES);
+    int64_t len = b ? b->len : 0;
+    int64_t start = 0;
+    int64_t at = bytes_find_from(b, sep, 0);
+    while (at >= 0) {
+        LIST_METHOD(append)(parts, (int64_t)BYTES_METHOD(__init__)(b->data + start, at - start));
+        start = at + sep->len;
+        at = bytes_find_from(b, sep, start);
+    }
+    LIST_METHOD(append)(parts, (int64_t)BYTES_METHOD(__init__)(b ? b->data + start : NULL, len - start));
+    return parts;
+}
+
+List* BYTES_METHOD(__split_whitespace__)(Bytes* b) {
+    List* parts = LIST_METHOD(__init__)(DICT_KIND_BYTES);
+    int64_t len = b ? b->len : 0;
+    int64_t i = 0;
+    while (i < len) {
+        while (i < len && is_ascii_space(b->data[i])) i++;
+        int64_t start = i;
+        while (i < len && !is_ascii_space(b->data[i])) i++;
+        if (i > start) {
+            LIST_METHOD(append)(parts, (int64_t)BYTES_METHOD(__init__)(b->data + start, i - start));
+        }
+    }
+    return parts;
+}
+
+Bytes* BYTES_METHOD(join)(Bytes* sep, List* parts) {
+    if (parts == NULL) {
+        rt_panic("Cannot join NULL list");
+    }
+    int64_t sep_len = sep ? sep->len : 0;
+    int64_t total = 0;
+    for (int64_t i = 0; i < parts->len; i++) {
+        Bytes* part = (Bytes*)__pyc_list_item(parts, i);
+        total += (part ? part->len : 0) + (i > 0 ? sep_len : 0);
+    }
+    Bytes* result = BYTES_METHOD(__init__)(NULL, total);
+    if (result == NULL) return NULL;
+    int64_t pos = 0;
+    for (int64_t i = 0; i < parts->len; i++) {
+        if (i > 0 && sep_len > 0) {
+            memcpy(result->data + pos, sep->data, sep_len);
+            pos += sep_len;
+        }
+        Bytes* part = (Bytes*)__pyc_list_item(parts, i);
+        if (part != NULL && part->len > 0) {
+            memcpy(result->data + pos, part->data, part->len);
+            pos += part->len;
+        }
+    }
+    return result;
+}
+
 String* BYTES_METHOD(__repr__)(Bytes* b) {
     if (b == NULL) {
         return STR_METHOD(from_literal)("b''", 3);
@@ -52,7 +243,7 @@ String* BYTES_METHOD(__repr__)(Bytes* b) {
         }
     }
 
-    String* result = (String*)malloc(sizeof(String) + out_len + 1);
+    String* result = str_alloc(out_len);
     if (result == NULL) return NULL;
 
     result->len = out_len;
@@ -84,3 +275,90 @@ String* BYTES_METHOD(__repr__)(Bytes* b) {
 String* BYTES_METHOD(__str__)(Bytes* b) {
     return BYTES_METHOD(__repr__)(b);
 }
+
+// ============================================================================
+// str <-> bytes (UTF-8)
+// ============================================================================
+
+// str.encode(): the UTF-8 bytes of the string
+Bytes* STR_METHOD(encode)(String* s) {
+    if (s == NULL) {
+        return BYTES_METHOD(__init__)(NULL, 0);
+    }
+    return BYTES_METHOD(__init__)((const uint8_t*)s->data, s->len);
+}
+
+// Raise UnicodeDecodeError for the bytes [start, end) of b, worded as CPython does
+static void raise_decode_error(Bytes* b, int64_t start, int64_t end, const char* reason) {
+    char buffer[128];
+    int len;
+    if (end - start == 1) {
+        len = snprintf(buffer, sizeof(buffer),
+                       "'utf-8' codec can't decode byte 0x%02x in position %ld: %s",
+                       b->data[start], start, reason);
+    } else {
+        len = snprintf(buffer, sizeof(buffer),
+                       "'utf-8' codec can't decode bytes in position %ld-%ld: %s", start,
+                       end - 1, reason);
+    }
+    __pyc_raise(__pyc_exception_new(
+        STR_METHOD(from_literal)("UnicodeDecodeError", 18),
+        STR_METHOD(from_literal)(buffer, len),
+        STR_METHOD(from_literal)("UnicodeError,ValueError,Exception", 33)));
+}
+
+// Number of bytes in the sequence started by lead, with the allowed range of
+// the first continuation byte (narrower than 0x80-0xBF where that excludes
+// overlong forms, surrogates and code points above U+10FFFF); 0 if lead
+// cannot start a sequence
+static int utf8_sequence_length(uint8_t lead, uint8_t* low, uint8_t* high) {
+    *low = 0x80;
+    *high = 0xBF;
+    if (lead < 0x80) return 1;
+    if (lead < 0xC2) return 0;
+    if (lead < 0xE0) return 2;
+    if (lead < 0xF0) {
+        if (lead == 0xE0) *low = 0xA0;
+        if (lead == 0xED) *high = 0x9F;
+        return 3;
+    }
+    if (lead < 0xF5) {
+        if (lead == 0xF0) *low = 0x90;
+        if (lead == 0xF4) *high = 0x8F;
+        return 4;
+    }
+    return 0;
+}
+
+// bytes.decode(): the string the UTF-8 bytes encode; raises UnicodeDecodeError
+// on the first malformed sequence
+String* BYTES_METHOD(decode)(Bytes* b) {
+    if (b == NULL) {
+        return STR_METHOD(from_literal)("", 0);
+    }
+
+    int64_t i = 0;
+    while (i < b->len) {
+        uint8_t low, high;
+        int length = utf8_sequence_length(b->data[i], &low, &high);
+        if (length == 0) {
+            raise_decode_error(b, i, i + 1, "invalid start byte");
+            return NULL;
+        }
+        for (int k = 1; k < length; k++) {
+            if (i + k == b->len) {
+                raise_decode_error(b, i, i + k, "unexpected end of data");
+                return NULL;
+            }
+            uint8_t c = b->data[i + k];
+            if (c < low || c > high) {
+                raise_decode_error(b, i, i + k, "invalid continuation byte");
+                return NULL;
+            }
+            low = 0x80;
+            high = 0xBF;
+        }
+        i += length;
+    }
+    return STR_METHOD(from_literal)((const char*)b->data, b->len);
+}
@@ -19,9 +19,31 @@ Bytes* BYTES_METHOD(__init__)(const uint8_t* data, int64_t len);
 void BYTES_METHOD(free)(Bytes* b);
 int64_t BYTES_METHOD(__len__)(Bytes* b);
 int64_t BYTES_METHOD(__getitem__)(Bytes* b, int64_t index);
+Bytes* BYTES_METHOD(__slice__)(Bytes* b, int64_t start, int64_t stop, int64_t step);
+
+// bytes + bytes
+Bytes* BYTES_METHOD(__add__)(Bytes* a, Bytes* b);
+
+// Searching: find() returns -1 when absent; count() counts non-overlapping matches
+int64_t BYTES_METHOD(find)(Bytes* b, Bytes* sub);
+int64_t BYTES_METHOD(count)(Bytes* b, Bytes* sub);
+int8_t BYTES_METHOD(__contains__)(Bytes* b, Bytes* sub);
+int8_t BYTES_METHOD(startswith)(Bytes* b, Bytes* prefix);
+int8_t BYTES_METHOD(endswith)(Bytes* b, Bytes* suffix);
+
+// New bytes: ASCII whitespace stripped, or every old replaced with new
+Bytes* BYTES_METHOD(strip)(Bytes* b);
+Bytes* BYTES_METHOD(replace)(Bytes* b, Bytes* old, Bytes* new_bytes);
+
+// bytes.hex(): two lowercase hex digits per byte
+String* BYTES_METHOD(hex)(Bytes* b);
 
 // bytes.__str__() and bytes.__repr__() - both return b'...' format
 String* BYTES_METHOD(__str__)(Bytes* b);
 String* BYTES_METHOD(__repr__)(Bytes* b);
 
+// str.encode() and bytes.decode() (UTF-8; decode raises UnicodeDecodeError)
+Bytes* STR_METHOD(encode)(String* s);
+String* BYTES_METHOD(decode)(Bytes* b);
+
 #endif // BYTES_H
@@ -4,7 +4,7 @@
 
 // Allocate memory for a new class instance
 void* class_new(int64_t size) {
-    void* instance = malloc((size_t)size);
+    void* instance = rt_malloc((size_t)size);
     if (instance == NULL) {
         rt_panic("Failed to allocate memory for class instance");
     }
@@ -0,0 +1,442 @@
+#include "runtime.h"
+#include <stdlib.h>
+#include <stdio.h>
+#include <string.h>
+
+// ============================================================================
+// Hashing and key comparison
+// ============================================================================
+
+static uint64_t hash_bytes(const uint8_t* data, int64_t len) {
+    // FNV-1a
+    uint64_t hash = 1469598103934665603ULL;
+    for (int64_t i = 0; i < len; i++) {
+        hash ^= data[i];
+        hash *= 1099511628211ULL;
+    }
+    return hash;
+}
+
+static uint64_t hash_int(uint64_t x) {
+    // splitmix64 finalizer
+    x ^= x >> 30;
+    x *= 0xbf58476d1ce4e5b9ULL;
+    x ^= x >> 27;
+    x *= 0x94d049bb133111ebULL;
+    x ^= x >> 31;
+    return x;
+}
+
+static uint64_t dict_hash(int8_t kind, int64_t key) {
+    switch (kind) {
+        case DICT_KIND_STR: {
+            String* s = (String*)key;
+            return s == NULL ? 0 : hash_bytes((const uint8_t*)s->data, s->len);
+        }
+        case DICT_KIND_BYTES: {
+            Bytes* b = (Bytes*)key;
+            return b == NULL ? 0 : hash_bytes(b->data, b->len);
+        }
+        case DICT_KIND_FLOAT: {
+            double d;
+            memcpy(&d, &key, sizeof(d));
+            if (d == 0.0) {
+                return 0;  // 0.0 and -0.0 are equal keys
+            }
+            return hash_int((uint64_t)key);
+        }
+        case DICT_KIND_TUPLE: {
+            Tuple* t = (Tuple*)key;
+            if (t == NULL) {
+                return 0;
+            }
+            uint64_t hash = (uint64_t)t->len;
+            for (int64_t i = 0; i < t->len; i++) {
+                hash = hash * 1000003ULL ^ dict_hash(t->kinds[i], t->data[i]);
+            }
+            return hash;
+        }
+        default:
+            return hash_int((uint64_t)key);
+    }
+}
+
+static int dict_keys_equal(int8_t kind, int64_t a, int64_t b) {
+    if (a == b) {
+        return 1;
+    }
+    switch (kind) {
+        case DICT_KIND_STR: {
+            String* x = (String*)a;
+            String* y = (String*)b;
+            return x != NULL && y != NULL && x->len == y->len &&
+                   memcmp(x->data, y->data, (size_t)x->len) == 0;
+        }
+        case DICT_KIND_BYTES: {
+            Bytes* x = (Bytes*)a;
+            Bytes* y = (Bytes*)b;
+            return x != NULL && y != NULL && x->len == y->len &&
+                   memcmp(x->data, y->data, (size_t)x->len) == 0;
+        }
+        case DICT_KIND_FLOAT: {
+            double x, y;
+            memcpy(&x, &a, sizeof(x));
+            memcpy(&y, &b, sizeof(y));
+            return x == y;
+        }
+        case DICT_KIND_TUPLE: {
+            Tuple* x = (Tuple*)a;
+            Tuple* y = (Tuple*)b;
+            if (x == NULL || y == NULL || x->len != y->len) {
+                return 0;
+            }
+            for (int64_t i = 0; i < x->len; i++) {
+                if (!dict_keys_equal(x->kinds[i], x->data[i], y->data[i])) {
+                    return 0;
+                }
+            }
+            return 1;
+        }
+        default:
+            return 0;
+    }
+}
+
+// ============================================================================
+// Slot table
+// ============================================================================
+
+#define SLOT_EMPTY   -1
+#define SLOT_DELETED -2  // Probing goes on past it
+
+// Find the slot for key: either the slot holding it or the empty slot where it belongs
+static int64_t dict_find_slot(Dict* dict, int64_t key) {
+    uint64_t mask = (uint64_t)dict->slot_count - 1;
+    uint64_t i = dict_hash(dict->key_kind, key) & mask;
+    while (1) {
+        int64_t entry = dict->slots[i];
+        if (entry == SLOT_EMPTY ||
+            (entry >= 0 && dict_keys_equal(dict->key_kind, dict->keys[entry], key))) {
+            return (int64_t)i;
+        }
+        i = (i + 1) & mask;
+    }
+}
+
+static void dict_alloc_slots(Dict* dict, int64_t slot_count) {
+    dict->slots = (int64_t*)rt_malloc(sizeof(int64_t) * slot_count);
+    if (dict->slots == NULL) {
+        rt_panic("Failed to allocate memory for dict");
+    }
+    memset(dict->slots, 0xff, sizeof(int64_t) * slot_count);  // all -1
+    dict->slot_count = slot_count;
+}
+
+// Drop the deleted entries, keeping the others in order, and index them in
+// a new slot table
+static void dict_rebuild(Dict* dict) {
+    if (dict->used > dict->len) {
+        // The entries a slot points to are those still in the dict
+        int8_t* live = (int8_t*)rt_malloc((size_t)dict->used);
+        if (live == NULL) {
+            rt_panic("Failed to allocate memory for dict");
+        }
+        memset(live, 0, (size_t)dict->used);
+        for (int64_t i = 0; i < dict->slot_count; i++) {
+            if (dict->slots[i] >= 0) {
+                live[dict->slots[i]] = 1;
+            }
+        }
+        int64_t kept = 0;
+        for (int64_t entry = 0; entry < dict->used; entry++) {
+            if (live[entry]) {
+                dict->keys[kept] = dict->keys[entry];
+                dict->values[kept] = dict->values[entry];
+                kept++;
+            }
+        }
+        rt_free(live);
+        dict->used = kept;
+    }
+
+    // Keep the load factor (deleted slots included) at or below 1/2
+    rt_free(dict->slots);
+    dict_alloc_slots(dict, dict->cap * 2);
+    for (int64_t entry = 0; entry < dict->used; entry++) {
+        dict->slots[dict_find_slot(dict, dict->keys[entry])] = entry;
+    }
+}
+
+// Make room for an entry once keys/values are full: grow them if the dict
+// fills more than half of them, and drop the deleted entries either way
+static void dict_grow(Dict* dict) {
+    if (dict->len * 2 > dict->cap) {
+        dict->cap *= 2;
+        dict->keys = (int64_t*)rt_realloc(dict->keys, sizeof(int64_t) * dict->cap);
+        dict->values = (int64_t*)rt_realloc(dict->values, sizeof(int64_t) * dict->cap);
+        if (dict->keys == NULL || dict->values == NULL) {
+            rt_panic("Failed to reallocate memory for dict");
+        }
+    }
+    dict_rebuild(dict);
+}
+
+void __pyc_dict_compact(Dict* dict) {
+    if (dict->used > dict->len) {
+        dict_rebuild(dict);
+    }
+}
+
+// ============================================================================
+// Dict methods
+// ============================================================================
+
+Dict* DICT_METHOD(__init__)(int64_t key_kind, int64_t value_kind) {
+    Dict* dict = (Dict*)rt_malloc(sizeof(Dict));
+    if (dict == NULL) {
+        rt_panic("Failed to allocate memory for dict");
+    }
+
+    dict->cap = 8;
+    dict->len = 0;
+    dict->used = 0;
+    dict->keys = (int64_t*)rt_malloc(sizeof(int64_t) * dict->cap);
+    dict->values = (int64_t*)rt_malloc(sizeof(int64_t) * dict->cap);
+    if (dict->keys == NULL || dict->values == NULL) {
+        rt_panic("Failed to allocate memory for dict data");
+    }
+    dict_alloc_slots(dict, dict->cap * 2);
+    dict->key_kind = (int8_t)key_kind;
+    dict->value_kind = (int8_t)value_kind;
+
+    return dict;
+}
+
+int64_t DICT_METHOD(__getitem__)(Dict* dict, int64_t key) {
+    if (dict == NULL) {
+        rt_panic("Cannot get from NULL dict");
+    }
+    int64_t entry = dict->slots[dict_find_slot(dict, key)];
+    if (entry < 0) {
+        __pyc_raise(__pyc_exception_new(
+            STR_METHOD(from_literal)("KeyError", 8),
+            __pyc_dict_item_repr(dict->key_kind, key),
+            STR_METHOD(from_literal)("LookupError,Exception", 21)));
+        return 0;
+    }
+    return dict->values[entry];
+}
+
+void DICT_METHOD(__setitem__)(Dict* dict, int64_t key, int64_t value) {
+    if (dict == NULL) {
+        rt_panic("Cannot set in NULL dict");
+    }
+    int64_t slot = dict_find_slot(dict, key);
+    int64_t entry = dict->slots[slot];
+    // The dict holds a reference to each key and value (with --refcount);
+    // an existing key is kept, and its old value dropped
+    __pyc_rc_incref_item(value, dict->value_kind);
+    if (entry >= 0) {
+        int64_t old = dict->values[entry];
+        dict->values[entry] = value;
+        __pyc_rc_decref_item(old, dict->value_kind);
+        return;
+    }
+    __pyc_rc_incref_item(key, dict->key_kind);
+
+    if (dict->used == dict->cap) {
+        dict_grow(dict);
+        slot = dict_find_slot(dict, key);
+    }
+    dict->keys[dict->used] = key;
+    dict->values[dict->used] = value;
+    dict->slots[slot] = dict->used;
+    dict->used++;
+    dict->len++;
+}
+
+void DICT_METHOD(__delitem__)(Dict* dict, int64_t key) {
+    if (dict == NULL) {
+        rt_panic("Cannot delete from NULL dict");
+    }
+    if (!__pyc_dict_remove(dict, key)) {
+        __pyc_raise(__pyc_exception_new(
+            STR_METHOD(from_literal)("KeyError", 8),
+            __pyc_dict_item_repr(dict->key_kind, key),
+            STR_METHOD(from_literal)("LookupError,Exception", 21)));
+    }
+}
+
+int8_t DICT_METHOD(__contains__)(Dict* dict, int64_t key) {
+    if (dict == NULL) {
+        rt_panic("Cannot search in NULL dict");
+    }
+    return dict->slots[dict_find_slot(dict, key)] >= 0;
+}
+
+int64_t DICT_METHOD(__len__)(Dict* dict) {
+    if (dict == NULL) {
+        rt_panic("Cannot get length of NULL dict");
+    }
+    return dict->len;
+}
+
+int8_t __pyc_dict_keys_equal(int8_t kind, int64_t a, int64_t b) {
+    return (int8_t)dict_keys_equal(kind, a, b);
+}
+
+int8_t __pyc_dict_remove(Dict* dict, int64_t key) {
+    int64_t slot = dict_find_slot(dict, key);
+    if (dict->slots[slot] < 0) {
+        return 0;
+    }
+    // The entry is dropped when the table is next rebuilt
+    int64_t entry = dict->slots[slot];
+    dict->slots[slot] = SLOT_DELETED;
+    dict->len--;
+    __pyc_rc_decref_item(dict->keys[entry], dict->key_kind);
+    __pyc_rc_decref_item(dict->values[entry], dict->value_kind);
+    return 1;
+}
+
+static List* dict_copy_to_list(const int64_t* data, int64_t len, int8_t kind) {
+    List* list = LIST_METHOD(__init__)(kind);
+    for (int64_t i = 0; i < len; i++) {
+        LIST_METHOD(append)(list, data[i]);
+    }
+    return list;
+}
+
+List* DICT_METHOD(keys)(Dict* dict) {
+    if (dict == NULL) {
+        rt_panic("Cannot get keys of NULL dict");
+    }
+    __pyc_dict_compact(dict);
+    return dict_copy_to_list(dict->keys, dict->len, dict->key_kind);
+}
+
+List* DICT_METHOD(values)(Dict* dict) {
+    if (dict == NULL) {
+        rt_panic("Cannot get values of NULL dict");
+    }
+    __pyc_dict_compact(dict);
+    return dict_copy_to_list(dict->values, dict->len, dict->value_kind);
+}
+
+// items() pairs each key with its value in a 2-tuple; all the tuples share one kinds table
+List* DICT_METHOD(items)(Dict* dict) {
+    if (dict == NULL) {
+        rt_panic("Cannot get items of NULL dict");
+    }
+    int8_t* kinds = (int8_t*)rt_malloc(2);
+    if (kinds == NULL) {
+        rt_panic("Failed to allocate memory for dict items");
+    }
+    kinds[0] = dict->key_kind;
+    kinds[1] = dict->value_kind;
+    __pyc_dict_compact(dict);
+
+    List* list = LIST_METHOD(__init__)(DICT_KIND_TUPLE);
+    for (int64_t i = 0; i < dict->len; i++) {
+        Tuple* item = TUPLE_METHOD(__init__)(2, kinds);
+        __pyc_tuple_set(item, 0, dict->keys[i]);
+        __pyc_tuple_set(item, 1, dict->values[i]);
+        LIST_METHOD(append)(list, (int64_t)item);
+    }
+    return list;
+}
+
+// Iterating a dict walks a snapshot of its keys
+ListIterator* DICT_METHOD(__iter__)(Dict* dict) {
+    return LIST_METHOD(__iter__)(DICT_METHOD(keys)(dict));
+}
+
+// ============================================================================
+// String conversion
+// ============================================================================
+
+String* __pyc_dict_item_repr(int8_t kind, int64_t value) {
+    char buffer[32];
+    switch (kind) {
+        case DICT_KIND_INT:
+            snprintf(buffer, sizeof(buffer), "%ld", value);
+            break;
+        case DICT_KIND_FLOAT: {
+            double d;
+            memcpy(&d, &value, sizeof(d));
+            float_to_str_impl(d, buffer);
+            break;
+        }
+        case DICT_KIND_BOOL:
+            snprintf(buffer, sizeof(buffer), "%s", value ? "True" : "False");
+            break;
+        case DICT_KIND_STR:
+            return STR_METHOD(__repr__)((String*)value);
+        case DICT_KIND_BYTES:
+            return BYTES_METHOD(__repr__)((Bytes*)value);
+        case DICT_KIND_TUPLE:
+            return TUPLE_METHOD(__repr__)((Tuple*)value);
+        case DICT_KIND_LIST:
+            return LIST_METHOD(__repr__)((List*)value);
+        case DICT_KIND_DICT:
+            return DICT_METHOD(__repr__)((Dict*)value);
+        case DICT_KIND_SET:
+            return SET_METHOD(__repr__)((Dict*)value);
+        case DICT_KIND_FROZENSET:
+            return FROZENSET_METHOD(__repr__)((Dict*)value);
+        default:
+            snprintf(buffer, sizeof(buffer), "<object at %p>", (void*)value);
+            break;
+    }
+    return STR_METHOD(from_literal)(buffer, (int64_t)strlen(buffer));
+}
+
+String* DICT_METHOD(__repr__)(Dict* dict) {
+    if (dict == NULL || dict->len == 0) {
+        return STR_METHOD(from_literal)("{}", 2);
+    }
+    __pyc_dict_compact(dict);
+
+    // Render every key and value first so the result can be sized exactly
+    String** parts = (String**)rt_malloc(sizeof(String*) * dict->len * 2);
+    if (parts == NULL) {
+        rt_panic("Failed to allocate memory for dict repr");
+    }
+    int64_t total = 2 + 4 * dict->len - 2;  // "{" "}" plus ": " and ", " separators
+    for (int64_t i = 0; i < dict->len; i++) {
+        parts[2 * i] = __pyc_dict_item_repr(dict->key_kind, dict->keys[i]);
+        parts[2 * i + 1] = __pyc_dict_item_repr(dict->value_kind, dict->values[i]);
+        total += parts[2 * i]->len + parts[2 * i + 1]->len;
+    }
+
+    char* buffer = (char*)rt_malloc(total);
+    if (buffer == NULL) {
+        rt_panic("Failed to allocate memory for dict repr");
+    }
+    int64_t pos = 0;
+    buffer[pos++] = '{';
+    for (int64_t i = 0; i < dict->len; i++) {
+        if (i > 0) {
+            buffer[pos++] = ',';
+            buffer[pos++] = ' ';
+        }
+        memcpy(buffer + pos, parts[2 * i]->data, (size_t)parts[2 * i]->len);
+        pos += parts[2 * i]->len;
+        buffer[pos++] = ':';
+        buffer[pos++] = ' ';
+        memcpy(buffer + pos, parts[2 * i + 1]->data, (size_t)parts[2 * i + 1]->len);
+        pos += parts[2 * i + 1]->len;
+        STR_METHOD(free)(parts[2 * i]);
+        STR_METHOD(free)(parts[2 * i + 1]);
+    }
+    buffer[pos++] = '}';
+
+    String* result = STR_METHOD(from_literal)(buffer, pos);
+    rt_free(buffer);
+    rt_free(parts);
+    return result;
+}
+
+String* DICT_METHOD(__str__)(Dict* dict) {
+    return DICT_METHOD(__repr__)(dict);
+}
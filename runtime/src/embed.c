@@ -0,0 +1,22 @@
+#include "embed.h"
+#include <string.h>
+
+// ============================================================================
+// Embedded file lookup
+// ============================================================================
+
+const EmbeddedFile* __pyc_embedded_find(const char* path, int64_t path_len) {
+    // Paths are stored without a leading "./", so strip it before comparing
+    while (path_len >= 2 && path[0] == '.' && path[1] == '/') {
+        path += 2;
+        path_len -= 2;
+    }
+
+    for (int64_t i = 0; i < __pyc_embedded_count; i++) {
+        const EmbeddedFile* file = &__pyc_embedded_files[i];
+        if (file->path_len == path_len && memcmp(file->path, path, (size_t)path_len) == 0) {
+            return file;
+        }
+    }
+    return NULL;
+}
@@ -0,0 +1,26 @@
+#ifndef EMBED_H
+#define EMBED_H
+
+#include "types.h"
+
+// ============================================================================
+// Embedded data files (pycc --embed)
+// The compiler emits a read-only table of files baked into the executable;
+// lookups by path read straight from that table instead of the filesystem.
+// ============================================================================
+
+typedef struct {
+    const char* path;        // Normalized path (no leading "./")
+    int64_t path_len;
+    const uint8_t* data;     // File contents (read-only section)
+    int64_t len;
+} EmbeddedFile;
+
+// Emitted by the compiler (always present, possibly empty)
+extern const EmbeddedFile __pyc_embedded_files[];
+extern const int64_t __pyc_embedded_count;
+
+// Find an embedded file by path, or NULL if it was not embedded
+const EmbeddedFile* __pyc_embedded_find(const char* path, int64_t path_len);
+
+#endif // EMBED_H
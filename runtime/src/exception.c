@@ -1,4 +1,5 @@
 #include "exception.h"
+#include "gc.h"
 #include "runtime.h"
 #include <stdlib.h>
 #include <string.h>
@@ -9,8 +10,11 @@
 // ============================================================================
 
 static ExceptionFrame* current_frame = NULL;
+static TracebackFrame* current_traceback = NULL;
 static Exception* current_exception = NULL;
+static Exception* handled_exception = NULL;
 static Exception* stop_iteration_singleton = NULL;
+static int64_t call_depth = 0;
 
 // ============================================================================
 // Stubs for setjmp/longjmp (polling-based, no actual jumps)
@@ -26,12 +30,23 @@ void __pyc_longjmp(JmpBuf* buf, int val) {
     (void)val;
 }
 
+// The compiler emits setjmp-based try blocks only for targets whose
+// __builtin_setjmp LLVM implements
+#if defined(__x86_64__) || defined(__aarch64__)
+#define EXCEPTION_LONGJMP(frame) __builtin_longjmp((frame)->buf.buf, 1)
+#else
+#define EXCEPTION_LONGJMP(frame) rt_panic("setjmp exceptions are not supported on this target")
+#endif
+
 // ============================================================================
 // Exception frame management
 // ============================================================================
 
 void __pyc_push_exception_frame(ExceptionFrame* frame) {
     frame->prev = current_frame;
+    frame->gc_depth = __pyc_gc_depth();
+    frame->traceback = current_traceback;
+    frame->call_depth = call_depth;
     current_frame = frame;
 }
 
@@ -45,6 +60,134 @@ ExceptionFrame* __pyc_get_exception_frame(void) {
     return current_frame;
 }
 
+// ============================================================================
+// Traceback records
+// ============================================================================
+
+void __pyc_traceback_push(TracebackFrame* frame) {
+    frame->prev = current_traceback;
+    current_traceback = frame;
+}
+
+void __pyc_traceback_pop(void) {
+    if (current_traceback) {
+        current_traceback = current_traceback->prev;
+    }
+}
+
+// ============================================================================
+// Recursion depth
+// ============================================================================
+
+int __pyc_recursion_enter(int64_t limit) {
+    // The functions between a polled raise and its try block go on running
+    // until they return; they start no more calls, so that runaway
+    // recursion unwinds instead of recursing again from every frame
+    if (current_exception) {
+        return 1;
+    }
+    if (call_depth >= limit) {
+        const char* message = "maximum recursion depth exceeded";
+        __pyc_raise(__pyc_exception_new(
+            STR_METHOD(from_literal)("RecursionError", 14),
+            STR_METHOD(from_literal)(message, (int64_t)strlen(message)),
+            STR_METHOD(from_literal)("RuntimeError,Exception", 22)));
+        return 1;
+    }
+    call_depth++;
+    return 0;
+}
+
+void __pyc_recursion_leave(void) {
+    if (call_depth > 0) {
+        call_depth--;
+    }
+}
+
+// Print line `line` of `file` without its indentation, as the traceback
+// shows it under the record; nothing if the file cannot be read
+static void print_source_line(const char* file, int64_t line) {
+    FILE* source = fopen(file, "r");
+    if (!source) {
+        return;
+    }
+    int64_t current = 1;
+    int c;
+    while (current < line && (c = fgetc(source)) != EOF) {
+        if (c == '\n') {
+            current++;
+        }
+    }
+    char text[256];
+    if (current == line && fgets(text, sizeof(text), source)) {
+        char* start = text;
+        while (*start == ' ' || *start == '\t') {
+            start++;
+        }
+        size_t len = strlen(start);
+        while (len > 0 && (start[len - 1] == '\n' || start[len - 1] == '\r' ||
+                           start[len - 1] == ' ')) {
+            len--;
+        }
+        if (len > 0) {
+            fputs("    ", stderr);
+            fwrite(start, 1, len, stderr);
+            fputc('\n', stderr);
+        }
+    }
+    fclose(source);
+}
+
+// Print the last line of a traceback: the exception's type and message
+static void print_exception(Exception* exc) {
+    fwrite(exc->type_name->data, 1, exc->type_name->len, stderr);
+    if (exc->message && exc->message->len > 0) {
+        fputs(": ", stderr);
+        fwrite(exc->message->data, 1, exc->message->len, stderr);
+    }
+    fputc('\n', stderr);
+}
+
+// Print the records, outermost first, as Python prints a traceback: a
+// record the same as the ones before it is only counted once three were
+// printed (deep recursion). The program exits next, so the stack is
+// reversed in place.
+static void print_traceback(void) {
+    TracebackFrame* outermost = NULL;
+    while (current_traceback) {
+        TracebackFrame* prev = current_traceback->prev;
+        current_traceback->prev = outermost;
+        outermost = current_traceback;
+        current_traceback = prev;
+    }
+
+    fputs("Traceback (most recent call last):\n", stderr);
+    TracebackFrame* last = NULL;
+    int64_t repeated = 0;
+    for (TracebackFrame* frame = outermost; frame; frame = frame->prev) {
+        if (last && frame->line == last->line && strcmp(frame->function, last->function) == 0 &&
+            strcmp(frame->file, last->file) == 0) {
+            repeated++;
+        } else {
+            if (repeated > 2) {
+                fprintf(stderr, "  [Previous line repeated %lld more times]\n",
+                        (long long)(repeated - 2));
+            }
+            repeated = 0;
+        }
+        last = frame;
+        if (repeated > 2) {
+            continue;
+        }
+        fprintf(stderr, "  File \"%s\", line %lld, in %s\n", frame->file, (long long)frame->line,
+                frame->function);
+        print_source_line(frame->file, frame->line);
+    }
+    if (repeated > 2) {
+        fprintf(stderr, "  [Previous line repeated %lld more times]\n", (long long)(repeated - 2));
+    }
+}
+
 // ============================================================================
 // Exception state management
 // ============================================================================
@@ -65,32 +208,108 @@ int __pyc_has_exception(void) {
     return current_exception != NULL;
 }
 
+Exception* __pyc_get_handled_exception(void) {
+    return handled_exception;
+}
+
+void __pyc_set_handled_exception(Exception* exc) {
+    handled_exception = exc;
+}
+
+// Make the exception being handled the context of `exc`, raised while it
+// is, unless `exc` already has one; a context chain leading back to `exc`
+// is cut there, as Python does, so that chains never loop
+static void set_context(Exception* exc) {
+    if (!handled_exception || handled_exception == exc || exc->context ||
+        exc == stop_iteration_singleton) {
+        return;
+    }
+    for (Exception* link = handled_exception; link; link = link->context) {
+        if (link->context == exc) {
+            link->context = NULL;
+            break;
+        }
+    }
+    exc->context = handled_exception;
+}
+
 // ============================================================================
 // Raise exception
 // ============================================================================
 
+// The exception `exc` is reported after, if any, and the line between them
+static Exception* chained_exception(Exception* exc, const char** message) {
+    if (exc->cause) {
+        *message = "The above exception was the direct cause of the following exception:";
+        return exc->cause;
+    }
+    if (exc->context && !exc->suppress_context) {
+        *message = "During handling of the above exception, another exception occurred:";
+        return exc->context;
+    }
+    return NULL;
+}
+
+// Print the exceptions `exc` was chained to, the first raised first, each
+// followed by how the next is chained to it
+static void print_chain(Exception* exc) {
+    const char* message;
+    Exception* chained = chained_exception(exc, &message);
+    if (!chained) {
+        return;
+    }
+    print_chain(chained);
+    print_exception(chained);
+    fprintf(stderr, "\n%s\n\n", message);
+}
+
+void __pyc_report_exception(Exception* exc) {
+    if (exc) {
+        print_chain(exc);
+    }
+    fputs("Uncaught exception", stderr);
+    if (exc && exc->type_name) {
+        fputs(": ", stderr);
+        fwrite(exc->type_name->data, 1, exc->type_name->len, stderr);
+    }
+    if (exc && exc->message && exc->message->len > 0) {
+        fputs(": ", stderr);
+        fwrite(exc->message->data, 1, exc->message->len, stderr);
+    }
+    fputc('\n', stderr);
+}
+
 void __pyc_raise(Exception* exc) {
+    set_context(exc);
     current_exception = exc;
 
     if (!current_frame) {
-        // No handler - print error and exit
-        fputs("Uncaught exception", stderr);
-        if (exc && exc->type_name) {
-            fputs(": ", stderr);
-            fwrite(exc->type_name->data, 1, exc->type_name->len, stderr);
-        }
-        if (exc && exc->message && exc->message->len > 0) {
-            fputs(": ", stderr);
-            fwrite(exc->message->data, 1, exc->message->len, stderr);
+        // No handler - print error and exit; with traceback records, as
+        // Python prints it
+        if (current_traceback) {
+            print_chain(exc);
+            print_traceback();
+            print_exception(exc);
+        } else {
+            __pyc_report_exception(exc);
         }
-        fputc('\n', stderr);
         exit(1);
     }
+
+    if (__pyc_setjmp_exceptions) {
+        // The functions between here and the try block never return, so
+        // their shadow stack frames, traceback records and recursion depth
+        // are dropped for them
+        __pyc_gc_unwind(current_frame->gc_depth);
+        current_traceback = current_frame->traceback;
+        call_depth = current_frame->call_depth;
+        EXCEPTION_LONGJMP(current_frame);
+    }
 }
 
 void __pyc_reraise(void) {
-    if (current_exception) {
-        __pyc_raise(current_exception);
+    if (handled_exception) {
+        __pyc_raise(handled_exception);
     } else {
         fputs("RuntimeError: No active exception to re-raise\n", stderr);
         exit(1);
@@ -106,10 +325,17 @@ Exception* EXCEPTION_METHOD(__init__)(String* message) {
 }
 
 Exception* __pyc_exception_new(String* type_name, String* message, String* parent_types) {
-    Exception* exc = (Exception*)malloc(sizeof(Exception));
+    // Exceptions are never freed, so their strings are kept for good
+    __pyc_rc_untrack(type_name);
+    __pyc_rc_untrack(message);
+    __pyc_rc_untrack(parent_types);
+    Exception* exc = (Exception*)rt_malloc(sizeof(Exception));
     exc->type_name = type_name;
     exc->message = message;
     exc->parent_types = parent_types;
+    exc->cause = NULL;
+    exc->context = NULL;
+    exc->suppress_context = 0;
     return exc;
 }
 
@@ -129,7 +355,7 @@ String* EXCEPTION_METHOD(__repr__)(Exception* exc) {
     int64_t msg_len = exc->message ? exc->message->len : 0;
     int64_t total_len = type_len + 4 + msg_len;  // "Type('msg')"
 
-    String* result = (String*)malloc(sizeof(String) + total_len + 1);
+    String* result = str_alloc(total_len);
     result->len = total_len;
 
     char* p = result->data;
@@ -157,6 +383,20 @@ String* EXCEPTION_METHOD(__repr__)(Exception* exc) {
     return result;
 }
 
+Exception* EXCEPTION_METHOD(__cause__)(Exception* exc) {
+    return exc->cause;
+}
+
+Exception* EXCEPTION_METHOD(__context__)(Exception* exc) {
+    return exc->context;
+}
+
+Exception* __pyc_exception_with_cause(Exception* exc, Exception* cause) {
+    exc->cause = cause;
+    exc->suppress_context = 1;
+    return exc;
+}
+
 String* __pyc_exception_type(Exception* exc) {
     if (exc && exc->type_name) {
         return exc->type_name;
@@ -175,6 +415,61 @@ Exception* __pyc_stop_iteration(void) {
     return stop_iteration_singleton;
 }
 
+// Raise OverflowError for an int result outside the 64-bit range (emitted by
+// the compiler's checked-int mode, as CPython would promote to a big int)
+void __pyc_raise_overflow(void) {
+    const char* message = "int result does not fit in 64 bits";
+    __pyc_raise(__pyc_exception_new(
+        STR_METHOD(from_literal)("OverflowError", 13),
+        STR_METHOD(from_literal)(message, (int64_t)strlen(message)),
+        STR_METHOD(from_literal)("ArithmeticError,Exception", 25)));
+}
+
+// Raise ValueError for a shift by a negative count (checked-int mode)
+void __pyc_raise_negative_shift(void) {
+    const char* message = "negative shift count";
+    __pyc_raise(__pyc_exception_new(
+        STR_METHOD(from_literal)("ValueError", 10),
+        STR_METHOD(from_literal)(message, (int64_t)strlen(message)),
+        STR_METHOD(from_literal)("Exception", 9)));
+}
+
+// Raise ZeroDivisionError (emitted by the compiler's division checks)
+void __pyc_raise_zero_division(const char* message) {
+    __pyc_raise(__pyc_exception_new(
+        STR_METHOD(from_literal)("ZeroDivisionError", 17),
+        STR_METHOD(from_literal)(message, (int64_t)strlen(message)),
+        STR_METHOD(from_literal)("ArithmeticError,Exception", 25)));
+}
+
+// Raise ValueError for a math function argument outside its domain, such as
+// math.sqrt(-1) or math.log(0)
+void __pyc_raise_math_domain_error(void) {
+    const char* message = "math domain error";
+    __pyc_raise(__pyc_exception_new(
+        STR_METHOD(from_literal)("ValueError", 10),
+        STR_METHOD(from_literal)(message, (int64_t)strlen(message)),
+        STR_METHOD(from_literal)("Exception", 9)));
+}
+
+// Raise OverflowError for a math function result too large for a float, such
+// as math.exp(1000)
+void __pyc_raise_math_range_error(void) {
+    const char* message = "math range error";
+    __pyc_raise(__pyc_exception_new(
+        STR_METHOD(from_literal)("OverflowError", 13),
+        STR_METHOD(from_literal)(message, (int64_t)strlen(message)),
+        STR_METHOD(from_literal)("ArithmeticError,Exception", 25)));
+}
+
+// Raise IndexError for a subscript outside the sequence
+void __pyc_raise_index_error(const char* message) {
+    __pyc_raise(__pyc_exception_new(
+        STR_METHOD(from_literal)("IndexError", 10),
+        STR_METHOD(from_literal)(message, (int64_t)strlen(message)),
+        STR_METHOD(from_literal)("LookupError,Exception", 21)));
+}
+
 int __pyc_exception_matches(Exception* exc, const char* type_name) {
     if (!exc || !exc->type_name || !type_name) {
         return 0;
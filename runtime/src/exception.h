@@ -6,7 +6,12 @@
 
 // ============================================================================
 // Exception handling for Python-like try/except/finally
-// Uses setjmp/longjmp for stack unwinding (freestanding implementation)
+// Every try block pushes an ExceptionFrame. How a raise reaches it depends on
+// the exception model the program was compiled with (pycc --exceptions):
+//   - polling: the raise sets the pending exception and returns; the try
+//     block checks for it after each statement
+//   - setjmp: the try block saves its registers in the frame's jump buffer
+//     (__builtin_setjmp) and the raise longjmps back to it
 // ============================================================================
 
 // Exception method macro
@@ -25,12 +30,28 @@ typedef struct {
 // Exception structure
 // ============================================================================
 
-typedef struct {
-    String* type_name;    // Exception type name (e.g., "ValueError")
-    String* message;      // Exception message
-    String* parent_types; // Comma-separated parent type names (e.g., "BaseError,Exception")
+typedef struct Exception {
+    String* type_name;          // Exception type name (e.g., "ValueError")
+    String* message;            // Exception message
+    String* parent_types;       // Comma-separated parent type names (e.g., "BaseError,Exception")
+    struct Exception* cause;    // __cause__: the exception `raise ... from` named
+    struct Exception* context;  // __context__: the exception being handled when it was raised
+    int64_t suppress_context;   // Set by `raise ... from`: the cause is reported, not the context
 } Exception;
 
+// ============================================================================
+// Traceback record of a function being run (pycc --traceback)
+// Every function pushes one on entry and pops it on return; an uncaught
+// exception prints them, outermost first, as Python's traceback
+// ============================================================================
+
+typedef struct TracebackFrame {
+    struct TracebackFrame* prev;     // Record of the caller
+    const char* function;            // Function name ("<module>" for a module's code)
+    const char* file;                // Source file of the function
+    int64_t line;                    // Line of the statement being run
+} TracebackFrame;
+
 // ============================================================================
 // Exception frame for try block (linked list stack)
 // ============================================================================
@@ -38,6 +59,9 @@ typedef struct {
 typedef struct ExceptionFrame {
     JmpBuf buf;                      // Jump buffer for longjmp
     struct ExceptionFrame* prev;     // Previous frame in stack
+    int64_t gc_depth;                // Shadow stack depth when the frame was pushed
+    TracebackFrame* traceback;       // Traceback record when the frame was pushed
+    int64_t call_depth;              // Recursion depth when the frame was pushed
 } ExceptionFrame;
 
 // ============================================================================
@@ -63,6 +87,21 @@ void __pyc_pop_exception_frame(void);
 // Get the current exception frame (for longjmp target)
 ExceptionFrame* __pyc_get_exception_frame(void);
 
+// Push a function's traceback record as it starts
+void __pyc_traceback_push(TracebackFrame* frame);
+
+// Pop the innermost traceback record as its function returns
+void __pyc_traceback_pop(void);
+
+// Count a function starting (pycc --recursion-limit); returns nonzero, and
+// leaves the count as it was, if the function must return at once: when
+// `limit` functions are already running, which raises RecursionError, or
+// when an exception is pending in polling mode
+int __pyc_recursion_enter(int64_t limit);
+
+// Count a function counted by __pyc_recursion_enter returning
+void __pyc_recursion_leave(void);
+
 // ============================================================================
 // Exception state management
 // ============================================================================
@@ -79,18 +118,30 @@ void __pyc_clear_exception(void);
 // Check if an exception is pending
 int __pyc_has_exception(void);
 
+// The exception the innermost running except (or finally) block handles,
+// NULL outside of them: a bare raise re-raises it, and it becomes the
+// __context__ of an exception raised meanwhile. Every try block saves it
+// and restores it as its finally block ends; its handlers set it.
+Exception* __pyc_get_handled_exception(void);
+void __pyc_set_handled_exception(Exception* exc);
+
 // ============================================================================
 // Raise an exception
 // In polling mode: sets exception and returns (caller polls with __pyc_has_exception)
-// If no handler, prints error and exits
+// In setjmp mode: longjmps to the innermost try block
+// If no handler, prints error (after the traceback, with --traceback) and exits
 // ============================================================================
 
 void __pyc_raise(Exception* exc);
 
-// Re-raise the current exception
-// This exits if no exception is pending
+// Re-raise the exception being handled (a bare raise)
+// This exits if no exception is being handled
 void __pyc_reraise(void);
 
+// Print an exception as an uncaught one is printed, without exiting
+// (pyrepl reports the exception an input raised, then reads the next)
+void __pyc_report_exception(Exception* exc);
+
 // ============================================================================
 // Exception class methods
 // ============================================================================
@@ -107,6 +158,13 @@ String* EXCEPTION_METHOD(__str__)(Exception* exc);
 // Exception.__repr__()
 String* EXCEPTION_METHOD(__repr__)(Exception* exc);
 
+// Exception.__cause__ and Exception.__context__ (NULL for None)
+Exception* EXCEPTION_METHOD(__cause__)(Exception* exc);
+Exception* EXCEPTION_METHOD(__context__)(Exception* exc);
+
+// raise exc from cause: record the cause (NULL for None) and return exc
+Exception* __pyc_exception_with_cause(Exception* exc, Exception* cause);
+
 // Get exception type name
 String* __pyc_exception_type(Exception* exc);
 
@@ -116,4 +174,33 @@ int __pyc_exception_matches(Exception* exc, const char* type_name);
 // Get the singleton StopIteration exception (avoids repeated allocations)
 Exception* __pyc_stop_iteration(void);
 
+// Raise OverflowError for int arithmetic that left the 64-bit range
+// (checked-int mode)
+void __pyc_raise_overflow(void);
+
+// Raise ValueError("negative shift count") (checked-int mode)
+void __pyc_raise_negative_shift(void);
+
+// Raise ZeroDivisionError with CPython's message for the operation
+void __pyc_raise_zero_division(const char* message);
+
+// Raise ValueError("math domain error") for a math function argument outside
+// its domain
+void __pyc_raise_math_domain_error(void);
+
+// Raise OverflowError("math range error") for a math function result that
+// overflows
+void __pyc_raise_math_range_error(void);
+
+// Raise IndexError (e.g. "list index out of range")
+void __pyc_raise_index_error(const char* message);
+
+// Emitted by the compiler: 0 when it was told to skip the ZeroDivisionError
+// and IndexError checks, which then become undefined behaviour
+extern const int8_t __pyc_runtime_checks;
+
+// Emitted by the compiler: 1 when try blocks were compiled with setjmp
+// (pycc --exceptions=setjmp), so a raise must jump instead of returning
+extern const int8_t __pyc_setjmp_exceptions;
+
 #endif // EXCEPTION_H
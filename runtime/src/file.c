@@ -0,0 +1,387 @@
+#include "runtime.h"
+#include <errno.h>
+#include <fcntl.h>
+#include <stdlib.h>
+#include <string.h>
+#include <unistd.h>
+
+// Bytes requested from the descriptor per read() call
+#define FILE_READ_CHUNK 4096
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+static void raise_file_error(const char* type_name, const char* parent_types,
+                             String* message) {
+    __pyc_raise(__pyc_exception_new(
+        STR_METHOD(from_literal)(type_name, (int64_t)strlen(type_name)),
+        message,
+        STR_METHOD(from_literal)(parent_types, (int64_t)strlen(parent_types))));
+}
+
+static void raise_file_message(const char* type_name, const char* parent_types,
+                               const char* message) {
+    raise_file_error(type_name, parent_types,
+                     STR_METHOD(from_literal)(message, (int64_t)strlen(message)));
+}
+
+// OSError (or the subclass Python uses for this errno) with the message
+// "[Errno N] description: 'path'"
+static void raise_os_error(int err, String* path) {
+    const char* type_name = "OSError";
+    switch (err) {
+        case ENOENT:
+            type_name = "FileNotFoundError";
+            break;
+        case EEXIST:
+            type_name = "FileExistsError";
+            break;
+        case EACCES:
+        case EPERM:
+            type_name = "PermissionError";
+            break;
+        case EISDIR:
+            type_name = "IsADirectoryError";
+            break;
+        case ENOTDIR:
+            type_name = "NotADirectoryError";
+            break;
+    }
+
+    char prefix[128];
+    int prefix_len = snprintf(prefix, sizeof(prefix), "[Errno %d] %s: ", err, strerror(err));
+    if (prefix_len < 0 || prefix_len >= (int)sizeof(prefix)) {
+        prefix_len = (int)strlen(prefix);
+    }
+    String* message = STR_METHOD(__add__)(STR_METHOD(from_literal)(prefix, prefix_len),
+                                          STR_METHOD(__repr__)(path));
+    raise_file_error(type_name, strcmp(type_name, "OSError") == 0 ? "Exception" : "OSError,Exception",
+                     message);
+}
+
+static void check_open(File* file) {
+    if (file == NULL) {
+        rt_panic("Cannot use NULL file");
+    }
+    if (file->closed) {
+        raise_file_message("ValueError", "Exception", "I/O operation on closed file.");
+    }
+}
+
+static void check_readable(File* file) {
+    check_open(file);
+    if (!file->readable) {
+        raise_file_message("UnsupportedOperation", "OSError,ValueError,Exception", "not readable");
+    }
+}
+
+// ============================================================================
+// open()
+// ============================================================================
+
+// Parse an open() mode ("r", "w", "a" or "x", optionally with "+" and "t")
+// into open(2) flags; returns 0 for an invalid mode
+static int parse_mode(String* mode, int* flags, int8_t* readable, int8_t* writable) {
+    const char* p = string_data(mode);
+    const char* end = p + string_len(mode);
+    if (p == end) {
+        return 0;
+    }
+
+    char kind = *p++;
+    int8_t plus = 0;
+    int8_t text = 0;
+    for (; p < end; p++) {
+        if (*p == '+' && !plus) {
+            plus = 1;
+        } else if (*p == 't' && !text) {
+            text = 1;
+        } else {
+            return 0;
+        }
+    }
+
+    switch (kind) {
+        case 'r':
+            *flags = 0;
+            *readable = 1;
+            *writable = plus;
+            break;
+        case 'w':
+            *flags = O_CREAT | O_TRUNC;
+            *readable = plus;
+            *writable = 1;
+            break;
+        case 'a':
+            *flags = O_CREAT | O_APPEND;
+            *readable = plus;
+            *writable = 1;
+            break;
+        case 'x':
+            *flags = O_CREAT | O_EXCL;
+            *readable = plus;
+            *writable = 1;
+            break;
+        default:
+            return 0;
+    }
+    *flags |= plus ? O_RDWR : (*readable ? O_RDONLY : O_WRONLY);
+    return 1;
+}
+
+static File* file_new(int fd, String* path, int8_t readable, int8_t writable) {
+    File* file = (File*)rt_malloc(sizeof(File));
+    if (file == NULL) {
+        rt_panic("Failed to allocate memory for file");
+    }
+    file->fd = fd;
+    file->readable = readable;
+    file->writable = writable;
+    file->closed = 0;
+    file->console = 0;
+    // Files are never freed, so their paths are kept for good
+    __pyc_rc_untrack(path);
+    file->path = path;
+    file->buf = NULL;
+    file->buf_pos = 0;
+    file->buf_len = 0;
+    file->buf_cap = 0;
+    return file;
+}
+
+File* __pyc___builtin___open(String* path, String* mode) {
+    int flags;
+    int8_t readable;
+    int8_t writable;
+    if (memchr(string_data(mode), 'b', (size_t)string_len(mode)) != NULL) {
+        raise_file_message("ValueError", "Exception", "binary mode is not supported");
+        return NULL;
+    }
+    if (!parse_mode(mode, &flags, &readable, &writable)) {
+        String* message = STR_METHOD(__add__)(STR_METHOD(from_literal)("invalid mode: ", 14),
+                                              STR_METHOD(__repr__)(mode));
+        raise_file_error("ValueError", "Exception", message);
+        return NULL;
+    }
+
+    // Embedded files shadow the filesystem for reading
+    if (!writable) {
+        const EmbeddedFile* embedded = __pyc_embedded_find(string_data(path), string_len(path));
+        if (embedded != NULL) {
+            File* file = file_new(-1, path, 1, 0);
+            file->buf = (char*)rt_malloc(embedded->len > 0 ? (size_t)embedded->len : 1);
+            if (file->buf == NULL) {
+                rt_panic("Failed to allocate memory for file");
+            }
+            memcpy(file->buf, embedded->data, (size_t)embedded->len);
+            file->buf_len = embedded->len;
+            file->buf_cap = embedded->len;
+            return file;
+        }
+    }
+
+    int64_t path_len = string_len(path);
+    char* c_path = (char*)rt_malloc((size_t)path_len + 1);
+    if (c_path == NULL) {
+        rt_panic("Failed to allocate memory for file path");
+    }
+    memcpy(c_path, string_data(path), (size_t)path_len);
+    c_path[path_len] = '\0';
+
+    int fd;
+    do {
+        fd = open(c_path, flags | O_CLOEXEC, 0666);
+    } while (fd < 0 && errno == EINTR);
+    int err = errno;
+    rt_free(c_path);
+    if (fd < 0) {
+        raise_os_error(err, path);
+        return NULL;
+    }
+    return file_new(fd, path, readable, writable);
+}
+
+// ============================================================================
+// Reading
+// ============================================================================
+
+// Read another chunk from the descriptor into the buffer, first dropping the
+// bytes already consumed; returns 0 at end of file
+static int file_fill(File* file) {
+    if (file->fd < 0) {
+        return 0;  // Embedded files are buffered whole
+    }
+
+    int64_t unread = file->buf_len - file->buf_pos;
+    if (file->buf_pos > 0) {
+        memmove(file->buf, file->buf + file->buf_pos, (size_t)unread);
+        file->buf_pos = 0;
+        file->buf_len = unread;
+    }
+    if (file->buf_len + FILE_READ_CHUNK > file->buf_cap) {
+        int64_t cap = file->buf_cap > 0 ? file->buf_cap * 2 : FILE_READ_CHUNK;
+        while (cap < file->buf_len + FILE_READ_CHUNK) {
+            cap *= 2;
+        }
+        char* buf = (char*)rt_realloc(file->buf, (size_t)cap);
+        if (buf == NULL) {
+            rt_panic("Failed to allocate memory for file buffer");
+        }
+        file->buf = buf;
+        file->buf_cap = cap;
+    }
+
+    ssize_t n;
+    do {
+        n = read(file->fd, file->buf + file->buf_len, FILE_READ_CHUNK);
+    } while (n < 0 && errno == EINTR);
+    if (n < 0) {
+        raise_os_error(errno, file->path);
+        return 0;
+    }
+    file->buf_len += n;
+    return n > 0;
+}
+
+// Take the next `len` unread bytes as a str
+static String* file_take(File* file, int64_t len) {
+    String* s = STR_METHOD(from_literal)(file->buf + file->buf_pos, len);
+    file->buf_pos += len;
+    return s;
+}
+
+String* FILE_METHOD(read)(File* file) {
+    check_readable(file);
+    while (file_fill(file)) {
+    }
+    return file_take(file, file->buf_len - file->buf_pos);
+}
+
+String* FILE_METHOD(readline)(File* file) {
+    check_readable(file);
+    // Unread bytes already searched for a newline
+    int64_t scanned = 0;
+    for (;;) {
+        int64_t unread = file->buf_len - file->buf_pos;
+        const char* start = file->buf + file->buf_pos;
+        const char* newline =
+            unread > scanned ? memchr(start + scanned, '\n', (size_t)(unread - scanned)) : NULL;
+        if (newline != NULL) {
+            return file_take(file, newline - start + 1);
+        }
+        scanned = unread;
+        if (!file_fill(file)) {
+            // Last line without a newline, or "" at end of file
+            return file_take(file, file->buf_len - file->buf_pos);
+        }
+    }
+}
+
+// ============================================================================
+// Writing
+// ============================================================================
+
+int64_t FILE_METHOD(write)(File* file, String* s) {
+    check_open(file);
+    if (!file->writable) {
+        raise_file_message("UnsupportedOperation", "OSError,ValueError,Exception", "not writable");
+        return 0;
+    }
+
+    if (file->console) {
+        if (file->fd == 2) {
+            write_stderr_string_impl(s);
+        } else {
+            write_string_impl(s);
+        }
+        return STR_METHOD(__len__)(s);
+    }
+
+    // In "+" modes, rewind over data read ahead so the write lands after what was read
+    int64_t unread = file->buf_len - file->buf_pos;
+    if (unread > 0) {
+        lseek(file->fd, -unread, SEEK_CUR);
+    }
+    file->buf_pos = 0;
+    file->buf_len = 0;
+
+    const char* data = string_data(s);
+    int64_t remaining = string_len(s);
+    while (remaining > 0) {
+        ssize_t n = write(file->fd, data, (size_t)remaining);
+        if (n < 0) {
+            if (errno == EINTR) {
+                continue;
+            }
+            raise_os_error(errno, file->path);
+            return 0;
+        }
+        data += n;
+        remaining -= n;
+    }
+    return STR_METHOD(__len__)(s);
+}
+
+// ============================================================================
+// Closing and the context manager protocol
+// ============================================================================
+
+void FILE_METHOD(close)(File* file) {
+    if (file == NULL || file->closed) {
+        return;  // Closing twice is allowed
+    }
+    if (file->console) {
+        // The descriptor stays open for the runtime's own output
+        fflush(file->fd == 2 ? stderr : stdout);
+        file->closed = 1;
+        return;
+    }
+    if (file->fd >= 0) {
+        close(file->fd);
+        file->fd = -1;
+    }
+    rt_free(file->buf);
+    file->buf = NULL;
+    file->buf_pos = 0;
+    file->buf_len = 0;
+    file->buf_cap = 0;
+    file->closed = 1;
+}
+
+File* FILE_METHOD(__enter__)(File* file) {
+    check_open(file);
+    return file;
+}
+
+void FILE_METHOD(__exit__)(File* file) {
+    FILE_METHOD(close)(file);
+}
+
+// ============================================================================
+// sys.stdin, sys.stdout and sys.stderr
+// ============================================================================
+
+static File* console_file(File** slot, int fd, const char* name, int8_t readable) {
+    if (*slot == NULL) {
+        String* path = STR_METHOD(from_literal)(name, (int64_t)strlen(name));
+        *slot = file_new(fd, path, readable, !readable);
+        (*slot)->console = 1;
+    }
+    return *slot;
+}
+
+File* __pyc_sys_stdin(void) {
+    static File* stdin_file = NULL;
+    return console_file(&stdin_file, 0, "<stdin>", 1);
+}
+
+File* __pyc_sys_stdout(void) {
+    static File* stdout_file = NULL;
+    return console_file(&stdout_file, 1, "<stdout>", 0);
+}
+
+File* __pyc_sys_stderr(void) {
+    static File* stderr_file = NULL;
+    return console_file(&stderr_file, 2, "<stderr>", 0);
+}
@@ -0,0 +1,207 @@
+#include "gc.h"
+#include "refcount.h"
+#include "io.h"
+#include <stdlib.h>
+
+// Collect once this many instances are tracked; afterwards twice the survivors
+#define GC_MIN_THRESHOLD 1024
+
+typedef struct {
+    void*** roots;
+    int64_t count;
+    int64_t nursery_mark;  // Nursery length when the function was entered
+} GcFrame;
+
+static int gc_enabled = 0;
+static int64_t gc_threshold = GC_MIN_THRESHOLD;
+
+static GcFrame* gc_frames = NULL;
+static int64_t gc_frame_count = 0;
+static int64_t gc_frame_capacity = 0;
+
+static void*** gc_globals = NULL;
+static int64_t gc_global_count = 0;
+static int64_t gc_global_capacity = 0;
+
+static void** gc_nursery = NULL;
+static int64_t gc_nursery_len = 0;
+static int64_t gc_nursery_capacity = 0;
+
+static void** gc_mark_stack = NULL;
+static int64_t gc_mark_len = 0;
+static int64_t gc_mark_capacity = 0;
+
+// Make room for one more item in a growable array
+static void* gc_reserve(void* items, int64_t len, int64_t* capacity, size_t item_size) {
+    if (len < *capacity) {
+        return items;
+    }
+    *capacity = *capacity == 0 ? 64 : *capacity * 2;
+    items = rt_realloc(items, (size_t)*capacity * item_size);
+    if (items == NULL) {
+        rt_panic("Failed to allocate memory for the garbage collector");
+    }
+    return items;
+}
+
+// ============================================================================
+// Roots
+// ============================================================================
+
+void __pyc_gc_init(void) {
+    gc_enabled = 1;
+}
+
+void __pyc_gc_add_root(void** slot) {
+    gc_globals = gc_reserve(gc_globals, gc_global_count, &gc_global_capacity, sizeof(void**));
+    gc_globals[gc_global_count++] = slot;
+}
+
+void __pyc_gc_push_frame(void*** roots, int64_t count) {
+    gc_frames = gc_reserve(gc_frames, gc_frame_count, &gc_frame_capacity, sizeof(GcFrame));
+    GcFrame* frame = &gc_frames[gc_frame_count++];
+    frame->roots = roots;
+    frame->count = count;
+    frame->nursery_mark = gc_nursery_len;
+}
+
+void __pyc_gc_track_new(void* obj) {
+    if (!gc_enabled) {
+        return;
+    }
+    gc_nursery = gc_reserve(gc_nursery, gc_nursery_len, &gc_nursery_capacity, sizeof(void*));
+    gc_nursery[gc_nursery_len++] = obj;
+}
+
+void __pyc_gc_pop_frame(void* result) {
+    if (gc_frame_count == 0) {
+        return;
+    }
+    gc_nursery_len = gc_frames[--gc_frame_count].nursery_mark;
+    if (result != NULL) {
+        __pyc_gc_track_new(result);
+    }
+}
+
+void __pyc_gc_end_statement(void) {
+    if (gc_frame_count > 0) {
+        gc_nursery_len = gc_frames[gc_frame_count - 1].nursery_mark;
+    }
+}
+
+int64_t __pyc_gc_depth(void) {
+    return gc_frame_count;
+}
+
+void __pyc_gc_unwind(int64_t depth) {
+    // The nursery is left alone: the instances the dropped frames allocated
+    // stay alive until the handler's statement ends
+    if (depth < gc_frame_count) {
+        gc_frame_count = depth;
+    }
+}
+
+// ============================================================================
+// Mark and sweep
+// ============================================================================
+
+static void gc_mark(void* obj) {
+    RcEntry* entry = __pyc_rc_entry(obj);
+    if (entry == NULL || entry->marked) {
+        return;
+    }
+    entry->marked = 1;
+    gc_mark_stack = gc_reserve(gc_mark_stack, gc_mark_len, &gc_mark_capacity, sizeof(void*));
+    gc_mark_stack[gc_mark_len++] = obj;
+}
+
+// Mark everything reachable from the marked instances
+static void gc_trace(void) {
+    while (gc_mark_len > 0) {
+        void* obj = gc_mark_stack[--gc_mark_len];
+        int64_t words = __pyc_rc_entry(obj)->size / (int64_t)sizeof(void*);
+        void** fields = (void**)obj;
+        for (int64_t i = 0; i < words; i++) {
+            gc_mark(fields[i]);
+        }
+    }
+}
+
+void __pyc_gc_collect(void) {
+    int64_t slot_count;
+    RcEntry* slots = __pyc_rc_slots(&slot_count);
+
+    for (int64_t i = 0; i < slot_count; i++) {
+        if (slots[i].obj != NULL && slots[i].pinned) {
+            gc_mark(slots[i].obj);
+        }
+    }
+    for (int64_t i = 0; i < gc_global_count; i++) {
+        gc_mark(*gc_globals[i]);
+    }
+    for (int64_t f = 0; f < gc_frame_count; f++) {
+        for (int64_t i = 0; i < gc_frames[f].count; i++) {
+            gc_mark(*gc_frames[f].roots[i]);
+        }
+    }
+    for (int64_t i = 0; i < gc_nursery_len; i++) {
+        gc_mark(gc_nursery[i]);
+    }
+    gc_trace();
+
+    // Gather the unreached instances (reusing the empty mark stack) and
+    // unmark the rest for the next collection; strings and lists are left
+    // to counting, as the roots do not cover them
+    for (int64_t i = 0; i < slot_count; i++) {
+        if (slots[i].obj == NULL) {
+            continue;
+        }
+        if (slots[i].marked) {
+            slots[i].marked = 0;
+        } else if (!slots[i].builtin) {
+            gc_mark_stack = gc_reserve(gc_mark_stack, gc_mark_len, &gc_mark_capacity, sizeof(void*));
+            gc_mark_stack[gc_mark_len++] = slots[i].obj;
+        }
+    }
+
+    // Untrack all the garbage before releasing any of it, so that the
+    // references between garbage instances are ignored; references to
+    // survivors are dropped as usual (with --refcount)
+    int64_t garbage_count = gc_mark_len;
+    RcRelease* releases = rt_malloc((size_t)(garbage_count > 0 ? garbage_count : 1) * sizeof(RcRelease));
+    if (releases == NULL) {
+        rt_panic("Failed to allocate memory for the garbage collector");
+    }
+    for (int64_t i = 0; i < garbage_count; i++) {
+        releases[i] = __pyc_rc_entry(gc_mark_stack[i])->release;
+        __pyc_rc_untrack(gc_mark_stack[i]);
+    }
+    for (int64_t i = 0; i < garbage_count; i++) {
+        if (releases[i] != NULL) {
+            releases[i](gc_mark_stack[i]);
+        }
+    }
+    for (int64_t i = 0; i < garbage_count; i++) {
+        rt_free(gc_mark_stack[i]);
+    }
+    rt_free(releases);
+    gc_mark_len = 0;
+
+    int64_t live = __pyc_rc_live();
+    gc_threshold = live * 2 > GC_MIN_THRESHOLD ? live * 2 : GC_MIN_THRESHOLD;
+}
+
+void __pyc_gc_collect_at_exit(void) {
+    if (!gc_enabled) {
+        return;
+    }
+    gc_global_count = 0;
+    gc_nursery_len = 0;
+    __pyc_gc_collect();
+}
+
+void __pyc_gc_maybe_collect(void) {
+    if (gc_enabled && __pyc_rc_live() >= gc_threshold) {
+        __pyc_gc_collect();
+    }
+}
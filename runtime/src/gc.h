@@ -0,0 +1,46 @@
+#ifndef GC_H
+#define GC_H
+
+#include "types.h"
+
+// ============================================================================
+// Tracing mark-sweep collector for class instances (pycc --gc=tracing)
+// Collects the instances tracked by refcount.c, including reference cycles.
+// The roots are
+//   - the slots registered by the frames on the shadow stack: every compiled
+//     function pushes the addresses of its instance locals and parameters
+//   - the instance globals
+//   - pinned instances (stored in a container, see __pyc_rc_incref_item)
+//   - the nursery: instances allocated during the statement being executed,
+//     which may only be held in registers
+// Instances are scanned conservatively: every pointer-sized word that holds
+// the address of a tracked instance keeps it alive.
+// ============================================================================
+
+// Turn the collector on (called first thing in main)
+void __pyc_gc_init(void);
+
+// Register the address of a global holding an instance
+void __pyc_gc_add_root(void** slot);
+
+// Enter a function: roots holds the addresses of count root slots
+void __pyc_gc_push_frame(void*** roots, int64_t count);
+// Leave a function; its result (NULL if none) stays in the caller's nursery
+void __pyc_gc_pop_frame(void* result);
+// A statement of the current function starts: earlier temporaries are dead
+void __pyc_gc_end_statement(void);
+// The number of frames on the shadow stack, and dropping the frames above
+// it when a raise jumps past the functions that pushed them
+int64_t __pyc_gc_depth(void);
+void __pyc_gc_unwind(int64_t depth);
+
+// Free every instance not reachable from the roots
+void __pyc_gc_collect(void);
+// Drop the global roots and collect (before pycc --leak-check)
+void __pyc_gc_collect_at_exit(void);
+
+// Called by __pyc_rc_new
+void __pyc_gc_maybe_collect(void);
+void __pyc_gc_track_new(void* obj);
+
+#endif // GC_H
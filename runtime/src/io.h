@@ -11,6 +11,7 @@
 #include <string.h>
 
 #include "types.h"
+#include "alloc.h"
 
 // ============================================================================
 // Output functions
@@ -37,9 +38,44 @@ static inline void rt_panic(const char* message) {
     exit(1);
 }
 
-static inline void rt_panic_index(const char* message, int64_t index, int64_t length) {
-    fprintf(stderr, "Error: %s: %ld (length: %ld)\n", message, index, length);
-    exit(1);
+// ============================================================================
+// Slicing
+// ============================================================================
+
+// Passed for a missing slice bound (e.g. the start of s[:3])
+#define SLICE_NONE INT64_MIN
+
+// Clamp start/stop of a slice over a sequence of length len the way Python
+// does (negative indices count from the end, missing bounds follow the step
+// direction) and return the number of selected items
+static inline int64_t rt_slice_indices(int64_t len, int64_t* start, int64_t* stop, int64_t step) {
+    if (step == 0) {
+        rt_panic("slice step cannot be zero");
+    }
+
+    int64_t* bounds[2] = {start, stop};
+    for (int i = 0; i < 2; i++) {
+        int64_t* bound = bounds[i];
+        if (*bound == SLICE_NONE) {
+            if (i == 0) {
+                *bound = step < 0 ? len - 1 : 0;
+            } else {
+                *bound = step < 0 ? -1 : len;
+            }
+        } else if (*bound < 0) {
+            *bound += len;
+            if (*bound < 0) {
+                *bound = step < 0 ? -1 : 0;
+            }
+        } else if (*bound >= len) {
+            *bound = step < 0 ? len - 1 : len;
+        }
+    }
+
+    if (step < 0) {
+        return *stop < *start ? (*start - *stop - 1) / -step + 1 : 0;
+    }
+    return *start < *stop ? (*stop - *start - 1) / step + 1 : 0;
 }
 
 #endif // IO_H
@@ -1,22 +1,70 @@
 #include "runtime.h"
 #include <stdlib.h>
 #include <stdio.h>
+#include <string.h>
 
-List* LIST_METHOD(__init__)(void) {
-    List* list = (List*)malloc(sizeof(List));
-    if (list == NULL) {
-        rt_panic("Failed to allocate memory for list");
+// ============================================================================
+// Item storage
+// ============================================================================
+
+static int64_t list_load(const List* list, int64_t index) {
+    if (list->item_size == 1) {
+        return ((const uint8_t*)list->data)[index];
     }
+    return ((const int64_t*)list->data)[index];
+}
 
-    list->cap = 8;
+static void list_store(List* list, int64_t index, int64_t value) {
+    if (list->item_size == 1) {
+        ((uint8_t*)list->data)[index] = (uint8_t)value;
+    } else {
+        ((int64_t*)list->data)[index] = value;
+    }
+}
+
+static char* list_at(const List* list, int64_t index) {
+    return (char*)list->data + index * list->item_size;
+}
+
+int64_t __pyc_list_item(List* list, int64_t index) {
+    return list_load(list, index);
+}
+
+// A list holds a reference to each of its items (with --refcount)
+static void list_release(void* obj) {
+    List* list = (List*)obj;
+    for (int64_t i = 0; i < list->len; i++) {
+        __pyc_rc_decref_item(list_load(list, i), list->kind);
+    }
+    rt_free(list->data);
+}
+
+// ============================================================================
+// List methods
+// ============================================================================
+
+List* LIST_METHOD(__init__)(int64_t kind) {
+    List* list = (List*)__pyc_rc_alloc(sizeof(List), list_release);
+    list->data = NULL;
+    __pyc_list_init_in(list, kind);
+    return list;
+}
+
+void __pyc_list_init_in(List* list, int64_t kind) {
     list->len = 0;
-    list->data = (int64_t*)malloc(sizeof(int64_t) * list->cap);
+    list->kind = (int8_t)kind;
+    list->item_size = kind == DICT_KIND_BOOL ? 1 : (int8_t)sizeof(int64_t);
+    if (list->data != NULL) {
+        // The previous list in this slot is dead; keep its buffer
+        return;
+    }
+
+    list->cap = 8;
+    list->data = rt_malloc((size_t)list->item_size * list->cap);
 
     if (list->data == NULL) {
         rt_panic("Failed to allocate memory for list data");
     }
-
-    return list;
 }
 
 void LIST_METHOD(append)(List* list, int64_t value) {
@@ -26,34 +74,216 @@ void LIST_METHOD(append)(List* list, int64_t value) {
 
     if (list->len == list->cap) {
         list->cap *= 2;
-        int64_t* new_data = (int64_t*)realloc(list->data, sizeof(int64_t) * list->cap);
+        void* new_data = rt_realloc(list->data, (size_t)list->item_size * list->cap);
         if (new_data == NULL) {
             rt_panic("Failed to reallocate memory for list");
         }
         list->data = new_data;
     }
 
-    list->data[list->len++] = value;
+    __pyc_rc_incref_item(value, list->kind);
+    list_store(list, list->len++, value);
+}
+
+int64_t LIST_METHOD(pop)(List* list, int64_t index) {
+    if (list == NULL) {
+        rt_panic("Cannot pop from NULL list");
+    }
+    // Not a bounds check: without it the shift below would move a negative
+    // length, so it stays with --no-runtime-checks
+    if (list->len == 0) {
+        __pyc_raise_index_error("pop from empty list");
+        return 0;
+    }
+    if (index < 0) {
+        index += list->len;
+    }
+    if (__pyc_runtime_checks && (index < 0 || index >= list->len)) {
+        __pyc_raise_index_error("pop index out of range");
+        return 0;
+    }
+    int64_t value = list_load(list, index);
+    memmove(list_at(list, index), list_at(list, index + 1),
+            (size_t)list->item_size * (list->len - index - 1));
+    list->len--;
+    // The list's reference goes to the caller
+    __pyc_rc_unown_item(value, list->kind);
+    return value;
+}
+
+void LIST_METHOD(insert)(List* list, int64_t index, int64_t value) {
+    if (list == NULL) {
+        rt_panic("Cannot insert into NULL list");
+    }
+    if (index < 0) {
+        index += list->len;
+        if (index < 0) {
+            index = 0;
+        }
+    }
+    if (index > list->len) {
+        index = list->len;
+    }
+    // Grow through append, then shift the tail up by one
+    LIST_METHOD(append)(list, value);
+    memmove(list_at(list, index + 1), list_at(list, index),
+            (size_t)list->item_size * (list->len - index - 1));
+    list_store(list, index, value);
+}
+
+// ValueError for list.index(): "<repr of value> is not in list"
+static void raise_not_in_list(int64_t value, int64_t kind) {
+    static const char suffix[] = " is not in list";
+    String* repr = __pyc_dict_item_repr((int8_t)kind, value);
+    size_t len = (size_t)repr->len + sizeof(suffix) - 1;
+    char* buffer = (char*)rt_malloc(len);
+    if (buffer == NULL) {
+        rt_panic("Failed to allocate memory for error message");
+    }
+    memcpy(buffer, repr->data, (size_t)repr->len);
+    memcpy(buffer + repr->len, suffix, sizeof(suffix) - 1);
+    __pyc_raise(__pyc_exception_new(
+        STR_METHOD(from_literal)("ValueError", 10),
+        STR_METHOD(from_literal)(buffer, (int64_t)len),
+        STR_METHOD(from_literal)("Exception", 9)));
+    rt_free(buffer);
+}
+
+void LIST_METHOD(remove)(List* list, int64_t value, int64_t kind) {
+    if (list == NULL) {
+        rt_panic("Cannot remove from NULL list");
+    }
+    for (int64_t i = 0; i < list->len; i++) {
+        if (__pyc_dict_keys_equal((int8_t)kind, list_load(list, i), value)) {
+            LIST_METHOD(__delitem__)(list, i);
+            return;
+        }
+    }
+    const char* message = "list.remove(x): x not in list";
+    __pyc_raise(__pyc_exception_new(
+        STR_METHOD(from_literal)("ValueError", 10),
+        STR_METHOD(from_literal)(message, (int64_t)strlen(message)),
+        STR_METHOD(from_literal)("Exception", 9)));
+}
+
+// index(value, start, stop): start and stop are read like slice bounds
+int64_t LIST_METHOD(index)(List* list, int64_t value, int64_t kind, int64_t start, int64_t stop) {
+    if (list == NULL) {
+        rt_panic("Cannot search in NULL list");
+    }
+    if (start < 0) {
+        start = start + list->len < 0 ? 0 : start + list->len;
+    }
+    if (stop < 0) {
+        stop = stop + list->len < 0 ? 0 : stop + list->len;
+    }
+    if (stop > list->len) {
+        stop = list->len;
+    }
+    for (int64_t i = start; i < stop; i++) {
+        if (__pyc_dict_keys_equal((int8_t)kind, list_load(list, i), value)) {
+            return i;
+        }
+    }
+    raise_not_in_list(value, kind);
+    return -1;
+}
+
+int64_t LIST_METHOD(count)(List* list, int64_t value, int64_t kind) {
+    if (list == NULL) {
+        rt_panic("Cannot search in NULL list");
+    }
+    int64_t count = 0;
+    for (int64_t i = 0; i < list->len; i++) {
+        if (__pyc_dict_keys_equal((int8_t)kind, list_load(list, i), value)) {
+            count++;
+        }
+    }
+    return count;
+}
+
+void LIST_METHOD(extend)(List* list, List* other) {
+    if (list == NULL || other == NULL) {
+        rt_panic("Cannot extend NULL list");
+    }
+    // Read the length first: other may be list itself
+    int64_t count = other->len;
+    for (int64_t i = 0; i < count; i++) {
+        LIST_METHOD(append)(list, list_load(other, i));
+    }
+}
+
+void LIST_METHOD(reverse)(List* list) {
+    if (list == NULL) {
+        rt_panic("Cannot reverse NULL list");
+    }
+    for (int64_t i = 0, j = list->len - 1; i < j; i++, j--) {
+        int64_t tmp = list_load(list, i);
+        list_store(list, i, list_load(list, j));
+        list_store(list, j, tmp);
+    }
+}
+
+void LIST_METHOD(clear)(List* list) {
+    if (list == NULL) {
+        rt_panic("Cannot clear NULL list");
+    }
+    // Empty the list before dropping the items, which may free objects
+    int64_t len = list->len;
+    list->len = 0;
+    for (int64_t i = 0; i < len; i++) {
+        __pyc_rc_decref_item(list_load(list, i), list->kind);
+    }
 }
 
 int64_t LIST_METHOD(__getitem__)(List* list, int64_t index) {
     if (list == NULL) {
         rt_panic("Cannot get from NULL list");
     }
-    if (index < 0 || index >= list->len) {
-        rt_panic_index("Index out of bounds", index, list->len);
+    if (index < 0) {
+        index += list->len;
     }
-    return list->data[index];
+    if (__pyc_runtime_checks && (index < 0 || index >= list->len)) {
+        __pyc_raise_index_error("list index out of range");
+        return 0;
+    }
+    return list_load(list, index);
 }
 
 void LIST_METHOD(__setitem__)(List* list, int64_t index, int64_t value) {
     if (list == NULL) {
         rt_panic("Cannot set in NULL list");
     }
-    if (index < 0 || index >= list->len) {
-        rt_panic_index("Index out of bounds", index, list->len);
+    if (index < 0) {
+        index += list->len;
+    }
+    if (__pyc_runtime_checks && (index < 0 || index >= list->len)) {
+        __pyc_raise_index_error("list assignment index out of range");
+        return;
+    }
+    // Take the new reference first: the item may be stored over itself
+    __pyc_rc_incref_item(value, list->kind);
+    int64_t old = list_load(list, index);
+    list_store(list, index, value);
+    __pyc_rc_decref_item(old, list->kind);
+}
+
+void LIST_METHOD(__delitem__)(List* list, int64_t index) {
+    if (list == NULL) {
+        rt_panic("Cannot delete from NULL list");
+    }
+    if (index < 0) {
+        index += list->len;
+    }
+    if (__pyc_runtime_checks && (index < 0 || index >= list->len)) {
+        __pyc_raise_index_error("list assignment index out of range");
+        return;
     }
-    list->data[index] = value;
+    int64_t old = list_load(list, index);
+    memmove(list_at(list, index), list_at(list, index + 1),
+            (size_t)list->item_size * (list->len - index - 1));
+    list->len--;
+    __pyc_rc_decref_item(old, list->kind);
 }
 
 int64_t LIST_METHOD(__len__)(List* list) {
@@ -63,11 +293,185 @@ int64_t LIST_METHOD(__len__)(List* list) {
     return list->len;
 }
 
-void LIST_METHOD(free)(List* list) {
-    if (list != NULL) {
-        free(list->data);
-        free(list);
+List* LIST_METHOD(__slice__)(List* list, int64_t start, int64_t stop, int64_t step) {
+    if (list == NULL) {
+        rt_panic("Cannot slice NULL list");
+    }
+    int64_t count = rt_slice_indices(list->len, &start, &stop, step);
+    List* result = LIST_METHOD(__init__)(list->kind);
+    for (int64_t i = 0; i < count; i++) {
+        LIST_METHOD(append)(result, list_load(list, start + i * step));
+    }
+    return result;
+}
+
+List* LIST_METHOD(__add__)(List* list, List* other) {
+    if (list == NULL || other == NULL) {
+        rt_panic("Cannot concatenate NULL list");
+    }
+    List* result = LIST_METHOD(__init__)(list->kind);
+    LIST_METHOD(extend)(result, list);
+    LIST_METHOD(extend)(result, other);
+    return result;
+}
+
+List* LIST_METHOD(__iadd__)(List* list, List* other) {
+    LIST_METHOD(extend)(list, other);
+    return list;
+}
+
+int8_t LIST_METHOD(__eq__)(List* list, List* other, int64_t kind) {
+    if (list == NULL || other == NULL) {
+        rt_panic("Cannot compare NULL list");
+    }
+    if (list->len != other->len) {
+        return 0;
+    }
+    for (int64_t i = 0; i < list->len; i++) {
+        if (!__pyc_dict_keys_equal((int8_t)kind, list_load(list, i), list_load(other, i))) {
+            return 0;
+        }
     }
+    return 1;
+}
+
+// list * n: a new list holding the items n times, empty when n <= 0
+List* LIST_METHOD(__mul__)(List* list, int64_t n) {
+    if (list == NULL) {
+        rt_panic("Cannot repeat NULL list");
+    }
+    List* result = LIST_METHOD(__init__)(list->kind);
+    if (n <= 0 || list->len == 0) {
+        return result;
+    }
+    if (n > INT64_MAX / (int64_t)sizeof(int64_t) / list->len) {
+        rt_panic("Repeated list is too long");
+    }
+
+    int64_t total_len = list->len * n;
+    size_t run = (size_t)list->item_size * list->len;
+    char* data = (char*)rt_realloc(result->data, run * n);
+    if (data == NULL) {
+        rt_panic("Failed to allocate memory for repeated list");
+    }
+    for (int64_t i = 0; i < n; i++) {
+        memcpy(data + i * run, list->data, run);
+    }
+    result->data = data;
+    result->len = total_len;
+    result->cap = total_len;
+    for (int64_t i = 0; i < total_len; i++) {
+        __pyc_rc_incref_item(list_load(result, i), result->kind);
+    }
+    return result;
+}
+
+// n * list
+List* LIST_METHOD(__rmul__)(List* list, int64_t n) {
+    return LIST_METHOD(__mul__)(list, n);
+}
+
+// Split a list for `a, *rest, z = list`: check there are enough items for the
+// `before` leading and `after` trailing targets and return the items between
+// them (the starred target's value)
+List* LIST_METHOD(__unpack__)(List* list, int64_t before, int64_t after) {
+    if (list == NULL) {
+        rt_panic("Cannot unpack NULL list");
+    }
+    if (list->len < before + after) {
+        fprintf(stderr, "Error: not enough values to unpack (expected at least %ld, got %ld)\n",
+                before + after, list->len);
+        exit(1);
+    }
+    return LIST_METHOD(__slice__)(list, before, list->len - after, 1);
+}
+
+// Order two sort keys of the given DICT_KIND_*: negative, zero or positive
+static int compare_sort_keys(int64_t a, int64_t b, int64_t kind) {
+    switch (kind) {
+        case DICT_KIND_FLOAT: {
+            double x, y;
+            memcpy(&x, &a, sizeof(x));
+            memcpy(&y, &b, sizeof(y));
+            return (x > y) - (x < y);
+        }
+        case DICT_KIND_STR:
+            if (STR_METHOD(__lt__)((String*)a, (String*)b)) {
+                return -1;
+            }
+            return STR_METHOD(__lt__)((String*)b, (String*)a);
+        default:
+            return (a > b) - (a < b);
+    }
+}
+
+// Merge sort of index runs; stable, so equal keys keep their original order
+static void merge_sort_indices(int64_t* order, int64_t* scratch, int64_t len,
+                               const List* keys, int64_t kind, int sign) {
+    if (len < 2) {
+        return;
+    }
+    int64_t mid = len / 2;
+    merge_sort_indices(order, scratch, mid, keys, kind, sign);
+    merge_sort_indices(order + mid, scratch, len - mid, keys, kind, sign);
+
+    int64_t i = 0, j = mid, k = 0;
+    while (i < mid && j < len) {
+        // Take from the right run only when strictly smaller, keeping stability
+        if (sign * compare_sort_keys(list_load(keys, order[j]), list_load(keys, order[i]), kind) < 0) {
+            scratch[k++] = order[j++];
+        } else {
+            scratch[k++] = order[i++];
+        }
+    }
+    while (i < mid) {
+        scratch[k++] = order[i++];
+    }
+    while (j < len) {
+        scratch[k++] = order[j++];
+    }
+    memcpy(order, scratch, sizeof(int64_t) * len);
+}
+
+List* LIST_METHOD(__argsort__)(List* keys, int64_t kind, int8_t reverse) {
+    if (keys == NULL) {
+        rt_panic("Cannot sort NULL list");
+    }
+    List* order = LIST_METHOD(__init__)(DICT_KIND_INT);
+    for (int64_t i = 0; i < keys->len; i++) {
+        LIST_METHOD(append)(order, i);
+    }
+    int64_t* scratch = (int64_t*)rt_malloc(sizeof(int64_t) * (keys->len > 0 ? keys->len : 1));
+    if (scratch == NULL) {
+        rt_panic("Failed to allocate memory for sort");
+    }
+    merge_sort_indices((int64_t*)order->data, scratch, order->len, keys, kind, reverse ? -1 : 1);
+    rt_free(scratch);
+    return order;
+}
+
+void LIST_METHOD(__permute__)(List* list, List* order) {
+    if (list == NULL || order == NULL) {
+        rt_panic("Cannot permute NULL list");
+    }
+    if (order->len != list->len) {
+        rt_panic("list modified during sort");
+    }
+    int64_t* data = (int64_t*)rt_malloc(sizeof(int64_t) * (list->len > 0 ? list->len : 1));
+    if (data == NULL) {
+        rt_panic("Failed to allocate memory for sort");
+    }
+    for (int64_t i = 0; i < list->len; i++) {
+        data[i] = list_load(list, list_load(order, i));
+    }
+    for (int64_t i = 0; i < list->len; i++) {
+        list_store(list, i, data[i]);
+    }
+    rt_free(data);
+}
+
+void LIST_METHOD(free)(List* list) {
+    __pyc_rc_free(list, list_release);
 }
 
 String* LIST_METHOD(__repr__)(List* list) {
@@ -75,26 +479,37 @@ String* LIST_METHOD(__repr__)(List* list) {
         return STR_METHOD(from_literal)("[]", 2);
     }
 
-    // Max: "[" + 21 chars per int + ", " separators + "]"
-    int64_t max_len = 2 + (21 * list->len) + (2 * (list->len - 1));
-    String* result = (String*)malloc(sizeof(String) + max_len + 1);
-    if (result == NULL) return NULL;
+    // Render every item first so the result can be sized exactly
+    String** parts = (String**)rt_malloc(sizeof(String*) * list->len);
+    if (parts == NULL) {
+        rt_panic("Failed to allocate memory for list repr");
+    }
+    int64_t total = 2 + 2 * (list->len - 1);  // "[" "]" plus ", " separators
+    for (int64_t i = 0; i < list->len; i++) {
+        parts[i] = __pyc_dict_item_repr(list->kind, list_load(list, i));
+        total += parts[i]->len;
+    }
 
+    char* buffer = (char*)rt_malloc(total);
+    if (buffer == NULL) {
+        rt_panic("Failed to allocate memory for list repr");
+    }
     int64_t pos = 0;
-    result->data[pos++] = '[';
-
+    buffer[pos++] = '[';
     for (int64_t i = 0; i < list->len; i++) {
         if (i > 0) {
-            result->data[pos++] = ',';
-            result->data[pos++] = ' ';
+            buffer[pos++] = ',';
+            buffer[pos++] = ' ';
         }
-        pos += snprintf(result->data + pos, 22, "%ld", list->data[i]);
+        memcpy(buffer + pos, parts[i]->data, (size_t)parts[i]->len);
+        pos += parts[i]->len;
+        STR_METHOD(free)(parts[i]);
     }
+    buffer[pos++] = ']';
 
-    result->data[pos++] = ']';
-    result->data[pos] = '\0';
-    result->len = pos;
-
+    String* result = STR_METHOD(from_literal)(buffer, pos);
+    rt_free(buffer);
+    rt_free(parts);
     return result;
 }
 
@@ -107,10 +522,12 @@ String* LIST_METHOD(__str__)(List* list) {
 // ============================================================================
 
 ListIterator* LIST_METHOD(__iter__)(List* list) {
-    ListIterator* iter = (ListIterator*)malloc(sizeof(ListIterator));
+    ListIterator* iter = (ListIterator*)rt_malloc(sizeof(ListIterator));
     if (iter == NULL) {
         rt_panic("Failed to allocate memory for list iterator");
     }
+    // The iterator keeps the list alive until the loop deallocates it
+    __pyc_rc_incref(list);
     iter->list = list;
     iter->index = 0;
     return iter;
@@ -133,9 +550,103 @@ int64_t LIST_ITERATOR_METHOD(__next__)(ListIterator* iter) {
         return 0;
     }
 
-    return iter->list->data[iter->index++];
+    return list_load(iter->list, iter->index++);
 }
 
 void LIST_ITERATOR_METHOD(__dealloc__)(ListIterator* iter) {
-    free(iter);
+    if (iter != NULL) {
+        __pyc_rc_decref(iter->list);
+    }
+    rt_free(iter);
+}
+
+// ============================================================================
+// list[float] and list[bool]
+// The compiler calls these for lists of those items, so the values travel
+// as double and int8_t rather than int64_t slots; they share the storage
+// above (item_size comes from the kind).
+// ============================================================================
+
+static int64_t float_slot(double value) {
+    int64_t slot;
+    memcpy(&slot, &value, sizeof(slot));
+    return slot;
+}
+
+static double slot_float(int64_t slot) {
+    double value;
+    memcpy(&value, &slot, sizeof(value));
+    return value;
+}
+
+void FLOAT_LIST_METHOD(append)(List* list, double value) {
+    LIST_METHOD(append)(list, float_slot(value));
+}
+
+double FLOAT_LIST_METHOD(pop)(List* list, int64_t index) {
+    return slot_float(LIST_METHOD(pop)(list, index));
+}
+
+void FLOAT_LIST_METHOD(insert)(List* list, int64_t index, double value) {
+    LIST_METHOD(insert)(list, index, float_slot(value));
+}
+
+void FLOAT_LIST_METHOD(remove)(List* list, double value, int64_t kind) {
+    LIST_METHOD(remove)(list, float_slot(value), kind);
+}
+
+int64_t FLOAT_LIST_METHOD(index)(List* list, double value, int64_t kind, int64_t start, int64_t stop) {
+    return LIST_METHOD(index)(list, float_slot(value), kind, start, stop);
+}
+
+int64_t FLOAT_LIST_METHOD(count)(List* list, double value, int64_t kind) {
+    return LIST_METHOD(count)(list, float_slot(value), kind);
+}
+
+double FLOAT_LIST_METHOD(__getitem__)(List* list, int64_t index) {
+    return slot_float(LIST_METHOD(__getitem__)(list, index));
+}
+
+void FLOAT_LIST_METHOD(__setitem__)(List* list, int64_t index, double value) {
+    LIST_METHOD(__setitem__)(list, index, float_slot(value));
+}
+
+double FLOAT_LIST_ITERATOR_METHOD(__next__)(ListIterator* iter) {
+    return slot_float(LIST_ITERATOR_METHOD(__next__)(iter));
+}
+
+void BOOL_LIST_METHOD(append)(List* list, int8_t value) {
+    LIST_METHOD(append)(list, value != 0);
+}
+
+int8_t BOOL_LIST_METHOD(pop)(List* list, int64_t index) {
+    return (int8_t)LIST_METHOD(pop)(list, index);
+}
+
+void BOOL_LIST_METHOD(insert)(List* list, int64_t index, int8_t value) {
+    LIST_METHOD(insert)(list, index, value != 0);
+}
+
+void BOOL_LIST_METHOD(remove)(List* list, int8_t value, int64_t kind) {
+    LIST_METHOD(remove)(list, value != 0, kind);
+}
+
+int64_t BOOL_LIST_METHOD(index)(List* list, int8_t value, int64_t kind, int64_t start, int64_t stop) {
+    return LIST_METHOD(index)(list, value != 0, kind, start, stop);
+}
+
+int64_t BOOL_LIST_METHOD(count)(List* list, int8_t value, int64_t kind) {
+    return LIST_METHOD(count)(list, value != 0, kind);
+}
+
+int8_t BOOL_LIST_METHOD(__getitem__)(List* list, int64_t index) {
+    return (int8_t)LIST_METHOD(__getitem__)(list, index);
+}
+
+void BOOL_LIST_METHOD(__setitem__)(List* list, int64_t index, int8_t value) {
+    LIST_METHOD(__setitem__)(list, index, value != 0);
+}
+
+int8_t BOOL_LIST_ITERATOR_METHOD(__next__)(ListIterator* iter) {
+    return (int8_t)LIST_ITERATOR_METHOD(__next__)(iter);
 }
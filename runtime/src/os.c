@@ -0,0 +1,70 @@
+#include "runtime.h"
+#include <errno.h>
+#include <stdlib.h>
+#include <string.h>
+#include <unistd.h>
+
+// The process environment, as "NAME=value" strings
+extern char** environ;
+
+// ============================================================================
+// os module
+// ============================================================================
+
+// os.environ, built from the environment on first use; os.getenv reads it too,
+// so assignments to os.environ are seen by later os.getenv calls
+static Dict* environ_dict = NULL;
+
+Dict* __pyc_os_environ(void) {
+    if (environ_dict == NULL) {
+        environ_dict = DICT_METHOD(__init__)(DICT_KIND_STR, DICT_KIND_STR);
+        for (char** entry = environ; entry != NULL && *entry != NULL; entry++) {
+            const char* eq = strchr(*entry, '=');
+            if (eq == NULL) {
+                continue;
+            }
+            String* name = STR_METHOD(from_literal)(*entry, (int64_t)(eq - *entry));
+            String* value = STR_METHOD(from_literal)(eq + 1, (int64_t)strlen(eq + 1));
+            DICT_METHOD(__setitem__)(environ_dict, (int64_t)name, (int64_t)value);
+            // Kept for the whole run
+            __pyc_rc_untrack(name);
+            __pyc_rc_untrack(value);
+        }
+    }
+    return environ_dict;
+}
+
+String* __pyc_os_getenv(String* key, String* default_value) {
+    Dict* env = __pyc_os_environ();
+    if (!DICT_METHOD(__contains__)(env, (int64_t)key)) {
+        return default_value;
+    }
+    return (String*)DICT_METHOD(__getitem__)(env, (int64_t)key);
+}
+
+String* __pyc_os_getcwd(void) {
+    size_t cap = 256;
+    for (;;) {
+        char* buf = (char*)rt_malloc(cap);
+        if (buf == NULL) {
+            rt_panic("Failed to allocate memory for getcwd");
+        }
+        if (getcwd(buf, cap) != NULL) {
+            String* cwd = STR_METHOD(from_literal)(buf, (int64_t)strlen(buf));
+            rt_free(buf);
+            return cwd;
+        }
+        int err = errno;
+        rt_free(buf);
+        if (err != ERANGE) {
+            char message[128];
+            snprintf(message, sizeof(message), "[Errno %d] %s", err, strerror(err));
+            __pyc_raise(__pyc_exception_new(
+                STR_METHOD(from_literal)("OSError", 7),
+                STR_METHOD(from_literal)(message, (int64_t)strlen(message)),
+                STR_METHOD(from_literal)("Exception", 9)));
+            return NULL;
+        }
+        cap *= 2;
+    }
+}
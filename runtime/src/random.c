@@ -0,0 +1,113 @@
+#include "runtime.h"
+#include <string.h>
+#include <time.h>
+#include <unistd.h>
+
+// ============================================================================
+// Generator: xoshiro256** seeded through splitmix64
+// ============================================================================
+
+static uint64_t state[4];
+static int seeded = 0;
+
+static uint64_t rotl(uint64_t x, int k) {
+    return (x << k) | (x >> (64 - k));
+}
+
+static uint64_t splitmix64(uint64_t* x) {
+    uint64_t z = (*x += 0x9e3779b97f4a7c15ULL);
+    z = (z ^ (z >> 30)) * 0xbf58476d1ce4e5b9ULL;
+    z = (z ^ (z >> 27)) * 0x94d049bb133111ebULL;
+    return z ^ (z >> 31);
+}
+
+static void seed_state(uint64_t seed) {
+    for (int i = 0; i < 4; i++) {
+        state[i] = splitmix64(&seed);
+    }
+    seeded = 1;
+}
+
+// Seed from the clock and process id, as random.seed() does with no argument
+static void seed_from_system(void) {
+    struct timespec ts;
+    clock_gettime(CLOCK_REALTIME, &ts);
+    seed_state(((uint64_t)ts.tv_sec * 1000000000ULL + (uint64_t)ts.tv_nsec) ^
+               ((uint64_t)getpid() << 32));
+}
+
+static uint64_t next_u64(void) {
+    if (!seeded) {
+        seed_from_system();
+    }
+    uint64_t result = rotl(state[1] * 5, 7) * 9;
+    uint64_t t = state[1] << 17;
+    state[2] ^= state[0];
+    state[3] ^= state[1];
+    state[1] ^= state[2];
+    state[0] ^= state[3];
+    state[2] ^= t;
+    state[3] = rotl(state[3], 45);
+    return result;
+}
+
+// Uniform integer in [0, n) for n > 0, rejecting the biased tail of the range
+static uint64_t next_below(uint64_t n) {
+    uint64_t limit = UINT64_MAX - UINT64_MAX % n;
+    uint64_t r;
+    do {
+        r = next_u64();
+    } while (r >= limit);
+    return r % n;
+}
+
+static void raise_random_error(const char* type_name, const char* parent_types, String* message) {
+    __pyc_raise(__pyc_exception_new(
+        STR_METHOD(from_literal)(type_name, (int64_t)strlen(type_name)), message,
+        STR_METHOD(from_literal)(parent_types, (int64_t)strlen(parent_types))));
+}
+
+// ============================================================================
+// random module
+// ============================================================================
+
+void __pyc_random_seed(int64_t seed) {
+    seed_state((uint64_t)seed);
+}
+
+void __pyc_random_reseed(void) {
+    seed_from_system();
+}
+
+// random.random(): a float in [0.0, 1.0) from the top 53 bits
+double __pyc_random_random(void) {
+    return (double)(next_u64() >> 11) * (1.0 / 9007199254740992.0);
+}
+
+int64_t __pyc_random_randint(int64_t a, int64_t b) {
+    if (b < a) {
+        char message[96];
+        int len = snprintf(message, sizeof(message), "empty range in randint(%lld, %lld)",
+                           (long long)a, (long long)b);
+        if (len < 0 || len >= (int)sizeof(message)) {
+            len = (int)strlen(message);
+        }
+        raise_random_error("ValueError", "Exception", STR_METHOD(from_literal)(message, len));
+        return 0;
+    }
+    // b - a + 1 wraps to 0 only for the full int64 range, where any value will do
+    uint64_t span = (uint64_t)b - (uint64_t)a + 1;
+    uint64_t offset = span == 0 ? next_u64() : next_below(span);
+    return (int64_t)((uint64_t)a + offset);
+}
+
+// The index random.choice() picks from a sequence of length `len`
+int64_t __pyc_random_index(int64_t len) {
+    if (len <= 0) {
+        const char* message = "Cannot choose from an empty sequence";
+        raise_random_error("IndexError", "LookupError,Exception",
+                           STR_METHOD(from_literal)(message, (int64_t)strlen(message)));
+        return 0;
+    }
+    return (int64_t)next_below((uint64_t)len);
+}
@@ -3,7 +3,7 @@
 #include <stdio.h>
 
 Range* __pyc___builtin___range_1(int64_t stop) {
-    Range* r = (Range*)malloc(sizeof(Range));
+    Range* r = (Range*)rt_malloc(sizeof(Range));
     if (r == NULL) {
         rt_panic("Failed to allocate memory for range");
     }
@@ -15,7 +15,7 @@ Range* __pyc___builtin___range_1(int64_t stop) {
 }
 
 Range* __pyc___builtin___range_2(int64_t start, int64_t stop) {
-    Range* r = (Range*)malloc(sizeof(Range));
+    Range* r = (Range*)rt_malloc(sizeof(Range));
     if (r == NULL) {
         rt_panic("Failed to allocate memory for range");
     }
@@ -30,7 +30,7 @@ Range* __pyc___builtin___range_3(int64_t start, int64_t stop, int64_t step) {
     if (step == 0) {
         rt_panic("range() step argument must not be zero");
     }
-    Range* r = (Range*)malloc(sizeof(Range));
+    Range* r = (Range*)rt_malloc(sizeof(Range));
     if (r == NULL) {
         rt_panic("Failed to allocate memory for range");
     }
@@ -65,7 +65,7 @@ int64_t RANGE_METHOD(__next__)(Range* r) {
 }
 
 void RANGE_METHOD(__dealloc__)(Range* r) {
-    free(r);
+    rt_free(r);
 }
 
 int64_t RANGE_METHOD(__len__)(Range* r) {
@@ -0,0 +1,230 @@
+#include "runtime.h"
+#include <stdlib.h>
+#include <string.h>
+
+// ============================================================================
+// Side table of tracked objects
+// ============================================================================
+
+static RcEntry* rc_slots = NULL;
+static int64_t rc_slot_count = 0;  // Always a power of two (or 0 before first use)
+static int64_t rc_live = 0;        // Tracked objects, i.e. used slots
+
+static uint64_t rc_hash(void* obj) {
+    // splitmix64 finalizer; the low bits of heap pointers are mostly zero
+    uint64_t x = (uint64_t)(uintptr_t)obj;
+    x ^= x >> 30;
+    x *= 0xbf58476d1ce4e5b9ULL;
+    x ^= x >> 27;
+    x *= 0x94d049bb133111ebULL;
+    x ^= x >> 31;
+    return x;
+}
+
+// The slot holding obj, or the empty slot where it belongs
+static int64_t rc_find_slot(void* obj) {
+    uint64_t mask = (uint64_t)rc_slot_count - 1;
+    uint64_t i = rc_hash(obj) & mask;
+    while (rc_slots[i].obj != NULL && rc_slots[i].obj != obj) {
+        i = (i + 1) & mask;
+    }
+    return (int64_t)i;
+}
+
+static RcEntry* rc_lookup(void* obj) {
+    if (obj == NULL || rc_live == 0) {
+        return NULL;
+    }
+    RcEntry* entry = &rc_slots[rc_find_slot(obj)];
+    return entry->obj != NULL ? entry : NULL;
+}
+
+static void rc_grow(void) {
+    RcEntry* old_slots = rc_slots;
+    int64_t old_count = rc_slot_count;
+    rc_slot_count = old_count == 0 ? 64 : old_count * 2;
+    rc_slots = (RcEntry*)rt_calloc((size_t)rc_slot_count, sizeof(RcEntry));
+    if (rc_slots == NULL) {
+        rt_panic("Failed to allocate memory for reference counts");
+    }
+    for (int64_t i = 0; i < old_count; i++) {
+        if (old_slots[i].obj != NULL) {
+            rc_slots[rc_find_slot(old_slots[i].obj)] = old_slots[i];
+        }
+    }
+    rt_free(old_slots);
+}
+
+// Remove a tracked object; backward-shift deletion: later slots in the probe
+// chain move into the hole unless that would put them before their home slot
+static void rc_remove(RcEntry* entry) {
+    uint64_t mask = (uint64_t)rc_slot_count - 1;
+    uint64_t hole = (uint64_t)(entry - rc_slots);
+    uint64_t i = (hole + 1) & mask;
+    while (rc_slots[i].obj != NULL) {
+        uint64_t home = rc_hash(rc_slots[i].obj) & mask;
+        if (((i - home) & mask) >= ((i - hole) & mask)) {
+            rc_slots[hole] = rc_slots[i];
+            hole = i;
+        }
+        i = (i + 1) & mask;
+    }
+    rc_slots[hole].obj = NULL;
+    rc_live--;
+}
+
+// Untrack obj, let it release what it holds, then free it. It is untracked
+// first so that references back to it found while releasing are ignored.
+static void rc_free(RcEntry* entry) {
+    void* obj = entry->obj;
+    RcRelease release = entry->release;
+    rc_remove(entry);
+    if (release != NULL) {
+        release(obj);
+    }
+    rt_free(obj);
+}
+
+static void rc_track(void* obj, int64_t size, RcRelease release, uint8_t builtin) {
+    // Keep the table at most half full
+    if ((rc_live + 1) * 2 > rc_slot_count) {
+        rc_grow();
+    }
+    RcEntry* entry = &rc_slots[rc_find_slot(obj)];
+    entry->obj = obj;
+    entry->count = 0;
+    entry->release = release;
+    entry->size = size;
+    entry->pinned = 0;
+    entry->marked = 0;
+    entry->builtin = builtin;
+    rc_live++;
+}
+
+// ============================================================================
+// Count operations
+// ============================================================================
+
+void* __pyc_rc_new(int64_t size, RcRelease release) {
+    __pyc_gc_maybe_collect();
+    void* obj = rt_calloc(1, (size_t)(size > 0 ? size : 1));
+    if (obj == NULL) {
+        rt_panic("Failed to allocate memory for class instance");
+    }
+    rc_track(obj, size, release, 0);
+    __pyc_gc_track_new(obj);
+    return obj;
+}
+
+void* __pyc_rc_alloc(int64_t size, RcRelease release) {
+    void* obj = rt_malloc((size_t)(size > 0 ? size : 1));
+    if (obj == NULL) {
+        rt_panic("Failed to allocate memory");
+    }
+    if (__pyc_refcount) {
+        rc_track(obj, size, release, 1);
+    }
+    return obj;
+}
+
+void __pyc_rc_free(void* obj, RcRelease release) {
+    if (obj == NULL) {
+        return;
+    }
+    __pyc_rc_untrack(obj);
+    if (release != NULL) {
+        release(obj);
+    }
+    rt_free(obj);
+}
+
+void __pyc_rc_incref(void* obj) {
+    RcEntry* entry = rc_lookup(obj);
+    if (entry != NULL) {
+        entry->count++;
+    }
+}
+
+void __pyc_rc_decref(void* obj) {
+    RcEntry* entry = rc_lookup(obj);
+    if (entry != NULL && --entry->count <= 0) {
+        rc_free(entry);
+    }
+}
+
+void __pyc_rc_unown(void* obj) {
+    RcEntry* entry = rc_lookup(obj);
+    if (entry != NULL && entry->count > 0) {
+        entry->count--;
+    }
+}
+
+void __pyc_rc_release_unowned(void* obj) {
+    RcEntry* entry = rc_lookup(obj);
+    if (entry != NULL && entry->count <= 0) {
+        rc_free(entry);
+    }
+}
+
+// Only strings, lists and instances are tracked; the other pointer kinds are
+// never found in the table, and ints, floats and bools must not be looked up
+static int rc_item_counted(int64_t kind) {
+    return __pyc_refcount && kind >= DICT_KIND_STR;
+}
+
+void __pyc_rc_incref_item(int64_t item, int64_t kind) {
+    if (kind < DICT_KIND_STR) {
+        return;
+    }
+    // Without --refcount only traced instances are in the table
+    RcEntry* entry = rc_lookup((void*)item);
+    if (entry != NULL) {
+        entry->count++;
+        entry->pinned |= !entry->builtin;
+    }
+}
+
+void __pyc_rc_decref_item(int64_t item, int64_t kind) {
+    if (rc_item_counted(kind)) {
+        __pyc_rc_decref((void*)item);
+    }
+}
+
+void __pyc_rc_unown_item(int64_t item, int64_t kind) {
+    if (rc_item_counted(kind)) {
+        __pyc_rc_unown((void*)item);
+    }
+}
+
+// ============================================================================
+// Table access for the tracing collector
+// ============================================================================
+
+RcEntry* __pyc_rc_entry(void* obj) {
+    return rc_lookup(obj);
+}
+
+RcEntry* __pyc_rc_slots(int64_t* slot_count) {
+    *slot_count = rc_slot_count;
+    return rc_slots;
+}
+
+int64_t __pyc_rc_live(void) {
+    return rc_live;
+}
+
+void __pyc_rc_untrack(void* obj) {
+    RcEntry* entry = rc_lookup(obj);
+    if (entry != NULL) {
+        rc_remove(entry);
+    }
+}
+
+void __pyc_rc_leak_check(void) {
+    if (rc_live == 0) {
+        return;
+    }
+    fflush(stdout);
+    fprintf(stderr, "leak check: %ld object%s still alive\n", rc_live, rc_live == 1 ? "" : "s");
+    exit(1);
+}
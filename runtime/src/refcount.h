@@ -0,0 +1,69 @@
+#ifndef REFCOUNT_H
+#define REFCOUNT_H
+
+#include "types.h"
+
+// ============================================================================
+// Reference counting (pycc --refcount)
+// Class instances (__pyc_rc_new), strings and lists (__pyc_rc_alloc) are
+// tracked in a side table, so the count operations accept any pointer:
+// untracked objects (literals, dicts, tuples, NULL) are left alone. The
+// compiler counts the references held by locals, parameters, globals and
+// fields; containers count the references their items hold. What the runtime
+// keeps for the whole run (sys.argv, exception messages) is untracked.
+// The tracing collector (gc.c, pycc --gc=tracing) works on the same table.
+// ============================================================================
+
+// Whether the program counts references; set by the compiler
+extern const int8_t __pyc_refcount;
+
+// Frees what an object holds (an instance's counted fields, a list's items);
+// emitted per class for instances
+typedef void (*RcRelease)(void* obj);
+
+// A tracked object
+typedef struct {
+    void* obj;            // NULL = empty slot
+    int64_t count;
+    RcRelease release;
+    int64_t size;
+    uint8_t pinned;       // Stored in a container: a root of the collector
+    uint8_t marked;       // Reached in the current collection (gc.c)
+    uint8_t builtin;      // A string or list: freed by counting, never collected
+} RcEntry;
+
+// A zeroed, tracked instance with no references yet
+void* __pyc_rc_new(int64_t size, RcRelease release);
+
+// A string or list: tracked with the release function when the program counts
+// references, plain memory otherwise
+void* __pyc_rc_alloc(int64_t size, RcRelease release);
+// Free a string or list at once, running its release function
+void __pyc_rc_free(void* obj, RcRelease release);
+
+void __pyc_rc_incref(void* obj);
+// Drop a reference, freeing the object when none are left
+void __pyc_rc_decref(void* obj);
+// Drop a reference without freeing: a returned value is handed to the caller
+// with the references its callee held removed
+void __pyc_rc_unown(void* obj);
+// Free an object nothing refers to (a discarded call result)
+void __pyc_rc_release_unowned(void* obj);
+
+// The count operations for a container item of the given DICT_KIND_*; ints,
+// floats and bools are left alone. An instance stored in a container is
+// pinned as well, since the collector does not look into containers.
+void __pyc_rc_incref_item(int64_t item, int64_t kind);
+void __pyc_rc_decref_item(int64_t item, int64_t kind);
+void __pyc_rc_unown_item(int64_t item, int64_t kind);
+
+// Table access for the tracing collector
+RcEntry* __pyc_rc_entry(void* obj);                 // NULL if obj is not tracked
+RcEntry* __pyc_rc_slots(int64_t* slot_count);       // Empty slots have obj == NULL
+int64_t __pyc_rc_live(void);
+void __pyc_rc_untrack(void* obj);                   // Forget obj without freeing it
+
+// pycc --leak-check: report objects still alive at exit and fail the run
+void __pyc_rc_leak_check(void);
+
+#endif // REFCOUNT_H
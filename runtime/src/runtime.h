@@ -9,28 +9,85 @@
 
 #include "types.h"
 #include "io.h"
+#include "alloc.h"
 #include "str.h"
 #include "bytes.h"
 #include "exception.h"
+#include "embed.h"
+#include "refcount.h"
+#include "gc.h"
 
 // ============================================================================
-// List structure for list[int]
+// List structure for list[T]
+// kind is the items' DICT_KIND_* so the runtime can print them. Items are
+// int64_t slots (floats by their bit pattern), except that bool items take a
+// byte each; the LIST_METHOD functions pass and return every item as a slot.
 // ============================================================================
 
 typedef struct {
-    int64_t* data;
+    void* data;
     int64_t len;
     int64_t cap;
+    int8_t kind;
+    int8_t item_size;  // Bytes per item: 1 for bool, 8 otherwise
 } List;
 
-List* LIST_METHOD(__init__)(void);
+List* LIST_METHOD(__init__)(int64_t kind);
 void LIST_METHOD(append)(List* list, int64_t value);
+// pop(index) removes and returns an item; insert clamps the index like CPython
+int64_t LIST_METHOD(pop)(List* list, int64_t index);
+void LIST_METHOD(insert)(List* list, int64_t index, int64_t value);
+// Searches compare items by their DICT_KIND_* (kind is passed by the compiler)
+void LIST_METHOD(remove)(List* list, int64_t value, int64_t kind);
+int64_t LIST_METHOD(index)(List* list, int64_t value, int64_t kind, int64_t start, int64_t stop);
+int64_t LIST_METHOD(count)(List* list, int64_t value, int64_t kind);
+void LIST_METHOD(extend)(List* list, List* other);
+void LIST_METHOD(reverse)(List* list);
+void LIST_METHOD(clear)(List* list);
 int64_t LIST_METHOD(__getitem__)(List* list, int64_t index);
 void LIST_METHOD(__setitem__)(List* list, int64_t index, int64_t value);
+void LIST_METHOD(__delitem__)(List* list, int64_t index);
 int64_t LIST_METHOD(__len__)(List* list);
+List* LIST_METHOD(__slice__)(List* list, int64_t start, int64_t stop, int64_t step);
+List* LIST_METHOD(__unpack__)(List* list, int64_t before, int64_t after);
+// list + other: a new list; list += other extends list and returns it
+List* LIST_METHOD(__add__)(List* list, List* other);
+List* LIST_METHOD(__iadd__)(List* list, List* other);
+// list == other, comparing items by their DICT_KIND_*
+int8_t LIST_METHOD(__eq__)(List* list, List* other, int64_t kind);
+// list * n and n * list: a new list with the items repeated n times
+List* LIST_METHOD(__mul__)(List* list, int64_t n);
+List* LIST_METHOD(__rmul__)(List* list, int64_t n);
+// Stable sort order of keys (indices into the list); kind is the keys' DICT_KIND_*
+List* LIST_METHOD(__argsort__)(List* keys, int64_t kind, int8_t reverse);
+// Reorder list in place so that item i is the old item order[i]
+void LIST_METHOD(__permute__)(List* list, List* order);
 void LIST_METHOD(free)(List* list);
 String* LIST_METHOD(__str__)(List* list);
 String* LIST_METHOD(__repr__)(List* list);
+// Item index as an int64_t slot, without bounds checks (for other runtime files)
+int64_t __pyc_list_item(List* list, int64_t index);
+// Empty list of the given DICT_KIND_* items in a slot the compiler placed on
+// the stack; a non-NULL data is the buffer of the slot's previous list, reused
+void __pyc_list_init_in(List* list, int64_t kind);
+
+// list[float] and list[bool] methods taking and returning their items typed
+void FLOAT_LIST_METHOD(append)(List* list, double value);
+double FLOAT_LIST_METHOD(pop)(List* list, int64_t index);
+void FLOAT_LIST_METHOD(insert)(List* list, int64_t index, double value);
+void FLOAT_LIST_METHOD(remove)(List* list, double value, int64_t kind);
+int64_t FLOAT_LIST_METHOD(index)(List* list, double value, int64_t kind, int64_t start, int64_t stop);
+int64_t FLOAT_LIST_METHOD(count)(List* list, double value, int64_t kind);
+double FLOAT_LIST_METHOD(__getitem__)(List* list, int64_t index);
+void FLOAT_LIST_METHOD(__setitem__)(List* list, int64_t index, double value);
+void BOOL_LIST_METHOD(append)(List* list, int8_t value);
+int8_t BOOL_LIST_METHOD(pop)(List* list, int64_t index);
+void BOOL_LIST_METHOD(insert)(List* list, int64_t index, int8_t value);
+void BOOL_LIST_METHOD(remove)(List* list, int8_t value, int64_t kind);
+int64_t BOOL_LIST_METHOD(index)(List* list, int8_t value, int64_t kind, int64_t start, int64_t stop);
+int64_t BOOL_LIST_METHOD(count)(List* list, int8_t value, int64_t kind);
+int8_t BOOL_LIST_METHOD(__getitem__)(List* list, int64_t index);
+void BOOL_LIST_METHOD(__setitem__)(List* list, int64_t index, int8_t value);
 
 // ============================================================================
 // ListIterator structure
@@ -45,6 +102,124 @@ ListIterator* LIST_METHOD(__iter__)(List* list);
 ListIterator* LIST_ITERATOR_METHOD(__iter__)(ListIterator* iter);
 int64_t LIST_ITERATOR_METHOD(__next__)(ListIterator* iter);
 void LIST_ITERATOR_METHOD(__dealloc__)(ListIterator* iter);
+double FLOAT_LIST_ITERATOR_METHOD(__next__)(ListIterator* iter);
+int8_t BOOL_LIST_ITERATOR_METHOD(__next__)(ListIterator* iter);
+
+// bytes.split(sep) (ValueError for an empty sep), bytes.split() on runs of
+// ASCII whitespace, and sep.join(list)
+List* BYTES_METHOD(split)(Bytes* b, Bytes* sep);
+List* BYTES_METHOD(__split_whitespace__)(Bytes* b);
+Bytes* BYTES_METHOD(join)(Bytes* sep, List* parts);
+
+// ============================================================================
+// Tuple structure for tuple[A, B, ...]
+// Elements are int64_t slots; kinds points to a constant table with the
+// DICT_KIND_* of each element so the runtime can hash, compare and print it.
+// ============================================================================
+
+typedef struct {
+    int64_t len;
+    const int8_t* kinds;
+    int64_t data[];
+} Tuple;
+
+Tuple* TUPLE_METHOD(__init__)(int64_t len, const int8_t* kinds);
+int64_t TUPLE_METHOD(__getitem__)(Tuple* tuple, int64_t index);
+int64_t TUPLE_METHOD(__len__)(Tuple* tuple);
+String* TUPLE_METHOD(__str__)(Tuple* tuple);
+String* TUPLE_METHOD(__repr__)(Tuple* tuple);
+
+// Fill a slot while building a tuple (tuples are immutable once built)
+void __pyc_tuple_set(Tuple* tuple, int64_t index, int64_t value);
+
+// ============================================================================
+// Dict structure for dict[K, V]
+// Keys and values are stored as int64_t (like List); the kinds tell the
+// runtime how to hash, compare and print them.
+// ============================================================================
+
+#define DICT_KIND_INT    0
+#define DICT_KIND_FLOAT  1
+#define DICT_KIND_BOOL   2
+#define DICT_KIND_STR    3
+#define DICT_KIND_BYTES  4
+#define DICT_KIND_OBJECT 5  // Any other object, hashed by identity
+#define DICT_KIND_TUPLE  6  // Tuple, hashed and compared element-wise
+// Containers (unhashable, compared by identity) print with their own kinds
+#define DICT_KIND_LIST      7
+#define DICT_KIND_DICT      8
+#define DICT_KIND_SET       9
+#define DICT_KIND_FROZENSET 10
+
+// A deleted entry stays in keys/values, and its slot is marked deleted, until
+// the table is rebuilt as it fills up or the entries are read as a whole
+// (__pyc_dict_compact), so that deleting is O(1)
+typedef struct {
+    int64_t* keys;        // Keys in insertion order
+    int64_t* values;      // Values, parallel to keys
+    int64_t len;          // Entries in the dict
+    int64_t cap;          // Capacity of keys/values
+    int64_t* slots;       // Open-addressing table of entry indices (-1 = empty, -2 = deleted)
+    int64_t slot_count;   // Always a power of two
+    int64_t used;         // Entries in keys/values, deleted ones included
+    int8_t key_kind;
+    int8_t value_kind;
+} Dict;
+
+Dict* DICT_METHOD(__init__)(int64_t key_kind, int64_t value_kind);
+int64_t DICT_METHOD(__getitem__)(Dict* dict, int64_t key);
+void DICT_METHOD(__setitem__)(Dict* dict, int64_t key, int64_t value);
+void DICT_METHOD(__delitem__)(Dict* dict, int64_t key);
+int8_t DICT_METHOD(__contains__)(Dict* dict, int64_t key);
+int64_t DICT_METHOD(__len__)(Dict* dict);
+List* DICT_METHOD(keys)(Dict* dict);
+List* DICT_METHOD(values)(Dict* dict);
+List* DICT_METHOD(items)(Dict* dict);
+ListIterator* DICT_METHOD(__iter__)(Dict* dict);
+String* DICT_METHOD(__str__)(Dict* dict);
+String* DICT_METHOD(__repr__)(Dict* dict);
+
+// Shared with set.c, tuple.c and list.c: compare two stored keys/values, remove
+// a key (returns 0 if absent), drop the deleted entries so that keys/values
+// hold the first len, and repr a stored key/value
+int8_t __pyc_dict_keys_equal(int8_t kind, int64_t a, int64_t b);
+int8_t __pyc_dict_remove(Dict* dict, int64_t key);
+void __pyc_dict_compact(Dict* dict);
+String* __pyc_dict_item_repr(int8_t kind, int64_t value);
+
+// ============================================================================
+// Set and frozenset (a Dict whose values are unused)
+// ============================================================================
+
+Dict* SET_METHOD(__init__)(int64_t elem_kind);
+void SET_METHOD(add)(Dict* set, int64_t elem);
+void SET_METHOD(remove)(Dict* set, int64_t elem);
+void SET_METHOD(discard)(Dict* set, int64_t elem);
+int8_t SET_METHOD(__contains__)(Dict* set, int64_t elem);
+int64_t SET_METHOD(__len__)(Dict* set);
+Dict* SET_METHOD(__or__)(Dict* a, Dict* b);
+Dict* SET_METHOD(__and__)(Dict* a, Dict* b);
+Dict* SET_METHOD(__sub__)(Dict* a, Dict* b);
+Dict* SET_METHOD(__xor__)(Dict* a, Dict* b);
+Dict* SET_METHOD(union)(Dict* a, Dict* b);
+Dict* SET_METHOD(intersection)(Dict* a, Dict* b);
+void SET_METHOD(update)(Dict* set, Dict* other);
+void SET_METHOD(update_from_list)(Dict* set, List* list);
+ListIterator* SET_METHOD(__iter__)(Dict* set);
+String* SET_METHOD(__str__)(Dict* set);
+String* SET_METHOD(__repr__)(Dict* set);
+
+int8_t FROZENSET_METHOD(__contains__)(Dict* set, int64_t elem);
+int64_t FROZENSET_METHOD(__len__)(Dict* set);
+Dict* FROZENSET_METHOD(__or__)(Dict* a, Dict* b);
+Dict* FROZENSET_METHOD(__and__)(Dict* a, Dict* b);
+Dict* FROZENSET_METHOD(__sub__)(Dict* a, Dict* b);
+Dict* FROZENSET_METHOD(__xor__)(Dict* a, Dict* b);
+Dict* FROZENSET_METHOD(union)(Dict* a, Dict* b);
+Dict* FROZENSET_METHOD(intersection)(Dict* a, Dict* b);
+ListIterator* FROZENSET_METHOD(__iter__)(Dict* set);
+String* FROZENSET_METHOD(__str__)(Dict* set);
+String* FROZENSET_METHOD(__repr__)(Dict* set);
 
 // ============================================================================
 // Range structure
@@ -68,6 +243,76 @@ void RANGE_METHOD(__dealloc__)(Range* r);
 String* RANGE_METHOD(__str__)(Range* r);
 String* RANGE_METHOD(__repr__)(Range* r);
 
+// ============================================================================
+// File structure for open()
+// Reads are buffered; writes go straight to the descriptor. Files embedded
+// with pycc --embed are opened for reading from a copy of their contents.
+// sys.stdout and sys.stderr write through stdio instead, so their output
+// stays in order with print().
+// ============================================================================
+
+typedef struct {
+    int fd;            // -1 once closed, and for embedded files
+    int8_t readable;
+    int8_t writable;
+    int8_t closed;
+    int8_t console;    // One of the sys standard streams
+    String* path;      // For error messages
+    char* buf;         // Read buffer; unread data is buf[pos..len)
+    int64_t buf_pos;
+    int64_t buf_len;
+    int64_t buf_cap;
+} File;
+
+File* __pyc___builtin___open(String* path, String* mode);
+
+String* FILE_METHOD(read)(File* file);
+String* FILE_METHOD(readline)(File* file);
+int64_t FILE_METHOD(write)(File* file, String* s);
+void FILE_METHOD(close)(File* file);
+File* FILE_METHOD(__enter__)(File* file);
+void FILE_METHOD(__exit__)(File* file);
+
+// ============================================================================
+// sys module
+// ============================================================================
+
+// Called from main() before any module code runs
+void __pyc_sys_init(int32_t argc, char** argv);
+
+List* __pyc_sys_argv(void);
+void __pyc_sys_exit(int64_t code);
+void __pyc_sys_exit_message(String* message);
+File* __pyc_sys_stdin(void);
+File* __pyc_sys_stdout(void);
+File* __pyc_sys_stderr(void);
+
+// ============================================================================
+// os module
+// ============================================================================
+
+Dict* __pyc_os_environ(void);
+String* __pyc_os_getenv(String* key, String* default_value);
+String* __pyc_os_getcwd(void);
+
+// ============================================================================
+// time module
+// ============================================================================
+
+double __pyc_time_time(void);
+double __pyc_time_monotonic(void);
+void __pyc_time_sleep(double seconds);
+
+// ============================================================================
+// random module
+// ============================================================================
+
+void __pyc_random_seed(int64_t seed);
+void __pyc_random_reseed(void);
+double __pyc_random_random(void);
+int64_t __pyc_random_randint(int64_t a, int64_t b);
+int64_t __pyc_random_index(int64_t len);
+
 // ============================================================================
 // ByteArray structure
 // ============================================================================
@@ -83,6 +328,17 @@ void BYTEARRAY_METHOD(append)(ByteArray* ba, int64_t value);
 int64_t BYTEARRAY_METHOD(__getitem__)(ByteArray* ba, int64_t index);
 void BYTEARRAY_METHOD(__setitem__)(ByteArray* ba, int64_t index, int64_t value);
 int64_t BYTEARRAY_METHOD(__len__)(ByteArray* ba);
+ByteArray* BYTEARRAY_METHOD(__slice__)(ByteArray* ba, int64_t start, int64_t stop, int64_t step);
+// ba[start:stop:step] = value; a plain slice may change the length, an
+// extended one must match it (ValueError otherwise)
+void BYTEARRAY_METHOD(__setslice__)(ByteArray* ba, int64_t start, int64_t stop, int64_t step,
+                                    Bytes* value);
+void BYTEARRAY_METHOD(extend)(ByteArray* ba, Bytes* value);
+// ba += value extends ba in place and returns it
+ByteArray* BYTEARRAY_METHOD(__iadd__)(ByteArray* ba, Bytes* value);
+// bytearray(b) and bytes(ba): copies
+ByteArray* __pyc___builtin___bytearray_from_bytes(Bytes* value);
+Bytes* __pyc___builtin___bytes_from_bytearray(ByteArray* ba);
 void BYTEARRAY_METHOD(free)(ByteArray* ba);
 String* BYTEARRAY_METHOD(__str__)(ByteArray* ba);
 String* BYTEARRAY_METHOD(__repr__)(ByteArray* ba);
@@ -99,12 +355,32 @@ void* class_new(int64_t size);
 
 void write_str_impl(const char* str);
 void write_string_impl(String* str);
+void write_stderr_string_impl(String* str);
 void write_char_impl(char c);
 void write_newline_impl(void);
 void write_space_impl(void);
 char* int64_to_str_impl(int64_t value, char* buffer);
+// repr() of a float, the shortest form that round-trips; buffer of 32 bytes
+char* float_to_str_impl(double value, char* buffer);
+// hex()/oct()/bin() formatting for base 16, 8 or 2, with the 0x/0o/0b prefix
+String* int64_to_str_radix_impl(int64_t value, int64_t base);
 
 void __pyc___builtin___int___print__(int64_t value);
 void __pyc___builtin___bool___print__(int8_t value);
 
+String* __pyc___builtin___int___str__(int64_t value);
+String* __pyc___builtin___bool___str__(int8_t value);
+String* __pyc___builtin___float___str__(double value);
+
+int64_t __pyc___builtin___int_from_str(String* s, int64_t base);
+int64_t __pyc___builtin___int_from_float(double value);
+int64_t __pyc___builtin___int_from_bool(int8_t value);
+double __pyc___builtin___float_from_str(String* s);
+double __pyc___builtin___float_from_int(int64_t value);
+
+// round(x, ndigits), ties to even
+double __pyc___builtin___float_round(double value, int64_t ndigits);
+int64_t __pyc___builtin___int_round(int64_t value, int64_t ndigits);
+int8_t __pyc___builtin___float_is_integer(double value);
+
 #endif // RUNTIME_H
@@ -0,0 +1,219 @@
+#include "runtime.h"
+#include <stdlib.h>
+#include <string.h>
+
+// ============================================================================
+// Set: a Dict keyed by the elements (values are unused)
+// ============================================================================
+
+Dict* SET_METHOD(__init__)(int64_t elem_kind) {
+    return DICT_METHOD(__init__)(elem_kind, DICT_KIND_INT);
+}
+
+void SET_METHOD(add)(Dict* set, int64_t elem) {
+    DICT_METHOD(__setitem__)(set, elem, 0);
+}
+
+void SET_METHOD(remove)(Dict* set, int64_t elem) {
+    if (set == NULL) {
+        rt_panic("Cannot remove from NULL set");
+    }
+    if (!__pyc_dict_remove(set, elem)) {
+        __pyc_raise(__pyc_exception_new(
+            STR_METHOD(from_literal)("KeyError", 8),
+            __pyc_dict_item_repr(set->key_kind, elem),
+            STR_METHOD(from_literal)("LookupError,Exception", 21)));
+    }
+}
+
+void SET_METHOD(discard)(Dict* set, int64_t elem) {
+    if (set == NULL) {
+        rt_panic("Cannot discard from NULL set");
+    }
+    __pyc_dict_remove(set, elem);
+}
+
+int8_t SET_METHOD(__contains__)(Dict* set, int64_t elem) {
+    return DICT_METHOD(__contains__)(set, elem);
+}
+
+int64_t SET_METHOD(__len__)(Dict* set) {
+    return DICT_METHOD(__len__)(set);
+}
+
+void SET_METHOD(update)(Dict* set, Dict* other) {
+    if (other == NULL) {
+        rt_panic("Cannot update set from NULL set");
+    }
+    __pyc_dict_compact(other);
+    for (int64_t i = 0; i < other->len; i++) {
+        SET_METHOD(add)(set, other->keys[i]);
+    }
+}
+
+void SET_METHOD(update_from_list)(Dict* set, List* list) {
+    if (list == NULL) {
+        rt_panic("Cannot update set from NULL list");
+    }
+    for (int64_t i = 0; i < list->len; i++) {
+        SET_METHOD(add)(set, __pyc_list_item(list, i));
+    }
+}
+
+// Elements of a that are (keep_common) or are not (!keep_common) in b, in a's order
+static Dict* set_filter(Dict* a, Dict* b, int keep_common) {
+    if (a == NULL || b == NULL) {
+        rt_panic("Cannot combine NULL sets");
+    }
+    Dict* result = SET_METHOD(__init__)(a->key_kind);
+    __pyc_dict_compact(a);
+    for (int64_t i = 0; i < a->len; i++) {
+        if ((DICT_METHOD(__contains__)(b, a->keys[i]) != 0) == keep_common) {
+            SET_METHOD(add)(result, a->keys[i]);
+        }
+    }
+    return result;
+}
+
+Dict* SET_METHOD(__or__)(Dict* a, Dict* b) {
+    if (a == NULL) {
+        rt_panic("Cannot combine NULL sets");
+    }
+    Dict* result = SET_METHOD(__init__)(a->key_kind);
+    SET_METHOD(update)(result, a);
+    SET_METHOD(update)(result, b);
+    return result;
+}
+
+Dict* SET_METHOD(__and__)(Dict* a, Dict* b) {
+    return set_filter(a, b, 1);
+}
+
+Dict* SET_METHOD(__sub__)(Dict* a, Dict* b) {
+    return set_filter(a, b, 0);
+}
+
+Dict* SET_METHOD(__xor__)(Dict* a, Dict* b) {
+    Dict* result = set_filter(a, b, 0);
+    __pyc_dict_compact(b);
+    for (int64_t i = 0; i < b->len; i++) {
+        if (!DICT_METHOD(__contains__)(a, b->keys[i])) {
+            SET_METHOD(add)(result, b->keys[i]);
+        }
+    }
+    return result;
+}
+
+Dict* SET_METHOD(union)(Dict* a, Dict* b) {
+    return SET_METHOD(__or__)(a, b);
+}
+
+Dict* SET_METHOD(intersection)(Dict* a, Dict* b) {
+    return SET_METHOD(__and__)(a, b);
+}
+
+ListIterator* SET_METHOD(__iter__)(Dict* set) {
+    return DICT_METHOD(__iter__)(set);
+}
+
+// Render as prefix + "{e1, e2}" + suffix, or `empty` when there are no elements
+static String* set_repr(Dict* set, const char* prefix, const char* suffix, const char* empty) {
+    if (set == NULL || set->len == 0) {
+        return STR_METHOD(from_literal)(empty, (int64_t)strlen(empty));
+    }
+    __pyc_dict_compact(set);
+
+    String** parts = (String**)rt_malloc(sizeof(String*) * set->len);
+    if (parts == NULL) {
+        rt_panic("Failed to allocate memory for set repr");
+    }
+    int64_t prefix_len = (int64_t)strlen(prefix);
+    int64_t suffix_len = (int64_t)strlen(suffix);
+    int64_t total = prefix_len + suffix_len + 2 + 2 * (set->len - 1);  // braces and ", "
+    for (int64_t i = 0; i < set->len; i++) {
+        parts[i] = __pyc_dict_item_repr(set->key_kind, set->keys[i]);
+        total += parts[i]->len;
+    }
+
+    char* buffer = (char*)rt_malloc(total);
+    if (buffer == NULL) {
+        rt_panic("Failed to allocate memory for set repr");
+    }
+    int64_t pos = 0;
+    memcpy(buffer, prefix, (size_t)prefix_len);
+    pos += prefix_len;
+    buffer[pos++] = '{';
+    for (int64_t i = 0; i < set->len; i++) {
+        if (i > 0) {
+            buffer[pos++] = ',';
+            buffer[pos++] = ' ';
+        }
+        memcpy(buffer + pos, parts[i]->data, (size_t)parts[i]->len);
+        pos += parts[i]->len;
+        STR_METHOD(free)(parts[i]);
+    }
+    buffer[pos++] = '}';
+    memcpy(buffer + pos, suffix, (size_t)suffix_len);
+    pos += suffix_len;
+
+    String* result = STR_METHOD(from_literal)(buffer, pos);
+    rt_free(buffer);
+    rt_free(parts);
+    return result;
+}
+
+String* SET_METHOD(__repr__)(Dict* set) {
+    return set_repr(set, "", "", "set()");
+}
+
+String* SET_METHOD(__str__)(Dict* set) {
+    return SET_METHOD(__repr__)(set);
+}
+
+// ============================================================================
+// Frozenset: same representation, only the non-mutating operations
+// ============================================================================
+
+int8_t FROZENSET_METHOD(__contains__)(Dict* set, int64_t elem) {
+    return DICT_METHOD(__contains__)(set, elem);
+}
+
+int64_t FROZENSET_METHOD(__len__)(Dict* set) {
+    return DICT_METHOD(__len__)(set);
+}
+
+Dict* FROZENSET_METHOD(__or__)(Dict* a, Dict* b) {
+    return SET_METHOD(__or__)(a, b);
+}
+
+Dict* FROZENSET_METHOD(__and__)(Dict* a, Dict* b) {
+    return SET_METHOD(__and__)(a, b);
+}
+
+Dict* FROZENSET_METHOD(__sub__)(Dict* a, Dict* b) {
+    return SET_METHOD(__sub__)(a, b);
+}
+
+Dict* FROZENSET_METHOD(__xor__)(Dict* a, Dict* b) {
+    return SET_METHOD(__xor__)(a, b);
+}
+
+Dict* FROZENSET_METHOD(union)(Dict* a, Dict* b) {
+    return SET_METHOD(__or__)(a, b);
+}
+
+Dict* FROZENSET_METHOD(intersection)(Dict* a, Dict* b) {
+    return SET_METHOD(__and__)(a, b);
+}
+
+ListIterator* FROZENSET_METHOD(__iter__)(Dict* set) {
+    return DICT_METHOD(__iter__)(set);
+}
+
+String* FROZENSET_METHOD(__repr__)(Dict* set) {
+    return set_repr(set, "frozenset(", ")", "frozenset()");
+}
+
+String* FROZENSET_METHOD(__str__)(Dict* set) {
+    return FROZENSET_METHOD(__repr__)(set);
+}
@@ -1,4 +1,7 @@
 #include "str.h"
+#include "exception.h"
+#include "io.h"
+#include "refcount.h"
 #include <stdlib.h>
 #include <string.h>
 #include <stdint.h>
@@ -28,7 +31,7 @@ static inline uint16_t detect_flags(const char* data, int64_t len) {
 
 String* STR_METHOD(__init__)(const char* cstr) {
     if (cstr == NULL) {
-        String* s = (String*)malloc(sizeof(String) + 1);
+        String* s = str_alloc(0);
         if (s == NULL) return NULL;
         s->len = 0;
         s->cp_count = 0;
@@ -38,7 +41,7 @@ String* STR_METHOD(__init__)(const char* cstr) {
     }
 
     size_t len = strlen(cstr);
-    String* s = (String*)malloc(sizeof(String) + len + 1);
+    String* s = str_alloc(len);
     if (s == NULL) return NULL;
 
     s->len = (int64_t)len;
@@ -48,8 +51,12 @@ String* STR_METHOD(__init__)(const char* cstr) {
     return s;
 }
 
+String* str_alloc(int64_t len) {
+    return (String*)__pyc_rc_alloc((int64_t)sizeof(String) + len + 1, NULL);
+}
+
 String* STR_METHOD(from_literal)(const char* cstr, int64_t len) {
-    String* s = (String*)malloc(sizeof(String) + len + 1);
+    String* s = str_alloc(len);
     if (s == NULL) return NULL;
 
     s->len = len;
@@ -61,7 +68,7 @@ String* STR_METHOD(from_literal)(const char* cstr, int64_t len) {
 }
 
 void STR_METHOD(free)(String* s) {
-    free(s);
+    __pyc_rc_free(s, NULL);
 }
 
 int64_t STR_METHOD(__len__)(String* str) {
@@ -108,15 +115,30 @@ int64_t STR_METHOD(__len__)(String* str) {
 #endif
 }
 
+// s[index] past either end: IndexError, with -1 standing in for the codepoint
+static int64_t str_index_error(void) {
+    if (__pyc_runtime_checks) {
+        __pyc_raise_index_error("string index out of range");
+    }
+    return -1;
+}
+
 int64_t STR_METHOD(__getitem__)(String* s, int64_t index) {
-    if (s == NULL || index < 0) {
+    if (s == NULL) {
         return -1;  // Error: invalid input
     }
+    // Negative indices count codepoints from the end
+    if (index < 0) {
+        index += STR_METHOD(__len__)(s);
+        if (index < 0) {
+            return str_index_error();
+        }
+    }
 
     // Fast path for ASCII strings
     if (s->flags & STR_FLAG_ASCII_ONLY) {
         if (index >= s->len) {
-            return -1;  // Out of bounds
+            return str_index_error();
         }
         return (int64_t)(unsigned char)s->data[index];
     }
@@ -158,7 +180,7 @@ int64_t STR_METHOD(__getitem__)(String* s, int64_t index) {
         current_idx++;
     }
 
-    return -1;  // Index out of bounds
+    return str_index_error();
 #else
     // For Unicode strings, navigate to the index-th codepoint
     int32_t byte_idx = 0;
@@ -176,65 +198,120 @@ int64_t STR_METHOD(__getitem__)(String* s, int64_t index) {
         current_idx++;
     }
 
-    return -1;  // Index out of bounds
+    return str_index_error();
 #endif
 }
 
+// Byte length of the UTF-8 sequence starting with lead byte c
+static inline int64_t utf8_seq_len(unsigned char c) {
+    if ((c & 0x80) == 0) return 1;
+    if ((c & 0xE0) == 0xC0) return 2;
+    if ((c & 0xF0) == 0xE0) return 3;
+    if ((c & 0xF8) == 0xF0) return 4;
+    return 1;  // Invalid, treat as a single byte
+}
+
+String* STR_METHOD(__slice__)(String* s, int64_t start, int64_t stop, int64_t step) {
+    if (s == NULL) {
+        rt_slice_indices(0, &start, &stop, step);
+        return STR_METHOD(from_literal)("", 0);
+    }
+
+    // Fast path for ASCII strings: codepoint index == byte index
+    if (s->flags & STR_FLAG_ASCII_ONLY) {
+        int64_t count = rt_slice_indices(s->len, &start, &stop, step);
+        if (step == 1) {
+            return STR_METHOD(from_literal)(s->data + start, count);
+        }
+        char* buffer = (char*)rt_malloc(count + 1);
+        if (buffer == NULL) return NULL;
+        for (int64_t i = 0; i < count; i++) {
+            buffer[i] = s->data[start + i * step];
+        }
+        String* result = STR_METHOD(from_literal)(buffer, count);
+        rt_free(buffer);
+        return result;
+    }
+
+    // Record the byte offset of every codepoint (plus the end offset)
+    int64_t* offsets = (int64_t*)rt_malloc(sizeof(int64_t) * (s->len + 1));
+    if (offsets == NULL) return NULL;
+    int64_t cp_count = 0;
+    int64_t byte_idx = 0;
+    while (byte_idx < s->len) {
+        offsets[cp_count++] = byte_idx;
+        byte_idx += utf8_seq_len((unsigned char)s->data[byte_idx]);
+    }
+    offsets[cp_count] = s->len;
+
+    int64_t count = rt_slice_indices(cp_count, &start, &stop, step);
+    char* buffer = (char*)rt_malloc(s->len + 1);
+    if (buffer == NULL) {
+        rt_free(offsets);
+        return NULL;
+    }
+    int64_t len = 0;
+    for (int64_t i = 0; i < count; i++) {
+        int64_t cp = start + i * step;
+        int64_t cp_len = offsets[cp + 1] - offsets[cp];
+        memcpy(buffer + len, s->data + offsets[cp], cp_len);
+        len += cp_len;
+    }
+
+    String* result = STR_METHOD(from_literal)(buffer, len);
+    rt_free(buffer);
+    rt_free(offsets);
+    return result;
+}
+
 String* STR_METHOD(__str__)(String* str) {
     return str;
 }
 
+// Like CPython: single quotes unless the string has a single quote and no
+// double quote; control characters are escaped, other characters are kept
 String* STR_METHOD(__repr__)(String* str) {
     if (str == NULL) {
         return STR_METHOD(from_literal)("''", 2);
     }
 
-    // Calculate output length
-    int64_t out_len = 2;  // Quotes
-    for (int64_t i = 0; i < str->len; i++) {
-        char c = str->data[i];
-        if (c == '\n' || c == '\t' || c == '\r' || c == '\\' || c == '\'') {
-            out_len += 2;
-        } else if (c >= 32 && c < 127) {
-            out_len += 1;
-        } else {
-            out_len += 4;  // \xNN
-        }
+    char quote = '\'';
+    if (memchr(str->data, '\'', (size_t)str->len) != NULL &&
+        memchr(str->data, '"', (size_t)str->len) == NULL) {
+        quote = '"';
     }
 
-    String* result = (String*)malloc(sizeof(String) + out_len + 1);
-    if (result == NULL) return NULL;
-    result->len = out_len;
-    result->cp_count = -1;  // Not computed
-    result->flags = STR_FLAG_ASCII_ONLY | STR_FLAG_VALID_UTF8;  // __repr__ output is ASCII
-
+    // At most 4 output bytes (\xNN) per input byte, plus the quotes
+    char* buffer = (char*)rt_malloc((size_t)str->len * 4 + 2);
+    if (buffer == NULL) {
+        rt_panic("Failed to allocate memory for str repr");
+    }
     int64_t pos = 0;
-    result->data[pos++] = '\'';
+    buffer[pos++] = quote;
 
     for (int64_t i = 0; i < str->len; i++) {
-        char c = str->data[i];
+        unsigned char c = (unsigned char)str->data[i];
         if (c == '\n') {
-            result->data[pos++] = '\\'; result->data[pos++] = 'n';
+            buffer[pos++] = '\\'; buffer[pos++] = 'n';
         } else if (c == '\t') {
-            result->data[pos++] = '\\'; result->data[pos++] = 't';
+            buffer[pos++] = '\\'; buffer[pos++] = 't';
         } else if (c == '\r') {
-            result->data[pos++] = '\\'; result->data[pos++] = 'r';
-        } else if (c == '\\') {
-            result->data[pos++] = '\\'; result->data[pos++] = '\\';
-        } else if (c == '\'') {
-            result->data[pos++] = '\\'; result->data[pos++] = '\'';
-        } else if (c >= 32 && c < 127) {
-            result->data[pos++] = c;
+            buffer[pos++] = '\\'; buffer[pos++] = 'r';
+        } else if (c == '\\' || c == (unsigned char)quote) {
+            buffer[pos++] = '\\'; buffer[pos++] = (char)c;
+        } else if (c < 32 || c == 127) {
+            buffer[pos++] = '\\';
+            buffer[pos++] = 'x';
+            buffer[pos++] = "0123456789abcdef"[c >> 4];
+            buffer[pos++] = "0123456789abcdef"[c & 0xf];
         } else {
-            result->data[pos++] = '\\';
-            result->data[pos++] = 'x';
-            result->data[pos++] = "0123456789abcdef"[(unsigned char)c >> 4];
-            result->data[pos++] = "0123456789abcdef"[(unsigned char)c & 0xf];
+            buffer[pos++] = (char)c;  // Printable ASCII or part of a UTF-8 sequence
         }
     }
 
-    result->data[pos++] = '\'';
-    result->data[pos] = '\0';
+    buffer[pos++] = quote;
+    String* result = STR_METHOD(from_literal)(buffer, pos);
+    rt_free(buffer);
     return result;
 }
 
@@ -250,7 +327,7 @@ String* STR_METHOD(__add__)(String* a, String* b) {
     if (b == NULL) return a;
 
     int64_t total_len = a->len + b->len;
-    String* result = (String*)malloc(sizeof(String) + total_len + 1);
+    String* result = str_alloc(total_len);
     if (result == NULL) return NULL;
 
     result->len = total_len;
@@ -272,6 +349,38 @@ String* STR_METHOD(__add__)(String* a, String* b) {
     return result;
 }
 
+// s * n: n copies of s, empty when n <= 0
+String* STR_METHOD(__mul__)(String* s, int64_t n) {
+    if (s == NULL || n <= 0 || s->len == 0) {
+        return STR_METHOD(from_literal)("", 0);
+    }
+    if (n > INT64_MAX / s->len) {
+        rt_panic("Repeated string is too long");
+    }
+
+    int64_t total_len = s->len * n;
+    String* result = str_alloc(total_len);
+    if (result == NULL) {
+        rt_panic("Failed to allocate memory for repeated string");
+    }
+
+    result->len = total_len;
+    result->flags = s->flags;
+    result->cp_count = (s->cp_count >= 0 && (int64_t)s->cp_count * n <= INT32_MAX)
+                           ? (int32_t)(s->cp_count * n)
+                           : -1;
+    for (int64_t i = 0; i < n; i++) {
+        memcpy(result->data + i * s->len, s->data, s->len);
+    }
+    result->data[total_len] = '\0';
+    return result;
+}
+
+// n * s
+String* STR_METHOD(__rmul__)(String* s, int64_t n) {
+    return STR_METHOD(__mul__)(s, n);
+}
+
 // ============================================================================
 // String comparison operators
 // ============================================================================
@@ -323,7 +432,7 @@ String* STR_METHOD(lower)(String* str) {
 
     // Fast path for ASCII strings
     if (str->flags & STR_FLAG_ASCII_ONLY) {
-        String* result = (String*)malloc(sizeof(String) + str->len + 1);
+        String* result = str_alloc(str->len);
         if (result == NULL) return NULL;
 
         result->len = str->len;
@@ -340,7 +449,7 @@ String* STR_METHOD(lower)(String* str) {
 
 #ifdef NO_ICU
     // Without ICU, only handle ASCII (already done above), return copy for non-ASCII
-    String* result = (String*)malloc(sizeof(String) + str->len + 1);
+    String* result = str_alloc(str->len);
     if (result == NULL) return NULL;
     result->len = str->len;
     result->cp_count = str->cp_count;
@@ -363,7 +472,7 @@ String* STR_METHOD(lower)(String* str) {
     }
 
     // Allocate and convert
-    String* result = (String*)malloc(sizeof(String) + dest_len + 1);
+    String* result = str_alloc(dest_len);
     if (result == NULL) {
         ucasemap_close(csm);
         return NULL;
@@ -376,7 +485,7 @@ String* STR_METHOD(lower)(String* str) {
     ucasemap_close(csm);
 
     if (U_FAILURE(status)) {
-        free(result);
+        STR_METHOD(free)(result);
         return NULL;
     }
 
@@ -394,7 +503,7 @@ String* STR_METHOD(upper)(String* str) {
 
     // Fast path for ASCII strings
     if (str->flags & STR_FLAG_ASCII_ONLY) {
-        String* result = (String*)malloc(sizeof(String) + str->len + 1);
+        String* result = str_alloc(str->len);
         if (result == NULL) return NULL;
 
         result->len = str->len;
@@ -411,7 +520,7 @@ String* STR_METHOD(upper)(String* str) {
 
 #ifdef NO_ICU
     // Without ICU, only handle ASCII (already done above), return copy for non-ASCII
-    String* result = (String*)malloc(sizeof(String) + str->len + 1);
+    String* result = str_alloc(str->len);
     if (result == NULL) return NULL;
     result->len = str->len;
     result->cp_count = str->cp_count;
@@ -434,7 +543,7 @@ String* STR_METHOD(upper)(String* str) {
     }
 
     // Allocate and convert
-    String* result = (String*)malloc(sizeof(String) + dest_len + 1);
+    String* result = str_alloc(dest_len);
     if (result == NULL) {
         ucasemap_close(csm);
         return NULL;
@@ -447,7 +556,7 @@ String* STR_METHOD(upper)(String* str) {
     ucasemap_close(csm);
 
     if (U_FAILURE(status)) {
-        free(result);
+        STR_METHOD(free)(result);
         return NULL;
     }
 
@@ -490,7 +599,7 @@ String* STR_METHOD(strip)(String* str) {
         return str;
     }
 
-    String* result = (String*)malloc(sizeof(String) + new_len + 1);
+    String* result = str_alloc(new_len);
     if (result == NULL) return NULL;
 
     result->len = new_len;
@@ -519,6 +628,11 @@ int64_t STR_METHOD(find)(String* str, String* substr) {
     return -1;
 }
 
+// `substr in str`
+int8_t STR_METHOD(__contains__)(String* str, String* substr) {
+    return STR_METHOD(find)(str, substr) >= 0 ? 1 : 0;
+}
+
 int8_t STR_METHOD(startswith)(String* str, String* prefix) {
     if (str == NULL || prefix == NULL) return 0;
     if (prefix->len > str->len) return 0;
@@ -555,7 +669,7 @@ String* STR_METHOD(replace)(String* str, String* old, String* new_str) {
     // Calculate new length
     int64_t new_len = str->len + count * (new_str->len - old->len);
 
-    String* result = (String*)malloc(sizeof(String) + new_len + 1);
+    String* result = str_alloc(new_len);
     if (result == NULL) return NULL;
 
     result->len = new_len;
@@ -31,14 +31,22 @@ typedef struct {
 String* STR_METHOD(__init__)(const char* cstr);
 String* STR_METHOD(from_literal)(const char* cstr, int64_t len);
 void STR_METHOD(free)(String* s);
+// An uninitialized string of len bytes plus the terminator, counted with
+// pycc --refcount; every string the runtime creates comes from here
+String* str_alloc(int64_t len);
 
 // String operations - use String* struct
 int64_t STR_METHOD(__len__)(String* str);
 int64_t STR_METHOD(__getitem__)(String* s, int64_t index);  // Returns Unicode codepoint
+String* STR_METHOD(__slice__)(String* s, int64_t start, int64_t stop, int64_t step);  // Indices count codepoints
 
 // String concatenation
 String* STR_METHOD(__add__)(String* a, String* b);
 
+// String repetition (s * n and n * s)
+String* STR_METHOD(__mul__)(String* s, int64_t n);
+String* STR_METHOD(__rmul__)(String* s, int64_t n);
+
 // String comparison operators
 int8_t STR_METHOD(__eq__)(String* a, String* b);
 int8_t STR_METHOD(__ne__)(String* a, String* b);
@@ -76,6 +84,7 @@ String* STR_METHOD(strip)(String* str);
 
 // String search
 int64_t STR_METHOD(find)(String* str, String* substr);
+int8_t STR_METHOD(__contains__)(String* str, String* substr);
 int8_t STR_METHOD(startswith)(String* str, String* prefix);
 int8_t STR_METHOD(endswith)(String* str, String* suffix);
 
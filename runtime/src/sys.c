@@ -0,0 +1,45 @@
+#include "runtime.h"
+#include <stdlib.h>
+#include <string.h>
+
+// ============================================================================
+// sys module
+// ============================================================================
+
+// Command line as passed to main(), recorded by __pyc_sys_init
+static int32_t saved_argc = 0;
+static char** saved_argv = NULL;
+
+// sys.argv, built on first use so it is the same (mutable) list every time
+static List* argv_list = NULL;
+
+void __pyc_sys_init(int32_t argc, char** argv) {
+    saved_argc = argc;
+    saved_argv = argv;
+}
+
+List* __pyc_sys_argv(void) {
+    if (argv_list == NULL) {
+        argv_list = LIST_METHOD(__init__)(DICT_KIND_STR);
+        for (int32_t i = 0; i < saved_argc; i++) {
+            String* arg = STR_METHOD(from_literal)(saved_argv[i], (int64_t)strlen(saved_argv[i]));
+            LIST_METHOD(append)(argv_list, (int64_t)arg);
+            __pyc_rc_untrack(arg);
+        }
+        // Kept for the whole run
+        __pyc_rc_untrack(argv_list);
+    }
+    return argv_list;
+}
+
+void __pyc_sys_exit(int64_t code) {
+    // exit() flushes stdout, so everything printed so far is kept
+    exit((int)code);
+}
+
+void __pyc_sys_exit_message(String* message) {
+    fflush(stdout);
+    write_stderr_string_impl(message);
+    write_stderr("\n", 1);
+    exit(1);
+}
@@ -0,0 +1,51 @@
+#include "runtime.h"
+#include <errno.h>
+#include <math.h>
+#include <string.h>
+#include <time.h>
+
+// ============================================================================
+// time module
+// ============================================================================
+
+static double clock_seconds(clockid_t clock) {
+    struct timespec ts;
+    if (clock_gettime(clock, &ts) != 0) {
+        rt_panic("clock_gettime failed");
+    }
+    return (double)ts.tv_sec + (double)ts.tv_nsec / 1e9;
+}
+
+// time.time(): seconds since the Unix epoch
+double __pyc_time_time(void) {
+    return clock_seconds(CLOCK_REALTIME);
+}
+
+// time.monotonic(): seconds from an unspecified start that never goes backwards
+double __pyc_time_monotonic(void) {
+    return clock_seconds(CLOCK_MONOTONIC);
+}
+
+// time.sleep(seconds), resumed after signal interruptions until the full time has passed
+void __pyc_time_sleep(double seconds) {
+    const char* error = NULL;
+    if (isnan(seconds)) {
+        error = "Invalid value NaN (not a number)";
+    } else if (seconds < 0) {
+        error = "sleep length must be non-negative";
+    }
+    if (error != NULL) {
+        __pyc_raise(__pyc_exception_new(STR_METHOD(from_literal)("ValueError", 10),
+                                        STR_METHOD(from_literal)(error, (int64_t)strlen(error)),
+                                        STR_METHOD(from_literal)("Exception", 9)));
+        return;
+    }
+
+    struct timespec request;
+    request.tv_sec = (time_t)seconds;
+    request.tv_nsec = (long)((seconds - (double)request.tv_sec) * 1e9);
+    struct timespec remaining;
+    while (nanosleep(&request, &remaining) != 0 && errno == EINTR) {
+        request = remaining;
+    }
+}
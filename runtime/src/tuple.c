@@ -0,0 +1,93 @@
+#include "runtime.h"
+#include <stdlib.h>
+#include <string.h>
+
+// ============================================================================
+// Tuple methods
+// ============================================================================
+
+Tuple* TUPLE_METHOD(__init__)(int64_t len, const int8_t* kinds) {
+    Tuple* tuple = (Tuple*)rt_malloc(sizeof(Tuple) + sizeof(int64_t) * len);
+    if (tuple == NULL) {
+        rt_panic("Failed to allocate memory for tuple");
+    }
+    tuple->len = len;
+    tuple->kinds = kinds;
+    memset(tuple->data, 0, sizeof(int64_t) * len);
+    return tuple;
+}
+
+// Tuples are never freed; the references their elements hold are counted all
+// the same (with --refcount), as elements are only set while building a tuple
+void __pyc_tuple_set(Tuple* tuple, int64_t index, int64_t value) {
+    __pyc_rc_incref_item(value, tuple->kinds[index]);
+    tuple->data[index] = value;
+}
+
+// Indexes are checked at compile time, so a bad index here is a compiler bug
+int64_t TUPLE_METHOD(__getitem__)(Tuple* tuple, int64_t index) {
+    if (tuple == NULL) {
+        rt_panic("Cannot index NULL tuple");
+    }
+    if (index < 0 || index >= tuple->len) {
+        rt_panic("Tuple index out of range");
+    }
+    return tuple->data[index];
+}
+
+int64_t TUPLE_METHOD(__len__)(Tuple* tuple) {
+    if (tuple == NULL) {
+        rt_panic("Cannot get length of NULL tuple");
+    }
+    return tuple->len;
+}
+
+// ============================================================================
+// String conversion
+// ============================================================================
+
+String* TUPLE_METHOD(__repr__)(Tuple* tuple) {
+    if (tuple == NULL || tuple->len == 0) {
+        return STR_METHOD(from_literal)("()", 2);
+    }
+
+    String** parts = (String**)rt_malloc(sizeof(String*) * tuple->len);
+    if (parts == NULL) {
+        rt_panic("Failed to allocate memory for tuple repr");
+    }
+    // "(" ")" plus ", " separators, and a trailing "," for 1-tuples
+    int64_t total = 2 + 2 * (tuple->len - 1) + (tuple->len == 1 ? 1 : 0);
+    for (int64_t i = 0; i < tuple->len; i++) {
+        parts[i] = __pyc_dict_item_repr(tuple->kinds[i], tuple->data[i]);
+        total += parts[i]->len;
+    }
+
+    char* buffer = (char*)rt_malloc(total);
+    if (buffer == NULL) {
+        rt_panic("Failed to allocate memory for tuple repr");
+    }
+    int64_t pos = 0;
+    buffer[pos++] = '(';
+    for (int64_t i = 0; i < tuple->len; i++) {
+        if (i > 0) {
+            buffer[pos++] = ',';
+            buffer[pos++] = ' ';
+        }
+        memcpy(buffer + pos, parts[i]->data, (size_t)parts[i]->len);
+        pos += parts[i]->len;
+        STR_METHOD(free)(parts[i]);
+    }
+    if (tuple->len == 1) {
+        buffer[pos++] = ',';
+    }
+    buffer[pos++] = ')';
+
+    String* result = STR_METHOD(from_literal)(buffer, pos);
+    rt_free(buffer);
+    rt_free(parts);
+    return result;
+}
+
+String* TUPLE_METHOD(__str__)(Tuple* tuple) {
+    return TUPLE_METHOD(__repr__)(tuple);
+}
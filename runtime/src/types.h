@@ -19,10 +19,19 @@
 // Type-specific method macros
 #define LIST_METHOD(name)          BUILTIN_METHOD(list, name)
 #define LIST_ITERATOR_METHOD(name) BUILTIN_METHOD(list_iterator, name)
+#define FLOAT_LIST_METHOD(name)    BUILTIN_METHOD(list_float, name)
+#define BOOL_LIST_METHOD(name)     BUILTIN_METHOD(list_bool, name)
+#define FLOAT_LIST_ITERATOR_METHOD(name) BUILTIN_METHOD(list_iterator_float, name)
+#define BOOL_LIST_ITERATOR_METHOD(name)  BUILTIN_METHOD(list_iterator_bool, name)
+#define DICT_METHOD(name)          BUILTIN_METHOD(dict, name)
+#define SET_METHOD(name)           BUILTIN_METHOD(set, name)
+#define FROZENSET_METHOD(name)     BUILTIN_METHOD(frozenset, name)
+#define TUPLE_METHOD(name)         BUILTIN_METHOD(tuple, name)
 #define BYTEARRAY_METHOD(name)     BUILTIN_METHOD(bytearray, name)
 #define BYTES_METHOD(name)         BUILTIN_METHOD(bytes, name)
 #define STR_METHOD(name)           BUILTIN_METHOD(str, name)
 #define RANGE_METHOD(name)         BUILTIN_METHOD(range, name)
+#define FILE_METHOD(name)          BUILTIN_METHOD(file, name)
 #define EXCEPTION_METHOD(name)     BUILTIN_METHOD(Exception, name)
 #define STOPITERATION_METHOD(name) BUILTIN_METHOD(StopIteration, name)
 
@@ -1,11 +1,23 @@
 //! pycc - Compile Python files to executables
 //!
 //! Works like gcc: `pycc input.py -o output`
+//!
+//! With `--project DIR`, builds every binary declared in `DIR/pyproject.toml`
+//! into the `-o` directory (default `DIR/build`).
+//!
+//! With `--check`, only type-checks the program (or project): nothing is
+//! generated or linked, and no `-o` is needed.
 
 use anyhow::Result;
 use clap::Parser;
-use compiler::{Compiler, CompilerOptions, Target};
+use compiler::{
+    diagnostics, env_search_paths, AllocMode, Compiler, CompilerError, CompilerOptions,
+    CustomTarget, EmitKind, ErrorFormat, ExceptionModel, GcMode, Linker, OptLevel, Project, Target,
+    TargetSpec, WarningLevel,
+};
+use std::io::IsTerminal;
 use std::path::PathBuf;
+use std::process;
 
 #[derive(Parser)]
 #[command(name = "pycc")]
@@ -13,29 +25,272 @@ use std::path::PathBuf;
 #[command(version)]
 struct Args {
     /// Python file to compile
-    input: PathBuf,
+    #[arg(required_unless_present = "project", conflicts_with = "project")]
+    input: Option<PathBuf>,
+
+    /// Output executable path (output directory with --project)
+    #[arg(short, long, required_unless_present_any = ["project", "check"])]
+    output: Option<PathBuf>,
+
+    /// Compile the project in DIR as declared by its pyproject.toml
+    #[arg(long, value_name = "DIR")]
+    project: Option<PathBuf>,
+
+    /// Also resolve imports against DIR, after the input's directory (or the project's
+    /// source roots); may be repeated, and the directories of PYCPATH follow
+    #[arg(short = 'I', value_name = "DIR")]
+    include: Vec<PathBuf>,
+
+    /// Only type-check: parse and lower the program, reporting errors and warnings,
+    /// without generating code or linking
+    #[arg(long)]
+    check: bool,
+
+    /// Keep the object file of each program built in the cache directory, and only
+    /// relink it while the program's sources and options are unchanged
+    #[arg(long)]
+    incremental: bool,
+
+    /// Cache directory of --incremental (default: .pycc-cache)
+    #[arg(long, value_name = "DIR", requires = "incremental")]
+    cache_dir: Option<PathBuf>,
+
+    /// Write a native object file to the output path instead of linking an executable
+    #[arg(long)]
+    emit_obj: bool,
+
+    /// Print the typed IR of the program, with every type spelled out
+    #[arg(long)]
+    emit_tir: bool,
+
+    /// Also write these artifacts next to the output, named after it: llvm-ir (.ll),
+    /// llvm-bc (.bc), asm (.s) and obj (.o); comma-separated. staticlib writes a static
+    /// library of the @export functions to the output path instead of an executable,
+    /// with the interface other programs import it by next to it
+    #[arg(long, value_name = "KINDS", value_delimiter = ',')]
+    emit: Vec<String>,
+
+    /// Generate DWARF debug info mapping the code back to the Python source, for gdb and perf
+    #[arg(short = 'g')]
+    debug_info: bool,
+
+    /// Link a shared library exporting the @export functions with C symbols, instead of an executable
+    #[arg(long)]
+    shared: bool,
+
+    /// Embed data files into the executable (file, directory, or quoted glob); may be repeated
+    #[arg(long, value_name = "PATH")]
+    embed: Vec<PathBuf>,
+
+    /// Give classes without __repr__/__eq__ field-wise defaults, as @dataclass does
+    #[arg(long)]
+    synthesize_methods: bool,
+
+    /// Call the base's __init__ first in a subclass __init__ that never calls super().__init__()
+    #[arg(long)]
+    auto_super_init: bool,
+
+    /// Raise OverflowError when int arithmetic overflows 64 bits instead of wrapping
+    #[arg(long)]
+    checked_int: bool,
+
+    /// Skip the ZeroDivisionError and IndexError checks (faster, but dividing
+    /// by zero or indexing out of range is then undefined behaviour)
+    #[arg(long)]
+    no_runtime_checks: bool,
+
+    /// Free class instances, strings and lists when their last reference goes away
+    #[arg(long)]
+    refcount: bool,
+
+    /// Reference count and fail if any instance, string or list is still alive
+    /// at exit (dicts, sets and tuples are never freed)
+    #[arg(long)]
+    leak_check: bool,
 
-    /// Output executable path
-    #[arg(short, long)]
-    output: PathBuf,
+    /// Garbage collector for class instances: none, or tracing (also frees cycles)
+    #[arg(long, default_value = "none")]
+    gc: String,
 
-    /// Target architecture (x86_64 or riscv64)
+    /// Runtime allocator: malloc, or arena (faster; nothing is freed until exit)
+    #[arg(long, default_value = "malloc")]
+    alloc: String,
+
+    /// Optimization level: 0, 1 (fold constants, remove dead code, inline small functions,
+    /// run LLVM's -O1 passes), 2, 3, or s (as 1, with LLVM's -O2, -O3 or -Os passes)
+    #[arg(long, default_value = "0")]
+    opt_level: String,
+
+    /// How try blocks catch exceptions: polling, or setjmp (x86_64 and aarch64 only; faster)
+    #[arg(long, default_value = "polling")]
+    exceptions: String,
+
+    /// Print a Python-style traceback, with the functions and source lines it
+    /// passed through, when an exception is not caught
+    #[arg(long)]
+    traceback: bool,
+
+    /// Raise RecursionError when a call would run more than DEPTH functions
+    /// nested (as Python's sys.setrecursionlimit), instead of overflowing the stack
+    #[arg(long, value_name = "DEPTH", value_parser = clap::value_parser!(u32).range(1..))]
+    recursion_limit: Option<u32>,
+
+    /// Linker for executables: auto (clang if installed, else lld), clang, or lld
+    /// (ld.lld, or the Rust toolchain's rust-lld; no clang needed)
+    #[arg(long, default_value = "auto")]
+    linker: String,
+
+    /// What to do with warnings: warn, allow (print nothing), or error (fail the build)
+    #[arg(
+        short = 'W',
+        long = "warnings",
+        value_name = "LEVEL",
+        default_value = "warn"
+    )]
+    warnings: String,
+
+    /// How errors and warnings are printed: human (with the source line), short
+    /// (one `file:line:column: message` line each), or json (one JSON object per line)
+    #[arg(long, default_value = "human")]
+    error_format: String,
+
+    /// Target architecture (x86_64, riscv64 or aarch64)
     #[arg(long, default_value = "x86_64")]
     target: String,
+
+    /// Custom LLVM target triple (overrides --target; requires --runtime)
+    #[arg(long, value_name = "TRIPLE")]
+    target_triple: Option<String>,
+
+    /// Custom target described by a TOML or JSON file: triple, sysroot, runtime,
+    /// crt-begin, crt-end and linker (overrides --target)
+    #[arg(long, value_name = "FILE", conflicts_with = "target_triple")]
+    target_spec: Option<PathBuf>,
+
+    /// Sysroot for the custom target
+    #[arg(long, value_name = "DIR", requires = "target_triple")]
+    sysroot: Option<PathBuf>,
+
+    /// Startup object linked before the program (e.g., crt1.o); may be repeated
+    #[arg(long, value_name = "FILE", requires = "target_triple")]
+    crt_begin: Vec<PathBuf>,
+
+    /// Object linked after everything else (e.g., crtn.o); may be repeated
+    #[arg(long, value_name = "FILE", requires = "target_triple")]
+    crt_end: Vec<PathBuf>,
+
+    /// Runtime object built for the custom target
+    #[arg(long, value_name = "FILE", requires = "target_triple")]
+    runtime: Option<PathBuf>,
+
+    /// Linker command template, e.g. "ld.lld {crt_begin} {inputs} {crt_end} -o {output}"
+    #[arg(long, value_name = "TEMPLATE", requires = "target_triple")]
+    linker_template: Option<String>,
 }
 
 fn main() -> Result<()> {
     let args = Args::parse();
 
     let target: Target = args.target.parse().map_err(|e| anyhow::anyhow!("{}", e))?;
+    let gc: GcMode = args.gc.parse().map_err(|e| anyhow::anyhow!("{}", e))?;
+    let alloc: AllocMode = args.alloc.parse().map_err(|e| anyhow::anyhow!("{}", e))?;
+    let opt_level: OptLevel = args
+        .opt_level
+        .parse()
+        .map_err(|e| anyhow::anyhow!("{}", e))?;
+    let exceptions: ExceptionModel = args
+        .exceptions
+        .parse()
+        .map_err(|e| anyhow::anyhow!("{}", e))?;
+    let linker: Linker = args.linker.parse().map_err(|e| anyhow::anyhow!("{}", e))?;
+    let warnings: WarningLevel = args
+        .warnings
+        .parse()
+        .map_err(|e| anyhow::anyhow!("{}", e))?;
+    let error_format: ErrorFormat = args
+        .error_format
+        .parse()
+        .map_err(|e| anyhow::anyhow!("{}", e))?;
+    let emit = args
+        .emit
+        .iter()
+        .map(|kind| kind.parse::<EmitKind>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| anyhow::anyhow!("{}", e))?;
+
+    let custom_target = match &args.target_spec {
+        Some(path) => Some(TargetSpec::load(path)?.into()),
+        None => args.target_triple.map(|triple| CustomTarget {
+            triple,
+            sysroot: args.sysroot,
+            crt_begin: args.crt_begin,
+            crt_end: args.crt_end,
+            runtime: args.runtime,
+            linker_template: args.linker_template,
+        }),
+    };
 
     let options = CompilerOptions {
         target,
+        emit_tir: args.emit_tir,
+        emit_obj: args.emit_obj,
+        shared: args.shared,
+        emit,
+        debug_info: args.debug_info,
+        embed: args.embed,
+        search_paths: args.include.into_iter().chain(env_search_paths()).collect(),
+        custom_target,
+        synthesize_methods: args.synthesize_methods,
+        auto_super_init: args.auto_super_init,
+        checked_int: args.checked_int,
+        no_runtime_checks: args.no_runtime_checks,
+        refcount: args.refcount,
+        leak_check: args.leak_check,
+        gc,
+        alloc,
+        opt_level,
+        exceptions,
+        traceback: args.traceback,
+        recursion_limit: args.recursion_limit,
+        linker,
+        warnings,
+        error_format,
+        incremental: args.incremental.then(|| {
+            args.cache_dir
+                .unwrap_or_else(|| PathBuf::from(".pycc-cache"))
+        }),
         ..Default::default()
     };
 
     let compiler = Compiler::new(options);
-    compiler.compile(&args.input, &args.output)?;
+    if args.check {
+        let checked = match &args.project {
+            Some(project_dir) => compiler.check_project(&Project::load(project_dir)?),
+            None => compiler.check(&args.input.unwrap()),
+        };
+        checked.unwrap_or_else(|e| report(e, error_format));
+    } else if let Some(project_dir) = &args.project {
+        let project = Project::load(project_dir)?;
+        let output_dir = args.output.unwrap_or_else(|| project.root.join("build"));
+        let binaries = compiler
+            .compile_project(&project, &output_dir)
+            .unwrap_or_else(|e| report(e, error_format));
+        for binary in binaries {
+            println!("{}", binary.display());
+        }
+    } else {
+        // clap guarantees both are present without --project
+        compiler
+            .compile(&args.input.unwrap(), &args.output.unwrap())
+            .unwrap_or_else(|e| report(e, error_format));
+    }
 
     Ok(())
 }
+
+/// Print a compile error with its source frame and exit, as compilers do
+fn report(error: CompilerError, format: ErrorFormat) -> ! {
+    let color = std::io::stderr().is_terminal();
+    eprint!("{}", diagnostics::render(&error, format, color));
+    process::exit(1);
+}
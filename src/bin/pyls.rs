@@ -0,0 +1,21 @@
+//! pyls - Language server for editors
+//!
+//! Speaks the Language Server Protocol over stdin and stdout: editors start
+//! `pyls` and get the compiler's diagnostics on save, the types of names on
+//! hover, and go to definition (see `compiler::lsp`).
+
+use anyhow::Result;
+use clap::Parser;
+use std::io;
+
+#[derive(Parser)]
+#[command(name = "pyls")]
+#[command(about = "Language server for Python files compiled by pycc")]
+#[command(version)]
+struct Args {}
+
+fn main() -> Result<()> {
+    Args::parse();
+    compiler::lsp::run(io::stdin().lock(), io::stdout().lock())?;
+    Ok(())
+}
@@ -0,0 +1,111 @@
+//! pyrepl - Interactive Python session
+//!
+//! Works like the Python interpreter without a script: each statement (or
+//! block, ended by an empty line) typed is compiled with those before it and
+//! run in this process with LLVM's JIT, and the value of each expression is
+//! printed with `repr()` (see `compiler::repl`). Prompts go to stderr.
+
+use anyhow::Result;
+use clap::Parser;
+use compiler::{
+    diagnostics, env_search_paths, Compiler, CompilerError, CompilerOptions, ErrorFormat,
+    ExceptionModel, OptLevel, Target, WarningLevel,
+};
+use std::io::{self, IsTerminal};
+use std::path::PathBuf;
+use std::process;
+
+#[derive(Parser)]
+#[command(name = "pyrepl")]
+#[command(about = "Interactive Python session, compiled and run statement by statement")]
+#[command(version)]
+struct Args {
+    /// Also resolve imports against DIR, after the current directory; may be
+    /// repeated, and the directories of the PYCPATH environment variable follow
+    #[arg(short = 'I', value_name = "DIR")]
+    include: Vec<PathBuf>,
+
+    /// Give classes without __repr__/__eq__ field-wise defaults, as @dataclass does
+    #[arg(long)]
+    synthesize_methods: bool,
+
+    /// Call the base's __init__ first in a subclass __init__ that never calls super().__init__()
+    #[arg(long)]
+    auto_super_init: bool,
+
+    /// Raise OverflowError when int arithmetic overflows 64 bits instead of wrapping
+    #[arg(long)]
+    checked_int: bool,
+
+    /// Optimization level of LLVM's passes: 0, 1, 2, 3, or s
+    #[arg(long, default_value = "0")]
+    opt_level: String,
+
+    /// How try blocks catch exceptions: polling, or setjmp (x86_64 and aarch64 only; faster)
+    #[arg(long, default_value = "polling")]
+    exceptions: String,
+
+    /// What to do with warnings: warn, allow (print nothing), or error (reject the input)
+    #[arg(
+        short = 'W',
+        long = "warnings",
+        value_name = "LEVEL",
+        default_value = "warn"
+    )]
+    warnings: String,
+
+    /// How errors and warnings are printed: human (with the source line), short
+    /// (one `file:line:column: message` line each), or json (one JSON object per line)
+    #[arg(long, default_value = "human")]
+    error_format: String,
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+
+    let opt_level: OptLevel = args
+        .opt_level
+        .parse()
+        .map_err(|e| anyhow::anyhow!("{}", e))?;
+    let exceptions: ExceptionModel = args
+        .exceptions
+        .parse()
+        .map_err(|e| anyhow::anyhow!("{}", e))?;
+    let warnings: WarningLevel = args
+        .warnings
+        .parse()
+        .map_err(|e| anyhow::anyhow!("{}", e))?;
+    let error_format: ErrorFormat = args
+        .error_format
+        .parse()
+        .map_err(|e| anyhow::anyhow!("{}", e))?;
+
+    let options = CompilerOptions {
+        // The inputs run in this process, so they are compiled for it
+        target: Target::host().unwrap_or_default(),
+        synthesize_methods: args.synthesize_methods,
+        auto_super_init: args.auto_super_init,
+        checked_int: args.checked_int,
+        opt_level,
+        exceptions,
+        warnings,
+        search_paths: args.include.into_iter().chain(env_search_paths()).collect(),
+        error_format,
+        ..Default::default()
+    };
+
+    let compiler = Compiler::new(options);
+    compiler
+        .run_repl(io::stdin().lock(), io::stderr())
+        .unwrap_or_else(|e| report(e, error_format));
+
+    Ok(())
+}
+
+/// Print an error that ends the session (the runtime is missing, or the
+/// options cannot run on this machine) and exit
+fn report(error: CompilerError, format: ErrorFormat) -> ! {
+    let color = std::io::stderr().is_terminal();
+    eprint!("{}", diagnostics::render(&error, format, color));
+    process::exit(1);
+}
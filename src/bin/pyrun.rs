@@ -1,11 +1,19 @@
 //! pyrun - Python-like interpreter interface
 //!
-//! Works like the Python interpreter: `pyrun script.py`
+//! Works like the Python interpreter: `pyrun script.py [args...]`
+//!
+//! With `--jit`, the program runs in pyrun's own process, compiled by LLVM's
+//! JIT, instead of being linked into an executable that pyrun then starts.
 
 use anyhow::Result;
 use clap::Parser;
-use compiler::{Compiler, CompilerOptions, Target};
+use compiler::{
+    diagnostics, env_search_paths, AllocMode, Compiler, CompilerError, CompilerOptions,
+    ErrorFormat, ExceptionModel, GcMode, Linker, OptLevel, Target, WarningLevel,
+};
+use std::io::IsTerminal;
 use std::path::PathBuf;
+use std::process;
 
 #[derive(Parser)]
 #[command(name = "pyrun")]
@@ -15,7 +23,19 @@ struct Args {
     /// Python file to run
     input: PathBuf,
 
-    /// Target architecture (x86_64 or riscv64)
+    /// Arguments passed to the program (its sys.argv[1:])
+    script_args: Vec<String>,
+
+    /// Run the program in this process with LLVM's JIT, without linking an executable
+    #[arg(long)]
+    jit: bool,
+
+    /// Also resolve imports against DIR, after the script's directory; may be
+    /// repeated, and the directories of the PYCPATH environment variable follow
+    #[arg(short = 'I', value_name = "DIR")]
+    include: Vec<PathBuf>,
+
+    /// Target architecture (x86_64, riscv64 or aarch64)
     #[arg(long, default_value = "x86_64")]
     target: String,
 
@@ -23,24 +43,149 @@ struct Args {
     #[arg(long)]
     emit_ast: bool,
 
+    /// Emit the typed IR, with every type spelled out (for debugging)
+    #[arg(long)]
+    emit_tir: bool,
+
     /// Emit LLVM IR (for debugging)
     #[arg(long)]
     emit_llvm: bool,
+
+    /// Give classes without __repr__/__eq__ field-wise defaults, as @dataclass does
+    #[arg(long)]
+    synthesize_methods: bool,
+
+    /// Call the base's __init__ first in a subclass __init__ that never calls super().__init__()
+    #[arg(long)]
+    auto_super_init: bool,
+
+    /// Raise OverflowError when int arithmetic overflows 64 bits instead of wrapping
+    #[arg(long)]
+    checked_int: bool,
+
+    /// Skip the ZeroDivisionError and IndexError checks (faster, but dividing
+    /// by zero or indexing out of range is then undefined behaviour)
+    #[arg(long)]
+    no_runtime_checks: bool,
+
+    /// Free class instances, strings and lists when their last reference goes away
+    #[arg(long)]
+    refcount: bool,
+
+    /// Reference count and fail if any instance, string or list is still alive
+    /// at exit (dicts, sets and tuples are never freed)
+    #[arg(long)]
+    leak_check: bool,
+
+    /// Garbage collector for class instances: none, or tracing (also frees cycles)
+    #[arg(long, default_value = "none")]
+    gc: String,
+
+    /// Runtime allocator: malloc, or arena (faster; nothing is freed until exit)
+    #[arg(long, default_value = "malloc")]
+    alloc: String,
+
+    /// Optimization level: 0, 1 (fold constants, remove dead code, inline small functions,
+    /// run LLVM's -O1 passes), 2, 3, or s (as 1, with LLVM's -O2, -O3 or -Os passes)
+    #[arg(long, default_value = "0")]
+    opt_level: String,
+
+    /// How try blocks catch exceptions: polling, or setjmp (x86_64 and aarch64 only; faster)
+    #[arg(long, default_value = "polling")]
+    exceptions: String,
+
+    /// Print a Python-style traceback, with the functions and source lines it
+    /// passed through, when an exception is not caught
+    #[arg(long)]
+    traceback: bool,
+
+    /// Raise RecursionError when a call would run more than DEPTH functions
+    /// nested (as Python's sys.setrecursionlimit), instead of overflowing the stack
+    #[arg(long, value_name = "DEPTH", value_parser = clap::value_parser!(u32).range(1..))]
+    recursion_limit: Option<u32>,
+
+    /// Linker for executables: auto (clang if installed, else lld), clang, or lld
+    /// (ld.lld, or the Rust toolchain's rust-lld; no clang needed)
+    #[arg(long, default_value = "auto")]
+    linker: String,
+
+    /// What to do with warnings: warn, allow (print nothing), or error (fail the build)
+    #[arg(
+        short = 'W',
+        long = "warnings",
+        value_name = "LEVEL",
+        default_value = "warn"
+    )]
+    warnings: String,
+
+    /// How errors and warnings are printed: human (with the source line), short
+    /// (one `file:line:column: message` line each), or json (one JSON object per line)
+    #[arg(long, default_value = "human")]
+    error_format: String,
 }
 
 fn main() -> Result<()> {
     let args = Args::parse();
 
     let target: Target = args.target.parse().map_err(|e| anyhow::anyhow!("{}", e))?;
+    let gc: GcMode = args.gc.parse().map_err(|e| anyhow::anyhow!("{}", e))?;
+    let alloc: AllocMode = args.alloc.parse().map_err(|e| anyhow::anyhow!("{}", e))?;
+    let opt_level: OptLevel = args
+        .opt_level
+        .parse()
+        .map_err(|e| anyhow::anyhow!("{}", e))?;
+    let exceptions: ExceptionModel = args
+        .exceptions
+        .parse()
+        .map_err(|e| anyhow::anyhow!("{}", e))?;
+    let linker: Linker = args.linker.parse().map_err(|e| anyhow::anyhow!("{}", e))?;
+    let warnings: WarningLevel = args
+        .warnings
+        .parse()
+        .map_err(|e| anyhow::anyhow!("{}", e))?;
+    let error_format: ErrorFormat = args
+        .error_format
+        .parse()
+        .map_err(|e| anyhow::anyhow!("{}", e))?;
 
     let options = CompilerOptions {
         emit_ast: args.emit_ast,
+        emit_tir: args.emit_tir,
         emit_llvm: args.emit_llvm,
+        synthesize_methods: args.synthesize_methods,
+        auto_super_init: args.auto_super_init,
+        checked_int: args.checked_int,
+        no_runtime_checks: args.no_runtime_checks,
+        refcount: args.refcount,
+        leak_check: args.leak_check,
+        gc,
+        alloc,
+        opt_level,
+        exceptions,
+        traceback: args.traceback,
+        recursion_limit: args.recursion_limit,
+        linker,
+        warnings,
+        error_format,
+        search_paths: args.include.into_iter().chain(env_search_paths()).collect(),
         target,
+        ..Default::default()
     };
 
     let compiler = Compiler::new(options);
-    compiler.run(&args.input, &[])?;
+    let ran = if args.jit {
+        compiler.run_jit(&args.input, &args.script_args)
+    } else {
+        compiler.run(&args.input, &args.script_args)
+    };
+    ran.unwrap_or_else(|e| report(e, error_format));
 
     Ok(())
 }
+
+/// Print a compile error with its source frame and exit, as compilers do
+fn report(error: CompilerError, format: ErrorFormat) -> ! {
+    let color = std::io::stderr().is_terminal();
+    eprint!("{}", diagnostics::render(&error, format, color));
+    process::exit(1);
+}
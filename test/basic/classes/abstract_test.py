@@ -0,0 +1,81 @@
+# Abstract method tests
+
+from abc import ABC, abstractmethod
+
+class Animal(ABC):
+    name: str
+
+    def __init__(self, name: str) -> None:
+        self.name = name
+
+    @abstractmethod
+    def sound(self) -> str:
+        pass
+
+    @abstractmethod
+    def legs(self) -> int: ...
+
+    def greet(self) -> str:
+        return "I am " + self.name
+
+class Bird(Animal):
+    def legs(self) -> int:
+        return 2
+
+class Parrot(Bird):
+    def sound(self) -> str:
+        return "squawk"
+
+class Dog(Animal):
+    def sound(self) -> str:
+        return "woof"
+
+    def legs(self) -> int:
+        return 4
+
+class Counter:
+    count: int
+
+    def __init__(self) -> None:
+        self.count = 0
+
+    def step(self) -> int:
+        """Subclasses say how far to count"""
+        raise NotImplementedError
+
+    def advance(self, times: int) -> None:
+        i: int = 0
+        while i < times:
+            self.count += 1
+            i += 1
+
+class ByOne(Counter):
+    def step(self) -> int:
+        return 1
+
+def describe_dog(dog: Dog) -> str:
+    return dog.greet() + " and I say " + dog.sound()
+
+def test_concrete_subclass() -> int:
+    """A subclass implementing every abstract method can be instantiated"""
+    dog: Dog = Dog("Rex")
+    print(describe_dog(dog))
+    return dog.legs()  # Expected: 4
+
+def test_inherited_implementation() -> int:
+    """Implementations may come from different classes in the chain"""
+    parrot: Parrot = Parrot("Polly")
+    print(parrot.greet(), parrot.sound())
+    return parrot.legs()  # Expected: 2
+
+def test_stub_body() -> int:
+    """A method raising NotImplementedError is abstract too"""
+    counter: ByOne = ByOne()
+    counter.advance(3)
+    return counter.count + counter.step()  # Expected: 4
+
+def main() -> int:
+    print(test_concrete_subclass())
+    print(test_inherited_implementation())
+    print(test_stub_body())
+    return 0
@@ -0,0 +1,96 @@
+# __call__ tests: calling instances like functions
+
+class Adder:
+    amount: int
+
+    def __init__(self, amount: int) -> None:
+        self.amount = amount
+
+    def __call__(self, x: int) -> int:
+        return x + self.amount
+
+class Counter:
+    calls: int
+
+    def __init__(self) -> None:
+        self.calls = 0
+
+    def __call__(self) -> int:
+        self.calls = self.calls + 1
+        return self.calls
+
+class Greeter:
+    greeting: str
+
+    def __init__(self, greeting: str) -> None:
+        self.greeting = greeting
+
+    def __call__(self, name: str, punctuation: str = "!") -> str:
+        return self.greeting + ", " + name + punctuation
+
+class Pipeline:
+    first: Adder
+    second: Adder
+
+    def __init__(self, first: Adder, second: Adder) -> None:
+        self.first = first
+        self.second = second
+
+    def run(self, x: int) -> int:
+        return self.second(self.first(x))
+
+def make_adder(amount: int) -> Adder:
+    return Adder(amount)
+
+def apply_twice(f: Adder, x: int) -> int:
+    return f(f(x))
+
+def test_call_variable() -> int:
+    """A variable holding an instance can be called"""
+    add5: Adder = Adder(5)
+    return add5(10)  # Expected: 15
+
+def test_call_state() -> int:
+    """__call__ can update the instance"""
+    counter: Counter = Counter()
+    counter()
+    counter()
+    return counter()  # Expected: 3
+
+def test_call_defaults() -> int:
+    """__call__ takes default arguments like any method"""
+    hello: Greeter = Greeter("Hello")
+    print(hello("world"))
+    print(hello("there", "?"))
+    return 1  # Expected: 1
+
+def test_call_param() -> int:
+    """Callable objects can be passed to functions"""
+    return apply_twice(Adder(3), 1)  # Expected: 7
+
+def test_call_field() -> int:
+    """Fields holding callable objects can be called"""
+    p: Pipeline = Pipeline(Adder(1), Adder(100))
+    return p.run(5)  # Expected: 106
+
+def test_call_result() -> int:
+    """The result of a call can be called directly"""
+    return make_adder(20)(22)  # Expected: 42
+
+def test_call_in_map() -> int:
+    """Callable objects work as the function of map()"""
+    add1: Adder = Adder(1)
+    total: int = 0
+    for y in map(add1, [1, 2, 3]):
+        total += y
+    return total  # Expected: 9
+
+def main() -> int:
+    print(test_call_variable())
+    print(test_call_state())
+    print(test_call_defaults())
+    print(test_call_param())
+    print(test_call_field())
+    print(test_call_result())
+    print(test_call_in_map())
+    return 0
@@ -0,0 +1,93 @@
+# @dataclass tests: generated __init__, __eq__ and __repr__
+
+from dataclasses import dataclass
+import dataclasses
+
+@dataclass
+class Point:
+    x: int
+    y: int
+
+@dataclass
+class Item:
+    name: str
+    price: float
+    count: int = 1
+    fragile: bool = False
+
+@dataclasses.dataclass
+class Counter:
+    value: int = 0
+
+@dataclass
+class Line:
+    start: Point
+    end: Point
+
+@dataclass
+class Temperature:
+    celsius: float
+
+    def __repr__(self) -> str:
+        return str(self.celsius) + " C"
+
+    def fahrenheit(self) -> float:
+        return self.celsius * 1.8 + 32.0
+
+def test_init() -> int:
+    """__init__ takes the fields in order and stores them"""
+    p: Point = Point(3, 4)
+    return p.x * 10 + p.y  # Expected: 34
+
+def test_defaults() -> int:
+    """Fields with a value in the class body are optional __init__ arguments"""
+    a: Item = Item("pen", 1.5)
+    b: Item = Item("vase", 20.0, 2, True)
+    print(a.count, a.fragile, b.count, b.fragile)
+    return a.count + b.count  # Expected: 3
+
+def test_eq() -> int:
+    """== and != compare field by field"""
+    count: int = 0
+    if Point(1, 2) == Point(1, 2):
+        count += 1
+    if Point(1, 2) != Point(2, 1):
+        count += 1
+    if not (Item("pen", 1.5) == Item("pen", 1.5, 2)):
+        count += 1
+    if Line(Point(0, 0), Point(1, 1)) == Line(Point(0, 0), Point(1, 1)):
+        count += 1
+    return count  # Expected: 4
+
+def test_repr() -> int:
+    """print() and str() use the generated __repr__"""
+    p: Point = Point(3, -4)
+    print(p)
+    print(Item("pen", 1.5))
+    print(Line(Point(0, 0), Point(1, 1)))
+    s: str = str(Counter())
+    print(s)
+    return len(s)  # Expected: 16
+
+def test_own_methods() -> int:
+    """A method the class defines itself is kept, and other methods work as usual"""
+    t: Temperature = Temperature(37.5)
+    print(t)
+    print(t == Temperature(37.5))
+    return int(t.fahrenheit())  # Expected: 99
+
+def test_mutation() -> int:
+    """Dataclass fields can be reassigned"""
+    p: Point = Point(1, 1)
+    p.x = 5
+    print(p)
+    return p.x  # Expected: 5
+
+def main() -> int:
+    print(test_init())
+    print(test_defaults())
+    print(test_eq())
+    print(test_repr())
+    print(test_own_methods())
+    print(test_mutation())
+    return 0
@@ -0,0 +1,42 @@
+# --synthesize-methods test program: classes without __repr__/__eq__ get
+# field-wise defaults, printed and compared like dataclasses
+
+class Point:
+    x: int
+    y: int
+
+    def __init__(self, x: int, y: int) -> None:
+        self.x = x
+        self.y = y
+
+class Point3(Point):
+    z: int
+
+    def __init__(self, x: int, y: int, z: int) -> None:
+        self.x = x
+        self.y = y
+        self.z = z
+
+class Label:
+    text: str
+    at: Point
+
+    def __init__(self, text: str, at: Point) -> None:
+        self.text = text
+        self.at = at
+
+class Money:
+    cents: int
+
+    def __init__(self, cents: int) -> None:
+        self.cents = cents
+
+    def __repr__(self) -> str:
+        return "$" + str(self.cents // 100)
+
+print(Point(1, 2))
+print(Point3(1, 2, 3))
+print(Label("origin", Point(0, 0)))
+print(Money(500))
+print(Point(1, 2) == Point(1, 2), Point(1, 2) == Point(2, 1))
+print(Money(1) == Money(1))
@@ -0,0 +1,63 @@
+# --gc=tracing test program: rings of nodes point at each other, so only the
+# tracing collector can free them; the ones still in use must survive
+
+from typing import Optional
+
+class Link:
+    value: int
+    prev: Optional["Link"]
+    next: Optional["Link"]
+
+    def __init__(self, value: int) -> None:
+        self.value = value
+        self.prev = None
+        self.next = None
+
+def ring(size: int) -> Link:
+    """A circular doubly linked list of 0..size-1"""
+    first: Link = Link(0)
+    last: Link = first
+    i: int = 1
+    while i < size:
+        link: Link = Link(i)
+        link.prev = last
+        last.next = link
+        last = link
+        i += 1
+    last.next = first
+    first.prev = last
+    return first
+
+def ring_sum(start: Link) -> int:
+    total: int = start.value
+    node: Optional[Link] = start.next
+    while node is not None and node is not start:
+        total += node.value
+        node = node.next
+    return total
+
+def churn(rounds: int, size: int) -> int:
+    """Each round drops the previous ring, leaving garbage cycles behind"""
+    acc: int = 0
+    r: int = 0
+    while r < rounds:
+        current: Link = ring(size)
+        acc += ring_sum(current)
+        r += 1
+    return acc
+
+def pair_sum(a: Link, b: Link) -> int:
+    return a.value + b.value
+
+kept: Link = ring(10)
+
+def main() -> int:
+    held: Link = ring(20)
+    stored: list[Link] = [ring(5)]
+    print(churn(200, 30))
+    # Arguments evaluated before a collection-triggering call stay alive
+    print(pair_sum(Link(5), ring(4)))
+    print(ring_sum(kept), ring_sum(held), ring_sum(stored[0]))
+    return 0
+
+main()
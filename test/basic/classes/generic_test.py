@@ -0,0 +1,129 @@
+# Generic class tests
+
+from typing import Generic, Optional, TypeVar
+
+T = TypeVar("T")
+K = TypeVar("K")
+V = TypeVar("V")
+
+class Stack(Generic[T]):
+    items: list[T]
+
+    def __init__(self) -> None:
+        self.items = []
+
+    def push(self, item: T) -> None:
+        self.items.append(item)
+
+    def pop(self) -> T:
+        last: int = len(self.items) - 1
+        item: T = self.items[last]
+        del self.items[last]
+        return item
+
+    def size(self) -> int:
+        return len(self.items)
+
+    def copy(self) -> "Stack[T]":
+        result: Stack[T] = Stack()
+        for item in self.items:
+            result.push(item)
+        return result
+
+class Pair(Generic[K, V]):
+    key: K
+    value: V
+
+    def __init__(self, key: K, value: V) -> None:
+        self.key = key
+        self.value = value
+
+    def swap(self) -> "Pair[V, K]":
+        return Pair(self.value, self.key)
+
+class Node(Generic[T]):
+    value: T
+    next: Optional["Node[T]"]
+
+    def __init__(self, value: T) -> None:
+        self.value = value
+        self.next = None
+
+def total(stack: Stack[int]) -> int:
+    result: int = 0
+    for item in stack.items:
+        result += item
+    return result
+
+def make_stack(n: int) -> Stack[int]:
+    stack: Stack[int] = Stack()
+    i: int = 0
+    while i < n:
+        stack.push(i * i)
+        i += 1
+    return stack
+
+def test_stack_int() -> int:
+    """A Stack[int] pushes and pops ints"""
+    stack: Stack[int] = Stack()
+    stack.push(3)
+    stack.push(4)
+    stack.push(5)
+    print(stack.pop(), stack.size())
+    return total(stack)  # Expected: 7
+
+def test_stack_str() -> int:
+    """A Stack[str] is a separate class with the same methods"""
+    words: Stack[str] = Stack()
+    words.push("generic")
+    words.push("hello")
+    top: str = words.pop()
+    print(top.upper(), words.size())
+    return len(top)  # Expected: 5
+
+def test_copy_and_return() -> int:
+    """Instances are constructed from the expected type, also in methods"""
+    squares: Stack[int] = make_stack(4)
+    copied: Stack[int] = squares.copy()
+    copied.push(100)
+    print(squares.size(), copied.size())
+    return total(copied)  # Expected: 114
+
+def test_two_parameters() -> int:
+    """Pair[K, V] binds each type parameter, and swap() gives Pair[V, K]"""
+    pair: Pair[str, int] = Pair("answer", 42)
+    swapped: Pair[int, str] = pair.swap()
+    print(pair.key, pair.value)
+    print(swapped.key, swapped.value)
+    return swapped.key + len(swapped.value)  # Expected: 48
+
+def test_linked_nodes() -> int:
+    """A generic class may refer to its own instance through Optional"""
+    head: Node[int] = Node(1)
+    second: Node[int] = Node(2)
+    third: Node[int] = Node(3)
+    second.next = third
+    head.next = second
+    result: int = 0
+    node: Optional[Node[int]] = head
+    while node is not None:
+        result += node.value
+        node = node.next
+    return result  # Expected: 6
+
+def test_nested_arguments() -> int:
+    """Type arguments may themselves be generic"""
+    stacks: Stack[Stack[int]] = Stack()
+    stacks.push(make_stack(2))
+    stacks.push(make_stack(3))
+    inner: Stack[int] = stacks.pop()
+    return inner.size() * 10 + stacks.size()  # Expected: 31
+
+def main() -> int:
+    print(test_stack_int())
+    print(test_stack_str())
+    print(test_copy_and_return())
+    print(test_two_parameters())
+    print(test_linked_nodes())
+    print(test_nested_arguments())
+    return 0
@@ -0,0 +1,65 @@
+# Fields that __init__ assigns without class-level annotations
+
+from typing import Generic, TypeVar
+
+T = TypeVar("T")
+
+class Point:
+    def __init__(self, x: int, y: int) -> None:
+        self.x = x
+        self.y = y
+        self.label = "point"
+        self.norm = x * x + y * y
+
+class Shape:
+    def __init__(self, name: str, scale: float) -> None:
+        self.name = name
+        self.scale = scale
+        self.origin = Point(0, 0)
+        self.sides: list[int] = []
+
+class Square(Shape):
+    def __init__(self, side: int) -> None:
+        super().__init__("square", 1.5)
+        self.name = "square!"
+        self.side = side
+        self.area = self.side * self.side
+        self.sides.append(side)
+
+class Pair:
+    def __init__(self, first: int, second: str) -> None:
+        self.first, self.second = first, second
+
+class Box(Generic[T]):
+    def __init__(self, item: T) -> None:
+        self.item = item
+        self.count = 1
+
+def test_params_and_literals() -> int:
+    p: Point = Point(3, 4)
+    print(p.label)
+    return p.x + p.y + p.norm  # Expected: 32
+
+def test_inherited_fields() -> int:
+    s: Square = Square(5)
+    print(s.name)
+    print(s.scale)
+    print(len(s.sides))
+    return s.area + s.origin.x  # Expected: 25
+
+def test_tuple_assignment() -> int:
+    pair: Pair = Pair(7, "seven")
+    print(pair.second)
+    return pair.first  # Expected: 7
+
+def test_generic_field() -> int:
+    box: Box[str] = Box("hello")
+    print(box.item)
+    return box.count  # Expected: 1
+
+def main() -> int:
+    print(test_params_and_literals())
+    print(test_inherited_fields())
+    print(test_tuple_assignment())
+    print(test_generic_field())
+    return 0
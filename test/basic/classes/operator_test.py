@@ -0,0 +1,192 @@
+# Operator overloading tests: arithmetic, reflected, in-place, unary and
+# comparison operators defined by dunder methods
+
+class Vector:
+    x: int
+    y: int
+
+    def __init__(self, x: int, y: int) -> None:
+        self.x = x
+        self.y = y
+
+    def __add__(self, other: "Vector") -> "Vector":
+        return Vector(self.x + other.x, self.y + other.y)
+
+    def __sub__(self, other: "Vector") -> "Vector":
+        return Vector(self.x - other.x, self.y - other.y)
+
+    def __mul__(self, k: int) -> "Vector":
+        return Vector(self.x * k, self.y * k)
+
+    def __rmul__(self, k: int) -> "Vector":
+        return Vector(self.x * k, self.y * k)
+
+    def __neg__(self) -> "Vector":
+        return Vector(-self.x, -self.y)
+
+    def __eq__(self, other: "Vector") -> bool:
+        return self.x == other.x and self.y == other.y
+
+    def __repr__(self) -> str:
+        return "Vector(" + str(self.x) + ", " + str(self.y) + ")"
+
+class Money:
+    cents: int
+
+    def __init__(self, cents: int) -> None:
+        self.cents = cents
+
+    def __lt__(self, other: "Money") -> bool:
+        return self.cents < other.cents
+
+    def __le__(self, other: "Money") -> bool:
+        return self.cents <= other.cents
+
+class Tally:
+    count: int
+    updates: int
+
+    def __init__(self) -> None:
+        self.count = 0
+        self.updates = 0
+
+    def __iadd__(self, n: int) -> "Tally":
+        self.count = self.count + n
+        self.updates = self.updates + 1
+        return self
+
+class Matrix:
+    a: int
+    b: int
+    c: int
+    d: int
+
+    def __init__(self, a: int, b: int, c: int, d: int) -> None:
+        self.a = a
+        self.b = b
+        self.c = c
+        self.d = d
+
+    def __mul__(self, other: "Matrix") -> "Matrix":
+        return Matrix(
+            self.a * other.a + self.b * other.c,
+            self.a * other.b + self.b * other.d,
+            self.c * other.a + self.d * other.c,
+            self.c * other.b + self.d * other.d,
+        )
+
+    def __floordiv__(self, k: int) -> "Matrix":
+        return Matrix(self.a // k, self.b // k, self.c // k, self.d // k)
+
+    def trace(self) -> int:
+        return self.a + self.d
+
+order: str = ""
+
+def tagged(tag: str, cents: int) -> Money:
+    global order
+    order = order + tag
+    return Money(cents)
+
+def test_arithmetic() -> int:
+    """+, - and * call __add__, __sub__ and __mul__"""
+    a: Vector = Vector(1, 2)
+    b: Vector = Vector(3, 5)
+    print(a + b)
+    print(b - a)
+    print(a * 3)
+    c: Vector = a + b * 2 - Vector(1, 1)
+    return c.x * 10 + c.y  # Expected: 71
+
+def test_reflected() -> int:
+    """int * Vector falls back to Vector.__rmul__"""
+    v: Vector = 3 * Vector(2, 1)
+    print(v)
+    return v.x + v.y  # Expected: 9
+
+def test_unary() -> int:
+    """Unary minus calls __neg__"""
+    v: Vector = -Vector(4, -6)
+    print(v)
+    return v.y  # Expected: 6
+
+def test_equality() -> int:
+    """== and != call __eq__"""
+    count: int = 0
+    if Vector(1, 2) + Vector(2, 1) == Vector(3, 3):
+        count += 1
+    if Vector(1, 2) != Vector(2, 1):
+        count += 1
+    return count  # Expected: 2
+
+def test_ordering() -> int:
+    """< and <= call __lt__ and __le__; > and >= use them reflected"""
+    cheap: Money = Money(150)
+    dear: Money = Money(900)
+    count: int = 0
+    if cheap < dear:
+        count += 1
+    if dear > cheap:
+        count += 1
+    if cheap <= Money(150):
+        count += 1
+    if dear >= cheap:
+        count += 1
+    if not (dear < cheap):
+        count += 1
+    return count  # Expected: 5
+
+def test_reflected_order() -> int:
+    """A reflected comparison still evaluates the left operand first"""
+    global order
+    order = ""
+    if tagged("a", 5) > tagged("b", 1):
+        order = order + "!"
+    print(order)
+    return len(order)  # Expected: 3
+
+def test_chained() -> int:
+    """Chained comparisons dispatch each link"""
+    if Money(1) < Money(2) <= Money(2):
+        return 1  # Expected: 1
+    return 0
+
+def test_augmented() -> int:
+    """+= uses __iadd__ when defined and __add__ otherwise"""
+    t: Tally = Tally()
+    t += 5
+    t += 7
+    print(t.updates)
+    v: Vector = Vector(1, 1)
+    v += Vector(2, 3)
+    v *= 2
+    print(v)
+    return t.count  # Expected: 12
+
+def test_matrix() -> int:
+    """Classes like matrices can define their own arithmetic"""
+    m: Matrix = Matrix(1, 1, 1, 0)
+    p: Matrix = m * m * m * m * m
+    print(p.b)
+    half: Matrix = p // 2
+    return half.trace()  # Expected: 5
+
+def test_str_augmented() -> int:
+    """+= also works on strings"""
+    s: str = "ab"
+    s += "cd"
+    print(s)
+    return len(s)  # Expected: 4
+
+def main() -> int:
+    print(test_arithmetic())
+    print(test_reflected())
+    print(test_unary())
+    print(test_equality())
+    print(test_ordering())
+    print(test_reflected_order())
+    print(test_chained())
+    print(test_augmented())
+    print(test_matrix())
+    print(test_str_augmented())
+    return 0
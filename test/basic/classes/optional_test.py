@@ -0,0 +1,166 @@
+# Optional type tests
+
+from typing import Optional, Union
+
+class Node:
+    value: int
+    next: Optional["Node"]
+
+    def __init__(self, value: int) -> None:
+        self.value = value
+        self.next = None
+
+class LinkedList:
+    head: Optional[Node]
+
+    def __init__(self) -> None:
+        self.head = None
+
+    def push(self, value: int) -> None:
+        node: Node = Node(value)
+        node.next = self.head
+        self.head = node
+
+    def total(self) -> int:
+        result: int = 0
+        node: Optional[Node] = self.head
+        while node is not None:
+            result += node.value
+            node = node.next
+        return result
+
+    def first(self) -> int:
+        if self.head is None:
+            return -1
+        return self.head.value
+
+def find(values: list[int], target: int) -> Optional[int]:
+    i: int = 0
+    for value in values:
+        if value == target:
+            return i
+        i += 1
+    return None
+
+def describe(limit: Optional[int] = None) -> str:
+    if limit is not None:
+        return "limit " + str(limit + 1)
+    return "no limit"
+
+def pick(a: int | None, b: Union[int, None]) -> int:
+    if a is None or b is None:
+        return 0
+    return a * b
+
+def last(node: Node) -> Node | None:
+    if node.next is None:
+        return
+    current: Optional[Node] = node.next
+    while current is not None and current.next is not None:
+        current = current.next
+    return current
+
+def report(message: str) -> None:
+    if message == "":
+        return None
+    print(message)
+
+def test_returning_none() -> int:
+    """return None ends a function without a return value, and a bare
+    return gives None from one returning an Optional"""
+    report("")
+    report("reported")
+    head: Node = Node(1)
+    print(last(head) is None)
+    head.next = Node(2)
+    tail: Optional[Node] = last(head)
+    if tail is not None:
+        return tail.value  # Expected: 2
+    return 0
+
+def test_none_values() -> int:
+    """None is a value that can be stored, printed and tested"""
+    nothing = None
+    print(nothing, str(nothing), repr(nothing))
+    print(nothing is None, nothing is not None)
+    count: int = 0
+    if not nothing:
+        count += 1
+    node: Optional[Node] = nothing
+    if node is None:
+        count += 1
+    print(None)
+    return count  # Expected: 2
+
+def test_linked_list() -> int:
+    """Walking a linked list narrows each node after its None check"""
+    items: LinkedList = LinkedList()
+    print(items.first())
+    items.push(3)
+    items.push(4)
+    items.push(5)
+    print(items.first())
+    return items.total()  # Expected: 12
+
+def test_parameters() -> int:
+    """Optional parameters accept None, values, and default to None"""
+    print(describe())
+    print(describe(4))
+    print(describe(None))
+    print(pick(6, 7), pick(None, 7), pick(6, None))
+    return pick(2, 3)  # Expected: 6
+
+def test_return_values() -> int:
+    """A function may return a value or None"""
+    found: Optional[int] = find([5, 7, 9], 9)
+    missing: Optional[int] = find([5, 7, 9], 4)
+    count: int = 0
+    if found is not None:
+        count += found
+    if missing is None:
+        count += 10
+    print(found, missing)
+    return count  # Expected: 12
+
+def test_narrowing() -> int:
+    """Narrowing follows and, not, early returns and reassignment"""
+    x: Optional[int] = None
+    count: int = 0
+    if x is not None and x > 0:
+        count += 100
+    x = 5
+    if x is not None and x > 0:
+        count += 1
+    if not (x is None):
+        count += x
+    if x:
+        count += 1
+    x = None
+    if not x:
+        count += 1
+    print(x)
+    return count  # Expected: 8
+
+def test_optional_str() -> int:
+    """Optional values print as their value or None"""
+    name: Optional[str] = "Ada"
+    print(name)
+    if name is not None:
+        print(name.upper(), len(name))
+    name = None
+    print(name)
+    node: Optional[Node] = Node(1)
+    print(node is None)
+    node = None
+    print(node is None)
+    return 0
+
+def main() -> int:
+    print(test_linked_list())
+    print(test_parameters())
+    print(test_return_values())
+    print(test_narrowing())
+    print(test_optional_str())
+    print(test_returning_none())
+    print(test_none_values())
+    return 0
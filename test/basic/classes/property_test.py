@@ -0,0 +1,103 @@
+# @property and @<name>.setter tests
+
+class Rectangle:
+    width: int
+    height: int
+
+    def __init__(self, width: int, height: int) -> None:
+        self.width = width
+        self.height = height
+
+    @property
+    def area(self) -> int:
+        return self.width * self.height
+
+    @property
+    def label(self) -> str:
+        return str(self.width) + "x" + str(self.height)
+
+class BalanceError(Exception):
+    pass
+
+class Account:
+    _balance: int
+    changes: int
+
+    def __init__(self, balance: int) -> None:
+        self._balance = balance
+        self.changes = 0
+
+    @property
+    def balance(self) -> int:
+        return self._balance
+
+    @balance.setter
+    def balance(self, value: int) -> None:
+        if value < 0:
+            raise BalanceError("balance cannot be negative")
+        self._balance = value
+        self.changes = self.changes + 1
+
+    def deposit(self, amount: int) -> None:
+        self.balance = self.balance + amount
+
+class Square(Rectangle):
+    def __init__(self, side: int) -> None:
+        self.width = side
+        self.height = side
+
+    @property
+    def side(self) -> int:
+        return self.width
+
+def test_getter() -> int:
+    """Reading a property runs its getter"""
+    r: Rectangle = Rectangle(3, 4)
+    print(r.label)
+    return r.area  # Expected: 12
+
+def test_getter_recomputed() -> int:
+    """The getter runs on every read, so it sees later field changes"""
+    r: Rectangle = Rectangle(2, 5)
+    before: int = r.area
+    r.width = 3
+    return before * 100 + r.area  # Expected: 1015
+
+def test_getter_in_expressions() -> int:
+    """Properties can be used wherever a value can"""
+    r: Rectangle = Rectangle(2, 3)
+    print(f"{r.label} has area {r.area}")
+    total: int = r.area * 2 + Rectangle(1, 1).area
+    return total  # Expected: 13
+
+def test_setter() -> int:
+    """Assigning a property runs its setter"""
+    a: Account = Account(10)
+    a.balance = 25
+    a.deposit(5)
+    print(a.changes)
+    return a.balance  # Expected: 30
+
+def test_setter_validation() -> int:
+    """A setter can reject values by raising"""
+    a: Account = Account(10)
+    try:
+        a.balance = -5
+    except BalanceError:
+        print("rejected")
+    return a.balance  # Expected: 10
+
+def test_inherited_property() -> int:
+    """Properties are inherited, and subclasses can add their own"""
+    s: Square = Square(6)
+    print(s.label)
+    return s.area + s.side  # Expected: 42
+
+def main() -> int:
+    print(test_getter())
+    print(test_getter_recomputed())
+    print(test_getter_in_expressions())
+    print(test_setter())
+    print(test_setter_validation())
+    print(test_inherited_property())
+    return 0
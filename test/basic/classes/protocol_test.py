@@ -0,0 +1,103 @@
+# Protocol tests
+
+from typing import Protocol
+
+class Shape(Protocol):
+    def area(self) -> int: ...
+
+    def name(self) -> str: ...
+
+class Scalable(Protocol):
+    def scale(self, factor: int) -> None: ...
+
+class Square:
+    side: int
+
+    def __init__(self, side: int) -> None:
+        self.side = side
+
+    def area(self) -> int:
+        return self.side * self.side
+
+    def name(self) -> str:
+        return "square"
+
+    def scale(self, factor: int) -> None:
+        self.side = self.side * factor
+
+class Rect:
+    width: int
+    height: int
+
+    def __init__(self, width: int, height: int) -> None:
+        self.width = width
+        self.height = height
+
+    def area(self) -> int:
+        return self.width * self.height
+
+    def name(self) -> str:
+        return "rect"
+
+    def perimeter(self) -> int:
+        return 2 * (self.width + self.height)
+
+class Cube(Square):
+    def name(self) -> str:
+        return "cube"
+
+def describe(shape: Shape) -> str:
+    return shape.name() + " " + str(shape.area())
+
+def total_area(first: Shape, second: Shape) -> int:
+    return first.area() + second.area()
+
+def grow(item: Scalable, times: int) -> None:
+    i: int = 0
+    while i < times:
+        item.scale(2)
+        i += 1
+
+def describe_all(shape: Shape, label: str = "shape") -> str:
+    return label + ": " + describe(shape)
+
+def test_structural_match() -> int:
+    """Classes providing the protocol methods are passed without inheriting it"""
+    square: Square = Square(3)
+    rect: Rect = Rect(2, 5)
+    print(describe(square))
+    print(describe(rect))
+    return square.area() + rect.area()  # Expected: 19
+
+def test_mixed_arguments() -> int:
+    """Each protocol parameter takes its own class"""
+    print(total_area(Square(2), Rect(3, 4)))
+    print(total_area(Rect(1, 1), Rect(2, 2)))
+    return total_area(Square(1), Square(5))  # Expected: 26
+
+def test_inherited_methods() -> int:
+    """Methods inherited from a base class count toward the protocol"""
+    cube: Cube = Cube(2)
+    print(describe(cube))
+    return cube.area()  # Expected: 4
+
+def test_mutation() -> int:
+    """A protocol parameter is the object passed, so changes are visible"""
+    square: Square = Square(1)
+    grow(square, 3)
+    print(describe(square))
+    return square.side  # Expected: 8
+
+def test_nested_calls() -> int:
+    """Protocol functions may call each other and take defaults"""
+    print(describe_all(Rect(2, 3)))
+    print(describe_all(Square(4), "big"))
+    return len(describe_all(Square(1)))  # Expected: 15
+
+def main() -> int:
+    print(test_structural_match())
+    print(test_mixed_arguments())
+    print(test_inherited_methods())
+    print(test_mutation())
+    print(test_nested_calls())
+    return 0
@@ -0,0 +1,85 @@
+# --leak-check test program: every instance is freed when its last
+# reference goes away, so nothing is left alive at exit
+
+from typing import Optional
+
+class Node:
+    value: int
+    next: Optional["Node"]
+
+    def __init__(self, value: int, next: Optional["Node"]) -> None:
+        self.value = value
+        self.next = next
+
+    def last(self) -> "Node":
+        node: Node = self
+        while node.next is not None:
+            node = node.next
+        return node
+
+class Stack:
+    top: Optional[Node]
+    size: int
+
+    def __init__(self) -> None:
+        self.top = None
+        self.size = 0
+
+    def push(self, value: int) -> "Stack":
+        self.top = Node(value, self.top)
+        self.size += 1
+        return self
+
+    def pop(self) -> int:
+        node: Optional[Node] = self.top
+        if node is None:
+            return -1
+        self.top = node.next
+        self.size -= 1
+        return node.value
+
+def build(n: int) -> Node:
+    head: Node = Node(0, None)
+    i: int = 1
+    while i < n:
+        head = Node(i, head)
+        i += 1
+    return head
+
+def total(head: Optional[Node]) -> int:
+    result: int = 0
+    node: Optional[Node] = head
+    while node is not None:
+        result += node.value
+        node = node.next
+    return result
+
+def churn(rounds: int) -> int:
+    """Replacing a variable frees the list it held"""
+    acc: int = 0
+    r: int = 0
+    while r < rounds:
+        chain: Node = build(50)
+        acc += total(chain)
+        r += 1
+    return acc
+
+kept: Optional[Node] = build(3)
+
+def main() -> int:
+    print(total(build(4)), total(kept))
+    print(churn(100))
+    Node(7, Node(8, None))
+    print(build(5).last().value)
+
+    stack: Stack = Stack()
+    stack.push(1).push(2).push(3)
+    print(stack.pop(), stack.pop(), stack.size)
+
+    head: Node = build(3)
+    tail: Node = head.last()
+    head = Node(9, None)
+    print(head.value, tail.value)
+    return 0
+
+main()
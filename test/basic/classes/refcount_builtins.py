@@ -0,0 +1,27 @@
+# --leak-check test program: strings and lists are counted, and a list holds a
+# reference to each of its items, so everything is freed but the string stored
+# in the dict, which is never freed itself
+
+class Item:
+    value: int
+
+    def __init__(self, value: int) -> None:
+        self.value = value
+
+def fill() -> int:
+    items: list[Item] = [Item(3)]
+    items.append(Item(5))
+    items[0] = Item(6)
+    popped: Item = items.pop()
+    missing: int = items.count(Item(9))
+    words: list[str] = ["abc"]
+    text: str = words[0] + words[0]
+    words.append(text.upper())
+    nested: list[list[str]] = [words, ["x" + text]]
+    position: int = words.index("ab" + "c")
+    kept: Item = Item(4)
+    names: dict[str, int] = {text + "!": 1}
+    print(items[0].value, popped.value, text, len(nested[1][0]), position, missing)
+    return kept.value + len(names)
+
+fill()
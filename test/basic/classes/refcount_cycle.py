@@ -0,0 +1,19 @@
+# --leak-check test program: a reference cycle is never freed, so the
+# leak check reports both instances and fails the run
+
+from typing import Optional
+
+class Peer:
+    other: Optional["Peer"]
+
+    def __init__(self) -> None:
+        self.other = None
+
+def pair() -> None:
+    a: Peer = Peer()
+    b: Peer = Peer()
+    a.other = b
+    b.other = a
+
+pair()
+print("done")
@@ -0,0 +1,83 @@
+# Instances and lists that do not escape their function live on its stack,
+# one slot per variable that every construction into it reuses
+
+class Vec2:
+    x: int
+    y: int
+
+    def __init__(self, x: int, y: int) -> None:
+        self.x = x
+        self.y = y
+
+    def dot(self, other: "Vec2") -> int:
+        return self.x * other.x + self.y * other.y
+
+    def scaled(self, k: int) -> "Vec2":
+        return Vec2(self.x * k, self.y * k)
+
+def area(v: Vec2) -> int:
+    return v.x * v.y
+
+def test_instances_in_loop() -> int:
+    """A new instance per iteration; its arguments read the previous one"""
+    v: Vec2 = Vec2(1, 0)
+    total: int = 0
+    for i in range(10):
+        v = Vec2(v.y + i, v.x)
+        w: Vec2 = Vec2(i, -i)
+        total += v.dot(w) + area(w)
+    print(v.x, v.y, total)
+    return total  # Expected: -125
+
+def test_lists_in_loop() -> int:
+    """A list rebuilt every iteration, from its previous items"""
+    pair: list[int] = [0, 1]
+    for _ in range(20):
+        pair = [pair[1], pair[0] + pair[1]]
+    longest: int = 0
+    for n in range(12):
+        buf: list[int] = [n]
+        for j in range(n):
+            buf.append(j * n)
+        total: int = 0
+        for item in buf:
+            total += item
+        if total > longest:
+            longest = total
+    print(pair, longest)
+    return len(pair)  # Expected: 2
+
+def test_typed_lists_in_loop() -> int:
+    """Float and bool lists reuse their slots too"""
+    count: int = 0
+    for i in range(6):
+        weights: list[float] = [0.5 * i, 1.5]
+        flags: list[bool] = [i % 2 == 0, i > 3, True]
+        if weights[0] < weights[1] and flags[0]:
+            count += 1
+        count += flags.count(True)
+    return count  # Expected: 13
+
+def test_escaping_values() -> int:
+    """Values that outlive the iteration stay on the heap"""
+    kept: list[Vec2] = []
+    rows: list[list[int]] = []
+    for i in range(4):
+        v: Vec2 = Vec2(i, i * i)
+        kept.append(v)
+        row: list[int] = [i, -i]
+        rows.append(row)
+        doubled: Vec2 = v.scaled(2)
+        kept.append(doubled)
+    total: int = 0
+    for k in kept:
+        total += k.x + k.y
+    print(total, rows)
+    return len(kept)  # Expected: 8
+
+def main() -> int:
+    print(test_instances_in_loop())
+    print(test_lists_in_loop())
+    print(test_typed_lists_in_loop())
+    print(test_escaping_values())
+    return 0
@@ -0,0 +1,124 @@
+# @staticmethod and @classmethod tests
+
+class Temperature:
+    celsius: float
+
+    def __init__(self, celsius: float) -> None:
+        self.celsius = celsius
+
+    @staticmethod
+    def to_fahrenheit(celsius: float) -> float:
+        return celsius * 1.8 + 32.0
+
+    @staticmethod
+    def is_freezing(celsius: float, threshold: float = 0.0) -> bool:
+        return celsius <= threshold
+
+    @classmethod
+    def from_fahrenheit(cls, fahrenheit: float) -> "Temperature":
+        return cls((fahrenheit - 32.0) / 1.8)
+
+    @classmethod
+    def boiling(cls) -> "Temperature":
+        return cls.from_fahrenheit(212.5)
+
+    def fahrenheit(self) -> float:
+        return Temperature.to_fahrenheit(self.celsius)
+
+class Counter:
+    count: int
+
+    def __init__(self) -> None:
+        self.count = 0
+
+    @staticmethod
+    def describe() -> str:
+        return "counts things"
+
+    @staticmethod
+    def add(a: int, b: int) -> int:
+        return a + b
+
+    def bump(self) -> int:
+        self.count = self.add(self.count, 1)
+        return self.count
+
+class LoudCounter(Counter):
+    def shout(self) -> str:
+        return self.describe().upper()
+
+made: int = 0
+
+def make_counter() -> Counter:
+    global made
+    made += 1
+    return Counter()
+
+def test_static_on_class() -> int:
+    """A static method is called on the class name without an instance"""
+    print(Counter.describe())
+    return Counter.add(2, 3)  # Expected: 5
+
+def test_static_defaults() -> int:
+    """Static methods take default arguments like functions do"""
+    count: int = 0
+    if Temperature.is_freezing(-1.5):
+        count += 1
+    if not Temperature.is_freezing(1.5):
+        count += 1
+    if Temperature.is_freezing(1.5, 2.5):
+        count += 1
+    return count  # Expected: 3
+
+def test_static_on_instance() -> int:
+    """A static method can also be called through an instance"""
+    c: Counter = Counter()
+    c.bump()
+    c.bump()
+    return c.add(c.count, 40)  # Expected: 42
+
+def test_static_receiver_evaluated() -> int:
+    """The instance a static method is called through is still evaluated"""
+    global made
+    made = 0
+    total: int = make_counter().add(1, 2)
+    return total * 10 + made  # Expected: 31
+
+def test_static_from_method() -> int:
+    """Methods can call static methods through the class name"""
+    t: Temperature = Temperature(25.5)
+    print(t.fahrenheit())
+    return 1  # Expected: 1
+
+def test_classmethod() -> int:
+    """A class method receives the class as cls and can construct it"""
+    t: Temperature = Temperature.from_fahrenheit(50.0)
+    print(t.celsius > 9.99 and t.celsius < 10.01)
+    boiling: Temperature = Temperature.boiling()
+    print(boiling.celsius > 100.2 and boiling.celsius < 100.3)
+    return 1  # Expected: 1
+
+def test_classmethod_on_instance() -> int:
+    """A class method called through an instance still gets the class"""
+    t: Temperature = Temperature(0.5)
+    u: Temperature = t.from_fahrenheit(59.9)
+    print(u.celsius > 15.49 and u.celsius < 15.51)
+    return 1  # Expected: 1
+
+def test_inherited_static() -> int:
+    """Static methods are inherited"""
+    loud: LoudCounter = LoudCounter()
+    print(loud.shout())
+    print(LoudCounter.describe())
+    return LoudCounter.add(loud.bump(), 1)  # Expected: 2
+
+def main() -> int:
+    print(test_static_on_class())
+    print(test_static_defaults())
+    print(test_static_on_instance())
+    print(test_static_receiver_evaluated())
+    print(test_static_from_method())
+    print(test_classmethod())
+    print(test_classmethod_on_instance())
+    print(test_inherited_static())
+    return 0
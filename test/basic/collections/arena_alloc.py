@@ -0,0 +1,47 @@
+# --alloc=arena test program: many short-lived lists, strings and dicts, plus
+# lists that keep growing (and so get reallocated) while others are allocated
+
+def build(n: int) -> list[int]:
+    items: list[int] = []
+    i: int = 0
+    while i < n:
+        items.append(i * i)
+        i += 1
+    return items
+
+def churn(rounds: int) -> int:
+    acc: int = 0
+    r: int = 0
+    while r < rounds:
+        squares: list[int] = build(50)
+        label: str = "round " + str(r)
+        counts: dict[str, int] = {label: len(squares)}
+        acc += squares[49] + counts[label] + len(label)
+        r += 1
+    return acc
+
+def interleaved(n: int) -> str:
+    evens: list[int] = []
+    odds: list[int] = []
+    i: int = 0
+    while i < n:
+        if i % 2 == 0:
+            evens.append(i)
+        else:
+            odds.append(i)
+        i += 1
+    total: int = 0
+    for e in evens:
+        total += e
+    return str(total) + " " + str(len(odds)) + " " + str(odds[-1])
+
+def main() -> int:
+    print(churn(20000))
+    print(interleaved(100000))
+    line: str = ""
+    for i in range(5):
+        line = line + "w" + str(i) + " "
+    print(line)
+    return 0
+
+main()
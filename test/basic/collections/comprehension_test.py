@@ -0,0 +1,67 @@
+# Comprehension tests: list, set and dict comprehensions with filters, nesting and unpacking
+
+def test_list_comp() -> int:
+    """A list comprehension maps every element of the iterable"""
+    squares = [x * x for x in range(5)]
+    print(squares)
+    total: int = 0
+    for s in squares:
+        total = total + s
+    return total  # Expected: 30
+
+def test_list_comp_filter() -> int:
+    """Every if clause must hold for an element to be kept"""
+    evens = [x for x in range(10) if x % 2 == 0 if x > 2]
+    print(evens)
+    return len(evens)  # Expected: 3
+
+def test_nested_generators() -> int:
+    """Later for clauses are nested inside earlier ones and can use their targets"""
+    pairs = [i * 10 + j for i in range(3) for j in range(i)]
+    print(pairs)
+    return len(pairs)  # Expected: 3
+
+def test_nested_comp() -> int:
+    """The element of a comprehension can itself be a comprehension"""
+    rows = [[j for j in range(i)] for i in range(4)]
+    count: int = 0
+    for row in rows:
+        count = count + len(row)
+    return count  # Expected: 6
+
+def test_set_comp() -> int:
+    """A set comprehension drops duplicates"""
+    residues = {x % 3 for x in range(10)}
+    print(residues)
+    return len(residues)  # Expected: 3
+
+def test_dict_comp() -> int:
+    """A dict comprehension builds key: value entries"""
+    words: list[str] = ["a", "bb", "ccc"]
+    lengths = {w: len(w) for w in words if w != "bb"}
+    print(lengths)
+    return lengths["ccc"]  # Expected: 3
+
+def test_comp_unpack() -> int:
+    """Tuple targets unpack each element, e.g. inverting a dict"""
+    scores: dict[str, int] = {"x": 1, "y": 2}
+    names = {v: k for k, v in scores.items()}
+    print(names[2])
+    return len(names)  # Expected: 2
+
+def test_comp_scope() -> int:
+    """The comprehension variable does not leak or clobber an outer variable"""
+    x: int = 100
+    ys = [x + 1 for x in range(3)]
+    return x + len(ys)  # Expected: 103
+
+def main() -> int:
+    print(test_list_comp())
+    print(test_list_comp_filter())
+    print(test_nested_generators())
+    print(test_nested_comp())
+    print(test_set_comp())
+    print(test_dict_comp())
+    print(test_comp_unpack())
+    print(test_comp_scope())
+    return 0
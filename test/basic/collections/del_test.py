@@ -0,0 +1,110 @@
+# del statement tests: __delitem__ on containers and unbinding local variables
+
+class Shelf:
+    removed: int
+
+    def __init__(self) -> None:
+        self.removed = 0
+
+    def __delitem__(self, slot: int) -> None:
+        self.removed = self.removed + slot
+
+class Handle:
+    name: str
+
+    def __init__(self, name: str) -> None:
+        self.name = name
+
+    def __dealloc__(self) -> None:
+        self.name = ""
+
+def test_del_dict_key() -> int:
+    """del d[k] removes the entry and keeps the remaining insertion order"""
+    d: dict[str, int] = {"a": 1, "b": 2, "c": 3}
+    del d["b"]
+    print(d)
+    if "b" in d:
+        return 0
+    return len(d)  # Expected: 2
+
+def test_del_dict_missing_key() -> int:
+    """Deleting a missing key raises KeyError"""
+    d: dict[int, int] = {1: 10}
+    try:
+        del d[2]
+    except KeyError:
+        return len(d)  # Expected: 1
+    return 0
+
+def test_del_dict_many() -> int:
+    """Deleting most keys, then adding more, keeps lookups and insertion order"""
+    d: dict[int, int] = {}
+    i: int = 0
+    while i < 100:
+        d[i] = i
+        i += 1
+    i = 0
+    while i < 100:
+        if i % 10 != 0:
+            del d[i]
+        i += 1
+    while i < 160:
+        d[i] = i
+        i += 1
+    d[5] = 5
+    del d[150]
+    keys: list[int] = []
+    for k in d:
+        keys.append(k)
+    print(keys[8:13])
+    print(keys[len(keys) - 1])
+    return len(d) * 1000 + d[90] + d[159]  # Expected: 70249
+
+def test_del_list_item() -> int:
+    """del lst[i] removes the item and shifts later items down"""
+    items: list[int] = [10, 20, 30, 40]
+    del items[1]
+    del items[2]
+    print(items)
+    return items[0] + items[1]  # Expected: 40
+
+def test_del_several_targets() -> int:
+    """del a, b deletes each target in order"""
+    items: list[int] = [1, 2, 3, 4]
+    d: dict[int, str] = {1: "x", 2: "y"}
+    del items[0], d[2], items[0]
+    print(items)
+    return len(items) * 10 + len(d)  # Expected: 21
+
+def test_del_user_class() -> int:
+    """del obj[k] dispatches to a user-defined __delitem__"""
+    shelf: Shelf = Shelf()
+    del shelf[3]
+    del shelf[4]
+    return shelf.removed  # Expected: 7
+
+def test_del_local() -> int:
+    """del x unbinds a local, which can then be bound again"""
+    x: int = 5
+    total: int = x
+    del x
+    x = 7
+    return total + x  # Expected: 12
+
+def test_del_dealloc() -> int:
+    """del x calls __dealloc__ when the class defines one"""
+    h: Handle = Handle("file")
+    count: int = len(h.name)
+    del h
+    return count  # Expected: 4
+
+def main() -> int:
+    print(test_del_dict_key())
+    print(test_del_dict_missing_key())
+    print(test_del_dict_many())
+    print(test_del_list_item())
+    print(test_del_several_targets())
+    print(test_del_user_class())
+    print(test_del_local())
+    print(test_del_dealloc())
+    return 0
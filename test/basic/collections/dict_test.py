@@ -0,0 +1,86 @@
+# Dict tests: literals, annotated empty dicts, lookup, update, iteration, keys/values
+
+def test_dict_literal() -> int:
+    """Look up a value in an int-keyed dict literal"""
+    d: dict[int, int] = {1: 100, 2: 200, 3: 300}
+    return d[2]  # Expected: 200
+
+def test_dict_str_keys() -> int:
+    """String keys are hashed and compared by content"""
+    ages: dict[str, int] = {"alice": 30, "bob": 25}
+    name: str = "bo" + "b"
+    return ages[name]  # Expected: 25
+
+def test_dict_setitem() -> int:
+    """Annotated empty dict filled by index assignment"""
+    d: dict[int, int] = {}
+    d[1] = 10
+    d[2] = 20
+    d[1] = 15
+    return d[1] + d[2]  # Expected: 35
+
+def test_dict_len() -> int:
+    """len() counts distinct keys"""
+    d: dict[str, int] = {}
+    d["a"] = 1
+    d["b"] = 2
+    d["a"] = 3
+    return len(d)  # Expected: 2
+
+def test_dict_contains() -> int:
+    """__contains__ reports key membership"""
+    d: dict[str, int] = {"x": 1}
+    count: int = 0
+    if d.__contains__("x"):
+        count = count + 1
+    if not d.__contains__("y"):
+        count = count + 1
+    return count  # Expected: 2
+
+def test_dict_iteration() -> int:
+    """Iterating a dict yields its keys in insertion order"""
+    prices: dict[int, int] = {10: 5, 20: 10, 30: 15}
+    total: int = 0
+    for item_id in prices:
+        print(item_id)
+        total = total + prices[item_id]
+    return total  # Expected: 30
+
+def test_dict_keys_values() -> int:
+    """keys() and values() follow insertion order"""
+    d: dict[str, int] = {"one": 1, "two": 2, "three": 3}
+    for key in d.keys():
+        print(key)
+    total: int = 0
+    for value in d.values():
+        total = total * 10 + value
+    return total + len(d.keys())  # Expected: 126
+
+def test_dict_print() -> int:
+    """Printing a dict shows its entries"""
+    d: dict[str, int] = {"a": 1, "b": 2}
+    print(d)
+    empty: dict[int, bool] = {}
+    print(empty)
+    flags: dict[int, bool] = {1: True, 2: False}
+    print(flags)
+    return len(flags)  # Expected: 2
+
+def test_dict_many_entries() -> int:
+    """The table grows past its initial capacity"""
+    squares: dict[int, int] = {}
+    for i in range(100):
+        squares[i] = i * i
+    return squares[99] + len(squares)  # Expected: 9901
+
+def main() -> int:
+    print(test_dict_literal())
+    print(test_dict_str_keys())
+    print(test_dict_setitem())
+    print(test_dict_len())
+    print(test_dict_contains())
+    print(test_dict_iteration())
+    print(test_dict_keys_values())
+    print(test_dict_print())
+    print(test_dict_many_entries())
+    return 0
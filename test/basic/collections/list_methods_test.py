@@ -0,0 +1,169 @@
+# list.pop / insert / remove / index / count / extend / reverse / clear, + and == tests
+
+class Node:
+    value: int
+
+    def __init__(self, value: int) -> None:
+        self.value = value
+
+class Point:
+    x: int
+    y: int
+
+    def __init__(self, x: int, y: int) -> None:
+        self.x = x
+        self.y = y
+
+    def __eq__(self, other: "Point") -> bool:
+        return self.x == other.x and self.y == other.y
+
+def joined(words: list[str]) -> str:
+    out: str = ""
+    for word in words:
+        out = out + word + " "
+    return out.strip()
+
+def test_pop() -> int:
+    """pop() takes the last item; pop(i) any item, counting from the end if negative"""
+    items: list[int] = [10, 20, 30, 40, 50]
+    last: int = items.pop()
+    first: int = items.pop(0)
+    middle: int = items.pop(-2)
+    print(items, last, first, middle)
+    return last + first + middle + len(items)  # Expected: 92
+
+def test_insert() -> int:
+    """insert() shifts later items up; out-of-range indices clamp to the ends"""
+    items: list[int] = [1, 2, 3]
+    items.insert(0, 0)
+    items.insert(2, 15)
+    items.insert(-1, 25)
+    items.insert(100, 4)
+    items.insert(-100, -1)
+    print(items)
+    return len(items)  # Expected: 8
+
+def test_remove() -> int:
+    """remove() deletes the first equal item"""
+    words: list[str] = ["a", "b", "c", "b"]
+    words.remove("b")
+    print(joined(words))
+    values: list[float] = [1.5, 2.5, 1.5]
+    values.remove(1.5)
+    return len(words) * 10 + len(values)  # Expected: 32
+
+def test_index() -> int:
+    """index() finds the first equal item, optionally within [start, stop)"""
+    items: list[int] = [5, 6, 7, 5, 6, 7]
+    words: list[str] = ["x", "y", "z"]
+    print(items.index(7), items.index(5, 1), items.index(6, -2), items.index(7, 0, 3))
+    return items.index(6, 2, 6) + words.index("z")  # Expected: 6
+
+def test_count() -> int:
+    """count() counts equal items, including strings and tuples"""
+    items: list[int] = [1, 2, 1, 3, 1]
+    words: list[str] = ["hi", "yo", "hi"]
+    pairs: list[tuple[int, str]] = [(1, "a"), (2, "b"), (1, "a")]
+    print(items.count(1), items.count(4), words.count("hi"), pairs.count((1, "a")))
+    return items.count(1) + words.count("yo")  # Expected: 4
+
+def test_objects_by_identity() -> int:
+    """Objects without __eq__ are found by identity"""
+    a: Node = Node(1)
+    b: Node = Node(1)
+    nodes: list[Node] = [a, b, a]
+    nodes.remove(b)
+    return nodes.count(a) * 10 + nodes.index(a) + len(nodes)  # Expected: 22
+
+def test_extend_reverse_clear() -> int:
+    """extend() appends another list (even itself); reverse() and clear() work in place"""
+    items: list[int] = [1, 2]
+    items.extend([3, 4])
+    items.extend(items)
+    print(items)
+    items.reverse()
+    print(items)
+    total: int = items[0] + items[7]
+    items.clear()
+    items.append(9)
+    return total + len(items)  # Expected: 6
+
+def test_missing_items() -> int:
+    """Missing items raise ValueError; popping an empty list raises IndexError"""
+    items: list[int] = [1, 2]
+    count: int = 0
+    try:
+        items.remove(3)
+    except ValueError:
+        count = count + 1
+    try:
+        position: int = items.index(3)
+    except ValueError as e:
+        print(e)
+        count = count + 1
+    items.clear()
+    try:
+        value: int = items.pop()
+    except IndexError as e:
+        print(e)
+        count = count + 1
+    return count  # Expected: 3
+
+def test_concat() -> int:
+    """+ builds a new list; += extends the list in place"""
+    a: list[int] = [1, 2]
+    b: list[int] = [3]
+    c: list[int] = a + b + a
+    print(c, a, b)
+    alias: list[int] = a
+    a += b
+    print(alias)
+    words: list[str] = ["x"] + ["y", "z"]
+    return len(c) * 10 + len(alias) + len(words)  # Expected: 56
+
+def test_equality() -> int:
+    """== compares lengths and items, by value for str, float and tuples"""
+    count: int = 0
+    if [1, 2, 3] == [1, 2, 3]:
+        count += 1
+    if [1, 2, 3] != [1, 2]:
+        count += 1
+    if ["a", "b"] == ["a", "b"]:
+        count += 1
+    if [0.5, -0.0] == [0.5, 0.0]:
+        count += 1
+    if [(1, "x")] == [(1, "x")]:
+        count += 1
+    empty: list[str] = []
+    if not (empty == ["a"]):
+        count += 1
+    return count  # Expected: 6
+
+def test_itemwise_equality() -> int:
+    """Nested lists and items with __eq__ are compared with their own =="""
+    count: int = 0
+    if [[1, 2], [3]] == [[1, 2], [3]]:
+        count += 1
+    if [[1, 2], [3]] != [[1, 2], [4]]:
+        count += 1
+    if [Point(1, 2), Point(3, 4)] == [Point(1, 2), Point(3, 4)]:
+        count += 1
+    if [Point(1, 2)] != [Point(2, 1)]:
+        count += 1
+    if [Node(1)] != [Node(1)]:
+        count += 1
+    return count  # Expected: 5
+
+def main() -> int:
+    print(test_pop())
+    print(test_insert())
+    print(test_remove())
+    print(test_index())
+    print(test_count())
+    print(test_objects_by_identity())
+    print(test_extend_reverse_clear())
+    print(test_missing_items())
+    print(test_concat())
+    print(test_equality())
+    print(test_itemwise_equality())
+    return 0
@@ -0,0 +1,144 @@
+# in / not in tests
+
+from typing import Iterator
+
+class Bag:
+    items: list[int]
+
+    def __init__(self, items: list[int]) -> None:
+        self.items = items
+
+    def __contains__(self, item: int) -> bool:
+        return item % 2 == 0 or item == 7
+
+def countdown(start: int) -> Iterator[int]:
+    n: int = start
+    while n > 0:
+        yield n
+        n -= 1
+
+order: str = ""
+
+def traced_key(key: str) -> str:
+    global order
+    order = order + "k"
+    return key
+
+def traced_dict() -> dict[str, int]:
+    global order
+    order = order + "d"
+    return {"a": 1}
+
+def test_list() -> int:
+    """Lists are searched item by item"""
+    nums: list[int] = [3, 1, 4, 1, 5]
+    count: int = 0
+    if 4 in nums:
+        count += 1
+    if 9 not in nums:
+        count += 1
+    if not (2 in nums):
+        count += 1
+    words: list[str] = ["apple", "pear"]
+    if "pe" + "ar" in words:
+        count += 1
+    return count  # Expected: 4
+
+def test_str() -> int:
+    """For strings, in tests for a substring"""
+    s: str = "hello world"
+    count: int = 0
+    if "lo w" in s:
+        count += 1
+    if "xyz" not in s:
+        count += 1
+    if "" in s:
+        count += 1
+    print("ell" in "hello", "Hello" in "hello")
+    return count  # Expected: 3
+
+def test_dict_and_set() -> int:
+    """Dicts test their keys, sets their elements"""
+    d: dict[str, int] = {"a": 1, "b": 2}
+    s: set[int] = {10, 20}
+    count: int = 0
+    if "a" in d:
+        count += 1
+    if 1 not in s:
+        count += 1
+    if 20 in s and "c" not in d:
+        count += 1
+    return count  # Expected: 3
+
+def test_tuple() -> int:
+    """Tuples are compared element by element"""
+    t: tuple[int, int, int] = (2, 4, 8)
+    count: int = 0
+    if 4 in t:
+        count += 1
+    if 5 not in t:
+        count += 1
+    if "b" in ("a", "b"):
+        count += 1
+    return count  # Expected: 3
+
+def test_range() -> int:
+    """Ranges are searched by iterating"""
+    count: int = 0
+    if 3 in range(5):
+        count += 1
+    if 5 not in range(5):
+        count += 1
+    return count  # Expected: 2
+
+def test_contains_method() -> int:
+    """A class defining __contains__ decides membership itself"""
+    bag: Bag = Bag([])
+    count: int = 0
+    if 4 in bag:
+        count += 1
+    if 7 in bag:
+        count += 1
+    if 3 not in bag:
+        count += 1
+    return count  # Expected: 3
+
+def test_generator() -> int:
+    """Other iterables, such as generators, are searched through their items"""
+    count: int = 0
+    if 2 in countdown(3):
+        count += 1
+    if 4 not in countdown(3):
+        count += 1
+    return count  # Expected: 2
+
+def test_evaluation_order() -> int:
+    """The item is evaluated before the container"""
+    global order
+    order = ""
+    found: bool = traced_key("a") in traced_dict()
+    print(order)
+    if found:
+        return 1  # Expected: 1
+    return 0
+
+def test_in_expressions() -> int:
+    """Membership tests work in comprehensions and as values"""
+    vowels: str = "aeiou"
+    letters: list[str] = [c for c in ["a", "b", "e", "z"] if c in vowels]
+    print(letters[0], letters[1])
+    flags: list[bool] = [n in [1, 2] for n in range(4)]
+    print(flags[0], flags[1], flags[3])
+    return len(letters)  # Expected: 2
+
+def main() -> int:
+    print(test_list())
+    print(test_str())
+    print(test_dict_and_set())
+    print(test_tuple())
+    print(test_range())
+    print(test_contains_method())
+    print(test_generator())
+    print(test_evaluation_order())
+    print(test_in_expressions())
+    return 0
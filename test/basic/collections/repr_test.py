@@ -0,0 +1,46 @@
+# Container repr tests: items print with their own repr, nested containers recursively
+
+def test_flat_lists() -> int:
+    """List items print like their repr, not as raw slots"""
+    words: list[str] = ["a", "b c"]
+    flags: list[bool] = [True, False]
+    blobs: list[bytes] = [b"x", b"\n"]
+    print(words, flags, blobs)
+    print([(1, "one"), (2, "two")])
+    return len(str(words))  # Expected: 12
+
+def test_nested() -> int:
+    """Lists, dicts and sets inside each other print recursively"""
+    rows: list[dict[str, int]] = [{"a": 1}, {"b": 2, "c": 3}]
+    print(rows)
+    grid: list[list[int]] = [[1, 2], [], [3]]
+    print(grid)
+    index: dict[str, list[str]] = {"x": ["y", "z"], "empty": []}
+    print(index)
+    groups: dict[int, set[int]] = {1: {1, 2}, 2: {5}}
+    print(groups)
+    frozen: list[frozenset[str]] = [frozenset({"k"})]
+    print(frozen)
+    return len(repr(grid))  # Expected: 17
+
+def test_quotes() -> int:
+    """Strings choose their quotes like CPython"""
+    quotes: list[str] = ["it's", 'say "hi"', "both ' and \"", "tab\there"]
+    print(quotes)
+    print({"don't": "x"})
+    text: str = repr("it's")
+    return len(text)  # Expected: 6
+
+def test_derived_lists() -> int:
+    """Lists built by the runtime keep the item kind"""
+    names: list[str] = ["p", "q", "r"]
+    print(names[1:], names + ["s"], names * 2)
+    print(b"a b".split())
+    return len(names)  # Expected: 3
+
+def main() -> int:
+    print(test_flat_lists())
+    print(test_nested())
+    print(test_quotes())
+    print(test_derived_lists())
+    return 0
@@ -0,0 +1,88 @@
+# Set tests: literals, annotated empty sets, add/remove/discard, set operators, frozenset
+
+def test_set_literal() -> int:
+    """Duplicate elements in a literal are collapsed"""
+    s: set[int] = {1, 2, 2, 3, 3, 3}
+    return len(s)  # Expected: 3
+
+def test_set_add_discard() -> int:
+    """add ignores existing elements; remove/discard drop them"""
+    s: set[int] = set()
+    s.add(5)
+    s.add(7)
+    s.add(5)
+    s.add(9)
+    s.remove(7)
+    s.discard(100)
+    s.discard(9)
+    return len(s)  # Expected: 1
+
+def test_set_contains() -> int:
+    """String elements are compared by content"""
+    names: set[str] = {"alice", "bob"}
+    count: int = 0
+    if names.__contains__("bo" + "b"):
+        count = count + 1
+    if not names.__contains__("carol"):
+        count = count + 1
+    return count  # Expected: 2
+
+def test_set_operators() -> int:
+    """| & - ^ build new sets"""
+    a: set[int] = {1, 2, 3}
+    b: set[int] = {3, 4}
+    print(a | b)
+    print(a & b)
+    print(a - b)
+    print(a ^ b)
+    return len(a.union(b)) * 10 + len(a.intersection(b))  # Expected: 41
+
+def test_set_update() -> int:
+    """update() adds every element of another set"""
+    s: set[int] = {1, 2}
+    s.update({2, 3, 4})
+    return len(s)  # Expected: 4
+
+def test_set_from_list() -> int:
+    """set(list) keeps one copy of each element"""
+    words: list[str] = ["a", "b", "a", "c", "b"]
+    unique: set[str] = set(words)
+    return len(unique)  # Expected: 3
+
+def test_set_iteration() -> int:
+    """Iterating a set visits each element once"""
+    s: set[int] = {10, 20, 30, 20}
+    total: int = 0
+    for x in s:
+        total = total + x
+    return total  # Expected: 60
+
+def test_frozenset() -> int:
+    """frozenset supports the non-mutating operations"""
+    f: frozenset[int] = frozenset([1, 2, 2])
+    print(f)
+    g: frozenset[int] = frozenset()
+    print(g)
+    both: frozenset[int] = f | frozenset([3])
+    return len(both)  # Expected: 3
+
+def test_set_print() -> int:
+    """Printing a set shows its elements; an empty set prints as set()"""
+    s: set[int] = {1, 2, 3}
+    print(s)
+    empty: set[str] = set()
+    print(empty)
+    print({"x"})
+    return len(s)  # Expected: 3
+
+def main() -> int:
+    print(test_set_literal())
+    print(test_set_add_discard())
+    print(test_set_contains())
+    print(test_set_operators())
+    print(test_set_update())
+    print(test_set_from_list())
+    print(test_set_iteration())
+    print(test_frozenset())
+    print(test_set_print())
+    return 0
@@ -0,0 +1,129 @@
+# sorted() and list.sort() tests, with and without key and reverse
+
+class Task:
+    name: str
+    priority: int
+
+    def __init__(self, name: str, priority: int) -> None:
+        self.name = name
+        self.priority = priority
+
+def joined(words: list[str]) -> str:
+    out: str = ""
+    for word in words:
+        out = out + word + " "
+    return out.strip()
+
+def negate(x: int) -> int:
+    return -x
+
+def last_digit(x: int) -> int:
+    return x % 10
+
+def priority(task: Task) -> int:
+    return task.priority
+
+def test_sort_ints() -> int:
+    """list.sort() orders the list in place"""
+    items: list[int] = [5, 2, 9, 1, 7]
+    items.sort()
+    print(items)
+    return items[0] + items[4]  # Expected: 10
+
+def test_sorted_copy() -> int:
+    """sorted() returns a new list and leaves the original alone"""
+    items: list[int] = [3, 1, 2]
+    result: list[int] = sorted(items)
+    print(result)
+    print(items)
+    return result[0] * 100 + items[0]  # Expected: 103
+
+def test_reverse() -> int:
+    """reverse=True sorts in descending order"""
+    items: list[int] = [4, 8, 1, 6]
+    items.sort(reverse=True)
+    print(items)
+    print(sorted([2, 3, 1], reverse=False))
+    return items[0]  # Expected: 8
+
+def test_sort_strings() -> int:
+    """Strings sort lexicographically"""
+    words: list[str] = ["pear", "apple", "fig", "banana"]
+    print(joined(sorted(words)))
+    words.sort(reverse=True)
+    print(joined(words))
+    return len(words[0])  # Expected: 4
+
+def test_sort_floats() -> int:
+    """Floats sort numerically, including negative values"""
+    values: list[float] = [2.5, -1.5, 0.25, 10.0]
+    values.sort()
+    if values[0] == -1.5 and values[3] == 10.0:
+        return 1  # Expected: 1
+    return 0
+
+def test_key_function() -> int:
+    """key= sorts by the value the function returns"""
+    items: list[int] = [13, 21, 5, 42, 7]
+    print(sorted(items, key=negate))
+    items.sort(key=last_digit)
+    print(items)
+    return items[0]  # Expected: 21
+
+def test_key_builtin() -> int:
+    """key= accepts builtins such as len"""
+    words: list[str] = ["ccc", "a", "bb", "dddd"]
+    print(joined(sorted(words, key=len, reverse=True)))
+    return len(sorted(words, key=len)[0])  # Expected: 1
+
+def test_stable() -> int:
+    """Items with equal keys keep their original order, even reversed"""
+    words: list[str] = ["bb", "a", "cc", "d", "ee"]
+    print(joined(sorted(words, key=len)))
+    print(joined(sorted(words, key=len, reverse=True)))
+    return 1  # Expected: 1
+
+def test_sort_objects() -> int:
+    """Objects sort by a key extracted from them"""
+    tasks: list[Task] = [Task("write", 3), Task("plan", 1), Task("ship", 5), Task("test", 3)]
+    tasks.sort(key=priority)
+    order: str = ""
+    for task in tasks:
+        order = order + task.name + " "
+    print(order.strip())
+    return tasks[0].priority  # Expected: 1
+
+def test_sorted_iterables() -> int:
+    """sorted() accepts any iterable and always returns a list"""
+    print(sorted(range(5), key=negate))
+    print(sorted({3, 1, 2}))
+    d: dict[str, int] = {"b": 2, "c": 3, "a": 1}
+    print(joined(sorted(d)))
+    return len(sorted([x * x for x in range(4)]))  # Expected: 4
+
+def test_key_none() -> int:
+    """key=None sorts by the items themselves"""
+    items: list[int] = [2, 1]
+    items.sort(key=None)
+    return items[0]  # Expected: 1
+
+def test_sort_empty() -> int:
+    """Sorting an empty list is a no-op"""
+    items: list[int] = []
+    items.sort()
+    return len(sorted(items))  # Expected: 0
+
+def main() -> int:
+    print(test_sort_ints())
+    print(test_sorted_copy())
+    print(test_reverse())
+    print(test_sort_strings())
+    print(test_sort_floats())
+    print(test_key_function())
+    print(test_key_builtin())
+    print(test_stable())
+    print(test_sort_objects())
+    print(test_sorted_iterables())
+    print(test_key_none())
+    print(test_sort_empty())
+    return 0
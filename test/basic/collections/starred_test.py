@@ -0,0 +1,96 @@
+# Starred unpacking tests: `*rest` targets and `f(*args)` call arguments
+
+def total3(a: int, b: int, c: int) -> int:
+    return a * 100 + b * 10 + c
+
+def total4(a: int, b: int, c: int, d: int) -> int:
+    return a * 1000 + b * 100 + c * 10 + d
+
+def label(name: str, count: int, sep: str = ":") -> str:
+    return name + sep + f"{count}"
+
+def triple() -> tuple[int, int, int]:
+    print("triple")
+    return (7, 8, 9)
+
+def first_arg() -> int:
+    print("first")
+    return 1
+
+def test_list_star_tail() -> int:
+    """a, *rest = list binds the head and collects the remaining items"""
+    items: list[int] = [1, 2, 3, 4]
+    head, *rest = items
+    print(rest)
+    return head * 10 + len(rest)  # Expected: 13
+
+def test_list_star_middle() -> int:
+    """Targets after the starred one take the last items"""
+    items: list[int] = [5, 6, 7, 8, 9]
+    first, *middle, last = items
+    print(middle)
+    return first * 10 + last + len(middle) * 100  # Expected: 359
+
+def test_list_star_head() -> int:
+    """A leading starred target takes everything but the last items"""
+    items: list[str] = ["a", "b", "c"]
+    *init, tail = items
+    print(tail)
+    return len(init) + len(tail)  # Expected: 3
+
+def test_list_star_empty() -> int:
+    """The starred target may be empty"""
+    items: list[int] = [4, 2]
+    a, *rest, b = items
+    print(rest)
+    return a * 10 + b + len(rest)  # Expected: 42
+
+def test_tuple_star() -> int:
+    """Starred targets of tuples collect the middle elements into a list"""
+    t = (1, 2, 3, 4)
+    a, *mid, d = t
+    mid.append(10)
+    print(mid)
+    return a + d + len(mid)  # Expected: 8
+
+def test_tuple_star_empty() -> int:
+    """An empty starred target gets its element type from later use"""
+    t = ("x", 3)
+    name, *extra, count = t
+    extra.append(count)
+    return len(name) + len(extra) * count  # Expected: 4
+
+def test_comprehension_star_target() -> int:
+    """Comprehension targets may contain a starred target"""
+    sizes: list[int] = [first * len(others) for first, *others in [(1, 2, 3), (4, 5, 6)]]
+    print(sizes)
+    return sizes[0] + sizes[1]  # Expected: 10
+
+def test_call_star_tuple() -> int:
+    """f(*t) passes the tuple's elements as positional arguments"""
+    t = (1, 2, 3)
+    return total3(*t)  # Expected: 123
+
+def test_call_star_mixed() -> int:
+    """Starred arguments mix with plain ones and defaults"""
+    rest = (4,)
+    print(label("n", *rest))
+    print(label(*("w", 2), "="))
+    return total3(1, *(2, 3))  # Expected: 123
+
+def test_call_star_order() -> int:
+    """Arguments are still evaluated once, left to right"""
+    return total4(first_arg(), *triple())  # Expected: 1789
+
+def main() -> int:
+    print(test_list_star_tail())
+    print(test_list_star_middle())
+    print(test_list_star_head())
+    print(test_list_star_empty())
+    print(test_tuple_star())
+    print(test_tuple_star_empty())
+    print(test_comprehension_star_target())
+    print(test_call_star_tuple())
+    print(test_call_star_mixed())
+    print(test_call_star_order())
+    return 0
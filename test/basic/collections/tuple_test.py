@@ -0,0 +1,78 @@
+# Tuple tests: literals, constant indexing, unpacking, tuples in functions and containers
+
+def test_tuple_index() -> int:
+    """Constant indexes select elements of different types"""
+    t: tuple[int, str, bool] = (42, "answer", True)
+    name: str = t[1]
+    if t[2]:
+        return t[0] + len(name)  # Expected: 48
+    return 0
+
+def test_tuple_negative_index() -> int:
+    """Negative constant indexes count from the end; len() counts elements"""
+    t = (1, 2, 3)
+    return t[-1] * 100 + t[-3] * 10 + len(t)  # Expected: 313
+
+def divmod_pair(a: int, b: int) -> tuple[int, int]:
+    return (a // b, a % b)
+
+def test_tuple_return() -> int:
+    """Functions return several values as a tuple"""
+    q, r = divmod_pair(17, 5)
+    return q * 10 + r  # Expected: 32
+
+def test_tuple_swap() -> int:
+    """a, b = b, a evaluates the right-hand side first"""
+    a: int = 1
+    b: int = 2
+    a, b = b, a
+    return a * 10 + b  # Expected: 21
+
+def test_nested_unpack() -> int:
+    """Nested tuple targets unpack nested tuples"""
+    pair = (("x", 5), 7.5)
+    (label, count), weight = pair
+    print(label)
+    if weight > 7.0:
+        return count  # Expected: 5
+    return 0
+
+def test_tuple_print() -> int:
+    """Printing a tuple shows its elements; 1-tuples keep the trailing comma"""
+    print((1, "two", False))
+    print((3,))
+    empty: tuple[()] = ()
+    print(empty)
+    print(((1, 2), "nested"))
+    return 0  # Expected: 0
+
+def test_dict_items() -> int:
+    """dict.items() yields (key, value) tuples in insertion order"""
+    scores: dict[str, int] = {"a": 1, "b": 2, "c": 3}
+    total: int = 0
+    for item in scores.items():
+        print(item)
+        key, value = item
+        total = total + value * len(key)
+    return total  # Expected: 6
+
+def test_tuple_keys() -> int:
+    """Tuples with equal elements are equal dict keys and set elements"""
+    grid: dict[tuple[int, int], str] = {}
+    grid[(0, 1)] = "a"
+    grid[(2, 3)] = "b"
+    grid[(0, 1)] = "c"
+    seen: set[tuple[int, str]] = {(1, "x"), (1, "x"), (2, "x")}
+    print(grid[(0, 1)])
+    return len(grid) * 10 + len(seen)  # Expected: 22
+
+def main() -> int:
+    print(test_tuple_index())
+    print(test_tuple_negative_index())
+    print(test_tuple_return())
+    print(test_tuple_swap())
+    print(test_nested_unpack())
+    print(test_tuple_print())
+    print(test_dict_items())
+    print(test_tuple_keys())
+    return 0
@@ -0,0 +1,71 @@
+# list[float] and list[bool]: typed items in the runtime (floats keep every
+# bit, bools take a byte each) behind the usual list methods
+
+def test_float_items() -> int:
+    """Floats come back exactly as stored"""
+    values: list[float] = [0.1, -2.5]
+    values.append(0.1 + 0.2)
+    values.append(1e300)
+    values.insert(0, 0.75)
+    values[1] = values[1] * 3
+    print(values[::2][:2])
+    count: int = 0
+    if values.pop() == 1e300:
+        count += 1
+    if values.pop(0) == 0.75:
+        count += 1
+    if values[0] == 0.30000000000000004 and values[-1] == 0.1 + 0.2:
+        count += 1
+    return count * 100 + values.index(-2.5) * 10 + values.count(0.30000000000000004)  # Expected: 312
+
+def test_float_loops() -> int:
+    """Iteration, sorting and comprehensions over float items"""
+    values: list[float] = [x / 4 for x in range(-3, 4)]
+    total: float = 0.0
+    for v in values:
+        total += v * v
+    values.sort(reverse=True)
+    values.remove(0.0)
+    print(values, total)
+    return len(values)  # Expected: 6
+
+def test_bool_items() -> int:
+    """Bools survive every method that moves items around"""
+    flags: list[bool] = []
+    for n in range(10):
+        flags.append(n % 3 == 0)
+    flags.insert(1, True)
+    flags[2] = not flags[2]
+    print(flags)
+    print(flags.pop(), flags.index(False), flags.count(True))
+    doubled: list[bool] = flags[2:5] * 2
+    doubled.reverse()
+    print(doubled, doubled == [True, False, True, True, False, True])
+    trues: int = 0
+    for flag in flags + doubled:
+        if flag:
+            trues += 1
+    return trues  # Expected: 9
+
+def test_bool_keys() -> int:
+    """Dict and set iteration hands out bool and float items too"""
+    seen: dict[bool, int] = {True: 1, False: 2}
+    total: int = 0
+    for key in seen:
+        if key:
+            total += seen[key] * 10
+        else:
+            total += seen[key]
+    halves: set[float] = {0.5, 1.5}
+    sum_halves: float = 0.0
+    for h in halves:
+        sum_halves += h
+    print(total, sum_halves == 2.0)
+    return total  # Expected: 12
+
+def main() -> int:
+    print(test_float_items())
+    print(test_float_loops())
+    print(test_bool_items())
+    print(test_bool_keys())
+    return 0
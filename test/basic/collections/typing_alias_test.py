@@ -0,0 +1,74 @@
+# typing.List / typing.Dict / ... annotation tests
+
+import typing
+import collections.abc
+from typing import Dict, FrozenSet, List, Optional, Set, Tuple
+
+def total(values: List[int]) -> int:
+    result: int = 0
+    for value in values:
+        result += value
+    return result
+
+def count_words(words: typing.List[str]) -> Dict[str, int]:
+    counts: Dict[str, int] = {}
+    for word in words:
+        if word in counts:
+            counts[word] = counts[word] + 1
+        else:
+            counts[word] = 1
+    return counts
+
+def min_max(values: List[int]) -> Tuple[int, int]:
+    low: int = values[0]
+    high: int = values[0]
+    for value in values:
+        if value < low:
+            low = value
+        if value > high:
+            high = value
+    return (low, high)
+
+def first_even(values: typing.List[int]) -> typing.Optional[int]:
+    for value in values:
+        if value % 2 == 0:
+            return value
+    return None
+
+def evens(limit: int) -> collections.abc.Iterator[int]:
+    n: int = 0
+    while n < limit:
+        yield n
+        n += 2
+
+def test_list_and_dict() -> int:
+    """List[T] and Dict[K, V] mean list[T] and dict[K, V]"""
+    counts: typing.Dict[str, int] = count_words(["a", "b", "a"])
+    print(counts["a"], counts["b"])
+    return total([1, 2, 3])  # Expected: 6
+
+def test_tuple_and_sets() -> int:
+    """Tuple, Set and FrozenSet work like their builtin types"""
+    low, high = min_max([4, 9, 2])
+    seen: Set[int] = {1, 2}
+    seen.add(2)
+    fixed: FrozenSet[int] = frozenset([5, 6, 7])
+    pair: typing.Tuple[str, int] = ("x", 1)
+    print(low, high, pair[0])
+    return len(seen) + len(fixed)  # Expected: 5
+
+def test_dotted() -> int:
+    """typing.X and collections.abc.X can be used unimported"""
+    found: Optional[int] = first_even([3, 5, 8])
+    count: int = 0
+    if found is not None:
+        count += found
+    for n in evens(5):
+        count += n
+    return count  # Expected: 14
+
+def main() -> int:
+    print(test_list_and_dict())
+    print(test_tuple_and_sets())
+    print(test_dotted())
+    return 0
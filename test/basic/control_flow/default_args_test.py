@@ -0,0 +1,90 @@
+# Default argument tests: missing trailing arguments are filled in at the call site
+
+class Counter:
+    count: int
+    step: int
+
+    def __init__(self, start: int = 0, step: int = 1) -> None:
+        self.count = start
+        self.step = step
+
+    def advance(self, times: int = 1) -> int:
+        for _ in range(times):
+            self.count = self.count + self.step
+        return self.count
+
+class Greeter:
+    def greet(self, name: str, punctuation: str = "!") -> str:
+        return "Hello, " + name + punctuation
+
+class LoudGreeter(Greeter):
+    def greet(self, name: str, punctuation: str = "!!!") -> str:
+        return super().greet(name)
+
+def add(x: int, y: int = 3) -> int:
+    return x + y
+
+def scale(x: float, factor: float = 2) -> float:
+    return x * factor
+
+def offset(x: int, delta: int = -5) -> int:
+    return x + delta
+
+def describe(label: str = "value", enabled: bool = True) -> str:
+    if enabled:
+        return label + " on"
+    return label + " off"
+
+def test_default_int() -> int:
+    """Omitted int default is used, explicit argument overrides it"""
+    print(add(1))
+    print(add(1, 10))
+    return add(4) + add(4, 4)  # Expected: 15
+
+def test_default_float() -> float:
+    """Int default of a float parameter is widened to float"""
+    print(scale(1.5))
+    return scale(1.5) + scale(1.0, 0.5)  # Expected: 3.5
+
+def test_default_negative() -> int:
+    """Negative numeric defaults"""
+    return offset(20) + offset(20, 1)  # Expected: 36
+
+def test_default_all_omitted() -> int:
+    """Every parameter may be omitted"""
+    print(describe())
+    print(describe("light"))
+    print(describe("fan", False))
+    return len(describe())  # Expected: 8
+
+def test_default_constructor() -> int:
+    """Constructor defaults"""
+    a: Counter = Counter()
+    b: Counter = Counter(10)
+    c: Counter = Counter(10, 5)
+    return a.count + b.count + c.step  # Expected: 15
+
+def test_default_method() -> int:
+    """Method defaults"""
+    c: Counter = Counter(0, 2)
+    c.advance()
+    c.advance(3)
+    return c.count  # Expected: 8
+
+def test_default_super_call() -> int:
+    """Defaults apply to super() calls and overriding methods"""
+    g: LoudGreeter = LoudGreeter()
+    s: str = g.greet("Bob")
+    print(s)
+    print(g.greet("Ann", "?"))
+    return len(s)  # Expected: 11
+
+def main() -> int:
+    print(test_default_int())
+    print(test_default_float())
+    print(test_default_negative())
+    print(test_default_all_omitted())
+    print(test_default_constructor())
+    print(test_default_method())
+    print(test_default_super_call())
+    return 0
@@ -0,0 +1,99 @@
+# global / nonlocal tests: rebinding module variables and enclosing-function variables
+
+counter: int = 0
+label: str = "start"
+
+def increment() -> None:
+    global counter
+    counter += 1
+
+def set_label(text: str) -> None:
+    global label
+    label = text
+
+def shadow_counter() -> int:
+    # No global declaration: this creates a local
+    counter = 100
+    return counter
+
+def test_global_rebind() -> int:
+    """global lets a function rebind a module variable"""
+    increment()
+    increment()
+    increment()
+    return counter  # Expected: 3
+
+def test_global_str() -> str:
+    """Rebinding a str module variable"""
+    set_label("done")
+    return label  # Expected: done
+
+def test_global_shadow() -> int:
+    """Assigning without a global declaration leaves the module variable alone"""
+    before: int = counter
+    local_value: int = shadow_counter()
+    return local_value + counter - before  # Expected: 100
+
+def test_nonlocal_counter() -> int:
+    """nonlocal rebinds a local of the enclosing function"""
+    count: int = 0
+    def bump() -> None:
+        nonlocal count
+        count += 1
+    bump()
+    bump()
+    return count  # Expected: 2
+
+def test_nonlocal_param(total: int) -> int:
+    """Parameters can be rebound through nonlocal too"""
+    def add(x: int) -> None:
+        nonlocal total
+        total = total + x
+    add(5)
+    add(7)
+    return total  # Expected: 13 for total=1
+
+def test_nonlocal_shared() -> int:
+    """Other nested functions see the rebinding"""
+    value: int = 1
+    def double() -> None:
+        nonlocal value
+        value = value * 2
+    def get() -> int:
+        return value
+    double()
+    double()
+    return get()  # Expected: 4
+
+def test_nonlocal_deep() -> int:
+    """nonlocal reaches through an intermediate function"""
+    steps: int = 0
+    def outer_step() -> None:
+        def inner_step() -> None:
+            nonlocal steps
+            steps += 10
+        inner_step()
+        inner_step()
+    outer_step()
+    return steps  # Expected: 20
+
+def test_nonlocal_float() -> float:
+    """Float accumulator updated from a loop"""
+    acc: float = 0.5
+    def add(x: float) -> None:
+        nonlocal acc
+        acc += x
+    for _ in range(3):
+        add(1.0)
+    return acc  # Expected: 3.5
+
+def main() -> int:
+    print(test_global_rebind())
+    print(test_global_str())
+    print(test_global_shadow())
+    print(test_nonlocal_counter())
+    print(test_nonlocal_param(1))
+    print(test_nonlocal_shared())
+    print(test_nonlocal_deep())
+    print(test_nonlocal_float())
+    return 0
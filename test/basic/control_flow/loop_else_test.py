@@ -0,0 +1,103 @@
+# Loop else tests: the else clause of while and for loops
+from typing import Iterator
+
+class LoopError(Exception):
+    code: int
+
+def count_down(n: int) -> list[int]:
+    items: list[int] = []
+    i: int = n
+    while i > 0:
+        items.append(i)
+        i = i - 1
+    else:
+        items.append(0)
+    return items
+
+def first_negative(values: list[int]) -> int:
+    for v in values:
+        if v < 0:
+            raise LoopError("negative value")
+    else:
+        return -1
+    return 0
+
+def numbers(n: int) -> Iterator[int]:
+    for i in range(n):
+        yield i
+    else:
+        yield 100
+
+def test_while_else() -> int:
+    """The else clause runs after the loop condition becomes false"""
+    items: list[int] = count_down(3)
+    print(items)
+    return len(items)  # Expected: 4
+
+def test_while_else_no_iterations() -> int:
+    """The else clause runs even when the body never executes"""
+    total: int = 0
+    while total > 10:
+        total = total + 1
+    else:
+        total = total + 7
+    return total  # Expected: 7
+
+def test_for_else() -> int:
+    """The else clause runs once after the last iteration"""
+    total: int = 0
+    for x in [1, 2, 3]:
+        total = total + x
+    else:
+        total = total * 10
+    return total  # Expected: 60
+
+def test_for_else_empty() -> int:
+    """The else clause runs for an empty iterable"""
+    hits: int = 0
+    for i in range(0):
+        hits = hits + 1
+    else:
+        hits = hits + 5
+    return hits  # Expected: 5
+
+def test_for_else_skipped_on_raise() -> int:
+    """An exception leaving the loop skips the else clause"""
+    result: int = 0
+    try:
+        result = first_negative([3, -1, 2])
+    except LoopError:
+        result = 2
+    return result + first_negative([1, 2])  # Expected: 1
+
+def test_nested_loop_else() -> int:
+    """Each loop runs its own else clause"""
+    log: list[int] = []
+    j: int = 0
+    for i in range(2):
+        j = 0
+        while j < i:
+            j = j + 1
+        else:
+            log.append(i * 10 + j)
+    else:
+        log.append(99)
+    print(log)
+    return len(log)  # Expected: 3
+
+def test_generator_for_else() -> int:
+    """A generator may yield from a loop's else clause"""
+    total: int = 0
+    for v in numbers(3):
+        total = total + v
+    return total  # Expected: 103
+
+def main() -> int:
+    print(test_while_else())
+    print(test_while_else_no_iterations())
+    print(test_for_else())
+    print(test_for_else_empty())
+    print(test_for_else_skipped_on_raise())
+    print(test_nested_loop_else())
+    print(test_generator_for_else())
+    return 0
@@ -0,0 +1,106 @@
+# Nested function tests: inner functions read the enclosing function's variables
+
+class Account:
+    balance: int
+
+    def __init__(self, balance: int) -> None:
+        self.balance = balance
+
+    def apply_fees(self, fee: int, times: int) -> int:
+        def charge() -> None:
+            self.balance = self.balance - fee
+        for _ in range(times):
+            charge()
+        return self.balance
+
+def test_capture_local() -> int:
+    """Inner function reads a local of the enclosing function"""
+    k: int = 3
+    def scale(x: int) -> int:
+        return x * k
+    return scale(5)  # Expected: 15
+
+def test_capture_param(base: int) -> int:
+    """Inner function reads a parameter of the enclosing function"""
+    def add(x: int) -> int:
+        return base + x
+    return add(1) + add(2)  # Expected: 23 for base=10
+
+def test_late_binding() -> int:
+    """Captured variables are read when the function is called"""
+    n: int = 1
+    def get() -> int:
+        return n
+    first: int = get()
+    n = 100
+    return first + get()  # Expected: 101
+
+def test_recursion() -> int:
+    """Inner function calls itself"""
+    limit: int = 10
+    def fact(n: int) -> int:
+        if n <= 1 or n > limit:
+            return 1
+        return n * fact(n - 1)
+    return fact(5)  # Expected: 120
+
+def test_sibling_call() -> int:
+    """Inner function calls an earlier sibling that captures a variable"""
+    offset: int = 7
+    def shift(x: int) -> int:
+        return x + offset
+    def shift_twice(x: int) -> int:
+        return shift(shift(x))
+    return shift_twice(1)  # Expected: 15
+
+def test_deep_nesting() -> int:
+    """Variables are captured through several levels"""
+    a: int = 1
+    def middle(b: int) -> int:
+        def inner(c: int) -> int:
+            return a + b + c
+        return inner(100)
+    return middle(10)  # Expected: 111
+
+def test_shared_list() -> int:
+    """Objects are shared, so mutations are visible to the enclosing function"""
+    items: list[int] = [1]
+    def push(x: int) -> None:
+        items.append(x)
+    push(2)
+    push(3)
+    return len(items) * 10 + items[2]  # Expected: 33
+
+def test_local_shadowing() -> int:
+    """Assigning a name inside the inner function makes it local there"""
+    x: int = 5
+    def compute() -> int:
+        x: int = 40
+        return x + 2
+    return compute() + x  # Expected: 47
+
+def test_nested_defaults() -> str:
+    """Nested functions support default values"""
+    prefix: str = "item"
+    def label(n: int, sep: str = "-") -> str:
+        return prefix + sep + f"{n}"
+    print(label(1, ":"))
+    return label(2)  # Expected: item-2
+
+def test_method_self() -> int:
+    """Inner functions of methods can use self"""
+    acct: Account = Account(100)
+    return acct.apply_fees(5, 3)  # Expected: 85
+
+def main() -> int:
+    print(test_capture_local())
+    print(test_capture_param(10))
+    print(test_late_binding())
+    print(test_recursion())
+    print(test_sibling_call())
+    print(test_deep_nesting())
+    print(test_shared_list())
+    print(test_local_shadowing())
+    print(test_nested_defaults())
+    print(test_method_self())
+    return 0
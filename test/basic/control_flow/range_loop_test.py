@@ -0,0 +1,111 @@
+# for loops over range(), which count in a local when the step is constant
+from typing import Iterator
+
+calls: list[str] = []
+
+def bound(name: str, value: int) -> int:
+    calls.append(name)
+    return value
+
+def test_steps() -> int:
+    total: int = 0
+    for i in range(5):
+        total += i
+    for i in range(2, 6):
+        total += i * 10
+    for i in range(10, 0, -3):
+        total += i * 100
+    for i in range(1, 10, 4):
+        total += i * 1000
+    return total  # Expected: 10 + 140 + 2200 + 15000 = 17350
+
+def test_empty() -> int:
+    count: int = 0
+    for i in range(0):
+        count += 1
+    for i in range(5, 2):
+        count += 1
+    for i in range(2, 5, -1):
+        count += 1
+    return count  # Expected: 0
+
+def test_bounds_evaluated_once() -> int:
+    """start and stop are evaluated once, before the first iteration"""
+    calls.clear()
+    n: int = 3
+    count: int = 0
+    for i in range(bound("start", 0), bound("stop", n)):
+        n = 100
+        count += 1
+    print(calls)
+    return count  # Expected: 3
+
+def test_target_after_loop() -> int:
+    """The target keeps its last value, and assigning it does not skip ahead"""
+    last: int = -1
+    seen: list[int] = []
+    for last in range(4):
+        seen.append(last)
+        last = 100
+    print(seen)
+    return last  # Expected: 100
+
+def test_variable_step(step: int) -> int:
+    total: int = 0
+    for i in range(0, 10, step):
+        total += i
+    return total  # Expected: 0 + 3 + 6 + 9 = 18
+
+def test_nested() -> int:
+    pairs: int = 0
+    for i in range(4):
+        for j in range(i, 4):
+            pairs += 1
+    return pairs  # Expected: 10
+
+def test_comprehension() -> list[int]:
+    return [i * i for i in range(6, 0, -2)]  # Expected: [36, 16, 4]
+
+def evens(n: int) -> Iterator[int]:
+    for i in range(0, n, 2):
+        yield i
+
+def test_generator() -> int:
+    total: int = 0
+    for i in evens(7):
+        total += i
+    return total  # Expected: 0 + 2 + 4 + 6 = 12
+
+def test_loop_else() -> int:
+    result: int = 0
+    for i in range(3):
+        result += 1
+    else:
+        result += 10
+    return result  # Expected: 13
+
+def test_int_limits() -> int:
+    """The index stops short of wrapping around at the ends of the int range"""
+    top: int = 9223372036854775807
+    seen: list[int] = []
+    for i in range(top - 3, top, 2):
+        seen.append(top - i)
+    for i in range(top - 2, top, 1):
+        seen.append(top - i)
+    for i in range(-top + 2, -top - 1, -2):
+        seen.append(i + top)
+    print(seen)  # Expected: [3, 1, 2, 1, 2, 0]
+    return len(seen)
+
+def main() -> int:
+    print(test_steps())
+    print(test_empty())
+    print(test_bounds_evaluated_once())
+    print(test_target_after_loop())
+    print(test_variable_step(3))
+    print(test_nested())
+    print(test_comprehension())
+    print(test_generator())
+    print(test_loop_else())
+    print(test_int_limits())
+    return 0
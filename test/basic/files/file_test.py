@@ -0,0 +1,99 @@
+# open() and file read/readline/write/close tests
+
+from typing import TextIO
+
+PATH: str = "/tmp/typepython_file_test.txt"
+
+def write_lines(lines: list[str]) -> int:
+    f = open(PATH, "w")
+    written: int = 0
+    for line in lines:
+        written += f.write(line + "\n")
+    f.close()
+    return written
+
+def first_line(f: TextIO) -> str:
+    return f.readline().strip()
+
+def test_write_then_read() -> int:
+    """write() returns the character count and read() returns the whole file"""
+    written: int = write_lines(["alpha", "beta", "gamma"])
+    f = open(PATH)
+    content: str = f.read()
+    f.close()
+    print(content.strip())
+    return written  # Expected: 17
+
+def test_readline() -> int:
+    """readline() keeps the newline and returns "" at end of file"""
+    write_lines(["one", "two"])
+    f = open(PATH, "r")
+    count: int = 0
+    line: str = f.readline()
+    while line != "":
+        count += 1
+        print(line.strip())
+        line = f.readline()
+    f.close()
+    return count  # Expected: 2
+
+def test_with_statement() -> int:
+    """`with open(...) as f` closes the file on exit"""
+    with open(PATH, "w") as f:
+        f.write("first\n")
+        f.write("second\n")
+    rest: str = ""
+    with open(PATH) as f:
+        print(first_line(f))
+        rest = f.read()
+    return len(rest)  # Expected: 7
+
+def test_append() -> int:
+    """Mode "a" adds to the end of the file"""
+    write_lines(["start"])
+    with open(PATH, "a") as f:
+        f.write("more\n")
+    text: str = ""
+    with open(PATH) as f:
+        text = f.read()
+    print(text.strip().replace("\n", ","))
+    return len(text)  # Expected: 11
+
+def test_unicode() -> int:
+    """Text round-trips, and write() counts characters rather than bytes"""
+    n: int = 0
+    with open(PATH, "w") as f:
+        n = f.write("héllo wörld")
+    with open(PATH) as f:
+        print(f.read())
+    return n  # Expected: 11
+
+def test_missing_file() -> int:
+    """Opening a missing file for reading raises FileNotFoundError"""
+    try:
+        open("/tmp/typepython_missing_dir/nothing.txt")
+    except FileNotFoundError:
+        return 1  # Expected: 1
+    return 0
+
+def test_closed_file() -> int:
+    """Reading a closed file raises ValueError; closing twice is fine"""
+    write_lines(["x"])
+    f = open(PATH)
+    f.close()
+    f.close()
+    try:
+        f.read()
+    except ValueError:
+        return 1  # Expected: 1
+    return 0
+
+def main() -> int:
+    print(test_write_then_read())
+    print(test_readline())
+    print(test_with_statement())
+    print(test_append())
+    print(test_unicode())
+    print(test_missing_file())
+    print(test_closed_file())
+    return 0
@@ -0,0 +1,113 @@
+# Generator tests: functions that yield are iterated with for loops and next()
+from typing import Iterator, Generator
+
+def count_up(n: int) -> Iterator[int]:
+    i: int = 0
+    while i < n:
+        yield i
+        i += 1
+
+def evens_and_odds(n: int) -> Iterator[str]:
+    for i in range(n):
+        if i % 2 == 0:
+            yield "even"
+        else:
+            yield "odd"
+
+def first_negative(nums: list[int]) -> Iterator[int]:
+    for x in nums:
+        if x < 0:
+            return
+        yield x
+
+def pairs(n: int) -> Generator[int, None, None]:
+    for i in range(n):
+        for j in range(i):
+            yield i * 10 + j
+    yield -1
+
+def words(parts: list[str]) -> Iterator[str]:
+    yield "start"
+    for p in parts:
+        yield p
+    yield "end"
+
+def test_generator_for() -> int:
+    """A generator drives a for loop like any other iterator"""
+    total: int = 0
+    for x in count_up(5):
+        total = total + x
+    return total  # Expected: 10
+
+def test_generator_branches() -> int:
+    """Yields in both branches of an if resume in the right branch"""
+    result: str = ""
+    for w in evens_and_odds(4):
+        result = result + w[0]
+    print(result)
+    return len(result)  # Expected: 4
+
+def test_generator_return() -> int:
+    """A bare return ends the iteration early"""
+    nums: list[int] = [3, 4, -1, 5]
+    count: int = 0
+    for x in first_negative(nums):
+        count = count + x
+    return count  # Expected: 7
+
+def test_generator_nested_loops() -> int:
+    """Yields inside nested loops keep each loop's position"""
+    values = [v for v in pairs(4)]
+    print(values)
+    return len(values)  # Expected: 7
+
+def test_generator_next() -> int:
+    """next() advances a generator one value at a time"""
+    gen = count_up(10)
+    a: int = next(gen)
+    b: int = next(gen)
+    c: int = next(gen)
+    return a + b + c  # Expected: 3
+
+def test_generator_independent() -> int:
+    """Each call creates a generator with its own state"""
+    first = count_up(3)
+    second = count_up(3)
+    next(first)
+    next(first)
+    return next(first) * 10 + next(second)  # Expected: 20
+
+def test_generator_exhausted() -> int:
+    """An exhausted generator keeps raising StopIteration"""
+    gen = count_up(1)
+    next(gen)
+    stops: int = 0
+    try:
+        next(gen)
+    except StopIteration:
+        stops = stops + 1
+    try:
+        next(gen)
+    except StopIteration:
+        stops = stops + 1
+    return stops  # Expected: 2
+
+def test_generator_strings() -> int:
+    """Generators can yield heap values from before, inside and after a loop"""
+    parts: list[str] = ["a", "b"]
+    joined: str = ""
+    for w in words(parts):
+        joined = joined + w + " "
+    print(joined)
+    return len(joined)  # Expected: 14
+
+def main() -> int:
+    print(test_generator_for())
+    print(test_generator_branches())
+    print(test_generator_return())
+    print(test_generator_nested_loops())
+    print(test_generator_next())
+    print(test_generator_independent())
+    print(test_generator_exhausted())
+    print(test_generator_strings())
+    return 0
@@ -0,0 +1,95 @@
+# map() and filter() builtin tests
+
+calls: int = 0
+
+def double(x: int) -> int:
+    return x * 2
+
+def is_even(x: int) -> bool:
+    return x % 2 == 0
+
+def remainder(x: int) -> int:
+    return x % 3
+
+def traced(x: int) -> int:
+    global calls
+    calls += 1
+    return x + 100
+
+def shout(word: str) -> str:
+    return word.upper()
+
+def test_map_loop() -> int:
+    """A for loop over map() sees each transformed item"""
+    total: int = 0
+    for y in map(double, [1, 2, 3]):
+        print(y)
+        total += y
+    return total  # Expected: 12
+
+def test_filter_loop() -> int:
+    """A for loop over filter() only sees items the predicate accepts"""
+    total: int = 0
+    for y in filter(is_even, range(10)):
+        total += y
+    return total  # Expected: 20
+
+def test_filter_truthiness() -> int:
+    """filter() uses the truthiness of non-bool results, and None keeps truthy items"""
+    kept: list[int] = [x for x in filter(remainder, [3, 4, 5, 6, 7])]
+    print(kept)
+    nonzero: list[int] = [x for x in filter(None, [0, 1, 0, 2])]
+    print(nonzero)
+    return len(kept) + len(nonzero)  # Expected: 5
+
+def test_map_is_lazy() -> int:
+    """Items are computed one at a time as the loop asks for them"""
+    global calls
+    calls = 0
+    for y in map(traced, [1, 2, 3, 4]):
+        print(calls)
+    return calls  # Expected: 4
+
+def test_map_builtin() -> int:
+    """Builtins such as len and str work as the function"""
+    total: int = 0
+    for n in map(len, ["a", "bb", "ccc"]):
+        total += n
+    out: str = ""
+    for s in map(str, [7, 8]):
+        out = out + s
+    print(out)
+    return total  # Expected: 6
+
+def test_map_value() -> int:
+    """map() and filter() can be stored and consumed with next()"""
+    it = map(double, [5, 6])
+    first: int = next(it)
+    second: int = next(it)
+    evens = filter(is_even, [1, 4, 6])
+    return first + second + next(evens)  # Expected: 26
+
+def test_nested() -> int:
+    """map() over filter() chains lazily"""
+    result: list[int] = [y for y in map(double, filter(is_even, range(7)))]
+    print(result)
+    return len(result)  # Expected: 4
+
+def test_map_strings() -> int:
+    """map() can change the item type"""
+    out: str = ""
+    for word in map(shout, ["hi", "there"]):
+        out = out + word + " "
+    print(out.strip())
+    return len(out)  # Expected: 9
+
+def main() -> int:
+    print(test_map_loop())
+    print(test_filter_loop())
+    print(test_filter_truthiness())
+    print(test_map_is_lazy())
+    print(test_map_builtin())
+    print(test_map_value())
+    print(test_nested())
+    print(test_map_strings())
+    return 0
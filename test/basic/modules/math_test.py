@@ -0,0 +1,155 @@
+# math module tests: sqrt, sin, cos, log, exp, floor, ceil, pow, pi and e
+
+import math
+from math import sqrt, floor, pi
+
+def close(a: float, b: float) -> bool:
+    return a - b < 0.000000001 and b - a < 0.000000001
+
+def test_sqrt() -> int:
+    """sqrt accepts floats and ints"""
+    print(math.sqrt(2.25), sqrt(6.25))
+    if not close(sqrt(2) * sqrt(2), 2.0):
+        return 0
+    return int(math.sqrt(144))  # Expected: 12
+
+def test_trig() -> int:
+    """sin and cos agree with the unit circle"""
+    count: int = 0
+    if close(math.sin(0.0), 0.0) and close(math.cos(0), 1.0):
+        count += 1
+    if close(math.sin(pi / 2), 1.0) and close(math.cos(math.pi), -1.0):
+        count += 1
+    x: float = 0.75
+    if close(math.sin(x) * math.sin(x) + math.cos(x) * math.cos(x), 1.0):
+        count += 1
+    return count  # Expected: 3
+
+def test_log_exp() -> int:
+    """log is the natural logarithm and the inverse of exp"""
+    count: int = 0
+    if close(math.log(math.e), 1.0) and close(math.exp(0), 1.0):
+        count += 1
+    if close(math.log(math.exp(2.5)), 2.5):
+        count += 1
+    if close(math.exp(1.0), math.e):
+        count += 1
+    return count  # Expected: 3
+
+def test_floor_ceil() -> int:
+    """floor and ceil round toward -inf and +inf and return ints"""
+    print(math.floor(2.5), math.ceil(2.5), floor(-2.5), math.ceil(-2.5))
+    print(math.floor(7), math.ceil(-7))
+    n: int = math.floor(9.99) + math.ceil(0.01)
+    return n  # Expected: 10
+
+def test_floor_ceil_int() -> int:
+    """floor and ceil give an int argument back exactly, beyond float precision"""
+    big: int = 9007199254740993
+    print(math.floor(big), math.ceil(-big))
+    return math.floor(big) - big  # Expected: 0
+
+def test_floor_ceil_errors() -> int:
+    """floor and ceil of NaN or an infinity raise, as int() does"""
+    count: int = 0
+    nan: float = float("nan")
+    inf: float = float("inf")
+    try:
+        math.floor(nan)
+    except ValueError as e:
+        print(e)
+        count += 1
+    try:
+        math.ceil(inf)
+    except OverflowError as e:
+        print(e)
+        count += 1
+    try:
+        math.floor(-inf)
+    except OverflowError:
+        count += 1
+    return count  # Expected: 3
+
+def test_domain_errors() -> int:
+    """Arguments outside a function's domain raise ValueError"""
+    count: int = 0
+    try:
+        math.sqrt(-1.0)
+    except ValueError as e:
+        print(e)
+        count += 1
+    try:
+        math.log(0)
+    except ValueError as e:
+        print(e)
+        count += 1
+    try:
+        math.log(-2.5)
+    except ValueError:
+        count += 1
+    try:
+        math.pow(-1, 0.5)
+    except ValueError:
+        count += 1
+    try:
+        math.pow(0, -1)
+    except ValueError:
+        count += 1
+    try:
+        math.sin(float("inf"))
+    except ValueError:
+        count += 1
+    try:
+        math.cos(-float("inf"))
+    except ValueError:
+        count += 1
+    print(math.sqrt(0.0), math.log(1))
+    return count  # Expected: 7
+
+def test_range_errors() -> int:
+    """Results too large for a float raise OverflowError"""
+    count: int = 0
+    try:
+        math.exp(1000)
+    except OverflowError as e:
+        print(e)
+        count += 1
+    big: float = 10.0
+    try:
+        math.pow(big, 400)
+    except OverflowError:
+        count += 1
+    return count  # Expected: 2
+
+def test_non_finite_arguments() -> int:
+    """NaN and infinite arguments give NaN or infinite results without raising"""
+    inf: float = float("inf")
+    nan: float = float("nan")
+    print(math.exp(inf), math.log(inf), math.pow(0.0, -inf), math.exp(-inf))
+    print(math.sqrt(nan), math.pow(1, nan), math.sin(nan))
+    return 1
+
+def test_pow() -> int:
+    """pow always returns a float"""
+    print(math.pow(2, 0.5) == math.sqrt(2), math.pow(4.0, -0.5))
+    return int(math.pow(2, 10))  # Expected: 1024
+
+def test_constants() -> int:
+    """pi and e are floats"""
+    print(math.pi > 3.14159 and math.pi < 3.1416, math.e > 2.71828 and math.e < 2.7183)
+    area: float = pi * 2.0 * 2.0
+    return int(area * 100)  # Expected: 1256
+
+def main() -> int:
+    print(test_sqrt())
+    print(test_trig())
+    print(test_log_exp())
+    print(test_floor_ceil())
+    print(test_floor_ceil_int())
+    print(test_floor_ceil_errors())
+    print(test_domain_errors())
+    print(test_range_errors())
+    print(test_non_finite_arguments())
+    print(test_pow())
+    print(test_constants())
+    return 0
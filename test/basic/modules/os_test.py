@@ -0,0 +1,51 @@
+# os module tests: getenv, environ and getcwd
+
+import os
+from os import getenv
+
+def test_getenv() -> int:
+    """getenv returns the variable's value, or the default when it is unset"""
+    path: str = os.getenv("PATH", "")
+    print(len(path) > 0)
+    print(getenv("TYPEPYTHON_UNSET_VARIABLE", "fallback"))
+    return len(getenv("TYPEPYTHON_UNSET_VARIABLE", ""))  # Expected: 0
+
+def test_environ() -> int:
+    """environ is a dict of the environment that getenv agrees with"""
+    print("PATH" in os.environ, "TYPEPYTHON_UNSET_VARIABLE" in os.environ)
+    print(os.environ["PATH"] == os.getenv("PATH", ""))
+    count: int = 0
+    for name in os.environ:
+        if name == "PATH":
+            count += 1
+    return count  # Expected: 1
+
+def test_environ_assignment() -> int:
+    """Assigning to environ is seen by later getenv calls"""
+    os.environ["TYPEPYTHON_SET_VARIABLE"] = "42"
+    value: str = os.getenv("TYPEPYTHON_SET_VARIABLE", "unset")
+    print(value)
+    return int(value)  # Expected: 42
+
+def test_environ_missing_key() -> int:
+    """Reading an unset variable through environ raises KeyError"""
+    try:
+        print(os.environ["TYPEPYTHON_UNSET_VARIABLE"])
+    except KeyError:
+        return 1  # Expected: 1
+    return 0
+
+def test_getcwd() -> int:
+    """getcwd returns an absolute path"""
+    cwd: str = os.getcwd()
+    if cwd.startswith("/"):
+        return 1  # Expected: 1
+    return 0
+
+def main() -> int:
+    print(test_getenv())
+    print(test_environ())
+    print(test_environ_assignment())
+    print(test_environ_missing_key())
+    print(test_getcwd())
+    return 0
@@ -0,0 +1,87 @@
+# random module tests: random, randint, seed and choice
+# Only properties are checked, since the generator differs from CPython's
+
+import random
+from random import randint, choice
+
+def test_seed_repeats() -> int:
+    """The same seed gives the same sequence"""
+    random.seed(42)
+    first: list[int] = [randint(0, 1000000) for i in range(5)]
+    x: float = random.random()
+    random.seed(42)
+    same: int = 0
+    for n in first:
+        if randint(0, 1000000) == n:
+            same += 1
+    y: float = random.random()
+    print(x == y)
+    random.seed()
+    return same  # Expected: 5
+
+def test_random_range() -> int:
+    """random() is in [0.0, 1.0)"""
+    random.seed(7)
+    count: int = 0
+    r: float = 0.0
+    for i in range(1000):
+        r = random.random()
+        if r >= 0.0 and r < 1.0:
+            count += 1
+    return count  # Expected: 1000
+
+def test_randint_bounds() -> int:
+    """randint(a, b) includes both ends and nothing outside them"""
+    random.seed(1)
+    seen: set[int] = set()
+    n: int = 0
+    for i in range(500):
+        n = random.randint(-2, 2)
+        if n < -2 or n > 2:
+            return 0
+        seen.add(n)
+    print(sorted(seen))
+    return random.randint(9, 9)  # Expected: 9
+
+def test_randint_empty() -> int:
+    """randint() with b < a raises ValueError"""
+    try:
+        random.randint(5, 4)
+    except ValueError:
+        return 1  # Expected: 1
+    return 0
+
+def test_choice() -> int:
+    """choice() returns an item of a list or a character of a str"""
+    random.seed(3)
+    words: list[str] = ["red", "green", "blue"]
+    count: int = 0
+    w: str = ""
+    c: str = ""
+    for i in range(50):
+        w = choice(words)
+        if w == "red" or w == "green" or w == "blue":
+            count += 1
+        c = random.choice("xyz")
+        if c == "x" or c == "y" or c == "z":
+            count += 1
+    print(random.choice([7]))
+    return count  # Expected: 100
+
+def test_choice_empty() -> int:
+    """choice() on an empty sequence raises IndexError"""
+    empty: list[int] = []
+    try:
+        print(random.choice(empty))
+    except IndexError:
+        return 1  # Expected: 1
+    return 0
+
+def main() -> int:
+    print(test_seed_repeats())
+    print(test_random_range())
+    print(test_randint_bounds())
+    print(test_randint_empty())
+    print(test_choice())
+    print(test_choice_empty())
+    return 0
@@ -0,0 +1,12 @@
+# sys.exit() test program: echoes its arguments, then exits with status 3,
+# or with a message on stderr (status 1) when given more than two arguments
+
+import sys
+
+print("args:", len(sys.argv) - 1)
+for arg in sys.argv[1:]:
+    print(arg)
+if len(sys.argv) > 3:
+    sys.exit("too many arguments")
+sys.exit(3)
+print("not reached")
@@ -0,0 +1,45 @@
+# sys module tests: argv and the standard streams
+
+import sys
+from sys import argv as arguments, stdout
+from typing import TextIO
+
+def emit(out: TextIO, text: str) -> int:
+    return out.write(text)
+
+def test_argv() -> int:
+    """sys.argv starts with the program name and is one shared list"""
+    count: int = len(sys.argv)
+    print(count >= 1, len(sys.argv[0]) > 0)
+    sys.argv.append("extra")
+    grown: int = len(arguments) - count
+    print(arguments[len(arguments) - 1])
+    return grown  # Expected: 1
+
+def test_stdout_write() -> int:
+    """sys.stdout.write() interleaves with print() in order"""
+    n: int = sys.stdout.write("written ")
+    print("then printed")
+    stdout.write("imported\n")
+    return n  # Expected: 8
+
+def test_stderr_write() -> int:
+    """sys.stderr.write() goes to stderr, not stdout"""
+    return sys.stderr.write("to stderr\n")  # Expected: 10
+
+def test_stream_as_file() -> int:
+    """The standard streams are files, so they can be passed as TextIO"""
+    return emit(sys.stdout, "via TextIO\n") + emit(sys.stderr, "")  # Expected: 11
+
+def test_shadowed() -> int:
+    """A local variable shadows an imported name"""
+    stdout: int = 7
+    return stdout + 1  # Expected: 8
+
+def main() -> int:
+    print(test_argv())
+    print(test_stdout_write())
+    print(test_stderr_write())
+    print(test_stream_as_file())
+    print(test_shadowed())
+    return 0
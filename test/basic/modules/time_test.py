@@ -0,0 +1,53 @@
+# time module tests: time, monotonic and sleep
+
+import time
+from time import monotonic, sleep
+
+def test_time() -> int:
+    """time() is seconds since the epoch, as a float"""
+    now: float = time.time()
+    print(now > 1600000000.0)
+    return 1  # Expected: 1
+
+def test_monotonic() -> int:
+    """monotonic() never goes backwards"""
+    first: float = time.monotonic()
+    second: float = monotonic()
+    if second >= first:
+        return 1  # Expected: 1
+    return 0
+
+def test_sleep() -> int:
+    """sleep() waits at least as long as asked, for float and int durations"""
+    start: float = time.monotonic()
+    time.sleep(0.02)
+    sleep(0)
+    elapsed: float = time.monotonic() - start
+    print(elapsed >= 0.02, elapsed < 5.0)
+    return 1  # Expected: 1
+
+def test_sleep_negative() -> int:
+    """A negative duration raises ValueError"""
+    try:
+        time.sleep(-1)
+    except ValueError:
+        return 1  # Expected: 1
+    return 0
+
+def test_benchmark() -> int:
+    """Timing a loop with monotonic()"""
+    start: float = time.monotonic()
+    total: int = 0
+    for i in range(1000):
+        total += i
+    elapsed: float = time.monotonic() - start
+    print(elapsed >= 0.0)
+    return total  # Expected: 499500
+
+def main() -> int:
+    print(test_time())
+    print(test_monotonic())
+    print(test_sleep())
+    print(test_sleep_negative())
+    print(test_benchmark())
+    return 0
@@ -0,0 +1,69 @@
+# bytearray methods: extend, +=, slice assignment and bytes() conversion
+
+def test_extend() -> int:
+    """extend() and += append the bytes of a bytes value in place"""
+    ba: bytearray = bytearray(b"ab")
+    alias: bytearray = ba
+    ba.extend(b"cd")
+    ba.extend(b"")
+    ba += b"ef"
+    print(alias, len(alias))
+    return ba[5]  # Expected: 102
+
+def test_slice_replace() -> int:
+    """Assigning to a plain slice replaces it, growing or shrinking the bytearray"""
+    ba: bytearray = bytearray(b"hello world")
+    ba[0:5] = b"HELLO"
+    print(ba)
+    ba[5:6] = b"___"
+    print(ba)
+    ba[:5] = b""
+    print(ba)
+    ba[len(ba):] = b"!"
+    print(ba)
+    ba[-1:] = b"?"
+    print(ba)
+    return len(ba)  # Expected: 9
+
+def test_slice_insert() -> int:
+    """An empty slice inserts without removing anything"""
+    ba: bytearray = bytearray(b"ad")
+    ba[1:1] = b"bc"
+    ba[:0] = b">"
+    print(ba)
+    return len(ba)  # Expected: 5
+
+def test_extended_slice() -> int:
+    """A slice with a step needs a value of the same size"""
+    ba: bytearray = bytearray(b"abcdef")
+    ba[::2] = b"XYZ"
+    print(ba)
+    ba[::-1] = b"123456"
+    print(ba)
+    message: str = ""
+    try:
+        ba[::2] = b"xy"
+    except ValueError as e:
+        message = str(e)
+    print(message)
+    return len(ba)  # Expected: 6
+
+def test_bytes_conversion() -> int:
+    """bytes() copies a bytearray, so later changes do not show"""
+    ba: bytearray = bytearray(b"abc")
+    data: bytes = bytes(ba)
+    ba[0] = 120
+    ba.extend(b"d")
+    print(data, ba, bytes(), bytes(b"xy"))
+    copy: bytearray = bytearray(data)
+    copy[1] = 66
+    print(copy, data)
+    return len(data)  # Expected: 3
+
+def main() -> int:
+    print(test_extend())
+    print(test_slice_replace())
+    print(test_slice_insert())
+    print(test_extended_slice())
+    print(test_bytes_conversion())
+    return 0
@@ -0,0 +1,75 @@
+# bytes methods: +, hex, find, count, in, startswith, endswith, strip, replace, split, join
+
+def test_concat() -> int:
+    """bytes + bytes builds new bytes; += rebinds"""
+    data: bytes = b"ab" + b"cd"
+    data += b"!"
+    print(data, b"" + b"x")
+    return len(data)  # Expected: 5
+
+def test_hex() -> int:
+    """hex() gives two lowercase digits per byte"""
+    print(b"\x00\xffAz".hex(), b"".hex())
+    return len(b"hello".hex())  # Expected: 10
+
+def test_search() -> int:
+    """find(), count() and in look for a byte sequence"""
+    data: bytes = b"abcabcab"
+    print(data.find(b"ca"), data.find(b"x"), data.find(b""))
+    print(data.count(b"ab"), data.count(b"x"), data.count(b""))
+    count: int = 0
+    if b"bca" in data:
+        count += 1
+    if b"cc" not in data:
+        count += 1
+    return count  # Expected: 2
+
+def test_prefix_suffix() -> int:
+    """startswith() and endswith() compare the ends"""
+    data: bytes = b"GET /index.html"
+    count: int = 0
+    if data.startswith(b"GET "):
+        count += 1
+    if data.endswith(b".html"):
+        count += 1
+    if not data.startswith(b"POST"):
+        count += 1
+    if data.endswith(b""):
+        count += 1
+    return count  # Expected: 4
+
+def test_strip_replace() -> int:
+    """strip() removes ASCII whitespace; replace() swaps every match"""
+    print(b"  \t hi there \r\n".strip(), b"   ".strip())
+    print(b"a-b-c".replace(b"-", b"+"), b"aaa".replace(b"a", b"bb"), b"ab".replace(b"", b"."))
+    return len(b"x.y.z".replace(b".", b""))  # Expected: 3
+
+def test_split_join() -> int:
+    """split(sep) keeps empty fields; split() drops whitespace runs; join() reverses split"""
+    fields: list[bytes] = b"a,b,,c".split(b",")
+    print(len(fields), fields[0], fields[2], fields[3])
+    words: list[bytes] = b"  one two\n three ".split()
+    print(len(words), words[0], words[2])
+    joined: bytes = b"-".join(words)
+    print(joined, b"".join(fields))
+    return len(fields) * 10 + len(words)  # Expected: 43
+
+def test_empty_separator() -> int:
+    """split() with an empty separator raises ValueError"""
+    count: int = 0
+    try:
+        parts: list[bytes] = b"abc".split(b"")
+    except ValueError as e:
+        print(e)
+        count += 1
+    return count  # Expected: 1
+
+def main() -> int:
+    print(test_concat())
+    print(test_hex())
+    print(test_search())
+    print(test_prefix_suffix())
+    print(test_strip_replace())
+    print(test_split_join())
+    print(test_empty_separator())
+    return 0
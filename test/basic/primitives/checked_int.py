@@ -0,0 +1,72 @@
+# --checked-int test program: int arithmetic that leaves the 64-bit range
+# raises OverflowError instead of wrapping around
+
+def largest() -> int:
+    return 9223372036854775807
+
+def describe_add(a: int, b: int) -> str:
+    result: str = ""
+    try:
+        result = str(a + b)
+    except OverflowError as e:
+        result = "error: " + str(e)
+    return result
+
+def count_overflows(a: int, b: int) -> int:
+    """Each operation that overflows is counted once"""
+    count: int = 0
+    try:
+        a - b
+    except OverflowError:
+        count += 1
+    try:
+        a * b
+    except OverflowError:
+        count += 1
+    try:
+        -(a - 1)
+    except OverflowError:
+        count += 1
+    return count
+
+def power_overflows(base: int, exp: int) -> bool:
+    overflowed: bool = False
+    try:
+        base ** exp
+    except OverflowError:
+        overflowed = True
+    return overflowed
+
+def describe_shift(a: int, n: int) -> str:
+    result: str = ""
+    try:
+        result = str(a << n)
+    except OverflowError:
+        result = "overflow"
+    except ValueError as e:
+        result = "error: " + str(e)
+    return result
+
+def main() -> int:
+    top: int = largest()
+    print(describe_add(top - 1, 1))
+    print(describe_add(top, 1))
+    print(count_overflows(-top, 2))
+    print(power_overflows(2, 62), power_overflows(2, 63), power_overflows(-2, 63))
+    smallest: int = -top - 1
+    print(smallest // 2, smallest % 3)
+    try:
+        smallest // -1
+    except OverflowError:
+        print("floor division overflowed")
+    print(describe_shift(1, 62), describe_shift(-1, 63), describe_shift(1, 63))
+    print(describe_shift(3, 62), describe_shift(0, 100), describe_shift(1, 64))
+    print(describe_shift(1, -1))
+    # A range loop's index never steps past the end of the int range
+    count: int = 0
+    for i in range(top - 3, top, 2):
+        count += 1
+    print(count)
+    return 0
+
+main()
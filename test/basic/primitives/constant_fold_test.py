@@ -0,0 +1,88 @@
+# Constant expressions, branches on constants and unused locals, which
+# --opt-level=1 folds and removes without changing what the program does
+
+calls: list[str] = []
+
+def record(name: str, value: bool) -> bool:
+    calls.append(name)
+    return value
+
+def test_int_arithmetic() -> int:
+    a: int = 2 * 3 + 4 - 1
+    b: int = -7 // 2 + -7 % 2 + 7 // -2 + 7 % -2
+    c: int = (1 << 10) + (-256 >> 4) + (6 & 3) + (6 | 3) + (6 ^ 3)
+    d: int = -(5 - 8) * 3
+    print(a, b, c, d)
+    return a + b + c + d  # Expected: 1032
+
+def test_float_arithmetic() -> bool:
+    x: float = 1.5 * 4.0 - 0.5
+    y: float = 7 / 2
+    z: float = -7.5 // 2.0 + 7.5 % -2.0
+    return x == 5.5 and y == 3.5 and z == -4.5  # Expected: True
+
+def test_comparisons() -> int:
+    count: int = 0
+    if 3 < 5:
+        count += 1
+    if 2.5 >= 3:
+        count += 10
+    if True != False:
+        count += 100
+    if not 0:
+        count += 1000
+    if 1 == 1.0:
+        count += 10000
+    return count  # Expected: 11101
+
+def test_short_circuit() -> int:
+    """Constant operands decide or drop out; the calls left still run in order"""
+    calls.clear()
+    first: bool = False and record("skipped", True)
+    second: bool = True and record("and", False)
+    third: bool = record("or", False) or True or record("skipped", True)
+    fourth: bool = 0 or record("last", True)
+    print(first, second, third, fourth, calls)
+    return len(calls)  # Expected: 3
+
+def test_dead_branches() -> int:
+    total: int = 0
+    if False:
+        total += 1000
+    else:
+        total += 1
+    while False:
+        total += 1000
+    if 1 + 1 == 2:
+        total += 10
+        if 0:
+            total += 1000
+    return total  # Expected: 11
+
+def test_unused_locals(n: int) -> int:
+    """Values with side effects are still evaluated"""
+    unused: int = n * 2 + 1
+    spare: bool = record("unused call", True)
+    items: list[int] = [n, n]
+    items2: list[int] = items
+    items2 = [0]
+    try:
+        raise ValueError("ignored")
+    except ValueError as e:
+        pass
+    print(calls[-1])
+    return n  # Expected: 5
+
+def test_after_return() -> int:
+    return 42  # Expected: 42
+    print("unreachable")
+
+def main() -> int:
+    print(test_int_arithmetic())
+    print(test_float_arithmetic())
+    print(test_comparisons())
+    print(test_short_circuit())
+    print(test_dead_branches())
+    print(test_unused_locals(5))
+    print(test_after_return())
+    return 0
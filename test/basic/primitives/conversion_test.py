@@ -0,0 +1,196 @@
+# str() / int() / float() / bool() / hex() / oct() / bin() / round() conversion builtin tests
+
+class Meters:
+    value: int
+
+    def __init__(self, value: int) -> None:
+        self.value = value
+
+    def __int__(self) -> int:
+        return self.value
+
+    def __float__(self) -> float:
+        return float(self.value) + 0.5
+
+    def __bool__(self) -> bool:
+        return self.value > 0
+
+    def __round__(self) -> int:
+        return self.value * 100
+
+class Bag:
+    items: list[int]
+
+    def __init__(self, items: list[int]) -> None:
+        self.items = items
+
+    def __len__(self) -> int:
+        return len(self.items)
+
+class Plain:
+    tag: int
+
+    def __init__(self, tag: int) -> None:
+        self.tag = tag
+
+class Flags:
+    bits: int
+
+    def __init__(self, bits: int) -> None:
+        self.bits = bits
+
+    def __index__(self) -> int:
+        return self.bits
+
+def test_str_conversion() -> int:
+    """str() formats ints and bools and returns strings unchanged"""
+    s: str = str(42) + "/" + str(-7) + "/" + str(True) + "/" + str("x")
+    print(s)
+    return len(str()) + len(str(12345))  # Expected: 5
+
+def test_int_from_str() -> int:
+    """int() parses decimal literals, ignoring surrounding whitespace"""
+    a: int = int("42")
+    b: int = int("  -17\n")
+    c: int = int("+1_000")
+    print(a, b, c)
+    return a + b + c  # Expected: 1025
+
+def test_int_with_base() -> int:
+    """int(s, base) parses other bases; base 0 reads the prefix"""
+    print(int("ff", 16), int("0x1F", 16), int("777", 8), int("0b101", 0), int("z", 36))
+    return int("1010", 2)  # Expected: 10
+
+def test_int_from_numbers() -> int:
+    """int() truncates floats toward zero and converts bools"""
+    print(int(3.9), int(-3.9), int(True), int(False), int(7), int())
+    return int(2.5) + int(True)  # Expected: 3
+
+def test_int_invalid() -> int:
+    """A malformed literal raises ValueError"""
+    count: int = 0
+    try:
+        int("12a")
+    except ValueError:
+        count = count + 1
+    try:
+        int("")
+    except ValueError:
+        count = count + 1
+    try:
+        int("9", 8)
+    except ValueError:
+        count = count + 1
+    return count  # Expected: 3
+
+def test_float_conversion() -> int:
+    """float() parses strings and widens ints and bools"""
+    a: float = float("2.5")
+    b: float = float(" -0.125 ")
+    c: float = float("1e3")
+    print(a, b, c == 1000.0)
+    if float(4) / 8 != 0.5:
+        return 0
+    if float(True) + float() != 1.0:
+        return 0
+    return int(a * 4 + c)  # Expected: 1010
+
+def test_float_invalid() -> int:
+    """A malformed float literal raises ValueError"""
+    try:
+        float("abc")
+    except ValueError:
+        return 1  # Expected: 1
+    return 0
+
+def test_bool_conversion() -> int:
+    """bool() is false for zero, empty and falsy objects"""
+    print(bool(0), bool(3), bool(0.0), bool(-1.5), bool(), bool(True))
+    print(bool(Bag([])), bool(Bag([1, 2])), bool(Plain(0)), bool((1, 2)))
+    count: int = 0
+    if bool(Meters(5)):
+        count = count + 1
+    if not bool(Meters(0)):
+        count = count + 1
+    return count  # Expected: 2
+
+def test_class_conversions() -> int:
+    """int() and float() dispatch to __int__ and __float__"""
+    m: Meters = Meters(4)
+    f: float = float(m)
+    print(f)
+    return int(m) * 10 + int(f * 2)  # Expected: 49
+
+def test_radix_strings() -> int:
+    """hex(), oct() and bin() add the base prefix, after the sign"""
+    print(hex(255), oct(8), bin(5))
+    print(hex(-31), oct(0), bin(-1), hex(True))
+    print(hex(-9223372036854775807 - 1))
+    print(bin(Flags(10)), hex(Flags(48879)))
+    return len(bin(255))  # Expected: 10
+
+def test_radix_round_trip() -> int:
+    """int(s, 0) reads back what hex(), oct() and bin() produce"""
+    value: int = 1234567
+    total: int = 0
+    if int(hex(value), 0) == value:
+        total += 1
+    if int(oct(value), 0) == value:
+        total += 1
+    if int(bin(-value), 0) == -value:
+        total += 1
+    if int(hex(value)[2:], 16) == value:
+        total += 1
+    return total  # Expected: 4
+
+def test_round_float() -> int:
+    """round() rounds half to even; with ndigits the result stays a float"""
+    print(round(2.5), round(3.5), round(-2.5), round(0.5), round(2.675, 2), round(-1.25, 1))
+    print(round(1234.5678, -2), round(0.1 + 0.2, 3), round(7.0, 0))
+    return round(9.99) + round(-0.4)  # Expected: 10
+
+def test_round_int() -> int:
+    """round() leaves ints alone unless ndigits is negative"""
+    print(round(7), round(1234, 2), round(1250, -2), round(1350, -2), round(-1251, -2), round(True))
+    return round(987654, -3) + round(5, -1)  # Expected: 988000
+
+def test_round_errors() -> int:
+    """Rounding infinity or NaN to an int raises"""
+    count: int = 0
+    big: float = 1e308
+    try:
+        value: int = round(big * 10)
+    except OverflowError:
+        count = count + 1
+    try:
+        value = round((big * 10) - (big * 10))
+    except ValueError:
+        count = count + 1
+    return count  # Expected: 2
+
+def test_number_methods() -> int:
+    """Numbers have __int__, __float__, __round__ and is_integer()"""
+    x: float = 3.75
+    n: int = 12
+    big: float = 1e308
+    print(x.__int__(), n.__float__(), x.__round__(1), x.__round__(), True.__int__())
+    print((2.0).is_integer(), x.is_integer(), (1e300).is_integer(), (big * 10).is_integer())
+    return round(Meters(3)) + x.__int__()  # Expected: 303
+
+def main() -> int:
+    print(test_str_conversion())
+    print(test_int_from_str())
+    print(test_int_with_base())
+    print(test_int_from_numbers())
+    print(test_int_invalid())
+    print(test_float_conversion())
+    print(test_float_invalid())
+    print(test_bool_conversion())
+    print(test_class_conversions())
+    print(test_radix_strings())
+    print(test_radix_round_trip())
+    print(test_round_float())
+    print(test_round_int())
+    print(test_round_errors())
+    print(test_number_methods())
+    return 0
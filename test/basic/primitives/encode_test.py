@@ -0,0 +1,45 @@
+# str.encode() / bytes.decode() tests
+
+def test_encode() -> int:
+    """encode() gives the UTF-8 bytes of a string"""
+    ascii_bytes: bytes = "hi!".encode()
+    accented: bytes = "héllo".encode()
+    print(ascii_bytes, accented)
+    print("".encode())
+    return len(accented) + accented[1]  # Expected: 201
+
+def test_round_trip() -> int:
+    """decode() inverts encode(), including multi-byte characters"""
+    text: str = "naïve 😀 ok"
+    data: bytes = text.encode()
+    back: str = data.decode()
+    print(back, back == text)
+    return len(data) - len(back)  # Expected: 4
+
+def describe_decode(data: bytes) -> str:
+    result: str = ""
+    try:
+        result = data.decode()
+    except UnicodeDecodeError as e:
+        result = "error: " + str(e)
+    return result
+
+def test_invalid_utf8() -> int:
+    """Malformed input raises UnicodeDecodeError with CPython's message"""
+    print(describe_decode(b"ok"))
+    print(describe_decode(b"\xff"))
+    print(describe_decode(b"a\xe4\xbdb"))
+    print(describe_decode(b"abc\xe4\xbd"))
+    print(describe_decode(b"\xed\xa0\x80"))
+    count: int = 0
+    try:
+        b"\xc0\xaf".decode()
+    except ValueError:
+        count += 1
+    return count  # Expected: 1
+
+def main() -> int:
+    print(test_encode())
+    print(test_round_trip())
+    print(test_invalid_utf8())
+    return 0
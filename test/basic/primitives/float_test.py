@@ -129,3 +129,17 @@ def test_print_float() -> int:
     x: float = 3.14
     print(x)
     return 1
+
+# Test floats print the shortest repr that round-trips
+def test_print_float_repr() -> int:
+    print(3.0)
+    print(-0.0)
+    print(0.1 + 0.2)
+    print(1 / 3)
+    print(1e16)
+    print(123456789012345.6)
+    print(0.0001)
+    print(1.5e-05)
+    print(2.0 ** 63)
+    print(str(100.0) + " " + f"{2.5e-10}")
+    return 1
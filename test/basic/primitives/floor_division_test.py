@@ -0,0 +1,57 @@
+# Floor division, modulo and divmod() tests (rounding toward negative infinity)
+
+def test_int_floor_division() -> int:
+    """// rounds down, also when exactly one operand is negative"""
+    print(7 // 2, -7 // 2, 7 // -2, -7 // -2)
+    print(6 // 3, -6 // 3, 0 // -5)
+    return -1 // 10  # Expected: -1
+
+def test_int_modulo() -> int:
+    """% takes the sign of the divisor"""
+    print(7 % 3, -7 % 3, 7 % -3, -7 % -3)
+    print(-6 % 3, 6 % -3)
+    hour: int = -1
+    return hour % 24  # Expected: 23
+
+def test_float_modulo() -> int:
+    """Float % follows the same sign rule"""
+    r: float = -7.5 % 2.0
+    s: float = 7.5 % -2.0
+    t: float = -7.0 // 2.0
+    if r == 0.5 and s == -0.5 and t == -4.0:
+        return 1
+    return 0  # Expected: 1
+
+def next_value(counter: list[int]) -> int:
+    counter[0] = counter[0] + 1
+    return counter[0] * 10
+
+def test_divmod() -> int:
+    """divmod(a, b) is (a // b, a % b), evaluating each operand once"""
+    q, r = divmod(17, 5)
+    print(q, r)
+    print(divmod(-17, 5), divmod(17, -5))
+    counter: list[int] = [0]
+    pair: tuple[int, int] = divmod(next_value(counter), 3)
+    print(pair, counter[0])
+    fq, fr = divmod(7.5, 2.0)
+    if fq == 3.0 and fr == 1.5:
+        print("float divmod ok")
+    return q * 10 + r  # Expected: 32
+
+def test_identity() -> int:
+    """(a // b) * b + a % b == a for every sign combination"""
+    ok: int = 0
+    for a in [13, -13]:
+        for b in [4, -4]:
+            if (a // b) * b + a % b == a:
+                ok += 1
+    return ok  # Expected: 4
+
+def main() -> int:
+    print(test_int_floor_division())
+    print(test_int_modulo())
+    print(test_float_modulo())
+    print(test_divmod())
+    print(test_identity())
+    return 0
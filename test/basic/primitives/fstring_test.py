@@ -0,0 +1,93 @@
+# F-string tests: replacement fields are converted with str() (or repr() for !r)
+
+class Point:
+    x: int
+    y: int
+
+    def __init__(self, x: int, y: int) -> None:
+        self.x = x
+        self.y = y
+
+    def __str__(self) -> str:
+        return f"({self.x}, {self.y})"
+
+class Celsius:
+    degrees: float
+
+    def __init__(self, degrees: float) -> None:
+        self.degrees = degrees
+
+    def __repr__(self) -> str:
+        return f"Celsius({self.degrees})"
+
+def test_fstring_int() -> int:
+    """Ints are formatted in decimal, including negatives"""
+    x: int = 42
+    y: int = -7
+    s: str = f"x={x}, y={y}"
+    print(s)
+    return len(s)  # Expected: 10
+
+def test_fstring_float_bool() -> int:
+    """Floats and bools use their str() forms"""
+    t: float = 2.5
+    ok: bool = True
+    s: str = f"t={t} ok={ok} not={not ok}"
+    print(s)
+    return len(s)  # Expected: 23
+
+def test_fstring_str() -> int:
+    """Strings are spliced in as-is, or quoted with !r"""
+    name: str = "world"
+    s: str = f"hello {name}! {name!r}"
+    print(s)
+    return len(s)  # Expected: 20
+
+def test_fstring_expressions() -> int:
+    """Replacement fields can hold arbitrary expressions"""
+    nums: list[int] = [1, 2, 3]
+    s: str = f"{len(nums)} items, sum {nums[0] + nums[1] + nums[2]}, {nums}"
+    print(s)
+    return len(s)  # Expected: 25
+
+def test_fstring_objects() -> int:
+    """Objects use __str__, falling back to __repr__"""
+    p: Point = Point(3, 4)
+    c: Celsius = Celsius(21.5)
+    s: str = f"p={p} c={c}"
+    print(s)
+    return len(s)  # Expected: 24
+
+def test_fstring_tuple() -> int:
+    """Tuples are formatted with their repr"""
+    pair: tuple[int, str] = (1, "a")
+    s: str = f"pair={pair}"
+    print(s)
+    return len(s)  # Expected: 13
+
+def test_fstring_literals() -> int:
+    """Literal-only f-strings, escaped braces and empty f-strings"""
+    empty: str = f""
+    plain: str = f"plain"
+    braces: str = f"{{}} {len(plain)}"
+    print(braces)
+    return len(empty) * 100 + len(plain) * 10 + len(braces)  # Expected: 54
+
+def test_fstring_loop() -> int:
+    """F-strings build up strings inside loops"""
+    out: str = ""
+    for i in range(3):
+        out = out + f"[{i}:{i * i}]"
+    print(out)
+    return len(out)  # Expected: 15
+
+def main() -> int:
+    print(test_fstring_int())
+    print(test_fstring_float_bool())
+    print(test_fstring_str())
+    print(test_fstring_expressions())
+    print(test_fstring_objects())
+    print(test_fstring_tuple())
+    print(test_fstring_literals())
+    print(test_fstring_loop())
+    return 0
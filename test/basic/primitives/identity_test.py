@@ -0,0 +1,82 @@
+# is / is not tests
+
+class Node:
+    value: int
+
+    def __init__(self, value: int) -> None:
+        self.value = value
+
+    def __eq__(self, other: "Node") -> bool:
+        return self.value == other.value
+
+def same_node(a: Node, b: Node) -> bool:
+    return a is b
+
+def test_objects() -> int:
+    """Class instances are identical only when they are the same object"""
+    a: Node = Node(1)
+    b: Node = a
+    c: Node = Node(1)
+    count: int = 0
+    if a is b:
+        count += 1
+    if a is not c:
+        count += 1
+    if a == c:
+        count += 1
+    if same_node(c, c):
+        count += 1
+    print(same_node(a, c), same_node(b, a))
+    return count  # Expected: 4
+
+def test_containers() -> int:
+    """Lists and dicts compare by identity, not contents"""
+    xs: list[int] = [1, 2]
+    ys: list[int] = xs
+    zs: list[int] = [1, 2]
+    d: dict[str, int] = {"a": 1}
+    count: int = 0
+    if xs is ys:
+        count += 1
+    if xs is not zs:
+        count += 1
+    ys.append(3)
+    if len(xs) == 3 and d is d:
+        count += 1
+    return count  # Expected: 3
+
+def test_primitives() -> int:
+    """Small ints and bools compare by value"""
+    a: int = 7
+    b: int = 3 + 4
+    flag: bool = a > b
+    other: bool = False
+    count: int = 0
+    if a is b:
+        count += 1
+    if flag is other:
+        count += 1
+    if flag is not True:
+        count += 1
+    return count  # Expected: 3
+
+def test_none() -> int:
+    """Only None is None"""
+    n: int = 0
+    s: str = ""
+    count: int = 0
+    if None is None:
+        count += 1
+    if n is not None:
+        count += 1
+    if not (s is None):
+        count += 1
+    print(n is None, None is not None)
+    return count  # Expected: 3
+
+def main() -> int:
+    print(test_objects())
+    print(test_containers())
+    print(test_primitives())
+    print(test_none())
+    return 0
@@ -0,0 +1,59 @@
+# Negative indexing tests for list, str, bytes and bytearray
+
+def test_list_negative() -> int:
+    """xs[-i] reads, writes and deletes counting from the end"""
+    items: list[int] = [10, 20, 30, 40]
+    print(items[-1], items[-4])
+    items[-2] = 35
+    del items[-4]
+    print(items)
+    return items[-1] + items[-3]  # Expected: 60
+
+def test_str_negative() -> int:
+    """s[-i] counts codepoints from the end, for ASCII and non-ASCII strings"""
+    s: str = "hello"
+    u: str = "héllo wörld"
+    print(s[-1], s[-5], u[-4], u[-10])
+    return len(s[-2] + u[-1])  # Expected: 2
+
+def test_bytes_negative() -> int:
+    """b[-i] and bytearray[-i] index bytes from the end"""
+    data: bytes = b"abc"
+    buffer: bytearray = bytearray(b"xyz")
+    buffer[-1] = 65
+    print(data[-1], data[-3], buffer[-1], buffer[-3])
+    return data[-2] + buffer[-2]  # Expected: 219
+
+def test_out_of_range() -> int:
+    """Indices before the start raise IndexError"""
+    items: list[int] = [1, 2, 3]
+    s: str = "ab"
+    data: bytes = b"xy"
+    count: int = 0
+    try:
+        value: int = items[-4]
+    except IndexError as e:
+        print(e)
+        count += 1
+    try:
+        items[-4] = 0
+    except IndexError as e:
+        print(e)
+        count += 1
+    try:
+        c: str = s[-3]
+    except IndexError as e:
+        print(e)
+        count += 1
+    try:
+        byte: int = data[-3]
+    except IndexError:
+        count += 1
+    return count  # Expected: 4
+
+def main() -> int:
+    print(test_list_negative())
+    print(test_str_negative())
+    print(test_bytes_negative())
+    print(test_out_of_range())
+    return 0
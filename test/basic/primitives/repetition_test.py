@@ -0,0 +1,55 @@
+# String and list repetition tests
+
+def make_row(width: int) -> list[int]:
+    return [0] * width
+
+def test_str_repeat() -> int:
+    """str * int and int * str repeat the string"""
+    print("ab" * 3)
+    print(2 * "xy")
+    line: str = "-" * 10
+    print(line)
+    return len(line)  # Expected: 10
+
+def test_str_repeat_edge() -> int:
+    """Zero and negative counts give the empty string"""
+    empty: str = "abc" * 0
+    negative: str = "abc" * -2
+    print(len(empty), len(negative))
+    return len("" * 5) + len("é" * 3)  # Expected: 3
+
+def test_list_repeat() -> int:
+    """list * int builds a new list with the items repeated"""
+    row: list[int] = make_row(4)
+    row[1] = 7
+    print(row)
+    pattern: list[int] = [1, 2] * 3
+    print(pattern)
+    print(3 * ["a"])
+    return len(pattern) + row[1]  # Expected: 13
+
+def test_list_repeat_copies() -> int:
+    """The repeated list is independent of the original"""
+    base: list[int] = [5, 6]
+    twice: list[int] = base * 2
+    base[0] = 100
+    print(twice)
+    print(base * 0, len([9] * -1))
+    return twice[0] + twice[2]  # Expected: 10
+
+def test_aug_repeat() -> int:
+    """*= repeats a variable in place"""
+    word: str = "ha"
+    word *= 3
+    items: list[int] = [1]
+    items *= 4
+    print(word, items)
+    return len(word) + len(items)  # Expected: 10
+
+def main() -> int:
+    print(test_str_repeat())
+    print(test_str_repeat_edge())
+    print(test_list_repeat())
+    print(test_list_repeat_copies())
+    print(test_aug_repeat())
+    return 0
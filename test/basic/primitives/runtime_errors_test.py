@@ -0,0 +1,101 @@
+# ZeroDivisionError and IndexError raised by the runtime checks
+
+def divide(a: int, b: int) -> str:
+    result: str = ""
+    try:
+        result = str(a // b)
+    except ZeroDivisionError as e:
+        result = "error: " + str(e)
+    return result
+
+def modulo(a: int, b: int) -> str:
+    result: str = ""
+    try:
+        result = str(a % b)
+    except ZeroDivisionError as e:
+        result = "error: " + str(e)
+    return result
+
+def true_divide(a: int, b: int) -> str:
+    result: str = ""
+    try:
+        result = str(a / b)
+    except ZeroDivisionError as e:
+        result = "error: " + str(e)
+    return result
+
+def test_zero_division() -> int:
+    """Integer and float division by zero raise instead of trapping"""
+    print(divide(7, 2), divide(7, 0))
+    print(modulo(7, 2), modulo(7, 0))
+    print(true_divide(1, 4), true_divide(1, 0))
+    count: int = 0
+    zero: int = 0
+    try:
+        y: float = 2.5 // zero
+    except ArithmeticError as e:
+        print(str(e))
+        count += 1
+    try:
+        x: int = 5
+        x //= zero
+    except ZeroDivisionError:
+        count += 1
+    return count  # Expected: 2
+
+def list_item(xs: list[int], i: int) -> str:
+    result: str = ""
+    try:
+        result = str(xs[i])
+    except IndexError as e:
+        result = "error: " + str(e)
+    return result
+
+def test_list_index() -> int:
+    """Reading, writing and deleting past the end of a list raise IndexError"""
+    xs: list[int] = [10, 20, 30]
+    print(list_item(xs, 2), list_item(xs, 3))
+    count: int = 0
+    try:
+        xs[3] = 40
+    except IndexError as e:
+        print(str(e))
+        count += 1
+    try:
+        del xs[7]
+    except LookupError:
+        count += 1
+    return count + len(xs)  # Expected: 5
+
+def test_other_sequences() -> int:
+    """str, bytes and bytearray subscripts are checked too"""
+    count: int = 0
+    s: str = "abc"
+    data: bytes = b"abc"
+    buffer: bytearray = bytearray(b"abc")
+    try:
+        s[3]
+    except IndexError as e:
+        print(str(e))
+        count += 1
+    try:
+        data[3]
+    except IndexError as e:
+        print(str(e))
+        count += 1
+    try:
+        buffer[3]
+    except IndexError as e:
+        print(str(e))
+        count += 1
+    try:
+        buffer[5] = 1
+    except IndexError:
+        count += 1
+    return count  # Expected: 4
+
+def main() -> int:
+    print(test_zero_division())
+    print(test_list_index())
+    print(test_other_sequences())
+    return 0
@@ -0,0 +1,102 @@
+# Slicing tests: s[start:stop:step] on str, list, bytes and bytearray
+
+def test_str_slice() -> int:
+    """Basic str slices with explicit and missing bounds"""
+    s: str = "hello world"
+    print(s[1:5])
+    print(s[:5])
+    print(s[6:])
+    print(s[:])
+    return len(s[2:9])  # Expected: 7
+
+def test_str_negative() -> int:
+    """Negative bounds count from the end"""
+    s: str = "hello world"
+    print(s[-5:])
+    print(s[:-6])
+    print(s[-3:-1])
+    return len(s[-100:100])  # Expected: 11
+
+def test_str_step() -> int:
+    """Steps, including negative steps that reverse"""
+    s: str = "abcdefgh"
+    print(s[::2])
+    print(s[1::3])
+    print(s[::-1])
+    print(s[6:1:-2])
+    return len(s[::-3])  # Expected: 3
+
+def test_str_unicode_slice() -> int:
+    """Slices index codepoints, not bytes"""
+    s: str = "héllo wörld"
+    print(s[1:8])
+    print(s[::-1])
+    return len(s[7:])  # Expected: 4
+
+def test_str_empty_slice() -> int:
+    """Out-of-order and out-of-range bounds give empty results"""
+    s: str = "abc"
+    empty: str = s[2:1]
+    far: str = s[10:20]
+    return len(empty) + len(far)  # Expected: 0
+
+def test_slice_variables() -> int:
+    """Bounds can be arbitrary int expressions"""
+    s: str = "0123456789"
+    i: int = 2
+    j: int = 7
+    total: int = 0
+    for k in range(1, 4):
+        total = total + len(s[i:j:k])
+    print(s[i + 1:j - 1])
+    return total  # Expected: 10
+
+def test_list_slice() -> int:
+    """List slices copy the selected elements into a new list"""
+    nums: list[int] = [10, 20, 30, 40, 50, 60]
+    print(nums[1:4])
+    print(nums[::-2])
+    head: list[int] = nums[:2]
+    head.append(99)
+    print(head)
+    print(nums)
+    return len(nums[-3:])  # Expected: 3
+
+def test_list_str_slice() -> int:
+    """Slices of lists of strings keep the element type"""
+    words: list[str] = ["a", "bb", "ccc", "dddd"]
+    total: int = 0
+    for w in words[1:]:
+        total = total + len(w)
+    return total  # Expected: 9
+
+def test_bytes_slice() -> int:
+    """Bytes and bytearray slices"""
+    data: bytes = b"abcdef"
+    print(data[1:-1])
+    print(data[::2])
+    buf: bytearray = bytearray()
+    for k in range(6):
+        buf.append(65 + k)
+    print(buf[2:])
+    print(buf[::-1])
+    return len(data[3:]) + len(buf[:4])  # Expected: 7
+
+def test_chained_slice() -> int:
+    """Slicing the result of a slice"""
+    s: str = "abcdefghij"
+    print(s[2:8][::-1][1:3])
+    return len(s[1:][1:][1:])  # Expected: 7
+
+def main() -> int:
+    print(test_str_slice())
+    print(test_str_negative())
+    print(test_str_step())
+    print(test_str_unicode_slice())
+    print(test_str_empty_slice())
+    print(test_slice_variables())
+    print(test_list_slice())
+    print(test_list_str_slice())
+    print(test_bytes_slice())
+    print(test_chained_slice())
+    return 0
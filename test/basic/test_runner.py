@@ -16,6 +16,18 @@ from basic.classes.complex_types import test_list_element_modify, test_deep_nest
 from basic.classes.string_repr import test_str_only, test_repr_only, test_both_str_and_repr
 from basic.classes.string_repr import test_str_with_internal_print, test_repr_with_internal_print
 from basic.classes.string_repr import test_nested_with_str, test_multiple_instances, test_str_in_expression
+from basic.classes.dataclass_test import main as dataclass_main
+from basic.classes.staticmethod_test import main as staticmethod_main
+from basic.classes.property_test import main as property_main
+from basic.classes.operator_test import main as operator_main
+from basic.classes.callable_test import main as callable_main
+from basic.classes.optional_test import main as optional_main
+from basic.classes.generic_test import main as generic_main
+from basic.classes.protocol_test import main as protocol_main
+from basic.classes.abstract_test import main as abstract_main
+from basic.classes.stack_alloc_test import main as stack_alloc_main
+from basic.classes.init_fields_test import main as init_fields_main
+from basic.primitives.constant_fold_test import main as constant_fold_main
 from datastructure.hashmap import test_hashmap_basic, test_hashmap_update, test_hashmap_contains
 from datastructure.hashset import test_hashset_basic, test_hashset_contains
 from datastructure.bst import test_bst_insert, test_bst_contains
@@ -61,6 +73,42 @@ from basic.primitives.float_test import test_float_truthy, test_float_falsy
 from basic.primitives.float_test import test_float_floordiv, test_float_mod, test_float_pow, test_print_float
 from basic.primitives.str_methods_test import main as str_methods_main
 from basic.primitives.str_unicode_test import main as str_unicode_main
+from basic.primitives.fstring_test import main as fstring_main
+from basic.primitives.slice_test import main as slice_main
+from basic.primitives.repetition_test import main as repetition_main
+from basic.primitives.encode_test import main as encode_main
+from basic.primitives.bytes_methods_test import main as bytes_methods_main
+from basic.primitives.bytearray_methods_test import main as bytearray_methods_main
+from basic.primitives.floor_division_test import main as floor_division_main
+from basic.primitives.conversion_test import main as conversion_main
+from basic.primitives.runtime_errors_test import main as runtime_errors_main
+from basic.primitives.negative_index_test import main as negative_index_main
+from basic.control_flow.default_args_test import main as default_args_main
+from basic.control_flow.nested_functions_test import main as nested_functions_main
+from basic.control_flow.global_nonlocal_test import main as global_nonlocal_main
+from basic.control_flow.loop_else_test import main as loop_else_main
+from basic.control_flow.range_loop_test import main as range_loop_main
+from basic.collections.dict_test import main as dict_main
+from basic.collections.set_test import main as set_main
+from basic.collections.tuple_test import main as tuple_main
+from basic.collections.starred_test import main as starred_main
+from basic.collections.del_test import main as del_main
+from basic.collections.sort_test import main as sort_main
+from basic.collections.list_methods_test import main as list_methods_main
+from basic.collections.typed_list_test import main as typed_list_main
+from basic.collections.membership_test import main as membership_main
+from basic.collections.repr_test import main as repr_main
+from basic.primitives.identity_test import main as identity_main
+from basic.collections.typing_alias_test import main as typing_alias_main
+from basic.collections.comprehension_test import main as comprehension_main
+from basic.iterators.generator_test import main as generator_main
+from basic.iterators.map_filter_test import main as map_filter_main
+from basic.files.file_test import main as file_main
+from basic.modules.sys_test import main as sys_main
+from basic.modules.os_test import main as os_main
+from basic.modules.math_test import main as math_main
+from basic.modules.time_test import main as time_main
+from basic.modules.random_test import main as random_main
 
 def test() -> int:
     # Basic function tests
@@ -146,6 +194,42 @@ def test() -> int:
     print(test_multiple_instances()) # 2 (prints two Point instances)
     print(test_str_in_expression())  # 1 (prints Point and field value)
 
+    # @dataclass tests
+    print(dataclass_main())                  # 0
+
+    # @staticmethod and @classmethod tests
+    print(staticmethod_main())               # 0
+
+    # @property tests
+    print(property_main())                   # 0
+
+    # Operator overloading tests
+    print(operator_main())                   # 0
+
+    # __call__ tests
+    print(callable_main())                   # 0
+
+    # Optional type tests
+    print(optional_main())                   # 0
+
+    # Generic class tests
+    print(generic_main())                    # 0
+
+    # Protocol tests
+    print(protocol_main())                   # 0
+
+    # Abstract method tests
+    print(abstract_main())                   # 0
+
+    # Stack allocation of values that do not escape
+    print(stack_alloc_main())                # 0
+
+    # Fields inferred from __init__
+    print(init_fields_main())                # 0
+
+    # Constant expressions, dead branches and unused locals
+    print(constant_fold_main())              # 0
+
     # Data structure tests - HashMap
     print(test_hashmap_basic())      # 200
     print(test_hashmap_update())     # 999
@@ -338,4 +422,112 @@ def test() -> int:
     # String methods tests (Phase 3: Unicode support)
     print(str_methods_main())                # 0 (all 34 tests pass)
     print(str_unicode_main())                # 0 (all 15 tests pass)
+
+    # Dict tests
+    print(dict_main())                       # 0
+
+    # Set tests
+    print(set_main())                        # 0
+
+    # Tuple tests
+    print(tuple_main())                      # 0
+
+    # Starred unpacking tests
+    print(starred_main())                    # 0
+
+    # del statement tests
+    print(del_main())                        # 0
+
+    # sorted() / list.sort() tests
+    print(sort_main())                       # 0
+
+    # list.pop() / insert() / remove() / index() / count() / extend() tests
+    print(list_methods_main())               # 0
+
+    # list[float] / list[bool] tests
+    print(typed_list_main())                 # 0
+
+    # in / not in tests
+    print(membership_main())                 # 0
+
+    # Nested container repr tests
+    print(repr_main())                       # 0
+
+    # is / is not tests
+    print(identity_main())                   # 0
+
+    # typing.List / typing.Dict / ... annotation tests
+    print(typing_alias_main())               # 0
+
+    # Comprehension tests
+    print(comprehension_main())              # 0
+
+    # Generator tests
+    print(generator_main())                  # 0
+
+    # map() / filter() tests
+    print(map_filter_main())                 # 0
+
+    # open() / file tests
+    print(file_main())                       # 0
+
+    # sys module tests
+    print(sys_main())                        # 0
+
+    # os module tests
+    print(os_main())                         # 0
+
+    # math module tests
+    print(math_main())                       # 0
+
+    # time module tests
+    print(time_main())                       # 0
+
+    # random module tests
+    print(random_main())                     # 0
+
+    # F-string tests
+    print(fstring_main())                    # 0
+
+    # Slicing tests
+    print(slice_main())                      # 0
+
+    # String and list repetition tests
+    print(repetition_main())                 # 0
+
+    # str.encode() / bytes.decode() tests
+    print(encode_main())                     # 0
+
+    # bytes method tests
+    print(bytes_methods_main())              # 0
+
+    # bytearray method tests
+    print(bytearray_methods_main())          # 0
+
+    # Floor division, modulo and divmod() tests
+    print(floor_division_main())             # 0
+
+    # Conversion builtin tests
+    print(conversion_main())                 # 0
+
+    # ZeroDivisionError / IndexError tests
+    print(runtime_errors_main())             # 0
+
+    # Negative index tests
+    print(negative_index_main())             # 0
+
+    # Default argument tests
+    print(default_args_main())               # 0
+
+    # Nested function tests
+    print(nested_functions_main())           # 0
+
+    # global / nonlocal tests
+    print(global_nonlocal_main())            # 0
+
+    # while-else / for-else tests
+    print(loop_else_main())                  # 0
+
+    # for loops over range()
+    print(range_loop_main())                 # 0
     return 0
@@ -0,0 +1,132 @@
+# Builtin exception hierarchy tests - except clauses naming builtin classes
+
+class ConfigError(ValueError):
+    code: int
+
+class MissingKey(KeyError):
+    code: int
+
+def test_value_error_not_caught_as_type_error() -> int:
+    """A handler for another builtin class does not catch the exception"""
+    try:
+        raise ValueError("bad value")
+    except TypeError:
+        print(0)
+    except ValueError as e:
+        print(str(e))
+    print(2)
+    return 0
+
+def test_lookup_error_catches_key_error() -> int:
+    """KeyError from a dict lookup is a LookupError"""
+    d: dict[str, int] = {"a": 1}
+    try:
+        print(d["b"])
+    except IndexError:
+        print(0)
+    except LookupError:
+        print(1)
+    print(2)
+    return 0
+
+def test_lookup_error_catches_index_error() -> int:
+    """IndexError from a list subscript is a LookupError"""
+    items: list[int] = [1, 2, 3]
+    try:
+        print(items[5])
+    except KeyError:
+        print(0)
+    except LookupError:
+        print(1)
+    print(2)
+    return 0
+
+def test_arithmetic_error_catches_zero_division() -> int:
+    """ZeroDivisionError is an ArithmeticError"""
+    zero: int = 0
+    try:
+        print(10 // zero)
+    except ArithmeticError:
+        print(1)
+    print(2)
+    return 0
+
+def test_user_subclass_of_builtin() -> int:
+    """A class deriving from ValueError is caught as ValueError"""
+    try:
+        raise ConfigError("missing option")
+    except LookupError:
+        print(0)
+    except ValueError as e:
+        print(str(e))
+    print(2)
+    return 0
+
+def test_user_subclass_of_key_error() -> int:
+    """A class deriving from KeyError is caught as LookupError"""
+    try:
+        raise MissingKey("name")
+    except ValueError:
+        print(0)
+    except LookupError:
+        print(1)
+    print(2)
+    return 0
+
+def test_raise_builtin_classes() -> int:
+    """Raising builtin classes directly"""
+    try:
+        raise NotImplementedError("later")
+    except RuntimeError as e:
+        print(str(e))
+    try:
+        raise TypeError("wrong type")
+    except RuntimeError:
+        print(0)
+    except TypeError as e:
+        print(str(e))
+    try:
+        raise FileNotFoundError("no such file")
+    except OSError as e:
+        print(str(e))
+    print(2)
+    return 0
+
+def test_exception_catches_builtin() -> int:
+    """Exception catches every builtin exception"""
+    try:
+        raise AttributeError("no attribute")
+    except Exception as e:
+        print(str(e))
+    print(2)
+    return 0
+
+def test() -> int:
+    print("=== Builtin Exception Types Tests ===")
+
+    print("Test: ValueError not caught as TypeError")
+    test_value_error_not_caught_as_type_error()
+
+    print("Test: LookupError catches KeyError")
+    test_lookup_error_catches_key_error()
+
+    print("Test: LookupError catches IndexError")
+    test_lookup_error_catches_index_error()
+
+    print("Test: ArithmeticError catches ZeroDivisionError")
+    test_arithmetic_error_catches_zero_division()
+
+    print("Test: user subclass of ValueError")
+    test_user_subclass_of_builtin()
+
+    print("Test: user subclass of KeyError")
+    test_user_subclass_of_key_error()
+
+    print("Test: raise builtin classes")
+    test_raise_builtin_classes()
+
+    print("Test: Exception catches builtins")
+    test_exception_catches_builtin()
+
+    print("=== Builtin Exception Types Tests Complete ===")
+    return 0
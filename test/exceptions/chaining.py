@@ -0,0 +1,209 @@
+# Exception chaining tests - __cause__, __context__ and nested try blocks
+
+from typing import Optional
+
+class ChainError(Exception):
+    code: int
+
+def describe(exc: Optional[Exception]) -> str:
+    if exc is None:
+        return "None"
+    return str(exc)
+
+def test_raise_from_sets_cause() -> int:
+    """raise ... from sets __cause__; __context__ is still recorded"""
+    try:
+        try:
+            raise ValueError("bad value")
+        except ValueError as inner:
+            raise ChainError("wrapped") from inner
+    except ChainError as e:
+        print(describe(e.__cause__))
+        print(describe(e.__context__))
+    return 0
+
+def test_raise_from_none() -> int:
+    """raise ... from None leaves __cause__ unset"""
+    try:
+        try:
+            raise ValueError("hidden")
+        except ValueError:
+            raise ChainError("shown") from None
+    except ChainError as e:
+        print(describe(e.__cause__))
+        print(describe(e.__context__))
+    return 0
+
+def test_implicit_context() -> int:
+    """An exception raised in a handler takes the handled one as __context__"""
+    try:
+        try:
+            raise ValueError("first")
+        except ValueError:
+            raise ChainError("second")
+    except ChainError as e:
+        print(describe(e.__cause__))
+        print(describe(e.__context__))
+    return 0
+
+def test_no_context_outside_handler() -> int:
+    """An exception raised outside of a handler has no __context__"""
+    try:
+        raise ChainError("alone")
+    except ChainError as e:
+        print(describe(e.__context__))
+    return 0
+
+def test_context_after_nested_try() -> int:
+    """A nested try block in a handler does not change the handled exception"""
+    try:
+        try:
+            raise ValueError("outer")
+        except ValueError:
+            try:
+                raise TypeError("inner")
+            except TypeError:
+                print(1)
+            raise ChainError("after")
+    except ChainError as e:
+        print(describe(e.__context__))
+    return 0
+
+def test_bare_raise_after_nested_try() -> int:
+    """A bare raise re-raises the exception its handler caught"""
+    try:
+        try:
+            raise ValueError("first")
+        except ValueError:
+            try:
+                raise TypeError("inner")
+            except TypeError:
+                print(1)
+            raise
+    except TypeError:
+        print(0)
+    except ValueError as e:
+        print(str(e))
+    return 0
+
+def swallow() -> int:
+    try:
+        raise TypeError("swallowed")
+    except TypeError:
+        return 1
+    return 0
+
+def test_bare_raise_after_return_from_handler() -> int:
+    """A function returning from its own handler leaves the caller's in place"""
+    try:
+        try:
+            raise ValueError("active")
+        except ValueError:
+            print(swallow())
+            raise
+    except ValueError as e:
+        print(str(e))
+    return 0
+
+def test_raise_in_else_runs_finally() -> int:
+    """A raise in the else block skips the handlers but runs finally"""
+    x: int = 1
+    try:
+        try:
+            print(1)
+        except ChainError:
+            print(0)
+        else:
+            if x > 0:
+                raise ChainError("from else")
+            print(0)
+        finally:
+            print(2)
+    except ChainError as e:
+        print(str(e))
+    return 0
+
+def test_nested_try_in_finally() -> int:
+    """A try block in finally does not catch the pending exception"""
+    try:
+        try:
+            raise ValueError("pending")
+        finally:
+            try:
+                print(1)
+            except ValueError:
+                print(0)
+            print(2)
+    except ValueError as e:
+        print(str(e))
+    return 0
+
+def test_raise_in_finally_replaces_pending() -> int:
+    """An exception raised in finally replaces the pending one"""
+    try:
+        try:
+            raise ValueError("first")
+        finally:
+            raise ChainError("second")
+    except ValueError:
+        print(0)
+    except ChainError as e:
+        print(str(e))
+        print(describe(e.__context__))
+    return 0
+
+def test_finally_order() -> int:
+    """Inner finally, outer handler, then outer finally"""
+    try:
+        try:
+            try:
+                raise ChainError("deep")
+            finally:
+                print(1)
+        finally:
+            print(2)
+    except ChainError:
+        print(3)
+    finally:
+        print(4)
+    print(5)
+    return 0
+
+def test() -> int:
+    print("=== Exception Chaining Tests ===")
+
+    print("Test: raise from sets __cause__")
+    test_raise_from_sets_cause()
+
+    print("Test: raise from None")
+    test_raise_from_none()
+
+    print("Test: implicit __context__")
+    test_implicit_context()
+
+    print("Test: no __context__ outside handler")
+    test_no_context_outside_handler()
+
+    print("Test: __context__ after nested try")
+    test_context_after_nested_try()
+
+    print("Test: bare raise after nested try")
+    test_bare_raise_after_nested_try()
+
+    print("Test: bare raise after return from handler")
+    test_bare_raise_after_return_from_handler()
+
+    print("Test: raise in else runs finally")
+    test_raise_in_else_runs_finally()
+
+    print("Test: nested try in finally")
+    test_nested_try_in_finally()
+
+    print("Test: raise in finally replaces pending")
+    test_raise_in_finally_replaces_pending()
+
+    print("Test: finally order")
+    test_finally_order()
+
+    print("=== Exception Chaining Tests Complete ===")
+    return 0
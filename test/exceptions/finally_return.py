@@ -0,0 +1,172 @@
+# Finally clause tests - a return leaving a try block runs its finally block
+
+class ReturnError(Exception):
+    code: int
+
+class Box:
+    value: int
+
+    def __init__(self, value: int) -> None:
+        self.value = value
+
+def return_in_try() -> int:
+    try:
+        print(1)
+        return 10
+    finally:
+        print(2)
+    return 0
+
+def return_in_except() -> int:
+    try:
+        raise ReturnError("caught")
+    except ReturnError:
+        print(1)
+        return 20
+    finally:
+        print(2)
+    return 0
+
+def return_in_else() -> int:
+    try:
+        print(1)
+    except ReturnError:
+        print(0)
+    else:
+        return 30
+    finally:
+        print(2)
+    return 0
+
+def return_through_nested() -> int:
+    try:
+        try:
+            try:
+                return 40
+            finally:
+                print(1)
+        except ReturnError:
+            print(0)
+        print(0)
+    finally:
+        print(2)
+    return 0
+
+def return_value_before_finally() -> int:
+    n: int = 1
+    try:
+        return n
+    finally:
+        n = 5
+        print(n)
+    return 0
+
+def return_instance() -> Box:
+    box: Box = Box(1)
+    try:
+        return box
+    finally:
+        box = Box(2)
+        print(box.value)
+    return box
+
+def return_from_loop(items: list[int]) -> int:
+    try:
+        for item in items:
+            if item > 2:
+                return item
+    finally:
+        print(1)
+    return 0
+
+def raise_in_finally() -> int:
+    try:
+        return 50
+    finally:
+        raise ReturnError("replaces the return")
+    return 0
+
+def return_none(flag: bool) -> None:
+    try:
+        if flag:
+            return
+        print(0)
+    finally:
+        print(1)
+
+def test_return_in_try() -> int:
+    """Finally runs before a return in the try body"""
+    print(return_in_try())
+    return 0
+
+def test_return_in_except() -> int:
+    """Finally runs before a return in a handler"""
+    print(return_in_except())
+    return 0
+
+def test_return_in_else() -> int:
+    """Finally runs before a return in the else block"""
+    print(return_in_else())
+    return 0
+
+def test_return_through_nested() -> int:
+    """Every finally block a return leaves runs, innermost first"""
+    print(return_through_nested())
+    return 0
+
+def test_return_value_before_finally() -> int:
+    """The returned value is computed before finally runs"""
+    print(return_value_before_finally())
+    print(return_instance().value)
+    return 0
+
+def test_return_from_loop() -> int:
+    """A return in a loop runs the finally block around the loop"""
+    print(return_from_loop([1, 2, 3, 4]))
+    print(return_from_loop([1]))
+    return 0
+
+def test_raise_in_finally() -> int:
+    """An exception raised in finally replaces the return"""
+    try:
+        value: int = raise_in_finally()
+        print(value)
+    except ReturnError as e:
+        print(str(e))
+    return 0
+
+def test_return_none() -> int:
+    """A bare return runs finally too"""
+    return_none(True)
+    return_none(False)
+    return 0
+
+def test() -> int:
+    print("=== Finally Return Tests ===")
+
+    print("Test: return in try")
+    test_return_in_try()
+
+    print("Test: return in except")
+    test_return_in_except()
+
+    print("Test: return in else")
+    test_return_in_else()
+
+    print("Test: return through nested")
+    test_return_through_nested()
+
+    print("Test: return value before finally")
+    test_return_value_before_finally()
+
+    print("Test: return from loop")
+    test_return_from_loop()
+
+    print("Test: raise in finally")
+    test_raise_in_finally()
+
+    print("Test: return None")
+    test_return_none()
+
+    print("=== Finally Return Tests Complete ===")
+    return 0
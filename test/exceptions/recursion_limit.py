@@ -0,0 +1,56 @@
+# --recursion-limit test program: runaway recursion raises RecursionError
+# instead of overflowing the stack, a try block catches it, and the depth is
+# back where it was once it is caught
+
+def runaway(n: int) -> int:
+    return runaway(n + 1) + 1
+
+def branching(n: int) -> int:
+    return branching(n + 1) + branching(n + 2)
+
+def ping(n: int) -> int:
+    return pong(n + 1)
+
+def pong(n: int) -> int:
+    return ping(n + 1)
+
+def count_down(n: int) -> int:
+    if n == 0:
+        return 0
+    return count_down(n - 1) + 1
+
+def catch_runaway() -> str:
+    try:
+        result: int = runaway(0)
+        return "not raised " + str(result)
+    except RecursionError as e:
+        return "RecursionError: " + str(e)
+
+def catch_branching() -> str:
+    try:
+        result: int = branching(0)
+        return "not raised " + str(result)
+    except RuntimeError:
+        return "caught as RuntimeError"
+
+def catch_mutual() -> str:
+    log: str = ""
+    try:
+        result: int = ping(0)
+        log = "not raised " + str(result)
+    except RecursionError:
+        log = "mutual"
+    finally:
+        log = log + " finally"
+    return log
+
+def main() -> int:
+    print(catch_runaway())
+    print(catch_branching())
+    print(catch_mutual())
+    # Deep, but within the limit, once the runaway calls are gone
+    print(count_down(90))
+    return 0
+
+main()
+runaway(0)
@@ -0,0 +1,96 @@
+# --exceptions=setjmp test program: a raise leaves the functions between it
+# and its handler at once, and try blocks in loops, handlers, else blocks and
+# early returns keep the frame stack straight
+
+class Node:
+    value: int
+
+    def __init__(self, value: int) -> None:
+        self.value = value
+
+def fail(message: str) -> int:
+    raise ValueError(message)
+    print("unreachable: after raise")
+    return 0
+
+def middle(n: int) -> int:
+    node: Node = Node(n)
+    result: int = fail("from middle " + str(node.value))
+    print("unreachable: after failing call")
+    return result
+
+def locals_survive() -> str:
+    count: int = 1
+    name: str = "start"
+    try:
+        count = count + 10
+        name = "changed"
+        middle(count)
+        count = 1000
+    except ValueError as e:
+        return name + " " + str(count) + " " + str(e)
+    return "not raised"
+
+def early_return(flag: bool) -> int:
+    try:
+        if flag:
+            return 1
+    except ValueError:
+        return 2
+    return 3
+
+def handler_raises() -> str:
+    log: str = ""
+    try:
+        try:
+            fail("first")
+        except ValueError:
+            log = log + "handled "
+            fail("second")
+        finally:
+            log = log + "finally "
+    except ValueError as e:
+        log = log + "outer " + str(e)
+    return log
+
+def else_raises() -> str:
+    log: str = ""
+    try:
+        try:
+            log = log + "body "
+        except ValueError:
+            log = log + "wrong "
+        else:
+            fail("from else")
+    except ValueError as e:
+        log = log + "outer " + str(e)
+    return log
+
+def in_loop(n: int) -> int:
+    caught: int = 0
+    total: int = 0
+    i: int = 0
+    while i < n:
+        try:
+            if i % 3 == 0:
+                middle(i)
+            total += i
+        except ValueError:
+            caught += 1
+        i += 1
+    return caught * 1000000 + total
+
+def main() -> int:
+    print(locals_survive())
+    print(early_return(True), early_return(False))
+    # The frame early_return(True) returned from is gone
+    try:
+        fail("after early return")
+    except ValueError as e:
+        print(e)
+    print(handler_raises())
+    print(else_raises())
+    print(in_loop(30000))
+    return 0
+
+main()
@@ -4,8 +4,12 @@
 from . import nested_try
 from . import exception_reraise
 from . import finally_always
+from . import finally_return
 from . import except_types
+from . import builtin_types
+from . import chaining
 from . import raise_from_except
+from . import with_statement
 
 # Custom exception classes for testing
 class MyError(Exception):
@@ -199,8 +203,12 @@ def test() -> int:
     nested_try.test()
     exception_reraise.test()
     finally_always.test()
+    finally_return.test()
     except_types.test()
+    builtin_types.test()
+    chaining.test()
     raise_from_except.test()
+    with_statement.test()
 
     print("=== Exception Tests Complete ===")
     return 0
@@ -0,0 +1,175 @@
+# With statement tests - __enter__/__exit__ around a block
+
+class WithError(Exception):
+    code: int
+
+class Tracer:
+    name: str
+
+    def __init__(self, name: str) -> None:
+        self.name = name
+
+    def __enter__(self) -> "Tracer":
+        print("enter " + self.name)
+        return self
+
+    def __exit__(self, exc_type, exc_value, traceback) -> None:
+        print("exit " + self.name)
+
+class Counter:
+    total: int
+    exits: int
+
+    def __init__(self) -> None:
+        self.total = 0
+        self.exits = 0
+
+    def __enter__(self) -> int:
+        return 10
+
+    def __exit__(self, exc_type, exc_value, traceback) -> None:
+        self.exits = self.exits + 1
+
+class Suppress:
+    suppressed: int
+
+    def __init__(self) -> None:
+        self.suppressed = 0
+
+    def __enter__(self) -> "Suppress":
+        return self
+
+    def __exit__(self, exc_type: Exception, exc_value: Exception, traceback: Exception) -> bool:
+        self.suppressed = self.suppressed + 1
+        return True
+
+class NoSuppress:
+    def __enter__(self) -> "NoSuppress":
+        return self
+
+    def __exit__(self, exc_type: Exception, exc_value: Exception, traceback: Exception) -> bool:
+        print("exit without suppressing")
+        return False
+
+def test_with_basic() -> int:
+    """__enter__ runs before the body and __exit__ after it"""
+    with Tracer("a"):
+        print("body")
+    print("after")
+    return 0
+
+def test_with_as() -> int:
+    """The as target is bound to the result of __enter__"""
+    with Tracer("b") as t:
+        print(t.name)
+    counter: Counter = Counter()
+    with counter as start:
+        counter.total = start + 5
+    print(counter.total)
+    print(counter.exits)
+    return 0
+
+def test_with_exception_propagates() -> int:
+    """__exit__ runs when the body raises, then the exception propagates"""
+    try:
+        with Tracer("c"):
+            print(1)
+            raise WithError("boom")
+        print(0)
+    except WithError:
+        print(2)
+    print(3)
+    return 0
+
+def test_with_suppress() -> int:
+    """An __exit__ returning True swallows the exception"""
+    s: Suppress = Suppress()
+    with s:
+        print(1)
+        raise WithError("swallowed")
+    print(2)
+    with s:
+        print(3)
+    print(s.suppressed)
+    return 0
+
+def test_with_no_suppress() -> int:
+    """An __exit__ returning False lets the exception through"""
+    try:
+        with NoSuppress():
+            raise WithError("kept")
+    except WithError:
+        print(1)
+    return 0
+
+def test_with_multiple() -> int:
+    """Several managers are exited in reverse order"""
+    with Tracer("outer"), Tracer("inner") as inner:
+        print(inner.name)
+    return 0
+
+def test_with_nested_exception() -> int:
+    """An exception unwinds through every enclosing manager"""
+    try:
+        with Tracer("x"):
+            with Tracer("y"):
+                raise WithError("deep")
+    except WithError:
+        print(1)
+    return 0
+
+def test_with_in_loop() -> int:
+    """Each iteration enters and exits the manager once"""
+    counter: Counter = Counter()
+    for i in range(3):
+        with counter:
+            counter.total = counter.total + i
+    print(counter.total)
+    print(counter.exits)
+    return 0
+
+def find_in_with(items: list[int], target: int) -> int:
+    with Tracer("find"):
+        for item in items:
+            if item == target:
+                return item
+    return -1
+
+def test_with_return() -> int:
+    """A return in the body exits the managers first"""
+    print(find_in_with([1, 2, 3], 2))
+    print(find_in_with([1, 2, 3], 5))
+    return 0
+
+def test() -> int:
+    print("=== With Statement Tests ===")
+
+    print("Test: with basic")
+    test_with_basic()
+
+    print("Test: with as")
+    test_with_as()
+
+    print("Test: with exception propagates")
+    test_with_exception_propagates()
+
+    print("Test: with suppress")
+    test_with_suppress()
+
+    print("Test: with no suppress")
+    test_with_no_suppress()
+
+    print("Test: with multiple")
+    test_with_multiple()
+
+    print("Test: with nested exception")
+    test_with_nested_exception()
+
+    print("Test: with in loop")
+    test_with_in_loop()
+
+    print("Test: with return")
+    test_with_return()
+
+    print("=== With Statement Tests Complete ===")
+    return 0
@@ -0,0 +1,20 @@
+# A star import of this module only imports the names __all__ lists
+__all__ = ["perimeter", "Rect", "UNIT", "double"]
+
+from imports.math_utils import double
+
+UNIT: int = 1
+
+def perimeter(width: int, height: int) -> int:
+    return double(width + height)
+
+def box_area(width: int, height: int) -> int:
+    return width * height
+
+class Rect:
+    def __init__(self, width: int, height: int) -> None:
+        self.width = width
+        self.height = height
+
+    def area(self) -> int:
+        return box_area(self.width, self.height)
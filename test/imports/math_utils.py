@@ -3,6 +3,11 @@ PI_APPROX: int = 3
 MAX_VALUE: int = 999
 counter: int = 50
 
+# Typed module constants
+RATE = 2.5
+GREETING = "hello"
+PRIMES = [2, 3, 5, 7]
+
 def square(n: int) -> int:
     return n * n
 
@@ -11,3 +16,6 @@ def double(n: int) -> int:
 
 def get_max() -> int:
     return MAX_VALUE
+
+def scaled(n: int) -> float:
+    return n * RATE
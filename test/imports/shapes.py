@@ -0,0 +1,10 @@
+# Names imported here can be imported from this module in turn
+from imports.math_utils import square as area_of_square, MAX_VALUE
+from . import helper
+
+class Square:
+    def __init__(self, side: int) -> None:
+        self.side = side
+
+    def area(self) -> int:
+        return area_of_square(self.side)
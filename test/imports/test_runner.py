@@ -1,5 +1,6 @@
 # Import tests module
 from imports.helper import *
+from imports.geometry import *
 from imports.math_utils import square, double as double_value
 from imports.deep.level1.level2.level3.core import test_relative_imports
 from .deep.level1.sibling import get_sibling_sum
@@ -11,6 +12,9 @@ from . import helper, math_utils as math_utils_alias
 from imports.math_utils import PI_APPROX, MAX_VALUE as MAX_VAL, counter
 from .helper import helper_value
 
+# Test importing a class, and names a module imports itself
+from imports.shapes import Square, area_of_square, MAX_VALUE as SHAPES_MAX, helper as shapes_helper
+
 def test() -> int:
     # Test imported functions
     print(add(3, 4))       # 7
@@ -46,4 +50,22 @@ def test() -> int:
     print(helper.get_helper_constant())    # 42
     print(math_utils_alias.get_max())      # 999
 
+    # Test module globals of types other than int
+    print(math_utils_alias.RATE)           # 2.5
+    print(math_utils_alias.GREETING)       # hello
+    print(math_utils_alias.PRIMES[2])      # 5
+    print(math_utils_alias.scaled(2))      # 5.0
+
+    # Test from-imported class and re-exported names
+    print(Square(4).area())          # 16
+    print(area_of_square(7))         # 49
+    print(SHAPES_MAX)                # 999
+    print(shapes_helper.add(1, 2))   # 3
+
+    # Test star import of the names __all__ lists, a re-exported one included
+    print(perimeter(2, 3))           # 10
+    print(Rect(2, 3).area())         # 6
+    print(UNIT)                      # 1
+    print(double(4))                 # 8
+
     return 0
@@ -16,6 +16,9 @@ from inheritance.complex_inherit import test_list_of_derived
 from inheritance.complex_inherit import test_derived_uses_parent_method
 from inheritance.complex_inherit import test_modify_inherited_field
 from inheritance.super_method_call import test_super_method_call, test_super_paramless_method, test_super_preserves_self
+from inheritance.virtual_dispatch import test_declared_base, test_parameter_base, test_base_parameter
+from inheritance.virtual_dispatch import test_self_dispatch, test_sibling_overrides, test_returned_instance
+from inheritance.virtual_dispatch import test_abstract_method
 
 
 def test() -> int:
@@ -76,4 +79,14 @@ def test() -> int:
     print(test_super_paramless_method())   # 21
     print(test_super_preserves_self())     # 20
 
+    # Virtual dispatch tests
+    print("=== Virtual Dispatch Tests ===")
+    print(test_declared_base())            # woof
+    print(test_parameter_base())           # ... woof yip, not woof tweet
+    print(test_base_parameter())           # woofwoof tweettweet
+    print(test_self_dispatch())            # tweety says tweet
+    print(test_sibling_overrides())        # 10
+    print(test_returned_instance())        # yip, not woof / tweet
+    print(test_abstract_method())          # 13
+
     return 0
@@ -0,0 +1,122 @@
+# Virtual dispatch tests: an overridden method runs the implementation of
+# the receiver's own class, whatever the declared type holding it
+
+from abc import ABC, abstractmethod
+
+
+class Animal:
+    name: str
+
+    def __init__(self, name: str) -> None:
+        self.name = name
+
+    def speak(self) -> str:
+        return "..."
+
+    def legs(self) -> int:
+        return 4
+
+    def describe(self) -> str:
+        return self.name + " says " + self.speak()
+
+
+class Dog(Animal):
+    def speak(self) -> str:
+        return "woof"
+
+
+class Puppy(Dog):
+    def speak(self) -> str:
+        return "yip, not " + super().speak()
+
+
+class Bird(Animal):
+    def speak(self) -> str:
+        return "tweet"
+
+    def legs(self) -> int:
+        return 2
+
+
+class Shape(ABC):
+    @abstractmethod
+    def area(self) -> int: ...
+
+    def __len__(self) -> int:
+        return 0
+
+
+class Square(Shape):
+    side: int
+
+    def __init__(self, side: int) -> None:
+        self.side = side
+
+    def area(self) -> int:
+        return self.side * self.side
+
+    def __len__(self) -> int:
+        return 4
+
+
+def chorus(animals: list[Animal]) -> str:
+    result: str = ""
+    for animal in animals:
+        result = result + animal.speak() + " "
+    return result
+
+
+def speak_twice(animal: Animal) -> str:
+    result: str = ""
+    for i in range(2):
+        result = result + animal.speak()
+    return result
+
+
+def adopt(young: bool) -> Animal:
+    if young:
+        return Puppy("max")
+    return Bird("kiwi")
+
+
+def test_declared_base() -> str:
+    """A subclass instance held by a base class variable"""
+    pet: Animal = Dog("rex")
+    return pet.speak()  # Expected: woof
+
+
+def test_parameter_base() -> str:
+    """Each instance passed for a base class parameter"""
+    animals: list[Animal] = [Animal("a"), Dog("b"), Puppy("c"), Bird("d")]
+    return chorus(animals)  # Expected: ... woof yip, not woof tweet
+
+
+def test_base_parameter() -> str:
+    """An override called through a base class parameter"""
+    return speak_twice(Dog("rex")) + " " + speak_twice(Bird("kiwi"))  # Expected: woofwoof tweettweet
+
+
+def test_self_dispatch() -> str:
+    """An inherited method calling an overridden one on self"""
+    bird: Animal = Bird("tweety")
+    return bird.describe()  # Expected: tweety says tweet
+
+
+def test_sibling_overrides() -> int:
+    """Only some subclasses override the method"""
+    animals: list[Animal] = [Dog("a"), Bird("b"), Puppy("c")]
+    total: int = 0
+    for animal in animals:
+        total += animal.legs()
+    return total  # Expected: 4 + 2 + 4 = 10
+
+
+def test_returned_instance() -> str:
+    """The receiver is the result of a call"""
+    return adopt(True).speak() + " / " + adopt(False).speak()  # Expected: yip, not woof / tweet
+
+
+def test_abstract_method() -> int:
+    """An abstract method, and a dunder method, called through the abstract class"""
+    shape: Shape = Square(3)
+    return shape.area() + len(shape)  # Expected: 9 + 4 = 13
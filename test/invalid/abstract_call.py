@@ -0,0 +1,14 @@
+# Calling an abstract method through super(), which has no implementation
+from abc import ABC, abstractmethod
+
+class Shape(ABC):
+    @abstractmethod
+    def area(self) -> int: ...
+
+class Square(Shape):
+    def area(self) -> int:
+        return super().area() + 4
+
+def main() -> None:
+    shape: Shape = Square()
+    print(shape.area())
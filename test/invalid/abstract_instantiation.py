@@ -0,0 +1,17 @@
+# Instantiating a class that leaves an abstract method unimplemented
+from abc import ABC, abstractmethod
+
+class Shape(ABC):
+    @abstractmethod
+    def area(self) -> int: ...
+
+    @abstractmethod
+    def name(self) -> str: ...
+
+class Square(Shape):
+    def area(self) -> int:
+        return 4
+
+def main() -> None:
+    square: Square = Square()
+    print(square.area())
@@ -0,0 +1,9 @@
+# ERROR: __call__ invoked with an argument of the wrong type
+
+class Doubler:
+    def __call__(self, x: int) -> int:
+        return x * 2
+
+def main() -> None:
+    d: Doubler = Doubler()
+    print(d("two"))
@@ -0,0 +1,11 @@
+# ERROR: calling an instance of a class without __call__
+
+class Box:
+    size: int
+
+    def __init__(self, size: int) -> None:
+        self.size = size
+
+def main() -> None:
+    b: Box = Box(3)
+    print(b(1))
@@ -0,0 +1,9 @@
+# ERROR: class decorator other than @dataclass
+# This should fail because @dataclass is the only supported class decorator
+
+def register(cls: int) -> int:
+    return cls
+
+@register
+class Widget:
+    size: int
@@ -0,0 +1,6 @@
+# ERROR: Dict comprehension with an unhashable key type
+# This should fail because lists are mutable and cannot be dict keys
+
+def test_comprehension_unhashable_key() -> int:
+    d = {[x]: x for x in range(3)}  # Error: unhashable type: 'list'
+    return 0
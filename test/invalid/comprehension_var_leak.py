@@ -0,0 +1,6 @@
+# ERROR: Comprehension variable used after the comprehension
+# This should fail because comprehension variables are local to the comprehension
+
+def test_comprehension_var_leak() -> int:
+    squares = [x * x for x in range(3)]
+    return x  # Error: undefined variable 'x'
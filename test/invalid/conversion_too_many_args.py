@@ -0,0 +1,5 @@
+# ERROR: float() called with two arguments
+# This should fail because float() takes at most one argument
+
+def test_float_args() -> float:
+    return float("1.5", 10)  # Error: float() takes at most 1 argument
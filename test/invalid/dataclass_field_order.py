@@ -0,0 +1,10 @@
+# ERROR: dataclass field without a default after one with a default
+# This should fail because the generated __init__ would have a required
+# parameter after an optional one
+
+from dataclasses import dataclass
+
+@dataclass
+class Item:
+    name: str = ""
+    price: float  # Error: non-default argument 'price' follows default argument 'name'
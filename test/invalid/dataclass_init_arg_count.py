@@ -0,0 +1,13 @@
+# ERROR: dataclass constructed with too few arguments
+# This should fail because the generated __init__ requires every field without a default
+
+from dataclasses import dataclass
+
+@dataclass
+class Point:
+    x: int
+    y: int
+
+def test_point() -> int:
+    p: Point = Point(1)  # Error: missing argument for y
+    return p.x
@@ -0,0 +1,7 @@
+# ERROR: Required argument omitted before a defaulted one
+# This should fail because 'x' has no default
+def add(x: int, y: int = 3) -> int:
+    return x + y
+
+def main() -> int:
+    return add()  # Error: missing argument 'x'
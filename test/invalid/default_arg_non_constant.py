@@ -0,0 +1,4 @@
+# ERROR: Non-constant default value
+# This should fail because defaults must be constants
+def total(x: int, items: list[int] = []) -> int:  # Error: list default
+    return x + len(items)
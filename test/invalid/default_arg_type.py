@@ -0,0 +1,4 @@
+# ERROR: Default value does not match the parameter type
+# This should fail because the default of an int parameter is a str
+def add(x: int, y: int = "3") -> int:  # Error: str default for int parameter
+    return x + y
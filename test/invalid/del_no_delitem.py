@@ -0,0 +1,7 @@
+# ERROR: Deleting an item from a type without __delitem__
+# This should fail because str does not support item deletion
+
+def test_del_no_delitem() -> int:
+    s: str = "abc"
+    del s[0]  # Error: str has no __delitem__
+    return len(s)
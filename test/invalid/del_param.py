@@ -0,0 +1,6 @@
+# ERROR: Deleting a function parameter
+# This should fail because only local variables can be deleted
+
+def test_del_param(x: int) -> int:
+    del x  # Error: cannot delete parameter 'x'
+    return 0
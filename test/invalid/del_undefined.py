@@ -0,0 +1,7 @@
+# ERROR: Using a variable after it was deleted
+# This should fail because `del x` unbinds x for the rest of the function
+
+def test_del_undefined() -> int:
+    x: int = 1
+    del x
+    return x  # Error: undefined variable 'x'
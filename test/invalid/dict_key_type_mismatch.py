@@ -0,0 +1,6 @@
+# ERROR: Dict subscript with the wrong key type
+# This should fail because the dict is keyed by str
+
+def test_key_type_mismatch() -> int:
+    d: dict[str, int] = {"a": 1}
+    return d[1]  # Error: int key for dict[str, int]
@@ -0,0 +1,6 @@
+# ERROR: Dict with an unhashable key type
+# This should fail because lists are mutable and cannot be dict keys
+
+def test_unhashable_key() -> int:
+    d = {[1, 2]: 100}  # Error: unhashable type: 'list'
+    return 0
@@ -0,0 +1,9 @@
+# except clause naming a class that does not inherit from Exception
+class Point:
+    x: int
+
+def main() -> None:
+    try:
+        print(1)
+    except Point:  # catching classes that do not inherit from Exception is not allowed
+        print(2)
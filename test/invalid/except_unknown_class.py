@@ -0,0 +1,6 @@
+# except clause naming a class that is not defined
+def main() -> None:
+    try:
+        print(1)
+    except NoSuchError:  # Undefined exception class: 'NoSuchError'
+        print(2)
@@ -0,0 +1,6 @@
+# ERROR: exported function taking a str
+# This should fail because C callers can only pass ints, floats and bools
+
+@export
+def greet(name: str) -> int:
+    return len(name)
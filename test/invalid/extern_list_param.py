@@ -0,0 +1,5 @@
+# ERROR: extern function taking a list
+# This should fail because only ints, floats, bools, strs and bytes can be passed to C
+
+@extern("qsort")
+def qsort(items: list[int]) -> None: ...
@@ -0,0 +1,8 @@
+# ERROR: file.write() with a non-string argument
+# This should fail because files are opened in text mode and write() takes a str
+
+def test_write() -> int:
+    f = open("/tmp/out.txt", "w")
+    f.write(42)  # Error: write() expects str
+    f.close()
+    return 0
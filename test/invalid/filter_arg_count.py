@@ -0,0 +1,9 @@
+# ERROR: filter() without an iterable
+# This should fail because filter() takes a function and an iterable
+
+def is_even(x: int) -> bool:
+    return x % 2 == 0
+
+def test_filter_one_arg() -> int:
+    it = filter(is_even)  # Error: filter() takes exactly 2 arguments
+    return next(it)
@@ -0,0 +1,4 @@
+# ERROR: Format specification in an f-string
+# This should fail because format specs like :.2f are not supported
+def show(x: float) -> str:
+    return f"{x:.2f}"  # Error: format spec not supported
@@ -0,0 +1,9 @@
+# ERROR: function decorator other than @export and @extern
+# This should fail because @export and @extern are the only supported function decorators
+
+def traced(x: int) -> int:
+    return x
+
+@traced
+def area(size: int) -> int:
+    return size * size
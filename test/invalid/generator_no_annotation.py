@@ -0,0 +1,8 @@
+# ERROR: Generator function without an Iterator return annotation
+# This should fail because the element type of a generator must be declared
+
+def numbers(n: int):
+    i: int = 0
+    while i < n:
+        yield i  # Error: generator must be annotated to return Iterator[T]
+        i += 1
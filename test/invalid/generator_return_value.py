@@ -0,0 +1,7 @@
+# ERROR: Returning a value from a generator
+# This should fail because generators can only use a bare return
+from typing import Iterator
+
+def numbers() -> Iterator[int]:
+    yield 1
+    return 2  # Error: cannot return a value from a generator
@@ -0,0 +1,6 @@
+# ERROR: Yielding a value of the wrong type
+# This should fail because the generator is declared to yield ints
+from typing import Iterator
+
+def names() -> Iterator[int]:
+    yield "alice"  # Error: yield type mismatch
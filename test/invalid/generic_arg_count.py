@@ -0,0 +1,14 @@
+# Giving a generic class the wrong number of type arguments
+from typing import Generic, TypeVar
+
+T = TypeVar("T")
+
+class Box(Generic[T]):
+    item: T
+
+    def __init__(self, item: T) -> None:
+        self.item = item
+
+def main() -> None:
+    box: Box[int, str] = Box(1)
+    print(box.item)
@@ -0,0 +1,13 @@
+# Constructing a generic class without saying its type arguments
+from typing import Generic, TypeVar
+
+T = TypeVar("T")
+
+class Box(Generic[T]):
+    item: T
+
+    def __init__(self, item: T) -> None:
+        self.item = item
+
+def main() -> None:
+    print(Box(1).item)
@@ -0,0 +1,6 @@
+# ERROR: Modifying a module variable without a global declaration
+# This should fail because 'count' would be a local read before assignment
+count: int = 0
+
+def bump() -> None:
+    count += 1  # Error: missing 'global count'
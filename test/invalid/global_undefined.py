@@ -0,0 +1,5 @@
+# ERROR: global declaration of a name that is not a module variable
+# This should fail because module variables must be defined at module level
+def init() -> None:
+    global total  # Error: 'total' is not a module variable
+    total = 0
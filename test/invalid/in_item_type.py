@@ -0,0 +1,6 @@
+# ERROR: membership test with an item of the wrong type
+
+def main() -> None:
+    d: dict[str, int] = {"a": 1}
+    if 1 in d:
+        print("found")
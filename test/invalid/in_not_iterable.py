@@ -0,0 +1,6 @@
+# ERROR: 'in' on a value that is neither a container nor iterable
+
+def main() -> None:
+    n: int = 5
+    if 1 in n:
+        print("found")
@@ -0,0 +1,5 @@
+# ERROR: int() with an explicit base on a non-string
+# This should fail because a base is only allowed when parsing a str
+
+def test_int_base() -> int:
+    return int(3.5, 10)  # Error: int() can't convert non-string with explicit base
@@ -0,0 +1,6 @@
+# ERROR: int() of a list
+# This should fail because a list cannot be converted to int
+
+def test_int_list() -> int:
+    items: list[int] = [1, 2]
+    return int(items)  # Error: list has no __int__
@@ -0,0 +1,4 @@
+# ERROR: __intrinsic__ naming no LLVM intrinsic
+# This should fail because llvm.popcount is not an intrinsic (population count is llvm.ctpop.i64)
+
+print(__intrinsic__("llvm.popcount", 255))
@@ -0,0 +1,8 @@
+# ERROR: Keyword argument to a user function
+# This should fail because keyword arguments are only supported by sorted() and list.sort()
+
+def scale(x: int, factor: int) -> int:
+    return x * factor
+
+def test_keyword() -> int:
+    return scale(2, factor=3)  # Error: keyword argument 'factor' is not supported
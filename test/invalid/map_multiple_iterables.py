@@ -0,0 +1,11 @@
+# ERROR: map() over two iterables
+# This should fail because map() only supports a single iterable
+
+def add(a: int, b: int) -> int:
+    return a + b
+
+def test_map_two() -> int:
+    total: int = 0
+    for x in map(add, [1, 2], [3, 4]):  # Error: map() over several iterables is not supported
+        total += x
+    return total
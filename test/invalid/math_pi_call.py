@@ -0,0 +1,7 @@
+# ERROR: calling math.pi
+# This should fail because math.pi is a float constant, not a function
+
+import math
+
+def test_pi() -> float:
+    return math.pi()  # Error: 'math.pi' is not callable
@@ -0,0 +1,7 @@
+# ERROR: math.pow() with one argument
+# This should fail because math.pow() takes a base and an exponent
+
+import math
+
+def test_pow() -> float:
+    return math.pow(2.0)  # Error: math.pow() takes 2 arguments
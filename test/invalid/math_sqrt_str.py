@@ -0,0 +1,7 @@
+# ERROR: math.sqrt() with a str argument
+# This should fail because math functions take int or float
+
+import math
+
+def test_sqrt() -> float:
+    return math.sqrt("4")  # Error: math.sqrt() argument must be int or float
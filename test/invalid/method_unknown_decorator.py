@@ -0,0 +1,11 @@
+# ERROR: method decorator other than @staticmethod and @classmethod
+
+def traced(x: int) -> int:
+    return x
+
+class Widget:
+    size: int
+
+    @traced
+    def area(self) -> int:
+        return self.size * self.size
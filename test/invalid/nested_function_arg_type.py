@@ -0,0 +1,7 @@
+# ERROR: Wrong argument type passed to a nested function
+# This should fail because 'scale' expects an int
+def outer() -> int:
+    k: int = 2
+    def scale(x: int) -> int:
+        return x * k
+    return scale("3")  # Error: str argument
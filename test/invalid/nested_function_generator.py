@@ -0,0 +1,11 @@
+# ERROR: Nested generator function
+# This should fail because generators must be defined at module level
+from typing import Iterator
+
+def outer(n: int) -> int:
+    def count() -> Iterator[int]:  # Error: nested generator
+        i: int = 0
+        while i < n:
+            yield i
+            i += 1
+    return n
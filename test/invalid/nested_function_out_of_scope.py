@@ -0,0 +1,9 @@
+# ERROR: Calling a nested function outside its enclosing function
+# This should fail because 'inner' is only visible inside 'outer'
+def outer() -> int:
+    def inner() -> int:
+        return 1
+    return inner()
+
+def main() -> int:
+    return inner()  # Error: undefined function
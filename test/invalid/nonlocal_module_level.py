@@ -0,0 +1,4 @@
+# ERROR: nonlocal declaration at module level
+# This should fail because there is no enclosing function
+x: int = 1
+nonlocal x  # Error: nonlocal at module level
@@ -0,0 +1,8 @@
+# ERROR: nonlocal name not bound in an enclosing function
+# This should fail because 'missing' is not a variable of 'outer'
+def outer() -> int:
+    def inner() -> None:
+        nonlocal missing  # Error: no binding for 'missing'
+        missing = 1
+    inner()
+    return 0
@@ -0,0 +1,6 @@
+# ERROR: open() with a non-string path
+# This should fail because open() only takes a str path (file descriptors are not supported)
+
+def test_open() -> int:
+    f = open(3)  # Error: open() path must be str
+    return 0
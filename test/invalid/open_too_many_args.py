@@ -0,0 +1,6 @@
+# ERROR: open() with more than two arguments
+# This should fail because only the path and mode arguments are supported
+
+def test_open() -> int:
+    f = open("data.txt", "r", 1)  # Error: open() takes 1 or 2 arguments
+    return 0
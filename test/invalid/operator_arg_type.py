@@ -0,0 +1,14 @@
+# ERROR: operator method called with an operand of the wrong type
+
+class Vector:
+    x: int
+
+    def __init__(self, x: int) -> None:
+        self.x = x
+
+    def __add__(self, other: "Vector") -> "Vector":
+        return Vector(self.x + other.x)
+
+def main() -> None:
+    v: Vector = Vector(1) + 2
+    print(v.x)
@@ -0,0 +1,11 @@
+# ERROR: arithmetic on a class that does not define the operator method
+
+class Point:
+    x: int
+
+    def __init__(self, x: int) -> None:
+        self.x = x
+
+def main() -> None:
+    p: Point = Point(1) + Point(2)
+    print(p.x)
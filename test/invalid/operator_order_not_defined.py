@@ -0,0 +1,11 @@
+# ERROR: ordering comparison on a class without __lt__ and friends
+
+class Point:
+    x: int
+
+    def __init__(self, x: int) -> None:
+        self.x = x
+
+def main() -> None:
+    if Point(1) < Point(2):
+        print("less")
@@ -0,0 +1,6 @@
+# Using an Optional value without checking it for None
+from typing import Optional
+
+def main() -> None:
+    x: Optional[int] = 3
+    print(x + 1)
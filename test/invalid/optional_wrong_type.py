@@ -0,0 +1,7 @@
+# Assigning a str to an Optional[int]
+from typing import Optional
+
+def main() -> None:
+    x: Optional[int] = None
+    x = "hello"
+    print(x)
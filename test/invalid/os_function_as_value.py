@@ -0,0 +1,8 @@
+# ERROR: Using an os function as a value
+# This should fail because intrinsic module functions can only be called
+
+import os
+
+def test_value() -> int:
+    f = os.getcwd  # Error: os.getcwd can only be called
+    return 0
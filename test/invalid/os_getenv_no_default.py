@@ -0,0 +1,7 @@
+# ERROR: os.getenv() without a default
+# This should fail because there is no None to return for an unset variable
+
+import os
+
+def test_getenv() -> str:
+    return os.getenv("HOME")  # Error: os.getenv() needs a default for unset variables
@@ -0,0 +1,12 @@
+# Overriding a method with different parameter types
+class Animal:
+    def feed(self, grams: int) -> int:
+        return grams
+
+class Dog(Animal):
+    def feed(self, grams: float) -> int:
+        return 0
+
+def main() -> None:
+    pet: Animal = Dog()
+    print(pet.feed(3))
@@ -0,0 +1,15 @@
+# ERROR: calling a property as if it were a method
+
+class Circle:
+    radius: int
+
+    def __init__(self, radius: int) -> None:
+        self.radius = radius
+
+    @property
+    def diameter(self) -> int:
+        return self.radius * 2
+
+def main() -> None:
+    c: Circle = Circle(2)
+    print(c.diameter())
@@ -0,0 +1,15 @@
+# ERROR: assigning a property that has no setter
+
+class Circle:
+    radius: int
+
+    def __init__(self, radius: int) -> None:
+        self.radius = radius
+
+    @property
+    def diameter(self) -> int:
+        return self.radius * 2
+
+def main() -> None:
+    c: Circle = Circle(2)
+    c.diameter = 10
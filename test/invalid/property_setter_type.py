@@ -0,0 +1,19 @@
+# ERROR: property assigned a value of the wrong type
+
+class Circle:
+    _radius: int
+
+    def __init__(self, radius: int) -> None:
+        self._radius = radius
+
+    @property
+    def radius(self) -> int:
+        return self._radius
+
+    @radius.setter
+    def radius(self, value: int) -> None:
+        self._radius = value
+
+def main() -> None:
+    c: Circle = Circle(2)
+    c.radius = "big"
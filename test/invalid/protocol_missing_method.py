@@ -0,0 +1,22 @@
+# Passing a class without every protocol method for a protocol parameter
+from typing import Protocol
+
+class Shape(Protocol):
+    def area(self) -> int: ...
+
+    def name(self) -> str: ...
+
+class Square:
+    side: int
+
+    def __init__(self, side: int) -> None:
+        self.side = side
+
+    def area(self) -> int:
+        return self.side * self.side
+
+def describe(shape: Shape) -> str:
+    return str(shape.area())
+
+def main() -> None:
+    print(describe(Square(2)))
@@ -0,0 +1,18 @@
+# A protocol used as the type of a variable
+from typing import Protocol
+
+class Shape(Protocol):
+    def area(self) -> int: ...
+
+class Square:
+    side: int
+
+    def __init__(self, side: int) -> None:
+        self.side = side
+
+    def area(self) -> int:
+        return self.side * self.side
+
+def main() -> None:
+    shape: Shape = Square(2)
+    print(shape.area())
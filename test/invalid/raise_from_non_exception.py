@@ -0,0 +1,6 @@
+# raise ... from naming something that is not an exception
+def main() -> None:
+    try:
+        print(1)
+    except ValueError:
+        raise RuntimeError("failed") from 5  # exception causes must be exceptions or None
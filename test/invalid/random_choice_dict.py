@@ -0,0 +1,8 @@
+# ERROR: random.choice() on a dict
+# This should fail because choice() needs a list or str
+
+import random
+
+def test_choice() -> str:
+    d: dict[str, int] = {"a": 1}
+    return random.choice(d)  # Error: random.choice() argument must be a list or str
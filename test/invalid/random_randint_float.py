@@ -0,0 +1,7 @@
+# ERROR: random.randint() with a float bound
+# This should fail because randint() takes int bounds
+
+import random
+
+def test_randint() -> int:
+    return random.randint(0, 2.5)  # Error: random.randint() arguments must be int
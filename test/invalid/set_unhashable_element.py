@@ -0,0 +1,6 @@
+# ERROR: Set with an unhashable element type
+# This should fail because lists are mutable and cannot be set elements
+
+def test_unhashable_element() -> int:
+    s = {[1, 2], [3]}  # Error: unhashable type: 'list'
+    return 0
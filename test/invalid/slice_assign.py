@@ -0,0 +1,4 @@
+# ERROR: Assigning to a slice
+# This should fail because slice assignment is not supported
+def clear_head(nums: list[int]) -> None:
+    nums[:2] = [0, 0]  # Error: slice assignment
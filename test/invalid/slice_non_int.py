@@ -0,0 +1,4 @@
+# ERROR: Slicing with a non-integer bound
+# This should fail because slice indices must be ints
+def head(s: str) -> str:
+    return s[:"3"]  # Error: slice bound is a str
@@ -0,0 +1,4 @@
+# ERROR: Slicing a type that does not support it
+# This should fail because dicts cannot be sliced
+def middle(d: dict[int, int]) -> dict[int, int]:
+    return d[1:3]  # Error: dict has no slices
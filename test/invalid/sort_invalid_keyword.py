@@ -0,0 +1,7 @@
+# ERROR: Unknown keyword argument to sorted()
+# This should fail because sorted() only accepts key= and reverse=
+
+def test_sorted_keyword() -> int:
+    items: list[int] = [2, 1]
+    result: list[int] = sorted(items, order=True)  # Error: invalid keyword argument
+    return result[0]
@@ -0,0 +1,13 @@
+# ERROR: Sorting objects without a key
+# This should fail because only int, float, bool and str items can be compared
+
+class Box:
+    size: int
+
+    def __init__(self, size: int) -> None:
+        self.size = size
+
+def test_sort_objects() -> int:
+    boxes: list[Box] = [Box(2), Box(1)]
+    boxes.sort()  # Error: cannot sort by keys of type Box
+    return boxes[0].size
@@ -0,0 +1,9 @@
+# ERROR: Starred call argument that is not a tuple
+# This should fail because a list's length is unknown at compile time
+
+def add(x: int, y: int) -> int:
+    return x + y
+
+def test_starred_call_non_tuple() -> int:
+    items: list[int] = [1, 2]
+    return add(*items)  # Error: starred call argument must be a tuple
@@ -0,0 +1,7 @@
+# ERROR: More than one starred target in an unpacking
+# This should fail because only one target may collect the remaining items
+
+def test_starred_multiple_targets() -> int:
+    items: list[int] = [1, 2, 3]
+    a, *b, *c = items  # Error: multiple starred targets
+    return a
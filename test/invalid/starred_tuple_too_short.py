@@ -0,0 +1,6 @@
+# ERROR: Unpacking a tuple with fewer elements than non-starred targets
+# This should fail because three targets besides `*rest` need three elements
+
+def test_starred_tuple_too_short() -> int:
+    a, b, *rest, c = (1, 2)  # Error: cannot unpack a tuple of length 2 into at least 3 targets
+    return a + b + c
@@ -0,0 +1,9 @@
+# ERROR: static method called with an argument of the wrong type
+
+class Math:
+    @staticmethod
+    def double(x: int) -> int:
+        return x * 2
+
+def main() -> None:
+    print(Math.double("two"))
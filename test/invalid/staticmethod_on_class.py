@@ -0,0 +1,15 @@
+# ERROR: instance method called on the class name
+# Only @staticmethod and @classmethod methods can be called without an instance
+
+class Counter:
+    count: int
+
+    def __init__(self) -> None:
+        self.count = 0
+
+    def bump(self) -> int:
+        self.count = self.count + 1
+        return self.count
+
+def main() -> None:
+    print(Counter.bump())
@@ -0,0 +1,12 @@
+# Subclass __init__ that never calls super().__init__(), leaving a field unset
+class Base:
+    def __init__(self) -> None:
+        self.items: list[int] = []
+
+class Child(Base):
+    def __init__(self, size: int) -> None:  # 'items' is never set
+        self.size = size
+
+def main() -> None:
+    c: Child = Child(3)
+    print(len(c.items))
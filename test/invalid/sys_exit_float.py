@@ -0,0 +1,7 @@
+# ERROR: sys.exit() with a float
+# This should fail because the exit status must be an int (or a str message)
+
+import sys
+
+def test_exit() -> None:
+    sys.exit(1.5)  # Error: sys.exit() argument must be an int exit status or a str message
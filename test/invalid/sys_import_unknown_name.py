@@ -0,0 +1,7 @@
+# ERROR: Importing a name sys does not provide
+# This should fail because only argv, exit, stdin, stdout and stderr can be imported
+
+from sys import getsizeof  # Error: Cannot import 'getsizeof' from module 'sys'
+
+def test_import() -> int:
+    return 0
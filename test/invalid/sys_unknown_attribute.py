@@ -0,0 +1,7 @@
+# ERROR: Accessing a sys attribute the compiler does not provide
+# This should fail because only argv, exit, stdin, stdout and stderr are available
+
+import sys
+
+def test_version() -> int:
+    return len(sys.version)  # Error: module 'sys' has no attribute 'version'
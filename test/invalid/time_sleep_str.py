@@ -0,0 +1,7 @@
+# ERROR: time.sleep() with a str argument
+# This should fail because the duration must be an int or float
+
+import time
+
+def test_sleep() -> None:
+    time.sleep("1")  # Error: time.sleep() argument must be int or float
@@ -0,0 +1,7 @@
+# ERROR: time.time() with an argument
+# This should fail because time.time() takes no arguments
+
+import time
+
+def test_time() -> float:
+    return time.time(1)  # Error: time.time() takes 0 arguments
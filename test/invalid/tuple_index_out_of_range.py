@@ -0,0 +1,6 @@
+# ERROR: Tuple index out of range
+# This should fail because tuple indexes are checked against the tuple's length
+
+def test_tuple_index_out_of_range() -> int:
+    t = (1, 2)
+    return t[2]  # Error: index 2 out of range for a tuple of length 2
@@ -0,0 +1,7 @@
+# ERROR: Tuple indexed by a variable
+# This should fail because tuple element types are only known for constant indexes
+
+def test_tuple_non_constant_index() -> int:
+    t = (1, "a")
+    i: int = 0
+    return t[i]  # Error: tuple index must be an integer constant
@@ -0,0 +1,6 @@
+# ERROR: Unpacking a tuple into the wrong number of targets
+# This should fail because the tuple has 3 elements but only 2 targets
+
+def test_tuple_unpack_count_mismatch() -> int:
+    a, b = (1, 2, 3)  # Error: cannot unpack a tuple of length 3 into 2 targets
+    return a + b
@@ -0,0 +1,3 @@
+# No binary imports this module, but its type error is still reported
+def broken() -> int:
+    return "not an int"
@@ -0,0 +1,12 @@
+# ERROR: __exit__ returning something other than bool or None
+# This should fail because the with statement cannot interpret an int result
+class Resource:
+    def __enter__(self) -> int:
+        return 1
+
+    def __exit__(self, exc_type, exc_value, traceback) -> int:
+        return 0
+
+def use() -> None:
+    with Resource():  # Error: __exit__ returns int
+        print(1)
@@ -0,0 +1,9 @@
+# ERROR: with statement on an object without __exit__
+# This should fail because the class is not a context manager
+class Resource:
+    def __enter__(self) -> int:
+        return 1
+
+def use() -> None:
+    with Resource() as r:  # Error: Resource has no __exit__
+        print(r)
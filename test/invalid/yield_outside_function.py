@@ -0,0 +1,4 @@
+# ERROR: yield at module level
+# This should fail because only functions can be generators
+
+yield 1  # Error: 'yield' outside of a generator function
@@ -0,0 +1,3 @@
+from app.greet import greeting
+
+print(greeting())
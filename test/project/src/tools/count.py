@@ -0,0 +1,7 @@
+def count(n: int) -> int:
+    total: int = 0
+    for i in range(n):
+        total = total + i
+    return total
+
+print(count(5))
@@ -70,14 +70,10 @@ fn test_pyrun_missing_value_for_target() {
         .stderr(predicate::str::contains("a value is required"));
 }
 
-#[test]
-fn test_pyrun_main_program() {
+/// Check that a compiled test/main.py printed what python3 does
+fn assert_matches_python(compiler_output: &std::process::Output) {
     let main_py = test_dir().join("main.py");
 
-    if !main_py.exists() {
-        panic!("main.py not found at {}", main_py.display());
-    }
-
     // Run with Python interpreter
     let python_output = std::process::Command::new("python3")
         .arg(&main_py)
@@ -89,15 +85,7 @@ fn test_pyrun_main_program() {
     );
     let python_output = String::from_utf8_lossy(&python_output.stdout).to_string();
 
-    // Run with pyrun
-    let compiler_output = cargo_bin_cmd!("pyrun")
-        .arg(&main_py)
-        .output()
-        .expect("Failed to run pyrun");
-    assert!(
-        compiler_output.status.success(),
-        "pyrun failed to execute main.py"
-    );
+    assert!(compiler_output.status.success(), "compiled main.py failed");
     let compiler_output = String::from_utf8_lossy(&compiler_output.stdout).to_string();
 
     if python_output != compiler_output {
@@ -159,10 +147,27 @@ fn test_pyrun_main_program() {
         eprintln!("=====================================\n");
         panic!("Output mismatch between Python and compiler");
     }
+}
+
+#[test]
+fn test_pyrun_main_program() {
+    let main_py = test_dir().join("main.py");
+
+    if !main_py.exists() {
+        panic!("main.py not found at {}", main_py.display());
+    }
+
+    // Run with pyrun
+    let compiler_output = cargo_bin_cmd!("pyrun")
+        .arg(&main_py)
+        .output()
+        .expect("Failed to run pyrun");
+    assert_matches_python(&compiler_output);
 
     cargo_bin_cmd!("pyrun")
         .arg(&main_py)
         .arg("--emit-ast")
+        .arg("--emit-tir")
         .arg("--emit-llvm")
         .output()
         .expect("Failed to run pyrun");
@@ -201,6 +206,112 @@ fn test_pyrun_riscv64() {
     assert!(stdout.contains("1") && stdout.contains("2"));
 }
 
+#[test]
+fn test_pyrun_aarch64() {
+    // Skip if QEMU is not available
+    if std::process::Command::new("qemu-aarch64")
+        .arg("--version")
+        .output()
+        .is_err()
+    {
+        eprintln!("Skipping aarch64 test: qemu-aarch64 not available");
+        return;
+    }
+
+    let simple_py = test_dir().join("exceptions/simple.py");
+
+    if !simple_py.exists() {
+        panic!("simple.py not found at {}", simple_py.display());
+    }
+
+    // Run with pyrun targeting aarch64
+    let output = cargo_bin_cmd!("pyrun")
+        .args([simple_py.to_str().unwrap(), "--target", "aarch64"])
+        .output()
+        .expect("Failed to run pyrun with aarch64 target");
+
+    assert!(
+        output.status.success(),
+        "pyrun --target aarch64 failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("1") && stdout.contains("2"));
+}
+
+#[test]
+fn test_pyrun_jit() {
+    let sys_exit_py = test_dir().join("basic/modules/sys_exit.py");
+
+    // The program runs in pyrun's process, so its exit status is pyrun's
+    let output = cargo_bin_cmd!("pyrun")
+        .args(["--jit", sys_exit_py.to_str().unwrap(), "a", "b"])
+        .output()
+        .expect("Failed to run pyrun --jit");
+    assert_eq!(output.status.code(), Some(3));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "args: 2\na\nb\n");
+
+    // Another architecture's code cannot run in this process
+    let simple_py = test_dir().join("exceptions/simple.py");
+    let target = if cfg!(target_arch = "riscv64") {
+        "aarch64"
+    } else {
+        "riscv64"
+    };
+    cargo_bin_cmd!("pyrun")
+        .args(["--jit", simple_py.to_str().unwrap(), "--target", target])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("built for this machine"));
+}
+
+// ============================================================================
+// pyrepl tests
+// ============================================================================
+
+#[test]
+fn test_pyrepl_session() {
+    let session = r#"x = 20
+def double(n: int) -> int:
+    return n * 2
+
+double(x) + 2
+print("hi")
+"a" + "b"
+class Vector:
+    x: int
+    y: int
+    def __init__(self, x: int, y: int) -> None:
+        self.x = x
+        self.y = y
+    def __repr__(self) -> str:
+        return f"Vector({self.x}, {self.y})"
+
+v = Vector(1, 2)
+v
+x = x + 1
+raise ValueError("boom")
+x + "a"
+x * 2
+v.y
+"#;
+
+    // Globals, functions and classes persist from one input to the next,
+    // and an exception or an error is reported without ending the session
+    let output = cargo_bin_cmd!("pyrepl")
+        .write_stdin(session)
+        .output()
+        .expect("Failed to run pyrepl");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "pyrepl failed: {stderr}");
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "42\nhi\n'ab'\nVector(1, 2)\n42\n2\n"
+    );
+    assert!(stderr.contains("ValueError: boom"));
+    assert!(stderr.contains("error"));
+}
+
 // ============================================================================
 // pycc tests
 // ============================================================================
@@ -359,98 +470,2100 @@ fn test_pycc_compile_riscv64() {
     }
 }
 
-// ============================================================================
-// Static linking tests
-// ============================================================================
-
 #[test]
-fn test_pycc_static_binary() {
+fn test_pycc_compile_aarch64() {
     let temp_dir = TempDir::new().unwrap();
-    let output_path = temp_dir.path().join("main_static");
+    let output_path = temp_dir.path().join("simple_aarch64");
 
-    // Compile main.py to a binary
     cargo_bin_cmd!("pycc")
         .args([
-            test_dir().join("main.py").to_str().unwrap(),
+            test_dir().join("exceptions/simple.py").to_str().unwrap(),
             "-o",
             output_path.to_str().unwrap(),
             "--target",
-            "x86_64",
+            "aarch64",
         ])
         .assert()
         .success();
 
-    assert!(output_path.exists(), "Compiled binary should exist");
+    assert!(output_path.exists());
 
-    // Check with 'file' command that it's statically linked
-    let file_output = std::process::Command::new("file")
-        .arg(&output_path)
+    // Run with QEMU if available
+    if std::process::Command::new("qemu-aarch64")
+        .arg("--version")
         .output()
-        .expect("Failed to run file command");
-    let file_str = String::from_utf8_lossy(&file_output.stdout);
-    assert!(
-        file_str.contains("statically linked"),
-        "Binary should be statically linked. file output: {}",
-        file_str
-    );
+        .is_ok()
+    {
+        let output = std::process::Command::new("qemu-aarch64")
+            .arg(&output_path)
+            .output()
+            .expect("Failed to run with QEMU");
+        assert!(output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("1") && stdout.contains("2"));
+    }
+}
 
-    // Check with 'ldd' command that there are no dynamic dependencies
-    let ldd_output = std::process::Command::new("ldd")
-        .arg(&output_path)
+#[test]
+fn test_pycc_project() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_dir = temp_dir.path().join("build");
+
+    cargo_bin_cmd!("pycc")
+        .args([
+            "--project",
+            test_dir().join("project").to_str().unwrap(),
+            "-o",
+            output_dir.to_str().unwrap(),
+        ])
+        .assert()
+        .success();
+
+    let output = std::process::Command::new(output_dir.join("app"))
         .output()
-        .expect("Failed to run ldd command");
-    let ldd_str = String::from_utf8_lossy(&ldd_output.stdout);
-    let ldd_stderr = String::from_utf8_lossy(&ldd_output.stderr);
+        .expect("Failed to run app binary");
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "hello from app\n");
 
-    // ldd returns non-zero for static binaries and outputs "not a dynamic executable"
-    assert!(
-        ldd_str.contains("not a dynamic executable")
-            || ldd_stderr.contains("not a dynamic executable"),
-        "Binary should have no dynamic dependencies. ldd output: {} stderr: {}",
-        ldd_str,
-        ldd_stderr
-    );
+    let output = std::process::Command::new(output_dir.join("count"))
+        .output()
+        .expect("Failed to run count binary");
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "10\n");
+}
 
-    // Also verify the binary runs correctly
-    let run_output = std::process::Command::new(&output_path)
+#[test]
+fn test_pycc_include_paths() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path();
+    std::fs::create_dir_all(dir.join("app")).unwrap();
+    std::fs::create_dir_all(dir.join("libs/shapes")).unwrap();
+    std::fs::create_dir_all(dir.join("vendor")).unwrap();
+    std::fs::write(
+        dir.join("app/main.py"),
+        "import shapes.square as square\nimport greet\n\nprint(square.area(3))\ngreet.hello()\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("libs/shapes/square.py"),
+        "def area(side: int) -> int:\n    return side * side\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("vendor/greet.py"),
+        "def hello() -> None:\n    print(\"hello\")\n",
+    )
+    .unwrap();
+    let main = dir.join("app/main.py");
+    let output_path = dir.join("main");
+
+    // Neither sibling directory is searched by default
+    cargo_bin_cmd!("pycc")
+        .args(["--check", main.to_str().unwrap()])
+        .env_remove("PYCPATH")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("shapes"));
+
+    cargo_bin_cmd!("pycc")
+        .args([
+            main.to_str().unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+            "-I",
+            dir.join("libs").to_str().unwrap(),
+        ])
+        .env("PYCPATH", dir.join("vendor"))
+        .assert()
+        .success();
+
+    let output = std::process::Command::new(&output_path)
         .output()
         .expect("Failed to run compiled binary");
-    assert!(
-        run_output.status.success(),
-        "Static binary should execute successfully"
-    );
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "9\nhello\n");
 }
 
-// ============================================================================
-// Invalid file tests
-// ============================================================================
+#[test]
+fn test_pycc_check() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("simple");
+
+    cargo_bin_cmd!("pycc")
+        .args([
+            "--check",
+            test_dir().join("exceptions/simple.py").to_str().unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty());
+
+    // Nothing is generated or linked
+    assert!(!output_path.exists());
+}
 
 #[test]
-fn test_invalid_files() {
-    let invalid_dir = test_dir().join("invalid");
+fn test_pycc_check_type_error() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = temp_dir.path().join("bad.py");
+    std::fs::write(
+        &source,
+        "def f() -> int:\n    return \"one\"\n\nprint(f())\n",
+    )
+    .unwrap();
 
-    if !invalid_dir.exists() {
-        panic!("invalid directory not found at {}", invalid_dir.display());
-    }
+    cargo_bin_cmd!("pycc")
+        .args(["--check", source.to_str().unwrap()])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Return type mismatch in function 'f'",
+        ));
+}
 
-    let entries = std::fs::read_dir(&invalid_dir).expect("Failed to read invalid directory");
+#[test]
+fn test_pycc_check_project() {
+    cargo_bin_cmd!("pycc")
+        .args([
+            "--check",
+            "--project",
+            test_dir().join("project").to_str().unwrap(),
+        ])
+        .assert()
+        .success();
 
-    let mut test_count = 0;
-    let mut failed_to_error = Vec::new();
+    assert!(!test_dir().join("project/build").exists());
+}
 
-    for entry in entries {
-        let entry = entry.expect("Failed to read directory entry");
-        let path = entry.path();
+#[test]
+fn test_pycc_incremental() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = temp_dir.path().join("app.py");
+    let output_path = temp_dir.path().join("app");
+    let cache_dir = temp_dir.path().join("cache");
+    let build = || {
+        cargo_bin_cmd!("pycc")
+            .args([
+                source.to_str().unwrap(),
+                "-o",
+                output_path.to_str().unwrap(),
+                "--incremental",
+                "--cache-dir",
+                cache_dir.to_str().unwrap(),
+            ])
+            .assert()
+            .success();
+        let output = std::process::Command::new(&output_path)
+            .output()
+            .expect("Failed to run app binary");
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+    let cached_objects = || std::fs::read_dir(&cache_dir).unwrap().count();
+
+    std::fs::write(&source, "print('first')\n").unwrap();
+    assert_eq!(build(), "first\n");
+    assert_eq!(cached_objects(), 1);
+
+    // Unchanged: the cached object is relinked
+    std::fs::remove_file(&output_path).unwrap();
+    assert_eq!(build(), "first\n");
+    assert_eq!(cached_objects(), 1);
+
+    // Changed: the object is generated again and replaces the old one
+    std::fs::write(&source, "print('second')\n").unwrap();
+    assert_eq!(build(), "second\n");
+    assert_eq!(cached_objects(), 1);
+}
 
-        if path.extension().and_then(|s| s.to_str()) == Some("py") {
-            test_count += 1;
-            let file_name = path.file_name().unwrap().to_str().unwrap();
+#[test]
+fn test_pycc_sys_argv_and_exit() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("sys_exit");
 
-            let temp_dir = TempDir::new().unwrap();
-            let output_path = temp_dir.path().join("output");
+    cargo_bin_cmd!("pycc")
+        .args([
+            test_dir()
+                .join("basic/modules/sys_exit.py")
+                .to_str()
+                .unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+        ])
+        .assert()
+        .success();
+
+    // sys.argv holds the arguments and sys.exit(3) sets the exit status
+    let output = std::process::Command::new(&output_path)
+        .args(["a", "b"])
+        .output()
+        .expect("Failed to run sys_exit binary");
+    assert_eq!(output.status.code(), Some(3));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "args: 2\na\nb\n");
+
+    // sys.exit(message) prints the message to stderr and exits with status 1
+    let output = std::process::Command::new(&output_path)
+        .args(["a", "b", "c"])
+        .output()
+        .expect("Failed to run sys_exit binary");
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        "too many arguments\n"
+    );
+}
+
+#[test]
+fn test_pycc_synthesize_methods() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("default_methods");
+
+    cargo_bin_cmd!("pycc")
+        .args([
+            test_dir()
+                .join("basic/classes/default_methods.py")
+                .to_str()
+                .unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+            "--synthesize-methods",
+        ])
+        .assert()
+        .success();
+
+    // Methods a class defines itself are kept; Money has no __eq__ of its own,
+    // so it compares its field
+    let output = std::process::Command::new(&output_path)
+        .output()
+        .expect("Failed to run default_methods binary");
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "Point(x=1, y=2)\n\
+         Point3(x=1, y=2, z=3)\n\
+         Label(text='origin', at=Point(x=0, y=0))\n\
+         $5\n\
+         True False\n\
+         True\n"
+    );
+}
+
+#[test]
+fn test_pycc_auto_super_init() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = temp_dir.path().join("shapes.py");
+    std::fs::write(
+        &source,
+        "class Shape:\n    def __init__(self, name: str = \"shape\") -> None:\n        self.name = name\n        self.sides: list[int] = []\n\n\
+         class Square(Shape):\n    def __init__(self, side: int) -> None:\n        self.sides.append(side)\n\n\
+         s = Square(4)\nprint(s.name, s.sides)\n",
+    )
+    .unwrap();
+    let output_path = temp_dir.path().join("shapes");
+
+    // Square.__init__ leaves the fields Shape.__init__ assigns unset
+    cargo_bin_cmd!("pycc")
+        .args(["--check", source.to_str().unwrap()])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "error: 'Square.__init__' does not call super().__init__(), so inherited fields 'name', 'sides' are never set",
+        ))
+        .stderr(predicate::str::contains("shapes.py:7:"));
 
+    // With the option, it calls Shape.__init__() first
+    cargo_bin_cmd!("pycc")
+        .args([
+            source.to_str().unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+            "--auto-super-init",
+        ])
+        .assert()
+        .success();
+    let output = std::process::Command::new(&output_path)
+        .output()
+        .expect("Failed to run shapes binary");
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "shape [4]\n");
+}
+
+#[test]
+fn test_pycc_checked_int() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("checked_int");
+
+    cargo_bin_cmd!("pycc")
+        .args([
+            test_dir()
+                .join("basic/primitives/checked_int.py")
+                .to_str()
+                .unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+            "--checked-int",
+        ])
+        .assert()
+        .success();
+
+    // (-2) ** 63 and -1 << 63 are exactly INT64_MIN, so they fit
+    let output = std::process::Command::new(&output_path)
+        .output()
+        .expect("Failed to run checked_int binary");
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "9223372036854775807\n\
+         error: int result does not fit in 64 bits\n\
+         3\n\
+         False True False\n\
+         -4611686018427387904 1\n\
+         floor division overflowed\n\
+         4611686018427387904 -9223372036854775808 overflow\n\
+         overflow 0 overflow\n\
+         error: negative shift count\n\
+         2\n"
+    );
+}
+
+#[test]
+fn test_pycc_embed() {
+    let temp_dir = TempDir::new().unwrap();
+    let data_dir = temp_dir.path().join("data");
+    std::fs::create_dir(&data_dir).unwrap();
+    std::fs::write(data_dir.join("a.txt"), "embedded a").unwrap();
+    std::fs::write(data_dir.join("b.txt"), "embedded b").unwrap();
+    std::fs::write(
+        temp_dir.path().join("main.py"),
+        r#"
+def read(path: str) -> str:
+    f = open(path)
+    content: str = f.read()
+    f.close()
+    return content
+
+print(read("data/a.txt"))
+print(read("./data/b.txt"))
+print(read("data/c.md"))
+out = open("data/a.txt", "w")
+out.write("written")
+out.close()
+print(read("data/a.txt"))
+"#,
+    )
+    .unwrap();
+    std::fs::write(data_dir.join("c.md"), "on disk c").unwrap();
+
+    cargo_bin_cmd!("pycc")
+        .current_dir(temp_dir.path())
+        .args(["main.py", "-o", "app", "--embed", "data/*.txt"])
+        .assert()
+        .success();
+
+    // Embedded files no longer depend on the filesystem; others are read from it
+    std::fs::write(data_dir.join("a.txt"), "changed a").unwrap();
+    std::fs::remove_file(data_dir.join("b.txt")).unwrap();
+    let output = std::process::Command::new(temp_dir.path().join("app"))
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to run embed binary");
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "embedded a\nembedded b\non disk c\nembedded a\n"
+    );
+    // Writing went to the file on disk
+    assert_eq!(
+        std::fs::read_to_string(data_dir.join("a.txt")).unwrap(),
+        "written"
+    );
+
+    // Two patterns picking the same file
+    cargo_bin_cmd!("pycc")
+        .current_dir(temp_dir.path())
+        .args(["main.py", "-o", "app", "--embed", "data/*.txt"])
+        .args(["--embed", "./data/a.txt"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "'data/a.txt' is embedded more than once",
+        ));
+}
+
+#[test]
+fn test_pycc_no_runtime_checks() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("floor_division");
+
+    cargo_bin_cmd!("pycc")
+        .args([
+            test_dir()
+                .join("basic/primitives/floor_division_test.py")
+                .to_str()
+                .unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+            "--no-runtime-checks",
+        ])
+        .assert()
+        .success();
+
+    // Programs that never divide by zero or index out of range are unaffected
+    let output = std::process::Command::new(&output_path)
+        .output()
+        .expect("Failed to run floor_division binary");
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_pycc_no_runtime_checks_pop_empty() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = temp_dir.path().join("pop_empty.py");
+    std::fs::write(
+        &source,
+        r#"
+items: list[int] = []
+try:
+    items.pop()
+except IndexError as e:
+    print(e)
+items.append(1)
+print(items.pop(), len(items))
+"#,
+    )
+    .unwrap();
+    let output_path = temp_dir.path().join("pop_empty");
+
+    cargo_bin_cmd!("pycc")
+        .args([
+            source.to_str().unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+            "--no-runtime-checks",
+        ])
+        .assert()
+        .success();
+
+    // Popping from an empty list still raises: it is not a bounds check
+    let output = std::process::Command::new(&output_path)
+        .output()
+        .expect("Failed to run pop_empty binary");
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "pop from empty list\n1 0\n"
+    );
+}
+
+#[test]
+fn test_pycc_leak_check() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("refcount");
+
+    cargo_bin_cmd!("pycc")
+        .args([
+            test_dir()
+                .join("basic/classes/refcount.py")
+                .to_str()
+                .unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+            "--leak-check",
+        ])
+        .assert()
+        .success();
+
+    // Every instance, including the global's, is freed by the end of main
+    let output = std::process::Command::new(&output_path)
+        .output()
+        .expect("Failed to run refcount binary");
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "6 3\n122500\n0\n3 2 1\n9 0\n"
+    );
+    assert!(output.stderr.is_empty());
+}
+
+#[test]
+fn test_pycc_leak_check_builtins() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("refcount_builtins");
+
+    cargo_bin_cmd!("pycc")
+        .args([
+            test_dir()
+                .join("basic/classes/refcount_builtins.py")
+                .to_str()
+                .unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+            "--leak-check",
+        ])
+        .assert()
+        .success();
+
+    // The dropped lists free their items; only the string left in the dict
+    // is reported
+    let output = std::process::Command::new(&output_path)
+        .output()
+        .expect("Failed to run refcount_builtins binary");
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "6 5 abcabc 7 0 0\n"
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        "leak check: 1 object still alive\n"
+    );
+}
+
+#[test]
+fn test_pycc_leak_check_cycle() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("refcount_cycle");
+
+    cargo_bin_cmd!("pycc")
+        .args([
+            test_dir()
+                .join("basic/classes/refcount_cycle.py")
+                .to_str()
+                .unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+            "--leak-check",
+        ])
+        .assert()
+        .success();
+
+    // Reference counting cannot free a cycle
+    let output = std::process::Command::new(&output_path)
+        .output()
+        .expect("Failed to run refcount_cycle binary");
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "done\n");
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        "leak check: 2 objects still alive\n"
+    );
+}
+
+#[test]
+fn test_pycc_tracing_gc() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("gc_cycles");
+
+    cargo_bin_cmd!("pycc")
+        .args([
+            test_dir()
+                .join("basic/classes/gc_cycles.py")
+                .to_str()
+                .unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+            "--gc=tracing",
+        ])
+        .assert()
+        .success();
+
+    // The churn allocates enough to collect several times
+    let output = std::process::Command::new(&output_path)
+        .output()
+        .expect("Failed to run gc_cycles binary");
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "87000\n5\n45 190 10\n"
+    );
+}
+
+#[test]
+fn test_pycc_tracing_gc_frees_cycles() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("refcount_cycle");
+
+    cargo_bin_cmd!("pycc")
+        .args([
+            test_dir()
+                .join("basic/classes/refcount_cycle.py")
+                .to_str()
+                .unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+            "--leak-check",
+            "--gc=tracing",
+        ])
+        .assert()
+        .success();
+
+    // The cycle counting leaves behind is collected before the leak check
+    let output = std::process::Command::new(&output_path)
+        .output()
+        .expect("Failed to run refcount_cycle binary");
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "done\n");
+    assert!(output.stderr.is_empty());
+}
+
+#[test]
+fn test_pycc_unknown_gc() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("output");
+
+    cargo_bin_cmd!("pycc")
+        .args([
+            test_dir().join("exceptions/simple.py").to_str().unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+            "--gc=boehm",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown garbage collector"));
+}
+
+#[test]
+fn test_pycc_arena_alloc() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("arena_alloc");
+
+    cargo_bin_cmd!("pycc")
+        .args([
+            test_dir()
+                .join("basic/collections/arena_alloc.py")
+                .to_str()
+                .unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+            "--alloc=arena",
+        ])
+        .assert()
+        .success();
+
+    let output = std::process::Command::new(&output_path)
+        .output()
+        .expect("Failed to run arena_alloc binary");
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "49228890\n2499950000 50000 99999\nw0 w1 w2 w3 w4 \n"
+    );
+}
+
+#[test]
+fn test_pycc_arena_alloc_with_tracing_gc() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("gc_cycles");
+
+    cargo_bin_cmd!("pycc")
+        .args([
+            test_dir()
+                .join("basic/classes/gc_cycles.py")
+                .to_str()
+                .unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+            "--alloc=arena",
+            "--gc=tracing",
+        ])
+        .assert()
+        .success();
+
+    // Collections still run, they just hand nothing back to the arena
+    let output = std::process::Command::new(&output_path)
+        .output()
+        .expect("Failed to run gc_cycles binary");
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "87000\n5\n45 190 10\n"
+    );
+}
+
+#[test]
+fn test_pycc_unknown_alloc() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("output");
+
+    cargo_bin_cmd!("pycc")
+        .args([
+            test_dir().join("exceptions/simple.py").to_str().unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+            "--alloc=pool",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown allocator"));
+}
+
+#[test]
+fn test_pycc_opt_level_1() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("main");
+
+    cargo_bin_cmd!("pycc")
+        .args([
+            test_dir().join("main.py").to_str().unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+            "--opt-level=1",
+        ])
+        .assert()
+        .success();
+
+    // The optimized program prints exactly what the unoptimized one does
+    let output = std::process::Command::new(&output_path)
+        .output()
+        .expect("Failed to run main binary");
+    assert_matches_python(&output);
+}
+
+#[test]
+fn test_pycc_llvm_opt_levels() {
+    let temp_dir = TempDir::new().unwrap();
+
+    for level in ["3", "s"] {
+        let output_path = temp_dir.path().join(format!("main_o{level}"));
+        cargo_bin_cmd!("pycc")
+            .args([
+                test_dir().join("main.py").to_str().unwrap(),
+                "-o",
+                output_path.to_str().unwrap(),
+                &format!("--opt-level={level}"),
+            ])
+            .assert()
+            .success();
+
+        let output = std::process::Command::new(&output_path)
+            .output()
+            .expect("Failed to run main binary");
+        assert_matches_python(&output);
+    }
+}
+
+#[test]
+fn test_pycc_emit_obj() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("simple.o");
+
+    cargo_bin_cmd!("pycc")
+        .args([
+            test_dir().join("exceptions/simple.py").to_str().unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+            "--emit-obj",
+        ])
+        .assert()
+        .success();
+
+    // A native ELF object rather than LLVM bitcode
+    let object = std::fs::read(&output_path).unwrap();
+    assert!(object.starts_with(b"\x7fELF"));
+}
+
+#[test]
+fn test_pycc_emit_artifacts() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("simple");
+
+    cargo_bin_cmd!("pycc")
+        .args([
+            test_dir().join("exceptions/simple.py").to_str().unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+            "--emit=llvm-ir,llvm-bc,asm,obj",
+        ])
+        .assert()
+        .success();
+
+    // The executable is still linked, next to its artifacts
+    assert!(output_path.is_file());
+    let ir = std::fs::read_to_string(output_path.with_extension("ll")).unwrap();
+    assert!(ir.contains("define"));
+    let bitcode = std::fs::read(output_path.with_extension("bc")).unwrap();
+    assert!(bitcode.starts_with(b"BC"));
+    let asm = std::fs::read_to_string(output_path.with_extension("s")).unwrap();
+    assert!(asm.contains("main"));
+    let object = std::fs::read(output_path.with_extension("o")).unwrap();
+    assert!(object.starts_with(b"\x7fELF"));
+}
+
+#[test]
+fn test_pycc_unknown_emit_kind() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("simple");
+
+    cargo_bin_cmd!("pycc")
+        .args([
+            test_dir().join("exceptions/simple.py").to_str().unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+            "--emit=llvm-ir,mir",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown emit kind 'mir'"));
+}
+
+#[test]
+fn test_pycc_emit_tir() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = temp_dir.path().join("shapes.py");
+    std::fs::write(
+        &source,
+        r#"
+def add(a: int, b: int) -> int:
+    return a + b
+
+class Point:
+    def __init__(self, x: int) -> None:
+        self.x = x
+
+p = Point(add(1, 2))
+"#,
+    )
+    .unwrap();
+    let output_path = temp_dir.path().join("shapes");
+
+    let output = cargo_bin_cmd!("pycc")
+        .args([
+            source.to_str().unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+            "--emit-tir",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let tir = String::from_utf8_lossy(&output.stdout);
+    assert!(tir.contains("module shapes (entry)\n    global p: shapes.Point\n"));
+    assert!(tir.contains("def shapes.add(a: int, b: int) -> int:\n    return (a + b)\n"));
+    assert!(tir.contains(
+        "class shapes.Point:\n    field x: int\n    method __init__ = shapes.Point.__init__\n"
+    ));
+    assert!(tir.contains("self.x = x\n"));
+}
+
+#[test]
+fn test_pycc_debug_info() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = temp_dir.path().join("shapes.py");
+    std::fs::write(
+        &source,
+        r#"
+def add(a: int, b: int) -> int:
+    total = a + b
+    return total
+
+class Point:
+    def norm(self) -> int:
+        return 0
+
+print(add(1, 2), Point().norm())
+"#,
+    )
+    .unwrap();
+    let output_path = temp_dir.path().join("shapes");
+
+    cargo_bin_cmd!("pycc")
+        .args([
+            source.to_str().unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+            "-g",
+            "--emit=llvm-ir",
+        ])
+        .assert()
+        .success();
+
+    let ir = std::fs::read_to_string(temp_dir.path().join("shapes.ll")).unwrap();
+    assert!(ir.contains("DIFile(filename: \"shapes.py\""));
+    assert!(ir.contains("name: \"shapes.add\", linkageName: \"__pyc_shapes_add\""));
+    assert!(ir.contains("name: \"shapes.Point.norm\""));
+    assert!(ir.contains("name: \"<module>\""));
+    assert!(ir.contains("DILocalVariable(name: \"b\", arg: 2"));
+    assert!(ir.contains("DILocalVariable(name: \"total\""));
+
+    let output = std::process::Command::new(&output_path)
+        .output()
+        .expect("Failed to run compiled executable");
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "3 0\n");
+}
+
+#[test]
+fn test_pycc_error_location() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = temp_dir.path().join("bad.py");
+    std::fs::write(
+        &source,
+        "def main() -> None:\n    n: int = 1\n    if n > 0:\n        x: int = \"hello\"\n\nmain()\n",
+    )
+    .unwrap();
+    let output_path = temp_dir.path().join("bad");
+
+    cargo_bin_cmd!("pycc")
+        .args([
+            source.to_str().unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("bad.py:4:9\n"))
+        .stderr(predicate::str::contains(
+            "4 |         x: int = \"hello\"\n  |         ^^^^^^^^^^^^^^^^\n",
+        ));
+}
+
+#[test]
+fn test_pycc_error_suggests_similar_name() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = temp_dir.path().join("typo.py");
+    std::fs::write(
+        &source,
+        "def main() -> None:\n    total: int = 0\n    print(totl)\n\nmain()\n",
+    )
+    .unwrap();
+    let output_path = temp_dir.path().join("typo");
+
+    cargo_bin_cmd!("pycc")
+        .args([
+            source.to_str().unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "error: Undefined variable: 'totl'",
+        ))
+        .stderr(predicate::str::contains(
+            "= help: a name with a similar spelling exists: 'total'",
+        ));
+}
+
+#[test]
+fn test_pycc_error_import_name_not_found() {
+    let temp_dir = TempDir::new().unwrap();
+    std::fs::write(
+        temp_dir.path().join("utils.py"),
+        "def helper() -> int:\n    return 1\n",
+    )
+    .unwrap();
+    let source = temp_dir.path().join("main.py");
+    std::fs::write(&source, "from utils import helpr\n\nprint(helpr())\n").unwrap();
+
+    cargo_bin_cmd!("pycc")
+        .args(["--check", source.to_str().unwrap()])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "error: Cannot import 'helpr' from module 'utils'",
+        ))
+        .stderr(predicate::str::contains("main.py:1:1"))
+        .stderr(predicate::str::contains(
+            "= help: the module defines a name with a similar spelling: 'helper'",
+        ));
+}
+
+#[test]
+fn test_pycc_star_import_all() {
+    let temp_dir = TempDir::new().unwrap();
+    let utils = temp_dir.path().join("utils.py");
+    std::fs::write(
+        &utils,
+        "__all__ = [\"helper\"]\n\ndef helper() -> int:\n    return 1\n\n\
+         def hidden() -> int:\n    return 2\n",
+    )
+    .unwrap();
+    let source = temp_dir.path().join("main.py");
+    std::fs::write(
+        &source,
+        "from utils import *\n\nprint(helper())\nprint(hidden())\n",
+    )
+    .unwrap();
+
+    // Only the names __all__ lists are imported
+    cargo_bin_cmd!("pycc")
+        .args(["--check", source.to_str().unwrap()])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("'hidden'"))
+        .stderr(predicate::str::contains("'helper'").not());
+
+    std::fs::write(
+        &utils,
+        "__all__ = [\"helper\", \"missing\"]\n\ndef helper() -> int:\n    return 1\n",
+    )
+    .unwrap();
+    std::fs::write(&source, "from utils import *\n\nprint(helper())\n").unwrap();
+    cargo_bin_cmd!("pycc")
+        .args(["--check", source.to_str().unwrap()])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "error: Cannot import 'missing' from module 'utils'",
+        ));
+}
+
+#[test]
+fn test_pycc_circular_import_functions() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path();
+    std::fs::write(
+        dir.join("even.py"),
+        "import odd\n\ndef is_even(n: int) -> bool:\n    if n == 0:\n        return True\n    \
+         return odd.is_odd(n - 1)\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("odd.py"),
+        "import even\n\ndef is_odd(n: int) -> bool:\n    if n == 0:\n        return False\n    \
+         return even.is_even(n - 1)\n",
+    )
+    .unwrap();
+    std::fs::write(dir.join("zeta.py"), "LIMIT = 7\n").unwrap();
+    let source = dir.join("main.py");
+    std::fs::write(
+        &source,
+        "import even\nimport zeta\n\nprint(even.is_even(10))\nprint(zeta.LIMIT)\n",
+    )
+    .unwrap();
+    let output_path = dir.join("main");
+
+    // The modules' functions call each other, and 'zeta' is initialized
+    // before the module importing it
+    cargo_bin_cmd!("pycc")
+        .args([
+            source.to_str().unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+        ])
+        .assert()
+        .success();
+
+    let output = std::process::Command::new(&output_path)
+        .output()
+        .expect("Failed to run compiled binary");
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "True\n7\n");
+}
+
+#[test]
+fn test_pycc_error_circular_import() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path();
+    std::fs::write(
+        dir.join("a.py"),
+        "import b\n\nLIMIT = 3\n\ndef limit() -> int:\n    return LIMIT\n",
+    )
+    .unwrap();
+    std::fs::write(dir.join("b.py"), "import a\n\nprint(a.LIMIT)\n").unwrap();
+    let source = dir.join("main.py");
+    std::fs::write(&source, "import a\n\nprint(a.limit())\n").unwrap();
+
+    cargo_bin_cmd!("pycc")
+        .args(["--check", source.to_str().unwrap()])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "error: Circular import detected: a → b → a: module 'b' uses 'LIMIT' of module 'a' \
+             before 'a' is initialized",
+        ))
+        .stderr(predicate::str::contains("b.py:3:"));
+
+    // Through a function of the other module too
+    std::fs::write(dir.join("b.py"), "import a\n\nprint(a.limit())\n").unwrap();
+    cargo_bin_cmd!("pycc")
+        .args(["--check", source.to_str().unwrap()])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "module 'b' uses 'LIMIT' of module 'a'",
+        ));
+}
+
+#[test]
+fn test_pycc_error_global_type_unknown() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = temp_dir.path().join("main.py");
+    std::fs::write(&source, "names = []\n\nprint(len(names))\n").unwrap();
+
+    cargo_bin_cmd!("pycc")
+        .args(["--check", source.to_str().unwrap()])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "error: Cannot tell the type of global 'names'",
+        ))
+        .stderr(predicate::str::contains(
+            "annotate it where it is first assigned: names: <type> = ...",
+        ));
+
+    // Annotated, it is a list of the given type
+    std::fs::write(&source, "names: list[str] = []\n\nprint(len(names))\n").unwrap();
+    cargo_bin_cmd!("pycc")
+        .args(["--check", source.to_str().unwrap()])
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_pycc_error_field_type_unknown() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = temp_dir.path().join("main.py");
+    std::fs::write(
+        &source,
+        "class Bag:\n    def __init__(self) -> None:\n        self.items = []\n\nprint(len(Bag().items))\n",
+    )
+    .unwrap();
+
+    cargo_bin_cmd!("pycc")
+        .args(["--check", source.to_str().unwrap()])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "error: Cannot tell the type of field 'items'",
+        ))
+        .stderr(predicate::str::contains("main.py:3:"))
+        .stderr(predicate::str::contains(
+            "annotate the assignment (self.items: <type> = ...)",
+        ));
+
+    // Annotated where it is assigned, it is a list of the given type
+    std::fs::write(
+        &source,
+        "class Bag:\n    def __init__(self) -> None:\n        self.items: list[int] = []\n\nprint(len(Bag().items))\n",
+    )
+    .unwrap();
+    cargo_bin_cmd!("pycc")
+        .args(["--check", source.to_str().unwrap()])
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_pycc_error_labels_binding() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = temp_dir.path().join("rebind.py");
+    std::fs::write(
+        &source,
+        "def main() -> None:\n    count = 1\n    print(count)\n    count = \"one\"\n\nmain()\n",
+    )
+    .unwrap();
+    let output_path = temp_dir.path().join("rebind");
+
+    cargo_bin_cmd!("pycc")
+        .args([
+            source.to_str().unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "2 |     count = 1\n  |     --------- 'count' gets its type here\n...\n",
+        ))
+        .stderr(predicate::str::contains(
+            "4 |     count = \"one\"\n  |     ^^^^^^^^^^^^^\n",
+        ));
+}
+
+#[test]
+fn test_pycc_reports_all_errors() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = temp_dir.path().join("many.py");
+    std::fs::write(
+        &source,
+        "def main() -> None:\n    x: int = \"a\"\n    y = undefined_name\n    print(y + 1)\n    if x > 0:\n        z: str = 5\n\nmain()\n",
+    )
+    .unwrap();
+    let output_path = temp_dir.path().join("many");
+
+    // The use of y, which failed to be defined, is not reported again
+    cargo_bin_cmd!("pycc")
+        .args([
+            source.to_str().unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("many.py:2:5\n"))
+        .stderr(predicate::str::contains(
+            "error: Undefined variable: 'undefined_name'",
+        ))
+        .stderr(predicate::str::contains("many.py:6:9\n"))
+        .stderr(predicate::str::contains("error: aborting due to 3 errors"));
+}
+
+/// A program with one warning of each lint
+fn write_lint_source(dir: &std::path::Path) -> PathBuf {
+    let source = dir.join("lints.py");
+    std::fs::write(
+        &source,
+        "import math\n\ndef main() -> None:\n    unused = 1\n    sum = 2\n    print(sum)\n    return\n    print(3)\n\nmain()\n",
+    )
+    .unwrap();
+    source
+}
+
+#[test]
+fn test_pycc_warnings() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = write_lint_source(temp_dir.path());
+    let output_path = temp_dir.path().join("lints");
+
+    cargo_bin_cmd!("pycc")
+        .args([
+            source.to_str().unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+        ])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "warning[unused-import]: unused import 'math'\n --> ",
+        ))
+        .stderr(predicate::str::contains(
+            "warning[unused-variable]: unused variable 'unused'",
+        ))
+        .stderr(predicate::str::contains("4 |     unused = 1\n"))
+        .stderr(predicate::str::contains(
+            "warning[shadowed-builtin]: 'sum' shadows a builtin",
+        ))
+        .stderr(predicate::str::contains(
+            "warning[unreachable-code]: unreachable code",
+        ));
+
+    let output = std::process::Command::new(&output_path)
+        .output()
+        .expect("Failed to run lints binary");
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "2\n");
+}
+
+#[test]
+fn test_pycc_warnings_as_errors() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = write_lint_source(temp_dir.path());
+    let output_path = temp_dir.path().join("lints");
+
+    cargo_bin_cmd!("pycc")
+        .args([
+            source.to_str().unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+            "-W",
+            "error",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "error[unused-variable]: unused variable 'unused'",
+        ))
+        .stderr(predicate::str::contains("error: aborting due to 4 errors"));
+    assert!(!output_path.exists());
+}
+
+#[test]
+fn test_pycc_warnings_allowed() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = write_lint_source(temp_dir.path());
+    let output_path = temp_dir.path().join("lints");
+
+    cargo_bin_cmd!("pycc")
+        .args([
+            source.to_str().unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+            "-W",
+            "allow",
+        ])
+        .assert()
+        .success()
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn test_pycc_short_error_format() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = write_lint_source(temp_dir.path());
+    let output_path = temp_dir.path().join("lints");
+
+    cargo_bin_cmd!("pycc")
+        .args([
+            source.to_str().unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+            "--error-format=short",
+        ])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "lints.py:1:1: warning[unused-import]: unused import 'math'\n",
+        ))
+        .stderr(predicate::str::contains(
+            "lints.py:8:5: warning[unreachable-code]: unreachable code\n",
+        ))
+        .stderr(predicate::str::contains(" |").not());
+}
+
+#[test]
+fn test_pycc_json_error_format() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = temp_dir.path().join("bad.py");
+    std::fs::write(
+        &source,
+        "def main() -> None:\n    count = 1\n    print(count)\n    count = \"one\"\n\nmain()\n",
+    )
+    .unwrap();
+    let output_path = temp_dir.path().join("bad");
+
+    let output = cargo_bin_cmd!("pycc")
+        .args([
+            source.to_str().unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+            "--error-format=json",
+        ])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    // One object per line, with the label as a child
+    assert_eq!(stderr.lines().count(), 1);
+    assert!(stderr.starts_with("{\"severity\":\"error\",\"code\":null,\"message\":\"Cannot assign"));
+    assert!(stderr.contains("bad.py\",\"span\":{\"line\":4,\"column\":5},\"children\":[{\"severity\":\"note\",\"message\":\"'count' gets its type here\",\"span\":{\"line\":2,\"column\":5}}]"));
+    assert!(stderr.contains("\"rendered\":\"error: Cannot assign"));
+}
+
+#[test]
+fn test_pycc_json_warnings() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = write_lint_source(temp_dir.path());
+    let output_path = temp_dir.path().join("lints");
+
+    cargo_bin_cmd!("pycc")
+        .args([
+            source.to_str().unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+            "--error-format=json",
+        ])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "{\"severity\":\"warning\",\"code\":\"unused-import\",\"message\":\"unused import 'math'\",",
+        ))
+        .stderr(predicate::str::contains(
+            "\"span\":{\"line\":8,\"column\":5},\"children\":[],",
+        ));
+}
+
+#[test]
+fn test_pycc_unknown_warning_level() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = write_lint_source(temp_dir.path());
+
+    cargo_bin_cmd!("pycc")
+        .args([
+            source.to_str().unwrap(),
+            "-o",
+            temp_dir.path().join("lints").to_str().unwrap(),
+            "-W",
+            "deny",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown warning level 'deny'"));
+}
+
+/// Frame language server messages with their Content-Length headers
+fn lsp_messages(messages: &[&str]) -> String {
+    messages
+        .iter()
+        .map(|message| format!("Content-Length: {}\r\n\r\n{}", message.len(), message))
+        .collect()
+}
+
+#[test]
+fn test_pyls_initialize_and_shutdown() {
+    let input = lsp_messages(&[
+        r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#,
+        r#"{"jsonrpc":"2.0","method":"initialized","params":{}}"#,
+        r#"{"jsonrpc":"2.0","id":2,"method":"shutdown"}"#,
+        r#"{"jsonrpc":"2.0","method":"exit"}"#,
+    ]);
+
+    cargo_bin_cmd!("pyls")
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"hoverProvider\":true"))
+        .stdout(predicate::str::contains("\"definitionProvider\":true"))
+        .stdout(predicate::str::contains(
+            "{\"jsonrpc\":\"2.0\",\"id\":2,\"result\":null}",
+        ));
+}
+
+#[test]
+fn test_pycc_lld_linker() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("simple");
+
+    cargo_bin_cmd!("pycc")
+        .args([
+            test_dir().join("exceptions/simple.py").to_str().unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+            "--linker=lld",
+        ])
+        .assert()
+        .success();
+
+    let output = std::process::Command::new(&output_path)
+        .output()
+        .expect("Failed to run simple binary");
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_pycc_shared_library() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = temp_dir.path().join("mathlib.py");
+    std::fs::write(
+        &source,
+        r#"
+scale: int = 10
+
+@export
+def add(a: int, b: int) -> int:
+    return (a + b) * scale
+
+@export
+def half(x: float) -> float:
+    return x / 2.0
+
+@export
+def is_even(n: int) -> bool:
+    return n % 2 == 0
+"#,
+    )
+    .unwrap();
+    let library = temp_dir.path().join("libmathlib.so");
+
+    cargo_bin_cmd!("pycc")
+        .args([
+            source.to_str().unwrap(),
+            "-o",
+            library.to_str().unwrap(),
+            "--shared",
+        ])
+        .assert()
+        .success();
+
+    // A C program calling the exported functions; `scale` is set by the
+    // library's constructor before main runs
+    let host = temp_dir.path().join("host.c");
+    std::fs::write(
+        &host,
+        r#"
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdio.h>
+int64_t add(int64_t a, int64_t b);
+double half(double x);
+bool is_even(int64_t n);
+int main(void) {
+    printf("%lld %.2f %d %d
+", (long long)add(1, 2), half(5.0), is_even(4), is_even(7));
+    return 0;
+}
+"#,
+    )
+    .unwrap();
+    let host_exe = temp_dir.path().join("host");
+    let status = std::process::Command::new("clang")
+        .arg(&host)
+        .arg(&library)
+        .arg(format!("-Wl,-rpath,{}", temp_dir.path().display()))
+        .arg("-o")
+        .arg(&host_exe)
+        .status()
+        .expect("Failed to run clang");
+    assert!(status.success());
+
+    let output = std::process::Command::new(&host_exe)
+        .output()
+        .expect("Failed to run host binary");
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "30 2.50 1 0\n");
+}
+
+#[test]
+fn test_pycc_static_library() {
+    let temp_dir = TempDir::new().unwrap();
+    let lib_dir = temp_dir.path().join("lib");
+    let app_dir = temp_dir.path().join("app");
+    std::fs::create_dir_all(&lib_dir).unwrap();
+    std::fs::create_dir_all(&app_dir).unwrap();
+    let library_source = lib_dir.join("mathlib.py");
+    std::fs::write(
+        &library_source,
+        r#"
+counter: int = 10
+
+def bump() -> int:
+    global counter
+    counter += 1
+    return counter
+
+@export
+def add(a: int, b: int) -> int:
+    return a + b
+
+@export
+def next_id() -> int:
+    return bump()
+"#,
+    )
+    .unwrap();
+    let archive = app_dir.join("libmathlib.a");
+
+    cargo_bin_cmd!("pycc")
+        .args([
+            library_source.to_str().unwrap(),
+            "-o",
+            archive.to_str().unwrap(),
+            "--emit=staticlib",
+        ])
+        .assert()
+        .success();
+    assert!(archive.exists());
+    let interface = std::fs::read_to_string(app_dir.join("mathlib.pyi")).unwrap();
+    assert!(interface.contains("# pycc-staticlib: libmathlib.a"));
+    assert!(interface.contains("def add(a: int, b: int) -> int: ..."));
+
+    // The program imports the interface next to it, not the library's
+    // source, and has a `bump` of its own
+    let source = app_dir.join("main.py");
+    std::fs::write(
+        &source,
+        r#"
+import mathlib
+
+def bump() -> int:
+    return 0
+
+print(mathlib.add(40, 2))
+print(mathlib.next_id(), bump())
+"#,
+    )
+    .unwrap();
+    let output_path = app_dir.join("main");
+
+    cargo_bin_cmd!("pycc")
+        .args([
+            source.to_str().unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+        ])
+        .assert()
+        .success();
+
+    let output = std::process::Command::new(&output_path)
+        .output()
+        .expect("Failed to run main binary");
+    assert!(output.status.success());
+    // The library's module-level code set `counter` before main's ran
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "42\n11 0\n");
+}
+
+#[test]
+fn test_pycc_static_library_rejects_shared() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = temp_dir.path().join("mathlib.py");
+    std::fs::write(&source, "@export\ndef one() -> int:\n    return 1\n").unwrap();
+
+    cargo_bin_cmd!("pycc")
+        .args([
+            source.to_str().unwrap(),
+            "-o",
+            temp_dir.path().join("libmathlib.a").to_str().unwrap(),
+            "--emit=staticlib",
+            "--shared",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "--emit=staticlib cannot be combined with --shared",
+        ));
+}
+
+#[test]
+fn test_pycc_extern_functions() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = temp_dir.path().join("ffi.py");
+    std::fs::write(
+        &source,
+        r#"
+@extern("puts")
+def puts(s: str) -> int: ...
+
+@extern("strlen")
+def c_strlen(s: str) -> int: ...
+
+@extern("memcmp")
+def memcmp(a: bytes, b: bytes, n: int) -> int: ...
+
+puts("hello from C")
+print(c_strlen("héllo"))
+print(memcmp(b"abc", b"abd", 2) == 0, memcmp(b"abc", b"abd", 3) == 0)
+"#,
+    )
+    .unwrap();
+    let output_path = temp_dir.path().join("ffi");
+
+    cargo_bin_cmd!("pycc")
+        .args([
+            source.to_str().unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+        ])
+        .assert()
+        .success();
+
+    let output = std::process::Command::new(&output_path)
+        .output()
+        .expect("Failed to run ffi binary");
+    assert!(output.status.success());
+    // strlen counts the UTF-8 bytes of the str
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "hello from C\n6\nTrue False\n"
+    );
+}
+
+#[test]
+fn test_pycc_llvm_intrinsics() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = temp_dir.path().join("intrinsics.py");
+    std::fs::write(
+        &source,
+        r#"
+def popcount(x: int) -> int:
+    return __intrinsic__("llvm.ctpop.i64", x)
+
+print(popcount(255), popcount(-1))
+print(__intrinsic__("llvm.ctlz.i64", 1, False))
+print(__intrinsic__("llvm.fma.f64", 2.0, 3.0, 0.5))
+"#,
+    )
+    .unwrap();
+    let output_path = temp_dir.path().join("intrinsics");
+
+    cargo_bin_cmd!("pycc")
+        .args([
+            source.to_str().unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+        ])
+        .assert()
+        .success();
+
+    let output = std::process::Command::new(&output_path)
+        .output()
+        .expect("Failed to run intrinsics binary");
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "8 64\n63\n6.5\n");
+}
+
+#[test]
+fn test_pycc_unknown_opt_level() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("output");
+
+    cargo_bin_cmd!("pycc")
+        .args([
+            test_dir().join("exceptions/simple.py").to_str().unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+            "--opt-level=4",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown optimization level"));
+}
+
+#[test]
+fn test_pycc_setjmp_exceptions() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("main");
+
+    cargo_bin_cmd!("pycc")
+        .args([
+            test_dir().join("main.py").to_str().unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+            "--exceptions=setjmp",
+        ])
+        .assert()
+        .success();
+
+    let output = std::process::Command::new(&output_path)
+        .output()
+        .expect("Failed to run main binary");
+    assert_matches_python(&output);
+}
+
+#[test]
+fn test_pycc_setjmp_exceptions_unwind() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("setjmp_unwind");
+
+    // The collector's shadow stack is unwound along with the functions
+    for gc in ["none", "tracing"] {
+        cargo_bin_cmd!("pycc")
+            .args([
+                test_dir()
+                    .join("exceptions/setjmp_unwind.py")
+                    .to_str()
+                    .unwrap(),
+                "-o",
+                output_path.to_str().unwrap(),
+                "--exceptions=setjmp",
+                &format!("--gc={gc}"),
+            ])
+            .assert()
+            .success();
+
+        let output = std::process::Command::new(&output_path)
+            .output()
+            .expect("Failed to run setjmp_unwind binary");
+        assert!(output.status.success());
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            "changed 11 from middle 11\n\
+             1 3\n\
+             after early return\n\
+             handled finally outer second\n\
+             body outer from else\n\
+             10300000000\n"
+        );
+    }
+}
+
+#[test]
+fn test_pycc_setjmp_exceptions_riscv64() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("output");
+
+    cargo_bin_cmd!("pycc")
+        .args([
+            test_dir().join("exceptions/simple.py").to_str().unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+            "--target",
+            "riscv64",
+            "--exceptions=setjmp",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "--exceptions=setjmp is not supported on riscv64",
+        ));
+}
+
+#[test]
+fn test_pycc_unknown_exception_model() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("output");
+
+    cargo_bin_cmd!("pycc")
+        .args([
+            test_dir().join("exceptions/simple.py").to_str().unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+            "--exceptions=unwind",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown exception model"));
+}
+
+#[test]
+fn test_pycc_traceback() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = temp_dir.path().join("totals.py");
+    std::fs::write(
+        &source,
+        "def check(n: int) -> int:\n    if n < 0:\n        raise ValueError(\"negative\")\n    return n\n\n\
+         def total(n: int) -> int:\n    if n == 0:\n        return check(-1)\n    return total(n - 1)\n\n\
+         try:\n    check(-2)\nexcept ValueError:\n    print(\"caught\")\nprint(total(5))\n",
+    )
+    .unwrap();
+    let output_path = temp_dir.path().join("totals");
+    let path = source.canonicalize().unwrap().display().to_string();
+    let frame = |line: u32, function: &str, code: &str| {
+        format!("  File \"{path}\", line {line}, in {function}\n    {code}\n")
+    };
+    let recursion = frame(9, "total", "return total(n - 1)");
+    let expected = format!(
+        "Traceback (most recent call last):\n{}{}{}{}  [Previous line repeated 2 more times]\n{}{}ValueError: negative\n",
+        frame(15, "<module>", "print(total(5))"),
+        recursion,
+        recursion,
+        recursion,
+        frame(8, "total", "return check(-1)"),
+        frame(3, "check", "raise ValueError(\"negative\")"),
+    );
+
+    // The caught exception leaves the records as they were, whether the
+    // functions return to the try block or it jumps back
+    for exceptions in ["polling", "setjmp"] {
+        cargo_bin_cmd!("pycc")
+            .args([
+                source.to_str().unwrap(),
+                "-o",
+                output_path.to_str().unwrap(),
+                "--traceback",
+                &format!("--exceptions={exceptions}"),
+            ])
+            .assert()
+            .success();
+        let output = std::process::Command::new(&output_path)
+            .output()
+            .expect("Failed to run totals binary");
+        assert_eq!(output.status.code(), Some(1));
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "caught\n");
+        assert_eq!(String::from_utf8_lossy(&output.stderr), expected);
+    }
+}
+
+#[test]
+fn test_pycc_recursion_limit() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("recursion_limit");
+
+    // The depth caught calls left behind is dropped, whether they return
+    // to the try block or it jumps back
+    for exceptions in ["polling", "setjmp"] {
+        cargo_bin_cmd!("pycc")
+            .args([
+                test_dir()
+                    .join("exceptions/recursion_limit.py")
+                    .to_str()
+                    .unwrap(),
+                "-o",
+                output_path.to_str().unwrap(),
+                "--recursion-limit=100",
+                &format!("--exceptions={exceptions}"),
+            ])
+            .assert()
+            .success();
+
+        let output = std::process::Command::new(&output_path)
+            .output()
+            .expect("Failed to run recursion_limit binary");
+        assert_eq!(output.status.code(), Some(1));
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            "RecursionError: maximum recursion depth exceeded\n\
+             caught as RuntimeError\n\
+             mutual finally\n\
+             90\n"
+        );
+        assert!(String::from_utf8_lossy(&output.stderr)
+            .contains("RecursionError: maximum recursion depth exceeded"));
+    }
+}
+
+/// Benchmark gate for the exception models: try blocks whose statements
+/// never raise must not run slower with setjmp than with polling. Run with
+/// `cargo test --release -- --ignored test_setjmp_exceptions_benchmark`.
+#[test]
+#[ignore]
+fn test_setjmp_exceptions_benchmark() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = temp_dir.path().join("bench.py");
+    std::fs::write(
+        &source,
+        r#"
+def step(x: int) -> int:
+    return (x * 31 + 7) % 1000003
+
+def main() -> int:
+    total: int = 0
+    i: int = 0
+    while i < 20000000:
+        try:
+            total = step(total)
+            total = step(total + i)
+            total = step(total)
+            total = step(total - i)
+        except ValueError:
+            total = 0
+        i += 1
+    print(total)
+    return 0
+
+main()
+"#,
+    )
+    .unwrap();
+
+    let mut timings = Vec::new();
+    for model in ["polling", "setjmp"] {
+        let output_path = temp_dir.path().join(model);
+        cargo_bin_cmd!("pycc")
+            .args([
+                source.to_str().unwrap(),
+                "-o",
+                output_path.to_str().unwrap(),
+                &format!("--exceptions={model}"),
+            ])
+            .assert()
+            .success();
+
+        // Best of three runs
+        let mut best = std::time::Duration::MAX;
+        let mut stdout = Vec::new();
+        for _ in 0..3 {
+            let start = std::time::Instant::now();
+            let output = std::process::Command::new(&output_path)
+                .output()
+                .expect("Failed to run benchmark binary");
+            best = best.min(start.elapsed());
+            assert!(output.status.success());
+            stdout = output.stdout;
+        }
+        eprintln!("--exceptions={model}: {best:?}");
+        timings.push((best, stdout));
+    }
+
+    let (polling, setjmp) = (&timings[0], &timings[1]);
+    assert_eq!(polling.1, setjmp.1);
+    assert!(
+        setjmp.0 <= polling.0.mul_f64(1.05),
+        "setjmp ({:?}) is slower than polling ({:?})",
+        setjmp.0,
+        polling.0
+    );
+}
+
+#[test]
+fn test_pycc_project_missing_config() {
+    let temp_dir = TempDir::new().unwrap();
+
+    cargo_bin_cmd!("pycc")
+        .args(["--project", temp_dir.path().to_str().unwrap()])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("pyproject.toml"));
+}
+
+// ============================================================================
+// Static linking tests
+// ============================================================================
+
+#[test]
+fn test_pycc_static_binary() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("main_static");
+
+    // Compile main.py to a binary
+    cargo_bin_cmd!("pycc")
+        .args([
+            test_dir().join("main.py").to_str().unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+            "--target",
+            "x86_64",
+        ])
+        .assert()
+        .success();
+
+    assert!(output_path.exists(), "Compiled binary should exist");
+
+    // Check with 'file' command that it's statically linked
+    let file_output = std::process::Command::new("file")
+        .arg(&output_path)
+        .output()
+        .expect("Failed to run file command");
+    let file_str = String::from_utf8_lossy(&file_output.stdout);
+    assert!(
+        file_str.contains("statically linked"),
+        "Binary should be statically linked. file output: {}",
+        file_str
+    );
+
+    // Check with 'ldd' command that there are no dynamic dependencies
+    let ldd_output = std::process::Command::new("ldd")
+        .arg(&output_path)
+        .output()
+        .expect("Failed to run ldd command");
+    let ldd_str = String::from_utf8_lossy(&ldd_output.stdout);
+    let ldd_stderr = String::from_utf8_lossy(&ldd_output.stderr);
+
+    // ldd returns non-zero for static binaries and outputs "not a dynamic executable"
+    assert!(
+        ldd_str.contains("not a dynamic executable")
+            || ldd_stderr.contains("not a dynamic executable"),
+        "Binary should have no dynamic dependencies. ldd output: {} stderr: {}",
+        ldd_str,
+        ldd_stderr
+    );
+
+    // Also verify the binary runs correctly
+    let run_output = std::process::Command::new(&output_path)
+        .output()
+        .expect("Failed to run compiled binary");
+    assert!(
+        run_output.status.success(),
+        "Static binary should execute successfully"
+    );
+}
+
+// ============================================================================
+// Invalid file tests
+// ============================================================================
+
+#[test]
+fn test_invalid_files() {
+    let invalid_dir = test_dir().join("invalid");
+
+    if !invalid_dir.exists() {
+        panic!("invalid directory not found at {}", invalid_dir.display());
+    }
+
+    let entries = std::fs::read_dir(&invalid_dir).expect("Failed to read invalid directory");
+
+    let mut test_count = 0;
+    let mut failed_to_error = Vec::new();
+
+    for entry in entries {
+        let entry = entry.expect("Failed to read directory entry");
+        let path = entry.path();
+
+        // A directory with a pyproject.toml is an invalid project
+        let is_project = path.join("pyproject.toml").is_file();
+        if is_project || path.extension().and_then(|s| s.to_str()) == Some("py") {
+            test_count += 1;
+            let file_name = path.file_name().unwrap().to_str().unwrap();
+
+            let temp_dir = TempDir::new().unwrap();
+            let output_path = temp_dir.path().join("output");
+
+            let mut args = vec![path.to_str().unwrap(), "-o", output_path.to_str().unwrap()];
+            if is_project {
+                args.insert(0, "--project");
+            }
             let result = cargo_bin_cmd!("pycc")
-                .args([path.to_str().unwrap(), "-o", output_path.to_str().unwrap()])
+                .args(args)
                 .output()
                 .expect("Failed to run pycc");
 